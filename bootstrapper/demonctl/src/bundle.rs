@@ -1,4 +1,5 @@
-use anyhow::{anyhow, Context, Result};
+use crate::seed::SeedProfile;
+use anyhow::{anyhow, bail, Context, Result};
 use jsonschema::{Draft, Validator};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -16,6 +17,9 @@ pub struct Bundle {
     pub operate_ui: OperateUi,
     #[serde(default)]
     pub seed: Seed,
+    /// Declared variables, referenced as `${vars.NAME}` in bundle fields
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, Variable>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -39,14 +43,28 @@ pub struct OperateUi {
     pub base_url: Option<String>,
     #[serde(rename = "approverAllowlist")]
     pub approver_allowlist: Option<Vec<String>>,
+    /// No longer used: the Operate UI dropped `ADMIN_TOKEN`, and verify sends a
+    /// bearer JWT instead. Still accepted so older bundles keep loading.
     #[serde(rename = "adminToken")]
     pub admin_token: Option<String>,
+    /// Operate UI version this environment expects; checked by `--diff`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct Seed {
     pub enabled: Option<bool>,
     pub runs: Option<Vec<RunSpec>>,
+    /// Profiles seeded when none are requested on the command line
+    #[serde(
+        rename = "activeProfiles",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub active_profiles: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, SeedProfile>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -67,6 +85,156 @@ pub struct GateSpec {
     pub ttl_seconds: Option<u64>,
 }
 
+/// A bundle variable. The default keeps the bundle loadable (and signable)
+/// without any overrides.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Variable {
+    #[serde(rename = "type")]
+    pub kind: VariableType,
+    pub default: JsonValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    String,
+    Integer,
+    Boolean,
+    /// Absolute URL with a host, e.g. `nats://nats.prod:4222`
+    Url,
+}
+
+impl VariableType {
+    /// Check `value` (a `--set` string or a values-file scalar) and return
+    /// the text substituted into the bundle
+    fn render(self, value: &JsonValue) -> Result<String> {
+        match (self, value) {
+            (Self::String, JsonValue::String(s)) => Ok(s.clone()),
+            (Self::Integer, JsonValue::Number(n)) if n.is_i64() || n.is_u64() => Ok(n.to_string()),
+            (Self::Integer, JsonValue::String(s)) => s
+                .trim()
+                .parse::<i64>()
+                .map(|n| n.to_string())
+                .map_err(|_| anyhow!("expected an integer, got '{}'", s)),
+            (Self::Boolean, JsonValue::Bool(b)) => Ok(b.to_string()),
+            (Self::Boolean, JsonValue::String(s)) if s == "true" || s == "false" => Ok(s.clone()),
+            (Self::Url, JsonValue::String(s)) => match reqwest::Url::parse(s) {
+                Ok(url) if url.has_host() => Ok(s.clone()),
+                _ => bail!("expected an absolute URL with a host, got '{}'", s),
+            },
+            (kind, other) => bail!("expected {}, got {}", kind.describe(), other),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::String => "a string",
+            Self::Integer => "an integer",
+            Self::Boolean => "true or false",
+            Self::Url => "a URL string",
+        }
+    }
+}
+
+/// Overrides for bundle variables from `--values` files and `--set`
+/// assignments; later sources win
+#[derive(Debug, Clone, Default)]
+pub struct BundleValues {
+    values: BTreeMap<String, JsonValue>,
+}
+
+impl BundleValues {
+    /// Apply `files` in order, then `sets` (`key=value`)
+    pub fn from_sources(files: &[std::path::PathBuf], sets: &[String]) -> Result<Self> {
+        let mut values = Self::default();
+        for file in files {
+            values.merge_file(file)?;
+        }
+        for assignment in sets {
+            values.set(assignment)?;
+        }
+        Ok(values)
+    }
+
+    /// Merge a flat YAML mapping of variable names to values
+    pub fn merge_file(&mut self, path: &Path) -> Result<()> {
+        let raw =
+            fs::read_to_string(path).with_context(|| format!("read values: {}", path.display()))?;
+        let doc: BTreeMap<String, serde_yaml::Value> = serde_yaml::from_str(&raw)
+            .with_context(|| format!("parse values {}: expected a mapping", path.display()))?;
+        for (key, value) in doc {
+            let value = serde_json::to_value(value)
+                .with_context(|| format!("values {}: {}", path.display(), key))?;
+            self.values.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// Apply a `key=value` assignment; the value is checked against the
+    /// variable's declared type when the bundle is loaded
+    pub fn set(&mut self, assignment: &str) -> Result<()> {
+        let (key, value) = assignment
+            .split_once('=')
+            .filter(|(key, _)| !key.trim().is_empty())
+            .ok_or_else(|| anyhow!("invalid --set '{}': expected key=value", assignment))?;
+        self.values
+            .insert(key.trim().to_string(), JsonValue::String(value.to_string()));
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Resolve every declared variable to the text substituted for
+/// `${vars.NAME}`, reporting all type errors and unknown overrides at once
+pub fn resolve_variables(
+    declared: &BTreeMap<String, Variable>,
+    values: &BundleValues,
+) -> Result<BTreeMap<String, String>> {
+    let mut problems = Vec::new();
+    for key in values.values.keys() {
+        if !declared.contains_key(key) {
+            let known: Vec<&str> = declared.keys().map(String::as_str).collect();
+            problems.push(format!(
+                "'{}' is not a declared variable (declared: {})",
+                key,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            ));
+        }
+    }
+
+    let mut resolved = BTreeMap::new();
+    for (name, variable) in declared {
+        let (value, source) = match values.values.get(name) {
+            Some(value) => (value, "value"),
+            None => (&variable.default, "default"),
+        };
+        match variable.kind.render(value) {
+            Ok(text) => {
+                resolved.insert(name.clone(), text);
+            }
+            Err(err) => problems.push(format!("variable '{}' {}: {}", name, source, err)),
+        }
+    }
+
+    if !problems.is_empty() {
+        let mut msg = String::from("bundle variable errors:\n");
+        for problem in problems {
+            msg.push_str(&format!("- {}\n", problem));
+        }
+        return Err(anyhow!(msg));
+    }
+    Ok(resolved)
+}
+
 fn default_stream_name() -> String {
     "RITUAL_EVENTS".into()
 }
@@ -78,9 +246,15 @@ fn default_dup_window() -> u64 {
 }
 
 pub fn load_bundle(path: &Path) -> Result<Bundle> {
+    load_bundle_with_values(path, &BundleValues::default())
+}
+
+/// Load a bundle, substituting `${vars.NAME}` from `values` over the
+/// declared defaults
+pub fn load_bundle_with_values(path: &Path, values: &BundleValues) -> Result<Bundle> {
     let raw =
         fs::read_to_string(path).with_context(|| format!("read bundle: {}", path.display()))?;
-    let interpolated = interpolate_env(&raw);
+    let interpolated = render_variables(&interpolate_env(&raw), values)?;
     let bundle: Bundle = serde_yaml::from_str(&interpolated).context("parse bundle YAML")?;
     validate_against_schema(&interpolated)?;
     Ok(bundle)
@@ -107,8 +281,42 @@ fn validate_against_schema(yaml_text: &str) -> Result<()> {
     Ok(())
 }
 
+/// Substitute `${vars.NAME}` after env interpolation, so variable defaults
+/// may themselves use `${VAR}`
+fn render_variables(text: &str, values: &BundleValues) -> Result<String> {
+    let doc: serde_yaml::Value = serde_yaml::from_str(text).context("parse bundle YAML")?;
+    let declared: BTreeMap<String, Variable> = match doc.get("variables") {
+        Some(section) => {
+            serde_yaml::from_value(section.clone()).context("parse bundle variables")?
+        }
+        None => BTreeMap::new(),
+    };
+    let resolved = resolve_variables(&declared, values)?;
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(VARS_PREFIX) {
+        let name_start = start + VARS_PREFIX.len();
+        let end = rest[name_start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("unterminated ${{vars.}} reference"))?;
+        let name = &rest[name_start..name_start + end];
+        let value = resolved
+            .get(name)
+            .ok_or_else(|| anyhow!("${{vars.{}}} refers to an undeclared variable", name))?;
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &rest[name_start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+const VARS_PREFIX: &str = "${vars.";
+
 fn interpolate_env(s: &str) -> String {
-    // Supports ${VAR} and ${VAR:-default}
+    // Supports ${VAR} and ${VAR:-default}; ${vars.NAME} is left for
+    // render_variables
     let mut out = String::with_capacity(s.len());
     let bytes = s.as_bytes();
     let mut i = 0;
@@ -116,6 +324,11 @@ fn interpolate_env(s: &str) -> String {
         if bytes[i] == b'$' && i + 1 < bytes.len() && bytes[i + 1] == b'{' {
             if let Some(end) = s[i + 2..].find('}') {
                 let token = &s[i + 2..i + 2 + end];
+                if token.starts_with("vars.") {
+                    out.push_str(&s[i..i + 2 + end + 1]);
+                    i += 2 + end + 1;
+                    continue;
+                }
                 let parts: Vec<&str> = token.splitn(2, ":-").collect();
                 let key = parts[0];
                 let default = if parts.len() == 2 {
@@ -182,4 +395,9 @@ mod tests {
         let s = "x=${FOO},y=${MISSING:-def}";
         assert_eq!(interpolate_env(s), "x=bar,y=def");
     }
+
+    #[test]
+    fn interpolate_leaves_variable_references() {
+        assert_eq!(interpolate_env("u=${vars.natsUrl}"), "u=${vars.natsUrl}");
+    }
 }
@@ -0,0 +1,288 @@
+//! Drift between a bundle and what is already provisioned
+//!
+//! `bootstrap --diff` compares the ritual stream's configuration, the seeded
+//! fixtures and the Operate UI against the bundle without changing anything.
+//! `--apply` then fixes only the differences bootstrap owns (stream config and
+//! fixtures); Operate UI drift is reported but needs a redeploy.
+
+use crate::{bundle, plan_seed, preview_min_subjects, publish_idem, seed_preview_min};
+use crate::{BootstrapConfig, PlannedProfile};
+use anyhow::{Context, Result};
+use async_nats::jetstream;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    Stream,
+    Fixture,
+    OperateUi,
+}
+
+/// One difference between the bundle and the provisioned state
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DriftItem {
+    pub kind: DriftKind,
+    /// Stream name, fixture subject or Operate UI URL
+    pub resource: String,
+    pub field: String,
+    pub expected: Value,
+    pub actual: Value,
+    /// Whether `--apply` can reconcile it
+    pub fixable: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DriftReport {
+    pub items: Vec<DriftItem>,
+}
+
+impl DriftReport {
+    pub fn in_sync(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn has(&self, kind: DriftKind) -> bool {
+        self.items.iter().any(|item| item.kind == kind)
+    }
+
+    fn missing_fixture(&self, subject: &str) -> bool {
+        self.items
+            .iter()
+            .any(|item| item.kind == DriftKind::Fixture && item.resource == subject)
+    }
+}
+
+/// Compare the stream `cfg` expects with the one JetStream reports (`None`
+/// when the stream does not exist). Subjects are compared as a set.
+pub fn diff_stream(
+    cfg: &BootstrapConfig,
+    actual: Option<&jetstream::stream::Config>,
+) -> Vec<DriftItem> {
+    let item = |field: &str, expected: Value, actual: Value| DriftItem {
+        kind: DriftKind::Stream,
+        resource: cfg.stream_name.clone(),
+        field: field.to_string(),
+        expected,
+        actual,
+        fixable: true,
+    };
+    let Some(actual) = actual else {
+        return vec![item("exists", json!(true), json!(false))];
+    };
+
+    let mut items = Vec::new();
+    let expected_subjects: BTreeSet<&String> = cfg.subjects.iter().collect();
+    let actual_subjects: BTreeSet<&String> = actual.subjects.iter().collect();
+    if expected_subjects != actual_subjects {
+        items.push(item(
+            "subjects",
+            json!(expected_subjects),
+            json!(actual_subjects),
+        ));
+    }
+    if actual.duplicate_window.as_secs() != cfg.dedupe_window_secs {
+        items.push(item(
+            "duplicate_window_seconds",
+            json!(cfg.dedupe_window_secs),
+            json!(actual.duplicate_window.as_secs()),
+        ));
+    }
+    items
+}
+
+/// Fixture subjects the selected profiles write, in seeding order
+fn fixture_subjects(plan: &[PlannedProfile], ritual: &str) -> Vec<String> {
+    let mut subjects: Vec<String> = Vec::new();
+    for profile in plan {
+        let profile_subjects = match &profile.events {
+            Some(events) => events.iter().map(|e| e.subject.clone()).collect(),
+            None => preview_min_subjects(ritual),
+        };
+        for subject in profile_subjects {
+            if !subjects.contains(&subject) {
+                subjects.push(subject);
+            }
+        }
+    }
+    subjects
+}
+
+/// Build the drift report for `bundle` without changing anything.
+///
+/// Fixtures are the profiles `bootstrap --seed` would apply (see
+/// [`plan_seed`]); a subject with no message in the stream counts as drift.
+pub async fn detect(
+    js: &jetstream::Context,
+    cfg: &BootstrapConfig,
+    bundle: &Value,
+    ritual: &str,
+    requested: &[String],
+) -> Result<DriftReport> {
+    let plan = plan_seed(bundle, ritual, requested)?;
+    let mut report = DriftReport::default();
+
+    let stream = match js.get_stream(&cfg.stream_name).await {
+        Ok(mut stream) => {
+            let config = stream.info().await?.config.clone();
+            report.items.extend(diff_stream(cfg, Some(&config)));
+            Some(stream)
+        }
+        Err(_) => {
+            report.items.extend(diff_stream(cfg, None));
+            None
+        }
+    };
+
+    for subject in fixture_subjects(&plan, ritual) {
+        let seeded = match &stream {
+            Some(stream) => stream
+                .get_last_raw_message_by_subject(&subject)
+                .await
+                .is_ok(),
+            None => false,
+        };
+        if !seeded {
+            report.items.push(DriftItem {
+                kind: DriftKind::Fixture,
+                resource: subject,
+                field: "seeded".to_string(),
+                expected: json!(true),
+                actual: json!(false),
+                fixable: true,
+            });
+        }
+    }
+
+    let operate_ui: bundle::OperateUi = match bundle.get("operateUi") {
+        Some(value) => serde_json::from_value(value.clone()).context("parse bundle operateUi")?,
+        None => bundle::OperateUi::default(),
+    };
+    report
+        .items
+        .extend(diff_operate_ui(&cfg.ui_url, operate_ui.version.as_deref()).await);
+
+    Ok(report)
+}
+
+async fn diff_operate_ui(ui_url: &str, expected_version: Option<&str>) -> Vec<DriftItem> {
+    let item = |field: &str, expected: Value, actual: Value| DriftItem {
+        kind: DriftKind::OperateUi,
+        resource: ui_url.to_string(),
+        field: field.to_string(),
+        expected,
+        actual,
+        fixable: false,
+    };
+
+    let probe = async {
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()?
+            .get(format!("{}/admin/templates/report", ui_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Value>()
+            .await
+    }
+    .await;
+    let probe = match probe {
+        Ok(probe) => probe,
+        Err(err) => return vec![item("reachable", json!(true), json!(err.to_string()))],
+    };
+
+    let mut items = Vec::new();
+    if probe.get("template_ready").and_then(Value::as_bool) != Some(true) {
+        items.push(item(
+            "template_ready",
+            json!(true),
+            probe.get("template_ready").cloned().unwrap_or(Value::Null),
+        ));
+    }
+    if let Some(expected) = expected_version {
+        let actual = probe.get("version").cloned().unwrap_or(Value::Null);
+        if actual.as_str() != Some(expected) {
+            items.push(item("version", json!(expected), actual));
+        }
+    }
+    items
+}
+
+/// Reconcile the fixable items of `report`, returning the items applied.
+///
+/// An existing stream keeps every setting the bundle does not manage; only
+/// its subjects and duplicate window are replaced. Fixtures are re-seeded for
+/// missing subjects only.
+pub async fn apply(
+    js: &jetstream::Context,
+    cfg: &BootstrapConfig,
+    bundle: &Value,
+    ritual: &str,
+    requested: &[String],
+    report: &DriftReport,
+) -> Result<Vec<DriftItem>> {
+    if report.has(DriftKind::Stream) {
+        let duplicate_window = Duration::from_secs(cfg.dedupe_window_secs);
+        match js.get_stream(&cfg.stream_name).await {
+            Ok(mut stream) => {
+                let mut config = stream.info().await?.config.clone();
+                config.subjects = cfg.subjects.clone();
+                config.duplicate_window = duplicate_window;
+                js.update_stream(config)
+                    .await
+                    .with_context(|| format!("update stream {}", cfg.stream_name))?;
+            }
+            Err(_) => {
+                js.create_stream(jetstream::stream::Config {
+                    name: cfg.stream_name.clone(),
+                    subjects: cfg.subjects.clone(),
+                    duplicate_window,
+                    ..Default::default()
+                })
+                .await
+                .with_context(|| format!("create stream {}", cfg.stream_name))?;
+            }
+        }
+        tracing::info!(stream = %cfg.stream_name, "apply: stream reconciled");
+    }
+
+    if report.has(DriftKind::Fixture) {
+        for profile in plan_seed(bundle, ritual, requested)? {
+            match profile.events {
+                Some(events) => {
+                    let missing: Vec<_> = events
+                        .iter()
+                        .filter(|event| report.missing_fixture(&event.subject))
+                        .collect();
+                    for event in &missing {
+                        publish_idem(js, &event.subject, &event.body, &event.idempotency_key)
+                            .await?;
+                    }
+                    if !missing.is_empty() {
+                        tracing::info!(profile = %profile.name, events = missing.len(), "apply: fixtures seeded");
+                    }
+                }
+                None => {
+                    if preview_min_subjects(ritual)
+                        .iter()
+                        .any(|subject| report.missing_fixture(subject))
+                    {
+                        seed_preview_min(js, ritual, &cfg.ui_url).await?;
+                        tracing::info!(profile = %profile.name, "apply: fixtures seeded");
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(report
+        .items
+        .iter()
+        .filter(|item| item.fixable)
+        .cloned()
+        .collect())
+}
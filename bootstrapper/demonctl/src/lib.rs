@@ -1,6 +1,8 @@
 pub mod bundle;
+pub mod drift;
 pub mod libindex;
 pub mod provenance;
+pub mod seed;
 
 use anyhow::{anyhow, Context, Result};
 use async_nats::jetstream;
@@ -90,13 +92,27 @@ pub async fn ensure_stream(cfg: &BootstrapConfig) -> Result<jetstream::stream::S
     Ok(stream)
 }
 
+/// Runs seeded by [`seed_preview_min`]
+pub const PREVIEW_MIN_RUNS: [&str; 2] = ["bootstrap-run-b", "bootstrap-run-c"];
+
+fn preview_min_subject(ritual: &str, run: &str) -> String {
+    format!("demon.ritual.v1.default.{}.{}.events", ritual, run)
+}
+
+/// Event subjects written by [`seed_preview_min`]
+pub fn preview_min_subjects(ritual: &str) -> Vec<String> {
+    PREVIEW_MIN_RUNS
+        .iter()
+        .map(|run| preview_min_subject(ritual, run))
+        .collect()
+}
+
 pub async fn seed_preview_min(js: &jetstream::Context, ritual: &str, ui_url: &str) -> Result<()> {
     let tenant = "default";
-    let run_b = "bootstrap-run-b";
-    let run_c = "bootstrap-run-c";
+    let [run_b, run_c] = PREVIEW_MIN_RUNS;
     let gate_b = "gate-b";
     let gate_c = "gate-c";
-    let subject = |run: &str| format!("demon.ritual.v1.default.{}.{}.events", ritual, run);
+    let subject = |run: &str| preview_min_subject(ritual, run);
     let now = || Utc::now().to_rfc3339();
 
     // approval.requested (B)
@@ -141,7 +157,7 @@ pub async fn seed_preview_min(js: &jetstream::Context, ritual: &str, ui_url: &st
     Ok(())
 }
 
-async fn publish_idem(
+pub(crate) async fn publish_idem(
     js: &jetstream::Context,
     subject: &str,
     value: &serde_json::Value,
@@ -161,11 +177,31 @@ pub fn compute_effective_config(
     stream_name: Option<&str>,
     subjects: Option<Vec<String>>,
     ui_url: Option<&str>,
+) -> Result<(BootstrapConfig, Option<serde_json::Value>)> {
+    compute_effective_config_with_values(
+        bundle_path,
+        &bundle::BundleValues::default(),
+        nats_url,
+        stream_name,
+        subjects,
+        ui_url,
+    )
+}
+
+/// [`compute_effective_config`] with `--set` / `--values` overrides for the
+/// bundle's variables
+pub fn compute_effective_config_with_values(
+    bundle_path: Option<&std::path::Path>,
+    values: &bundle::BundleValues,
+    nats_url: Option<&str>,
+    stream_name: Option<&str>,
+    subjects: Option<Vec<String>>,
+    ui_url: Option<&str>,
 ) -> Result<(BootstrapConfig, Option<serde_json::Value>)> {
     let mut cfg = BootstrapConfig::default();
 
     let provenance = if let Some(path) = bundle_path {
-        let bundle = bundle::load_bundle(path)?;
+        let bundle = bundle::load_bundle_with_values(path, values)?;
 
         // Override config from bundle
         if !bundle.nats.url.is_empty() {
@@ -177,6 +213,9 @@ pub fn compute_effective_config(
         if !bundle.stream.subjects.is_empty() {
             cfg.subjects = bundle.stream.subjects.clone();
         }
+        if bundle.stream.duplicate_window_seconds > 0 {
+            cfg.dedupe_window_secs = bundle.stream.duplicate_window_seconds;
+        }
         if let Some(ref url) = bundle.operate_ui.base_url {
             if !url.is_empty() {
                 cfg.ui_url = url.clone();
@@ -206,25 +245,99 @@ pub fn compute_effective_config(
     Ok((cfg, provenance))
 }
 
+/// A profile selected for seeding; `events` is `None` for the built-in
+/// `preview-min`, which also grants an approval through the Operate UI
+pub struct PlannedProfile {
+    pub name: String,
+    pub events: Option<Vec<seed::SeedEvent>>,
+}
+
+/// Select and render the profiles to seed from a bundle.
+///
+/// Profiles come from `requested`, else the bundle's `seed.activeProfiles`,
+/// else the built-in `preview-min`. Bundle profiles shadow `preview-min`.
+/// Everything is rendered up front so a typo does not leave a partial seed.
+pub fn plan_seed(
+    bundle: &serde_json::Value,
+    ritual: &str,
+    requested: &[String],
+) -> Result<Vec<PlannedProfile>> {
+    let seeding: bundle::Seed = match bundle.get("seed") {
+        Some(value) => serde_json::from_value(value.clone()).context("parse bundle seed")?,
+        None => bundle::Seed::default(),
+    };
+    let selected = if !requested.is_empty() {
+        requested.to_vec()
+    } else {
+        seeding
+            .active_profiles
+            .clone()
+            .filter(|profiles| !profiles.is_empty())
+            .unwrap_or_else(|| vec![seed::PREVIEW_MIN_PROFILE.to_string()])
+    };
+
+    let now = Utc::now();
+    let mut plan = Vec::new();
+    for name in selected {
+        let events = match seeding.profiles.get(&name) {
+            Some(profile) => Some(seed::render_profile(&name, profile, ritual, now)?),
+            None if name == seed::PREVIEW_MIN_PROFILE => None,
+            None => {
+                let mut available: Vec<&str> =
+                    seeding.profiles.keys().map(String::as_str).collect();
+                if !seeding.profiles.contains_key(seed::PREVIEW_MIN_PROFILE) {
+                    available.push(seed::PREVIEW_MIN_PROFILE);
+                }
+                return Err(anyhow!(
+                    "unknown seed profile '{}' (available: {})",
+                    name,
+                    available.join(", ")
+                ));
+            }
+        };
+        plan.push(PlannedProfile { name, events });
+    }
+    Ok(plan)
+}
+
+/// Seed the named profiles from a bundle (see [`plan_seed`]), returning the
+/// profiles applied.
 pub async fn seed_from_bundle(
     js: &jetstream::Context,
-    _bundle: &serde_json::Value,
-    stream_name: &str,
+    bundle: &serde_json::Value,
+    ritual: &str,
     ui_url: &str,
-) -> Result<()> {
-    // For now, just seed preview min
-    seed_preview_min(js, stream_name, ui_url).await
+    requested: &[String],
+) -> Result<Vec<String>> {
+    let plan = plan_seed(bundle, ritual, requested)?;
+    for profile in &plan {
+        match &profile.events {
+            Some(events) => {
+                for event in events {
+                    publish_idem(js, &event.subject, &event.body, &event.idempotency_key).await?;
+                }
+                tracing::info!(profile = %profile.name, events = events.len(), "seed profile applied");
+            }
+            None => seed_preview_min(js, ritual, ui_url).await?,
+        }
+    }
+    Ok(plan.into_iter().map(|p| p.name).collect())
 }
 
-pub async fn verify_ui_with_token(ui_url: &str, _token: Option<&str>) -> Result<()> {
-    verify_ui(ui_url).await
+pub async fn verify_ui(ui_url: &str) -> Result<()> {
+    verify_ui_with_token(ui_url, None).await
 }
 
-pub async fn verify_ui(ui_url: &str) -> Result<()> {
+/// Like [`verify_ui`], sending `token` as a bearer JWT for an Operate UI that
+/// runs with `OPERATE_UI_AUTH=jwt`.
+pub async fn verify_ui_with_token(ui_url: &str, token: Option<&str>) -> Result<()> {
     let c = reqwest::Client::builder().build()?;
+    let get = |url: String| match token {
+        Some(token) => c.get(url).bearer_auth(token),
+        None => c.get(url),
+    };
     // Admin JSON probe
-    let probe: serde_json::Value = c
-        .get(format!("{}/admin/templates/report", ui_url))
+    let probe: serde_json::Value = get(format!("{}/admin/templates/report", ui_url))
         .send()
         .await
         .context("failed GET /admin/templates/report")?
@@ -240,20 +353,23 @@ pub async fn verify_ui(ui_url: &str) -> Result<()> {
     if tr.get("has_filter_tojson").and_then(|v| v.as_bool()) != Some(true) {
         return Err(anyhow!("verify: admin probe has_filter_tojson!=true"));
     }
-    // Try tenant-aware endpoint first
-    let runs: serde_json::Value = c
-        .get(format!("{}/api/tenants/default/runs", ui_url))
+    // Try tenant-aware endpoint first; one run is enough to verify
+    let runs: serde_json::Value = get(format!("{}/api/tenants/default/runs?limit=1", ui_url))
         .send()
         .await
         .context("failed GET /api/tenants/default/runs")?
         .error_for_status()?
         .json()
         .await?;
-    let len = runs.as_array().map(|a| a.len()).unwrap_or(0);
+    let len = runs
+        .get("runs")
+        .unwrap_or(&runs)
+        .as_array()
+        .map(|a| a.len())
+        .unwrap_or(0);
     if len < 1 {
         // Fallback to legacy endpoint for compatibility
-        let legacy_runs: serde_json::Value = c
-            .get(format!("{}/api/runs", ui_url))
+        let legacy_runs: serde_json::Value = get(format!("{}/api/runs?limit=1", ui_url))
             .send()
             .await
             .context("failed GET /api/runs")?
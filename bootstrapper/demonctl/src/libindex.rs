@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use jsonschema::{Draft, Validator};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
 use std::io::Write;
@@ -9,15 +9,15 @@ use std::path::{Path, PathBuf};
 use crate::bundle::canonicalize_bundle_to_bytes;
 use crate::provenance::compute_digest_hex;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct LibraryIndex {
     pub provider: String,
-    #[serde(rename = "baseUrl")]
+    #[serde(rename = "baseUrl", skip_serializing_if = "Option::is_none")]
     pub base_url: Option<String>,
     pub bundles: Vec<LibraryBundle>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct LibraryBundle {
     pub name: String,
     pub version: String,
@@ -28,12 +28,12 @@ pub struct LibraryBundle {
     pub pub_key_id: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Digest {
     pub sha256: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Signature {
     pub ed25519: String,
 }
@@ -46,6 +46,48 @@ pub fn load_index(path: &Path) -> Result<LibraryIndex> {
     Ok(idx)
 }
 
+/// `bootstrapper/library/index.json`, searched from the working directory upwards
+pub fn default_index_path() -> PathBuf {
+    let idx_path = PathBuf::from("bootstrapper/library/index.json");
+    if !idx_path.exists() {
+        for prefix in ["..", "../..", "../../.."].iter() {
+            let p = Path::new(prefix).join(&idx_path);
+            if p.exists() {
+                return p;
+            }
+        }
+    }
+    idx_path
+}
+
+/// Add `entry` to a local index, replacing any bundle with the same name and
+/// version; a missing index file is created
+pub fn upsert_local(index_path: &Path, entry: LibraryBundle) -> Result<()> {
+    let mut idx = if index_path.exists() {
+        load_index(index_path)?
+    } else {
+        LibraryIndex {
+            provider: "local".into(),
+            base_url: None,
+            bundles: Vec::new(),
+        }
+    };
+    if idx.provider != "local" {
+        anyhow::bail!("unsupported provider: {}", idx.provider);
+    }
+    match idx
+        .bundles
+        .iter_mut()
+        .find(|b| b.name == entry.name && b.version == entry.version)
+    {
+        Some(existing) => *existing = entry,
+        None => idx.bundles.push(entry),
+    }
+    let text = serde_json::to_string_pretty(&idx)? + "\n";
+    validate_index_schema(&text)?;
+    fs::write(index_path, text).with_context(|| format!("write index: {}", index_path.display()))
+}
+
 fn validate_index_schema(text: &str) -> Result<()> {
     let mut schema_path =
         Path::new("contracts/schemas/bootstrap.library.index.v0.json").to_path_buf();
@@ -222,12 +264,228 @@ pub fn resolve_https(uri: &str, index_path: &Path) -> Result<ResolvedBundle> {
     })
 }
 
-/// Resolve a bundle URI (supports both lib://local/ and lib://https/)
+/// Where `lib://remote/` URIs are resolved from
+#[derive(Debug, Clone)]
+pub struct RemoteIndexConfig {
+    /// URL of a published index.json (https, or http on localhost)
+    pub index_url: String,
+    /// Holds the last fetched index and every verified bundle
+    pub cache_dir: PathBuf,
+    /// Resolve from the cache only, without network access
+    pub offline: bool,
+}
+
+impl RemoteIndexConfig {
+    /// `DEMON_LIBRARY_INDEX_URL`, `DEMON_LIBRARY_CACHE` (default
+    /// `~/.demon/library-cache`) and `DEMON_LIBRARY_OFFLINE`
+    pub fn from_env() -> Result<Self> {
+        let index_url = std::env::var("DEMON_LIBRARY_INDEX_URL").map_err(|_| {
+            anyhow::anyhow!(
+                "lib://remote/ requires DEMON_LIBRARY_INDEX_URL to point at an index.json"
+            )
+        })?;
+        let cache_dir = match std::env::var("DEMON_LIBRARY_CACHE") {
+            Ok(dir) => PathBuf::from(dir),
+            Err(_) => std::env::var("HOME")
+                .map(|home| PathBuf::from(home).join(".demon/library-cache"))
+                .unwrap_or_else(|_| std::env::temp_dir().join("demon-library-cache")),
+        };
+        let offline = std::env::var("DEMON_LIBRARY_OFFLINE")
+            .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        Ok(Self {
+            index_url,
+            cache_dir,
+            offline,
+        })
+    }
+}
+
+/// Resolve `lib://remote/<name>@<version>` against a published index.
+///
+/// The index is fetched on every call and cached; when the index server is
+/// unreachable (or `offline` is set) the cached copy is used instead.
+/// Bundles are downloaded once, checked against the index digest and kept in
+/// the cache, so a verified bundle resolves without network access.
+pub fn resolve_remote(uri: &str, config: &RemoteIndexConfig) -> Result<ResolvedBundle> {
+    let without = uri
+        .strip_prefix("lib://remote/")
+        .ok_or_else(|| anyhow::anyhow!("unsupported uri: {}", uri))?;
+    let (name, version) = without.split_once('@').unwrap_or((without, ""));
+    // Both parts become cache path components
+    if name.is_empty()
+        || version.is_empty()
+        || without.contains(['/', '\\'])
+        || name.starts_with('.')
+        || version.starts_with('.')
+    {
+        anyhow::bail!("invalid bundle uri: {}", uri);
+    }
+
+    let index_url = reqwest::Url::parse(&config.index_url)
+        .with_context(|| format!("invalid library index URL: {}", config.index_url))?;
+    let local_http = matches!(
+        index_url.host_str(),
+        Some("localhost" | "127.0.0.1" | "[::1]")
+    );
+    if index_url.scheme() != "https" && !(index_url.scheme() == "http" && local_http) {
+        anyhow::bail!(
+            "library index must be served over https: {}",
+            config.index_url
+        );
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .context("build HTTP client")?;
+    let idx = fetch_remote_index(&client, &index_url, config)?;
+
+    let b = idx
+        .bundles
+        .into_iter()
+        .find(|b| b.name == name && b.version == version)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "bundle not found: {}@{} in {}",
+                name,
+                version,
+                config.index_url
+            )
+        })?;
+
+    let cached = config
+        .cache_dir
+        .join("bundles")
+        .join(name)
+        .join(format!("{}.yaml", version));
+    let cached_ok = cached.exists()
+        && canonicalize_bundle_to_bytes(&cached)
+            .map(|bytes| compute_digest_hex(&bytes) == b.digest.sha256)
+            .unwrap_or(false);
+
+    if !cached_ok {
+        if config.offline {
+            anyhow::bail!(
+                "bundle {}@{} is not cached in {} and offline mode is set",
+                name,
+                version,
+                config.cache_dir.display()
+            );
+        }
+        // Bundle paths are relative to baseUrl, or to the index itself
+        let base = match idx.base_url.as_deref() {
+            Some(base) => reqwest::Url::parse(&format!("{}/", base.trim_end_matches('/')))
+                .with_context(|| format!("invalid baseUrl: {}", base))?,
+            None => index_url.clone(),
+        };
+        let url = base
+            .join(b.path.trim_start_matches('/'))
+            .with_context(|| format!("invalid bundle path: {}", b.path))?;
+        let response = client
+            .get(url.clone())
+            .send()
+            .with_context(|| format!("fetch bundle from: {}", url))?;
+        if !response.status().is_success() {
+            anyhow::bail!("HTTP error {}: fetching {}", response.status(), url);
+        }
+        let content = response.bytes().context("read response body")?;
+
+        // Verify before the bundle becomes visible in the cache
+        let dir = cached.parent().expect("cache path has a parent");
+        fs::create_dir_all(dir).with_context(|| format!("create cache dir: {}", dir.display()))?;
+        let partial = dir.join(format!(".{}.yaml.partial", version));
+        fs::write(&partial, &content)
+            .with_context(|| format!("write bundle to: {}", partial.display()))?;
+        let actual_digest = compute_digest_hex(
+            &canonicalize_bundle_to_bytes(&partial).context("canonicalize downloaded bundle")?,
+        );
+        if actual_digest != b.digest.sha256 {
+            let _ = fs::remove_file(&partial);
+            anyhow::bail!(
+                "digest mismatch for bundle {}@{}: expected {}, got {}",
+                name,
+                version,
+                b.digest.sha256,
+                actual_digest
+            );
+        }
+        fs::rename(&partial, &cached)
+            .with_context(|| format!("write bundle to cache: {}", cached.display()))?;
+    }
+
+    let pathbuf = std::fs::canonicalize(&cached).unwrap_or(cached);
+    Ok(ResolvedBundle {
+        provider: "remote".into(),
+        name: b.name,
+        version: b.version,
+        path: pathbuf,
+        digest_sha256: b.digest.sha256,
+        sig_ed25519: b.sig.ed25519,
+        pub_key_id: b.pub_key_id,
+    })
+}
+
+/// Fetch and cache the remote index, falling back to the cached copy
+fn fetch_remote_index(
+    client: &reqwest::blocking::Client,
+    index_url: &reqwest::Url,
+    config: &RemoteIndexConfig,
+) -> Result<LibraryIndex> {
+    let cached = config.cache_dir.join("index.json");
+    let fetched = if config.offline {
+        Err(anyhow::anyhow!("offline mode is set"))
+    } else {
+        client
+            .get(index_url.clone())
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.text())
+            .with_context(|| format!("fetch library index from: {}", index_url))
+    };
+
+    let text = match fetched {
+        Ok(text) => {
+            validate_index_schema(&text)
+                .with_context(|| format!("invalid library index at {}", index_url))?;
+            fs::create_dir_all(&config.cache_dir)
+                .with_context(|| format!("create cache dir: {}", config.cache_dir.display()))?;
+            fs::write(&cached, &text)
+                .with_context(|| format!("write index cache: {}", cached.display()))?;
+            text
+        }
+        Err(err) if cached.exists() => {
+            tracing::warn!(
+                error = %format!("{:#}", err),
+                cache = %cached.display(),
+                "library index unavailable; using cached copy"
+            );
+            fs::read_to_string(&cached)
+                .with_context(|| format!("read index cache: {}", cached.display()))?
+        }
+        Err(err) => {
+            return Err(err.context(format!(
+                "no cached library index in {}",
+                config.cache_dir.display()
+            )))
+        }
+    };
+
+    let idx: LibraryIndex = serde_json::from_str(&text).context("parse index.json")?;
+    if idx.provider != "remote" && idx.provider != "https" {
+        anyhow::bail!("expected remote provider, got: {}", idx.provider);
+    }
+    Ok(idx)
+}
+
+/// Resolve a bundle URI (supports lib://local/, lib://https/ and lib://remote/)
 pub fn resolve(uri: &str, index_path: &Path) -> Result<ResolvedBundle> {
     if uri.starts_with("lib://local/") {
         resolve_local(uri, index_path)
     } else if uri.starts_with("lib://https/") {
         resolve_https(uri, index_path)
+    } else if uri.starts_with("lib://remote/") {
+        resolve_remote(uri, &RemoteIndexConfig::from_env()?)
     } else {
         anyhow::bail!("unsupported URI scheme: {}", uri)
     }
@@ -1,9 +1,11 @@
 use anyhow::Result;
+use bootstrapper_demonctl::drift;
 use bootstrapper_demonctl::libindex::resolve;
 use bootstrapper_demonctl::provenance::verify_provenance;
 use bootstrapper_demonctl::{
-    bundle::load_bundle, ensure_stream, seed_from_bundle, seed_preview_min, verify_ui_with_token,
-    BootstrapConfig, Profile,
+    bundle::{load_bundle_with_values, Bundle, BundleValues},
+    ensure_stream, seed_from_bundle, seed_preview_min, verify_ui_with_token, BootstrapConfig,
+    Profile,
 };
 use clap::{ArgAction, Parser, ValueEnum};
 use tracing::{info, Level};
@@ -36,6 +38,18 @@ struct Cli {
     #[arg(long)]
     bundle: Option<String>,
 
+    /// Seeding profile from the bundle's seed.profiles (repeatable;
+    /// default: seed.activeProfiles, else preview-min)
+    #[arg(long = "seed-profile", value_name = "NAME")]
+    seed_profiles: Vec<String>,
+
+    /// Set a bundle variable (repeatable; overrides --values)
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
+    /// YAML file of bundle variable values (repeatable; later files win)
+    #[arg(long = "values", value_name = "FILE")]
+    values: Vec<std::path::PathBuf>,
+
     /// Optional overrides (flags > bundle > env)
     #[arg(long)]
     nats_url: Option<String>,
@@ -47,6 +61,14 @@ struct Cli {
     /// Verify only (resolve + provenance check; no NATS/seed/verify-UI phases)
     #[arg(long, action = ArgAction::SetTrue)]
     verify_only: bool,
+
+    /// Report drift between the bundle and the provisioned stream, fixtures
+    /// and Operate UI without changing anything
+    #[arg(long, action = ArgAction::SetTrue)]
+    diff: bool,
+    /// With --diff, reconcile only the differences bootstrap owns
+    #[arg(long, action = ArgAction::SetTrue, requires = "diff")]
+    apply: bool,
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -88,8 +110,14 @@ async fn main() -> Result<()> {
         .bundle
         .as_deref()
         .filter(|uri| !uri.starts_with("lib://"));
-    let (cfg, provenance) = bootstrapper_demonctl::compute_effective_config(
+    let values = BundleValues::from_sources(&cli.values, &cli.set)?;
+    anyhow::ensure!(
+        values.is_empty() || cli.bundle.is_some(),
+        "--set/--values require a bundle (--bundle)"
+    );
+    let (cfg, provenance) = bootstrapper_demonctl::compute_effective_config_with_values(
         bundle_for_config.map(std::path::Path::new),
+        &values,
         cli.nats_url.as_deref(),
         cli.stream_name.as_deref(),
         None, // subjects - not in CLI yet
@@ -174,48 +202,109 @@ async fn main() -> Result<()> {
         anyhow::bail!("--verify-only requires --bundle lib://... URI");
     }
 
-    if !(cli.ensure_stream || cli.seed || cli.verify) {
+    if cli.diff {
+        run_diff(&cfg, &cli, &values).await
+    } else if !(cli.ensure_stream || cli.seed || cli.verify) {
         // default: run all
-        run_all(&cfg, &cli.ritual_id, cli.bundle.as_deref()).await
+        run_all(&cfg, &cli, &values).await
+    } else {
+        run_some(&cfg, &cli, &values).await
+    }
+}
+
+fn load_seed_bundle(uri: &str, values: &BundleValues) -> Result<Bundle> {
+    // Resolve the URI if it's a lib:// URI
+    let bundle_path = if uri.starts_with("lib://") {
+        let mut idx_path = std::path::PathBuf::from("bootstrapper/library/index.json");
+        if !idx_path.exists() {
+            for prefix in ["..", "../..", "../../.."].iter() {
+                let p = std::path::Path::new(prefix).join("bootstrapper/library/index.json");
+                if p.exists() {
+                    idx_path = p;
+                    break;
+                }
+            }
+        }
+        let resolved = tokio::task::block_in_place(|| resolve(uri, &idx_path))?;
+        resolved.path
     } else {
-        run_some(&cfg, &cli).await
+        std::path::PathBuf::from(uri)
+    };
+    load_bundle_with_values(&bundle_path, values)
+}
+
+/// Report drift against the bundle and, with --apply, reconcile it
+async fn run_diff(cfg: &BootstrapConfig, cli: &Cli, values: &BundleValues) -> Result<()> {
+    let bundle = match cli.bundle.as_deref() {
+        Some(uri) => serde_json::to_value(load_seed_bundle(uri, values)?)?,
+        None => {
+            anyhow::ensure!(
+                cli.seed_profiles.is_empty(),
+                "--seed-profile requires a bundle (--bundle)"
+            );
+            serde_json::json!({})
+        }
+    };
+    let client = async_nats::connect(&cfg.nats_url).await?;
+    let js = async_nats::jetstream::new(client);
+
+    let report = drift::detect(&js, cfg, &bundle, &cli.ritual_id, &cli.seed_profiles).await?;
+    println!(
+        "{}",
+        serde_json::json!({
+            "phase": "diff",
+            "in_sync": report.in_sync(),
+            "drift": report.items,
+        })
+    );
+    if cli.apply {
+        let applied = drift::apply(
+            &js,
+            cfg,
+            &bundle,
+            &cli.ritual_id,
+            &cli.seed_profiles,
+            &report,
+        )
+        .await?;
+        println!(
+            "{}",
+            serde_json::json!({ "phase": "apply", "applied": applied })
+        );
     }
+    Ok(())
 }
 
-async fn run_all(cfg: &BootstrapConfig, ritual: &str, bundle_uri: Option<&str>) -> Result<()> {
+async fn run_all(cfg: &BootstrapConfig, cli: &Cli, values: &BundleValues) -> Result<()> {
     let stream = ensure_stream(cfg).await?;
     info!(name=%stream.cached_info().config.name, "ensure_stream: ok");
     let client = async_nats::connect(&cfg.nats_url).await?;
     let js = async_nats::jetstream::new(client);
-    if let Some(uri) = bundle_uri {
-        // Resolve the URI if it's a lib:// URI
-        let bundle_path = if uri.starts_with("lib://") {
-            let mut idx_path = std::path::PathBuf::from("bootstrapper/library/index.json");
-            if !idx_path.exists() {
-                for prefix in ["..", "../..", "../../.."].iter() {
-                    let p = std::path::Path::new(prefix).join("bootstrapper/library/index.json");
-                    if p.exists() {
-                        idx_path = p;
-                        break;
-                    }
-                }
-            }
-            let resolved = tokio::task::block_in_place(|| resolve(uri, &idx_path))?;
-            resolved.path
-        } else {
-            std::path::PathBuf::from(uri)
-        };
-        let b = load_bundle(&bundle_path)?;
+    if let Some(uri) = cli.bundle.as_deref() {
+        let b = load_seed_bundle(uri, values)?;
         let b_json = serde_json::to_value(&b)?;
-        seed_from_bundle(&js, &b_json, &cfg.stream_name, &cfg.ui_url).await?;
-        let token = b
-            .operate_ui
-            .admin_token
-            .or_else(|| std::env::var("ADMIN_TOKEN").ok());
-        verify_ui_with_token(&cfg.ui_url, token.as_deref()).await?;
+        let applied = seed_from_bundle(
+            &js,
+            &b_json,
+            &cli.ritual_id,
+            &cfg.ui_url,
+            &cli.seed_profiles,
+        )
+        .await?;
+        info!(profiles = ?applied, "seed profiles applied");
+        if b.operate_ui.admin_token.is_some() {
+            tracing::warn!(
+                "operateUi.adminToken is no longer used; verify authenticates with DEMONCTL_JWT"
+            );
+        }
+        verify_ui_with_token(&cfg.ui_url, std::env::var("DEMONCTL_JWT").ok().as_deref()).await?;
     } else {
-        seed_preview_min(&js, ritual, &cfg.ui_url).await?;
-        verify_ui_with_token(&cfg.ui_url, std::env::var("ADMIN_TOKEN").ok().as_deref()).await?;
+        anyhow::ensure!(
+            cli.seed_profiles.is_empty(),
+            "--seed-profile requires a bundle (--bundle)"
+        );
+        seed_preview_min(&js, &cli.ritual_id, &cfg.ui_url).await?;
+        verify_ui_with_token(&cfg.ui_url, std::env::var("DEMONCTL_JWT").ok().as_deref()).await?;
     }
     info!("seed: ok");
     info!("verify: ok");
@@ -223,7 +312,7 @@ async fn run_all(cfg: &BootstrapConfig, ritual: &str, bundle_uri: Option<&str>)
     Ok(())
 }
 
-async fn run_some(cfg: &BootstrapConfig, cli: &Cli) -> Result<()> {
+async fn run_some(cfg: &BootstrapConfig, cli: &Cli, values: &BundleValues) -> Result<()> {
     if cli.ensure_stream {
         let stream = ensure_stream(cfg).await?;
         info!(name=%stream.cached_info().config.name, "ensure_stream: ok");
@@ -231,11 +320,31 @@ async fn run_some(cfg: &BootstrapConfig, cli: &Cli) -> Result<()> {
     if cli.seed {
         let client = async_nats::connect(&cfg.nats_url).await?;
         let js = async_nats::jetstream::new(client);
-        seed_preview_min(&js, &cli.ritual_id, &cfg.ui_url).await?;
+        match cli.bundle.as_deref() {
+            // Profiles are opt-in here; a plain --seed keeps seeding preview-min
+            Some(uri) if !cli.seed_profiles.is_empty() => {
+                let b_json = serde_json::to_value(load_seed_bundle(uri, values)?)?;
+                seed_from_bundle(
+                    &js,
+                    &b_json,
+                    &cli.ritual_id,
+                    &cfg.ui_url,
+                    &cli.seed_profiles,
+                )
+                .await?;
+            }
+            _ => {
+                anyhow::ensure!(
+                    cli.seed_profiles.is_empty(),
+                    "--seed-profile requires a bundle (--bundle)"
+                );
+                seed_preview_min(&js, &cli.ritual_id, &cfg.ui_url).await?;
+            }
+        }
         info!("seed: ok");
     }
     if cli.verify {
-        verify_ui_with_token(&cfg.ui_url, std::env::var("ADMIN_TOKEN").ok().as_deref()).await?;
+        verify_ui_with_token(&cfg.ui_url, std::env::var("DEMONCTL_JWT").ok().as_deref()).await?;
         info!("verify: ok");
     }
     info!("done");
@@ -1,10 +1,11 @@
 use crate::bundle::canonicalize_bundle_to_bytes;
 use anyhow::{Context, Result};
 use base64::{engine::general_purpose, Engine as _};
-use ed25519_dalek::{Signature, VerifyingKey};
+use ed25519_dalek::pkcs8::DecodePrivateKey;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
 use sha2::{Digest, Sha256};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct VerifyResult {
     pub digest_hex: String,
@@ -12,6 +13,12 @@ pub struct VerifyResult {
     pub reason: Option<String>,
 }
 
+pub struct SignResult {
+    pub digest_hex: String,
+    pub sig_b64: String,
+    pub pub_key_b64: String,
+}
+
 pub fn compute_digest_hex(bytes: &[u8]) -> String {
     let mut h = Sha256::new();
     h.update(bytes);
@@ -34,36 +41,10 @@ pub fn verify_provenance(
         reason = Some("digest-mismatch".to_string());
     } else {
         // Load pubkey
-        let mut pk_path = Path::new("contracts/keys").join(format!("{}.ed25519.pub", pubkey_id));
-        if !pk_path.exists() {
-            for prefix in ["..", "../..", "../../.."].iter() {
-                let p = Path::new(prefix).join(&pk_path);
-                if p.exists() {
-                    pk_path = p;
-                    break;
-                }
-            }
-        }
+        let pk_path = pubkey_path(pubkey_id);
         let pk_b64 = fs::read_to_string(&pk_path)
             .with_context(|| format!("read pubkey: {}", pk_path.display()))?;
-        let pk_trim = pk_b64.trim();
-        let pk_bytes = general_purpose::STANDARD_NO_PAD
-            .decode(pk_trim)
-            .or_else(|_| general_purpose::STANDARD.decode(pk_trim))
-            .context("decode pubkey base64")?;
-        let vk = VerifyingKey::from_bytes(
-            pk_bytes
-                .as_slice()
-                .try_into()
-                .map_err(|_| anyhow::anyhow!("pubkey length"))?,
-        )?;
-        let sig_trim = sig_b64.trim();
-        let sig_bytes = general_purpose::STANDARD_NO_PAD
-            .decode(sig_trim)
-            .or_else(|_| general_purpose::STANDARD.decode(sig_trim))
-            .context("decode signature base64")?;
-        let sig = Signature::from_slice(&sig_bytes)?;
-        ok = vk.verify_strict(&canon, &sig).is_ok();
+        ok = verify_signature(&canon, &pk_b64, sig_b64)?;
         if !ok {
             reason = Some("signature-invalid".to_string());
         }
@@ -74,3 +55,106 @@ pub fn verify_provenance(
         reason,
     })
 }
+
+/// `contracts/keys/<id>.ed25519.pub`, searched from the working directory upwards
+pub fn pubkey_path(pubkey_id: &str) -> PathBuf {
+    let pk_path = Path::new("contracts/keys").join(format!("{}.ed25519.pub", pubkey_id));
+    if !pk_path.exists() {
+        for prefix in ["..", "../..", "../../.."].iter() {
+            let p = Path::new(prefix).join(&pk_path);
+            if p.exists() {
+                return p;
+            }
+        }
+    }
+    pk_path
+}
+
+fn decode_b64(text: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let trimmed = text.trim();
+    general_purpose::STANDARD_NO_PAD
+        .decode(trimmed)
+        .or_else(|_| general_purpose::STANDARD.decode(trimmed))
+}
+
+/// Check an ed25519 signature over canonical bundle bytes
+pub fn verify_signature(canon: &[u8], pk_b64: &str, sig_b64: &str) -> Result<bool> {
+    let pk_bytes = decode_b64(pk_b64).context("decode pubkey base64")?;
+    let vk = VerifyingKey::from_bytes(
+        pk_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("pubkey length"))?,
+    )?;
+    let sig_bytes = decode_b64(sig_b64).context("decode signature base64")?;
+    let sig = Signature::from_slice(&sig_bytes)?;
+    Ok(vk.verify_strict(canon, &sig).is_ok())
+}
+
+/// Load an ed25519 private key: PKCS#8 PEM (`openssl genpkey -algorithm ed25519`)
+/// or the base64 32-byte seed
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let text = fs::read_to_string(path).with_context(|| format!("read key: {}", path.display()))?;
+    if text.trim_start().starts_with("-----BEGIN") {
+        return SigningKey::from_pkcs8_pem(&text)
+            .map_err(|e| anyhow::anyhow!("parse PKCS#8 key {}: {}", path.display(), e));
+    }
+    let bytes = decode_b64(&text).context("decode key base64")?;
+    // Accept the 64-byte seed+pubkey form some tools write
+    let seed: [u8; 32] = match bytes.len() {
+        32 | 64 => bytes[..32].try_into()?,
+        _ => anyhow::bail!("key must be a 32-byte ed25519 seed"),
+    };
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Base64 (unpadded) public key, the `contracts/keys/*.ed25519.pub` format
+pub fn public_key_b64(key: &VerifyingKey) -> String {
+    general_purpose::STANDARD_NO_PAD.encode(key.as_bytes())
+}
+
+/// Digest and sign a bundle in the format [`verify_provenance`] checks
+pub fn sign_bundle(bundle_path: &Path, key: &SigningKey) -> Result<SignResult> {
+    let canon = canonicalize_bundle_to_bytes(bundle_path)?;
+    let sig = key.sign(&canon);
+    Ok(SignResult {
+        digest_hex: compute_digest_hex(&canon),
+        sig_b64: general_purpose::STANDARD_NO_PAD.encode(sig.to_bytes()),
+        pub_key_b64: public_key_b64(&key.verifying_key()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_bundle_verifies_and_tampering_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.yaml");
+        fs::write(
+            &path,
+            "nats:\n  url: nats://a:4222\nstream:\n  name: S\n  subjects: [x]\noperateUi:\n  baseUrl: http://ui\nseed:\n  enabled: false\n",
+        )
+        .unwrap();
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+
+        let signed = sign_bundle(&path, &key).unwrap();
+        let canon = canonicalize_bundle_to_bytes(&path).unwrap();
+        assert_eq!(signed.digest_hex, compute_digest_hex(&canon));
+        assert!(verify_signature(&canon, &signed.pub_key_b64, &signed.sig_b64).unwrap());
+
+        let mut tampered = canon.clone();
+        tampered.push(b' ');
+        assert!(!verify_signature(&tampered, &signed.pub_key_b64, &signed.sig_b64).unwrap());
+    }
+
+    #[test]
+    fn seed_key_file_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("k.ed25519");
+        fs::write(&path, general_purpose::STANDARD.encode([7u8; 32])).unwrap();
+        let key = load_signing_key(&path).unwrap();
+        assert_eq!(key.to_bytes(), [7u8; 32]);
+    }
+}
@@ -0,0 +1,185 @@
+//! Named seeding profiles declared under `seed.profiles` in a bundle
+//!
+//! A profile is a list of event templates rendered `count` times. Strings in
+//! templates may use `{{name}}` placeholders (`${VAR}` is already taken by
+//! bundle env interpolation):
+//!
+//! - `{{index}}` — 1-based instance number
+//! - `{{profile}}` / `{{ritualId}}` — profile name and seeding ritual
+//! - `{{now}}`, `{{now+30s}}`, `{{now-5s}}` — RFC 3339 timestamps
+//! - any key from the profile's `params`, which may itself use the above
+//!
+//! Each rendered event is published with its `idempotencyKey` as
+//! `Nats-Msg-Id`, so re-running a seed inside the dedupe window is a no-op.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+
+/// Built-in profile seeding the two bootstrap approval runs
+pub const PREVIEW_MIN_PROFILE: &str = "preview-min";
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SeedProfile {
+    #[serde(default)]
+    pub description: Option<String>,
+    /// How many times the templates are rendered
+    #[serde(default = "default_count")]
+    pub count: u32,
+    #[serde(default)]
+    pub params: BTreeMap<String, JsonValue>,
+    pub events: Vec<EventTemplate>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EventTemplate {
+    #[serde(rename = "idempotencyKey")]
+    pub idempotency_key: String,
+    /// Event body; must render a `runId`
+    pub event: JsonValue,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// A rendered event ready to publish
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedEvent {
+    pub subject: String,
+    pub idempotency_key: String,
+    pub body: JsonValue,
+}
+
+/// Render every instance of `profile` in publish order
+pub fn render_profile(
+    name: &str,
+    profile: &SeedProfile,
+    ritual: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<SeedEvent>> {
+    let mut events = Vec::new();
+    for index in 1..=profile.count {
+        let mut vars = BTreeMap::new();
+        vars.insert("index".to_string(), index.to_string());
+        vars.insert("profile".to_string(), name.to_string());
+        vars.insert("ritualId".to_string(), ritual.to_string());
+
+        let mut params = BTreeMap::new();
+        for (key, value) in &profile.params {
+            let raw = match value {
+                JsonValue::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            let rendered = render_str(&raw, &vars, now)
+                .with_context(|| format!("seed profile '{}': params.{}", name, key))?;
+            params.insert(key.clone(), rendered);
+        }
+        vars.extend(params);
+
+        for (position, template) in profile.events.iter().enumerate() {
+            let context = || format!("seed profile '{}': events[{}]", name, position);
+            let body = render_value(&template.event, &vars, now).with_context(context)?;
+            let idempotency_key =
+                render_str(&template.idempotency_key, &vars, now).with_context(context)?;
+            let subject = subject_for(&body, ritual).with_context(context)?;
+            events.push(SeedEvent {
+                subject,
+                idempotency_key,
+                body,
+            });
+        }
+    }
+    Ok(events)
+}
+
+/// `demon.ritual.v1.<tenant>.<ritual>.<run>.events` from the event body
+fn subject_for(body: &JsonValue, ritual: &str) -> Result<String> {
+    let run_id = body
+        .get("runId")
+        .and_then(JsonValue::as_str)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| anyhow!("event must set runId"))?;
+    let ritual_id = body
+        .get("ritualId")
+        .and_then(JsonValue::as_str)
+        .unwrap_or(ritual);
+    let tenant = body
+        .get("tenantId")
+        .and_then(JsonValue::as_str)
+        .unwrap_or("default");
+    Ok(format!(
+        "demon.ritual.v1.{}.{}.{}.events",
+        tenant, ritual_id, run_id
+    ))
+}
+
+fn render_value(
+    value: &JsonValue,
+    vars: &BTreeMap<String, String>,
+    now: DateTime<Utc>,
+) -> Result<JsonValue> {
+    Ok(match value {
+        JsonValue::String(s) => JsonValue::String(render_str(s, vars, now)?),
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .map(|item| render_value(item, vars, now))
+                .collect::<Result<_>>()?,
+        ),
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), render_value(v, vars, now)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn render_str(s: &str, vars: &BTreeMap<String, String>, now: DateTime<Utc>) -> Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| anyhow!("unterminated placeholder in '{}'", s))?;
+        out.push_str(&rest[..start]);
+        let name = rest[start + 2..start + end].trim();
+        out.push_str(&resolve(name, vars, now)?);
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn resolve(name: &str, vars: &BTreeMap<String, String>, now: DateTime<Utc>) -> Result<String> {
+    if let Some(value) = vars.get(name) {
+        return Ok(value.clone());
+    }
+    if let Some(offset) = name.strip_prefix("now") {
+        let at = if offset.is_empty() {
+            now
+        } else {
+            now + parse_offset(offset)
+                .ok_or_else(|| anyhow!("invalid time offset in '{{{{{}}}}}'", name))?
+        };
+        return Ok(at.to_rfc3339());
+    }
+    bail!(
+        "unknown placeholder '{{{{{}}}}}'; define it under the profile's params",
+        name
+    )
+}
+
+/// `+30s` / `-5s`
+fn parse_offset(offset: &str) -> Option<Duration> {
+    let (sign, digits) = match offset.as_bytes().first()? {
+        b'+' => (1, &offset[1..]),
+        b'-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+    let secs: i64 = digits.strip_suffix('s')?.parse().ok()?;
+    Some(Duration::seconds(sign * secs))
+}
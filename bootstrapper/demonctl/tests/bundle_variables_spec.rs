@@ -0,0 +1,123 @@
+use bootstrapper_demonctl::bundle::{load_bundle, load_bundle_with_values, BundleValues};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn repo_path(rel: &str) -> PathBuf {
+    let candidates = [
+        Path::new(rel).to_path_buf(),
+        Path::new("..").join(rel),
+        Path::new("../..").join(rel),
+        Path::new("../../..").join(rel),
+    ];
+    for p in candidates {
+        if p.exists() {
+            return p;
+        }
+    }
+    PathBuf::from(rel)
+}
+
+fn write_bundle(dir: &TempDir) -> PathBuf {
+    let path = dir.path().join("bundle.yaml");
+    fs::write(
+        &path,
+        r#"variables:
+  natsUrl: { type: url, default: "nats://127.0.0.1:4222" }
+  streamName: { type: string, default: RITUAL_EVENTS }
+  window: { type: integer, default: 120 }
+nats:
+  url: "${vars.natsUrl}"
+stream:
+  name: "${vars.streamName}"
+  subjects: ["demon.ritual.v1.>"]
+  duplicateWindowSeconds: ${vars.window}
+operateUi:
+  baseUrl: "http://127.0.0.1:3000"
+seed:
+  enabled: false
+"#,
+    )
+    .unwrap();
+    path
+}
+
+#[test]
+fn defaults_apply_without_overrides() {
+    let dir = TempDir::new().unwrap();
+    let b = load_bundle(&write_bundle(&dir)).unwrap();
+    assert_eq!(b.nats.url, "nats://127.0.0.1:4222");
+    assert_eq!(b.stream.name, "RITUAL_EVENTS");
+    assert_eq!(b.stream.duplicate_window_seconds, 120);
+}
+
+#[test]
+fn set_overrides_values_file() {
+    let dir = TempDir::new().unwrap();
+    let bundle = write_bundle(&dir);
+    let values_file = dir.path().join("staging.yaml");
+    fs::write(
+        &values_file,
+        "natsUrl: nats://nats.staging:4222\nwindow: 300\n",
+    )
+    .unwrap();
+
+    let values = BundleValues::from_sources(&[values_file], &["window=45".to_string()]).unwrap();
+    let b = load_bundle_with_values(&bundle, &values).unwrap();
+    assert_eq!(b.nats.url, "nats://nats.staging:4222");
+    assert_eq!(b.stream.duplicate_window_seconds, 45);
+}
+
+#[test]
+fn type_errors_and_unknown_keys_are_reported_together() {
+    let dir = TempDir::new().unwrap();
+    let bundle = write_bundle(&dir);
+    let values = BundleValues::from_sources(
+        &[],
+        &[
+            "window=soon".to_string(),
+            "natsUrl=localhost".to_string(),
+            "uiUrl=http://x".to_string(),
+        ],
+    )
+    .unwrap();
+
+    let msg = format!(
+        "{:#}",
+        load_bundle_with_values(&bundle, &values).unwrap_err()
+    );
+    assert!(
+        msg.contains("variable 'window' value: expected an integer"),
+        "{msg}"
+    );
+    assert!(
+        msg.contains("variable 'natsUrl' value: expected an absolute URL"),
+        "{msg}"
+    );
+    assert!(msg.contains("'uiUrl' is not a declared variable"), "{msg}");
+}
+
+#[test]
+fn set_requires_key_value_form() {
+    assert!(BundleValues::from_sources(&[], &["natsUrl".to_string()]).is_err());
+}
+
+#[test]
+fn environments_example_renders_each_values_file() {
+    let bundle = repo_path("examples/bundles/environments.yaml");
+    let prod =
+        BundleValues::from_sources(&[repo_path("examples/bundles/values/production.yaml")], &[])
+            .unwrap();
+    let b = load_bundle_with_values(&bundle, &prod).unwrap();
+    assert_eq!(b.stream.name, "RITUAL_EVENTS_PROD");
+    assert_eq!(b.seed.enabled, Some(false));
+
+    let staging =
+        BundleValues::from_sources(&[repo_path("examples/bundles/values/staging.yaml")], &[])
+            .unwrap();
+    let b = load_bundle_with_values(&bundle, &staging).unwrap();
+    assert_eq!(
+        b.operate_ui.base_url.as_deref(),
+        Some("https://operate.staging.example.com")
+    );
+}
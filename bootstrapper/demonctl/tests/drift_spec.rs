@@ -0,0 +1,61 @@
+use async_nats::jetstream::stream::Config;
+use bootstrapper_demonctl::drift::{diff_stream, DriftKind};
+use bootstrapper_demonctl::BootstrapConfig;
+use serde_json::json;
+use std::time::Duration;
+
+fn expected() -> BootstrapConfig {
+    BootstrapConfig {
+        stream_name: "RITUAL_EVENTS".to_string(),
+        subjects: vec![
+            "demon.ritual.v1.>".to_string(),
+            "demon.approval.v1.>".to_string(),
+        ],
+        dedupe_window_secs: 120,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn missing_stream_is_reported_as_fixable() {
+    let items = diff_stream(&expected(), None);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].kind, DriftKind::Stream);
+    assert_eq!(items[0].field, "exists");
+    assert!(items[0].fixable);
+}
+
+#[test]
+fn matching_stream_has_no_drift_regardless_of_subject_order() {
+    let actual = Config {
+        name: "RITUAL_EVENTS".to_string(),
+        subjects: vec![
+            "demon.approval.v1.>".to_string(),
+            "demon.ritual.v1.>".to_string(),
+        ],
+        duplicate_window: Duration::from_secs(120),
+        ..Default::default()
+    };
+    assert!(diff_stream(&expected(), Some(&actual)).is_empty());
+}
+
+#[test]
+fn changed_subjects_and_duplicate_window_are_each_reported() {
+    let actual = Config {
+        name: "RITUAL_EVENTS".to_string(),
+        subjects: vec!["demon.ritual.v1.>".to_string()],
+        duplicate_window: Duration::from_secs(30),
+        ..Default::default()
+    };
+
+    let items = diff_stream(&expected(), Some(&actual));
+    let fields: Vec<&str> = items.iter().map(|i| i.field.as_str()).collect();
+    assert_eq!(fields, ["subjects", "duplicate_window_seconds"]);
+    assert_eq!(
+        items[0].expected,
+        json!(["demon.approval.v1.>", "demon.ritual.v1.>"])
+    );
+    assert_eq!(items[0].actual, json!(["demon.ritual.v1.>"]));
+    assert_eq!(items[1].expected, json!(120));
+    assert_eq!(items[1].actual, json!(30));
+}
@@ -251,3 +251,144 @@ fn libindex_generic_resolve() {
     let msg = format!("{}", err);
     assert!(msg.contains("unsupported URI scheme"));
 }
+
+#[test]
+fn libindex_upsert_creates_index_and_replaces_same_version() {
+    use bootstrapper_demonctl::libindex::{
+        load_index, upsert_local, Digest, LibraryBundle, Signature,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let idx_path = dir.path().join("index.json");
+    let entry = |version: &str, sig: &str| LibraryBundle {
+        name: "team".into(),
+        version: version.into(),
+        path: "bundles/team.yaml".into(),
+        digest: Digest {
+            sha256: "a".repeat(64),
+        },
+        sig: Signature {
+            ed25519: sig.into(),
+        },
+        pub_key_id: "team".into(),
+    };
+
+    upsert_local(&idx_path, entry("1.0.0", "first")).unwrap();
+    upsert_local(&idx_path, entry("1.0.0", "second")).unwrap();
+    upsert_local(&idx_path, entry("1.1.0", "third")).unwrap();
+
+    let idx = load_index(&idx_path).unwrap();
+    assert_eq!(idx.provider, "local");
+    assert_eq!(idx.bundles.len(), 2);
+    assert_eq!(idx.bundles[0].sig.ed25519, "second");
+    assert_eq!(idx.bundles[1].version, "1.1.0");
+}
+
+fn remote_index(bundle_path: &str) -> String {
+    format!(
+        r#"{{
+      "provider": "remote",
+      "bundles": [{{
+        "name": "preview-local-dev",
+        "version": "0.0.1",
+        "path": "{}",
+        "digest": {{"sha256": "f691d7f0acf56b000bea35321d5dcdfcdc56a0f2f033f49840b86e2438d59445"}},
+        "sig": {{"ed25519": "azOENOcSL/BhHwi9TAZQwrCpyR4GYml9kHgJUp9wYrNSoixdog7rF6VJvDYp4JkvO2BJzppRLwDh27Ik38kfCQ"}},
+        "pubKeyId": "preview"
+      }}]
+    }}"#,
+        bundle_path
+    )
+}
+
+#[test]
+fn libindex_remote_resolve_caches_and_falls_back_offline() {
+    use bootstrapper_demonctl::libindex::{resolve_remote, RemoteIndexConfig};
+    let _guard = HTTPS_RESOLVE_MUTEX.lock().unwrap();
+    let cache = tempfile::tempdir().unwrap();
+    let bundle_yaml = fs::read_to_string(repo_path("examples/bundles/local-dev.yaml")).unwrap();
+
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/library/index.json"))
+            .respond_with(status_code(200).body(remote_index("bundles/local-dev.yaml"))),
+    );
+    // Downloaded once; the second resolve is served from the cache
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            "/library/bundles/local-dev.yaml",
+        ))
+        .times(1)
+        .respond_with(status_code(200).body(bundle_yaml)),
+    );
+    let config = RemoteIndexConfig {
+        index_url: server.url_str("/library/index.json"),
+        cache_dir: cache.path().to_path_buf(),
+        offline: false,
+    };
+
+    let resolved = resolve_remote("lib://remote/preview-local-dev@0.0.1", &config).unwrap();
+    assert_eq!(resolved.provider, "remote");
+    assert!(resolved
+        .path
+        .starts_with(fs::canonicalize(cache.path()).unwrap()));
+    assert!(cache.path().join("index.json").exists());
+    drop(server);
+
+    // Index server gone: the cached index and bundle still resolve
+    let again = resolve_remote("lib://remote/preview-local-dev@0.0.1", &config).unwrap();
+    assert_eq!(again.path, resolved.path);
+
+    let offline = RemoteIndexConfig {
+        offline: true,
+        ..config
+    };
+    assert!(resolve_remote("lib://remote/preview-local-dev@0.0.1", &offline).is_ok());
+}
+
+#[test]
+fn libindex_remote_digest_mismatch_is_not_cached() {
+    use bootstrapper_demonctl::libindex::{resolve_remote, RemoteIndexConfig};
+    let _guard = HTTPS_RESOLVE_MUTEX.lock().unwrap();
+    let cache = tempfile::tempdir().unwrap();
+
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/index.json"))
+            .respond_with(status_code(200).body(remote_index("local-dev.yaml"))),
+    );
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/local-dev.yaml"))
+            .respond_with(status_code(200).body("nats:\n  url: nats://evil:4222\n")),
+    );
+    let config = RemoteIndexConfig {
+        index_url: server.url_str("/index.json"),
+        cache_dir: cache.path().to_path_buf(),
+        offline: false,
+    };
+
+    let err = resolve_remote("lib://remote/preview-local-dev@0.0.1", &config).unwrap_err();
+    assert!(format!("{}", err).contains("digest mismatch"));
+    assert!(!cache
+        .path()
+        .join("bundles/preview-local-dev/0.0.1.yaml")
+        .exists());
+}
+
+#[test]
+fn libindex_remote_offline_without_cache_errors() {
+    use bootstrapper_demonctl::libindex::{resolve_remote, RemoteIndexConfig};
+    let cache = tempfile::tempdir().unwrap();
+    let config = RemoteIndexConfig {
+        index_url: "https://bundles.example.com/index.json".into(),
+        cache_dir: cache.path().to_path_buf(),
+        offline: true,
+    };
+
+    let err = resolve_remote("lib://remote/preview-local-dev@0.0.1", &config).unwrap_err();
+    assert!(format!("{:#}", err).contains("no cached library index"));
+
+    let err = resolve_remote("lib://remote/../etc@1", &config).unwrap_err();
+    assert!(format!("{}", err).contains("invalid bundle uri"));
+}
@@ -0,0 +1,114 @@
+use bootstrapper_demonctl::seed::{render_profile, SeedProfile};
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+fn repo_path(rel: &str) -> PathBuf {
+    let candidates = [
+        Path::new(rel).to_path_buf(),
+        Path::new("..").join(rel),
+        Path::new("../..").join(rel),
+        Path::new("../../..").join(rel),
+    ];
+    for p in candidates {
+        if p.exists() {
+            return p;
+        }
+    }
+    PathBuf::from(rel)
+}
+
+fn profile(value: serde_json::Value) -> SeedProfile {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn profile_renders_each_instance_with_params_and_idempotency_keys() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    let p = profile(json!({
+        "count": 2,
+        "params": { "runId": "seed-{{index}}", "gateId": "promote" },
+        "events": [
+            {
+                "idempotencyKey": "{{runId}}:approval:{{gateId}}",
+                "event": {
+                    "event": "approval.requested:v1",
+                    "ts": "{{now}}",
+                    "tenantId": "acme",
+                    "runId": "{{runId}}",
+                    "gateId": "{{gateId}}"
+                }
+            },
+            {
+                "idempotencyKey": "{{runId}}:expiry",
+                "event": {
+                    "event": "timer.scheduled:v1",
+                    "runId": "{{runId}}",
+                    "ritualId": "{{ritualId}}",
+                    "scheduledFor": "{{now+30s}}"
+                }
+            }
+        ]
+    }));
+
+    let events = render_profile("approvals", &p, "echo-ritual", now).unwrap();
+    assert_eq!(events.len(), 4);
+
+    assert_eq!(
+        events[0].subject,
+        "demon.ritual.v1.acme.echo-ritual.seed-1.events"
+    );
+    assert_eq!(events[0].idempotency_key, "seed-1:approval:promote");
+    assert_eq!(events[0].body["ts"], now.to_rfc3339());
+
+    let timer = &events[3];
+    assert_eq!(timer.idempotency_key, "seed-2:expiry");
+    assert_eq!(
+        timer.subject,
+        "demon.ritual.v1.default.echo-ritual.seed-2.events"
+    );
+    assert_eq!(
+        timer.body["scheduledFor"],
+        (now + chrono::Duration::seconds(30)).to_rfc3339()
+    );
+}
+
+#[test]
+fn unknown_placeholder_names_profile_and_event() {
+    let p = profile(json!({
+        "events": [{
+            "idempotencyKey": "{{runId}}:started",
+            "event": { "event": "ritual.started:v1", "runId": "{{runId}}" }
+        }]
+    }));
+
+    let err = render_profile("broken", &p, "preview", Utc::now()).unwrap_err();
+    let msg = format!("{:#}", err);
+    assert!(msg.contains("seed profile 'broken': events[0]"), "{msg}");
+    assert!(msg.contains("unknown placeholder '{{runId}}'"), "{msg}");
+}
+
+#[test]
+fn event_without_run_id_is_rejected() {
+    let p = profile(json!({
+        "events": [{ "idempotencyKey": "k", "event": { "event": "ritual.started:v1", "runId": "" } }]
+    }));
+
+    let err = render_profile("empty", &p, "preview", Utc::now()).unwrap_err();
+    assert!(format!("{:#}", err).contains("event must set runId"));
+}
+
+#[test]
+fn integration_bundle_declares_valid_profiles() {
+    let b =
+        bootstrapper_demonctl::bundle::load_bundle(&repo_path("examples/bundles/integration.yaml"))
+            .unwrap();
+    let active = b.seed.active_profiles.clone().unwrap();
+    assert!(active.contains(&"preview-min".to_string()));
+
+    for name in active.iter().filter(|n| n.as_str() != "preview-min") {
+        let p = &b.seed.profiles[name];
+        let events = render_profile(name, p, "echo-ritual", Utc::now()).unwrap();
+        assert_eq!(events.len(), p.count as usize * p.events.len());
+    }
+}
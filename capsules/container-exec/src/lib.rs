@@ -14,12 +14,18 @@ use std::io::{self, Read};
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Component, Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 use tempfile::TempDir;
 use thiserror::Error;
 use wait_timeout::ChildExt;
 
+mod warm_pool;
+
+pub use warm_pool::{drain_warm_pool, WarmPoolSettings, WARM_POOL_LABEL};
+
 type Envelope = ResultEnvelope<JsonValue>;
 
 /// Configuration for executing a containerized capsule invocation.
@@ -41,6 +47,212 @@ pub struct ContainerExecConfig {
     pub app_pack_dir: Option<PathBuf>,
     #[serde(default)]
     pub artifacts_dir: Option<PathBuf>,
+    /// Limits declared by the capsule; unset limits fall back to the
+    /// `DEMON_CONTAINER_*` environment defaults
+    #[serde(default)]
+    pub resources: ResourceLimits,
+    #[serde(default)]
+    pub network: NetworkMode,
+    /// GPUs passed through with `--gpus`; must be allowed by
+    /// `DEMON_CONTAINER_GPU_ALLOWLIST`
+    #[serde(default)]
+    pub gpus: Option<GpuRequest>,
+    /// Host devices passed through with `--device`, as
+    /// `host[:container[:perms]]`; must be allowed by
+    /// `DEMON_CONTAINER_DEVICE_ALLOWLIST`
+    #[serde(default)]
+    pub devices: Vec<String>,
+    /// Writable scratch space mounted at `/workspace/.scratch` for the run
+    #[serde(default)]
+    pub scratch: Option<ScratchVolume>,
+    /// How the capsule hands its result envelope back
+    #[serde(default)]
+    pub result_transport: ResultTransport,
+}
+
+/// Environment variable telling the capsule which result transport is in use
+pub const RESULT_TRANSPORT_ENV: &str = "DEMON_RESULT_TRANSPORT";
+
+/// How the result envelope travels from the capsule to container-exec
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResultTransport {
+    /// Written to `envelopePath` through a host bind mount
+    #[default]
+    File,
+    /// Printed to stdout between [`STDOUT_ENVELOPE_BEGIN`] and
+    /// [`STDOUT_ENVELOPE_END`], or as the last JSON document; no envelope
+    /// mount is created
+    Stdout,
+}
+
+/// Container path of the per-invocation scratch volume
+pub const SCRATCH_MOUNT: &str = "/workspace/.scratch";
+
+/// Size-limited scratch space that is discarded once the container exits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScratchVolume {
+    /// Maximum size with an optional b, k, m or g suffix (e.g. `512m`)
+    pub size: String,
+    #[serde(default)]
+    pub backing: ScratchBacking,
+}
+
+/// Storage behind the scratch volume
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScratchBacking {
+    /// In-memory tmpfs; the size limit is enforced by the kernel
+    #[default]
+    Tmpfs,
+    /// Host directory under the run's temp dir; usage is checked after the run
+    Host,
+}
+
+impl ScratchVolume {
+    /// Size limit in bytes, or `None` if `size` is malformed
+    pub fn size_bytes(&self) -> Option<u64> {
+        parse_size(&self.size)
+    }
+}
+
+/// Parse a Docker-style size such as `256m` into bytes
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.chars().last()?.to_ascii_lowercase() {
+        'b' => (&value[..value.len() - 1], 1),
+        'k' => (&value[..value.len() - 1], 1 << 10),
+        'm' => (&value[..value.len() - 1], 1 << 20),
+        'g' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// Per-capsule container resource limits
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    #[serde(default)]
+    pub cpus: Option<f64>,
+    #[serde(default)]
+    pub memory: Option<String>,
+    #[serde(default)]
+    pub pids_limit: Option<u32>,
+}
+
+/// Network the capsule container is attached to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkMode {
+    /// `--network none`
+    #[default]
+    None,
+    /// The runtime's default bridge network, for outbound access
+    Egress,
+}
+
+impl NetworkMode {
+    fn as_arg(self) -> &'static str {
+        match self {
+            NetworkMode::None => "none",
+            NetworkMode::Egress => "bridge",
+        }
+    }
+}
+
+/// GPUs requested by a capsule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "GpuRequestRepr", into = "GpuRequestRepr")]
+pub enum GpuRequest {
+    /// Every GPU on the operator allowlist (`"all"`)
+    All,
+    /// Any `n` GPUs from the operator allowlist
+    Count(u32),
+    /// Specific GPU indexes or UUIDs
+    Devices(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum GpuRequestRepr {
+    Count(u32),
+    Devices(Vec<String>),
+    Keyword(String),
+}
+
+impl TryFrom<GpuRequestRepr> for GpuRequest {
+    type Error = String;
+
+    fn try_from(repr: GpuRequestRepr) -> Result<Self, Self::Error> {
+        match repr {
+            GpuRequestRepr::Count(count) => Ok(GpuRequest::Count(count)),
+            GpuRequestRepr::Devices(ids) => Ok(GpuRequest::Devices(ids)),
+            GpuRequestRepr::Keyword(keyword) if keyword == "all" => Ok(GpuRequest::All),
+            GpuRequestRepr::Keyword(other) => Err(format!(
+                "invalid gpus value '{}': expected \"all\", a count or a list of device ids",
+                other
+            )),
+        }
+    }
+}
+
+impl From<GpuRequest> for GpuRequestRepr {
+    fn from(request: GpuRequest) -> Self {
+        match request {
+            GpuRequest::All => GpuRequestRepr::Keyword("all".to_string()),
+            GpuRequest::Count(count) => GpuRequestRepr::Count(count),
+            GpuRequest::Devices(ids) => GpuRequestRepr::Devices(ids),
+        }
+    }
+}
+
+/// GPUs and host devices the operator lets capsules request, read from the
+/// comma-separated `DEMON_CONTAINER_GPU_ALLOWLIST` and
+/// `DEMON_CONTAINER_DEVICE_ALLOWLIST`. Nothing is allowed when unset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceAllowlist {
+    pub gpus: Vec<String>,
+    pub devices: Vec<String>,
+}
+
+impl DeviceAllowlist {
+    pub fn from_env() -> Self {
+        let list = |name: &str| -> Vec<String> {
+            env::var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+        Self {
+            gpus: list("DEMON_CONTAINER_GPU_ALLOWLIST"),
+            devices: list("DEMON_CONTAINER_DEVICE_ALLOWLIST"),
+        }
+    }
+}
+
+/// GPUs and devices actually passed through to the container
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DeviceGrant {
+    pub gpus: Vec<String>,
+    pub devices: Vec<String>,
+}
+
+impl DeviceGrant {
+    pub fn is_empty(&self) -> bool {
+        self.gpus.is_empty() && self.devices.is_empty()
+    }
+}
+
+/// Host path of a `host[:container[:perms]]` device mapping
+fn device_host_path(spec: &str) -> &str {
+    spec.split(':').next().unwrap_or(spec)
 }
 
 impl ContainerExecConfig {
@@ -114,8 +326,149 @@ impl ContainerExecConfig {
             }
         }
 
+        if let Some(cpus) = self.resources.cpus {
+            if cpus.is_nan() || cpus <= 0.0 {
+                anyhow::bail!("CPU limit must be greater than 0");
+            }
+        }
+        if let Some(memory) = &self.resources.memory {
+            let digits = memory.trim_end_matches(['b', 'k', 'm', 'g', 'B', 'K', 'M', 'G']);
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                anyhow::bail!(
+                    "Memory limit '{}' must be a number with an optional b, k, m or g suffix",
+                    memory
+                );
+            }
+        }
+        if self.resources.pids_limit == Some(0) {
+            anyhow::bail!("PIDs limit must be greater than 0");
+        }
+
+        match &self.gpus {
+            Some(GpuRequest::Count(0)) => {
+                anyhow::bail!("GPU count must be greater than 0");
+            }
+            Some(GpuRequest::Devices(ids)) => {
+                if ids.is_empty() {
+                    anyhow::bail!("GPU device list cannot be empty");
+                }
+                if let Some(id) = ids
+                    .iter()
+                    .find(|id| id.trim().is_empty() || id.contains([',', '"']))
+                {
+                    anyhow::bail!("Invalid GPU device id '{}'", id);
+                }
+            }
+            _ => {}
+        }
+        for spec in &self.devices {
+            let parts: Vec<&str> = spec.split(':').collect();
+            let valid = parts.len() <= 3
+                && parts[0].starts_with("/dev/")
+                && parts.get(1).is_none_or(|target| target.starts_with('/'))
+                && parts.get(2).is_none_or(|perms| {
+                    !perms.is_empty() && perms.chars().all(|c| matches!(c, 'r' | 'w' | 'm'))
+                });
+            if !valid {
+                anyhow::bail!(
+                    "Device '{}' must be a /dev path with an optional :container[:perms] mapping",
+                    spec
+                );
+            }
+        }
+
+        if let Some(scratch) = &self.scratch {
+            match scratch.size_bytes() {
+                Some(0) => anyhow::bail!("Scratch volume size must be greater than 0"),
+                Some(_) => {}
+                None => anyhow::bail!(
+                    "Scratch volume size '{}' must be a number with an optional b, k, m or g suffix",
+                    scratch.size
+                ),
+            }
+        }
+
         Ok(())
     }
+
+    /// Resolve the requested GPUs and devices against the operator allowlist.
+    ///
+    /// `"all"` and counts are filled from the allowlisted GPUs; anything asked
+    /// for by name must be on the allowlist or the request is rejected.
+    pub fn grant_devices(&self, allowlist: &DeviceAllowlist) -> Result<DeviceGrant> {
+        let gpus = match &self.gpus {
+            None => Vec::new(),
+            Some(GpuRequest::All) => {
+                if allowlist.gpus.is_empty() {
+                    anyhow::bail!(
+                        "GPU access requested but no GPUs are allowed (DEMON_CONTAINER_GPU_ALLOWLIST is empty)"
+                    );
+                }
+                allowlist.gpus.clone()
+            }
+            Some(GpuRequest::Count(count)) => {
+                let count = *count as usize;
+                if count > allowlist.gpus.len() {
+                    anyhow::bail!(
+                        "{} GPU(s) requested but only {} allowed by DEMON_CONTAINER_GPU_ALLOWLIST",
+                        count,
+                        allowlist.gpus.len()
+                    );
+                }
+                allowlist.gpus[..count].to_vec()
+            }
+            Some(GpuRequest::Devices(ids)) => {
+                if let Some(id) = ids.iter().find(|id| !allowlist.gpus.contains(id)) {
+                    anyhow::bail!(
+                        "GPU '{}' is not allowed by DEMON_CONTAINER_GPU_ALLOWLIST",
+                        id
+                    );
+                }
+                ids.clone()
+            }
+        };
+
+        if let Some(spec) = self.devices.iter().find(|spec| {
+            !allowlist
+                .devices
+                .iter()
+                .any(|allowed| allowed == device_host_path(spec))
+        }) {
+            anyhow::bail!(
+                "Device '{}' is not allowed by DEMON_CONTAINER_DEVICE_ALLOWLIST",
+                device_host_path(spec)
+            );
+        }
+
+        Ok(DeviceGrant {
+            gpus,
+            devices: self.devices.clone(),
+        })
+    }
+}
+
+/// Interval at which a running container is checked for cancellation.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Shared flag that asks an in-flight `execute_with_cancel` to stop.
+///
+/// Cancelling kills the container runtime process and force-removes the
+/// container recorded in its cidfile, the same cleanup used on timeout.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
 }
 
 /// Result of running the container execution capsule.
@@ -132,7 +485,13 @@ pub struct ContainerExecResult {
 /// and validates the emitted envelope. If any step fails, a canonical error envelope
 /// is produced with diagnostic context.
 pub fn execute(config: &ContainerExecConfig) -> Envelope {
-    match execute_internal(config) {
+    execute_with_cancel(config, &CancelToken::new())
+}
+
+/// Same as [`execute`], but stops early once `cancel` is triggered and returns
+/// a `CONTAINER_EXEC_CANCELED` error envelope.
+pub fn execute_with_cancel(config: &ContainerExecConfig, cancel: &CancelToken) -> Envelope {
+    match execute_internal(config, cancel) {
         Ok(mut result) => {
             annotate_success(&mut result, config);
             result.envelope
@@ -141,15 +500,45 @@ pub fn execute(config: &ContainerExecConfig) -> Envelope {
     }
 }
 
-fn execute_internal(config: &ContainerExecConfig) -> Result<ContainerExecResult, ExecError> {
+fn execute_internal(
+    config: &ContainerExecConfig,
+    cancel: &CancelToken,
+) -> Result<ContainerExecResult, ExecError> {
     config.validate().map_err(|err| ExecError::InvalidConfig {
         message: err.to_string(),
     })?;
+    let grant = config
+        .grant_devices(&DeviceAllowlist::from_env())
+        .map_err(|err| ExecError::InvalidConfig {
+            message: err.to_string(),
+        })?;
+
+    let mut result = match detect_runtime_kind() {
+        RuntimeKind::Stub => execute_stub(config)?,
+        RuntimeKind::Binary(runtime_bin) => {
+            execute_with_runtime(config, &grant, runtime_bin, cancel)?
+        }
+    };
 
-    match detect_runtime_kind() {
-        RuntimeKind::Stub => execute_stub(config),
-        RuntimeKind::Binary(runtime_bin) => execute_with_runtime(config, runtime_bin),
+    if config.gpus.is_some() || !config.devices.is_empty() {
+        result.envelope.diagnostics.push(
+            Diagnostic::info(format!(
+                "device passthrough granted {} GPU(s) and {} device(s)",
+                grant.gpus.len(),
+                grant.devices.len()
+            ))
+            .with_source("container-exec")
+            .with_context(serde_json::json!({
+                "requested": {
+                    "gpus": config.gpus,
+                    "devices": config.devices,
+                },
+                "granted": grant,
+            })),
+        );
     }
+
+    Ok(result)
 }
 
 fn execute_stub(config: &ContainerExecConfig) -> Result<ContainerExecResult, ExecError> {
@@ -200,7 +589,9 @@ fn execute_stub(config: &ContainerExecConfig) -> Result<ContainerExecResult, Exe
 
 fn execute_with_runtime(
     config: &ContainerExecConfig,
+    grant: &DeviceGrant,
     runtime_bin: String,
+    cancel: &CancelToken,
 ) -> Result<ContainerExecResult, ExecError> {
     if let Some(artifacts_dir) = &config.artifacts_dir {
         fs::create_dir_all(artifacts_dir).map_err(|err| ExecError::Io {
@@ -224,6 +615,10 @@ fn execute_with_runtime(
         }
     }
 
+    if let Some(result) = warm_pool::execute(config, grant, &runtime_bin, cancel) {
+        return result;
+    }
+
     // Ensure the workspace mount point and, if possible, the container-visible envelope
     // path exist under the App Pack directory. This helps Docker file-level binds succeed
     // even when the parent `/workspace` is bound read-only.
@@ -251,12 +646,23 @@ fn execute_with_runtime(
             )?;
         }
 
+        if config.scratch.is_some() {
+            let scratch_mp = app_pack_dir.join(".scratch");
+            fs::create_dir_all(&scratch_mp).map_err(|err| ExecError::Io {
+                message: format!(
+                    "Failed to ensure App Pack scratch mount point {}: {}",
+                    scratch_mp.display(),
+                    err
+                ),
+            })?;
+        }
+
         // If the envelope path is under /workspace/.artifacts, create a placeholder under
         // the App Pack so the container-side target exists before mount wiring.
         if let Some(rel) = config
             .envelope_path
             .strip_prefix("/workspace/.artifacts/")
-            .filter(|s| !s.is_empty())
+            .filter(|s| !s.is_empty() && config.result_transport == ResultTransport::File)
         {
             let app_side_path = artifacts_mp.join(rel);
             if let Some(parent) = app_side_path.parent() {
@@ -294,14 +700,172 @@ fn execute_with_runtime(
         message: format!("Failed to create temp directory: {}", err),
     })?;
 
+    // Host-backed scratch lives in the run's temp dir so it is wiped with it
+    let scratch_dir = match &config.scratch {
+        Some(scratch) if scratch.backing == ScratchBacking::Host => {
+            let dir = temp_dir.path().join("scratch");
+            fs::create_dir_all(&dir).map_err(|err| ExecError::Io {
+                message: format!(
+                    "Failed to create scratch directory {}: {}",
+                    dir.display(),
+                    err
+                ),
+            })?;
+            #[cfg(unix)]
+            fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).map_err(|err| {
+                ExecError::Io {
+                    message: format!(
+                        "Failed to set permissions on scratch directory {}: {}",
+                        dir.display(),
+                        err
+                    ),
+                }
+            })?;
+            Some(dir)
+        }
+        _ => None,
+    };
+
+    let mount = match config.result_transport {
+        ResultTransport::File => Some(prepare_envelope_mount(config, temp_dir.path())?),
+        ResultTransport::Stdout => None,
+    };
+
+    let cidfile_path = temp_dir.path().join("container.cid");
+
+    let mut command = Command::new(&runtime_bin);
+    configure_command(
+        &mut command,
+        config,
+        grant,
+        mount.as_ref(),
+        scratch_dir.as_deref(),
+        Some(&cidfile_path),
+    )?;
+    let runtime_cmdline = command_line_string(&command);
+    let timeout = resolve_timeout(config)?;
+
+    let start = Instant::now();
+    let run_result = run_container_command(
+        runtime_bin.clone(),
+        command,
+        timeout,
+        Some(cidfile_path.clone()),
+        cancel,
+    )?;
+    let duration = start.elapsed();
+    let scratch_used = scratch_dir.as_deref().map(dir_size);
+
+    collect_result(
+        config,
+        mount.as_ref(),
+        run_result,
+        duration,
+        scratch_used,
+        &runtime_cmdline,
+    )
+}
+
+/// Read, validate and annotate the envelope of a finished container run
+fn collect_result(
+    config: &ContainerExecConfig,
+    mount: Option<&EnvelopeMount>,
+    run: CommandRun,
+    duration: Duration,
+    scratch_used: Option<u64>,
+    runtime_cmdline: &str,
+) -> Result<ContainerExecResult, ExecError> {
+    let host_target = mount
+        .map(|mount| mount.container_root.clone())
+        .unwrap_or_else(|| "stdout".to_string());
+    let CommandRun { status, mut logs } = run;
+
+    let (envelope, envelope_source) = match mount {
+        Some(mount) => {
+            let envelope_bytes =
+                fs::read(&mount.host_envelope_path).map_err(|err| ExecError::EnvelopeMissing {
+                    path: mount.host_envelope_path.clone(),
+                    status,
+                    logs: logs.clone(),
+                    source: err,
+                })?;
+            let envelope: Envelope = serde_json::from_slice(&envelope_bytes).map_err(|err| {
+                ExecError::EnvelopeInvalid {
+                    path: mount.host_envelope_path.clone(),
+                    status,
+                    logs: logs.clone(),
+                    source: anyhow!(err),
+                }
+            })?;
+            (envelope, mount.host_envelope_path.clone())
+        }
+        None => {
+            let source = PathBuf::from("stdout");
+            let envelope = take_stdout_envelope(&mut logs).map_err(|err| match err {
+                StdoutEnvelopeError::Missing => ExecError::EnvelopeMissing {
+                    path: source.clone(),
+                    status,
+                    logs: logs.clone(),
+                    source: io::Error::new(
+                        io::ErrorKind::NotFound,
+                        "no result envelope found on stdout",
+                    ),
+                },
+                StdoutEnvelopeError::Invalid(err) => ExecError::EnvelopeInvalid {
+                    path: source.clone(),
+                    status,
+                    logs: logs.clone(),
+                    source: anyhow!(err),
+                },
+            })?;
+            (envelope, source)
+        }
+    };
+
+    if let Err(err) = envelope.validate() {
+        return Err(ExecError::EnvelopeInvalid {
+            path: envelope_source,
+            status,
+            logs,
+            source: err,
+        });
+    }
+
+    Ok(ContainerExecResult {
+        envelope,
+        duration_ms: duration.as_secs_f64() * 1000.0,
+        exit_status: exit_code(&status),
+    }
+    .tap(|result| {
+        annotate_logs(&mut result.envelope, &logs, &host_target, config);
+        if let Some(scratch) = &config.scratch {
+            annotate_scratch(&mut result.envelope, scratch, scratch_used);
+        }
+        if debug_enabled() {
+            if let Some(mount) = mount {
+                annotate_host_postrun(
+                    &mut result.envelope,
+                    &mount.host_envelope_path,
+                    runtime_cmdline,
+                );
+            }
+        }
+    }))
+}
+
+/// Create the host side of the envelope bind mount, including a writable
+/// placeholder for the envelope file itself.
+fn prepare_envelope_mount(
+    config: &ContainerExecConfig,
+    temp_root: &Path,
+) -> Result<EnvelopeMount, ExecError> {
     let artifacts_dir = config.artifacts_dir.as_ref();
     let mount = EnvelopeMount::prepare(
         &config.envelope_path,
-        temp_dir.path(),
+        temp_root,
         artifacts_dir.map(Path::new),
     )?;
 
-    let host_target = mount.container_root.clone();
     if let Some(root) = mount.host_root() {
         fs::create_dir_all(root).map_err(|err| ExecError::Io {
             message: format!(
@@ -350,64 +914,63 @@ fn execute_with_runtime(
 
     ensure_envelope_placeholder(&mount.host_envelope_path)?;
 
-    let cidfile_path = temp_dir.path().join("container.cid");
-
-    let mut command = Command::new(&runtime_bin);
-    configure_command(&mut command, config, &mount, Some(&cidfile_path))?;
-    let runtime_cmdline = command_line_string(&command);
-    let timeout = resolve_timeout(config)?;
-
-    let start = Instant::now();
-    let run_result = run_container_command(
-        runtime_bin.clone(),
-        command,
-        timeout,
-        Some(cidfile_path.clone()),
-    )?;
-    let duration = start.elapsed();
-
-    let CommandRun { status, logs } = run_result;
+    Ok(mount)
+}
 
-    let envelope_bytes =
-        fs::read(&mount.host_envelope_path).map_err(|err| ExecError::EnvelopeMissing {
-            path: mount.host_envelope_path.clone(),
-            status,
-            logs: logs.clone(),
-            source: err,
-        })?;
+/// Opening line of an envelope written to stdout
+pub const STDOUT_ENVELOPE_BEGIN: &str = "---DEMON-ENVELOPE-BEGIN---";
+/// Closing line of an envelope written to stdout
+pub const STDOUT_ENVELOPE_END: &str = "---DEMON-ENVELOPE-END---";
 
-    let envelope: Envelope =
-        serde_json::from_slice(&envelope_bytes).map_err(|err| ExecError::EnvelopeInvalid {
-            path: mount.host_envelope_path.clone(),
-            status,
-            logs: logs.clone(),
-            source: anyhow!(err),
-        })?;
+#[derive(Debug)]
+enum StdoutEnvelopeError {
+    Missing,
+    Invalid(serde_json::Error),
+}
 
-    if let Err(err) = envelope.validate() {
-        return Err(ExecError::EnvelopeInvalid {
-            path: mount.host_envelope_path.clone(),
-            status,
-            logs,
-            source: err,
-        });
+/// Extract the result envelope from captured stdout and remove it from the
+/// logs. The envelope is taken from between the sentinel lines when present,
+/// otherwise from the last JSON document that runs to the end of the output.
+fn take_stdout_envelope(logs: &mut CommandLogs) -> Result<Envelope, StdoutEnvelopeError> {
+    let stdout = &logs.stdout;
+
+    if let Some(begin) = stdout.rfind(STDOUT_ENVELOPE_BEGIN) {
+        let body_start = begin + STDOUT_ENVELOPE_BEGIN.len();
+        let body_len = stdout[body_start..]
+            .find(STDOUT_ENVELOPE_END)
+            .ok_or(StdoutEnvelopeError::Missing)?;
+        let envelope = serde_json::from_str(&stdout[body_start..body_start + body_len])
+            .map_err(StdoutEnvelopeError::Invalid)?;
+        let end = body_start + body_len + STDOUT_ENVELOPE_END.len();
+        logs.stdout = format!("{}{}", &stdout[..begin], &stdout[end..]);
+        return Ok(envelope);
+    }
+
+    let mut last_error = None;
+    let line_starts = std::iter::once(0)
+        .chain(stdout.match_indices('\n').map(|(idx, _)| idx + 1))
+        .filter(|&idx| stdout[idx..].starts_with('{'))
+        .collect::<Vec<_>>();
+    for start in line_starts.into_iter().rev() {
+        let mut stream =
+            serde_json::Deserializer::from_str(&stdout[start..]).into_iter::<JsonValue>();
+        let value = match stream.next() {
+            Some(Ok(value)) => value,
+            Some(Err(err)) => {
+                last_error.get_or_insert(err);
+                continue;
+            }
+            None => continue,
+        };
+        if !stdout[start + stream.byte_offset()..].trim().is_empty() {
+            continue;
+        }
+        let envelope = serde_json::from_value(value).map_err(StdoutEnvelopeError::Invalid)?;
+        logs.stdout = stdout[..start].to_string();
+        return Ok(envelope);
     }
 
-    Ok(ContainerExecResult {
-        envelope,
-        duration_ms: duration.as_secs_f64() * 1000.0,
-        exit_status: exit_code(&status),
-    }
-    .tap(|result| {
-        annotate_logs(&mut result.envelope, &logs, &host_target, config);
-        if debug_enabled() {
-            annotate_host_postrun(
-                &mut result.envelope,
-                &mount.host_envelope_path,
-                &runtime_cmdline,
-            );
-        }
-    }))
+    Err(last_error.map_or(StdoutEnvelopeError::Missing, StdoutEnvelopeError::Invalid))
 }
 
 fn annotate_logs(
@@ -480,6 +1043,7 @@ fn annotate_success(result: &mut ContainerExecResult, config: &ContainerExecConf
             span_id: None,
             parent_span_id: None,
             chain: vec![],
+            signature: None,
         });
 
     // Enrich with tool.gitSha if available
@@ -506,13 +1070,15 @@ fn annotate_success(result: &mut ContainerExecResult, config: &ContainerExecConf
 fn configure_command(
     command: &mut Command,
     config: &ContainerExecConfig,
-    mount: &EnvelopeMount,
+    grant: &DeviceGrant,
+    mount: Option<&EnvelopeMount>,
+    scratch_dir: Option<&Path>,
     cidfile: Option<&Path>,
 ) -> Result<(), ExecError> {
     command.arg("run");
     command.arg("--rm");
     command.arg("--pull").arg("never");
-    command.arg("--network").arg("none");
+    command.arg("--network").arg(config.network.as_arg());
     command.arg("--read-only");
     command.arg("--security-opt").arg("no-new-privileges");
     command.arg("--user").arg(container_user());
@@ -560,7 +1126,7 @@ fn configure_command(
         command.arg("--cidfile").arg(cidfile.display().to_string());
     }
 
-    if let Some(host_root) = mount.host_root() {
+    if let Some((host_root, mount)) = mount.and_then(|m| m.host_root().map(|root| (root, m))) {
         command.arg("--mount").arg(format!(
             "type=bind,source={},target={},readonly=false",
             host_root.display(),
@@ -568,6 +1134,24 @@ fn configure_command(
         ));
     }
 
+    if let Some(scratch) = &config.scratch {
+        match scratch_dir {
+            Some(dir) => {
+                command.arg("--mount").arg(format!(
+                    "type=bind,source={},target={},readonly=false",
+                    dir.display(),
+                    SCRATCH_MOUNT
+                ));
+            }
+            None => {
+                let size = scratch.size_bytes().unwrap_or_default();
+                command
+                    .arg("--tmpfs")
+                    .arg(format!("{}:rw,nosuid,nodev,size={}", SCRATCH_MOUNT, size));
+            }
+        }
+    }
+
     let mut workdir_set = false;
     if let Some(dir) = &config.working_dir {
         command.arg("--workdir").arg(dir);
@@ -578,18 +1162,25 @@ fn configure_command(
         command.arg("--workdir").arg("/workspace");
     }
 
-    // Additionally, bind the host envelope file directly to the container target to
-    // guarantee writability regardless of parent mount semantics or UID.
-    command.arg("--mount").arg(format!(
-        "type=bind,source={},target={},readonly=false",
-        mount.host_envelope_path.display(),
-        config.envelope_path
-    ));
+    if let Some(mount) = mount {
+        // Additionally, bind the host envelope file directly to the container target to
+        // guarantee writability regardless of parent mount semantics or UID.
+        command.arg("--mount").arg(format!(
+            "type=bind,source={},target={},readonly=false",
+            mount.host_envelope_path.display(),
+            config.envelope_path
+        ));
 
-    // Ensure capsule receives the enforced envelope path regardless of manifest env.
-    command
-        .arg("--env")
-        .arg(format!("ENVELOPE_PATH={}", config.envelope_path));
+        // Ensure capsule receives the enforced envelope path regardless of manifest env.
+        command
+            .arg("--env")
+            .arg(format!("ENVELOPE_PATH={}", config.envelope_path));
+    } else {
+        // Tell the capsule to print its envelope instead of writing a file
+        command
+            .arg("--env")
+            .arg(format!("{}=stdout", RESULT_TRANSPORT_ENV));
+    }
 
     // Pass through DEMON_DEBUG to the container if enabled
     if let Ok(val) = env::var("DEMON_DEBUG") {
@@ -606,29 +1197,26 @@ fn configure_command(
     // These must appear BEFORE the image per `docker run` semantics; any
     // options after the image are treated as container args and ignored by
     // the Docker CLI. Place them here before setting entrypoint/image.
-    if let Ok(cpus) = env::var("DEMON_CONTAINER_CPUS") {
-        let cpus = cpus.trim();
-        if !cpus.is_empty() {
-            command.arg("--cpus").arg(cpus);
-        }
+    for (flag, value) in limit_args(config) {
+        command.arg(flag).arg(value);
     }
-    if let Ok(mem) = env::var("DEMON_CONTAINER_MEMORY") {
-        let mem = mem.trim();
-        if !mem.is_empty() {
-            command.arg("--memory").arg(mem);
-        }
+
+    // Device passthrough, already checked against the operator allowlist.
+    // The quotes keep Docker from splitting the device list on commas.
+    if !grant.gpus.is_empty() {
+        command
+            .arg("--gpus")
+            .arg(format!("\"device={}\"", grant.gpus.join(",")));
     }
-    if let Ok(pids) = env::var("DEMON_CONTAINER_PIDS_LIMIT") {
-        let pids = pids.trim();
-        if !pids.is_empty() {
-            command.arg("--pids-limit").arg(pids);
-        }
+    for device in &grant.devices {
+        command.arg("--device").arg(device);
     }
 
     command.arg("--entrypoint").arg("");
     command.arg(&config.image_digest);
 
-    if debug_enabled() {
+    // The debug wrapper prints to stdout, which would corrupt a stdout envelope
+    if debug_enabled() && mount.is_some() {
         let original = shell_join(&config.command);
         let script = format!(
             "set -e; echo '=== DEMON_DEBUG pre-run ==='; echo uid: $(id -u); echo gid: $(id -g); \
@@ -654,6 +1242,38 @@ fn configure_command(
     Ok(())
 }
 
+/// Resource limit flags; limits declared by the capsule take precedence over
+/// the env defaults
+fn limit_args(config: &ContainerExecConfig) -> Vec<(&'static str, String)> {
+    let limits = &config.resources;
+    let limit_args = [
+        (
+            "--cpus",
+            limits.cpus.map(|c| c.to_string()),
+            "DEMON_CONTAINER_CPUS",
+        ),
+        ("--memory", limits.memory.clone(), "DEMON_CONTAINER_MEMORY"),
+        (
+            "--pids-limit",
+            limits.pids_limit.map(|p| p.to_string()),
+            "DEMON_CONTAINER_PIDS_LIMIT",
+        ),
+    ];
+    limit_args
+        .into_iter()
+        .filter_map(|(flag, declared, env_name)| {
+            declared
+                .or_else(|| {
+                    env::var(env_name)
+                        .ok()
+                        .map(|v| v.trim().to_string())
+                        .filter(|v| !v.is_empty())
+                })
+                .map(|value| (flag, value))
+        })
+        .collect()
+}
+
 fn container_user() -> String {
     if let Ok(value) = env::var("DEMON_CONTAINER_USER") {
         if !value.trim().is_empty() {
@@ -673,6 +1293,46 @@ fn container_user() -> String {
     }
 }
 
+/// Record the scratch volume in the envelope; host-backed scratch that grew past
+/// its limit is flagged as a warning
+fn annotate_scratch(envelope: &mut Envelope, scratch: &ScratchVolume, used: Option<u64>) {
+    let limit = scratch.size_bytes().unwrap_or_default();
+    let diagnostic = match used {
+        Some(used) if used > limit => Diagnostic::warning(format!(
+            "scratch volume used {} bytes, over its {} limit",
+            used, scratch.size
+        )),
+        _ => Diagnostic::info(format!("scratch volume ({}) discarded", scratch.size)),
+    };
+    envelope
+        .diagnostics
+        .push(
+            diagnostic
+                .with_source("container-exec")
+                .with_context(serde_json::json!({
+                    "mount": SCRATCH_MOUNT,
+                    "backing": scratch.backing,
+                    "limitBytes": limit,
+                    "usedBytes": used,
+                })),
+        );
+}
+
+/// Total size of the regular files under `dir`, without following symlinks
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
 fn ensure_envelope_placeholder(path: &Path) -> Result<(), ExecError> {
     let file = OpenOptions::new()
         .create(true)
@@ -954,7 +1614,15 @@ fn run_container_command(
     mut command: Command,
     timeout: Option<Duration>,
     cidfile: Option<PathBuf>,
+    cancel: &CancelToken,
 ) -> Result<CommandRun, ExecError> {
+    if cancel.is_canceled() {
+        return Err(ExecError::Canceled {
+            runtime: runtime_bin,
+            logs: CommandLogs::new(String::new(), String::new(), None),
+        });
+    }
+
     let mut child = command.spawn().map_err(|err| ExecError::RuntimeSpawn {
         runtime: runtime_bin.clone(),
         source: err,
@@ -963,14 +1631,29 @@ fn run_container_command(
     let stdout_handle = spawn_pipe_reader(child.stdout.take());
     let stderr_handle = spawn_pipe_reader(child.stderr.take());
 
-    let (status, timed_out) = match timeout {
-        Some(duration) => match child.wait_timeout(duration) {
-            Ok(Some(status)) => (status, None),
+    // Wait in short slices so a timeout or cancellation is noticed promptly
+    let deadline = timeout.map(|duration| Instant::now() + duration);
+    let (status, interrupted) = loop {
+        let slice = match deadline {
+            Some(deadline) => deadline
+                .saturating_duration_since(Instant::now())
+                .min(CANCEL_POLL_INTERVAL),
+            None => CANCEL_POLL_INTERVAL,
+        };
+        match child.wait_timeout(slice) {
+            Ok(Some(status)) => break (status, None),
             Ok(None) => {
+                let reason = if cancel.is_canceled() {
+                    Interruption::Canceled
+                } else if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    Interruption::Timeout(timeout.unwrap_or_default())
+                } else {
+                    continue;
+                };
                 child.kill().map_err(|err| ExecError::Io {
                     message: format!(
-                        "Failed to terminate container runtime '{}' after {:?} timeout: {}",
-                        runtime_bin, duration, err
+                        "Failed to terminate container runtime '{}' after {}: {}",
+                        runtime_bin, reason, err
                     ),
                 })?;
                 let status = child.wait().map_err(|err| ExecError::Io {
@@ -979,7 +1662,7 @@ fn run_container_command(
                         runtime_bin, err
                     ),
                 })?;
-                (status, Some(duration))
+                break (status, Some(reason));
             }
             Err(err) => {
                 return Err(ExecError::Io {
@@ -989,30 +1672,27 @@ fn run_container_command(
                     ),
                 });
             }
-        },
-        None => (
-            child.wait().map_err(|err| ExecError::Io {
-                message: format!(
-                    "Failed to wait on container runtime '{}': {}",
-                    runtime_bin, err
-                ),
-            })?,
-            None,
-        ),
+        }
     };
 
     let stdout = collect_pipe(stdout_handle, "stdout", &runtime_bin)?;
     let stderr = collect_pipe(stderr_handle, "stderr", &runtime_bin)?;
     let logs = CommandLogs::new(stdout, stderr, exit_code(&status));
 
-    if let Some(duration) = timed_out {
+    if let Some(reason) = interrupted {
         if let Some(ref cidfile) = cidfile {
             cleanup_container(&runtime_bin, cidfile);
         }
-        return Err(ExecError::Timeout {
-            runtime: runtime_bin,
-            duration,
-            logs,
+        return Err(match reason {
+            Interruption::Timeout(duration) => ExecError::Timeout {
+                runtime: runtime_bin,
+                duration,
+                logs,
+            },
+            Interruption::Canceled => ExecError::Canceled {
+                runtime: runtime_bin,
+                logs,
+            },
         });
     }
 
@@ -1023,6 +1703,21 @@ fn run_container_command(
     Ok(CommandRun { status, logs })
 }
 
+/// Why a running container was stopped before it exited on its own
+enum Interruption {
+    Timeout(Duration),
+    Canceled,
+}
+
+impl std::fmt::Display for Interruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Interruption::Timeout(duration) => write!(f, "{:?} timeout", duration),
+            Interruption::Canceled => write!(f, "cancellation"),
+        }
+    }
+}
+
 fn cleanup_container(runtime_bin: &str, cidfile: &Path) {
     let contents = match fs::read_to_string(cidfile) {
         Ok(data) => data,
@@ -1252,6 +1947,8 @@ enum ExecError {
         duration: Duration,
         logs: CommandLogs,
     },
+    #[error("Container runtime {runtime} was canceled")]
+    Canceled { runtime: String, logs: CommandLogs },
     #[error("Stub mode error: {message}")]
     Stub { message: String },
 }
@@ -1283,6 +1980,9 @@ impl ExecError {
                 "Container runtime '{}' timed out after {:?}",
                 runtime, duration
             ),
+            ExecError::Canceled { runtime, .. } => {
+                format!("Container runtime '{}' was canceled", runtime)
+            }
             ExecError::Stub { message } => message.clone(),
         }
     }
@@ -1295,6 +1995,7 @@ impl ExecError {
             ExecError::EnvelopeMissing { .. } => "CONTAINER_EXEC_ENVELOPE_MISSING",
             ExecError::EnvelopeInvalid { .. } => "CONTAINER_EXEC_ENVELOPE_INVALID",
             ExecError::Timeout { .. } => "CONTAINER_EXEC_TIMEOUT",
+            ExecError::Canceled { .. } => "CONTAINER_EXEC_CANCELED",
             ExecError::Stub { .. } => "CONTAINER_EXEC_STUB_ERROR",
         }
     }
@@ -1304,7 +2005,7 @@ impl ExecError {
             ExecError::EnvelopeMissing { logs, .. } | ExecError::EnvelopeInvalid { logs, .. } => {
                 Some(logs)
             }
-            ExecError::Timeout { logs, .. } => Some(logs),
+            ExecError::Timeout { logs, .. } | ExecError::Canceled { logs, .. } => Some(logs),
             _ => None,
         }
     }
@@ -1401,6 +2102,17 @@ case "$mode" in
     cat "${TEST_ENVELOPE_SOURCE:?missing}" > "$host"
     exit 0
     ;;
+  stdout)
+    echo "capsule progress"
+    echo "---DEMON-ENVELOPE-BEGIN---"
+    cat "${TEST_ENVELOPE_SOURCE:?missing}"
+    echo
+    echo "---DEMON-ENVELOPE-END---"
+    if [ -f "$host" ] && [ -s "$host" ]; then
+      echo "envelope file unexpectedly written" >&2
+    fi
+    exit 0
+    ;;
   missing)
     rm -f "$host"
     echo "capsule missing envelope" >&2
@@ -1489,6 +2201,12 @@ esac
             capsule_name: None,
             app_pack_dir: None,
             artifacts_dir: None,
+            resources: ResourceLimits::default(),
+            network: NetworkMode::None,
+            gpus: None,
+            devices: Vec::new(),
+            scratch: None,
+            result_transport: ResultTransport::File,
         }
     }
 
@@ -1665,6 +2383,12 @@ esac
             capsule_name: None,
             app_pack_dir: Some(app_pack_dir.clone()),
             artifacts_dir: Some(artifacts_dir.clone()),
+            resources: ResourceLimits::default(),
+            network: NetworkMode::None,
+            gpus: None,
+            devices: Vec::new(),
+            scratch: None,
+            result_transport: ResultTransport::File,
         };
 
         config.validate().unwrap();
@@ -1677,9 +2401,17 @@ esac
         )
         .unwrap();
 
-        let mut command = Command::new("docker");
-        configure_command(&mut command, &config, &mount, None).unwrap();
-
+        let mut command = Command::new("docker");
+        configure_command(
+            &mut command,
+            &config,
+            &DeviceGrant::default(),
+            Some(&mount),
+            None,
+            None,
+        )
+        .unwrap();
+
         let args: Vec<String> = command
             .get_args()
             .map(|arg| arg.to_string_lossy().to_string())
@@ -1729,6 +2461,12 @@ esac
             capsule_name: None,
             app_pack_dir: Some(app_pack_dir),
             artifacts_dir: Some(artifacts_dir),
+            resources: ResourceLimits::default(),
+            network: NetworkMode::None,
+            gpus: None,
+            devices: Vec::new(),
+            scratch: None,
+            result_transport: ResultTransport::File,
         };
 
         let tmp = tempfile::tempdir().unwrap();
@@ -1740,7 +2478,15 @@ esac
         .unwrap();
 
         let mut command = Command::new("docker");
-        configure_command(&mut command, &config, &mount, None).unwrap();
+        configure_command(
+            &mut command,
+            &config,
+            &DeviceGrant::default(),
+            Some(&mount),
+            None,
+            None,
+        )
+        .unwrap();
 
         let args: Vec<String> = command
             .get_args()
@@ -1792,6 +2538,12 @@ esac
             capsule_name: None,
             app_pack_dir: Some(app_pack_dir),
             artifacts_dir: Some(artifacts_dir),
+            resources: ResourceLimits::default(),
+            network: NetworkMode::None,
+            gpus: None,
+            devices: Vec::new(),
+            scratch: None,
+            result_transport: ResultTransport::File,
         };
 
         let tmp = tempfile::tempdir().unwrap();
@@ -1803,7 +2555,15 @@ esac
         .unwrap();
 
         let mut command = Command::new("docker");
-        configure_command(&mut command, &config, &mount, None).unwrap();
+        configure_command(
+            &mut command,
+            &config,
+            &DeviceGrant::default(),
+            Some(&mount),
+            None,
+            None,
+        )
+        .unwrap();
         let args: Vec<String> = command
             .get_args()
             .map(|a| a.to_string_lossy().to_string())
@@ -1838,6 +2598,286 @@ esac
     // NOTE: We intentionally assert flag ORDER in the same test to avoid
     // env-var race conditions across parallel tests.
 
+    #[test]
+    fn declared_resources_and_network_override_defaults() {
+        let mut config = base_config();
+        config.resources = ResourceLimits {
+            cpus: Some(1.5),
+            memory: Some("512m".to_string()),
+            pids_limit: Some(64),
+        };
+        config.network = NetworkMode::Egress;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let mount = EnvelopeMount::prepare(&config.envelope_path, tmp.path(), None).unwrap();
+
+        let mut command = Command::new("docker");
+        configure_command(
+            &mut command,
+            &config,
+            &DeviceGrant::default(),
+            Some(&mount),
+            None,
+            None,
+        )
+        .unwrap();
+        let args: Vec<String> = command
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+
+        let value_after = |flag: &str| {
+            args.iter()
+                .position(|a| a == flag)
+                .and_then(|idx| args.get(idx + 1))
+                .cloned()
+        };
+        assert_eq!(value_after("--cpus").as_deref(), Some("1.5"));
+        assert_eq!(value_after("--memory").as_deref(), Some("512m"));
+        assert_eq!(value_after("--pids-limit").as_deref(), Some("64"));
+        assert_eq!(value_after("--network").as_deref(), Some("bridge"));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_resource_limits() {
+        let mut config = base_config();
+        config.resources.memory = Some("lots".to_string());
+        assert!(config.validate().is_err());
+
+        let mut config = base_config();
+        config.resources.cpus = Some(0.0);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn gpu_request_accepts_all_count_and_device_ids() {
+        let parse = |value: serde_json::Value| serde_json::from_value::<GpuRequest>(value);
+        assert_eq!(parse(serde_json::json!("all")).unwrap(), GpuRequest::All);
+        assert_eq!(parse(serde_json::json!(2)).unwrap(), GpuRequest::Count(2));
+        assert_eq!(
+            parse(serde_json::json!(["0", "GPU-abc"])).unwrap(),
+            GpuRequest::Devices(vec!["0".to_string(), "GPU-abc".to_string()])
+        );
+        assert!(parse(serde_json::json!("some")).is_err());
+        assert_eq!(
+            serde_json::to_value(GpuRequest::All).unwrap(),
+            serde_json::json!("all")
+        );
+    }
+
+    #[test]
+    fn validate_rejects_malformed_device_requests() {
+        let mut config = base_config();
+        config.gpus = Some(GpuRequest::Count(0));
+        assert!(config.validate().is_err());
+
+        let mut config = base_config();
+        config.devices = vec!["/tmp/fake".to_string()];
+        assert!(config.validate().is_err());
+
+        let mut config = base_config();
+        config.devices = vec!["/dev/fuse:/dev/fuse:rwx".to_string()];
+        assert!(config.validate().is_err());
+
+        let mut config = base_config();
+        config.devices = vec!["/dev/fuse:/dev/fuse:rwm".to_string()];
+        config.gpus = Some(GpuRequest::Devices(vec!["0".to_string()]));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn grant_devices_enforces_allowlist() {
+        let allowlist = DeviceAllowlist {
+            gpus: vec!["0".to_string(), "1".to_string()],
+            devices: vec!["/dev/fuse".to_string()],
+        };
+
+        let mut config = base_config();
+        assert!(config.grant_devices(&allowlist).unwrap().is_empty());
+
+        config.gpus = Some(GpuRequest::All);
+        config.devices = vec!["/dev/fuse:/dev/fuse:r".to_string()];
+        let grant = config.grant_devices(&allowlist).unwrap();
+        assert_eq!(grant.gpus, vec!["0", "1"]);
+        assert_eq!(grant.devices, vec!["/dev/fuse:/dev/fuse:r"]);
+
+        config.gpus = Some(GpuRequest::Count(1));
+        assert_eq!(config.grant_devices(&allowlist).unwrap().gpus, vec!["0"]);
+
+        config.gpus = Some(GpuRequest::Count(3));
+        assert!(config.grant_devices(&allowlist).is_err());
+
+        config.gpus = Some(GpuRequest::Devices(vec!["2".to_string()]));
+        let err = config.grant_devices(&allowlist).unwrap_err();
+        assert!(err.to_string().contains("GPU '2' is not allowed"));
+
+        config.gpus = None;
+        config.devices = vec!["/dev/kvm".to_string()];
+        let err = config.grant_devices(&allowlist).unwrap_err();
+        assert!(err.to_string().contains("Device '/dev/kvm' is not allowed"));
+
+        config.devices.clear();
+        config.gpus = Some(GpuRequest::All);
+        assert!(config.grant_devices(&DeviceAllowlist::default()).is_err());
+    }
+
+    #[test]
+    fn configure_command_passes_granted_devices_before_image() {
+        let config = base_config();
+        let grant = DeviceGrant {
+            gpus: vec!["0".to_string(), "1".to_string()],
+            devices: vec!["/dev/fuse".to_string()],
+        };
+
+        let tmp = tempfile::tempdir().unwrap();
+        let mount = EnvelopeMount::prepare(&config.envelope_path, tmp.path(), None).unwrap();
+
+        let mut command = Command::new("docker");
+        configure_command(&mut command, &config, &grant, Some(&mount), None, None).unwrap();
+        let args: Vec<String> = command
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+
+        let image_idx = args.iter().position(|a| a == &config.image_digest).unwrap();
+        let gpus_idx = args.iter().position(|a| a == "--gpus").unwrap();
+        assert_eq!(args[gpus_idx + 1], "\"device=0,1\"");
+        assert!(gpus_idx < image_idx);
+        let device_idx = args.iter().position(|a| a == "--device").unwrap();
+        assert_eq!(args[device_idx + 1], "/dev/fuse");
+        assert!(device_idx < image_idx);
+    }
+
+    #[test]
+    fn validate_checks_scratch_size() {
+        assert_eq!(parse_size("512m"), Some(512 << 20));
+        assert_eq!(parse_size("2G"), Some(2 << 30));
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("lots"), None);
+
+        let mut config = base_config();
+        config.scratch = Some(ScratchVolume {
+            size: "1g".to_string(),
+            backing: ScratchBacking::Tmpfs,
+        });
+        assert!(config.validate().is_ok());
+
+        for size in ["0", "big", ""] {
+            config.scratch.as_mut().unwrap().size = size.to_string();
+            assert!(config.validate().is_err(), "{size}");
+        }
+    }
+
+    #[test]
+    fn configure_command_mounts_scratch_volume() {
+        let mut config = base_config();
+        config.scratch = Some(ScratchVolume {
+            size: "128m".to_string(),
+            backing: ScratchBacking::Tmpfs,
+        });
+
+        let tmp = tempfile::tempdir().unwrap();
+        let mount = EnvelopeMount::prepare(&config.envelope_path, tmp.path(), None).unwrap();
+        let args_for = |config: &ContainerExecConfig, scratch_dir: Option<&Path>| {
+            let mut command = Command::new("docker");
+            configure_command(
+                &mut command,
+                config,
+                &DeviceGrant::default(),
+                Some(&mount),
+                scratch_dir,
+                None,
+            )
+            .unwrap();
+            command
+                .get_args()
+                .map(|a| a.to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let args = args_for(&config, None);
+        assert!(args.contains(&format!(
+            "/workspace/.scratch:rw,nosuid,nodev,size={}",
+            128 << 20
+        )));
+
+        config.scratch.as_mut().unwrap().backing = ScratchBacking::Host;
+        let scratch_dir = tmp.path().join("scratch");
+        let args = args_for(&config, Some(&scratch_dir));
+        assert!(args.contains(&format!(
+            "type=bind,source={},target=/workspace/.scratch,readonly=false",
+            scratch_dir.display()
+        )));
+        assert!(!args.iter().any(|a| a.starts_with("/workspace/.scratch:")));
+    }
+
+    #[test]
+    fn annotate_scratch_warns_when_host_scratch_exceeds_limit() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("nested")).unwrap();
+        fs::write(tmp.path().join("a.bin"), vec![0u8; 600]).unwrap();
+        fs::write(tmp.path().join("nested/b.bin"), vec![0u8; 600]).unwrap();
+        assert_eq!(dir_size(tmp.path()), 1200);
+
+        let scratch = ScratchVolume {
+            size: "1k".to_string(),
+            backing: ScratchBacking::Host,
+        };
+        let mut envelope = sample_envelope();
+        annotate_scratch(&mut envelope, &scratch, Some(dir_size(tmp.path())));
+        let diag = envelope.diagnostics.last().unwrap();
+        assert_eq!(diag.level, DiagnosticLevel::Warning);
+        let context = diag.context.as_ref().unwrap();
+        assert_eq!(context["usedBytes"], serde_json::json!(1200));
+        assert_eq!(context["limitBytes"], serde_json::json!(1024));
+
+        let mut envelope = sample_envelope();
+        annotate_scratch(&mut envelope, &scratch, Some(10));
+        assert_eq!(
+            envelope.diagnostics.last().unwrap().level,
+            DiagnosticLevel::Info
+        );
+    }
+
+    #[test]
+    fn stub_runtime_records_requested_and_granted_devices() {
+        let _guard = env_guard();
+        let tmp = tempfile::tempdir().unwrap();
+        let stub = tmp.path().join("stub.json");
+        fs::write(&stub, serde_json::to_vec(&sample_envelope()).unwrap()).unwrap();
+        env::set_var("DEMON_CONTAINER_RUNTIME", "stub");
+        env::set_var("DEMON_CONTAINER_EXEC_STUB_ENVELOPE", &stub);
+        env::set_var("DEMON_CONTAINER_GPU_ALLOWLIST", "0, 1");
+
+        let mut config = base_config();
+        config.gpus = Some(GpuRequest::Count(1));
+        let envelope = execute(&config);
+
+        config.gpus = Some(GpuRequest::Devices(vec!["7".to_string()]));
+        let denied = execute(&config);
+
+        env::remove_var("DEMON_CONTAINER_RUNTIME");
+        env::remove_var("DEMON_CONTAINER_EXEC_STUB_ENVELOPE");
+        env::remove_var("DEMON_CONTAINER_GPU_ALLOWLIST");
+
+        assert!(envelope.result.is_success());
+        let diag = envelope
+            .diagnostics
+            .iter()
+            .find(|d| d.message.starts_with("device passthrough granted"))
+            .expect("device diagnostic");
+        let context = diag.context.as_ref().unwrap();
+        assert_eq!(context["requested"]["gpus"], serde_json::json!(1));
+        assert_eq!(context["granted"]["gpus"], serde_json::json!(["0"]));
+
+        if let OperationResult::Error { error, .. } = denied.result {
+            assert_eq!(error.code.as_deref(), Some("CONTAINER_EXEC_INVALID_CONFIG"));
+            assert!(error.message.contains("GPU '7' is not allowed"));
+        } else {
+            panic!("expected invalid config error");
+        }
+    }
+
     #[cfg(unix)]
     #[test]
     fn configure_command_respects_container_user_env() {
@@ -1860,6 +2900,12 @@ esac
             capsule_name: None,
             app_pack_dir: Some(app_pack_dir),
             artifacts_dir: Some(artifacts_dir),
+            resources: ResourceLimits::default(),
+            network: NetworkMode::None,
+            gpus: None,
+            devices: Vec::new(),
+            scratch: None,
+            result_transport: ResultTransport::File,
         };
 
         let tmp = tempfile::tempdir().unwrap();
@@ -1871,7 +2917,15 @@ esac
         .unwrap();
 
         let mut command = Command::new("docker");
-        configure_command(&mut command, &config, &mount, None).unwrap();
+        configure_command(
+            &mut command,
+            &config,
+            &DeviceGrant::default(),
+            Some(&mount),
+            None,
+            None,
+        )
+        .unwrap();
         let args: Vec<String> = command
             .get_args()
             .map(|a| a.to_string_lossy().to_string())
@@ -2144,6 +3198,138 @@ esac
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn runtime_script_stdout_transport_parses_envelope_without_mount() {
+        let _guard = env_guard();
+        let envelope = sample_envelope();
+        let fixture = RuntimeFixture::new(&envelope);
+        let log = fixture.artifacts_dir().join("runtime.log");
+
+        env::set_var(
+            "DEMON_CONTAINER_RUNTIME",
+            fixture.script().to_string_lossy().to_string(),
+        );
+        env::set_var(
+            "TEST_ENVELOPE_HOST_PATH",
+            fixture.host_envelope().to_string_lossy().to_string(),
+        );
+        env::set_var(
+            "TEST_ENVELOPE_SOURCE",
+            fixture.stub_source().to_string_lossy().to_string(),
+        );
+        env::set_var("TEST_RUNTIME_MODE", "stdout");
+        env::set_var("TEST_RUNTIME_LOG", log.to_string_lossy().to_string());
+
+        let mut config = base_config();
+        config.timeout_seconds = Some(5);
+        config.result_transport = ResultTransport::Stdout;
+
+        let result = execute(&config);
+
+        for key in [
+            "DEMON_CONTAINER_RUNTIME",
+            "TEST_ENVELOPE_HOST_PATH",
+            "TEST_ENVELOPE_SOURCE",
+            "TEST_RUNTIME_MODE",
+            "TEST_RUNTIME_LOG",
+        ] {
+            env::remove_var(key);
+        }
+
+        assert!(result.result.is_success(), "{:?}", result.result);
+        let stdout_diag = result
+            .diagnostics
+            .iter()
+            .find(|d| d.message.starts_with("stdout:"))
+            .expect("stdout diagnostic");
+        assert!(stdout_diag.message.contains("capsule progress"));
+        assert!(!stdout_diag.message.contains(STDOUT_ENVELOPE_BEGIN));
+        assert!(!result
+            .diagnostics
+            .iter()
+            .any(|d| d.message.contains("unexpectedly written")));
+    }
+
+    #[test]
+    fn take_stdout_envelope_reads_sentinels_or_last_json_document() {
+        let envelope = serde_json::to_string_pretty(&sample_envelope()).unwrap();
+
+        let mut logs = CommandLogs::new(
+            format!(
+                "starting\n{}\n{}\n{}\ntrailing\n",
+                STDOUT_ENVELOPE_BEGIN, envelope, STDOUT_ENVELOPE_END
+            ),
+            String::new(),
+            Some(0),
+        );
+        let parsed = take_stdout_envelope(&mut logs).unwrap();
+        assert!(parsed.result.is_success());
+        assert_eq!(logs.stdout, "starting\n\ntrailing\n");
+
+        let mut logs = CommandLogs::new(
+            format!("{{\"progress\": 1}}\nworking\n{}\n", envelope),
+            String::new(),
+            Some(0),
+        );
+        assert!(take_stdout_envelope(&mut logs).is_ok());
+        assert_eq!(logs.stdout, "{\"progress\": 1}\nworking\n");
+
+        let mut logs = CommandLogs::new("no envelope here\n".to_string(), String::new(), None);
+        assert!(matches!(
+            take_stdout_envelope(&mut logs),
+            Err(StdoutEnvelopeError::Missing)
+        ));
+
+        let mut logs = CommandLogs::new(
+            format!("{}\n{{\"result\": \n", STDOUT_ENVELOPE_BEGIN),
+            String::new(),
+            None,
+        );
+        assert!(matches!(
+            take_stdout_envelope(&mut logs),
+            Err(StdoutEnvelopeError::Missing)
+        ));
+
+        let mut logs = CommandLogs::new(
+            format!(
+                "{}\n{{\"oops\"}}\n{}\n",
+                STDOUT_ENVELOPE_BEGIN, STDOUT_ENVELOPE_END
+            ),
+            String::new(),
+            None,
+        );
+        assert!(matches!(
+            take_stdout_envelope(&mut logs),
+            Err(StdoutEnvelopeError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn configure_command_skips_envelope_mount_for_stdout_transport() {
+        let mut config = base_config();
+        config.result_transport = ResultTransport::Stdout;
+
+        let mut command = Command::new("docker");
+        configure_command(
+            &mut command,
+            &config,
+            &DeviceGrant::default(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let args: Vec<String> = command
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+
+        assert!(!args.iter().any(|a| a.contains("result.json")));
+        assert!(!args.iter().any(|a| a.starts_with("ENVELOPE_PATH=")));
+        assert!(args.contains(&"DEMON_RESULT_TRANSPORT=stdout".to_string()));
+    }
+
     #[test]
     fn runtime_missing_binary_returns_error_envelope() {
         let _guard = env_guard();
@@ -2159,6 +3345,12 @@ esac
             capsule_name: None,
             app_pack_dir: None,
             artifacts_dir: None,
+            resources: ResourceLimits::default(),
+            network: NetworkMode::None,
+            gpus: None,
+            devices: Vec::new(),
+            scratch: None,
+            result_transport: ResultTransport::File,
         };
 
         let result = execute(&config);
@@ -2278,6 +3470,66 @@ esac
         }
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn runtime_cancel_kills_container_and_triggers_cleanup() {
+        let _guard = env_guard();
+        let envelope = sample_envelope();
+        let fixture = RuntimeFixture::new(&envelope);
+        let log_dir = tempfile::tempdir().unwrap();
+        let log_path = log_dir.path().join("runtime.log");
+
+        env::set_var(
+            "DEMON_CONTAINER_RUNTIME",
+            fixture.script().to_string_lossy().to_string(),
+        );
+        env::set_var(
+            "TEST_ENVELOPE_HOST_PATH",
+            fixture.host_envelope().to_string_lossy().to_string(),
+        );
+        env::set_var(
+            "TEST_ENVELOPE_SOURCE",
+            fixture.stub_source().to_string_lossy().to_string(),
+        );
+        env::set_var("TEST_RUNTIME_MODE", "sleep");
+        env::set_var("TEST_SLEEP_SECS", "3");
+        env::set_var("TEST_RUNTIME_LOG", log_path.to_string_lossy().to_string());
+
+        let pack = tempfile::tempdir().unwrap();
+        let mut config = base_config();
+        config.artifacts_dir = Some(fixture.artifacts_dir().to_path_buf());
+        config.app_pack_dir = Some(pack.path().to_path_buf());
+
+        let cancel = CancelToken::new();
+        let trigger = cancel.clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            trigger.cancel();
+        });
+
+        let result = execute_with_cancel(&config, &cancel);
+        canceller.join().unwrap();
+
+        if let OperationResult::Error { error, .. } = &result.result {
+            assert_eq!(error.code.as_deref(), Some("CONTAINER_EXEC_CANCELED"));
+        } else {
+            panic!("expected error result");
+        }
+        let log_contents = fs::read_to_string(&log_path).unwrap();
+        assert!(log_contents.contains("cleanup stub-container-id"));
+
+        for key in [
+            "DEMON_CONTAINER_RUNTIME",
+            "TEST_ENVELOPE_HOST_PATH",
+            "TEST_ENVELOPE_SOURCE",
+            "TEST_RUNTIME_MODE",
+            "TEST_SLEEP_SECS",
+            "TEST_RUNTIME_LOG",
+        ] {
+            env::remove_var(key);
+        }
+    }
+
     #[cfg(unix)]
     #[test]
     fn debug_mode_emits_host_diagnostics() {
@@ -0,0 +1,708 @@
+//! Warm container pool
+//!
+//! Short capsule runs spend most of their time starting a container. With
+//! `DEMON_CONTAINER_WARM_POOL=1`, container settings (image digest, network,
+//! user, resource limits, scratch) that have been invoked at least
+//! `DEMON_CONTAINER_WARM_MIN_USES` times (default 2) get pre-created, paused
+//! containers that later invocations reuse:
+//!
+//! 1. the App Pack is copied into the container's private workspace slot,
+//!    which is bind-mounted at `/workspace` and `/workspace/.artifacts`
+//! 2. the container is unpaused and the command runs with `exec`
+//! 3. the envelope and artifacts are read back from the slot
+//! 4. `/tmp` and the scratch volume are wiped and the container is paused and
+//!    returned to the pool
+//!
+//! A container is removed instead of reused once it is older than
+//! `DEMON_CONTAINER_WARM_TTL_SECONDS` (default 600), after a failed, timed out
+//! or canceled invocation, or when the wipe fails. At most
+//! `DEMON_CONTAINER_WARM_POOL_SIZE` (default 2) idle containers are kept per
+//! settings. Invocations with GPUs, devices, host-backed scratch or
+//! `DEMON_DEBUG` always start a fresh container. Pooled containers carry the
+//! `demon.warm-pool` label and are removed by [`drain_warm_pool`].
+
+use super::{
+    collect_result, command_line_string, container_user, debug_enabled, limit_args,
+    resolve_timeout, run_container_command, CancelToken, ContainerExecConfig, ContainerExecResult,
+    DeviceGrant, EnvelopeMount, ExecError, ResultTransport, ScratchBacking, RESULT_TRANSPORT_ENV,
+    SCRATCH_MOUNT,
+};
+use envelope::Diagnostic;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use tracing::{debug, warn};
+
+/// Label set on every pooled container
+pub const WARM_POOL_LABEL: &str = "demon.warm-pool";
+
+/// Keeps a pooled container running until it is used; far longer than any TTL
+const IDLE_COMMAND: [&str; 2] = ["sleep", "2147483647"];
+
+/// Operator settings for the warm pool, from `DEMON_CONTAINER_WARM_*`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmPoolSettings {
+    pub enabled: bool,
+    /// Idle containers kept per container settings
+    pub size: usize,
+    /// Containers older than this are removed instead of reused
+    pub ttl: Duration,
+    /// Invocations before containers are pre-created for the settings
+    pub min_uses: u32,
+}
+
+impl Default for WarmPoolSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size: 2,
+            ttl: Duration::from_secs(600),
+            min_uses: 2,
+        }
+    }
+}
+
+impl WarmPoolSettings {
+    pub fn from_env() -> Self {
+        fn number<T: std::str::FromStr>(name: &str) -> Option<T> {
+            env::var(name).ok().and_then(|v| v.trim().parse().ok())
+        }
+        let defaults = Self::default();
+        Self {
+            enabled: env::var("DEMON_CONTAINER_WARM_POOL")
+                .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+            size: number("DEMON_CONTAINER_WARM_POOL_SIZE").unwrap_or(defaults.size),
+            ttl: number("DEMON_CONTAINER_WARM_TTL_SECONDS")
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.ttl),
+            min_uses: number("DEMON_CONTAINER_WARM_MIN_USES").unwrap_or(defaults.min_uses),
+        }
+    }
+}
+
+/// A paused container and the host directory behind its workspace mounts
+struct WarmContainer {
+    id: String,
+    slot: TempDir,
+    created: Instant,
+}
+
+impl WarmContainer {
+    fn workspace(&self) -> PathBuf {
+        self.slot.path().join("workspace")
+    }
+
+    fn artifacts(&self) -> PathBuf {
+        self.slot.path().join("artifacts")
+    }
+}
+
+#[derive(Default)]
+struct WarmPool {
+    idle: HashMap<String, Vec<WarmContainer>>,
+    /// Containers being created, by settings
+    warming: HashMap<String, usize>,
+    uses: HashMap<String, u32>,
+    /// Settings whose image could not be kept running, e.g. without `sleep`
+    unwarmable: HashSet<String>,
+    draining: bool,
+}
+
+static POOL: OnceLock<Mutex<WarmPool>> = OnceLock::new();
+
+fn pool() -> MutexGuard<'static, WarmPool> {
+    POOL.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+}
+
+/// Run `config` in a pooled container if one is available. `None` means the
+/// caller starts a fresh container; the use still counts toward warming.
+pub(crate) fn execute(
+    config: &ContainerExecConfig,
+    grant: &DeviceGrant,
+    runtime_bin: &str,
+    cancel: &CancelToken,
+) -> Option<Result<ContainerExecResult, ExecError>> {
+    let settings = WarmPoolSettings::from_env();
+    if !settings.enabled || !eligible(config, grant) {
+        return None;
+    }
+    let key = pool_key(config);
+    let (container, expired) = {
+        let mut pool = pool();
+        *pool.uses.entry(key.clone()).or_default() += 1;
+        let idle = pool.idle.entry(key.clone()).or_default();
+        let (expired, fresh): (Vec<_>, Vec<_>) = idle
+            .drain(..)
+            .partition(|c| c.created.elapsed() >= settings.ttl);
+        *idle = fresh;
+        (idle.pop(), expired)
+    };
+    for container in expired {
+        discard(runtime_bin, container);
+    }
+
+    let result = container.map(|container| {
+        let age = container.created.elapsed();
+        let result = run_warm(config, runtime_bin, &container, cancel);
+        if result.is_ok() && reset(runtime_bin, &container).is_ok() {
+            recycle(runtime_bin, &key, container, &settings);
+        } else {
+            discard(runtime_bin, container);
+        }
+        result.map(|mut result| {
+            result.envelope.diagnostics.push(
+                Diagnostic::info(format!(
+                    "reused a warm container created {}s ago",
+                    age.as_secs()
+                ))
+                .with_source("container-exec")
+                .with_context(serde_json::json!({
+                    "warmPool": {"ageMs": age.as_millis() as u64},
+                })),
+            );
+            result
+        })
+    });
+
+    refill(runtime_bin, &key, config, &settings);
+    result
+}
+
+/// Remove every idle pooled container, e.g. on shutdown; containers still
+/// being created are removed once they are ready
+pub fn drain_warm_pool() {
+    let containers: Vec<WarmContainer> = {
+        let mut pool = pool();
+        pool.draining = true;
+        pool.idle.drain().flat_map(|(_, idle)| idle).collect()
+    };
+    if let super::RuntimeKind::Binary(runtime_bin) = super::detect_runtime_kind() {
+        for container in containers {
+            discard(&runtime_bin, container);
+        }
+    }
+}
+
+/// Whether an invocation can run in a pooled container
+fn eligible(config: &ContainerExecConfig, grant: &DeviceGrant) -> bool {
+    let envelope_in_artifacts = match config.result_transport {
+        ResultTransport::Stdout => true,
+        ResultTransport::File => {
+            EnvelopeMount::prepare(&config.envelope_path, Path::new("/"), Some(Path::new("/")))
+                .is_ok()
+        }
+    };
+    grant.is_empty()
+        && config.image_digest.contains("@sha256:")
+        && !config
+            .scratch
+            .as_ref()
+            .is_some_and(|scratch| scratch.backing == ScratchBacking::Host)
+        && !debug_enabled()
+        && envelope_in_artifacts
+}
+
+/// Container-level `run` flags, shared by every invocation a pooled container
+/// serves; the image is last
+fn container_args(config: &ContainerExecConfig) -> Vec<String> {
+    let mut args = vec![
+        "--pull".to_string(),
+        "never".to_string(),
+        "--network".to_string(),
+        config.network.as_arg().to_string(),
+        "--read-only".to_string(),
+        "--security-opt".to_string(),
+        "no-new-privileges".to_string(),
+        "--user".to_string(),
+        container_user(),
+        "--tmpfs".to_string(),
+        "/tmp:rw,noexec,nosuid,nodev,size=67108864".to_string(),
+    ];
+    if let Some(scratch) = &config.scratch {
+        args.push("--tmpfs".to_string());
+        args.push(format!(
+            "{}:rw,nosuid,nodev,size={}",
+            SCRATCH_MOUNT,
+            scratch.size_bytes().unwrap_or_default()
+        ));
+    }
+    for (flag, value) in limit_args(config) {
+        args.push(flag.to_string());
+        args.push(value);
+    }
+    args.push(config.image_digest.clone());
+    args
+}
+
+/// Pooled containers are shared between invocations with equal settings
+fn pool_key(config: &ContainerExecConfig) -> String {
+    container_args(config).join(" ")
+}
+
+/// Start a paused container for `config`'s settings
+fn create(runtime_bin: &str, config: &ContainerExecConfig) -> Result<WarmContainer, String> {
+    let slot = TempDir::new().map_err(|err| format!("creating workspace slot: {}", err))?;
+    for dir in ["workspace/.artifacts", "workspace/.scratch", "artifacts"] {
+        make_shared_dir(&slot.path().join(dir)).map_err(|err| err.to_string())?;
+    }
+
+    let mut args = vec![
+        "run".to_string(),
+        "--detach".to_string(),
+        "--rm".to_string(),
+        "--label".to_string(),
+        format!("{}=1", WARM_POOL_LABEL),
+        "--mount".to_string(),
+        format!(
+            "type=bind,source={},target=/workspace,readonly=true",
+            slot.path().join("workspace").display()
+        ),
+        "--mount".to_string(),
+        format!(
+            "type=bind,source={},target=/workspace/.artifacts,readonly=false",
+            slot.path().join("artifacts").display()
+        ),
+        "--entrypoint".to_string(),
+        String::new(),
+    ];
+    // The image comes last in container_args, right before the command
+    args.extend(container_args(config));
+    args.extend(IDLE_COMMAND.iter().map(|arg| arg.to_string()));
+
+    let id = runtime(runtime_bin, &args)?;
+    let container = WarmContainer {
+        id,
+        slot,
+        created: Instant::now(),
+    };
+    if let Err(err) = runtime(runtime_bin, &["pause".to_string(), container.id.clone()]) {
+        discard(runtime_bin, container);
+        return Err(err);
+    }
+    Ok(container)
+}
+
+/// Run one invocation in a pooled container
+fn run_warm(
+    config: &ContainerExecConfig,
+    runtime_bin: &str,
+    container: &WarmContainer,
+    cancel: &CancelToken,
+) -> Result<ContainerExecResult, ExecError> {
+    let io_error = |message: String| ExecError::Io { message };
+
+    // Re-mount the invocation's workspace by refreshing the slot contents
+    let workspace = container.workspace();
+    clear_dir(&workspace, &[".artifacts", ".scratch"]).map_err(|err| {
+        io_error(format!(
+            "Failed to clear warm workspace {}: {}",
+            workspace.display(),
+            err
+        ))
+    })?;
+    if let Some(app_pack_dir) = &config.app_pack_dir {
+        copy_dir(app_pack_dir, &workspace, &[".artifacts", ".scratch"]).map_err(|err| {
+            io_error(format!(
+                "Failed to copy App Pack {} into warm workspace: {}",
+                app_pack_dir.display(),
+                err
+            ))
+        })?;
+    }
+    let artifacts = container.artifacts();
+    clear_dir(&artifacts, &[]).map_err(|err| {
+        io_error(format!(
+            "Failed to clear warm artifacts {}: {}",
+            artifacts.display(),
+            err
+        ))
+    })?;
+
+    let slot_mount = match config.result_transport {
+        ResultTransport::File => {
+            let mount = EnvelopeMount::prepare(
+                &config.envelope_path,
+                container.slot.path(),
+                Some(&artifacts),
+            )?;
+            if let Some(parent) = mount.host_envelope_path.parent() {
+                make_shared_dir(parent).map_err(|err| {
+                    io_error(format!(
+                        "Failed to create envelope directory {}: {}",
+                        parent.display(),
+                        err
+                    ))
+                })?;
+            }
+            super::ensure_envelope_placeholder(&mount.host_envelope_path)?;
+            Some(mount)
+        }
+        ResultTransport::Stdout => None,
+    };
+
+    runtime(runtime_bin, &["unpause".to_string(), container.id.clone()])
+        .map_err(|err| io_error(format!("Failed to unpause warm container: {}", err)))?;
+
+    let mut command = Command::new(runtime_bin);
+    command.arg("exec").arg("--user").arg(container_user());
+    match &config.working_dir {
+        Some(dir) => {
+            command.arg("--workdir").arg(dir);
+        }
+        None if config.app_pack_dir.is_some() => {
+            command.arg("--workdir").arg("/workspace");
+        }
+        None => {}
+    }
+    match &slot_mount {
+        Some(_) => command
+            .arg("--env")
+            .arg(format!("ENVELOPE_PATH={}", config.envelope_path)),
+        None => command
+            .arg("--env")
+            .arg(format!("{}=stdout", RESULT_TRANSPORT_ENV)),
+    };
+    for (key, value) in &config.env {
+        command.arg("--env").arg(format!("{}={}", key, value));
+    }
+    command.arg(&container.id);
+    command.args(&config.command);
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    let runtime_cmdline = command_line_string(&command);
+
+    let start = Instant::now();
+    let run = run_container_command(
+        runtime_bin.to_string(),
+        command,
+        resolve_timeout(config)?,
+        None,
+        cancel,
+    )?;
+    let duration = start.elapsed();
+
+    // Artifacts leave the slot before it is wiped for the next invocation
+    let mount = match (slot_mount, &config.artifacts_dir) {
+        (_, Some(artifacts_dir)) => {
+            copy_dir(&artifacts, artifacts_dir, &[]).map_err(|err| {
+                io_error(format!(
+                    "Failed to copy artifacts to {}: {}",
+                    artifacts_dir.display(),
+                    err
+                ))
+            })?;
+            match config.result_transport {
+                ResultTransport::File => Some(EnvelopeMount::prepare(
+                    &config.envelope_path,
+                    container.slot.path(),
+                    Some(artifacts_dir),
+                )?),
+                ResultTransport::Stdout => None,
+            }
+        }
+        (slot_mount, None) => slot_mount,
+    };
+
+    collect_result(
+        config,
+        mount.as_ref(),
+        run,
+        duration,
+        None,
+        &runtime_cmdline,
+    )
+}
+
+/// Wipe what an invocation may have left in writable paths and pause
+fn reset(runtime_bin: &str, container: &WarmContainer) -> Result<(), String> {
+    let wipe = format!(
+        "rm -rf /tmp/* /tmp/.[!.]* {scratch}/* {scratch}/.[!.]* 2>/dev/null; exit 0",
+        scratch = SCRATCH_MOUNT
+    );
+    runtime(
+        runtime_bin,
+        &[
+            "exec".to_string(),
+            "--user".to_string(),
+            container_user(),
+            container.id.clone(),
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            wipe,
+        ],
+    )?;
+    runtime(runtime_bin, &["pause".to_string(), container.id.clone()])?;
+    Ok(())
+}
+
+fn recycle(runtime_bin: &str, key: &str, container: WarmContainer, settings: &WarmPoolSettings) {
+    let rejected = {
+        let mut pool = pool();
+        let draining = pool.draining;
+        let idle = pool.idle.entry(key.to_string()).or_default();
+        if draining || idle.len() >= settings.size || container.created.elapsed() >= settings.ttl {
+            Some(container)
+        } else {
+            idle.push(container);
+            None
+        }
+    };
+    if let Some(container) = rejected {
+        discard(runtime_bin, container);
+    }
+}
+
+/// Pre-create a container in the background once the settings are in
+/// frequent use and the pool has room
+fn refill(runtime_bin: &str, key: &str, config: &ContainerExecConfig, settings: &WarmPoolSettings) {
+    {
+        let mut pool = pool();
+        let uses = pool.uses.get(key).copied().unwrap_or_default();
+        let idle = pool.idle.get(key).map(Vec::len).unwrap_or_default();
+        let warming = pool.warming.get(key).copied().unwrap_or_default();
+        if pool.draining
+            || pool.unwarmable.contains(key)
+            || uses < settings.min_uses
+            || idle + warming >= settings.size
+        {
+            return;
+        }
+        *pool.warming.entry(key.to_string()).or_default() += 1;
+    }
+
+    let runtime_bin = runtime_bin.to_string();
+    let key = key.to_string();
+    let config = config.clone();
+    thread::spawn(move || {
+        let created = create(&runtime_bin, &config);
+        let rejected = {
+            let mut pool = pool();
+            if let Some(warming) = pool.warming.get_mut(&key) {
+                *warming = warming.saturating_sub(1);
+            }
+            match created {
+                Ok(container) if pool.draining => Some(container),
+                Ok(container) => {
+                    debug!(image = %config.image_digest, "warm container ready");
+                    pool.idle.entry(key).or_default().push(container);
+                    None
+                }
+                Err(err) => {
+                    warn!(
+                        image = %config.image_digest,
+                        error = %err,
+                        "could not pre-create a warm container; invocations will start fresh containers"
+                    );
+                    pool.unwarmable.insert(key);
+                    None
+                }
+            }
+        };
+        if let Some(container) = rejected {
+            discard(&runtime_bin, container);
+        }
+    });
+}
+
+fn discard(runtime_bin: &str, container: WarmContainer) {
+    if let Err(err) = runtime(
+        runtime_bin,
+        &[
+            "rm".to_string(),
+            "--force".to_string(),
+            container.id.clone(),
+        ],
+    ) {
+        warn!(container = %container.id, error = %err, "failed to remove warm container");
+    }
+}
+
+/// Run a short runtime command and return its trimmed stdout
+fn runtime(runtime_bin: &str, args: &[String]) -> Result<String, String> {
+    let output = Command::new(runtime_bin)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| format!("{} {}: {}", runtime_bin, args[0], err))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} {} failed: {}",
+            runtime_bin,
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Create `dir` writable by any container user
+fn make_shared_dir(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o777))?;
+    Ok(())
+}
+
+/// Remove everything in `dir` except the entries named in `keep`
+fn clear_dir(dir: &Path, keep: &[&str]) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if keep.iter().any(|name| entry.file_name() == *name) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the contents of `from` into `to`, skipping top-level entries named in
+/// `skip`; symlinks are copied as links
+fn copy_dir(from: &Path, to: &Path, skip: &[&str]) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if skip.iter().any(|name| entry.file_name() == *name) {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target, &[])?;
+        } else if file_type.is_symlink() {
+            #[cfg(unix)]
+            {
+                let _ = fs::remove_file(&target);
+                std::os::unix::fs::symlink(fs::read_link(entry.path())?, &target)?;
+            }
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NetworkMode, ResourceLimits, ScratchVolume};
+    use std::collections::BTreeMap;
+
+    fn config() -> ContainerExecConfig {
+        ContainerExecConfig {
+            image_digest: "ghcr.io/example/app@sha256:abcdef".to_string(),
+            command: vec!["/bin/true".to_string()],
+            env: BTreeMap::new(),
+            working_dir: None,
+            envelope_path: "/workspace/.artifacts/result.json".to_string(),
+            timeout_seconds: None,
+            capsule_name: None,
+            app_pack_dir: None,
+            artifacts_dir: None,
+            resources: ResourceLimits::default(),
+            network: NetworkMode::None,
+            gpus: None,
+            devices: Vec::new(),
+            scratch: None,
+            result_transport: ResultTransport::File,
+        }
+    }
+
+    fn no_devices() -> DeviceGrant {
+        DeviceGrant {
+            gpus: Vec::new(),
+            devices: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn only_plain_digest_pinned_invocations_are_pooled() {
+        assert!(eligible(&config(), &no_devices()));
+
+        let mut outside_artifacts = config();
+        outside_artifacts.envelope_path = "/workspace/result.json".to_string();
+        assert!(!eligible(&outside_artifacts, &no_devices()));
+        outside_artifacts.result_transport = ResultTransport::Stdout;
+        assert!(eligible(&outside_artifacts, &no_devices()));
+
+        let mut tagged = config();
+        tagged.image_digest = "ghcr.io/example/app:latest".to_string();
+        assert!(!eligible(&tagged, &no_devices()));
+
+        let mut host_scratch = config();
+        host_scratch.scratch = Some(ScratchVolume {
+            size: "64m".to_string(),
+            backing: ScratchBacking::Host,
+        });
+        assert!(!eligible(&host_scratch, &no_devices()));
+
+        let gpu = DeviceGrant {
+            gpus: vec!["0".to_string()],
+            devices: Vec::new(),
+        };
+        assert!(!eligible(&config(), &gpu));
+    }
+
+    #[test]
+    fn pool_key_tracks_container_settings_but_not_the_command() {
+        let mut other_command = config();
+        other_command.command = vec!["/bin/echo".to_string(), "hi".to_string()];
+        other_command
+            .env
+            .insert("GREETING".to_string(), "hi".to_string());
+        assert_eq!(pool_key(&config()), pool_key(&other_command));
+
+        let mut limited = config();
+        limited.resources.memory = Some("256m".to_string());
+        assert_ne!(pool_key(&config()), pool_key(&limited));
+        assert!(container_args(&limited).ends_with(&[
+            "--memory".to_string(),
+            "256m".to_string(),
+            limited.image_digest.clone()
+        ]));
+    }
+
+    #[test]
+    fn workspace_copy_skips_mount_points_and_clear_keeps_them() {
+        let app = tempfile::tempdir().unwrap();
+        fs::create_dir_all(app.path().join("bin")).unwrap();
+        fs::write(app.path().join("bin/run.sh"), "echo hi").unwrap();
+        fs::create_dir_all(app.path().join(".artifacts")).unwrap();
+        fs::write(app.path().join(".artifacts/stale.json"), "{}").unwrap();
+
+        let slot = tempfile::tempdir().unwrap();
+        fs::create_dir_all(slot.path().join(".artifacts")).unwrap();
+        fs::write(slot.path().join("left-over.txt"), "x").unwrap();
+        clear_dir(slot.path(), &[".artifacts"]).unwrap();
+        copy_dir(app.path(), slot.path(), &[".artifacts"]).unwrap();
+
+        assert!(slot.path().join("bin/run.sh").exists());
+        assert!(!slot.path().join("left-over.txt").exists());
+        assert!(slot.path().join(".artifacts").is_dir());
+        assert!(!slot.path().join(".artifacts/stale.json").exists());
+    }
+
+    #[test]
+    fn settings_default_to_disabled() {
+        let settings = WarmPoolSettings::default();
+        assert!(!settings.enabled);
+        assert_eq!(settings.size, 2);
+        assert_eq!(settings.ttl, Duration::from_secs(600));
+    }
+}
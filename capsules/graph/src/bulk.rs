@@ -0,0 +1,698 @@
+//! Bulk import/export for graph capsule
+//!
+//! Exports the materialized graph at a commit as JSONL (one node or edge record
+//! per line) or GraphML, and imports either format as a chain of batched commits.
+//! Exports are rendered one node or edge at a time as the stream is polled, and
+//! GraphML is read with a streaming XML reader. GraphML support is intentionally
+//! minimal: it understands the `key`, `node`, `edge` and `data` elements emitted
+//! by common tools (including Neo4j APOC exports) and ignores everything else.
+
+use crate::storage::{self, GraphStore};
+use crate::types::{EdgeSnapshot, GraphScope, Mutation, NodeSnapshot, Property};
+use crate::{compute_commit_id, events};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use envelope::{AsEnvelope, Diagnostic, DiagnosticLevel, ResultEnvelope};
+use futures_util::stream::{self, Stream};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+
+/// Default number of mutations per imported commit
+pub const DEFAULT_IMPORT_BATCH_SIZE: usize = 500;
+
+/// Serialization format for bulk transfer
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferFormat {
+    Jsonl,
+    #[serde(rename = "graphml")]
+    GraphMl,
+}
+
+impl TransferFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferFormat::Jsonl => "jsonl",
+            TransferFormat::GraphMl => "graphml",
+        }
+    }
+}
+
+impl std::str::FromStr for TransferFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "jsonl" | "ndjson" => Ok(TransferFormat::Jsonl),
+            "graphml" | "xml" => Ok(TransferFormat::GraphMl),
+            other => bail!("Unsupported graph transfer format '{}'", other),
+        }
+    }
+}
+
+/// A single line of a JSONL export
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum JsonlRecord {
+    Node(NodeSnapshot),
+    Edge(EdgeSnapshot),
+}
+
+/// Options controlling how imported data is split into commits
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Maximum number of mutations per commit
+    pub batch_size: usize,
+    /// Commit to chain the first imported batch onto (None starts a new graph)
+    pub parent_commit_id: Option<String>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_IMPORT_BATCH_SIZE,
+            parent_commit_id: None,
+        }
+    }
+}
+
+/// Progress snapshot reported after each committed batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportProgress {
+    pub batches_committed: usize,
+    pub batches_total: usize,
+    pub mutations_committed: usize,
+    pub mutations_total: usize,
+    pub last_commit_id: String,
+}
+
+/// Result of an import operation
+#[derive(Serialize, Deserialize, AsEnvelope, Debug, Clone)]
+pub struct ImportResult {
+    pub format: TransferFormat,
+    pub commit_ids: Vec<String>,
+    pub head_commit_id: Option<String>,
+    pub nodes_imported: usize,
+    pub edges_imported: usize,
+}
+
+/// Stream the materialized graph at `commit_id` in the requested format
+///
+/// Each stream item is a self-contained chunk (one JSONL line, or one GraphML
+/// element) terminated by a newline, so callers can write items straight to a
+/// file or HTTP body. Chunks are rendered as the stream is polled.
+pub async fn export(
+    scope: GraphScope,
+    commit_id: String,
+    format: TransferFormat,
+) -> Result<impl Stream<Item = Result<String>>> {
+    let graph = storage::materialize_graph_at_commit(&scope, &commit_id).await?;
+    tracing::info!(
+        "Exporting graph {} at commit {} as {} ({} nodes, {} edges)",
+        scope.graph_id,
+        commit_id,
+        format.as_str(),
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+    Ok(stream::iter(render(graph, format)))
+}
+
+/// Render a materialized graph into export chunks, one node or edge per chunk
+///
+/// Nodes and edges are emitted sorted by ID so exports are reproducible. Each
+/// chunk is only rendered when the iterator reaches it.
+pub fn render(
+    graph: GraphStore,
+    format: TransferFormat,
+) -> Box<dyn Iterator<Item = Result<String>> + Send> {
+    let mut nodes: Vec<NodeSnapshot> = graph.nodes.into_values().collect();
+    nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    let mut edges: Vec<EdgeSnapshot> = graph.edges.into_values().collect();
+    edges.sort_by(|a, b| a.edge_id.cmp(&b.edge_id));
+
+    match format {
+        TransferFormat::Jsonl => Box::new(
+            nodes
+                .into_iter()
+                .map(JsonlRecord::Node)
+                .chain(edges.into_iter().map(JsonlRecord::Edge))
+                .map(|record| Ok(format!("{}\n", serde_json::to_string(&record)?))),
+        ),
+        TransferFormat::GraphMl => {
+            let header = graphml_header(&nodes, &edges);
+            let footer = ["</graph>\n".to_string(), "</graphml>\n".to_string()];
+            Box::new(
+                header
+                    .into_iter()
+                    .chain(nodes.into_iter().map(|node| graphml_node(&node)))
+                    .chain(edges.into_iter().map(|edge| graphml_edge(&edge)))
+                    .chain(footer)
+                    .map(Ok),
+            )
+        }
+    }
+}
+
+/// XML declaration, `<key>` declarations and the opening `<graph>` element
+fn graphml_header(nodes: &[NodeSnapshot], edges: &[EdgeSnapshot]) -> Vec<String> {
+    // Collect property keys per element kind so <key> declarations precede data
+    let mut node_keys: BTreeMap<&str, &'static str> = BTreeMap::new();
+    for node in nodes {
+        for prop in &node.properties {
+            node_keys
+                .entry(&prop.key)
+                .or_insert_with(|| graphml_type(&prop.value));
+        }
+    }
+    let mut edge_keys: BTreeMap<&str, &'static str> = BTreeMap::new();
+    for edge in edges {
+        for prop in &edge.properties {
+            edge_keys
+                .entry(&prop.key)
+                .or_insert_with(|| graphml_type(&prop.value));
+        }
+    }
+
+    let mut chunks = vec![
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_string(),
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n".to_string(),
+    ];
+    for (name, ty) in &node_keys {
+        chunks.push(format!(
+            "<key id=\"n_{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+            escape_xml(name),
+            escape_xml(name),
+            ty
+        ));
+    }
+    for (name, ty) in &edge_keys {
+        chunks.push(format!(
+            "<key id=\"e_{}\" for=\"edge\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+            escape_xml(name),
+            escape_xml(name),
+            ty
+        ));
+    }
+    chunks.push("<graph edgedefault=\"directed\">\n".to_string());
+    chunks
+}
+
+fn graphml_node(node: &NodeSnapshot) -> String {
+    let mut element = format!("<node id=\"{}\"", escape_xml(&node.node_id));
+    if !node.labels.is_empty() {
+        element.push_str(&format!(
+            " labels=\":{}\"",
+            escape_xml(&node.labels.join(":"))
+        ));
+    }
+    element.push('>');
+    for prop in &node.properties {
+        element.push_str(&graphml_data("n_", prop));
+    }
+    element.push_str("</node>\n");
+    element
+}
+
+fn graphml_edge(edge: &EdgeSnapshot) -> String {
+    let mut element = format!(
+        "<edge id=\"{}\" source=\"{}\" target=\"{}\"",
+        escape_xml(&edge.edge_id),
+        escape_xml(&edge.from_node),
+        escape_xml(&edge.to_node)
+    );
+    if let Some(label) = &edge.label {
+        element.push_str(&format!(" label=\"{}\"", escape_xml(label)));
+    }
+    element.push('>');
+    for prop in &edge.properties {
+        element.push_str(&graphml_data("e_", prop));
+    }
+    element.push_str("</edge>\n");
+    element
+}
+
+/// GraphML attribute type for a JSON value (structured values travel as JSON text)
+fn graphml_type(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => "long",
+        serde_json::Value::Number(_) => "double",
+        _ => "string",
+    }
+}
+
+fn graphml_data(prefix: &str, prop: &Property) -> String {
+    let text = match &prop.value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    format!(
+        "<data key=\"{}{}\">{}</data>",
+        prefix,
+        escape_xml(&prop.key),
+        escape_xml(&text)
+    )
+}
+
+fn escape_xml(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Parse an import source into add-node/add-edge mutations
+///
+/// Node mutations are ordered before edge mutations so every batch only
+/// references nodes that already exist in the same or an earlier commit.
+pub fn parse_mutations<R: BufRead>(reader: R, format: TransferFormat) -> Result<Vec<Mutation>> {
+    let (nodes, edges) = match format {
+        TransferFormat::Jsonl => parse_jsonl(reader)?,
+        TransferFormat::GraphMl => parse_graphml(reader)?,
+    };
+
+    let mut mutations = Vec::with_capacity(nodes.len() + edges.len());
+    mutations.extend(nodes.into_iter().map(|n| Mutation::AddNode {
+        node_id: n.node_id,
+        labels: n.labels,
+        properties: n.properties,
+    }));
+    mutations.extend(edges.into_iter().map(|e| Mutation::AddEdge {
+        edge_id: e.edge_id,
+        from: e.from_node,
+        to: e.to_node,
+        label: e.label,
+        properties: e.properties,
+    }));
+    Ok(mutations)
+}
+
+fn parse_jsonl<R: BufRead>(reader: R) -> Result<(Vec<NodeSnapshot>, Vec<EdgeSnapshot>)> {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line.context("Failed to read JSONL input")?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let record: JsonlRecord = serde_json::from_str(trimmed)
+            .with_context(|| format!("Invalid JSONL record on line {}", idx + 1))?;
+        match record {
+            JsonlRecord::Node(node) => nodes.push(node),
+            JsonlRecord::Edge(edge) => edges.push(edge),
+        }
+    }
+    Ok((nodes, edges))
+}
+
+/// Declared GraphML attribute (from a `<key>` element)
+struct GraphMlKey {
+    name: String,
+    ty: String,
+}
+
+enum GraphMlElement {
+    Node(NodeSnapshot),
+    Edge(EdgeSnapshot),
+}
+
+fn parse_graphml<R: BufRead>(reader: R) -> Result<(Vec<NodeSnapshot>, Vec<EdgeSnapshot>)> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut keys: HashMap<String, GraphMlKey> = HashMap::new();
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    let mut current: Option<GraphMlElement> = None;
+    let mut data_key: Option<String> = None;
+    let mut text = String::new();
+
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .with_context(|| format!("Invalid GraphML at byte {}", reader.buffer_position()))?;
+        match event {
+            Event::Start(ref element) | Event::Empty(ref element) => {
+                let self_closing = matches!(event, Event::Empty(_));
+                let attrs = graphml_attrs(element)?;
+                match element.local_name().as_ref() {
+                    b"key" => {
+                        if let Some(id) = attrs.get("id") {
+                            keys.insert(
+                                id.clone(),
+                                GraphMlKey {
+                                    name: attrs.get("attr.name").unwrap_or(id).clone(),
+                                    ty: attrs
+                                        .get("attr.type")
+                                        .cloned()
+                                        .unwrap_or_else(|| "string".to_string()),
+                                },
+                            );
+                        }
+                    }
+                    b"node" => {
+                        let node_id = attrs
+                            .get("id")
+                            .cloned()
+                            .context("GraphML <node> is missing an id attribute")?;
+                        let labels = attrs
+                            .get("labels")
+                            .map(|l| split_labels(l))
+                            .unwrap_or_default();
+                        let node = NodeSnapshot {
+                            node_id,
+                            labels,
+                            properties: Vec::new(),
+                        };
+                        if self_closing {
+                            nodes.push(node);
+                        } else {
+                            current = Some(GraphMlElement::Node(node));
+                        }
+                    }
+                    b"edge" => {
+                        let from_node = attrs
+                            .get("source")
+                            .cloned()
+                            .context("GraphML <edge> is missing a source attribute")?;
+                        let to_node = attrs
+                            .get("target")
+                            .cloned()
+                            .context("GraphML <edge> is missing a target attribute")?;
+                        let edge = EdgeSnapshot {
+                            edge_id: attrs
+                                .get("id")
+                                .cloned()
+                                .unwrap_or_else(|| format!("e{}", edges.len())),
+                            from_node,
+                            to_node,
+                            label: attrs.get("label").cloned(),
+                            properties: Vec::new(),
+                        };
+                        if self_closing {
+                            edges.push(edge);
+                        } else {
+                            current = Some(GraphMlElement::Edge(edge));
+                        }
+                    }
+                    b"data" if !self_closing => {
+                        data_key = attrs.get("key").cloned();
+                        text.clear();
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"data" => {
+                    if let (Some(key), Some(element)) = (data_key.take(), current.as_mut()) {
+                        apply_graphml_data(element, &keys, &key, &text);
+                    }
+                    text.clear();
+                }
+                b"node" | b"edge" => match current.take() {
+                    Some(GraphMlElement::Node(n)) => nodes.push(n),
+                    Some(GraphMlElement::Edge(e)) => edges.push(e),
+                    None => {}
+                },
+                _ => {}
+            },
+            Event::Text(content) if data_key.is_some() => {
+                text.push_str(
+                    &content
+                        .unescape()
+                        .context("Invalid text in GraphML <data>")?,
+                );
+            }
+            Event::CData(content) if data_key.is_some() => {
+                text.push_str(&reader.decoder().decode(&content)?);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok((nodes, edges))
+}
+
+/// Attributes of a GraphML element, unescaped and keyed by their full name
+fn graphml_attrs(element: &BytesStart) -> Result<HashMap<String, String>> {
+    element
+        .attributes()
+        .map(|attr| {
+            let attr = attr.context("Malformed attribute in GraphML")?;
+            let name = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = attr
+                .unescape_value()
+                .with_context(|| format!("Invalid value for GraphML attribute '{}'", name))?
+                .into_owned();
+            Ok((name, value))
+        })
+        .collect()
+}
+
+fn split_labels(raw: &str) -> Vec<String> {
+    raw.split(':')
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn apply_graphml_data(
+    element: &mut GraphMlElement,
+    keys: &HashMap<String, GraphMlKey>,
+    key_id: &str,
+    text: &str,
+) {
+    let (name, ty) = match keys.get(key_id) {
+        Some(k) => (k.name.as_str(), k.ty.as_str()),
+        None => (key_id, "string"),
+    };
+
+    match element {
+        GraphMlElement::Node(node) if name == "labels" => {
+            if node.labels.is_empty() {
+                node.labels = split_labels(text);
+            }
+        }
+        GraphMlElement::Edge(edge) if name == "label" => {
+            if edge.label.is_none() {
+                edge.label = Some(text.to_string());
+            }
+        }
+        GraphMlElement::Node(node) => node.properties.push(Property {
+            key: name.to_string(),
+            value: graphml_value(ty, text),
+        }),
+        GraphMlElement::Edge(edge) => edge.properties.push(Property {
+            key: name.to_string(),
+            value: graphml_value(ty, text),
+        }),
+    }
+}
+
+fn graphml_value(ty: &str, text: &str) -> serde_json::Value {
+    let parsed = match ty {
+        "boolean" => text
+            .trim()
+            .parse::<bool>()
+            .ok()
+            .map(serde_json::Value::from),
+        "int" | "long" => text.trim().parse::<i64>().ok().map(serde_json::Value::from),
+        "float" | "double" => text
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| serde_json::Value::String(text.to_string()))
+}
+
+/// Split mutations into commit-sized batches
+pub fn plan_batches(mutations: Vec<Mutation>, batch_size: usize) -> Vec<Vec<Mutation>> {
+    let batch_size = batch_size.max(1);
+    let mut batches = Vec::new();
+    let mut iter = mutations.into_iter().peekable();
+    while iter.peek().is_some() {
+        batches.push(iter.by_ref().take(batch_size).collect());
+    }
+    batches
+}
+
+/// Import nodes and edges from `reader` as a chain of batched commits
+///
+/// Each batch emits a graph.commit.created:v1 event whose parent is the
+/// previous batch, and `on_progress` is called after every committed batch.
+/// On failure the envelope reports the commits that were already written so
+/// the import can be resumed from the last one via `ImportOptions::parent_commit_id`.
+pub async fn import<R, F>(
+    scope: GraphScope,
+    reader: R,
+    format: TransferFormat,
+    options: ImportOptions,
+    mut on_progress: F,
+) -> ResultEnvelope<ImportResult>
+where
+    R: BufRead,
+    F: FnMut(&ImportProgress),
+{
+    let start = std::time::Instant::now();
+
+    let mutations = match parse_mutations(reader, format) {
+        Ok(m) => m,
+        Err(e) => {
+            let builder = ResultEnvelope::builder()
+                .add_diagnostic(Diagnostic::new(
+                    DiagnosticLevel::Error,
+                    format!("Failed to parse {} input: {:#}", format.as_str(), e),
+                ))
+                .with_source_info("graph-capsule", Some("0.0.1"), None::<String>);
+
+            return builder
+                .error_with_code(
+                    format!("Import parse error: {:#}", e),
+                    "IMPORT_PARSE_FAILED",
+                )
+                .build()
+                .expect("Valid envelope");
+        }
+    };
+
+    if mutations.is_empty() {
+        let builder = ResultEnvelope::builder()
+            .add_diagnostic(Diagnostic::new(
+                DiagnosticLevel::Error,
+                "Import source contained no nodes or edges".to_string(),
+            ))
+            .with_source_info("graph-capsule", Some("0.0.1"), None::<String>);
+
+        return builder
+            .error_with_code("Import source is empty", "EMPTY_IMPORT")
+            .build()
+            .expect("Valid envelope");
+    }
+
+    let nodes_imported = mutations
+        .iter()
+        .filter(|m| matches!(m, Mutation::AddNode { .. }))
+        .count();
+    let edges_imported = mutations.len() - nodes_imported;
+    let mutations_total = mutations.len();
+
+    let batches = plan_batches(mutations, options.batch_size);
+    let batches_total = batches.len();
+    let mut parent = options.parent_commit_id.clone();
+    let mut commit_ids = Vec::with_capacity(batches_total);
+    let mut mutations_committed = 0;
+
+    for batch in batches {
+        let timestamp = Utc::now();
+        let commit_id = compute_commit_id(&scope, parent.as_deref(), &batch);
+
+        if let Err(e) =
+            events::emit_commit_created(&scope, &commit_id, parent.as_deref(), &batch, timestamp)
+                .await
+        {
+            let builder = ResultEnvelope::builder()
+                .add_diagnostic(Diagnostic::new(
+                    DiagnosticLevel::Error,
+                    format!(
+                        "Failed to emit commit event for batch {}/{}: {}",
+                        commit_ids.len() + 1,
+                        batches_total,
+                        e
+                    ),
+                ))
+                .add_diagnostic(Diagnostic::new(
+                    DiagnosticLevel::Info,
+                    format!(
+                        "{} batches committed before failure; last commit: {}",
+                        commit_ids.len(),
+                        parent.as_deref().unwrap_or("none")
+                    ),
+                ))
+                .with_source_info("graph-capsule", Some("0.0.1"), None::<String>);
+
+            return builder
+                .error_with_code(
+                    format!("Event emission error: {}", e),
+                    "EVENT_EMISSION_FAILED",
+                )
+                .build()
+                .expect("Valid envelope");
+        }
+
+        mutations_committed += batch.len();
+        commit_ids.push(commit_id.clone());
+
+        let progress = ImportProgress {
+            batches_committed: commit_ids.len(),
+            batches_total,
+            mutations_committed,
+            mutations_total,
+            last_commit_id: commit_id.clone(),
+        };
+        tracing::info!(
+            "Imported batch {}/{} ({}/{} mutations) as commit {}",
+            progress.batches_committed,
+            progress.batches_total,
+            progress.mutations_committed,
+            progress.mutations_total,
+            commit_id
+        );
+        on_progress(&progress);
+
+        parent = Some(commit_id);
+    }
+
+    let result = ImportResult {
+        format,
+        head_commit_id: parent,
+        commit_ids,
+        nodes_imported,
+        edges_imported,
+    };
+
+    let mut builder = ResultEnvelope::builder()
+        .success(result)
+        .add_diagnostic(Diagnostic::new(
+            DiagnosticLevel::Info,
+            format!(
+                "Imported {} nodes and {} edges in {} commits",
+                nodes_imported, edges_imported, batches_total
+            ),
+        ))
+        .with_source_info("graph-capsule", Some("0.0.1"), None::<String>);
+
+    let duration = start.elapsed();
+    let mut counters = HashMap::new();
+    counters.insert("mutations".to_string(), mutations_total as i64);
+    counters.insert("commits".to_string(), batches_total as i64);
+
+    builder = builder.metrics(envelope::Metrics {
+        duration: Some(envelope::DurationMetrics {
+            total_ms: Some(duration.as_secs_f64() * 1000.0),
+            phases: HashMap::new(),
+        }),
+        resources: None,
+        counters,
+        custom: None,
+    });
+
+    builder.build().expect("Valid envelope")
+}
@@ -20,6 +20,66 @@ pub async fn emit_commit_created(
     mutations: &[Mutation],
     timestamp: DateTime<Utc>,
 ) -> Result<()> {
+    let msg_id = format!(
+        "{}:{}:{}:{}",
+        scope.tenant_id, scope.project_id, scope.namespace, commit_id
+    );
+    publish_commit(
+        scope,
+        commit_id,
+        parent_commit_id,
+        mutations,
+        &timestamp.to_rfc3339(),
+        false,
+        &msg_id,
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Emit a retention snapshot: a graph.commit.created:v1 event with
+/// `snapshot: true` whose mutations are the whole graph at `commit_id`
+///
+/// The snapshot reuses the id and `ts` string of the commit it stands in for,
+/// so it sorts into the same replay position; the message id carries the
+/// publish time to get past deduplication. Returns the stream sequence of the
+/// snapshot.
+pub async fn emit_snapshot_commit(
+    scope: &GraphScope,
+    commit_id: &str,
+    parent_commit_id: Option<&str>,
+    mutations: &[Mutation],
+    ts: &str,
+) -> Result<u64> {
+    let msg_id = format!(
+        "{}:{}:{}:{}:snapshot:{}",
+        scope.tenant_id,
+        scope.project_id,
+        scope.namespace,
+        commit_id,
+        Utc::now().timestamp_micros()
+    );
+    publish_commit(
+        scope,
+        commit_id,
+        parent_commit_id,
+        mutations,
+        ts,
+        true,
+        &msg_id,
+    )
+    .await
+}
+
+async fn publish_commit(
+    scope: &GraphScope,
+    commit_id: &str,
+    parent_commit_id: Option<&str>,
+    mutations: &[Mutation],
+    ts: &str,
+    snapshot: bool,
+    msg_id: &str,
+) -> Result<u64> {
     let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
     let client = async_nats::connect(&url).await?;
     let js = jetstream::new(client);
@@ -36,7 +96,7 @@ pub async fn emit_commit_created(
         "projectId": scope.project_id,
         "namespace": scope.namespace,
         "commitId": commit_id,
-        "ts": timestamp.to_rfc3339(),
+        "ts": ts,
         "mutations": serialize_mutations(mutations),
     });
 
@@ -46,6 +106,12 @@ pub async fn emit_commit_created(
             .unwrap()
             .insert("parentCommitId".to_string(), serde_json::json!(parent));
     }
+    if snapshot {
+        payload
+            .as_object_mut()
+            .unwrap()
+            .insert("snapshot".to_string(), serde_json::json!(true));
+    }
 
     let subject = format!(
         "demon.graph.v1.{}.{}.{}.commit",
@@ -53,18 +119,15 @@ pub async fn emit_commit_created(
     );
 
     let mut headers = async_nats::HeaderMap::new();
-    let msg_id = format!(
-        "{}:{}:{}:{}",
-        scope.tenant_id, scope.project_id, scope.namespace, commit_id
-    );
-    headers.insert("Nats-Msg-Id", msg_id.as_str());
+    headers.insert("Nats-Msg-Id", msg_id);
 
-    js.publish_with_headers(subject, headers, serde_json::to_vec(&payload)?.into())
+    let ack = js
+        .publish_with_headers(subject, headers, serde_json::to_vec(&payload)?.into())
         .await?
         .await
         .context("Failed to await JetStream ack for commit event")?;
 
-    Ok(())
+    Ok(ack.sequence)
 }
 
 /// Emit graph.tag.updated:v1 event to JetStream
@@ -209,6 +272,58 @@ fn serialize_mutations(mutations: &[Mutation]) -> Vec<serde_json::Value> {
                     "edgeId": edge_id,
                 })
             }
+            Mutation::UpdateNodeProperties {
+                node_id,
+                set,
+                remove,
+            } => {
+                serde_json::json!({
+                    "op": "update-node-properties",
+                    "nodeId": node_id,
+                    "set": set,
+                    "remove": remove,
+                })
+            }
+            Mutation::UpdateEdgeProperties {
+                edge_id,
+                set,
+                remove,
+            } => {
+                serde_json::json!({
+                    "op": "update-edge-properties",
+                    "edgeId": edge_id,
+                    "set": set,
+                    "remove": remove,
+                })
+            }
+            Mutation::UpsertNode {
+                node_id,
+                labels,
+                properties,
+            } => {
+                serde_json::json!({
+                    "op": "upsert-node",
+                    "nodeId": node_id,
+                    "labels": labels,
+                    "properties": properties,
+                })
+            }
+            Mutation::UpsertEdge {
+                edge_id,
+                from,
+                to,
+                label,
+                properties,
+            } => {
+                serde_json::json!({
+                    "op": "upsert-edge",
+                    "edgeId": edge_id,
+                    "from": from,
+                    "to": to,
+                    "label": label,
+                    "properties": properties,
+                })
+            }
         })
         .collect()
 }
@@ -10,12 +10,17 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 
+pub mod bulk;
 pub mod events;
+pub mod retention;
 pub mod storage;
 pub mod types;
 
 pub use types::*;
 
+/// Most nodes a single query page may return
+pub const MAX_QUERY_LIMIT: u32 = 1000;
+
 /// Result of a commit operation
 #[derive(Serialize, Deserialize, AsEnvelope, Debug, Clone)]
 pub struct CommitResult {
@@ -41,16 +46,91 @@ pub enum TagAction {
     Delete,
 }
 
+/// Change notification emitted by `watch_tags` when a tag moves or is removed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TagChanged {
+    pub tag: String,
+    pub old_commit: Option<String>,
+    pub new_commit: Option<String>,
+    pub action: TagAction,
+    /// KV revision of the update, useful for ordering and de-duplication
+    pub revision: u64,
+}
+
+/// Commit ID scheme used for new commits; see [`compute_commit_id`]
+pub const COMMIT_ID_VERSION: u32 = 2;
+
 /// Generate deterministic SHA256 commit ID from scope, parent, and mutations
 ///
-/// Commit ID format: sha256(tenant||project||namespace||parent||sorted_mutations_json)
+/// Commit ID format (v2):
+/// sha256("graph-commit:v2"||tenant||project||namespace||graph||parent||sorted_canonical_mutations),
+/// where each mutation is hashed in the envelope's canonical JSON form (sorted
+/// keys, normalized numbers), so property maps built in a different key order
+/// or with `1.0` instead of `1` give the same ID. The version tag keeps v2
+/// preimages apart from [`compute_commit_id_v1`]; IDs already issued under v1
+/// stay valid and are accepted by [`verify_commit_id`].
 pub fn compute_commit_id(
     scope: &GraphScope,
     parent_commit_id: Option<&str>,
     mutations: &[Mutation],
 ) -> String {
+    let mut sorted_mutations: Vec<Vec<u8>> = mutations
+        .iter()
+        .map(|m| {
+            serde_json::to_value(m)
+                .map(|v| envelope::canonical_json(&v))
+                .unwrap_or_default()
+        })
+        .collect();
+    sorted_mutations.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(format!("graph-commit:v{}|", COMMIT_ID_VERSION).as_bytes());
+    hash_commit_header(&mut hasher, scope, parent_commit_id);
+    for m in sorted_mutations {
+        hasher.update(&m);
+        hasher.update(b"|");
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Commit ID under the original scheme, for commits created before v2
+///
+/// Commit ID format (v1): sha256(tenant||project||namespace||graph||parent||sorted_mutations_json)
+pub fn compute_commit_id_v1(
+    scope: &GraphScope,
+    parent_commit_id: Option<&str>,
+    mutations: &[Mutation],
+) -> String {
+    // Sort mutations for determinism (by JSON repr)
+    let mut sorted_mutations: Vec<_> = mutations
+        .iter()
+        .map(|m| serde_json::to_string(m).unwrap_or_default())
+        .collect();
+    sorted_mutations.sort();
+
     let mut hasher = Sha256::new();
+    hash_commit_header(&mut hasher, scope, parent_commit_id);
+    for m in sorted_mutations {
+        hasher.update(m.as_bytes());
+        hasher.update(b"|");
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Whether `commit_id` matches the commit under any supported scheme
+pub fn verify_commit_id(
+    commit_id: &str,
+    scope: &GraphScope,
+    parent_commit_id: Option<&str>,
+    mutations: &[Mutation],
+) -> bool {
+    commit_id == compute_commit_id(scope, parent_commit_id, mutations)
+        || commit_id == compute_commit_id_v1(scope, parent_commit_id, mutations)
+}
 
+fn hash_commit_header(hasher: &mut Sha256, scope: &GraphScope, parent_commit_id: Option<&str>) {
     // Hash scope components
     hasher.update(scope.tenant_id.as_bytes());
     hasher.update(b"|");
@@ -66,21 +146,6 @@ pub fn compute_commit_id(
         hasher.update(parent.as_bytes());
     }
     hasher.update(b"|");
-
-    // Sort mutations for determinism (by JSON repr)
-    let mut sorted_mutations: Vec<_> = mutations
-        .iter()
-        .map(|m| serde_json::to_string(m).unwrap_or_default())
-        .collect();
-    sorted_mutations.sort();
-
-    for m in sorted_mutations {
-        hasher.update(m.as_bytes());
-        hasher.update(b"|");
-    }
-
-    let result = hasher.finalize();
-    hex::encode(result)
 }
 
 /// Create a new graph by seeding an initial commit
@@ -571,6 +636,23 @@ pub async fn list_tags(scope: GraphScope) -> ResultEnvelope<Vec<TaggedCommit>> {
     builder.build().expect("Valid envelope")
 }
 
+/// Watch tag changes for the graph scope
+///
+/// Returns a stream of `TagChanged` events driven by a GRAPH_TAGS KV watch, so
+/// callers can react to tag moves without polling `list_tags`. The stream only
+/// yields updates made after the watch starts and ends when the NATS
+/// connection closes.
+pub async fn watch_tags(
+    scope: GraphScope,
+) -> anyhow::Result<impl futures_util::Stream<Item = anyhow::Result<TagChanged>>> {
+    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let client = async_nats::connect(&url).await?;
+    let js = async_nats::jetstream::new(client);
+    let kv = storage::ensure_graph_tags_kv(&js).await?;
+
+    storage::watch_tags(kv, &scope).await
+}
+
 // Query operations using graph materialization
 
 /// Retrieve a node snapshot for a given commit and node identifier
@@ -636,14 +718,28 @@ pub async fn get_node(
 /// List neighboring nodes up to the specified depth from the starting node
 ///
 /// Uses BFS to traverse the graph and find all nodes reachable within the specified depth.
+/// Results are ordered by hop count then node ID and returned one page at a time: at most
+/// `page.limit` nodes (default and ceiling [`MAX_QUERY_LIMIT`]), resuming after `page.cursor`.
+/// A warning diagnostic is attached whenever more results remain.
 pub async fn neighbors(
     scope: GraphScope,
     commit_id: String,
     node_id: String,
     depth: u32,
-) -> ResultEnvelope<Vec<NodeSnapshot>> {
+    page: PageRequest,
+) -> ResultEnvelope<NodePage> {
     let start = std::time::Instant::now();
 
+    if page.limit == Some(0) {
+        return ResultEnvelope::builder()
+            .with_source_info("graph-capsule", Some("0.0.1"), None::<String>)
+            .error_with_code("limit must be at least 1", "INVALID_LIMIT")
+            .build()
+            .expect("Valid envelope");
+    }
+    let requested_limit = page.limit.unwrap_or(MAX_QUERY_LIMIT);
+    let limit = requested_limit.min(MAX_QUERY_LIMIT);
+
     // Materialize graph at commit
     let graph = match storage::materialize_graph_at_commit(&scope, &commit_id).await {
         Ok(g) => g,
@@ -665,27 +761,59 @@ pub async fn neighbors(
         }
     };
 
-    let neighbors = graph.neighbors(&node_id, depth);
+    let neighbors =
+        match graph.neighbors_page(&node_id, depth, limit as usize, page.cursor.as_deref()) {
+            Ok(neighbors) => neighbors,
+            Err(e) => {
+                return ResultEnvelope::builder()
+                    .with_source_info("graph-capsule", Some("0.0.1"), None::<String>)
+                    .error_with_code(e.to_string(), "INVALID_CURSOR")
+                    .build()
+                    .expect("Valid envelope");
+            }
+        };
 
     let mut builder = ResultEnvelope::builder()
-        .success(neighbors.clone())
         .add_diagnostic(Diagnostic::new(
             DiagnosticLevel::Info,
             format!(
                 "Found {} neighbors within depth {} from node '{}'",
-                neighbors.len(),
-                depth,
-                node_id
+                neighbors.total, depth, node_id
             ),
         ))
         .with_source_info("graph-capsule", Some("0.0.1"), None::<String>);
 
+    if requested_limit > MAX_QUERY_LIMIT {
+        builder = builder.add_diagnostic(Diagnostic::new(
+            DiagnosticLevel::Warning,
+            format!(
+                "Requested limit {} exceeds the maximum of {}; capped",
+                requested_limit, MAX_QUERY_LIMIT
+            ),
+        ));
+    }
+    if let Some(cursor) = &neighbors.next_cursor {
+        builder = builder.add_diagnostic(Diagnostic::new(
+            DiagnosticLevel::Warning,
+            format!(
+                "Results truncated to {} of {} neighbors; pass cursor '{}' for the next page",
+                neighbors.nodes.len(),
+                neighbors.total,
+                cursor
+            ),
+        ));
+    }
+
     let duration = start.elapsed();
     let mut counters = HashMap::new();
     counters.insert("nodes_in_graph".to_string(), graph.nodes.len() as i64);
     counters.insert("edges_in_graph".to_string(), graph.edges.len() as i64);
     counters.insert("commits_replayed".to_string(), graph.commit_count as i64);
-    counters.insert("neighbors_found".to_string(), neighbors.len() as i64);
+    counters.insert("neighbors_found".to_string(), neighbors.total as i64);
+    counters.insert(
+        "neighbors_returned".to_string(),
+        neighbors.nodes.len() as i64,
+    );
     counters.insert("max_depth".to_string(), depth as i64);
 
     builder = builder.metrics(envelope::Metrics {
@@ -698,7 +826,7 @@ pub async fn neighbors(
         custom: None,
     });
 
-    builder.build().expect("Valid envelope")
+    builder.success(neighbors).build().expect("Valid envelope")
 }
 
 /// Determine whether a path exists between two nodes within the depth constraint
@@ -864,4 +992,52 @@ mod tests {
 
         assert_eq!(id1, id2, "Commit ID should be order-independent");
     }
+
+    #[test]
+    fn commit_id_uses_canonical_property_values() {
+        let scope = GraphScope {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "proj-1".to_string(),
+            namespace: "ns-1".to_string(),
+            graph_id: "graph-1".to_string(),
+        };
+        let with_value = |value: serde_json::Value| {
+            vec![Mutation::AddNode {
+                node_id: "node-1".to_string(),
+                labels: vec![],
+                properties: vec![Property {
+                    key: "replicas".to_string(),
+                    value,
+                }],
+            }]
+        };
+
+        assert_eq!(
+            compute_commit_id(&scope, None, &with_value(serde_json::json!(3))),
+            compute_commit_id(&scope, None, &with_value(serde_json::json!(3.0)))
+        );
+    }
+
+    #[test]
+    fn legacy_commit_ids_still_verify() {
+        let scope = GraphScope {
+            tenant_id: "tenant-1".to_string(),
+            project_id: "proj-1".to_string(),
+            namespace: "ns-1".to_string(),
+            graph_id: "graph-1".to_string(),
+        };
+        let mutations = vec![Mutation::AddNode {
+            node_id: "node-1".to_string(),
+            labels: vec![],
+            properties: vec![],
+        }];
+
+        let v1 = compute_commit_id_v1(&scope, None, &mutations);
+        let v2 = compute_commit_id(&scope, None, &mutations);
+
+        assert_ne!(v1, v2);
+        assert!(verify_commit_id(&v1, &scope, None, &mutations));
+        assert!(verify_commit_id(&v2, &scope, None, &mutations));
+        assert!(!verify_commit_id(&v1, &scope, Some("other"), &mutations));
+    }
 }
@@ -0,0 +1,582 @@
+//! Retention for graph commit streams
+//!
+//! Commits are never removed from GRAPH_COMMITS on their own, and every query
+//! replays a graph from its first commit. Retention compacts the history of
+//! one graph that is older than a horizon:
+//!
+//! 1. The commits to compact are the oldest commits with `ts` before
+//!    `now - horizon`, always leaving the newest `min_commits` commits alone.
+//! 2. Anchors among them are replaced by snapshot commits: the newest
+//!    compacted commit, every compacted commit a tag points at, and every
+//!    compacted commit a retained commit names as its parent. A snapshot holds
+//!    the whole graph at its anchor as `add-node`/`add-edge` mutations, is
+//!    flagged `snapshot: true` and keeps the anchor's commit id and `ts`, so
+//!    tags, parent references and queries at those commits resolve to the same
+//!    state as before.
+//! 3. Once every snapshot is acknowledged, and no tag has moved onto a commit
+//!    that is about to go, the original messages are deleted newest first.
+//!    An interrupted prune leaves an intact prefix of the old history.
+//!
+//! A dry run builds the same plan and reports it without writing anything.
+
+use crate::events;
+use crate::storage::{self, GraphStore, LoggedCommit};
+use crate::types::{GraphScope, Mutation, TaggedCommit};
+use anyhow::{Context, Result};
+use async_nats::jetstream;
+use chrono::{DateTime, Utc};
+use envelope::{AsEnvelope, Diagnostic, DiagnosticLevel, ResultEnvelope};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Most commits a retention pass reads for one graph
+const MAX_COMMITS_TO_SCAN: usize = 100_000;
+
+/// How much history to keep for a graph
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetentionPolicy {
+    /// Commits older than this are compacted
+    pub horizon_seconds: u64,
+    /// Newest commits kept as they are, whatever their age
+    pub min_commits: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            horizon_seconds: 30 * 24 * 60 * 60,
+            min_commits: 10,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Commits with a `ts` before this are eligible for compaction
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        i64::try_from(self.horizon_seconds)
+            .ok()
+            .and_then(chrono::Duration::try_seconds)
+            .and_then(|horizon| now.checked_sub_signed(horizon))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC)
+    }
+}
+
+/// A snapshot commit a retention pass writes (or would write)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    pub commit_id: String,
+    pub parent_commit_id: Option<String>,
+    pub nodes: usize,
+    pub edges: usize,
+    /// Tags pointing at this commit
+    pub tags: Vec<String>,
+}
+
+/// Result of a retention pass
+#[derive(Serialize, Deserialize, AsEnvelope, Debug, Clone)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub cutoff: DateTime<Utc>,
+    pub commits_scanned: usize,
+    /// Original commits replaced by snapshots
+    pub commits_compacted: usize,
+    pub commits_retained: usize,
+    pub snapshots: Vec<SnapshotSummary>,
+    /// Stream messages deleted; always 0 for a dry run
+    pub messages_pruned: usize,
+}
+
+/// What a retention pass will do
+#[derive(Debug, Clone)]
+pub(crate) struct CompactionPlan {
+    pub cutoff: DateTime<Utc>,
+    pub commits_scanned: usize,
+    /// Stream sequence and commit id of every compacted message, oldest first
+    pub compacted: Vec<(u64, String)>,
+    pub snapshots: Vec<PlannedSnapshot>,
+    /// Sequences of compacted anchors that are already snapshots
+    snapshot_sequences: HashSet<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct PlannedSnapshot {
+    pub commit_id: String,
+    pub parent_commit_id: Option<String>,
+    pub ts: String,
+    pub mutations: Vec<Mutation>,
+    pub nodes: usize,
+    pub edges: usize,
+    pub tags: Vec<String>,
+}
+
+impl CompactionPlan {
+    /// Nothing to compact, or the old history is already exactly its snapshots
+    pub fn is_noop(&self) -> bool {
+        self.compacted.len() == self.snapshots.len()
+            && self
+                .compacted
+                .iter()
+                .all(|(sequence, _)| self.is_snapshot(*sequence))
+    }
+
+    fn is_snapshot(&self, sequence: u64) -> bool {
+        self.snapshot_sequences.contains(&sequence)
+    }
+}
+
+/// Plan compaction of `commits` (one graph, in replay order)
+pub(crate) fn plan(
+    commits: &[LoggedCommit],
+    tags: &[TaggedCommit],
+    policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> CompactionPlan {
+    let cutoff = policy.cutoff(now);
+    let eligible = commits.len().saturating_sub(policy.min_commits);
+    let compacted_count = commits
+        .iter()
+        .take(eligible)
+        .take_while(|c| {
+            DateTime::parse_from_rfc3339(&c.event.ts)
+                .map(|ts| ts.with_timezone(&Utc) < cutoff)
+                .unwrap_or(false)
+        })
+        .count();
+    let (compacted, retained) = commits.split_at(compacted_count);
+
+    let mut tags_by_commit: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for tag in tags {
+        tags_by_commit
+            .entry(tag.commit_id.as_str())
+            .or_default()
+            .push(tag.tag.clone());
+    }
+    let parents: HashSet<&str> = retained
+        .iter()
+        .filter_map(|c| c.event.parent_commit_id.as_deref())
+        .collect();
+
+    // An id can appear twice while an interrupted pass is cleaned up; queries
+    // stop at the first occurrence, so that is the state to keep
+    let mut anchored = HashSet::new();
+    let mut anchors = HashSet::new();
+    for (index, commit) in compacted.iter().enumerate() {
+        let id = commit.event.commit_id.as_str();
+        let is_anchor =
+            index + 1 == compacted.len() || tags_by_commit.contains_key(id) || parents.contains(id);
+        if is_anchor && anchored.insert(id) {
+            anchors.insert(index);
+        }
+    }
+
+    let mut graph = GraphStore::new();
+    let mut snapshots: Vec<PlannedSnapshot> = Vec::new();
+    let mut snapshot_sequences = HashSet::new();
+    for (index, commit) in compacted.iter().enumerate() {
+        graph.apply_commit(&commit.event);
+        if !anchors.contains(&index) {
+            continue;
+        }
+        if commit.event.snapshot {
+            snapshot_sequences.insert(commit.sequence);
+        }
+        let mut tags = tags_by_commit
+            .get(commit.event.commit_id.as_str())
+            .cloned()
+            .unwrap_or_default();
+        tags.sort();
+        snapshots.push(PlannedSnapshot {
+            commit_id: commit.event.commit_id.clone(),
+            parent_commit_id: snapshots.last().map(|s| s.commit_id.clone()),
+            ts: commit.event.ts.clone(),
+            mutations: graph.snapshot_mutations(),
+            nodes: graph.nodes.len(),
+            edges: graph.edges.len(),
+            tags,
+        });
+    }
+
+    CompactionPlan {
+        cutoff,
+        commits_scanned: commits.len(),
+        compacted: compacted
+            .iter()
+            .map(|c| (c.sequence, c.event.commit_id.clone()))
+            .collect(),
+        snapshots,
+        snapshot_sequences,
+    }
+}
+
+/// Compact the commit history of `scope` older than the policy horizon
+///
+/// With `dry_run` the envelope reports the snapshots that would be written and
+/// the commits that would be pruned, and nothing is changed.
+pub async fn compact(
+    scope: GraphScope,
+    policy: RetentionPolicy,
+    dry_run: bool,
+) -> ResultEnvelope<RetentionReport> {
+    let start = std::time::Instant::now();
+
+    let (plan, report) = match run(&scope, &policy, dry_run).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            let builder = ResultEnvelope::builder()
+                .add_diagnostic(Diagnostic::new(
+                    DiagnosticLevel::Error,
+                    format!("Retention failed for graph {}: {:#}", scope.graph_id, e),
+                ))
+                .with_source_info("graph-capsule", Some("0.0.1"), None::<String>);
+
+            return builder
+                .error_with_code(format!("Retention error: {:#}", e), "RETENTION_FAILED")
+                .build()
+                .expect("Valid envelope");
+        }
+    };
+
+    let summary = if plan.is_noop() {
+        format!(
+            "No commits before {} to compact ({} scanned)",
+            plan.cutoff.to_rfc3339(),
+            plan.commits_scanned
+        )
+    } else if dry_run {
+        format!(
+            "Dry run: would replace {} commits before {} with {} snapshots",
+            report.commits_compacted,
+            plan.cutoff.to_rfc3339(),
+            report.snapshots.len()
+        )
+    } else {
+        format!(
+            "Replaced {} commits before {} with {} snapshots; pruned {} messages",
+            report.commits_compacted,
+            plan.cutoff.to_rfc3339(),
+            report.snapshots.len(),
+            report.messages_pruned
+        )
+    };
+
+    let mut counters = HashMap::new();
+    counters.insert("commits_scanned".to_string(), report.commits_scanned as i64);
+    counters.insert(
+        "commits_compacted".to_string(),
+        report.commits_compacted as i64,
+    );
+    counters.insert("messages_pruned".to_string(), report.messages_pruned as i64);
+
+    ResultEnvelope::builder()
+        .success(report)
+        .add_diagnostic(Diagnostic::new(DiagnosticLevel::Info, summary))
+        .with_source_info("graph-capsule", Some("0.0.1"), None::<String>)
+        .metrics(envelope::Metrics {
+            duration: Some(envelope::DurationMetrics {
+                total_ms: Some(start.elapsed().as_secs_f64() * 1000.0),
+                phases: HashMap::new(),
+            }),
+            resources: None,
+            counters,
+            custom: None,
+        })
+        .build()
+        .expect("Valid envelope")
+}
+
+async fn run(
+    scope: &GraphScope,
+    policy: &RetentionPolicy,
+    dry_run: bool,
+) -> Result<(CompactionPlan, RetentionReport)> {
+    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let client = async_nats::connect(&url).await?;
+    let js = jetstream::new(client);
+    let stream = js
+        .get_stream("GRAPH_COMMITS")
+        .await
+        .context("Failed to get GRAPH_COMMITS stream")?;
+    let kv = storage::ensure_graph_tags_kv(&js).await?;
+
+    let commits = storage::load_commits(&stream, scope, MAX_COMMITS_TO_SCAN).await?;
+    let tags = storage::list_tags(&kv, scope).await?;
+    let plan = plan(&commits, &tags, policy, Utc::now());
+
+    let mut report = RetentionReport {
+        dry_run,
+        cutoff: plan.cutoff,
+        commits_scanned: plan.commits_scanned,
+        commits_compacted: 0,
+        commits_retained: plan.commits_scanned,
+        snapshots: Vec::new(),
+        messages_pruned: 0,
+    };
+    if plan.is_noop() {
+        return Ok((plan, report));
+    }
+    report.commits_compacted = plan.compacted.len();
+    report.commits_retained = plan.commits_scanned - plan.compacted.len();
+    report.snapshots = plan
+        .snapshots
+        .iter()
+        .map(|s| SnapshotSummary {
+            commit_id: s.commit_id.clone(),
+            parent_commit_id: s.parent_commit_id.clone(),
+            nodes: s.nodes,
+            edges: s.edges,
+            tags: s.tags.clone(),
+        })
+        .collect();
+    if dry_run {
+        return Ok((plan, report));
+    }
+
+    for snapshot in &plan.snapshots {
+        let sequence = events::emit_snapshot_commit(
+            scope,
+            &snapshot.commit_id,
+            snapshot.parent_commit_id.as_deref(),
+            &snapshot.mutations,
+            &snapshot.ts,
+        )
+        .await
+        .with_context(|| format!("Failed to write snapshot of {}", snapshot.commit_id))?;
+        tracing::info!(
+            "Wrote snapshot of commit {} for graph {} at sequence {}",
+            snapshot.commit_id,
+            scope.graph_id,
+            sequence
+        );
+    }
+
+    // A tag moved onto a commit without a snapshot since planning would be
+    // left dangling; the snapshots written so far are harmless, so stop here
+    let anchors: HashSet<&str> = plan
+        .snapshots
+        .iter()
+        .map(|s| s.commit_id.as_str())
+        .collect();
+    let doomed: HashSet<&str> = plan
+        .compacted
+        .iter()
+        .map(|(_, id)| id.as_str())
+        .filter(|id| !anchors.contains(id))
+        .collect();
+    if let Some(tag) = storage::list_tags(&kv, scope)
+        .await?
+        .into_iter()
+        .find(|t| doomed.contains(t.commit_id.as_str()))
+    {
+        anyhow::bail!(
+            "Tag '{}' moved to commit {} during retention; nothing was pruned, run it again",
+            tag.tag,
+            tag.commit_id
+        );
+    }
+
+    for (sequence, commit_id) in plan.compacted.iter().rev() {
+        let deleted = stream.delete_message(*sequence).await.with_context(|| {
+            format!(
+                "Failed to prune commit {} at sequence {} ({} of {} pruned)",
+                commit_id,
+                sequence,
+                report.messages_pruned,
+                plan.compacted.len()
+            )
+        })?;
+        if deleted {
+            report.messages_pruned += 1;
+        }
+    }
+
+    tracing::info!(
+        "Compacted {} commits of graph {} into {} snapshots",
+        report.commits_compacted,
+        scope.graph_id,
+        report.snapshots.len()
+    );
+    Ok((plan, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::CommitEvent;
+    use serde_json::json;
+
+    fn scope_commit(
+        sequence: u64,
+        id: &str,
+        parent: Option<&str>,
+        ts: &str,
+        mutations: Vec<serde_json::Value>,
+    ) -> LoggedCommit {
+        LoggedCommit {
+            sequence,
+            event: CommitEvent {
+                event: "graph.commit.created:v1".to_string(),
+                graph_id: "g".to_string(),
+                tenant_id: "t".to_string(),
+                project_id: "p".to_string(),
+                namespace: "n".to_string(),
+                commit_id: id.to_string(),
+                parent_commit_id: parent.map(str::to_string),
+                ts: ts.to_string(),
+                mutations,
+                snapshot: false,
+            },
+        }
+    }
+
+    fn add(node: &str) -> serde_json::Value {
+        json!({ "op": "add-node", "nodeId": node, "labels": [] })
+    }
+
+    fn remove(node: &str) -> serde_json::Value {
+        json!({ "op": "remove-node", "nodeId": node })
+    }
+
+    fn tag(name: &str, commit: &str) -> TaggedCommit {
+        TaggedCommit {
+            tag: name.to_string(),
+            commit_id: commit.to_string(),
+            timestamp: "2025-06-01T00:00:00Z".to_string(),
+        }
+    }
+
+    /// c1..c4 in January, c5 in June
+    fn history() -> Vec<LoggedCommit> {
+        vec![
+            scope_commit(1, "c1", None, "2025-01-01T00:00:00Z", vec![add("a")]),
+            scope_commit(2, "c2", Some("c1"), "2025-01-02T00:00:00Z", vec![add("b")]),
+            scope_commit(
+                3,
+                "c3",
+                Some("c2"),
+                "2025-01-03T00:00:00Z",
+                vec![remove("a")],
+            ),
+            scope_commit(4, "c4", Some("c3"), "2025-01-04T00:00:00Z", vec![add("c")]),
+            scope_commit(5, "c5", Some("c4"), "2025-06-01T00:00:00Z", vec![add("d")]),
+        ]
+    }
+
+    fn policy(min_commits: usize) -> RetentionPolicy {
+        RetentionPolicy {
+            horizon_seconds: 30 * 24 * 60 * 60,
+            min_commits,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2025-06-15T00:00:00Z".parse().unwrap()
+    }
+
+    fn node_ids(snapshot: &PlannedSnapshot) -> Vec<String> {
+        snapshot
+            .mutations
+            .iter()
+            .filter_map(|m| match m {
+                Mutation::AddNode { node_id, .. } => Some(node_id.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compacts_old_commits_into_snapshots_at_tags_and_boundary() {
+        let plan = plan(&history(), &[tag("release", "c2")], &policy(1), now());
+
+        let compacted: Vec<u64> = plan.compacted.iter().map(|(seq, _)| *seq).collect();
+        assert_eq!(compacted, vec![1, 2, 3, 4]);
+        let ids: Vec<&str> = plan
+            .snapshots
+            .iter()
+            .map(|s| s.commit_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["c2", "c4"]);
+
+        let release = &plan.snapshots[0];
+        assert_eq!(node_ids(release), vec!["a", "b"]);
+        assert_eq!(release.tags, vec!["release".to_string()]);
+        assert_eq!(release.parent_commit_id, None);
+        assert_eq!(release.ts, "2025-01-02T00:00:00Z");
+
+        let boundary = &plan.snapshots[1];
+        assert_eq!(node_ids(boundary), vec!["b", "c"]);
+        assert_eq!(boundary.parent_commit_id.as_deref(), Some("c2"));
+        assert!(!plan.is_noop());
+    }
+
+    #[test]
+    fn min_commits_and_horizon_limit_compaction() {
+        let kept = plan(&history(), &[], &policy(3), now());
+        let compacted: Vec<&str> = kept.compacted.iter().map(|(_, id)| id.as_str()).collect();
+        assert_eq!(compacted, vec!["c1", "c2"]);
+
+        let recent = RetentionPolicy {
+            horizon_seconds: 365 * 24 * 60 * 60,
+            min_commits: 0,
+        };
+        assert!(plan(&history(), &[], &recent, now()).is_noop());
+    }
+
+    #[test]
+    fn parents_of_retained_commits_are_anchored() {
+        let mut commits = history();
+        // A branch off c1 made after the horizon
+        commits.push(scope_commit(
+            6,
+            "b1",
+            Some("c1"),
+            "2025-06-02T00:00:00Z",
+            vec![add("x")],
+        ));
+        let plan = plan(&commits, &[], &policy(0), now());
+        let ids: Vec<&str> = plan
+            .snapshots
+            .iter()
+            .map(|s| s.commit_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["c1", "c4"]);
+    }
+
+    #[test]
+    fn replay_restarts_at_snapshots_and_second_pass_is_noop() {
+        let first = plan(&history(), &[tag("release", "c2")], &policy(1), now());
+
+        // The log after the first pass: snapshots, then the retained commit
+        let mut compacted: Vec<LoggedCommit> = first
+            .snapshots
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let mut commit = scope_commit(
+                    10 + i as u64,
+                    &s.commit_id,
+                    s.parent_commit_id.as_deref(),
+                    &s.ts,
+                    s.mutations
+                        .iter()
+                        .map(|m| serde_json::to_value(m).unwrap())
+                        .collect(),
+                );
+                commit.event.snapshot = true;
+                commit
+            })
+            .collect();
+        compacted.push(history().pop().unwrap());
+
+        let mut graph = GraphStore::new();
+        for commit in &compacted {
+            graph.apply_commit(&commit.event);
+        }
+        let mut nodes: Vec<&String> = graph.nodes.keys().collect();
+        nodes.sort();
+        assert_eq!(nodes, vec!["b", "c", "d"]);
+
+        let second = plan(&compacted, &[tag("release", "c2")], &policy(1), now());
+        assert!(second.is_noop());
+    }
+}
@@ -3,11 +3,18 @@
 //! This module provides helpers to interact with graph storage (GRAPH_COMMITS stream
 //! and GRAPH_TAGS KV bucket), including graph materialization from commit history.
 
-use crate::types::{EdgeSnapshot, GraphScope, Mutation, NodeSnapshot, TaggedCommit};
+use crate::types::{
+    EdgeSnapshot, GraphScope, Mutation, NodePage, NodeSnapshot, Property, TaggedCommit,
+};
+use crate::{TagAction, TagChanged};
 use anyhow::{Context, Result};
-use async_nats::jetstream::{self, consumer::DeliverPolicy, kv::Store};
+use async_nats::jetstream::{
+    self,
+    consumer::DeliverPolicy,
+    kv::{Operation, Store},
+};
 use chrono::Utc;
-use futures_util::{StreamExt, TryStreamExt};
+use futures_util::{Stream, StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::Duration;
@@ -111,10 +118,7 @@ pub async fn delete_tag(kv: &Store, scope: &GraphScope, tag: &str) -> Result<Opt
 ///
 /// Scans KV bucket for keys matching the scope prefix and returns TaggedCommit entries.
 pub async fn list_tags(kv: &Store, scope: &GraphScope) -> Result<Vec<TaggedCommit>> {
-    let prefix = format!(
-        "{}/{}/{}/{}/",
-        scope.tenant_id, scope.project_id, scope.namespace, scope.graph_id
-    );
+    let prefix = tag_prefix(scope);
 
     let mut keys = kv.keys().await.context("Failed to list KV keys")?;
 
@@ -149,10 +153,147 @@ pub async fn list_tags(kv: &Store, scope: &GraphScope) -> Result<Vec<TaggedCommi
     Ok(tags)
 }
 
+/// Build the KV key prefix shared by all tags within a scope
+fn tag_prefix(scope: &GraphScope) -> String {
+    format!(
+        "{}/{}/{}/{}/",
+        scope.tenant_id, scope.project_id, scope.namespace, scope.graph_id
+    )
+}
+
+/// Tracks the last known commit per tag so watch events can report both
+/// sides of a tag move
+#[derive(Debug, Clone, Default)]
+pub struct TagWatchState {
+    prefix: String,
+    current: HashMap<String, String>,
+}
+
+impl TagWatchState {
+    /// Create watch state seeded with the tags that exist when the watch starts
+    pub fn new(scope: &GraphScope, initial: &[TaggedCommit]) -> Self {
+        Self {
+            prefix: tag_prefix(scope),
+            current: initial
+                .iter()
+                .map(|t| (t.tag.clone(), t.commit_id.clone()))
+                .collect(),
+        }
+    }
+
+    /// Apply a KV update and return the resulting change, if any
+    ///
+    /// Keys outside the scope and puts that do not move the tag are ignored.
+    pub fn apply(
+        &mut self,
+        key: &str,
+        action: TagAction,
+        commit_id: Option<String>,
+        revision: u64,
+    ) -> Option<TagChanged> {
+        let tag = key.strip_prefix(&self.prefix)?;
+        if tag.is_empty() || tag.contains('/') {
+            return None;
+        }
+
+        let old_commit = match (action, &commit_id) {
+            (TagAction::Set, Some(new)) => {
+                let old = self.current.insert(tag.to_string(), new.clone());
+                if old.as_deref() == Some(new.as_str()) {
+                    return None;
+                }
+                old
+            }
+            (TagAction::Set, None) => return None,
+            (TagAction::Delete, _) => self.current.remove(tag),
+        };
+
+        Some(TagChanged {
+            tag: tag.to_string(),
+            old_commit,
+            new_commit: match action {
+                TagAction::Set => commit_id,
+                TagAction::Delete => None,
+            },
+            action,
+            revision,
+        })
+    }
+}
+
+/// Watch tag updates for a scope
+///
+/// The KV watch is started before the initial tag listing so no update is
+/// lost between the two; only changes made after this call are yielded. The
+/// watch runs on its own task, which owns `kv` and stops once the returned
+/// stream is dropped.
+pub async fn watch_tags(
+    kv: Store,
+    scope: &GraphScope,
+) -> Result<impl Stream<Item = Result<TagChanged>>> {
+    let scope = scope.clone();
+    let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+    let (tx, rx) = tokio::sync::mpsc::channel(64);
+
+    tokio::spawn(async move {
+        let started = async {
+            let watch = kv
+                .watch_all()
+                .await
+                .context("Failed to watch GRAPH_TAGS KV bucket")?;
+            let initial = list_tags(&kv, &scope).await?;
+            Ok::<_, anyhow::Error>((watch, TagWatchState::new(&scope, &initial)))
+        };
+        let (mut watch, mut state) = match started.await {
+            Ok(started) => {
+                let _ = ready_tx.send(Ok(()));
+                started
+            }
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+                return;
+            }
+        };
+
+        loop {
+            let entry = tokio::select! {
+                _ = tx.closed() => return,
+                entry = watch.next() => entry,
+            };
+            let change = match entry {
+                None => return,
+                Some(Ok(entry)) => {
+                    let (action, commit_id) = match entry.operation {
+                        Operation::Put => {
+                            (TagAction::Set, String::from_utf8(entry.value.to_vec()).ok())
+                        }
+                        Operation::Delete | Operation::Purge => (TagAction::Delete, None),
+                    };
+                    match state.apply(&entry.key, action, commit_id, entry.revision) {
+                        Some(change) => Ok(change),
+                        None => continue,
+                    }
+                }
+                Some(Err(e)) => Err(anyhow::anyhow!("GRAPH_TAGS watch error: {}", e)),
+            };
+            if tx.send(change).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    ready_rx
+        .await
+        .context("GRAPH_TAGS watch task stopped before starting")??;
+    Ok(futures_util::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|change| (change, rx))
+    }))
+}
+
 /// Commit event payload from GRAPH_COMMITS stream (internal representation)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct CommitEvent {
+pub(crate) struct CommitEvent {
     pub event: String,
     pub graph_id: String,
     pub tenant_id: String,
@@ -162,6 +303,17 @@ struct CommitEvent {
     pub parent_commit_id: Option<String>,
     pub ts: String,
     pub mutations: Vec<serde_json::Value>,
+    /// Written by retention: the mutations are the whole graph, so replay
+    /// starts over from an empty graph at this commit
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub snapshot: bool,
+}
+
+/// A commit event and its GRAPH_COMMITS stream sequence
+#[derive(Debug, Clone)]
+pub(crate) struct LoggedCommit {
+    pub sequence: u64,
+    pub event: CommitEvent,
 }
 
 /// Materialized graph state reconstructed from commits
@@ -258,16 +410,183 @@ impl GraphStore {
             Mutation::RemoveEdge { edge_id } => {
                 self.edges.remove(edge_id);
             }
+            Mutation::UpdateNodeProperties {
+                node_id,
+                set,
+                remove,
+            } => {
+                if let Some(node) = self.nodes.get_mut(node_id) {
+                    patch_properties(&mut node.properties, set, remove);
+                }
+            }
+            Mutation::UpdateEdgeProperties {
+                edge_id,
+                set,
+                remove,
+            } => {
+                if let Some(edge) = self.edges.get_mut(edge_id) {
+                    patch_properties(&mut edge.properties, set, remove);
+                }
+            }
+            Mutation::UpsertNode {
+                node_id,
+                labels,
+                properties,
+            } => {
+                let node = self
+                    .nodes
+                    .entry(node_id.clone())
+                    .or_insert_with(|| NodeSnapshot {
+                        node_id: node_id.clone(),
+                        labels: Vec::new(),
+                        properties: Vec::new(),
+                    });
+                for label in labels {
+                    if !node.labels.contains(label) {
+                        node.labels.push(label.clone());
+                    }
+                }
+                patch_properties(&mut node.properties, properties, &[]);
+            }
+            Mutation::UpsertEdge {
+                edge_id,
+                from,
+                to,
+                label,
+                properties,
+            } => {
+                let edge = self
+                    .edges
+                    .entry(edge_id.clone())
+                    .or_insert_with(|| EdgeSnapshot {
+                        edge_id: edge_id.clone(),
+                        from_node: from.clone(),
+                        to_node: to.clone(),
+                        label: None,
+                        properties: Vec::new(),
+                    });
+                edge.from_node = from.clone();
+                edge.to_node = to.clone();
+                if label.is_some() {
+                    edge.label = label.clone();
+                }
+                patch_properties(&mut edge.properties, properties, &[]);
+            }
         }
     }
 
+    /// Replay one commit; a snapshot commit replaces the whole graph
+    pub(crate) fn apply_commit(&mut self, event: &CommitEvent) {
+        if event.snapshot {
+            self.nodes.clear();
+            self.edges.clear();
+        }
+        for mutation_json in &event.mutations {
+            match serde_json::from_value::<Mutation>(mutation_json.clone()) {
+                Ok(mutation) => {
+                    self.apply_mutation(&mutation);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to deserialize mutation in commit {}: {}. JSON: {}",
+                        event.commit_id,
+                        e,
+                        mutation_json
+                    );
+                }
+            }
+        }
+        self.commit_count += 1;
+    }
+
+    /// The whole graph as `add-node` then `add-edge` mutations, sorted by ID
+    pub fn snapshot_mutations(&self) -> Vec<Mutation> {
+        let mut nodes: Vec<&NodeSnapshot> = self.nodes.values().collect();
+        nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        let mut edges: Vec<&EdgeSnapshot> = self.edges.values().collect();
+        edges.sort_by(|a, b| a.edge_id.cmp(&b.edge_id));
+
+        let mut mutations = Vec::with_capacity(nodes.len() + edges.len());
+        mutations.extend(nodes.into_iter().map(|n| Mutation::AddNode {
+            node_id: n.node_id.clone(),
+            labels: n.labels.clone(),
+            properties: n.properties.clone(),
+        }));
+        mutations.extend(edges.into_iter().map(|e| Mutation::AddEdge {
+            edge_id: e.edge_id.clone(),
+            from: e.from_node.clone(),
+            to: e.to_node.clone(),
+            label: e.label.clone(),
+            properties: e.properties.clone(),
+        }));
+        mutations
+    }
+
     /// Get a node by ID
     pub fn get_node(&self, node_id: &str) -> Option<NodeSnapshot> {
         self.nodes.get(node_id).cloned()
     }
 
     /// Find neighbors of a node up to a given depth using BFS
+    ///
+    /// Nodes are ordered by hop count, then node ID, so repeated queries at the
+    /// same commit return the same sequence.
     pub fn neighbors(&self, start_node_id: &str, max_depth: u32) -> Vec<NodeSnapshot> {
+        self.neighbors_by_depth(start_node_id, max_depth)
+            .into_iter()
+            .map(|(_, node)| node)
+            .collect()
+    }
+
+    /// One page of [`GraphStore::neighbors`], starting after `cursor`
+    ///
+    /// The cursor is the `nextCursor` of the previous page; it encodes the
+    /// position of the last node returned, so it stays valid for the commit.
+    pub fn neighbors_page(
+        &self,
+        start_node_id: &str,
+        max_depth: u32,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<NodePage> {
+        let after = cursor.map(parse_cursor).transpose()?;
+        let all = self.neighbors_by_depth(start_node_id, max_depth);
+        let total = all.len();
+
+        let mut remaining = all
+            .into_iter()
+            .filter(|(depth, node)| match &after {
+                Some((after_depth, after_id)) => {
+                    (*depth, node.node_id.as_str()) > (*after_depth, after_id.as_str())
+                }
+                None => true,
+            })
+            .peekable();
+
+        let mut nodes = Vec::with_capacity(limit.min(total));
+        let mut last = None;
+        while nodes.len() < limit {
+            let Some((depth, node)) = remaining.next() else {
+                break;
+            };
+            last = Some(format!("{}:{}", depth, node.node_id));
+            nodes.push(node);
+        }
+
+        let next_cursor = if remaining.peek().is_some() {
+            last
+        } else {
+            None
+        };
+        Ok(NodePage {
+            nodes,
+            next_cursor,
+            total,
+        })
+    }
+
+    /// BFS from `start_node_id`, returning `(hops, node)` sorted by hops then node ID
+    fn neighbors_by_depth(&self, start_node_id: &str, max_depth: u32) -> Vec<(u32, NodeSnapshot)> {
         let mut visited = HashSet::new();
         let mut queue = VecDeque::new();
         let mut result = Vec::new();
@@ -285,7 +604,7 @@ impl GraphStore {
             if depth > 0 {
                 // Don't include the start node itself
                 if let Some(node) = self.nodes.get(&node_id) {
-                    result.push(node.clone());
+                    result.push((depth, node.clone()));
                 }
             }
 
@@ -310,6 +629,9 @@ impl GraphStore {
             }
         }
 
+        result.sort_by(|(a_depth, a), (b_depth, b)| {
+            a_depth.cmp(b_depth).then_with(|| a.node_id.cmp(&b.node_id))
+        });
         result
     }
 
@@ -358,6 +680,26 @@ impl GraphStore {
     }
 }
 
+/// Split a `<hops>:<nodeId>` page cursor
+fn parse_cursor(cursor: &str) -> Result<(u32, String)> {
+    cursor
+        .split_once(':')
+        .and_then(|(depth, node_id)| Some((depth.parse().ok()?, node_id.to_string())))
+        .filter(|(_, node_id)| !node_id.is_empty())
+        .with_context(|| format!("Invalid page cursor '{}'", cursor))
+}
+
+/// Overwrite (or append) each property in `set` by key, then drop the keys in `remove`
+fn patch_properties(properties: &mut Vec<Property>, set: &[Property], remove: &[String]) {
+    for property in set {
+        match properties.iter_mut().find(|p| p.key == property.key) {
+            Some(existing) => existing.value = property.value.clone(),
+            None => properties.push(property.clone()),
+        }
+    }
+    properties.retain(|p| !remove.contains(&p.key));
+}
+
 impl Default for GraphStore {
     fn default() -> Self {
         Self::new()
@@ -367,37 +709,18 @@ impl Default for GraphStore {
 /// Maximum number of commits to replay (safety limit)
 const MAX_COMMITS_TO_REPLAY: usize = 10_000;
 
-/// Materialize graph state up to a given commit by replaying all commits in order
-///
-/// This function fetches commits from the GRAPH_COMMITS stream and replays them
-/// in chronological order to reconstruct the graph state at the specified commit.
-pub async fn materialize_graph_at_commit(
+/// Load up to `limit` commits of one graph from GRAPH_COMMITS in replay
+/// order (by timestamp, then stream order)
+pub(crate) async fn load_commits(
+    stream: &jetstream::stream::Stream,
     scope: &GraphScope,
-    target_commit_id: &str,
-) -> Result<GraphStore> {
-    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
-    let client = async_nats::connect(&url).await?;
-    let js = jetstream::new(client);
-
-    let stream = js
-        .get_stream("GRAPH_COMMITS")
-        .await
-        .context("Failed to get GRAPH_COMMITS stream")?;
-
+    limit: usize,
+) -> Result<Vec<LoggedCommit>> {
     let subject = format!(
         "demon.graph.v1.{}.{}.{}.commit",
         scope.tenant_id, scope.project_id, scope.namespace
     );
 
-    tracing::debug!(
-        "Materializing graph for scope {}/{}/{}/{} at commit {}",
-        scope.tenant_id,
-        scope.project_id,
-        scope.namespace,
-        scope.graph_id,
-        target_commit_id
-    );
-
     // Create consumer to fetch commits
     let consumer = stream
         .create_consumer(jetstream::consumer::pull::Config {
@@ -412,7 +735,7 @@ pub async fn materialize_graph_at_commit(
     // Fetch all commits up to limit
     let mut batch = consumer
         .batch()
-        .max_messages(MAX_COMMITS_TO_REPLAY)
+        .max_messages(limit)
         .expires(Duration::from_secs(10))
         .messages()
         .await?;
@@ -421,6 +744,10 @@ pub async fn materialize_graph_at_commit(
 
     while let Some(result) = batch.next().await {
         let msg = result.map_err(|e| anyhow::anyhow!("Failed to fetch message: {}", e))?;
+        let sequence = msg
+            .info()
+            .map_err(|e| anyhow::anyhow!("Failed to read message info: {}", e))?
+            .stream_sequence;
 
         // Parse event
         match serde_json::from_slice::<CommitEvent>(&msg.payload) {
@@ -434,7 +761,7 @@ pub async fn materialize_graph_at_commit(
                             event.commit_id,
                             event.graph_id
                         );
-                        commits.push(event);
+                        commits.push(LoggedCommit { sequence, event });
                     } else {
                         tracing::debug!(
                             "Skipping commit for different graph: {} != {}",
@@ -456,40 +783,55 @@ pub async fn materialize_graph_at_commit(
         scope.graph_id
     );
 
-    if commits.len() >= MAX_COMMITS_TO_REPLAY {
+    if commits.len() >= limit {
         anyhow::bail!(
             "Commit count ({}) exceeds safety limit ({}). Graph too large for replay.",
             commits.len(),
-            MAX_COMMITS_TO_REPLAY
+            limit
         );
     }
 
     // Sort commits by timestamp (chronological order)
-    commits.sort_by(|a, b| a.ts.cmp(&b.ts));
+    commits.sort_by(|a, b| a.event.ts.cmp(&b.event.ts));
+
+    Ok(commits)
+}
+
+/// Materialize graph state up to a given commit by replaying all commits in order
+///
+/// This function fetches commits from the GRAPH_COMMITS stream and replays them
+/// in chronological order to reconstruct the graph state at the specified commit.
+/// Replay starts over at each snapshot commit left by retention.
+pub async fn materialize_graph_at_commit(
+    scope: &GraphScope,
+    target_commit_id: &str,
+) -> Result<GraphStore> {
+    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let client = async_nats::connect(&url).await?;
+    let js = jetstream::new(client);
+
+    let stream = js
+        .get_stream("GRAPH_COMMITS")
+        .await
+        .context("Failed to get GRAPH_COMMITS stream")?;
+
+    tracing::debug!(
+        "Materializing graph for scope {}/{}/{}/{} at commit {}",
+        scope.tenant_id,
+        scope.project_id,
+        scope.namespace,
+        scope.graph_id,
+        target_commit_id
+    );
+
+    let commits = load_commits(&stream, scope, MAX_COMMITS_TO_REPLAY).await?;
 
     // Build graph state by replaying commits up to target
     let mut store = GraphStore::new();
     let mut found_target = false;
 
-    for event in commits {
-        // Parse mutations and apply them
-        for mutation_json in &event.mutations {
-            match serde_json::from_value::<Mutation>(mutation_json.clone()) {
-                Ok(mutation) => {
-                    store.apply_mutation(&mutation);
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to deserialize mutation in commit {}: {}. JSON: {}",
-                        event.commit_id,
-                        e,
-                        mutation_json
-                    );
-                }
-            }
-        }
-
-        store.commit_count += 1;
+    for LoggedCommit { event, .. } in commits {
+        store.apply_commit(&event);
 
         // Stop if we've reached the target commit
         if event.commit_id == target_commit_id {
@@ -518,3 +860,205 @@ pub async fn materialize_graph_at_commit(
 
     Ok(store)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn prop(key: &str, value: serde_json::Value) -> Property {
+        Property {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    fn seeded() -> GraphStore {
+        let mut store = GraphStore::new();
+        for mutation in [
+            Mutation::AddNode {
+                node_id: "a".to_string(),
+                labels: vec!["Article".to_string()],
+                properties: vec![prop("title", json!("Home")), prop("status", json!("draft"))],
+            },
+            Mutation::AddNode {
+                node_id: "b".to_string(),
+                labels: vec![],
+                properties: vec![],
+            },
+            Mutation::AddEdge {
+                edge_id: "e".to_string(),
+                from: "a".to_string(),
+                to: "b".to_string(),
+                label: Some("related".to_string()),
+                properties: vec![prop("weight", json!(0.5))],
+            },
+        ] {
+            store.apply_mutation(&mutation);
+        }
+        store
+    }
+
+    #[test]
+    fn update_node_properties_patches_only_named_keys() {
+        let mut store = seeded();
+
+        store.apply_mutation(&Mutation::UpdateNodeProperties {
+            node_id: "a".to_string(),
+            set: vec![prop("status", json!("published")), prop("rank", json!(1))],
+            remove: vec!["title".to_string()],
+        });
+
+        let node = store.get_node("a").unwrap();
+        assert_eq!(node.labels, vec!["Article".to_string()]);
+        assert_eq!(
+            node.properties,
+            vec![prop("status", json!("published")), prop("rank", json!(1))]
+        );
+    }
+
+    #[test]
+    fn update_edge_properties_keeps_endpoints_and_label() {
+        let mut store = seeded();
+
+        store.apply_mutation(&Mutation::UpdateEdgeProperties {
+            edge_id: "e".to_string(),
+            set: vec![prop("weight", json!(0.9))],
+            remove: vec![],
+        });
+
+        let edge = &store.edges["e"];
+        assert_eq!(edge.from_node, "a");
+        assert_eq!(edge.label.as_deref(), Some("related"));
+        assert_eq!(edge.properties, vec![prop("weight", json!(0.9))]);
+    }
+
+    #[test]
+    fn property_updates_on_missing_elements_are_ignored() {
+        let mut store = seeded();
+
+        store.apply_mutation(&Mutation::UpdateNodeProperties {
+            node_id: "missing".to_string(),
+            set: vec![prop("x", json!(1))],
+            remove: vec![],
+        });
+
+        assert!(store.get_node("missing").is_none());
+        assert_eq!(store.nodes.len(), 2);
+    }
+
+    #[test]
+    fn upsert_node_creates_then_merges() {
+        let mut store = seeded();
+
+        store.apply_mutation(&Mutation::UpsertNode {
+            node_id: "c".to_string(),
+            labels: vec!["Guide".to_string()],
+            properties: vec![prop("title", json!("Search"))],
+        });
+        store.apply_mutation(&Mutation::UpsertNode {
+            node_id: "a".to_string(),
+            labels: vec!["Article".to_string(), "Root".to_string()],
+            properties: vec![prop("status", json!("published"))],
+        });
+
+        assert_eq!(
+            store.get_node("c").unwrap().labels,
+            vec!["Guide".to_string()]
+        );
+        let node = store.get_node("a").unwrap();
+        assert_eq!(node.labels, vec!["Article".to_string(), "Root".to_string()]);
+        assert_eq!(
+            node.properties,
+            vec![
+                prop("title", json!("Home")),
+                prop("status", json!("published"))
+            ]
+        );
+    }
+
+    /// Hub `h` linked to `n0`..`n4`, with `n4` linked on to `far`
+    fn hub() -> GraphStore {
+        let mut store = GraphStore::new();
+        for id in ["h", "n3", "n0", "n4", "n1", "n2", "far"] {
+            store.apply_mutation(&Mutation::AddNode {
+                node_id: id.to_string(),
+                labels: vec![],
+                properties: vec![],
+            });
+        }
+        for (from, to) in [
+            ("h", "n3"),
+            ("h", "n0"),
+            ("n4", "far"),
+            ("h", "n4"),
+            ("n1", "h"),
+            ("h", "n2"),
+        ] {
+            store.apply_mutation(&Mutation::AddEdge {
+                edge_id: format!("{}-{}", from, to),
+                from: from.to_string(),
+                to: to.to_string(),
+                label: None,
+                properties: vec![],
+            });
+        }
+        store
+    }
+
+    fn ids(nodes: &[NodeSnapshot]) -> Vec<&str> {
+        nodes.iter().map(|n| n.node_id.as_str()).collect()
+    }
+
+    #[test]
+    fn neighbors_are_ordered_by_hops_then_id() {
+        let store = hub();
+
+        assert_eq!(
+            ids(&store.neighbors("h", 2)),
+            vec!["n0", "n1", "n2", "n3", "n4", "far"]
+        );
+    }
+
+    #[test]
+    fn neighbors_page_walks_all_results_with_cursor() {
+        let store = hub();
+
+        let first = store.neighbors_page("h", 2, 4, None).unwrap();
+        assert_eq!(ids(&first.nodes), vec!["n0", "n1", "n2", "n3"]);
+        assert_eq!(first.total, 6);
+        assert_eq!(first.next_cursor.as_deref(), Some("1:n3"));
+
+        let second = store
+            .neighbors_page("h", 2, 4, first.next_cursor.as_deref())
+            .unwrap();
+        assert_eq!(ids(&second.nodes), vec!["n4", "far"]);
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[test]
+    fn neighbors_page_rejects_malformed_cursor() {
+        let store = hub();
+
+        assert!(store.neighbors_page("h", 1, 10, Some("n3")).is_err());
+        assert!(store.neighbors_page("h", 1, 10, Some("x:n3")).is_err());
+    }
+
+    #[test]
+    fn upsert_edge_repoints_existing_edge() {
+        let mut store = seeded();
+
+        store.apply_mutation(&Mutation::UpsertEdge {
+            edge_id: "e".to_string(),
+            from: "b".to_string(),
+            to: "a".to_string(),
+            label: None,
+            properties: vec![prop("since", json!(2024))],
+        });
+
+        let edge = &store.edges["e"];
+        assert_eq!((edge.from_node.as_str(), edge.to_node.as_str()), ("b", "a"));
+        assert_eq!(edge.label.as_deref(), Some("related"));
+        assert_eq!(edge.properties.len(), 2);
+    }
+}
@@ -87,6 +87,71 @@ pub enum Mutation {
         #[serde(rename = "edgeId")]
         edge_id: String,
     },
+    /// Set and/or remove individual node properties, keeping the rest
+    #[serde(rename = "update-node-properties")]
+    UpdateNodeProperties {
+        #[serde(rename = "nodeId")]
+        node_id: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        set: Vec<Property>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        remove: Vec<String>,
+    },
+    /// Set and/or remove individual edge properties, keeping the rest
+    #[serde(rename = "update-edge-properties")]
+    UpdateEdgeProperties {
+        #[serde(rename = "edgeId")]
+        edge_id: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        set: Vec<Property>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        remove: Vec<String>,
+    },
+    /// Create the node, or merge labels and properties into an existing one
+    #[serde(rename = "upsert-node")]
+    UpsertNode {
+        #[serde(rename = "nodeId")]
+        node_id: String,
+        #[serde(default)]
+        labels: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        properties: Vec<Property>,
+    },
+    /// Create the edge, or re-point it and merge properties into an existing one
+    #[serde(rename = "upsert-edge")]
+    UpsertEdge {
+        #[serde(rename = "edgeId")]
+        edge_id: String,
+        from: String,
+        to: String,
+        label: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        properties: Vec<Property>,
+    },
+}
+
+/// Page of a node query requested by the caller
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageRequest {
+    /// Maximum number of nodes to return; capped at `MAX_QUERY_LIMIT`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// `nextCursor` of the previous page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// One page of nodes, in stable order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NodePage {
+    pub nodes: Vec<NodeSnapshot>,
+    /// Cursor for the following page; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Number of matching nodes across all pages
+    pub total: usize,
 }
 
 /// Association between a tag and commit
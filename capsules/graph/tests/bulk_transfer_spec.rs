@@ -0,0 +1,258 @@
+//! Tests for graph bulk import/export
+//!
+//! These tests exercise rendering and parsing without NATS:
+//! - JSONL and GraphML exports are deterministic
+//! - Exports round-trip back into equivalent add mutations
+//! - Neo4j-style GraphML (labels attribute, typed keys) is understood
+//! - `>` inside GraphML attribute values does not end the tag
+//! - Mutations are split into ordered commit batches
+
+use capsules_graph::bulk::{self, JsonlRecord, TransferFormat};
+use capsules_graph::storage::GraphStore;
+use capsules_graph::{EdgeSnapshot, Mutation, NodeSnapshot, Property};
+use std::io::Cursor;
+
+fn sample_store() -> GraphStore {
+    let mut store = GraphStore::new();
+    store.nodes.insert(
+        "b".to_string(),
+        NodeSnapshot {
+            node_id: "b".to_string(),
+            labels: vec!["Service".to_string()],
+            properties: vec![Property {
+                key: "replicas".to_string(),
+                value: serde_json::json!(3),
+            }],
+        },
+    );
+    store.nodes.insert(
+        "a".to_string(),
+        NodeSnapshot {
+            node_id: "a".to_string(),
+            labels: vec!["Team".to_string(), "Owner".to_string()],
+            properties: vec![Property {
+                key: "name".to_string(),
+                value: serde_json::json!("Platform & <Ops>"),
+            }],
+        },
+    );
+    store.edges.insert(
+        "e1".to_string(),
+        EdgeSnapshot {
+            edge_id: "e1".to_string(),
+            from_node: "a".to_string(),
+            to_node: "b".to_string(),
+            label: Some("OWNS".to_string()),
+            properties: vec![Property {
+                key: "critical".to_string(),
+                value: serde_json::json!(true),
+            }],
+        },
+    );
+    store
+}
+
+fn render(store: GraphStore, format: TransferFormat) -> Vec<String> {
+    bulk::render(store, format)
+        .collect::<anyhow::Result<_>>()
+        .unwrap()
+}
+
+#[test]
+fn given_graph_when_exported_as_jsonl_then_nodes_precede_edges_sorted_by_id() {
+    let chunks = render(sample_store(), TransferFormat::Jsonl);
+
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks.iter().all(|c| c.ends_with('\n')));
+
+    let records: Vec<JsonlRecord> = chunks
+        .iter()
+        .map(|c| serde_json::from_str(c.trim()).unwrap())
+        .collect();
+    match (&records[0], &records[1], &records[2]) {
+        (JsonlRecord::Node(a), JsonlRecord::Node(b), JsonlRecord::Edge(e)) => {
+            assert_eq!(a.node_id, "a");
+            assert_eq!(b.node_id, "b");
+            assert_eq!(e.edge_id, "e1");
+        }
+        other => panic!("unexpected record order: {:?}", other),
+    }
+
+    let first: serde_json::Value = serde_json::from_str(chunks[0].trim()).unwrap();
+    assert_eq!(first["type"], "node");
+    assert_eq!(first["nodeId"], "a");
+}
+
+#[test]
+fn given_jsonl_export_when_imported_then_mutations_round_trip() {
+    let exported = render(sample_store(), TransferFormat::Jsonl).concat();
+
+    let mutations = bulk::parse_mutations(Cursor::new(exported), TransferFormat::Jsonl).unwrap();
+
+    assert_eq!(mutations.len(), 3);
+    assert!(matches!(&mutations[0], Mutation::AddNode { node_id, .. } if node_id == "a"));
+    assert!(
+        matches!(&mutations[2], Mutation::AddEdge { edge_id, label, .. }
+        if edge_id == "e1" && label.as_deref() == Some("OWNS"))
+    );
+}
+
+#[test]
+fn given_graphml_export_when_imported_then_labels_and_typed_properties_round_trip() {
+    let exported = render(sample_store(), TransferFormat::GraphMl).concat();
+
+    assert!(exported.starts_with("<?xml"));
+    assert!(exported.contains("Platform &amp; &lt;Ops&gt;"));
+
+    let mutations = bulk::parse_mutations(Cursor::new(exported), TransferFormat::GraphMl).unwrap();
+
+    assert_eq!(
+        mutations[0],
+        Mutation::AddNode {
+            node_id: "a".to_string(),
+            labels: vec!["Team".to_string(), "Owner".to_string()],
+            properties: vec![Property {
+                key: "name".to_string(),
+                value: serde_json::json!("Platform & <Ops>"),
+            }],
+        }
+    );
+    assert_eq!(
+        mutations[1],
+        Mutation::AddNode {
+            node_id: "b".to_string(),
+            labels: vec!["Service".to_string()],
+            properties: vec![Property {
+                key: "replicas".to_string(),
+                value: serde_json::json!(3),
+            }],
+        }
+    );
+    assert_eq!(
+        mutations[2],
+        Mutation::AddEdge {
+            edge_id: "e1".to_string(),
+            from: "a".to_string(),
+            to: "b".to_string(),
+            label: Some("OWNS".to_string()),
+            properties: vec![Property {
+                key: "critical".to_string(),
+                value: serde_json::json!(true),
+            }],
+        }
+    );
+}
+
+#[test]
+fn given_neo4j_style_graphml_when_parsed_then_edges_are_ordered_after_nodes() {
+    let graphml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <!-- exported by apoc -->
+  <key id="name" for="node" attr.name="name" attr.type="string"/>
+  <key id="since" for="edge" attr.name="since" attr.type="long"/>
+  <graph id="G" edgedefault="directed">
+    <edge source="n1" target="n2" label="KNOWS"><data key="since">2019</data></edge>
+    <node id="n1" labels=":Person"><data key="labels">:Person</data><data key="name">Ada</data></node>
+    <node id="n2" labels=":Person"><data key="name"><![CDATA[Grace <H>]]></data></node>
+  </graph>
+</graphml>"#;
+
+    let mutations = bulk::parse_mutations(Cursor::new(graphml), TransferFormat::GraphMl).unwrap();
+
+    assert_eq!(mutations.len(), 3);
+    match &mutations[1] {
+        Mutation::AddNode { properties, .. } => {
+            assert_eq!(properties[0].value, serde_json::json!("Grace <H>"));
+        }
+        other => panic!("expected node, got {:?}", other),
+    }
+    match &mutations[2] {
+        Mutation::AddEdge {
+            edge_id,
+            label,
+            properties,
+            ..
+        } => {
+            assert_eq!(edge_id, "e0");
+            assert_eq!(label.as_deref(), Some("KNOWS"));
+            assert_eq!(properties[0].value, serde_json::json!(2019));
+        }
+        other => panic!("expected edge, got {:?}", other),
+    }
+}
+
+#[test]
+fn given_gt_inside_graphml_attribute_when_parsed_then_value_is_kept_whole() {
+    let graphml = r#"<graphml>
+  <key id="note" for="node" attr.name="note" attr.type="string"/>
+  <graph>
+    <node id="a>b" labels=":Gate"><data key="note">x > y</data></node>
+    <edge id="e>1" source="a>b" target="a>b" label="A->B"/>
+  </graph>
+</graphml>"#;
+
+    let mutations = bulk::parse_mutations(Cursor::new(graphml), TransferFormat::GraphMl).unwrap();
+
+    assert_eq!(
+        mutations[0],
+        Mutation::AddNode {
+            node_id: "a>b".to_string(),
+            labels: vec!["Gate".to_string()],
+            properties: vec![Property {
+                key: "note".to_string(),
+                value: serde_json::json!("x > y"),
+            }],
+        }
+    );
+    assert_eq!(
+        mutations[1],
+        Mutation::AddEdge {
+            edge_id: "e>1".to_string(),
+            from: "a>b".to_string(),
+            to: "a>b".to_string(),
+            label: Some("A->B".to_string()),
+            properties: vec![],
+        }
+    );
+}
+
+#[test]
+fn given_malformed_jsonl_when_parsed_then_error_names_line() {
+    let input = "{\"type\":\"node\",\"nodeId\":\"a\",\"labels\":[],\"properties\":[]}\nnot-json\n";
+
+    let err = bulk::parse_mutations(Cursor::new(input), TransferFormat::Jsonl).unwrap_err();
+
+    assert!(format!("{:#}", err).contains("line 2"));
+}
+
+#[test]
+fn given_mutations_when_batched_then_batches_respect_size() {
+    let mutations: Vec<Mutation> = (0..5)
+        .map(|i| Mutation::AddNode {
+            node_id: format!("n{}", i),
+            labels: vec![],
+            properties: vec![],
+        })
+        .collect();
+
+    let batches = bulk::plan_batches(mutations, 2);
+
+    assert_eq!(
+        batches.iter().map(Vec::len).collect::<Vec<_>>(),
+        vec![2, 2, 1]
+    );
+    assert!(bulk::plan_batches(Vec::new(), 2).is_empty());
+}
+
+#[test]
+fn given_format_names_when_parsed_then_aliases_are_accepted() {
+    assert_eq!(
+        "JSONL".parse::<TransferFormat>().unwrap(),
+        TransferFormat::Jsonl
+    );
+    assert_eq!(
+        "graphml".parse::<TransferFormat>().unwrap(),
+        TransferFormat::GraphMl
+    );
+    assert!("csv".parse::<TransferFormat>().is_err());
+}
@@ -8,7 +8,7 @@
 
 use anyhow::Result;
 use async_nats::jetstream::{self, consumer::DeliverPolicy};
-use capsules_graph::{self as graph, GraphScope, Mutation, Property};
+use capsules_graph::{self as graph, GraphScope, Mutation, PageRequest, Property};
 use futures_util::StreamExt;
 use std::time::Duration;
 
@@ -593,34 +593,87 @@ async fn given_graph_with_edges_when_neighbors_then_returns_connected_nodes() ->
     tokio::time::sleep(Duration::from_millis(100)).await;
 
     // Act - find neighbors of A within depth 1
-    let query_envelope =
-        graph::neighbors(scope.clone(), commit_id.clone(), "A".to_string(), 1).await;
+    let query_envelope = graph::neighbors(
+        scope.clone(),
+        commit_id.clone(),
+        "A".to_string(),
+        1,
+        PageRequest::default(),
+    )
+    .await;
 
     // Assert - should find B
     assert!(query_envelope.result.is_success());
 
     if let envelope::OperationResult::Success { data, .. } = &query_envelope.result {
-        assert_eq!(data.len(), 1);
-        assert_eq!(data[0].node_id, "B");
+        assert_eq!(data.nodes.len(), 1);
+        assert_eq!(data.nodes[0].node_id, "B");
     } else {
         panic!("Expected success result for neighbors");
     }
 
     // Act - find neighbors of A within depth 2
-    let query_envelope2 = graph::neighbors(scope, commit_id, "A".to_string(), 2).await;
+    let query_envelope2 = graph::neighbors(
+        scope.clone(),
+        commit_id.clone(),
+        "A".to_string(),
+        2,
+        PageRequest::default(),
+    )
+    .await;
 
     // Assert - should find B and C
     assert!(query_envelope2.result.is_success());
 
     if let envelope::OperationResult::Success { data, .. } = &query_envelope2.result {
-        assert_eq!(data.len(), 2);
-        let node_ids: Vec<String> = data.iter().map(|n| n.node_id.clone()).collect();
-        assert!(node_ids.contains(&"B".to_string()));
-        assert!(node_ids.contains(&"C".to_string()));
+        let node_ids: Vec<&str> = data.nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(node_ids, vec!["B", "C"]);
+        assert_eq!(data.next_cursor, None);
     } else {
         panic!("Expected success result for neighbors");
     }
 
+    // Act - page through depth 2 one node at a time
+    let first_page = graph::neighbors(
+        scope.clone(),
+        commit_id.clone(),
+        "A".to_string(),
+        2,
+        PageRequest {
+            limit: Some(1),
+            cursor: None,
+        },
+    )
+    .await;
+
+    // Assert - first page holds B and points at C
+    let envelope::OperationResult::Success { data: first, .. } = &first_page.result else {
+        panic!("Expected success result for first page");
+    };
+    assert_eq!(first.nodes[0].node_id, "B");
+    assert_eq!(first.total, 2);
+    assert!(first_page
+        .diagnostics
+        .iter()
+        .any(|d| d.message.contains("truncated")));
+
+    let second_page = graph::neighbors(
+        scope,
+        commit_id,
+        "A".to_string(),
+        2,
+        PageRequest {
+            limit: Some(1),
+            cursor: first.next_cursor.clone(),
+        },
+    )
+    .await;
+    let envelope::OperationResult::Success { data: second, .. } = &second_page.result else {
+        panic!("Expected success result for second page");
+    };
+    assert_eq!(second.nodes[0].node_id, "C");
+    assert_eq!(second.next_cursor, None);
+
     Ok(())
 }
 
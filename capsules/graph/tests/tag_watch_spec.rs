@@ -0,0 +1,142 @@
+//! Tests for the graph tag-watch subscription API
+//!
+//! These tests verify:
+//! - Tag moves report both the old and the new commit
+//! - Deletes report the commit the tag pointed at
+//! - Updates outside the watched scope are ignored
+//! - A live KV watch yields changes made through `tag`/`delete_tag`
+
+use anyhow::Result;
+use capsules_graph::storage::TagWatchState;
+use capsules_graph::{self as graph, GraphScope, TagAction, TagChanged, TaggedCommit};
+use futures_util::StreamExt;
+use std::time::Duration;
+
+fn scope(tenant: &str) -> GraphScope {
+    GraphScope {
+        tenant_id: tenant.to_string(),
+        project_id: "proj-1".to_string(),
+        namespace: "ns-1".to_string(),
+        graph_id: "graph-1".to_string(),
+    }
+}
+
+#[test]
+fn given_known_tag_when_moved_then_change_reports_old_and_new_commit() {
+    let initial = vec![TaggedCommit {
+        tag: "latest".to_string(),
+        commit_id: "c1".to_string(),
+        timestamp: "2025-01-01T00:00:00Z".to_string(),
+    }];
+    let mut state = TagWatchState::new(&scope("t1"), &initial);
+
+    let change = state.apply(
+        "t1/proj-1/ns-1/graph-1/latest",
+        TagAction::Set,
+        Some("c2".to_string()),
+        7,
+    );
+
+    assert_eq!(
+        change,
+        Some(TagChanged {
+            tag: "latest".to_string(),
+            old_commit: Some("c1".to_string()),
+            new_commit: Some("c2".to_string()),
+            action: TagAction::Set,
+            revision: 7,
+        })
+    );
+}
+
+#[test]
+fn given_tag_when_deleted_then_change_reports_previous_commit() {
+    let mut state = TagWatchState::new(&scope("t1"), &[]);
+    state.apply(
+        "t1/proj-1/ns-1/graph-1/v1",
+        TagAction::Set,
+        Some("c1".to_string()),
+        1,
+    );
+
+    let change = state
+        .apply("t1/proj-1/ns-1/graph-1/v1", TagAction::Delete, None, 2)
+        .expect("delete should produce a change");
+
+    assert_eq!(change.action, TagAction::Delete);
+    assert_eq!(change.old_commit.as_deref(), Some("c1"));
+    assert_eq!(change.new_commit, None);
+}
+
+#[test]
+fn given_other_scope_or_same_commit_when_applied_then_no_change_is_emitted() {
+    let mut state = TagWatchState::new(&scope("t1"), &[]);
+
+    assert!(state
+        .apply(
+            "t2/proj-1/ns-1/graph-1/latest",
+            TagAction::Set,
+            Some("c1".to_string()),
+            1
+        )
+        .is_none());
+
+    assert!(state
+        .apply(
+            "t1/proj-1/ns-1/graph-1/latest",
+            TagAction::Set,
+            Some("c1".to_string()),
+            2
+        )
+        .is_some());
+    assert!(state
+        .apply(
+            "t1/proj-1/ns-1/graph-1/latest",
+            TagAction::Set,
+            Some("c1".to_string()),
+            3
+        )
+        .is_none());
+}
+
+#[test]
+fn given_tag_changed_when_serialized_then_uses_camel_case() {
+    let change = TagChanged {
+        tag: "latest".to_string(),
+        old_commit: None,
+        new_commit: Some("c1".to_string()),
+        action: TagAction::Set,
+        revision: 1,
+    };
+
+    let json = serde_json::to_value(&change).unwrap();
+
+    assert_eq!(json["newCommit"], "c1");
+    assert_eq!(json["action"], "set");
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS to be running
+async fn given_watch_when_tag_set_and_deleted_then_stream_yields_both_changes() -> Result<()> {
+    let scope = scope(&format!("tenant-{}", uuid::Uuid::new_v4()));
+    let mut changes = Box::pin(graph::watch_tags(scope.clone()).await?);
+
+    let env = graph::tag(scope.clone(), "latest".to_string(), "commit-a".to_string()).await;
+    assert!(env.result.is_success());
+    let env = graph::delete_tag(scope.clone(), "latest".to_string()).await;
+    assert!(env.result.is_success());
+
+    let set = tokio::time::timeout(Duration::from_secs(5), changes.next())
+        .await?
+        .expect("stream ended")?;
+    assert_eq!(set.action, TagAction::Set);
+    assert_eq!(set.new_commit.as_deref(), Some("commit-a"));
+
+    let deleted = tokio::time::timeout(Duration::from_secs(5), changes.next())
+        .await?
+        .expect("stream ended")?;
+    assert_eq!(deleted.action, TagAction::Delete);
+    assert_eq!(deleted.old_commit.as_deref(), Some("commit-a"));
+
+    Ok(())
+}
@@ -18,6 +18,16 @@ pub enum Recommendation {
     Steady,
 }
 
+impl Recommendation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recommendation::ScaleUp => "scale_up",
+            Recommendation::ScaleDown => "scale_down",
+            Recommendation::Steady => "steady",
+        }
+    }
+}
+
 /// Scale hint event payload (matches contract schema)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScaleHintEvent {
@@ -33,6 +43,12 @@ pub struct ScaleHintEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "traceId")]
     pub trace_id: Option<String>,
+    /// Capsule the hint is about, used for routing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capsule: Option<String>,
+    /// Queue the hint is about, used for routing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,11 +93,75 @@ pub struct HysteresisPayload {
     pub min_signals_for_transition: u32,
 }
 
+/// What was done about a scale hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaleOutcome {
+    /// Replicas changed by the full step
+    Scaled,
+    /// Replicas changed, but less than requested because of the bounds or
+    /// the maximum step
+    Clamped,
+    /// Nothing changed
+    Skipped,
+    /// Handed to an external autoscaler, which decides what to do
+    Forwarded,
+    /// The autoscaler could not be reached or rejected the change
+    Failed,
+}
+
+/// The action taken for a scale hint and the reason for it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaleDecision {
+    pub outcome: ScaleOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_replicas: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desired_replicas: Option<i32>,
+    pub reason: String,
+}
+
+impl ScaleDecision {
+    pub fn new(outcome: ScaleOutcome, reason: impl Into<String>) -> Self {
+        Self {
+            outcome,
+            current_replicas: None,
+            desired_replicas: None,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn skipped(reason: impl Into<String>) -> Self {
+        Self::new(ScaleOutcome::Skipped, reason)
+    }
+
+    pub fn failed(reason: impl Into<String>) -> Self {
+        Self::new(ScaleOutcome::Failed, reason)
+    }
+
+    pub fn with_replicas(mut self, current: i32, desired: i32) -> Self {
+        self.current_replicas = Some(current);
+        self.desired_replicas = Some(desired);
+        self
+    }
+
+    /// Whether the decision changes the replica count
+    pub fn changes_replicas(&self) -> bool {
+        matches!(self.outcome, ScaleOutcome::Scaled | ScaleOutcome::Clamped)
+    }
+}
+
 /// Autoscale client trait - implement this to integrate with different autoscalers
 #[async_trait]
 pub trait AutoscaleClient: Send + Sync {
-    /// Handle a scale hint event
-    async fn handle_scale_hint(&self, event: &ScaleHintEvent) -> Result<()>;
+    /// Handle a scale hint event, returning what was done about it
+    async fn handle_scale_hint(&self, event: &ScaleHintEvent) -> Result<ScaleDecision>;
+
+    /// The workload `event` scales, if the client knows it (e.g. `namespace/name`)
+    fn target(&self, _event: &ScaleHintEvent) -> Option<String> {
+        None
+    }
 }
 
 /// Log-only autoscale client (default implementation)
@@ -89,7 +169,7 @@ pub struct LogOnlyAutoscaleClient;
 
 #[async_trait]
 impl AutoscaleClient for LogOnlyAutoscaleClient {
-    async fn handle_scale_hint(&self, event: &ScaleHintEvent) -> Result<()> {
+    async fn handle_scale_hint(&self, event: &ScaleHintEvent) -> Result<ScaleDecision> {
         info!(
             tenant_id = %event.tenant_id,
             recommendation = ?event.recommendation,
@@ -99,7 +179,7 @@ impl AutoscaleClient for LogOnlyAutoscaleClient {
             reason = %event.reason,
             "Scale recommendation (log-only mode)"
         );
-        Ok(())
+        Ok(ScaleDecision::skipped("log-only mode"))
     }
 }
 
@@ -120,6 +200,10 @@ struct AutoscaleRequest {
     reason: String,
     timestamp: String,
     trace_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capsule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    queue: Option<String>,
 }
 
 impl HttpAutoscaleClient {
@@ -146,7 +230,7 @@ impl HttpAutoscaleClient {
 
 #[async_trait]
 impl AutoscaleClient for HttpAutoscaleClient {
-    async fn handle_scale_hint(&self, event: &ScaleHintEvent) -> Result<()> {
+    async fn handle_scale_hint(&self, event: &ScaleHintEvent) -> Result<ScaleDecision> {
         let request = AutoscaleRequest {
             tenant_id: event.tenant_id.clone(),
             recommendation: event.recommendation,
@@ -154,6 +238,8 @@ impl AutoscaleClient for HttpAutoscaleClient {
             reason: event.reason.clone(),
             timestamp: event.ts.clone(),
             trace_id: event.trace_id.clone(),
+            capsule: event.capsule.clone(),
+            queue: event.queue.clone(),
         };
 
         let mut last_error = None;
@@ -180,7 +266,10 @@ impl AutoscaleClient for HttpAutoscaleClient {
                             attempt = attempt + 1,
                             "Successfully called autoscale endpoint"
                         );
-                        return Ok(());
+                        return Ok(ScaleDecision::new(
+                            ScaleOutcome::Forwarded,
+                            format!("accepted by {} ({})", self.endpoint, response.status()),
+                        ));
                     } else {
                         let status = response.status();
                         let body = response
@@ -213,8 +302,8 @@ mod tests {
         let client = LogOnlyAutoscaleClient;
         let event = create_test_event(Recommendation::ScaleUp);
 
-        let result = client.handle_scale_hint(&event).await;
-        assert!(result.is_ok());
+        let decision = client.handle_scale_hint(&event).await.unwrap();
+        assert_eq!(decision.outcome, ScaleOutcome::Skipped);
     }
 
     #[tokio::test]
@@ -253,6 +342,8 @@ mod tests {
             },
             reason: "Test scale hint".to_string(),
             trace_id: None,
+            capsule: None,
+            queue: None,
         }
     }
 }
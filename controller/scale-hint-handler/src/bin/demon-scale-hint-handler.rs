@@ -1,8 +1,8 @@
 //! Scale Hint Handler binary - consumes scale hint events and triggers autoscale actions
 
 use scale_hint_handler::{
-    AutoscaleClient, Config, HttpAutoscaleClient, LogOnlyAutoscaleClient, Metrics,
-    ScaleHintConsumer,
+    AutoscaleClient, Config, HttpAutoscaleClient, K8sAutoscaleClient, LogOnlyAutoscaleClient,
+    Metrics, RoutedAutoscaleClient, ScaleHintConsumer,
 };
 use std::sync::Arc;
 use tracing::{error, info};
@@ -25,31 +25,52 @@ async fn main() -> anyhow::Result<()> {
     info!("  Consumer: {}", config.consumer_name);
     info!("  Subject filter: {}", config.subject_filter());
     info!("  Dry-run: {}", config.dry_run);
+    info!(
+        "  Stabilization: {} consecutive hints within {}s",
+        config.stabilization_hints, config.stabilization_window_secs
+    );
     info!("  Metrics port: {}", config.metrics_port);
 
-    // Initialize Prometheus metrics
-    Metrics::init(config.metrics_port)?;
+    // Serve Prometheus metrics
+    let metrics = Metrics::new();
+    metrics.serve(config.metrics_port).await?;
 
-    let metrics = Metrics;
-
-    // Create autoscale client based on configuration
-    if config.has_autoscale_endpoint() {
+    // Create the default autoscale client based on configuration
+    let fallback: Arc<dyn AutoscaleClient> = if config.has_k8s_target() {
+        let autoscale_client = K8sAutoscaleClient::connect(&config.k8s, config.dry_run).await?;
+        info!(
+            "Using Kubernetes autoscale client for deployment {} (replicas {}..={}, dry-run: {})",
+            config.k8s.deployment.as_deref().unwrap_or_default(),
+            config.k8s.min_replicas,
+            config.k8s.max_replicas,
+            config.dry_run
+        );
+        Arc::new(autoscale_client)
+    } else if config.has_autoscale_endpoint() {
         let endpoint = config.autoscale_endpoint.clone().unwrap();
         info!("Using HTTP autoscale client with endpoint: {}", endpoint);
 
-        let autoscale_client = Arc::new(HttpAutoscaleClient::new(
+        Arc::new(HttpAutoscaleClient::new(
             endpoint,
             config.autoscale_timeout_secs,
             config.max_retry_attempts,
             config.retry_backoff_ms,
-        )?);
-
-        run_consumer(config, autoscale_client, metrics).await
+        )?)
     } else {
         info!("Using log-only autoscale client (dry-run mode)");
-        let autoscale_client = Arc::new(LogOnlyAutoscaleClient);
-        run_consumer(config, autoscale_client, metrics).await
+        Arc::new(LogOnlyAutoscaleClient)
+    };
+
+    // Hints matching a route go to that route's Deployment instead
+    let routes = config.routing_table()?.unwrap_or_default();
+    if !routes.routes.is_empty() {
+        info!("Loaded {} scale hint routes", routes.routes.len());
     }
+    let autoscale_client = Arc::new(
+        RoutedAutoscaleClient::connect(&routes, &config.k8s, config.dry_run, fallback).await?,
+    );
+
+    run_consumer(config, autoscale_client, metrics).await
 }
 
 /// Run the consumer with the specified autoscale client
@@ -1,6 +1,9 @@
 //! Configuration for the scale hint handler service
 
-use clap::Parser;
+use crate::autoscale::ScaleHintEvent;
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser};
+use serde::Deserialize;
 
 /// Configuration for scale hint handler
 #[derive(Debug, Clone, Parser)]
@@ -54,6 +57,207 @@ pub struct Config {
     /// Autoscale API timeout in seconds
     #[arg(long, env, default_value = "10")]
     pub autoscale_timeout_secs: u64,
+
+    /// JetStream stream recording every hint and the action taken
+    #[arg(
+        long,
+        env = "SCALE_DECISIONS_STREAM",
+        default_value = "SCALE_DECISIONS"
+    )]
+    pub decisions_stream: String,
+
+    /// Record scale decisions (disable to keep decisions in logs only)
+    #[arg(long, env, default_value = "true")]
+    pub record_decisions: bool,
+
+    /// Consecutive same-direction hints required before acting on one
+    #[arg(long, env, default_value = "1")]
+    pub stabilization_hints: u32,
+
+    /// Window (seconds) the consecutive hints must fall within
+    #[arg(long, env, default_value = "300")]
+    pub stabilization_window_secs: u64,
+
+    /// YAML routing table sending hints to different Deployments (see [`RoutingTable`])
+    #[arg(long, env = "SCALE_ROUTES_FILE")]
+    pub routes_file: Option<String>,
+
+    /// Kubernetes scale subresource target
+    #[command(flatten)]
+    pub k8s: K8sOptions,
+}
+
+/// Scaling a Deployment directly through the Kubernetes API
+#[derive(Debug, Clone, Args)]
+pub struct K8sOptions {
+    /// Deployment to scale (enables the Kubernetes autoscale client)
+    #[arg(long = "k8s-deployment", env = "K8S_DEPLOYMENT")]
+    pub deployment: Option<String>,
+
+    /// Namespace of the Deployment (defaults to the kubeconfig or in-cluster namespace)
+    #[arg(long = "k8s-namespace", env = "K8S_NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// Kubeconfig file (defaults to in-cluster config, then $KUBECONFIG / ~/.kube/config)
+    #[arg(long, env = "KUBECONFIG_PATH")]
+    pub kubeconfig: Option<String>,
+
+    /// Never scale below this many replicas
+    #[arg(
+        long = "k8s-min-replicas",
+        env = "K8S_MIN_REPLICAS",
+        default_value = "1"
+    )]
+    pub min_replicas: i32,
+
+    /// Never scale above this many replicas
+    #[arg(
+        long = "k8s-max-replicas",
+        env = "K8S_MAX_REPLICAS",
+        default_value = "10"
+    )]
+    pub max_replicas: i32,
+
+    /// Replicas added or removed per scale hint
+    #[arg(long = "k8s-scale-step", env = "K8S_SCALE_STEP", default_value = "1")]
+    pub scale_step: i32,
+
+    /// Largest replica change in one action, including moves back into the
+    /// min/max bounds (unlimited if unset)
+    #[arg(long = "k8s-max-scale-step", env = "K8S_MAX_SCALE_STEP")]
+    pub max_scale_step: Option<i32>,
+
+    /// Minimum seconds after a scale change before scaling up again
+    #[arg(long, env, default_value = "60")]
+    pub scale_up_cooldown_secs: u64,
+
+    /// Minimum seconds after a scale change before scaling down again
+    #[arg(long, env, default_value = "300")]
+    pub scale_down_cooldown_secs: u64,
+}
+
+impl Default for K8sOptions {
+    fn default() -> Self {
+        Self {
+            deployment: None,
+            namespace: None,
+            kubeconfig: None,
+            min_replicas: 1,
+            max_replicas: 10,
+            scale_step: 1,
+            max_scale_step: None,
+            scale_up_cooldown_secs: 60,
+            scale_down_cooldown_secs: 300,
+        }
+    }
+}
+
+/// Routes from hint metadata to Kubernetes Deployments
+///
+/// Routes are checked in order and the first match wins; hints matching no
+/// route go to the default autoscale client. Replica bounds, step and
+/// cooldowns not set on a route come from the top-level `K8S_*` options.
+///
+/// ```yaml
+/// routes:
+///   - match: { tenant: acme, capsule: build }
+///     deployment: acme-build-agents
+///     namespace: acme
+///     maxReplicas: 20
+///   - match: { queue: ingest }
+///     deployment: ingest-workers
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoutingTable {
+    #[serde(default)]
+    pub routes: Vec<Route>,
+}
+
+/// A Deployment and the hints it scales for
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Route {
+    #[serde(rename = "match", default)]
+    pub matches: RouteMatch,
+    pub deployment: String,
+    pub namespace: Option<String>,
+    pub min_replicas: Option<i32>,
+    pub max_replicas: Option<i32>,
+    pub scale_step: Option<i32>,
+    pub max_scale_step: Option<i32>,
+    pub scale_up_cooldown_secs: Option<u64>,
+    pub scale_down_cooldown_secs: Option<u64>,
+}
+
+/// Hint metadata a route applies to; unset fields match anything
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteMatch {
+    pub tenant: Option<String>,
+    pub capsule: Option<String>,
+    pub queue: Option<String>,
+}
+
+impl RoutingTable {
+    /// Load a routing table from a YAML (or JSON) file
+    pub fn load(path: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read routing table {}", path))?;
+        Self::parse(&contents).with_context(|| format!("Invalid routing table {}", path))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let table: Self = serde_yaml::from_str(contents)?;
+        for (index, route) in table.routes.iter().enumerate() {
+            if route.deployment.trim().is_empty() {
+                bail!("route {} has an empty deployment", index);
+            }
+        }
+        Ok(table)
+    }
+
+    /// The first route matching `event`
+    pub fn route(&self, event: &ScaleHintEvent) -> Option<&Route> {
+        self.routes
+            .iter()
+            .find(|route| route.matches.matches(event))
+    }
+}
+
+impl Route {
+    /// Kubernetes options for this route, filling unset fields from `defaults`
+    pub fn k8s_options(&self, defaults: &K8sOptions) -> K8sOptions {
+        K8sOptions {
+            deployment: Some(self.deployment.clone()),
+            namespace: self
+                .namespace
+                .clone()
+                .or_else(|| defaults.namespace.clone()),
+            kubeconfig: defaults.kubeconfig.clone(),
+            min_replicas: self.min_replicas.unwrap_or(defaults.min_replicas),
+            max_replicas: self.max_replicas.unwrap_or(defaults.max_replicas),
+            scale_step: self.scale_step.unwrap_or(defaults.scale_step),
+            max_scale_step: self.max_scale_step.or(defaults.max_scale_step),
+            scale_up_cooldown_secs: self
+                .scale_up_cooldown_secs
+                .unwrap_or(defaults.scale_up_cooldown_secs),
+            scale_down_cooldown_secs: self
+                .scale_down_cooldown_secs
+                .unwrap_or(defaults.scale_down_cooldown_secs),
+        }
+    }
+}
+
+impl RouteMatch {
+    pub fn matches(&self, event: &ScaleHintEvent) -> bool {
+        fn field(expected: &Option<String>, actual: Option<&str>) -> bool {
+            expected.as_deref().is_none_or(|e| actual == Some(e))
+        }
+        field(&self.tenant, Some(&event.tenant_id))
+            && field(&self.capsule, event.capsule.as_deref())
+            && field(&self.queue, event.queue.as_deref())
+    }
 }
 
 impl Config {
@@ -75,6 +279,20 @@ impl Config {
     pub fn has_autoscale_endpoint(&self) -> bool {
         self.autoscale_endpoint.is_some() && !self.dry_run
     }
+
+    /// Check if a Kubernetes Deployment is configured (used in dry-run mode too,
+    /// with server-side dry-run patches)
+    pub fn has_k8s_target(&self) -> bool {
+        self.k8s.deployment.is_some()
+    }
+
+    /// Load the routing table, if one is configured
+    pub fn routing_table(&self) -> Result<Option<RoutingTable>> {
+        self.routes_file
+            .as_deref()
+            .map(RoutingTable::load)
+            .transpose()
+    }
 }
 
 #[cfg(test)]
@@ -96,6 +314,12 @@ mod tests {
             retry_backoff_ms: 1000,
             max_retry_attempts: 3,
             autoscale_timeout_secs: 10,
+            decisions_stream: "SCALE_DECISIONS".to_string(),
+            record_decisions: true,
+            stabilization_hints: 1,
+            stabilization_window_secs: 300,
+            routes_file: None,
+            k8s: K8sOptions::default(),
         };
 
         assert_eq!(config.subject_filter(), "demon.scale.v1.*.hints");
@@ -116,6 +340,12 @@ mod tests {
             retry_backoff_ms: 1000,
             max_retry_attempts: 3,
             autoscale_timeout_secs: 10,
+            decisions_stream: "SCALE_DECISIONS".to_string(),
+            record_decisions: true,
+            stabilization_hints: 1,
+            stabilization_window_secs: 300,
+            routes_file: None,
+            k8s: K8sOptions::default(),
         };
 
         assert_eq!(config.subject_filter(), "demon.scale.v1.production.hints");
@@ -136,6 +366,12 @@ mod tests {
             retry_backoff_ms: 1000,
             max_retry_attempts: 3,
             autoscale_timeout_secs: 10,
+            decisions_stream: "SCALE_DECISIONS".to_string(),
+            record_decisions: true,
+            stabilization_hints: 1,
+            stabilization_window_secs: 300,
+            routes_file: None,
+            k8s: K8sOptions::default(),
         };
 
         // Dry-run mode disables autoscale
@@ -149,4 +385,47 @@ mod tests {
         config.autoscale_endpoint = None;
         assert!(!config.has_autoscale_endpoint());
     }
+
+    #[test]
+    fn test_routing_table_first_match_wins() {
+        let table = RoutingTable::parse(
+            r#"
+routes:
+  - match: { tenant: acme, capsule: build }
+    deployment: acme-build
+    maxReplicas: 20
+  - match: { tenant: acme }
+    deployment: acme-default
+"#,
+        )
+        .unwrap();
+
+        let mut event: ScaleHintEvent = serde_json::from_value(serde_json::json!({
+            "event": "agent.scale.hint:v1",
+            "ts": "2025-01-06T10:30:00Z",
+            "tenantId": "acme",
+            "recommendation": "scale_up",
+            "metrics": { "queueLag": 1, "p95LatencyMs": 1.0, "errorRate": 0.0, "totalProcessed": 1, "totalErrors": 0 },
+            "thresholds": { "queueLagHigh": 1, "queueLagLow": 0, "p95LatencyHighMs": 1.0, "p95LatencyLowMs": 0.0, "errorRateHigh": 0.1 },
+            "hysteresis": { "currentState": "pressure", "stateChangedAt": null, "consecutiveHighSignals": 1, "consecutiveLowSignals": 0, "minSignalsForTransition": 1 },
+            "reason": "test",
+            "capsule": "build"
+        }))
+        .unwrap();
+
+        let route = table.route(&event).unwrap();
+        assert_eq!(route.deployment, "acme-build");
+        let options = route.k8s_options(&K8sOptions::default());
+        assert_eq!(options.max_replicas, 20);
+        assert_eq!(options.min_replicas, 1);
+
+        event.capsule = Some("deploy".to_string());
+        assert_eq!(table.route(&event).unwrap().deployment, "acme-default");
+
+        event.tenant_id = "other".to_string();
+        assert!(table.route(&event).is_none());
+
+        assert!(RoutingTable::parse("routes:\n  - deployment: ''\n").is_err());
+        assert!(RoutingTable::parse("routes:\n  - deployment: x\n    replicas: 3\n").is_err());
+    }
 }
@@ -1,8 +1,10 @@
 //! NATS JetStream consumer for scale hint events
 
-use crate::autoscale::{AutoscaleClient, ScaleHintEvent};
+use crate::autoscale::{AutoscaleClient, ScaleDecision, ScaleHintEvent};
 use crate::config::Config;
+use crate::history::{self, ScaleDecisionRecord};
 use crate::metrics::Metrics;
+use crate::stabilization::{StabilizationPolicy, Stabilizer};
 use anyhow::{Context, Result};
 use async_nats::jetstream::{
     self,
@@ -19,15 +21,18 @@ pub struct ScaleHintConsumer<C: AutoscaleClient> {
     config: Config,
     autoscale_client: Arc<C>,
     metrics: Metrics,
+    stabilizer: Stabilizer,
 }
 
 impl<C: AutoscaleClient> ScaleHintConsumer<C> {
     /// Create a new scale hint consumer
     pub fn new(config: Config, autoscale_client: Arc<C>, metrics: Metrics) -> Self {
+        let stabilizer = Stabilizer::new(StabilizationPolicy::from_config(&config));
         Self {
             config,
             autoscale_client,
             metrics,
+            stabilizer,
         }
     }
 
@@ -52,7 +57,7 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
         );
 
         // Process messages continuously
-        self.process_messages(consumer).await
+        self.process_messages(consumer, &jetstream).await
     }
 
     /// Connect to NATS server
@@ -129,7 +134,11 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
     }
 
     /// Process messages continuously
-    async fn process_messages(&self, consumer: PullConsumer) -> Result<()> {
+    async fn process_messages(
+        &self,
+        consumer: PullConsumer,
+        jetstream: &jetstream::Context,
+    ) -> Result<()> {
         const BATCH_SIZE: usize = 10;
         const BATCH_TIMEOUT_SECS: u64 = 30;
 
@@ -148,7 +157,7 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
                 match msg_result {
                     Ok(msg) => {
                         batch_count += 1;
-                        self.handle_message(msg).await;
+                        self.handle_message(msg, jetstream).await;
                     }
                     Err(e) => {
                         error!("Error receiving message: {}", e);
@@ -169,7 +178,11 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
     }
 
     /// Handle a single message
-    async fn handle_message(&self, msg: async_nats::jetstream::Message) {
+    async fn handle_message(
+        &self,
+        msg: async_nats::jetstream::Message,
+        jetstream: &jetstream::Context,
+    ) {
         let subject = msg.subject.clone();
         let payload = msg.payload.clone();
 
@@ -198,11 +211,11 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
         };
 
         let tenant_id = &event.tenant_id;
-        let recommendation = format!("{:?}", event.recommendation).to_lowercase();
+        let recommendation = event.recommendation.as_str();
 
         // Record metrics
         self.metrics
-            .record_recommendation(&recommendation, tenant_id);
+            .record_recommendation(recommendation, tenant_id);
         self.metrics.update_gauges(
             event.metrics.queue_lag,
             event.metrics.p95_latency_ms,
@@ -210,6 +223,21 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
             tenant_id,
         );
 
+        // Hold the hint back until enough consecutive hints agree
+        if let Some(decision) = self.stabilizer.observe(&event) {
+            debug!(
+                tenant_id = %tenant_id,
+                reason = %decision.reason,
+                "Scale hint held by stabilization window"
+            );
+            self.metrics.record_throttled(tenant_id);
+            self.record_decision(jetstream, &event, decision).await;
+            if let Err(e) = msg.ack().await {
+                error!("Failed to ack held message: {}", e);
+            }
+            return;
+        }
+
         // Handle scale hint with retry
         let mut last_error = None;
         for attempt in 0..=self.config.max_retry_attempts {
@@ -226,8 +254,10 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
             }
 
             match self.autoscale_client.handle_scale_hint(&event).await {
-                Ok(()) => {
+                Ok(decision) => {
                     self.metrics.record_autoscale_call(true, tenant_id);
+                    self.stabilizer.reset(&event);
+                    self.record_decision(jetstream, &event, decision).await;
                     // Successfully processed, ack the message
                     if let Err(e) = msg.ack().await {
                         error!("Failed to ack message: {}", e);
@@ -241,6 +271,7 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
                     return;
                 }
                 Err(e) => {
+                    self.metrics.record_autoscale_error(tenant_id);
                     warn!(
                         attempt = attempt + 1,
                         max_attempts = self.config.max_retry_attempts + 1,
@@ -262,6 +293,11 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
         );
         self.metrics.record_autoscale_call(false, tenant_id);
         self.metrics.record_error("autoscale_exhausted", tenant_id);
+        let reason = last_error
+            .map(|e| format!("{:#}", e))
+            .unwrap_or_else(|| "autoscale call failed".to_string());
+        self.record_decision(jetstream, &event, ScaleDecision::failed(reason))
+            .await;
 
         // Nak the message to requeue (JetStream will respect max_deliver)
         if let Err(e) = msg
@@ -271,12 +307,40 @@ impl<C: AutoscaleClient> ScaleHintConsumer<C> {
             error!("Failed to nak message: {}", e);
         }
     }
+
+    /// Persist the action taken for a hint; failures are logged, never fatal
+    async fn record_decision(
+        &self,
+        jetstream: &jetstream::Context,
+        event: &ScaleHintEvent,
+        decision: ScaleDecision,
+    ) {
+        let target = self.autoscale_client.target(event);
+        self.metrics
+            .record_decision(event.recommendation.as_str(), target.as_deref(), &decision);
+        if !self.config.record_decisions {
+            return;
+        }
+        let record = ScaleDecisionRecord::new(event, decision)
+            .with_target(target)
+            .dry_run(self.config.dry_run);
+        if let Err(e) = history::record(jetstream, &self.config.decisions_stream, &record).await {
+            warn!(
+                tenant_id = %event.tenant_id,
+                error = %e,
+                "Failed to record scale decision"
+            );
+            self.metrics
+                .record_error("decision_record", &event.tenant_id);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::autoscale::LogOnlyAutoscaleClient;
+    use crate::config::K8sOptions;
 
     #[test]
     fn test_consumer_creation() {
@@ -293,10 +357,16 @@ mod tests {
             retry_backoff_ms: 1000,
             max_retry_attempts: 3,
             autoscale_timeout_secs: 10,
+            decisions_stream: "SCALE_DECISIONS".to_string(),
+            record_decisions: true,
+            stabilization_hints: 1,
+            stabilization_window_secs: 300,
+            routes_file: None,
+            k8s: K8sOptions::default(),
         };
 
         let client = Arc::new(LogOnlyAutoscaleClient);
-        let metrics = Metrics;
+        let metrics = Metrics::new();
 
         let consumer = ScaleHintConsumer::new(config, client, metrics);
         assert_eq!(consumer.config.consumer_name, "test-consumer");
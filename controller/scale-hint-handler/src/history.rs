@@ -0,0 +1,270 @@
+//! Scale decision history
+//!
+//! Every hint the handler receives is recorded together with what was done
+//! about it as an `agent.scale.decision:v1` event on
+//! `demon.scale.v1.<tenant>.decisions` in the `SCALE_DECISIONS` stream. The
+//! Operate UI reads the stream to show the last recommendation and recent
+//! actions per tenant; [`query`] lists records for other consumers.
+//!
+//! Recording is best-effort: a failure to persist a decision is logged but
+//! never changes how the hint is acknowledged.
+
+use crate::autoscale::{
+    MetricsPayload, Recommendation, ScaleDecision, ScaleHintEvent, ScaleOutcome,
+};
+use anyhow::{Context, Result};
+use async_nats::jetstream::{self, consumer::DeliverPolicy};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::debug;
+
+/// Event name carried by every decision record
+pub const DECISION_EVENT: &str = "agent.scale.decision:v1";
+
+/// Default stream holding decision records
+pub const DEFAULT_DECISIONS_STREAM: &str = "SCALE_DECISIONS";
+
+/// Upper bound on records returned by a single query
+pub const MAX_QUERY_LIMIT: usize = 1000;
+
+/// How long decision records are kept
+const RETENTION: Duration = Duration::from_secs(3600 * 24 * 30);
+
+const FETCH_BATCH: usize = 256;
+
+/// The hint a decision was made for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HintSummary {
+    pub ts: String,
+    pub reason: String,
+    pub metrics: MetricsPayload,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+}
+
+/// A single `agent.scale.decision:v1` record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaleDecisionRecord {
+    pub event: String,
+    pub ts: DateTime<Utc>,
+    pub tenant_id: String,
+    pub recommendation: Recommendation,
+    #[serde(flatten)]
+    pub decision: ScaleDecision,
+    /// Workload the decision applies to, e.g. `namespace/deployment`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+    pub hint: HintSummary,
+}
+
+impl ScaleDecisionRecord {
+    pub fn new(event: &ScaleHintEvent, decision: ScaleDecision) -> Self {
+        Self {
+            event: DECISION_EVENT.to_string(),
+            ts: Utc::now(),
+            tenant_id: event.tenant_id.clone(),
+            recommendation: event.recommendation,
+            decision,
+            target: None,
+            dry_run: false,
+            hint: HintSummary {
+                ts: event.ts.clone(),
+                reason: event.reason.clone(),
+                metrics: event.metrics.clone(),
+                trace_id: event.trace_id.clone(),
+            },
+        }
+    }
+
+    pub fn with_target(mut self, target: Option<String>) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    fn msg_id(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            self.tenant_id,
+            self.hint.ts,
+            self.ts.timestamp_nanos_opt().unwrap_or(0)
+        )
+    }
+}
+
+/// Filters for listing decision records; every set field must match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryQuery {
+    pub tenant_id: Option<String>,
+    pub outcome: Option<ScaleOutcome>,
+    pub since: Option<DateTime<Utc>>,
+    /// Maximum records returned, newest first (capped at [`MAX_QUERY_LIMIT`])
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    pub fn for_tenant(tenant_id: impl Into<String>) -> Self {
+        Self {
+            tenant_id: Some(tenant_id.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn matches(&self, record: &ScaleDecisionRecord) -> bool {
+        self.tenant_id
+            .as_deref()
+            .is_none_or(|t| record.tenant_id == t)
+            && self.outcome.is_none_or(|o| record.decision.outcome == o)
+            && self.since.is_none_or(|since| record.ts >= since)
+    }
+
+    fn effective_limit(&self) -> usize {
+        self.limit.unwrap_or(100).clamp(1, MAX_QUERY_LIMIT)
+    }
+}
+
+/// Apply a query to already-loaded records: filter, newest first, limit
+pub fn filter_records(
+    records: impl IntoIterator<Item = ScaleDecisionRecord>,
+    query: &HistoryQuery,
+) -> Vec<ScaleDecisionRecord> {
+    let mut matched: Vec<ScaleDecisionRecord> =
+        records.into_iter().filter(|r| query.matches(r)).collect();
+    matched.sort_by_key(|r| std::cmp::Reverse(r.ts));
+    matched.truncate(query.effective_limit());
+    matched
+}
+
+/// Subject a decision record for `tenant_id` is published on
+pub fn decision_subject(tenant_id: &str) -> String {
+    let tenant: String = tenant_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("demon.scale.v1.{}.decisions", tenant)
+}
+
+/// Publish a decision record to `stream`, creating the stream if needed
+pub async fn record(
+    js: &jetstream::Context,
+    stream: &str,
+    record: &ScaleDecisionRecord,
+) -> Result<()> {
+    ensure_stream(js, stream).await?;
+
+    let payload = serde_json::to_vec(record).context("Failed to serialize scale decision")?;
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert("Nats-Msg-Id", record.msg_id().as_str());
+    js.publish_with_headers(decision_subject(&record.tenant_id), headers, payload.into())
+        .await
+        .context("Failed to publish scale decision")?
+        .await
+        .context("Scale decision was not acknowledged")?;
+    Ok(())
+}
+
+/// List decision records in `stream` matching `query`, newest first
+///
+/// Reads the tenant's subject when `tenant_id` is set, otherwise every
+/// tenant. A missing stream means nothing has been recorded yet.
+pub async fn query(
+    js: &jetstream::Context,
+    stream: &str,
+    query: &HistoryQuery,
+) -> Result<Vec<ScaleDecisionRecord>> {
+    let stream = match js.get_stream(stream).await {
+        Ok(stream) => stream,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let filter_subject = query
+        .tenant_id
+        .as_deref()
+        .map(decision_subject)
+        .unwrap_or_else(|| "demon.scale.v1.*.decisions".to_string());
+    let consumer = stream
+        .create_consumer(jetstream::consumer::pull::Config {
+            filter_subject,
+            durable_name: None,
+            deliver_policy: DeliverPolicy::All,
+            ack_policy: jetstream::consumer::AckPolicy::None,
+            inactive_threshold: Duration::from_secs(60),
+            ..Default::default()
+        })
+        .await
+        .context("Failed to create scale decision consumer")?;
+
+    let mut pending = consumer.cached_info().num_pending as usize;
+    let mut records = Vec::new();
+    while pending > 0 {
+        let mut messages = consumer
+            .batch()
+            .max_messages(pending.min(FETCH_BATCH))
+            .expires(Duration::from_secs(2))
+            .messages()
+            .await
+            .context("Failed to fetch scale decisions")?;
+
+        let mut received = 0;
+        while let Some(message) = messages.next().await {
+            let message =
+                message.map_err(|e| anyhow::anyhow!("Failed to receive scale decision: {}", e))?;
+            received += 1;
+            match serde_json::from_slice::<ScaleDecisionRecord>(&message.payload) {
+                Ok(record) if query.matches(&record) => records.push(record),
+                Ok(_) => {}
+                Err(e) => debug!("Skipping malformed scale decision record: {}", e),
+            }
+        }
+        if received == 0 {
+            break;
+        }
+        pending = pending.saturating_sub(received);
+    }
+
+    Ok(filter_records(records, query))
+}
+
+async fn ensure_stream(js: &jetstream::Context, stream: &str) -> Result<()> {
+    if js.get_stream(stream).await.is_ok() {
+        return Ok(());
+    }
+    js.get_or_create_stream(jetstream::stream::Config {
+        name: stream.to_string(),
+        subjects: vec!["demon.scale.v1.*.decisions".to_string()],
+        max_age: RETENTION,
+        ..Default::default()
+    })
+    .await
+    .context("Failed to create scale decision stream")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decision_subject_sanitizes_tenant() {
+        assert_eq!(
+            decision_subject("acme.eu"),
+            "demon.scale.v1.acme_eu.decisions"
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Kubernetes autoscale client
+//!
+//! Patches the scale subresource of a Deployment in response to scale hints,
+//! so no shim service is needed between the handler and the cluster. Each
+//! hint moves the replica count by a fixed step, clamped to configured
+//! bounds and to an optional maximum change per action, and hints arriving
+//! within the cooldown window after a change are skipped. In dry-run mode patches are sent with server-side dry-run: the API
+//! server validates them but nothing changes.
+
+use crate::autoscale::{
+    AutoscaleClient, Recommendation, ScaleDecision, ScaleHintEvent, ScaleOutcome,
+};
+use crate::config::K8sOptions;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use k8s_openapi::api::apps::v1::Deployment;
+use kube::api::{Api, Patch, PatchParams};
+use kube::config::{KubeConfigOptions, Kubeconfig};
+use kube::Client;
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Field manager recorded on scale patches
+const FIELD_MANAGER: &str = "demon-scale-hint-handler";
+
+/// Replica bounds, step and cooldowns applied to scale hints
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScalePolicy {
+    pub min_replicas: i32,
+    pub max_replicas: i32,
+    pub step: i32,
+    /// Largest change in one action; also limits jumps back into the bounds
+    pub max_step: Option<i32>,
+    pub scale_up_cooldown: Duration,
+    pub scale_down_cooldown: Duration,
+}
+
+impl ScalePolicy {
+    pub fn from_options(options: &K8sOptions) -> Self {
+        Self {
+            min_replicas: options.min_replicas,
+            max_replicas: options.max_replicas,
+            step: options.scale_step,
+            max_step: options.max_scale_step,
+            scale_up_cooldown: Duration::from_secs(options.scale_up_cooldown_secs),
+            scale_down_cooldown: Duration::from_secs(options.scale_down_cooldown_secs),
+        }
+    }
+
+    /// Decide the replica count for a hint, given the current count and the
+    /// time since replicas were last changed
+    pub fn decide(
+        &self,
+        recommendation: Recommendation,
+        current: i32,
+        since_last_change: Option<Duration>,
+    ) -> ScaleDecision {
+        let (target, cooldown) = match recommendation {
+            Recommendation::Steady => {
+                return ScaleDecision::skipped("steady recommendation")
+                    .with_replicas(current, current);
+            }
+            Recommendation::ScaleUp => (current.saturating_add(self.step), self.scale_up_cooldown),
+            Recommendation::ScaleDown => {
+                (current.saturating_sub(self.step), self.scale_down_cooldown)
+            }
+        };
+
+        if let Some(elapsed) = since_last_change.filter(|elapsed| *elapsed < cooldown) {
+            return ScaleDecision::skipped(format!(
+                "cooldown: {}s of {}s remaining",
+                (cooldown - elapsed).as_secs(),
+                cooldown.as_secs()
+            ))
+            .with_replicas(current, current);
+        }
+
+        let bounded = target.clamp(self.min_replicas, self.max_replicas);
+        let desired = match self.max_step.map(|max_step| max_step.max(1)) {
+            Some(max_step) => bounded.clamp(
+                current.saturating_sub(max_step),
+                current.saturating_add(max_step),
+            ),
+            None => bounded,
+        };
+        if desired == current {
+            let bound = if recommendation == Recommendation::ScaleUp {
+                "max"
+            } else {
+                "min"
+            };
+            return ScaleDecision::skipped(format!("already at {} replicas ({})", bound, current))
+                .with_replicas(current, current);
+        }
+
+        let (outcome, reason) = if desired == target {
+            (
+                ScaleOutcome::Scaled,
+                format!("{:?} from {} to {}", recommendation, current, desired),
+            )
+        } else if desired != bounded {
+            (
+                ScaleOutcome::Clamped,
+                format!(
+                    "{:?} to {} limited to {} (max step {})",
+                    recommendation,
+                    bounded,
+                    desired,
+                    self.max_step.unwrap_or_default()
+                ),
+            )
+        } else {
+            (
+                ScaleOutcome::Clamped,
+                format!(
+                    "{:?} to {} clamped to {} (bounds {}..={})",
+                    recommendation, target, desired, self.min_replicas, self.max_replicas
+                ),
+            )
+        };
+        ScaleDecision::new(outcome, reason).with_replicas(current, desired)
+    }
+}
+
+/// A workload whose replica count can be read and set
+#[async_trait]
+pub trait ScaleTarget: Send + Sync {
+    /// `namespace/name`, for logs
+    fn describe(&self) -> String;
+
+    async fn replicas(&self) -> Result<i32>;
+
+    /// Set the replica count; with `dry_run` the change is validated but not applied
+    async fn set_replicas(&self, replicas: i32, dry_run: bool) -> Result<()>;
+}
+
+/// The scale subresource of a Deployment
+pub struct DeploymentScale {
+    api: Api<Deployment>,
+    namespace: String,
+    name: String,
+}
+
+impl DeploymentScale {
+    /// Connect using `kubeconfig` if given, otherwise in-cluster config or the
+    /// default kubeconfig
+    pub async fn connect(
+        name: &str,
+        namespace: Option<&str>,
+        kubeconfig: Option<&str>,
+    ) -> Result<Self> {
+        let config = match kubeconfig {
+            Some(path) => {
+                let kubeconfig = Kubeconfig::read_from(path)
+                    .with_context(|| format!("Failed to read kubeconfig {}", path))?;
+                kube::Config::from_custom_kubeconfig(kubeconfig, &KubeConfigOptions::default())
+                    .await
+                    .context("Failed to load kubeconfig")?
+            }
+            None => kube::Config::infer()
+                .await
+                .context("Failed to infer Kubernetes config")?,
+        };
+        let namespace = namespace
+            .map(str::to_string)
+            .unwrap_or_else(|| config.default_namespace.clone());
+        let client = Client::try_from(config).context("Failed to build Kubernetes client")?;
+
+        Ok(Self {
+            api: Api::namespaced(client, &namespace),
+            namespace,
+            name: name.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl ScaleTarget for DeploymentScale {
+    fn describe(&self) -> String {
+        format!("{}/{}", self.namespace, self.name)
+    }
+
+    async fn replicas(&self) -> Result<i32> {
+        let scale =
+            self.api.get_scale(&self.name).await.with_context(|| {
+                format!("Failed to read scale of deployment {}", self.describe())
+            })?;
+        Ok(scale.spec.and_then(|spec| spec.replicas).unwrap_or(0))
+    }
+
+    async fn set_replicas(&self, replicas: i32, dry_run: bool) -> Result<()> {
+        let mut params = PatchParams::apply(FIELD_MANAGER);
+        params.dry_run = dry_run;
+        let patch = Patch::Merge(json!({ "spec": { "replicas": replicas } }));
+        self.api
+            .patch_scale(&self.name, &params, &patch)
+            .await
+            .with_context(|| format!("Failed to scale deployment {}", self.describe()))?;
+        Ok(())
+    }
+}
+
+/// Autoscale client that scales a Kubernetes workload directly
+pub struct K8sAutoscaleClient<T: ScaleTarget = DeploymentScale> {
+    target: T,
+    policy: ScalePolicy,
+    dry_run: bool,
+    last_change: Mutex<Option<Instant>>,
+}
+
+impl K8sAutoscaleClient<DeploymentScale> {
+    /// Connect to the Deployment named in `options`
+    pub async fn connect(options: &K8sOptions, dry_run: bool) -> Result<Self> {
+        let deployment = options
+            .deployment
+            .as_deref()
+            .context("No Kubernetes deployment configured")?;
+        let target = DeploymentScale::connect(
+            deployment,
+            options.namespace.as_deref(),
+            options.kubeconfig.as_deref(),
+        )
+        .await?;
+        Ok(Self::new(
+            target,
+            ScalePolicy::from_options(options),
+            dry_run,
+        ))
+    }
+}
+
+impl<T: ScaleTarget> K8sAutoscaleClient<T> {
+    pub fn new(target: T, policy: ScalePolicy, dry_run: bool) -> Self {
+        Self {
+            target,
+            policy,
+            dry_run,
+            last_change: Mutex::new(None),
+        }
+    }
+
+    /// Decide what to do about a hint and apply it. Dry-run changes still
+    /// start the cooldown, so dry-run decisions match what a live run would do.
+    pub async fn apply(&self, event: &ScaleHintEvent) -> Result<ScaleDecision> {
+        let current = self.target.replicas().await?;
+        let since_last_change = self
+            .last_change
+            .lock()
+            .expect("last change lock poisoned")
+            .map(|at| at.elapsed());
+        let decision = self
+            .policy
+            .decide(event.recommendation, current, since_last_change);
+
+        if let (true, Some(desired)) = (decision.changes_replicas(), decision.desired_replicas) {
+            self.target.set_replicas(desired, self.dry_run).await?;
+            *self.last_change.lock().expect("last change lock poisoned") = Some(Instant::now());
+        }
+        Ok(decision)
+    }
+}
+
+#[async_trait]
+impl<T: ScaleTarget> AutoscaleClient for K8sAutoscaleClient<T> {
+    async fn handle_scale_hint(&self, event: &ScaleHintEvent) -> Result<ScaleDecision> {
+        let decision = self.apply(event).await?;
+        info!(
+            tenant_id = %event.tenant_id,
+            target = %self.target.describe(),
+            recommendation = ?event.recommendation,
+            outcome = ?decision.outcome,
+            current_replicas = ?decision.current_replicas,
+            desired_replicas = ?decision.desired_replicas,
+            dry_run = self.dry_run,
+            reason = %decision.reason,
+            "Handled scale hint"
+        );
+        Ok(decision)
+    }
+
+    fn target(&self, _event: &ScaleHintEvent) -> Option<String> {
+        Some(self.target.describe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ScalePolicy {
+        ScalePolicy {
+            min_replicas: 2,
+            max_replicas: 6,
+            step: 2,
+            max_step: None,
+            scale_up_cooldown: Duration::from_secs(60),
+            scale_down_cooldown: Duration::from_secs(300),
+        }
+    }
+
+    #[test]
+    fn test_policy_scales_by_step() {
+        let decision = policy().decide(Recommendation::ScaleUp, 2, None);
+        assert_eq!(decision.outcome, ScaleOutcome::Scaled);
+        assert_eq!(decision.desired_replicas, Some(4));
+
+        let decision = policy().decide(Recommendation::ScaleDown, 6, None);
+        assert_eq!(decision.outcome, ScaleOutcome::Scaled);
+        assert_eq!(decision.desired_replicas, Some(4));
+    }
+
+    #[test]
+    fn test_policy_clamps_to_bounds() {
+        let decision = policy().decide(Recommendation::ScaleUp, 5, None);
+        assert_eq!(decision.outcome, ScaleOutcome::Clamped);
+        assert_eq!(decision.desired_replicas, Some(6));
+
+        let decision = policy().decide(Recommendation::ScaleDown, 3, None);
+        assert_eq!(decision.outcome, ScaleOutcome::Clamped);
+        assert_eq!(decision.desired_replicas, Some(2));
+
+        let decision = policy().decide(Recommendation::ScaleDown, 2, None);
+        assert_eq!(decision.outcome, ScaleOutcome::Skipped);
+        assert!(decision.reason.contains("min"));
+    }
+
+    #[test]
+    fn test_policy_respects_cooldowns() {
+        let decision = policy().decide(Recommendation::ScaleUp, 2, Some(Duration::from_secs(30)));
+        assert_eq!(decision.outcome, ScaleOutcome::Skipped);
+        assert!(decision.reason.starts_with("cooldown"));
+
+        // Scale-down cooldown is longer than scale-up
+        let decision =
+            policy().decide(Recommendation::ScaleDown, 6, Some(Duration::from_secs(120)));
+        assert_eq!(decision.outcome, ScaleOutcome::Skipped);
+
+        let decision = policy().decide(Recommendation::ScaleUp, 2, Some(Duration::from_secs(90)));
+        assert_eq!(decision.outcome, ScaleOutcome::Scaled);
+    }
+
+    #[test]
+    fn test_policy_limits_change_to_max_step() {
+        let policy = ScalePolicy {
+            max_step: Some(1),
+            ..policy()
+        };
+        let decision = policy.decide(Recommendation::ScaleUp, 2, None);
+        assert_eq!(decision.outcome, ScaleOutcome::Clamped);
+        assert_eq!(decision.desired_replicas, Some(3));
+        assert!(decision.reason.contains("max step 1"));
+
+        // Moving back into the bounds is limited too
+        let policy = ScalePolicy {
+            min_replicas: 5,
+            max_step: Some(2),
+            ..policy
+        };
+        let decision = policy.decide(Recommendation::ScaleUp, 1, None);
+        assert_eq!(decision.desired_replicas, Some(3));
+    }
+
+    #[test]
+    fn test_policy_skips_steady() {
+        let decision = policy().decide(Recommendation::Steady, 4, None);
+        assert_eq!(decision.outcome, ScaleOutcome::Skipped);
+        assert_eq!(decision.desired_replicas, Some(4));
+    }
+}
@@ -2,14 +2,25 @@
 //!
 //! This service subscribes to scale hint events from NATS JetStream and provides
 //! pluggable autoscaling integrations. By default it logs recommendations, but can
-//! optionally call external autoscale APIs (e.g., Kubernetes HPA, cloud autoscalers).
+//! scale Kubernetes Deployments directly (routing hints to several of them by
+//! tenant, capsule or queue) or call external autoscale APIs.
 
 pub mod autoscale;
 pub mod config;
 pub mod consumer;
+pub mod history;
+pub mod k8s;
 pub mod metrics;
+pub mod routing;
+pub mod stabilization;
 
-pub use autoscale::{AutoscaleClient, HttpAutoscaleClient, LogOnlyAutoscaleClient};
-pub use config::Config;
+pub use autoscale::{
+    AutoscaleClient, HttpAutoscaleClient, LogOnlyAutoscaleClient, ScaleDecision, ScaleOutcome,
+};
+pub use config::{Config, RoutingTable};
 pub use consumer::ScaleHintConsumer;
+pub use history::{HistoryQuery, ScaleDecisionRecord};
+pub use k8s::{K8sAutoscaleClient, ScalePolicy};
 pub use metrics::Metrics;
+pub use routing::RoutedAutoscaleClient;
+pub use stabilization::{StabilizationPolicy, Stabilizer};
@@ -1,53 +1,233 @@
-//! Metrics stub for scale hint handler
+//! Prometheus metrics for the scale hint handler
 //!
-//! NOTE: Full Prometheus implementation pending resolution of metrics crate version conflict
-//! between metrics 0.21 (direct dependency) and 0.22 (transitive via metrics-exporter-prometheus).
-//! Current implementation uses structured logging. Follow-up PR will add full Prometheus support.
+//! Served in the text exposition format from `GET /metrics` on
+//! `METRICS_PORT`:
+//!
+//! - `scale_hint_handler_hints_received_total{tenant,recommendation}`
+//! - `scale_hint_handler_last_hint_timestamp_seconds` (alert on silence)
+//! - `scale_hint_handler_held_hints_total{tenant}` (stabilization window)
+//! - `scale_hint_handler_actions_total{direction,outcome}`
+//! - `scale_hint_handler_clamped_decisions_total{direction}`
+//! - `scale_hint_handler_autoscale_calls_total{result}`
+//! - `scale_hint_handler_autoscale_errors_total{tenant}` (every failed attempt)
+//! - `scale_hint_handler_errors_total{error_type}`
+//! - `scale_hint_handler_target_replicas{target}` (last desired replica count)
+//! - `scale_hint_handler_queue_lag{tenant}`,
+//!   `scale_hint_handler_p95_latency_ms{tenant}`,
+//!   `scale_hint_handler_error_rate{tenant}` (from the latest hint)
 
-use anyhow::Result;
-use tracing::info;
+use crate::autoscale::{ScaleDecision, ScaleOutcome};
+use anyhow::{Context, Result};
+use axum::{http::header, routing::get, Router};
+use prometheus::{
+    Encoder, Gauge, GaugeVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use tracing::{error, info};
 
-/// Metrics collector for scale hint handler
+/// Metrics collector for scale hint handler; clones share the same registry
 #[derive(Clone)]
-pub struct Metrics;
+pub struct Metrics {
+    registry: Registry,
+    hints_received: IntCounterVec,
+    last_hint_timestamp: Gauge,
+    held_hints: IntCounterVec,
+    actions: IntCounterVec,
+    clamped_decisions: IntCounterVec,
+    autoscale_calls: IntCounterVec,
+    autoscale_errors: IntCounterVec,
+    errors: IntCounterVec,
+    target_replicas: IntGaugeVec,
+    queue_lag: GaugeVec,
+    p95_latency_ms: GaugeVec,
+    error_rate: GaugeVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Metrics {
-    /// Initialize metrics (currently logs-based)
-    pub fn init(port: u16) -> Result<()> {
-        info!("Metrics initialized (log-based) on port {}", port);
+    /// A fresh registry with every metric registered
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("scale_hint_handler".to_string()), None)
+            .expect("valid metrics prefix");
+
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels).expect("valid counter");
+            registry
+                .register(Box::new(counter.clone()))
+                .expect("metric registered once");
+            counter
+        };
+        let hints_received = counter(
+            "hints_received_total",
+            "Scale hints consumed, by tenant and recommendation",
+            &["tenant", "recommendation"],
+        );
+        let held_hints = counter(
+            "held_hints_total",
+            "Hints held back by the stabilization window",
+            &["tenant"],
+        );
+        let actions = counter(
+            "actions_total",
+            "Decisions taken for scale hints, by direction and outcome",
+            &["direction", "outcome"],
+        );
+        let clamped_decisions = counter(
+            "clamped_decisions_total",
+            "Replica changes reduced by the bounds or the maximum step",
+            &["direction"],
+        );
+        let autoscale_calls = counter(
+            "autoscale_calls_total",
+            "Hints handed to the autoscaler, by final result",
+            &["result"],
+        );
+        let autoscale_errors = counter(
+            "autoscale_errors_total",
+            "Failed autoscale attempts, including ones that were retried",
+            &["tenant"],
+        );
+        let errors = counter("errors_total", "Processing errors by type", &["error_type"]);
+
+        let gauge = |name: &str, help: &str| {
+            let gauge = GaugeVec::new(Opts::new(name, help), &["tenant"]).expect("valid gauge");
+            registry
+                .register(Box::new(gauge.clone()))
+                .expect("metric registered once");
+            gauge
+        };
+        let queue_lag = gauge("queue_lag", "Queue lag reported by the latest hint");
+        let p95_latency_ms = gauge(
+            "p95_latency_ms",
+            "P95 latency in milliseconds reported by the latest hint",
+        );
+        let error_rate = gauge("error_rate", "Error rate reported by the latest hint");
+
+        let last_hint_timestamp = Gauge::with_opts(Opts::new(
+            "last_hint_timestamp_seconds",
+            "Unix time the latest scale hint was received",
+        ))
+        .expect("valid gauge");
+        let target_replicas = IntGaugeVec::new(
+            Opts::new(
+                "target_replicas",
+                "Replica count the latest decision asked for, by scale target",
+            ),
+            &["target"],
+        )
+        .expect("valid gauge");
+        for collector in [
+            Box::new(last_hint_timestamp.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(target_replicas.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            hints_received,
+            last_hint_timestamp,
+            held_hints,
+            actions,
+            clamped_decisions,
+            autoscale_calls,
+            autoscale_errors,
+            errors,
+            target_replicas,
+            queue_lag,
+            p95_latency_ms,
+            error_rate,
+        }
+    }
+
+    /// Serve `GET /metrics` on `port` in the background
+    pub async fn serve(&self, port: u16) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+            .await
+            .with_context(|| format!("Failed to bind metrics port {}", port))?;
+        let metrics = self.clone();
+        let app = Router::new().route(
+            "/metrics",
+            get(move || {
+                let metrics = metrics.clone();
+                async move {
+                    (
+                        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+                        metrics.encode(),
+                    )
+                }
+            }),
+        );
+        info!("Serving Prometheus metrics on port {}", port);
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                error!("Metrics server failed: {}", e);
+            }
+        });
         Ok(())
     }
 
+    /// Current values in the Prometheus text format
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            error!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+
     /// Record a scale recommendation
     pub fn record_recommendation(&self, recommendation: &str, tenant_id: &str) {
-        info!(
-            recommendation = %recommendation,
-            tenant_id = %tenant_id,
-            "Recorded scale recommendation"
-        );
+        self.hints_received
+            .with_label_values(&[tenant_id, recommendation])
+            .inc();
+        self.last_hint_timestamp
+            .set(chrono::Utc::now().timestamp_millis() as f64 / 1000.0);
     }
 
-    /// Record autoscale API call result
-    pub fn record_autoscale_call(&self, success: bool, tenant_id: &str) {
-        info!(
-            success = success,
-            tenant_id = %tenant_id,
-            "Recorded autoscale call"
-        );
+    /// Record the decision taken for a hint in `direction`, and the replica
+    /// count it asked `target` for
+    pub fn record_decision(&self, direction: &str, target: Option<&str>, decision: &ScaleDecision) {
+        let outcome = serde_json::to_value(decision.outcome)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        self.actions.with_label_values(&[direction, &outcome]).inc();
+        if decision.outcome == ScaleOutcome::Clamped {
+            self.clamped_decisions.with_label_values(&[direction]).inc();
+        }
+        if let (Some(target), Some(desired)) = (target, decision.desired_replicas) {
+            self.target_replicas
+                .with_label_values(&[target])
+                .set(desired as i64);
+        }
+    }
+
+    /// Record the final result of handing a hint to the autoscaler
+    pub fn record_autoscale_call(&self, success: bool, _tenant_id: &str) {
+        let result = if success { "success" } else { "failure" };
+        self.autoscale_calls.with_label_values(&[result]).inc();
+    }
+
+    /// Record one failed autoscale attempt
+    pub fn record_autoscale_error(&self, tenant_id: &str) {
+        self.autoscale_errors.with_label_values(&[tenant_id]).inc();
     }
 
     /// Record throttled event
     pub fn record_throttled(&self, tenant_id: &str) {
-        info!(tenant_id = %tenant_id, "Recorded throttled event");
+        self.held_hints.with_label_values(&[tenant_id]).inc();
     }
 
     /// Record processing error
-    pub fn record_error(&self, error_type: &str, tenant_id: &str) {
-        info!(
-            error_type = %error_type,
-            tenant_id = %tenant_id,
-            "Recorded error"
-        );
+    pub fn record_error(&self, error_type: &str, _tenant_id: &str) {
+        self.errors.with_label_values(&[error_type]).inc();
     }
 
     /// Update metrics gauges from event
@@ -58,13 +238,15 @@ impl Metrics {
         error_rate: f64,
         tenant_id: &str,
     ) {
-        info!(
-            queue_lag = queue_lag,
-            p95_latency_ms = p95_latency_ms,
-            error_rate = error_rate,
-            tenant_id = %tenant_id,
-            "Updated metrics gauges"
-        );
+        self.queue_lag
+            .with_label_values(&[tenant_id])
+            .set(queue_lag as f64);
+        self.p95_latency_ms
+            .with_label_values(&[tenant_id])
+            .set(p95_latency_ms);
+        self.error_rate
+            .with_label_values(&[tenant_id])
+            .set(error_rate);
     }
 }
 
@@ -74,11 +256,40 @@ mod tests {
 
     #[test]
     fn test_metrics_creation() {
-        let metrics = Metrics;
+        let metrics = Metrics::new();
         metrics.record_recommendation("scale_up", "test-tenant");
         metrics.record_autoscale_call(true, "test-tenant");
         metrics.record_throttled("test-tenant");
         metrics.record_error("deserialization", "test-tenant");
         metrics.update_gauges(100, 250.5, 0.05, "test-tenant");
+
+        let text = metrics.encode();
+        assert!(text.contains(
+            r#"scale_hint_handler_hints_received_total{recommendation="scale_up",tenant="test-tenant"} 1"#
+        ));
+        assert!(text.contains(r#"scale_hint_handler_autoscale_calls_total{result="success"} 1"#));
+        assert!(text.contains(r#"scale_hint_handler_queue_lag{tenant="test-tenant"} 100"#));
+        assert!(text.contains("scale_hint_handler_last_hint_timestamp_seconds"));
+    }
+
+    #[test]
+    fn clamped_decisions_are_counted_and_set_the_target() {
+        let metrics = Metrics::new();
+        let clamped =
+            ScaleDecision::new(ScaleOutcome::Clamped, "max replicas").with_replicas(8, 10);
+        metrics.record_decision("scale_up", Some("prod/agents"), &clamped);
+        metrics.record_decision("scale_down", None, &ScaleDecision::skipped("cooldown"));
+
+        let text = metrics.encode();
+        assert!(text.contains(
+            r#"scale_hint_handler_actions_total{direction="scale_up",outcome="clamped"} 1"#
+        ));
+        assert!(text.contains(
+            r#"scale_hint_handler_actions_total{direction="scale_down",outcome="skipped"} 1"#
+        ));
+        assert!(
+            text.contains(r#"scale_hint_handler_clamped_decisions_total{direction="scale_up"} 1"#)
+        );
+        assert!(text.contains(r#"scale_hint_handler_target_replicas{target="prod/agents"} 10"#));
     }
 }
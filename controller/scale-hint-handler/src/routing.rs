@@ -0,0 +1,81 @@
+//! Routing hints to several autoscale targets
+//!
+//! One handler instance can scale several Deployments: a [`RoutingTable`]
+//! maps hint metadata (tenant, capsule, queue) to a Deployment, and the
+//! [`RoutedAutoscaleClient`] hands each hint to the client for its route.
+//! Hints matching no route go to the fallback client chosen from the
+//! top-level configuration. Each route has its own client, so cooldowns are
+//! tracked per Deployment.
+
+use crate::autoscale::{AutoscaleClient, ScaleDecision, ScaleHintEvent};
+use crate::config::{K8sOptions, RouteMatch, RoutingTable};
+use crate::k8s::K8sAutoscaleClient;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::info;
+
+/// Autoscale client dispatching each hint to the first matching route
+pub struct RoutedAutoscaleClient {
+    routes: Vec<(RouteMatch, Arc<dyn AutoscaleClient>)>,
+    fallback: Arc<dyn AutoscaleClient>,
+}
+
+impl RoutedAutoscaleClient {
+    /// A client sending every hint to `fallback` until routes are added
+    pub fn new(fallback: Arc<dyn AutoscaleClient>) -> Self {
+        Self {
+            routes: Vec::new(),
+            fallback,
+        }
+    }
+
+    /// Add a route, checked after the ones already added
+    pub fn with_route(mut self, matches: RouteMatch, client: Arc<dyn AutoscaleClient>) -> Self {
+        self.routes.push((matches, client));
+        self
+    }
+
+    /// Connect a Kubernetes client for every route in `table`; unset route
+    /// options come from `defaults`
+    pub async fn connect(
+        table: &RoutingTable,
+        defaults: &K8sOptions,
+        dry_run: bool,
+        fallback: Arc<dyn AutoscaleClient>,
+    ) -> Result<Self> {
+        let mut client = Self::new(fallback);
+        for route in &table.routes {
+            let options = route.k8s_options(defaults);
+            let target = K8sAutoscaleClient::connect(&options, dry_run)
+                .await
+                .with_context(|| format!("Failed to connect route to {}", route.deployment))?;
+            info!(
+                deployment = %route.deployment,
+                matches = ?route.matches,
+                "Routing scale hints"
+            );
+            client = client.with_route(route.matches.clone(), Arc::new(target));
+        }
+        Ok(client)
+    }
+
+    /// The client handling `event`
+    pub fn route(&self, event: &ScaleHintEvent) -> &dyn AutoscaleClient {
+        self.routes
+            .iter()
+            .find(|(matches, _)| matches.matches(event))
+            .map_or(self.fallback.as_ref(), |(_, client)| client.as_ref())
+    }
+}
+
+#[async_trait]
+impl AutoscaleClient for RoutedAutoscaleClient {
+    async fn handle_scale_hint(&self, event: &ScaleHintEvent) -> Result<ScaleDecision> {
+        self.route(event).handle_scale_hint(event).await
+    }
+
+    fn target(&self, event: &ScaleHintEvent) -> Option<String> {
+        self.route(event).target(event)
+    }
+}
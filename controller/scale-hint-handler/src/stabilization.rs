@@ -0,0 +1,150 @@
+//! Stabilization window for scale hints
+//!
+//! A single hint is a weak signal: load that hovers around a threshold makes
+//! the runtime alternate between `scale_up` and `scale_down`, and acting on
+//! each one flaps replicas. The [`Stabilizer`] holds hints back until it has
+//! seen a run of consecutive hints in the same direction within the window,
+//! tracked separately per tenant, capsule and queue; a `steady` hint or a
+//! change of direction starts over. Once a run has been handled it is
+//! [reset](Stabilizer::reset), so every action needs a fresh run.
+//!
+//! Redelivered hints (same timestamp as the previous one) are not counted
+//! twice, and a run that failed to be handled is kept, so the redelivered hint
+//! is acted on again.
+
+use crate::autoscale::{Recommendation, ScaleDecision, ScaleHintEvent};
+use crate::config::Config;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How many consecutive hints are needed, and how close together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StabilizationPolicy {
+    /// Consecutive same-direction hints required before acting (1 acts on every hint)
+    pub required_hints: u32,
+    /// The run must fit within this window, measured on hint timestamps
+    pub window: Duration,
+}
+
+impl StabilizationPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            required_hints: config.stabilization_hints,
+            window: Duration::from_secs(config.stabilization_window_secs),
+        }
+    }
+}
+
+impl Default for StabilizationPolicy {
+    fn default() -> Self {
+        Self {
+            required_hints: 1,
+            window: Duration::from_secs(300),
+        }
+    }
+}
+
+/// The current run of same-direction hints for one tenant, capsule and queue
+#[derive(Debug, Clone)]
+struct Streak {
+    recommendation: Recommendation,
+    started_at: DateTime<Utc>,
+    last_ts: String,
+    count: u32,
+}
+
+/// Tracks hint runs per tenant, capsule and queue
+#[derive(Debug, Default)]
+pub struct Stabilizer {
+    policy: StabilizationPolicy,
+    streaks: Mutex<HashMap<String, Streak>>,
+}
+
+impl Stabilizer {
+    pub fn new(policy: StabilizationPolicy) -> Self {
+        Self {
+            policy,
+            streaks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &StabilizationPolicy {
+        &self.policy
+    }
+
+    /// Count `event` towards its tenant's run. Returns `None` when the hint
+    /// should be acted on, or a skipped decision while the run is too short.
+    pub fn observe(&self, event: &ScaleHintEvent) -> Option<ScaleDecision> {
+        let mut streaks = self.streaks.lock().expect("stabilizer lock poisoned");
+        if event.recommendation == Recommendation::Steady {
+            streaks.remove(&streak_key(event));
+            return None;
+        }
+        if self.policy.required_hints <= 1 {
+            return None;
+        }
+
+        let at = DateTime::parse_from_rfc3339(&event.ts)
+            .map(|ts| ts.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now());
+
+        let streak = streaks
+            .entry(streak_key(event))
+            .and_modify(|streak| {
+                // A hint older than the run's start also starts over
+                let expired = (at - streak.started_at)
+                    .to_std()
+                    .map_or(true, |elapsed| elapsed > self.policy.window);
+                if streak.recommendation != event.recommendation || expired {
+                    *streak = Streak::start(event, at);
+                } else if streak.last_ts != event.ts {
+                    streak.count += 1;
+                    streak.last_ts = event.ts.clone();
+                }
+            })
+            .or_insert_with(|| Streak::start(event, at));
+
+        if streak.count >= self.policy.required_hints {
+            return None;
+        }
+        Some(ScaleDecision::skipped(format!(
+            "stabilizing: {} of {} consecutive {:?} hints within {}s",
+            streak.count,
+            self.policy.required_hints,
+            event.recommendation,
+            self.policy.window.as_secs()
+        )))
+    }
+
+    /// Start a new run after `event` was handled
+    pub fn reset(&self, event: &ScaleHintEvent) {
+        self.streaks
+            .lock()
+            .expect("stabilizer lock poisoned")
+            .remove(&streak_key(event));
+    }
+}
+
+/// Hints about different capsules or queues of a tenant may go to different
+/// targets, so they are stabilized separately
+fn streak_key(event: &ScaleHintEvent) -> String {
+    format!(
+        "{}/{}/{}",
+        event.tenant_id,
+        event.capsule.as_deref().unwrap_or_default(),
+        event.queue.as_deref().unwrap_or_default()
+    )
+}
+
+impl Streak {
+    fn start(event: &ScaleHintEvent, at: DateTime<Utc>) -> Self {
+        Self {
+            recommendation: event.recommendation,
+            started_at: at,
+            last_ts: event.ts.clone(),
+            count: 1,
+        }
+    }
+}
@@ -6,16 +6,26 @@
 //! - Autoscale client integration
 //! - HTTP stub interactions
 //! - Retry and backoff logic
+//! - Kubernetes scale decisions against a fake scale subresource
+//! - Stabilization window for consecutive hints
+//! - Routing hints to per-capsule targets
 
 use anyhow::Result;
+use scale_hint_handler::k8s::{K8sAutoscaleClient, ScalePolicy, ScaleTarget};
 use scale_hint_handler::{
     autoscale::{
         AutoscaleClient, HysteresisPayload, MetricsPayload, Recommendation, ScaleHintEvent,
-        ThresholdsPayload,
+        ScaleOutcome, ThresholdsPayload,
     },
-    Config, LogOnlyAutoscaleClient,
+    config::{K8sOptions, RouteMatch},
+    history::{filter_records, HistoryQuery, ScaleDecisionRecord},
+    Config, LogOnlyAutoscaleClient, RoutedAutoscaleClient, ScaleDecision, StabilizationPolicy,
+    Stabilizer,
 };
 use serde_json::json;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use wiremock::{
     matchers::{method, path},
     Mock, MockServer, ResponseTemplate,
@@ -56,6 +66,7 @@ async fn test_http_client_successful_call() {
         result.is_ok(),
         "HTTP client should succeed with 200 response"
     );
+    assert_eq!(result.unwrap().outcome, ScaleOutcome::Forwarded);
 }
 
 #[tokio::test]
@@ -139,6 +150,12 @@ async fn test_config_subject_filter() {
         retry_backoff_ms: 1000,
         max_retry_attempts: 3,
         autoscale_timeout_secs: 10,
+        decisions_stream: "SCALE_DECISIONS".to_string(),
+        record_decisions: true,
+        stabilization_hints: 1,
+        stabilization_window_secs: 300,
+        routes_file: None,
+        k8s: K8sOptions::default(),
     };
 
     // All tenants
@@ -191,7 +208,7 @@ async fn test_event_deserialization() {
 
 #[tokio::test]
 async fn test_metrics_recording() {
-    let metrics = scale_hint_handler::Metrics;
+    let metrics = scale_hint_handler::Metrics::new();
 
     // These should not panic
     metrics.record_recommendation("scale_up", "test-tenant");
@@ -201,6 +218,214 @@ async fn test_metrics_recording() {
     metrics.update_gauges(100, 250.5, 0.05, "test-tenant");
 }
 
+/// In-memory stand-in for a Deployment's scale subresource
+struct FakeScaleTarget {
+    replicas: AtomicI32,
+    patches: Mutex<Vec<(i32, bool)>>,
+}
+
+#[async_trait::async_trait]
+impl ScaleTarget for FakeScaleTarget {
+    fn describe(&self) -> String {
+        "test/agents".to_string()
+    }
+
+    async fn replicas(&self) -> Result<i32> {
+        Ok(self.replicas.load(Ordering::SeqCst))
+    }
+
+    async fn set_replicas(&self, replicas: i32, dry_run: bool) -> Result<()> {
+        self.patches.lock().unwrap().push((replicas, dry_run));
+        if !dry_run {
+            self.replicas.store(replicas, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
+
+fn fake_target(replicas: i32) -> FakeScaleTarget {
+    FakeScaleTarget {
+        replicas: AtomicI32::new(replicas),
+        patches: Mutex::new(Vec::new()),
+    }
+}
+
+fn test_policy() -> ScalePolicy {
+    ScalePolicy {
+        min_replicas: 1,
+        max_replicas: 3,
+        step: 1,
+        max_step: None,
+        scale_up_cooldown: Duration::ZERO,
+        scale_down_cooldown: Duration::from_secs(300),
+    }
+}
+
+#[tokio::test]
+async fn test_k8s_client_scales_within_bounds() {
+    let client = K8sAutoscaleClient::new(fake_target(2), test_policy(), false);
+    let event = create_test_event(Recommendation::ScaleUp, "test-tenant");
+
+    let decision = client.apply(&event).await.unwrap();
+    assert_eq!(decision.outcome, ScaleOutcome::Scaled);
+    assert_eq!(decision.desired_replicas, Some(3));
+
+    // Already at max
+    let decision = client.apply(&event).await.unwrap();
+    assert_eq!(decision.outcome, ScaleOutcome::Skipped);
+
+    // Scale-down cooldown started by the scale-up
+    let event = create_test_event(Recommendation::ScaleDown, "test-tenant");
+    let decision = client.apply(&event).await.unwrap();
+    assert_eq!(decision.outcome, ScaleOutcome::Skipped);
+    assert!(decision.reason.starts_with("cooldown"));
+}
+
+#[tokio::test]
+async fn test_k8s_client_dry_run_does_not_change_replicas() {
+    let client = K8sAutoscaleClient::new(fake_target(1), test_policy(), true);
+    let event = create_test_event(Recommendation::ScaleUp, "test-tenant");
+
+    assert!(client.handle_scale_hint(&event).await.is_ok());
+    let decision = client.apply(&event).await.unwrap();
+    assert_eq!(decision.current_replicas, Some(1));
+    assert_eq!(decision.desired_replicas, Some(2));
+}
+
+#[tokio::test]
+async fn test_decision_records_serialize_and_filter() {
+    let event = create_test_event(Recommendation::ScaleUp, "test-tenant");
+    let scaled = ScaleDecisionRecord::new(
+        &event,
+        ScaleDecision::new(ScaleOutcome::Scaled, "ScaleUp from 2 to 3").with_replicas(2, 3),
+    )
+    .with_target(Some("test/agents".to_string()));
+    let skipped = ScaleDecisionRecord::new(&event, ScaleDecision::skipped("cooldown"));
+    let other_tenant = ScaleDecisionRecord::new(
+        &create_test_event(Recommendation::ScaleDown, "other-tenant"),
+        ScaleDecision::skipped("log-only mode"),
+    );
+
+    let value = serde_json::to_value(&scaled).unwrap();
+    assert_eq!(value["event"], "agent.scale.decision:v1");
+    assert_eq!(value["tenantId"], "test-tenant");
+    assert_eq!(value["recommendation"], "scale_up");
+    assert_eq!(value["outcome"], "scaled");
+    assert_eq!(value["currentReplicas"], 2);
+    assert_eq!(value["desiredReplicas"], 3);
+    assert_eq!(value["hint"]["metrics"]["queueLag"], 600);
+    let round_trip: ScaleDecisionRecord = serde_json::from_value(value).unwrap();
+    assert_eq!(round_trip.decision.outcome, ScaleOutcome::Scaled);
+
+    let records = vec![scaled, skipped, other_tenant];
+    let query = HistoryQuery::for_tenant("test-tenant");
+    let matched = filter_records(records.clone(), &query);
+    assert_eq!(matched.len(), 2);
+    assert!(matched[0].ts >= matched[1].ts, "newest first");
+
+    let query = HistoryQuery {
+        outcome: Some(ScaleOutcome::Skipped),
+        limit: Some(1),
+        ..Default::default()
+    };
+    assert_eq!(filter_records(records, &query).len(), 1);
+}
+
+#[tokio::test]
+async fn test_stabilizer_requires_consecutive_hints_within_window() {
+    let stabilizer = Stabilizer::new(StabilizationPolicy {
+        required_hints: 3,
+        window: Duration::from_secs(120),
+    });
+    let hint = |recommendation, ts: &str| ScaleHintEvent {
+        ts: ts.to_string(),
+        ..create_test_event(recommendation, "test-tenant")
+    };
+
+    let held = stabilizer
+        .observe(&hint(Recommendation::ScaleUp, "2025-01-06T10:30:00Z"))
+        .expect("first hint is held");
+    assert_eq!(held.outcome, ScaleOutcome::Skipped);
+    assert!(held.reason.starts_with("stabilizing: 1 of 3"));
+
+    // A redelivered hint does not count twice
+    let held = stabilizer
+        .observe(&hint(Recommendation::ScaleUp, "2025-01-06T10:30:00Z"))
+        .unwrap();
+    assert!(held.reason.starts_with("stabilizing: 1 of 3"));
+
+    // A change of direction starts over
+    assert!(stabilizer
+        .observe(&hint(Recommendation::ScaleDown, "2025-01-06T10:30:30Z"))
+        .is_some());
+    assert!(stabilizer
+        .observe(&hint(Recommendation::ScaleUp, "2025-01-06T10:31:00Z"))
+        .is_some());
+    assert!(stabilizer
+        .observe(&hint(Recommendation::ScaleUp, "2025-01-06T10:31:30Z"))
+        .is_some());
+    assert!(stabilizer
+        .observe(&hint(Recommendation::ScaleUp, "2025-01-06T10:32:00Z"))
+        .is_none());
+
+    // Until handled, the completed run still acts (e.g. on redelivery)
+    assert!(stabilizer
+        .observe(&hint(Recommendation::ScaleUp, "2025-01-06T10:32:00Z"))
+        .is_none());
+    stabilizer.reset(&hint(Recommendation::ScaleUp, "2025-01-06T10:32:00Z"));
+
+    // Hints spread beyond the window never complete a run
+    for ts in [
+        "2025-01-06T11:00:00Z",
+        "2025-01-06T11:01:30Z",
+        "2025-01-06T11:03:00Z",
+    ] {
+        assert!(stabilizer
+            .observe(&hint(Recommendation::ScaleUp, ts))
+            .is_some());
+    }
+
+    // Steady hints pass through and clear the run; other tenants are separate
+    assert!(stabilizer
+        .observe(&hint(Recommendation::Steady, "2025-01-06T11:03:30Z"))
+        .is_none());
+    let other = create_test_event(Recommendation::ScaleUp, "other-tenant");
+    assert!(stabilizer
+        .observe(&other)
+        .unwrap()
+        .reason
+        .starts_with("stabilizing: 1 of 3"));
+}
+
+#[tokio::test]
+async fn test_routed_client_dispatches_by_capsule() {
+    let client = RoutedAutoscaleClient::new(Arc::new(LogOnlyAutoscaleClient)).with_route(
+        RouteMatch {
+            capsule: Some("build".to_string()),
+            ..Default::default()
+        },
+        Arc::new(K8sAutoscaleClient::new(
+            fake_target(1),
+            test_policy(),
+            false,
+        )),
+    );
+
+    let routed = ScaleHintEvent {
+        capsule: Some("build".to_string()),
+        ..create_test_event(Recommendation::ScaleUp, "test-tenant")
+    };
+    let decision = client.handle_scale_hint(&routed).await.unwrap();
+    assert_eq!(decision.outcome, ScaleOutcome::Scaled);
+    assert_eq!(client.target(&routed).as_deref(), Some("test/agents"));
+
+    // Unrouted hints go to the fallback
+    let unrouted = create_test_event(Recommendation::ScaleUp, "test-tenant");
+    let decision = client.handle_scale_hint(&unrouted).await.unwrap();
+    assert_eq!(decision.outcome, ScaleOutcome::Skipped);
+    assert_eq!(client.target(&unrouted), None);
+}
+
 // Helper function to create test events
 fn create_test_event(recommendation: Recommendation, tenant_id: &str) -> ScaleHintEvent {
     ScaleHintEvent {
@@ -231,5 +456,7 @@ fn create_test_event(recommendation: Recommendation, tenant_id: &str) -> ScaleHi
         },
         reason: "Test scale hint".to_string(),
         trace_id: None,
+        capsule: None,
+        queue: None,
     }
 }
@@ -0,0 +1,779 @@
+//! Rust config types generated from the capsule config schemas
+//!
+//! Each `contracts/config/<capsule>-config.v1.json` schema becomes a module
+//! with a `<Capsule>Config` struct, so capsules deserialize into types that
+//! cannot drift from their schema. `demonctl contracts codegen-config` writes
+//! the modules to `crates/config-loader/src/generated/`, and with `--check`
+//! fails when the checked-in files are stale.
+//!
+//! Mapping: `string` → `String`, `boolean` → `bool`, `integer` → `i64`,
+//! `number` → `f64`, `array` → `Vec<_>`, objects with `properties` → a nested
+//! struct, other objects → `BTreeMap<String, _>`, string `enum`s → a Rust enum.
+//! Properties not in `required`, and `["<type>", "null"]` unions, are
+//! `Option<_>`. `additionalProperties: false` adds `deny_unknown_fields`, and
+//! schema defaults become the struct's `Default`. Local `$ref`s
+//! (`#/$defs/<name>`, `#/definitions/<name>`) become one shared type, named
+//! `<Root><Name>` like nested types. Anything else (`oneOf`, mixed types)
+//! falls back to `serde_json::Value`.
+//!
+//! [`generate_rust_module`] applies the same mapping to any schema; `demonctl
+//! contracts codegen` uses it for client models of the contract schemas.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// First line of every generated file
+pub const GENERATED_HEADER: &str =
+    "// @generated by `demonctl contracts codegen-config`; do not edit.";
+
+const CONFIG_SUFFIX: &str = "-config.v1.json";
+
+/// One generated source file, relative to the output directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// `echo-config.v1.json` → `("echo_config", "EchoConfig")`
+pub fn config_type_names(schema_file: &str) -> Option<(String, String)> {
+    let capsule = schema_file.strip_suffix(CONFIG_SUFFIX)?;
+    if capsule.is_empty() {
+        return None;
+    }
+    Some((
+        format!("{}_config", snake_case(capsule)),
+        format!("{}Config", pascal_case(capsule)),
+    ))
+}
+
+/// Rust source for one config schema, with `type_name` as the root struct
+pub fn generate_config_module(schema: &Value, type_name: &str) -> Result<String> {
+    generate_rust_module(schema, type_name, GENERATED_HEADER)
+}
+
+/// Rust source for any schema, starting with `header`. An object root becomes
+/// the `type_name` struct; any other root becomes a `type_name` alias.
+pub fn generate_rust_module(schema: &Value, type_name: &str, header: &str) -> Result<String> {
+    let mut generator = Generator {
+        root: schema.clone(),
+        root_name: type_name.to_string(),
+        ..Default::default()
+    };
+    let has_properties = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .is_some_and(|p| !p.is_empty());
+    if has_properties {
+        let description = schema
+            .get("description")
+            .or_else(|| schema.get("title"))
+            .and_then(Value::as_str);
+        generator.object(type_name, schema, description)?;
+    } else {
+        let root = generator.rust_type(schema, type_name)?;
+        if root.name != type_name {
+            let mut alias = doc_comment("", schema.get("description").and_then(Value::as_str));
+            alias.push_str(&format!("pub type {} = {};\n", type_name, option_of(&root)));
+            generator.items.insert(0, alias);
+        }
+    }
+
+    let mut out = format!("{}\n\nuse serde::{{Deserialize, Serialize}};\n", header);
+    if generator.uses_map {
+        out.push_str("use std::collections::BTreeMap;\n");
+    }
+    for item in &generator.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+    Ok(out)
+}
+
+/// Modules for every `*-config.v1.json` schema in `schema_dir`, plus the
+/// `mod.rs` that re-exports them
+pub fn generate_config_modules(schema_dir: &Path) -> Result<Vec<GeneratedFile>> {
+    let mut schemas = Vec::new();
+    for entry in std::fs::read_dir(schema_dir)
+        .with_context(|| format!("Failed to read {}", schema_dir.display()))?
+    {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if let Some((module, type_name)) = config_type_names(file_name) {
+            schemas.push((module, type_name, path));
+        }
+    }
+    if schemas.is_empty() {
+        bail!(
+            "No *{} schemas found in {}",
+            CONFIG_SUFFIX,
+            schema_dir.display()
+        );
+    }
+    schemas.sort();
+
+    let mut files = Vec::new();
+    let mut mod_rs = format!("{}\n\n", GENERATED_HEADER);
+    for (module, type_name, path) in &schemas {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let schema: Value = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let contents = generate_config_module(&schema, type_name)
+            .with_context(|| format!("Failed to generate {}", path.display()))?;
+        files.push(GeneratedFile {
+            path: PathBuf::from(format!("{}.rs", module)),
+            contents,
+        });
+        mod_rs.push_str(&format!("mod {};\n", module));
+    }
+    mod_rs.push('\n');
+    for (module, _, _) in &schemas {
+        mod_rs.push_str(&format!("pub use {}::*;\n", module));
+    }
+    files.push(GeneratedFile {
+        path: PathBuf::from("mod.rs"),
+        contents: mod_rs,
+    });
+    Ok(files)
+}
+
+/// Files in `out_dir` that differ from `files`, are missing, or are files of
+/// the same kind (e.g. `.rs`) no schema generates any more
+pub fn stale_files(out_dir: &Path, files: &[GeneratedFile]) -> Result<Vec<PathBuf>> {
+    let mut stale = Vec::new();
+    for file in files {
+        let path = out_dir.join(&file.path);
+        if std::fs::read_to_string(&path).ok().as_deref() != Some(file.contents.as_str()) {
+            stale.push(path);
+        }
+    }
+    stale.extend(orphans(out_dir, files)?);
+    Ok(stale)
+}
+
+/// Write `files` to `out_dir` and remove files of the same kind no schema
+/// generates any more
+pub fn write_generated(out_dir: &Path, files: &[GeneratedFile]) -> Result<()> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    for orphan in orphans(out_dir, files)? {
+        std::fs::remove_file(&orphan)
+            .with_context(|| format!("Failed to remove {}", orphan.display()))?;
+    }
+    for file in files {
+        let path = out_dir.join(&file.path);
+        std::fs::write(&path, &file.contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn orphans(out_dir: &Path, files: &[GeneratedFile]) -> Result<Vec<PathBuf>> {
+    if !out_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let expected: BTreeSet<&Path> = files.iter().map(|f| f.path.as_path()).collect();
+    let extensions: BTreeSet<_> = files.iter().filter_map(|f| f.path.extension()).collect();
+    let mut orphans = Vec::new();
+    for entry in std::fs::read_dir(out_dir)? {
+        let path = entry?.path();
+        let generated_kind = path.extension().is_some_and(|ext| extensions.contains(ext));
+        let name = path.file_name().map(Path::new);
+        if generated_kind && name.is_some_and(|name| !expected.contains(name)) {
+            orphans.push(path);
+        }
+    }
+    orphans.sort();
+    Ok(orphans)
+}
+
+/// A schema rendered as a Rust type
+#[derive(Clone)]
+struct RustType {
+    name: String,
+    /// `null` is allowed, so the field is an `Option` even when required
+    nullable: bool,
+    /// Variants by JSON value, for rendering enum defaults
+    variants: Vec<(String, String)>,
+    /// A generated struct that implements `Default`
+    has_default: bool,
+}
+
+impl RustType {
+    fn plain(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            nullable: false,
+            variants: Vec::new(),
+            has_default: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Generator {
+    /// The whole schema, for resolving `$ref`s
+    root: Value,
+    root_name: String,
+    items: Vec<String>,
+    names: BTreeSet<String>,
+    /// Types generated for `$defs`, by type name
+    refs: BTreeMap<String, RustType>,
+    uses_map: bool,
+}
+
+impl Generator {
+    fn claim(&mut self, name: &str) -> Result<()> {
+        if !self.names.insert(name.to_string()) {
+            bail!("Generated type name '{}' is used twice", name);
+        }
+        Ok(())
+    }
+
+    fn rust_type(&mut self, schema: &Value, name: &str) -> Result<RustType> {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return self.reference(reference);
+        }
+        let (ty, nullable) = match schema.get("type") {
+            Some(Value::String(ty)) => (Some(ty.as_str()), false),
+            Some(Value::Array(types)) => {
+                let types: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+                let non_null: Vec<&str> = types.iter().copied().filter(|t| *t != "null").collect();
+                let nullable = non_null.len() < types.len();
+                match non_null.as_slice() {
+                    [ty] => (Some(*ty), nullable),
+                    _ => (None, nullable),
+                }
+            }
+            _ if schema.get("enum").is_some() => (Some("string"), false),
+            _ => (None, false),
+        };
+
+        let mut rust = match ty {
+            Some("string") => match string_enum(schema) {
+                Some(values) => self.enumeration(name, schema, &values)?,
+                None => RustType::plain("String"),
+            },
+            Some("boolean") => RustType::plain("bool"),
+            Some("integer") => RustType::plain("i64"),
+            Some("number") => RustType::plain("f64"),
+            Some("array") => {
+                let item = match schema.get("items") {
+                    Some(items) => self.rust_type(items, &format!("{}Item", name))?,
+                    None => RustType::plain("serde_json::Value"),
+                };
+                RustType::plain(&format!("Vec<{}>", option_of(&item)))
+            }
+            Some("object") => {
+                let has_properties = schema
+                    .get("properties")
+                    .and_then(Value::as_object)
+                    .is_some_and(|p| !p.is_empty());
+                if has_properties {
+                    let description = schema.get("description").and_then(Value::as_str);
+                    self.object(name, schema, description)?
+                } else {
+                    self.uses_map = true;
+                    let value = match schema.get("additionalProperties") {
+                        Some(extra @ Value::Object(_)) => {
+                            self.rust_type(extra, &format!("{}Value", name))?
+                        }
+                        _ => RustType::plain("serde_json::Value"),
+                    };
+                    RustType::plain(&format!("BTreeMap<String, {}>", option_of(&value)))
+                }
+            }
+            _ => RustType::plain("serde_json::Value"),
+        };
+        rust.nullable = nullable;
+        Ok(rust)
+    }
+
+    /// The type for a local definition, generated on first use
+    fn reference(&mut self, reference: &str) -> Result<RustType> {
+        let Some(definition) = reference
+            .strip_prefix("#/$defs/")
+            .or_else(|| reference.strip_prefix("#/definitions/"))
+        else {
+            return Ok(RustType::plain("serde_json::Value"));
+        };
+        let name = format!("{}{}", self.root_name, pascal_case(definition));
+        if let Some(ty) = self.refs.get(&name) {
+            return Ok(ty.clone());
+        }
+        let Some(target) = self.root.pointer(&reference[1..]).cloned() else {
+            bail!("Unresolved $ref '{}'", reference);
+        };
+        // A recursive reference sees the type by name while it is generated
+        self.refs.insert(name.clone(), RustType::plain(&name));
+        let ty = self.rust_type(&target, &name)?;
+        self.refs.insert(name, ty.clone());
+        Ok(ty)
+    }
+
+    fn enumeration(&mut self, name: &str, schema: &Value, values: &[&str]) -> Result<RustType> {
+        self.claim(name)?;
+        let mut variants = Vec::new();
+        let mut seen = BTreeSet::new();
+        for value in values {
+            let mut variant = pascal_case(value);
+            if variant.is_empty() || variant.starts_with(|c: char| c.is_ascii_digit()) {
+                variant = format!("V{}", variant);
+            }
+            if !seen.insert(variant.clone()) {
+                bail!(
+                    "Enum values of '{}' map to the same variant '{}'",
+                    name,
+                    variant
+                );
+            }
+            variants.push((value.to_string(), variant));
+        }
+
+        let mut out = doc_comment("", schema.get("description").and_then(Value::as_str));
+        out.push_str("#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]\n");
+        out.push_str(&format!("pub enum {} {{\n", name));
+        for (value, variant) in &variants {
+            if value != variant {
+                out.push_str(&format!("    #[serde(rename = {:?})]\n", value));
+            }
+            out.push_str(&format!("    {},\n", variant));
+        }
+        out.push_str("}\n");
+        self.items.push(out);
+
+        Ok(RustType {
+            name: name.to_string(),
+            nullable: false,
+            variants,
+            has_default: false,
+        })
+    }
+
+    fn object(
+        &mut self,
+        name: &str,
+        schema: &Value,
+        description: Option<&str>,
+    ) -> Result<RustType> {
+        self.claim(name)?;
+        let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+            bail!("'{}' has no properties", name);
+        };
+        let required: BTreeSet<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        // Reserve the slot so the struct comes before its nested types
+        let at = self.items.len();
+        let mut fields = String::new();
+        let mut inits = Vec::new();
+        let mut field_names = BTreeSet::new();
+        for (property, property_schema) in properties {
+            let field = field_name(property);
+            if !field_names.insert(field.clone()) {
+                bail!("Properties of '{}' map to the same field '{}'", name, field);
+            }
+            let ty = self.rust_type(
+                property_schema,
+                &format!("{}{}", name, pascal_case(property)),
+            )?;
+            let optional = ty.nullable || !required.contains(property.as_str());
+            let default = property_schema
+                .get("default")
+                .and_then(|value| default_expr(value, &ty));
+
+            fields.push_str(&doc_comment(
+                "    ",
+                property_schema.get("description").and_then(Value::as_str),
+            ));
+            if field.trim_start_matches("r#") != property {
+                fields.push_str(&format!("    #[serde(rename = {:?})]\n", property));
+            }
+            if optional {
+                fields
+                    .push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+                fields.push_str(&format!("    pub {}: Option<{}>,\n", field, ty.name));
+                let init = match default {
+                    Some(default) => format!("Some({})", default),
+                    None => "None".to_string(),
+                };
+                inits.push((field, Some(init)));
+            } else {
+                fields.push_str(&format!("    pub {}: {},\n", field, ty.name));
+                let init =
+                    default.or_else(|| ty.has_default.then(|| "Default::default()".to_string()));
+                inits.push((field, init));
+            }
+        }
+
+        let has_default = inits.iter().all(|(_, init)| init.is_some());
+        let derivable = has_default
+            && inits
+                .iter()
+                .all(|(_, init)| init.as_deref().is_some_and(is_type_default));
+
+        let mut out = doc_comment("", description);
+        out.push_str(if derivable {
+            "#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]\n"
+        } else {
+            "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n"
+        });
+        if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+            out.push_str("#[serde(deny_unknown_fields)]\n");
+        }
+        out.push_str(&format!("pub struct {} {{\n{}}}\n", name, fields));
+        if has_default && !derivable {
+            out.push_str(&format!(
+                "\nimpl Default for {} {{\n    fn default() -> Self {{\n        Self {{\n",
+                name
+            ));
+            for (field, init) in &inits {
+                out.push_str(&format!(
+                    "            {}: {},\n",
+                    field,
+                    init.as_deref().unwrap_or_default()
+                ));
+            }
+            out.push_str("        }\n    }\n}\n");
+        }
+        self.items.insert(at, out);
+
+        Ok(RustType {
+            name: name.to_string(),
+            nullable: false,
+            variants: Vec::new(),
+            has_default,
+        })
+    }
+}
+
+fn option_of(ty: &RustType) -> String {
+    if ty.nullable {
+        format!("Option<{}>", ty.name)
+    } else {
+        ty.name.clone()
+    }
+}
+
+fn string_enum(schema: &Value) -> Option<Vec<&str>> {
+    let values = schema.get("enum")?.as_array()?;
+    let strings: Vec<&str> = values.iter().filter_map(Value::as_str).collect();
+    (!strings.is_empty() && strings.len() == values.len()).then_some(strings)
+}
+
+/// A Rust expression for a schema default, when the type allows one
+fn default_expr(value: &Value, ty: &RustType) -> Option<String> {
+    match value {
+        Value::Bool(b) if ty.name == "bool" => Some(b.to_string()),
+        Value::Number(n) if ty.name == "i64" => n.as_i64().map(|n| n.to_string()),
+        Value::Number(n) if ty.name == "f64" => n.as_f64().map(|n| format!("{:?}", n)),
+        Value::String(s) if !ty.variants.is_empty() => ty
+            .variants
+            .iter()
+            .find(|(value, _)| value == s)
+            .map(|(_, variant)| format!("{}::{}", ty.name, variant)),
+        Value::String(s) if ty.name == "String" && s.is_empty() => Some("String::new()".into()),
+        Value::String(s) if ty.name == "String" => Some(format!("{:?}.to_string()", s)),
+        Value::Array(items) if items.is_empty() && ty.name.starts_with("Vec<") => {
+            Some("Vec::new()".into())
+        }
+        Value::Object(map) if map.is_empty() && ty.name.starts_with("BTreeMap<") => {
+            Some("BTreeMap::new()".into())
+        }
+        _ => None,
+    }
+}
+
+/// Whether `#[derive(Default)]` would produce the same value
+fn is_type_default(expr: &str) -> bool {
+    matches!(
+        expr,
+        "None"
+            | "false"
+            | "0"
+            | "0.0"
+            | "String::new()"
+            | "Vec::new()"
+            | "BTreeMap::new()"
+            | "Default::default()"
+    )
+}
+
+fn doc_comment(indent: &str, text: Option<&str>) -> String {
+    let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) else {
+        return String::new();
+    };
+    text.lines()
+        .map(|line| {
+            let line = line.trim_end();
+            if line.is_empty() {
+                format!("{}///\n", indent)
+            } else {
+                format!("{}/// {}\n", indent, line)
+            }
+        })
+        .collect()
+}
+
+/// Words of an identifier: split on separators and lower-to-upper case changes
+fn words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = s.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let boundary = c.is_ascii_uppercase()
+            && i > 0
+            && (chars[i - 1].is_ascii_lowercase()
+                || chars[i - 1].is_ascii_digit()
+                || (chars[i - 1].is_ascii_uppercase()
+                    && chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase())));
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// `http-fetch` → `HttpFetch`
+pub fn pascal_case(s: &str) -> String {
+    words(s)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => {
+                    first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+                }
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// `maxMessageLength` → `max_message_length`
+pub fn snake_case(s: &str) -> String {
+    words(s)
+        .iter()
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn field_name(property: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+        "where", "while",
+    ];
+    let name = snake_case(property);
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else if matches!(name.as_str(), "self" | "super" | "crate") {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn type_names_come_from_the_capsule_name() {
+        assert_eq!(
+            config_type_names("echo-config.v1.json"),
+            Some(("echo_config".to_string(), "EchoConfig".to_string()))
+        );
+        assert_eq!(
+            config_type_names("http-fetch-config.v1.json"),
+            Some((
+                "http_fetch_config".to_string(),
+                "HttpFetchConfig".to_string()
+            ))
+        );
+        assert_eq!(config_type_names("events.run.canceled.v1.json"), None);
+    }
+
+    #[test]
+    fn identifiers_follow_rust_conventions() {
+        assert_eq!(field_name("maxMessageLength"), "max_message_length");
+        assert_eq!(field_name("HTTPTimeout"), "http_timeout");
+        assert_eq!(field_name("retry-count"), "retry_count");
+        assert_eq!(field_name("type"), "r#type");
+        assert_eq!(field_name("self"), "self_");
+        assert_eq!(pascal_case("structured"), "Structured");
+        assert_eq!(pascal_case("dry_run"), "DryRun");
+    }
+
+    #[test]
+    fn schema_defaults_become_the_default_impl() {
+        let source = generate_config_module(
+            &json!({
+                "description": "Echo settings",
+                "type": "object",
+                "properties": {
+                    "enableTrim": {"type": "boolean", "default": true},
+                    "outputFormat": {
+                        "type": "string",
+                        "enum": ["plain", "json"],
+                        "default": "plain"
+                    },
+                    "ratio": {"type": "number", "default": 1}
+                },
+                "required": ["enableTrim"],
+                "additionalProperties": false
+            }),
+            "EchoConfig",
+        )
+        .unwrap();
+
+        assert!(source.starts_with(GENERATED_HEADER));
+        assert!(source.contains("/// Echo settings\n#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n#[serde(deny_unknown_fields)]\npub struct EchoConfig {"));
+        assert!(
+            source.contains("    #[serde(rename = \"enableTrim\")]\n    pub enable_trim: bool,\n")
+        );
+        assert!(source.contains("    pub output_format: Option<EchoConfigOutputFormat>,\n"));
+        assert!(
+            source.contains("            output_format: Some(EchoConfigOutputFormat::Plain),\n")
+        );
+        assert!(source.contains("            ratio: Some(1.0),\n"));
+        assert!(source.contains(
+            "pub enum EchoConfigOutputFormat {\n    #[serde(rename = \"plain\")]\n    Plain,\n"
+        ));
+        assert!(source.find("pub struct EchoConfig").unwrap() < source.find("pub enum").unwrap());
+    }
+
+    #[test]
+    fn nested_objects_arrays_and_maps_get_their_own_types() {
+        let source = generate_config_module(
+            &json!({
+                "type": "object",
+                "properties": {
+                    "targets": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {"url": {"type": "string"}},
+                            "required": ["url"]
+                        }
+                    },
+                    "labels": {"type": "object", "additionalProperties": {"type": "string"}},
+                    "token": {"type": ["string", "null"]},
+                    "extra": {"oneOf": [{"type": "string"}, {"type": "integer"}]}
+                },
+                "required": ["targets", "token"]
+            }),
+            "FetchConfig",
+        )
+        .unwrap();
+
+        assert!(source.contains("use std::collections::BTreeMap;\n"));
+        assert!(source.contains("    pub targets: Vec<FetchConfigTargetsItem>,\n"));
+        assert!(source.contains("pub struct FetchConfigTargetsItem {\n    pub url: String,\n}"));
+        assert!(source.contains("    pub labels: Option<BTreeMap<String, String>>,\n"));
+        assert!(source.contains("    pub token: Option<String>,\n"));
+        assert!(source.contains("    pub extra: Option<serde_json::Value>,\n"));
+        // `url` has no default, so neither the item nor the root implements Default
+        assert!(!source.contains("impl Default"));
+        assert!(!source.contains("Default,"));
+    }
+
+    #[test]
+    fn type_defaults_are_derived_instead_of_written_out() {
+        let source = generate_config_module(
+            &json!({
+                "type": "object",
+                "properties": {
+                    "prefix": {"type": "string", "default": ""},
+                    "verbose": {"type": "boolean", "default": false}
+                },
+                "required": ["prefix", "verbose"]
+            }),
+            "QuietConfig",
+        )
+        .unwrap();
+
+        assert!(
+            source.contains("#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]")
+        );
+        assert!(!source.contains("impl Default"));
+    }
+
+    #[test]
+    fn colliding_names_are_rejected() {
+        let error = generate_config_module(
+            &json!({
+                "type": "object",
+                "properties": {"dryRun": {"type": "boolean"}, "dry_run": {"type": "boolean"}}
+            }),
+            "RunConfig",
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("same field 'dry_run'"));
+    }
+
+    #[test]
+    fn refs_become_one_named_type() {
+        let source = generate_rust_module(
+            &json!({
+                "type": "object",
+                "required": ["source"],
+                "properties": {
+                    "source": {"$ref": "#/$defs/endpoint"},
+                    "replicas": {"type": "array", "items": {"$ref": "#/$defs/endpoint"}}
+                },
+                "$defs": {
+                    "endpoint": {
+                        "type": "object",
+                        "properties": {"url": {"type": "string"}}
+                    }
+                }
+            }),
+            "Route",
+            "// header",
+        )
+        .unwrap();
+        assert!(source.starts_with("// header\n"));
+        assert_eq!(source.matches("pub struct RouteEndpoint {").count(), 1);
+        assert!(source.contains("pub source: RouteEndpoint,"));
+        assert!(source.contains("pub replicas: Option<Vec<RouteEndpoint>>,"));
+
+        let error =
+            generate_rust_module(&json!({"$ref": "#/$defs/missing"}), "Missing", "").unwrap_err();
+        assert!(error.to_string().contains("Unresolved $ref"));
+    }
+
+    #[test]
+    fn non_object_roots_become_aliases() {
+        let source = generate_rust_module(
+            &json!({"type": "array", "items": {"type": "string"}}),
+            "Tags",
+            "",
+        )
+        .unwrap();
+        assert!(source.contains("pub type Tags = Vec<String>;"));
+    }
+}
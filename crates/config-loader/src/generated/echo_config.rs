@@ -0,0 +1,45 @@
+// @generated by `demonctl contracts codegen-config`; do not edit.
+
+use serde::{Deserialize, Serialize};
+
+/// Configuration schema for the echo capsule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EchoConfig {
+    /// Whether to trim whitespace from messages
+    #[serde(rename = "enableTrim")]
+    pub enable_trim: bool,
+    /// Maximum length of messages to process
+    #[serde(rename = "maxMessageLength")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_message_length: Option<i64>,
+    /// Prefix to add to echoed messages
+    #[serde(rename = "messagePrefix")]
+    pub message_prefix: String,
+    /// Format for output messages
+    #[serde(rename = "outputFormat")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<EchoConfigOutputFormat>,
+}
+
+impl Default for EchoConfig {
+    fn default() -> Self {
+        Self {
+            enable_trim: true,
+            max_message_length: Some(1000),
+            message_prefix: String::new(),
+            output_format: Some(EchoConfigOutputFormat::Plain),
+        }
+    }
+}
+
+/// Format for output messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EchoConfigOutputFormat {
+    #[serde(rename = "plain")]
+    Plain,
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "structured")]
+    Structured,
+}
@@ -0,0 +1,5 @@
+// @generated by `demonctl contracts codegen-config`; do not edit.
+
+mod echo_config;
+
+pub use echo_config::*;
@@ -0,0 +1,371 @@
+//! Layered configuration resolution
+//!
+//! Config values are merged from, in increasing precedence:
+//! schema defaults → `<link>.json` → `<link>.<env>.json` → `DEMON_CFG_<LINK>__<FIELD>`
+//! environment variables. Every leaf value remembers the layer that supplied it
+//! so `ConfigManager::explain` can report where a setting came from.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Prefix for environment-variable overrides
+pub const ENV_OVERRIDE_PREFIX: &str = "DEMON_CFG_";
+
+/// Environment variable selecting the `<link>.<env>.json` layer
+pub const DEMON_ENV_VAR: &str = "DEMON_ENV";
+
+/// Source of a resolved configuration value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "layer", rename_all = "kebab-case")]
+pub enum ConfigLayer {
+    SchemaDefault,
+    BaseFile { path: String },
+    EnvFile { env: String, path: String },
+    EnvVar { name: String },
+}
+
+impl std::fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigLayer::SchemaDefault => write!(f, "schema default"),
+            ConfigLayer::BaseFile { path } => write!(f, "{}", path),
+            ConfigLayer::EnvFile { path, .. } => write!(f, "{}", path),
+            ConfigLayer::EnvVar { name } => write!(f, "${}", name),
+        }
+    }
+}
+
+/// Resolved configuration plus per-field provenance
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigExplanation {
+    pub link: String,
+    pub environment: Option<String>,
+    pub value: Value,
+    /// JSON pointer of each leaf value → layer that supplied it
+    pub fields: BTreeMap<String, ConfigLayer>,
+}
+
+impl ConfigExplanation {
+    pub fn new(link: &str, environment: Option<String>) -> Self {
+        Self {
+            link: link.to_string(),
+            environment,
+            value: Value::Object(Map::new()),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    /// Layer that supplied the value at `pointer` (e.g. `/messagePrefix`)
+    pub fn source_of(&self, pointer: &str) -> Option<&ConfigLayer> {
+        self.fields.get(pointer)
+    }
+
+    /// Merge `overlay` on top of the current value, attributing leaves to `layer`
+    pub fn apply(&mut self, overlay: Value, layer: &ConfigLayer) {
+        let mut value = std::mem::take(&mut self.value);
+        merge_value(&mut value, overlay, "", layer, &mut self.fields);
+        self.value = value;
+    }
+
+    /// Set a single value at `path`, creating intermediate objects as needed
+    pub fn set_path(&mut self, path: &[String], new_value: Value, layer: &ConfigLayer) {
+        let mut overlay = new_value;
+        for segment in path.iter().rev() {
+            let mut map = Map::new();
+            map.insert(segment.clone(), overlay);
+            overlay = Value::Object(map);
+        }
+        self.apply(overlay, layer);
+    }
+}
+
+fn merge_value(
+    base: &mut Value,
+    overlay: Value,
+    pointer: &str,
+    layer: &ConfigLayer,
+    fields: &mut BTreeMap<String, ConfigLayer>,
+) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                let child_pointer = format!("{}/{}", pointer, escape_pointer(&key));
+                if value.is_object() && base_map.get(&key).is_some_and(Value::is_object) {
+                    if let Some(existing) = base_map.get_mut(&key) {
+                        merge_value(existing, value, &child_pointer, layer, fields);
+                    }
+                } else {
+                    forget_subtree(fields, &child_pointer);
+                    record_leaves(&value, &child_pointer, layer, fields);
+                    base_map.insert(key, value);
+                }
+            }
+        }
+        (base, overlay) => {
+            forget_subtree(fields, pointer);
+            record_leaves(&overlay, pointer, layer, fields);
+            *base = overlay;
+        }
+    }
+}
+
+fn record_leaves(
+    value: &Value,
+    pointer: &str,
+    layer: &ConfigLayer,
+    fields: &mut BTreeMap<String, ConfigLayer>,
+) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                record_leaves(
+                    child,
+                    &format!("{}/{}", pointer, escape_pointer(key)),
+                    layer,
+                    fields,
+                );
+            }
+        }
+        _ => {
+            fields.insert(pointer.to_string(), layer.clone());
+        }
+    }
+}
+
+fn forget_subtree(fields: &mut BTreeMap<String, ConfigLayer>, pointer: &str) {
+    let nested = format!("{}/", pointer);
+    fields.retain(|key, _| key != pointer && !key.starts_with(&nested));
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+/// Extract top-level `default` values from a config schema
+///
+/// When `include_required` is false, defaults for required properties are
+/// skipped so an explicit config file must still set them itself.
+pub fn schema_defaults(schema: &Value, include_required: bool) -> Value {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    let mut defaults = Map::new();
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        for (key, property) in properties {
+            if !include_required && required.contains(&key.as_str()) {
+                continue;
+            }
+            if let Some(default_value) = property.get("default") {
+                defaults.insert(key.clone(), default_value.clone());
+            }
+        }
+    }
+    Value::Object(defaults)
+}
+
+/// Environment variable prefix for a link, e.g. `container-exec` → `DEMON_CFG_CONTAINER_EXEC__`
+pub fn env_override_prefix(link: &str) -> String {
+    format!(
+        "{}{}__",
+        ENV_OVERRIDE_PREFIX,
+        link.to_ascii_uppercase().replace(['-', '.'], "_")
+    )
+}
+
+/// A single environment-variable override resolved against the schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvOverride {
+    pub var: String,
+    pub path: Vec<String>,
+    pub value: Value,
+}
+
+/// Collect overrides for `link` from the given environment variables
+///
+/// Field names are matched case-insensitively against schema properties
+/// ignoring `_`/`-`, so `MESSAGE_PREFIX` maps to `messagePrefix`; nested
+/// fields are separated by `__`. Values are typed using the schema where
+/// possible and otherwise parsed as JSON, falling back to a plain string.
+pub fn env_overrides<I>(link: &str, vars: I, schema: Option<&Value>) -> Vec<EnvOverride>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let prefix = env_override_prefix(link);
+    let mut overrides: Vec<EnvOverride> = vars
+        .into_iter()
+        .filter_map(|(var, raw)| {
+            let field = var.strip_prefix(&prefix)?;
+            if field.is_empty() {
+                return None;
+            }
+
+            let mut path = Vec::new();
+            let mut property_schema = schema;
+            for segment in field.split("__").filter(|s| !s.is_empty()) {
+                let properties = property_schema
+                    .and_then(|s| s.get("properties"))
+                    .and_then(|p| p.as_object());
+                let name = properties
+                    .and_then(|props| {
+                        props
+                            .keys()
+                            .find(|k| normalize_key(k) == normalize_key(segment))
+                            .cloned()
+                    })
+                    .unwrap_or_else(|| camel_case(segment));
+                property_schema = properties.and_then(|props| props.get(&name));
+                path.push(name);
+            }
+            if path.is_empty() {
+                return None;
+            }
+
+            let value = typed_value(&raw, property_schema);
+            Some(EnvOverride { var, path, value })
+        })
+        .collect();
+
+    // Deterministic application order regardless of environment iteration order
+    overrides.sort_by(|a, b| a.var.cmp(&b.var));
+    overrides
+}
+
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn camel_case(segment: &str) -> String {
+    let mut out = String::new();
+    for (idx, part) in segment.split('_').filter(|p| !p.is_empty()).enumerate() {
+        let lower = part.to_ascii_lowercase();
+        if idx == 0 {
+            out.push_str(&lower);
+        } else {
+            let mut chars = lower.chars();
+            if let Some(first) = chars.next() {
+                out.extend(first.to_uppercase());
+                out.push_str(chars.as_str());
+            }
+        }
+    }
+    out
+}
+
+fn typed_value(raw: &str, property_schema: Option<&Value>) -> Value {
+    let declared_type = property_schema
+        .and_then(|s| s.get("type"))
+        .and_then(|t| t.as_str());
+
+    if declared_type == Some("string") {
+        return Value::String(raw.to_string());
+    }
+
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "messagePrefix": { "type": "string", "default": "" },
+                "enableTrim": { "type": "boolean", "default": true },
+                "maxMessageLength": { "type": "integer", "default": 1000 },
+                "limits": {
+                    "type": "object",
+                    "properties": { "maxRetries": { "type": "integer" } }
+                }
+            },
+            "required": ["messagePrefix", "enableTrim"]
+        })
+    }
+
+    #[test]
+    fn env_var_names_map_to_schema_properties() {
+        let vars = vec![
+            (
+                "DEMON_CFG_ECHO__MESSAGE_PREFIX".to_string(),
+                "123".to_string(),
+            ),
+            (
+                "DEMON_CFG_ECHO__LIMITS__MAX_RETRIES".to_string(),
+                "5".to_string(),
+            ),
+            ("DEMON_CFG_ECHO__NEW_FIELD".to_string(), "true".to_string()),
+            (
+                "DEMON_CFG_OTHER__MESSAGE_PREFIX".to_string(),
+                "x".to_string(),
+            ),
+        ];
+
+        let overrides = env_overrides("echo", vars, Some(&schema()));
+
+        assert_eq!(overrides.len(), 3);
+        assert_eq!(overrides[0].path, vec!["limits", "maxRetries"]);
+        assert_eq!(overrides[0].value, json!(5));
+        assert_eq!(overrides[1].path, vec!["messagePrefix"]);
+        assert_eq!(overrides[1].value, json!("123"));
+        assert_eq!(overrides[2].path, vec!["newField"]);
+        assert_eq!(overrides[2].value, json!(true));
+    }
+
+    #[test]
+    fn later_layers_win_and_are_attributed() {
+        let base = ConfigLayer::BaseFile {
+            path: "echo.json".to_string(),
+        };
+        let env_var = ConfigLayer::EnvVar {
+            name: "DEMON_CFG_ECHO__LIMITS__MAX_RETRIES".to_string(),
+        };
+
+        let mut explanation = ConfigExplanation::new("echo", None);
+        explanation.apply(
+            schema_defaults(&schema(), true),
+            &ConfigLayer::SchemaDefault,
+        );
+        explanation.apply(
+            json!({ "messagePrefix": "file", "limits": { "maxRetries": 1 } }),
+            &base,
+        );
+        explanation.set_path(
+            &["limits".to_string(), "maxRetries".to_string()],
+            json!(3),
+            &env_var,
+        );
+
+        assert_eq!(explanation.value["messagePrefix"], "file");
+        assert_eq!(explanation.value["limits"]["maxRetries"], 3);
+        assert_eq!(explanation.source_of("/messagePrefix"), Some(&base));
+        assert_eq!(
+            explanation.source_of("/enableTrim"),
+            Some(&ConfigLayer::SchemaDefault)
+        );
+        assert_eq!(explanation.source_of("/limits/maxRetries"), Some(&env_var));
+    }
+
+    #[test]
+    fn optional_defaults_skip_required_properties() {
+        let defaults = schema_defaults(&schema(), false);
+
+        assert_eq!(defaults, json!({ "maxMessageLength": 1000 }));
+    }
+
+    #[test]
+    fn link_names_are_normalized_in_prefix() {
+        assert_eq!(
+            env_override_prefix("container-exec"),
+            "DEMON_CFG_CONTAINER_EXEC__"
+        );
+    }
+}
@@ -2,19 +2,36 @@ use anyhow::Result;
 use jsonschema::{Draft, JSONSchema};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 use thiserror::Error;
 use tracing::{debug, instrument};
 
+pub mod codegen;
+/// Config types generated from `contracts/config`; see [`codegen`]
+pub mod generated;
+pub mod layers;
 pub mod provider_factory;
+pub mod remote_source;
 pub mod secrets;
+pub mod secrets_crypto;
 pub mod secrets_store;
 pub mod vault_http;
+pub use layers::{ConfigExplanation, ConfigLayer};
 pub use provider_factory::{ProviderFactoryError, SecretProviderFactory, VaultStubProvider};
-pub use secrets::{EnvFileSecretProvider, SecretError, SecretProvider};
+pub use remote_source::{ConfigSource, RemoteConfigError, RemoteConfigSource, SyncReport};
+pub use secrets::{
+    EnvFileSecretProvider, SecretError, SecretMetadata, SecretProvider, SecretRotationPolicy,
+    SecretWarning,
+};
+pub use secrets_crypto::{CryptoError, SecretsKey};
 pub use secrets_store::{SecretsStore, StoreError};
-pub use vault_http::VaultHttpSecretProvider;
+pub use vault_http::{TokenLease, VaultHttpSecretProvider};
 
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -47,9 +64,20 @@ pub struct ValidationError {
     pub schema_path: String,
 }
 
+/// Compiled schema plus the file fingerprint it was compiled from
+struct CachedSchema {
+    modified: Option<SystemTime>,
+    len: u64,
+    content_hash: u64,
+    schema: Arc<JSONSchema>,
+}
+
 pub struct ConfigManager {
     contracts_dir: PathBuf,
     config_dir: PathBuf,
+    environment: Option<String>,
+    rotation_policy: SecretRotationPolicy,
+    schema_cache: Mutex<HashMap<String, CachedSchema>>,
 }
 
 impl ConfigManager {
@@ -58,23 +86,93 @@ impl ConfigManager {
             Self::find_contracts_dir().unwrap_or_else(|| PathBuf::from("contracts"));
         let config_dir = Self::find_config_dir();
 
-        Self {
-            contracts_dir,
-            config_dir,
-        }
+        Self::with_dirs(contracts_dir, config_dir)
     }
 
     pub fn with_dirs(contracts_dir: PathBuf, config_dir: PathBuf) -> Self {
+        let environment = std::env::var(layers::DEMON_ENV_VAR)
+            .ok()
+            .filter(|env| !env.trim().is_empty());
+
         Self {
             contracts_dir,
             config_dir,
+            environment,
+            rotation_policy: SecretRotationPolicy::from_env(),
+            schema_cache: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Select the `<link>.<env>.json` overlay explicitly instead of via `DEMON_ENV`
+    pub fn with_environment(mut self, environment: Option<&str>) -> Self {
+        self.environment = environment.map(str::to_string);
+        self
+    }
+
+    /// Override the secret max-age policy read from `CONFIG_SECRETS_MAX_AGE_DAYS`
+    pub fn with_rotation_policy(mut self, policy: SecretRotationPolicy) -> Self {
+        self.rotation_policy = policy;
+        self
+    }
+
     pub fn config_dir(&self) -> &PathBuf {
         &self.config_dir
     }
 
+    pub fn environment(&self) -> Option<&str> {
+        self.environment.as_deref()
+    }
+
+    /// Resolve the layered config for a link and report which layer supplied each field
+    ///
+    /// Secrets are left unresolved and the result is not validated, so this
+    /// is safe to call for diagnostics even when the config is invalid.
+    #[instrument(skip(self))]
+    pub fn explain(&self, link_name: &str) -> Result<ConfigExplanation, ConfigError> {
+        self.resolve_layers(link_name, std::env::vars())
+    }
+
+    /// Compile and cache the schemas for the given capsules up front
+    ///
+    /// Hot paths can call this at startup so the first invocation of each
+    /// capsule does not pay the schema compilation cost.
+    #[instrument(skip(self))]
+    pub fn preload(&self, capsules: &[&str]) -> Result<(), ConfigError> {
+        for capsule in capsules {
+            self.get_compiled_schema(capsule)?;
+        }
+        Ok(())
+    }
+
+    /// Drop the cached schema for a capsule so the next use recompiles it
+    pub fn invalidate_schema(&self, capsule: &str) {
+        self.lock_schema_cache().remove(capsule);
+    }
+
+    /// Drop all cached schemas
+    pub fn clear_schema_cache(&self) {
+        self.lock_schema_cache().clear();
+    }
+
+    /// Number of compiled schemas currently cached
+    pub fn cached_schema_count(&self) -> usize {
+        self.lock_schema_cache().len()
+    }
+
+    fn lock_schema_cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedSchema>> {
+        // A panic while holding the lock cannot leave a half-written entry, so
+        // recover the cache rather than propagating the poison.
+        self.schema_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn schema_path(&self, capsule: &str) -> PathBuf {
+        self.contracts_dir
+            .join("config")
+            .join(format!("{}-config.v1.json", capsule))
+    }
+
     fn find_contracts_dir() -> Option<PathBuf> {
         // Check environment variable first
         if let Ok(contracts_dir) = std::env::var("CONTRACTS_DIR") {
@@ -101,6 +199,8 @@ impl ConfigManager {
     fn find_config_dir() -> PathBuf {
         if let Ok(config_dir) = std::env::var("CONFIG_DIR") {
             PathBuf::from(config_dir)
+        } else if remote_source::ConfigSource::from_env() == remote_source::ConfigSource::Kv {
+            remote_source::cache_dir_from_env()
         } else {
             PathBuf::from(".demon/config")
         }
@@ -117,21 +217,38 @@ impl ConfigManager {
         link_name: &str,
         provider: &P,
     ) -> Result<T, ConfigError> {
+        self.load_with_warnings(link_name, provider)
+            .map(|(config, _warnings)| config)
+    }
+
+    /// Load a config and return any secret staleness warnings alongside it
+    #[instrument(skip(self, provider))]
+    pub fn load_with_warnings<T: DeserializeOwned, P: SecretProvider + ?Sized>(
+        &self,
+        link_name: &str,
+        provider: &P,
+    ) -> Result<(T, Vec<SecretWarning>), ConfigError> {
         debug!("Loading config for link: {}", link_name);
 
         // Load and validate against schema
         let mut config_value = self.load_config_file(link_name)?;
 
         // Resolve secrets before validation
-        secrets::resolve_secrets_in_config(&mut config_value, provider)
-            .map_err(|e| ConfigError::SecretResolutionFailed { error: e })?;
+        let warnings = secrets::resolve_secrets_in_config_with_policy(
+            &mut config_value,
+            provider,
+            &self.rotation_policy,
+        )
+        .map_err(|e| ConfigError::SecretResolutionFailed { error: e })?;
 
         self.validate_config(link_name, &config_value)?;
 
         // Deserialize to the target type
-        serde_json::from_value(config_value).map_err(|e| ConfigError::JsonParsingFailed {
-            message: e.to_string(),
-        })
+        let config =
+            serde_json::from_value(config_value).map_err(|e| ConfigError::JsonParsingFailed {
+                message: e.to_string(),
+            })?;
+        Ok((config, warnings))
     }
 
     #[instrument(skip(self))]
@@ -171,8 +288,12 @@ impl ConfigManager {
             })?;
 
         // Resolve secrets before validation
-        secrets::resolve_secrets_in_config(&mut config_value, provider)
-            .map_err(|e| ConfigError::SecretResolutionFailed { error: e })?;
+        secrets::resolve_secrets_in_config_with_policy(
+            &mut config_value,
+            provider,
+            &self.rotation_policy,
+        )
+        .map_err(|e| ConfigError::SecretResolutionFailed { error: e })?;
 
         self.validate_config(capsule, &config_value)
     }
@@ -200,73 +321,105 @@ impl ConfigManager {
         let mut config_value = config_value.clone();
 
         // Resolve secrets before validation
-        secrets::resolve_secrets_in_config(&mut config_value, provider)
-            .map_err(|e| ConfigError::SecretResolutionFailed { error: e })?;
+        secrets::resolve_secrets_in_config_with_policy(
+            &mut config_value,
+            provider,
+            &self.rotation_policy,
+        )
+        .map_err(|e| ConfigError::SecretResolutionFailed { error: e })?;
 
         self.validate_config(capsule, &config_value)
     }
 
     fn load_config_file(&self, link_name: &str) -> Result<Value, ConfigError> {
-        let config_path = self.config_dir.join(format!("{}.json", link_name));
-
-        debug!("Loading config from: {:?}", config_path);
+        let explanation = self.explain(link_name)?;
+        debug!("Resolved layered config: {}", explanation.value);
+        Ok(explanation.value)
+    }
 
-        if !config_path.exists() {
-            debug!("Config file not found, loading defaults from schema");
-            return self.load_default_config(link_name);
-        }
+    fn resolve_layers<I>(&self, link_name: &str, vars: I) -> Result<ConfigExplanation, ConfigError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let schema_value = self.read_schema_value(link_name)?;
 
-        let content = fs::read_to_string(&config_path).map_err(|e| ConfigError::IoError {
-            message: format!("Failed to read config file: {}", e),
-        })?;
+        let base_path = self.config_dir.join(format!("{}.json", link_name));
+        let base = read_optional_json(&base_path)?;
 
-        serde_json::from_str(&content).map_err(|e| ConfigError::JsonParsingFailed {
-            message: e.to_string(),
-        })
-    }
+        let env_file = match &self.environment {
+            Some(env) => {
+                let env_path = self.config_dir.join(format!("{}.{}.json", link_name, env));
+                read_optional_json(&env_path)?.map(|value| (env.clone(), env_path, value))
+            }
+            None => None,
+        };
 
-    fn load_default_config(&self, link_name: &str) -> Result<Value, ConfigError> {
-        // Load the schema to extract defaults
-        let schema_path = self
-            .contracts_dir
-            .join("config")
-            .join(format!("{}-config.v1.json", link_name));
+        let overrides = layers::env_overrides(link_name, vars, schema_value.as_ref());
 
-        if !schema_path.exists() {
+        let has_file = base.is_some() || env_file.is_some();
+        if schema_value.is_none() && !has_file && overrides.is_empty() {
             return Err(ConfigError::SchemaNotFound {
                 capsule: link_name.to_string(),
             });
         }
 
-        let schema_content =
-            fs::read_to_string(&schema_path).map_err(|e| ConfigError::IoError {
-                message: format!("Failed to read schema file: {}", e),
-            })?;
+        let mut explanation = ConfigExplanation::new(link_name, self.environment.clone());
 
-        let schema_value: Value =
-            serde_json::from_str(&schema_content).map_err(|e| ConfigError::JsonParsingFailed {
-                message: e.to_string(),
-            })?;
+        // Required-field defaults only apply when no config file exists, so an
+        // explicit config file must still set every required field itself.
+        if let Some(schema) = &schema_value {
+            debug!("Applying schema defaults for {}", link_name);
+            explanation.apply(
+                layers::schema_defaults(schema, !has_file),
+                &ConfigLayer::SchemaDefault,
+            );
+        }
+
+        if let Some(value) = base {
+            debug!("Applying config file: {:?}", base_path);
+            explanation.apply(
+                value,
+                &ConfigLayer::BaseFile {
+                    path: base_path.to_string_lossy().to_string(),
+                },
+            );
+        }
 
-        // Extract defaults from schema properties
-        let mut default_config = serde_json::Map::new();
+        if let Some((env, env_path, value)) = env_file {
+            debug!("Applying {} overlay: {:?}", env, env_path);
+            explanation.apply(
+                value,
+                &ConfigLayer::EnvFile {
+                    env,
+                    path: env_path.to_string_lossy().to_string(),
+                },
+            );
+        }
 
-        if let Some(properties) = schema_value.get("properties").and_then(|p| p.as_object()) {
-            for (key, property) in properties {
-                if let Some(default_value) = property.get("default") {
-                    default_config.insert(key.clone(), default_value.clone());
-                }
-            }
+        for o in overrides {
+            debug!("Applying environment override: {}", o.var);
+            explanation.set_path(&o.path, o.value, &ConfigLayer::EnvVar { name: o.var });
         }
 
-        let default_config_value = Value::Object(default_config);
+        Ok(explanation)
+    }
 
-        // Validate that the default config is valid according to the schema
-        // This ensures the schema defaults work correctly
-        self.validate_config(link_name, &default_config_value)?;
+    fn read_schema_value(&self, capsule: &str) -> Result<Option<Value>, ConfigError> {
+        let schema_path = self.schema_path(capsule);
+        if !schema_path.exists() {
+            return Ok(None);
+        }
 
-        debug!("Loaded default config: {}", default_config_value);
-        Ok(default_config_value)
+        let schema_content =
+            fs::read_to_string(&schema_path).map_err(|e| ConfigError::IoError {
+                message: format!("Failed to read schema file: {}", e),
+            })?;
+
+        serde_json::from_str(&schema_content)
+            .map(Some)
+            .map_err(|e| ConfigError::JsonParsingFailed {
+                message: e.to_string(),
+            })
     }
 
     fn validate_config(&self, capsule: &str, config: &Value) -> Result<(), ConfigError> {
@@ -290,40 +443,95 @@ impl ConfigManager {
         Ok(())
     }
 
-    fn get_compiled_schema(&self, capsule: &str) -> Result<JSONSchema, ConfigError> {
-        // Load and compile schema (simplified without caching for now)
-        let schema_path = self
-            .contracts_dir
-            .join("config")
-            .join(format!("{}-config.v1.json", capsule));
+    fn get_compiled_schema(&self, capsule: &str) -> Result<Arc<JSONSchema>, ConfigError> {
+        let schema_path = self.schema_path(capsule);
 
-        if !schema_path.exists() {
-            return Err(ConfigError::SchemaNotFound {
-                capsule: capsule.to_string(),
-            });
+        let metadata = match fs::metadata(&schema_path) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                self.invalidate_schema(capsule);
+                return Err(ConfigError::SchemaNotFound {
+                    capsule: capsule.to_string(),
+                });
+            }
+        };
+        let modified = metadata.modified().ok();
+        let len = metadata.len();
+
+        // Fast path: file fingerprint unchanged since compilation
+        if let Some(cached) = self.lock_schema_cache().get(capsule) {
+            if cached.modified.is_some() && cached.modified == modified && cached.len == len {
+                return Ok(Arc::clone(&cached.schema));
+            }
         }
 
         let schema_content =
             fs::read_to_string(&schema_path).map_err(|e| ConfigError::IoError {
                 message: format!("Failed to read schema file: {}", e),
             })?;
+        let content_hash = hash_content(&schema_content);
+
+        // File was touched but content is identical: refresh the fingerprint only
+        if let Some(cached) = self.lock_schema_cache().get_mut(capsule) {
+            if cached.content_hash == content_hash {
+                cached.modified = modified;
+                cached.len = len;
+                return Ok(Arc::clone(&cached.schema));
+            }
+        }
+
+        debug!("Compiling config schema for capsule: {}", capsule);
 
         let schema_value: Value =
             serde_json::from_str(&schema_content).map_err(|e| ConfigError::JsonParsingFailed {
                 message: e.to_string(),
             })?;
 
-        let compiled_schema = JSONSchema::options()
-            .with_draft(Draft::Draft7)
-            .compile(&schema_value)
-            .map_err(|e| ConfigError::SchemaCompilationFailed {
-                message: e.to_string(),
-            })?;
+        let compiled_schema = Arc::new(
+            JSONSchema::options()
+                .with_draft(Draft::Draft7)
+                .compile(&schema_value)
+                .map_err(|e| ConfigError::SchemaCompilationFailed {
+                    message: e.to_string(),
+                })?,
+        );
+
+        self.lock_schema_cache().insert(
+            capsule.to_string(),
+            CachedSchema {
+                modified,
+                len,
+                content_hash,
+                schema: Arc::clone(&compiled_schema),
+            },
+        );
 
         Ok(compiled_schema)
     }
 }
 
+fn read_optional_json(path: &Path) -> Result<Option<Value>, ConfigError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| ConfigError::IoError {
+        message: format!("Failed to read config file: {}", e),
+    })?;
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| ConfigError::JsonParsingFailed {
+            message: e.to_string(),
+        })
+}
+
+fn hash_content(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
 impl Default for ConfigManager {
     fn default() -> Self {
         Self::new()
@@ -333,22 +541,10 @@ impl Default for ConfigManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde::Deserialize;
+    use crate::generated::{EchoConfig, EchoConfigOutputFormat};
     use std::fs;
     use tempfile::TempDir;
 
-    #[derive(Deserialize, Debug, PartialEq)]
-    struct EchoConfig {
-        #[serde(rename = "messagePrefix")]
-        message_prefix: String,
-        #[serde(rename = "enableTrim")]
-        enable_trim: bool,
-        #[serde(rename = "maxMessageLength")]
-        max_message_length: Option<i32>,
-        #[serde(rename = "outputFormat")]
-        output_format: Option<String>,
-    }
-
     fn setup_test_env() -> (TempDir, ConfigManager) {
         let temp_dir = TempDir::new().unwrap();
         let contracts_dir = temp_dir.path().join("contracts");
@@ -398,7 +594,7 @@ mod tests {
         assert_eq!(config.message_prefix, "Test: ");
         assert!(config.enable_trim);
         assert_eq!(config.max_message_length, Some(500));
-        assert_eq!(config.output_format, Some("plain".to_string()));
+        assert_eq!(config.output_format, Some(EchoConfigOutputFormat::Plain));
     }
 
     #[test]
@@ -449,7 +645,64 @@ mod tests {
         assert_eq!(config.message_prefix, "");
         assert!(config.enable_trim);
         assert_eq!(config.max_message_length, Some(1000));
-        assert_eq!(config.output_format, Some("plain".to_string()));
+        assert_eq!(config.output_format, Some(EchoConfigOutputFormat::Plain));
+    }
+
+    #[test]
+    fn test_preload_caches_compiled_schema() {
+        let (_temp_dir, manager) = setup_test_env();
+
+        manager.preload(&["echo"]).unwrap();
+        assert_eq!(manager.cached_schema_count(), 1);
+
+        let first = manager.get_compiled_schema("echo").unwrap();
+        let second = manager.get_compiled_schema("echo").unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn test_preload_unknown_capsule_fails() {
+        let (_temp_dir, manager) = setup_test_env();
+
+        let result = manager.preload(&["echo", "missing"]);
+        assert!(matches!(result, Err(ConfigError::SchemaNotFound { .. })));
+    }
+
+    #[test]
+    fn test_schema_change_invalidates_cache() {
+        let (_temp_dir, manager) = setup_test_env();
+
+        let config = serde_json::json!({ "messagePrefix": "x", "enableTrim": true });
+        assert!(manager.validate_config_value("echo", &config).is_ok());
+        let before = manager.get_compiled_schema("echo").unwrap();
+
+        // Tighten the schema: messagePrefix must now be at least 5 characters
+        let stricter = r#"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": {
+                "messagePrefix": { "type": "string", "minLength": 5 },
+                "enableTrim": { "type": "boolean" }
+            },
+            "required": ["messagePrefix", "enableTrim"]
+        }"#;
+        fs::write(manager.schema_path("echo"), stricter).unwrap();
+
+        assert!(manager.validate_config_value("echo", &config).is_err());
+        let after = manager.get_compiled_schema("echo").unwrap();
+        assert!(!Arc::ptr_eq(&before, &after));
+    }
+
+    #[test]
+    fn test_invalidate_schema_forces_recompile() {
+        let (_temp_dir, manager) = setup_test_env();
+
+        let before = manager.get_compiled_schema("echo").unwrap();
+        manager.invalidate_schema("echo");
+        assert_eq!(manager.cached_schema_count(), 0);
+
+        let after = manager.get_compiled_schema("echo").unwrap();
+        assert!(!Arc::ptr_eq(&before, &after));
     }
 
     #[test]
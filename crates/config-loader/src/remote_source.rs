@@ -0,0 +1,431 @@
+//! Capsule config documents served from a JetStream KV bucket
+//!
+//! With `CONFIG_SOURCE=kv`, runtimes read config from a shared bucket instead
+//! of files synchronized onto every host. Keys follow the file naming: `echo`
+//! holds the base document for the `echo` link and `echo.prod` its `prod`
+//! overlay. [`RemoteConfigSource`] mirrors the bucket into a local cache
+//! directory that [`ConfigManager`](crate::ConfigManager) reads like any
+//! `CONFIG_DIR`, so loading stays synchronous and a host keeps its last good
+//! config while NATS is unreachable.
+//!
+//! Base documents are validated against the capsule schema before they are
+//! cached; a document that fails keeps the previously cached copy in place.
+
+use crate::{ConfigError, ConfigManager};
+use async_nats::jetstream::{self, kv};
+use futures_util::StreamExt;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+/// Selects where capsule config comes from
+pub const CONFIG_SOURCE_VAR: &str = "CONFIG_SOURCE";
+pub const DEFAULT_CONFIG_BUCKET: &str = "DEMON_CONFIG";
+pub const DEFAULT_CACHE_DIR: &str = ".demon/config-cache";
+
+#[derive(Error, Debug)]
+pub enum RemoteConfigError {
+    #[error("Config KV connection failed: {message}")]
+    ConnectionFailed { message: String },
+
+    #[error("Config KV request failed: {message}")]
+    RequestFailed { message: String },
+
+    #[error("Config key '{key}' is not a valid config document name")]
+    InvalidKey { key: String },
+
+    #[error("Config document '{key}' rejected: {message}")]
+    InvalidDocument { key: String, message: String },
+
+    #[error("Config cache error: {message}")]
+    CacheFailed { message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigSource {
+    /// `<CONFIG_DIR>/<link>.json` files on the host
+    #[default]
+    File,
+    /// Documents in a JetStream KV bucket, cached under `CONFIG_KV_CACHE_DIR`
+    Kv,
+}
+
+impl ConfigSource {
+    /// `CONFIG_SOURCE`, `file` when unset; unknown values fall back to `file`
+    pub fn from_env() -> Self {
+        match env::var(CONFIG_SOURCE_VAR) {
+            Ok(value) if value.trim().eq_ignore_ascii_case("kv") => Self::Kv,
+            Ok(value) if !value.trim().is_empty() && !value.trim().eq_ignore_ascii_case("file") => {
+                warn!(
+                    "Unknown {}='{}'; reading config files",
+                    CONFIG_SOURCE_VAR, value
+                );
+                Self::File
+            }
+            _ => Self::File,
+        }
+    }
+}
+
+/// `CONFIG_KV_CACHE_DIR`, or `.demon/config-cache`
+pub fn cache_dir_from_env() -> PathBuf {
+    env::var("CONFIG_KV_CACHE_DIR")
+        .ok()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CACHE_DIR))
+}
+
+/// What one [`RemoteConfigSource::sync`] changed in the cache
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub updated: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub removed: Vec<String>,
+    /// Keys left at their cached copy, with the reason
+    pub rejected: Vec<(String, String)>,
+}
+
+/// Outcome of writing one document into the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheUpdate {
+    Updated,
+    Unchanged,
+}
+
+/// The directory of `<key>.json` files mirrored from the bucket
+pub struct ConfigCache {
+    dir: PathBuf,
+    validator: ConfigManager,
+}
+
+impl ConfigCache {
+    /// Validate base documents with the schemas `validator` finds
+    pub fn new(dir: impl Into<PathBuf>, validator: ConfigManager) -> Self {
+        Self {
+            dir: dir.into(),
+            validator,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Validate `bytes` as the document for `key` and write it to the cache
+    pub fn apply(&self, key: &str, bytes: &[u8]) -> Result<CacheUpdate, RemoteConfigError> {
+        let path = self.path_for(key)?;
+        let invalid = |message: String| RemoteConfigError::InvalidDocument {
+            key: key.to_string(),
+            message,
+        };
+        let document: Value =
+            serde_json::from_slice(bytes).map_err(|e| invalid(format!("not JSON: {}", e)))?;
+        if !document.is_object() {
+            return Err(invalid("must be a JSON object".to_string()));
+        }
+
+        // Overlays are partial, so only base documents can be checked alone
+        if !key.contains('.') {
+            match self.validator.validate_config(key, &document) {
+                Ok(()) | Err(ConfigError::SchemaNotFound { .. }) => {}
+                Err(ConfigError::ValidationFailed { errors }) => {
+                    let messages: Vec<String> = errors
+                        .iter()
+                        .map(|e| format!("{}: {}", e.json_pointer, e.message))
+                        .collect();
+                    return Err(invalid(messages.join("; ")));
+                }
+                Err(e) => return Err(invalid(e.to_string())),
+            }
+        }
+
+        if fs::read(&path).is_ok_and(|cached| cached == bytes) {
+            return Ok(CacheUpdate::Unchanged);
+        }
+        self.write_atomically(&path, bytes)?;
+        debug!("Cached config document {} at {:?}", key, path);
+        Ok(CacheUpdate::Updated)
+    }
+
+    /// Drop the cached document for `key`; `false` when none was cached
+    pub fn remove(&self, key: &str) -> Result<bool, RemoteConfigError> {
+        let path = self.path_for(key)?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(RemoteConfigError::CacheFailed {
+                message: format!("Failed to remove {:?}: {}", path, e),
+            }),
+        }
+    }
+
+    /// Keys of every cached document
+    pub fn cached_keys(&self) -> Result<BTreeSet<String>, RemoteConfigError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeSet::new()),
+            Err(e) => {
+                return Err(RemoteConfigError::CacheFailed {
+                    message: format!("Failed to read {:?}: {}", self.dir, e),
+                })
+            }
+        };
+        Ok(entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.strip_suffix(".json"))
+                    .filter(|key| valid_key(key))
+                    .map(str::to_string)
+            })
+            .collect())
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf, RemoteConfigError> {
+        if !valid_key(key) {
+            return Err(RemoteConfigError::InvalidKey {
+                key: key.to_string(),
+            });
+        }
+        Ok(self.dir.join(format!("{}.json", key)))
+    }
+
+    /// Write through a temp file so readers never see a partial document
+    fn write_atomically(&self, path: &Path, bytes: &[u8]) -> Result<(), RemoteConfigError> {
+        let failed = |e: std::io::Error| RemoteConfigError::CacheFailed {
+            message: format!("Failed to write {:?}: {}", path, e),
+        };
+        fs::create_dir_all(&self.dir).map_err(failed)?;
+        let mut file = tempfile::NamedTempFile::new_in(&self.dir).map_err(failed)?;
+        file.write_all(bytes).map_err(failed)?;
+        file.persist(path).map_err(|e| failed(e.error))?;
+        Ok(())
+    }
+}
+
+/// `<link>` or `<link>.<env>`, each token a plain file name component
+fn valid_key(key: &str) -> bool {
+    let tokens: Vec<&str> = key.split('.').collect();
+    tokens.len() <= 2
+        && tokens.iter().all(|token| {
+            !token.is_empty()
+                && token
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+/// Mirrors a JetStream KV bucket of config documents into a [`ConfigCache`]
+pub struct RemoteConfigSource {
+    store: kv::Store,
+    cache: ConfigCache,
+}
+
+impl RemoteConfigSource {
+    /// Connect using `NATS_URL`, `CONFIG_KV_BUCKET` and `CONFIG_KV_CACHE_DIR`
+    pub async fn from_env() -> Result<Self, RemoteConfigError> {
+        let nats_url = env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+        let bucket = env::var("CONFIG_KV_BUCKET")
+            .ok()
+            .filter(|bucket| !bucket.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_CONFIG_BUCKET.to_string());
+        Self::connect(&nats_url, &bucket, cache_dir_from_env()).await
+    }
+
+    pub async fn connect(
+        nats_url: &str,
+        bucket: &str,
+        cache_dir: impl Into<PathBuf>,
+    ) -> Result<Self, RemoteConfigError> {
+        let client = async_nats::connect(nats_url).await.map_err(|e| {
+            RemoteConfigError::ConnectionFailed {
+                message: format!("{}: {}", nats_url, e),
+            }
+        })?;
+        let store = jetstream::new(client)
+            .get_key_value(bucket)
+            .await
+            .map_err(|e| RemoteConfigError::ConnectionFailed {
+                message: format!("bucket {}: {}", bucket, e),
+            })?;
+        Ok(Self::with_store(
+            store,
+            ConfigCache::new(cache_dir, ConfigManager::new()),
+        ))
+    }
+
+    pub fn with_store(store: kv::Store, cache: ConfigCache) -> Self {
+        Self { store, cache }
+    }
+
+    pub fn cache(&self) -> &ConfigCache {
+        &self.cache
+    }
+
+    /// Bring the cache in line with the bucket: write new and changed
+    /// documents and drop those whose keys are gone
+    pub async fn sync(&self) -> Result<SyncReport, RemoteConfigError> {
+        let request_failed = |e: &dyn std::fmt::Display| RemoteConfigError::RequestFailed {
+            message: e.to_string(),
+        };
+        let mut keys = self.store.keys().await.map_err(|e| request_failed(&e))?;
+        let mut remote = BTreeSet::new();
+        while let Some(key) = keys.next().await {
+            remote.insert(key.map_err(|e| request_failed(&e))?);
+        }
+
+        let mut report = SyncReport::default();
+        for key in &remote {
+            let Some(bytes) = self.store.get(key).await.map_err(|e| request_failed(&e))? else {
+                continue;
+            };
+            match self.cache.apply(key, &bytes) {
+                Ok(CacheUpdate::Updated) => report.updated.push(key.clone()),
+                Ok(CacheUpdate::Unchanged) => report.unchanged.push(key.clone()),
+                Err(e @ RemoteConfigError::CacheFailed { .. }) => return Err(e),
+                Err(e) => {
+                    warn!("Keeping cached config for {}: {}", key, e);
+                    report.rejected.push((key.clone(), e.to_string()));
+                }
+            }
+        }
+
+        for key in self.cache.cached_keys()?.difference(&remote) {
+            if self.cache.remove(key)? {
+                report.removed.push(key.clone());
+            }
+        }
+
+        info!(
+            updated = report.updated.len(),
+            unchanged = report.unchanged.len(),
+            removed = report.removed.len(),
+            rejected = report.rejected.len(),
+            "Synchronized config cache {:?} from KV",
+            self.cache.dir()
+        );
+        Ok(report)
+    }
+
+    /// Apply bucket changes to the cache as they happen; returns when the
+    /// watch ends or fails
+    pub async fn watch(&self) -> Result<(), RemoteConfigError> {
+        let mut updates =
+            self.store
+                .watch_all()
+                .await
+                .map_err(|e| RemoteConfigError::RequestFailed {
+                    message: e.to_string(),
+                })?;
+        while let Some(entry) = updates.next().await {
+            let entry = entry.map_err(|e| RemoteConfigError::RequestFailed {
+                message: e.to_string(),
+            })?;
+            let applied = match entry.operation {
+                kv::Operation::Put => self.cache.apply(&entry.key, &entry.value).map(|_| ()),
+                kv::Operation::Delete | kv::Operation::Purge => {
+                    self.cache.remove(&entry.key).map(|_| ())
+                }
+            };
+            match applied {
+                Ok(()) => debug!("Applied config change to {}", entry.key),
+                Err(e @ RemoteConfigError::CacheFailed { .. }) => return Err(e),
+                Err(e) => warn!("Keeping cached config for {}: {}", entry.key, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn cache() -> (TempDir, ConfigCache) {
+        let temp_dir = TempDir::new().unwrap();
+        let contracts_dir = temp_dir.path().join("contracts");
+        fs::create_dir_all(contracts_dir.join("config")).unwrap();
+        fs::write(
+            contracts_dir.join("config").join("echo-config.v1.json"),
+            r#"{
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": { "maxMessageLength": { "type": "integer", "minimum": 1 } },
+                "required": ["maxMessageLength"]
+            }"#,
+        )
+        .unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let validator = ConfigManager::with_dirs(contracts_dir, cache_dir.clone());
+        (temp_dir, ConfigCache::new(cache_dir, validator))
+    }
+
+    #[test]
+    fn apply_writes_valid_documents_and_reports_unchanged_ones() {
+        let (_temp, cache) = cache();
+        let document = br#"{"maxMessageLength": 10}"#;
+
+        assert_eq!(cache.apply("echo", document).unwrap(), CacheUpdate::Updated);
+        assert_eq!(
+            cache.apply("echo", document).unwrap(),
+            CacheUpdate::Unchanged
+        );
+        assert_eq!(
+            fs::read(cache.dir().join("echo.json")).unwrap(),
+            document.to_vec()
+        );
+        assert_eq!(
+            cache.cached_keys().unwrap().into_iter().collect::<Vec<_>>(),
+            vec!["echo"]
+        );
+    }
+
+    #[test]
+    fn invalid_base_documents_keep_the_cached_copy() {
+        let (_temp, cache) = cache();
+        cache.apply("echo", br#"{"maxMessageLength": 10}"#).unwrap();
+
+        let err = cache
+            .apply("echo", br#"{"maxMessageLength": 0}"#)
+            .unwrap_err();
+        assert!(matches!(err, RemoteConfigError::InvalidDocument { .. }));
+        assert!(cache.apply("echo", b"not json").is_err());
+        assert_eq!(
+            fs::read_to_string(cache.dir().join("echo.json")).unwrap(),
+            r#"{"maxMessageLength": 10}"#
+        );
+
+        // Overlays are partial and only need to be objects
+        assert_eq!(
+            cache
+                .apply("echo.prod", br#"{"maxMessageLength": 0}"#)
+                .unwrap(),
+            CacheUpdate::Updated
+        );
+        assert!(cache.apply("echo.prod", b"[]").is_err());
+    }
+
+    #[test]
+    fn keys_must_map_to_config_file_names() {
+        let (_temp, cache) = cache();
+        for key in ["../echo", "a/b", "echo.prod.extra", ".echo", ""] {
+            assert!(
+                matches!(
+                    cache.apply(key, b"{}"),
+                    Err(RemoteConfigError::InvalidKey { .. })
+                ),
+                "{key}"
+            );
+        }
+        assert!(!cache.remove("unknown").unwrap());
+    }
+}
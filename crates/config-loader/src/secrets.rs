@@ -1,3 +1,5 @@
+use crate::secrets_crypto::{self, SecretsKey};
+use chrono::{DateTime, Duration, Utc};
 use once_cell::sync::OnceCell;
 use regex::Regex;
 use serde_json::Value;
@@ -25,11 +27,125 @@ pub enum SecretError {
 
 pub trait SecretProvider: Send + Sync {
     fn resolve(&self, scope: &str, key: &str) -> Result<String, SecretError>;
+
+    /// Rotation metadata for a secret, if the provider tracks it
+    fn metadata(&self, _scope: &str, _key: &str) -> Option<SecretMetadata> {
+        None
+    }
+}
+
+/// Creation and rotation timestamps recorded alongside a stored secret
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecretMetadata {
+    pub created_at: Option<DateTime<Utc>>,
+    pub rotated_at: Option<DateTime<Utc>>,
+}
+
+impl SecretMetadata {
+    /// When the current value was set (last rotation, or creation if never rotated)
+    pub fn last_changed(&self) -> Option<DateTime<Utc>> {
+        self.rotated_at.or(self.created_at)
+    }
+
+    /// Parse metadata fields from a secrets-file entry object
+    pub fn from_json(entry: &serde_json::Map<String, Value>) -> Self {
+        let timestamp = |field: &str| {
+            entry
+                .get(field)
+                .and_then(|v| v.as_str())
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+        };
+        Self {
+            created_at: timestamp("created_at"),
+            rotated_at: timestamp("rotated_at"),
+        }
+    }
+}
+
+/// Environment variable setting the maximum secret age in days
+pub const SECRETS_MAX_AGE_ENV: &str = "CONFIG_SECRETS_MAX_AGE_DAYS";
+
+/// Staleness policy applied while resolving secrets
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecretRotationPolicy {
+    pub max_age: Option<Duration>,
 }
 
+impl SecretRotationPolicy {
+    pub fn with_max_age(max_age: Duration) -> Self {
+        Self {
+            max_age: Some(max_age),
+        }
+    }
+
+    /// Read the max age from `CONFIG_SECRETS_MAX_AGE_DAYS`; unset disables staleness checks
+    pub fn from_env() -> Self {
+        let max_age =
+            env::var(SECRETS_MAX_AGE_ENV)
+                .ok()
+                .and_then(|days| match days.trim().parse::<i64>() {
+                    Ok(days) if days > 0 => Some(Duration::days(days)),
+                    _ => {
+                        warn!("Ignoring invalid {} value: {}", SECRETS_MAX_AGE_ENV, days);
+                        None
+                    }
+                });
+        Self { max_age }
+    }
+
+    /// Check a secret's metadata against the policy
+    pub fn check(
+        &self,
+        scope: &str,
+        key: &str,
+        metadata: &SecretMetadata,
+        now: DateTime<Utc>,
+    ) -> Option<SecretWarning> {
+        let max_age = self.max_age?;
+        let last_changed = metadata.last_changed()?;
+        let age = now.signed_duration_since(last_changed);
+        if age > max_age {
+            Some(SecretWarning {
+                scope: scope.to_string(),
+                key: key.to_string(),
+                last_changed,
+                age_days: age.num_days(),
+                max_age_days: max_age.num_days(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Warning raised when a resolved secret is older than the rotation policy allows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretWarning {
+    pub scope: String,
+    pub key: String,
+    pub last_changed: DateTime<Utc>,
+    pub age_days: i64,
+    pub max_age_days: i64,
+}
+
+impl std::fmt::Display for SecretWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Secret {}/{} was last rotated {} days ago (max age {} days)",
+            self.scope, self.key, self.age_days, self.max_age_days
+        )
+    }
+}
+
+/// Parsed secrets file: scope -> key -> (value, metadata)
+type SecretsFileContents = HashMap<String, HashMap<String, (String, SecretMetadata)>>;
+
 pub struct EnvFileSecretProvider {
     secrets_file_path: Option<PathBuf>,
-    cached_secrets: OnceCell<HashMap<String, HashMap<String, String>>>,
+    key: Option<SecretsKey>,
+    cached_secrets: OnceCell<SecretsFileContents>,
 }
 
 impl EnvFileSecretProvider {
@@ -48,6 +164,7 @@ impl EnvFileSecretProvider {
 
         Self {
             secrets_file_path,
+            key: None,
             cached_secrets: OnceCell::new(),
         }
     }
@@ -55,20 +172,31 @@ impl EnvFileSecretProvider {
     pub fn with_secrets_file<P: Into<PathBuf>>(secrets_file_path: P) -> Self {
         Self {
             secrets_file_path: Some(secrets_file_path.into()),
+            key: None,
             cached_secrets: OnceCell::new(),
         }
     }
 
-    fn load_secrets_from_file(
-        &self,
-    ) -> Result<HashMap<String, HashMap<String, String>>, SecretError> {
+    /// Decrypt the secrets file with `key` instead of `DEMON_SECRETS_PASSPHRASE`
+    pub fn with_key(mut self, key: SecretsKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    fn load_secrets_from_file(&self) -> Result<SecretsFileContents, SecretError> {
         if let Some(ref path) = self.secrets_file_path {
             debug!("Loading secrets from file: {:?}", path);
 
-            let content = fs::read_to_string(path).map_err(|e| SecretError::SecretsFileError {
-                path: path.to_string_lossy().to_string(),
-                message: e.to_string(),
-            })?;
+            let content = fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    secrets_crypto::decrypt_if_encrypted(&content, self.key.as_ref())
+                        .map_err(|e| e.to_string())
+                })
+                .map_err(|message| SecretError::SecretsFileError {
+                    path: path.to_string_lossy().to_string(),
+                    message,
+                })?;
 
             let parsed: Value =
                 serde_json::from_str(&content).map_err(|e| SecretError::SecretsParseError {
@@ -83,7 +211,21 @@ impl EnvFileSecretProvider {
                         let mut scope_secrets = HashMap::new();
                         for (key, value) in scope_obj {
                             if let Some(string_value) = value.as_str() {
-                                scope_secrets.insert(key.clone(), string_value.to_string());
+                                scope_secrets.insert(
+                                    key.clone(),
+                                    (string_value.to_string(), SecretMetadata::default()),
+                                );
+                            } else if let Some((string_value, metadata)) = value
+                                .as_object()
+                                .and_then(|entry| Some((entry.get("value")?.as_str()?, entry)))
+                            {
+                                scope_secrets.insert(
+                                    key.clone(),
+                                    (
+                                        string_value.to_string(),
+                                        SecretMetadata::from_json(metadata),
+                                    ),
+                                );
                             } else {
                                 warn!(
                                     "Non-string value found in secrets file for {}/{}: {:?}",
@@ -102,7 +244,7 @@ impl EnvFileSecretProvider {
         }
     }
 
-    fn get_cached_secrets(&self) -> &HashMap<String, HashMap<String, String>> {
+    fn get_cached_secrets(&self) -> &SecretsFileContents {
         self.cached_secrets.get_or_init(|| {
             self.load_secrets_from_file().unwrap_or_else(|e| {
                 warn!("Failed to load secrets from file: {}", e);
@@ -129,7 +271,7 @@ impl SecretProvider for EnvFileSecretProvider {
         let cached_secrets = self.get_cached_secrets();
 
         if let Some(scope_secrets) = cached_secrets.get(scope) {
-            if let Some(value) = scope_secrets.get(key) {
+            if let Some((value, _)) = scope_secrets.get(key) {
                 debug!("Resolved secret {}/{} from secrets file", scope, key);
                 return Ok(value.clone());
             }
@@ -140,6 +282,19 @@ impl SecretProvider for EnvFileSecretProvider {
             key: key.to_string(),
         })
     }
+
+    fn metadata(&self, scope: &str, key: &str) -> Option<SecretMetadata> {
+        // Environment-provided secrets carry no rotation metadata
+        let env_var_name = format!("SECRET_{}_{}", scope.to_uppercase(), key.to_uppercase());
+        if env::var(&env_var_name).is_ok() {
+            return None;
+        }
+
+        self.get_cached_secrets()
+            .get(scope)
+            .and_then(|scope_secrets| scope_secrets.get(key))
+            .map(|(_, metadata)| metadata.clone())
+    }
 }
 
 impl Default for EnvFileSecretProvider {
@@ -148,18 +303,35 @@ impl Default for EnvFileSecretProvider {
     }
 }
 
+/// Resolve `secret://scope/key` references in place
+///
+/// Returns staleness warnings for secrets older than the rotation policy
+/// configured via `CONFIG_SECRETS_MAX_AGE_DAYS`.
 pub fn resolve_secrets_in_config<P: SecretProvider + ?Sized>(
     config: &mut Value,
     provider: &P,
-) -> Result<(), SecretError> {
+) -> Result<Vec<SecretWarning>, SecretError> {
+    resolve_secrets_in_config_with_policy(config, provider, &SecretRotationPolicy::from_env())
+}
+
+/// Resolve `secret://scope/key` references in place using an explicit rotation policy
+pub fn resolve_secrets_in_config_with_policy<P: SecretProvider + ?Sized>(
+    config: &mut Value,
+    provider: &P,
+    policy: &SecretRotationPolicy,
+) -> Result<Vec<SecretWarning>, SecretError> {
     let secret_regex = Regex::new(r"^secret://([^/]+)/(.+)$").unwrap();
-    resolve_secrets_recursive(config, provider, &secret_regex)
+    let mut warnings = Vec::new();
+    resolve_secrets_recursive(config, provider, &secret_regex, policy, &mut warnings)?;
+    Ok(warnings)
 }
 
 fn resolve_secrets_recursive<P: SecretProvider + ?Sized>(
     value: &mut Value,
     provider: &P,
     secret_regex: &Regex,
+    policy: &SecretRotationPolicy,
+    warnings: &mut Vec<SecretWarning>,
 ) -> Result<(), SecretError> {
     match value {
         Value::String(s) => {
@@ -168,17 +340,30 @@ fn resolve_secrets_recursive<P: SecretProvider + ?Sized>(
                 let key = captures.get(2).unwrap().as_str();
 
                 let resolved_secret = provider.resolve(scope, key)?;
+
+                if policy.max_age.is_some() {
+                    if let Some(warning) = provider
+                        .metadata(scope, key)
+                        .and_then(|metadata| policy.check(scope, key, &metadata, Utc::now()))
+                    {
+                        warn!("{}", warning);
+                        if !warnings.contains(&warning) {
+                            warnings.push(warning);
+                        }
+                    }
+                }
+
                 *s = resolved_secret;
             }
         }
         Value::Object(obj) => {
             for (_, v) in obj.iter_mut() {
-                resolve_secrets_recursive(v, provider, secret_regex)?;
+                resolve_secrets_recursive(v, provider, secret_regex, policy, warnings)?;
             }
         }
         Value::Array(arr) => {
             for item in arr.iter_mut() {
-                resolve_secrets_recursive(item, provider, secret_regex)?;
+                resolve_secrets_recursive(item, provider, secret_regex, policy, warnings)?;
             }
         }
         _ => {}
@@ -271,6 +456,30 @@ mod tests {
         assert_eq!(result.unwrap(), "admin");
     }
 
+    #[test]
+    fn test_encrypted_file_secret_resolution() {
+        let temp_dir = TempDir::new().unwrap();
+        let secrets_file = temp_dir.path().join("secrets.json");
+        let key = SecretsKey::passphrase("test-passphrase").with_scrypt_log_n(10);
+        crate::SecretsStore::new(&secrets_file)
+            .with_key(key.clone())
+            .set("database", "password", "encrypted_value")
+            .unwrap();
+
+        let provider = EnvFileSecretProvider::with_secrets_file(&secrets_file).with_key(key);
+        assert_eq!(
+            provider.resolve("database", "password").unwrap(),
+            "encrypted_value"
+        );
+
+        let wrong_key = SecretsKey::passphrase("wrong").with_scrypt_log_n(10);
+        let provider = EnvFileSecretProvider::with_secrets_file(&secrets_file).with_key(wrong_key);
+        assert!(matches!(
+            provider.load_secrets_from_file(),
+            Err(SecretError::SecretsFileError { .. })
+        ));
+    }
+
     #[test]
     fn test_secret_not_found() {
         let provider = EnvFileSecretProvider::new();
@@ -311,6 +520,74 @@ mod tests {
         env::remove_var("SECRET_API_KEY");
     }
 
+    #[test]
+    fn test_file_secret_with_metadata_resolves_value() {
+        let temp_dir = TempDir::new().unwrap();
+        let secrets_file = temp_dir.path().join("secrets.json");
+
+        let secrets_content = json!({
+            "database": {
+                "password": {
+                    "value": "rotated_value",
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "rotated_at": "2024-06-01T00:00:00Z"
+                }
+            }
+        });
+        fs::write(
+            &secrets_file,
+            serde_json::to_string(&secrets_content).unwrap(),
+        )
+        .unwrap();
+
+        let provider = EnvFileSecretProvider::with_secrets_file(&secrets_file);
+
+        assert_eq!(
+            provider.resolve("database", "password").unwrap(),
+            "rotated_value"
+        );
+        let metadata = provider.metadata("database", "password").unwrap();
+        assert_eq!(
+            metadata.last_changed().unwrap().to_rfc3339(),
+            "2024-06-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_stale_secret_produces_warning() {
+        let temp_dir = TempDir::new().unwrap();
+        let secrets_file = temp_dir.path().join("secrets.json");
+
+        let secrets_content = json!({
+            "stale": { "token": { "value": "old", "created_at": "2020-01-01T00:00:00Z" } },
+            "fresh": { "token": { "value": "new", "created_at": Utc::now().to_rfc3339() } },
+            "legacy": { "token": "unknown_age" }
+        });
+        fs::write(
+            &secrets_file,
+            serde_json::to_string(&secrets_content).unwrap(),
+        )
+        .unwrap();
+
+        let provider = EnvFileSecretProvider::with_secrets_file(&secrets_file);
+        let policy = SecretRotationPolicy::with_max_age(Duration::days(90));
+        let mut config = json!({
+            "a": "secret://stale/token",
+            "b": ["secret://stale/token", "secret://fresh/token"],
+            "c": "secret://legacy/token"
+        });
+
+        let warnings =
+            resolve_secrets_in_config_with_policy(&mut config, &provider, &policy).unwrap();
+
+        assert_eq!(config["a"], "old");
+        assert_eq!(config["b"][1], "new");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].scope, "stale");
+        assert_eq!(warnings[0].max_age_days, 90);
+        assert!(warnings[0].to_string().contains("stale/token"));
+    }
+
     #[test]
     fn test_redact_secrets_in_config() {
         let mut config = json!({
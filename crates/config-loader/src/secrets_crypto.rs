@@ -0,0 +1,440 @@
+//! Encryption at rest for the envfile secrets store
+//!
+//! `.demon/secrets.json` is written as an AES-256-GCM envelope. The key is
+//! derived from `DEMON_SECRETS_PASSPHRASE` with scrypt, or is a random key kept
+//! in the OS keychain when `DEMON_SECRETS_KEY_SOURCE=keychain` (macOS and
+//! Windows only; the Linux kernel keyring loses keys on reboot). The envelope
+//! records how its key was obtained, so readers only need the passphrase (or
+//! keychain access) and never the key source setting.
+//!
+//! Plaintext files from before encryption are still read; they are encrypted
+//! on the next write or by `demonctl secrets migrate`.
+
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Passphrase the file key is derived from
+pub const PASSPHRASE_ENV: &str = "DEMON_SECRETS_PASSPHRASE";
+/// `passphrase` (default) or `keychain`
+pub const KEY_SOURCE_ENV: &str = "DEMON_SECRETS_KEY_SOURCE";
+/// scrypt cost (log2 N) for newly written files
+pub const SCRYPT_LOG_N_ENV: &str = "DEMON_SECRETS_SCRYPT_LOG_N";
+
+const FORMAT: &str = "demon-secrets";
+const VERSION: u8 = 1;
+const CIPHER: &str = "aes-256-gcm";
+const KEYCHAIN_SERVICE: &str = "demon-secrets";
+const KEYCHAIN_ACCOUNT: &str = "default";
+const DEFAULT_SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_LOG_N_RANGE: std::ops::RangeInclusive<u8> = 10..=20;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Error, Debug)]
+pub enum CryptoError {
+    #[error("No secrets encryption key: {0}")]
+    KeyUnavailable(String),
+
+    #[error("Cannot decrypt secrets file: wrong passphrase or corrupted file")]
+    DecryptFailed,
+
+    #[error("Corrupt encrypted secrets file: {0}")]
+    Corrupt(String),
+
+    #[error("OS keychain error: {0}")]
+    Keychain(String),
+}
+
+/// Where the file encryption key comes from
+#[derive(Clone, PartialEq, Eq)]
+pub enum SecretsKey {
+    /// Key derived from a passphrase; `log_n` is the scrypt cost for new files
+    Passphrase { passphrase: String, log_n: u8 },
+    /// Random key stored in the OS keychain, created on first write
+    Keychain,
+}
+
+impl std::fmt::Debug for SecretsKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SecretsKey::Passphrase { log_n, .. } => f
+                .debug_struct("Passphrase")
+                .field("passphrase", &"***")
+                .field("log_n", log_n)
+                .finish(),
+            SecretsKey::Keychain => f.write_str("Keychain"),
+        }
+    }
+}
+
+impl SecretsKey {
+    pub fn passphrase(passphrase: impl Into<String>) -> Self {
+        SecretsKey::Passphrase {
+            passphrase: passphrase.into(),
+            log_n: DEFAULT_SCRYPT_LOG_N,
+        }
+    }
+
+    /// Override the scrypt cost used when writing; lower values are for tests
+    pub fn with_scrypt_log_n(self, log_n: u8) -> Self {
+        match self {
+            SecretsKey::Passphrase { passphrase, .. } => {
+                SecretsKey::Passphrase { passphrase, log_n }
+            }
+            keychain => keychain,
+        }
+    }
+
+    /// Key selected by `DEMON_SECRETS_KEY_SOURCE`, `None` when no passphrase is set
+    pub fn from_env() -> Result<Option<Self>, CryptoError> {
+        let source = std::env::var(KEY_SOURCE_ENV).unwrap_or_default();
+        match source.trim().to_ascii_lowercase().as_str() {
+            "" | "passphrase" => {
+                let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) else {
+                    return Ok(None);
+                };
+                let key = SecretsKey::passphrase(passphrase);
+                match std::env::var(SCRYPT_LOG_N_ENV) {
+                    Ok(value) => Ok(Some(key.with_scrypt_log_n(parse_log_n(&value)?))),
+                    Err(_) => Ok(Some(key)),
+                }
+            }
+            "keychain" => {
+                keychain_supported()?;
+                Ok(Some(SecretsKey::Keychain))
+            }
+            other => Err(CryptoError::KeyUnavailable(format!(
+                "invalid {} '{}': expected passphrase or keychain",
+                KEY_SOURCE_ENV, other
+            ))),
+        }
+    }
+
+    /// Short name for reports: `passphrase` or `keychain`
+    pub fn source(&self) -> &'static str {
+        match self {
+            SecretsKey::Passphrase { .. } => "passphrase",
+            SecretsKey::Keychain => "keychain",
+        }
+    }
+}
+
+fn parse_log_n(value: &str) -> Result<u8, CryptoError> {
+    value
+        .trim()
+        .parse::<u8>()
+        .ok()
+        .filter(|log_n| SCRYPT_LOG_N_RANGE.contains(log_n))
+        .ok_or_else(|| {
+            CryptoError::KeyUnavailable(format!(
+                "invalid {} '{}': expected {} to {}",
+                SCRYPT_LOG_N_ENV,
+                value,
+                SCRYPT_LOG_N_RANGE.start(),
+                SCRYPT_LOG_N_RANGE.end()
+            ))
+        })
+}
+
+/// On-disk envelope of an encrypted secrets file
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope {
+    format: String,
+    version: u8,
+    cipher: String,
+    kdf: Kdf,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "lowercase")]
+enum Kdf {
+    Scrypt {
+        salt: String,
+        #[serde(rename = "logN")]
+        log_n: u8,
+        r: u32,
+        p: u32,
+    },
+    Keychain {
+        service: String,
+        account: String,
+    },
+}
+
+/// Whether `content` is an encrypted envelope rather than a plaintext secrets file
+pub fn is_encrypted(content: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|value| value.get("format")?.as_str().map(|f| f == FORMAT))
+        .unwrap_or(false)
+}
+
+/// Encrypt a serialized secrets file into an envelope
+pub fn encrypt(plaintext: &str, key: &SecretsKey) -> Result<String, CryptoError> {
+    let (file_key, kdf) = match key {
+        SecretsKey::Passphrase { passphrase, log_n } => {
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let file_key = derive_key(passphrase, &salt, *log_n, SCRYPT_R, SCRYPT_P)?;
+            let kdf = Kdf::Scrypt {
+                salt: STANDARD.encode(salt),
+                log_n: *log_n,
+                r: SCRYPT_R,
+                p: SCRYPT_P,
+            };
+            (file_key, kdf)
+        }
+        SecretsKey::Keychain => {
+            let file_key = match keychain_key(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)? {
+                Some(file_key) => file_key,
+                None => create_keychain_key(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)?,
+            };
+            let kdf = Kdf::Keychain {
+                service: KEYCHAIN_SERVICE.to_string(),
+                account: KEYCHAIN_ACCOUNT.to_string(),
+            };
+            (file_key, kdf)
+        }
+    };
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = Aes256Gcm::new(&file_key)
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| CryptoError::Corrupt("encryption failed".to_string()))?;
+    let envelope = Envelope {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        cipher: CIPHER.to_string(),
+        kdf,
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    };
+    serde_json::to_string_pretty(&envelope).map_err(|e| CryptoError::Corrupt(e.to_string()))
+}
+
+/// Decrypt an envelope
+///
+/// Passphrase-encrypted files use the passphrase from `key`, falling back to
+/// `DEMON_SECRETS_PASSPHRASE`; keychain-encrypted files read the keychain
+/// entry recorded in the envelope.
+pub fn decrypt(content: &str, key: Option<&SecretsKey>) -> Result<String, CryptoError> {
+    let envelope: Envelope =
+        serde_json::from_str(content).map_err(|e| CryptoError::Corrupt(e.to_string()))?;
+    if envelope.format != FORMAT || envelope.version != VERSION || envelope.cipher != CIPHER {
+        return Err(CryptoError::Corrupt(format!(
+            "unsupported envelope {} v{} ({})",
+            envelope.format, envelope.version, envelope.cipher
+        )));
+    }
+
+    let file_key = match &envelope.kdf {
+        Kdf::Scrypt { salt, log_n, r, p } => {
+            let passphrase = match key {
+                Some(SecretsKey::Passphrase { passphrase, .. }) => passphrase.clone(),
+                _ => std::env::var(PASSPHRASE_ENV).map_err(|_| {
+                    CryptoError::KeyUnavailable(format!(
+                        "the secrets file is passphrase-encrypted; set {}",
+                        PASSPHRASE_ENV
+                    ))
+                })?,
+            };
+            derive_key(&passphrase, &decode(salt, "salt")?, *log_n, *r, *p)?
+        }
+        Kdf::Keychain { service, account } => keychain_key(service, account)?.ok_or_else(|| {
+            CryptoError::KeyUnavailable(format!(
+                "no OS keychain entry {}/{} for the secrets file",
+                service, account
+            ))
+        })?,
+    };
+
+    let nonce = decode(&envelope.nonce, "nonce")?;
+    if nonce.len() != NONCE_LEN {
+        return Err(CryptoError::Corrupt("invalid nonce length".to_string()));
+    }
+    let ciphertext = decode(&envelope.ciphertext, "ciphertext")?;
+    let plaintext = Aes256Gcm::new(&file_key)
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| CryptoError::DecryptFailed)?;
+    String::from_utf8(plaintext).map_err(|e| CryptoError::Corrupt(e.to_string()))
+}
+
+/// Plaintext of a secrets file, decrypting it when it is an envelope
+pub fn decrypt_if_encrypted(
+    content: &str,
+    key: Option<&SecretsKey>,
+) -> Result<String, CryptoError> {
+    if is_encrypted(content) {
+        decrypt(content, key)
+    } else {
+        Ok(content.to_string())
+    }
+}
+
+fn decode(value: &str, field: &str) -> Result<Vec<u8>, CryptoError> {
+    STANDARD
+        .decode(value)
+        .map_err(|e| CryptoError::Corrupt(format!("{}: {}", field, e)))
+}
+
+/// 256-bit key derived from `passphrase` and `salt` with scrypt
+///
+/// Also used for demonctl's encrypted credentials file.
+pub fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<Key<Aes256Gcm>, CryptoError> {
+    let params = scrypt::Params::new(log_n, r, p, 32)
+        .map_err(|e| CryptoError::Corrupt(format!("scrypt parameters: {}", e)))?;
+    let mut file_key = Key::<Aes256Gcm>::default();
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut file_key)
+        .map_err(|e| CryptoError::Corrupt(format!("scrypt: {}", e)))?;
+    Ok(file_key)
+}
+
+/// Refuse the keychain where keyring has no persistent store
+///
+/// On Linux keyring only has the kernel keyutils backend, whose keys are gone
+/// after a reboot, leaving the secrets file undecryptable.
+fn keychain_supported() -> Result<(), CryptoError> {
+    if cfg!(target_os = "linux") {
+        return Err(CryptoError::Keychain(format!(
+            "{}=keychain is not supported on Linux because the kernel keyring does not \
+             survive a reboot; use {} instead",
+            KEY_SOURCE_ENV, PASSPHRASE_ENV
+        )));
+    }
+    Ok(())
+}
+
+fn keychain_entry(service: &str, account: &str) -> Result<keyring::Entry, CryptoError> {
+    keychain_supported()?;
+    keyring::Entry::new(service, account).map_err(|e| CryptoError::Keychain(e.to_string()))
+}
+
+fn keychain_key(service: &str, account: &str) -> Result<Option<Key<Aes256Gcm>>, CryptoError> {
+    let entry = keychain_entry(service, account)?;
+    let encoded = match entry.get_password() {
+        Ok(encoded) => encoded,
+        Err(keyring::Error::NoEntry) => return Ok(None),
+        Err(e) => return Err(CryptoError::Keychain(e.to_string())),
+    };
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| CryptoError::Keychain(format!("corrupt key in {}/{}", service, account)))?;
+    if bytes.len() != 32 {
+        return Err(CryptoError::Keychain(format!(
+            "corrupt key in {}/{}",
+            service, account
+        )));
+    }
+    Ok(Some(*Key::<Aes256Gcm>::from_slice(&bytes)))
+}
+
+fn create_keychain_key(service: &str, account: &str) -> Result<Key<Aes256Gcm>, CryptoError> {
+    let file_key = Aes256Gcm::generate_key(&mut OsRng);
+    keychain_entry(service, account)?
+        .set_password(&STANDARD.encode(file_key))
+        .map_err(|e| CryptoError::Keychain(e.to_string()))?;
+    Ok(file_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRETS: &str = r#"{"db": {"password": "hunter2-but-longer"}}"#;
+
+    fn test_key(passphrase: &str) -> SecretsKey {
+        SecretsKey::passphrase(passphrase).with_scrypt_log_n(10)
+    }
+
+    #[test]
+    fn passphrase_envelope_round_trips() {
+        let key = test_key("correct horse");
+        let envelope = encrypt(SECRETS, &key).unwrap();
+
+        assert!(is_encrypted(&envelope));
+        assert!(!envelope.contains("hunter2"));
+        let parsed: serde_json::Value = serde_json::from_str(&envelope).unwrap();
+        assert_eq!(parsed["kdf"]["name"], "scrypt");
+        assert_eq!(parsed["kdf"]["logN"], 10);
+        assert_eq!(decrypt(&envelope, Some(&key)).unwrap(), SECRETS);
+    }
+
+    #[test]
+    fn each_write_uses_a_fresh_salt_and_nonce() {
+        let key = test_key("correct horse");
+        assert_ne!(
+            encrypt(SECRETS, &key).unwrap(),
+            encrypt(SECRETS, &key).unwrap()
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_fails_to_decrypt() {
+        let envelope = encrypt(SECRETS, &test_key("correct horse")).unwrap();
+
+        assert!(matches!(
+            decrypt(&envelope, Some(&test_key("battery staple"))),
+            Err(CryptoError::DecryptFailed)
+        ));
+    }
+
+    #[test]
+    fn tampered_ciphertext_fails_to_decrypt() {
+        let key = test_key("correct horse");
+        let mut envelope: serde_json::Value =
+            serde_json::from_str(&encrypt(SECRETS, &key).unwrap()).unwrap();
+        let mut ciphertext = STANDARD
+            .decode(envelope["ciphertext"].as_str().unwrap())
+            .unwrap();
+        ciphertext[0] ^= 0xff;
+        envelope["ciphertext"] = STANDARD.encode(ciphertext).into();
+
+        assert!(matches!(
+            decrypt(&envelope.to_string(), Some(&key)),
+            Err(CryptoError::DecryptFailed)
+        ));
+    }
+
+    #[test]
+    fn plaintext_files_pass_through() {
+        assert!(!is_encrypted(SECRETS));
+        assert!(!is_encrypted("not json"));
+        assert_eq!(decrypt_if_encrypted(SECRETS, None).unwrap(), SECRETS);
+    }
+
+    #[test]
+    fn scrypt_cost_must_be_in_range() {
+        assert_eq!(parse_log_n("12").unwrap(), 12);
+        assert!(parse_log_n("4").is_err());
+        assert!(parse_log_n("many").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn keychain_is_refused_on_linux() {
+        let err = encrypt(SECRETS, &SecretsKey::Keychain).unwrap_err();
+        assert!(matches!(err, CryptoError::Keychain(_)));
+        assert!(err.to_string().contains(PASSPHRASE_ENV));
+    }
+
+    #[test]
+    fn debug_output_hides_passphrase() {
+        assert!(!format!("{:?}", test_key("correct horse")).contains("horse"));
+    }
+}
@@ -1,4 +1,7 @@
+use crate::secrets::SecretMetadata;
+use crate::secrets_crypto::{self, CryptoError, SecretsKey};
 use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, SubsecRound, Utc};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::fs;
@@ -24,11 +27,77 @@ pub enum StoreError {
 
     #[error("Invalid scope/key format: {input}")]
     InvalidFormat { input: String },
+
+    #[error(transparent)]
+    Encryption(#[from] CryptoError),
+}
+
+/// A stored secret value plus its rotation metadata
+///
+/// On disk an entry is either a plain string (legacy format, no metadata) or
+/// an object `{"value": ..., "created_at": ..., "rotated_at": ...}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretEntry {
+    pub value: String,
+    pub metadata: SecretMetadata,
+}
+
+impl SecretEntry {
+    fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => Some(Self {
+                value: s.clone(),
+                metadata: SecretMetadata::default(),
+            }),
+            Value::Object(obj) => Some(Self {
+                value: obj.get("value")?.as_str()?.to_string(),
+                metadata: SecretMetadata::from_json(obj),
+            }),
+            _ => None,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        if self.metadata == SecretMetadata::default() {
+            return Value::String(self.value.clone());
+        }
+
+        let mut obj = Map::new();
+        obj.insert("value".to_string(), Value::String(self.value.clone()));
+        if let Some(created_at) = self.metadata.created_at {
+            obj.insert("created_at".to_string(), timestamp(created_at));
+        }
+        if let Some(rotated_at) = self.metadata.rotated_at {
+            obj.insert("rotated_at".to_string(), timestamp(rotated_at));
+        }
+        Value::Object(obj)
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> Value {
+    Value::String(at.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+type SecretEntries = HashMap<String, HashMap<String, SecretEntry>>;
+
+/// Outcome of [`SecretsStore::migrate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub secret_count: usize,
+    /// False when the file was plaintext before the migration
+    pub was_encrypted: bool,
+    /// Key source the file is now encrypted with
+    pub key_source: &'static str,
 }
 
-/// Manages secrets storage in JSON files with atomic writes
+/// Manages secrets storage in encrypted JSON files with atomic writes
+///
+/// Writes need a key: the one given to [`SecretsStore::with_key`], otherwise
+/// [`SecretsKey::from_env`]. Plaintext files are read as-is and encrypted on
+/// the next write.
 pub struct SecretsStore {
     secrets_file: PathBuf,
+    key: Option<SecretsKey>,
 }
 
 impl SecretsStore {
@@ -36,9 +105,16 @@ impl SecretsStore {
     pub fn new<P: Into<PathBuf>>(path: P) -> Self {
         Self {
             secrets_file: path.into(),
+            key: None,
         }
     }
 
+    /// Use `key` instead of the environment to encrypt and decrypt the file
+    pub fn with_key(mut self, key: SecretsKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
     /// Create a SecretsStore using the default location from env or .demon/secrets.json
     pub fn default_location() -> Self {
         let path = std::env::var("CONFIG_SECRETS_FILE")
@@ -54,6 +130,21 @@ impl SecretsStore {
 
     /// Load secrets from the file
     pub fn load(&self) -> Result<HashMap<String, HashMap<String, String>>, StoreError> {
+        Ok(self
+            .load_entries()?
+            .into_iter()
+            .map(|(scope, entries)| {
+                let values = entries
+                    .into_iter()
+                    .map(|(key, entry)| (key, entry.value))
+                    .collect();
+                (scope, values)
+            })
+            .collect())
+    }
+
+    /// Load secrets with their rotation metadata
+    pub fn load_entries(&self) -> Result<SecretEntries, StoreError> {
         if !self.secrets_file.exists() {
             debug!("Secrets file does not exist, returning empty store");
             return Ok(HashMap::new());
@@ -68,6 +159,16 @@ impl SecretsStore {
             return Ok(HashMap::new());
         }
 
+        let content = if secrets_crypto::is_encrypted(&content) {
+            secrets_crypto::decrypt(&content, self.key.as_ref())?
+        } else {
+            warn!(
+                "Secrets file {} is not encrypted; run `demonctl secrets migrate`",
+                self.secrets_file.display()
+            );
+            content
+        };
+
         let json_value: Value =
             serde_json::from_str(&content).map_err(|e| StoreError::JsonError {
                 message: e.to_string(),
@@ -79,8 +180,8 @@ impl SecretsStore {
                 if let Some(scope_obj) = scope_value.as_object() {
                     let mut scope_secrets = HashMap::new();
                     for (key, value) in scope_obj {
-                        if let Some(string_value) = value.as_str() {
-                            scope_secrets.insert(key.clone(), string_value.to_string());
+                        if let Some(entry) = SecretEntry::from_json(value) {
+                            scope_secrets.insert(key.clone(), entry);
                         }
                     }
                     if !scope_secrets.is_empty() {
@@ -94,10 +195,35 @@ impl SecretsStore {
     }
 
     /// Save secrets to file atomically
+    ///
+    /// Metadata of existing secrets is preserved; secrets whose value changed
+    /// are marked as rotated and new secrets get a creation timestamp.
     pub fn save(
         &self,
         secrets: &HashMap<String, HashMap<String, String>>,
     ) -> Result<(), StoreError> {
+        let existing = self.load_entries()?;
+        let now = Utc::now();
+
+        let entries = secrets
+            .iter()
+            .map(|(scope, scope_secrets)| {
+                let scope_entries = scope_secrets
+                    .iter()
+                    .map(|(key, value)| {
+                        let previous = existing.get(scope).and_then(|entries| entries.get(key));
+                        (key.clone(), updated_entry(previous, value, now))
+                    })
+                    .collect();
+                (scope.clone(), scope_entries)
+            })
+            .collect();
+
+        self.save_entries(&entries)
+    }
+
+    /// Save secrets with their rotation metadata to file atomically
+    pub fn save_entries(&self, secrets: &SecretEntries) -> Result<(), StoreError> {
         // Create parent directory if it doesn't exist
         if let Some(parent) = self.secrets_file.parent() {
             fs::create_dir_all(parent).map_err(|e| StoreError::FileWriteError {
@@ -109,8 +235,8 @@ impl SecretsStore {
         let mut json_obj = Map::new();
         for (scope, scope_secrets) in secrets {
             let mut scope_obj = Map::new();
-            for (key, value) in scope_secrets {
-                scope_obj.insert(key.clone(), Value::String(value.clone()));
+            for (key, entry) in scope_secrets {
+                scope_obj.insert(key.clone(), entry.to_json());
             }
             if !scope_obj.is_empty() {
                 json_obj.insert(scope.clone(), Value::Object(scope_obj));
@@ -122,6 +248,7 @@ impl SecretsStore {
             serde_json::to_string_pretty(&json_value).map_err(|e| StoreError::JsonError {
                 message: e.to_string(),
             })?;
+        let envelope = secrets_crypto::encrypt(&json_string, &self.write_key()?)?;
 
         // Write atomically using a temp file
         let parent_dir = self.secrets_file.parent().unwrap_or_else(|| Path::new("."));
@@ -131,7 +258,7 @@ impl SecretsStore {
             })?;
 
         temp_file
-            .write_all(json_string.as_bytes())
+            .write_all(envelope.as_bytes())
             .map_err(|e| StoreError::FileWriteError {
                 message: format!("Failed to write to temp file: {}", e),
             })?;
@@ -164,14 +291,101 @@ impl SecretsStore {
         Ok(())
     }
 
+    /// Key used for writing, from [`SecretsStore::with_key`] or the environment
+    fn write_key(&self) -> Result<SecretsKey, StoreError> {
+        if let Some(key) = &self.key {
+            return Ok(key.clone());
+        }
+        SecretsKey::from_env()?.ok_or_else(|| {
+            CryptoError::KeyUnavailable(format!(
+                "set {} or {}=keychain to write {}",
+                secrets_crypto::PASSPHRASE_ENV,
+                secrets_crypto::KEY_SOURCE_ENV,
+                self.secrets_file.display()
+            ))
+            .into()
+        })
+    }
+
+    /// Whether the file on disk is an encrypted envelope
+    pub fn is_encrypted(&self) -> Result<bool, StoreError> {
+        match fs::read_to_string(&self.secrets_file) {
+            Ok(content) => Ok(secrets_crypto::is_encrypted(&content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(StoreError::FileReadError {
+                message: format!("{}: {}", self.secrets_file.display(), e),
+            }),
+        }
+    }
+
+    /// Rewrite the file encrypted with the current key
+    ///
+    /// Encrypts plaintext files and re-encrypts encrypted ones, which moves a
+    /// file between passphrase and keychain keys.
+    pub fn migrate(&self) -> Result<MigrationReport, StoreError> {
+        if !self.secrets_file.exists() {
+            return Err(StoreError::FileReadError {
+                message: format!("{}: no such file", self.secrets_file.display()),
+            });
+        }
+
+        let was_encrypted = self.is_encrypted()?;
+        let entries = self.load_entries()?;
+        let key_source = self.write_key()?.source();
+        self.save_entries(&entries)?;
+
+        Ok(MigrationReport {
+            secret_count: entries.values().map(HashMap::len).sum(),
+            was_encrypted,
+            key_source,
+        })
+    }
+
     /// Set a secret value
     pub fn set(&self, scope: &str, key: &str, value: &str) -> Result<(), StoreError> {
-        let mut secrets = self.load()?;
+        let mut secrets = self.load_entries()?;
+        let scope_secrets = secrets.entry(scope.to_string()).or_default();
+        let entry = updated_entry(scope_secrets.get(key), value, Utc::now());
+        scope_secrets.insert(key.to_string(), entry);
+        self.save_entries(&secrets)
+    }
+
+    /// Replace an existing secret's value and record the rotation time
+    ///
+    /// The value and timestamps are written in a single atomic save. Fails
+    /// with `SecretNotFound` if the secret does not exist yet.
+    pub fn rotate(&self, scope: &str, key: &str, value: &str) -> Result<SecretEntry, StoreError> {
+        let mut secrets = self.load_entries()?;
+        let entry = secrets
+            .get_mut(scope)
+            .and_then(|scope_secrets| scope_secrets.get_mut(key))
+            .ok_or_else(|| StoreError::SecretNotFound {
+                scope: scope.to_string(),
+                key: key.to_string(),
+            })?;
+
+        // Stored timestamps have whole seconds; match them in the returned entry
+        let now = Utc::now().trunc_subsecs(0);
+        entry.value = value.to_string();
+        // Legacy entries have no creation time; the best we know is "no later than now"
+        entry.metadata.created_at.get_or_insert(now);
+        entry.metadata.rotated_at = Some(now);
+        let rotated = entry.clone();
+
+        self.save_entries(&secrets)?;
+        Ok(rotated)
+    }
+
+    /// Get a secret value together with its rotation metadata
+    pub fn get_entry(&self, scope: &str, key: &str) -> Result<SecretEntry, StoreError> {
+        let mut secrets = self.load_entries()?;
         secrets
-            .entry(scope.to_string())
-            .or_insert_with(HashMap::new)
-            .insert(key.to_string(), value.to_string());
-        self.save(&secrets)
+            .get_mut(scope)
+            .and_then(|scope_secrets| scope_secrets.remove(key))
+            .ok_or_else(|| StoreError::SecretNotFound {
+                scope: scope.to_string(),
+                key: key.to_string(),
+            })
     }
 
     /// Get a secret value
@@ -189,7 +403,7 @@ impl SecretsStore {
 
     /// Delete a secret
     pub fn delete(&self, scope: &str, key: &str) -> Result<(), StoreError> {
-        let mut secrets = self.load()?;
+        let mut secrets = self.load_entries()?;
 
         let removed = secrets
             .get_mut(scope)
@@ -210,7 +424,7 @@ impl SecretsStore {
             }
         }
 
-        self.save(&secrets)
+        self.save_entries(&secrets)
     }
 
     /// List all secrets (returns scope -> key -> redacted value)
@@ -288,6 +502,27 @@ impl SecretsStore {
     }
 }
 
+/// Entry for `value` given the previously stored entry, stamping creation or rotation time
+fn updated_entry(previous: Option<&SecretEntry>, value: &str, now: DateTime<Utc>) -> SecretEntry {
+    match previous {
+        Some(previous) if previous.value == value => previous.clone(),
+        Some(previous) => SecretEntry {
+            value: value.to_string(),
+            metadata: SecretMetadata {
+                created_at: previous.metadata.created_at,
+                rotated_at: Some(now),
+            },
+        },
+        None => SecretEntry {
+            value: value.to_string(),
+            metadata: SecretMetadata {
+                created_at: Some(now),
+                rotated_at: None,
+            },
+        },
+    }
+}
+
 /// Redact a secret value for display
 pub fn redact_value(value: &str) -> String {
     let char_count = value.chars().count();
@@ -304,13 +539,24 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    fn test_key(passphrase: &str) -> SecretsKey {
+        SecretsKey::passphrase(passphrase).with_scrypt_log_n(10)
+    }
+
     fn setup_test_store() -> (TempDir, SecretsStore) {
         let temp_dir = TempDir::new().unwrap();
         let store_path = temp_dir.path().join("test_secrets.json");
-        let store = SecretsStore::new(store_path);
+        let store = SecretsStore::new(store_path).with_key(test_key("test-passphrase"));
         (temp_dir, store)
     }
 
+    fn read_plaintext(store: &SecretsStore) -> Value {
+        let content = fs::read_to_string(store.path()).unwrap();
+        let plaintext =
+            secrets_crypto::decrypt(&content, Some(&test_key("test-passphrase"))).unwrap();
+        serde_json::from_str(&plaintext).unwrap()
+    }
+
     #[test]
     fn test_empty_store() {
         let (_temp_dir, store) = setup_test_store();
@@ -397,6 +643,117 @@ mod tests {
         assert!(!secrets.contains_key("temp"));
     }
 
+    #[test]
+    fn test_set_records_created_at() {
+        let (_temp_dir, store) = setup_test_store();
+
+        store.set("db", "password", "first").unwrap();
+        let entry = store.get_entry("db", "password").unwrap();
+        assert!(entry.metadata.created_at.is_some());
+        assert!(entry.metadata.rotated_at.is_none());
+
+        // Re-setting the same value is not a rotation
+        store.set("db", "password", "first").unwrap();
+        assert_eq!(store.get_entry("db", "password").unwrap(), entry);
+
+        store.set("db", "password", "second").unwrap();
+        let updated = store.get_entry("db", "password").unwrap();
+        assert_eq!(updated.metadata.created_at, entry.metadata.created_at);
+        assert!(updated.metadata.rotated_at.is_some());
+    }
+
+    #[test]
+    fn test_rotate_updates_value_and_timestamp() {
+        let (_temp_dir, store) = setup_test_store();
+        fs::write(
+            store.path(),
+            r#"{"db": {"password": "legacy", "user": "admin"}}"#,
+        )
+        .unwrap();
+
+        let rotated = store.rotate("db", "password", "fresh").unwrap();
+
+        assert_eq!(rotated.value, "fresh");
+        assert!(rotated.metadata.rotated_at.is_some());
+        assert_eq!(store.get_entry("db", "password").unwrap(), rotated);
+        // Untouched legacy entries keep the plain string format
+        let raw = read_plaintext(&store);
+        assert_eq!(raw["db"]["user"], "admin");
+        assert_eq!(raw["db"]["password"]["value"], "fresh");
+    }
+
+    #[test]
+    fn test_rotate_missing_secret_fails() {
+        let (_temp_dir, store) = setup_test_store();
+
+        assert!(matches!(
+            store.rotate("db", "password", "value"),
+            Err(StoreError::SecretNotFound { .. })
+        ));
+        assert!(!store.path().exists());
+    }
+
+    #[test]
+    fn test_file_is_encrypted_at_rest() {
+        let (_temp_dir, store) = setup_test_store();
+
+        store.set("db", "password", "hunter2-but-longer").unwrap();
+
+        let content = fs::read_to_string(store.path()).unwrap();
+        assert!(store.is_encrypted().unwrap());
+        assert!(!content.contains("hunter2"));
+        assert_eq!(
+            read_plaintext(&store)["db"]["password"]["value"],
+            "hunter2-but-longer"
+        );
+    }
+
+    #[test]
+    fn test_wrong_passphrase_cannot_read() {
+        let (_temp_dir, store) = setup_test_store();
+        store.set("db", "password", "secret123").unwrap();
+
+        let other = SecretsStore::new(store.path()).with_key(test_key("not-the-passphrase"));
+        assert!(matches!(
+            other.get("db", "password"),
+            Err(StoreError::Encryption(CryptoError::DecryptFailed))
+        ));
+    }
+
+    #[test]
+    fn test_migrate_encrypts_plaintext_file() {
+        let (_temp_dir, store) = setup_test_store();
+        fs::write(
+            store.path(),
+            r#"{"db": {"password": "legacy", "user": {"value": "admin", "created_at": "2024-01-01T00:00:00Z"}}}"#,
+        )
+        .unwrap();
+        assert!(!store.is_encrypted().unwrap());
+
+        let report = store.migrate().unwrap();
+
+        assert_eq!(report.secret_count, 2);
+        assert!(!report.was_encrypted);
+        assert_eq!(report.key_source, "passphrase");
+        assert!(store.is_encrypted().unwrap());
+        assert_eq!(store.get("db", "password").unwrap(), "legacy");
+        let user = store.get_entry("db", "user").unwrap();
+        assert_eq!(user.value, "admin");
+        assert!(user.metadata.created_at.is_some());
+        assert!(store.migrate().unwrap().was_encrypted);
+    }
+
+    #[test]
+    fn test_plaintext_file_is_encrypted_on_next_write() {
+        let (_temp_dir, store) = setup_test_store();
+        fs::write(store.path(), r#"{"api": {"token": "legacy-token"}}"#).unwrap();
+
+        store.set("api", "other", "value").unwrap();
+
+        assert!(store.is_encrypted().unwrap());
+        assert_eq!(store.get("api", "token").unwrap(), "legacy-token");
+    }
+
     #[test]
     fn test_redact_value() {
         assert_eq!(redact_value("abc"), "***");
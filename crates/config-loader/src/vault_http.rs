@@ -4,7 +4,10 @@ use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use tracing::{debug, warn};
 
@@ -38,12 +41,245 @@ struct VaultKV2WriteRequest {
     data: HashMap<String, String>,
 }
 
-pub struct VaultHttpSecretProvider {
+#[derive(Debug, Deserialize)]
+struct VaultListResponse {
+    data: VaultListData,
+}
+
+#[derive(Debug, Deserialize)]
+struct VaultListData {
+    #[serde(default)]
+    keys: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VaultTokenLookupResponse {
+    data: VaultTokenLookupData,
+}
+
+#[derive(Debug, Deserialize)]
+struct VaultTokenLookupData {
+    #[serde(default)]
+    ttl: u64,
+    #[serde(default)]
+    renewable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct VaultTokenRenewResponse {
+    auth: VaultTokenRenewAuth,
+}
+
+#[derive(Debug, Deserialize)]
+struct VaultTokenRenewAuth {
+    #[serde(default)]
+    lease_duration: u64,
+    #[serde(default)]
+    renewable: bool,
+}
+
+/// Remaining lifetime of the Vault token; a zero TTL never expires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenLease {
+    pub ttl: Duration,
+    pub renewable: bool,
+}
+
+/// Retry delay after a failed token renewal
+const RENEW_RETRY: Duration = Duration::from_secs(10);
+
+/// Address, token and namespace shared by the provider and its token renewer
+#[derive(Clone)]
+struct VaultConnection {
     client: Client,
     vault_addr: String,
     vault_token: String,
     vault_namespace: Option<String>,
+}
+
+impl VaultConnection {
+    fn build_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-Vault-Token",
+            HeaderValue::from_str(&self.vault_token).unwrap(),
+        );
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        if let Some(ref namespace) = self.vault_namespace {
+            headers.insert(
+                "X-Vault-Namespace",
+                HeaderValue::from_str(namespace).unwrap(),
+            );
+        }
+
+        headers
+    }
+
+    fn lookup_self(&self) -> Result<TokenLease, VaultHttpError> {
+        let url = format!("{}/v1/auth/token/lookup-self", self.vault_addr);
+        let response = self
+            .client
+            .get(&url)
+            .headers(self.build_headers())
+            .send()
+            .map_err(|e| VaultHttpError::RequestFailed {
+                message: format!("HTTP request failed: {}", e),
+            })?;
+
+        let lookup: VaultTokenLookupResponse = token_response(response)?;
+        Ok(TokenLease {
+            ttl: Duration::from_secs(lookup.data.ttl),
+            renewable: lookup.data.renewable,
+        })
+    }
+
+    fn renew_self(&self, increment: Option<Duration>) -> Result<TokenLease, VaultHttpError> {
+        let url = format!("{}/v1/auth/token/renew-self", self.vault_addr);
+        let body = match increment {
+            Some(increment) => {
+                serde_json::json!({ "increment": format!("{}s", increment.as_secs()) })
+            }
+            None => serde_json::json!({}),
+        };
+        let response = self
+            .client
+            .post(&url)
+            .headers(self.build_headers())
+            .json(&body)
+            .send()
+            .map_err(|e| VaultHttpError::RequestFailed {
+                message: format!("HTTP request failed: {}", e),
+            })?;
+
+        let renewed: VaultTokenRenewResponse = token_response(response)?;
+        Ok(TokenLease {
+            ttl: Duration::from_secs(renewed.auth.lease_duration),
+            renewable: renewed.auth.renewable,
+        })
+    }
+}
+
+fn token_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::blocking::Response,
+) -> Result<T, VaultHttpError> {
+    match response.status().as_u16() {
+        200 => response
+            .json()
+            .map_err(|e| VaultHttpError::InvalidResponse {
+                message: format!("Failed to parse token response: {}", e),
+            }),
+        401 | 403 => Err(VaultHttpError::AuthFailed {
+            message: format!("Token lookup or renewal rejected: {}", response.status()),
+        }),
+        _ => Err(VaultHttpError::RequestFailed {
+            message: format!("Unexpected status: {}", response.status()),
+        }),
+    }
+}
+
+/// Background thread that keeps the provider's token alive
+///
+/// Looks the token up once, then renews it at two thirds of each TTL. Stops on
+/// its own when the token is not renewable (or never expires) or Vault rejects
+/// the renewal, and is stopped when the provider is dropped.
+pub struct TokenRenewer {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TokenRenewer {
+    fn spawn(connection: VaultConnection, increment: Option<Duration>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let handle = std::thread::Builder::new()
+            .name("vault-token-renewer".to_string())
+            .spawn(move || run_token_renewer(&connection, increment, &thread_stop))
+            .ok();
+        if handle.is_none() {
+            warn!("Failed to start Vault token renewer thread");
+        }
+        Self { stop, handle }
+    }
+}
+
+impl Drop for TokenRenewer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run_token_renewer(connection: &VaultConnection, increment: Option<Duration>, stop: &AtomicBool) {
+    let mut lease = match connection.lookup_self() {
+        Ok(lease) => lease,
+        Err(e) => {
+            warn!(
+                "Vault token lookup failed, token will not be renewed: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    loop {
+        if !lease.renewable || lease.ttl.is_zero() {
+            debug!("Vault token is not renewable or does not expire; renewer stopping");
+            return;
+        }
+        if sleep_unless_stopped(renew_delay(lease.ttl), stop) {
+            return;
+        }
+        match connection.renew_self(increment) {
+            Ok(renewed) => {
+                debug!("Renewed Vault token, ttl {}s", renewed.ttl.as_secs());
+                lease = renewed;
+            }
+            Err(e @ VaultHttpError::AuthFailed { .. }) => {
+                warn!("Vault token can no longer be renewed: {}", e);
+                return;
+            }
+            Err(e) => {
+                warn!("Vault token renewal failed, retrying: {}", e);
+                if sleep_unless_stopped(RENEW_RETRY, stop) {
+                    return;
+                }
+                lease.ttl = lease
+                    .ttl
+                    .saturating_sub(RENEW_RETRY)
+                    .max(Duration::from_secs(1));
+            }
+        }
+    }
+}
+
+/// Renew at two thirds of the TTL so a slow or failed attempt still has time left
+fn renew_delay(ttl: Duration) -> Duration {
+    (ttl * 2 / 3).max(Duration::from_millis(500))
+}
+
+/// Sleep for `duration`; true if the renewer was stopped meanwhile
+fn sleep_unless_stopped(duration: Duration, stop: &AtomicBool) -> bool {
+    let deadline = Instant::now() + duration;
+    while !stop.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::park_timeout(deadline - now);
+    }
+    true
+}
+
+pub struct VaultHttpSecretProvider {
+    connection: VaultConnection,
+    mount: String,
+    path_prefix: Option<String>,
     max_retries: u32,
+    renewer: Option<TokenRenewer>,
 }
 
 impl VaultHttpSecretProvider {
@@ -71,7 +307,13 @@ impl VaultHttpSecretProvider {
                 message: "VAULT_TOKEN is required for HTTP provider".to_string(),
             })?;
 
-        let namespace = vault_namespace.or_else(|| env::var("VAULT_NAMESPACE").ok());
+        let namespace = vault_namespace
+            .or_else(|| env::var("VAULT_NAMESPACE").ok())
+            .map(|namespace| namespace.trim_matches('/').to_string())
+            .filter(|namespace| !namespace.is_empty());
+
+        let mount = env::var("VAULT_KV_MOUNT").unwrap_or_else(|_| "secret".to_string());
+        let path_prefix = env::var("VAULT_KV_PATH_PREFIX").ok();
 
         let max_retries = env::var("VAULT_RETRY_ATTEMPTS")
             .ok()
@@ -111,35 +353,78 @@ impl VaultHttpSecretProvider {
                 message: format!("Failed to create HTTP client: {}", e),
             })?;
 
-        Ok(Self {
-            client,
-            vault_addr: addr.trim_end_matches('/').to_string(),
-            vault_token: token,
-            vault_namespace: namespace,
+        let provider = Self {
+            connection: VaultConnection {
+                client,
+                vault_addr: addr.trim_end_matches('/').to_string(),
+                vault_token: token,
+                vault_namespace: namespace,
+            },
+            mount: String::new(),
+            path_prefix: None,
             max_retries,
-        })
+            renewer: None,
+        };
+        Ok(provider.with_mount(&mount).with_path_prefix(path_prefix))
     }
 
+    /// Provider configured from the environment
+    ///
+    /// Besides `new`'s variables this honours `VAULT_TOKEN_RENEW=true`, which
+    /// starts a [`TokenRenewer`], with `VAULT_TOKEN_RENEW_INCREMENT` (seconds)
+    /// as the requested lease extension.
     pub fn from_env() -> Result<Self, VaultHttpError> {
-        Self::new(None, None, None)
+        let provider = Self::new(None, None, None)?;
+        if env::var("VAULT_TOKEN_RENEW").unwrap_or_default() != "true" {
+            return Ok(provider);
+        }
+
+        let increment = match env::var("VAULT_TOKEN_RENEW_INCREMENT") {
+            Ok(value) => Some(Duration::from_secs(value.parse::<u64>().map_err(|_| {
+                VaultHttpError::ConfigError {
+                    message: format!(
+                        "Invalid VAULT_TOKEN_RENEW_INCREMENT '{}': expected seconds",
+                        value
+                    ),
+                }
+            })?)),
+            Err(_) => None,
+        };
+        Ok(provider.with_token_renewal(increment))
     }
 
-    fn build_headers(&self) -> HeaderMap {
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            "X-Vault-Token",
-            HeaderValue::from_str(&self.vault_token).unwrap(),
-        );
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    /// KV v2 secrets engine mount, `secret` by default (`VAULT_KV_MOUNT`)
+    pub fn with_mount(mut self, mount: &str) -> Self {
+        self.mount = mount.trim_matches('/').to_string();
+        self
+    }
 
-        if let Some(ref namespace) = self.vault_namespace {
-            headers.insert(
-                "X-Vault-Namespace",
-                HeaderValue::from_str(namespace).unwrap(),
-            );
-        }
+    /// Path under the mount that scopes live in (`VAULT_KV_PATH_PREFIX`)
+    pub fn with_path_prefix(mut self, prefix: Option<String>) -> Self {
+        self.path_prefix = prefix
+            .map(|prefix| prefix.trim_matches('/').to_string())
+            .filter(|prefix| !prefix.is_empty());
+        self
+    }
 
-        headers
+    /// Keep the token alive with a background [`TokenRenewer`] for the provider's lifetime
+    pub fn with_token_renewal(mut self, increment: Option<Duration>) -> Self {
+        self.renewer = Some(TokenRenewer::spawn(self.connection.clone(), increment));
+        self
+    }
+
+    /// Look up the token's remaining TTL
+    pub fn lookup_token(&self) -> Result<TokenLease, VaultHttpError> {
+        self.connection.lookup_self()
+    }
+
+    /// Renew the token once, optionally asking for `increment` more lifetime
+    pub fn renew_token(&self, increment: Option<Duration>) -> Result<TokenLease, VaultHttpError> {
+        self.connection.renew_self(increment)
+    }
+
+    fn build_headers(&self) -> HeaderMap {
+        self.connection.build_headers()
     }
 
     fn retry_with_backoff<F, T>(&self, mut operation: F) -> Result<T, VaultHttpError>
@@ -184,8 +469,32 @@ impl VaultHttpSecretProvider {
         }))
     }
 
+    /// Path of `segments` below the mount, including the configured prefix
+    fn secret_path(&self, segments: &[&str]) -> String {
+        self.path_prefix
+            .iter()
+            .map(String::as_str)
+            .chain(segments.iter().copied())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
     fn kv_path(&self, scope: &str, key: &str) -> String {
-        format!("{}/v1/secret/data/{}/{}", self.vault_addr, scope, key)
+        format!(
+            "{}/v1/{}/data/{}",
+            self.connection.vault_addr,
+            self.mount,
+            self.secret_path(&[scope, key])
+        )
+    }
+
+    fn metadata_path(&self, segments: &[&str]) -> String {
+        format!(
+            "{}/v1/{}/metadata/{}",
+            self.connection.vault_addr,
+            self.mount,
+            self.secret_path(segments)
+        )
     }
 
     pub fn resolve_secret(&self, scope: &str, key: &str) -> Result<String, SecretError> {
@@ -194,6 +503,7 @@ impl VaultHttpSecretProvider {
 
         let result = self.retry_with_backoff(|| {
             let response = self
+                .connection
                 .client
                 .get(&url)
                 .headers(self.build_headers())
@@ -266,6 +576,7 @@ impl VaultHttpSecretProvider {
 
         self.retry_with_backoff(|| {
             let response = self
+                .connection
                 .client
                 .post(&url)
                 .headers(self.build_headers())
@@ -292,11 +603,12 @@ impl VaultHttpSecretProvider {
     }
 
     pub fn delete(&self, scope: &str, key: &str) -> Result<(), String> {
-        let url = format!("{}/v1/secret/metadata/{}/{}", self.vault_addr, scope, key);
+        let url = self.metadata_path(&[scope, key]);
         debug!("Deleting secret from Vault: {}/{}", scope, key);
 
         self.retry_with_backoff(|| {
             let response = self
+                .connection
                 .client
                 .delete(&url)
                 .headers(self.build_headers())
@@ -324,13 +636,79 @@ impl VaultHttpSecretProvider {
         .map_err(|e| e.to_string())
     }
 
+    /// List secret names per scope (KV v2 metadata LIST)
+    ///
+    /// Values are not read, so every entry maps to `***`. Nested paths deeper
+    /// than `scope/key` are skipped.
     pub fn list(
         &self,
-        _scope: Option<&str>,
+        scope: Option<&str>,
     ) -> Result<HashMap<String, HashMap<String, String>>, String> {
-        // For now, return an error as listing is complex in Vault KV v2
-        // This would require LIST operations which have different semantics
-        Err("List operation not implemented for HTTP provider".to_string())
+        let scopes = match scope {
+            Some(scope) => vec![scope.to_string()],
+            None => self
+                .list_keys(&[""])
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter_map(|key| key.strip_suffix('/').map(str::to_string))
+                .collect(),
+        };
+
+        let mut result = HashMap::new();
+        for scope in scopes {
+            let keys: HashMap<String, String> = self
+                .list_keys(&[scope.as_str(), ""])
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|key| !key.ends_with('/'))
+                .map(|key| (key, "***".to_string()))
+                .collect();
+            if !keys.is_empty() {
+                result.insert(scope, keys);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Keys under a metadata path; folders end in `/`, an unknown path is empty
+    fn list_keys(&self, segments: &[&str]) -> Result<Vec<String>, VaultHttpError> {
+        let url = self.metadata_path(segments);
+        debug!("Listing Vault metadata: {}", url);
+
+        self.retry_with_backoff(|| {
+            let response = self
+                .connection
+                .client
+                .get(&url)
+                .query(&[("list", "true")])
+                .headers(self.build_headers())
+                .send()
+                .map_err(|e| VaultHttpError::RequestFailed {
+                    message: format!("HTTP request failed: {}", e),
+                })?;
+
+            match response.status().as_u16() {
+                200 => {
+                    let list: VaultListResponse =
+                        response
+                            .json()
+                            .map_err(|e| VaultHttpError::InvalidResponse {
+                                message: format!("Failed to parse list response: {}", e),
+                            })?;
+                    Ok(list.data.keys)
+                }
+                404 => Ok(Vec::new()),
+                401 | 403 => Err(VaultHttpError::AuthFailed {
+                    message: format!("Authentication failed: {}", response.status()),
+                }),
+                status if status >= 500 => Err(VaultHttpError::RequestFailed {
+                    message: format!("Server error: {}", response.status()),
+                }),
+                _ => Err(VaultHttpError::RequestFailed {
+                    message: format!("Failed to list secrets: {}", response.status()),
+                }),
+            }
+        })
     }
 }
 
@@ -354,10 +732,47 @@ mod tests {
         // Test with token should succeed
         std::env::set_var("VAULT_TOKEN", "test-token");
         let provider = VaultHttpSecretProvider::from_env().unwrap();
-        assert_eq!(provider.vault_addr, "http://127.0.0.1:8200");
+        assert_eq!(provider.connection.vault_addr, "http://127.0.0.1:8200");
         std::env::remove_var("VAULT_TOKEN");
     }
 
+    #[test]
+    fn test_secret_paths_use_mount_and_prefix() {
+        let provider = VaultHttpSecretProvider::new(
+            Some("http://vault:8200/".to_string()),
+            Some("token".to_string()),
+            None,
+        )
+        .unwrap()
+        .with_mount("/kv/")
+        .with_path_prefix(Some("demon/prod/".to_string()));
+
+        assert_eq!(
+            provider.kv_path("db", "password"),
+            "http://vault:8200/v1/kv/data/demon/prod/db/password"
+        );
+        assert_eq!(
+            provider.metadata_path(&["db", ""]),
+            "http://vault:8200/v1/kv/metadata/demon/prod/db/"
+        );
+        assert_eq!(
+            provider.with_path_prefix(None).metadata_path(&[""]),
+            "http://vault:8200/v1/kv/metadata/"
+        );
+    }
+
+    #[test]
+    fn test_renew_delay_is_two_thirds_of_ttl() {
+        assert_eq!(
+            renew_delay(Duration::from_secs(3600)),
+            Duration::from_secs(2400)
+        );
+        assert_eq!(
+            renew_delay(Duration::from_millis(300)),
+            Duration::from_millis(500)
+        );
+    }
+
     #[test]
     fn test_invalid_vault_addr() {
         let result = VaultHttpSecretProvider::new(
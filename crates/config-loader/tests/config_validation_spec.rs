@@ -1,20 +1,8 @@
+use config_loader::generated::{EchoConfig, EchoConfigOutputFormat};
 use config_loader::{ConfigError, ConfigManager};
-use serde::Deserialize;
 use std::fs;
 use tempfile::TempDir;
 
-#[derive(Deserialize, Debug, PartialEq)]
-struct EchoConfig {
-    #[serde(rename = "messagePrefix")]
-    message_prefix: String,
-    #[serde(rename = "enableTrim")]
-    enable_trim: bool,
-    #[serde(rename = "maxMessageLength")]
-    max_message_length: Option<i32>,
-    #[serde(rename = "outputFormat")]
-    output_format: Option<String>,
-}
-
 fn setup_test_environment() -> (TempDir, ConfigManager) {
     let temp_dir = TempDir::new().unwrap();
     let contracts_dir = temp_dir.path().join("contracts");
@@ -85,7 +73,7 @@ fn given_valid_config_when_load_then_success() {
     assert_eq!(config.message_prefix, "Test: ");
     assert!(config.enable_trim);
     assert_eq!(config.max_message_length, Some(500));
-    assert_eq!(config.output_format, Some("plain".to_string()));
+    assert_eq!(config.output_format, Some(EchoConfigOutputFormat::Plain));
 }
 
 #[test]
@@ -152,7 +140,7 @@ fn given_missing_config_file_with_valid_schema_when_load_then_uses_defaults() {
     assert_eq!(config.message_prefix, "");
     assert!(config.enable_trim);
     assert_eq!(config.max_message_length, Some(1000));
-    assert_eq!(config.output_format, Some("plain".to_string()));
+    assert_eq!(config.output_format, Some(EchoConfigOutputFormat::Plain));
 }
 
 #[test]
@@ -0,0 +1,173 @@
+use config_loader::generated::{EchoConfig, EchoConfigOutputFormat};
+use config_loader::{ConfigError, ConfigLayer, ConfigManager};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const SCHEMA: &str = r#"{
+    "$schema": "http://json-schema.org/draft-07/schema#",
+    "type": "object",
+    "properties": {
+        "messagePrefix": { "type": "string", "default": "" },
+        "enableTrim": { "type": "boolean", "default": true },
+        "maxMessageLength": { "type": "integer", "minimum": 1, "default": 1000 },
+        "outputFormat": { "type": "string", "enum": ["plain", "json", "structured"], "default": "plain" }
+    },
+    "required": ["messagePrefix", "enableTrim"],
+    "additionalProperties": false
+}"#;
+
+/// Each test uses its own link name so environment overrides set by one test
+/// never leak into another running in parallel.
+fn setup(link: &str) -> (TempDir, ConfigManager) {
+    let temp_dir = TempDir::new().unwrap();
+    let contracts_dir = temp_dir.path().join("contracts");
+    let config_dir = temp_dir.path().join("config");
+
+    fs::create_dir_all(contracts_dir.join("config")).unwrap();
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        contracts_dir.join(format!("config/{}-config.v1.json", link)),
+        SCHEMA,
+    )
+    .unwrap();
+
+    let manager = ConfigManager::with_dirs(contracts_dir, config_dir).with_environment(None);
+    (temp_dir, manager)
+}
+
+fn write(dir: &Path, name: &str, content: &str) {
+    fs::write(dir.join(name), content).unwrap();
+}
+
+#[test]
+fn given_env_overlay_when_load_then_overlay_fields_win_over_base_file() {
+    let (_temp_dir, manager) = setup("layer-overlay");
+    let manager = manager.with_environment(Some("staging"));
+
+    write(
+        manager.config_dir(),
+        "layer-overlay.json",
+        r#"{ "messagePrefix": "base: ", "enableTrim": false, "outputFormat": "json" }"#,
+    );
+    write(
+        manager.config_dir(),
+        "layer-overlay.staging.json",
+        r#"{ "messagePrefix": "staging: " }"#,
+    );
+
+    let config: EchoConfig = manager.load("layer-overlay").unwrap();
+
+    assert_eq!(config.message_prefix, "staging: ");
+    assert!(!config.enable_trim);
+    assert_eq!(config.output_format, Some(EchoConfigOutputFormat::Json));
+    // Optional field missing from both files falls back to the schema default
+    assert_eq!(config.max_message_length, Some(1000));
+}
+
+#[test]
+fn given_other_environment_when_load_then_overlay_is_ignored() {
+    let (_temp_dir, manager) = setup("layer-other-env");
+    let manager = manager.with_environment(Some("prod"));
+
+    write(
+        manager.config_dir(),
+        "layer-other-env.json",
+        r#"{ "messagePrefix": "base: ", "enableTrim": true }"#,
+    );
+    write(
+        manager.config_dir(),
+        "layer-other-env.staging.json",
+        r#"{ "messagePrefix": "staging: " }"#,
+    );
+
+    let config: EchoConfig = manager.load("layer-other-env").unwrap();
+
+    assert_eq!(config.message_prefix, "base: ");
+}
+
+#[test]
+fn given_env_var_override_when_load_then_value_is_typed_from_schema() {
+    let (_temp_dir, manager) = setup("layer-envvar");
+
+    write(
+        manager.config_dir(),
+        "layer-envvar.json",
+        r#"{ "messagePrefix": "base: ", "enableTrim": true }"#,
+    );
+    std::env::set_var("DEMON_CFG_LAYER_ENVVAR__MESSAGE_PREFIX", "42");
+    std::env::set_var("DEMON_CFG_LAYER_ENVVAR__MAX_MESSAGE_LENGTH", "250");
+
+    let config: EchoConfig = manager.load("layer-envvar").unwrap();
+    let explanation = manager.explain("layer-envvar").unwrap();
+
+    std::env::remove_var("DEMON_CFG_LAYER_ENVVAR__MESSAGE_PREFIX");
+    std::env::remove_var("DEMON_CFG_LAYER_ENVVAR__MAX_MESSAGE_LENGTH");
+
+    assert_eq!(config.message_prefix, "42");
+    assert_eq!(config.max_message_length, Some(250));
+    assert_eq!(
+        explanation.source_of("/maxMessageLength"),
+        Some(&ConfigLayer::EnvVar {
+            name: "DEMON_CFG_LAYER_ENVVAR__MAX_MESSAGE_LENGTH".to_string()
+        })
+    );
+}
+
+#[test]
+fn given_layers_when_explain_then_each_field_reports_its_source() {
+    let (_temp_dir, manager) = setup("layer-explain");
+    let manager = manager.with_environment(Some("dev"));
+
+    write(
+        manager.config_dir(),
+        "layer-explain.json",
+        r#"{ "messagePrefix": "base: ", "enableTrim": true }"#,
+    );
+    write(
+        manager.config_dir(),
+        "layer-explain.dev.json",
+        r#"{ "enableTrim": false }"#,
+    );
+
+    let explanation = manager.explain("layer-explain").unwrap();
+
+    assert_eq!(explanation.environment.as_deref(), Some("dev"));
+    assert!(matches!(
+        explanation.source_of("/messagePrefix"),
+        Some(ConfigLayer::BaseFile { .. })
+    ));
+    assert!(matches!(
+        explanation.source_of("/enableTrim"),
+        Some(ConfigLayer::EnvFile { env, .. }) if env == "dev"
+    ));
+    assert_eq!(
+        explanation.source_of("/outputFormat"),
+        Some(&ConfigLayer::SchemaDefault)
+    );
+}
+
+#[test]
+fn given_env_overlay_missing_required_field_when_no_base_then_validation_error() {
+    let (_temp_dir, manager) = setup("layer-required");
+    let manager = manager.with_environment(Some("dev"));
+
+    write(
+        manager.config_dir(),
+        "layer-required.dev.json",
+        r#"{ "messagePrefix": "dev: " }"#,
+    );
+
+    let result: Result<EchoConfig, ConfigError> = manager.load("layer-required");
+
+    assert!(matches!(result, Err(ConfigError::ValidationFailed { .. })));
+}
+
+#[test]
+fn given_no_schema_and_no_layers_when_explain_then_schema_not_found() {
+    let (_temp_dir, manager) = setup("layer-present");
+
+    let result = manager.explain("layer-absent");
+
+    assert!(matches!(result, Err(ConfigError::SchemaNotFound { .. })));
+}
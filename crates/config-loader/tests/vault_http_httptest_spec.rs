@@ -1,6 +1,7 @@
-use config_loader::{SecretError, SecretProvider, VaultHttpSecretProvider};
+use config_loader::{SecretError, SecretProvider, TokenLease, VaultHttpSecretProvider};
 use httptest::{matchers::*, responders::*, Expectation, Server};
 use serde_json::json;
+use std::time::Duration;
 
 #[test]
 fn test_vault_http_resolve_secret_success() {
@@ -225,3 +226,178 @@ fn test_vault_http_all_retries_exhausted() {
 
     std::env::remove_var("VAULT_RETRY_ATTEMPTS");
 }
+
+fn provider_for(server: &Server) -> VaultHttpSecretProvider {
+    VaultHttpSecretProvider::new(
+        Some(format!("http://{}", server.addr())),
+        Some("test-token".to_string()),
+        None,
+    )
+    .unwrap()
+}
+
+/// Matcher for a KV v2 metadata LIST of `$path`
+macro_rules! list_request {
+    ($path:expr) => {
+        all_of![
+            request::method_path("GET", $path),
+            request::query(url_decoded(contains(("list", "true")))),
+            request::headers(contains(("x-vault-token", "test-token"))),
+        ]
+    };
+}
+
+#[test]
+fn test_vault_http_list_all_scopes() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(list_request!("/v1/secret/metadata/")).respond_with(json_encoded(
+            json!({ "data": { "keys": ["api/", "database/", "empty/"] } }),
+        )),
+    );
+    server.expect(
+        Expectation::matching(list_request!("/v1/secret/metadata/api/")).respond_with(
+            json_encoded(json!({ "data": { "keys": ["key", "nested/"] } })),
+        ),
+    );
+    server.expect(
+        Expectation::matching(list_request!("/v1/secret/metadata/database/")).respond_with(
+            json_encoded(json!({ "data": { "keys": ["password", "user"] } })),
+        ),
+    );
+    server.expect(
+        Expectation::matching(list_request!("/v1/secret/metadata/empty/"))
+            .respond_with(json_encoded(json!({ "data": { "keys": ["folder/"] } }))),
+    );
+
+    let secrets = provider_for(&server).list(None).unwrap();
+
+    assert_eq!(secrets.len(), 2);
+    assert_eq!(secrets["api"].keys().collect::<Vec<_>>(), vec!["key"]);
+    assert_eq!(secrets["database"].len(), 2);
+    assert_eq!(secrets["database"]["password"], "***");
+}
+
+#[test]
+fn test_vault_http_list_unknown_scope_is_empty() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(list_request!("/v1/secret/metadata/missing/"))
+            .respond_with(status_code(404)),
+    );
+
+    let secrets = provider_for(&server).list(Some("missing")).unwrap();
+    assert!(secrets.is_empty());
+}
+
+#[test]
+fn test_vault_http_list_auth_failure() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(list_request!("/v1/secret/metadata/"))
+            .times(1)
+            .respond_with(status_code(403)),
+    );
+
+    let result = provider_for(&server).list(None);
+    assert!(result.unwrap_err().contains("authentication failed"));
+}
+
+#[test]
+fn test_vault_http_custom_mount_prefix_and_namespace() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", "/v1/kv/data/demon/prod/demo/key"),
+            request::headers(contains(("x-vault-namespace", "team/apps"))),
+        ])
+        .respond_with(json_encoded(
+            json!({ "data": { "data": { "key": "mounted" } } }),
+        )),
+    );
+    server.expect(
+        Expectation::matching(all_of![
+            list_request!("/v1/kv/metadata/demon/prod/demo/"),
+            request::headers(contains(("x-vault-namespace", "team/apps"))),
+        ])
+        .respond_with(json_encoded(json!({ "data": { "keys": ["key"] } }))),
+    );
+
+    let provider = VaultHttpSecretProvider::new(
+        Some(format!("http://{}", server.addr())),
+        Some("test-token".to_string()),
+        Some("/team/apps/".to_string()),
+    )
+    .unwrap()
+    .with_mount("kv")
+    .with_path_prefix(Some("demon/prod".to_string()));
+
+    assert_eq!(provider.resolve("demo", "key").unwrap(), "mounted");
+    assert!(provider.list(Some("demo")).unwrap()["demo"].contains_key("key"));
+}
+
+#[test]
+fn test_vault_http_renew_token() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("POST", "/v1/auth/token/renew-self"),
+            request::headers(contains(("x-vault-token", "test-token"))),
+            request::body(json_decoded(eq(json!({ "increment": "3600s" })))),
+        ])
+        .respond_with(json_encoded(json!({
+            "auth": { "client_token": "test-token", "lease_duration": 3600, "renewable": true }
+        }))),
+    );
+
+    let lease = provider_for(&server)
+        .renew_token(Some(Duration::from_secs(3600)))
+        .unwrap();
+    assert_eq!(
+        lease,
+        TokenLease {
+            ttl: Duration::from_secs(3600),
+            renewable: true
+        }
+    );
+}
+
+#[test]
+fn test_vault_http_background_token_renewal() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/v1/auth/token/lookup-self"))
+            .times(1)
+            .respond_with(json_encoded(
+                json!({ "data": { "ttl": 1, "renewable": true } }),
+            )),
+    );
+    server.expect(
+        Expectation::matching(request::method_path("POST", "/v1/auth/token/renew-self"))
+            .times(1..)
+            .respond_with(json_encoded(json!({
+                "auth": { "lease_duration": 1, "renewable": true }
+            }))),
+    );
+
+    let provider = provider_for(&server).with_token_renewal(None);
+    std::thread::sleep(Duration::from_millis(1500));
+    // Dropping the provider stops the renewer before the server verifies expectations
+    drop(provider);
+}
+
+#[test]
+fn test_vault_http_renewal_stops_for_non_renewable_token() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("GET", "/v1/auth/token/lookup-self"))
+            .times(1)
+            .respond_with(json_encoded(
+                json!({ "data": { "ttl": 0, "renewable": false } }),
+            )),
+    );
+
+    let provider = provider_for(&server).with_token_renewal(None);
+    std::thread::sleep(Duration::from_millis(200));
+    drop(provider);
+}
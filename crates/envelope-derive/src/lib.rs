@@ -1,19 +1,178 @@
 use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Ident, LitBool, LitStr, Token};
 
-#[proc_macro_derive(AsEnvelope)]
+/// `#[envelope(source = "...", version, instance = "...")]` on the type
+#[derive(Default)]
+struct SourceAttrs {
+    source: Option<LitStr>,
+    /// `version` alone uses the deriving crate's `CARGO_PKG_VERSION`
+    version: Option<TokenStream2>,
+    instance: Option<LitStr>,
+}
+
+/// `#[envelope(code = "...", category = "...", retryable = bool)]` on an error variant
+#[derive(Default)]
+struct ErrorAttrs {
+    code: Option<LitStr>,
+    category: Option<Ident>,
+    retryable: Option<LitBool>,
+}
+
+impl SourceAttrs {
+    /// `.with_source_info(...)` for the builder, when a source is configured
+    fn builder_call(&self) -> TokenStream2 {
+        let Some(source) = &self.source else {
+            return quote! {};
+        };
+        let version = match &self.version {
+            Some(version) => quote! { ::core::option::Option::Some(#version) },
+            None => quote! { ::core::option::Option::None::<&str> },
+        };
+        let instance = match &self.instance {
+            Some(instance) => quote! { ::core::option::Option::Some(#instance) },
+            None => quote! { ::core::option::Option::None::<&str> },
+        };
+        quote! { .with_source_info(#source, #version, #instance) }
+    }
+}
+
+fn is_envelope(attr: &Attribute) -> bool {
+    attr.path().is_ident("envelope")
+}
+
+/// Parse type-level attributes; error attributes are only accepted when
+/// `errors` is set (structs deriving `AsErrorEnvelope`)
+fn parse_type_attrs(
+    attrs: &[Attribute],
+    mut errors: Option<&mut ErrorAttrs>,
+) -> syn::Result<SourceAttrs> {
+    let mut source = SourceAttrs::default();
+    for attr in attrs.iter().filter(|a| is_envelope(a)) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("source") {
+                source.source = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("version") {
+                source.version = Some(if meta.input.peek(Token![=]) {
+                    let version: LitStr = meta.value()?.parse()?;
+                    quote! { #version }
+                } else {
+                    quote! { ::core::env!("CARGO_PKG_VERSION") }
+                });
+            } else if meta.path.is_ident("instance") {
+                source.instance = Some(meta.value()?.parse()?);
+            } else if let Some(errors) = errors.as_deref_mut() {
+                parse_error_meta(&meta, errors)?;
+            } else {
+                return Err(meta.error("expected `source`, `version` or `instance`"));
+            }
+            Ok(())
+        })?;
+    }
+    if source.source.is_none() && (source.version.is_some() || source.instance.is_some()) {
+        return Err(syn::Error::new_spanned(
+            attrs.iter().find(|a| is_envelope(a)),
+            "`version` and `instance` require `source`",
+        ));
+    }
+    Ok(source)
+}
+
+fn parse_error_meta(meta: &syn::meta::ParseNestedMeta, errors: &mut ErrorAttrs) -> syn::Result<()> {
+    if meta.path.is_ident("code") {
+        errors.code = Some(meta.value()?.parse()?);
+    } else if meta.path.is_ident("category") {
+        let category: LitStr = meta.value()?.parse()?;
+        let variant = match category.value().as_str() {
+            "user" => "User",
+            "config" => "Config",
+            "infrastructure" => "Infrastructure",
+            "timeout" => "Timeout",
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    category,
+                    "expected one of `user`, `config`, `infrastructure`, `timeout`",
+                ))
+            }
+        };
+        errors.category = Some(Ident::new(variant, category.span()));
+    } else if meta.path.is_ident("retryable") {
+        errors.retryable = Some(if meta.input.peek(Token![=]) {
+            meta.value()?.parse()?
+        } else {
+            LitBool::new(true, meta.path.span())
+        });
+    } else {
+        return Err(meta.error("expected `code`, `category` or `retryable`"));
+    }
+    Ok(())
+}
+
+fn parse_variant_attrs(attrs: &[Attribute]) -> syn::Result<ErrorAttrs> {
+    let mut errors = ErrorAttrs::default();
+    for attr in attrs.iter().filter(|a| is_envelope(a)) {
+        attr.parse_nested_meta(|meta| parse_error_meta(&meta, &mut errors))?;
+    }
+    Ok(errors)
+}
+
+/// `NotFound` -> `NOT_FOUND`, `HTTPTimeout` -> `HTTP_TIMEOUT`
+fn screaming_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_uppercase());
+    }
+    out
+}
+
+/// `info` with the code, category and retryability an error variant declares
+fn error_info_chain(default_code: &Ident, attrs: &ErrorAttrs) -> TokenStream2 {
+    let code = attrs
+        .code
+        .as_ref()
+        .map(LitStr::value)
+        .unwrap_or_else(|| screaming_snake_case(&default_code.to_string()));
+    let category = attrs.category.as_ref().map(|category| {
+        quote! { .with_category(::envelope::ErrorCategory::#category) }
+    });
+    // After the category, which sets its own default
+    let retryable = attrs
+        .retryable
+        .as_ref()
+        .map(|retryable| quote! { .retryable(#retryable) });
+    quote! { info.with_code(#code) #category #retryable }
+}
+
+#[proc_macro_derive(AsEnvelope, attributes(envelope))]
 pub fn derive_as_envelope(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
     let generics = &input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let source = match parse_type_attrs(&input.attrs, None) {
+        Ok(source) => source.builder_call(),
+        Err(e) => return e.to_compile_error().into(),
+    };
 
     let expanded = quote! {
         impl #impl_generics ::envelope::AsEnvelope for #name #ty_generics #where_clause {
             fn into_envelope(self) -> ::envelope::ResultEnvelope<Self> {
                 ::envelope::ResultEnvelope::builder()
                     .result(::envelope::OperationResult::success(self))
+                    #source
                     .build()
                     .expect("Building envelope with valid data should not fail")
             }
@@ -22,3 +181,83 @@ pub fn derive_as_envelope(input: TokenStream) -> TokenStream {
 
     TokenStream::from(expanded)
 }
+
+#[proc_macro_derive(AsErrorEnvelope, attributes(envelope))]
+pub fn derive_as_error_envelope(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_as_error_envelope(&input) {
+        Ok(expanded) => expanded.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_as_error_envelope(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let (source, body) = match &input.data {
+        Data::Enum(data) => {
+            let source = parse_type_attrs(&input.attrs, None)?;
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let chain = error_info_chain(ident, &parse_variant_attrs(&variant.attrs)?);
+                    Ok(quote! { Self::#ident { .. } => #chain, })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            // `match *self {}` is the only exhaustive match on an empty enum
+            let body = if arms.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! { match self { #(#arms)* } }
+            };
+            (source, body)
+        }
+        Data::Struct(_) => {
+            let mut errors = ErrorAttrs::default();
+            let source = parse_type_attrs(&input.attrs, Some(&mut errors))?;
+            (source, error_info_chain(name, &errors))
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "AsErrorEnvelope can only be derived for enums and structs",
+            ))
+        }
+    };
+    let source = source.builder_call();
+
+    Ok(quote! {
+        impl #impl_generics ::envelope::AsErrorEnvelope for #name #ty_generics #where_clause {
+            fn error_info(&self) -> ::envelope::ErrorInfo {
+                let info = ::envelope::ErrorInfo::new(::std::string::ToString::to_string(self));
+                #body
+            }
+
+            fn into_error_envelope<__EnvelopeData>(
+                self,
+            ) -> ::envelope::ResultEnvelope<__EnvelopeData> {
+                ::envelope::ResultEnvelope::builder()
+                    .error_info(::envelope::AsErrorEnvelope::error_info(&self))
+                    #source
+                    .build()
+                    .expect("Building envelope with an error result should not fail")
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_names_become_screaming_snake_case_codes() {
+        assert_eq!(screaming_snake_case("NotFound"), "NOT_FOUND");
+        assert_eq!(screaming_snake_case("HTTPTimeout"), "HTTP_TIMEOUT");
+        assert_eq!(screaming_snake_case("Io"), "IO");
+        assert_eq!(screaming_snake_case("Retry3Times"), "RETRY3_TIMES");
+    }
+}
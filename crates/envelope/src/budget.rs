@@ -0,0 +1,198 @@
+//! Size budgets for result envelopes
+//!
+//! Envelopes travel in NATS messages, which are capped (`max_payload`, 1 MiB
+//! by default). A [`SizeBudget`] keeps an envelope under a byte limit by
+//! moving the largest diagnostics and `metrics.custom` into artifacts through
+//! an [`ArtifactSink`] and leaving stubs that point at them:
+//!
+//! - a spilled diagnostic keeps its level, source and timestamp, a shortened
+//!   message, and `context: {"spilled_to": <reference>, "original_bytes": n}`
+//! - spilled `metrics.custom` becomes `{"spilled_to": ..., "original_bytes": n}`
+//!
+//! A warning diagnostic from `envelope.size_budget` lists the artifacts. The
+//! result itself is never spilled; if it alone is over budget, enforcement
+//! fails with [`BudgetError::TooLarge`].
+
+use crate::envelope::{Diagnostic, ResultEnvelope};
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// NATS's default 1 MiB `max_payload`, less room for the event wrapping the envelope
+pub const DEFAULT_MAX_ENVELOPE_BYTES: usize = 960 * 1024;
+
+/// Diagnostic source of the spill summary
+pub const SIZE_BUDGET_SOURCE: &str = "envelope.size_budget";
+
+/// Characters of a spilled diagnostic's message kept in its stub
+const STUB_MESSAGE_CHARS: usize = 200;
+
+/// Items smaller than this are not worth replacing with a stub
+const MIN_SPILL_BYTES: usize = 512;
+
+/// Somewhere to put content that does not fit in an envelope
+pub trait ArtifactSink: Send + Sync {
+    /// Store `bytes` under `name` and return a reference to them, such as a
+    /// file path or an object-store URL
+    fn store(&self, name: &str, bytes: &[u8]) -> anyhow::Result<String>;
+}
+
+/// Writes artifacts as files in a directory and references them by path
+#[derive(Debug, Clone)]
+pub struct DirectorySink {
+    dir: PathBuf,
+}
+
+impl DirectorySink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl ArtifactSink for DirectorySink {
+    fn store(&self, name: &str, bytes: &[u8]) -> anyhow::Result<String> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(name);
+        std::fs::write(&path, bytes)?;
+        Ok(path.display().to_string())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BudgetError {
+    #[error("Envelope is {size} bytes after spilling, over the {max_bytes} byte budget")]
+    TooLarge { size: usize, max_bytes: usize },
+    #[error("Failed to store spilled envelope content: {0}")]
+    Spill(String),
+    #[error("Failed to serialize envelope: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// A maximum serialized envelope size and where to spill what does not fit
+#[derive(Clone)]
+pub struct SizeBudget {
+    max_bytes: usize,
+    sink: Arc<dyn ArtifactSink>,
+}
+
+/// Something that can be moved out of an envelope
+enum Spillable {
+    Diagnostic(usize),
+    CustomMetrics,
+}
+
+impl SizeBudget {
+    pub fn new(max_bytes: usize, sink: impl ArtifactSink + 'static) -> Self {
+        Self {
+            max_bytes,
+            sink: Arc::new(sink),
+        }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Spill the largest diagnostics and custom metrics until the envelope
+    /// fits, and return how many items were spilled
+    pub fn enforce<T>(&self, envelope: &mut ResultEnvelope<T>) -> Result<usize, BudgetError>
+    where
+        T: Serialize,
+    {
+        let original_bytes = serde_json::to_vec(&*envelope)?.len();
+        if original_bytes <= self.max_bytes {
+            return Ok(0);
+        }
+
+        let mut candidates = Vec::new();
+        for (index, diagnostic) in envelope.diagnostics.iter().enumerate() {
+            let size = serde_json::to_vec(diagnostic)?.len();
+            candidates.push((size, Spillable::Diagnostic(index)));
+        }
+        if let Some(custom) = envelope.metrics.as_ref().and_then(|m| m.custom.as_ref()) {
+            candidates.push((serde_json::to_vec(custom)?.len(), Spillable::CustomMetrics));
+        }
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.0));
+
+        let batch = uuid::Uuid::new_v4().simple().to_string();
+        let mut size = original_bytes;
+        let mut artifacts = Vec::new();
+        for (item_bytes, item) in candidates {
+            if size <= self.max_bytes || item_bytes < MIN_SPILL_BYTES {
+                break;
+            }
+            let stub_bytes = match item {
+                Spillable::Diagnostic(index) => {
+                    let diagnostic = &mut envelope.diagnostics[index];
+                    let name = format!("envelope-{}-diagnostic-{}.json", batch, index);
+                    let reference = self.store(&name, &serde_json::to_vec(&*diagnostic)?)?;
+                    *diagnostic = stub_diagnostic(diagnostic, &reference, item_bytes);
+                    artifacts.push(reference);
+                    serde_json::to_vec(&*diagnostic)?.len()
+                }
+                Spillable::CustomMetrics => {
+                    let metrics = envelope.metrics.as_mut().expect("candidate exists");
+                    let name = format!("envelope-{}-metrics-custom.json", batch);
+                    let reference = self.store(&name, &serde_json::to_vec(&metrics.custom)?)?;
+                    let stub = json!({ "spilled_to": reference, "original_bytes": item_bytes });
+                    let stub_bytes = serde_json::to_vec(&stub)?.len();
+                    metrics.custom = Some(stub);
+                    artifacts.push(reference);
+                    stub_bytes
+                }
+            };
+            size = size - item_bytes + stub_bytes;
+        }
+
+        if !artifacts.is_empty() {
+            envelope.diagnostics.push(
+                Diagnostic::warning(format!(
+                    "Envelope was {} bytes, over the {} byte budget; moved {} item(s) to artifacts",
+                    original_bytes,
+                    self.max_bytes,
+                    artifacts.len()
+                ))
+                .with_source(SIZE_BUDGET_SOURCE)
+                .with_context(json!({
+                    "max_bytes": self.max_bytes,
+                    "original_bytes": original_bytes,
+                    "artifacts": artifacts,
+                })),
+            );
+        }
+
+        let size = serde_json::to_vec(&*envelope)?.len();
+        if size > self.max_bytes {
+            return Err(BudgetError::TooLarge {
+                size,
+                max_bytes: self.max_bytes,
+            });
+        }
+        Ok(artifacts.len())
+    }
+
+    fn store(&self, name: &str, bytes: &[u8]) -> Result<String, BudgetError> {
+        self.sink
+            .store(name, bytes)
+            .map_err(|e| BudgetError::Spill(format!("{}: {:#}", name, e)))
+    }
+}
+
+fn stub_diagnostic(diagnostic: &Diagnostic, reference: &str, original_bytes: usize) -> Diagnostic {
+    let mut message: String = diagnostic
+        .message
+        .chars()
+        .take(STUB_MESSAGE_CHARS)
+        .collect();
+    if message.len() < diagnostic.message.len() {
+        message.push_str("… (truncated)");
+    }
+    Diagnostic {
+        level: diagnostic.level.clone(),
+        message,
+        timestamp: diagnostic.timestamp,
+        source: diagnostic.source.clone(),
+        context: Some(json!({ "spilled_to": reference, "original_bytes": original_bytes })),
+    }
+}
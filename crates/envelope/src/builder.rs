@@ -1,5 +1,7 @@
+use crate::budget::{BudgetError, SizeBudget};
 use crate::envelope::*;
 use chrono::Utc;
+use serde::Serialize;
 use std::collections::HashMap;
 
 pub struct ResultEnvelopeBuilder<T> {
@@ -47,6 +49,47 @@ impl<T> ResultEnvelopeBuilder<T> {
         self
     }
 
+    pub fn error_info(mut self, error: ErrorInfo) -> Self {
+        self.result = Some(OperationResult::failure(error));
+        self
+    }
+
+    /// Bad input; not retryable
+    pub fn user_error(self, message: impl Into<String>) -> Self {
+        self.error_info(ErrorInfo::new(message).with_category(ErrorCategory::User))
+    }
+
+    /// Missing or invalid configuration; not retryable
+    pub fn config_error(self, message: impl Into<String>) -> Self {
+        self.error_info(ErrorInfo::new(message).with_category(ErrorCategory::Config))
+    }
+
+    /// A failed dependency; retryable
+    pub fn infrastructure_error(self, message: impl Into<String>) -> Self {
+        self.error_info(ErrorInfo::new(message).with_category(ErrorCategory::Infrastructure))
+    }
+
+    /// Ran out of time; retryable
+    pub fn timeout_error(self, message: impl Into<String>) -> Self {
+        self.error_info(ErrorInfo::new(message).with_category(ErrorCategory::Timeout))
+    }
+
+    /// Override whether the error set so far is retryable; no-op for successes
+    pub fn retryable(mut self, retryable: bool) -> Self {
+        if let Some(OperationResult::Error { error, .. }) = &mut self.result {
+            error.retryable = retryable;
+        }
+        self
+    }
+
+    /// Ask callers to wait before retrying the error set so far; no-op for successes
+    pub fn retry_after(mut self, delay: std::time::Duration) -> Self {
+        if let Some(OperationResult::Error { error, .. }) = self.result.take() {
+            self.result = Some(OperationResult::failure(error.with_retry_after(delay)));
+        }
+        self
+    }
+
     pub fn add_diagnostic(mut self, diagnostic: Diagnostic) -> Self {
         self.diagnostics.push(diagnostic);
         self
@@ -137,6 +180,7 @@ impl<T> ResultEnvelopeBuilder<T> {
             span_id: None,
             parent_span_id: None,
             chain: Vec::new(),
+            signature: None,
         };
 
         self.provenance(provenance)
@@ -171,6 +215,18 @@ impl<T> ResultEnvelopeBuilder<T> {
     }
 }
 
+impl<T> ResultEnvelopeBuilder<T>
+where
+    T: Serialize,
+{
+    /// Build, then spill diagnostics and custom metrics as needed to fit `budget`
+    pub fn build_within(self, budget: &SizeBudget) -> Result<ResultEnvelope<T>, BuildError> {
+        let mut envelope = self.build()?;
+        budget.enforce(&mut envelope)?;
+        Ok(envelope)
+    }
+}
+
 impl<T> ResultEnvelope<T> {
     pub fn builder() -> ResultEnvelopeBuilder<T> {
         ResultEnvelopeBuilder::new()
@@ -186,6 +242,7 @@ impl Default for Provenance {
             span_id: None,
             parent_span_id: None,
             chain: Vec::new(),
+            signature: None,
         }
     }
 }
@@ -194,6 +251,8 @@ impl Default for Provenance {
 pub enum BuildError {
     #[error("Result is required to build an envelope")]
     MissingResult,
+    #[error(transparent)]
+    Budget(#[from] BudgetError),
 }
 
 pub struct SuggestionBuilder {
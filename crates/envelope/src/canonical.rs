@@ -0,0 +1,206 @@
+//! Canonical JSON for digests and signatures
+//!
+//! The canonical form of a value is the same bytes however the value was built
+//! and whichever producer serialized it:
+//!
+//! - object keys sorted, at every level
+//! - no insignificant whitespace
+//! - numbers with an integral value written as integers (`1.0` → `1`,
+//!   `-0.0` → `0`); other numbers in their shortest round-trip form
+//!
+//! An envelope's canonical form additionally writes its own timestamps
+//! (`diagnostics[].timestamp`, `provenance.timestamp` and
+//! `provenance.chain[].timestamp`) in UTC with a `Z` suffix and no trailing
+//! zero fraction (`2025-01-15T11:00:31+01:00` → `2025-01-15T10:00:31Z`).
+//! Strings anywhere else, such as capsule data, are kept as they are.
+//!
+//! Envelope digests and signatures are computed over this form, and so are
+//! graph commit IDs from scheme v2 on.
+
+use crate::envelope::ResultEnvelope;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::{Number, Value};
+use sha2::{Digest, Sha256};
+
+/// Largest integer an `f64` holds exactly
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// `value` in canonical form
+pub fn canonical_json(value: &Value) -> Vec<u8> {
+    let mut out = Vec::new();
+    write(value, &mut out);
+    out
+}
+
+/// Hex SHA-256 of the canonical form of `value`
+pub fn canonical_digest(value: &Value) -> String {
+    hex::encode(Sha256::digest(canonical_json(value)))
+}
+
+/// `envelope` (a serialized [`ResultEnvelope`]) in canonical form, with its
+/// timestamp fields normalized
+pub fn canonical_envelope_json(envelope: &Value) -> Vec<u8> {
+    let mut envelope = envelope.clone();
+    normalize_envelope_timestamps(&mut envelope);
+    canonical_json(&envelope)
+}
+
+/// Hex SHA-256 of [`canonical_envelope_json`]
+pub fn canonical_envelope_digest(envelope: &Value) -> String {
+    hex::encode(Sha256::digest(canonical_envelope_json(envelope)))
+}
+
+impl<T> ResultEnvelope<T>
+where
+    T: Serialize,
+{
+    /// The whole envelope in canonical form, signature included
+    pub fn to_canonical_json(&self) -> Result<String, serde_json::Error> {
+        let bytes = canonical_envelope_json(&serde_json::to_value(self)?);
+        Ok(String::from_utf8(bytes).expect("canonical JSON is UTF-8"))
+    }
+
+    /// Hex SHA-256 of [`ResultEnvelope::to_canonical_json`]
+    pub fn digest(&self) -> Result<String, serde_json::Error> {
+        Ok(canonical_envelope_digest(&serde_json::to_value(self)?))
+    }
+}
+
+fn write(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push(b'{');
+            for (i, (key, child)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                out.extend(serde_json::to_vec(key).expect("strings serialize"));
+                out.push(b':');
+                write(child, out);
+            }
+            out.push(b'}');
+        }
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write(item, out);
+            }
+            out.push(b']');
+        }
+        Value::Number(number) => out.extend(normalized_number(number).into_bytes()),
+        scalar => out.extend(serde_json::to_vec(scalar).expect("scalars serialize")),
+    }
+}
+
+fn normalized_number(number: &Number) -> String {
+    match number.as_f64() {
+        Some(float)
+            if number.is_f64() && float.fract() == 0.0 && float.abs() < MAX_EXACT_INTEGER =>
+        {
+            (float as i64).to_string()
+        }
+        // Drop the explicit `+` some formatters give positive exponents
+        _ => number.to_string().replace("e+", "e"),
+    }
+}
+
+fn normalize_envelope_timestamps(envelope: &mut Value) {
+    if let Some(diagnostics) = envelope
+        .get_mut("diagnostics")
+        .and_then(Value::as_array_mut)
+    {
+        diagnostics.iter_mut().for_each(normalize_timestamp_field);
+    }
+    if let Some(provenance) = envelope.get_mut("provenance") {
+        normalize_timestamp_field(provenance);
+        if let Some(chain) = provenance.get_mut("chain").and_then(Value::as_array_mut) {
+            chain.iter_mut().for_each(normalize_timestamp_field);
+        }
+    }
+}
+
+/// Normalize `object.timestamp` when it holds an RFC 3339 string
+fn normalize_timestamp_field(object: &mut Value) {
+    if let Some(field) = object.get_mut("timestamp") {
+        if let Some(normalized) = field.as_str().and_then(normalized_timestamp) {
+            *field = Value::String(normalized);
+        }
+    }
+}
+
+fn normalized_timestamp(text: &str) -> Option<String> {
+    let parsed = DateTime::parse_from_rfc3339(text).ok()?;
+    Some(
+        parsed
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn canonical_json_sorts_keys_at_every_level() {
+        let value = json!({"b": [{"z": 1, "a": null}], "a": "x\"y"});
+        assert_eq!(
+            String::from_utf8(canonical_json(&value)).unwrap(),
+            r#"{"a":"x\"y","b":[{"a":null,"z":1}]}"#
+        );
+    }
+
+    #[test]
+    fn numbers_are_normalized() {
+        let value = json!({
+            "integral": 100.0,
+            "negative_zero": -0.0,
+            "fraction": 0.25,
+            "large": 1e300,
+            "ts": "2025-01-15T11:00:31+01:00"
+        });
+        assert_eq!(
+            String::from_utf8(canonical_json(&value)).unwrap(),
+            concat!(
+                r#"{"fraction":0.25,"integral":100,"large":1e300,"#,
+                r#""negative_zero":0,"ts":"2025-01-15T11:00:31+01:00"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn only_envelope_timestamps_are_normalized() {
+        let envelope = json!({
+            "result": {"success": true, "data": {"at": "2025-01-15T11:00:31+01:00"}},
+            "diagnostics": [{"level": "info", "message": "m", "timestamp": "2025-01-15T10:00:31.000Z"}],
+            "provenance": {
+                "timestamp": "2025-01-15T11:00:31+01:00",
+                "chain": [{"step": "s", "timestamp": "2025-01-15T10:00:31.120Z"}]
+            }
+        });
+        let canonical = String::from_utf8(canonical_envelope_json(&envelope)).unwrap();
+        assert!(canonical.contains(r#""data":{"at":"2025-01-15T11:00:31+01:00"}"#));
+        assert!(canonical.contains(r#""message":"m","timestamp":"2025-01-15T10:00:31Z""#));
+        assert!(
+            canonical.contains(r#""chain":[{"step":"s","timestamp":"2025-01-15T10:00:31.120Z"}]"#)
+        );
+        assert!(canonical.contains(r#""timestamp":"2025-01-15T10:00:31Z"},"result""#));
+    }
+
+    #[test]
+    fn equal_values_have_equal_digests() {
+        let a = json!({"provenance": {"timestamp": "2025-01-15T10:00:31Z"}, "n": 3});
+        let b = json!({"n": 3.0, "provenance": {"timestamp": "2025-01-15T12:00:31+02:00"}});
+        assert_eq!(canonical_envelope_digest(&a), canonical_envelope_digest(&b));
+        assert_ne!(canonical_digest(&a), canonical_digest(&b));
+        assert_eq!(canonical_digest(&a).len(), 64);
+        assert_ne!(canonical_digest(&a), canonical_digest(&json!({"n": 4})));
+    }
+}
@@ -35,6 +35,31 @@ pub struct ErrorInfo {
     pub code: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub category: Option<ErrorCategory>,
+    /// Whether running the same operation again may succeed; absent means false
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    #[serde(default)]
+    pub retryable: bool,
+    /// Earliest sensible retry, when the producer knows (e.g. a rate limit reset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub retry_after_ms: Option<u64>,
+}
+
+/// Where an error came from, which decides who can fix it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorCategory {
+    /// Bad input from the caller; retrying the same request fails again
+    User,
+    /// Missing or invalid configuration; needs an operator
+    Config,
+    /// A dependency (network, storage, runtime) failed; often transient
+    Infrastructure,
+    /// The operation ran out of time
+    Timeout,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,6 +197,10 @@ pub struct Provenance {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
     pub chain: Vec<ProcessingStep>,
+    /// Set by [`ResultEnvelope::sign`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub signature: Option<crate::signing::EnvelopeSignature>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -202,27 +231,37 @@ impl<T> OperationResult<T> {
     }
 
     pub fn error(message: impl Into<String>) -> Self {
-        Self::Error {
-            success: false,
-            error: ErrorInfo {
-                message: message.into(),
-                code: None,
-                details: None,
-            },
-        }
+        Self::failure(ErrorInfo::new(message))
     }
 
     pub fn error_with_code(message: impl Into<String>, code: impl Into<String>) -> Self {
+        Self::failure(ErrorInfo::new(message).with_code(code))
+    }
+
+    /// An error categorized as `category`, retryable if the category usually is
+    pub fn error_with_category(message: impl Into<String>, category: ErrorCategory) -> Self {
+        Self::failure(ErrorInfo::new(message).with_category(category))
+    }
+
+    pub fn failure(error: ErrorInfo) -> Self {
         Self::Error {
             success: false,
-            error: ErrorInfo {
-                message: message.into(),
-                code: Some(code.into()),
-                details: None,
-            },
+            error,
+        }
+    }
+
+    pub fn error_info(&self) -> Option<&ErrorInfo> {
+        match self {
+            Self::Error { error, .. } => Some(error),
+            Self::Success { .. } => None,
         }
     }
 
+    /// Whether this is an error the producer marked as worth retrying
+    pub fn is_retryable(&self) -> bool {
+        self.error_info().is_some_and(|e| e.retryable)
+    }
+
     pub fn is_success(&self) -> bool {
         matches!(self, Self::Success { .. })
     }
@@ -232,6 +271,61 @@ impl<T> OperationResult<T> {
     }
 }
 
+impl ErrorInfo {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            code: None,
+            details: None,
+            category: None,
+            retryable: false,
+            retry_after_ms: None,
+        }
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Set the category and the retryability that goes with it; call
+    /// [`ErrorInfo::retryable`] afterwards to override
+    pub fn with_category(mut self, category: ErrorCategory) -> Self {
+        self.category = Some(category);
+        self.retryable = category.is_usually_retryable();
+        self
+    }
+
+    pub fn retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Ask callers to wait before retrying; marks the error retryable
+    pub fn with_retry_after(mut self, delay: std::time::Duration) -> Self {
+        self.retryable = true;
+        self.retry_after_ms = Some(delay.as_millis().try_into().unwrap_or(u64::MAX));
+        self
+    }
+
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        self.retry_after_ms.map(std::time::Duration::from_millis)
+    }
+}
+
+impl ErrorCategory {
+    /// Infrastructure failures and timeouts are often transient; user and
+    /// config errors fail the same way until someone changes something
+    pub fn is_usually_retryable(self) -> bool {
+        matches!(self, Self::Infrastructure | Self::Timeout)
+    }
+}
+
 impl Diagnostic {
     pub fn new(level: DiagnosticLevel, message: impl Into<String>) -> Self {
         Self {
@@ -0,0 +1,187 @@
+//! Custom `format` validators and vocabulary extensions for contract schemas
+//!
+//! The JSON Schema drafts only define generic formats (`date-time`, `uri`,
+//! ...). A [`FormatRegistry`] adds the ones our contracts need; the built-in
+//! set is:
+//!
+//! - `digest`: `<algorithm>:<hex>` content digests, e.g. `sha256:9f86...`
+//!   (`sha256`, `sha384` and `sha512`, lowercase hex of the right length)
+//! - `rfc3339-with-offset`: an RFC 3339 timestamp with a numeric offset
+//!   (`+02:00`, not `Z`), for values whose local offset matters
+//! - `semver`: a semantic version (`1.2.3`, `1.0.0-rc.1+build.5`)
+//!
+//! A [`Vocabulary`] is a set of custom keywords identified by a URI. Schemas
+//! that list the URI as required in `$vocabulary` only compile when the
+//! vocabulary is registered with the validator.
+
+use chrono::DateTime;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+
+/// Checks whether a string is valid for a format
+pub type FormatCheck = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Checks an instance against a custom keyword's value (the keyword's value
+/// in the schema comes first), returning why it fails
+pub type KeywordCheck = Arc<dyn Fn(&Value, &Value) -> Result<(), String> + Send + Sync>;
+
+/// Format validators by format name
+#[derive(Clone)]
+pub struct FormatRegistry {
+    formats: BTreeMap<String, FormatCheck>,
+}
+
+/// Custom keywords grouped under a vocabulary URI
+#[derive(Clone)]
+pub struct Vocabulary {
+    uri: String,
+    keywords: BTreeMap<String, KeywordCheck>,
+}
+
+impl FormatRegistry {
+    /// A registry without any formats
+    pub fn empty() -> Self {
+        Self {
+            formats: BTreeMap::new(),
+        }
+    }
+
+    /// The built-in formats described in the module docs
+    pub fn builtin() -> Self {
+        Self::empty()
+            .with_format("digest", is_digest)
+            .with_format("rfc3339-with-offset", is_rfc3339_with_offset)
+            .with_format("semver", is_semver)
+    }
+
+    /// Add or replace a format
+    pub fn register<F>(&mut self, name: impl Into<String>, check: F)
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.formats.insert(name.into(), Arc::new(check));
+    }
+
+    pub fn with_format<F>(mut self, name: impl Into<String>, check: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.register(name, check);
+        self
+    }
+
+    /// Whether `value` is valid for `name`, or `None` for an unknown format
+    pub fn check(&self, name: &str, value: &str) -> Option<bool> {
+        self.formats.get(name).map(|check| check(value))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FormatCheck)> {
+        self.formats
+            .iter()
+            .map(|(name, check)| (name.as_str(), check))
+    }
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Vocabulary {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            keywords: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_keyword<F>(mut self, name: impl Into<String>, check: F) -> Self
+    where
+        F: Fn(&Value, &Value) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.keywords.insert(name.into(), Arc::new(check));
+        self
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    pub fn keywords(&self) -> impl Iterator<Item = (&str, &KeywordCheck)> {
+        self.keywords
+            .iter()
+            .map(|(name, check)| (name.as_str(), check))
+    }
+}
+
+/// `sha256:<64 hex>`, `sha384:<96 hex>` or `sha512:<128 hex>`
+pub fn is_digest(value: &str) -> bool {
+    let Some((algorithm, hex)) = value.split_once(':') else {
+        return false;
+    };
+    let expected = match algorithm {
+        "sha256" => 64,
+        "sha384" => 96,
+        "sha512" => 128,
+        _ => return false,
+    };
+    hex.len() == expected && hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// RFC 3339 with a numeric offset rather than `Z`
+pub fn is_rfc3339_with_offset(value: &str) -> bool {
+    let offset_start = value.len().saturating_sub(6);
+    DateTime::parse_from_rfc3339(value).is_ok()
+        && value
+            .get(offset_start..)
+            .is_some_and(|offset| offset.starts_with(['+', '-']))
+}
+
+/// Semantic Versioning 2.0.0, using the regex from semver.org
+pub fn is_semver(value: &str) -> bool {
+    static SEMVER: OnceLock<Regex> = OnceLock::new();
+    SEMVER
+        .get_or_init(|| {
+            Regex::new(
+                r"^(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(?:-((?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+([0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$",
+            )
+            .expect("semver regex is valid")
+        })
+        .is_match(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_formats_accept_and_reject() {
+        let formats = FormatRegistry::builtin();
+
+        assert_eq!(
+            formats.check("digest", &format!("sha256:{}", "a1".repeat(32))),
+            Some(true)
+        );
+        assert_eq!(formats.check("digest", "sha256:abc123"), Some(false));
+        assert_eq!(
+            formats.check("digest", &format!("md5:{}", "a".repeat(32))),
+            Some(false)
+        );
+
+        assert_eq!(
+            formats.check("rfc3339-with-offset", "2025-01-15T10:30:00+02:00"),
+            Some(true)
+        );
+        assert_eq!(
+            formats.check("rfc3339-with-offset", "2025-01-15T10:30:00Z"),
+            Some(false)
+        );
+
+        assert_eq!(formats.check("semver", "1.0.0-rc.1+build.5"), Some(true));
+        assert_eq!(formats.check("semver", "1.02.0"), Some(false));
+        assert_eq!(formats.check("unknown", "x"), None);
+    }
+}
@@ -33,6 +33,52 @@
 //! envelope.validate().expect("Should validate");
 //! ```
 //!
+//! Type attributes fill in provenance; `version` on its own uses the
+//! deriving crate's version:
+//!
+//! ```rust
+//! use envelope::*;
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize, AsEnvelope)]
+//! #[envelope(source = "my-capsule", version)]
+//! struct Report {
+//!     passed: bool,
+//! }
+//!
+//! let envelope = Report { passed: true }.into_envelope();
+//! let source = envelope.provenance.unwrap().source.unwrap();
+//! assert_eq!(source.system, "my-capsule");
+//! ```
+//!
+//! ## Error Envelopes
+//!
+//! `AsErrorEnvelope` maps error variants to codes (SCREAMING_SNAKE_CASE of
+//! the variant name unless `code` is given) and takes the message from
+//! `Display`:
+//!
+//! ```rust
+//! use envelope::*;
+//!
+//! #[derive(Debug, thiserror::Error, AsErrorEnvelope)]
+//! #[envelope(source = "my-capsule")]
+//! enum FetchError {
+//!     #[error("manifest not found")]
+//!     #[envelope(category = "user")]
+//!     NotFound,
+//!     #[error("registry unavailable: {0}")]
+//!     #[envelope(code = "REGISTRY_DOWN", category = "infrastructure")]
+//!     Registry(String),
+//! }
+//!
+//! let info = FetchError::Registry("503".into()).error_info();
+//! assert_eq!(info.code.as_deref(), Some("REGISTRY_DOWN"));
+//! assert!(info.retryable);
+//!
+//! let envelope: ResultEnvelope<()> = FetchError::NotFound.into_error_envelope();
+//! assert!(!envelope.result.is_retryable());
+//! ```
+//!
 //! ## Builder Pattern
 //!
 //! The builder pattern provides a fluent API for constructing complex envelopes:
@@ -79,20 +125,80 @@
 //! // Validate the envelope
 //! assert!(envelope.validate().is_ok());
 //! ```
+//!
+//! ## Streaming Envelopes
+//!
+//! Long-running operations can publish diagnostics and metrics as they go and
+//! assemble the final envelope from the fragments:
+//!
+//! ```rust
+//! use envelope::*;
+//!
+//! let fragments = vec![
+//!     PartialEnvelope::new(0).add_diagnostic(Diagnostic::info("Downloading inputs")),
+//!     PartialEnvelope::new(1).add_diagnostic(Diagnostic::warning("Retrying mirror")),
+//!     PartialEnvelope::new(2).result(OperationResult::success("done")),
+//! ];
+//!
+//! // Each fragment can be published as an `envelope.fragment:v1` event
+//! let event = fragments[0].clone().into_event("default", "build", "run-1", "fetch");
+//! assert_eq!(event.event, FRAGMENT_EVENT);
+//!
+//! let envelope = ResultEnvelope::assemble(fragments).expect("Complete stream");
+//! assert_eq!(envelope.diagnostics.len(), 2);
+//! ```
+//!
+//! ## Redaction
+//!
+//! Strip secrets from envelopes before they are persisted or displayed:
+//!
+//! ```rust
+//! use envelope::*;
+//! use serde_json::json;
+//!
+//! let redactor = Redactor::default();
+//! let mut envelope = json!({
+//!     "result": {"success": true, "data": {"user": "ops", "password": "hunter2"}}
+//! });
+//! assert_eq!(redactor.redact_json(Some("deploy"), &mut envelope), 1);
+//! assert_eq!(envelope["result"]["data"]["password"], REDACTED);
+//! assert_eq!(envelope["diagnostics"][0]["source"], REDACTION_SOURCE);
+//! ```
 
+mod budget;
 mod builder;
+mod canonical;
 mod envelope;
+mod formats;
+mod partial;
+mod redaction;
+mod signing;
 mod validation;
 
+pub use budget::*;
 pub use builder::*;
+pub use canonical::*;
 pub use envelope::*;
+pub use formats::*;
+pub use partial::*;
+pub use redaction::*;
+pub use signing::*;
 pub use validation::*;
 
-// Re-export the derive macro
-pub use envelope_derive::AsEnvelope;
+// Re-export the derive macros
+pub use envelope_derive::{AsEnvelope, AsErrorEnvelope};
 
 pub trait AsEnvelope {
     fn into_envelope(self) -> ResultEnvelope<Self>
     where
         Self: Sized;
 }
+
+/// Errors that know their envelope error code, category and retryability
+pub trait AsErrorEnvelope {
+    fn error_info(&self) -> ErrorInfo;
+
+    fn into_error_envelope<T>(self) -> ResultEnvelope<T>
+    where
+        Self: Sized;
+}
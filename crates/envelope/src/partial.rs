@@ -0,0 +1,226 @@
+//! Streaming result envelopes
+//!
+//! Long-running capsules can publish their envelope in pieces: each
+//! [`PartialEnvelope`] carries whatever diagnostics, suggestions and metrics
+//! are known so far, wrapped in an `envelope.fragment:v1` event for the run
+//! stream. The last fragment carries the result, and
+//! [`ResultEnvelope::assemble`] merges the pieces back into one envelope.
+//!
+//! Merge rules, in sequence order:
+//! - diagnostics and suggestions are appended
+//! - metrics are snapshots: each field, phase and counter keeps its latest value
+//! - provenance, tool and matrix keep their latest value
+//! - exactly one fragment, the last, carries the result
+
+use crate::envelope::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Event name for a published fragment
+pub const FRAGMENT_EVENT: &str = "envelope.fragment:v1";
+
+/// One increment of a result envelope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialEnvelope<T> {
+    /// Position in the stream, starting at 0 with no gaps
+    pub sequence: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<OperationResult<T>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub diagnostics: Vec<Diagnostic>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub suggestions: Vec<Suggestion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<Metrics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<ToolInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matrix: Option<MatrixInfo>,
+}
+
+/// A fragment as published on the run's event stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvelopeFragmentEvent<T> {
+    pub event: String,
+    pub ts: DateTime<Utc>,
+    pub tenant_id: String,
+    pub ritual_id: String,
+    pub run_id: String,
+    pub step_id: String,
+    pub fragment: PartialEnvelope<T>,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum AssembleError {
+    #[error("No fragments to assemble")]
+    Empty,
+    #[error("Fragment {0} appears more than once")]
+    DuplicateSequence(u64),
+    #[error("Fragment {0} is missing")]
+    MissingSequence(u64),
+    #[error("No fragment carries a result")]
+    MissingResult,
+    #[error("Fragment {0} follows the fragment carrying the result")]
+    FragmentAfterResult(u64),
+    #[error("Assembled envelope is invalid: {0}")]
+    Invalid(String),
+}
+
+impl<T> PartialEnvelope<T> {
+    pub fn new(sequence: u64) -> Self {
+        Self {
+            sequence,
+            result: None,
+            diagnostics: Vec::new(),
+            suggestions: Vec::new(),
+            metrics: None,
+            provenance: None,
+            tool: None,
+            matrix: None,
+        }
+    }
+
+    pub fn result(mut self, result: OperationResult<T>) -> Self {
+        self.result = Some(result);
+        self
+    }
+
+    pub fn add_diagnostic(mut self, diagnostic: Diagnostic) -> Self {
+        self.diagnostics.push(diagnostic);
+        self
+    }
+
+    pub fn add_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Whether this is the closing fragment
+    pub fn is_final(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// Wrap the fragment for publishing on a run's event stream
+    pub fn into_event(
+        self,
+        tenant_id: impl Into<String>,
+        ritual_id: impl Into<String>,
+        run_id: impl Into<String>,
+        step_id: impl Into<String>,
+    ) -> EnvelopeFragmentEvent<T> {
+        EnvelopeFragmentEvent {
+            event: FRAGMENT_EVENT.to_string(),
+            ts: Utc::now(),
+            tenant_id: tenant_id.into(),
+            ritual_id: ritual_id.into(),
+            run_id: run_id.into(),
+            step_id: step_id.into(),
+            fragment: self,
+        }
+    }
+}
+
+impl<T> ResultEnvelope<T>
+where
+    T: Serialize,
+{
+    /// Merge fragments (in any order) into a complete envelope and validate it
+    /// against the result envelope schema
+    pub fn assemble(
+        fragments: impl IntoIterator<Item = PartialEnvelope<T>>,
+    ) -> Result<Self, AssembleError> {
+        let mut fragments: Vec<PartialEnvelope<T>> = fragments.into_iter().collect();
+        if fragments.is_empty() {
+            return Err(AssembleError::Empty);
+        }
+        fragments.sort_by_key(|f| f.sequence);
+        for (expected, fragment) in (0u64..).zip(&fragments) {
+            if fragment.sequence < expected {
+                return Err(AssembleError::DuplicateSequence(fragment.sequence));
+            }
+            if fragment.sequence > expected {
+                return Err(AssembleError::MissingSequence(expected));
+            }
+        }
+        let last = fragments.len() - 1;
+        if let Some(early) = fragments[..last].iter().position(PartialEnvelope::is_final) {
+            return Err(AssembleError::FragmentAfterResult(
+                fragments[early + 1].sequence,
+            ));
+        }
+
+        let mut result = None;
+        let mut diagnostics = Vec::new();
+        let mut suggestions = Vec::new();
+        let mut metrics: Option<Metrics> = None;
+        let mut provenance = None;
+        let mut tool = None;
+        let mut matrix = None;
+        for fragment in fragments {
+            result = fragment.result.or(result);
+            diagnostics.extend(fragment.diagnostics);
+            suggestions.extend(fragment.suggestions);
+            metrics = match (metrics, fragment.metrics) {
+                (Some(current), Some(update)) => Some(merge_metrics(current, update)),
+                (current, update) => update.or(current),
+            };
+            provenance = fragment.provenance.or(provenance);
+            tool = fragment.tool.or(tool);
+            matrix = fragment.matrix.or(matrix);
+        }
+
+        let envelope = ResultEnvelope {
+            result: result.ok_or(AssembleError::MissingResult)?,
+            diagnostics,
+            suggestions,
+            metrics,
+            provenance,
+            tool,
+            matrix,
+        };
+        envelope
+            .validate()
+            .map_err(|e| AssembleError::Invalid(e.to_string()))?;
+        Ok(envelope)
+    }
+}
+
+/// Overlay a later metrics snapshot on an earlier one
+fn merge_metrics(mut current: Metrics, update: Metrics) -> Metrics {
+    current.duration = match (current.duration, update.duration) {
+        (Some(mut duration), Some(update)) => {
+            duration.total_ms = update.total_ms.or(duration.total_ms);
+            duration.phases.extend(update.phases);
+            Some(duration)
+        }
+        (duration, update) => update.or(duration),
+    };
+    current.resources = match (current.resources, update.resources) {
+        (Some(mut resources), Some(update)) => {
+            resources.memory_bytes = update.memory_bytes.or(resources.memory_bytes);
+            resources.cpu_percent = update.cpu_percent.or(resources.cpu_percent);
+            resources.io_operations = update.io_operations.or(resources.io_operations);
+            resources.additional.extend(update.additional);
+            Some(resources)
+        }
+        (resources, update) => update.or(resources),
+    };
+    current.counters.extend(update.counters);
+    current.custom = update.custom.or(current.custom);
+    current
+}
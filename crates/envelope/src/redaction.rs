@@ -0,0 +1,465 @@
+//! Redaction of sensitive values in result envelopes
+//!
+//! Capsules put whatever they print into their envelopes, and envelopes end
+//! up in the run stream and the Operate UI. A [`Redactor`] replaces sensitive
+//! values with [`REDACTED`] before that happens, using three kinds of rule:
+//!
+//! - `paths`: JSONPath-style locations rooted at the envelope, e.g.
+//!   `$.result.data.credentials`, `$.diagnostics[*].context.env` or `$..password`
+//! - `keys`: object keys whose values are always redacted, wherever they
+//!   appear (case-insensitive, `-` and `_` are interchangeable)
+//! - `patterns`: regexes; matching text inside any string is replaced
+//!
+//! Rules under `capsules.<name>` only apply to that capsule's envelopes. The
+//! built-in keys and patterns (passwords, bearer tokens, JWTs, cloud and VCS
+//! access keys, PEM private keys) apply unless `builtin` is `false`.
+//!
+//! When anything is redacted the envelope gets an info diagnostic from
+//! `envelope.redaction` recording how many values were replaced.
+
+use crate::envelope::{Diagnostic, ResultEnvelope};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Diagnostic source of the audit diagnostic
+pub const REDACTION_SOURCE: &str = "envelope.redaction";
+
+/// Environment variable naming a JSON file of [`RedactionRules`]
+pub const RULES_ENV: &str = "ENVELOPE_REDACTION_RULES";
+
+const BUILTIN_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "client_secret",
+    "token",
+    "access_token",
+    "refresh_token",
+    "api_key",
+    "apikey",
+    "access_key",
+    "secret_key",
+    "private_key",
+    "authorization",
+];
+
+const BUILTIN_PATTERNS: &[&str] = &[
+    // Bearer credentials in headers or logs
+    r"(?i)\bbearer\s+[a-z0-9._~+/=-]{8,}",
+    // JWTs
+    r"\beyJ[A-Za-z0-9_-]{5,}\.[A-Za-z0-9_-]{5,}\.[A-Za-z0-9_-]{5,}",
+    // AWS access key ids
+    r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",
+    // GitHub tokens
+    r"\bgh[pousr]_[A-Za-z0-9]{36,}\b",
+    // Slack tokens
+    r"\bxox[abprs]-[A-Za-z0-9-]{10,}",
+    // PEM private keys
+    r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+];
+
+/// One set of rules; see the module docs for their meaning
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patterns: Vec<String>,
+}
+
+/// Redaction configuration, usually loaded from [`RULES_ENV`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedactionRules {
+    /// Include the built-in keys and patterns
+    #[serde(default = "default_builtin")]
+    pub builtin: bool,
+    #[serde(flatten)]
+    pub rules: RuleSet,
+    /// Extra rules per capsule name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub capsules: BTreeMap<String, RuleSet>,
+}
+
+fn default_builtin() -> bool {
+    true
+}
+
+impl Default for RedactionRules {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            rules: RuleSet::default(),
+            capsules: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RedactionError {
+    #[error("Invalid redaction path '{path}': {reason}")]
+    InvalidPath { path: String, reason: String },
+    #[error("Invalid redaction pattern '{pattern}': {source}")]
+    InvalidPattern {
+        pattern: String,
+        #[source]
+        source: regex::Error,
+    },
+    #[error("Failed to read redaction rules from {path}: {reason}")]
+    Config { path: String, reason: String },
+    #[error("Redacted envelope no longer matches the envelope structure: {0}")]
+    Structure(#[from] serde_json::Error),
+}
+
+/// A step in a redaction path
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    /// `*` or `[*]`: every member of an object or array
+    Wildcard,
+    /// `..key`: `key` at any depth
+    Descendant(String),
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRules {
+    paths: Vec<Vec<Segment>>,
+    keys: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+/// Applies [`RedactionRules`] to envelopes
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    global: CompiledRules,
+    capsules: BTreeMap<String, CompiledRules>,
+}
+
+impl Default for Redactor {
+    /// The built-in rules only
+    fn default() -> Self {
+        Self::new(RedactionRules::default()).expect("built-in redaction rules compile")
+    }
+}
+
+impl Redactor {
+    pub fn new(rules: RedactionRules) -> Result<Self, RedactionError> {
+        let mut global = CompiledRules::compile(&rules.rules)?;
+        if rules.builtin {
+            global
+                .keys
+                .extend(BUILTIN_KEYS.iter().map(|k| normalize_key(k)));
+            for pattern in BUILTIN_PATTERNS {
+                global.patterns.push(compile_pattern(pattern)?);
+            }
+        }
+        let capsules = rules
+            .capsules
+            .iter()
+            .map(|(name, set)| Ok((name.clone(), CompiledRules::compile(set)?)))
+            .collect::<Result<_, RedactionError>>()?;
+        Ok(Self { global, capsules })
+    }
+
+    /// Rules from the JSON file named by [`RULES_ENV`], or the built-in rules
+    /// when it is unset
+    pub fn from_env() -> Result<Self, RedactionError> {
+        let Ok(path) = std::env::var(RULES_ENV) else {
+            return Ok(Self::default());
+        };
+        let config_error = |reason: String| RedactionError::Config {
+            path: path.clone(),
+            reason,
+        };
+        let text = std::fs::read_to_string(&path).map_err(|e| config_error(e.to_string()))?;
+        let rules: RedactionRules =
+            serde_json::from_str(&text).map_err(|e| config_error(e.to_string()))?;
+        Self::new(rules)
+    }
+
+    /// Redact an envelope (or any JSON value) produced by `capsule` in place
+    /// and return how many values were replaced. Envelopes, i.e. objects with
+    /// a `result`, get the audit diagnostic when the count is non-zero.
+    pub fn redact_json(&self, capsule: Option<&str>, envelope: &mut Value) -> usize {
+        let capsule_rules = capsule.and_then(|name| self.capsules.get(name));
+        let mut count = 0;
+        for rules in std::iter::once(&self.global).chain(capsule_rules) {
+            for path in &rules.paths {
+                count += redact_path(envelope, path);
+            }
+        }
+        count += self.redact_values(capsule_rules, envelope);
+        if count > 0 {
+            if let Some(object) = envelope
+                .as_object_mut()
+                .filter(|o| o.contains_key("result"))
+            {
+                let diagnostic =
+                    serde_json::to_value(audit_diagnostic(count)).expect("diagnostics serialize");
+                match object.get_mut("diagnostics") {
+                    Some(Value::Array(diagnostics)) => diagnostics.push(diagnostic),
+                    _ => {
+                        object.insert("diagnostics".to_string(), json!([diagnostic]));
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Typed form of [`Redactor::redact_json`]. Fails if a path rule replaced
+    /// part of the envelope's own structure (e.g. `$.result.success`); the
+    /// envelope is left unchanged in that case and should not be shown.
+    pub fn redact_envelope(
+        &self,
+        capsule: Option<&str>,
+        envelope: &mut ResultEnvelope<Value>,
+    ) -> Result<usize, RedactionError> {
+        let mut value = serde_json::to_value(&*envelope)?;
+        let count = self.redact_json(capsule, &mut value);
+        if count > 0 {
+            *envelope = serde_json::from_value(value)?;
+        }
+        Ok(count)
+    }
+
+    /// Apply key and pattern rules to every value under `value`
+    fn redact_values(&self, capsule_rules: Option<&CompiledRules>, value: &mut Value) -> usize {
+        let sensitive_key = |key: &str| {
+            let key = normalize_key(key);
+            std::iter::once(&self.global)
+                .chain(capsule_rules)
+                .any(|rules| rules.keys.contains(&key))
+        };
+        match value {
+            Value::Object(object) => object
+                .iter_mut()
+                .map(|(key, child)| {
+                    if sensitive_key(key) {
+                        replace(child)
+                    } else {
+                        self.redact_values(capsule_rules, child)
+                    }
+                })
+                .sum(),
+            Value::Array(items) => items
+                .iter_mut()
+                .map(|item| self.redact_values(capsule_rules, item))
+                .sum(),
+            Value::String(text) => {
+                let mut redacted = text.clone();
+                for rules in std::iter::once(&self.global).chain(capsule_rules) {
+                    for pattern in &rules.patterns {
+                        if pattern.is_match(&redacted) {
+                            redacted = pattern.replace_all(&redacted, REDACTED).into_owned();
+                        }
+                    }
+                }
+                if redacted == *text {
+                    0
+                } else {
+                    *text = redacted;
+                    1
+                }
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl CompiledRules {
+    fn compile(set: &RuleSet) -> Result<Self, RedactionError> {
+        Ok(Self {
+            paths: set
+                .paths
+                .iter()
+                .map(|p| parse_path(p))
+                .collect::<Result<_, _>>()?,
+            keys: set.keys.iter().map(|k| normalize_key(k)).collect(),
+            patterns: set
+                .patterns
+                .iter()
+                .map(|p| compile_pattern(p))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex, RedactionError> {
+    Regex::new(pattern).map_err(|source| RedactionError::InvalidPattern {
+        pattern: pattern.to_string(),
+        source,
+    })
+}
+
+fn normalize_key(key: &str) -> String {
+    key.to_ascii_lowercase().replace('-', "_")
+}
+
+fn audit_diagnostic(count: usize) -> Diagnostic {
+    Diagnostic::info(format!("Redacted {} sensitive value(s)", count))
+        .with_source(REDACTION_SOURCE)
+        .with_context(json!({ "redacted_fields": count }))
+}
+
+/// Replace `value` with the marker; already-redacted values are not counted
+fn replace(value: &mut Value) -> usize {
+    if value.as_str() == Some(REDACTED) {
+        return 0;
+    }
+    *value = Value::String(REDACTED.to_string());
+    1
+}
+
+/// Parse `$.a.b`, `$.a[0]`, `$.a[*].b`, `$.a.*`, `$..b` and `$['a b']`
+fn parse_path(path: &str) -> Result<Vec<Segment>, RedactionError> {
+    let invalid = |reason: &str| RedactionError::InvalidPath {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    };
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| invalid("must start with '$'"))?;
+    let mut segments = Vec::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                let descendant = chars.peek() == Some(&'.');
+                if descendant {
+                    chars.next();
+                }
+                let mut name = String::new();
+                while let Some(&next) = chars.peek() {
+                    if next == '.' || next == '[' {
+                        break;
+                    }
+                    name.push(next);
+                    chars.next();
+                }
+                segments.push(match (descendant, name.as_str()) {
+                    (_, "") => return Err(invalid("empty key")),
+                    (true, "*") => return Err(invalid("'..*' is not supported")),
+                    (true, _) => Segment::Descendant(name),
+                    (false, "*") => Segment::Wildcard,
+                    (false, _) => Segment::Key(name),
+                });
+            }
+            '[' => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some(']') => break,
+                        Some(next) => inner.push(next),
+                        None => return Err(invalid("unclosed '['")),
+                    }
+                }
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                segments.push(if inner == "*" {
+                    Segment::Wildcard
+                } else if let Some(key) = quoted {
+                    Segment::Key(key.to_string())
+                } else {
+                    Segment::Index(
+                        inner
+                            .parse()
+                            .map_err(|_| invalid("brackets need an index, '*' or a quoted key"))?,
+                    )
+                });
+            }
+            _ => return Err(invalid("expected '.' or '['")),
+        }
+    }
+    if segments.is_empty() {
+        return Err(invalid("matches the whole envelope"));
+    }
+    Ok(segments)
+}
+
+/// Replace every value matched by `path` under `value`
+fn redact_path(value: &mut Value, path: &[Segment]) -> usize {
+    let Some((segment, rest)) = path.split_first() else {
+        return replace(value);
+    };
+    match segment {
+        Segment::Key(key) => value
+            .as_object_mut()
+            .and_then(|o| o.get_mut(key))
+            .map_or(0, |child| redact_path(child, rest)),
+        Segment::Index(index) => value
+            .as_array_mut()
+            .and_then(|a| a.get_mut(*index))
+            .map_or(0, |child| redact_path(child, rest)),
+        Segment::Wildcard => match value {
+            Value::Object(object) => object.values_mut().map(|c| redact_path(c, rest)).sum(),
+            Value::Array(items) => items.iter_mut().map(|c| redact_path(c, rest)).sum(),
+            _ => 0,
+        },
+        Segment::Descendant(key) => {
+            let mut count = 0;
+            match value {
+                Value::Object(object) => {
+                    for (name, child) in object.iter_mut() {
+                        count += if name == key {
+                            redact_path(child, rest)
+                        } else {
+                            redact_path(child, path)
+                        };
+                    }
+                }
+                Value::Array(items) => {
+                    for item in items {
+                        count += redact_path(item, path);
+                    }
+                }
+                _ => {}
+            }
+            count
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_parse_into_segments() {
+        assert_eq!(
+            parse_path("$.result.data[*]['api key'][2]..token").unwrap(),
+            vec![
+                Segment::Key("result".into()),
+                Segment::Key("data".into()),
+                Segment::Wildcard,
+                Segment::Key("api key".into()),
+                Segment::Index(2),
+                Segment::Descendant("token".into()),
+            ]
+        );
+        assert!(parse_path("result.data").is_err());
+        assert!(parse_path("$").is_err());
+        assert!(parse_path("$.a[x]").is_err());
+        assert!(parse_path("$..*").is_err());
+    }
+
+    #[test]
+    fn descendant_paths_match_at_any_depth() {
+        let mut value = json!({"a": {"pin": 1, "b": [{"pin": 2}]}, "pin": 3});
+        let count = redact_path(&mut value, &parse_path("$..pin").unwrap());
+        assert_eq!(count, 3);
+        assert_eq!(
+            value,
+            json!({"a": {"pin": REDACTED, "b": [{"pin": REDACTED}]}, "pin": REDACTED})
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Ed25519 signatures over result envelopes
+//!
+//! A signature covers the [canonical form](crate::canonical_envelope_json) of the whole
+//! envelope minus `provenance.signature` itself, and is stored there with the
+//! id of the key that made it. Consumers verify against a set of
+//! [`TrustedKeys`], which can be loaded from the same `<id>.ed25519.pub` files
+//! (unpadded base64) used for bundle provenance.
+//!
+//! Anything that rewrites the envelope after signing, redaction included,
+//! invalidates the signature: redact first, then sign.
+
+use crate::canonical::canonical_envelope_json;
+use crate::envelope::{Provenance, ResultEnvelope};
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The only algorithm currently produced and accepted
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+const PUBLIC_KEY_SUFFIX: &str = ".ed25519.pub";
+
+/// Signature stored in `provenance.signature`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeSignature {
+    pub algorithm: String,
+    pub key_id: String,
+    /// Unpadded base64 of the 64-byte signature
+    pub value: String,
+}
+
+/// A private key and the id verifiers know it by
+pub struct EnvelopeSigningKey {
+    key_id: String,
+    key: SigningKey,
+}
+
+/// Public keys trusted to sign envelopes, by key id
+#[derive(Debug, Clone, Default)]
+pub struct TrustedKeys {
+    keys: BTreeMap<String, VerifyingKey>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignatureError {
+    #[error("Envelope is not signed")]
+    Unsigned,
+    #[error("Signing key '{0}' is not trusted")]
+    UnknownKey(String),
+    #[error("Unsupported signature algorithm '{0}'")]
+    UnsupportedAlgorithm(String),
+    #[error("Malformed {what}: {reason}")]
+    Malformed { what: &'static str, reason: String },
+    #[error("Signature by '{0}' does not match the envelope")]
+    Mismatch(String),
+    #[error("Failed to read public keys from {path}: {reason}")]
+    Keys { path: String, reason: String },
+    #[error("Failed to serialize envelope: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+fn malformed(what: &'static str, reason: impl ToString) -> SignatureError {
+    SignatureError::Malformed {
+        what,
+        reason: reason.to_string(),
+    }
+}
+
+fn decode_b64(text: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    let trimmed = text.trim();
+    general_purpose::STANDARD_NO_PAD
+        .decode(trimmed)
+        .or_else(|_| general_purpose::STANDARD.decode(trimmed))
+}
+
+impl EnvelopeSigningKey {
+    pub fn new(key_id: impl Into<String>, key: SigningKey) -> Self {
+        Self {
+            key_id: key_id.into(),
+            key,
+        }
+    }
+
+    /// From the base64 32-byte seed (the 64-byte seed+public key form is also accepted)
+    pub fn from_seed_b64(key_id: impl Into<String>, seed: &str) -> Result<Self, SignatureError> {
+        let bytes = decode_b64(seed).map_err(|e| malformed("signing key", e))?;
+        let seed: [u8; 32] = match bytes.len() {
+            32 | 64 => bytes[..32].try_into().expect("length checked"),
+            n => return Err(malformed("signing key", format!("{} bytes", n))),
+        };
+        Ok(Self::new(key_id, SigningKey::from_bytes(&seed)))
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.key.verifying_key()
+    }
+
+    /// Unpadded base64 public key, the `<id>.ed25519.pub` file format
+    pub fn public_key_b64(&self) -> String {
+        general_purpose::STANDARD_NO_PAD.encode(self.verifying_key().as_bytes())
+    }
+}
+
+impl TrustedKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key_id: impl Into<String>, key: VerifyingKey) {
+        self.keys.insert(key_id.into(), key);
+    }
+
+    pub fn with_key(mut self, key_id: impl Into<String>, key: VerifyingKey) -> Self {
+        self.insert(key_id, key);
+        self
+    }
+
+    /// Add a key given as base64 (padded or not)
+    pub fn insert_b64(
+        &mut self,
+        key_id: impl Into<String>,
+        public_key: &str,
+    ) -> Result<(), SignatureError> {
+        let bytes = decode_b64(public_key).map_err(|e| malformed("public key", e))?;
+        let bytes: [u8; 32] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| malformed("public key", format!("{} bytes", bytes.len())))?;
+        let key = VerifyingKey::from_bytes(&bytes).map_err(|e| malformed("public key", e))?;
+        self.insert(key_id, key);
+        Ok(())
+    }
+
+    /// Every `<id>.ed25519.pub` file in `dir`
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, SignatureError> {
+        let dir = dir.as_ref();
+        let keys_error = |reason: String| SignatureError::Keys {
+            path: dir.display().to_string(),
+            reason,
+        };
+        let mut trusted = Self::new();
+        for entry in std::fs::read_dir(dir).map_err(|e| keys_error(e.to_string()))? {
+            let path = entry.map_err(|e| keys_error(e.to_string()))?.path();
+            let Some(key_id) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(PUBLIC_KEY_SUFFIX))
+            else {
+                continue;
+            };
+            let text = std::fs::read_to_string(&path).map_err(|e| keys_error(e.to_string()))?;
+            trusted
+                .insert_b64(key_id, &text)
+                .map_err(|e| keys_error(format!("{}: {}", path.display(), e)))?;
+        }
+        Ok(trusted)
+    }
+
+    pub fn get(&self, key_id: &str) -> Option<&VerifyingKey> {
+        self.keys.get(key_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<T> ResultEnvelope<T>
+where
+    T: Serialize,
+{
+    /// Sign the envelope, replacing any earlier signature. Adds provenance if
+    /// the envelope has none.
+    pub fn sign(&mut self, key: &EnvelopeSigningKey) -> Result<(), SignatureError> {
+        self.provenance
+            .get_or_insert_with(Provenance::default)
+            .signature = None;
+        let signature = key
+            .key
+            .sign(&canonical_envelope_json(&serde_json::to_value(&*self)?));
+        self.provenance
+            .get_or_insert_with(Provenance::default)
+            .signature = Some(EnvelopeSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            key_id: key.key_id.clone(),
+            value: general_purpose::STANDARD_NO_PAD.encode(signature.to_bytes()),
+        });
+        Ok(())
+    }
+
+    /// Check the signature against `keys` and return the id of the key that made it
+    pub fn verify(&self, keys: &TrustedKeys) -> Result<String, SignatureError> {
+        verify_json(&serde_json::to_value(self)?, keys)
+    }
+}
+
+/// [`ResultEnvelope::verify`] for an envelope held as JSON, e.g. read from an event
+pub fn verify_json(envelope: &Value, keys: &TrustedKeys) -> Result<String, SignatureError> {
+    let signature = envelope
+        .pointer("/provenance/signature")
+        .ok_or(SignatureError::Unsigned)?;
+    let signature: EnvelopeSignature =
+        serde_json::from_value(signature.clone()).map_err(|e| malformed("signature", e))?;
+    if signature.algorithm != SIGNATURE_ALGORITHM {
+        return Err(SignatureError::UnsupportedAlgorithm(signature.algorithm));
+    }
+    let key = keys
+        .get(&signature.key_id)
+        .ok_or_else(|| SignatureError::UnknownKey(signature.key_id.clone()))?;
+    let bytes = decode_b64(&signature.value).map_err(|e| malformed("signature", e))?;
+    let value = Signature::from_slice(&bytes).map_err(|e| malformed("signature", e))?;
+    key.verify_strict(
+        &canonical_envelope_json(&unsigned(envelope.clone())),
+        &value,
+    )
+    .map_err(|_| SignatureError::Mismatch(signature.key_id.clone()))?;
+    Ok(signature.key_id)
+}
+
+/// The envelope without `provenance.signature`, i.e. what the signature covers
+fn unsigned(mut envelope: Value) -> Value {
+    if let Some(provenance) = envelope
+        .get_mut("provenance")
+        .and_then(|p| p.as_object_mut())
+    {
+        provenance.remove("signature");
+    }
+    envelope
+}
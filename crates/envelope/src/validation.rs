@@ -1,25 +1,46 @@
+//! Schema validation for result envelopes and other contract documents
+//!
+//! The draft is taken from the schema's `$schema` (draft 7 and 2020-12 are
+//! supported; anything else is treated as draft 7), so 2020-12 keywords such
+//! as `$defs`, `prefixItems`, `dependentRequired` and
+//! `unevaluatedProperties` are enforced rather than ignored. `format` is
+//! always asserted, using the [`FormatRegistry`] on top of the draft's own
+//! formats, and keywords from registered [`Vocabulary`] extensions are
+//! validated alongside the standard ones.
+
 use crate::envelope::ResultEnvelope;
-use anyhow::{anyhow, Result};
-use jsonschema::{Draft, JSONSchema};
+use crate::formats::{FormatRegistry, KeywordCheck, Vocabulary};
+use anyhow::{anyhow, bail, Result};
+use jsonschema::paths::{JSONPointer, JsonPointerNode};
+use jsonschema::{Draft, ErrorIterator, JSONSchema, Keyword, ValidationError};
 use serde_json::Value;
 
 const RESULT_ENVELOPE_SCHEMA: &str = include_str!("../../../contracts/envelopes/result.json");
 
+const DRAFT_2020_12_URI: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Vocabularies defined by the JSON Schema specification itself
+const STANDARD_VOCABULARY_PREFIX: &str = "https://json-schema.org/draft/2020-12/vocab/";
+
 pub struct EnvelopeValidator {
     schema: JSONSchema,
 }
 
+/// Options for compiling an [`EnvelopeValidator`]
+#[derive(Clone, Default)]
+pub struct ValidatorBuilder {
+    formats: FormatRegistry,
+    vocabularies: Vec<Vocabulary>,
+}
+
 impl EnvelopeValidator {
+    /// Validator for the result envelope schema with the built-in formats
     pub fn new() -> Result<Self> {
-        let schema_value: Value = serde_json::from_str(RESULT_ENVELOPE_SCHEMA)
-            .map_err(|e| anyhow!("Failed to parse envelope schema: {}", e))?;
-
-        let schema = JSONSchema::options()
-            .with_draft(Draft::Draft7)
-            .compile(&schema_value)
-            .map_err(|e| anyhow!("Failed to compile envelope schema: {}", e))?;
+        Self::builder().build()
+    }
 
-        Ok(Self { schema })
+    pub fn builder() -> ValidatorBuilder {
+        ValidatorBuilder::default()
     }
 
     pub fn validate<T>(&self, envelope: &ResultEnvelope<T>) -> Result<()>
@@ -63,6 +84,122 @@ impl EnvelopeValidator {
     }
 }
 
+impl ValidatorBuilder {
+    /// Replace the format registry, e.g. with [`FormatRegistry::empty`]
+    pub fn formats(mut self, formats: FormatRegistry) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    pub fn with_format<F>(mut self, name: impl Into<String>, check: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.formats.register(name, check);
+        self
+    }
+
+    pub fn with_vocabulary(mut self, vocabulary: Vocabulary) -> Self {
+        self.vocabularies.push(vocabulary);
+        self
+    }
+
+    /// Compile the result envelope schema
+    pub fn build(self) -> Result<EnvelopeValidator> {
+        let schema: Value = serde_json::from_str(RESULT_ENVELOPE_SCHEMA)
+            .map_err(|e| anyhow!("Failed to parse envelope schema: {}", e))?;
+        self.build_for(&schema)
+    }
+
+    /// Compile any contract schema
+    pub fn build_for(self, schema: &Value) -> Result<EnvelopeValidator> {
+        self.check_vocabularies(schema)?;
+
+        let mut options = JSONSchema::options();
+        options
+            .with_draft(draft_of(schema))
+            .should_validate_formats(true);
+        for (name, check) in self.formats.iter() {
+            let check = check.clone();
+            options.with_format(name, move |value: &str| check(value));
+        }
+        for vocabulary in &self.vocabularies {
+            for (name, check) in vocabulary.keywords() {
+                let check = check.clone();
+                let keyword = name.to_string();
+                // The factory's error type is fixed by jsonschema's `with_keyword`
+                #[allow(clippy::result_large_err)]
+                options.with_keyword(name, move |_parent, value, schema_path| {
+                    Ok(Box::new(CustomKeyword {
+                        keyword: keyword.clone(),
+                        value: value.clone(),
+                        check: check.clone(),
+                        schema_path,
+                    }) as Box<dyn Keyword>)
+                });
+            }
+        }
+
+        let schema = options
+            .compile(schema)
+            .map_err(|e| anyhow!("Failed to compile schema: {}", e))?;
+        Ok(EnvelopeValidator { schema })
+    }
+
+    /// Reject schemas that require vocabularies we cannot enforce
+    fn check_vocabularies(&self, schema: &Value) -> Result<()> {
+        let Some(required) = schema.get("$vocabulary").and_then(Value::as_object) else {
+            return Ok(());
+        };
+        for (uri, is_required) in required {
+            let known = uri.starts_with(STANDARD_VOCABULARY_PREFIX)
+                || self.vocabularies.iter().any(|v| v.uri() == uri);
+            if is_required.as_bool() == Some(true) && !known {
+                bail!("Schema requires unknown vocabulary '{}'", uri);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The draft a schema declares in `$schema`
+fn draft_of(schema: &Value) -> Draft {
+    match schema.get("$schema").and_then(Value::as_str) {
+        Some(uri) if uri.trim_end_matches('#') == DRAFT_2020_12_URI => Draft::Draft202012,
+        _ => Draft::Draft7,
+    }
+}
+
+/// A vocabulary keyword as the schema compiler sees it
+struct CustomKeyword {
+    keyword: String,
+    value: Value,
+    check: KeywordCheck,
+    schema_path: JSONPointer,
+}
+
+impl Keyword for CustomKeyword {
+    fn validate<'instance>(
+        &self,
+        instance: &'instance Value,
+        instance_path: &JsonPointerNode,
+    ) -> ErrorIterator<'instance> {
+        match (self.check)(&self.value, instance) {
+            Ok(()) => Box::new(std::iter::empty()),
+            Err(reason) => Box::new(std::iter::once(ValidationError::custom(
+                self.schema_path.clone(),
+                instance_path.into(),
+                instance,
+                format!("{}: {}", self.keyword, reason),
+            ))),
+        }
+    }
+
+    fn is_valid(&self, instance: &Value) -> bool {
+        (self.check)(&self.value, instance).is_ok()
+    }
+}
+
 impl Default for EnvelopeValidator {
     fn default() -> Self {
         Self::new().expect("Failed to create default envelope validator")
@@ -20,6 +20,18 @@ struct GenericResult<T> {
     timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, AsEnvelope)]
+#[envelope(source = "my-capsule", version)]
+struct SourcedResult {
+    ok: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, AsEnvelope)]
+#[envelope(source = "my-capsule", version = "2.1.0", instance = "worker-1")]
+struct PinnedResult {
+    ok: bool,
+}
+
 #[test]
 fn given_simple_struct_when_using_derive_macro_then_creates_envelope() {
     let result = SimpleResult {
@@ -158,3 +170,28 @@ fn given_multiple_derived_types_when_creating_envelopes_then_all_validate() {
     assert!(complex_envelope.validate().is_ok());
     assert!(generic_envelope.validate().is_ok());
 }
+
+#[test]
+fn given_source_attribute_when_using_derive_macro_then_sets_provenance_source() {
+    let envelope = SourcedResult { ok: true }.into_envelope();
+
+    let source = envelope
+        .provenance
+        .as_ref()
+        .and_then(|p| p.source.as_ref())
+        .expect("source info");
+    assert_eq!(source.system, "my-capsule");
+    assert_eq!(source.version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+    assert!(source.instance.is_none());
+    assert!(envelope.validate().is_ok());
+}
+
+#[test]
+fn given_explicit_version_and_instance_when_using_derive_macro_then_uses_them() {
+    let envelope = PinnedResult { ok: true }.into_envelope();
+
+    let source = envelope.provenance.unwrap().source.unwrap();
+    assert_eq!(source.system, "my-capsule");
+    assert_eq!(source.version.as_deref(), Some("2.1.0"));
+    assert_eq!(source.instance.as_deref(), Some("worker-1"));
+}
@@ -0,0 +1,87 @@
+use envelope::*;
+use std::fmt;
+
+#[derive(Debug, thiserror::Error, AsErrorEnvelope)]
+#[envelope(source = "fetch-capsule", version = "0.3.0")]
+enum FetchError {
+    #[error("manifest {0} not found")]
+    #[envelope(category = "user")]
+    NotFound(String),
+    #[error("registry unavailable")]
+    #[envelope(code = "REGISTRY_DOWN", category = "infrastructure")]
+    RegistryUnavailable,
+    #[error("gave up after {attempts} attempts")]
+    #[envelope(category = "timeout", retryable = false)]
+    Exhausted { attempts: u32 },
+    #[error("HTTP request failed")]
+    HTTPFailure,
+}
+
+#[derive(Debug, AsErrorEnvelope)]
+#[envelope(code = "QUOTA", category = "config", retryable)]
+struct QuotaExceeded;
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tenant quota exceeded")
+    }
+}
+
+#[test]
+fn given_error_variants_when_deriving_then_codes_default_to_variant_names() {
+    let info = FetchError::NotFound("app.yaml".into()).error_info();
+    assert_eq!(info.message, "manifest app.yaml not found");
+    assert_eq!(info.code.as_deref(), Some("NOT_FOUND"));
+    assert_eq!(info.category, Some(ErrorCategory::User));
+    assert!(!info.retryable);
+
+    let info = FetchError::HTTPFailure.error_info();
+    assert_eq!(info.code.as_deref(), Some("HTTP_FAILURE"));
+    assert_eq!(info.category, None);
+}
+
+#[test]
+fn given_variant_attributes_when_deriving_then_code_category_and_retryable_apply() {
+    let info = FetchError::RegistryUnavailable.error_info();
+    assert_eq!(info.code.as_deref(), Some("REGISTRY_DOWN"));
+    assert_eq!(info.category, Some(ErrorCategory::Infrastructure));
+    assert!(info.retryable);
+
+    // An explicit `retryable` overrides the category's default
+    let info = FetchError::Exhausted { attempts: 3 }.error_info();
+    assert_eq!(info.message, "gave up after 3 attempts");
+    assert_eq!(info.category, Some(ErrorCategory::Timeout));
+    assert!(!info.retryable);
+}
+
+#[test]
+fn given_error_when_converting_to_envelope_then_carries_error_and_source() {
+    let envelope: ResultEnvelope<serde_json::Value> =
+        FetchError::RegistryUnavailable.into_error_envelope();
+
+    assert!(envelope.result.is_error());
+    assert!(envelope.result.is_retryable());
+    let source = envelope
+        .provenance
+        .as_ref()
+        .unwrap()
+        .source
+        .as_ref()
+        .unwrap();
+    assert_eq!(source.system, "fetch-capsule");
+    assert_eq!(source.version.as_deref(), Some("0.3.0"));
+    assert!(envelope.validate().is_ok());
+}
+
+#[test]
+fn given_error_struct_when_deriving_then_type_attributes_describe_the_error() {
+    let info = QuotaExceeded.error_info();
+    assert_eq!(info.message, "tenant quota exceeded");
+    assert_eq!(info.code.as_deref(), Some("QUOTA"));
+    assert_eq!(info.category, Some(ErrorCategory::Config));
+    assert!(info.retryable);
+
+    let envelope: ResultEnvelope<()> = QuotaExceeded.into_error_envelope();
+    assert!(envelope.provenance.is_none());
+    assert!(envelope.validate().is_ok());
+}
@@ -0,0 +1,81 @@
+use envelope::*;
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn given_category_when_building_error_then_retryable_follows_category() {
+    let user = ResultEnvelope::<()>::builder()
+        .user_error("Missing field 'name'")
+        .build()
+        .unwrap();
+    let timeout = ResultEnvelope::<()>::builder()
+        .timeout_error("Step exceeded 30s")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        user.result.error_info().unwrap().category,
+        Some(ErrorCategory::User)
+    );
+    assert!(!user.result.is_retryable());
+    assert_eq!(
+        timeout.result.error_info().unwrap().category,
+        Some(ErrorCategory::Timeout)
+    );
+    assert!(timeout.result.is_retryable());
+}
+
+#[test]
+fn given_retry_after_when_serializing_then_fields_are_snake_case_and_valid() {
+    let envelope = ResultEnvelope::<()>::builder()
+        .config_error("Registry credentials missing")
+        .retry_after(Duration::from_millis(1500))
+        .build()
+        .unwrap();
+
+    let value = serde_json::to_value(&envelope).unwrap();
+    assert_eq!(
+        value["result"]["error"],
+        json!({
+            "message": "Registry credentials missing",
+            "category": "config",
+            "retryable": true,
+            "retry_after_ms": 1500
+        })
+    );
+    assert!(envelope.validate().is_ok());
+}
+
+#[test]
+fn given_legacy_error_when_deserializing_then_not_retryable() {
+    let result: OperationResult<()> = serde_json::from_value(json!({
+        "success": false,
+        "error": {"message": "boom", "code": "E1"}
+    }))
+    .unwrap();
+
+    let error = result.error_info().unwrap();
+    assert_eq!(error.category, None);
+    assert!(!result.is_retryable());
+    assert_eq!(error.retry_after(), None);
+    assert!(!serde_json::to_value(&result).unwrap()["error"]
+        .as_object()
+        .unwrap()
+        .contains_key("retryable"));
+}
+
+#[test]
+fn given_unknown_category_when_validating_then_fails() {
+    let envelope: ResultEnvelope<()> = serde_json::from_value(json!({
+        "result": {"success": false, "error": {"message": "boom"}}
+    }))
+    .unwrap();
+    let mut value = serde_json::to_value(&envelope).unwrap();
+    value["result"]["error"]["category"] = json!("cosmic-rays");
+
+    assert!(serde_json::from_value::<ResultEnvelope<()>>(value.clone()).is_err());
+    assert!(EnvelopeValidator::new()
+        .unwrap()
+        .validate_json(&value)
+        .is_err());
+}
@@ -1,6 +1,7 @@
 use envelope::*;
 use serde_json::Value;
 use std::path::Path;
+use std::time::Duration;
 
 const FIXTURES_DIR: &str = "../../contracts/fixtures/envelopes";
 
@@ -84,6 +85,10 @@ fn given_error_fixture_when_deserializing_then_matches_schema() {
         serde_json::from_str(&fixture_content).expect("Should deserialize error fixture");
 
     assert!(envelope.result.is_error());
+    let error = envelope.result.error_info().unwrap();
+    assert_eq!(error.category, Some(ErrorCategory::Infrastructure));
+    assert!(envelope.result.is_retryable());
+    assert_eq!(error.retry_after(), Some(Duration::from_secs(30)));
 
     // Validate against schema
     assert!(envelope.validate().is_ok());
@@ -0,0 +1,137 @@
+use envelope::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+fn counters(pairs: &[(&str, i64)]) -> Metrics {
+    Metrics {
+        duration: None,
+        resources: None,
+        counters: pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        custom: None,
+    }
+}
+
+#[test]
+fn given_out_of_order_fragments_when_assembling_then_merges_in_sequence() {
+    let fragments = vec![
+        PartialEnvelope::new(2)
+            .add_diagnostic(Diagnostic::info("Linking"))
+            .metrics(counters(&[("modules", 300)]))
+            .result(OperationResult::success(json!({ "artifact": "app.tar" }))),
+        PartialEnvelope::new(0)
+            .add_diagnostic(Diagnostic::info("Compiling"))
+            .metrics(counters(&[("modules", 120), ("warnings", 2)])),
+        PartialEnvelope::new(1)
+            .add_diagnostic(Diagnostic::warning("Slow mirror"))
+            .add_suggestion(Suggestion::optimization("Cache dependencies").build()),
+    ];
+
+    let envelope = ResultEnvelope::assemble(fragments).expect("Should assemble");
+
+    assert!(envelope.result.is_success());
+    let messages: Vec<&str> = envelope
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(messages, vec!["Compiling", "Slow mirror", "Linking"]);
+    assert_eq!(envelope.suggestions.len(), 1);
+    let metrics = envelope.metrics.expect("Metrics carried over");
+    assert_eq!(metrics.counters.get("modules"), Some(&300));
+    assert_eq!(metrics.counters.get("warnings"), Some(&2));
+}
+
+#[test]
+fn given_duration_snapshots_when_assembling_then_latest_values_win() {
+    let phases = |pairs: &[(&str, f64)]| -> HashMap<String, f64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    };
+    let duration = |total: f64, p: HashMap<String, f64>| Metrics {
+        duration: Some(DurationMetrics {
+            total_ms: Some(total),
+            phases: p,
+        }),
+        resources: None,
+        counters: HashMap::new(),
+        custom: None,
+    };
+    let fragments = vec![
+        PartialEnvelope::new(0).metrics(duration(1000.0, phases(&[("fetch", 1000.0)]))),
+        PartialEnvelope::new(1)
+            .metrics(duration(4000.0, phases(&[("build", 3000.0)])))
+            .result(OperationResult::<Value>::success(json!(null))),
+    ];
+
+    let envelope = ResultEnvelope::assemble(fragments).expect("Should assemble");
+    let duration = envelope.metrics.unwrap().duration.unwrap();
+    assert_eq!(duration.total_ms, Some(4000.0));
+    assert_eq!(duration.phases.get("fetch"), Some(&1000.0));
+    assert_eq!(duration.phases.get("build"), Some(&3000.0));
+}
+
+#[test]
+fn given_incomplete_streams_when_assembling_then_reports_why() {
+    let done = || OperationResult::success(json!("ok"));
+
+    assert_eq!(
+        ResultEnvelope::<Value>::assemble(Vec::new()).unwrap_err(),
+        AssembleError::Empty
+    );
+    assert_eq!(
+        ResultEnvelope::assemble(vec![
+            PartialEnvelope::new(0),
+            PartialEnvelope::new(2).result(done()),
+        ])
+        .unwrap_err(),
+        AssembleError::MissingSequence(1)
+    );
+    assert_eq!(
+        ResultEnvelope::assemble(vec![
+            PartialEnvelope::new(0),
+            PartialEnvelope::new(0),
+            PartialEnvelope::new(1).result(done()),
+        ])
+        .unwrap_err(),
+        AssembleError::DuplicateSequence(0)
+    );
+    assert_eq!(
+        ResultEnvelope::<Value>::assemble(vec![PartialEnvelope::new(0)]).unwrap_err(),
+        AssembleError::MissingResult
+    );
+    assert_eq!(
+        ResultEnvelope::assemble(vec![
+            PartialEnvelope::new(0).result(done()),
+            PartialEnvelope::new(1).add_diagnostic(Diagnostic::info("late")),
+        ])
+        .unwrap_err(),
+        AssembleError::FragmentAfterResult(1)
+    );
+}
+
+#[test]
+fn given_fragment_event_when_serializing_then_matches_contract_schema() {
+    let schema: Value = serde_json::from_str(
+        &std::fs::read_to_string("../../contracts/schemas/events.envelope.fragment.v1.json")
+            .expect("Should read fragment schema"),
+    )
+    .unwrap();
+    let schema = jsonschema::JSONSchema::compile(&schema).expect("Schema compiles");
+
+    let fixture: Value = serde_json::from_str(
+        &std::fs::read_to_string("../../contracts/fixtures/events/envelope.fragment.v1.json")
+            .expect("Should read fragment fixture"),
+    )
+    .unwrap();
+    assert!(schema.is_valid(&fixture));
+    let parsed: EnvelopeFragmentEvent<Value> =
+        serde_json::from_value(fixture).expect("Fixture deserializes");
+    assert_eq!(parsed.fragment.sequence, 1);
+    assert!(!parsed.fragment.is_final());
+
+    let event = PartialEnvelope::new(3)
+        .add_diagnostic(Diagnostic::info("Finished"))
+        .result(OperationResult::success(json!({ "ok": true })))
+        .into_event("acme", "release", "run-9", "deploy");
+    assert_eq!(event.event, FRAGMENT_EVENT);
+    assert!(schema.is_valid(&serde_json::to_value(&event).unwrap()));
+}
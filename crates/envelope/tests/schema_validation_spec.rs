@@ -0,0 +1,133 @@
+use envelope::*;
+use serde_json::{json, Value};
+
+fn contract_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "required": ["image", "version"],
+        "properties": {
+            "image": {"type": "string", "format": "digest"},
+            "version": {"type": "string", "format": "semver"},
+            "scheduledAt": {"type": "string", "format": "rfc3339-with-offset"},
+            "ports": {
+                "type": "array",
+                "prefixItems": [{"type": "integer"}, {"type": "string"}]
+            }
+        },
+        "dependentRequired": {"scheduledAt": ["version"]},
+        "unevaluatedProperties": false
+    })
+}
+
+fn valid_contract() -> Value {
+    json!({
+        "image": format!("sha256:{}", "0f".repeat(32)),
+        "version": "1.4.0-rc.2",
+        "scheduledAt": "2025-06-01T09:00:00+02:00",
+        "ports": [8080, "http"]
+    })
+}
+
+#[test]
+fn given_2020_12_schema_when_validating_then_draft_keywords_are_enforced() {
+    let validator = EnvelopeValidator::builder()
+        .build_for(&contract_schema())
+        .expect("schema compiles");
+
+    assert!(validator.validate_json(&valid_contract()).is_ok());
+
+    let mut wrong_prefix = valid_contract();
+    wrong_prefix["ports"] = json!(["http", 8080]);
+    assert!(validator.validate_json(&wrong_prefix).is_err());
+
+    let mut unevaluated = valid_contract();
+    unevaluated["extra"] = json!(true);
+    assert!(validator.validate_json(&unevaluated).is_err());
+}
+
+#[test]
+fn given_builtin_formats_when_validating_then_bad_values_are_rejected() {
+    let validator = EnvelopeValidator::builder()
+        .build_for(&contract_schema())
+        .unwrap();
+
+    for (field, bad) in [
+        ("image", "sha256:abc123"),
+        ("version", "v1.4"),
+        ("scheduledAt", "2025-06-01T09:00:00Z"),
+    ] {
+        let mut contract = valid_contract();
+        contract[field] = json!(bad);
+        let error = validator.validate_json(&contract).unwrap_err();
+        assert!(error.to_string().contains(field), "{}: {}", field, error);
+    }
+}
+
+#[test]
+fn given_registered_format_when_validating_then_it_is_asserted() {
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "string",
+        "format": "tenant-id"
+    });
+    let validator = EnvelopeValidator::builder()
+        .with_format("tenant-id", |value| {
+            !value.is_empty() && value.bytes().all(|b| b.is_ascii_lowercase() || b == b'-')
+        })
+        .build_for(&schema)
+        .unwrap();
+
+    assert!(validator.validate_json(&json!("team-a")).is_ok());
+    assert!(validator.validate_json(&json!("Team A")).is_err());
+}
+
+#[test]
+fn given_vocabulary_when_schema_uses_its_keywords_then_they_are_validated() {
+    let vocabulary = Vocabulary::new("https://demon.meta/vocab/limits").with_keyword(
+        "maxBytes",
+        |limit, instance| match (limit.as_u64(), instance.as_str()) {
+            (Some(limit), Some(text)) if text.len() as u64 > limit => {
+                Err(format!("{} bytes exceeds {}", text.len(), limit))
+            }
+            _ => Ok(()),
+        },
+    );
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$vocabulary": {
+            "https://json-schema.org/draft/2020-12/vocab/core": true,
+            "https://demon.meta/vocab/limits": true
+        },
+        "type": "object",
+        "properties": {"note": {"type": "string", "maxBytes": 8}}
+    });
+
+    let validator = EnvelopeValidator::builder()
+        .with_vocabulary(vocabulary)
+        .build_for(&schema)
+        .unwrap();
+
+    assert!(validator.validate_json(&json!({"note": "short"})).is_ok());
+    let error = validator
+        .validate_json(&json!({"note": "much too long"}))
+        .unwrap_err();
+    assert!(error.to_string().contains("maxBytes"));
+}
+
+#[test]
+fn given_unknown_required_vocabulary_when_building_then_fails() {
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$vocabulary": {"https://demon.meta/vocab/limits": true},
+        "type": "object"
+    });
+
+    let error = EnvelopeValidator::builder()
+        .build_for(&schema)
+        .err()
+        .expect("unknown vocabulary is rejected");
+    assert!(error
+        .to_string()
+        .contains("https://demon.meta/vocab/limits"));
+}
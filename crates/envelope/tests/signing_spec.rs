@@ -0,0 +1,101 @@
+use ed25519_dalek::SigningKey;
+use envelope::*;
+use serde_json::json;
+
+fn runtime_key() -> EnvelopeSigningKey {
+    EnvelopeSigningKey::new("runtime-2025", SigningKey::from_bytes(&[7u8; 32]))
+}
+
+fn trusted() -> TrustedKeys {
+    TrustedKeys::new().with_key("runtime-2025", runtime_key().verifying_key())
+}
+
+fn envelope() -> ResultEnvelope<serde_json::Value> {
+    ResultEnvelope::builder()
+        .success(json!({"image": "ghcr.io/demon/app", "replicas": 3}))
+        .add_info("Deployed")
+        .with_source_info("demon-runtime", Some("0.1.0"), None::<String>)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn given_signed_envelope_when_verifying_then_signing_key_is_returned() {
+    let mut envelope = envelope();
+    envelope.sign(&runtime_key()).unwrap();
+
+    let signature = envelope
+        .provenance
+        .as_ref()
+        .and_then(|p| p.signature.as_ref())
+        .unwrap();
+    assert_eq!(signature.algorithm, SIGNATURE_ALGORITHM);
+    assert_eq!(signature.key_id, "runtime-2025");
+    assert_eq!(envelope.verify(&trusted()).unwrap(), "runtime-2025");
+    assert!(envelope.validate().is_ok());
+}
+
+#[test]
+fn given_signed_envelope_when_reserialized_then_still_verifies() {
+    let mut envelope = envelope();
+    envelope.sign(&runtime_key()).unwrap();
+
+    // Consumers see the envelope as JSON text, possibly re-serialized
+    let text = serde_json::to_string_pretty(&envelope).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+    assert_eq!(verify_json(&value, &trusted()).unwrap(), "runtime-2025");
+}
+
+#[test]
+fn given_tampered_envelope_when_verifying_then_mismatch() {
+    let mut envelope = envelope();
+    envelope.sign(&runtime_key()).unwrap();
+    let mut value = serde_json::to_value(&envelope).unwrap();
+    value["result"]["data"]["replicas"] = json!(30);
+
+    assert!(matches!(
+        verify_json(&value, &trusted()),
+        Err(SignatureError::Mismatch(key)) if key == "runtime-2025"
+    ));
+}
+
+#[test]
+fn given_unsigned_or_untrusted_envelope_when_verifying_then_rejected() {
+    let unsigned = envelope();
+    assert!(matches!(
+        unsigned.verify(&trusted()),
+        Err(SignatureError::Unsigned)
+    ));
+
+    let mut other = envelope();
+    other
+        .sign(&EnvelopeSigningKey::new(
+            "laptop",
+            SigningKey::from_bytes(&[9u8; 32]),
+        ))
+        .unwrap();
+    assert!(matches!(
+        other.verify(&trusted()),
+        Err(SignatureError::UnknownKey(key)) if key == "laptop"
+    ));
+}
+
+#[test]
+fn given_key_directory_when_loading_then_pub_files_become_trusted_keys() {
+    let dir = std::env::temp_dir().join(format!("envelope-keys-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("runtime-2025.ed25519.pub"),
+        runtime_key().public_key_b64(),
+    )
+    .unwrap();
+    std::fs::write(dir.join("README.md"), "not a key").unwrap();
+
+    let keys = TrustedKeys::from_dir(&dir).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let mut envelope = envelope();
+    envelope.sign(&runtime_key()).unwrap();
+    assert_eq!(envelope.verify(&keys).unwrap(), "runtime-2025");
+}
@@ -0,0 +1,162 @@
+use envelope::*;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+/// (artifact name, bytes)
+type Stored = Vec<(String, Vec<u8>)>;
+
+/// Keeps spilled artifacts in memory and references them as `mem://<name>`
+#[derive(Clone, Default)]
+struct MemorySink {
+    stored: Arc<Mutex<Stored>>,
+}
+
+impl ArtifactSink for MemorySink {
+    fn store(&self, name: &str, bytes: &[u8]) -> anyhow::Result<String> {
+        self.stored
+            .lock()
+            .unwrap()
+            .push((name.to_string(), bytes.to_vec()));
+        Ok(format!("mem://{}", name))
+    }
+}
+
+struct FailingSink;
+
+impl ArtifactSink for FailingSink {
+    fn store(&self, _name: &str, _bytes: &[u8]) -> anyhow::Result<String> {
+        anyhow::bail!("bucket unavailable")
+    }
+}
+
+fn size<T: serde::Serialize>(envelope: &ResultEnvelope<T>) -> usize {
+    serde_json::to_vec(envelope).unwrap().len()
+}
+
+#[test]
+fn given_small_envelope_when_building_within_budget_then_nothing_is_spilled() {
+    let sink = MemorySink::default();
+    let envelope = ResultEnvelope::builder()
+        .success("ok")
+        .add_info("done")
+        .build_within(&SizeBudget::new(4096, sink.clone()))
+        .unwrap();
+
+    assert_eq!(envelope.diagnostics.len(), 1);
+    assert!(sink.stored.lock().unwrap().is_empty());
+}
+
+#[test]
+fn given_oversized_diagnostic_when_building_within_budget_then_it_is_spilled_to_a_stub() {
+    let sink = MemorySink::default();
+    let log = "x".repeat(20_000);
+    let envelope = ResultEnvelope::builder()
+        .success("ok")
+        .add_info("started")
+        .add_diagnostic(
+            Diagnostic::error(log.clone())
+                .with_source("container-exec")
+                .with_context(json!({"stderr": log})),
+        )
+        .build_within(&SizeBudget::new(4096, sink.clone()))
+        .unwrap();
+
+    assert!(size(&envelope) <= 4096);
+    assert_eq!(envelope.diagnostics[0].message, "started");
+    let stub = &envelope.diagnostics[1];
+    assert_eq!(stub.level, DiagnosticLevel::Error);
+    assert_eq!(stub.source.as_deref(), Some("container-exec"));
+    assert!(stub.message.ends_with("… (truncated)"));
+    let context = stub.context.as_ref().unwrap();
+    assert!(context["spilled_to"]
+        .as_str()
+        .unwrap()
+        .starts_with("mem://"));
+    assert!(context["original_bytes"].as_u64().unwrap() > 40_000);
+
+    let summary = &envelope.diagnostics[2];
+    assert_eq!(summary.source.as_deref(), Some(SIZE_BUDGET_SOURCE));
+    assert_eq!(
+        summary.context.as_ref().unwrap()["artifacts"],
+        json!([context["spilled_to"]])
+    );
+
+    let stored = sink.stored.lock().unwrap();
+    assert_eq!(stored.len(), 1);
+    let original: Diagnostic = serde_json::from_slice(&stored[0].1).unwrap();
+    assert_eq!(original.message.len(), 20_000);
+    assert!(envelope.validate().is_ok());
+}
+
+#[test]
+fn given_large_custom_metrics_when_enforcing_then_they_are_spilled() {
+    let sink = MemorySink::default();
+    let mut envelope = ResultEnvelope::builder()
+        .success("ok")
+        .metrics(Metrics {
+            duration: None,
+            resources: None,
+            counters: Default::default(),
+            custom: Some(json!({"samples": vec![1.5; 5_000]})),
+        })
+        .build()
+        .unwrap();
+
+    let spilled = SizeBudget::new(2048, sink.clone())
+        .enforce(&mut envelope)
+        .unwrap();
+
+    assert_eq!(spilled, 1);
+    let custom = envelope.metrics.as_ref().unwrap().custom.as_ref().unwrap();
+    assert!(custom["spilled_to"]
+        .as_str()
+        .unwrap()
+        .ends_with("metrics-custom.json"));
+    assert!(size(&envelope) <= 2048);
+}
+
+#[test]
+fn given_oversized_result_when_building_within_budget_then_too_large() {
+    let result = ResultEnvelope::builder()
+        .success("y".repeat(10_000))
+        .build_within(&SizeBudget::new(1024, MemorySink::default()));
+
+    assert!(matches!(
+        result,
+        Err(BuildError::Budget(BudgetError::TooLarge {
+            max_bytes: 1024,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn given_failing_sink_when_spilling_then_error_names_the_artifact() {
+    let err = ResultEnvelope::builder()
+        .success("ok")
+        .add_warning("z".repeat(10_000))
+        .build_within(&SizeBudget::new(1024, FailingSink))
+        .unwrap_err();
+
+    let message = err.to_string();
+    assert!(message.contains("diagnostic-0.json"));
+    assert!(message.contains("bucket unavailable"));
+}
+
+#[test]
+fn given_directory_sink_when_spilling_then_artifact_is_written_to_disk() {
+    let dir = std::env::temp_dir().join(format!("envelope-spill-{}", uuid::Uuid::new_v4()));
+    let envelope = ResultEnvelope::builder()
+        .success("ok")
+        .add_info("w".repeat(10_000))
+        .build_within(&SizeBudget::new(2048, DirectorySink::new(&dir)))
+        .unwrap();
+
+    let path = envelope.diagnostics[0].context.as_ref().unwrap()["spilled_to"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let written = std::fs::read(&path).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(written.len() > 10_000);
+}
@@ -0,0 +1,245 @@
+//! JWT verification shared by the Schema Registry and Operate UI
+//!
+//! Tokens are HS256/384/512-signed with `JWT_SECRET`. Besides `scopes`, a
+//! token may carry `roles` (`viewer`, `operator`, `admin`) and the `tenants`
+//! those roles apply to (`*` for every tenant).
+
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use tracing::warn;
+
+/// JWT Claims structure
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Claims {
+    pub sub: String,        // Subject (user identifier)
+    pub exp: usize,         // Expiration time (Unix timestamp)
+    pub iat: Option<usize>, // Issued at (Unix timestamp)
+    #[serde(default)]
+    pub scopes: Vec<String>, // Permission scopes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>, // viewer | operator | admin
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tenants: Vec<String>, // Tenants the roles apply to; "*" for all
+}
+
+impl Claims {
+    /// Check if the claims contain a specific scope
+    pub fn has_scope(&self, required_scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == required_scope)
+    }
+
+    /// Highest recognised role in the token; unknown role names are ignored
+    pub fn role(&self) -> Option<Role> {
+        self.roles.iter().filter_map(|r| r.parse().ok()).max()
+    }
+
+    /// Whether the token's roles apply to `tenant`
+    pub fn has_tenant(&self, tenant: &str) -> bool {
+        self.tenants.iter().any(|t| t == "*" || t == tenant)
+    }
+
+    /// Whether the token holds at least `required` for `tenant`
+    pub fn grants(&self, required: Role, tenant: &str) -> bool {
+        self.has_tenant(tenant) && self.role().is_some_and(|role| role >= required)
+    }
+}
+
+/// Access levels, each including the ones below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Operator,
+    Admin,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "viewer"),
+            Role::Operator => write!(f, "operator"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            other => Err(format!("unknown role '{}'", other)),
+        }
+    }
+}
+
+/// JWT configuration loaded from environment
+#[derive(Clone)]
+pub struct JwtConfig {
+    pub secret: String,
+    pub algorithm: Algorithm,
+    /// Required `iss` claim, from `JWT_ISSUER`
+    pub issuer: Option<String>,
+    /// Required `aud` claim, from `JWT_AUDIENCE`
+    pub audience: Option<String>,
+}
+
+impl JwtConfig {
+    /// Load JWT configuration from environment variables
+    ///
+    /// # Panics
+    ///
+    /// Panics if `JWT_SECRET` environment variable is not set, as this is required
+    /// for production security.
+    pub fn from_env() -> Self {
+        Self::try_from_env()
+            .expect("JWT_SECRET environment variable must be set. Set it to a secure random string (minimum 32 characters recommended).")
+    }
+
+    /// Like [`JwtConfig::from_env`], but `None` when `JWT_SECRET` is not set
+    pub fn try_from_env() -> Option<Self> {
+        let secret = std::env::var("JWT_SECRET").ok()?;
+
+        let algorithm = std::env::var("JWT_ALGORITHM")
+            .ok()
+            .and_then(|a| match a.as_str() {
+                "HS256" => Some(Algorithm::HS256),
+                "HS384" => Some(Algorithm::HS384),
+                "HS512" => Some(Algorithm::HS512),
+                _ => None,
+            })
+            .unwrap_or(Algorithm::HS256);
+
+        Some(Self {
+            secret,
+            algorithm,
+            issuer: std::env::var("JWT_ISSUER").ok(),
+            audience: std::env::var("JWT_AUDIENCE").ok(),
+        })
+    }
+
+    /// Create from explicit secret (for testing)
+    pub fn new(secret: String, algorithm: Algorithm) -> Self {
+        Self {
+            secret,
+            algorithm,
+            issuer: None,
+            audience: None,
+        }
+    }
+}
+
+/// Verify JWT token and extract claims
+pub fn verify(token: &str, config: &JwtConfig) -> Result<Claims, String> {
+    let mut validation = Validation::new(config.algorithm);
+    validation.validate_exp = true;
+    // `set_issuer`/`set_audience` only check claims that are present, so a
+    // configured issuer or audience must also be a required claim
+    let mut required = vec!["exp"];
+    if let Some(issuer) = &config.issuer {
+        validation.set_issuer(&[issuer]);
+        required.push("iss");
+    }
+    if let Some(audience) = &config.audience {
+        validation.set_audience(&[audience]);
+        required.push("aud");
+    }
+    validation.set_required_spec_claims(&required);
+
+    let decoding_key = DecodingKey::from_secret(config.secret.as_bytes());
+
+    let token_data = decode::<Claims>(token, &decoding_key, &validation).map_err(|e| {
+        warn!("JWT validation failed: {}", e);
+        format!("Token decode error: {}", e)
+    })?;
+
+    Ok(token_data.claims)
+}
+
+/// The token from an `Authorization: Bearer <token>` header value
+pub fn bearer_token(header_value: &str) -> Option<&str> {
+    header_value.strip_prefix("Bearer ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    fn token(claims: &Claims, secret: &str) -> String {
+        encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn claims(roles: &[&str], tenants: &[&str]) -> Claims {
+        Claims {
+            sub: "test-user".to_string(),
+            exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+            roles: roles.iter().map(|s| s.to_string()).collect(),
+            tenants: tenants.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn verify_reads_roles_and_tenants() {
+        let config = JwtConfig::new("test-secret".to_string(), Algorithm::HS256);
+        let verified = verify(
+            &token(&claims(&["operator"], &["acme"]), "test-secret"),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(verified.role(), Some(Role::Operator));
+        assert!(verified.has_tenant("acme"));
+        assert!(verify(&token(&claims(&[], &[]), "other-secret"), &config).is_err());
+    }
+
+    #[test]
+    fn verify_checks_issuer_when_configured() {
+        let mut config = JwtConfig::new("test-secret".to_string(), Algorithm::HS256);
+        config.issuer = Some("https://idp.example.com".to_string());
+        assert!(verify(&token(&claims(&[], &[]), "test-secret"), &config).is_err());
+    }
+
+    #[test]
+    fn grants_requires_a_high_enough_role_for_the_tenant() {
+        let operator = claims(&["viewer", "operator"], &["acme"]);
+        assert!(operator.grants(Role::Viewer, "acme"));
+        assert!(operator.grants(Role::Operator, "acme"));
+        assert!(!operator.grants(Role::Admin, "acme"));
+        assert!(!operator.grants(Role::Viewer, "globex"));
+
+        let admin = claims(&["admin", "auditor"], &["*"]);
+        assert!(admin.grants(Role::Admin, "globex"));
+
+        let no_tenants = claims(&["admin"], &[]);
+        assert!(!no_tenants.grants(Role::Viewer, "acme"));
+    }
+
+    #[test]
+    fn claims_without_roles_or_tenants_still_deserialize() {
+        let claims: Claims =
+            serde_json::from_str(r#"{"sub":"u","exp":1,"scopes":["contracts:read"]}"#).unwrap();
+        assert!(claims.has_scope("contracts:read"));
+        assert_eq!(claims.role(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "JWT_SECRET environment variable must be set")]
+    fn given_missing_jwt_secret_when_from_env_called_then_panics() {
+        // Ensure JWT_SECRET is not set for this test
+        std::env::remove_var("JWT_SECRET");
+
+        // This should panic with a clear error message
+        let _config = JwtConfig::from_env();
+    }
+}
@@ -0,0 +1,111 @@
+//! Compatibility checks run before an App Pack is installed
+//!
+//! `requires.capabilities` is checked against the capabilities of the runtime
+//! build; `requires.contracts` must be satisfied by a contract bundled with
+//! the pack or by a version published to the Schema Registry.
+
+use super::manifest::{AppPackManifest, ContractRequirement};
+use crate::commands::registry;
+use anyhow::{bail, Context, Result};
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct CompatArgs {
+    /// Schema Registry used to resolve `requires.contracts`
+    #[arg(
+        long,
+        env = "DEMONCTL_REGISTRY_URL",
+        default_value = "http://localhost:8090"
+    )]
+    pub registry_url: String,
+
+    /// JWT token for the registry API (defaults to the saved login)
+    #[arg(long, env = "DEMONCTL_JWT")]
+    pub jwt: Option<String>,
+
+    /// Do not check required contracts against the Schema Registry
+    #[arg(long, action)]
+    pub skip_registry_check: bool,
+}
+
+/// Fail with every unmet requirement of `manifest`
+pub async fn check(manifest: &AppPackManifest, args: &CompatArgs) -> Result<()> {
+    let requirements = manifest.requirements();
+    let mut problems = Vec::new();
+
+    if let Err(err) = requirements.ensure_capabilities() {
+        problems.push(err.to_string());
+    }
+
+    let unresolved: Vec<&ContractRequirement> = requirements
+        .contracts
+        .iter()
+        .filter(|requirement| {
+            !manifest.contracts.iter().any(|bundled| {
+                bundled.id == requirement.name && requirement.accepts(&bundled.version)
+            })
+        })
+        .collect();
+
+    if !unresolved.is_empty() && args.skip_registry_check {
+        eprintln!(
+            "Skipping Schema Registry check for required contracts: {}",
+            join(&unresolved)
+        );
+    } else if !unresolved.is_empty() {
+        let published = registry::fetch_contracts(&args.registry_url, args.jwt.as_deref())
+            .await
+            .with_context(|| {
+                format!(
+                    "Cannot verify required contracts ({}). Make the Schema Registry reachable \
+                     (--registry-url / DEMONCTL_REGISTRY_URL) or pass --skip-registry-check",
+                    join(&unresolved)
+                )
+            })?;
+
+        for requirement in unresolved {
+            let versions: Vec<&str> = published
+                .iter()
+                .filter(|contract| contract.name == requirement.name)
+                .map(|contract| contract.version.as_str())
+                .collect();
+            if versions.iter().any(|version| requirement.accepts(version)) {
+                continue;
+            }
+            problems.push(if versions.is_empty() {
+                format!(
+                    "Contract '{}' is not published to the Schema Registry at {}; publish it with \
+                     `demonctl registry publish` before installing this pack",
+                    requirement.name, args.registry_url
+                )
+            } else {
+                format!(
+                    "Contract '{}' requires {} but the Schema Registry only has {}; publish a \
+                     matching version or relax requires.contracts",
+                    requirement.name,
+                    requirement.version,
+                    versions.join(", ")
+                )
+            });
+        }
+    }
+
+    if !problems.is_empty() {
+        let mut lines = vec![format!(
+            "App Pack {}@{} is not compatible with this platform:",
+            manifest.name(),
+            manifest.version()
+        )];
+        lines.extend(problems.iter().map(|problem| format!("- {}", problem)));
+        bail!(lines.join("\n"));
+    }
+    Ok(())
+}
+
+fn join(requirements: &[&ContractRequirement]) -> String {
+    requirements
+        .iter()
+        .map(|requirement| requirement.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
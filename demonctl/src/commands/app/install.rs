@@ -1,4 +1,6 @@
+use super::compat::{self, CompatArgs};
 use super::manifest::{self, CosignSettings};
+use super::oci::{OciClient, OciReference};
 use super::registry::Registry;
 use super::{ensure_relative_path, packs_dir, registry_path, MANIFEST_BASENAMES};
 use anyhow::{anyhow, bail, ensure, Context, Result};
@@ -13,16 +15,25 @@ use std::path::{Path, PathBuf};
 
 #[derive(Args, Debug)]
 pub struct InstallArgs {
-    /// Path or URI to the App Pack bundle (directory or manifest file)
+    /// Path to the App Pack bundle (directory or manifest file), or a
+    /// digest-pinned `oci://registry/repo@sha256:...` reference
     #[arg(value_name = "PACK")]
     pub pack: String,
     /// Replace an existing installation of the same name@version
     #[arg(long, action)]
     pub overwrite: bool,
+    #[command(flatten)]
+    pub compat: CompatArgs,
 }
 
-pub fn run(args: InstallArgs) -> Result<()> {
-    let resolved = resolve_pack_source(&args.pack)?;
+pub async fn run(args: InstallArgs) -> Result<()> {
+    // `_pulled` keeps a pulled OCI pack on disk until it is copied into the store
+    let (_pulled, resolved) = if args.pack.starts_with("oci://") {
+        let (dir, resolved) = pull_oci_pack(&args.pack).await?;
+        (Some(dir), resolved)
+    } else {
+        (None, resolve_pack_source(&args.pack)?)
+    };
     let manifest_raw = fs::read_to_string(&resolved.manifest_path).with_context(|| {
         format!(
             "Failed to read manifest '{}'",
@@ -36,6 +47,8 @@ pub fn run(args: InstallArgs) -> Result<()> {
         verify_cosign_signature(&resolved, manifest.name(), &manifest_raw, settings)?;
     }
 
+    compat::check(&manifest, &args.compat).await?;
+
     let packs_root = packs_dir()?;
     let install_root = packs_root.join(manifest.name()).join(manifest.version());
 
@@ -349,10 +362,36 @@ fn extract_bundle_signature(json: &JsonValue) -> Result<(String, Option<String>,
     Ok((signature_b64.trim().to_string(), hash_algorithm, hash_value))
 }
 
+/// Pull a digest-pinned pack into a temporary directory
+async fn pull_oci_pack(input: &str) -> Result<(tempfile::TempDir, ResolvedPack)> {
+    let reference = OciReference::parse(input)?;
+    let dir = tempfile::tempdir().context("Failed to create directory for OCI pull")?;
+
+    let mut client = OciClient::new(&reference.registry)?;
+    let config = client
+        .pull(&reference, dir.path())
+        .await
+        .with_context(|| format!("Failed to pull App Pack from {}", reference))?;
+
+    let resolved = resolve_from_directory(dir.path().to_path_buf(), reference.to_string())?;
+    let manifest_raw = fs::read_to_string(&resolved.manifest_path)?;
+    let manifest = manifest::parse_manifest(&manifest_raw)?;
+    ensure!(
+        manifest.name() == config.name && manifest.version() == config.version,
+        "{} declares {}@{} but contains {}@{}",
+        reference,
+        config.name,
+        config.version,
+        manifest.name(),
+        manifest.version()
+    );
+
+    Ok((dir, resolved))
+}
+
 fn resolve_pack_source(input: &str) -> Result<ResolvedPack> {
-    if input.starts_with("http://") || input.starts_with("https://") || input.starts_with("oci://")
-    {
-        bail!("Remote App Pack URIs are not yet supported");
+    if input.starts_with("http://") || input.starts_with("https://") {
+        bail!("Remote App Pack URIs are not yet supported; use an oci:// reference");
     }
 
     let path = Path::new(input);
@@ -1,20 +1,15 @@
 #![allow(dead_code)]
 
-use anyhow::{anyhow, bail, ensure, Context, Result};
-use jsonschema::JSONSchema;
-use once_cell::sync::Lazy;
+use anyhow::{bail, ensure, Context, Result};
+use runtime::app_pack::{self, ManifestVersion};
 use serde::Deserialize;
-use serde_json::Value as JsonValue;
 use serde_yaml::Value as YamlValue;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
-static APP_PACK_SCHEMA: Lazy<JSONSchema> = Lazy::new(|| {
-    let schema_str = include_str!("../../../../contracts/schemas/app-pack.v1.schema.json");
-    let schema_json: JsonValue = serde_json::from_str(schema_str)
-        .expect("contracts/schemas/app-pack.v1.schema.json must be valid JSON");
-    JSONSchema::compile(&schema_json).expect("app pack schema must compile")
-});
+pub use runtime::app_pack::{
+    CapsuleNetwork, CapsuleResources, CapsuleSecret, ContractRequirement, PackRequirements, UiCard,
+};
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -42,16 +37,32 @@ impl AppPackManifest {
         &self.metadata.version
     }
 
+    pub fn manifest_version(&self) -> Option<ManifestVersion> {
+        ManifestVersion::from_api_version(&self.api_version)
+    }
+
     pub fn requires_schema_range(&self) -> &str {
+        let default = match self.manifest_version() {
+            Some(ManifestVersion::V2) => ">=2.0.0 <3.0.0",
+            _ => ">=1.0.0 <2.0.0",
+        };
         self.requires
             .as_ref()
             .and_then(|r| r.app_pack_schema.as_deref())
-            .unwrap_or(">=1.0.0 <2.0.0")
+            .unwrap_or(default)
+    }
+
+    /// Contract and capability dependencies; empty when none are declared
+    pub fn requirements(&self) -> PackRequirements {
+        self.requires
+            .as_ref()
+            .map(Requires::requirements)
+            .unwrap_or_default()
     }
 
     pub fn validate_semantics(&self) -> Result<()> {
         ensure!(
-            self.api_version == "demon.io/v1",
+            self.manifest_version().is_some(),
             "Unsupported apiVersion: {}",
             self.api_version
         );
@@ -81,6 +92,24 @@ impl AppPackManifest {
             if !capsule_names.insert(capsule.name.as_str()) {
                 bail!("Duplicate capsule name '{}'", capsule.name);
             }
+
+            let mut secret_envs = HashSet::new();
+            for secret in &capsule.secrets {
+                if !secret_envs.insert(secret.env.as_str()) {
+                    bail!(
+                        "Capsule '{}' declares secret env '{}' more than once",
+                        capsule.name,
+                        secret.env
+                    );
+                }
+                if capsule.env.contains_key(&secret.env) {
+                    bail!(
+                        "Capsule '{}' sets env '{}' both as a value and as a secret",
+                        capsule.name,
+                        secret.env
+                    );
+                }
+            }
         }
 
         let known_capsules = capsule_names.clone();
@@ -109,12 +138,12 @@ impl AppPackManifest {
         if let Some(ui) = &self.ui {
             for card in &ui.cards {
                 ensure!(
-                    !card.matching.rituals.is_empty(),
+                    !card.match_rules.rituals.is_empty(),
                     "UI card '{}' must reference at least one ritual",
                     card.id
                 );
 
-                for ritual in &card.matching.rituals {
+                for ritual in &card.match_rules.rituals {
                     if !ritual_names.contains(ritual.as_str()) {
                         bail!(
                             "UI card '{}' references unknown ritual '{}'",
@@ -126,6 +155,12 @@ impl AppPackManifest {
             }
         }
 
+        for requirement in &self.requirements().contracts {
+            requirement.version_req().with_context(|| {
+                format!("requires.contracts entry '{}' is invalid", requirement.name)
+            })?;
+        }
+
         if let Some(signing) = &self.signing {
             if let Some(cosign) = &signing.cosign {
                 match cosign {
@@ -309,6 +344,19 @@ pub struct Requires {
     pub app_pack_schema: Option<String>,
     #[serde(default)]
     pub platform_apis: Option<PlatformApis>,
+    #[serde(default)]
+    pub contracts: Vec<ContractRequirement>,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+impl Requires {
+    pub fn requirements(&self) -> PackRequirements {
+        PackRequirements {
+            contracts: self.contracts.clone(),
+            capabilities: self.capabilities.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -343,6 +391,12 @@ pub struct Capsule {
     #[serde(default)]
     pub working_dir: Option<String>,
     pub outputs: CapsuleOutputs,
+    #[serde(default)]
+    pub resources: Option<CapsuleResources>,
+    #[serde(default)]
+    pub secrets: Vec<CapsuleSecret>,
+    #[serde(default)]
+    pub network: Option<CapsuleNetwork>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -383,56 +437,14 @@ pub struct Ui {
     pub cards: Vec<UiCard>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UiCard {
-    pub id: String,
-    pub kind: String,
-    #[serde(default)]
-    pub title: Option<String>,
-    #[serde(rename = "match")]
-    pub matching: UiCardMatch,
-    #[serde(default)]
-    pub fields: Option<UiCardFields>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UiCardMatch {
-    pub rituals: Vec<String>,
-    #[serde(default)]
-    pub tags: Vec<String>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct UiCardFields {
-    #[serde(default)]
-    pub show: Vec<String>,
-    #[serde(default)]
-    pub map: BTreeMap<String, String>,
-}
-
 pub fn parse_manifest(raw: &str) -> Result<AppPackManifest> {
     let yaml_value: YamlValue =
         serde_yaml::from_str(raw).context("Failed to parse App Pack manifest YAML")?;
     let json_value = serde_json::to_value(&yaml_value)
         .context("Failed to convert manifest YAML to JSON for validation")?;
-    validate_against_schema(&json_value)?;
+    app_pack::validate_manifest(&json_value)?;
     let manifest: AppPackManifest = serde_yaml::from_value(yaml_value)
         .context("Failed to deserialize manifest into AppPackManifest")?;
     manifest.validate_semantics()?;
     Ok(manifest)
 }
-
-fn validate_against_schema(value: &JsonValue) -> Result<()> {
-    if let Err(errors) = APP_PACK_SCHEMA.validate(value) {
-        let mut lines = vec!["App Pack schema validation failed:".to_string()];
-        for error in errors {
-            lines.push(format!("  - {}", error));
-        }
-        let message = lines.join("\n");
-        return Err(anyhow!(message));
-    }
-    Ok(())
-}
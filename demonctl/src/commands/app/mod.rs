@@ -1,9 +1,12 @@
 pub mod install;
 pub mod list;
+pub mod push;
 pub mod uninstall;
 
 pub mod alias;
+mod compat;
 mod manifest;
+mod oci;
 mod registry;
 
 use anyhow::{bail, Result};
@@ -21,11 +24,14 @@ pub enum AppCommand {
     Uninstall(uninstall::UninstallArgs),
     /// List installed App Packs
     List(list::ListArgs),
+    /// Publish an App Pack directory to an OCI registry
+    Push(push::PushArgs),
 }
 
-pub fn handle(cmd: AppCommand) -> Result<()> {
+pub async fn handle(cmd: AppCommand) -> Result<()> {
     match cmd {
-        AppCommand::Install(args) => install::run(args),
+        AppCommand::Install(args) => install::run(args).await,
+        AppCommand::Push(args) => push::run(args).await,
         AppCommand::Uninstall(args) => uninstall::run(args),
         AppCommand::List(args) => list::run(args),
     }
@@ -0,0 +1,661 @@
+//! OCI distribution of App Packs
+//!
+//! A pack is stored as an OCI artifact: an image manifest whose single layer
+//! is the zipped pack directory and whose config blob records the pack name
+//! and version. Installs must pin the manifest digest
+//! (`oci://registry/repo@sha256:...`); the manifest and layer are checked
+//! against their digests before anything is unpacked.
+//!
+//! Registries on `localhost`/`127.0.0.1`, or any registry when
+//! `DEMON_OCI_PLAIN_HTTP=true`, are reached over plain HTTP. Credentials come
+//! from `DEMON_OCI_USERNAME`/`DEMON_OCI_PASSWORD` and are used for basic auth
+//! or to obtain a bearer token, whichever the registry asks for.
+
+use super::ensure_relative_path;
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use reqwest::header::{ACCEPT, CONTENT_TYPE, LOCATION, WWW_AUTHENTICATE};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::net::IpAddr;
+use std::path::Path;
+
+pub const ARTIFACT_TYPE: &str = "application/vnd.demon.app-pack.v1";
+pub const CONFIG_MEDIA_TYPE: &str = "application/vnd.demon.app-pack.config.v1+json";
+pub const LAYER_MEDIA_TYPE: &str = "application/vnd.demon.app-pack.layer.v1+zip";
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+
+/// `oci://registry/repository@sha256:...` or `oci://registry/repository:tag`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OciReference {
+    pub registry: String,
+    pub repository: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl OciReference {
+    pub fn parse(input: &str) -> Result<Self> {
+        let rest = input
+            .strip_prefix("oci://")
+            .ok_or_else(|| anyhow!("OCI reference '{}' must start with oci://", input))?;
+        let (registry, path) = rest
+            .split_once('/')
+            .ok_or_else(|| anyhow!("OCI reference '{}' must include a repository", input))?;
+
+        let (name, digest) = match path.split_once('@') {
+            Some((name, digest)) => {
+                ensure!(
+                    is_sha256_digest(digest),
+                    "OCI reference '{}' has an invalid digest (expected sha256:<64 hex chars>)",
+                    input
+                );
+                (name, Some(digest.to_string()))
+            }
+            None => (path, None),
+        };
+
+        // A ':' after the last '/' separates the tag
+        let (repository, tag) = match name.rsplit_once(':') {
+            Some((repo, tag)) if !tag.contains('/') => (repo, Some(tag.to_string())),
+            _ => (name, None),
+        };
+
+        ensure!(
+            !registry.is_empty() && !repository.is_empty(),
+            "OCI reference '{}' must name a registry and repository",
+            input
+        );
+        ensure!(
+            repository
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "._-/".contains(c)),
+            "OCI repository '{}' may only contain lowercase letters, digits, '.', '_', '-' and '/'",
+            repository
+        );
+        ensure!(
+            tag.is_some() || digest.is_some(),
+            "OCI reference '{}' must include a tag or digest",
+            input
+        );
+
+        Ok(Self {
+            registry: registry.to_string(),
+            repository: repository.to_string(),
+            tag,
+            digest,
+        })
+    }
+
+    /// The same repository pinned to `digest`
+    pub fn with_digest(&self, digest: &str) -> Self {
+        Self {
+            registry: self.registry.clone(),
+            repository: self.repository.clone(),
+            tag: None,
+            digest: Some(digest.to_string()),
+        }
+    }
+
+    fn manifest_reference(&self) -> &str {
+        self.digest
+            .as_deref()
+            .or(self.tag.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for OciReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "oci://{}/{}", self.registry, self.repository)?;
+        if let Some(tag) = &self.tag {
+            write!(f, ":{}", tag)?;
+        }
+        if let Some(digest) = &self.digest {
+            write!(f, "@{}", digest)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    media_type: String,
+    digest: String,
+    size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImageManifest {
+    schema_version: u32,
+    #[serde(default)]
+    media_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    artifact_type: Option<String>,
+    config: Descriptor,
+    layers: Vec<Descriptor>,
+}
+
+/// Config blob of a pack artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackConfig {
+    pub name: String,
+    pub version: String,
+    pub api_version: String,
+}
+
+/// Minimal OCI distribution client for pack artifacts
+pub struct OciClient {
+    http: Client,
+    base_url: String,
+    credentials: Option<(String, String)>,
+    authorization: Option<String>,
+}
+
+impl OciClient {
+    pub fn new(registry: &str) -> Result<Self> {
+        let plain_http = std::env::var("DEMON_OCI_PLAIN_HTTP")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        let scheme = if plain_http || is_loopback(registry) {
+            "http"
+        } else {
+            "https"
+        };
+
+        let credentials = match (
+            std::env::var("DEMON_OCI_USERNAME"),
+            std::env::var("DEMON_OCI_PASSWORD"),
+        ) {
+            (Ok(user), Ok(pass)) => Some((user, pass)),
+            _ => None,
+        };
+
+        Ok(Self {
+            http: Client::builder()
+                .user_agent(concat!("demonctl/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .context("Failed to build OCI HTTP client")?,
+            base_url: format!("{}://{}", scheme, registry),
+            credentials,
+            authorization: None,
+        })
+    }
+
+    /// Download a digest-pinned pack and unpack it into `dest`
+    pub async fn pull(&mut self, reference: &OciReference, dest: &Path) -> Result<PackConfig> {
+        let digest = reference.digest.as_deref().ok_or_else(|| {
+            anyhow!(
+                "App Packs must be installed by digest (oci://{}/{}@sha256:...)",
+                reference.registry,
+                reference.repository
+            )
+        })?;
+
+        let manifest_bytes = self
+            .get_verified(
+                &format!("/v2/{}/manifests/{}", reference.repository, digest),
+                MANIFEST_MEDIA_TYPE,
+                digest,
+            )
+            .await
+            .context("Failed to fetch App Pack manifest")?;
+        let manifest: ImageManifest = serde_json::from_slice(&manifest_bytes)
+            .context("Registry returned an invalid OCI image manifest")?;
+
+        let mut layers = manifest
+            .layers
+            .iter()
+            .filter(|l| l.media_type == LAYER_MEDIA_TYPE);
+        let layer = match (layers.next(), layers.next()) {
+            (Some(layer), None) => layer,
+            _ => bail!(
+                "{} is not an App Pack artifact (expected exactly one {} layer)",
+                reference,
+                LAYER_MEDIA_TYPE
+            ),
+        };
+
+        let config_bytes = self
+            .get_verified(
+                &format!(
+                    "/v2/{}/blobs/{}",
+                    reference.repository, manifest.config.digest
+                ),
+                &manifest.config.media_type,
+                &manifest.config.digest,
+            )
+            .await
+            .context("Failed to fetch App Pack config")?;
+        let config: PackConfig = serde_json::from_slice(&config_bytes)
+            .context("App Pack config blob is not valid JSON")?;
+
+        let archive = self
+            .get_verified(
+                &format!("/v2/{}/blobs/{}", reference.repository, layer.digest),
+                LAYER_MEDIA_TYPE,
+                &layer.digest,
+            )
+            .await
+            .context("Failed to fetch App Pack layer")?;
+        ensure!(
+            archive.len() as u64 == layer.size,
+            "App Pack layer size mismatch: expected {} bytes, got {}",
+            layer.size,
+            archive.len()
+        );
+
+        unpack(&archive, dest)?;
+        Ok(config)
+    }
+
+    /// Upload a pack and tag it; returns the manifest digest
+    pub async fn push(
+        &mut self,
+        reference: &OciReference,
+        config: &PackConfig,
+        archive: Vec<u8>,
+    ) -> Result<String> {
+        let config_bytes = serde_json::to_vec(config)?;
+        let config_desc = self
+            .upload_blob(&reference.repository, CONFIG_MEDIA_TYPE, config_bytes)
+            .await?;
+        let layer_desc = self
+            .upload_blob(&reference.repository, LAYER_MEDIA_TYPE, archive)
+            .await?;
+
+        let manifest = ImageManifest {
+            schema_version: 2,
+            media_type: Some(MANIFEST_MEDIA_TYPE.to_string()),
+            artifact_type: Some(ARTIFACT_TYPE.to_string()),
+            config: config_desc,
+            layers: vec![layer_desc],
+        };
+        let manifest_bytes = serde_json::to_vec(&manifest)?;
+        let digest = sha256_digest(&manifest_bytes);
+
+        let url = format!(
+            "{}/v2/{}/manifests/{}",
+            self.base_url,
+            reference.repository,
+            reference.manifest_reference()
+        );
+        let response = self
+            .send(|http| {
+                http.put(&url)
+                    .header(CONTENT_TYPE, MANIFEST_MEDIA_TYPE)
+                    .body(manifest_bytes.clone())
+            })
+            .await?;
+        expect_success(response, "push App Pack manifest").await?;
+
+        Ok(digest)
+    }
+
+    async fn upload_blob(
+        &mut self,
+        repository: &str,
+        media_type: &str,
+        data: Vec<u8>,
+    ) -> Result<Descriptor> {
+        let digest = sha256_digest(&data);
+        let size = data.len() as u64;
+
+        let start_url = format!("{}/v2/{}/blobs/uploads/", self.base_url, repository);
+        let response = self.send(|http| http.post(&start_url)).await?;
+        let response = expect_success(response, "start blob upload").await?;
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| anyhow!("Registry did not return an upload location"))?;
+
+        let mut upload_url = if location.starts_with("http://") || location.starts_with("https://")
+        {
+            location.to_string()
+        } else {
+            format!("{}{}", self.base_url, location)
+        };
+        upload_url.push(if upload_url.contains('?') { '&' } else { '?' });
+        upload_url.push_str(&format!("digest={}", digest));
+
+        let response = self
+            .send(|http| {
+                http.put(&upload_url)
+                    .header(CONTENT_TYPE, "application/octet-stream")
+                    .body(data.clone())
+            })
+            .await?;
+        expect_success(response, "upload blob").await?;
+
+        Ok(Descriptor {
+            media_type: media_type.to_string(),
+            digest,
+            size,
+        })
+    }
+
+    /// GET `path` and check the body hashes to `digest`
+    async fn get_verified(&mut self, path: &str, accept: &str, digest: &str) -> Result<Vec<u8>> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .send(|http| http.get(&url).header(ACCEPT, accept))
+            .await?;
+        let response = expect_success(response, &format!("fetch {}", path)).await?;
+        let body = response.bytes().await?.to_vec();
+
+        let actual = sha256_digest(&body);
+        ensure!(
+            actual == digest,
+            "Digest mismatch for {}: expected {}, got {}",
+            path,
+            digest,
+            actual
+        );
+        Ok(body)
+    }
+
+    /// Send a request, answering one auth challenge if the registry asks
+    async fn send(&mut self, build: impl Fn(&Client) -> RequestBuilder) -> Result<Response> {
+        let response = self.authorized(build(&self.http)).send().await?;
+        if response.status() != StatusCode::UNAUTHORIZED || self.authorization.is_some() {
+            return Ok(response);
+        }
+
+        let challenge = response
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        self.authorization = Some(self.authorize(&challenge).await?);
+        Ok(self.authorized(build(&self.http)).send().await?)
+    }
+
+    fn authorized(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.authorization {
+            Some(value) => request.header(reqwest::header::AUTHORIZATION, value),
+            None => request,
+        }
+    }
+
+    async fn authorize(&self, challenge: &str) -> Result<String> {
+        let (scheme, params) = challenge.split_once(' ').unwrap_or((challenge, ""));
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let (user, pass) = self.credentials.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "Registry requires credentials; set DEMON_OCI_USERNAME and DEMON_OCI_PASSWORD"
+                )
+            })?;
+            use base64::Engine as _;
+            let encoded =
+                base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, pass));
+            return Ok(format!("Basic {}", encoded));
+        }
+
+        ensure!(
+            scheme.eq_ignore_ascii_case("bearer"),
+            "Unsupported registry auth challenge '{}'",
+            challenge
+        );
+        let params = parse_challenge_params(params);
+        let realm = params
+            .iter()
+            .find(|(k, _)| k == "realm")
+            .map(|(_, v)| v.as_str())
+            .ok_or_else(|| anyhow!("Registry auth challenge has no realm"))?;
+        let query: Vec<(&str, &str)> = params
+            .iter()
+            .filter(|(k, _)| k == "service" || k == "scope")
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+
+        let mut request = self.http.get(realm).query(&query);
+        if let Some((user, pass)) = &self.credentials {
+            request = request.basic_auth(user, Some(pass));
+        }
+        let response = expect_success(request.send().await?, "obtain registry token").await?;
+
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            token: Option<String>,
+            access_token: Option<String>,
+        }
+        let body: TokenResponse = response
+            .json()
+            .await
+            .context("Registry token response is not valid JSON")?;
+        let token = body
+            .token
+            .or(body.access_token)
+            .ok_or_else(|| anyhow!("Registry token response has no token"))?;
+        Ok(format!("Bearer {}", token))
+    }
+}
+
+async fn expect_success(response: Response, action: &str) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    bail!(
+        "Failed to {}: registry returned {} {}",
+        action,
+        status,
+        body.trim()
+    )
+}
+
+/// Split `realm="...",service="...",scope="..."`
+fn parse_challenge_params(raw: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let mut rest = raw.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let key = key
+            .trim()
+            .trim_start_matches(',')
+            .trim()
+            .to_ascii_lowercase();
+        let (value, remaining) = match after.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some((value, remaining)) => (value, remaining),
+                None => (quoted, ""),
+            },
+            None => match after.split_once(',') {
+                Some((value, remaining)) => (value, remaining),
+                None => (after, ""),
+            },
+        };
+        params.push((key, value.to_string()));
+        rest = remaining.trim_start_matches(',').trim();
+    }
+    params
+}
+
+/// Whether a `host[:port]` registry is on this machine, including `[::1]`
+fn is_loopback(registry: &str) -> bool {
+    let host = match registry.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(rest),
+        None => registry.split(':').next().unwrap_or(registry),
+    };
+    host == "localhost" || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+fn is_sha256_digest(value: &str) -> bool {
+    value
+        .strip_prefix("sha256:")
+        .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+pub fn sha256_digest(data: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(data)))
+}
+
+/// Zip a pack directory. Entries are sorted and timestamps fixed so the same
+/// tree always produces the same layer digest.
+pub fn package(root: &Path) -> Result<Vec<u8>> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let base_options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(zip::DateTime::default());
+
+    for entry in walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .sort_by_file_name()
+    {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(root).unwrap();
+        if rel.as_os_str().is_empty() || rel.components().any(|c| c.as_os_str() == ".git") {
+            continue;
+        }
+        let name = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if entry.file_type().is_symlink() {
+            bail!(
+                "App Packs may not contain symlinks (found: '{}'). Please replace symlinks with regular files.",
+                rel.display()
+            );
+        } else if entry.file_type().is_dir() {
+            writer.add_directory(name, base_options)?;
+        } else {
+            let mut options = base_options;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                options = options.unix_permissions(entry.metadata()?.permissions().mode() & 0o777);
+            }
+            writer.start_file(name, options)?;
+            let contents = fs::read(entry.path())
+                .with_context(|| format!("Failed to read '{}'", entry.path().display()))?;
+            writer.write_all(&contents)?;
+        }
+    }
+
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Unpack a pack layer, refusing entries that escape `dest` or are symlinks
+pub fn unpack(archive: &[u8], dest: &Path) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(archive))
+        .context("App Pack layer is not a zip archive")?;
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let rel = file
+            .enclosed_name()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| anyhow!("App Pack layer entry '{}' escapes the pack", file.name()))?;
+        ensure_relative_path(&rel)?;
+        if file
+            .unix_mode()
+            .is_some_and(|mode| mode & 0o170000 == 0o120000)
+        {
+            bail!(
+                "App Packs may not contain symlinks (found: '{}')",
+                rel.display()
+            );
+        }
+
+        let target = dest.join(&rel);
+        if file.is_dir() {
+            fs::create_dir_all(&target)
+                .with_context(|| format!("Failed to create directory '{}'", target.display()))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory '{}'", parent.display()))?;
+        }
+        let mut contents = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut contents)?;
+        fs::write(&target, contents)
+            .with_context(|| format!("Failed to write '{}'", target.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Some(mode) = file.unix_mode() {
+                let _ = fs::set_permissions(&target, fs::Permissions::from_mode(mode & 0o777));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_digest_and_tag_references() {
+        let digest = format!("sha256:{}", "a".repeat(64));
+        let pinned = OciReference::parse(&format!("oci://ghcr.io/org/pack@{}", digest)).unwrap();
+        assert_eq!(pinned.registry, "ghcr.io");
+        assert_eq!(pinned.repository, "org/pack");
+        assert_eq!(pinned.digest.as_deref(), Some(digest.as_str()));
+
+        let tagged = OciReference::parse("oci://localhost:5000/packs/hello:1.0.0").unwrap();
+        assert_eq!(tagged.registry, "localhost:5000");
+        assert_eq!(tagged.repository, "packs/hello");
+        assert_eq!(tagged.tag.as_deref(), Some("1.0.0"));
+        assert_eq!(tagged.to_string(), "oci://localhost:5000/packs/hello:1.0.0");
+
+        assert!(OciReference::parse("oci://ghcr.io/org/pack").is_err());
+        assert!(OciReference::parse("oci://ghcr.io/org/pack@sha256:abc").is_err());
+    }
+
+    #[test]
+    fn loopback_registries_use_plain_http() {
+        assert!(is_loopback("localhost:5000"));
+        assert!(is_loopback("127.0.0.1:5000"));
+        assert!(is_loopback("[::1]:5000"));
+        assert!(!is_loopback("ghcr.io"));
+        assert!(!is_loopback("[2001:db8::1]:5000"));
+    }
+
+    #[test]
+    fn parses_bearer_challenge() {
+        let params = parse_challenge_params(
+            r#"realm="https://ghcr.io/token",service="ghcr.io",scope="repository:org/pack:pull""#,
+        );
+        assert_eq!(
+            params,
+            vec![
+                ("realm".to_string(), "https://ghcr.io/token".to_string()),
+                ("service".to_string(), "ghcr.io".to_string()),
+                ("scope".to_string(), "repository:org/pack:pull".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_round_trips_and_is_reproducible() {
+        let src = tempfile::tempdir().unwrap();
+        fs::create_dir_all(src.path().join("contracts")).unwrap();
+        fs::write(src.path().join("app-pack.yaml"), "kind: AppPack\n").unwrap();
+        fs::write(src.path().join("contracts/c.json"), "{}").unwrap();
+
+        let first = package(src.path()).unwrap();
+        let second = package(src.path()).unwrap();
+        assert_eq!(sha256_digest(&first), sha256_digest(&second));
+
+        let dest = tempfile::tempdir().unwrap();
+        unpack(&first, dest.path()).unwrap();
+        assert_eq!(
+            fs::read_to_string(dest.path().join("contracts/c.json")).unwrap(),
+            "{}"
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use super::manifest;
+use super::oci::{self, OciClient, OciReference, PackConfig};
+use super::MANIFEST_BASENAMES;
+use anyhow::{anyhow, ensure, Context, Result};
+use clap::Args;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct PushArgs {
+    /// App Pack directory containing app-pack.yaml
+    #[arg(value_name = "PACK_DIR")]
+    pub pack_dir: PathBuf,
+    /// Destination, e.g. oci://ghcr.io/org/packs/hello:1.0.0
+    #[arg(value_name = "REFERENCE")]
+    pub reference: String,
+}
+
+pub async fn run(args: PushArgs) -> Result<()> {
+    let reference = OciReference::parse(&args.reference)?;
+    ensure!(
+        reference.tag.is_some() && reference.digest.is_none(),
+        "Push to a tag (oci://registry/repo:tag); the digest is reported once pushed"
+    );
+
+    let manifest_path = MANIFEST_BASENAMES
+        .iter()
+        .map(|b| args.pack_dir.join(b))
+        .find(|p| p.exists())
+        .ok_or_else(|| {
+            anyhow!(
+                "No manifest found in '{}'. Expected one of: {}",
+                args.pack_dir.display(),
+                MANIFEST_BASENAMES.join(", ")
+            )
+        })?;
+    let manifest_raw = fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read manifest '{}'", manifest_path.display()))?;
+    let manifest = manifest::parse_manifest(&manifest_raw)?;
+
+    let config = PackConfig {
+        name: manifest.name().to_string(),
+        version: manifest.version().to_string(),
+        api_version: manifest.api_version.clone(),
+    };
+    let archive = oci::package(&args.pack_dir)?;
+
+    let mut client = OciClient::new(&reference.registry)?;
+    let digest = client
+        .push(&reference, &config, archive)
+        .await
+        .with_context(|| format!("Failed to push App Pack to {}", reference))?;
+
+    println!(
+        "Pushed App Pack {}@{} to {}",
+        config.name, config.version, reference
+    );
+    println!(
+        "Install with: demonctl app install {}",
+        reference.with_digest(&digest)
+    );
+
+    Ok(())
+}
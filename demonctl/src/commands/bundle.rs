@@ -0,0 +1,364 @@
+//! Bundle commands - author, sign and verify bootstrapper bundles
+//!
+//! A bundle's digest is the SHA-256 of its canonical JSON form after `${VAR}`
+//! interpolation, and its signature is ed25519 over the same bytes (see
+//! `bootstrapper_demonctl::provenance`). `sign` records both in the library
+//! index so `demonctl bootstrap --bundle lib://local/<name>@<version>` can
+//! check them.
+
+use crate::output::{self, OutputArgs};
+use anyhow::{bail, Context, Result};
+use bootstrapper_demonctl::{bundle, libindex, provenance};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct BundleArgs {
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    #[command(subcommand)]
+    pub cmd: BundleCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BundleCommand {
+    /// Write a new bundle YAML file
+    Create(CreateArgs),
+    /// Sign a bundle and record it in the library index
+    Sign(SignArgs),
+    /// Check a bundle's digest and signature against the library index
+    Verify(VerifyArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CreateArgs {
+    /// Bundle file to write
+    #[arg(value_name = "FILE")]
+    pub path: PathBuf,
+
+    /// NATS server URL
+    #[arg(long, default_value = "nats://127.0.0.1:4222")]
+    pub nats_url: String,
+
+    /// JetStream stream name
+    #[arg(long, default_value = "RITUAL_EVENTS")]
+    pub stream_name: String,
+
+    /// Stream subject (repeatable)
+    #[arg(long = "subject", default_value = "demon.ritual.v1.>")]
+    pub subjects: Vec<String>,
+
+    /// Stream duplicate window in seconds
+    #[arg(long, default_value_t = 120)]
+    pub duplicate_window_secs: u64,
+
+    /// Operate UI base URL
+    #[arg(long, default_value = "http://127.0.0.1:3000")]
+    pub ui_base_url: String,
+
+    /// Operate UI approver email (repeatable)
+    #[arg(long = "approver")]
+    pub approvers: Vec<String>,
+
+    /// Seed one preview run of this ritual during bootstrap
+    #[arg(long, value_name = "RITUAL_ID")]
+    pub seed_ritual: Option<String>,
+
+    /// Overwrite an existing file
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SignArgs {
+    /// Bundle file to sign
+    #[arg(value_name = "FILE")]
+    pub bundle: PathBuf,
+
+    /// ed25519 private key: PKCS#8 PEM or a base64 32-byte seed
+    #[arg(long, value_name = "KEY_FILE")]
+    pub key: PathBuf,
+
+    /// Key ID; verification reads contracts/keys/<ID>.ed25519.pub
+    #[arg(long, value_name = "ID")]
+    pub pub_key_id: String,
+
+    /// Bundle name in the index (default: file stem)
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Bundle version in the index
+    #[arg(long)]
+    pub version: String,
+
+    /// Library index to update (default: bootstrapper/library/index.json)
+    #[arg(long, value_name = "FILE")]
+    pub index: Option<PathBuf>,
+
+    /// Only print the digest and signature; leave the index and keys alone
+    #[arg(long)]
+    pub no_index: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Bundle file or lib://local/<name>@<version> URI
+    #[arg(value_name = "BUNDLE")]
+    pub target: String,
+
+    /// Library index to read (default: bootstrapper/library/index.json)
+    #[arg(long, value_name = "FILE")]
+    pub index: Option<PathBuf>,
+}
+
+/// A freshly written bundle (`BundleCreated` kind)
+#[derive(Debug, Serialize)]
+struct BundleCreated {
+    path: PathBuf,
+}
+
+/// A signed bundle (`BundleSignature` kind)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleSignature {
+    name: String,
+    version: String,
+    path: String,
+    digest: String,
+    signature: String,
+    pub_key_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<PathBuf>,
+}
+
+/// Result of checking a bundle (`BundleVerification` kind)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleVerification {
+    name: String,
+    version: String,
+    path: PathBuf,
+    digest: String,
+    /// `ok` or `failed`
+    signature: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    pub_key_id: String,
+}
+
+pub fn run(args: BundleArgs) -> Result<()> {
+    let format = args.output.format;
+    match args.cmd {
+        BundleCommand::Create(create_args) => create(create_args, format),
+        BundleCommand::Sign(sign_args) => sign(sign_args, format),
+        BundleCommand::Verify(verify_args) => verify(verify_args, format),
+    }
+}
+
+fn create(args: CreateArgs, format: output::OutputFormat) -> Result<()> {
+    if args.path.exists() && !args.force {
+        bail!(
+            "{} already exists; pass --force to overwrite",
+            args.path.display()
+        );
+    }
+
+    let runs = match &args.seed_ritual {
+        Some(ritual) => json!([{ "runId": format!("bundle-{}", ritual), "ritualId": ritual }]),
+        None => json!([]),
+    };
+    let doc = json!({
+        "nats": { "url": args.nats_url },
+        "stream": {
+            "name": args.stream_name,
+            "subjects": args.subjects,
+            "duplicateWindowSeconds": args.duplicate_window_secs,
+        },
+        "operateUi": {
+            "baseUrl": args.ui_base_url,
+            "approverAllowlist": args.approvers,
+        },
+        "seed": { "enabled": args.seed_ritual.is_some(), "runs": runs },
+    });
+
+    if let Some(parent) = args.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&args.path, serde_yaml::to_string(&doc)?)
+        .with_context(|| format!("Failed to write {}", args.path.display()))?;
+    if let Err(e) = bundle::load_bundle(&args.path) {
+        let _ = fs::remove_file(&args.path);
+        return Err(e.context("Generated bundle failed schema validation"));
+    }
+
+    let created = BundleCreated { path: args.path };
+    output::emit(format, "BundleCreated", &created, || {
+        println!("✓ Wrote bundle {}", created.path.display());
+        println!(
+            "Sign it with: demonctl bundle sign {} --key <KEY_FILE> --pub-key-id <ID> --version <VERSION>",
+            created.path.display()
+        );
+    })
+}
+
+fn sign(args: SignArgs, format: output::OutputFormat) -> Result<()> {
+    // Reject bundles the bootstrapper would refuse to load
+    bundle::load_bundle(&args.bundle)?;
+    let key = provenance::load_signing_key(&args.key)?;
+    let signed = provenance::sign_bundle(&args.bundle, &key)?;
+
+    let name = match args.name {
+        Some(name) => name,
+        None => args
+            .bundle
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .context("Could not derive a bundle name from the file name; pass --name")?
+            .to_string(),
+    };
+
+    let index = if args.no_index {
+        None
+    } else {
+        write_public_key(&args.pub_key_id, &signed.pub_key_b64)?;
+        let index = args.index.unwrap_or_else(libindex::default_index_path);
+        libindex::upsert_local(
+            &index,
+            libindex::LibraryBundle {
+                name: name.clone(),
+                version: args.version.clone(),
+                path: args.bundle.to_string_lossy().into_owned(),
+                digest: libindex::Digest {
+                    sha256: signed.digest_hex.clone(),
+                },
+                sig: libindex::Signature {
+                    ed25519: signed.sig_b64.clone(),
+                },
+                pub_key_id: args.pub_key_id.clone(),
+            },
+        )?;
+        Some(index)
+    };
+
+    let signature = BundleSignature {
+        name,
+        version: args.version,
+        path: args.bundle.to_string_lossy().into_owned(),
+        digest: signed.digest_hex,
+        signature: signed.sig_b64,
+        pub_key_id: args.pub_key_id,
+        index,
+    };
+    output::emit(format, "BundleSignature", &signature, || {
+        println!(
+            "✓ Signed {}@{} with key {}",
+            signature.name, signature.version, signature.pub_key_id
+        );
+        println!("  Digest:    {}", signature.digest);
+        println!("  Signature: {}", signature.signature);
+        match &signature.index {
+            Some(index) => println!(
+                "  Recorded in {} as lib://local/{}@{}",
+                index.display(),
+                signature.name,
+                signature.version
+            ),
+            None => println!("  Index not updated (--no-index)"),
+        }
+    })
+}
+
+/// Save the public key where verification looks for it, refusing to replace a
+/// different key under the same ID
+fn write_public_key(pub_key_id: &str, pub_key_b64: &str) -> Result<()> {
+    let path = provenance::pubkey_path(pub_key_id);
+    if path.exists() {
+        let existing = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if existing.trim().trim_end_matches('=') != pub_key_b64 {
+            bail!(
+                "{} holds a different public key; use another --pub-key-id or the matching key",
+                path.display()
+            );
+        }
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, format!("{}\n", pub_key_b64))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn verify(args: VerifyArgs, format: output::OutputFormat) -> Result<()> {
+    let index = args.index.unwrap_or_else(libindex::default_index_path);
+    let resolved = if args.target.starts_with("lib://") {
+        libindex::resolve(&args.target, &index)?
+    } else {
+        find_in_index(Path::new(&args.target), &index)?
+    };
+
+    let vr = provenance::verify_provenance(
+        &resolved.path,
+        &resolved.pub_key_id,
+        &resolved.digest_sha256,
+        &resolved.sig_ed25519,
+    )?;
+    let verification = BundleVerification {
+        name: resolved.name,
+        version: resolved.version,
+        path: resolved.path,
+        digest: vr.digest_hex,
+        signature: if vr.signature_ok { "ok" } else { "failed" },
+        reason: vr.reason,
+        pub_key_id: resolved.pub_key_id,
+    };
+
+    output::emit(format, "BundleVerification", &verification, || {
+        if vr.signature_ok {
+            println!(
+                "✓ {}@{}: signature ok (key {})",
+                verification.name, verification.version, verification.pub_key_id
+            );
+            println!("  Digest: {}", verification.digest);
+        } else {
+            eprintln!(
+                "✗ {}@{}: signature failed ({})",
+                verification.name,
+                verification.version,
+                verification.reason.as_deref().unwrap_or("unknown")
+            );
+            eprintln!("  Digest: {}", verification.digest);
+        }
+    })?;
+    if !vr.signature_ok {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// The index entry whose path points at `bundle_path`
+fn find_in_index(bundle_path: &Path, index: &Path) -> Result<libindex::ResolvedBundle> {
+    let wanted = fs::canonicalize(bundle_path)
+        .with_context(|| format!("Bundle not found: {}", bundle_path.display()))?;
+    let idx = libindex::load_index(index)?;
+    for entry in &idx.bundles {
+        let uri = format!("lib://local/{}@{}", entry.name, entry.version);
+        if let Ok(resolved) = libindex::resolve_local(&uri, index) {
+            if resolved.path == wanted {
+                return Ok(resolved);
+            }
+        }
+    }
+    bail!(
+        "{} is not listed in {}; sign it with demonctl bundle sign first",
+        bundle_path.display(),
+        index.display()
+    )
+}
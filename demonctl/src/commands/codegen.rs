@@ -0,0 +1,772 @@
+//! `demonctl contracts codegen` - typed client models for the contract schemas
+//!
+//! Every schema in `contracts/schemas` (or, with `--registry`, the latest
+//! version of every contract in the Schema Registry) plus the result envelope
+//! becomes one module of types. `--lang rust` uses the same mapping as the
+//! config codegen (`config_loader::codegen`); `--lang ts` emits interfaces and
+//! type aliases:
+//!
+//! - `string` → `string`, `integer`/`number` → `number`, `boolean` → `boolean`
+//! - objects with `properties` → an `interface`, properties outside
+//!   `required` are optional, and `additionalProperties` adds an index signature
+//! - other objects → `Record<string, _>`, arrays → `T[]`
+//! - string `enum`s → a named union of literals, `const` → a literal
+//! - `oneOf`/`anyOf` → a union, `allOf` → an intersection
+//! - local `$ref`s → one shared, named type
+//!
+//! A module's root type is the schema `title` when that is already a type name
+//! (`RitualStartedV1`), otherwise the file or contract name in PascalCase;
+//! nested types are named `<Root><Property>`. The output directory belongs to
+//! the generator: files of the generated kind that no schema produces any more
+//! are removed, and `--check` fails when anything would change.
+
+use crate::commands::registry;
+use crate::output::{self, OutputFormat};
+use anyhow::{bail, Context, Result};
+use clap::{Args, ValueEnum};
+use config_loader::codegen::{self, pascal_case, snake_case, GeneratedFile};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// First line of every generated file
+pub const HEADER: &str = "// @generated by `demonctl contracts codegen`; do not edit.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    Ts,
+    Rust,
+}
+
+#[derive(Args, Debug)]
+pub struct CodegenArgs {
+    /// Language to generate
+    #[arg(long, value_enum)]
+    pub lang: Lang,
+
+    /// Directory to write the generated files to
+    #[arg(long, value_name = "DIR")]
+    pub out: PathBuf,
+
+    /// Directory holding the contract schemas
+    #[arg(
+        long,
+        value_name = "DIR",
+        default_value = "contracts/schemas",
+        conflicts_with = "registry"
+    )]
+    pub schema_dir: PathBuf,
+
+    /// Result envelope schema, generated alongside the contracts
+    #[arg(
+        long,
+        value_name = "FILE",
+        default_value = "contracts/envelopes/result.json"
+    )]
+    pub envelope: PathBuf,
+
+    /// Do not generate the result envelope
+    #[arg(long, conflicts_with = "envelope")]
+    pub no_envelope: bool,
+
+    /// Generate from the latest version of each contract in the Schema Registry
+    #[arg(long)]
+    pub registry: bool,
+
+    /// Schema Registry base URL, for --registry
+    #[arg(
+        long,
+        env = "DEMONCTL_REGISTRY_URL",
+        default_value = "http://localhost:8090"
+    )]
+    pub registry_url: String,
+
+    /// JWT token for the registry API (defaults to the saved login)
+    #[arg(long, env = "DEMONCTL_JWT")]
+    pub jwt: Option<String>,
+
+    /// Fail if the generated files are stale instead of writing them
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// A schema and the name of the type generated for it
+#[derive(Debug, Clone)]
+pub struct Source {
+    /// Where the schema came from, for messages
+    pub origin: String,
+    pub type_name: String,
+    pub schema: Value,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContractsCodegenReport {
+    lang: Lang,
+    out: PathBuf,
+    files: Vec<PathBuf>,
+    check: bool,
+    stale: Vec<PathBuf>,
+}
+
+pub async fn run(args: CodegenArgs, format: OutputFormat) -> Result<()> {
+    let mut sources = if args.registry {
+        registry_sources(&args.registry_url, args.jwt.as_deref()).await?
+    } else {
+        local_sources(&args.schema_dir)?
+    };
+    if !args.no_envelope {
+        sources.push(schema_file(&args.envelope)?);
+    }
+
+    let files = generate(args.lang, &sources)?;
+    let stale = codegen::stale_files(&args.out, &files)?;
+    if !args.check && !stale.is_empty() {
+        codegen::write_generated(&args.out, &files)?;
+    }
+    let report = ContractsCodegenReport {
+        lang: args.lang,
+        out: args.out.clone(),
+        files: files.iter().map(|f| args.out.join(&f.path)).collect(),
+        check: args.check,
+        stale,
+    };
+    output::emit(format, "ContractsCodegenReport", &report, || {
+        if report.stale.is_empty() {
+            println!(
+                "✓ {} generated file{} up to date in {}",
+                report.files.len(),
+                if report.files.len() == 1 { "" } else { "s" },
+                report.out.display()
+            );
+        } else {
+            let verb = if report.check { "Stale" } else { "Updated" };
+            for path in &report.stale {
+                println!("{} {}", verb, path.display());
+            }
+        }
+    })?;
+    if args.check && !report.stale.is_empty() {
+        bail!("Generated client models are stale: run demonctl contracts codegen");
+    }
+    Ok(())
+}
+
+/// Every `*.json` schema directly in `dir`
+pub fn local_sources(dir: &Path) -> Result<Vec<Source>> {
+    let mut paths = Vec::new();
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    if paths.is_empty() {
+        bail!("No *.json schemas found in {}", dir.display());
+    }
+    paths.sort();
+    paths.iter().map(|path| schema_file(path)).collect()
+}
+
+/// One schema file, named after its title or file name
+fn schema_file(path: &Path) -> Result<Source> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let schema: Value = serde_json::from_str(&text)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let stem = file_name
+        .trim_end_matches(".json")
+        .trim_end_matches(".schema");
+    Ok(Source {
+        origin: path.display().to_string(),
+        type_name: type_name(&schema, stem),
+        schema,
+    })
+}
+
+/// The latest version of every contract with a JSON schema in the registry
+async fn registry_sources(registry_url: &str, jwt: Option<&str>) -> Result<Vec<Source>> {
+    let mut latest: BTreeMap<String, registry::ContractMetadata> = BTreeMap::new();
+    for contract in registry::fetch_contracts(registry_url, jwt).await? {
+        let older = latest
+            .get(&contract.name)
+            .is_some_and(|seen| version_key(seen) >= version_key(&contract));
+        if !older {
+            latest.insert(contract.name.clone(), contract);
+        }
+    }
+    if latest.is_empty() {
+        bail!("The Schema Registry at {} has no contracts", registry_url);
+    }
+
+    let mut sources = Vec::new();
+    for contract in latest.values() {
+        let bundle =
+            registry::fetch_bundle_from(registry_url, jwt, &contract.name, &contract.version)
+                .await?;
+        if bundle.json_schema.is_none() {
+            continue;
+        }
+        let schema = bundle.schema()?;
+        sources.push(Source {
+            origin: format!("{} v{}", bundle.name, bundle.version),
+            type_name: type_name(&schema, &bundle.name),
+            schema,
+        });
+    }
+    Ok(sources)
+}
+
+/// Semver order, with the publish time for versions that do not parse
+fn version_key(contract: &registry::ContractMetadata) -> (Option<semver::Version>, String) {
+    (
+        semver::Version::parse(&contract.version).ok(),
+        contract.created_at.clone(),
+    )
+}
+
+/// The schema `title` when it is already a type name, else `fallback` in PascalCase
+fn type_name(schema: &Value, fallback: &str) -> String {
+    match schema.get("title").and_then(Value::as_str) {
+        Some(title)
+            if title.starts_with(|c: char| c.is_ascii_uppercase())
+                && title.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            title.to_string()
+        }
+        _ => pascal_case(fallback),
+    }
+}
+
+/// One module per source, plus `mod.rs` or `index.ts` re-exporting them all
+pub fn generate(lang: Lang, sources: &[Source]) -> Result<Vec<GeneratedFile>> {
+    let mut owners: BTreeMap<&str, &str> = BTreeMap::new();
+    for source in sources {
+        if let Some(other) = owners.insert(&source.type_name, &source.origin) {
+            bail!(
+                "{} and {} both generate type '{}'",
+                other,
+                source.origin,
+                source.type_name
+            );
+        }
+    }
+
+    let mut modules = Vec::new();
+    let mut files = Vec::new();
+    for source in sources {
+        let module = snake_case(&source.type_name);
+        let (path, contents) = match lang {
+            Lang::Rust => (
+                format!("{}.rs", module),
+                codegen::generate_rust_module(&source.schema, &source.type_name, HEADER),
+            ),
+            Lang::Ts => (
+                format!("{}.ts", module.replace('_', "-")),
+                typescript_module(&source.schema, &source.type_name),
+            ),
+        };
+        let contents = contents.with_context(|| format!("Failed to generate {}", source.origin))?;
+        files.push(GeneratedFile {
+            path: PathBuf::from(path),
+            contents,
+        });
+        modules.push(module);
+    }
+    modules.sort();
+
+    let index = match lang {
+        Lang::Rust => {
+            let mut mod_rs = format!("{}\n\n", HEADER);
+            for module in &modules {
+                mod_rs.push_str(&format!("mod {};\n", module));
+            }
+            mod_rs.push('\n');
+            for module in &modules {
+                mod_rs.push_str(&format!("pub use {}::*;\n", module));
+            }
+            GeneratedFile {
+                path: PathBuf::from("mod.rs"),
+                contents: mod_rs,
+            }
+        }
+        Lang::Ts => {
+            let mut index_ts = format!("{}\n\n", HEADER);
+            for module in &modules {
+                index_ts.push_str(&format!(
+                    "export * from './{}';\n",
+                    module.replace('_', "-")
+                ));
+            }
+            GeneratedFile {
+                path: PathBuf::from("index.ts"),
+                contents: index_ts,
+            }
+        }
+    };
+    files.push(index);
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// TypeScript source for one schema, with `type_name` as the root type
+pub fn typescript_module(schema: &Value, type_name: &str) -> Result<String> {
+    let mut generator = TypeScript {
+        root: schema.clone(),
+        root_name: type_name.to_string(),
+        ..Default::default()
+    };
+    let plain_object = has_properties(schema)
+        && ["oneOf", "anyOf", "allOf"]
+            .iter()
+            .all(|key| schema.get(key).is_none());
+    if plain_object {
+        let description = schema
+            .get("description")
+            .or_else(|| schema.get("title"))
+            .and_then(Value::as_str);
+        generator.interface(type_name, schema, description)?;
+    } else {
+        let root = generator.ts_type(schema, type_name)?;
+        if root != type_name {
+            generator.claim(type_name)?;
+            let mut alias = js_doc("", schema.get("description").and_then(Value::as_str));
+            alias.push_str(&format!("export type {} = {};\n", type_name, root));
+            generator.items.insert(0, alias);
+        }
+    }
+
+    let mut out = format!("{}\n", HEADER);
+    for item in &generator.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+    Ok(out)
+}
+
+#[derive(Default)]
+struct TypeScript {
+    /// The whole schema, for resolving `$ref`s
+    root: Value,
+    root_name: String,
+    items: Vec<String>,
+    names: BTreeSet<String>,
+    /// Types generated for `$defs`, by type name
+    refs: BTreeSet<String>,
+}
+
+impl TypeScript {
+    fn claim(&mut self, name: &str) -> Result<()> {
+        if !self.names.insert(name.to_string()) {
+            bail!("Generated type name '{}' is used twice", name);
+        }
+        Ok(())
+    }
+
+    fn ts_type(&mut self, schema: &Value, name: &str) -> Result<String> {
+        if let Value::Bool(allowed) = schema {
+            return Ok(if *allowed { "unknown" } else { "never" }.to_string());
+        }
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            return self.reference(reference);
+        }
+        if let Some(value) = schema.get("const") {
+            return Ok(literal(value).unwrap_or_else(|| "unknown".to_string()));
+        }
+        if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+            return self.enumeration(name, schema, values);
+        }
+
+        let all_of = schema.get("allOf").and_then(Value::as_array);
+        let one_of = schema
+            .get("oneOf")
+            .or_else(|| schema.get("anyOf"))
+            .and_then(Value::as_array);
+        if all_of.is_none() && one_of.is_none() {
+            return self.typed(schema, name);
+        }
+
+        let mut members = Vec::new();
+        if has_properties(schema) {
+            let description = schema.get("description").and_then(Value::as_str);
+            members.push(self.interface(&format!("{}Base", name), schema, description)?);
+        }
+        for (i, part) in all_of.into_iter().flatten().enumerate() {
+            members.push(self.ts_type(part, &format!("{}Part{}", name, i + 1))?);
+        }
+        if let Some(branches) = one_of {
+            let mut variants = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                let variant = self.ts_type(branch, &format!("{}Variant{}", name, i + 1))?;
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+            }
+            members.push(variants.join(" | "));
+        }
+        if members.len() == 1 {
+            return Ok(members.remove(0));
+        }
+        Ok(members
+            .iter()
+            .map(|member| {
+                if member.contains(" | ") {
+                    format!("({})", member)
+                } else {
+                    member.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" & "))
+    }
+
+    /// A schema described by `type`, or by `properties`/`items` alone
+    fn typed(&mut self, schema: &Value, name: &str) -> Result<String> {
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::String(ty)) => vec![ty.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            _ if has_properties(schema) => vec!["object"],
+            _ if schema.get("items").is_some() => vec!["array"],
+            _ => Vec::new(),
+        };
+        if types.is_empty() {
+            return Ok("unknown".to_string());
+        }
+        let mut members: Vec<String> = Vec::new();
+        for ty in types {
+            let member = self.primitive(ty, schema, name)?;
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+        Ok(members.join(" | "))
+    }
+
+    fn primitive(&mut self, ty: &str, schema: &Value, name: &str) -> Result<String> {
+        Ok(match ty {
+            "string" => "string".to_string(),
+            "integer" | "number" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "null" => "null".to_string(),
+            "array" => match schema.get("items") {
+                Some(Value::Array(tuple)) => {
+                    let mut items = Vec::new();
+                    for (i, item) in tuple.iter().enumerate() {
+                        items.push(self.ts_type(item, &format!("{}Item{}", name, i + 1))?);
+                    }
+                    format!("[{}]", items.join(", "))
+                }
+                Some(items) => {
+                    let item = self.ts_type(items, &format!("{}Item", name))?;
+                    if item.contains(' ') {
+                        format!("Array<{}>", item)
+                    } else {
+                        format!("{}[]", item)
+                    }
+                }
+                None => "unknown[]".to_string(),
+            },
+            "object" if has_properties(schema) => {
+                let description = schema.get("description").and_then(Value::as_str);
+                self.interface(name, schema, description)?
+            }
+            "object" => match schema.get("additionalProperties") {
+                Some(extra @ Value::Object(_)) => format!(
+                    "Record<string, {}>",
+                    self.ts_type(extra, &format!("{}Value", name))?
+                ),
+                _ => "Record<string, unknown>".to_string(),
+            },
+            _ => "unknown".to_string(),
+        })
+    }
+
+    /// The type for a local definition, generated on first use
+    fn reference(&mut self, reference: &str) -> Result<String> {
+        let Some(definition) = reference
+            .strip_prefix("#/$defs/")
+            .or_else(|| reference.strip_prefix("#/definitions/"))
+        else {
+            return Ok("unknown".to_string());
+        };
+        let name = format!("{}{}", self.root_name, pascal_case(definition));
+        if self.refs.contains(&name) {
+            return Ok(name);
+        }
+        let Some(target) = self.root.pointer(&reference[1..]).cloned() else {
+            bail!("Unresolved $ref '{}'", reference);
+        };
+        // A recursive reference sees the type by name while it is generated
+        self.refs.insert(name.clone());
+        let ty = self.ts_type(&target, &name)?;
+        if ty != name {
+            self.claim(&name)?;
+            let mut alias = js_doc("", target.get("description").and_then(Value::as_str));
+            alias.push_str(&format!("export type {} = {};\n", name, ty));
+            self.items.push(alias);
+        }
+        Ok(name)
+    }
+
+    fn enumeration(&mut self, name: &str, schema: &Value, values: &[Value]) -> Result<String> {
+        let Some(literals) = values.iter().map(literal).collect::<Option<Vec<_>>>() else {
+            return Ok("unknown".to_string());
+        };
+        if !values.iter().all(Value::is_string) {
+            return Ok(literals.join(" | "));
+        }
+        self.claim(name)?;
+        let mut out = js_doc("", schema.get("description").and_then(Value::as_str));
+        out.push_str(&format!(
+            "export type {} = {};\n",
+            name,
+            literals.join(" | ")
+        ));
+        self.items.push(out);
+        Ok(name.to_string())
+    }
+
+    fn interface(
+        &mut self,
+        name: &str,
+        schema: &Value,
+        description: Option<&str>,
+    ) -> Result<String> {
+        self.claim(name)?;
+        let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+            bail!("'{}' has no properties", name);
+        };
+        let required: BTreeSet<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        // Reserve the slot so the interface comes before its nested types
+        let at = self.items.len();
+        let mut body = String::new();
+        for (property, property_schema) in properties {
+            let ty = self.ts_type(
+                property_schema,
+                &format!("{}{}", name, pascal_case(property)),
+            )?;
+            body.push_str(&js_doc(
+                "  ",
+                property_schema.get("description").and_then(Value::as_str),
+            ));
+            let optional = if required.contains(property.as_str()) {
+                ""
+            } else {
+                "?"
+            };
+            body.push_str(&format!(
+                "  {}{}: {};\n",
+                property_key(property),
+                optional,
+                ty
+            ));
+        }
+        if matches!(
+            schema.get("additionalProperties"),
+            Some(Value::Bool(true) | Value::Object(_))
+        ) {
+            body.push_str("  [key: string]: unknown;\n");
+        }
+
+        let mut out = js_doc("", description);
+        out.push_str(&format!("export interface {} {{\n{}}}\n", name, body));
+        self.items.insert(at, out);
+        Ok(name.to_string())
+    }
+}
+
+fn has_properties(schema: &Value) -> bool {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .is_some_and(|p| !p.is_empty())
+}
+
+/// A JSON scalar as a TypeScript literal type
+fn literal(value: &Value) -> Option<String> {
+    match value {
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {
+            serde_json::to_string(value).ok()
+        }
+        _ => None,
+    }
+}
+
+/// A property name, quoted unless it is a valid identifier
+fn property_key(property: &str) -> String {
+    let identifier = property
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && property
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        property.to_string()
+    } else {
+        format!("{:?}", property)
+    }
+}
+
+fn js_doc(indent: &str, text: Option<&str>) -> String {
+    let Some(text) = text.map(str::trim).filter(|t| !t.is_empty()) else {
+        return String::new();
+    };
+    let text = text.replace("*/", "*\\/");
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    if let [line] = lines.as_slice() {
+        return format!("{}/** {} */\n", indent, line);
+    }
+    let mut out = format!("{}/**\n", indent);
+    for line in lines {
+        if line.is_empty() {
+            out.push_str(&format!("{} *\n", indent));
+        } else {
+            out.push_str(&format!("{} * {}\n", indent, line));
+        }
+    }
+    out.push_str(&format!("{} */\n", indent));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn envelope() -> Value {
+        json!({
+            "title": "ResultEnvelope",
+            "description": "Standard envelope for operation results",
+            "type": "object",
+            "required": ["result"],
+            "properties": {
+                "result": {
+                    "oneOf": [
+                        {
+                            "type": "object",
+                            "properties": {"success": {"type": "boolean"}, "data": {}}
+                        },
+                        {"type": "string"}
+                    ]
+                },
+                "diagnostics": {
+                    "type": "array",
+                    "items": {"$ref": "#/$defs/diagnostic"}
+                },
+                "metrics": {
+                    "type": "object",
+                    "additionalProperties": {"type": "number"}
+                },
+                "x-trace": {"type": ["string", "null"]}
+            },
+            "additionalProperties": false,
+            "$defs": {
+                "diagnostic": {
+                    "type": "object",
+                    "required": ["level"],
+                    "properties": {
+                        "level": {"type": "string", "enum": ["info", "warning", "error"]},
+                        "message": {"type": "string", "description": "What happened"}
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn typescript_interfaces_follow_the_schema() {
+        let source = typescript_module(&envelope(), "ResultEnvelope").unwrap();
+        assert!(source.starts_with(HEADER));
+        assert!(source.contains(
+            "/** Standard envelope for operation results */\nexport interface ResultEnvelope {"
+        ));
+        assert!(source.contains("  result: ResultEnvelopeResultVariant1 | string;\n"));
+        assert!(source.contains("  diagnostics?: ResultEnvelopeDiagnostic[];\n"));
+        assert!(source.contains("  metrics?: Record<string, number>;\n"));
+        assert!(source.contains("  \"x-trace\"?: string | null;\n"));
+        assert!(source.contains("  data?: unknown;\n"));
+        assert!(source.contains(
+            "export type ResultEnvelopeDiagnosticLevel = \"info\" | \"warning\" | \"error\";"
+        ));
+        assert!(source.contains("  /** What happened */\n  message?: string;\n"));
+        assert_eq!(
+            source
+                .matches("export interface ResultEnvelopeDiagnostic {")
+                .count(),
+            1
+        );
+        // The root interface comes first
+        assert!(
+            source.find("interface ResultEnvelope {").unwrap()
+                < source.find("interface ResultEnvelopeDiagnostic {").unwrap()
+        );
+    }
+
+    #[test]
+    fn non_object_roots_become_aliases() {
+        let source = typescript_module(
+            &json!({"anyOf": [{"type": "string"}, {"type": "array", "items": {"type": "string"}}]}),
+            "Selector",
+        )
+        .unwrap();
+        assert!(source.contains("export type Selector = string | string[];"));
+    }
+
+    #[test]
+    fn titles_name_types_only_when_they_are_type_names() {
+        assert_eq!(
+            type_name(
+                &json!({"title": "RitualStartedV1"}),
+                "events.ritual.started.v1"
+            ),
+            "RitualStartedV1"
+        );
+        assert_eq!(
+            type_name(&json!({"title": "Demon App Pack (v1)"}), "app-pack.v1"),
+            "AppPackV1"
+        );
+    }
+
+    #[test]
+    fn both_languages_get_an_index_module() {
+        let sources = vec![Source {
+            origin: "result.json".to_string(),
+            type_name: "ResultEnvelope".to_string(),
+            schema: envelope(),
+        }];
+        let ts = generate(Lang::Ts, &sources).unwrap();
+        let paths: Vec<_> = ts
+            .iter()
+            .map(|f| f.path.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(paths, ["index.ts", "result-envelope.ts"]);
+        assert!(ts[0]
+            .contents
+            .contains("export * from './result-envelope';"));
+
+        let rust = generate(Lang::Rust, &sources).unwrap();
+        let paths: Vec<_> = rust
+            .iter()
+            .map(|f| f.path.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(paths, ["mod.rs", "result_envelope.rs"]);
+        assert!(rust[1].contents.contains("pub struct ResultEnvelope {"));
+
+        let twice = [sources[0].clone(), sources[0].clone()];
+        assert!(generate(Lang::Ts, &twice).is_err());
+    }
+}
@@ -0,0 +1,520 @@
+//! Dev command - run the local stack (NATS, runtime, Operate UI, registry)
+//!
+//! `dev up` starts NATS from `docker/dev/docker-compose.yml`, builds the
+//! services, runs them with one shared environment and prefixes their output.
+//! With `--watch` the services are rebuilt when sources change and restarted
+//! when their binary changed. Ctrl-C, or `dev down` from another shell, stops
+//! everything.
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand, ValueEnum};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+
+const COMPOSE_FILE: &str = "docker/dev/docker-compose.yml";
+/// Used when `JWT_SECRET` is unset; the registry refuses to start without one
+const DEV_JWT_SECRET: &str = "demon-dev-only-jwt-secret-do-not-use-in-production";
+/// Directories never scanned by `--watch`
+const WATCH_SKIP_DIRS: [&str; 4] = ["target", "node_modules", "test-results", "logs"];
+
+#[derive(Args, Debug)]
+pub struct DevArgs {
+    #[command(subcommand)]
+    pub cmd: DevCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DevCommand {
+    /// Start NATS and the services, tailing their logs until Ctrl-C
+    Up(UpArgs),
+    /// Stop services left running by `dev up` and shut down NATS
+    Down(DownArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Service {
+    Runtime,
+    OperateUi,
+    Registry,
+}
+
+impl Service {
+    const ALL: [Service; 3] = [Service::Runtime, Service::OperateUi, Service::Registry];
+
+    /// Cargo package, which is also the binary name
+    fn package(self) -> &'static str {
+        match self {
+            Service::Runtime => "runtime",
+            Service::OperateUi => "operate-ui",
+            Service::Registry => "demon-registry",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Service::Runtime => "runtime",
+            Service::OperateUi => "operate-ui",
+            Service::Registry => "registry",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct UpArgs {
+    /// Services to run next to NATS (default: all)
+    #[arg(long = "service", value_enum, value_delimiter = ',')]
+    pub services: Vec<Service>,
+
+    /// Host port for NATS
+    #[arg(long, default_value_t = 4222)]
+    pub nats_port: u16,
+
+    /// Port for the runtime API
+    #[arg(long, default_value_t = 8080)]
+    pub runtime_port: u16,
+
+    /// Port for the Operate UI
+    #[arg(long, default_value_t = 3000)]
+    pub ui_port: u16,
+
+    /// Port for the Schema Registry
+    #[arg(long, default_value_t = 8090)]
+    pub registry_port: u16,
+
+    /// Build with --release
+    #[arg(long)]
+    pub release: bool,
+
+    /// Rebuild on source changes and restart services whose binary changed
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Leave NATS running when the services stop
+    #[arg(long)]
+    pub keep_nats: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DownArgs {
+    /// Also delete the NATS volume (JetStream data)
+    #[arg(long)]
+    pub volumes: bool,
+}
+
+pub async fn run(args: DevArgs) -> Result<()> {
+    let root = find_repo_root(&std::env::current_dir()?)?;
+    match args.cmd {
+        DevCommand::Up(up_args) => up(&root, up_args).await,
+        DevCommand::Down(down_args) => down(&root, down_args).await,
+    }
+}
+
+/// The nearest ancestor of `start` holding the dev compose file
+fn find_repo_root(start: &Path) -> Result<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(COMPOSE_FILE).exists())
+        .map(Path::to_path_buf)
+        .with_context(|| {
+            format!(
+                "{} not found above {}; run demonctl dev inside the Demon repository",
+                COMPOSE_FILE,
+                start.display()
+            )
+        })
+}
+
+/// `target/demon-dev`, holding the PIDs of running services for `dev down`
+fn state_dir(root: &Path) -> PathBuf {
+    root.join("target").join("demon-dev")
+}
+
+fn pid_file(root: &Path) -> PathBuf {
+    state_dir(root).join("pids.json")
+}
+
+/// Environment for one service; everything points at the other local services
+fn service_env(service: Service, args: &UpArgs) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("NATS_URL", format!("nats://127.0.0.1:{}", args.nats_port)),
+        (
+            "RITUAL_STREAM_NAME",
+            std::env::var("RITUAL_STREAM_NAME").unwrap_or_else(|_| "RITUAL_EVENTS".to_string()),
+        ),
+    ];
+    match service {
+        Service::Runtime => env.push(("PORT", args.runtime_port.to_string())),
+        Service::OperateUi => env.extend([
+            ("PORT", args.ui_port.to_string()),
+            ("BIND_ADDR", "127.0.0.1".to_string()),
+            (
+                "RUNTIME_API_URL",
+                format!("http://127.0.0.1:{}", args.runtime_port),
+            ),
+            (
+                "SCHEMA_REGISTRY_URL",
+                format!("http://127.0.0.1:{}", args.registry_port),
+            ),
+        ]),
+        Service::Registry => env.extend([
+            ("BIND_ADDR", format!("127.0.0.1:{}", args.registry_port)),
+            (
+                "JWT_SECRET",
+                std::env::var("JWT_SECRET").unwrap_or_else(|_| DEV_JWT_SECRET.to_string()),
+            ),
+        ]),
+    }
+    env
+}
+
+async fn compose(root: &Path, compose_args: &[&str], nats_port: Option<u16>) -> Result<()> {
+    let mut cmd = Command::new("docker");
+    cmd.current_dir(root)
+        .args(["compose", "-f", COMPOSE_FILE])
+        .args(compose_args);
+    if let Some(port) = nats_port {
+        cmd.env("NATS_PORT", port.to_string());
+    }
+    let status = cmd
+        .status()
+        .await
+        .context("Failed to run docker compose; is Docker installed?")?;
+    if !status.success() {
+        bail!(
+            "docker compose {} failed: {}",
+            compose_args.join(" "),
+            status
+        );
+    }
+    Ok(())
+}
+
+async fn wait_for_port(port: u16, timeout: Duration) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while tokio::time::Instant::now() < deadline {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    bail!(
+        "NATS did not accept connections on port {} within {}s",
+        port,
+        timeout.as_secs()
+    )
+}
+
+async fn build(root: &Path, services: &[Service], release: bool) -> Result<()> {
+    let mut cmd = Command::new("cargo");
+    cmd.current_dir(root).arg("build");
+    for service in services {
+        cmd.args(["-p", service.package()]);
+    }
+    if release {
+        cmd.arg("--release");
+    }
+    let status = cmd.status().await.context("Failed to run cargo build")?;
+    if !status.success() {
+        bail!("cargo build failed: {}", status);
+    }
+    Ok(())
+}
+
+fn binary_path(root: &Path, service: Service, release: bool) -> PathBuf {
+    let profile = if release { "release" } else { "debug" };
+    root.join("target").join(profile).join(format!(
+        "{}{}",
+        service.package(),
+        std::env::consts::EXE_SUFFIX
+    ))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Newest modification time of any source file under `dir`
+fn latest_source_change(dir: &Path) -> Option<SystemTime> {
+    let mut latest = None;
+    let Ok(entries) = fs::read_dir(dir) else {
+        return None;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let stamp = if path.is_dir() {
+            if name.starts_with('.') || WATCH_SKIP_DIRS.contains(&name.as_ref()) {
+                continue;
+            }
+            latest_source_change(&path)
+        } else if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("rs" | "toml" | "html" | "css" | "js" | "json")
+        ) {
+            modified(&path)
+        } else {
+            None
+        };
+        latest = latest.max(stamp);
+    }
+    latest
+}
+
+fn tail<R: AsyncRead + Unpin + Send + 'static>(label: &'static str, stream: Option<R>) {
+    let Some(stream) = stream else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            println!("{:<10} | {}", label, line);
+        }
+    });
+}
+
+fn spawn(root: &Path, service: Service, args: &UpArgs) -> Result<Child> {
+    let binary = binary_path(root, service, args.release);
+    let mut child = Command::new(&binary)
+        .current_dir(root)
+        .envs(service_env(service, args))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", binary.display()))?;
+    tail(service.label(), child.stdout.take());
+    tail(service.label(), child.stderr.take());
+    Ok(child)
+}
+
+fn write_pids(root: &Path, running: &BTreeMap<Service, Child>) -> Result<()> {
+    let pids: BTreeMap<&str, u32> = running
+        .iter()
+        .filter_map(|(service, child)| child.id().map(|pid| (service.label(), pid)))
+        .collect();
+    fs::create_dir_all(state_dir(root))?;
+    fs::write(pid_file(root), serde_json::to_string_pretty(&pids)?)?;
+    Ok(())
+}
+
+async fn stop_all(root: &Path, running: &mut BTreeMap<Service, Child>) {
+    for (service, child) in running.iter_mut() {
+        if child.kill().await.is_ok() {
+            println!("{:<10} | stopped", service.label());
+        }
+    }
+    let _ = fs::remove_file(pid_file(root));
+}
+
+async fn up(root: &Path, mut args: UpArgs) -> Result<()> {
+    if args.services.is_empty() {
+        args.services = Service::ALL.to_vec();
+    }
+    args.services.sort();
+    args.services.dedup();
+
+    println!("Starting NATS on port {}...", args.nats_port);
+    compose(root, &["up", "-d"], Some(args.nats_port)).await?;
+    wait_for_port(args.nats_port, Duration::from_secs(30)).await?;
+
+    println!("Building {}...", labels(&args.services));
+    build(root, &args.services, args.release).await?;
+
+    let mut running = BTreeMap::new();
+    let result = supervise(root, &args, &mut running).await;
+    stop_all(root, &mut running).await;
+    if !args.keep_nats {
+        compose(root, &["stop"], None).await?;
+    }
+    result
+}
+
+/// Run the services until Ctrl-C; a service exiting on its own ends the stack
+/// unless `--watch` is on, in which case the next successful build restarts it
+async fn supervise(
+    root: &Path,
+    args: &UpArgs,
+    running: &mut BTreeMap<Service, Child>,
+) -> Result<()> {
+    for &service in &args.services {
+        running.insert(service, spawn(root, service, args)?);
+    }
+    write_pids(root, running)?;
+    print_endpoints(args);
+
+    let mut binaries: BTreeMap<Service, Option<SystemTime>> = args
+        .services
+        .iter()
+        .map(|&s| (s, modified(&binary_path(root, s, args.release))))
+        .collect();
+    let mut last_change = if args.watch {
+        latest_source_change(root)
+    } else {
+        None
+    };
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut ticker = tokio::time::interval(Duration::from_secs(2));
+
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => {
+                println!();
+                println!("Shutting down...");
+                return Ok(());
+            }
+            _ = ticker.tick() => {}
+        }
+
+        for (service, child) in running.iter_mut() {
+            if let Some(status) = child.try_wait()? {
+                if !args.watch {
+                    bail!("{} exited: {}", service.label(), status);
+                }
+                eprintln!(
+                    "{:<10} | exited: {}; waiting for a change to rebuild",
+                    service.label(),
+                    status
+                );
+            }
+        }
+
+        if !args.watch {
+            continue;
+        }
+        let change = latest_source_change(root);
+        if change <= last_change {
+            continue;
+        }
+        last_change = change;
+        println!("Sources changed, rebuilding...");
+        if let Err(e) = build(root, &args.services, args.release).await {
+            eprintln!("{:#}; keeping the running services", e);
+            continue;
+        }
+        for &service in &args.services {
+            let binary = modified(&binary_path(root, service, args.release));
+            let exited = match running.get_mut(&service) {
+                Some(child) => matches!(child.try_wait(), Ok(Some(_))),
+                None => true,
+            };
+            if binary == binaries[&service] && !exited {
+                continue;
+            }
+            if let Some(mut child) = running.remove(&service) {
+                let _ = child.kill().await;
+            }
+            println!("{:<10} | restarting", service.label());
+            running.insert(service, spawn(root, service, args)?);
+            binaries.insert(service, binary);
+        }
+        write_pids(root, running)?;
+    }
+}
+
+fn labels(services: &[Service]) -> String {
+    services
+        .iter()
+        .map(|s| s.label())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_endpoints(args: &UpArgs) {
+    println!("Dev stack is up (Ctrl-C to stop):");
+    println!("  NATS        nats://127.0.0.1:{}", args.nats_port);
+    for service in &args.services {
+        match service {
+            Service::Runtime => println!("  Runtime     http://127.0.0.1:{}", args.runtime_port),
+            Service::OperateUi => println!("  Operate UI  http://127.0.0.1:{}", args.ui_port),
+            Service::Registry => {
+                println!("  Registry    http://127.0.0.1:{}", args.registry_port)
+            }
+        }
+    }
+}
+
+async fn down(root: &Path, args: DownArgs) -> Result<()> {
+    let pid_path = pid_file(root);
+    if pid_path.exists() {
+        let pids: BTreeMap<String, u32> = serde_json::from_str(&fs::read_to_string(&pid_path)?)
+            .with_context(|| format!("Failed to parse {}", pid_path.display()))?;
+        for (label, pid) in pids {
+            let status = Command::new("kill")
+                .arg(pid.to_string())
+                .stderr(Stdio::null())
+                .status()
+                .await;
+            if matches!(status, Ok(s) if s.success()) {
+                println!("Stopped {} (pid {})", label, pid);
+            }
+        }
+        fs::remove_file(&pid_path)?;
+    }
+
+    let mut compose_args = vec!["down"];
+    if args.volumes {
+        compose_args.push("-v");
+    }
+    compose(root, &compose_args, None).await?;
+    println!("✓ Dev stack stopped");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        up: UpArgs,
+    }
+
+    fn env_map(service: Service, args: &UpArgs) -> BTreeMap<&'static str, String> {
+        service_env(service, args).into_iter().collect()
+    }
+
+    #[test]
+    fn operate_ui_env_points_at_local_runtime_and_registry() {
+        let cli = Cli::parse_from(["dev", "--runtime-port", "9080", "--registry-port", "9090"]);
+        let env = env_map(Service::OperateUi, &cli.up);
+        assert_eq!(env["NATS_URL"], "nats://127.0.0.1:4222");
+        assert_eq!(env["PORT"], "3000");
+        assert_eq!(env["RUNTIME_API_URL"], "http://127.0.0.1:9080");
+        assert_eq!(env["SCHEMA_REGISTRY_URL"], "http://127.0.0.1:9090");
+    }
+
+    #[test]
+    fn services_accept_comma_separated_list() {
+        let cli = Cli::parse_from(["dev", "--service", "runtime,operate-ui"]);
+        assert_eq!(cli.up.services, vec![Service::Runtime, Service::OperateUi]);
+        assert_eq!(
+            env_map(Service::Registry, &cli.up)["BIND_ADDR"],
+            "127.0.0.1:8090"
+        );
+    }
+
+    #[test]
+    fn repo_root_is_found_from_a_subdirectory() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("docker/dev")).unwrap();
+        fs::write(temp.path().join(COMPOSE_FILE), "services: {}\n").unwrap();
+        let nested = temp.path().join("operate-ui/src");
+        fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(find_repo_root(&nested).unwrap(), temp.path());
+        assert!(find_repo_root(Path::new("/")).is_err());
+    }
+}
@@ -393,11 +393,14 @@ async fn import_flow(
     }
 
     // Submit to API
-    let jwt_token = jwt.or_else(|| env::var("DEMONCTL_JWT").ok()).ok_or_else(|| {
-        anyhow::anyhow!(
-            "JWT token required for API submission. Set --jwt flag or DEMONCTL_JWT environment variable"
-        )
-    })?;
+    let jwt = jwt.or_else(|| env::var("DEMONCTL_JWT").ok());
+    let jwt_token = crate::credentials::resolve_token(jwt.as_deref())
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "JWT token required for API submission. Set --jwt flag or DEMONCTL_JWT environment variable, or run demonctl login"
+            )
+        })?;
 
     let submit_url = format!("{}/api/flows/submit", api_url);
     debug!("Submitting to: {}", submit_url);
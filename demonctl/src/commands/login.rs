@@ -0,0 +1,239 @@
+//! Login commands - sign in once instead of exporting a JWT per shell
+//!
+//! `demonctl login` runs the OAuth2 device authorization grant (RFC 8628)
+//! against an OpenID Connect issuer, or saves a token passed with `--token`.
+//! See [`crate::credentials`] for where the result is stored.
+
+use crate::credentials::{self, Credentials, StoreKind, TokenResponse};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use clap::Args;
+use serde::Deserialize;
+use std::io::Read;
+use std::time::Duration;
+
+#[derive(Args, Debug)]
+pub struct LoginArgs {
+    /// Save this token instead of signing in (`-` reads it from stdin); wins over --issuer
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// OpenID Connect issuer URL for the device-code flow
+    #[arg(long, env = "DEMONCTL_OIDC_ISSUER")]
+    pub issuer: Option<String>,
+
+    /// OAuth2 client ID registered for demonctl
+    #[arg(long, env = "DEMONCTL_OIDC_CLIENT_ID", default_value = "demonctl")]
+    pub client_id: String,
+
+    /// Scopes to request
+    #[arg(
+        long,
+        env = "DEMONCTL_OIDC_SCOPE",
+        default_value = "openid offline_access contracts:read contracts:write"
+    )]
+    pub scope: String,
+
+    /// Where to keep the credentials
+    #[arg(long, value_enum, env = "DEMONCTL_CREDENTIAL_STORE", default_value_t = StoreKind::Auto)]
+    pub store: StoreKind,
+}
+
+#[derive(Args, Debug)]
+pub struct TokenArgs {
+    /// Print the saved login's expiry and store instead of the token
+    #[arg(long)]
+    pub info: bool,
+}
+
+/// OpenID Connect discovery document, the fields the device flow needs
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    device_authorization_endpoint: Option<String>,
+    token_endpoint: String,
+}
+
+/// Device authorization response (RFC 8628 section 3.2)
+#[derive(Debug, Deserialize)]
+struct DeviceAuthorization {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+/// Token endpoint error (RFC 6749 section 5.2)
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+pub async fn login(args: LoginArgs) -> Result<()> {
+    let credentials = match &args.token {
+        Some(token) => {
+            let token = if token == "-" {
+                let mut buf = String::new();
+                std::io::stdin().read_to_string(&mut buf)?;
+                buf.trim().to_string()
+            } else {
+                token.clone()
+            };
+            if token.is_empty() {
+                bail!("Token is empty");
+            }
+            Credentials::from_token(&token)
+        }
+        None => {
+            let issuer = args
+                .issuer
+                .as_deref()
+                .context("Pass --issuer (or set DEMONCTL_OIDC_ISSUER), or use --token")?;
+            device_code_login(issuer, &args.client_id, &args.scope).await?
+        }
+    };
+
+    let store = credentials::save(&credentials, args.store)?;
+    match credentials.expires_at {
+        Some(at) => println!("✓ Logged in (token expires {}, stored in {})", at, store),
+        None => println!("✓ Logged in (stored in {})", store),
+    }
+    Ok(())
+}
+
+pub fn logout() -> Result<()> {
+    if credentials::clear(StoreKind::from_env()?)? {
+        println!("✓ Logged out");
+    } else {
+        println!("Not logged in");
+    }
+    Ok(())
+}
+
+pub async fn token(args: TokenArgs) -> Result<()> {
+    if args.info {
+        let Some((credentials, store)) = credentials::load(StoreKind::from_env()?)? else {
+            bail!("Not logged in; run demonctl login");
+        };
+        println!("Store:       {}", store);
+        match credentials.expires_at {
+            Some(at) if at <= Utc::now() => println!("Expires:     {} (expired)", at),
+            Some(at) => println!("Expires:     {}", at),
+            None => println!("Expires:     unknown"),
+        }
+        println!(
+            "Refreshable: {}",
+            if credentials.can_refresh() {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+        return Ok(());
+    }
+
+    let token = credentials::resolve_token(None)
+        .await?
+        .context("Not logged in; run demonctl login")?;
+    println!("{}", token);
+    Ok(())
+}
+
+async fn device_code_login(issuer: &str, client_id: &str, scope: &str) -> Result<Credentials> {
+    let client = reqwest::Client::new();
+    let discovery_url = format!(
+        "{}/.well-known/openid-configuration",
+        issuer.trim_end_matches('/')
+    );
+    let discovery: Discovery = client
+        .get(&discovery_url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach issuer at {}", issuer))?
+        .error_for_status()
+        .with_context(|| format!("Issuer discovery failed at {}", discovery_url))?
+        .json()
+        .await
+        .context("Failed to parse issuer discovery document")?;
+    let device_endpoint = discovery
+        .device_authorization_endpoint
+        .with_context(|| format!("Issuer {} does not support the device-code flow", issuer))?;
+
+    let device: DeviceAuthorization = client
+        .post(&device_endpoint)
+        .form(&[("client_id", client_id), ("scope", scope)])
+        .send()
+        .await
+        .context("Failed to start device authorization")?
+        .error_for_status()
+        .context("Device authorization was rejected")?
+        .json()
+        .await
+        .context("Failed to parse device authorization response")?;
+
+    eprintln!(
+        "To sign in, open {} and enter code {}",
+        device.verification_uri, device.user_code
+    );
+    if let Some(complete) = &device.verification_uri_complete {
+        eprintln!("Or open {}", complete);
+    }
+    eprintln!("Waiting for approval...");
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(device.expires_in);
+    let mut interval = Duration::from_secs(device.interval.max(1));
+    loop {
+        tokio::time::sleep(interval).await;
+        if tokio::time::Instant::now() >= deadline {
+            bail!("Device code expired before sign-in was approved");
+        }
+
+        let response = client
+            .post(&discovery.token_endpoint)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", device.device_code.as_str()),
+                ("client_id", client_id),
+            ])
+            .send()
+            .await
+            .context("Failed to poll token endpoint")?;
+
+        if response.status().is_success() {
+            let token: TokenResponse = response
+                .json()
+                .await
+                .context("Failed to parse token response")?;
+            return Ok(token.into_credentials(&discovery.token_endpoint, client_id, None));
+        }
+
+        let status = response.status();
+        let error: TokenError = response
+            .json()
+            .await
+            .with_context(|| format!("Token endpoint returned {}", status))?;
+        match error.error.as_str() {
+            "authorization_pending" => {}
+            "slow_down" => interval += Duration::from_secs(5),
+            "access_denied" => bail!("Sign-in was denied"),
+            "expired_token" => bail!("Device code expired before sign-in was approved"),
+            other => bail!(
+                "Sign-in failed: {}{}",
+                other,
+                error
+                    .error_description
+                    .map(|d| format!(" ({})", d))
+                    .unwrap_or_default()
+            ),
+        }
+    }
+}
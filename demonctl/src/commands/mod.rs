@@ -1,3 +1,9 @@
 pub mod app;
+pub mod bundle;
+pub mod codegen;
+pub mod dev;
 pub mod flow;
 pub mod inspect;
+pub mod login;
+pub mod registry;
+pub mod runs;
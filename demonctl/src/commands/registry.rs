@@ -0,0 +1,640 @@
+//! Registry command - publish and inspect contracts in the Schema Registry
+//!
+//! Talks to the registry REST API (`/registry/contracts`). Every route needs
+//! a JWT, taken from `--jwt`/`DEMONCTL_JWT` or the saved `demonctl login`;
+//! publishing also needs the `contracts:write` scope.
+
+use crate::output::{self, OutputFormat};
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use tabled::{settings::style::Style, Table, Tabled};
+
+#[derive(Args, Debug)]
+pub struct RegistryArgs {
+    #[command(subcommand)]
+    pub cmd: RegistryCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RegistryCommand {
+    /// Publish a JSON schema as a new contract version
+    Publish(PublishArgs),
+    /// Show one contract version
+    Get(GetArgs),
+    /// List published contracts
+    List(ListArgs),
+    /// Compare the JSON schemas of two contract versions
+    Diff(DiffArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct PublishArgs {
+    /// JSON schema file to publish
+    #[arg(value_name = "SCHEMA")]
+    pub schema: PathBuf,
+
+    /// Contract name
+    #[arg(long)]
+    pub name: String,
+
+    /// Contract version
+    #[arg(long)]
+    pub version: String,
+
+    /// Optional description
+    #[arg(long)]
+    pub description: Option<String>,
+
+    /// Path to the WIT interface file
+    #[arg(long)]
+    pub wit_path: Option<String>,
+
+    /// Path to the protobuf descriptor file
+    #[arg(long)]
+    pub descriptor_path: Option<String>,
+
+    /// Compatibility policy checked against earlier versions; defaults to the
+    /// contract's current policy
+    #[arg(long, value_parser = ["backward", "forward", "full", "none"])]
+    pub compatibility: Option<String>,
+
+    #[command(flatten)]
+    pub conn: ConnectionArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct GetArgs {
+    /// Contract name
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Contract version
+    #[arg(value_name = "VERSION")]
+    pub version: String,
+
+    /// Print only the JSON schema
+    #[arg(long)]
+    pub schema: bool,
+
+    #[command(flatten)]
+    pub conn: ConnectionArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Only contracts whose name contains this text
+    #[arg(long)]
+    pub name: Option<String>,
+
+    #[command(flatten)]
+    pub conn: ConnectionArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Contract name
+    #[arg(value_name = "NAME")]
+    pub name: String,
+
+    /// Published version to compare from
+    #[arg(value_name = "FROM")]
+    pub from: String,
+
+    /// Published version to compare to
+    #[arg(value_name = "TO", required_unless_present = "file")]
+    pub to: Option<String>,
+
+    /// Compare against a local schema file instead of a published version
+    #[arg(long, conflicts_with = "to")]
+    pub file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub conn: ConnectionArgs,
+}
+
+/// How to reach the registry and how to print results
+#[derive(Args, Debug)]
+pub struct ConnectionArgs {
+    /// Schema Registry base URL
+    #[arg(
+        long,
+        env = "DEMONCTL_REGISTRY_URL",
+        default_value = "http://localhost:8090"
+    )]
+    pub registry_url: String,
+
+    /// JWT token for the registry API (defaults to the saved login)
+    #[arg(long, env = "DEMONCTL_JWT")]
+    pub jwt: Option<String>,
+
+    /// Output format
+    #[arg(long, short = 'o', value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+}
+
+/// Same shape as the registry's `ContractMetadata`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractMetadata {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub created_at: String,
+}
+
+/// Same shape as the registry's `ContractBundle`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractBundle {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub json_schema: Option<String>,
+    pub wit_path: Option<String>,
+    pub descriptor_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    /// SHA-256 of `json_schema`; registries older than content addressing omit it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_digest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<String>,
+}
+
+impl ContractBundle {
+    /// Check that `json_schema` hashes to the advertised `schema_digest`
+    pub fn verify_schema_digest(&self) -> Result<()> {
+        let (Some(body), Some(expected)) = (&self.json_schema, &self.schema_digest) else {
+            return Ok(());
+        };
+        let actual = hex::encode(Sha256::digest(body.as_bytes()));
+        if &actual != expected {
+            bail!(
+                "Contract {} v{} schema does not match its digest (expected {}, got {})",
+                self.name,
+                self.version,
+                expected,
+                actual
+            );
+        }
+        Ok(())
+    }
+
+    /// The bundle's JSON schema, parsed
+    pub fn schema(&self) -> Result<Value> {
+        let raw = self.json_schema.as_deref().with_context(|| {
+            format!(
+                "Contract {} v{} has no JSON schema",
+                self.name, self.version
+            )
+        })?;
+        serde_json::from_str(raw).with_context(|| {
+            format!(
+                "Contract {} v{} has an unparseable JSON schema",
+                self.name, self.version
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        };
+        f.write_str(s)
+    }
+}
+
+/// One difference between two schemas, addressed by JSON pointer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, Tabled)]
+struct ContractRow {
+    #[tabled(rename = "NAME")]
+    name: String,
+    #[tabled(rename = "VERSION")]
+    version: String,
+    #[tabled(rename = "CREATED")]
+    created_at: String,
+    #[tabled(rename = "DESCRIPTION")]
+    description: String,
+}
+
+#[derive(Debug, Tabled)]
+struct ChangeRow {
+    #[tabled(rename = "CHANGE")]
+    kind: String,
+    #[tabled(rename = "PATH")]
+    path: String,
+    #[tabled(rename = "BEFORE")]
+    before: String,
+    #[tabled(rename = "AFTER")]
+    after: String,
+}
+
+pub async fn run(args: RegistryArgs) -> Result<()> {
+    match args.cmd {
+        RegistryCommand::Publish(args) => publish(args).await,
+        RegistryCommand::Get(args) => get(args).await,
+        RegistryCommand::List(args) => list(args).await,
+        RegistryCommand::Diff(args) => diff(args).await,
+    }
+}
+
+async fn publish(args: PublishArgs) -> Result<()> {
+    let raw = std::fs::read_to_string(&args.schema)
+        .with_context(|| format!("Failed to read schema file {}", args.schema.display()))?;
+    let schema: Value = serde_json::from_str(&raw)
+        .with_context(|| format!("{} is not valid JSON", args.schema.display()))?;
+    if let Err(e) = jsonschema::JSONSchema::compile(&schema) {
+        bail!(
+            "{} is not a valid JSON schema: {}",
+            args.schema.display(),
+            e
+        );
+    }
+
+    let payload = json!({
+        "name": args.name,
+        "version": args.version,
+        "description": args.description,
+        "jsonSchema": raw,
+        "witPath": args.wit_path,
+        "descriptorPath": args.descriptor_path,
+        "compatibility": args.compatibility,
+    });
+    let url = format!("{}/registry/contracts", base_url(&args.conn));
+    let response = reqwest::Client::new()
+        .post(&url)
+        .headers(auth_headers(args.conn.jwt.as_deref()).await?)
+        .json(&payload)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach registry at {}", args.conn.registry_url))?;
+    let body: Value = check(response).await?.json().await?;
+
+    match args.conn.output {
+        format @ (OutputFormat::Json | OutputFormat::Yaml) => output::print(format, &body)?,
+        OutputFormat::Table => {
+            println!("Published {} v{}", args.name, args.version);
+            if let Some(digest) = body["digest"].as_str() {
+                println!("Digest:   {}", digest);
+            }
+            if let Some(compatibility) = body["compatibility"].as_str() {
+                println!("Compat:   {}", compatibility);
+            }
+            if let Some(created_at) = body["createdAt"].as_str() {
+                println!("Created:  {}", created_at);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn get(args: GetArgs) -> Result<()> {
+    let bundle = fetch_bundle(&args.conn, &args.name, &args.version).await?;
+
+    if args.schema {
+        println!("{}", serde_json::to_string_pretty(&bundle.schema()?)?);
+        return Ok(());
+    }
+
+    match args.conn.output {
+        format @ (OutputFormat::Json | OutputFormat::Yaml) => output::print(format, &bundle)?,
+        OutputFormat::Table => {
+            println!("Name:         {}", bundle.name);
+            println!("Version:      {}", bundle.version);
+            println!("Created:      {}", bundle.created_at);
+            if let Some(description) = &bundle.description {
+                println!("Description:  {}", description);
+            }
+            if let Some(digest) = &bundle.digest {
+                println!("Digest:       {}", digest);
+            }
+            if let Some(schema_digest) = &bundle.schema_digest {
+                println!("Schema:       sha256:{}", schema_digest);
+            }
+            if let Some(compatibility) = &bundle.compatibility {
+                println!("Compat:       {}", compatibility);
+            }
+            if let Some(wit_path) = &bundle.wit_path {
+                println!("WIT:          {}", wit_path);
+            }
+            if let Some(descriptor_path) = &bundle.descriptor_path {
+                println!("Descriptor:   {}", descriptor_path);
+            }
+            if bundle.json_schema.is_some() {
+                println!();
+                println!("{}", serde_json::to_string_pretty(&bundle.schema()?)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn list(args: ListArgs) -> Result<()> {
+    let mut contracts = fetch_contracts(&args.conn.registry_url, args.conn.jwt.as_deref()).await?;
+
+    if let Some(name) = &args.name {
+        contracts.retain(|c| c.name.contains(name.as_str()));
+    }
+    contracts.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version)));
+
+    match args.conn.output {
+        format @ (OutputFormat::Json | OutputFormat::Yaml) => output::print(format, &contracts)?,
+        OutputFormat::Table if contracts.is_empty() => println!("No contracts found"),
+        OutputFormat::Table => {
+            let rows = contracts.iter().map(|c| ContractRow {
+                name: c.name.clone(),
+                version: c.version.clone(),
+                created_at: c.created_at.clone(),
+                description: c.description.clone().unwrap_or_default(),
+            });
+            let mut table = Table::new(rows);
+            table.with(Style::rounded());
+            println!("{}", table);
+        }
+    }
+    Ok(())
+}
+
+async fn diff(args: DiffArgs) -> Result<()> {
+    let before = fetch_bundle(&args.conn, &args.name, &args.from)
+        .await?
+        .schema()?;
+    let after = match (&args.to, &args.file) {
+        (Some(to), _) => fetch_bundle(&args.conn, &args.name, to).await?.schema()?,
+        (None, Some(file)) => {
+            let raw = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read schema file {}", file.display()))?;
+            serde_json::from_str(&raw)
+                .with_context(|| format!("{} is not valid JSON", file.display()))?
+        }
+        (None, None) => bail!("Pass a version to compare to or --file"),
+    };
+
+    let changes = diff_schemas(&before, &after);
+
+    match args.conn.output {
+        format @ (OutputFormat::Json | OutputFormat::Yaml) => output::print(format, &changes)?,
+        OutputFormat::Table if changes.is_empty() => println!("Schemas are identical"),
+        OutputFormat::Table => {
+            let show = |v: &Option<Value>| v.as_ref().map(Value::to_string).unwrap_or_default();
+            let rows = changes.iter().map(|c| ChangeRow {
+                kind: c.kind.to_string(),
+                path: c.path.clone(),
+                before: show(&c.before),
+                after: show(&c.after),
+            });
+            let mut table = Table::new(rows);
+            table.with(Style::rounded());
+            println!("{}", table);
+        }
+    }
+    Ok(())
+}
+
+fn base_url(conn: &ConnectionArgs) -> &str {
+    conn.registry_url.trim_end_matches('/')
+}
+
+async fn auth_headers(jwt: Option<&str>) -> Result<HeaderMap> {
+    let jwt = crate::credentials::resolve_token(jwt)
+        .await?
+        .context("JWT token required: pass --jwt, set DEMONCTL_JWT or run demonctl login")?;
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(&format!("Bearer {}", jwt)).context("Invalid JWT token format")?,
+    );
+    Ok(headers)
+}
+
+async fn fetch_bundle(conn: &ConnectionArgs, name: &str, version: &str) -> Result<ContractBundle> {
+    fetch_bundle_from(base_url(conn), conn.jwt.as_deref(), name, version).await
+}
+
+/// One contract version from the registry at `registry_url`, digest-checked
+pub async fn fetch_bundle_from(
+    registry_url: &str,
+    jwt: Option<&str>,
+    name: &str,
+    version: &str,
+) -> Result<ContractBundle> {
+    let url = format!(
+        "{}/registry/contracts/{}/{}",
+        registry_url.trim_end_matches('/'),
+        name,
+        version
+    );
+    let response = reqwest::Client::new()
+        .get(&url)
+        .headers(auth_headers(jwt).await?)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach registry at {}", registry_url))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        bail!("Contract {} v{} not found", name, version);
+    }
+    let bundle: ContractBundle = check(response)
+        .await?
+        .json()
+        .await
+        .context("Failed to parse registry contract bundle")?;
+    bundle.verify_schema_digest()?;
+    Ok(bundle)
+}
+
+/// Every contract version published to the registry at `registry_url`
+pub async fn fetch_contracts(
+    registry_url: &str,
+    jwt: Option<&str>,
+) -> Result<Vec<ContractMetadata>> {
+    let url = format!("{}/registry/contracts", registry_url.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .get(&url)
+        .headers(auth_headers(jwt).await?)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach registry at {}", registry_url))?;
+    let body: Value = check(response).await?.json().await?;
+    serde_json::from_value(body.get("contracts").cloned().unwrap_or(json!([])))
+        .context("Failed to parse registry contract list")
+}
+
+/// Turn a non-2xx registry response into an error carrying its message
+async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let message = response.text().await.unwrap_or_default();
+    // Schemas rejected by the registry's rules come back as JSON findings
+    if let Ok(body) = serde_json::from_str::<Value>(&message) {
+        if let Some(findings) = body.get("findings").and_then(Value::as_array) {
+            let lines: Vec<String> = findings
+                .iter()
+                .map(|f| {
+                    let path = f["path"].as_str().filter(|p| !p.is_empty()).unwrap_or("/");
+                    format!(
+                        "[{}] {}: {}",
+                        f["rule"].as_str().unwrap_or_default(),
+                        path,
+                        f["message"].as_str().unwrap_or_default()
+                    )
+                })
+                .collect();
+            bail!(
+                "Registry returned {}: {}\n  {}",
+                status,
+                body["error"].as_str().unwrap_or_default(),
+                lines.join("\n  ")
+            );
+        }
+    }
+    bail!("Registry returned {}: {}", status, message)
+}
+
+/// Structural diff of two JSON documents, one entry per differing leaf.
+/// Arrays are compared as whole values.
+pub fn diff_schemas(before: &Value, after: &Value) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    diff_at("", before, after, &mut changes);
+    changes
+}
+
+fn diff_at(path: &str, before: &Value, after: &Value, changes: &mut Vec<SchemaChange>) {
+    match (before, after) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (old.get(key), new.get(key)) {
+                    (Some(o), Some(n)) => diff_at(&child, o, n, changes),
+                    (Some(o), None) => changes.push(SchemaChange {
+                        kind: ChangeKind::Removed,
+                        path: child,
+                        before: Some(o.clone()),
+                        after: None,
+                    }),
+                    (None, Some(n)) => changes.push(SchemaChange {
+                        kind: ChangeKind::Added,
+                        path: child,
+                        before: None,
+                        after: Some(n.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if before != after => changes.push(SchemaChange {
+            kind: ChangeKind::Changed,
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+            before: Some(before.clone()),
+            after: Some(after.clone()),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_paths_in_order() {
+        let before = json!({
+            "type": "object",
+            "required": ["id"],
+            "properties": {
+                "id": { "type": "string" },
+                "legacy": { "type": "boolean" }
+            }
+        });
+        let after = json!({
+            "type": "object",
+            "required": ["id", "ts"],
+            "properties": {
+                "id": { "type": "integer" },
+                "ts": { "type": "string", "format": "date-time" }
+            }
+        });
+
+        let changes = diff_schemas(&before, &after);
+
+        let summary: Vec<(ChangeKind, &str)> =
+            changes.iter().map(|c| (c.kind, c.path.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Changed, "/properties/id/type"),
+                (ChangeKind::Removed, "/properties/legacy"),
+                (ChangeKind::Added, "/properties/ts"),
+                (ChangeKind::Changed, "/required"),
+            ]
+        );
+        assert_eq!(changes[0].before, Some(json!("string")));
+        assert_eq!(changes[0].after, Some(json!("integer")));
+    }
+
+    #[test]
+    fn diff_escapes_pointer_segments_and_handles_root_changes() {
+        let changes = diff_schemas(&json!({ "a/b": 1 }), &json!({ "a/b": 2 }));
+        assert_eq!(changes[0].path, "/a~1b");
+
+        let changes = diff_schemas(&json!(true), &json!(false));
+        assert_eq!(changes[0].path, "/");
+
+        assert!(diff_schemas(&json!({ "x": [1] }), &json!({ "x": [1] })).is_empty());
+    }
+
+    #[test]
+    fn verify_schema_digest_rejects_tampered_body() {
+        let mut bundle: ContractBundle = serde_json::from_value(json!({
+            "name": "ritual.started",
+            "version": "1.0.0",
+            "description": null,
+            "createdAt": "2024-01-01T00:00:00Z",
+            "jsonSchema": "{}",
+            "witPath": null,
+            "descriptorPath": null,
+            "schemaDigest": "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        }))
+        .unwrap();
+        assert!(bundle.verify_schema_digest().is_ok());
+
+        bundle.json_schema = Some(r#"{"type":"string"}"#.to_string());
+        let err = bundle.verify_schema_digest().unwrap_err();
+        assert!(err.to_string().contains("does not match its digest"));
+
+        bundle.schema_digest = None;
+        assert!(bundle.verify_schema_digest().is_ok());
+    }
+}
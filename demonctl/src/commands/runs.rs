@@ -0,0 +1,994 @@
+//! Runs command - list and inspect ritual runs
+//!
+//! Reads from the Operate UI JSON API by default, or straight from the ritual
+//! event stream with `--nats` when the UI is not reachable. `runs tail`
+//! follows a run's events on the stream as they happen, and `run --watch`
+//! uses the same output for a run started by demonctl. `runs dlq` works on
+//! the `RITUAL_DLQ` stream of messages the engine gave up on.
+
+use crate::output::{self, OutputFormat};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use engine::rituals::dlq;
+use engine::rituals::log::EventLog;
+use futures_util::{Stream, StreamExt};
+use owo_colors::OwoColorize;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tabled::{settings::style::Style, Table, Tabled};
+use tokio::sync::oneshot;
+
+/// After a watched run returns, how long the event tail may stay quiet
+/// before it is considered caught up
+const WATCH_DRAIN: Duration = Duration::from_millis(500);
+
+#[derive(Args, Debug)]
+pub struct RunsArgs {
+    #[command(subcommand)]
+    pub cmd: RunsCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RunsCommand {
+    /// List recent runs, newest first
+    List(ListArgs),
+    /// Show a run's status and event timeline
+    Show(ShowArgs),
+    /// Print a run's events as they happen and exit with its final status
+    Tail(TailArgs),
+    /// List or requeue dead-lettered messages
+    Dlq(DlqArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct DlqArgs {
+    #[command(subcommand)]
+    pub cmd: DlqCommand,
+
+    /// NATS URL of the JetStream server holding RITUAL_DLQ
+    #[arg(
+        long,
+        env = "NATS_URL",
+        default_value = "nats://localhost:4222",
+        global = true
+    )]
+    pub nats_url: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DlqCommand {
+    /// List dead-lettered messages, oldest first
+    List(DlqListArgs),
+    /// Republish dead-lettered messages to their original subject
+    Requeue(DlqRequeueArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct DlqListArgs {
+    /// Only messages given up on by this consumer
+    #[arg(long)]
+    pub consumer: Option<String>,
+
+    /// Output format
+    #[arg(long, short = 'o', value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct DlqRequeueArgs {
+    /// RITUAL_DLQ sequence numbers, as shown by `runs dlq list`
+    #[arg(value_name = "SEQUENCE", required = true)]
+    pub sequences: Vec<u64>,
+}
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// Tenant whose runs to list
+    #[arg(long, env = "DEMON_TENANT", default_value = "default")]
+    pub tenant: String,
+
+    /// Only runs whose ritual id contains this text
+    #[arg(long)]
+    pub ritual: Option<String>,
+
+    /// Only runs in this status
+    #[arg(long, value_enum)]
+    pub status: Option<RunStatus>,
+
+    /// Only runs started at or after this RFC 3339 time or this long ago (e.g. 2h, 1d)
+    #[arg(long, value_parser = parse_since)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Maximum number of runs (1-1000)
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u16).range(1..=1000))]
+    pub limit: u16,
+
+    #[command(flatten)]
+    pub source: SourceArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ShowArgs {
+    /// Run to show
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Tenant that owns the run
+    #[arg(long, env = "DEMON_TENANT", default_value = "default")]
+    pub tenant: String,
+
+    /// Print only the run's events
+    #[arg(long, conflicts_with = "envelope")]
+    pub events: bool,
+
+    /// Print only the completion envelope (`ritual.completed:v1` outputs)
+    #[arg(long)]
+    pub envelope: bool,
+
+    #[command(flatten)]
+    pub source: SourceArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct TailArgs {
+    /// Run to follow
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Only events published under this tenant (default: any tenant)
+    #[arg(long)]
+    pub tenant: Option<String>,
+
+    /// NATS URL of the JetStream server holding the ritual events
+    #[arg(long, env = "NATS_URL", default_value = "nats://localhost:4222")]
+    pub nats_url: String,
+
+    /// Output format; json and yaml print one document per event
+    #[arg(long, short = 'o', value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+}
+
+/// Where run data comes from and how it is printed
+#[derive(Args, Debug)]
+pub struct SourceArgs {
+    /// Operate UI base URL
+    #[arg(
+        long,
+        env = "DEMONCTL_API_URL",
+        default_value = "http://localhost:3000"
+    )]
+    pub api_url: String,
+
+    /// JWT token for the Operate UI API (defaults to the saved login)
+    #[arg(long, env = "DEMONCTL_JWT")]
+    pub jwt: Option<String>,
+
+    /// Read the ritual event stream directly instead of the Operate UI API
+    #[arg(long)]
+    pub nats: bool,
+
+    /// NATS URL used with --nats
+    #[arg(long, env = "NATS_URL", default_value = "nats://localhost:4222")]
+    pub nats_url: String,
+
+    /// Output format
+    #[arg(long, short = 'o', value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+}
+
+/// Run status as reported by the Operate UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Failed,
+    Canceled,
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RunStatus::Running => "Running",
+            RunStatus::Completed => "Completed",
+            RunStatus::Failed => "Failed",
+            RunStatus::Canceled => "Canceled",
+        };
+        f.write_str(s)
+    }
+}
+
+impl RunStatus {
+    /// Status implied by a single event, if it settles the run
+    fn from_event(event: &str) -> Option<Self> {
+        match event {
+            "ritual.completed:v1" => Some(RunStatus::Completed),
+            "ritual.failed:v1" => Some(RunStatus::Failed),
+            "run.canceled:v1" => Some(RunStatus::Canceled),
+            _ => None,
+        }
+    }
+}
+
+/// Same shape as the Operate UI's `/api/tenants/:tenant/runs` items
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSummary {
+    pub run_id: String,
+    pub ritual_id: String,
+    pub start_ts: DateTime<Utc>,
+    pub status: RunStatus,
+}
+
+/// Body of the Operate UI's `/api/tenants/:tenant/runs`
+#[derive(Debug, Deserialize)]
+struct RunList {
+    runs: Vec<RunSummary>,
+}
+
+/// Same shape as the Operate UI's `/api/tenants/:tenant/runs/:run_id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunDetail {
+    pub run_id: String,
+    pub ritual_id: String,
+    pub events: Vec<Value>,
+}
+
+impl RunDetail {
+    pub fn status(&self) -> RunStatus {
+        self.events
+            .iter()
+            .filter_map(|e| RunStatus::from_event(event_name(e)))
+            .next_back()
+            .unwrap_or(RunStatus::Running)
+    }
+
+    pub fn completion(&self) -> Option<&Value> {
+        self.events
+            .iter()
+            .rev()
+            .find(|e| event_name(e) == "ritual.completed:v1")
+    }
+}
+
+#[derive(Debug, Tabled)]
+struct RunRow {
+    #[tabled(rename = "RUN ID")]
+    run_id: String,
+    #[tabled(rename = "RITUAL")]
+    ritual_id: String,
+    #[tabled(rename = "STATUS")]
+    status: String,
+    #[tabled(rename = "STARTED")]
+    started: String,
+}
+
+#[derive(Debug, Tabled)]
+struct DeadLetterRow {
+    #[tabled(rename = "SEQ")]
+    sequence: u64,
+    #[tabled(rename = "CONSUMER")]
+    consumer: String,
+    #[tabled(rename = "SUBJECT")]
+    subject: String,
+    #[tabled(rename = "DELIVERIES")]
+    deliveries: u64,
+    #[tabled(rename = "FAILED")]
+    failed_at: String,
+    #[tabled(rename = "REASON")]
+    reason: String,
+}
+
+#[derive(Debug, Tabled)]
+struct EventRow {
+    #[tabled(rename = "TIME")]
+    ts: String,
+    #[tabled(rename = "EVENT")]
+    event: String,
+    #[tabled(rename = "DETAIL")]
+    detail: String,
+}
+
+pub async fn run(args: RunsArgs) -> Result<()> {
+    match args.cmd {
+        RunsCommand::List(args) => list(args).await,
+        RunsCommand::Show(args) => show(args).await,
+        RunsCommand::Tail(args) => tail(args).await,
+        RunsCommand::Dlq(args) => dead_letters(args).await,
+    }
+}
+
+async fn list(args: ListArgs) -> Result<()> {
+    let mut runs = if args.source.nats {
+        let log = engine::rituals::log::EventLog::new(&args.source.nats_url).await?;
+        summarize(&log.read_tenant_events(&args.tenant).await?)
+    } else {
+        // The API cannot filter by start time, so widen the window for --since
+        let limit = if args.since.is_some() {
+            1000
+        } else {
+            args.limit
+        };
+        let mut query = vec![("limit", limit.to_string())];
+        if let Some(ritual) = &args.ritual {
+            query.push(("ritual", ritual.clone()));
+        }
+        if let Some(status) = args.status {
+            query.push(("status", status.to_string()));
+        }
+        let url = format!(
+            "{}/api/tenants/{}/runs",
+            args.source.api_url.trim_end_matches('/'),
+            args.tenant
+        );
+        api_get::<RunList>(&args.source, &url, &query)
+            .await?
+            .context("Operate UI returned no run list")?
+            .runs
+    };
+
+    filter_runs(&mut runs, &args);
+
+    match args.source.output {
+        format @ (OutputFormat::Json | OutputFormat::Yaml) => output::print(format, &runs)?,
+        OutputFormat::Table if runs.is_empty() => println!("No runs found"),
+        OutputFormat::Table => {
+            let rows = runs.iter().map(|r| RunRow {
+                run_id: r.run_id.clone(),
+                ritual_id: r.ritual_id.clone(),
+                status: r.status.to_string(),
+                started: r.start_ts.to_rfc3339(),
+            });
+            let mut table = Table::new(rows);
+            table.with(Style::rounded());
+            println!("{}", table);
+        }
+    }
+    Ok(())
+}
+
+async fn show(args: ShowArgs) -> Result<()> {
+    let detail = if args.source.nats {
+        let log = engine::rituals::log::EventLog::new(&args.source.nats_url).await?;
+        // Omitting the default tenant also searches legacy subjects
+        let tenant = (args.tenant != "default").then_some(args.tenant.as_str());
+        let events = log.read_run_events("*", &args.run_id, tenant).await?;
+        if events.is_empty() {
+            None
+        } else {
+            Some(RunDetail {
+                run_id: args.run_id.clone(),
+                ritual_id: events
+                    .iter()
+                    .find_map(|e| e.get("ritualId").and_then(|v| v.as_str()))
+                    .unwrap_or_default()
+                    .to_string(),
+                events,
+            })
+        }
+    } else {
+        let url = format!(
+            "{}/api/tenants/{}/runs/{}",
+            args.source.api_url.trim_end_matches('/'),
+            args.tenant,
+            args.run_id
+        );
+        api_get(&args.source, &url, &[]).await?
+    };
+    let Some(detail) = detail else {
+        bail!(
+            "Run '{}' not found for tenant '{}'",
+            args.run_id,
+            args.tenant
+        );
+    };
+
+    if args.envelope {
+        let completion = detail
+            .completion()
+            .with_context(|| format!("Run '{}' has not completed", detail.run_id))?;
+        let envelope = completion.get("outputs").cloned().unwrap_or(Value::Null);
+        println!("{}", serde_json::to_string_pretty(&envelope)?);
+        return Ok(());
+    }
+
+    match (args.source.output, args.events) {
+        (format @ (OutputFormat::Json | OutputFormat::Yaml), true) => {
+            output::print(format, &detail.events)?
+        }
+        (format @ (OutputFormat::Json | OutputFormat::Yaml), false) => {
+            let mut out = serde_json::to_value(&detail)?;
+            out["tenantId"] = Value::from(args.tenant.as_str());
+            out["status"] = serde_json::to_value(detail.status())?;
+            if let Some(reason) = detail.completion().and_then(|c| c.get("reason")) {
+                out["reason"] = reason.clone();
+            }
+            output::print(format, &out)?;
+        }
+        (OutputFormat::Table, events_only) => {
+            if !events_only {
+                println!("Run:     {}", detail.run_id);
+                println!("Ritual:  {}", detail.ritual_id);
+                println!("Tenant:  {}", args.tenant);
+                println!("Status:  {}", detail.status());
+                if let Some(reason) = detail
+                    .completion()
+                    .and_then(|c| c.get("reason"))
+                    .and_then(|r| r.as_str())
+                {
+                    println!("Reason:  {}", reason);
+                }
+                println!();
+            }
+            let rows = detail.events.iter().map(|e| EventRow {
+                ts: e
+                    .get("ts")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                event: event_name(e).to_string(),
+                detail: event_detail(e),
+            });
+            let mut table = Table::new(rows);
+            table.with(Style::rounded());
+            println!("{}", table);
+        }
+    }
+    Ok(())
+}
+
+async fn tail(args: TailArgs) -> Result<()> {
+    let log = EventLog::new(&args.nats_url).await?;
+    let mut events = log.follow_run(args.tenant.as_deref(), &args.run_id).await?;
+    let color = use_color(args.output);
+    while let Some(event) = events.next().await {
+        let event = event?;
+        print_event(&event, args.output, color)?;
+        if is_terminal(&event) {
+            if args.output.is_table() {
+                print_diagnostics(&event, color);
+            }
+            let code = exit_code(&event);
+            if code != 0 {
+                std::process::exit(code);
+            }
+            return Ok(());
+        }
+    }
+    bail!(
+        "Event stream for run '{}' ended before the run finished",
+        args.run_id
+    )
+}
+
+/// Execute `run`, which starts a run with id `run_id`, printing the run's
+/// events as they are published and then its outcome. Returns the completion
+/// event and the exit code for the run's final status. Without NATS only the
+/// outcome is printed.
+pub async fn watch(run_id: &str, run: impl Future<Output = Result<Value>>) -> Result<(Value, i32)> {
+    let color = use_color(OutputFormat::Table);
+    let nats_url =
+        std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let events = match EventLog::new(&nats_url).await {
+        Ok(log) => log.follow_run(None, run_id).await,
+        Err(e) => Err(e),
+    };
+    let (finished, finished_rx) = oneshot::channel();
+    let printer = match events {
+        Ok(events) => Some(tokio::spawn(print_live(events, finished_rx, color))),
+        Err(e) => {
+            eprintln!(
+                "Warning: not following run events ({:#}); only the outcome will be shown",
+                e
+            );
+            None
+        }
+    };
+
+    let completion = run.await;
+    let _ = finished.send(());
+    if let Some(printer) = printer {
+        let _ = printer.await;
+    }
+    let completion = completion?;
+
+    print_event(&completion, OutputFormat::Table, color)?;
+    print_diagnostics(&completion, color);
+    Ok((completion.clone(), exit_code(&completion)))
+}
+
+/// Print events until the run has finished and the stream has gone quiet
+async fn print_live(
+    mut events: impl Stream<Item = Result<Value>> + Unpin,
+    mut finished: oneshot::Receiver<()>,
+    color: bool,
+) {
+    let mut draining = false;
+    loop {
+        let next = if draining {
+            match tokio::time::timeout(WATCH_DRAIN, events.next()).await {
+                Ok(next) => next,
+                Err(_) => break,
+            }
+        } else {
+            tokio::select! {
+                next = events.next() => next,
+                _ = &mut finished => {
+                    draining = true;
+                    continue;
+                }
+            }
+        };
+        match next {
+            // The watched run's outcome is printed from its return value
+            Some(Ok(event)) if is_terminal(&event) => {}
+            Some(Ok(event)) => {
+                let _ = print_event(&event, OutputFormat::Table, color);
+            }
+            Some(Err(e)) => {
+                eprintln!("Warning: stopped following run events: {:#}", e);
+                break;
+            }
+            None => break,
+        }
+    }
+}
+
+async fn dead_letters(args: DlqArgs) -> Result<()> {
+    let client = async_nats::connect(&args.nats_url)
+        .await
+        .with_context(|| format!("Failed to connect to NATS at {}", args.nats_url))?;
+    let js = async_nats::jetstream::new(client);
+
+    match args.cmd {
+        DlqCommand::List(list) => {
+            let letters = dlq::list(&js, list.consumer.as_deref()).await?;
+            match list.output {
+                format @ (OutputFormat::Json | OutputFormat::Yaml) => {
+                    output::print(format, &letters)?
+                }
+                OutputFormat::Table if letters.is_empty() => println!("No dead letters"),
+                OutputFormat::Table => {
+                    let rows = letters.iter().map(|l| DeadLetterRow {
+                        sequence: l.sequence,
+                        consumer: l.consumer.clone(),
+                        subject: l.source_subject.clone(),
+                        deliveries: l.deliveries,
+                        failed_at: l.failed_at.clone(),
+                        reason: l.reason.clone(),
+                    });
+                    let mut table = Table::new(rows);
+                    table.with(Style::rounded());
+                    println!("{}", table);
+                }
+            }
+        }
+        DlqCommand::Requeue(requeue) => {
+            for sequence in requeue.sequences {
+                let letter = dlq::requeue(&js, sequence).await?;
+                println!(
+                    "Requeued dead letter {} to {}",
+                    sequence, letter.source_subject
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// GET a JSON document from the Operate UI; `None` on 404
+async fn api_get<T: serde::de::DeserializeOwned>(
+    source: &SourceArgs,
+    url: &str,
+    query: &[(&str, String)],
+) -> Result<Option<T>> {
+    let mut headers = HeaderMap::new();
+    if let Some(jwt) = crate::credentials::resolve_token(source.jwt.as_deref()).await? {
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", jwt))
+                .context("Invalid JWT token format")?,
+        );
+    }
+    let response = reqwest::Client::new()
+        .get(url)
+        .headers(headers)
+        .query(query)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach Operate UI at {}", source.api_url))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        let message = body
+            .get("error")
+            .and_then(|e| e.as_str())
+            .unwrap_or("no error message");
+        bail!("Operate UI returned {}: {}", status, message);
+    }
+    Ok(Some(
+        response
+            .json()
+            .await
+            .context("Failed to parse Operate UI response")?,
+    ))
+}
+
+/// Fold raw ritual events into one summary per run, newest first. The start
+/// time is the `ritual.started:v1` timestamp, else the earliest event seen.
+pub fn summarize(events: &[Value]) -> Vec<RunSummary> {
+    let mut runs: HashMap<(String, String), (RunSummary, bool)> = HashMap::new();
+    for event in events {
+        let field = |name: &str| event.get(name).and_then(|v| v.as_str());
+        let (Some(ritual_id), Some(run_id)) = (field("ritualId"), field("runId")) else {
+            continue;
+        };
+        let ts = field("ts")
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|ts| ts.with_timezone(&Utc));
+        let name = event_name(event);
+
+        let (summary, started) = runs
+            .entry((ritual_id.to_string(), run_id.to_string()))
+            .or_insert_with(|| {
+                (
+                    RunSummary {
+                        run_id: run_id.to_string(),
+                        ritual_id: ritual_id.to_string(),
+                        start_ts: ts.unwrap_or(DateTime::<Utc>::MAX_UTC),
+                        status: RunStatus::Running,
+                    },
+                    false,
+                )
+            });
+        if let Some(ts) = ts {
+            if name == "ritual.started:v1" && !*started {
+                summary.start_ts = ts;
+                *started = true;
+            } else if !*started && ts < summary.start_ts {
+                summary.start_ts = ts;
+            }
+        }
+        if let Some(status) = RunStatus::from_event(name) {
+            summary.status = status;
+        }
+    }
+
+    let mut runs: Vec<RunSummary> = runs.into_values().map(|(summary, _)| summary).collect();
+    runs.sort_by_key(|r| std::cmp::Reverse(r.start_ts));
+    runs
+}
+
+/// Apply the filters the API cannot: `--since`, and all of them for `--nats`
+fn filter_runs(runs: &mut Vec<RunSummary>, args: &ListArgs) {
+    if let Some(ritual) = &args.ritual {
+        let needle = ritual.to_ascii_lowercase();
+        runs.retain(|r| r.ritual_id.to_ascii_lowercase().contains(&needle));
+    }
+    if let Some(status) = args.status {
+        runs.retain(|r| r.status == status);
+    }
+    if let Some(since) = args.since {
+        runs.retain(|r| r.start_ts >= since);
+    }
+    runs.truncate(args.limit as usize);
+}
+
+/// `--since` accepts an RFC 3339 timestamp or a duration before now
+pub fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    let ago = humantime::parse_duration(value).with_context(|| {
+        format!(
+            "invalid --since '{}': expected an RFC 3339 time or a duration like 2h",
+            value
+        )
+    })?;
+    Ok(Utc::now() - chrono::Duration::from_std(ago)?)
+}
+
+fn event_name(event: &Value) -> &str {
+    event
+        .get("event")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+}
+
+/// Whether an event ends its run
+fn is_terminal(event: &Value) -> bool {
+    let name = event_name(event);
+    RunStatus::from_event(name).is_some() || name == "run.abandoned:v1"
+}
+
+/// Process exit status for a run's terminal event: 0 when it completed,
+/// 2 when it was canceled, 1 for any other ending (a halted step, failure or
+/// abandonment)
+pub fn exit_code(terminal: &Value) -> i32 {
+    let reason = terminal.get("reason").and_then(|r| r.as_str());
+    match (event_name(terminal), reason) {
+        ("ritual.completed:v1", None) => 0,
+        ("run.canceled:v1", _) | ("ritual.completed:v1", Some("canceled")) => 2,
+        _ => 1,
+    }
+}
+
+/// Honour `NO_COLOR` and only color a terminal
+fn use_color(format: OutputFormat) -> bool {
+    format.is_table() && std::env::var_os("NO_COLOR").is_none() && atty::is(atty::Stream::Stdout)
+}
+
+/// How an event reads at a glance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tone {
+    Plain,
+    Progress,
+    Good,
+    Attention,
+    Bad,
+}
+
+fn tone(event: &Value) -> Tone {
+    let field = |name: &str| event.get(name).and_then(|v| v.as_str());
+    match event_name(event) {
+        "ritual.started:v1" | "step.started:v1" => Tone::Progress,
+        "step.completed:v1" => match field("outcome") {
+            Some("succeeded") => Tone::Good,
+            Some("halted") => Tone::Attention,
+            _ => Tone::Bad,
+        },
+        "ritual.completed:v1" if field("reason").is_none() => Tone::Good,
+        "approval.granted:v1" => Tone::Good,
+        "step.retried:v1"
+        | "approval.requested:v1"
+        | "run.cancel.requested:v1"
+        | "run.canceled:v1" => Tone::Attention,
+        "ritual.completed:v1"
+        | "ritual.failed:v1"
+        | "run.abandoned:v1"
+        | "step.timeout:v1"
+        | "approval.denied:v1" => Tone::Bad,
+        _ => Tone::Plain,
+    }
+}
+
+fn paint(text: &str, tone: Tone, color: bool) -> String {
+    if !color {
+        return text.to_string();
+    }
+    match tone {
+        Tone::Plain => text.to_string(),
+        Tone::Progress => text.cyan().to_string(),
+        Tone::Good => text.green().to_string(),
+        Tone::Attention => text.yellow().to_string(),
+        Tone::Bad => text.red().to_string(),
+    }
+}
+
+/// One line of live output: time, event name and what it concerns
+pub fn render_event(event: &Value, color: bool) -> String {
+    let time = event
+        .get("ts")
+        .and_then(|v| v.as_str())
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|ts| ts.with_timezone(&Utc).format("%H:%M:%S%.3f").to_string())
+        .unwrap_or_else(|| "--:--:--.---".to_string());
+    let name = format!("{:<24}", event_name(event));
+    let mut detail = event_detail(event);
+    let field = |name: &str| event.get(name).and_then(|v| v.as_str());
+    let mut extra = Vec::new();
+    if let (Some(from), Some(to)) = (field("from"), field("to")) {
+        extra.push(format!("{} → {}", from, to));
+    }
+    if let Some(attempt) = event.get("attempt").and_then(|v| v.as_u64()) {
+        if attempt > 1 {
+            extra.push(format!("attempt {}", attempt));
+        }
+    }
+    if let Some(outcome) = field("outcome") {
+        extra.push(outcome.to_string());
+    }
+    if let Some(ms) = event.get("durationMs").and_then(|v| v.as_u64()) {
+        extra.push(format!("in {}ms", ms));
+    }
+    if let Some(approver) = field("approver") {
+        extra.push(format!("by {}", approver));
+    }
+    for part in extra {
+        if !detail.is_empty() {
+            detail.push(' ');
+        }
+        detail.push_str(&part);
+    }
+    format!("{} {} {}", time, paint(&name, tone(event), color), detail)
+        .trim_end()
+        .to_string()
+}
+
+fn print_event(event: &Value, format: OutputFormat, color: bool) -> Result<()> {
+    match format {
+        OutputFormat::Table => println!("{}", render_event(event, color)),
+        OutputFormat::Json => println!("{}", serde_json::to_string(event)?),
+        OutputFormat::Yaml => print!("---\n{}", serde_yaml::to_string(event)?),
+    }
+    Ok(())
+}
+
+/// Diagnostics from the step envelopes of a completion, one line each
+pub fn completion_diagnostics(completion: &Value) -> Vec<(String, String, String)> {
+    let Some(steps) = completion
+        .pointer("/outputs/steps")
+        .and_then(|s| s.as_object())
+    else {
+        return Vec::new();
+    };
+    let mut lines = Vec::new();
+    for (step_id, envelope) in steps {
+        let Some(diagnostics) = envelope.get("diagnostics").and_then(|d| d.as_array()) else {
+            continue;
+        };
+        for diagnostic in diagnostics {
+            let text = |name: &str| {
+                diagnostic
+                    .get(name)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            lines.push((step_id.clone(), text("level"), text("message")));
+        }
+    }
+    lines
+}
+
+fn print_diagnostics(completion: &Value, color: bool) {
+    for (step_id, level, message) in completion_diagnostics(completion) {
+        let tone = match level.as_str() {
+            "error" | "fatal" => Tone::Bad,
+            "warning" | "warn" => Tone::Attention,
+            _ => Tone::Plain,
+        };
+        println!(
+            "  {} {}: {}",
+            paint(&format!("{:<7}", level), tone, color),
+            step_id,
+            message
+        );
+    }
+}
+
+/// The most useful identifying field of an event for the timeline
+fn event_detail(event: &Value) -> String {
+    let field = |name: &str| event.get(name).and_then(|v| v.as_str());
+    let mut parts = Vec::new();
+    if let Some(step) = field("stepId") {
+        parts.push(format!("step={}", step));
+    }
+    if let Some(gate) = field("gateId") {
+        parts.push(format!("gate={}", gate));
+    }
+    if let (Some(from), Some(to)) = (field("stateFrom"), field("stateTo")) {
+        parts.push(format!("{} → {}", from, to));
+    }
+    if let Some(reason) = field("reason").or_else(|| field("error")) {
+        parts.push(reason.to_string());
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(name: &str, run: &str, ts: &str) -> Value {
+        json!({ "event": name, "ritualId": "release", "runId": run, "ts": ts })
+    }
+
+    #[test]
+    fn summarize_folds_events_per_run_newest_first() {
+        let events = vec![
+            event("ritual.started:v1", "a", "2025-01-01T00:00:00Z"),
+            event("step.retried:v1", "b", "2025-01-02T00:00:05Z"),
+            event("ritual.started:v1", "b", "2025-01-02T00:00:00Z"),
+            event("ritual.completed:v1", "a", "2025-01-01T00:05:00Z"),
+            event("run.canceled:v1", "c", "2025-01-03T00:00:00Z"),
+        ];
+
+        let runs = summarize(&events);
+
+        let ids: Vec<&str> = runs.iter().map(|r| r.run_id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "b", "a"]);
+        assert_eq!(runs[0].status, RunStatus::Canceled);
+        assert_eq!(runs[1].status, RunStatus::Running);
+        assert_eq!(
+            runs[1].start_ts,
+            parse_since("2025-01-02T00:00:00Z").unwrap()
+        );
+        assert_eq!(runs[2].status, RunStatus::Completed);
+    }
+
+    #[test]
+    fn since_accepts_timestamps_and_durations() {
+        assert_eq!(
+            parse_since("2025-01-01T00:00:00+02:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2024-12-31T22:00:00+00:00"
+        );
+        let two_hours_ago = parse_since("2h").unwrap();
+        let delta = Utc::now() - two_hours_ago;
+        assert!((delta.num_minutes() - 120).abs() <= 1);
+        assert!(parse_since("yesterday-ish").is_err());
+    }
+
+    #[test]
+    fn detail_status_and_completion_follow_latest_terminal_event() {
+        let detail = RunDetail {
+            run_id: "a".into(),
+            ritual_id: "release".into(),
+            events: vec![
+                event("ritual.started:v1", "a", "2025-01-01T00:00:00Z"),
+                json!({ "event": "ritual.completed:v1", "ts": "2025-01-01T00:01:00Z", "outputs": { "steps": {} }, "reason": "step_failed" }),
+            ],
+        };
+        assert_eq!(detail.status(), RunStatus::Completed);
+        assert_eq!(detail.completion().unwrap()["reason"], "step_failed");
+        assert_eq!(event_detail(&detail.events[1]), "step_failed");
+    }
+
+    #[test]
+    fn exit_code_follows_terminal_event() {
+        let completed = json!({ "event": "ritual.completed:v1" });
+        let halted = json!({ "event": "ritual.completed:v1", "reason": "step_failed" });
+        let canceled = json!({ "event": "run.canceled:v1" });
+        let abandoned = json!({ "event": "run.abandoned:v1" });
+
+        assert_eq!(exit_code(&completed), 0);
+        assert_eq!(exit_code(&halted), 1);
+        assert_eq!(exit_code(&canceled), 2);
+        assert_eq!(exit_code(&abandoned), 1);
+        assert!(is_terminal(&abandoned));
+        assert!(!is_terminal(&json!({ "event": "step.started:v1" })));
+    }
+
+    #[test]
+    fn live_lines_and_diagnostics_render_without_color() {
+        let step = json!({
+            "event": "step.completed:v1",
+            "ts": "2025-01-01T10:00:01.250Z",
+            "stepId": "build",
+            "attempt": 2,
+            "outcome": "succeeded",
+            "durationMs": 120
+        });
+        assert_eq!(
+            render_event(&step, false),
+            "10:00:01.250 step.completed:v1        step=build attempt 2 succeeded in 120ms"
+        );
+
+        let completion = json!({
+            "event": "ritual.completed:v1",
+            "outputs": { "steps": { "build": { "diagnostics": [
+                { "level": "warning", "message": "cache miss" }
+            ] } } }
+        });
+        assert_eq!(
+            completion_diagnostics(&completion),
+            vec![(
+                "build".to_string(),
+                "warning".to_string(),
+                "cache miss".to_string()
+            )]
+        );
+    }
+}
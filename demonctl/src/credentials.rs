@@ -0,0 +1,509 @@
+//! Stored login credentials for demonctl
+//!
+//! `demonctl login` saves an access token (and, for the device-code flow, a
+//! refresh token) in the OS keychain, or in an encrypted file under the Demon
+//! home when no keychain is available. Commands that call the Operate UI or
+//! the Schema Registry resolve their bearer token through [`resolve_token`],
+//! which refreshes stored tokens shortly before they expire.
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+const KEYCHAIN_SERVICE: &str = "demonctl";
+const KEYCHAIN_ACCOUNT: &str = "default";
+const CREDENTIALS_FILE: &str = "credentials.enc";
+const KEY_FILE: &str = "credentials.key";
+const PASSPHRASE_ENV: &str = "DEMONCTL_CREDENTIALS_KEY";
+/// Credentials file format; version 1 hashed the passphrase with unsalted SHA-256
+const FILE_VERSION: u8 = 2;
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LEN: usize = 16;
+
+/// Tokens are refreshed when they expire within this window
+const REFRESH_SKEW_SECONDS: i64 = 60;
+
+/// A saved login
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Credentials {
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Token endpoint used to refresh, recorded by the device-code flow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+}
+
+impl Credentials {
+    /// Wrap a token pasted by the user; expiry comes from its `exp` claim
+    pub fn from_token(token: &str) -> Self {
+        Self {
+            access_token: token.to_string(),
+            refresh_token: None,
+            expires_at: jwt_expiry(token),
+            token_endpoint: None,
+            client_id: None,
+        }
+    }
+
+    pub fn expires_within(&self, window: Duration) -> bool {
+        self.expires_at
+            .map(|at| at <= Utc::now() + window)
+            .unwrap_or(false)
+    }
+
+    pub fn can_refresh(&self) -> bool {
+        self.refresh_token.is_some() && self.token_endpoint.is_some() && self.client_id.is_some()
+    }
+}
+
+/// Where credentials are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StoreKind {
+    /// OS keychain, falling back to the encrypted file
+    Auto,
+    /// OS keychain only
+    Keychain,
+    /// Encrypted file under the Demon home only
+    File,
+}
+
+impl std::fmt::Display for StoreKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            StoreKind::Auto => "auto",
+            StoreKind::Keychain => "keychain",
+            StoreKind::File => "file",
+        };
+        f.write_str(s)
+    }
+}
+
+impl StoreKind {
+    /// Store selected by `DEMONCTL_CREDENTIAL_STORE`, defaulting to auto
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("DEMONCTL_CREDENTIAL_STORE") {
+            Ok(value) => StoreKind::from_str(&value, true).map_err(|_| {
+                anyhow::anyhow!(
+                    "Invalid DEMONCTL_CREDENTIAL_STORE '{}': expected auto, keychain or file",
+                    value
+                )
+            }),
+            Err(_) => Ok(StoreKind::Auto),
+        }
+    }
+}
+
+/// Save credentials, returning the store that took them
+pub fn save(credentials: &Credentials, kind: StoreKind) -> Result<StoreKind> {
+    let json = serde_json::to_string(credentials)?;
+    match kind {
+        StoreKind::Keychain => {
+            keychain_entry()?
+                .set_password(&json)
+                .context("Failed to write credentials to the OS keychain")?;
+            Ok(StoreKind::Keychain)
+        }
+        StoreKind::File => {
+            write_encrypted(&json)?;
+            Ok(StoreKind::File)
+        }
+        StoreKind::Auto => match keychain_entry().and_then(|e| Ok(e.set_password(&json)?)) {
+            Ok(()) => {
+                // A stale file would shadow nothing, but keep one copy only
+                let _ = std::fs::remove_file(demon_home()?.join(CREDENTIALS_FILE));
+                Ok(StoreKind::Keychain)
+            }
+            Err(e) => {
+                tracing::debug!("OS keychain unavailable ({}); using encrypted file", e);
+                write_encrypted(&json)?;
+                Ok(StoreKind::File)
+            }
+        },
+    }
+}
+
+/// Load saved credentials and the store they came from
+pub fn load(kind: StoreKind) -> Result<Option<(Credentials, StoreKind)>> {
+    if matches!(kind, StoreKind::Auto | StoreKind::Keychain) {
+        let stored = keychain_entry().and_then(|e| match e.get_password() {
+            Ok(json) => Ok(Some(json)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        });
+        match stored {
+            Ok(Some(json)) => {
+                let credentials = serde_json::from_str(&json)
+                    .context("Credentials in the OS keychain are corrupt; run demonctl login")?;
+                return Ok(Some((credentials, StoreKind::Keychain)));
+            }
+            Ok(None) => {}
+            Err(e) if kind == StoreKind::Keychain => {
+                return Err(e.context("Failed to read credentials from the OS keychain"))
+            }
+            Err(e) => tracing::debug!("OS keychain unavailable ({}); trying encrypted file", e),
+        }
+        if kind == StoreKind::Keychain {
+            return Ok(None);
+        }
+    }
+
+    match read_encrypted()? {
+        Some(json) => {
+            let credentials = serde_json::from_str(&json)
+                .context("Stored credentials are corrupt; run demonctl login")?;
+            Ok(Some((credentials, StoreKind::File)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Remove saved credentials from the selected stores; true if anything was removed
+pub fn clear(kind: StoreKind) -> Result<bool> {
+    let mut removed = false;
+    if kind != StoreKind::File {
+        if let Ok(entry) = keychain_entry() {
+            match entry.delete_password() {
+                Ok(()) => removed = true,
+                Err(keyring::Error::NoEntry) => {}
+                Err(e) => tracing::debug!("Could not clear OS keychain entry: {}", e),
+            }
+        }
+    }
+    let path = demon_home()?.join(CREDENTIALS_FILE);
+    if kind != StoreKind::Keychain && path.exists() {
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+        removed = true;
+    }
+    Ok(removed)
+}
+
+/// Bearer token for API calls: an explicit `--jwt`/env token wins, otherwise
+/// the saved login, refreshed first when it is about to expire.
+pub async fn resolve_token(explicit: Option<&str>) -> Result<Option<String>> {
+    if let Some(token) = explicit {
+        return Ok(Some(token.to_string()));
+    }
+
+    let Some((credentials, store)) = load(StoreKind::from_env()?)? else {
+        return Ok(None);
+    };
+    if !credentials.expires_within(Duration::seconds(REFRESH_SKEW_SECONDS)) {
+        return Ok(Some(credentials.access_token));
+    }
+    if !credentials.can_refresh() {
+        if credentials.expires_within(Duration::zero()) {
+            bail!("Saved login has expired; run demonctl login");
+        }
+        return Ok(Some(credentials.access_token));
+    }
+
+    let refreshed = refresh(&credentials).await?;
+    save(&refreshed, store)?;
+    Ok(Some(refreshed.access_token))
+}
+
+/// Token endpoint response (RFC 6749 section 5.1)
+#[derive(Debug, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub expires_in: Option<i64>,
+}
+
+impl TokenResponse {
+    /// Turn a token response into credentials that can refresh themselves
+    pub fn into_credentials(
+        self,
+        token_endpoint: &str,
+        client_id: &str,
+        previous_refresh: Option<String>,
+    ) -> Credentials {
+        let expires_at = self
+            .expires_in
+            .map(|secs| Utc::now() + Duration::seconds(secs))
+            .or_else(|| jwt_expiry(&self.access_token));
+        Credentials {
+            access_token: self.access_token,
+            // Servers may omit the refresh token when they do not rotate it
+            refresh_token: self.refresh_token.or(previous_refresh),
+            expires_at,
+            token_endpoint: Some(token_endpoint.to_string()),
+            client_id: Some(client_id.to_string()),
+        }
+    }
+}
+
+/// Exchange the refresh token for a new access token
+pub async fn refresh(credentials: &Credentials) -> Result<Credentials> {
+    let (Some(refresh_token), Some(endpoint), Some(client_id)) = (
+        credentials.refresh_token.as_deref(),
+        credentials.token_endpoint.as_deref(),
+        credentials.client_id.as_deref(),
+    ) else {
+        bail!("Saved login cannot be refreshed; run demonctl login");
+    };
+
+    let response = reqwest::Client::new()
+        .post(endpoint)
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", client_id),
+        ])
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach token endpoint {}", endpoint))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        bail!(
+            "Token refresh failed ({}): {}; run demonctl login",
+            status,
+            body
+        );
+    }
+    let token: TokenResponse = response
+        .json()
+        .await
+        .context("Failed to parse token refresh response")?;
+    Ok(token.into_credentials(endpoint, client_id, Some(refresh_token.to_string())))
+}
+
+/// `exp` claim of a JWT, if the token is one
+pub fn jwt_expiry(token: &str) -> Option<DateTime<Utc>> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    DateTime::from_timestamp(claims.get("exp")?.as_i64()?, 0)
+}
+
+fn keychain_entry() -> Result<keyring::Entry> {
+    Ok(keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)?)
+}
+
+/// `DEMON_HOME`, else `~/.demon`
+fn demon_home() -> Result<PathBuf> {
+    if let Ok(dir) = std::env::var("DEMON_HOME") {
+        Ok(PathBuf::from(dir))
+    } else if let Ok(home) = std::env::var("HOME") {
+        Ok(PathBuf::from(home).join(".demon"))
+    } else {
+        bail!("Unable to determine Demon home. Set HOME or DEMON_HOME.");
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u8,
+    /// How a passphrase key was derived; absent when the key file is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kdf: Option<Kdf>,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// scrypt parameters for a key derived from `DEMONCTL_CREDENTIALS_KEY`
+#[derive(Serialize, Deserialize)]
+struct Kdf {
+    salt: String,
+    #[serde(rename = "logN")]
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+fn passphrase() -> Option<String> {
+    std::env::var(PASSPHRASE_ENV).ok()
+}
+
+fn derive_key(passphrase: &str, kdf: &Kdf) -> Result<Key> {
+    let salt = STANDARD
+        .decode(&kdf.salt)
+        .context("Corrupt credentials file salt")?;
+    config_loader::secrets_crypto::derive_key(passphrase, &salt, kdf.log_n, kdf.r, kdf.p)
+        .context("Failed to derive the credentials key")
+}
+
+/// Random key kept next to the credentials with owner-only access, created on
+/// first use
+fn stored_key() -> Result<Key> {
+    let path = demon_home()?.join(KEY_FILE);
+    if let Ok(encoded) = std::fs::read_to_string(&path) {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .with_context(|| format!("Corrupt credentials key {}", path.display()))?;
+        if bytes.len() != 32 {
+            bail!("Corrupt credentials key {}", path.display());
+        }
+        return Ok(*Key::from_slice(&bytes));
+    }
+
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    write_private(&path, STANDARD.encode(key).as_bytes())?;
+    Ok(key)
+}
+
+/// Encrypt the credentials file with a key derived by salted scrypt from
+/// `DEMONCTL_CREDENTIALS_KEY` when set, otherwise with the stored random key
+fn write_encrypted(json: &str) -> Result<()> {
+    let (key, kdf) = match passphrase() {
+        Some(passphrase) => {
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let kdf = Kdf {
+                salt: STANDARD.encode(salt),
+                log_n: SCRYPT_LOG_N,
+                r: SCRYPT_R,
+                p: SCRYPT_P,
+            };
+            (derive_key(&passphrase, &kdf)?, Some(kdf))
+        }
+        None => (stored_key()?, None),
+    };
+    let cipher = ChaCha20Poly1305::new(&key);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, json.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt credentials"))?;
+    let file = EncryptedFile {
+        version: FILE_VERSION,
+        kdf,
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    };
+    write_private(
+        &demon_home()?.join(CREDENTIALS_FILE),
+        &serde_json::to_vec_pretty(&file)?,
+    )
+}
+
+fn read_encrypted() -> Result<Option<String>> {
+    let path = demon_home()?.join(CREDENTIALS_FILE);
+    let raw = match std::fs::read(&path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let file: EncryptedFile = serde_json::from_slice(&raw)
+        .with_context(|| format!("Corrupt credentials file {}", path.display()))?;
+    if file.version > FILE_VERSION {
+        bail!(
+            "{} was written by a newer demonctl (version {})",
+            path.display(),
+            file.version
+        );
+    }
+    let key = match (&file.kdf, passphrase()) {
+        (Some(kdf), Some(passphrase)) => derive_key(&passphrase, kdf)?,
+        (Some(_), None) => bail!(
+            "{} is encrypted with a passphrase; set {} or run demonctl login",
+            path.display(),
+            PASSPHRASE_ENV
+        ),
+        // Version 1 keyed passphrase files with an unsalted hash; they are
+        // rewritten with scrypt on the next save
+        (None, Some(passphrase)) if file.version < 2 => {
+            *Key::from_slice(&Sha256::digest(passphrase.as_bytes()))
+        }
+        (None, _) => stored_key()?,
+    };
+    let nonce = STANDARD.decode(&file.nonce)?;
+    if nonce.len() != 12 {
+        bail!("Corrupt credentials file {}", path.display());
+    }
+    let ciphertext = STANDARD.decode(&file.ciphertext)?;
+    let plaintext = ChaCha20Poly1305::new(&key)
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Cannot decrypt {}; check {} or run demonctl login",
+                path.display(),
+                PASSPHRASE_ENV
+            )
+        })?;
+    Ok(Some(String::from_utf8(plaintext)?))
+}
+
+fn write_private(path: &std::path::Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    std::io::Write::write_all(&mut file, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn jwt(claims: serde_json::Value) -> String {
+        format!(
+            "{}.{}.sig",
+            URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        )
+    }
+
+    #[test]
+    fn pasted_jwt_takes_expiry_from_exp_claim() {
+        let credentials =
+            Credentials::from_token(&jwt(json!({ "sub": "ops", "exp": 4102444800i64 })));
+        assert_eq!(
+            credentials.expires_at.unwrap().to_rfc3339(),
+            "2100-01-01T00:00:00+00:00"
+        );
+        assert!(!credentials.can_refresh());
+
+        assert_eq!(Credentials::from_token("opaque-token").expires_at, None);
+    }
+
+    #[test]
+    fn expiry_window_and_refresh_capability() {
+        let mut credentials = Credentials::from_token("t");
+        assert!(!credentials.expires_within(Duration::seconds(60)));
+
+        credentials.expires_at = Some(Utc::now() + Duration::seconds(30));
+        assert!(credentials.expires_within(Duration::seconds(60)));
+        assert!(!credentials.expires_within(Duration::zero()));
+
+        let refreshed = TokenResponse {
+            access_token: "new".into(),
+            refresh_token: None,
+            expires_in: Some(3600),
+        }
+        .into_credentials("https://idp/token", "demonctl", Some("r1".into()));
+        assert!(refreshed.can_refresh());
+        assert_eq!(refreshed.refresh_token.as_deref(), Some("r1"));
+    }
+}
@@ -503,7 +503,7 @@ mod tests {
             cluster: ClusterConfig {
                 name: "test-cluster".to_string(),
                 runtime: "k3s".to_string(),
-                k3s: K3sConfig {
+                k3s: Some(K3sConfig {
                     version: "v1.28.0+k3s1".to_string(),
                     install: K3sInstallConfig {
                         channel: "stable".to_string(),
@@ -512,7 +512,9 @@ mod tests {
                     data_dir: "/var/lib/rancher/k3s".to_string(),
                     node_name: "k3s-node".to_string(),
                     extra_args: vec![],
-                },
+                }),
+                kind: None,
+                existing: None,
             },
             demon: DemonConfig {
                 nats_url: "nats://localhost:4222".to_string(),
@@ -0,0 +1,170 @@
+//! Helm chart output for `k8s-bootstrap bootstrap --helm-chart`
+//!
+//! The templates under `resources/k8s` already use Go template syntax, so the
+//! chart reuses them with every `.key` lookup moved under `.Values`. The
+//! rendering context becomes `values.yaml`, and `helm template` with the
+//! generated values produces the same manifests as the imperative path.
+//! Secrets, registry pull secrets and add-ons stay out of the chart.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::k8s_bootstrap::templates::TemplateRenderer;
+use crate::k8s_bootstrap::K8sBootstrapConfig;
+
+pub const CHART_NAME: &str = "demon";
+
+/// Templates copied into the chart; `ingress.yaml` guards itself on
+/// `networking.ingress.enabled`
+const CHART_TEMPLATES: [&str; 6] = [
+    "namespace.yaml",
+    "nats.yaml",
+    "runtime.yaml",
+    "engine.yaml",
+    "operate-ui.yaml",
+    "ingress.yaml",
+];
+
+/// A rendered chart as relative path → contents
+pub struct HelmChart {
+    pub version: String,
+    pub files: BTreeMap<String, String>,
+    /// Parts of the bootstrap config that the chart does not carry
+    pub not_included: Vec<String>,
+}
+
+impl HelmChart {
+    /// Write the chart under `dir`, replacing files with the same names
+    pub fn write(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        for (relative, contents) in &self.files {
+            let path = dir.join(relative);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            fs::write(&path, contents)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+pub fn render_chart(
+    config: &K8sBootstrapConfig,
+    templates_dir: &str,
+    version: &str,
+) -> Result<HelmChart> {
+    let renderer = TemplateRenderer::new(templates_dir);
+    let values: BTreeMap<String, Value> = renderer
+        .build_template_context(config)?
+        .into_iter()
+        .collect();
+
+    let mut files = BTreeMap::new();
+    files.insert(
+        "Chart.yaml".to_string(),
+        chart_yaml(&config.metadata.name, version),
+    );
+    files.insert(
+        "values.yaml".to_string(),
+        format!(
+            "# Generated by `demonctl k8s-bootstrap bootstrap --helm-chart` from the\n\
+             # bootstrap config '{}'. Override per environment with -f / --set.\n{}",
+            config.metadata.name,
+            serde_yaml::to_string(&values).context("Failed to serialize chart values")?
+        ),
+    );
+    for file in CHART_TEMPLATES {
+        let path = Path::new(templates_dir).join(file);
+        let template = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read template file: {}", path.display()))?;
+        files.insert(format!("templates/{}", file), to_helm_template(&template));
+    }
+
+    let mut not_included = Vec::new();
+    let secrets = &config.secrets;
+    if secrets.vault.is_some() || secrets.env.as_ref().is_some_and(|env| !env.is_empty()) {
+        not_included.push("secrets (create the demon-secrets Secret separately)".to_string());
+    }
+    if config.registries.as_ref().is_some_and(|r| !r.is_empty()) {
+        not_included.push("registry image pull secrets".to_string());
+    }
+    for addon in config.addons.iter().filter(|addon| addon.enabled) {
+        not_included.push(format!("add-on '{}'", addon.name));
+    }
+
+    Ok(HelmChart {
+        version: version.to_string(),
+        files,
+        not_included,
+    })
+}
+
+fn chart_yaml(config_name: &str, version: &str) -> String {
+    format!(
+        "apiVersion: v2\n\
+         name: {}\n\
+         description: Demon runtime, engine, Operate UI and NATS ({})\n\
+         type: application\n\
+         version: {}\n\
+         appVersion: \"{}\"\n",
+        CHART_NAME, config_name, version, version
+    )
+}
+
+/// Move template lookups such as `{{ .namespace }}` and
+/// `{{- if .persistence.enabled }}` under `.Values`
+pub fn to_helm_template(template: &str) -> String {
+    template
+        .replace("{{ .", "{{ .Values.")
+        .replace("{{- if .", "{{- if .Values.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_helm_template_prefixes_lookups_with_values() {
+        let template = "namespace: {{ .namespace }}\n\
+                        {{- if .persistence.enabled }}\n\
+                        subjects: {{ .subjects | join \",\" }}\n\
+                        {{- else }}\n\
+                        {{- end }}";
+        assert_eq!(
+            to_helm_template(template),
+            "namespace: {{ .Values.namespace }}\n\
+             {{- if .Values.persistence.enabled }}\n\
+             subjects: {{ .Values.subjects | join \",\" }}\n\
+             {{- else }}\n\
+             {{- end }}"
+        );
+    }
+
+    #[test]
+    fn test_resource_templates_only_use_values_after_conversion() {
+        let templates_dir = format!("{}/resources/k8s", env!("CARGO_MANIFEST_DIR"));
+        for file in CHART_TEMPLATES {
+            let template =
+                fs::read_to_string(Path::new(&templates_dir).join(file)).expect("template exists");
+            let converted = to_helm_template(&template);
+            for action in converted.split("{{").skip(1) {
+                let action = action.trim_start_matches('-').trim_start();
+                assert!(
+                    action.starts_with(".Values.")
+                        || action.starts_with("if .Values.")
+                        || action.starts_with("else")
+                        || action.starts_with("end"),
+                    "{}: unexpected template action {{{{{}",
+                    file,
+                    action
+                );
+            }
+        }
+    }
+}
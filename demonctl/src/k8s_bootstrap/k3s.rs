@@ -1,8 +1,10 @@
 use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::time::Duration;
 use tracing::{info, warn};
 
+use crate::k8s_bootstrap::provider::{ClusterProvider, Kubectl, RUNTIME_K3S};
 use crate::k8s_bootstrap::K3sConfig;
 
 #[cfg(test)]
@@ -11,6 +13,16 @@ use crate::k8s_bootstrap::K3sInstallConfig;
 pub struct K3sInstaller {
     pub config: K3sConfig,
     pub dry_run: bool,
+    /// Install from these files instead of downloading k3s
+    pub offline: Option<K3sOfflineFiles>,
+}
+
+/// Pre-downloaded k3s release from an offline bundle
+#[derive(Debug, Clone)]
+pub struct K3sOfflineFiles {
+    pub binary: PathBuf,
+    pub install_script: PathBuf,
+    pub airgap_images: PathBuf,
 }
 
 #[derive(Debug, Clone)]
@@ -23,7 +35,16 @@ pub enum K3sStatus {
 
 impl K3sInstaller {
     pub fn new(config: K3sConfig, dry_run: bool) -> Self {
-        Self { config, dry_run }
+        Self {
+            config,
+            dry_run,
+            offline: None,
+        }
+    }
+
+    pub fn with_offline_files(mut self, files: K3sOfflineFiles) -> Self {
+        self.offline = Some(files);
+        self
     }
 
     pub fn install_k3s(&self) -> Result<()> {
@@ -53,7 +74,10 @@ impl K3sInstaller {
             }
         }
 
-        self.download_and_install_k3s()?;
+        match &self.offline {
+            Some(files) => self.install_from_files(files)?,
+            None => self.download_and_install_k3s()?,
+        }
         self.wait_for_k3s_ready(Duration::from_secs(300))?;
 
         info!("k3s installation completed successfully");
@@ -176,6 +200,30 @@ impl K3sInstaller {
         }
 
         println!();
+        if let Some(files) = &self.offline {
+            println!("🔧 Offline install that would be executed:");
+            println!(
+                "  sudo install -m 0755 {} /usr/local/bin/k3s",
+                files.binary.display()
+            );
+            println!(
+                "  sudo cp {} {}/",
+                files.airgap_images.display(),
+                self.airgap_images_dir().display()
+            );
+            println!("  INSTALL_K3S_SKIP_DOWNLOAD=true \\");
+            self.build_install_env_vars()
+                .iter()
+                .for_each(|(key, value)| {
+                    println!("    {}='{}' \\", key, value);
+                });
+            println!("    sh {} \\", files.install_script.display());
+            self.build_install_args().iter().for_each(|arg| {
+                println!("      {} \\", arg);
+            });
+            println!();
+            return Ok(());
+        }
         println!("🔧 Install Command that would be executed:");
         println!("  curl -sfL https://get.k3s.io | \\");
         self.build_install_env_vars()
@@ -224,6 +272,69 @@ impl K3sInstaller {
         Ok(())
     }
 
+    /// k3s imports every archive in this directory when it starts
+    fn airgap_images_dir(&self) -> PathBuf {
+        Path::new(&self.config.data_dir)
+            .join("agent")
+            .join("images")
+    }
+
+    fn install_from_files(&self, files: &K3sOfflineFiles) -> Result<()> {
+        info!("Installing k3s from offline bundle...");
+
+        let images_dir = self.airgap_images_dir();
+        let steps: [(&str, Vec<String>); 3] = [
+            (
+                "create the airgap images directory",
+                vec![
+                    "mkdir".into(),
+                    "-p".into(),
+                    images_dir.display().to_string(),
+                ],
+            ),
+            (
+                "install the k3s binary",
+                vec![
+                    "install".into(),
+                    "-m".into(),
+                    "0755".into(),
+                    files.binary.display().to_string(),
+                    "/usr/local/bin/k3s".into(),
+                ],
+            ),
+            (
+                "copy the k3s airgap images",
+                vec![
+                    "cp".into(),
+                    files.airgap_images.display().to_string(),
+                    images_dir.display().to_string(),
+                ],
+            ),
+        ];
+        for (step, args) in steps {
+            let status = Command::new("sudo")
+                .args(&args)
+                .status()
+                .with_context(|| format!("Failed to {}", step))?;
+            if !status.success() {
+                anyhow::bail!("Failed to {}: exit code {}", step, status);
+            }
+        }
+
+        let status = Command::new("sh")
+            .arg(&files.install_script)
+            .args(self.build_install_args())
+            .envs(self.build_install_env_vars())
+            .env("INSTALL_K3S_SKIP_DOWNLOAD", "true")
+            .status()
+            .context("Failed to execute k3s install script")?;
+        if !status.success() {
+            anyhow::bail!("k3s installation failed with exit code: {}", status);
+        }
+
+        Ok(())
+    }
+
     fn build_install_env_vars(&self) -> Vec<(String, String)> {
         let mut env_vars = vec![];
 
@@ -311,6 +422,44 @@ impl K3sInstaller {
     }
 }
 
+impl ClusterProvider for K3sInstaller {
+    fn name(&self) -> &'static str {
+        RUNTIME_K3S
+    }
+
+    fn describe(&self) -> String {
+        format!("k3s {}", self.config.version)
+    }
+
+    fn install(&self) -> Result<()> {
+        self.install_k3s()
+    }
+
+    fn is_ready(&self) -> Result<bool> {
+        self.is_k3s_ready()
+    }
+
+    fn kubectl(&self) -> Kubectl {
+        Kubectl::k3s()
+    }
+
+    fn load_images(&self, archive: &Path) -> Result<()> {
+        if self.dry_run {
+            println!("  sudo k3s ctr images import {}", archive.display());
+            return Ok(());
+        }
+        let status = Command::new("sudo")
+            .args(["k3s", "ctr", "images", "import"])
+            .arg(archive)
+            .status()
+            .context("Failed to run k3s ctr images import")?;
+        if !status.success() {
+            anyhow::bail!("k3s ctr images import failed with exit code: {}", status);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
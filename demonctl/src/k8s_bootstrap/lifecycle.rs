@@ -0,0 +1,289 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fmt;
+
+use super::provider::Kubectl;
+use super::CommandExecutor;
+
+/// A resource declared in a rendered manifest stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestResource {
+    pub kind: String,
+    pub name: String,
+    pub namespace: Option<String>,
+}
+
+impl fmt::Display for ManifestResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.kind, self.name)
+    }
+}
+
+/// What `kubectl diff` reported for a set of rendered manifests
+#[derive(Debug, Clone, Default)]
+pub struct ManifestDiff {
+    /// Unified diff as printed by kubectl
+    pub output: String,
+    /// `Kind/name` of every resource that would change, including new ones
+    pub changed: Vec<String>,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.output.trim().is_empty()
+    }
+}
+
+/// Every resource in a multi-document manifest stream, in document order
+pub fn parse_resources(manifests: &str) -> Result<Vec<ManifestResource>> {
+    let mut resources = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(manifests) {
+        let value = serde_yaml::Value::deserialize(document)
+            .context("Failed to parse rendered manifests")?;
+        let kind = value.get("kind").and_then(|v| v.as_str());
+        let metadata = value.get("metadata");
+        let name = metadata
+            .and_then(|m| m.get("name"))
+            .and_then(|v| v.as_str());
+        if let (Some(kind), Some(name)) = (kind, name) {
+            resources.push(ManifestResource {
+                kind: kind.to_string(),
+                name: name.to_string(),
+                namespace: metadata
+                    .and_then(|m| m.get("namespace"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+            });
+        }
+    }
+    Ok(resources)
+}
+
+/// Compare rendered manifests with what the cluster is running
+pub fn diff_manifests(
+    manifests: &str,
+    kubectl: &Kubectl,
+    executor: &dyn CommandExecutor,
+) -> Result<ManifestDiff> {
+    let output = kubectl.execute(executor, &["diff", "-f", "-"], Some(manifests))?;
+    // kubectl diff exits 1 when it found differences and >1 on errors
+    if output.status > 1 || (output.status != 0 && output.stdout.trim().is_empty()) {
+        anyhow::bail!(
+            "kubectl diff failed with exit code {}: {}",
+            output.status,
+            output.stderr.trim()
+        );
+    }
+    Ok(ManifestDiff {
+        changed: changed_resources(&output.stdout),
+        output: output.stdout,
+    })
+}
+
+/// `Kind/name` from the `diff -u -N LIVE MERGED` headers kubectl prints; the
+/// file names are `<group+version>.<Kind>.<namespace>.<name>`
+fn changed_resources(diff: &str) -> Vec<String> {
+    let mut changed = Vec::new();
+    for line in diff.lines() {
+        let Some(header) = line.strip_prefix("diff ") else {
+            continue;
+        };
+        let Some(path) = header.split_whitespace().last() else {
+            continue;
+        };
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let mut parts = file.rsplitn(3, '.');
+        let (Some(name), Some(_namespace), Some(rest)) = (parts.next(), parts.next(), parts.next())
+        else {
+            changed.push(file.to_string());
+            continue;
+        };
+        let kind = rest.rsplit('.').next().unwrap_or(rest);
+        let resource = format!("{}/{}", kind, name);
+        if !changed.contains(&resource) {
+            changed.push(resource);
+        }
+    }
+    changed
+}
+
+/// Wait for every Deployment, StatefulSet and DaemonSet in `resources` to finish
+/// rolling out; returns the workloads that were waited on
+pub fn wait_for_rollouts(
+    resources: &[ManifestResource],
+    default_namespace: &str,
+    kubectl: &Kubectl,
+    executor: &dyn CommandExecutor,
+    timeout_secs: u64,
+    verbose: bool,
+) -> Result<Vec<String>> {
+    let timeout = format!("--timeout={}s", timeout_secs);
+    let mut rolled_out = Vec::new();
+    for resource in resources
+        .iter()
+        .filter(|r| matches!(r.kind.as_str(), "Deployment" | "StatefulSet" | "DaemonSet"))
+    {
+        let target = format!("{}/{}", resource.kind.to_lowercase(), resource.name);
+        let namespace = resource.namespace.as_deref().unwrap_or(default_namespace);
+        if verbose {
+            println!("Waiting for {} to roll out...", resource);
+        }
+        let output = kubectl.execute(
+            executor,
+            &["rollout", "status", &target, "-n", namespace, &timeout],
+            None,
+        )?;
+        if output.status != 0 {
+            anyhow::bail!(
+                "Rollout of {} did not complete: {}",
+                resource,
+                output.stderr.trim()
+            );
+        }
+        if verbose {
+            println!("✓ {} rolled out", resource);
+        }
+        rolled_out.push(resource.to_string());
+    }
+    Ok(rolled_out)
+}
+
+/// Lower ranks are deleted first: traffic entry points, then workloads, then
+/// what the workloads consume, with the namespace last
+fn deletion_rank(kind: &str) -> u8 {
+    match kind {
+        "Ingress" => 0,
+        "Deployment" | "StatefulSet" | "DaemonSet" | "Job" | "CronJob" => 1,
+        "Service" => 2,
+        "ConfigMap" | "Secret" => 3,
+        "PersistentVolumeClaim" => 4,
+        "Namespace" => 6,
+        _ => 5,
+    }
+}
+
+/// `resources` in the order they should be deleted; within a rank, resources
+/// applied later are deleted first
+pub fn deletion_order(resources: &[ManifestResource]) -> Vec<ManifestResource> {
+    let mut ordered: Vec<ManifestResource> = resources.iter().rev().cloned().collect();
+    ordered.sort_by_key(|r| deletion_rank(&r.kind));
+    ordered
+}
+
+/// Delete `resources` one at a time in the given order, skipping any that are
+/// already gone
+pub fn delete_resources(
+    resources: &[ManifestResource],
+    default_namespace: &str,
+    kubectl: &Kubectl,
+    executor: &dyn CommandExecutor,
+    verbose: bool,
+) -> Result<Vec<String>> {
+    let mut deleted = Vec::new();
+    for resource in resources {
+        let kind = resource.kind.to_lowercase();
+        let mut args = vec!["delete", kind.as_str(), resource.name.as_str()];
+        if resource.kind != "Namespace" {
+            args.extend([
+                "-n",
+                resource.namespace.as_deref().unwrap_or(default_namespace),
+            ]);
+        }
+        args.push("--ignore-not-found");
+        let output = kubectl.execute(executor, &args, None)?;
+        if output.status != 0 {
+            anyhow::bail!("Failed to delete {}: {}", resource, output.stderr.trim());
+        }
+        if verbose {
+            println!("✓ Deleted {}", resource);
+        }
+        deleted.push(resource.to_string());
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFESTS: &str = r#"apiVersion: v1
+kind: Namespace
+metadata:
+  name: demon-system
+---
+apiVersion: v1
+kind: Secret
+metadata:
+  name: demon-secrets
+  namespace: demon-system
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: nats
+  namespace: demon-system
+---
+apiVersion: apps/v1
+kind: StatefulSet
+metadata:
+  name: nats
+  namespace: demon-system
+---
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: demon-runtime
+  namespace: demon-system
+---
+apiVersion: networking.k8s.io/v1
+kind: Ingress
+metadata:
+  name: demon-ingress
+  namespace: demon-system
+"#;
+
+    #[test]
+    fn parse_resources_reads_every_document() {
+        let resources = parse_resources(MANIFESTS).unwrap();
+        assert_eq!(resources.len(), 6);
+        assert_eq!(resources[0].to_string(), "Namespace/demon-system");
+        assert_eq!(resources[0].namespace, None);
+        assert_eq!(resources[4].namespace.as_deref(), Some("demon-system"));
+    }
+
+    #[test]
+    fn deletion_order_removes_workloads_before_dependencies_and_namespace_last() {
+        let resources = parse_resources(MANIFESTS).unwrap();
+        let order: Vec<String> = deletion_order(&resources)
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                "Ingress/demon-ingress",
+                "Deployment/demon-runtime",
+                "StatefulSet/nats",
+                "Service/nats",
+                "Secret/demon-secrets",
+                "Namespace/demon-system",
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_resources_reads_kubectl_diff_headers() {
+        let diff = "\
+diff -u -N /tmp/LIVE-1/apps.v1.Deployment.demon-system.demon-runtime /tmp/MERGED-1/apps.v1.Deployment.demon-system.demon-runtime
+--- /tmp/LIVE-1/apps.v1.Deployment.demon-system.demon-runtime
++++ /tmp/MERGED-1/apps.v1.Deployment.demon-system.demon-runtime
+-        image: ghcr.io/acme/demon-runtime:old
++        image: ghcr.io/acme/demon-runtime:new
+diff -u -N /tmp/LIVE-1/networking.k8s.io.v1.Ingress.demon-system.demon-ingress /tmp/MERGED-1/networking.k8s.io.v1.Ingress.demon-system.demon-ingress
+";
+        assert_eq!(
+            changed_resources(diff),
+            vec!["Deployment/demon-runtime", "Ingress/demon-ingress"]
+        );
+    }
+}
@@ -6,8 +6,13 @@ use std::process::Command;
 use crate::docker;
 
 pub mod addons;
+pub mod helm;
 pub mod k3s;
+pub mod lifecycle;
+pub mod offline;
+pub mod provider;
 pub mod secrets;
+pub mod smoke;
 pub mod templates;
 
 pub trait CommandExecutor {
@@ -121,8 +126,19 @@ pub struct ConfigMetadata {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClusterConfig {
     pub name: String,
+    /// One of `provider::RUNTIMES`
     pub runtime: String,
-    pub k3s: K3sConfig,
+    /// Required when `runtime` is `k3s`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub k3s: Option<K3sConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<KindConfig>,
+    #[serde(
+        default,
+        rename = "existingCluster",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub existing: Option<ExistingClusterConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -143,6 +159,27 @@ pub struct K3sInstallConfig {
     pub disable: Vec<String>,
 }
 
+/// Options for `runtime: kind`; the kind cluster is named after `cluster.name`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KindConfig {
+    /// Node image passed to `kind create cluster --image`
+    #[serde(rename = "nodeImage", default)]
+    pub node_image: Option<String>,
+    /// kind cluster config passed to `kind create cluster --config`
+    #[serde(rename = "configFile", default)]
+    pub config_file: Option<String>,
+}
+
+/// Options for `runtime: existing-cluster`; both default to kubectl's own
+/// resolution (`$KUBECONFIG` and the current context)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExistingClusterConfig {
+    #[serde(default)]
+    pub kubeconfig: Option<String>,
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DemonConfig {
     #[serde(rename = "natsUrl")]
@@ -286,9 +323,7 @@ pub fn load_config(config_path: &str) -> Result<K8sBootstrapConfig> {
 }
 
 pub fn validate_config(config: &K8sBootstrapConfig) -> Result<()> {
-    if config.cluster.runtime != "k3s" {
-        anyhow::bail!("Only 'k3s' runtime is currently supported");
-    }
+    provider::for_cluster(&config.cluster, true, None)?;
 
     if config.demon.namespace.is_empty() {
         anyhow::bail!("Demon namespace cannot be empty");
@@ -382,7 +417,7 @@ mod tests {
             cluster: ClusterConfig {
                 name: "test".to_string(),
                 runtime: "k3s".to_string(),
-                k3s: K3sConfig {
+                k3s: Some(K3sConfig {
                     version: "v1".to_string(),
                     install: K3sInstallConfig {
                         channel: "stable".to_string(),
@@ -391,7 +426,9 @@ mod tests {
                     data_dir: "/var/lib/rancher/k3s".to_string(),
                     node_name: "node".to_string(),
                     extra_args: vec![],
-                },
+                }),
+                kind: None,
+                existing: None,
             },
             demon: DemonConfig {
                 nats_url: "nats://localhost:4222".to_string(),
@@ -515,7 +552,7 @@ mod tests {
             cluster: ClusterConfig {
                 name: "test".to_string(),
                 runtime: "k3s".to_string(),
-                k3s: K3sConfig {
+                k3s: Some(K3sConfig {
                     version: "v1".to_string(),
                     install: K3sInstallConfig {
                         channel: "stable".to_string(),
@@ -524,7 +561,9 @@ mod tests {
                     data_dir: "/var/lib/rancher/k3s".to_string(),
                     node_name: "node".to_string(),
                     extra_args: vec![],
-                },
+                }),
+                kind: None,
+                existing: None,
             },
             demon: DemonConfig {
                 nats_url: "nats://localhost:4222".to_string(),
@@ -0,0 +1,553 @@
+//! Air-gapped installs: `docker save-bundle` and `k8s-bootstrap --offline`
+//!
+//! An offline bundle is a directory written on a connected machine:
+//!
+//! ```text
+//! offline-bundle.json   index: image references, digests, checksums
+//! images.tar            `docker save` of every image the manifests use
+//! k3s/                  k3s binary, install script and airgap images (k3s runtime only)
+//! ```
+//!
+//! On the disconnected host the bundle is verified against the rendered
+//! manifests before anything is installed. Image references pinned by digest
+//! are saved under an `offline-<digest>` tag, because `docker save` does not
+//! keep the registry manifest a digest refers to; the manifests are rewritten
+//! to that tag once the bundle has confirmed the digest.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const INDEX_FILE: &str = "offline-bundle.json";
+pub const IMAGES_ARCHIVE: &str = "images.tar";
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+const K3S_RELEASES_URL: &str = "https://github.com/k3s-io/k3s/releases/download";
+const K3S_INSTALL_SCRIPT_URL: &str = "https://get.k3s.io";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledImage {
+    /// Reference as it appears in the rendered manifests
+    pub reference: String,
+    /// Registry digest the reference resolved to when the bundle was saved
+    pub digest: String,
+    /// Name the image carries inside `images.tar`
+    pub load_as: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledFile {
+    /// Path relative to the bundle directory
+    pub path: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledK3s {
+    pub version: String,
+    pub arch: String,
+    pub binary: BundledFile,
+    pub install_script: BundledFile,
+    pub airgap_images: BundledFile,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineIndex {
+    pub version: u32,
+    pub created_at: String,
+    pub images: Vec<BundledImage>,
+    pub archive: BundledFile,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub k3s: Option<BundledK3s>,
+}
+
+/// A bundle directory with its parsed index
+#[derive(Debug, Clone)]
+pub struct OfflineBundle {
+    pub dir: PathBuf,
+    pub index: OfflineIndex,
+}
+
+impl OfflineBundle {
+    pub fn open(dir: &Path) -> Result<Self> {
+        let index_path = dir.join(INDEX_FILE);
+        let index: OfflineIndex = serde_json::from_str(
+            &fs::read_to_string(&index_path)
+                .with_context(|| format!("Failed to read {}", index_path.display()))?,
+        )
+        .with_context(|| format!("Failed to parse {}", index_path.display()))?;
+        if index.version != BUNDLE_FORMAT_VERSION {
+            anyhow::bail!(
+                "Unsupported offline bundle version {} (expected {})",
+                index.version,
+                BUNDLE_FORMAT_VERSION
+            );
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            index,
+        })
+    }
+
+    pub fn path(&self, file: &BundledFile) -> PathBuf {
+        self.dir.join(&file.path)
+    }
+
+    pub fn archive_path(&self) -> PathBuf {
+        self.path(&self.index.archive)
+    }
+
+    /// Check that every image in `manifests` is in the bundle with the
+    /// digest it is pinned to, that every bundled file matches its checksum,
+    /// and, when `k3s_version` is given, that the bundle carries that k3s
+    /// release. All problems are reported together.
+    pub fn verify(&self, manifests: &str, k3s_version: Option<&str>) -> Result<()> {
+        let mut errors = Vec::new();
+
+        for reference in referenced_images(manifests)? {
+            match self.image(&reference) {
+                None => errors.push(format!("image {} is not in the bundle", reference)),
+                Some(image) => {
+                    if let Some(pinned) = pinned_digest(&reference) {
+                        if image.digest != pinned {
+                            errors.push(format!(
+                                "image {} is bundled with digest {}",
+                                reference, image.digest
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut files = vec![&self.index.archive];
+        match (k3s_version, &self.index.k3s) {
+            (Some(version), Some(k3s)) => {
+                if k3s.version != version {
+                    errors.push(format!(
+                        "bundle has k3s {} but the config installs {}",
+                        k3s.version, version
+                    ));
+                }
+                files.extend([&k3s.binary, &k3s.install_script, &k3s.airgap_images]);
+            }
+            (Some(version), None) => {
+                errors.push(format!("bundle has no k3s files for {}", version));
+            }
+            (None, _) => {}
+        }
+        for file in files {
+            match sha256_file(&self.path(file)) {
+                Ok(actual) if actual == file.sha256 => {}
+                Ok(actual) => errors.push(format!(
+                    "{} has sha256 {} but the index records {}",
+                    file.path, actual, file.sha256
+                )),
+                Err(err) => errors.push(format!("{}: {:#}", file.path, err)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!("offline bundle errors:\n  - {}", errors.join("\n  - "))
+        }
+    }
+
+    /// Point every bundled image reference at the name it was saved under
+    pub fn rewrite_images(&self, manifests: &str) -> String {
+        let mut rewritten: Vec<String> = Vec::new();
+        for line in manifests.lines() {
+            let replaced = image_value(line).and_then(|value| {
+                let image = self.image(value)?;
+                (image.load_as != image.reference).then(|| line.replacen(value, &image.load_as, 1))
+            });
+            rewritten.push(replaced.unwrap_or_else(|| line.to_string()));
+        }
+        let mut output = rewritten.join("\n");
+        if manifests.ends_with('\n') {
+            output.push('\n');
+        }
+        output
+    }
+
+    fn image(&self, reference: &str) -> Option<&BundledImage> {
+        self.index
+            .images
+            .iter()
+            .find(|image| image.reference == reference)
+    }
+}
+
+/// The value of an `image:` line, without quotes
+fn image_value(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start().trim_start_matches("- ");
+    let value = trimmed.strip_prefix("image:")?.trim();
+    let value = value.trim_matches(|c| c == '"' || c == '\'');
+    (!value.is_empty()).then_some(value)
+}
+
+/// Every container image referenced in a multi-document manifest stream,
+/// sorted and deduplicated
+pub fn referenced_images(manifests: &str) -> Result<Vec<String>> {
+    fn collect(value: &serde_yaml::Value, images: &mut Vec<String>) {
+        match value {
+            serde_yaml::Value::Mapping(map) => {
+                for (key, value) in map {
+                    match (key.as_str(), value) {
+                        (Some("image"), serde_yaml::Value::String(image)) => {
+                            images.push(image.clone())
+                        }
+                        _ => collect(value, images),
+                    }
+                }
+            }
+            serde_yaml::Value::Sequence(items) => {
+                for item in items {
+                    collect(item, images);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut images = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(manifests) {
+        let value = serde_yaml::Value::deserialize(document)
+            .context("Failed to parse rendered manifests")?;
+        collect(&value, &mut images);
+    }
+    images.sort();
+    images.dedup();
+    Ok(images)
+}
+
+/// `sha256:...` from a `repo@sha256:...` reference
+pub fn pinned_digest(reference: &str) -> Option<&str> {
+    reference
+        .split_once('@')
+        .map(|(_, digest)| digest)
+        .filter(|digest| digest.starts_with("sha256:"))
+}
+
+/// Name a digest-pinned image is saved under, e.g.
+/// `ghcr.io/acme/demon-runtime:offline-0123456789ab`
+pub fn offline_tag(reference: &str) -> String {
+    match reference.split_once('@') {
+        Some((repository, digest)) => {
+            let hex = digest.trim_start_matches("sha256:");
+            format!(
+                "{}:offline-{}",
+                strip_tag(repository),
+                &hex[..hex.len().min(12)]
+            )
+        }
+        None => reference.to_string(),
+    }
+}
+
+/// `repo` from `repo:tag`, leaving a registry port alone
+fn strip_tag(repository: &str) -> &str {
+    match repository.rsplit_once(':') {
+        Some((name, tag)) if !tag.contains('/') => name,
+        _ => repository,
+    }
+}
+
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Pull every image in `manifests`, save them to `output`, and, when
+/// `k3s` is `(version, arch)`, download that k3s release for offline install
+pub async fn save_bundle(
+    manifests: &str,
+    k3s: Option<(&str, &str)>,
+    output: &Path,
+    verbose: bool,
+) -> Result<OfflineIndex> {
+    fs::create_dir_all(output).with_context(|| format!("Failed to create {}", output.display()))?;
+
+    let mut images = Vec::new();
+    for reference in referenced_images(manifests)? {
+        if verbose {
+            println!("Pulling {}", reference);
+        }
+        docker(&["pull", &reference])?;
+        let digest = match pinned_digest(&reference) {
+            Some(digest) => digest.to_string(),
+            None => {
+                let repo_digest = docker(&[
+                    "image",
+                    "inspect",
+                    "--format",
+                    "{{index .RepoDigests 0}}",
+                    &reference,
+                ])?;
+                pinned_digest(repo_digest.trim())
+                    .with_context(|| format!("No registry digest for {}", reference))?
+                    .to_string()
+            }
+        };
+        let load_as = offline_tag(&reference);
+        if load_as != reference {
+            docker(&["tag", &reference, &load_as])?;
+        }
+        images.push(BundledImage {
+            reference,
+            digest,
+            load_as,
+        });
+    }
+
+    let archive_path = output.join(IMAGES_ARCHIVE);
+    if verbose {
+        println!(
+            "Saving {} image(s) to {}",
+            images.len(),
+            archive_path.display()
+        );
+    }
+    let mut save_args = vec!["save".to_string(), "-o".to_string()];
+    save_args.push(archive_path.display().to_string());
+    save_args.extend(images.iter().map(|image| image.load_as.clone()));
+    docker(&save_args.iter().map(String::as_str).collect::<Vec<_>>())?;
+
+    let k3s = match k3s {
+        Some((version, arch)) => Some(download_k3s(output, version, arch, verbose).await?),
+        None => None,
+    };
+
+    let index = OfflineIndex {
+        version: BUNDLE_FORMAT_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        images,
+        archive: BundledFile {
+            path: IMAGES_ARCHIVE.to_string(),
+            sha256: sha256_file(&archive_path)?,
+        },
+        k3s,
+    };
+    fs::write(
+        output.join(INDEX_FILE),
+        serde_json::to_string_pretty(&index)?,
+    )
+    .with_context(|| format!("Failed to write {}", output.join(INDEX_FILE).display()))?;
+    Ok(index)
+}
+
+fn docker(args: &[&str]) -> Result<String> {
+    let output = Command::new("docker")
+        .args(args)
+        .output()
+        .context("Failed to run docker; is it installed?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "docker {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+async fn download_k3s(
+    output: &Path,
+    version: &str,
+    arch: &str,
+    verbose: bool,
+) -> Result<BundledK3s> {
+    let binary_name = match arch {
+        "amd64" => "k3s".to_string(),
+        "arm64" => "k3s-arm64".to_string(),
+        other => anyhow::bail!(
+            "Unsupported k3s architecture '{}' (expected amd64 or arm64)",
+            other
+        ),
+    };
+    let release = format!("{}/{}", K3S_RELEASES_URL, version.replace('+', "%2B"));
+    let airgap_name = format!("k3s-airgap-images-{}.tar.gz", arch);
+
+    let dir = output.join("k3s");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let client = reqwest::Client::new();
+    let fetch = |url: String, name: &str| {
+        let path = format!("k3s/{}", name);
+        let target = output.join(&path);
+        let client = client.clone();
+        async move {
+            if verbose {
+                println!("Downloading {}", url);
+            }
+            let bytes = client
+                .get(&url)
+                .send()
+                .await?
+                .error_for_status()
+                .with_context(|| format!("GET {}", url))?
+                .bytes()
+                .await?;
+            fs::write(&target, &bytes)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+            Ok::<_, anyhow::Error>(BundledFile {
+                sha256: sha256_file(&target)?,
+                path,
+            })
+        }
+    };
+
+    Ok(BundledK3s {
+        version: version.to_string(),
+        arch: arch.to_string(),
+        binary: fetch(format!("{}/{}", release, binary_name), "k3s").await?,
+        install_script: fetch(K3S_INSTALL_SCRIPT_URL.to_string(), "install.sh").await?,
+        airgap_images: fetch(format!("{}/{}", release, airgap_name), &airgap_name).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const MANIFESTS: &str = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: demon-runtime
+spec:
+  template:
+    spec:
+      containers:
+      - name: runtime
+        image: ghcr.io/acme/demon-runtime@sha256:aaaabbbbccccdddd
+---
+apiVersion: apps/v1
+kind: StatefulSet
+metadata:
+  name: nats
+spec:
+  template:
+    spec:
+      containers:
+        - name: nats
+          image: nats:2.10-alpine
+"#;
+
+    fn write_bundle(dir: &TempDir, images: Vec<BundledImage>) -> OfflineBundle {
+        fs::write(dir.path().join(IMAGES_ARCHIVE), b"images").unwrap();
+        let index = OfflineIndex {
+            version: BUNDLE_FORMAT_VERSION,
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            images,
+            archive: BundledFile {
+                path: IMAGES_ARCHIVE.to_string(),
+                sha256: sha256_file(&dir.path().join(IMAGES_ARCHIVE)).unwrap(),
+            },
+            k3s: None,
+        };
+        fs::write(
+            dir.path().join(INDEX_FILE),
+            serde_json::to_string(&index).unwrap(),
+        )
+        .unwrap();
+        OfflineBundle::open(dir.path()).unwrap()
+    }
+
+    fn bundled(reference: &str, digest: &str) -> BundledImage {
+        BundledImage {
+            reference: reference.to_string(),
+            digest: digest.to_string(),
+            load_as: offline_tag(reference),
+        }
+    }
+
+    #[test]
+    fn given_manifests_when_referenced_images_then_lists_each_container_image() {
+        assert_eq!(
+            referenced_images(MANIFESTS).unwrap(),
+            vec![
+                "ghcr.io/acme/demon-runtime@sha256:aaaabbbbccccdddd",
+                "nats:2.10-alpine"
+            ]
+        );
+    }
+
+    #[test]
+    fn given_digest_reference_when_offline_tag_then_replaces_digest_with_tag() {
+        assert_eq!(
+            offline_tag("ghcr.io/acme/demon-runtime:main@sha256:0123456789abcdef"),
+            "ghcr.io/acme/demon-runtime:offline-0123456789ab"
+        );
+        assert_eq!(
+            offline_tag("registry.local:5000/demon@sha256:0123456789abcdef"),
+            "registry.local:5000/demon:offline-0123456789ab"
+        );
+        assert_eq!(offline_tag("nats:2.10-alpine"), "nats:2.10-alpine");
+    }
+
+    #[test]
+    fn given_complete_bundle_when_verify_then_rewrites_pinned_images() {
+        let dir = TempDir::new().unwrap();
+        let bundle = write_bundle(
+            &dir,
+            vec![
+                bundled(
+                    "ghcr.io/acme/demon-runtime@sha256:aaaabbbbccccdddd",
+                    "sha256:aaaabbbbccccdddd",
+                ),
+                bundled("nats:2.10-alpine", "sha256:1111"),
+            ],
+        );
+
+        bundle.verify(MANIFESTS, None).unwrap();
+        let rewritten = bundle.rewrite_images(MANIFESTS);
+        assert!(
+            rewritten.contains("        image: ghcr.io/acme/demon-runtime:offline-aaaabbbbcccc\n")
+        );
+        assert!(rewritten.contains("          image: nats:2.10-alpine\n"));
+    }
+
+    #[test]
+    fn given_missing_image_digest_mismatch_and_k3s_when_verify_then_reports_all() {
+        let dir = TempDir::new().unwrap();
+        let bundle = write_bundle(
+            &dir,
+            vec![bundled(
+                "ghcr.io/acme/demon-runtime@sha256:aaaabbbbccccdddd",
+                "sha256:ffff",
+            )],
+        );
+        fs::write(dir.path().join(IMAGES_ARCHIVE), b"tampered").unwrap();
+
+        let message = bundle
+            .verify(MANIFESTS, Some("v1.28.2+k3s1"))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            message.contains("image nats:2.10-alpine is not in the bundle"),
+            "{message}"
+        );
+        assert!(
+            message.contains("is bundled with digest sha256:ffff"),
+            "{message}"
+        );
+        assert!(
+            message.contains("bundle has no k3s files for v1.28.2+k3s1"),
+            "{message}"
+        );
+        assert!(message.contains("images.tar has sha256"), "{message}");
+    }
+}
@@ -0,0 +1,483 @@
+//! Cluster providers for `k8s-bootstrap`
+//!
+//! `cluster.runtime` selects how the target cluster is obtained: `k3s`
+//! installs a single-node k3s server, `kind` creates a kind cluster, and
+//! `existing-cluster` uses a cluster that is already reachable through the
+//! current kubeconfig without installing anything. Each provider also decides
+//! how kubectl is invoked for the rest of the bootstrap.
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+use tracing::info;
+
+use crate::k8s_bootstrap::k3s::{K3sInstaller, K3sOfflineFiles};
+use crate::k8s_bootstrap::offline::OfflineBundle;
+use crate::k8s_bootstrap::{
+    ClusterConfig, CommandExecutor, CommandOutput, ExistingClusterConfig, KindConfig,
+    SystemCommandExecutor,
+};
+
+pub const RUNTIME_K3S: &str = "k3s";
+pub const RUNTIME_KIND: &str = "kind";
+pub const RUNTIME_EXISTING: &str = "existing-cluster";
+
+/// Values accepted for `cluster.runtime`
+pub const RUNTIMES: [&str; 3] = [RUNTIME_K3S, RUNTIME_KIND, RUNTIME_EXISTING];
+
+pub trait ClusterProvider {
+    /// The `cluster.runtime` value this provider handles
+    fn name(&self) -> &'static str;
+
+    /// One-line description for summaries, e.g. `k3s v1.28.2+k3s1`
+    fn describe(&self) -> String;
+
+    /// Make the cluster available; in dry-run mode print the plan instead
+    fn install(&self) -> Result<()>;
+
+    /// Whether at least one node reports `Ready`
+    fn is_ready(&self) -> Result<bool>;
+
+    /// How to reach the cluster's API server
+    fn kubectl(&self) -> Kubectl;
+
+    /// Import a `docker save` archive into the cluster's container runtime
+    fn load_images(&self, archive: &Path) -> Result<()>;
+}
+
+/// Build the provider selected by `cluster.runtime`; with an `offline`
+/// bundle, k3s is installed from the bundle's files
+pub fn for_cluster(
+    cluster: &ClusterConfig,
+    dry_run: bool,
+    offline: Option<&OfflineBundle>,
+) -> Result<Box<dyn ClusterProvider>> {
+    match cluster.runtime.as_str() {
+        RUNTIME_K3S => {
+            let k3s = cluster
+                .k3s
+                .clone()
+                .context("cluster.k3s is required when cluster.runtime is 'k3s'")?;
+            let mut installer = K3sInstaller::new(k3s, dry_run);
+            if let Some(files) = offline.and_then(|bundle| {
+                bundle.index.k3s.as_ref().map(|k3s| K3sOfflineFiles {
+                    binary: bundle.path(&k3s.binary),
+                    install_script: bundle.path(&k3s.install_script),
+                    airgap_images: bundle.path(&k3s.airgap_images),
+                })
+            }) {
+                installer = installer.with_offline_files(files);
+            }
+            Ok(Box::new(installer))
+        }
+        RUNTIME_KIND => Ok(Box::new(KindCluster {
+            name: cluster.name.clone(),
+            config: cluster.kind.clone().unwrap_or_default(),
+            dry_run,
+        })),
+        RUNTIME_EXISTING => Ok(Box::new(ExistingCluster {
+            config: cluster.existing.clone().unwrap_or_default(),
+            dry_run,
+        })),
+        other => anyhow::bail!(
+            "Unsupported cluster runtime '{}' (expected one of: {})",
+            other,
+            RUNTIMES.join(", ")
+        ),
+    }
+}
+
+/// A kubectl invocation: the program plus the arguments that select the
+/// cluster, e.g. `k3s kubectl` or `kubectl --context kind-demo`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kubectl {
+    program: String,
+    base_args: Vec<String>,
+}
+
+impl Kubectl {
+    /// `k3s kubectl`, which reads the kubeconfig k3s writes
+    pub fn k3s() -> Self {
+        Self {
+            program: "k3s".to_string(),
+            base_args: vec!["kubectl".to_string()],
+        }
+    }
+
+    /// Plain `kubectl`, optionally pinned to a kubeconfig file and context
+    pub fn standalone(kubeconfig: Option<&str>, context: Option<&str>) -> Self {
+        let mut base_args = Vec::new();
+        if let Some(kubeconfig) = kubeconfig {
+            base_args.extend(["--kubeconfig".to_string(), kubeconfig.to_string()]);
+        }
+        if let Some(context) = context {
+            base_args.extend(["--context".to_string(), context.to_string()]);
+        }
+        Self {
+            program: "kubectl".to_string(),
+            base_args,
+        }
+    }
+
+    pub fn execute(
+        &self,
+        executor: &dyn CommandExecutor,
+        args: &[&str],
+        input: Option<&str>,
+    ) -> Result<CommandOutput> {
+        let mut full_args: Vec<&str> = self.base_args.iter().map(String::as_str).collect();
+        full_args.extend_from_slice(args);
+        executor.execute(&self.program, &full_args, input)
+    }
+
+    /// A `Command` for long-running invocations such as `port-forward`
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.base_args);
+        command
+    }
+}
+
+impl fmt::Display for Kubectl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in &self.base_args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `kubectl get nodes` lists a node whose status is exactly `Ready`
+fn any_node_ready(kubectl: &Kubectl) -> Result<bool> {
+    let output = kubectl
+        .execute(
+            &SystemCommandExecutor,
+            &["get", "nodes", "--no-headers"],
+            None,
+        )
+        .with_context(|| format!("Failed to check node status with `{}`", kubectl))?;
+    if output.status != 0 {
+        return Ok(false);
+    }
+    Ok(output
+        .stdout
+        .lines()
+        .any(|line| line.split_whitespace().nth(1) == Some("Ready")))
+}
+
+/// A cluster created and managed by kind
+pub struct KindCluster {
+    pub name: String,
+    pub config: KindConfig,
+    pub dry_run: bool,
+}
+
+impl KindCluster {
+    fn create_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "create".to_string(),
+            "cluster".to_string(),
+            "--name".to_string(),
+            self.name.clone(),
+        ];
+        if let Some(image) = &self.config.node_image {
+            args.extend(["--image".to_string(), image.clone()]);
+        }
+        if let Some(config_file) = &self.config.config_file {
+            args.extend(["--config".to_string(), config_file.clone()]);
+        }
+        args.extend(["--wait".to_string(), "300s".to_string()]);
+        args
+    }
+
+    fn exists(&self) -> Result<bool> {
+        let output = Command::new("kind")
+            .args(["get", "clusters"])
+            .output()
+            .context("Failed to run `kind get clusters`; is kind installed?")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "`kind get clusters` failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .any(|line| line.trim() == self.name))
+    }
+}
+
+impl ClusterProvider for KindCluster {
+    fn name(&self) -> &'static str {
+        RUNTIME_KIND
+    }
+
+    fn describe(&self) -> String {
+        match &self.config.node_image {
+            Some(image) => format!("kind ({})", image),
+            None => "kind".to_string(),
+        }
+    }
+
+    fn install(&self) -> Result<()> {
+        if self.dry_run {
+            println!("📋 kind Cluster Plan:");
+            println!("  Cluster: {}", self.name);
+            println!("  Context: kind-{}", self.name);
+            println!();
+            println!("🔧 Command that would be executed if the cluster does not exist:");
+            println!("  kind {}", self.create_args().join(" "));
+            println!();
+            return Ok(());
+        }
+
+        if self.exists()? {
+            info!(
+                "kind cluster '{}' already exists, skipping creation",
+                self.name
+            );
+            return Ok(());
+        }
+
+        info!("Creating kind cluster '{}'", self.name);
+        let status = Command::new("kind")
+            .args(self.create_args())
+            .status()
+            .context("Failed to run `kind create cluster`")?;
+        if !status.success() {
+            anyhow::bail!("kind create cluster failed with exit code: {}", status);
+        }
+        Ok(())
+    }
+
+    fn is_ready(&self) -> Result<bool> {
+        if self.dry_run {
+            return Ok(true);
+        }
+        any_node_ready(&self.kubectl())
+    }
+
+    fn kubectl(&self) -> Kubectl {
+        Kubectl::standalone(None, Some(&format!("kind-{}", self.name)))
+    }
+
+    fn load_images(&self, archive: &Path) -> Result<()> {
+        if self.dry_run {
+            println!(
+                "  kind load image-archive {} --name {}",
+                archive.display(),
+                self.name
+            );
+            return Ok(());
+        }
+        let status = Command::new("kind")
+            .args(["load", "image-archive"])
+            .arg(archive)
+            .args(["--name", &self.name])
+            .status()
+            .context("Failed to run `kind load image-archive`")?;
+        if !status.success() {
+            anyhow::bail!("kind load image-archive failed with exit code: {}", status);
+        }
+        Ok(())
+    }
+}
+
+/// A cluster that already exists; nothing is installed
+pub struct ExistingCluster {
+    pub config: ExistingClusterConfig,
+    pub dry_run: bool,
+}
+
+impl ExistingCluster {
+    fn target(&self) -> String {
+        let context = self.config.context.as_deref().unwrap_or("current context");
+        match &self.config.kubeconfig {
+            Some(kubeconfig) => format!("{} in {}", context, kubeconfig),
+            None => context.to_string(),
+        }
+    }
+}
+
+impl ClusterProvider for ExistingCluster {
+    fn name(&self) -> &'static str {
+        RUNTIME_EXISTING
+    }
+
+    fn describe(&self) -> String {
+        format!("existing cluster, {}", self.target())
+    }
+
+    fn install(&self) -> Result<()> {
+        if self.dry_run {
+            println!("📋 Existing Cluster Plan:");
+            println!("  Target: {}", self.target());
+            println!(
+                "  Installation is skipped; `{}` must reach the cluster.",
+                self.kubectl()
+            );
+            println!();
+            return Ok(());
+        }
+
+        let output = self
+            .kubectl()
+            .execute(&SystemCommandExecutor, &["cluster-info"], None)
+            .context("Failed to run kubectl; is it installed?")?;
+        if output.status != 0 {
+            anyhow::bail!(
+                "Cannot reach the existing cluster ({}): {}",
+                self.target(),
+                output.stderr.trim()
+            );
+        }
+        info!("Using existing cluster ({})", self.target());
+        Ok(())
+    }
+
+    fn is_ready(&self) -> Result<bool> {
+        if self.dry_run {
+            return Ok(true);
+        }
+        any_node_ready(&self.kubectl())
+    }
+
+    fn kubectl(&self) -> Kubectl {
+        Kubectl::standalone(
+            self.config.kubeconfig.as_deref(),
+            self.config.context.as_deref(),
+        )
+    }
+
+    fn load_images(&self, archive: &Path) -> Result<()> {
+        anyhow::bail!(
+            "Cannot load {} into an existing cluster; import it on every node \
+             (e.g. `ctr -n k8s.io images import`) or push it to a registry the cluster can reach",
+            archive.display()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::k8s_bootstrap::{K3sConfig, K3sInstallConfig};
+
+    struct RecordingExecutor(std::cell::RefCell<Vec<String>>);
+
+    impl CommandExecutor for RecordingExecutor {
+        fn execute(
+            &self,
+            program: &str,
+            args: &[&str],
+            _input: Option<&str>,
+        ) -> Result<CommandOutput> {
+            self.0
+                .borrow_mut()
+                .push(format!("{} {}", program, args.join(" ")));
+            Ok(CommandOutput {
+                status: 0,
+                stdout: String::new(),
+                stderr: String::new(),
+            })
+        }
+    }
+
+    fn cluster(runtime: &str) -> ClusterConfig {
+        ClusterConfig {
+            name: "demo".to_string(),
+            runtime: runtime.to_string(),
+            k3s: None,
+            kind: None,
+            existing: None,
+        }
+    }
+
+    #[test]
+    fn given_each_runtime_when_for_cluster_then_selects_matching_provider() {
+        let mut k3s = cluster("k3s");
+        k3s.k3s = Some(K3sConfig {
+            version: "v1.28.2+k3s1".to_string(),
+            install: K3sInstallConfig {
+                channel: "stable".to_string(),
+                disable: vec![],
+            },
+            data_dir: "/var/lib/rancher/k3s".to_string(),
+            node_name: "demo-node".to_string(),
+            extra_args: vec![],
+        });
+
+        let provider = for_cluster(&k3s, true, None).unwrap();
+        assert_eq!(provider.name(), "k3s");
+        assert_eq!(provider.kubectl().to_string(), "k3s kubectl");
+
+        let provider = for_cluster(&cluster("kind"), true, None).unwrap();
+        assert_eq!(provider.name(), "kind");
+        assert_eq!(
+            provider.kubectl().to_string(),
+            "kubectl --context kind-demo"
+        );
+
+        let provider = for_cluster(&cluster("existing-cluster"), true, None).unwrap();
+        assert_eq!(provider.name(), "existing-cluster");
+        assert_eq!(provider.kubectl().to_string(), "kubectl");
+    }
+
+    #[test]
+    fn given_k3s_runtime_without_k3s_section_when_for_cluster_then_fails() {
+        let err = for_cluster(&cluster("k3s"), true, None).err().unwrap();
+        assert!(err.to_string().contains("cluster.k3s is required"));
+    }
+
+    #[test]
+    fn given_unknown_runtime_when_for_cluster_then_lists_supported_runtimes() {
+        let err = for_cluster(&cluster("eks"), true, None).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unsupported cluster runtime 'eks' (expected one of: k3s, kind, existing-cluster)"
+        );
+    }
+
+    #[test]
+    fn given_existing_cluster_context_when_kubectl_execute_then_prefixes_selection_args() {
+        let existing = ExistingCluster {
+            config: ExistingClusterConfig {
+                kubeconfig: Some("/etc/demon/kubeconfig".to_string()),
+                context: Some("staging".to_string()),
+            },
+            dry_run: true,
+        };
+        let executor = RecordingExecutor(Default::default());
+
+        existing
+            .kubectl()
+            .execute(&executor, &["get", "pods", "-n", "demon-system"], None)
+            .unwrap();
+
+        assert_eq!(
+            executor.0.borrow().as_slice(),
+            ["kubectl --kubeconfig /etc/demon/kubeconfig --context staging get pods -n demon-system"]
+        );
+    }
+
+    #[test]
+    fn given_kind_config_when_create_args_then_passes_image_and_config() {
+        let kind = KindCluster {
+            name: "demo".to_string(),
+            config: KindConfig {
+                node_image: Some("kindest/node:v1.29.2".to_string()),
+                config_file: Some("kind.yaml".to_string()),
+            },
+            dry_run: true,
+        };
+
+        assert_eq!(
+            kind.create_args().join(" "),
+            "create cluster --name demo --image kindest/node:v1.29.2 --config kind.yaml --wait 300s"
+        );
+        assert!(kind.install().is_ok());
+        assert!(kind.is_ready().unwrap());
+    }
+}
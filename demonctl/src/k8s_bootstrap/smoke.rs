@@ -0,0 +1,384 @@
+//! Post-deploy smoke test for `k8s-bootstrap bootstrap --smoke`
+//!
+//! Ready pods do not prove the event path works. The smoke suite
+//! port-forwards to the deployed services, publishes a ritual event through
+//! NATS, reads the run back through the Operate UI API and exercises the
+//! runtime's ritual API, then reports every check as passed, failed or
+//! skipped.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::net::TcpListener;
+use std::process::{Child, Stdio};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::k8s_bootstrap::provider::Kubectl;
+
+/// Ritual id the smoke event is published under
+pub const SMOKE_RITUAL: &str = "k8s-smoke";
+
+const NATS_SERVICE: (&str, u16) = ("svc/nats", 4222);
+const UI_SERVICE: (&str, u16) = ("svc/operate-ui", 3000);
+const RUNTIME_SERVICE: (&str, u16) = ("svc/demon-runtime", 8080);
+
+/// `APP:RITUAL` run on the runtime by `--smoke-ritual`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RitualTarget {
+    pub app: String,
+    pub ritual: String,
+}
+
+impl FromStr for RitualTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.split_once(':') {
+            Some((app, ritual)) if !app.is_empty() && !ritual.is_empty() => Ok(Self {
+                app: app.to_string(),
+                ritual: ritual.to_string(),
+            }),
+            _ => anyhow::bail!("expected APP:RITUAL, got '{}'", value),
+        }
+    }
+}
+
+pub struct SmokeOptions {
+    pub namespace: String,
+    pub ritual: Option<RitualTarget>,
+    /// Upper bound for each polling check
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmokeStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmokeCheck {
+    pub name: &'static str,
+    pub status: SmokeStatus,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// Machine-readable result of the smoke suite
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmokeReport {
+    /// No check failed; skipped checks do not fail the suite
+    pub passed: bool,
+    pub run_id: String,
+    pub checks: Vec<SmokeCheck>,
+}
+
+impl SmokeReport {
+    fn new(run_id: String) -> Self {
+        Self {
+            passed: true,
+            run_id,
+            checks: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, name: &'static str, started: Instant, outcome: Result<String>) {
+        let (status, detail) = match outcome {
+            Ok(detail) => (SmokeStatus::Passed, detail),
+            Err(err) => (SmokeStatus::Failed, format!("{:#}", err)),
+        };
+        self.passed &= status != SmokeStatus::Failed;
+        self.checks.push(SmokeCheck {
+            name,
+            status,
+            detail,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+    }
+
+    fn skip(&mut self, name: &'static str, detail: impl Into<String>) {
+        self.checks.push(SmokeCheck {
+            name,
+            status: SmokeStatus::Skipped,
+            detail: detail.into(),
+            duration_ms: 0,
+        });
+    }
+
+    fn status(&self, name: &str) -> Option<SmokeStatus> {
+        self.checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| check.status)
+    }
+}
+
+/// Subject the smoke event is published on; the Operate UI finds runs by
+/// `demon.ritual.v1.<tenant>.*.<runId>.events`
+pub fn smoke_subject(run_id: &str) -> String {
+    format!("demon.ritual.v1.default.{}.{}.events", SMOKE_RITUAL, run_id)
+}
+
+pub fn smoke_event(run_id: &str) -> Value {
+    json!({
+        "event": "ritual.started:v1",
+        "ts": chrono::Utc::now().to_rfc3339(),
+        "tenantId": "default",
+        "runId": run_id,
+        "ritualId": SMOKE_RITUAL,
+        "source": "k8s-bootstrap-smoke",
+    })
+}
+
+/// Run every check against the deployment in `options.namespace`
+pub async fn run(kubectl: &Kubectl, options: &SmokeOptions) -> SmokeReport {
+    let run_id = format!("smoke-{}", chrono::Utc::now().timestamp_millis());
+    let mut report = SmokeReport::new(run_id.clone());
+
+    let started = Instant::now();
+    let outcome = publish_smoke_event(kubectl, &options.namespace, &run_id).await;
+    report.record("nats_publish", started, outcome);
+
+    if report.status("nats_publish") == Some(SmokeStatus::Passed) {
+        let started = Instant::now();
+        let outcome = read_run_from_ui(kubectl, options, &run_id).await;
+        report.record("operate_ui_api", started, outcome);
+    } else {
+        report.skip("operate_ui_api", "no smoke event was published");
+    }
+
+    let started = Instant::now();
+    let outcome = runtime_ritual(kubectl, options).await;
+    report.record("runtime_ritual", started, outcome);
+
+    report
+}
+
+async fn publish_smoke_event(kubectl: &Kubectl, namespace: &str, run_id: &str) -> Result<String> {
+    let forward = PortForward::open(kubectl, namespace, NATS_SERVICE).await?;
+    let client = async_nats::connect(format!("nats://127.0.0.1:{}", forward.local_port))
+        .await
+        .context("Failed to connect to NATS through the port-forward")?;
+    let js = async_nats::jetstream::new(client);
+
+    let subject = smoke_subject(run_id);
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert("Nats-Msg-Id", format!("{}:started", run_id).as_str());
+    let ack = js
+        .publish_with_headers(
+            subject.clone(),
+            headers,
+            serde_json::to_vec(&smoke_event(run_id))?.into(),
+        )
+        .await
+        .context("Failed to publish the smoke event")?
+        .await
+        .with_context(|| format!("No JetStream stream accepted {}", subject))?;
+    Ok(format!(
+        "published {} to stream {} (seq {})",
+        subject, ack.stream, ack.sequence
+    ))
+}
+
+async fn read_run_from_ui(
+    kubectl: &Kubectl,
+    options: &SmokeOptions,
+    run_id: &str,
+) -> Result<String> {
+    let forward = PortForward::open(kubectl, &options.namespace, UI_SERVICE).await?;
+    let url = format!("{}/api/runs/{}", forward.base_url(), run_id);
+    let client = reqwest::Client::new();
+
+    let deadline = Instant::now() + options.timeout;
+    let mut last = String::new();
+    while Instant::now() < deadline {
+        match client.get(&url).send().await {
+            Ok(response) if response.status().is_success() => {
+                let run: Value = response.json().await.context("Invalid run JSON")?;
+                let events = run["events"].as_array().map(Vec::len).unwrap_or(0);
+                if events > 0 {
+                    return Ok(format!("GET {} returned {} event(s)", url, events));
+                }
+                last = "run has no events yet".to_string();
+            }
+            Ok(response) => last = format!("HTTP {}", response.status()),
+            Err(err) => last = err.to_string(),
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+    anyhow::bail!("GET {} did not return the smoke run: {}", url, last)
+}
+
+async fn runtime_ritual(kubectl: &Kubectl, options: &SmokeOptions) -> Result<String> {
+    let forward = PortForward::open(kubectl, &options.namespace, RUNTIME_SERVICE).await?;
+    let base = format!("{}/api/v1/rituals", forward.base_url());
+    let client = reqwest::Client::new();
+
+    let Some(target) = &options.ritual else {
+        let url = format!("{}/queue", base);
+        client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("GET {}", url))?;
+        return Ok(format!(
+            "GET {} succeeded; pass --smoke-ritual APP:RITUAL to run a ritual",
+            url
+        ));
+    };
+
+    let created: Value = client
+        .post(format!("{}/{}/runs", base, target.ritual))
+        .json(&json!({ "app": target.app, "parameters": {} }))
+        .send()
+        .await?
+        .error_for_status()
+        .with_context(|| format!("Failed to schedule {}:{}", target.app, target.ritual))?
+        .json()
+        .await?;
+    let run_id = created["runId"]
+        .as_str()
+        .context("Run response has no runId")?
+        .to_string();
+
+    let url = format!("{}/{}/runs/{}", base, target.ritual, run_id);
+    let deadline = Instant::now() + options.timeout;
+    while Instant::now() < deadline {
+        let run: Value = client
+            .get(&url)
+            .query(&[("app", target.app.as_str())])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        match run["status"].as_str() {
+            Some("Completed") => {
+                return Ok(format!(
+                    "{}:{} run {} completed",
+                    target.app, target.ritual, run_id
+                ))
+            }
+            Some(status @ ("Failed" | "Canceled")) => anyhow::bail!(
+                "{}:{} run {} {}: {}",
+                target.app,
+                target.ritual,
+                run_id,
+                status.to_lowercase(),
+                run["error"].as_str().unwrap_or("no error reported")
+            ),
+            _ => tokio::time::sleep(Duration::from_secs(2)).await,
+        }
+    }
+    anyhow::bail!(
+        "{}:{} run {} did not finish within {}s",
+        target.app,
+        target.ritual,
+        run_id,
+        options.timeout.as_secs()
+    )
+}
+
+/// `kubectl port-forward` to a service on a free local port, stopped on drop
+struct PortForward {
+    child: Child,
+    local_port: u16,
+}
+
+impl PortForward {
+    async fn open(kubectl: &Kubectl, namespace: &str, service: (&str, u16)) -> Result<Self> {
+        let (target, remote_port) = service;
+        let local_port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let child = kubectl
+            .command()
+            .args([
+                "port-forward",
+                "-n",
+                namespace,
+                target,
+                &format!("{}:{}", local_port, remote_port),
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start `{} port-forward {}`", kubectl, target))?;
+        let mut forward = Self { child, local_port };
+
+        for _ in 0..20 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", local_port))
+                .await
+                .is_ok()
+            {
+                return Ok(forward);
+            }
+            if let Some(status) = forward.child.try_wait()? {
+                anyhow::bail!("port-forward to {} exited with {}", target, status);
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        anyhow::bail!("port-forward to {} did not open within 10s", target)
+    }
+
+    fn base_url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.local_port)
+    }
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_app_and_ritual_when_parse_ritual_target_then_splits_on_colon() {
+        let target: RitualTarget = "hoss:noop".parse().unwrap();
+        assert_eq!(target.app, "hoss");
+        assert_eq!(target.ritual, "noop");
+
+        assert!("noop".parse::<RitualTarget>().is_err());
+        assert!(":noop".parse::<RitualTarget>().is_err());
+    }
+
+    #[test]
+    fn given_failed_check_when_recorded_then_report_fails_but_skips_do_not() {
+        let mut report = SmokeReport::new("smoke-1".to_string());
+        report.record("nats_publish", Instant::now(), Ok("ok".to_string()));
+        report.skip("operate_ui_api", "not needed");
+        assert!(report.passed);
+
+        report.record(
+            "runtime_ritual",
+            Instant::now(),
+            Err(anyhow::anyhow!("HTTP 503")),
+        );
+        assert!(!report.passed);
+        assert_eq!(report.status("runtime_ritual"), Some(SmokeStatus::Failed));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["checks"][1]["status"], "skipped");
+        assert_eq!(json["checks"][2]["detail"], "HTTP 503");
+    }
+
+    #[test]
+    fn given_run_id_when_smoke_event_then_matches_ui_subject_layout() {
+        let event = smoke_event("smoke-42");
+        assert_eq!(event["event"], "ritual.started:v1");
+        assert_eq!(event["runId"], "smoke-42");
+        assert_eq!(
+            smoke_subject("smoke-42"),
+            "demon.ritual.v1.default.k8s-smoke.smoke-42.events"
+        );
+    }
+}
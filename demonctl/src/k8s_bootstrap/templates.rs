@@ -51,7 +51,9 @@ impl TemplateRenderer {
         Ok(rendered_manifests.join("\n---\n"))
     }
 
-    fn build_template_context(
+    /// Values the manifest templates are rendered with; also the Helm
+    /// chart's `values.yaml`
+    pub(crate) fn build_template_context(
         &self,
         config: &K8sBootstrapConfig,
     ) -> Result<HashMap<String, Value>> {
@@ -631,7 +633,7 @@ mod tests {
             cluster: crate::k8s_bootstrap::ClusterConfig {
                 name: "test-cluster".to_string(),
                 runtime: "k3s".to_string(),
-                k3s: crate::k8s_bootstrap::K3sConfig {
+                k3s: Some(crate::k8s_bootstrap::K3sConfig {
                     version: "v1.28.0+k3s1".to_string(),
                     install: crate::k8s_bootstrap::K3sInstallConfig {
                         channel: "stable".to_string(),
@@ -640,7 +642,9 @@ mod tests {
                     data_dir: "/var/lib/rancher/k3s".to_string(),
                     node_name: "k3s-node".to_string(),
                     extra_args: vec![],
-                },
+                }),
+                kind: None,
+                existing: None,
             },
             demon: DemonConfig {
                 nats_url: "nats://localhost:4222".to_string(),
@@ -739,7 +743,7 @@ mod tests {
             cluster: crate::k8s_bootstrap::ClusterConfig {
                 name: "env-test".to_string(),
                 runtime: "k3s".to_string(),
-                k3s: crate::k8s_bootstrap::K3sConfig {
+                k3s: Some(crate::k8s_bootstrap::K3sConfig {
                     version: "v1.28.0+k3s1".to_string(),
                     install: crate::k8s_bootstrap::K3sInstallConfig {
                         channel: "stable".to_string(),
@@ -748,7 +752,9 @@ mod tests {
                     data_dir: "/var/lib/rancher/k3s".to_string(),
                     node_name: "env-node".to_string(),
                     extra_args: vec![],
-                },
+                }),
+                kind: None,
+                existing: None,
             },
             demon: DemonConfig {
                 nats_url: "nats://localhost:4222".to_string(),
@@ -1253,7 +1259,7 @@ volumes:
             cluster: crate::k8s_bootstrap::ClusterConfig {
                 name: "test-cluster".to_string(),
                 runtime: "k3s".to_string(),
-                k3s: crate::k8s_bootstrap::K3sConfig {
+                k3s: Some(crate::k8s_bootstrap::K3sConfig {
                     version: "v1.28.0+k3s1".to_string(),
                     install: crate::k8s_bootstrap::K3sInstallConfig {
                         channel: "stable".to_string(),
@@ -1262,7 +1268,9 @@ volumes:
                     data_dir: "/var/lib/rancher/k3s".to_string(),
                     node_name: "k3s-node".to_string(),
                     extra_args: vec![],
-                },
+                }),
+                kind: None,
+                existing: None,
             },
             demon: DemonConfig {
                 nats_url: "nats://localhost:4222".to_string(),
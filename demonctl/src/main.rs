@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::thread;
@@ -8,9 +8,11 @@ use tracing::{info, Level};
 use tracing_subscriber::{fmt, EnvFilter};
 
 mod commands;
+mod credentials;
 mod docker;
 mod github;
 mod k8s_bootstrap;
+mod output;
 
 const MANIFEST_FILES: [&str; 5] = [
     "namespace.yaml",
@@ -83,22 +85,39 @@ enum Commands {
         /// Path to ritual YAML or `<app>[:version]:<ritual>` alias
         #[arg(value_name = "TARGET")]
         target: String,
-        #[arg(long, default_value = "false")]
-        replay: bool,
-        /// Save result envelope to result.json
+        /// Replay a recorded run from its JetStream events instead of starting a new one
+        #[arg(long, value_name = "RUN_ID")]
+        replay: Option<String>,
+        /// Tenant of the run to replay (default: default, then legacy subjects)
+        #[arg(long, requires = "replay")]
+        tenant: Option<String>,
+        /// Save result envelope to result.json (replay: report to replay-report.json)
         #[arg(long)]
         save: bool,
         /// Output directory for saved files (default: current directory)
         #[arg(long, value_name = "DIR")]
         output_dir: Option<PathBuf>,
+        /// Set a ritual input (repeatable; overrides --inputs)
+        #[arg(long = "set", value_name = "KEY=VALUE", conflicts_with = "replay")]
+        set: Vec<String>,
+        /// JSON file of ritual input values
+        #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+        inputs: Option<PathBuf>,
+        /// Print the run's events live, then exit with its final status
+        #[arg(long, conflicts_with = "replay")]
+        watch: bool,
     },
     /// Contract management commands
     Contracts {
+        #[command(flatten)]
+        output: output::OutputArgs,
         #[command(subcommand)]
         cmd: ContractsCommands,
     },
     /// Manage secrets for capsules
     Secrets {
+        #[command(flatten)]
+        output: output::OutputArgs,
         #[command(subcommand)]
         cmd: SecretsCommands,
     },
@@ -131,6 +150,18 @@ enum Commands {
         #[arg(long)]
         bundle: Option<String>,
 
+        /// Seeding profile from the bundle's seed.profiles (repeatable;
+        /// default: seed.activeProfiles, else preview-min)
+        #[arg(long = "seed-profile", value_name = "NAME")]
+        seed_profiles: Vec<String>,
+
+        /// Set a bundle variable (repeatable; overrides --values)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// YAML file of bundle variable values (repeatable; later files win)
+        #[arg(long = "values", value_name = "FILE")]
+        values: Vec<PathBuf>,
+
         /// Optional overrides (flags > bundle > env)
         #[arg(long)]
         nats_url: Option<String>,
@@ -142,9 +173,22 @@ enum Commands {
         /// Verify only (resolve + provenance check; no NATS/seed/verify-UI phases)
         #[arg(long, action = ArgAction::SetTrue)]
         verify_only: bool,
+
+        /// Report drift between the bundle and the provisioned stream,
+        /// fixtures and Operate UI without changing anything
+        #[arg(long, action = ArgAction::SetTrue)]
+        diff: bool,
+        /// With --diff, reconcile only the differences bootstrap owns
+        #[arg(long, action = ArgAction::SetTrue, requires = "diff")]
+        apply: bool,
+
+        #[command(flatten)]
+        output: output::OutputArgs,
     },
     /// Kubernetes bootstrapper commands
     K8sBootstrap {
+        #[command(flatten)]
+        output: output::OutputArgs,
         #[command(subcommand)]
         cmd: K8sBootstrapCommands,
     },
@@ -163,6 +207,38 @@ enum Commands {
         #[command(flatten)]
         args: commands::inspect::InspectArgs,
     },
+    /// List and inspect ritual runs
+    Runs {
+        #[command(flatten)]
+        args: commands::runs::RunsArgs,
+    },
+    /// Publish, fetch and compare contracts in the Schema Registry
+    Registry {
+        #[command(flatten)]
+        args: commands::registry::RegistryArgs,
+    },
+    /// Run NATS, the runtime, Operate UI and registry locally
+    Dev {
+        #[command(flatten)]
+        args: commands::dev::DevArgs,
+    },
+    /// Create, sign and verify bootstrapper bundles
+    Bundle {
+        #[command(flatten)]
+        args: commands::bundle::BundleArgs,
+    },
+    /// Sign in and save a token for Operate UI and registry commands
+    Login {
+        #[command(flatten)]
+        args: commands::login::LoginArgs,
+    },
+    /// Remove the saved login
+    Logout,
+    /// Print the saved login's access token, refreshing it if needed
+    Token {
+        #[command(flatten)]
+        args: commands::login::TokenArgs,
+    },
     /// Flow export/import commands for agent-authored workflows
     Flow {
         #[command(flatten)]
@@ -170,6 +246,12 @@ enum Commands {
     },
     /// Print version and exit
     Version,
+    /// Generate a shell completion script
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
     /// Run a batch of rituals from a YAML file (minimal driver for HOSS v0.2)
     Batch {
         /// Path to batch YAML file
@@ -184,34 +266,91 @@ enum Commands {
     },
 }
 
+/// Config and image options shared by the k8s-bootstrap commands that render manifests
+#[derive(Args)]
+struct K8sRenderArgs {
+    /// Path to bootstrap configuration YAML file
+    #[arg(long, short, value_name = "FILE")]
+    config: String,
+    /// Resolve GHCR digests before rendering manifests
+    #[arg(long, action = ArgAction::SetTrue)]
+    use_latest_digests: bool,
+    /// GitHub Actions workflow file name or ID
+    #[arg(long, value_name = "WORKFLOW")]
+    workflow: Option<String>,
+    /// Repository in <owner>/<repo> format
+    #[arg(long, value_name = "OWNER/REPO")]
+    repo: Option<String>,
+    /// GitHub API base URL (for GitHub Enterprise)
+    #[arg(long, value_name = "URL")]
+    api_url: Option<String>,
+    /// Branch to inspect for docker build digests (default: main)
+    #[arg(long, value_name = "BRANCH", default_value = DEFAULT_DOCKER_BRANCH)]
+    branch: String,
+    /// Install from an offline bundle written by `docker save-bundle`
+    #[arg(long, value_name = "DIR", conflicts_with = "use_latest_digests")]
+    offline: Option<PathBuf>,
+}
+
 #[derive(Subcommand)]
 enum K8sBootstrapCommands {
     /// Bootstrap a Kubernetes cluster with Demon
     Bootstrap {
+        #[command(flatten)]
+        render: K8sRenderArgs,
+        /// Perform validation only, don't execute
+        #[arg(long)]
+        dry_run: bool,
+        /// Write a Helm chart to DIR instead of deploying
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["dry_run", "offline"])]
+        helm_chart: Option<PathBuf>,
+        /// After health checks, run the end-to-end smoke suite
+        #[arg(long, conflicts_with_all = ["dry_run", "helm_chart"])]
+        smoke: bool,
+        /// Schedule APP:RITUAL on the runtime during the smoke suite
+        #[arg(long, value_name = "APP:RITUAL", requires = "smoke")]
+        smoke_ritual: Option<k8s_bootstrap::smoke::RitualTarget>,
+        /// Also write the smoke report as JSON to FILE
+        #[arg(long, value_name = "FILE", requires = "smoke")]
+        smoke_report: Option<PathBuf>,
+        /// Seconds each smoke check may poll before failing
+        #[arg(long, value_name = "SECS", default_value_t = 60, requires = "smoke")]
+        smoke_timeout: u64,
+        /// Enable verbose output
+        #[arg(long, short)]
+        verbose: bool,
+    },
+    /// Apply config changes to a running deployment and wait for the rollout
+    Upgrade {
+        #[command(flatten)]
+        render: K8sRenderArgs,
+        /// Show what would change without applying it
+        #[arg(long)]
+        dry_run: bool,
+        /// Enable verbose output
+        #[arg(long, short)]
+        verbose: bool,
+        /// Seconds to wait for each workload to roll out
+        #[arg(long, value_name = "SECS", default_value_t = 240)]
+        timeout: u64,
+    },
+    /// Delete everything bootstrap created, workloads first and the namespace last
+    Uninstall {
         /// Path to bootstrap configuration YAML file
         #[arg(long, short, value_name = "FILE")]
         config: String,
-        /// Perform validation only, don't execute
+        /// Skip the confirmation prompt
+        #[arg(long, short)]
+        yes: bool,
+        /// List what would be deleted without deleting it
         #[arg(long)]
         dry_run: bool,
+        /// Leave the namespace itself in place
+        #[arg(long)]
+        keep_namespace: bool,
         /// Enable verbose output
         #[arg(long, short)]
         verbose: bool,
-        /// Resolve GHCR digests before rendering manifests
-        #[arg(long, action = ArgAction::SetTrue)]
-        use_latest_digests: bool,
-        /// GitHub Actions workflow file name or ID
-        #[arg(long, value_name = "WORKFLOW")]
-        workflow: Option<String>,
-        /// Repository in <owner>/<repo> format
-        #[arg(long, value_name = "OWNER/REPO")]
-        repo: Option<String>,
-        /// GitHub API base URL (for GitHub Enterprise)
-        #[arg(long, value_name = "URL")]
-        api_url: Option<String>,
-        /// Branch to inspect for docker build digests (default: main)
-        #[arg(long, value_name = "BRANCH", default_value = DEFAULT_DOCKER_BRANCH)]
-        branch: String,
     },
 }
 
@@ -222,6 +361,22 @@ enum DockerCommands {
         #[command(subcommand)]
         cmd: DockerDigestsCommands,
     },
+    /// Save the images (and k3s release) a bootstrap config needs for
+    /// `k8s-bootstrap --offline`
+    SaveBundle {
+        /// Path to bootstrap configuration YAML file
+        #[arg(long, short, value_name = "FILE")]
+        config: String,
+        /// Directory to write the bundle to
+        #[arg(long, value_name = "DIR")]
+        output: PathBuf,
+        /// k3s architecture to download: amd64 or arm64
+        #[arg(long, default_value = "amd64")]
+        arch: String,
+        /// Enable verbose output
+        #[arg(long, short)]
+        verbose: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -317,6 +472,34 @@ enum SecretsCommands {
         #[arg(long, value_enum, default_value_t = ProviderType::Envfile)]
         provider: ProviderType,
     },
+    /// Rotate an existing secret, replacing its value and recording the rotation time
+    Rotate {
+        /// Scope and key in format: scope/key
+        #[arg(value_name = "SCOPE/KEY")]
+        key_path: String,
+        /// New secret value (use --from-env to read from environment variable)
+        #[arg(
+            value_name = "VALUE",
+            conflicts_with = "from_env",
+            required_unless_present_any = ["from_env", "stdin"]
+        )]
+        value: Option<String>,
+        /// Read value from environment variable
+        #[arg(long, conflicts_with = "value")]
+        from_env: Option<String>,
+        /// Read value from stdin
+        #[arg(long, conflicts_with_all = &["value", "from_env"])]
+        stdin: bool,
+        /// Path to secrets file (defaults to CONFIG_SECRETS_FILE or .demon/secrets.json)
+        #[arg(long)]
+        secrets_file: Option<String>,
+    },
+    /// Encrypt a plaintext secrets file, or re-encrypt it with the current key source
+    Migrate {
+        /// Path to secrets file (defaults to CONFIG_SECRETS_FILE or .demon/secrets.json)
+        #[arg(long)]
+        secrets_file: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -335,7 +518,7 @@ enum GraphCommands {
         /// Graph ID
         #[arg(long)]
         graph_id: String,
-        /// Path to JSON file containing seed mutations
+        /// Mutations as a JSON array or JSONL file (`-` reads stdin)
         #[arg(value_name = "MUTATIONS_FILE")]
         mutations_file: String,
     },
@@ -356,7 +539,7 @@ enum GraphCommands {
         /// Optional parent commit ID
         #[arg(long)]
         parent_ref: Option<String>,
-        /// Path to JSON file containing mutations
+        /// Mutations as a JSON array or JSONL file (`-` reads stdin)
         #[arg(value_name = "MUTATIONS_FILE")]
         mutations_file: String,
     },
@@ -395,6 +578,96 @@ enum GraphCommands {
         /// Graph ID
         #[arg(long)]
         graph_id: String,
+        /// Query through the runtime REST API instead of NATS
+        #[arg(long)]
+        api_url: Option<String>,
+    },
+    /// Get a node snapshot at a commit
+    GetNode {
+        /// Tenant ID
+        #[arg(long)]
+        tenant_id: String,
+        /// Project ID
+        #[arg(long)]
+        project_id: String,
+        /// Namespace
+        #[arg(long)]
+        namespace: String,
+        /// Graph ID
+        #[arg(long)]
+        graph_id: String,
+        /// Commit ID to query
+        #[arg(long)]
+        commit_id: String,
+        /// Node ID to retrieve
+        #[arg(long)]
+        node_id: String,
+        /// Query through the runtime REST API instead of NATS
+        #[arg(long)]
+        api_url: Option<String>,
+    },
+    /// List nodes reachable from a node within a depth
+    Neighbors {
+        /// Tenant ID
+        #[arg(long)]
+        tenant_id: String,
+        /// Project ID
+        #[arg(long)]
+        project_id: String,
+        /// Namespace
+        #[arg(long)]
+        namespace: String,
+        /// Graph ID
+        #[arg(long)]
+        graph_id: String,
+        /// Commit ID to query
+        #[arg(long)]
+        commit_id: String,
+        /// Starting node ID
+        #[arg(long)]
+        node_id: String,
+        /// Maximum number of hops to traverse
+        #[arg(long, default_value_t = 1)]
+        depth: u32,
+        /// Maximum number of nodes to return (capped at 1000)
+        #[arg(long)]
+        limit: Option<u32>,
+        /// Cursor from the previous page's `nextCursor`
+        #[arg(long)]
+        cursor: Option<String>,
+        /// Query through the runtime REST API instead of NATS
+        #[arg(long)]
+        api_url: Option<String>,
+    },
+    /// Check whether two nodes are connected within a depth
+    PathExists {
+        /// Tenant ID
+        #[arg(long)]
+        tenant_id: String,
+        /// Project ID
+        #[arg(long)]
+        project_id: String,
+        /// Namespace
+        #[arg(long)]
+        namespace: String,
+        /// Graph ID
+        #[arg(long)]
+        graph_id: String,
+        /// Commit ID to query
+        #[arg(long)]
+        commit_id: String,
+        /// Source node ID
+        #[arg(long)]
+        from: String,
+        /// Target node ID
+        #[arg(long)]
+        to: String,
+        /// Maximum number of hops to traverse
+        #[arg(long, default_value_t = 5)]
+        max_depth: u32,
+        /// Query through the runtime REST API instead of NATS
+        #[arg(long)]
+        api_url: Option<String>,
     },
     /// Get a commit by ID via REST API
     GetCommit {
@@ -507,10 +780,40 @@ enum ContractsCommands {
         /// Registry endpoint URL
         #[arg(long, default_value = "http://localhost:8090")]
         registry_endpoint: String,
-        /// JWT token for authentication (or use JWT_TOKEN env var)
+        /// JWT token for authentication (or JWT_TOKEN env var, or demonctl login)
         #[arg(long)]
         jwt: Option<String>,
     },
+    /// Lint contract schemas for breaking changes against a git ref
+    Lint {
+        /// Git ref holding the old schemas (e.g. origin/main)
+        #[arg(long, value_name = "REF")]
+        base: String,
+        /// Schema directory, relative to the current directory
+        #[arg(long, value_name = "DIR", default_value = "contracts/schemas")]
+        dir: PathBuf,
+    },
+    /// Generate Rust config structs from the capsule config schemas
+    CodegenConfig {
+        /// Directory holding the `<capsule>-config.v1.json` schemas
+        #[arg(long, value_name = "DIR", default_value = "contracts/config")]
+        schema_dir: PathBuf,
+        /// Directory to write the generated modules to
+        #[arg(
+            long,
+            value_name = "DIR",
+            default_value = "crates/config-loader/src/generated"
+        )]
+        out: PathBuf,
+        /// Fail if the generated files are stale instead of writing them
+        #[arg(long)]
+        check: bool,
+    },
+    /// Generate typed TypeScript or Rust client models from the contract schemas
+    Codegen {
+        #[command(flatten)]
+        args: commands::codegen::CodegenArgs,
+    },
 }
 
 #[derive(Copy, Clone, Debug, ValueEnum)]
@@ -534,6 +837,7 @@ fn init_tracing() {
     let _ = fmt()
         .with_env_filter(EnvFilter::from_default_env())
         .with_target(false)
+        .with_writer(std::io::stderr)
         .try_init();
 }
 
@@ -545,10 +849,32 @@ async fn main() -> Result<()> {
     match cli.cmd {
         Commands::Run {
             target,
-            replay: _,
+            replay,
+            tenant,
             save,
             output_dir,
+            set,
+            inputs,
+            watch,
         } => {
+            if let Some(run_id) = replay {
+                let report = replay_run(&target, &run_id, tenant.as_deref()).await?;
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                if save {
+                    save_replay_report(&report, &output_dir)?;
+                }
+                if !report.matched {
+                    eprintln!(
+                        "Replay of run {} diverged in {} step(s)",
+                        run_id,
+                        report.mismatches().count()
+                    );
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+
+            let inputs = engine::rituals::inputs::RunInputs::from_sources(inputs.as_deref(), &set)?;
             let mut engine = engine::rituals::Engine::new();
 
             let mut _alias_spec = None;
@@ -565,8 +891,26 @@ async fn main() -> Result<()> {
                 .to_str()
                 .ok_or_else(|| anyhow::anyhow!("Ritual path contains invalid UTF-8"))?;
 
+            if watch {
+                let run_id = uuid::Uuid::new_v4().to_string();
+                let mut engine = engine.with_run_id(run_id.clone());
+                println!("Watching run {}", run_id);
+                let (completion, code) = commands::runs::watch(
+                    &run_id,
+                    engine.run_from_file_with_inputs_result(run_path_str, &inputs),
+                )
+                .await?;
+                if save {
+                    save_result_envelope(&completion, &output_dir)?;
+                }
+                std::process::exit(code);
+            }
+
             if save {
-                match engine.run_from_file_with_result(run_path_str).await {
+                match engine
+                    .run_from_file_with_inputs_result(run_path_str, &inputs)
+                    .await
+                {
                     Ok(result_event) => {
                         println!(
                             "{}",
@@ -584,7 +928,10 @@ async fn main() -> Result<()> {
                         std::process::exit(1);
                     }
                 }
-            } else if let Err(e) = engine.run_from_file(run_path_str).await {
+            } else if let Err(e) = engine
+                .run_from_file_with_inputs(run_path_str, &inputs)
+                .await
+            {
                 eprintln!("Error running ritual: {:?}", e);
                 std::process::exit(1);
             }
@@ -596,11 +943,18 @@ async fn main() -> Result<()> {
             verify,
             ritual_id,
             bundle,
+            seed_profiles,
+            set,
+            values,
             nats_url,
             stream_name,
             ui_base_url,
             verify_only,
+            diff,
+            apply,
+            output,
         } => {
+            let values = bootstrapper_demonctl::bundle::BundleValues::from_sources(&values, &set)?;
             run_bootstrap(
                 profile,
                 ensure_stream,
@@ -608,21 +962,27 @@ async fn main() -> Result<()> {
                 verify,
                 ritual_id,
                 bundle,
+                seed_profiles,
+                values,
                 nats_url,
                 stream_name,
                 ui_base_url,
                 verify_only,
+                DiffMode { diff, apply },
+                output.format,
             )
             .await?;
         }
-        Commands::Contracts { cmd } => {
-            handle_contracts_command(cmd).await?;
+        Commands::Contracts { output, cmd } => {
+            handle_contracts_command(cmd, output.format).await?;
         }
-        Commands::K8sBootstrap { cmd } => {
-            handle_k8s_bootstrap_command(cmd).await?;
+        Commands::K8sBootstrap { output, cmd } => {
+            handle_k8s_bootstrap_command(cmd, output.format).await?;
         }
-        Commands::Secrets { cmd } => {
-            handle_secrets_command(cmd)?;
+        Commands::Secrets { output, cmd } => {
+            // Vault HTTP uses a blocking client, which must not run on the async runtime
+            tokio::task::spawn_blocking(move || handle_secrets_command(cmd, output.format))
+                .await??;
         }
         Commands::Graph { cmd } => {
             handle_graph_command(cmd).await?;
@@ -631,7 +991,7 @@ async fn main() -> Result<()> {
             handle_docker_command(cmd).await?;
         }
         Commands::App { cmd } => {
-            commands::app::handle(cmd)?;
+            commands::app::handle(cmd).await?;
         }
         Commands::Inspect { args } => {
             commands::inspect::run(args).await?;
@@ -639,9 +999,38 @@ async fn main() -> Result<()> {
         Commands::Flow { args } => {
             commands::flow::run(args).await?;
         }
+        Commands::Runs { args } => {
+            commands::runs::run(args).await?;
+        }
+        Commands::Registry { args } => {
+            commands::registry::run(args).await?;
+        }
+        Commands::Dev { args } => {
+            commands::dev::run(args).await?;
+        }
+        Commands::Bundle { args } => {
+            commands::bundle::run(args)?;
+        }
+        Commands::Login { args } => {
+            commands::login::login(args).await?;
+        }
+        Commands::Logout => {
+            commands::login::logout()?;
+        }
+        Commands::Token { args } => {
+            commands::login::token(args).await?;
+        }
         Commands::Version => {
             println!("{}", env!("CARGO_PKG_VERSION"));
         }
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "demonctl",
+                &mut std::io::stdout(),
+            );
+        }
         Commands::Batch {
             file,
             save,
@@ -722,10 +1111,14 @@ async fn run_bootstrap(
     verify: bool,
     ritual_id: String,
     bundle: Option<String>,
+    seed_profiles: Vec<String>,
+    values: bootstrapper_demonctl::bundle::BundleValues,
     nats_url: Option<String>,
     stream_name: Option<String>,
     ui_base_url: Option<String>,
     verify_only: bool,
+    diff: DiffMode,
+    format: output::OutputFormat,
 ) -> Result<()> {
     // Only initialize tracing if not already initialized
     let _ = tracing_subscriber::fmt()
@@ -753,55 +1146,40 @@ async fn run_bootstrap(
     let bundle_for_config = effective_bundle
         .as_deref()
         .filter(|uri| !uri.starts_with("lib://"));
-    let (cfg, provenance) = bootstrapper_demonctl::compute_effective_config(
+    let (cfg, provenance) = bootstrapper_demonctl::compute_effective_config_with_values(
         bundle_for_config.map(std::path::Path::new),
+        &values,
         nats_url.as_deref(),
         stream_name.as_deref(),
         None, // subjects - not in CLI yet
         ui_base_url.as_deref(),
     )?;
 
-    println!(
-        "{}",
-        serde_json::json!({
-            "phase":"config",
-            "effective":{
-                "nats_url": cfg.nats_url,
-                "stream_name": cfg.stream_name,
-                "subjects": cfg.subjects,
-                "dedupe": cfg.dedupe_window_secs,
-                "ui_url": cfg.ui_url,
-            },
-            "provenance": provenance
-        })
-    );
+    let mut phases = PhaseLog::new(format);
+    phases.record(serde_json::json!({
+        "phase":"config",
+        "effective":{
+            "nats_url": cfg.nats_url,
+            "stream_name": cfg.stream_name,
+            "subjects": cfg.subjects,
+            "dedupe": cfg.dedupe_window_secs,
+            "ui_url": cfg.ui_url,
+        },
+        "provenance": provenance
+    }));
 
     if verify_only {
         if let Some(uri) = effective_bundle.as_deref() {
-            if uri.starts_with("lib://local/") {
-                let mut idx_path = std::path::PathBuf::from("bootstrapper/library/index.json");
-                if !idx_path.exists() {
-                    for prefix in ["..", "../..", "../../.."].iter() {
-                        let p =
-                            std::path::Path::new(prefix).join("bootstrapper/library/index.json");
-                        if p.exists() {
-                            idx_path = p;
-                            break;
-                        }
-                    }
-                }
-                let resolved = bootstrapper_demonctl::libindex::resolve_local(uri, &idx_path)?;
-                println!(
-                    "{}",
-                    serde_json::json!({
-                        "phase":"resolve",
-                        "uri": uri,
-                        "provider": resolved.provider,
-                        "name": resolved.name,
-                        "version": resolved.version,
-                        "path": resolved.path
-                    })
-                );
+            if uri.starts_with("lib://local/") || uri.starts_with("lib://remote/") {
+                let resolved = resolve_library_uri(uri)?;
+                phases.record(serde_json::json!({
+                    "phase":"resolve",
+                    "uri": uri,
+                    "provider": resolved.provider,
+                    "name": resolved.name,
+                    "version": resolved.version,
+                    "path": resolved.path
+                }));
                 let vr = bootstrapper_demonctl::provenance::verify_provenance(
                     &resolved.path,
                     &resolved.pub_key_id,
@@ -809,131 +1187,345 @@ async fn run_bootstrap(
                     &resolved.sig_ed25519,
                 )?;
                 if vr.signature_ok {
-                    println!(
-                        "{}",
-                        serde_json::json!({
-                            "phase":"verify",
-                            "bundle": {"name": resolved.name, "version": resolved.version},
-                            "digest": vr.digest_hex,
-                            "signature": "ok",
-                            "pubKeyId": resolved.pub_key_id
-                        })
-                    );
+                    phases.record(serde_json::json!({
+                        "phase":"verify",
+                        "bundle": {"name": resolved.name, "version": resolved.version},
+                        "digest": vr.digest_hex,
+                        "signature": "ok",
+                        "pubKeyId": resolved.pub_key_id
+                    }));
                 } else {
-                    println!(
-                        "{}",
-                        serde_json::json!({
-                            "phase":"verify",
-                            "bundle": {"name": resolved.name, "version": resolved.version},
-                            "digest": vr.digest_hex,
-                            "signature": "failed",
-                            "reason": vr.reason.unwrap_or_else(|| "unknown".to_string()),
-                            "pubKeyId": resolved.pub_key_id
-                        })
-                    );
+                    phases.record(serde_json::json!({
+                        "phase":"verify",
+                        "bundle": {"name": resolved.name, "version": resolved.version},
+                        "digest": vr.digest_hex,
+                        "signature": "failed",
+                        "reason": vr.reason.unwrap_or_else(|| "unknown".to_string()),
+                        "pubKeyId": resolved.pub_key_id
+                    }));
+                    phases.finish()?;
                     anyhow::bail!("signature verification failed");
                 }
-                return Ok(());
+                return phases.finish();
             }
         }
+        phases.finish()?;
         anyhow::bail!("--verify-only requires --bundle lib://local/... URI");
     }
 
-    if !(ensure_stream || seed || verify) {
+    let plan = SeedPlan {
+        ritual: &ritual_id,
+        bundle_uri: effective_bundle.as_deref(),
+        profiles: &seed_profiles,
+        values: &values,
+    };
+    let result = if diff.diff {
+        run_diff(&cfg, &plan, diff.apply, &mut phases).await
+    } else if !(ensure_stream || seed || verify) {
         // default: run all
-        run_all(&cfg, &ritual_id, effective_bundle.as_deref()).await
+        run_all(&cfg, &plan, &mut phases).await
     } else {
-        run_some(&cfg, ensure_stream, seed, verify, &ritual_id).await
+        run_some(&cfg, ensure_stream, seed, verify, &plan, &mut phases).await
+    };
+    // Report the phases that did complete even when a later one failed
+    phases.finish()?;
+    result
+}
+
+/// `--diff` / `--apply`
+#[derive(Clone, Copy)]
+struct DiffMode {
+    diff: bool,
+    apply: bool,
+}
+
+/// Bootstrap phase records: printed as JSON lines while running in table
+/// mode, collected into one `BootstrapReport` document otherwise
+struct PhaseLog {
+    format: output::OutputFormat,
+    phases: Vec<serde_json::Value>,
+}
+
+#[derive(serde::Serialize)]
+struct BootstrapReport<'a> {
+    phases: &'a [serde_json::Value],
+}
+
+impl PhaseLog {
+    fn new(format: output::OutputFormat) -> Self {
+        Self {
+            format,
+            phases: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, phase: serde_json::Value) {
+        if self.format.is_table() {
+            println!("{}", phase);
+        } else {
+            self.phases.push(phase);
+        }
     }
+
+    fn finish(self) -> Result<()> {
+        output::emit(
+            self.format,
+            "BootstrapReport",
+            &BootstrapReport {
+                phases: &self.phases,
+            },
+            || {},
+        )
+    }
+}
+
+/// What the seed phase publishes
+struct SeedPlan<'a> {
+    ritual: &'a str,
+    bundle_uri: Option<&'a str>,
+    /// `--seed-profile` values; these need a bundle
+    profiles: &'a [String],
+    /// `--set` / `--values` overrides for the bundle's variables
+    values: &'a bootstrapper_demonctl::bundle::BundleValues,
+}
+
+/// Resolve `lib://local/` against the repo library index and `lib://remote/`
+/// against `DEMON_LIBRARY_INDEX_URL` (blocking HTTP, hence `block_in_place`)
+fn resolve_library_uri(uri: &str) -> Result<bootstrapper_demonctl::libindex::ResolvedBundle> {
+    let index = bootstrapper_demonctl::libindex::default_index_path();
+    tokio::task::block_in_place(|| bootstrapper_demonctl::libindex::resolve(uri, &index))
+}
+
+fn load_seed_bundle(
+    uri: &str,
+    values: &bootstrapper_demonctl::bundle::BundleValues,
+) -> Result<bootstrapper_demonctl::bundle::Bundle> {
+    // Resolve the URI if it's a lib:// URI
+    let bundle_path = if uri.starts_with("lib://local/") || uri.starts_with("lib://remote/") {
+        resolve_library_uri(uri)?.path
+    } else {
+        std::path::PathBuf::from(uri)
+    };
+    bootstrapper_demonctl::bundle::load_bundle_with_values(&bundle_path, values)
+}
+
+async fn seed_bundle(
+    js: &async_nats::jetstream::Context,
+    cfg: &bootstrapper_demonctl::BootstrapConfig,
+    plan: &SeedPlan<'_>,
+    uri: &str,
+    b: &bootstrapper_demonctl::bundle::Bundle,
+    phases: &mut PhaseLog,
+) -> Result<()> {
+    let b_json = serde_json::to_value(b)?;
+    let applied = bootstrapper_demonctl::seed_from_bundle(
+        js,
+        &b_json,
+        plan.ritual,
+        &cfg.ui_url,
+        plan.profiles,
+    )
+    .await?;
+    info!("seed: ok");
+    phases.record(serde_json::json!({ "phase": "seed", "bundle": uri, "profiles": applied }));
+    Ok(())
 }
 
 async fn run_all(
     cfg: &bootstrapper_demonctl::BootstrapConfig,
-    ritual: &str,
-    bundle_uri: Option<&str>,
+    plan: &SeedPlan<'_>,
+    phases: &mut PhaseLog,
 ) -> Result<()> {
     let stream = bootstrapper_demonctl::ensure_stream(cfg).await?;
     info!(name=%stream.cached_info().config.name, "ensure_stream: ok");
+    phases.record(serde_json::json!({
+        "phase": "ensure_stream",
+        "stream_name": stream.cached_info().config.name
+    }));
     let client = async_nats::connect(&cfg.nats_url).await?;
     let js = async_nats::jetstream::new(client);
-    if let Some(uri) = bundle_uri {
-        // Resolve the URI if it's a lib:// URI
-        let bundle_path = if uri.starts_with("lib://local/") {
-            let mut idx_path = std::path::PathBuf::from("bootstrapper/library/index.json");
-            if !idx_path.exists() {
-                for prefix in ["..", "../..", "../../.."].iter() {
-                    let p = std::path::Path::new(prefix).join("bootstrapper/library/index.json");
-                    if p.exists() {
-                        idx_path = p;
-                        break;
-                    }
-                }
-            }
-            let resolved = bootstrapper_demonctl::libindex::resolve_local(uri, &idx_path)?;
-            resolved.path
-        } else {
-            std::path::PathBuf::from(uri)
-        };
-        let b = bootstrapper_demonctl::bundle::load_bundle(&bundle_path)?;
-        let b_json = serde_json::to_value(&b)?;
-        bootstrapper_demonctl::seed_from_bundle(&js, &b_json, &cfg.stream_name, &cfg.ui_url)
-            .await?;
-        let token = b
-            .operate_ui
-            .admin_token
-            .or_else(|| std::env::var("ADMIN_TOKEN").ok());
+    if let Some(uri) = plan.bundle_uri {
+        let b = load_seed_bundle(uri, plan.values)?;
+        seed_bundle(&js, cfg, plan, uri, &b, phases).await?;
+        warn_unused_admin_token(&b);
+        let token = ui_token().await?;
         bootstrapper_demonctl::verify_ui_with_token(&cfg.ui_url, token.as_deref()).await?;
     } else {
-        bootstrapper_demonctl::seed_preview_min(&js, ritual, &cfg.ui_url).await?;
-        bootstrapper_demonctl::verify_ui_with_token(
-            &cfg.ui_url,
-            std::env::var("ADMIN_TOKEN").ok().as_deref(),
-        )
-        .await?;
+        anyhow::ensure!(
+            plan.profiles.is_empty(),
+            "--seed-profile requires a bundle (--bundle)"
+        );
+        bootstrapper_demonctl::seed_preview_min(&js, plan.ritual, &cfg.ui_url).await?;
+        info!("seed: ok");
+        phases.record(serde_json::json!({ "phase": "seed", "ritual_id": plan.ritual }));
+        let token = ui_token().await?;
+        bootstrapper_demonctl::verify_ui_with_token(&cfg.ui_url, token.as_deref()).await?;
     }
-    info!("seed: ok");
     info!("verify: ok");
+    phases.record(serde_json::json!({ "phase": "verify_ui", "ui_url": cfg.ui_url }));
     info!("done: all checks passed");
     Ok(())
 }
 
+/// Bearer JWT for the UI checks: `DEMONCTL_JWT`, otherwise the saved login
+async fn ui_token() -> Result<Option<String>> {
+    credentials::resolve_token(std::env::var("DEMONCTL_JWT").ok().as_deref()).await
+}
+
+fn warn_unused_admin_token(bundle: &bootstrapper_demonctl::bundle::Bundle) {
+    if bundle.operate_ui.admin_token.is_some() {
+        tracing::warn!(
+            "operateUi.adminToken is no longer used; verify authenticates with DEMONCTL_JWT or the saved demonctl login"
+        );
+    }
+}
+
 async fn run_some(
     cfg: &bootstrapper_demonctl::BootstrapConfig,
     ensure_stream: bool,
     seed: bool,
     verify: bool,
-    ritual_id: &str,
+    plan: &SeedPlan<'_>,
+    phases: &mut PhaseLog,
 ) -> Result<()> {
     if ensure_stream {
         let stream = bootstrapper_demonctl::ensure_stream(cfg).await?;
         info!(name=%stream.cached_info().config.name, "ensure_stream: ok");
+        phases.record(serde_json::json!({
+            "phase": "ensure_stream",
+            "stream_name": stream.cached_info().config.name
+        }));
     }
     if seed {
         let client = async_nats::connect(&cfg.nats_url).await?;
         let js = async_nats::jetstream::new(client);
-        bootstrapper_demonctl::seed_preview_min(&js, ritual_id, &cfg.ui_url).await?;
-        info!("seed: ok");
+        match plan.bundle_uri {
+            // Profiles are opt-in here; a plain --seed keeps seeding preview-min
+            Some(uri) if !plan.profiles.is_empty() => {
+                let b = load_seed_bundle(uri, plan.values)?;
+                seed_bundle(&js, cfg, plan, uri, &b, phases).await?;
+            }
+            _ => {
+                anyhow::ensure!(
+                    plan.profiles.is_empty(),
+                    "--seed-profile requires a bundle (--bundle)"
+                );
+                bootstrapper_demonctl::seed_preview_min(&js, plan.ritual, &cfg.ui_url).await?;
+                info!("seed: ok");
+                phases.record(serde_json::json!({ "phase": "seed", "ritual_id": plan.ritual }));
+            }
+        }
     }
     if verify {
-        bootstrapper_demonctl::verify_ui_with_token(
-            &cfg.ui_url,
-            std::env::var("ADMIN_TOKEN").ok().as_deref(),
-        )
-        .await?;
+        let token = ui_token().await?;
+        bootstrapper_demonctl::verify_ui_with_token(&cfg.ui_url, token.as_deref()).await?;
         info!("verify: ok");
+        phases.record(serde_json::json!({ "phase": "verify_ui", "ui_url": cfg.ui_url }));
     }
     info!("done");
     Ok(())
 }
 
-/// Save the result envelope from a ritual completion event to result.json
-fn save_result_envelope(
-    result_event: &serde_json::Value,
-    output_dir: &Option<PathBuf>,
+/// Report drift against the bundle and, with `apply`, reconcile it. Drift is
+/// not an error: the report is the result.
+async fn run_diff(
+    cfg: &bootstrapper_demonctl::BootstrapConfig,
+    plan: &SeedPlan<'_>,
+    apply: bool,
+    phases: &mut PhaseLog,
 ) -> Result<()> {
-    use envelope::EnvelopeValidator;
+    let bundle = match plan.bundle_uri {
+        Some(uri) => serde_json::to_value(load_seed_bundle(uri, plan.values)?)?,
+        None => {
+            anyhow::ensure!(
+                plan.profiles.is_empty(),
+                "--seed-profile requires a bundle (--bundle)"
+            );
+            serde_json::json!({})
+        }
+    };
+    let client = async_nats::connect(&cfg.nats_url).await?;
+    let js = async_nats::jetstream::new(client);
 
-    // Extract the envelope from the "outputs" field
+    let report =
+        bootstrapper_demonctl::drift::detect(&js, cfg, &bundle, plan.ritual, plan.profiles).await?;
+    info!(
+        in_sync = report.in_sync(),
+        items = report.items.len(),
+        "diff: ok"
+    );
+    phases.record(serde_json::json!({
+        "phase": "diff",
+        "in_sync": report.in_sync(),
+        "drift": report.items,
+    }));
+
+    if apply {
+        let applied = bootstrapper_demonctl::drift::apply(
+            &js,
+            cfg,
+            &bundle,
+            plan.ritual,
+            plan.profiles,
+            &report,
+        )
+        .await?;
+        info!(items = applied.len(), "apply: ok");
+        phases.record(serde_json::json!({ "phase": "apply", "applied": applied }));
+    }
+    Ok(())
+}
+
+/// Save the result envelope from a ritual completion event to result.json
+/// Resolve the ritual behind `target` and replay `run_id` from JetStream
+async fn replay_run(
+    target: &str,
+    run_id: &str,
+    tenant: Option<&str>,
+) -> Result<engine::rituals::replay::ReplayReport> {
+    let path = match commands::app::alias::parse_alias(target) {
+        Some(alias_target) => commands::app::alias::build_alias_spec(&alias_target)?
+            .spec_path()
+            .to_path_buf(),
+        None => PathBuf::from(target),
+    };
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("reading ritual spec: {}", path.display()))?;
+    let spec: serde_json::Value = serde_yaml::from_str(&text).context("parsing ritual yaml")?;
+    let ritual_id = spec
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("ritual {} has no id", path.display()))?
+        .to_string();
+
+    let nats_url =
+        std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let log = engine::rituals::log::EventLog::new(&nats_url).await?;
+    engine::rituals::replay::replay_run(&log, tenant, &ritual_id, run_id, Some(&spec)).await
+}
+
+fn save_replay_report(
+    report: &engine::rituals::replay::ReplayReport,
+    output_dir: &Option<PathBuf>,
+) -> Result<()> {
+    let dir = output_dir
+        .as_ref()
+        .map(|p| p.as_path())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    let report_path = dir.join("replay-report.json");
+    std::fs::write(&report_path, serde_json::to_string_pretty(report)?)?;
+    info!("Replay report saved to: {}", report_path.display());
+    Ok(())
+}
+
+fn save_result_envelope(
+    result_event: &serde_json::Value,
+    output_dir: &Option<PathBuf>,
+) -> Result<()> {
+    use envelope::EnvelopeValidator;
+
+    // Extract the envelope from the "outputs" field
     let envelope_value = result_event
         .get("outputs")
         .ok_or_else(|| anyhow::anyhow!("No outputs field found in result event"))?;
@@ -964,7 +1556,10 @@ fn save_result_envelope(
     Ok(())
 }
 
-async fn handle_contracts_command(cmd: ContractsCommands) -> Result<()> {
+async fn handle_contracts_command(
+    cmd: ContractsCommands,
+    format: output::OutputFormat,
+) -> Result<()> {
     match cmd {
         ContractsCommands::ValidateEnvelope {
             file,
@@ -974,11 +1569,11 @@ async fn handle_contracts_command(cmd: ContractsCommands) -> Result<()> {
             bulk,
         } => {
             if let Some(bulk_dir) = bulk {
-                validate_bulk_envelopes(&bulk_dir, remote, &registry_endpoint).await?;
+                validate_bulk_envelopes(&bulk_dir, remote, &registry_endpoint, format).await?;
             } else if stdin {
-                validate_envelope_stdin(remote, &registry_endpoint).await?;
+                validate_envelope_stdin(remote, &registry_endpoint, format).await?;
             } else if let Some(file_path) = file {
-                validate_envelope_file(&file_path, remote, &registry_endpoint).await?;
+                validate_envelope_file(&file_path, remote, &registry_endpoint, format).await?;
             } else {
                 anyhow::bail!("Must specify either a file path, --stdin, or --bulk");
             }
@@ -990,18 +1585,18 @@ async fn handle_contracts_command(cmd: ContractsCommands) -> Result<()> {
             secrets_file,
         } => {
             if stdin {
-                validate_config_stdin(schema, secrets_file).await?;
+                validate_config_stdin(schema, secrets_file, format).await?;
             } else if let Some(file_path) = file {
-                validate_config_file(&file_path, schema, secrets_file).await?;
+                validate_config_file(&file_path, schema, secrets_file, format).await?;
             } else {
                 anyhow::bail!("Must specify either a file path or --stdin");
             }
         }
         ContractsCommands::Bundle {
-            format,
+            format: bundle_format,
             include_wit,
         } => {
-            export_contracts_bundle(&format, include_wit).await?;
+            export_contracts_bundle(&bundle_format, include_wit, format).await?;
         }
         ContractsCommands::Publish {
             name,
@@ -1013,12 +1608,13 @@ async fn handle_contracts_command(cmd: ContractsCommands) -> Result<()> {
             registry_endpoint,
             jwt,
         } => {
-            // Get JWT from arg or environment variable
-            let jwt_token = jwt
-                .or_else(|| std::env::var("JWT_TOKEN").ok())
+            // Get JWT from arg, environment variable or the saved login
+            let jwt = jwt.or_else(|| std::env::var("JWT_TOKEN").ok());
+            let jwt_token = credentials::resolve_token(jwt.as_deref())
+                .await?
                 .ok_or_else(|| {
                     anyhow::anyhow!(
-                        "JWT token required: use --jwt or set JWT_TOKEN environment variable"
+                        "JWT token required: use --jwt, set JWT_TOKEN or run demonctl login"
                     )
                 })?;
 
@@ -1031,9 +1627,215 @@ async fn handle_contracts_command(cmd: ContractsCommands) -> Result<()> {
                 descriptor_path: descriptor_path.as_deref(),
                 registry_endpoint: &registry_endpoint,
                 jwt: &jwt_token,
+                format,
             })
             .await?;
         }
+        ContractsCommands::Lint { base, dir } => {
+            lint_contracts_against(&base, &dir, format)?;
+        }
+        ContractsCommands::CodegenConfig {
+            schema_dir,
+            out,
+            check,
+        } => {
+            codegen_config(&schema_dir, &out, check, format)?;
+        }
+        ContractsCommands::Codegen { args } => {
+            commands::codegen::run(args, format).await?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigCodegenReport {
+    out: PathBuf,
+    files: Vec<PathBuf>,
+    check: bool,
+    stale: Vec<PathBuf>,
+}
+
+/// Generate config structs from `schema_dir` into `out`, or with `check` only
+/// report the files that would change
+fn codegen_config(
+    schema_dir: &Path,
+    out: &Path,
+    check: bool,
+    format: output::OutputFormat,
+) -> Result<()> {
+    use config_loader::codegen;
+
+    let files = codegen::generate_config_modules(schema_dir)?;
+    let stale = codegen::stale_files(out, &files)?;
+    if !check && !stale.is_empty() {
+        codegen::write_generated(out, &files)?;
+    }
+    let report = ConfigCodegenReport {
+        out: out.to_path_buf(),
+        files: files.iter().map(|f| out.join(&f.path)).collect(),
+        check,
+        stale,
+    };
+    output::emit(format, "ConfigCodegenReport", &report, || {
+        if report.stale.is_empty() {
+            println!(
+                "✓ {} generated file{} up to date in {}",
+                report.files.len(),
+                if report.files.len() == 1 { "" } else { "s" },
+                out.display()
+            );
+        } else {
+            let verb = if check { "Stale" } else { "Updated" };
+            for path in &report.stale {
+                println!("{} {}", verb, path.display());
+            }
+        }
+    })?;
+    if check && !report.stale.is_empty() {
+        anyhow::bail!("Generated config types are stale: run demonctl contracts codegen-config");
+    }
+    Ok(())
+}
+
+/// Lint `dir` in the working tree against the same directory at git `base`
+fn lint_contracts_against(base: &str, dir: &Path, format: output::OutputFormat) -> Result<()> {
+    let git = |args: &[&str]| -> Result<String> {
+        let out = Command::new("git")
+            .args(args)
+            .output()
+            .context("Failed to run git")?;
+        if !out.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+        }
+        String::from_utf8(out.stdout).context("git output is not UTF-8")
+    };
+
+    // `REF:./path` resolves relative to the current directory
+    let tree = format!(
+        "{}:./{}",
+        base,
+        dir.to_string_lossy().trim_start_matches("./")
+    );
+    let old_dir = tempfile::TempDir::new()?;
+    for name in git(&["ls-tree", "-r", "--name-only", &tree])?.lines() {
+        if !name.ends_with(".json") {
+            continue;
+        }
+        let target = old_dir.path().join(name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target, git(&["show", &format!("{}/{}", tree, name)])?)
+            .with_context(|| format!("Failed to write {}", target.display()))?;
+    }
+
+    let report = contract_linter::lint_contract_dirs(old_dir.path(), dir)?;
+    output::emit(format, "ContractLintReport", &report, || {
+        print_contract_lint_report(base, &report)
+    })?;
+    if !report.is_ok() {
+        anyhow::bail!("Contract lint failed: breaking changes need a version bump");
+    }
+    Ok(())
+}
+
+fn print_contract_lint_report(base: &str, report: &contract_linter::DirLintReport) {
+    use contract_linter::Severity;
+
+    println!(
+        "Compared {} schema{} against {}",
+        report.compared,
+        if report.compared == 1 { "" } else { "s" },
+        base
+    );
+    for file in &report.files {
+        println!();
+        println!("{}", file.path);
+        for finding in &file.report.findings {
+            let marker = match finding.severity {
+                Severity::Breaking => "✗",
+                Severity::Risky => "!",
+                Severity::Informational => "-",
+            };
+            println!("  {} [{}] {}", marker, finding.rule, finding.message);
+        }
+        if file.report.has_breaking_changes() && !file.report.version_check_passed {
+            println!(
+                "  Version bump required ({} -> {})",
+                file.report
+                    .current_version
+                    .as_deref()
+                    .unwrap_or("unversioned"),
+                file.report
+                    .proposed_version
+                    .as_deref()
+                    .unwrap_or("unversioned")
+            );
+        }
+    }
+    if !report.added.is_empty() {
+        println!();
+        println!("Added: {}", report.added.join(", "));
+    }
+    if !report.removed.is_empty() {
+        println!();
+        println!("✗ Removed: {}", report.removed.join(", "));
+    }
+    println!();
+    if report.is_ok() {
+        println!("✓ No unversioned breaking changes");
+    }
+}
+
+/// Result of validating one envelope or config (`ValidationReport` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ValidationReport {
+    /// `envelope` or `config`
+    subject: &'static str,
+    /// File path, or `-` for stdin
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capsule: Option<String>,
+    valid: bool,
+    errors: Vec<ValidationIssue>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ValidationIssue {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_path: Option<String>,
+}
+
+impl ValidationIssue {
+    fn message(message: impl Into<String>) -> Self {
+        Self {
+            path: None,
+            message: message.into(),
+            schema_path: None,
+        }
+    }
+}
+
+/// Print a validation report and exit 1 if it failed
+fn finish_validation(
+    report: ValidationReport,
+    format: output::OutputFormat,
+    table: impl FnOnce(&ValidationReport),
+) -> Result<()> {
+    output::emit(format, "ValidationReport", &report, || table(&report))?;
+    if !report.valid {
+        std::process::exit(1);
     }
     Ok(())
 }
@@ -1042,126 +1844,163 @@ async fn validate_envelope_file(
     file_path: &str,
     remote: bool,
     registry_endpoint: &str,
+    format: output::OutputFormat,
 ) -> Result<()> {
-    use envelope::EnvelopeValidator;
-
     let content = std::fs::read_to_string(file_path)?;
     let envelope: serde_json::Value = serde_json::from_str(&content)?;
-
-    if remote {
-        validate_envelope_remote(&envelope, registry_endpoint).await
-    } else {
-        let validator = EnvelopeValidator::new()?;
-        match validator.validate_json(&envelope) {
-            Ok(_) => {
-                println!("✓ Valid envelope");
-                Ok(())
-            }
-            Err(e) => {
-                eprintln!("✗ Invalid envelope:");
-                eprintln!("  {}", e);
-                std::process::exit(1);
-            }
-        }
-    }
+    report_envelope(file_path, &envelope, remote, registry_endpoint, format).await
 }
 
-async fn validate_envelope_stdin(remote: bool, registry_endpoint: &str) -> Result<()> {
-    use envelope::EnvelopeValidator;
+async fn validate_envelope_stdin(
+    remote: bool,
+    registry_endpoint: &str,
+    format: output::OutputFormat,
+) -> Result<()> {
     use std::io::Read;
 
     let mut buffer = String::new();
     std::io::stdin().read_to_string(&mut buffer)?;
     let envelope: serde_json::Value = serde_json::from_str(&buffer)?;
+    report_envelope("-", &envelope, remote, registry_endpoint, format).await
+}
 
-    if remote {
-        validate_envelope_remote(&envelope, registry_endpoint).await
-    } else {
-        let validator = EnvelopeValidator::new()?;
-        match validator.validate_json(&envelope) {
-            Ok(_) => {
-                println!("✓ Valid envelope");
-                Ok(())
-            }
-            Err(e) => {
-                eprintln!("✗ Invalid envelope:");
-                eprintln!("  {}", e);
-                std::process::exit(1);
+async fn report_envelope(
+    source: &str,
+    envelope: &serde_json::Value,
+    remote: bool,
+    registry_endpoint: &str,
+    format: output::OutputFormat,
+) -> Result<()> {
+    let errors = envelope_issues(envelope, remote, registry_endpoint).await?;
+    let report = ValidationReport {
+        subject: "envelope",
+        source: source.to_string(),
+        capsule: None,
+        valid: errors.is_empty(),
+        errors,
+    };
+    finish_validation(report, format, |report| {
+        if report.valid {
+            println!("✓ Valid envelope");
+        } else {
+            eprintln!("✗ Invalid envelope:");
+            for error in &report.errors {
+                match &error.path {
+                    Some(path) => eprintln!("  {} at {}", error.message, path),
+                    None => eprintln!("  {}", error.message),
+                }
             }
         }
+    })
+}
+
+/// Validate an envelope locally or against the registry; empty means valid
+async fn envelope_issues(
+    envelope: &serde_json::Value,
+    remote: bool,
+    registry_endpoint: &str,
+) -> Result<Vec<ValidationIssue>> {
+    if remote {
+        return validate_envelope_remote(envelope, registry_endpoint).await;
     }
+    let validator = envelope::EnvelopeValidator::new()?;
+    Ok(match validator.validate_json(envelope) {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![ValidationIssue::message(e.to_string())],
+    })
+}
+
+/// Per-directory summary of `--bulk` validation (`BulkValidationReport` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkValidationReport {
+    valid_count: usize,
+    invalid_count: usize,
+    results: Vec<BulkValidationResult>,
+}
+
+#[derive(serde::Serialize)]
+struct BulkValidationResult {
+    path: PathBuf,
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 async fn validate_bulk_envelopes(
     dir: &PathBuf,
     remote: bool,
     registry_endpoint: &str,
+    format: output::OutputFormat,
 ) -> Result<()> {
-    use envelope::EnvelopeValidator;
     use std::fs;
 
     let entries = fs::read_dir(dir)?;
     let mut results = Vec::new();
 
-    let validator = if !remote {
-        Some(EnvelopeValidator::new()?)
-    } else {
-        None
-    };
-
     for entry in entries {
         let entry = entry?;
         let path = entry.path();
         if path.is_file() && path.file_name() == Some(std::ffi::OsStr::new("result.json")) {
             let content = fs::read_to_string(&path)?;
-            let envelope: serde_json::Value = match serde_json::from_str(&content) {
-                Ok(v) => v,
-                Err(e) => {
-                    results.push((path, false, format!("JSON parse error: {}", e)));
-                    continue;
-                }
+            let error = match serde_json::from_str::<serde_json::Value>(&content) {
+                Err(e) => Some(format!("JSON parse error: {}", e)),
+                Ok(envelope) => match envelope_issues(&envelope, remote, registry_endpoint).await {
+                    Ok(issues) if issues.is_empty() => None,
+                    Ok(issues) => Some(
+                        issues
+                            .into_iter()
+                            .map(|i| i.message)
+                            .collect::<Vec<_>>()
+                            .join("; "),
+                    ),
+                    Err(e) => Some(e.to_string()),
+                },
             };
-
-            if remote {
-                match validate_envelope_remote_raw(&envelope, registry_endpoint).await {
-                    Ok(valid) => results.push((path, valid, String::new())),
-                    Err(e) => results.push((path, false, e.to_string())),
-                }
-            } else if let Some(ref v) = validator {
-                match v.validate_json(&envelope) {
-                    Ok(_) => results.push((path, true, String::new())),
-                    Err(e) => results.push((path, false, e.to_string())),
-                }
-            }
+            results.push(BulkValidationResult {
+                path,
+                valid: error.is_none(),
+                error,
+            });
         }
     }
 
-    let valid_count = results.iter().filter(|(_, valid, _)| *valid).count();
-    let invalid_count = results.len() - valid_count;
+    let valid_count = results.iter().filter(|r| r.valid).count();
+    let report = BulkValidationReport {
+        valid_count,
+        invalid_count: results.len() - valid_count,
+        results,
+    };
 
-    println!("Validation Results:");
-    println!("  Valid: {}", valid_count);
-    println!("  Invalid: {}", invalid_count);
+    output::emit(format, "BulkValidationReport", &report, || {
+        println!("Validation Results:");
+        println!("  Valid: {}", report.valid_count);
+        println!("  Invalid: {}", report.invalid_count);
 
-    if invalid_count > 0 {
-        println!("\nInvalid envelopes:");
-        for (path, valid, error) in &results {
-            if !valid {
-                println!("  ✗ {}: {}", path.display(), error);
+        if report.invalid_count > 0 {
+            println!("\nInvalid envelopes:");
+            for result in report.results.iter().filter(|r| !r.valid) {
+                println!(
+                    "  ✗ {}: {}",
+                    result.path.display(),
+                    result.error.as_deref().unwrap_or_default()
+                );
             }
+        } else {
+            println!("\n✓ All envelopes valid");
         }
+    })?;
+
+    if report.invalid_count > 0 {
         std::process::exit(1);
-    } else {
-        println!("\n✓ All envelopes valid");
     }
-
     Ok(())
 }
 
 async fn validate_envelope_remote(
     envelope: &serde_json::Value,
     registry_endpoint: &str,
-) -> Result<()> {
+) -> Result<Vec<ValidationIssue>> {
     let client = reqwest::Client::new();
     let url = format!("{}/api/contracts/validate/envelope", registry_endpoint);
 
@@ -1174,42 +2013,34 @@ async fn validate_envelope_remote(
     let result: serde_json::Value = response.json().await?;
 
     if result["valid"].as_bool().unwrap_or(false) {
-        println!("✓ Valid envelope");
-        Ok(())
-    } else {
-        eprintln!("✗ Invalid envelope:");
-        if let Some(errors) = result["errors"].as_array() {
-            for error in errors {
-                let path = error["path"].as_str().unwrap_or("");
-                let message = error["message"].as_str().unwrap_or("Unknown error");
-                eprintln!("  {} at {}", message, path);
-            }
-        }
-        std::process::exit(1);
+        return Ok(Vec::new());
     }
-}
-
-async fn validate_envelope_remote_raw(
-    envelope: &serde_json::Value,
-    registry_endpoint: &str,
-) -> Result<bool> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/api/contracts/validate/envelope", registry_endpoint);
-
-    let response = client.post(&url).json(envelope).send().await?;
-
-    if !response.status().is_success() {
-        anyhow::bail!("Registry returned error: {}", response.status());
+    let mut issues: Vec<ValidationIssue> = result["errors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|error| ValidationIssue {
+            path: Some(error["path"].as_str().unwrap_or("").to_string()),
+            message: error["message"]
+                .as_str()
+                .unwrap_or("Unknown error")
+                .to_string(),
+            schema_path: None,
+        })
+        .collect();
+    if issues.is_empty() {
+        issues.push(ValidationIssue::message(
+            "Registry reported the envelope invalid",
+        ));
     }
-
-    let result: serde_json::Value = response.json().await?;
-    Ok(result["valid"].as_bool().unwrap_or(false))
+    Ok(issues)
 }
 
 async fn validate_config_file(
     file_path: &str,
     schema: Option<String>,
     secrets_file: Option<String>,
+    format: output::OutputFormat,
 ) -> Result<()> {
     use config_loader::{ConfigManager, EnvFileSecretProvider};
     use std::path::Path;
@@ -1245,34 +2076,14 @@ async fn validate_config_file(
         config_manager.validate_config_file(&capsule_name, path)
     };
 
-    match result {
-        Ok(_) => {
-            println!("✓ Valid config for capsule: {}", capsule_name);
-            Ok(())
-        }
-        Err(config_loader::ConfigError::ValidationFailed { errors }) => {
-            eprintln!("✗ Invalid config for capsule '{}':", capsule_name);
-            for error in errors {
-                eprintln!("  Path {}: {}", error.json_pointer, error.message);
-                eprintln!("    Schema: {}", error.schema_path);
-            }
-            std::process::exit(1);
-        }
-        Err(config_loader::ConfigError::SecretResolutionFailed { error }) => {
-            eprintln!(
-                "✗ Secret resolution failed for capsule '{}': {}",
-                capsule_name, error
-            );
-            std::process::exit(1);
-        }
-        Err(e) => {
-            eprintln!("✗ Config validation failed: {}", e);
-            std::process::exit(1);
-        }
-    }
+    report_config(file_path, capsule_name, result, format)
 }
 
-async fn validate_config_stdin(schema: Option<String>, secrets_file: Option<String>) -> Result<()> {
+async fn validate_config_stdin(
+    schema: Option<String>,
+    secrets_file: Option<String>,
+    format: output::OutputFormat,
+) -> Result<()> {
     use config_loader::{ConfigManager, EnvFileSecretProvider};
     use std::io::Read;
 
@@ -1297,34 +2108,62 @@ async fn validate_config_stdin(schema: Option<String>, secrets_file: Option<Stri
         config_manager.validate_config_value(&capsule_name, &config_value)
     };
 
-    match result {
-        Ok(_) => {
-            println!("✓ Valid config for capsule: {}", capsule_name);
-            Ok(())
-        }
-        Err(config_loader::ConfigError::ValidationFailed { errors }) => {
+    report_config("-", capsule_name, result, format)
+}
+
+fn report_config(
+    source: &str,
+    capsule_name: String,
+    result: std::result::Result<(), config_loader::ConfigError>,
+    format: output::OutputFormat,
+) -> Result<()> {
+    use config_loader::ConfigError;
+
+    let errors = match &result {
+        Ok(_) => Vec::new(),
+        Err(ConfigError::ValidationFailed { errors }) => errors
+            .iter()
+            .map(|error| ValidationIssue {
+                path: Some(error.json_pointer.clone()),
+                message: error.message.clone(),
+                schema_path: Some(error.schema_path.clone()),
+            })
+            .collect(),
+        Err(ConfigError::SecretResolutionFailed { error }) => vec![ValidationIssue::message(
+            format!("Secret resolution failed: {}", error),
+        )],
+        Err(e) => vec![ValidationIssue::message(e.to_string())],
+    };
+    let report = ValidationReport {
+        subject: "config",
+        source: source.to_string(),
+        capsule: Some(capsule_name.clone()),
+        valid: errors.is_empty(),
+        errors,
+    };
+
+    finish_validation(report, format, |_| match &result {
+        Ok(_) => println!("✓ Valid config for capsule: {}", capsule_name),
+        Err(ConfigError::ValidationFailed { errors }) => {
             eprintln!("✗ Invalid config for capsule '{}':", capsule_name);
             for error in errors {
                 eprintln!("  Path {}: {}", error.json_pointer, error.message);
                 eprintln!("    Schema: {}", error.schema_path);
             }
-            std::process::exit(1);
-        }
-        Err(config_loader::ConfigError::SecretResolutionFailed { error }) => {
-            eprintln!(
-                "✗ Secret resolution failed for capsule '{}': {}",
-                capsule_name, error
-            );
-            std::process::exit(1);
-        }
-        Err(e) => {
-            eprintln!("✗ Config validation failed: {}", e);
-            std::process::exit(1);
         }
-    }
+        Err(ConfigError::SecretResolutionFailed { error }) => eprintln!(
+            "✗ Secret resolution failed for capsule '{}': {}",
+            capsule_name, error
+        ),
+        Err(e) => eprintln!("✗ Config validation failed: {}", e),
+    })
 }
 
-async fn export_contracts_bundle(format: &str, include_wit: bool) -> Result<()> {
+async fn export_contracts_bundle(
+    format: &str,
+    include_wit: bool,
+    output_format: output::OutputFormat,
+) -> Result<()> {
     use std::collections::BTreeMap;
     use std::fs;
 
@@ -1396,53 +2235,51 @@ async fn export_contracts_bundle(format: &str, include_wit: bool) -> Result<()>
         }
     }
 
-    match format {
-        "json" => {
-            let bundle = serde_json::json!({
-                "version": "1.0.0",
-                "schemas": schemas,
-                "apiContracts": api_contracts,
-                "wit": wit_definitions,
-            });
-            println!("{}", serde_json::to_string_pretty(&bundle)?);
+    // -o json|yaml wins over --format summary
+    if format == "json" || !output_format.is_table() {
+        let bundle = serde_json::json!({
+            "version": "1.0.0",
+            "schemas": schemas,
+            "apiContracts": api_contracts,
+            "wit": wit_definitions,
+        });
+        output::print(output_format, &bundle)?;
+    } else {
+        println!("Contract Bundle Summary");
+        println!("=======================");
+        println!();
+        println!("Schemas ({}):", schemas.len());
+        for (name, content) in &schemas {
+            let lines = content.lines().count();
+            let bytes = content.len();
+            println!("  - {} ({} lines, {} bytes)", name, lines, bytes);
         }
-        _ => {
-            println!("Contract Bundle Summary");
-            println!("=======================");
+
+        if !api_contracts.is_empty() {
             println!();
-            println!("Schemas ({}):", schemas.len());
-            for (name, content) in &schemas {
+            println!("API Contracts ({}):", api_contracts.len());
+            for (name, content) in &api_contracts {
                 let lines = content.lines().count();
                 let bytes = content.len();
                 println!("  - {} ({} lines, {} bytes)", name, lines, bytes);
             }
+        }
 
-            if !api_contracts.is_empty() {
-                println!();
-                println!("API Contracts ({}):", api_contracts.len());
-                for (name, content) in &api_contracts {
-                    let lines = content.lines().count();
-                    let bytes = content.len();
-                    println!("  - {} ({} lines, {} bytes)", name, lines, bytes);
-                }
-            }
-
-            if include_wit && !wit_definitions.is_empty() {
-                println!();
-                println!("WIT Definitions ({}):", wit_definitions.len());
-                for (name, content) in &wit_definitions {
-                    let lines = content.lines().count();
-                    let bytes = content.len();
-                    println!("  - {} ({} lines, {} bytes)", name, lines, bytes);
-                }
-            }
-
+        if include_wit && !wit_definitions.is_empty() {
             println!();
-            println!(
-                "Total contracts: {}",
-                schemas.len() + api_contracts.len() + wit_definitions.len()
-            );
+            println!("WIT Definitions ({}):", wit_definitions.len());
+            for (name, content) in &wit_definitions {
+                let lines = content.lines().count();
+                let bytes = content.len();
+                println!("  - {} ({} lines, {} bytes)", name, lines, bytes);
+            }
         }
+
+        println!();
+        println!(
+            "Total contracts: {}",
+            schemas.len() + api_contracts.len() + wit_definitions.len()
+        );
     }
 
     Ok(())
@@ -1457,6 +2294,19 @@ struct PublishContractInput<'a> {
     descriptor_path: Option<&'a Path>,
     registry_endpoint: &'a str,
     jwt: &'a str,
+    format: output::OutputFormat,
+}
+
+/// Registry acknowledgement of a publish (`PublishedContract` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishedContract {
+    name: String,
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    digest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<String>,
 }
 
 /// Publish a contract bundle to the schema registry
@@ -1472,6 +2322,7 @@ async fn publish_contract(input: PublishContractInput<'_>) -> Result<()> {
         descriptor_path,
         registry_endpoint,
         jwt,
+        format,
     } = input;
 
     // Read schema files if provided
@@ -1502,7 +2353,9 @@ async fn publish_contract(input: PublishContractInput<'_>) -> Result<()> {
     let client = reqwest::Client::new();
     let url = format!("{}/registry/contracts", registry_endpoint);
 
-    println!("Publishing contract {} v{} to {}", name, version, url);
+    if format.is_table() {
+        println!("Publishing contract {} v{} to {}", name, version, url);
+    }
 
     let response = client
         .post(&url)
@@ -1522,25 +2375,35 @@ async fn publish_contract(input: PublishContractInput<'_>) -> Result<()> {
     if status.is_success() {
         // Parse and display success response
         if let Ok(body_json) = serde_json::from_str::<serde_json::Value>(&body_text) {
-            println!("✓ Successfully published contract!");
-            println!();
-            println!(
-                "  Name:      {}",
-                body_json["name"].as_str().unwrap_or(name)
-            );
-            println!(
-                "  Version:   {}",
-                body_json["version"].as_str().unwrap_or(version)
-            );
-            if let Some(digest) = body_json["digest"].as_str() {
-                println!("  Digest:    {}", digest);
-            }
-            if let Some(created_at) = body_json["createdAt"].as_str() {
-                println!("  Created:   {}", created_at);
-            }
-        } else {
+            let published = PublishedContract {
+                name: body_json["name"].as_str().unwrap_or(name).to_string(),
+                version: body_json["version"].as_str().unwrap_or(version).to_string(),
+                digest: body_json["digest"].as_str().map(str::to_string),
+                created_at: body_json["createdAt"].as_str().map(str::to_string),
+            };
+            output::emit(format, "PublishedContract", &published, || {
+                println!("✓ Successfully published contract!");
+                println!();
+                println!("  Name:      {}", published.name);
+                println!("  Version:   {}", published.version);
+                if let Some(digest) = &published.digest {
+                    println!("  Digest:    {}", digest);
+                }
+                if let Some(created_at) = &published.created_at {
+                    println!("  Created:   {}", created_at);
+                }
+            })?;
+        } else if format.is_table() {
             println!("✓ Contract published successfully");
             println!("{}", body_text);
+        } else {
+            let published = PublishedContract {
+                name: name.to_string(),
+                version: version.to_string(),
+                digest: None,
+                created_at: None,
+            };
+            output::emit(format, "PublishedContract", &published, || {})?;
         }
         Ok(())
     } else {
@@ -1552,20 +2415,102 @@ async fn publish_contract(input: PublishContractInput<'_>) -> Result<()> {
     }
 }
 
-fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
-    use config_loader::{
-        secrets_store, SecretProvider, SecretsStore, VaultHttpSecretProvider, VaultStubProvider,
-    };
-    use std::env;
-    use std::io::Read;
+/// A secret that was set, rotated or deleted (`SecretChange` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SecretChange {
+    action: &'static str,
+    scope: String,
+    key: String,
+    provider: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rotated_at: Option<String>,
+}
 
-    match cmd {
-        SecretsCommands::Set {
-            key_path,
-            value,
-            from_env,
-            stdin,
-            secrets_file,
+impl SecretChange {
+    fn new(action: &'static str, scope: &str, key: &str, provider: &'static str) -> Self {
+        Self {
+            action,
+            scope: scope.to_string(),
+            key: key.to_string(),
+            provider,
+            path: None,
+            rotated_at: None,
+        }
+    }
+}
+
+/// A secrets file rewritten by `secrets migrate` (`SecretMigration` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SecretMigration {
+    path: PathBuf,
+    secret_count: usize,
+    was_encrypted: bool,
+    key_source: &'static str,
+}
+
+/// A secret read back (`Secret` kind); `value` is redacted unless `--raw`
+#[derive(serde::Serialize)]
+struct SecretValue {
+    scope: String,
+    key: String,
+    provider: &'static str,
+    value: String,
+    redacted: bool,
+}
+
+/// Redacted secrets by scope and key (`SecretList` kind)
+#[derive(serde::Serialize)]
+struct SecretList {
+    provider: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    secrets: std::collections::BTreeMap<String, std::collections::BTreeMap<String, String>>,
+}
+
+impl SecretList {
+    fn print_table(&self, heading_suffix: &str) {
+        if let Some(scope_filter) = &self.scope {
+            match self.secrets.get(scope_filter) {
+                Some(secrets) if !secrets.is_empty() => {
+                    println!("Secrets in scope '{}'{}:", scope_filter, heading_suffix);
+                    for (key, value) in secrets {
+                        println!("  {}: {}", key, value);
+                    }
+                }
+                _ => println!("No secrets found for scope: {}", scope_filter),
+            }
+        } else if self.secrets.is_empty() {
+            println!("No secrets found");
+        } else {
+            println!("Secrets{}:", heading_suffix);
+            for (scope, secrets) in &self.secrets {
+                println!("  {}:", scope);
+                for (key, value) in secrets {
+                    println!("    {}: {}", key, value);
+                }
+            }
+        }
+    }
+}
+
+fn handle_secrets_command(cmd: SecretsCommands, format: output::OutputFormat) -> Result<()> {
+    use config_loader::{
+        secrets_store, SecretProvider, SecretsStore, VaultHttpSecretProvider, VaultStubProvider,
+    };
+    use std::env;
+    use std::io::Read;
+
+    match cmd {
+        SecretsCommands::Set {
+            key_path,
+            value,
+            from_env,
+            stdin,
+            secrets_file,
             provider,
         } => {
             let (scope, key) = SecretsStore::parse_scope_key(&key_path)?;
@@ -1594,8 +2539,12 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                     #[cfg(unix)]
                     store.check_permissions()?;
 
-                    println!("✓ Secret {}/{} set successfully", scope, key);
-                    println!("  Stored in: {}", store.path().display());
+                    let mut change = SecretChange::new("set", &scope, &key, "envfile");
+                    change.path = Some(store.path().to_path_buf());
+                    output::emit(format, "SecretChange", &change, || {
+                        println!("✓ Secret {}/{} set successfully", scope, key);
+                        println!("  Stored in: {}", store.path().display());
+                    })?;
                 }
                 ProviderType::Vault => {
                     if secrets_file.is_some() {
@@ -1618,10 +2567,13 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                                 anyhow::anyhow!("Failed to store secret in Vault: {}", e)
                             })?;
 
-                        println!(
-                            "✓ Secret {}/{} set successfully in Vault (HTTP)",
-                            scope, key
-                        );
+                        let change = SecretChange::new("set", &scope, &key, "vault-http");
+                        output::emit(format, "SecretChange", &change, || {
+                            println!(
+                                "✓ Secret {}/{} set successfully in Vault (HTTP)",
+                                scope, key
+                            );
+                        })?;
                     } else {
                         // Use stub provider for file:// URLs
                         let vault_provider = VaultStubProvider::from_env().map_err(|e| {
@@ -1634,7 +2586,10 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                                 anyhow::anyhow!("Failed to store secret in vault stub: {}", e)
                             })?;
 
-                        println!("✓ Secret {}/{} set successfully in vault stub", scope, key);
+                        let change = SecretChange::new("set", &scope, &key, "vault-stub");
+                        output::emit(format, "SecretChange", &change, || {
+                            println!("✓ Secret {}/{} set successfully in vault stub", scope, key);
+                        })?;
                     }
                 }
             }
@@ -1647,7 +2602,7 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
         } => {
             let (scope, key) = SecretsStore::parse_scope_key(&key_path)?;
 
-            match provider {
+            let (provider_name, value) = match provider {
                 ProviderType::Envfile => {
                     let store = if let Some(path) = secrets_file {
                         SecretsStore::new(path)
@@ -1655,13 +2610,7 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                         SecretsStore::default_location()
                     };
 
-                    let value = store.get(&scope, &key)?;
-
-                    if raw {
-                        println!("{}", value);
-                    } else {
-                        println!("{}/{}: {}", scope, key, secrets_store::redact_value(&value));
-                    }
+                    ("envfile", store.get(&scope, &key)?)
                 }
                 ProviderType::Vault => {
                     if secrets_file.is_some() {
@@ -1672,35 +2621,48 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                     let vault_addr =
                         env::var("VAULT_ADDR").unwrap_or_else(|_| "file://vault_stub".to_string());
 
-                    let value = if vault_addr.starts_with("http://")
-                        || vault_addr.starts_with("https://")
-                    {
+                    if vault_addr.starts_with("http://") || vault_addr.starts_with("https://") {
                         // Use HTTP provider for real Vault
                         let vault_provider = VaultHttpSecretProvider::from_env().map_err(|e| {
                             anyhow::anyhow!("Failed to initialize Vault HTTP provider: {}", e)
                         })?;
 
-                        vault_provider.resolve(&scope, &key).map_err(|e| {
+                        let value = vault_provider.resolve(&scope, &key).map_err(|e| {
                             anyhow::anyhow!("Failed to get secret from Vault: {}", e)
-                        })?
+                        })?;
+                        ("vault-http", value)
                     } else {
                         // Use stub provider for file:// URLs
                         let vault_provider = VaultStubProvider::from_env().map_err(|e| {
                             anyhow::anyhow!("Failed to initialize Vault stub provider: {}", e)
                         })?;
 
-                        vault_provider.resolve(&scope, &key).map_err(|e| {
+                        let value = vault_provider.resolve(&scope, &key).map_err(|e| {
                             anyhow::anyhow!("Failed to get secret from vault stub: {}", e)
-                        })?
-                    };
-
-                    if raw {
-                        println!("{}", value);
-                    } else {
-                        println!("{}/{}: {}", scope, key, secrets_store::redact_value(&value));
+                        })?;
+                        ("vault-stub", value)
                     }
                 }
-            }
+            };
+
+            let secret = SecretValue {
+                scope,
+                key,
+                provider: provider_name,
+                value: if raw {
+                    value
+                } else {
+                    secrets_store::redact_value(&value)
+                },
+                redacted: !raw,
+            };
+            output::emit(format, "Secret", &secret, || {
+                if raw {
+                    println!("{}", secret.value);
+                } else {
+                    println!("{}/{}: {}", secret.scope, secret.key, secret.value);
+                }
+            })?;
         }
         SecretsCommands::List {
             scope,
@@ -1714,30 +2676,27 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                     SecretsStore::default_location()
                 };
 
-                if let Some(scope_filter) = scope {
-                    let secrets = store.list_scope(&scope_filter)?;
-                    if secrets.is_empty() {
-                        println!("No secrets found for scope: {}", scope_filter);
+                let secrets = if let Some(scope_filter) = &scope {
+                    let scoped = store.list_scope(scope_filter)?;
+                    if scoped.is_empty() {
+                        Default::default()
                     } else {
-                        println!("Secrets in scope '{}':", scope_filter);
-                        for (key, value) in secrets {
-                            println!("  {}: {}", key, value);
-                        }
+                        [(scope_filter.clone(), scoped.into_iter().collect())].into()
                     }
                 } else {
-                    let all_secrets = store.list()?;
-                    if all_secrets.is_empty() {
-                        println!("No secrets found");
-                    } else {
-                        println!("Secrets:");
-                        for (scope, secrets) in all_secrets {
-                            println!("  {}:", scope);
-                            for (key, value) in secrets {
-                                println!("    {}: {}", key, value);
-                            }
-                        }
-                    }
-                }
+                    store
+                        .list()?
+                        .into_iter()
+                        .map(|(scope, secrets)| (scope, secrets.into_iter().collect()))
+                        .collect()
+                };
+
+                let list = SecretList {
+                    provider: "envfile",
+                    scope,
+                    secrets,
+                };
+                output::emit(format, "SecretList", &list, || list.print_table(""))?;
             }
             ProviderType::Vault => {
                 if secrets_file.is_some() {
@@ -1749,10 +2708,26 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                     env::var("VAULT_ADDR").unwrap_or_else(|_| "file://vault_stub".to_string());
 
                 if vault_addr.starts_with("http://") || vault_addr.starts_with("https://") {
-                    // HTTP provider doesn't support list operation yet
-                    eprintln!("List operation is not yet supported for Vault HTTP provider");
-                    eprintln!("Use vault CLI directly: vault kv list secret/");
-                    std::process::exit(1);
+                    // Use HTTP provider for real Vault; only key names are listed
+                    let vault_provider = VaultHttpSecretProvider::from_env().map_err(|e| {
+                        anyhow::anyhow!("Failed to initialize Vault HTTP provider: {}", e)
+                    })?;
+
+                    let all_secrets = vault_provider
+                        .list(scope.as_deref())
+                        .map_err(|e| anyhow::anyhow!("Failed to list secrets from Vault: {}", e))?;
+
+                    let list = SecretList {
+                        provider: "vault-http",
+                        secrets: all_secrets
+                            .into_iter()
+                            .map(|(scope, secrets)| (scope, secrets.into_iter().collect()))
+                            .collect(),
+                        scope,
+                    };
+                    output::emit(format, "SecretList", &list, || {
+                        list.print_table(" (Vault HTTP)")
+                    })?;
                 } else {
                     // Use stub provider for file:// URLs
                     let vault_provider = VaultStubProvider::from_env().map_err(|e| {
@@ -1763,30 +2738,18 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                         anyhow::anyhow!("Failed to list secrets from vault stub: {}", e)
                     })?;
 
-                    if all_secrets.is_empty() {
-                        if let Some(scope_filter) = scope {
-                            println!("No secrets found for scope: {}", scope_filter);
-                        } else {
-                            println!("No secrets found");
-                        }
-                    } else if let Some(scope_filter) = scope {
-                        if let Some(secrets) = all_secrets.get(&scope_filter) {
-                            println!("Secrets in scope '{}' (vault stub):", scope_filter);
-                            for (key, value) in secrets {
-                                println!("  {}: {}", key, value);
-                            }
-                        } else {
-                            println!("No secrets found for scope: {}", scope_filter);
-                        }
-                    } else {
-                        println!("Secrets (vault stub):");
-                        for (scope, secrets) in all_secrets {
-                            println!("  {}:", scope);
-                            for (key, value) in secrets {
-                                println!("    {}: {}", key, value);
-                            }
-                        }
-                    }
+                    let list = SecretList {
+                        provider: "vault-stub",
+                        secrets: all_secrets
+                            .into_iter()
+                            .filter(|(name, _)| scope.as_deref().is_none_or(|s| s == name))
+                            .map(|(scope, secrets)| (scope, secrets.into_iter().collect()))
+                            .collect(),
+                        scope,
+                    };
+                    output::emit(format, "SecretList", &list, || {
+                        list.print_table(" (vault stub)")
+                    })?;
                 }
             }
         },
@@ -1806,7 +2769,11 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                     };
 
                     store.delete(&scope, &key)?;
-                    println!("✓ Secret {}/{} deleted", scope, key);
+                    let mut change = SecretChange::new("delete", &scope, &key, "envfile");
+                    change.path = Some(store.path().to_path_buf());
+                    output::emit(format, "SecretChange", &change, || {
+                        println!("✓ Secret {}/{} deleted", scope, key);
+                    })?;
                 }
                 ProviderType::Vault => {
                     if secrets_file.is_some() {
@@ -1827,7 +2794,10 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                             anyhow::anyhow!("Failed to delete secret from Vault: {}", e)
                         })?;
 
-                        println!("✓ Secret {}/{} deleted from Vault (HTTP)", scope, key);
+                        let change = SecretChange::new("delete", &scope, &key, "vault-http");
+                        output::emit(format, "SecretChange", &change, || {
+                            println!("✓ Secret {}/{} deleted from Vault (HTTP)", scope, key);
+                        })?;
                     } else {
                         // Use stub provider for file:// URLs
                         let vault_provider = VaultStubProvider::from_env().map_err(|e| {
@@ -1838,128 +2808,226 @@ fn handle_secrets_command(cmd: SecretsCommands) -> Result<()> {
                             anyhow::anyhow!("Failed to delete secret from vault stub: {}", e)
                         })?;
 
-                        println!("✓ Secret {}/{} deleted from vault stub", scope, key);
+                        let change = SecretChange::new("delete", &scope, &key, "vault-stub");
+                        output::emit(format, "SecretChange", &change, || {
+                            println!("✓ Secret {}/{} deleted from vault stub", scope, key);
+                        })?;
                     }
                 }
             }
         }
-    }
-
-    Ok(())
-}
-
-async fn handle_k8s_bootstrap_command(cmd: K8sBootstrapCommands) -> Result<()> {
-    match cmd {
-        K8sBootstrapCommands::Bootstrap {
-            config,
-            dry_run,
-            verbose,
-            use_latest_digests,
-            workflow,
-            repo,
-            api_url,
-            branch,
+        SecretsCommands::Rotate {
+            key_path,
+            value,
+            from_env,
+            stdin,
+            secrets_file,
         } => {
-            if verbose {
-                println!("Loading K8s bootstrap configuration from: {}", config);
-            }
-
-            let mut bootstrap_config = k8s_bootstrap::load_config(&config)?;
-
-            if verbose {
-                println!("Configuration loaded successfully");
-                println!("Cluster: {}", bootstrap_config.cluster.name);
-                println!("Namespace: {}", bootstrap_config.demon.namespace);
-            }
+            let (scope, key) = SecretsStore::parse_scope_key(&key_path)?;
 
-            k8s_bootstrap::validate_config(&bootstrap_config)?;
+            let secret_value = if stdin {
+                let mut buffer = String::new();
+                std::io::stdin().read_to_string(&mut buffer)?;
+                buffer.trim().to_string()
+            } else if let Some(env_var) = from_env {
+                std::env::var(&env_var)
+                    .map_err(|_| anyhow::anyhow!("Environment variable {} not found", env_var))?
+            } else {
+                value.ok_or_else(|| anyhow::anyhow!("No value provided"))?
+            };
 
-            if verbose {
-                println!("Configuration validation passed");
-            }
+            let store = if let Some(path) = secrets_file {
+                SecretsStore::new(path)
+            } else {
+                SecretsStore::default_location()
+            };
 
-            if use_latest_digests {
-                let token = std::env::var("GH_TOKEN").context(
-                    "GH_TOKEN environment variable must be set when using --use-latest-digests",
-                )?;
+            let entry = store.rotate(&scope, &key, &secret_value)?;
 
-                let workflow_name = workflow.unwrap_or_else(|| DEFAULT_DOCKER_WORKFLOW.to_string());
+            #[cfg(unix)]
+            store.check_permissions()?;
 
-                let client = docker::DockerDigestClient::new_with_overrides(
-                    token,
-                    repo.as_deref(),
-                    api_url.as_deref(),
-                )?;
+            let mut change = SecretChange::new("rotate", &scope, &key, "envfile");
+            change.path = Some(store.path().to_path_buf());
+            change.rotated_at = entry.metadata.rotated_at.map(|at| at.to_rfc3339());
+            output::emit(format, "SecretChange", &change, || {
+                println!("✓ Secret {}/{} rotated", scope, key);
+                if let Some(rotated_at) = &change.rotated_at {
+                    println!("  Rotated at: {}", rotated_at);
+                }
+                println!("  Stored in: {}", store.path().display());
+            })?;
+        }
+        SecretsCommands::Migrate { secrets_file } => {
+            let store = if let Some(path) = secrets_file {
+                SecretsStore::new(path)
+            } else {
+                SecretsStore::default_location()
+            };
 
-                let docker::FetchManifestResult {
-                    manifest,
-                    workflow_run,
-                } = client.fetch_manifest(&workflow_name, &branch).await?;
+            let report = store.migrate()?;
 
-                k8s_bootstrap::merge_digests_into_config(&mut bootstrap_config, &manifest)?;
+            #[cfg(unix)]
+            store.check_permissions()?;
 
-                if let Some(number) = workflow_run.run_number {
-                    eprintln!(
-                        "Resolved docker-image-digests from workflow run #{number} (id {}).",
-                        workflow_run.id
+            let migration = SecretMigration {
+                path: store.path().to_path_buf(),
+                secret_count: report.secret_count,
+                was_encrypted: report.was_encrypted,
+                key_source: report.key_source,
+            };
+            output::emit(format, "SecretMigration", &migration, || {
+                if migration.was_encrypted {
+                    println!(
+                        "✓ Re-encrypted {} secrets with the {} key",
+                        migration.secret_count, migration.key_source
                     );
                 } else {
-                    eprintln!(
-                        "Resolved docker-image-digests from workflow run id {}.",
-                        workflow_run.id
+                    println!(
+                        "✓ Encrypted {} secrets with the {} key",
+                        migration.secret_count, migration.key_source
                     );
                 }
+                println!("  Stored in: {}", migration.path.display());
+            })?;
+        }
+    }
 
-                if let Some(url) = &workflow_run.html_url {
-                    eprintln!("Run URL: {}", url);
-                }
-            }
+    Ok(())
+}
 
-            // Collect secrets
-            let secret_material =
-                k8s_bootstrap::secrets::collect_secrets(&bootstrap_config.secrets, dry_run)?;
+/// What `k8s-bootstrap bootstrap` planned or deployed (`K8sBootstrapSummary` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sBootstrapSummary {
+    /// `dry-run`, `apply-only` or `deploy`
+    mode: &'static str,
+    cluster: String,
+    namespace: String,
+    manifest_count: usize,
+    secrets_provider: Option<String>,
+    secret_keys: Vec<String>,
+    addons: Vec<K8sAddonSummary>,
+    ingress: bool,
+    service_mesh: bool,
+    /// Rendered manifests, dry run with `--verbose` only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    manifests: Option<String>,
+    /// Smoke suite result, `--smoke` only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    smoke: Option<k8s_bootstrap::smoke::SmokeReport>,
+}
 
-            // Render secret manifest
-            let secret_manifest = k8s_bootstrap::secrets::render_secret_manifest(
-                &bootstrap_config.demon.namespace,
-                None, // Use default name "demon-secrets"
-                &secret_material,
-            )?;
+#[derive(serde::Serialize)]
+struct K8sAddonSummary {
+    name: String,
+    enabled: bool,
+}
 
-            // Create image pull secrets for registries
-            let registry_secrets = if let Some(registries) = &bootstrap_config.registries {
-                k8s_bootstrap::secrets::create_image_pull_secrets(
-                    registries,
-                    &bootstrap_config.demon.namespace,
-                    dry_run,
-                )?
-            } else {
-                Vec::new()
-            };
+/// Chart written by `k8s-bootstrap bootstrap --helm-chart` (`K8sHelmChart` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sHelmChart {
+    path: PathBuf,
+    chart: &'static str,
+    version: String,
+    files: Vec<String>,
+    /// Config sections the chart does not carry
+    not_included: Vec<String>,
+}
 
-            // Initialize template renderer
-            let templates_dir = format!("{}/resources/k8s", env!("CARGO_MANIFEST_DIR"));
-            let template_renderer = k8s_bootstrap::templates::TemplateRenderer::new(&templates_dir);
+/// What `k8s-bootstrap upgrade` changed (`K8sUpgradeSummary` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sUpgradeSummary {
+    cluster: String,
+    namespace: String,
+    /// `Kind/name` of each resource that differed from the cluster
+    changed: Vec<String>,
+    applied: bool,
+    /// Workloads whose rollout was waited on
+    rolled_out: Vec<String>,
+}
 
-            // Render manifests
-            let mut manifests = template_renderer.render_manifests(&bootstrap_config)?;
+/// What `k8s-bootstrap uninstall` deleted, or would delete (`K8sUninstallSummary` kind)
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sUninstallSummary {
+    namespace: String,
+    dry_run: bool,
+    /// `Kind/name` in deletion order
+    resources: Vec<String>,
+}
 
-            // Prepend secret manifest if we have secrets
-            if !secret_manifest.is_empty() {
-                manifests = format!("{}\n---\n{}", secret_manifest, manifests);
+async fn handle_k8s_bootstrap_command(
+    cmd: K8sBootstrapCommands,
+    format: output::OutputFormat,
+) -> Result<()> {
+    match cmd {
+        K8sBootstrapCommands::Bootstrap {
+            render,
+            dry_run,
+            helm_chart,
+            smoke,
+            smoke_ritual,
+            smoke_report,
+            smoke_timeout,
+            verbose,
+        } => {
+            let bootstrap_config = load_k8s_config(&render, verbose).await?;
+            if let Some(dir) = helm_chart {
+                return write_helm_chart(&bootstrap_config, dir, format);
             }
+            let RenderedManifests {
+                secret_material,
+                secret_manifest,
+                addon_manifests,
+                mut manifests,
+            } = render_k8s_manifests(&bootstrap_config, dry_run, false, verbose)?;
+            let offline =
+                prepare_offline_bundle(&render, &bootstrap_config, &mut manifests, verbose)?;
+            let provider = k8s_bootstrap::provider::for_cluster(
+                &bootstrap_config.cluster,
+                dry_run,
+                offline.as_ref(),
+            )?;
+            let kubectl = provider.kubectl();
 
-            // Add registry secrets if we have any
-            if !registry_secrets.is_empty() {
-                let registry_manifests = registry_secrets.join("\n---\n");
-                manifests = format!("{}\n---\n{}", manifests, registry_manifests);
-            }
+            let manifest_count = MANIFEST_FILES.len()
+                + if secret_manifest.is_empty() { 0 } else { 1 }
+                + if bootstrap_config.networking.ingress.enabled {
+                    1
+                } else {
+                    0
+                }
+                + addon_manifests.len();
+            let mut secret_keys: Vec<String> = secret_material.data.keys().cloned().collect();
+            secret_keys.sort();
+            let mut summary = K8sBootstrapSummary {
+                mode: "dry-run",
+                cluster: bootstrap_config.cluster.name.clone(),
+                namespace: bootstrap_config.demon.namespace.clone(),
+                manifest_count,
+                secrets_provider: (!secret_material.provider_used.is_empty())
+                    .then(|| secret_material.provider_used.clone()),
+                secret_keys,
+                addons: bootstrap_config
+                    .addons
+                    .iter()
+                    .map(|addon| K8sAddonSummary {
+                        name: addon.name.clone(),
+                        enabled: addon.enabled,
+                    })
+                    .collect(),
+                ingress: bootstrap_config.networking.ingress.enabled,
+                service_mesh: bootstrap_config.networking.service_mesh.enabled,
+                manifests: None,
+                smoke: None,
+            };
 
-            // Process add-ons
-            let addon_manifests =
-                k8s_bootstrap::addons::process_addons(&bootstrap_config, dry_run, verbose)?;
-            if !addon_manifests.is_empty() {
-                manifests = format!("{}\n---\n{}", manifests, addon_manifests.join("\n---\n"));
+            if dry_run && !format.is_table() {
+                summary.manifests = verbose.then(|| manifests.clone());
+                return output::emit(format, "K8sBootstrapSummary", &summary, || {});
             }
 
             if dry_run {
@@ -1969,15 +3037,6 @@ async fn handle_k8s_bootstrap_command(cmd: K8sBootstrapCommands) -> Result<()> {
                     "Cluster: {} (namespace: {})",
                     bootstrap_config.cluster.name, bootstrap_config.demon.namespace
                 );
-                let addon_manifest_count = addon_manifests.len();
-                let manifest_count = MANIFEST_FILES.len()
-                    + if secret_manifest.is_empty() { 0 } else { 1 }
-                    + if bootstrap_config.networking.ingress.enabled {
-                        1
-                    } else {
-                        0
-                    }
-                    + addon_manifest_count;
                 println!(
                     "{} manifest{} will be generated.",
                     manifest_count,
@@ -1989,7 +3048,8 @@ async fn handle_k8s_bootstrap_command(cmd: K8sBootstrapCommands) -> Result<()> {
                     println!("Configuration summary:");
                     println!(
                         "  Cluster: {} ({})",
-                        bootstrap_config.cluster.name, bootstrap_config.cluster.k3s.version
+                        bootstrap_config.cluster.name,
+                        provider.describe()
                     );
                     println!("  Runtime: {}", bootstrap_config.cluster.runtime);
                     println!("  Namespace: {}", bootstrap_config.demon.namespace);
@@ -2041,142 +3101,613 @@ async fn handle_k8s_bootstrap_command(cmd: K8sBootstrapCommands) -> Result<()> {
                     }
 
                     println!();
-                    let k3s_installer = k8s_bootstrap::k3s::K3sInstaller::new(
-                        bootstrap_config.cluster.k3s.clone(),
-                        dry_run,
+                    provider.install()?;
+                    if let Some(bundle) = &offline {
+                        println!("Offline images ({}):", bundle.index.images.len());
+                        provider.load_images(&bundle.archive_path())?;
+                        println!();
+                    }
+
+                    println!("Manifests to be applied:");
+                    if !secret_manifest.is_empty() {
+                        println!("  - demon-secrets (Secret)");
+                    }
+                    for file in MANIFEST_FILES.iter() {
+                        println!("  - {}", file);
+                    }
+                    if !addon_manifests.is_empty() {
+                        println!("  - {} add-on manifest(s)", addon_manifests.len());
+                    }
+
+                    println!();
+                    println!("Generated manifests:");
+                    println!("---");
+                    println!("{}", manifests);
+                } else {
+                    println!(
+                        "Run with --verbose to view the {} cluster plan and manifest preview.",
+                        provider.name()
                     );
-                    k3s_installer.install_k3s()?;
+                    println!("Note: Health checks will run after deployment to verify runtime API and Operate UI.");
+                }
+
+                return Ok(());
+            }
+
+            let execution_mode = BootstrapExecutionMode::from_env();
+            // Progress goes to stdout, so structured output only gets the summary
+            let verbose = verbose && format.is_table();
+
+            if execution_mode.is_apply_only() {
+                summary.mode = "apply-only";
+                if format.is_table() {
+                    println!("🚀 Starting K8s bootstrap process (manifests only)...");
+                }
+                if verbose {
+                    println!("Phase 3: Deploying Demon components");
+                }
+
+                let command_executor = resolve_command_executor();
+                apply_manifests(&manifests, &kubectl, command_executor.as_ref(), verbose)?;
+
+                if verbose {
+                    println!("✓ Demon components deployed");
+                }
+
+                return output::emit(format, "K8sBootstrapSummary", &summary, || {
+                    println!("🎯 Manifest application simulation complete");
+                });
+            }
+
+            summary.mode = "deploy";
+            if format.is_table() {
+                println!("🚀 Starting K8s bootstrap process...");
+            }
+
+            if verbose {
+                println!("Phase 1: Preparing {} cluster", provider.name());
+            }
+
+            provider.install()?;
+
+            if verbose {
+                println!("✓ {} cluster prepared", provider.name());
+            }
+
+            // Wait for the cluster to be ready
+            if verbose {
+                println!(
+                    "Phase 2: Waiting for {} cluster to be ready",
+                    provider.name()
+                );
+            }
+
+            if !provider.is_ready()? {
+                anyhow::bail!("{} cluster is not ready", provider.name());
+            }
+
+            if verbose {
+                println!("✓ {} cluster is ready", provider.name());
+            }
+
+            if let Some(bundle) = &offline {
+                if verbose {
+                    println!("Loading {} offline images", bundle.index.images.len());
+                }
+                provider.load_images(&bundle.archive_path())?;
+            }
+
+            if verbose {
+                println!("Phase 3: Deploying Demon components");
+            }
+
+            // Apply manifests to cluster
+            let command_executor = resolve_command_executor();
+            apply_manifests(&manifests, &kubectl, command_executor.as_ref(), verbose)?;
+
+            if verbose {
+                println!("✓ Demon components deployed");
+                println!("Phase 4: Waiting for pods to be ready");
+            }
+
+            // Wait for Demon pods to be ready
+            wait_for_demon_pods(
+                &bootstrap_config.demon.namespace,
+                &kubectl,
+                command_executor.as_ref(),
+                verbose,
+            )?;
+
+            if verbose {
+                println!("Phase 5: Running health checks");
+            }
+
+            // Run health checks
+            run_health_checks(
+                &bootstrap_config.demon.namespace,
+                &kubectl,
+                command_executor.as_ref(),
+                verbose,
+            )?;
+
+            if smoke {
+                if verbose {
+                    println!("Phase 6: Running smoke suite");
+                }
+                let options = k8s_bootstrap::smoke::SmokeOptions {
+                    namespace: bootstrap_config.demon.namespace.clone(),
+                    ritual: smoke_ritual,
+                    timeout: Duration::from_secs(smoke_timeout),
+                };
+                let report = k8s_bootstrap::smoke::run(&kubectl, &options).await;
+                if let Some(path) = &smoke_report {
+                    std::fs::write(path, serde_json::to_string_pretty(&report)?)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                }
+                if format.is_table() {
+                    print_smoke_report(&report);
+                }
+                if !report.passed {
+                    if !format.is_table() {
+                        summary.smoke = Some(report);
+                        output::emit(format, "K8sBootstrapSummary", &summary, || {})?;
+                    }
+                    anyhow::bail!("Smoke suite failed");
+                }
+                summary.smoke = Some(report);
+            }
+
+            output::emit(format, "K8sBootstrapSummary", &summary, || {
+                println!("🎉 Demon deployment completed successfully!");
+                // k3s writes its kubeconfig root-only unless told otherwise
+                let kubectl_hint = if provider.name() == k8s_bootstrap::provider::RUNTIME_K3S {
+                    format!("sudo {}", kubectl)
+                } else {
+                    kubectl.to_string()
+                };
+                println!("You can now use kubectl to interact with your cluster:");
+                println!("  {} get nodes", kubectl_hint);
+                println!(
+                    "  {} get pods -n {}",
+                    kubectl_hint, bootstrap_config.demon.namespace
+                );
+                println!(
+                    "  {} get services -n {}",
+                    kubectl_hint, bootstrap_config.demon.namespace
+                );
+            })
+        }
+        K8sBootstrapCommands::Upgrade {
+            render,
+            dry_run,
+            verbose,
+            timeout,
+        } => {
+            let bootstrap_config = load_k8s_config(&render, verbose).await?;
+            let mut rendered = render_k8s_manifests(&bootstrap_config, false, false, verbose)?;
+            let namespace = bootstrap_config.demon.namespace.clone();
+            let verbose = verbose && format.is_table();
+            let offline = prepare_offline_bundle(
+                &render,
+                &bootstrap_config,
+                &mut rendered.manifests,
+                verbose,
+            )?;
+            let provider = k8s_bootstrap::provider::for_cluster(
+                &bootstrap_config.cluster,
+                dry_run,
+                offline.as_ref(),
+            )?;
+            let kubectl = provider.kubectl();
+
+            let command_executor = resolve_command_executor();
+            let diff = k8s_bootstrap::lifecycle::diff_manifests(
+                &rendered.manifests,
+                &kubectl,
+                command_executor.as_ref(),
+            )?;
+            let mut summary = K8sUpgradeSummary {
+                cluster: bootstrap_config.cluster.name.clone(),
+                namespace: namespace.clone(),
+                changed: diff.changed.clone(),
+                applied: false,
+                rolled_out: Vec::new(),
+            };
+
+            if diff.is_empty() {
+                return output::emit(format, "K8sUpgradeSummary", &summary, || {
+                    println!("✓ {} is up to date; nothing to apply", namespace);
+                });
+            }
+
+            if format.is_table() {
+                println!("{}", diff.output.trim_end());
+                println!();
+                println!(
+                    "{} resource{} will change:",
+                    diff.changed.len(),
+                    if diff.changed.len() == 1 { "" } else { "s" }
+                );
+                for resource in &diff.changed {
+                    println!("  - {}", resource);
+                }
+            }
+
+            if dry_run {
+                return output::emit(format, "K8sUpgradeSummary", &summary, || {
+                    println!("Dry run mode - no changes applied");
+                });
+            }
+
+            if let Some(bundle) = &offline {
+                provider.load_images(&bundle.archive_path())?;
+            }
+
+            apply_manifests(
+                &rendered.manifests,
+                &kubectl,
+                command_executor.as_ref(),
+                verbose,
+            )?;
+            summary.applied = true;
+
+            let resources = k8s_bootstrap::lifecycle::parse_resources(&rendered.manifests)?;
+            summary.rolled_out = k8s_bootstrap::lifecycle::wait_for_rollouts(
+                &resources,
+                &namespace,
+                &kubectl,
+                command_executor.as_ref(),
+                timeout,
+                verbose,
+            )?;
+
+            output::emit(format, "K8sUpgradeSummary", &summary, || {
+                println!(
+                    "🎉 Upgrade complete: {} workload{} rolled out in {}",
+                    summary.rolled_out.len(),
+                    if summary.rolled_out.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    },
+                    namespace
+                );
+            })
+        }
+        K8sBootstrapCommands::Uninstall {
+            config,
+            yes,
+            dry_run,
+            keep_namespace,
+            verbose,
+        } => {
+            let bootstrap_config = k8s_bootstrap::load_config(&config)?;
+            k8s_bootstrap::validate_config(&bootstrap_config)?;
+            let namespace = bootstrap_config.demon.namespace.clone();
+            let rendered = render_k8s_manifests(&bootstrap_config, false, true, false)?;
+
+            let mut resources = k8s_bootstrap::lifecycle::deletion_order(
+                &k8s_bootstrap::lifecycle::parse_resources(&rendered.manifests)?,
+            );
+            if keep_namespace {
+                resources.retain(|r| r.kind != "Namespace");
+            }
+            let mut summary = K8sUninstallSummary {
+                namespace: namespace.clone(),
+                dry_run,
+                resources: resources.iter().map(|r| r.to_string()).collect(),
+            };
+
+            if dry_run {
+                return output::emit(format, "K8sUninstallSummary", &summary, || {
+                    println!("Dry run mode - nothing will be deleted");
+                    println!("Would delete, in order:");
+                    for resource in &summary.resources {
+                        println!("  - {}", resource);
+                    }
+                });
+            }
+
+            if !yes {
+                confirm_uninstall(&namespace, &summary.resources)?;
+            }
+
+            let kubectl =
+                k8s_bootstrap::provider::for_cluster(&bootstrap_config.cluster, dry_run, None)?
+                    .kubectl();
+            let command_executor = resolve_command_executor();
+            summary.resources = k8s_bootstrap::lifecycle::delete_resources(
+                &resources,
+                &namespace,
+                &kubectl,
+                command_executor.as_ref(),
+                verbose && format.is_table(),
+            )?;
+
+            output::emit(format, "K8sUninstallSummary", &summary, || {
+                println!(
+                    "✓ Removed {} resource{} from {}",
+                    summary.resources.len(),
+                    if summary.resources.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    },
+                    namespace
+                );
+            })
+        }
+    }
+}
+
+/// Ask for the namespace name before deleting anything; refuses without a terminal
+fn confirm_uninstall(namespace: &str, resources: &[String]) -> Result<()> {
+    use std::io::{BufRead, IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Refusing to uninstall without confirmation; pass --yes to skip the prompt");
+    }
+    eprintln!("This will delete, in order:");
+    for resource in resources {
+        eprintln!("  - {}", resource);
+    }
+    eprint!("Type the namespace name ({}) to confirm: ", namespace);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    if answer.trim() != namespace {
+        anyhow::bail!("Uninstall cancelled");
+    }
+    Ok(())
+}
 
-                    println!("Manifests to be applied:");
-                    if !secret_manifest.is_empty() {
-                        println!("  - demon-secrets (Secret)");
-                    }
-                    for file in MANIFEST_FILES.iter() {
-                        println!("  - {}", file);
-                    }
-                    if !addon_manifests.is_empty() {
-                        println!("  - {} add-on manifest(s)", addon_manifests.len());
-                    }
+/// Load and validate a k8s bootstrap config, pinning images to the latest GHCR
+/// digests when asked
+async fn load_k8s_config(
+    render: &K8sRenderArgs,
+    verbose: bool,
+) -> Result<k8s_bootstrap::K8sBootstrapConfig> {
+    if verbose {
+        println!(
+            "Loading K8s bootstrap configuration from: {}",
+            render.config
+        );
+    }
 
-                    println!();
-                    println!("Generated manifests:");
-                    println!("---");
-                    println!("{}", manifests);
-                } else {
-                    println!(
-                        "Run with --verbose to view the k3s installation plan and manifest preview."
-                    );
-                    println!("Note: Health checks will run after deployment to verify runtime API and Operate UI.");
-                }
+    let mut bootstrap_config = k8s_bootstrap::load_config(&render.config)?;
 
-                return Ok(());
-            }
+    if verbose {
+        println!("Configuration loaded successfully");
+        println!("Cluster: {}", bootstrap_config.cluster.name);
+        println!("Namespace: {}", bootstrap_config.demon.namespace);
+    }
 
-            let execution_mode = BootstrapExecutionMode::from_env();
+    k8s_bootstrap::validate_config(&bootstrap_config)?;
 
-            if execution_mode.is_apply_only() {
-                println!("🚀 Starting K8s bootstrap process (manifests only)...");
-                if verbose {
-                    println!("Phase 3: Deploying Demon components");
-                }
+    if verbose {
+        println!("Configuration validation passed");
+    }
 
-                let command_executor = resolve_command_executor();
-                apply_manifests(&manifests, command_executor.as_ref(), verbose)?;
+    // Images come from the offline bundle, so registry credentials are unused
+    if render.offline.is_some() && bootstrap_config.registries.take().is_some() && verbose {
+        println!("Offline mode: skipping registry image pull secrets");
+    }
 
-                if verbose {
-                    println!("✓ Demon components deployed");
-                }
+    if render.use_latest_digests {
+        let token = std::env::var("GH_TOKEN")
+            .context("GH_TOKEN environment variable must be set when using --use-latest-digests")?;
 
-                println!("🎯 Manifest application simulation complete");
-                return Ok(());
-            }
+        let workflow_name = render
+            .workflow
+            .clone()
+            .unwrap_or_else(|| DEFAULT_DOCKER_WORKFLOW.to_string());
 
-            println!("🚀 Starting K8s bootstrap process...");
+        let client = docker::DockerDigestClient::new_with_overrides(
+            token,
+            render.repo.as_deref(),
+            render.api_url.as_deref(),
+        )?;
 
-            if verbose {
-                println!("Phase 1: Installing k3s cluster");
-            }
+        let docker::FetchManifestResult {
+            manifest,
+            workflow_run,
+        } = client
+            .fetch_manifest(&workflow_name, &render.branch)
+            .await?;
 
-            let k3s_installer = k8s_bootstrap::k3s::K3sInstaller::new(
-                bootstrap_config.cluster.k3s.clone(),
-                dry_run,
+        k8s_bootstrap::merge_digests_into_config(&mut bootstrap_config, &manifest)?;
+
+        if let Some(number) = workflow_run.run_number {
+            eprintln!(
+                "Resolved docker-image-digests from workflow run #{number} (id {}).",
+                workflow_run.id
+            );
+        } else {
+            eprintln!(
+                "Resolved docker-image-digests from workflow run id {}.",
+                workflow_run.id
             );
+        }
 
-            k3s_installer.install_k3s()?;
+        if let Some(url) = &workflow_run.html_url {
+            eprintln!("Run URL: {}", url);
+        }
+    }
 
-            if verbose {
-                println!("✓ k3s cluster installation completed");
-            }
+    Ok(bootstrap_config)
+}
 
-            // Wait for k3s to be ready
-            if verbose {
-                println!("Phase 2: Waiting for k3s cluster to be ready");
-            }
+/// Everything `bootstrap` and `upgrade` apply, rendered from one config
+struct RenderedManifests {
+    secret_material: k8s_bootstrap::secrets::SecretMaterial,
+    secret_manifest: String,
+    addon_manifests: Vec<String>,
+    manifests: String,
+}
 
-            let k3s_ready = k3s_installer.is_k3s_ready()?;
-            if !k3s_ready {
-                anyhow::bail!("k3s cluster is not ready after installation");
-            }
+/// Render the full manifest stream; `placeholder_secrets` skips reading secret
+/// values, for callers that only need resource names
+fn write_helm_chart(
+    bootstrap_config: &k8s_bootstrap::K8sBootstrapConfig,
+    dir: PathBuf,
+    format: output::OutputFormat,
+) -> Result<()> {
+    let templates_dir = format!("{}/resources/k8s", env!("CARGO_MANIFEST_DIR"));
+    let chart = k8s_bootstrap::helm::render_chart(
+        bootstrap_config,
+        &templates_dir,
+        env!("CARGO_PKG_VERSION"),
+    )?;
+    chart.write(&dir)?;
+
+    let summary = K8sHelmChart {
+        path: dir,
+        chart: k8s_bootstrap::helm::CHART_NAME,
+        version: chart.version,
+        files: chart.files.into_keys().collect(),
+        not_included: chart.not_included,
+    };
+    output::emit(format, "K8sHelmChart", &summary, || {
+        println!(
+            "✓ Wrote Helm chart {}-{} to {}",
+            summary.chart,
+            summary.version,
+            summary.path.display()
+        );
+        for file in &summary.files {
+            println!("  - {}", file);
+        }
+        for skipped in &summary.not_included {
+            println!("⚠ Not included in the chart: {}", skipped);
+        }
+        // The chart creates the namespace itself, so no --create-namespace
+        println!(
+            "Install with: helm install demon {} (deploys into namespace {})",
+            summary.path.display(),
+            bootstrap_config.demon.namespace
+        );
+    })
+}
 
-            if verbose {
-                println!("✓ k3s cluster is ready");
-                println!("Phase 3: Deploying Demon components");
-            }
+fn render_k8s_manifests(
+    bootstrap_config: &k8s_bootstrap::K8sBootstrapConfig,
+    dry_run: bool,
+    placeholder_secrets: bool,
+    verbose: bool,
+) -> Result<RenderedManifests> {
+    // Collect secrets
+    let secret_material = k8s_bootstrap::secrets::collect_secrets(
+        &bootstrap_config.secrets,
+        dry_run || placeholder_secrets,
+    )?;
 
-            // Apply manifests to cluster
-            let command_executor = resolve_command_executor();
-            apply_manifests(&manifests, command_executor.as_ref(), verbose)?;
+    // Render secret manifest
+    let secret_manifest = k8s_bootstrap::secrets::render_secret_manifest(
+        &bootstrap_config.demon.namespace,
+        None, // Use default name "demon-secrets"
+        &secret_material,
+    )?;
 
-            if verbose {
-                println!("✓ Demon components deployed");
-                println!("Phase 4: Waiting for pods to be ready");
-            }
+    // Create image pull secrets for registries
+    let registry_secrets = if let Some(registries) = &bootstrap_config.registries {
+        k8s_bootstrap::secrets::create_image_pull_secrets(
+            registries,
+            &bootstrap_config.demon.namespace,
+            dry_run || placeholder_secrets,
+        )?
+    } else {
+        Vec::new()
+    };
 
-            // Wait for Demon pods to be ready
-            wait_for_demon_pods(
-                &bootstrap_config.demon.namespace,
-                command_executor.as_ref(),
-                verbose,
-            )?;
+    // Initialize template renderer
+    let templates_dir = format!("{}/resources/k8s", env!("CARGO_MANIFEST_DIR"));
+    let template_renderer = k8s_bootstrap::templates::TemplateRenderer::new(&templates_dir);
 
-            if verbose {
-                println!("Phase 5: Running health checks");
-            }
+    // Render manifests
+    let mut manifests = template_renderer.render_manifests(bootstrap_config)?;
 
-            // Run health checks
-            run_health_checks(
-                &bootstrap_config.demon.namespace,
-                command_executor.as_ref(),
-                verbose,
-            )?;
+    // Prepend secret manifest if we have secrets
+    if !secret_manifest.is_empty() {
+        manifests = format!("{}\n---\n{}", secret_manifest, manifests);
+    }
 
-            println!("🎉 Demon deployment completed successfully!");
-            println!("You can now use kubectl to interact with your cluster:");
-            println!("  sudo k3s kubectl get nodes");
-            println!(
-                "  sudo k3s kubectl get pods -n {}",
-                bootstrap_config.demon.namespace
-            );
-            println!(
-                "  sudo k3s kubectl get services -n {}",
-                bootstrap_config.demon.namespace
-            );
+    // Add registry secrets if we have any
+    if !registry_secrets.is_empty() {
+        let registry_manifests = registry_secrets.join("\n---\n");
+        manifests = format!("{}\n---\n{}", manifests, registry_manifests);
+    }
 
-            Ok(())
-        }
+    // Process add-ons
+    let addon_manifests =
+        k8s_bootstrap::addons::process_addons(bootstrap_config, dry_run, verbose)?;
+    if !addon_manifests.is_empty() {
+        manifests = format!("{}\n---\n{}", manifests, addon_manifests.join("\n---\n"));
+    }
+
+    Ok(RenderedManifests {
+        secret_material,
+        secret_manifest,
+        addon_manifests,
+        manifests,
+    })
+}
+
+/// Open the `--offline` bundle, check that it holds everything `manifests`
+/// and the k3s install need, and point `manifests` at the bundled images
+fn prepare_offline_bundle(
+    render: &K8sRenderArgs,
+    bootstrap_config: &k8s_bootstrap::K8sBootstrapConfig,
+    manifests: &mut String,
+    verbose: bool,
+) -> Result<Option<k8s_bootstrap::offline::OfflineBundle>> {
+    let Some(dir) = &render.offline else {
+        return Ok(None);
+    };
+    let bundle = k8s_bootstrap::offline::OfflineBundle::open(dir)?;
+    let k3s_version = bootstrap_config
+        .cluster
+        .k3s
+        .as_ref()
+        .filter(|_| bootstrap_config.cluster.runtime == k8s_bootstrap::provider::RUNTIME_K3S)
+        .map(|k3s| k3s.version.as_str());
+    bundle.verify(manifests, k3s_version)?;
+    *manifests = bundle.rewrite_images(manifests);
+    if verbose {
+        println!(
+            "✓ Offline bundle {} verified ({} images)",
+            dir.display(),
+            bundle.index.images.len()
+        );
+    }
+    Ok(Some(bundle))
+}
+
+fn print_smoke_report(report: &k8s_bootstrap::smoke::SmokeReport) {
+    use k8s_bootstrap::smoke::SmokeStatus;
+
+    println!("Smoke suite ({}):", report.run_id);
+    for check in &report.checks {
+        let mark = match check.status {
+            SmokeStatus::Passed => "✓",
+            SmokeStatus::Failed => "✗",
+            SmokeStatus::Skipped => "-",
+        };
+        println!("  {} {}: {}", mark, check.name, check.detail);
     }
 }
 
 fn apply_manifests(
     manifests: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     executor: &dyn k8s_bootstrap::CommandExecutor,
     verbose: bool,
 ) -> Result<()> {
-    apply_manifests_with_namespace_wait(manifests, executor, verbose)
+    apply_manifests_with_namespace_wait(manifests, kubectl, executor, verbose)
 }
 
 fn apply_manifests_with_namespace_wait(
     manifests: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     executor: &dyn k8s_bootstrap::CommandExecutor,
     verbose: bool,
 ) -> Result<()> {
@@ -2208,11 +3739,7 @@ fn apply_manifests_with_namespace_wait(
             println!("Applying namespace manifests...");
         }
         let namespace_yaml = namespace_manifests.join("\n---\n");
-        let output = executor.execute(
-            "k3s",
-            &["kubectl", "apply", "-f", "-"],
-            Some(&namespace_yaml),
-        )?;
+        let output = kubectl.execute(executor, &["apply", "-f", "-"], Some(&namespace_yaml))?;
         if output.status != 0 {
             eprintln!("Failed to apply namespace manifests:");
             eprintln!("stdout: {}", output.stdout);
@@ -2227,7 +3754,7 @@ fn apply_manifests_with_namespace_wait(
         // Wait for namespaces to be ready
         for manifest in &namespace_manifests {
             if let Some(namespace_name) = extract_namespace_name(manifest) {
-                wait_for_namespace_ready(&namespace_name, executor, verbose)?;
+                wait_for_namespace_ready(&namespace_name, kubectl, executor, verbose)?;
             }
         }
 
@@ -2242,11 +3769,7 @@ fn apply_manifests_with_namespace_wait(
             println!("Applying remaining manifests...");
         }
         let remaining_yaml = other_manifests.join("\n---\n");
-        let output = executor.execute(
-            "k3s",
-            &["kubectl", "apply", "-f", "-"],
-            Some(&remaining_yaml),
-        )?;
+        let output = kubectl.execute(executor, &["apply", "-f", "-"], Some(&remaining_yaml))?;
         if output.status != 0 {
             eprintln!("Failed to apply manifests:");
             eprintln!("stdout: {}", output.stdout);
@@ -2311,6 +3834,7 @@ fn extract_namespace_name(manifest: &str) -> Option<String> {
 
 fn wait_for_namespace_ready(
     namespace: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     executor: &dyn k8s_bootstrap::CommandExecutor,
     verbose: bool,
 ) -> Result<()> {
@@ -2332,9 +3856,9 @@ fn wait_for_namespace_ready(
     let mut elapsed = 0;
 
     while elapsed < timeout_secs {
-        let output = executor.execute(
-            "k3s",
-            &["kubectl", "get", "namespace", namespace, "--no-headers"],
+        let output = kubectl.execute(
+            executor,
+            &["get", "namespace", namespace, "--no-headers"],
             None,
         )?;
 
@@ -2354,6 +3878,7 @@ fn wait_for_namespace_ready(
 
 fn wait_for_demon_pods(
     namespace: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     executor: &dyn k8s_bootstrap::CommandExecutor,
     verbose: bool,
 ) -> Result<()> {
@@ -2372,9 +3897,9 @@ fn wait_for_demon_pods(
     }
 
     while elapsed < timeout_secs {
-        let output = executor.execute(
-            "k3s",
-            &["kubectl", "get", "pods", "-n", namespace, "--no-headers"],
+        let output = kubectl.execute(
+            executor,
+            &["get", "pods", "-n", namespace, "--no-headers"],
             None,
         )?;
 
@@ -2431,9 +3956,9 @@ fn wait_for_demon_pods(
     );
 
     // Get pod status details
-    if let Ok(pod_output) = executor.execute(
-        "k3s",
-        &["kubectl", "get", "pods", "-n", namespace, "-o", "wide"],
+    if let Ok(pod_output) = kubectl.execute(
+        executor,
+        &["get", "pods", "-n", namespace, "-o", "wide"],
         None,
     ) {
         if pod_output.status == 0 {
@@ -2443,16 +3968,9 @@ fn wait_for_demon_pods(
     }
 
     // Get recent events
-    if let Ok(events_output) = executor.execute(
-        "k3s",
-        &[
-            "kubectl",
-            "get",
-            "events",
-            "-n",
-            namespace,
-            "--sort-by=.lastTimestamp",
-        ],
+    if let Ok(events_output) = kubectl.execute(
+        executor,
+        &["get", "events", "-n", namespace, "--sort-by=.lastTimestamp"],
         None,
     ) {
         if events_output.status == 0 && !events_output.stdout.trim().is_empty() {
@@ -2466,6 +3984,7 @@ fn wait_for_demon_pods(
 
 fn run_health_checks(
     namespace: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     executor: &dyn k8s_bootstrap::CommandExecutor,
     verbose: bool,
 ) -> Result<()> {
@@ -2474,14 +3993,18 @@ fn run_health_checks(
     }
 
     // Check runtime health endpoint
-    let runtime_pod =
-        get_pod_by_label(namespace, "app.kubernetes.io/name=demon-runtime", executor)?;
+    let runtime_pod = get_pod_by_label(
+        namespace,
+        "app.kubernetes.io/name=demon-runtime",
+        kubectl,
+        executor,
+    )?;
     if let Some(pod_name) = runtime_pod {
         if verbose {
             println!("Checking runtime health endpoint for pod: {}", pod_name);
         }
 
-        match check_runtime_health(namespace, &pod_name, executor, verbose) {
+        match check_runtime_health(namespace, &pod_name, kubectl, executor, verbose) {
             Ok(_) => {
                 if verbose {
                     println!("✓ Runtime health check passed");
@@ -2490,10 +4013,10 @@ fn run_health_checks(
             Err(e) => {
                 eprintln!("✗ Runtime health check failed: {}", e);
                 eprintln!("  To investigate, check runtime logs and port-forward to the pod:");
-                eprintln!("  kubectl logs -n {} {}", namespace, pod_name);
+                eprintln!("  {} logs -n {} {}", kubectl, namespace, pod_name);
                 eprintln!(
-                    "  kubectl port-forward -n {} pod/{} 8080:8080",
-                    namespace, pod_name
+                    "  {} port-forward -n {} pod/{} 8080:8080",
+                    kubectl, namespace, pod_name
                 );
                 return Err(e);
             }
@@ -2503,13 +4026,18 @@ fn run_health_checks(
     }
 
     // Check Operate UI health
-    let ui_pod = get_pod_by_label(namespace, "app.kubernetes.io/name=operate-ui", executor)?;
+    let ui_pod = get_pod_by_label(
+        namespace,
+        "app.kubernetes.io/name=operate-ui",
+        kubectl,
+        executor,
+    )?;
     if let Some(pod_name) = ui_pod {
         if verbose {
             println!("Checking Operate UI health endpoint for pod: {}", pod_name);
         }
 
-        match check_ui_health(namespace, &pod_name, executor, verbose) {
+        match check_ui_health(namespace, &pod_name, kubectl, executor, verbose) {
             Ok(_) => {
                 if verbose {
                     println!("✓ Operate UI health check passed");
@@ -2518,10 +4046,10 @@ fn run_health_checks(
             Err(e) => {
                 eprintln!("✗ Operate UI health check failed: {}", e);
                 eprintln!("  To investigate, check UI logs and port-forward to the pod:");
-                eprintln!("  kubectl logs -n {} {}", namespace, pod_name);
+                eprintln!("  {} logs -n {} {}", kubectl, namespace, pod_name);
                 eprintln!(
-                    "  kubectl port-forward -n {} pod/{} 3000:3000",
-                    namespace, pod_name
+                    "  {} port-forward -n {} pod/{} 3000:3000",
+                    kubectl, namespace, pod_name
                 );
                 return Err(e);
             }
@@ -2540,12 +4068,12 @@ fn run_health_checks(
 fn get_pod_by_label(
     namespace: &str,
     label_selector: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     executor: &dyn k8s_bootstrap::CommandExecutor,
 ) -> Result<Option<String>> {
-    let output = executor.execute(
-        "k3s",
+    let output = kubectl.execute(
+        executor,
         &[
-            "kubectl",
             "get",
             "pods",
             "-n",
@@ -2568,24 +4096,27 @@ fn get_pod_by_label(
 fn check_runtime_health(
     namespace: &str,
     pod_name: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     _executor: &dyn k8s_bootstrap::CommandExecutor,
     verbose: bool,
 ) -> Result<()> {
-    port_forward_and_check(namespace, pod_name, 8080, "/health", verbose)
+    port_forward_and_check(namespace, pod_name, kubectl, 8080, "/health", verbose)
 }
 
 fn check_ui_health(
     namespace: &str,
     pod_name: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     _executor: &dyn k8s_bootstrap::CommandExecutor,
     verbose: bool,
 ) -> Result<()> {
-    port_forward_and_check(namespace, pod_name, 3000, "/health", verbose)
+    port_forward_and_check(namespace, pod_name, kubectl, 3000, "/health", verbose)
 }
 
 fn port_forward_and_check(
     namespace: &str,
     pod_name: &str,
+    kubectl: &k8s_bootstrap::provider::Kubectl,
     remote_port: u16,
     path: &str,
     verbose: bool,
@@ -2599,15 +4130,9 @@ fn port_forward_and_check(
     let port_arg = format!("{}:{}", local_port, remote_port);
     let pod_ref = format!("pod/{}", pod_name);
 
-    let mut child = Command::new("k3s")
-        .args([
-            "kubectl",
-            "port-forward",
-            "-n",
-            namespace,
-            &pod_ref,
-            &port_arg,
-        ])
+    let mut child = kubectl
+        .command()
+        .args(["port-forward", "-n", namespace, &pod_ref, &port_arg])
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()?;
@@ -2649,6 +4174,46 @@ fn port_forward_and_check(
 async fn handle_docker_command(cmd: DockerCommands) -> Result<()> {
     match cmd {
         DockerCommands::Digests { cmd } => handle_docker_digests_command(cmd).await?,
+        DockerCommands::SaveBundle {
+            config,
+            output,
+            arch,
+            verbose,
+        } => {
+            let bootstrap_config = k8s_bootstrap::load_config(&config)?;
+            k8s_bootstrap::validate_config(&bootstrap_config)?;
+            let rendered = render_k8s_manifests(&bootstrap_config, false, true, false)?;
+            let k3s_version = bootstrap_config
+                .cluster
+                .k3s
+                .as_ref()
+                .filter(|_| {
+                    bootstrap_config.cluster.runtime == k8s_bootstrap::provider::RUNTIME_K3S
+                })
+                .map(|k3s| (k3s.version.as_str(), arch.as_str()));
+
+            let index = k8s_bootstrap::offline::save_bundle(
+                &rendered.manifests,
+                k3s_version,
+                &output,
+                verbose,
+            )
+            .await?;
+            println!(
+                "✓ Saved {} image{} to {}",
+                index.images.len(),
+                if index.images.len() == 1 { "" } else { "s" },
+                output.display()
+            );
+            if let Some(k3s) = &index.k3s {
+                println!("✓ Included k3s {} ({})", k3s.version, k3s.arch);
+            }
+            println!(
+                "Copy the directory to the disconnected host and run:\n  demonctl k8s-bootstrap bootstrap --config {} --offline {}",
+                config,
+                output.display()
+            );
+        }
     }
 
     Ok(())
@@ -2732,16 +4297,10 @@ async fn handle_graph_command(cmd: GraphCommands) -> Result<()> {
                 graph_id,
             };
 
-            let mutations_json = std::fs::read_to_string(&mutations_file)?;
-            let mutations: Vec<capsules_graph::Mutation> = serde_json::from_str(&mutations_json)?;
+            let mutations = read_graph_mutations(&mutations_file)?;
 
             let envelope = capsules_graph::create(scope, mutations).await;
-            let envelope_json = serde_json::to_string_pretty(&envelope)?;
-            println!("{}", envelope_json);
-
-            if !envelope.result.is_success() {
-                std::process::exit(1);
-            }
+            print_graph_envelope(&serde_json::to_value(&envelope)?)?;
         }
         GraphCommands::Commit {
             tenant_id,
@@ -2758,16 +4317,10 @@ async fn handle_graph_command(cmd: GraphCommands) -> Result<()> {
                 graph_id,
             };
 
-            let mutations_json = std::fs::read_to_string(&mutations_file)?;
-            let mutations: Vec<capsules_graph::Mutation> = serde_json::from_str(&mutations_json)?;
+            let mutations = read_graph_mutations(&mutations_file)?;
 
             let envelope = capsules_graph::commit(scope, parent_ref, mutations).await;
-            let envelope_json = serde_json::to_string_pretty(&envelope)?;
-            println!("{}", envelope_json);
-
-            if !envelope.result.is_success() {
-                std::process::exit(1);
-            }
+            print_graph_envelope(&serde_json::to_value(&envelope)?)?;
         }
         GraphCommands::Tag {
             tenant_id,
@@ -2785,18 +4338,14 @@ async fn handle_graph_command(cmd: GraphCommands) -> Result<()> {
             };
 
             let envelope = capsules_graph::tag(scope, tag, commit_id).await;
-            let envelope_json = serde_json::to_string_pretty(&envelope)?;
-            println!("{}", envelope_json);
-
-            if !envelope.result.is_success() {
-                std::process::exit(1);
-            }
+            print_graph_envelope(&serde_json::to_value(&envelope)?)?;
         }
         GraphCommands::ListTags {
             tenant_id,
             project_id,
             namespace,
             graph_id,
+            api_url,
         } => {
             let scope = capsules_graph::GraphScope {
                 tenant_id,
@@ -2805,13 +4354,129 @@ async fn handle_graph_command(cmd: GraphCommands) -> Result<()> {
                 graph_id,
             };
 
-            let envelope = capsules_graph::list_tags(scope).await;
-            let envelope_json = serde_json::to_string_pretty(&envelope)?;
-            println!("{}", envelope_json);
+            let envelope = match api_url {
+                Some(api_url) => {
+                    let url = format!("{}/api/graph/tags", api_url.trim_end_matches('/'));
+                    let response = reqwest::Client::new()
+                        .get(&url)
+                        .query(&graph_scope_query(&scope))
+                        .send()
+                        .await?;
+                    let status = response.status();
+                    if !status.is_success() {
+                        let body = response.text().await.unwrap_or_default();
+                        anyhow::bail!("HTTP {} - {}", status, body);
+                    }
+                    let tags: Vec<capsules_graph::TaggedCommit> = response.json().await?;
+                    let envelope = envelope::ResultEnvelope::builder()
+                        .success(tags)
+                        .with_source_info("graph-capsule", Some("0.0.1"), None::<String>)
+                        .build()?;
+                    serde_json::to_value(&envelope)?
+                }
+                None => serde_json::to_value(capsules_graph::list_tags(scope).await)?,
+            };
+            print_graph_envelope(&envelope)?;
+        }
+        GraphCommands::GetNode {
+            tenant_id,
+            project_id,
+            namespace,
+            graph_id,
+            commit_id,
+            node_id,
+            api_url,
+        } => {
+            let scope = capsules_graph::GraphScope {
+                tenant_id,
+                project_id,
+                namespace,
+                graph_id,
+            };
 
-            if !envelope.result.is_success() {
-                std::process::exit(1);
-            }
+            let envelope = match api_url {
+                Some(api_url) => {
+                    let path = format!("nodes/{}", node_id);
+                    fetch_graph_envelope(&api_url, &path, &scope, &commit_id, &[]).await?
+                }
+                None => {
+                    serde_json::to_value(capsules_graph::get_node(scope, commit_id, node_id).await)?
+                }
+            };
+            print_graph_envelope(&envelope)?;
+        }
+        GraphCommands::Neighbors {
+            tenant_id,
+            project_id,
+            namespace,
+            graph_id,
+            commit_id,
+            node_id,
+            depth,
+            limit,
+            cursor,
+            api_url,
+        } => {
+            let scope = capsules_graph::GraphScope {
+                tenant_id,
+                project_id,
+                namespace,
+                graph_id,
+            };
+
+            let envelope = match api_url {
+                Some(api_url) => {
+                    let path = format!("nodes/{}/neighbors", node_id);
+                    let mut extra = vec![("depth", depth.to_string())];
+                    if let Some(limit) = limit {
+                        extra.push(("limit", limit.to_string()));
+                    }
+                    if let Some(cursor) = cursor {
+                        extra.push(("cursor", cursor));
+                    }
+                    fetch_graph_envelope(&api_url, &path, &scope, &commit_id, &extra).await?
+                }
+                None => {
+                    let page = capsules_graph::PageRequest { limit, cursor };
+                    serde_json::to_value(
+                        capsules_graph::neighbors(scope, commit_id, node_id, depth, page).await,
+                    )?
+                }
+            };
+            print_graph_envelope(&envelope)?;
+        }
+        GraphCommands::PathExists {
+            tenant_id,
+            project_id,
+            namespace,
+            graph_id,
+            commit_id,
+            from,
+            to,
+            max_depth,
+            api_url,
+        } => {
+            let scope = capsules_graph::GraphScope {
+                tenant_id,
+                project_id,
+                namespace,
+                graph_id,
+            };
+
+            let envelope = match api_url {
+                Some(api_url) => {
+                    let extra = [
+                        ("from", from),
+                        ("to", to),
+                        ("maxDepth", max_depth.to_string()),
+                    ];
+                    fetch_graph_envelope(&api_url, "path", &scope, &commit_id, &extra).await?
+                }
+                None => serde_json::to_value(
+                    capsules_graph::path_exists(scope, commit_id, from, to, max_depth).await,
+                )?,
+            };
+            print_graph_envelope(&envelope)?;
         }
         GraphCommands::GetCommit {
             tenant_id,
@@ -2871,6 +4536,82 @@ async fn handle_graph_command(cmd: GraphCommands) -> Result<()> {
     Ok(())
 }
 
+/// Read graph mutations from a JSON array or a JSONL file (`-` reads stdin)
+fn read_graph_mutations(path: &str) -> Result<Vec<capsules_graph::Mutation>> {
+    let content = if path == "-" {
+        let mut buf = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)?;
+        buf
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read mutations file '{}'", path))?
+    };
+    parse_graph_mutations(&content).with_context(|| format!("Invalid mutations in '{}'", path))
+}
+
+/// Parse mutations as a JSON array, or as one JSON mutation per line
+fn parse_graph_mutations(content: &str) -> Result<Vec<capsules_graph::Mutation>> {
+    if content.trim_start().starts_with('[') {
+        return Ok(serde_json::from_str(content)?);
+    }
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).with_context(|| format!("line {}", index + 1))
+        })
+        .collect()
+}
+
+fn graph_scope_query(scope: &capsules_graph::GraphScope) -> Vec<(&'static str, String)> {
+    vec![
+        ("tenantId", scope.tenant_id.clone()),
+        ("projectId", scope.project_id.clone()),
+        ("namespace", scope.namespace.clone()),
+        ("graphId", scope.graph_id.clone()),
+    ]
+}
+
+/// Run a graph query through the runtime REST proxy and return its envelope
+async fn fetch_graph_envelope(
+    api_url: &str,
+    path: &str,
+    scope: &capsules_graph::GraphScope,
+    commit_id: &str,
+    extra: &[(&'static str, String)],
+) -> Result<serde_json::Value> {
+    let mut query = graph_scope_query(scope);
+    query.push(("commitId", commit_id.to_string()));
+    query.extend(extra.iter().cloned());
+
+    let url = format!("{}/api/graph/{}", api_url.trim_end_matches('/'), path);
+    let response = reqwest::Client::new()
+        .get(&url)
+        .query(&query)
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+
+    // Failed capsule calls still come back as envelopes; print those as-is
+    match serde_json::from_str::<serde_json::Value>(&body) {
+        Ok(envelope) if envelope.get("result").is_some() => Ok(envelope),
+        _ => anyhow::bail!("HTTP {} - {}", status, body),
+    }
+}
+
+/// Print a graph capsule envelope, exiting non-zero when it reports an error
+fn print_graph_envelope(envelope: &serde_json::Value) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(envelope)?);
+
+    if envelope["result"]["success"] != serde_json::Value::Bool(true) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 // no-op: exercise replies guard
 
 #[cfg(test)]
@@ -2994,7 +4735,12 @@ metadata:
 
         // For testing, we need to create a custom executor that simulates namespace readiness
         let executor = MockNamespaceWaitExecutor::new();
-        let result = apply_manifests_with_namespace_wait(manifests, &executor, false);
+        let result = apply_manifests_with_namespace_wait(
+            manifests,
+            &k8s_bootstrap::provider::Kubectl::k3s(),
+            &executor,
+            false,
+        );
 
         assert!(result.is_ok());
     }
@@ -3063,8 +4809,41 @@ metadata:
         let executor = SimulatedCommandExecutor::success(
             "service/test-service created\nconfigmap/test-config created",
         );
-        let result = apply_manifests_with_namespace_wait(manifests, &executor, false);
+        let result = apply_manifests_with_namespace_wait(
+            manifests,
+            &k8s_bootstrap::provider::Kubectl::k3s(),
+            &executor,
+            false,
+        );
 
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn given_jsonl_mutations_when_parsed_then_blank_lines_are_skipped() {
+        let content = r#"{"op":"add-node","nodeId":"a"}
+
+{"op":"add-edge","edgeId":"e1","from":"a","to":"a","label":null,"properties":[]}
+"#;
+        let mutations = parse_graph_mutations(content).unwrap();
+        assert_eq!(mutations.len(), 2);
+        assert!(matches!(
+            &mutations[0],
+            capsules_graph::Mutation::AddNode { node_id, .. } if node_id == "a"
+        ));
+    }
+
+    #[test]
+    fn given_json_array_mutations_when_parsed_then_array_form_still_works() {
+        let mutations = parse_graph_mutations(r#"[{"op":"remove-node","nodeId":"a"}]"#).unwrap();
+        assert_eq!(mutations.len(), 1);
+    }
+
+    #[test]
+    fn given_invalid_jsonl_line_when_parsed_then_error_names_the_line() {
+        let err =
+            parse_graph_mutations("{\"op\":\"add-node\",\"nodeId\":\"a\"}\n{\"op\":\"bogus\"}\n")
+                .unwrap_err();
+        assert_eq!(err.to_string(), "line 2");
+    }
 }
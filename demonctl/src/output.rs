@@ -0,0 +1,107 @@
+//! Output formats shared by every command that supports `-o/--output`
+//!
+//! `table` is the human-readable default. `json` and `yaml` print one
+//! document per invocation; documents that demonctl defines itself are
+//! wrapped in a `{apiVersion, kind, ...}` header and described by
+//! `contracts/schemas/demonctl.output.v1.json`.
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+
+/// `apiVersion` stamped on every demonctl-defined document
+pub const API_VERSION: &str = "demonctl/v1";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    pub fn is_table(self) -> bool {
+        self == OutputFormat::Table
+    }
+}
+
+/// `-o/--output` for command groups; global so it can follow any subcommand
+///
+/// The explicit id keeps it apart from subcommands' own `format` arguments.
+#[derive(Args, Debug, Clone, Copy)]
+pub struct OutputArgs {
+    /// Output format
+    #[arg(
+        id = "output_format",
+        long = "output",
+        short = 'o',
+        value_enum,
+        global = true,
+        default_value_t = OutputFormat::Table
+    )]
+    pub format: OutputFormat,
+}
+
+/// Header-wrapped document; `body` must serialize as a map
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Document<'a, T> {
+    api_version: &'static str,
+    kind: &'a str,
+    #[serde(flatten)]
+    body: &'a T,
+}
+
+/// Print `value` as-is in the structured formats (table falls back to JSON)
+pub fn print<T: Serialize + ?Sized>(format: OutputFormat, value: &T) -> Result<()> {
+    match format {
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+        OutputFormat::Json | OutputFormat::Table => {
+            println!("{}", serde_json::to_string_pretty(value)?)
+        }
+    }
+    Ok(())
+}
+
+/// Print a `kind` document in the structured formats, or run `table` otherwise
+pub fn emit<T: Serialize>(
+    format: OutputFormat,
+    kind: &str,
+    body: &T,
+    table: impl FnOnce(),
+) -> Result<()> {
+    if format.is_table() {
+        table();
+        return Ok(());
+    }
+    print(
+        format,
+        &Document {
+            api_version: API_VERSION,
+            kind,
+            body,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn document_flattens_body_under_header() {
+        let body = json!({ "valid": true });
+        let value = serde_json::to_value(Document {
+            api_version: API_VERSION,
+            kind: "ValidationReport",
+            body: &body,
+        })
+        .unwrap();
+        assert_eq!(
+            value,
+            json!({ "apiVersion": "demonctl/v1", "kind": "ValidationReport", "valid": true })
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use httptest::{matchers::*, responders::*, Expectation, Server};
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_pack(root: &Path, requires: Value) -> Result<()> {
+    fs::create_dir_all(root.join("contracts"))?;
+    fs::write(root.join("contracts/result.json"), r#"{"type":"object"}"#)?;
+    let manifest = json!({
+        "apiVersion": "demon.io/v2",
+        "kind": "AppPack",
+        "metadata": { "name": "compat-app", "version": "1.0.0" },
+        "requires": requires,
+        "contracts": [
+            { "id": "compat-app/result", "version": "1.0.0", "path": "contracts/result.json" }
+        ],
+        "capsules": [{
+            "type": "container-exec",
+            "name": "noop",
+            "imageDigest": format!("ghcr.io/example/noop@sha256:{}", "a".repeat(64)),
+            "command": ["/bin/true"],
+            "outputs": { "envelopePath": "/workspace/.artifacts/result.json" }
+        }],
+        "rituals": [{ "name": "noop", "steps": [{ "capsule": "noop" }] }]
+    });
+    fs::write(
+        root.join("app-pack.yaml"),
+        serde_yaml::to_string(&manifest)?,
+    )?;
+    Ok(())
+}
+
+fn serve_contracts(server: &Server, contracts: Value) {
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", "/registry/contracts"),
+            request::headers(contains(("authorization", "Bearer test-token"))),
+        ])
+        .respond_with(json_encoded(json!({ "contracts": contracts }))),
+    );
+}
+
+fn install(home: &Path, pack: &Path, registry: &Server) -> Command {
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.env("DEMON_APP_HOME", home)
+        .env("DEMONCTL_REGISTRY_URL", registry.url_str(""))
+        .env("DEMONCTL_JWT", "test-token")
+        .args(["app", "install"])
+        .arg(pack);
+    cmd
+}
+
+#[test]
+fn given_registry_with_matching_contract_when_install_then_pack_is_activated() -> Result<()> {
+    let temp = TempDir::new()?;
+    let pack = temp.path().join("pack");
+    write_pack(
+        &pack,
+        json!({
+            "contracts": [{ "name": "result-envelope", "version": ">=1.2.0 <2.0.0" }],
+            "capabilities": ["container-exec"]
+        }),
+    )?;
+
+    let server = Server::run();
+    serve_contracts(
+        &server,
+        json!([
+            { "name": "result-envelope", "version": "1.1.0", "createdAt": "2025-01-01T00:00:00Z" },
+            { "name": "result-envelope", "version": "1.3.0", "createdAt": "2025-02-01T00:00:00Z" }
+        ]),
+    );
+
+    let home = temp.path().join("home");
+    install(&home, &pack, &server)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Installed App Pack compat-app@1.0.0",
+        ));
+    assert!(home.join("registry.json").exists());
+    Ok(())
+}
+
+#[test]
+fn given_registry_without_matching_version_when_install_then_fails_with_available_versions(
+) -> Result<()> {
+    let temp = TempDir::new()?;
+    let pack = temp.path().join("pack");
+    write_pack(
+        &pack,
+        json!({ "contracts": [
+            { "name": "result-envelope", "version": ">=1.2.0" },
+            { "name": "scan-report", "version": "^1" }
+        ] }),
+    )?;
+
+    let server = Server::run();
+    serve_contracts(
+        &server,
+        json!([
+            { "name": "result-envelope", "version": "1.1.0", "createdAt": "2025-01-01T00:00:00Z" }
+        ]),
+    );
+
+    let home = temp.path().join("home");
+    install(&home, &pack, &server)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "App Pack compat-app@1.0.0 is not compatible with this platform",
+        ))
+        .stderr(predicate::str::contains(
+            "Contract 'result-envelope' requires >=1.2.0 but the Schema Registry only has 1.1.0",
+        ))
+        .stderr(predicate::str::contains(
+            "Contract 'scan-report' is not published to the Schema Registry",
+        ));
+    assert!(!home.join("packs/compat-app").exists());
+    Ok(())
+}
+
+#[test]
+fn given_contract_bundled_with_pack_when_install_then_registry_is_not_queried() -> Result<()> {
+    let temp = TempDir::new()?;
+    let pack = temp.path().join("pack");
+    write_pack(
+        &pack,
+        json!({ "contracts": [{ "name": "compat-app/result", "version": "1.x" }] }),
+    )?;
+
+    // No expectations: any request to the registry fails the test
+    let server = Server::run();
+    install(&temp.path().join("home"), &pack, &server)
+        .assert()
+        .success();
+    Ok(())
+}
+
+#[test]
+fn given_unreachable_registry_when_install_then_error_suggests_skipping() -> Result<()> {
+    let temp = TempDir::new()?;
+    let pack = temp.path().join("pack");
+    write_pack(
+        &pack,
+        json!({ "contracts": [{ "name": "result-envelope", "version": ">=1.2.0" }] }),
+    )?;
+    let home = temp.path().join("home");
+
+    Command::cargo_bin("demonctl")?
+        .env("DEMON_APP_HOME", &home)
+        .env("DEMONCTL_JWT", "test-token")
+        .args(["app", "install", "--registry-url", "http://127.0.0.1:1"])
+        .arg(&pack)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--skip-registry-check"));
+
+    Command::cargo_bin("demonctl")?
+        .env("DEMON_APP_HOME", &home)
+        .args(["app", "install", "--skip-registry-check"])
+        .arg(&pack)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Skipping Schema Registry check for required contracts: result-envelope >=1.2.0",
+        ));
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use httptest::{matchers::*, responders::*, Expectation, Server};
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use tempfile::TempDir;
+
+const LAYER_MEDIA_TYPE: &str = "application/vnd.demon.app-pack.layer.v1+zip";
+const CONFIG_MEDIA_TYPE: &str = "application/vnd.demon.app-pack.config.v1+json";
+
+fn digest(bytes: &[u8]) -> String {
+    format!("sha256:{}", hex::encode(Sha256::digest(bytes)))
+}
+
+fn write_pack(root: &Path, name: &str, version: &str) -> Result<()> {
+    fs::create_dir_all(root.join("contracts/test"))?;
+    fs::write(
+        root.join("contracts/test/contract.json"),
+        r#"{"type":"object"}"#,
+    )?;
+    let manifest = json!({
+        "apiVersion": "demon.io/v1",
+        "kind": "AppPack",
+        "metadata": { "name": name, "version": version },
+        "contracts": [
+            { "id": format!("{}/contract", name), "version": version, "path": "contracts/test/contract.json" }
+        ],
+        "capsules": [{
+            "type": "container-exec",
+            "name": "noop",
+            "imageDigest": format!("ghcr.io/example/noop@sha256:{}", "a".repeat(64)),
+            "command": ["/bin/true"],
+            "outputs": { "envelopePath": "/workspace/.artifacts/result.json" }
+        }],
+        "rituals": [{ "name": "noop", "steps": [{ "capsule": "noop" }] }]
+    });
+    fs::write(
+        root.join("app-pack.yaml"),
+        serde_yaml::to_string(&manifest)?,
+    )?;
+    Ok(())
+}
+
+fn zip_pack(root: &Path) -> Result<Vec<u8>> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::FileOptions::default();
+    for rel in ["app-pack.yaml", "contracts/test/contract.json"] {
+        writer.start_file(rel, options)?;
+        writer.write_all(&fs::read(root.join(rel))?)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Serves a pack artifact from `server`; returns the manifest digest
+fn serve_pack(server: &Server, repo: &str, layer: Vec<u8>, served_layer: Vec<u8>) -> String {
+    let config = serde_json::to_vec(&json!({
+        "name": "oci-app", "version": "1.0.0", "apiVersion": "demon.io/v1"
+    }))
+    .unwrap();
+    let manifest = serde_json::to_vec(&json!({
+        "schemaVersion": 2,
+        "mediaType": "application/vnd.oci.image.manifest.v1+json",
+        "artifactType": "application/vnd.demon.app-pack.v1",
+        "config": { "mediaType": CONFIG_MEDIA_TYPE, "digest": digest(&config), "size": config.len() },
+        "layers": [{ "mediaType": LAYER_MEDIA_TYPE, "digest": digest(&layer), "size": layer.len() }]
+    }))
+    .unwrap();
+    let manifest_digest = digest(&manifest);
+
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            format!("/v2/{}/manifests/{}", repo, manifest_digest),
+        ))
+        .times(..)
+        .respond_with(status_code(200).body(manifest)),
+    );
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            format!("/v2/{}/blobs/{}", repo, digest(&config)),
+        ))
+        .times(..)
+        .respond_with(status_code(200).body(config)),
+    );
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            format!("/v2/{}/blobs/{}", repo, digest(&layer)),
+        ))
+        .times(..)
+        .respond_with(status_code(200).body(served_layer)),
+    );
+
+    manifest_digest
+}
+
+#[test]
+fn given_digest_pinned_reference_when_install_then_pack_is_pulled_and_registered() -> Result<()> {
+    let temp = TempDir::new()?;
+    let pack_dir = temp.path().join("pack");
+    write_pack(&pack_dir, "oci-app", "1.0.0")?;
+    let layer = zip_pack(&pack_dir)?;
+
+    let server = Server::run();
+    let manifest_digest = serve_pack(&server, "demo/oci-app", layer.clone(), layer);
+    let reference = format!("oci://{}/demo/oci-app@{}", server.addr(), manifest_digest);
+
+    let install_home = temp.path().join("home");
+    Command::cargo_bin("demonctl")?
+        .env("DEMON_APP_HOME", &install_home)
+        .args(["app", "install", &reference])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed App Pack oci-app@1.0.0"));
+
+    assert!(install_home
+        .join("packs/oci-app/1.0.0/contracts/test/contract.json")
+        .exists());
+    let registry: Value =
+        serde_json::from_str(&fs::read_to_string(install_home.join("registry.json"))?)?;
+    assert_eq!(registry["apps"]["oci-app"][0]["source"], reference);
+
+    Ok(())
+}
+
+#[test]
+fn given_tampered_layer_when_install_then_digest_mismatch_fails() -> Result<()> {
+    let temp = TempDir::new()?;
+    let pack_dir = temp.path().join("pack");
+    write_pack(&pack_dir, "oci-app", "1.0.0")?;
+    let layer = zip_pack(&pack_dir)?;
+    let mut tampered = layer.clone();
+    tampered.extend_from_slice(b"tampered");
+
+    let server = Server::run();
+    let manifest_digest = serve_pack(&server, "demo/oci-app", layer, tampered);
+    let reference = format!("oci://{}/demo/oci-app@{}", server.addr(), manifest_digest);
+
+    let install_home = temp.path().join("home");
+    Command::cargo_bin("demonctl")?
+        .env("DEMON_APP_HOME", &install_home)
+        .args(["app", "install", &reference])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Digest mismatch"));
+
+    assert!(!install_home.join("packs/oci-app").exists());
+    Ok(())
+}
+
+#[test]
+fn given_tag_reference_when_install_then_digest_is_required() -> Result<()> {
+    let temp = TempDir::new()?;
+
+    Command::cargo_bin("demonctl")?
+        .env("DEMON_APP_HOME", temp.path())
+        .args(["app", "install", "oci://127.0.0.1:1/demo/oci-app:1.0.0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must be installed by digest"));
+
+    Ok(())
+}
+
+#[test]
+fn given_pack_dir_when_push_then_blobs_and_manifest_are_uploaded() -> Result<()> {
+    let temp = TempDir::new()?;
+    let pack_dir = temp.path().join("pack");
+    write_pack(&pack_dir, "oci-app", "1.0.0")?;
+
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path(
+            "POST",
+            "/v2/demo/oci-app/blobs/uploads/",
+        ))
+        .times(2)
+        .respond_with(
+            status_code(202).insert_header("Location", "/v2/demo/oci-app/blobs/uploads/session"),
+        ),
+    );
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("PUT", "/v2/demo/oci-app/blobs/uploads/session"),
+            request::query(url_decoded(contains(key("digest")))),
+        ])
+        .times(2)
+        .respond_with(status_code(201)),
+    );
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("PUT", "/v2/demo/oci-app/manifests/1.0.0"),
+            request::body(json_decoded(|manifest: &Value| {
+                manifest["artifactType"] == "application/vnd.demon.app-pack.v1"
+                    && manifest["layers"][0]["mediaType"] == LAYER_MEDIA_TYPE
+            })),
+        ])
+        .respond_with(status_code(201)),
+    );
+
+    let reference = format!("oci://{}/demo/oci-app:1.0.0", server.addr());
+    Command::cargo_bin("demonctl")?
+        .args(["app", "push"])
+        .arg(&pack_dir)
+        .arg(&reference)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "demonctl app install oci://{}/demo/oci-app@sha256:",
+            server.addr()
+        )));
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+// base64 of 32 bytes of 0x07
+const SEED_B64: &str = "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=";
+
+/// A scratch repo root holding the index schema, so sign/verify run from it
+fn workspace() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let schemas = temp_dir.path().join("contracts/schemas");
+    fs::create_dir_all(&schemas).unwrap();
+    fs::copy(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../contracts/schemas/bootstrap.library.index.v0.json"),
+        schemas.join("bootstrap.library.index.v0.json"),
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("dev.key"), SEED_B64).unwrap();
+    temp_dir
+}
+
+fn demonctl(dir: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.current_dir(dir);
+    cmd
+}
+
+fn create_and_sign(dir: &Path) {
+    demonctl(dir)
+        .args([
+            "bundle",
+            "create",
+            "bundles/dev.yaml",
+            "--approver",
+            "ops@example.com",
+            "--seed-ritual",
+            "preview",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote bundle"));
+
+    demonctl(dir)
+        .args([
+            "bundle",
+            "sign",
+            "bundles/dev.yaml",
+            "--key",
+            "dev.key",
+            "--pub-key-id",
+            "dev",
+            "--version",
+            "0.1.0",
+            "--index",
+            "index.json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Signed dev@0.1.0"));
+}
+
+#[test]
+fn given_signed_bundle_when_verifying_then_index_and_key_match() {
+    let temp_dir = workspace();
+    let dir = temp_dir.path();
+    create_and_sign(dir);
+
+    let index: Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("index.json")).unwrap()).unwrap();
+    let entry = &index["bundles"][0];
+    assert_eq!(entry["name"], "dev");
+    assert_eq!(entry["version"], "0.1.0");
+    assert_eq!(entry["pubKeyId"], "dev");
+    assert!(dir.join("contracts/keys/dev.ed25519.pub").exists());
+
+    let output = demonctl(dir)
+        .args([
+            "bundle",
+            "verify",
+            "lib://local/dev@0.1.0",
+            "--index",
+            "index.json",
+            "-o",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["kind"], "BundleVerification");
+    assert_eq!(report["signature"], "ok");
+    assert_eq!(report["digest"], entry["digest"]["sha256"]);
+
+    demonctl(dir)
+        .args([
+            "bundle",
+            "verify",
+            "bundles/dev.yaml",
+            "--index",
+            "index.json",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("signature ok"));
+}
+
+#[test]
+fn given_tampered_bundle_when_verifying_then_exit_is_nonzero() {
+    let temp_dir = workspace();
+    let dir = temp_dir.path();
+    create_and_sign(dir);
+
+    let bundle = dir.join("bundles/dev.yaml");
+    let text = fs::read_to_string(&bundle).unwrap();
+    fs::write(&bundle, text.replace("RITUAL_EVENTS", "OTHER_EVENTS")).unwrap();
+
+    demonctl(dir)
+        .args([
+            "bundle",
+            "verify",
+            "bundles/dev.yaml",
+            "--index",
+            "index.json",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("digest-mismatch"));
+}
+
+#[test]
+fn given_existing_key_id_when_signing_with_other_key_then_it_is_rejected() {
+    let temp_dir = workspace();
+    let dir = temp_dir.path();
+    create_and_sign(dir);
+    fs::write(
+        dir.join("other.key"),
+        "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAg=",
+    )
+    .unwrap();
+
+    demonctl(dir)
+        .args([
+            "bundle",
+            "sign",
+            "bundles/dev.yaml",
+            "--key",
+            "other.key",
+            "--pub-key-id",
+            "dev",
+            "--version",
+            "0.2.0",
+            "--index",
+            "index.json",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("different public key"));
+}
@@ -0,0 +1,175 @@
+use assert_cmd::Command;
+use predicates::str;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_schema(dir: &Path, name: &str, schema: serde_json::Value) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(
+        dir.join(name),
+        serde_json::to_string_pretty(&schema).unwrap(),
+    )
+    .unwrap();
+}
+
+fn codegen(workdir: &Path, extra: &[&str]) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .current_dir(workdir)
+        .args([
+            "contracts",
+            "codegen-config",
+            "--schema-dir",
+            "schemas",
+            "--out",
+            "generated",
+        ])
+        .args(extra)
+        .assert()
+}
+
+#[test]
+fn given_checked_in_config_types_when_codegen_check_then_up_to_date() {
+    let repo_root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .current_dir(repo_root)
+        .args(["contracts", "codegen-config", "--check"])
+        .assert()
+        .success()
+        .stdout(str::contains("up to date"));
+}
+
+#[test]
+fn given_new_schema_when_codegen_then_writes_module_and_check_tracks_drift() {
+    let dir = TempDir::new().unwrap();
+    let schemas = dir.path().join("schemas");
+    write_schema(
+        &schemas,
+        "fetch-config.v1.json",
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {"type": "string", "description": "Where to fetch from"},
+                "timeoutSeconds": {"type": "integer", "default": 30}
+            },
+            "required": ["url"],
+            "additionalProperties": false
+        }),
+    );
+    write_schema(&schemas, "notes.json", json!({"type": "object"}));
+
+    codegen(dir.path(), &["--check"])
+        .failure()
+        .stdout(str::contains("Stale"))
+        .stderr(str::contains("Generated config types are stale"));
+    codegen(dir.path(), &[])
+        .success()
+        .stdout(str::contains("Updated"));
+
+    let module = fs::read_to_string(dir.path().join("generated/fetch_config.rs")).unwrap();
+    assert!(module.starts_with("// @generated"));
+    assert!(module.contains("pub struct FetchConfig {"));
+    assert!(module.contains("    /// Where to fetch from\n    pub url: String,\n"));
+    assert!(module.contains("    pub timeout_seconds: Option<i64>,\n"));
+    let mod_rs = fs::read_to_string(dir.path().join("generated/mod.rs")).unwrap();
+    assert!(mod_rs.contains("pub use fetch_config::*;"));
+
+    codegen(dir.path(), &["--check"]).success();
+
+    write_schema(
+        &schemas,
+        "fetch-config.v1.json",
+        json!({"type": "object", "properties": {"url": {"type": "string"}}}),
+    );
+    codegen(dir.path(), &["--check"])
+        .failure()
+        .stdout(str::contains("fetch_config.rs"));
+}
+
+fn client_codegen(workdir: &Path, lang: &str, extra: &[&str]) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .current_dir(workdir)
+        .args(["contracts", "codegen", "--lang", lang, "--out", "client"])
+        .args(extra)
+        .assert()
+}
+
+#[test]
+fn given_contract_schemas_when_client_codegen_then_every_schema_has_a_module() {
+    let repo_root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let dir = TempDir::new().unwrap();
+
+    for lang in ["ts", "rust"] {
+        Command::cargo_bin("demonctl")
+            .unwrap()
+            .current_dir(&repo_root)
+            .args(["contracts", "codegen", "--lang", lang, "--out"])
+            .arg(dir.path().join(lang))
+            .assert()
+            .success()
+            .stdout(str::contains("Updated"));
+    }
+
+    let envelope = fs::read_to_string(dir.path().join("ts/result-envelope.ts")).unwrap();
+    assert!(envelope.contains("export interface ResultEnvelope {"));
+    let index = fs::read_to_string(dir.path().join("ts/index.ts")).unwrap();
+    assert!(index.contains("export * from './ritual-started-v1';"));
+    let mod_rs = fs::read_to_string(dir.path().join("rust/mod.rs")).unwrap();
+    assert!(mod_rs.contains("pub use result_envelope::*;"));
+}
+
+#[test]
+fn given_local_schemas_when_ts_codegen_then_writes_models_and_check_tracks_drift() {
+    let dir = TempDir::new().unwrap();
+    let schemas = dir.path().join("schemas");
+    write_schema(
+        &schemas,
+        "events.step.started.v1.json",
+        json!({
+            "title": "StepStartedV1",
+            "type": "object",
+            "required": ["runId", "stepId"],
+            "properties": {
+                "runId": {"type": "string", "description": "Run the step belongs to"},
+                "stepId": {"type": "string"},
+                "attempt": {"type": "integer"},
+                "phase": {"type": "string", "enum": ["start", "retry"]}
+            }
+        }),
+    );
+
+    client_codegen(
+        dir.path(),
+        "ts",
+        &["--schema-dir", "schemas", "--no-envelope", "--check"],
+    )
+    .failure()
+    .stderr(str::contains("Generated client models are stale"));
+    client_codegen(
+        dir.path(),
+        "ts",
+        &["--schema-dir", "schemas", "--no-envelope"],
+    )
+    .success()
+    .stdout(str::contains("Updated"));
+
+    let module = fs::read_to_string(dir.path().join("client/step-started-v1.ts")).unwrap();
+    assert!(module.starts_with("// @generated by `demonctl contracts codegen`"));
+    assert!(module.contains("export interface StepStartedV1 {"));
+    assert!(module.contains("  /** Run the step belongs to */\n  runId: string;\n"));
+    assert!(module.contains("  attempt?: number;\n"));
+    assert!(module.contains("export type StepStartedV1Phase = \"start\" | \"retry\";"));
+
+    client_codegen(
+        dir.path(),
+        "ts",
+        &["--schema-dir", "schemas", "--no-envelope", "--check"],
+    )
+    .success()
+    .stdout(str::contains("up to date"));
+}
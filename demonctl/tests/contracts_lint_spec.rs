@@ -0,0 +1,116 @@
+use assert_cmd::Command;
+use predicates::str;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn git(repo: &Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(repo)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+fn write_schema(repo: &Path, name: &str, schema: Value) {
+    let path = repo.join("contracts/schemas").join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, serde_json::to_string_pretty(&schema).unwrap()).unwrap();
+}
+
+/// A repo whose HEAD holds `events.status.v1.json` with two enum values
+fn repo_with_committed_schema() -> TempDir {
+    let repo = TempDir::new().unwrap();
+    git(repo.path(), &["init", "-q"]);
+    write_schema(
+        repo.path(),
+        "events.status.v1.json",
+        json!({
+            "type": "object",
+            "properties": {"status": {"type": "string", "enum": ["open", "closed"]}}
+        }),
+    );
+    write_schema(repo.path(), "nested/config.json", json!({"type": "object"}));
+    git(repo.path(), &["add", "-A"]);
+    git(repo.path(), &["commit", "-q", "-m", "base"]);
+    repo
+}
+
+#[test]
+fn given_unchanged_schemas_when_lint_against_head_then_succeeds() {
+    let repo = repo_with_committed_schema();
+
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .current_dir(repo.path())
+        .args(["contracts", "lint", "--base", "HEAD"])
+        .assert()
+        .success()
+        .stdout(str::contains("Compared 2 schemas against HEAD"))
+        .stdout(str::contains("✓ No unversioned breaking changes"));
+}
+
+#[test]
+fn given_removed_enum_value_in_same_version_when_lint_then_fails_with_finding() {
+    let repo = repo_with_committed_schema();
+    write_schema(
+        repo.path(),
+        "events.status.v1.json",
+        json!({
+            "type": "object",
+            "properties": {"status": {"type": "string", "enum": ["open"]}}
+        }),
+    );
+
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .current_dir(repo.path())
+        .args(["contracts", "lint", "--base", "HEAD"])
+        .assert()
+        .failure()
+        .stdout(str::contains("events.status.v1.json"))
+        .stdout(str::contains("[enum-value-removed]"))
+        .stdout(str::contains("Version bump required (1.0.0 -> 1.0.0)"))
+        .stderr(str::contains("Contract lint failed"));
+}
+
+#[test]
+fn given_json_output_when_lint_then_reports_added_and_removed_files() {
+    let repo = repo_with_committed_schema();
+    fs::remove_file(repo.path().join("contracts/schemas/nested/config.json")).unwrap();
+    write_schema(
+        repo.path(),
+        "events.status.v2.json",
+        json!({"type": "object"}),
+    );
+
+    let output = Command::cargo_bin("demonctl")
+        .unwrap()
+        .current_dir(repo.path())
+        .args(["contracts", "-o", "json", "lint", "--base", "HEAD"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["kind"], "ContractLintReport");
+    assert_eq!(report["compared"], 1);
+    assert_eq!(report["added"], json!(["events.status.v2.json"]));
+    assert_eq!(report["removed"], json!(["nested/config.json"]));
+}
+
+#[test]
+fn given_unknown_base_ref_when_lint_then_reports_git_error() {
+    let repo = repo_with_committed_schema();
+
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .current_dir(repo.path())
+        .args(["contracts", "lint", "--base", "no-such-ref"])
+        .assert()
+        .failure()
+        .stderr(str::contains("git ls-tree"));
+}
@@ -0,0 +1,109 @@
+use assert_cmd::Command;
+use httptest::{matchers::*, responders::*, Expectation, Server};
+use serde_json::{json, Value};
+
+const SCOPE: [&str; 8] = [
+    "--tenant-id",
+    "t1",
+    "--project-id",
+    "p1",
+    "--namespace",
+    "ns1",
+    "--graph-id",
+    "g1",
+];
+
+fn graph(args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.arg("graph")
+        .args(&args[..1])
+        .args(SCOPE)
+        .args(&args[1..]);
+    cmd
+}
+
+#[test]
+fn given_rest_proxy_when_getting_node_then_scope_is_forwarded_and_envelope_printed() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", "/api/graph/nodes/node-1"),
+            request::query(url_decoded(contains(("tenantId", "t1")))),
+            request::query(url_decoded(contains(("graphId", "g1")))),
+            request::query(url_decoded(contains(("commitId", "abc123")))),
+        ])
+        .respond_with(json_encoded(json!({
+            "result": {
+                "success": true,
+                "data": { "nodeId": "node-1", "labels": ["Team"], "properties": [] }
+            },
+            "diagnostics": []
+        }))),
+    );
+
+    let api_url = format!("http://{}", server.addr());
+    let output = graph(&[
+        "get-node",
+        "--commit-id",
+        "abc123",
+        "--node-id",
+        "node-1",
+        "--api-url",
+        &api_url,
+    ])
+    .output()
+    .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let envelope: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(envelope["result"]["data"]["labels"], json!(["Team"]));
+}
+
+#[test]
+fn given_failed_envelope_when_checking_path_then_command_exits_non_zero() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", "/api/graph/path"),
+            request::query(url_decoded(contains(("from", "a")))),
+            request::query(url_decoded(contains(("to", "b")))),
+            request::query(url_decoded(contains(("maxDepth", "2")))),
+        ])
+        .respond_with(
+            status_code(500).body(
+                json!({
+                    "result": {
+                        "success": false,
+                        "error": { "message": "Graph materialization error", "code": "MATERIALIZATION_FAILED" }
+                    },
+                    "diagnostics": []
+                })
+                .to_string(),
+            ),
+        ),
+    );
+
+    let api_url = format!("http://{}", server.addr());
+    let output = graph(&[
+        "path-exists",
+        "--commit-id",
+        "abc123",
+        "--from",
+        "a",
+        "--to",
+        "b",
+        "--max-depth",
+        "2",
+        "--api-url",
+        &api_url,
+    ])
+    .output()
+    .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let envelope: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        envelope["result"]["error"]["code"],
+        json!("MATERIALIZATION_FAILED")
+    );
+}
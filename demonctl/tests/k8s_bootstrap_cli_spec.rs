@@ -204,7 +204,7 @@ fn given_invalid_yaml_when_run_k8s_bootstrap_then_shows_error() {
 }
 
 #[test]
-fn given_runtime_not_k3s_when_run_bootstrap_then_fails() {
+fn given_unsupported_runtime_when_run_bootstrap_then_fails() {
     let invalid_config = BASE_CONFIG.replace("runtime: k3s", "runtime: eks");
     let file = write_config(&invalid_config);
 
@@ -216,7 +216,54 @@ fn given_runtime_not_k3s_when_run_bootstrap_then_fails() {
         .arg("--dry-run");
 
     cmd.assert().failure().stderr(predicate::str::contains(
-        "Only 'k3s' runtime is currently supported",
+        "Unsupported cluster runtime 'eks' (expected one of: k3s, kind, existing-cluster)",
+    ));
+}
+
+#[test]
+fn given_existing_cluster_runtime_when_dry_run_verbose_then_skips_install() {
+    let config = BASE_CONFIG.replace(
+        "  runtime: k3s\n",
+        "  runtime: existing-cluster\n  existingCluster:\n    context: staging\n",
+    );
+    let file = write_config(&config);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.arg("k8s-bootstrap")
+        .arg("bootstrap")
+        .arg("--config")
+        .arg(file.path())
+        .arg("--dry-run")
+        .arg("--verbose");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Cluster: test-cluster (existing cluster, staging)",
+        ))
+        .stdout(predicate::str::contains("📋 Existing Cluster Plan"))
+        .stdout(predicate::str::contains(
+            "`kubectl --context staging` must reach the cluster",
+        ))
+        .stdout(predicate::str::contains("k3s Installation Plan").not());
+}
+
+#[test]
+fn given_k3s_runtime_without_k3s_section_when_run_bootstrap_then_fails() {
+    let start = BASE_CONFIG.find("  k3s:").unwrap();
+    let end = BASE_CONFIG.find("demon:").unwrap();
+    let config = format!("{}{}", &BASE_CONFIG[..start], &BASE_CONFIG[end..]);
+    let file = write_config(&config);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.arg("k8s-bootstrap")
+        .arg("bootstrap")
+        .arg("--config")
+        .arg(file.path())
+        .arg("--dry-run");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "cluster.k3s is required when cluster.runtime is 'k3s'",
     ));
 }
 
@@ -1069,3 +1116,248 @@ fn given_apply_only_mode_when_namespace_wait_timeout_then_fails_with_timeout_err
             "Failed to apply namespace manifests",
         ));
 }
+
+#[test]
+fn given_cluster_differs_when_upgrade_then_applies_and_waits_for_rollout() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.arg("k8s-bootstrap")
+        .arg("upgrade")
+        .arg("--config")
+        .arg(file.path());
+    cmd.env("DEMONCTL_K8S_EXECUTOR", "simulate-success");
+    cmd.env(
+        "DEMONCTL_K8S_EXECUTOR_STDOUT",
+        "diff -u -N /tmp/LIVE-1/apps.v1.Deployment.test-system.demon-runtime /tmp/MERGED-1/apps.v1.Deployment.test-system.demon-runtime\n",
+    );
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1 resource will change:"))
+        .stdout(predicate::str::contains("  - Deployment/demon-runtime"))
+        .stdout(predicate::str::contains(
+            "🎉 Upgrade complete: 4 workloads rolled out in test-system",
+        ));
+}
+
+#[test]
+fn given_upgrade_dry_run_when_cluster_differs_then_nothing_is_applied() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.args([
+        "k8s-bootstrap",
+        "upgrade",
+        "--dry-run",
+        "-o",
+        "json",
+        "--config",
+    ])
+    .arg(file.path());
+    cmd.env("DEMONCTL_K8S_EXECUTOR", "simulate-success");
+    cmd.env(
+        "DEMONCTL_K8S_EXECUTOR_STDOUT",
+        "diff -u -N /tmp/LIVE-1/v1.Service.test-system.nats /tmp/MERGED-1/v1.Service.test-system.nats\n",
+    );
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["kind"], "K8sUpgradeSummary");
+    assert_eq!(summary["changed"], serde_json::json!(["Service/nats"]));
+    assert_eq!(summary["applied"], false);
+}
+
+#[test]
+fn given_uninstall_dry_run_then_lists_workloads_first_and_namespace_last() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.args([
+        "k8s-bootstrap",
+        "uninstall",
+        "--dry-run",
+        "-o",
+        "json",
+        "--config",
+    ])
+    .arg(file.path());
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let resources: Vec<&str> = summary["resources"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r.as_str().unwrap())
+        .collect();
+    assert_eq!(resources.first(), Some(&"Deployment/operate-ui"));
+    assert_eq!(resources.last(), Some(&"Namespace/test-system"));
+    let nats = resources.iter().position(|r| *r == "StatefulSet/nats");
+    let nats_service = resources.iter().position(|r| *r == "Service/nats");
+    assert!(nats < nats_service);
+}
+
+#[test]
+fn given_uninstall_without_yes_when_not_interactive_then_refuses() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.args(["k8s-bootstrap", "uninstall", "--config"])
+        .arg(file.path())
+        .write_stdin("");
+    cmd.env("DEMONCTL_K8S_EXECUTOR", "simulate-success");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --yes"));
+}
+
+#[test]
+fn given_uninstall_with_yes_then_deletes_every_resource() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.args([
+        "k8s-bootstrap",
+        "uninstall",
+        "--yes",
+        "--keep-namespace",
+        "--config",
+    ])
+    .arg(file.path());
+    cmd.env("DEMONCTL_K8S_EXECUTOR", "simulate-success");
+
+    cmd.assert().success().stdout(predicate::str::contains(
+        "✓ Removed 8 resources from test-system",
+    ));
+}
+
+#[test]
+fn given_helm_chart_dir_when_bootstrap_then_writes_chart_without_deploying() {
+    let file = write_config(BASE_CONFIG);
+    let out = tempfile::TempDir::new().unwrap();
+    let chart_dir = out.path().join("demon");
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.args(["k8s-bootstrap", "bootstrap", "--config"])
+        .arg(file.path())
+        .arg("--helm-chart")
+        .arg(&chart_dir);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("✓ Wrote Helm chart demon-"))
+        .stdout(predicate::str::contains("templates/ingress.yaml"))
+        .stdout(predicate::str::contains("Starting K8s bootstrap").not());
+
+    let values: serde_yaml::Value =
+        serde_yaml::from_str(&std::fs::read_to_string(chart_dir.join("values.yaml")).unwrap())
+            .unwrap();
+    assert_eq!(values["namespace"].as_str(), Some("test-system"));
+    assert_eq!(values["streamName"].as_str(), Some("TEST_EVENTS"));
+    assert_eq!(values["persistence"]["enabled"].as_bool(), Some(true));
+
+    let nats = std::fs::read_to_string(chart_dir.join("templates/nats.yaml")).unwrap();
+    assert!(nats.contains("namespace: {{ .Values.namespace }}"));
+    assert!(nats.contains("{{- if .Values.persistence.enabled }}"));
+    assert!(chart_dir.join("Chart.yaml").exists());
+}
+
+#[test]
+fn given_helm_chart_and_dry_run_when_bootstrap_then_rejects_combination() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.args([
+        "k8s-bootstrap",
+        "bootstrap",
+        "--dry-run",
+        "--helm-chart",
+        "chart",
+        "--config",
+    ])
+    .arg(file.path());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn given_smoke_with_dry_run_when_run_bootstrap_then_rejects_flags() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.arg("k8s-bootstrap")
+        .arg("bootstrap")
+        .arg("--config")
+        .arg(file.path())
+        .arg("--dry-run")
+        .arg("--smoke");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn given_malformed_smoke_ritual_when_run_bootstrap_then_fails_before_deploying() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.arg("k8s-bootstrap")
+        .arg("bootstrap")
+        .arg("--config")
+        .arg(file.path())
+        .arg("--smoke")
+        .arg("--smoke-ritual")
+        .arg("noop");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("expected APP:RITUAL, got 'noop'"));
+}
+
+#[test]
+fn given_offline_bundle_missing_images_when_bootstrap_dry_run_then_lists_bundle_errors() {
+    let file = write_config(BASE_CONFIG);
+    let bundle = tempfile::TempDir::new().unwrap();
+    std::fs::write(bundle.path().join("images.tar"), b"").unwrap();
+    std::fs::write(
+        bundle.path().join("offline-bundle.json"),
+        r#"{"version":1,"createdAt":"2026-01-01T00:00:00Z","images":[],
+            "archive":{"path":"images.tar","sha256":"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"}}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.args(["k8s-bootstrap", "bootstrap", "--dry-run", "--config"])
+        .arg(file.path())
+        .arg("--offline")
+        .arg(bundle.path());
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("offline bundle errors"))
+        .stderr(predicate::str::contains(
+            "image nats:2.10-alpine is not in the bundle",
+        ))
+        .stderr(predicate::str::contains("bundle has no k3s files"));
+}
+
+#[test]
+fn given_offline_and_latest_digests_when_bootstrap_then_rejects_combination() {
+    let file = write_config(BASE_CONFIG);
+
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.args(["k8s-bootstrap", "bootstrap", "--dry-run", "--config"])
+        .arg(file.path())
+        .args(["--offline", "bundle", "--use-latest-digests"]);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
@@ -0,0 +1,204 @@
+use assert_cmd::Command;
+use httptest::{cycle, matchers::*, responders::*, Expectation, Server};
+use predicates::prelude::*;
+use serde_json::json;
+use tempfile::TempDir;
+
+fn demonctl(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.env("DEMON_HOME", home.path())
+        .env("DEMONCTL_CREDENTIAL_STORE", "file")
+        .env_remove("DEMONCTL_JWT")
+        .env_remove("DEMONCTL_CREDENTIALS_KEY")
+        .env_remove("DEMONCTL_OIDC_ISSUER");
+    cmd
+}
+
+#[test]
+fn given_pasted_token_when_logging_in_then_token_is_encrypted_at_rest_and_removed_on_logout() {
+    let home = TempDir::new().unwrap();
+
+    demonctl(&home)
+        .args(["login", "--token", "pasted-secret-token"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("stored in file"));
+
+    let stored = std::fs::read_to_string(home.path().join("credentials.enc")).unwrap();
+    assert!(!stored.contains("pasted-secret-token"));
+
+    demonctl(&home)
+        .arg("token")
+        .assert()
+        .success()
+        .stdout("pasted-secret-token\n");
+
+    demonctl(&home)
+        .arg("logout")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Logged out"));
+    assert!(!home.path().join("credentials.enc").exists());
+
+    demonctl(&home)
+        .arg("token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Not logged in"));
+}
+
+#[test]
+fn given_device_code_flow_when_approved_then_expiring_token_is_refreshed_for_registry_calls() {
+    let home = TempDir::new().unwrap();
+    let idp = Server::run();
+    let issuer = format!("http://{}", idp.addr());
+
+    idp.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            "/.well-known/openid-configuration",
+        ))
+        .respond_with(json_encoded(json!({
+            "issuer": issuer,
+            "device_authorization_endpoint": format!("{}/device", issuer),
+            "token_endpoint": format!("{}/token", issuer)
+        }))),
+    );
+    idp.expect(
+        Expectation::matching(all_of![
+            request::method_path("POST", "/device"),
+            request::body(url_decoded(contains(("client_id", "demonctl")))),
+        ])
+        .respond_with(json_encoded(json!({
+            "device_code": "dev-1",
+            "user_code": "ABCD-EFGH",
+            "verification_uri": "https://idp.example.com/activate",
+            "expires_in": 60,
+            "interval": 1
+        }))),
+    );
+    idp.expect(
+        Expectation::matching(all_of![
+            request::method_path("POST", "/token"),
+            request::body(url_decoded(contains(("device_code", "dev-1")))),
+        ])
+        .times(2)
+        .respond_with(cycle![
+            status_code(400).body(json!({ "error": "authorization_pending" }).to_string()),
+            json_encoded(json!({
+                "access_token": "first-token",
+                "refresh_token": "refresh-1",
+                "expires_in": 0
+            })),
+        ]),
+    );
+
+    demonctl(&home)
+        .args(["login", "--issuer", &issuer])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("ABCD-EFGH"));
+
+    idp.expect(
+        Expectation::matching(all_of![
+            request::method_path("POST", "/token"),
+            request::body(url_decoded(contains(("grant_type", "refresh_token")))),
+            request::body(url_decoded(contains(("refresh_token", "refresh-1")))),
+        ])
+        .respond_with(json_encoded(json!({
+            "access_token": "second-token",
+            "expires_in": 3600
+        }))),
+    );
+    let registry = Server::run();
+    registry.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", "/registry/contracts"),
+            request::headers(contains(("authorization", "Bearer second-token"))),
+        ])
+        .respond_with(json_encoded(json!({ "contracts": [] }))),
+    );
+
+    demonctl(&home)
+        .env(
+            "DEMONCTL_REGISTRY_URL",
+            format!("http://{}", registry.addr()),
+        )
+        .args(["registry", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No contracts found"));
+
+    demonctl(&home)
+        .args(["token", "--info"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Refreshable: yes"));
+}
+
+#[test]
+fn given_passphrase_when_logging_in_then_file_key_is_salted_and_wrong_passphrase_fails() {
+    let home = TempDir::new().unwrap();
+
+    demonctl(&home)
+        .env("DEMONCTL_CREDENTIALS_KEY", "correct horse")
+        .args(["login", "--token", "pasted-secret-token"])
+        .assert()
+        .success();
+
+    let stored: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(home.path().join("credentials.enc")).unwrap())
+            .unwrap();
+    assert_eq!(stored["version"], 2);
+    assert_eq!(stored["kdf"]["logN"], 15);
+    assert!(!stored["kdf"]["salt"].as_str().unwrap().is_empty());
+    assert!(!home.path().join("credentials.key").exists());
+
+    demonctl(&home)
+        .env("DEMONCTL_CREDENTIALS_KEY", "correct horse")
+        .arg("token")
+        .assert()
+        .success()
+        .stdout("pasted-secret-token\n");
+
+    demonctl(&home)
+        .env("DEMONCTL_CREDENTIALS_KEY", "wrong horse")
+        .arg("token")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot decrypt"));
+}
+
+#[test]
+fn given_version_1_passphrase_file_when_reading_token_then_it_still_decrypts() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use chacha20poly1305::{aead::Aead, ChaCha20Poly1305, Key, KeyInit, Nonce};
+    use sha2::{Digest, Sha256};
+
+    let home = TempDir::new().unwrap();
+    let key = Sha256::digest(b"correct horse");
+    let nonce = [7u8; 12];
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            br#"{"accessToken":"legacy-token"}"#.as_ref(),
+        )
+        .unwrap();
+    std::fs::write(
+        home.path().join("credentials.enc"),
+        json!({
+            "version": 1,
+            "nonce": STANDARD.encode(nonce),
+            "ciphertext": STANDARD.encode(ciphertext),
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    demonctl(&home)
+        .env("DEMONCTL_CREDENTIALS_KEY", "correct horse")
+        .arg("token")
+        .assert()
+        .success()
+        .stdout("legacy-token\n");
+}
@@ -0,0 +1,202 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use std::fs;
+use tempfile::TempDir;
+
+fn output_schema() -> jsonschema::JSONSchema {
+    let schema: Value = serde_json::from_str(include_str!(
+        "../../contracts/schemas/demonctl.output.v1.json"
+    ))
+    .unwrap();
+    jsonschema::JSONSchema::compile(&schema).unwrap()
+}
+
+fn assert_matches_schema(document: &Value) {
+    let schema = output_schema();
+    let result = schema.validate(document);
+    if let Err(errors) = result {
+        let errors: Vec<String> = errors.map(|e| e.to_string()).collect();
+        panic!("{} does not match schema: {:?}", document, errors);
+    }
+}
+
+fn json_stdout(args: &[&str]) -> Value {
+    let output = Command::cargo_bin("demonctl")
+        .unwrap()
+        .env("DEMON_SECRETS_PASSPHRASE", "output-format-spec")
+        .env("DEMON_SECRETS_SCRYPT_LOG_N", "10")
+        .env_remove("DEMON_SECRETS_KEY_SOURCE")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn given_secrets_when_using_json_output_then_documents_match_schema() {
+    let temp_dir = TempDir::new().unwrap();
+    let secrets_file = temp_dir.path().join("secrets.json");
+    let secrets_file = secrets_file.to_str().unwrap();
+
+    let set = json_stdout(&[
+        "secrets",
+        "set",
+        "database/password",
+        "secretvalue123",
+        "--secrets-file",
+        secrets_file,
+        "-o",
+        "json",
+    ]);
+    assert_matches_schema(&set);
+    assert_eq!(set["kind"], "SecretChange");
+    assert_eq!(set["action"], "set");
+
+    let get = json_stdout(&[
+        "secrets",
+        "-o",
+        "json",
+        "get",
+        "database/password",
+        "--secrets-file",
+        secrets_file,
+    ]);
+    assert_matches_schema(&get);
+    assert_eq!(get["value"], "sec***");
+    assert_eq!(get["redacted"], true);
+
+    let list = json_stdout(&[
+        "secrets",
+        "list",
+        "--secrets-file",
+        secrets_file,
+        "--output",
+        "json",
+    ]);
+    assert_matches_schema(&list);
+    assert_eq!(list["secrets"]["database"]["password"], "sec***");
+
+    let migration = json_stdout(&[
+        "secrets",
+        "migrate",
+        "--secrets-file",
+        secrets_file,
+        "-o",
+        "json",
+    ]);
+    assert_matches_schema(&migration);
+    assert_eq!(migration["kind"], "SecretMigration");
+    assert_eq!(migration["secretCount"], 1);
+    assert_eq!(migration["wasEncrypted"], true);
+    assert_eq!(migration["keySource"], "passphrase");
+}
+
+#[test]
+fn given_invalid_envelope_when_using_json_output_then_report_is_printed_and_exit_is_nonzero() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("invalid_envelope.json");
+    fs::write(&file_path, json!({ "invalid_field": "test" }).to_string()).unwrap();
+
+    let output = Command::cargo_bin("demonctl")
+        .unwrap()
+        .args([
+            "contracts",
+            "validate-envelope",
+            file_path.to_str().unwrap(),
+            "-o",
+            "json",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_matches_schema(&report);
+    assert_eq!(report["kind"], "ValidationReport");
+    assert_eq!(report["valid"], false);
+    assert!(!report["errors"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn given_k8s_dry_run_when_using_yaml_output_then_summary_matches_schema() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = temp_dir.path().join("config.yaml");
+    fs::write(
+        &config,
+        r#"
+apiVersion: demon.io/v1
+kind: BootstrapConfig
+metadata:
+  name: test-cluster
+cluster:
+  name: test-cluster
+  runtime: k3s
+  k3s:
+    version: "v1.28.2+k3s1"
+    install:
+      channel: stable
+      disable: []
+    dataDir: "/var/lib/rancher/k3s"
+    nodeName: "test-node"
+    extraArgs: []
+demon:
+  natsUrl: "nats://localhost:4222"
+  streamName: "TEST_EVENTS"
+  subjects:
+    - "test.>"
+  dedupeWindowSecs: 30
+  uiUrl: "http://localhost:3000"
+  namespace: "test-system"
+  persistence:
+    enabled: true
+    storageClass: "local-path"
+    size: "10Gi"
+secrets:
+  provider: env
+  env: {}
+addons: []
+networking:
+  ingress:
+    enabled: false
+    tls:
+      enabled: false
+  serviceMesh:
+    enabled: false
+"#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("demonctl")
+        .unwrap()
+        .args([
+            "k8s-bootstrap",
+            "bootstrap",
+            "--config",
+            config.to_str().unwrap(),
+            "--dry-run",
+            "-o",
+            "yaml",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let summary: Value = serde_yaml::from_slice(&output.stdout).unwrap();
+    assert_matches_schema(&summary);
+    assert_eq!(summary["mode"], "dry-run");
+    assert_eq!(summary["namespace"], "test-system");
+    assert!(summary.get("manifests").is_none());
+}
+
+#[test]
+fn given_shell_when_generating_completions_then_script_names_subcommands() {
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_demonctl"))
+        .stdout(predicate::str::contains("k8s-bootstrap"));
+}
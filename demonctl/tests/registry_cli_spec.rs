@@ -0,0 +1,173 @@
+use assert_cmd::Command;
+use httptest::{matchers::*, responders::*, Expectation, Server};
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use std::io::Write;
+
+fn demonctl(server: &Server) -> Command {
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.env("DEMONCTL_REGISTRY_URL", format!("http://{}", server.addr()))
+        .env("DEMONCTL_JWT", "test-jwt");
+    cmd
+}
+
+fn bundle(version: &str, schema: Value) -> Value {
+    json!({
+        "name": "order.created",
+        "version": version,
+        "description": null,
+        "createdAt": "2025-01-01T00:00:00Z",
+        "jsonSchema": schema.to_string(),
+        "witPath": null,
+        "descriptorPath": null
+    })
+}
+
+#[test]
+fn given_schema_file_when_publishing_then_bundle_is_posted_with_bearer_token() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("POST", "/registry/contracts"),
+            request::headers(contains(("authorization", "Bearer test-jwt"))),
+            request::body(json_decoded(|body: &Value| {
+                body["name"] == "order.created"
+                    && body["version"] == "1.1.0"
+                    && body["jsonSchema"]
+                        .as_str()
+                        .is_some_and(|s| s.contains("\"object\""))
+            })),
+        ])
+        .respond_with(status_code(201).body(
+            json!({ "status": "created", "name": "order.created", "version": "1.1.0", "digest": "abc123" })
+                .to_string(),
+        )),
+    );
+
+    let mut schema = tempfile::NamedTempFile::new().unwrap();
+    write!(schema, "{}", json!({ "type": "object" })).unwrap();
+
+    demonctl(&server)
+        .args(["registry", "publish"])
+        .arg(schema.path())
+        .args(["--name", "order.created", "--version", "1.1.0"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Digest:   abc123"));
+}
+
+#[test]
+fn given_invalid_schema_when_publishing_then_nothing_is_sent() {
+    let server = Server::run();
+
+    let mut schema = tempfile::NamedTempFile::new().unwrap();
+    write!(schema, "{}", json!({ "type": 42 })).unwrap();
+
+    demonctl(&server)
+        .args(["registry", "publish"])
+        .arg(schema.path())
+        .args(["--name", "order.created", "--version", "1.1.0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a valid JSON schema"));
+}
+
+#[test]
+fn given_schema_rejected_by_house_rules_when_publishing_then_findings_are_listed() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path("POST", "/registry/contracts")).respond_with(
+            status_code(422).body(
+                json!({
+                    "status": "invalid",
+                    "error": "Contract order.created v1.1.0 has an invalid JSON schema",
+                    "findings": [
+                        {"rule": "title", "path": "", "message": "the root must have a non-empty title"},
+                        {"rule": "version", "path": "/$id", "message": "declares v2 but is published as version 1.1.0"}
+                    ]
+                })
+                .to_string(),
+            ),
+        ),
+    );
+
+    let mut schema = tempfile::NamedTempFile::new().unwrap();
+    write!(schema, "{}", json!({ "type": "object" })).unwrap();
+
+    demonctl(&server)
+        .args(["registry", "publish"])
+        .arg(schema.path())
+        .args(["--name", "order.created", "--version", "1.1.0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has an invalid JSON schema"))
+        .stderr(predicate::str::contains(
+            "[title] /: the root must have a non-empty title",
+        ))
+        .stderr(predicate::str::contains("[version] /$id: declares v2"));
+}
+
+#[test]
+fn given_two_versions_when_diffing_then_changed_paths_are_reported() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            "/registry/contracts/order.created/1.0.0",
+        ))
+        .respond_with(json_encoded(bundle(
+            "1.0.0",
+            json!({ "type": "object", "properties": { "id": { "type": "string" } } }),
+        ))),
+    );
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            "/registry/contracts/order.created/1.1.0",
+        ))
+        .respond_with(json_encoded(bundle(
+            "1.1.0",
+            json!({ "type": "object", "properties": { "id": { "type": "integer" } } }),
+        ))),
+    );
+
+    let output = demonctl(&server)
+        .args([
+            "registry",
+            "diff",
+            "order.created",
+            "1.0.0",
+            "1.1.0",
+            "-o",
+            "json",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let changes: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        changes,
+        json!([{ "kind": "changed", "path": "/properties/id/type", "before": "string", "after": "integer" }])
+    );
+}
+
+#[test]
+fn given_missing_version_when_getting_then_command_fails() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            "/registry/contracts/order.created/9.9.9",
+        ))
+        .respond_with(status_code(404).body("Contract not found: order.created v9.9.9")),
+    );
+
+    demonctl(&server)
+        .args(["registry", "get", "order.created", "9.9.9"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Contract order.created v9.9.9 not found",
+        ));
+}
@@ -147,3 +147,28 @@ fn run_help_shows_save_options() {
         "help should mention --output-dir flag"
     );
 }
+
+#[test]
+fn run_with_invalid_inputs_fails_before_running() {
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    let root = workspace_root();
+    let output = cmd
+        .current_dir(&root)
+        .args([
+            "run",
+            "examples/rituals/deploy.yaml",
+            "--set",
+            "environment=dev",
+            "--set",
+            "version=1.4.2",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("invalid inputs for ritual 'deploy-ritual'"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,141 @@
+use assert_cmd::Command;
+use httptest::{matchers::*, responders::*, Expectation, Server};
+use predicates::prelude::*;
+use serde_json::{json, Value};
+
+fn demonctl(server: &Server) -> Command {
+    let mut cmd = Command::cargo_bin("demonctl").unwrap();
+    cmd.env("DEMONCTL_API_URL", format!("http://{}", server.addr()))
+        .env("DEMONCTL_JWT", "test-jwt")
+        .env_remove("DEMON_TENANT");
+    cmd
+}
+
+#[test]
+fn given_operate_ui_when_listing_runs_then_filters_are_forwarded_and_since_applied() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(all_of![
+            request::method_path("GET", "/api/tenants/acme/runs"),
+            request::query(url_decoded(contains(("ritual", "release")))),
+            request::query(url_decoded(contains(("status", "Completed")))),
+            request::query(url_decoded(contains(("limit", "1000")))),
+            request::headers(contains(("authorization", "Bearer test-jwt"))),
+        ])
+        .respond_with(json_encoded(json!({
+            "runs": [
+                { "runId": "run-new", "ritualId": "release", "startTs": "2025-01-02T00:00:00Z", "status": "Completed" },
+                { "runId": "run-old", "ritualId": "release", "startTs": "2024-12-01T00:00:00Z", "status": "Completed" }
+            ],
+            "nextCursor": null,
+            "lastEventId": null
+        }))),
+    );
+
+    let output = demonctl(&server)
+        .args([
+            "runs",
+            "list",
+            "--tenant",
+            "acme",
+            "--ritual",
+            "release",
+            "--status",
+            "completed",
+            "--since",
+            "2025-01-01T00:00:00Z",
+            "-o",
+            "json",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let runs: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(runs.as_array().unwrap().len(), 1);
+    assert_eq!(runs[0]["runId"], "run-new");
+}
+
+#[test]
+fn given_completed_run_when_showing_then_table_and_envelope_views_render() {
+    let server = Server::run();
+    let detail = json!({
+        "runId": "run-1",
+        "ritualId": "release",
+        "events": [
+            { "event": "ritual.started:v1", "ts": "2025-01-01T00:00:00Z" },
+            { "event": "step.retried:v1", "ts": "2025-01-01T00:00:05Z", "stepId": "build", "error": "capsule unavailable" },
+            {
+                "event": "ritual.completed:v1",
+                "ts": "2025-01-01T00:01:00Z",
+                "reason": "step_failed",
+                "outputs": { "steps": { "build": { "error": "capsule unavailable" } } }
+            }
+        ]
+    });
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            "/api/tenants/default/runs/run-1",
+        ))
+        .times(2)
+        .respond_with(json_encoded(detail)),
+    );
+
+    demonctl(&server)
+        .args(["runs", "show", "run-1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Status:  Completed"))
+        .stdout(predicate::str::contains("Reason:  step_failed"))
+        .stdout(predicate::str::contains("step=build capsule unavailable"));
+
+    let output = demonctl(&server)
+        .args(["runs", "show", "run-1", "--envelope"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let envelope: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        envelope["steps"]["build"]["error"],
+        json!("capsule unavailable")
+    );
+}
+
+#[test]
+fn given_unknown_run_when_showing_then_command_fails() {
+    let server = Server::run();
+    server.expect(
+        Expectation::matching(request::method_path(
+            "GET",
+            "/api/tenants/default/runs/missing",
+        ))
+        .respond_with(status_code(404).body(json!({ "error": "Run not found" }).to_string())),
+    );
+
+    demonctl(&server)
+        .args(["runs", "show", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Run 'missing' not found"));
+}
+
+#[test]
+fn given_dlq_requeue_without_sequence_when_run_then_usage_error_names_argument() {
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .args(["runs", "dlq", "requeue"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<SEQUENCE>"));
+}
+
+#[test]
+fn given_run_watch_with_replay_when_parsed_then_flags_conflict() {
+    Command::cargo_bin("demonctl")
+        .unwrap()
+        .args(["run", "ritual.yaml", "--watch", "--replay", "run-1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--watch"));
+}
@@ -1,10 +1,32 @@
 use anyhow::Result;
 use assert_cmd::Command;
+use config_loader::SecretsKey;
 use predicates::prelude::*;
 use serde_json::Value;
 use std::fs;
 use tempfile::TempDir;
 
+const TEST_PASSPHRASE: &str = "secrets-cli-spec";
+
+/// demonctl with a secrets passphrase and a cheap scrypt cost for new files
+fn demonctl() -> Result<Command> {
+    let mut cmd = Command::cargo_bin("demonctl")?;
+    cmd.env("DEMON_SECRETS_PASSPHRASE", TEST_PASSPHRASE)
+        .env("DEMON_SECRETS_SCRYPT_LOG_N", "10")
+        .env_remove("DEMON_SECRETS_KEY_SOURCE");
+    Ok(cmd)
+}
+
+fn read_secrets_file(path: &str) -> Result<Value> {
+    let content = fs::read_to_string(path)?;
+    assert!(config_loader::secrets_crypto::is_encrypted(&content));
+    let plaintext = config_loader::secrets_crypto::decrypt(
+        &content,
+        Some(&SecretsKey::passphrase(TEST_PASSPHRASE)),
+    )?;
+    Ok(serde_json::from_str(&plaintext)?)
+}
+
 fn setup_test_env() -> (TempDir, String) {
     let temp_dir = TempDir::new().unwrap();
     let secrets_file = temp_dir.path().join("test_secrets.json");
@@ -16,7 +38,7 @@ fn test_secrets_set_get_delete_flow() -> Result<()> {
     let (_temp_dir, secrets_file) = setup_test_env();
 
     // Set a secret
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "database/password", "secretvalue123"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -27,7 +49,7 @@ fn test_secrets_set_get_delete_flow() -> Result<()> {
         ));
 
     // Get the secret (redacted)
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "get", "database/password"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -36,7 +58,7 @@ fn test_secrets_set_get_delete_flow() -> Result<()> {
         .stdout(predicate::str::contains("database/password: sec***"));
 
     // Get the secret (raw)
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "get", "database/password", "--raw"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -47,7 +69,7 @@ fn test_secrets_set_get_delete_flow() -> Result<()> {
         );
 
     // Delete the secret
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "delete", "database/password"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -56,7 +78,7 @@ fn test_secrets_set_get_delete_flow() -> Result<()> {
         .stdout(predicate::str::contains("Secret database/password deleted"));
 
     // Try to get deleted secret (should fail)
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "get", "database/password"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -73,7 +95,7 @@ fn test_secrets_set_from_env() -> Result<()> {
 
     std::env::set_var("TEST_SECRET_VALUE", "env_secret_123");
 
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args([
             "secrets",
             "set",
@@ -90,7 +112,7 @@ fn test_secrets_set_from_env() -> Result<()> {
         ));
 
     // Verify it was set correctly
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "get", "api/token", "--raw"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -107,21 +129,21 @@ fn test_secrets_list() -> Result<()> {
     let (_temp_dir, secrets_file) = setup_test_env();
 
     // Set multiple secrets
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "database/password", "dbpass123"])
         .arg("--secrets-file")
         .arg(&secrets_file)
         .assert()
         .success();
 
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "database/username", "admin"])
         .arg("--secrets-file")
         .arg(&secrets_file)
         .assert()
         .success();
 
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "api/key", "apikey456"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -129,7 +151,7 @@ fn test_secrets_list() -> Result<()> {
         .success();
 
     // List all secrets
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "list"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -144,7 +166,7 @@ fn test_secrets_list() -> Result<()> {
         );
 
     // List by scope
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "list", "--scope", "database"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -164,7 +186,7 @@ fn test_secrets_invalid_format() -> Result<()> {
     let (_temp_dir, secrets_file) = setup_test_env();
 
     // Invalid format (no slash)
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "invalidkey", "value"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -173,7 +195,7 @@ fn test_secrets_invalid_format() -> Result<()> {
         .stderr(predicate::str::contains("Invalid scope/key format"));
 
     // Invalid format (empty scope)
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "/key", "value"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -182,7 +204,7 @@ fn test_secrets_invalid_format() -> Result<()> {
         .stderr(predicate::str::contains("Invalid scope/key format"));
 
     // Invalid format (empty key)
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "scope/", "value"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -198,14 +220,14 @@ fn test_secrets_file_format() -> Result<()> {
     let (_temp_dir, secrets_file) = setup_test_env();
 
     // Set some secrets
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "echo/api_key", "echo123"])
         .arg("--secrets-file")
         .arg(&secrets_file)
         .assert()
         .success();
 
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args([
             "secrets",
             "set",
@@ -217,14 +239,18 @@ fn test_secrets_file_format() -> Result<()> {
         .assert()
         .success();
 
-    // Read the file and verify JSON format
-    let content = fs::read_to_string(&secrets_file)?;
-    let json: Value = serde_json::from_str(&content)?;
+    // Decrypt the file and verify JSON format
+    assert!(!fs::read_to_string(&secrets_file)?.contains("echo123"));
+    let json = read_secrets_file(&secrets_file)?;
 
     // Verify structure matches EnvFileSecretProvider expectations
     assert!(json.is_object());
-    assert_eq!(json["echo"]["api_key"], "echo123");
-    assert_eq!(json["database"]["connection"], "postgres://localhost");
+    assert_eq!(json["echo"]["api_key"]["value"], "echo123");
+    assert!(json["echo"]["api_key"]["created_at"].is_string());
+    assert_eq!(
+        json["database"]["connection"]["value"],
+        "postgres://localhost"
+    );
 
     Ok(())
 }
@@ -233,7 +259,7 @@ fn test_secrets_file_format() -> Result<()> {
 fn test_secrets_empty_list() -> Result<()> {
     let (_temp_dir, secrets_file) = setup_test_env();
 
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "list"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -248,7 +274,7 @@ fn test_secrets_empty_list() -> Result<()> {
 fn test_secrets_delete_nonexistent() -> Result<()> {
     let (_temp_dir, secrets_file) = setup_test_env();
 
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "delete", "nonexistent/key"])
         .arg("--secrets-file")
         .arg(&secrets_file)
@@ -273,7 +299,7 @@ fn test_secrets_integration_with_config_validation() -> Result<()> {
     fs::write(&config_file, config_content)?;
 
     // Set the secret using our CLI
-    Command::cargo_bin("demonctl")?
+    demonctl()?
         .args(["secrets", "set", "echo/prefix", "Test Secret: "])
         .arg("--secrets-file")
         .arg(secrets_file.to_str().unwrap())
@@ -283,7 +309,7 @@ fn test_secrets_integration_with_config_validation() -> Result<()> {
     // Now validate config with the secrets file
     // Note: This assumes the echo schema exists in contracts/config/
     // We'll just verify the command structure is correct
-    let result = Command::cargo_bin("demonctl")?
+    let result = demonctl()?
         .args(["contracts", "validate-config"])
         .arg(config_file.to_str().unwrap())
         .arg("--schema")
@@ -300,3 +326,114 @@ fn test_secrets_integration_with_config_validation() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_secrets_rotate() -> Result<()> {
+    let (_temp_dir, secrets_file) = setup_test_env();
+
+    // Rotating a secret that does not exist fails
+    demonctl()?
+        .args(["secrets", "rotate", "database/password", "newpass456"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .failure();
+
+    demonctl()?
+        .args(["secrets", "set", "database/password", "oldpass123"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .success();
+
+    demonctl()?
+        .args(["secrets", "rotate", "database/password", "newpass456"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Secret database/password rotated"))
+        .stdout(predicate::str::contains("Rotated at:"));
+
+    let json = read_secrets_file(&secrets_file)?;
+    assert_eq!(json["database"]["password"]["value"], "newpass456");
+    assert!(json["database"]["password"]["created_at"].is_string());
+    assert!(json["database"]["password"]["rotated_at"].is_string());
+
+    Ok(())
+}
+
+#[test]
+fn test_secrets_migrate_encrypts_plaintext_file() -> Result<()> {
+    let (_temp_dir, secrets_file) = setup_test_env();
+    fs::write(
+        &secrets_file,
+        r#"{"database": {"password": "legacypass123"}, "api": {"key": "apikey456"}}"#,
+    )?;
+
+    // Plaintext files stay readable until migrated
+    demonctl()?
+        .args(["secrets", "get", "database/password", "--raw"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("legacypass123"));
+
+    demonctl()?
+        .args(["secrets", "migrate"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Encrypted 2 secrets with the passphrase key",
+        ));
+
+    assert!(!fs::read_to_string(&secrets_file)?.contains("legacypass123"));
+    let json = read_secrets_file(&secrets_file)?;
+    assert_eq!(json["database"]["password"], "legacypass123");
+
+    demonctl()?
+        .args(["secrets", "get", "api/key", "--raw"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("apikey456"));
+
+    Ok(())
+}
+
+#[test]
+fn test_secrets_require_passphrase_for_encrypted_file() -> Result<()> {
+    let (_temp_dir, secrets_file) = setup_test_env();
+
+    demonctl()?
+        .args(["secrets", "set", "database/password", "secretvalue123"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .success();
+
+    // Without a key nothing can be written or read
+    demonctl()?
+        .env_remove("DEMON_SECRETS_PASSPHRASE")
+        .args(["secrets", "set", "database/user", "admin"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("DEMON_SECRETS_PASSPHRASE"));
+
+    demonctl()?
+        .env("DEMON_SECRETS_PASSPHRASE", "not-the-passphrase")
+        .args(["secrets", "get", "database/password"])
+        .arg("--secrets-file")
+        .arg(&secrets_file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("wrong passphrase"));
+
+    Ok(())
+}
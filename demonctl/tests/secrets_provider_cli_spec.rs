@@ -5,6 +5,8 @@ use tempfile::TempDir;
 fn demonctl_binary() -> Command {
     let mut cmd = Command::new(env!("CARGO_BIN_EXE_demonctl"));
     cmd.env_clear(); // Start with clean environment
+    cmd.env("DEMON_SECRETS_PASSPHRASE", "secrets-provider-cli-spec")
+        .env("DEMON_SECRETS_SCRYPT_LOG_N", "10");
     cmd
 }
 
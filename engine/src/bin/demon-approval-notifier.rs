@@ -0,0 +1,12 @@
+use engine::rituals::worker::approval_notifier::{run_loop, NotifierConfig};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+    let cfg = NotifierConfig::from_env()?;
+    if cfg.channels.is_empty() {
+        println!("Approval notifications disabled (set APPROVAL_NOTIFY_CHANNELS to start)");
+        return Ok(());
+    }
+    run_loop(cfg).await
+}
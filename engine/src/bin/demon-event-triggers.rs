@@ -0,0 +1,14 @@
+use engine::rituals::worker::event_triggers::{run_loop, EventTriggerConfig};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+    let enabled =
+        std::env::var("EVENT_TRIGGERS_ENABLED").unwrap_or_else(|_| "0".to_string()) == "1";
+    if !enabled {
+        println!("Event triggers disabled (set EVENT_TRIGGERS_ENABLED=1 to start)");
+        return Ok(());
+    }
+    let cfg = EventTriggerConfig::default();
+    run_loop(cfg).await
+}
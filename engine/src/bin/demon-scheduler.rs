@@ -0,0 +1,13 @@
+use engine::rituals::worker::scheduler::{run_loop, SchedulerConfig};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+    let enabled = std::env::var("SCHEDULER_ENABLED").unwrap_or_else(|_| "0".to_string()) == "1";
+    if !enabled {
+        println!("Scheduler disabled (set SCHEDULER_ENABLED=1 to start)");
+        return Ok(());
+    }
+    let cfg = SchedulerConfig::default();
+    run_loop(cfg).await
+}
@@ -1,5 +1,9 @@
 use anyhow::Result;
+use engine::rituals::checkpoint::KvCheckpointStore;
+use engine::rituals::ledger::KvExecutionLedger;
+use engine::rituals::Engine;
 use std::env;
+use std::sync::Arc;
 use tokio::net::TcpListener;
 use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
@@ -19,6 +23,22 @@ async fn main() -> Result<()> {
     let listener = TcpListener::bind(&addr).await?;
     info!("Engine server listening on {}", addr);
 
+    if !matches!(
+        env::var("ENGINE_RECOVER_RUNS").as_deref(),
+        Ok("0" | "false")
+    ) {
+        // Runs are not `Send`; give recovery its own single-threaded runtime
+        std::thread::spawn(|| {
+            match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime.block_on(recover_in_flight_runs()),
+                Err(e) => warn!("Run recovery disabled: {}", e),
+            }
+        });
+    }
+
     loop {
         match listener.accept().await {
             Ok((mut stream, addr)) => {
@@ -72,3 +92,28 @@ async fn main() -> Result<()> {
         }
     }
 }
+
+/// Resume runs a previous engine process left in flight
+async fn recover_in_flight_runs() {
+    let nats_url = env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let store = match KvCheckpointStore::connect(&nats_url).await {
+        Ok(store) => store,
+        Err(e) => {
+            warn!("Run recovery disabled: {:#}", e);
+            return;
+        }
+    };
+    let mut engine = Engine::new().with_checkpoints(Arc::new(store));
+    // Steps that were mid-flight when the engine died may already have run
+    match KvExecutionLedger::connect(&nats_url).await {
+        Ok(ledger) => engine = engine.with_execution_ledger(Arc::new(ledger)),
+        Err(e) => warn!(
+            "Execution ledger unavailable, recovered steps may re-run: {:#}",
+            e
+        ),
+    }
+    match engine.recover_runs().await {
+        Ok(completions) => info!("Recovered {} in-flight runs", completions.len()),
+        Err(e) => warn!("Run recovery failed: {:#}", e),
+    }
+}
@@ -2,6 +2,7 @@ use crate::rituals::escalation::{EscalationConfig, EscalationState};
 use anyhow::Result;
 use chrono::{Duration, Utc};
 use futures_util::StreamExt;
+use wards::audit::{self, WardsDecision};
 
 /// Compute the approval expiry key for a (run, gate).
 pub fn expiry_key(run_id: &str, gate_id: &str) -> String {
@@ -67,6 +68,23 @@ pub fn parse_escalation_timer_id(timer_id: &str) -> Option<(String, String, u32)
     }
 }
 
+/// Timer id for a wards escalation-policy rule: "{runId}:approval:{gateId}:escalation:{index}".
+pub fn policy_escalation_timer_id(run_id: &str, gate_id: &str, rule_index: usize) -> String {
+    format!("{}:approval:{}:escalation:{}", run_id, gate_id, rule_index)
+}
+
+/// Parse a policy escalation timer id produced by `policy_escalation_timer_id`.
+/// Returns Some((run_id, gate_id, rule_index)) if the format matches, otherwise None.
+pub fn parse_policy_escalation_timer_id(timer_id: &str) -> Option<(String, String, usize)> {
+    let parts: Vec<&str> = timer_id.split(':').collect();
+    if parts.len() == 5 && parts[1] == "approval" && parts[3] == "escalation" {
+        let rule_index = parts[4].parse::<usize>().ok()?;
+        Some((parts[0].to_string(), parts[2].to_string(), rule_index))
+    } else {
+        None
+    }
+}
+
 /// Emit approval.requested:v1 exactly once for a given (runId, gateId).
 /// Subject: demon.ritual.v1.<ritualId>.<runId>.events
 /// Idempotency: Nats-Msg-Id = "<runId>:approval:<gateId>"
@@ -175,6 +193,36 @@ pub async fn await_gate_with_ttl(
             .await?;
     }
 
+    // Escalation policy timers (optional): one timer per rule, relative to the request
+    let escalations = wards::schedule::load_escalations_from_env();
+    if let Some(policy) = escalations.policy_for("default", gate_id) {
+        let subject = format!("demon.ritual.v1.default.{}.{}.events", ritual_id, run_id);
+        for rule_index in 0..policy.rules.len() {
+            let Some(fires_at) = policy.fires_at(rule_index, now) else {
+                continue;
+            };
+            let timer_id = policy_escalation_timer_id(run_id, gate_id, rule_index);
+            let timer_evt = serde_json::json!({
+                "event": "timer.scheduled:v1",
+                "ts": now.to_rfc3339(),
+                "runId": run_id,
+                "timerId": timer_id,
+                "scheduledFor": fires_at.to_rfc3339(),
+            });
+
+            let mut headers = async_nats::HeaderMap::new();
+            let msg_id = format!("{}:scheduled", timer_id);
+            headers.insert("Nats-Msg-Id", msg_id.as_str());
+            js.publish_with_headers(
+                subject.clone(),
+                headers,
+                serde_json::to_vec(&timer_evt)?.into(),
+            )
+            .await?
+            .await?;
+        }
+    }
+
     Ok(())
 }
 
@@ -395,20 +443,14 @@ pub async fn escalate_approval(
     Ok(true)
 }
 
-/// Process an expiry for a (tenant, run, ritual, gate): if no terminal exists,
-/// either escalate to next level or emit approval.denied:v1.
-/// Idempotency key: "{runId}:approval:{gateId}:denied" or "{runId}:approval:{gateId}:escalated:{level}".
-pub async fn process_expiry_if_pending(
+/// Read all events recorded so far for a run, trying the tenant-aware subject first.
+async fn load_run_events(
+    js: &async_nats::jetstream::Context,
     tenant: &str,
-    run_id: &str,
     ritual_id: &str,
-    gate_id: &str,
-) -> Result<bool> {
+    run_id: &str,
+) -> Result<Vec<serde_json::Value>> {
     use async_nats::jetstream;
-    // Connect and resolve stream context
-    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
-    let client = async_nats::connect(&url).await?;
-    let js = jetstream::new(client);
 
     // Resolve stream by scanning both likely names
     let stream = if let Ok(s) = js.get_stream("RITUAL_EVENTS").await {
@@ -458,6 +500,24 @@ pub async fn process_expiry_if_pending(
         events.push(serde_json::from_slice(&m.message.payload)?);
     }
 
+    Ok(events)
+}
+
+/// Process an expiry for a (tenant, run, ritual, gate): if no terminal exists,
+/// either escalate to next level or emit approval.denied:v1.
+/// Idempotency key: "{runId}:approval:{gateId}:denied" or "{runId}:approval:{gateId}:escalated:{level}".
+pub async fn process_expiry_if_pending(
+    tenant: &str,
+    run_id: &str,
+    ritual_id: &str,
+    gate_id: &str,
+) -> Result<bool> {
+    // Connect and resolve stream context
+    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let client = async_nats::connect(&url).await?;
+    let js = async_nats::jetstream::new(client);
+    let events = load_run_events(&js, tenant, ritual_id, run_id).await?;
+
     if terminal_for_gate(&events, gate_id).is_some() {
         // Already terminal; nothing to emit
         return Ok(false);
@@ -564,5 +624,165 @@ pub async fn process_expiry_if_pending(
     js.publish_with_headers(subject, headers, serde_json::to_vec(&payload)?.into())
         .await?
         .await?;
+    if let Some(decision) = WardsDecision::from_approval_event(&payload) {
+        record_decision(
+            &js,
+            decision.with_rule(format!("approval:{}:expiry", gate_id)),
+        )
+        .await;
+    }
+    Ok(true)
+}
+
+/// Append a policy decision to the wards audit trail; failures are only logged.
+async fn record_decision(js: &async_nats::jetstream::Context, decision: WardsDecision) {
+    if let Err(e) = audit::record(js, &audit::decisions_stream(), &decision).await {
+        tracing::warn!(rule_id = %decision.rule_id, "failed to record policy decision: {}", e);
+    }
+}
+
+/// Time the gate was first requested, from its approval.requested:v1 event.
+pub fn requested_at_for_gate(
+    events: &[serde_json::Value],
+    gate_id: &str,
+) -> Option<chrono::DateTime<Utc>> {
+    events.iter().find_map(|e| {
+        if e.get("event")?.as_str()? != "approval.requested:v1"
+            || e.get("gateId")?.as_str()? != gate_id
+        {
+            return None;
+        }
+        chrono::DateTime::parse_from_rfc3339(e.get("ts")?.as_str()?)
+            .ok()
+            .map(|ts| ts.with_timezone(&Utc))
+    })
+}
+
+/// Latest approval.granted/denied/override:v1 event for a gate, if the gate is resolved.
+pub fn terminal_event_for_gate<'a>(
+    events: &'a [serde_json::Value],
+    gate_id: &str,
+) -> Option<&'a serde_json::Value> {
+    events.iter().rev().find(|e| {
+        let is_terminal = matches!(
+            e.get("event").and_then(|v| v.as_str()),
+            Some("approval.granted:v1" | "approval.denied:v1" | "approval.override:v1")
+        );
+        is_terminal && e.get("gateId").and_then(|v| v.as_str()) == Some(gate_id)
+    })
+}
+
+/// Poll the run's events until the gate is granted, denied, or overridden and return
+/// the terminal event. Expiry is enforced by the TTL worker, which emits a denial.
+pub async fn wait_for_gate_resolution(
+    tenant: &str,
+    run_id: &str,
+    ritual_id: &str,
+    gate_id: &str,
+    poll_interval: std::time::Duration,
+) -> Result<serde_json::Value> {
+    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let client = async_nats::connect(&url).await?;
+    let js = async_nats::jetstream::new(client);
+
+    loop {
+        let events = load_run_events(&js, tenant, ritual_id, run_id).await?;
+        if let Some(terminal) = terminal_event_for_gate(&events, gate_id) {
+            return Ok(terminal.clone());
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Fire rule `rule_index` of the gate's wards escalation policy if the gate is still pending.
+/// Emits approval.escalated:v1, followed by approval.granted:v1 / approval.denied:v1 for
+/// auto-grant / auto-deny rules.
+/// Idempotency key: "{runId}:approval:{gateId}:escalation:{index}".
+pub async fn process_policy_escalation(
+    tenant: &str,
+    run_id: &str,
+    ritual_id: &str,
+    gate_id: &str,
+    rule_index: usize,
+) -> Result<bool> {
+    let escalations = wards::schedule::load_escalations_from_env();
+    let Some(policy) = escalations.policy_for(tenant, gate_id) else {
+        return Ok(false); // Policy removed since the timer was scheduled
+    };
+
+    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    let client = async_nats::connect(&url).await?;
+    let js = async_nats::jetstream::new(client);
+    let events = load_run_events(&js, tenant, ritual_id, run_id).await?;
+
+    if terminal_for_gate(&events, gate_id).is_some() {
+        return Ok(false);
+    }
+    let Some(requested_at) = requested_at_for_gate(&events, gate_id) else {
+        return Ok(false);
+    };
+    let Some(escalation) = policy.escalation(run_id, gate_id, rule_index, requested_at, Utc::now())
+    else {
+        return Ok(false);
+    };
+
+    let subject = format!("demon.ritual.v1.{}.{}.{}.events", tenant, ritual_id, run_id);
+    let payload = escalation.event_payload(tenant, ritual_id);
+    let mut headers = async_nats::HeaderMap::new();
+    let msg_id = policy_escalation_timer_id(run_id, gate_id, rule_index);
+    headers.insert("Nats-Msg-Id", msg_id.as_str());
+    js.publish_with_headers(
+        subject.clone(),
+        headers,
+        serde_json::to_vec(&payload)?.into(),
+    )
+    .await?
+    .await?;
+
+    let terminal = match escalation.rule.action.resolution() {
+        Some(wards::approvals::GateState::Granted) => Some((
+            "granted",
+            serde_json::json!({
+                "event": "approval.granted:v1",
+                "ts": Utc::now().to_rfc3339(),
+                "tenantId": tenant,
+                "runId": run_id,
+                "ritualId": ritual_id,
+                "gateId": gate_id,
+                "approver": "system",
+                "note": "auto-granted by escalation policy",
+            }),
+        )),
+        Some(wards::approvals::GateState::Denied) => Some((
+            "denied",
+            serde_json::json!({
+                "event": "approval.denied:v1",
+                "ts": Utc::now().to_rfc3339(),
+                "tenantId": tenant,
+                "runId": run_id,
+                "ritualId": ritual_id,
+                "gateId": gate_id,
+                "approver": "system",
+                "reason": "escalation_policy",
+            }),
+        )),
+        _ => None,
+    };
+    if let Some((kind, payload)) = terminal {
+        let mut headers = async_nats::HeaderMap::new();
+        let msg_id = format!("{}:approval:{}:{}", run_id, gate_id, kind);
+        headers.insert("Nats-Msg-Id", msg_id.as_str());
+        js.publish_with_headers(subject, headers, serde_json::to_vec(&payload)?.into())
+            .await?
+            .await?;
+        if let Some(decision) = WardsDecision::from_approval_event(&payload) {
+            record_decision(
+                &js,
+                decision.with_rule(format!("approval:{}:escalation:{}", gate_id, rule_index)),
+            )
+            .await;
+        }
+    }
+
     Ok(true)
 }
@@ -0,0 +1,338 @@
+//! Persistent run state machine for crash recovery
+//!
+//! Every definition run moves through an explicit lifecycle:
+//!
+//! ```text
+//! pending -> running <-> awaiting-approval
+//!               |
+//!               +-> completed | failed | canceled
+//! ```
+//!
+//! When the engine is given a [`CheckpointStore`], each transition is
+//! published as `run.state.changed:v1` and a snapshot of the run (phase,
+//! definition, inputs and the step outputs recorded so far) is written to the
+//! `RITUAL_RUNS` KV bucket. The snapshot is rewritten after every step, so it
+//! is the checkpoint a restarted engine resumes from: steps with a recorded
+//! output are not executed again (see `Engine::recover_runs`).
+
+use anyhow::{bail, Context, Result};
+use async_nats::jetstream::{self, kv};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use tracing::warn;
+
+use super::definition::RitualDefinition;
+
+const KEY_PREFIX: &str = "run.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunPhase {
+    Pending,
+    Running,
+    AwaitingApproval,
+    Completed,
+    Failed,
+    Canceled,
+}
+
+impl RunPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunPhase::Pending => "pending",
+            RunPhase::Running => "running",
+            RunPhase::AwaitingApproval => "awaiting-approval",
+            RunPhase::Completed => "completed",
+            RunPhase::Failed => "failed",
+            RunPhase::Canceled => "canceled",
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            RunPhase::Completed | RunPhase::Failed | RunPhase::Canceled
+        )
+    }
+
+    /// Whether the lifecycle allows moving from `self` to `next`
+    pub fn can_transition_to(&self, next: RunPhase) -> bool {
+        use RunPhase::*;
+        matches!(
+            (self, next),
+            (Pending, Running | Failed | Canceled)
+                | (Running, AwaitingApproval | Completed | Failed | Canceled)
+                | (AwaitingApproval, Running | Failed | Canceled)
+        )
+    }
+}
+
+impl fmt::Display for RunPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Snapshot of a run as stored in the checkpoint bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunCheckpoint {
+    pub tenant_id: String,
+    pub ritual_id: String,
+    pub run_id: String,
+    pub phase: RunPhase,
+    pub definition: RitualDefinition,
+    /// Validated inputs the run was started with
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub inputs: Map<String, Value>,
+    /// Output per step id recorded so far
+    #[serde(default)]
+    pub outputs: Map<String, Value>,
+    /// Why a terminal run stopped early (`approval_denied`, `canceled`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Bumped on every write, so events and snapshots can be ordered
+    pub revision: u64,
+    /// How many times the run has been resumed after an engine restart
+    #[serde(default)]
+    pub recoveries: u32,
+}
+
+impl RunCheckpoint {
+    pub fn new(run_id: &str, tenant_id: &str, definition: &RitualDefinition) -> Self {
+        let now = Utc::now();
+        Self {
+            tenant_id: tenant_id.to_string(),
+            ritual_id: definition.id.clone(),
+            run_id: run_id.to_string(),
+            phase: RunPhase::Pending,
+            definition: definition.clone(),
+            inputs: Map::new(),
+            outputs: Map::new(),
+            reason: None,
+            started_at: now,
+            updated_at: now,
+            revision: 0,
+            recoveries: 0,
+        }
+    }
+
+    /// Move to `next`, returning the phase left behind
+    pub fn transition(&mut self, next: RunPhase) -> Result<RunPhase> {
+        if !self.phase.can_transition_to(next) {
+            bail!(
+                "run {} cannot move from {} to {}",
+                self.run_id,
+                self.phase,
+                next
+            );
+        }
+        let previous = self.phase;
+        self.phase = next;
+        Ok(previous)
+    }
+
+    /// Stamp the snapshot for another write
+    pub fn touch(&mut self) {
+        self.revision += 1;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Where run snapshots are persisted
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    /// Replace the snapshot of `checkpoint.run_id`
+    async fn save(&self, checkpoint: &RunCheckpoint) -> Result<()>;
+
+    async fn load(&self, run_id: &str) -> Result<Option<RunCheckpoint>>;
+
+    /// Every snapshot whose phase is not terminal
+    async fn in_flight(&self) -> Result<Vec<RunCheckpoint>>;
+}
+
+/// Snapshots in a JetStream KV bucket, one `run.<runId>` key per run
+pub struct KvCheckpointStore {
+    store: kv::Store,
+}
+
+impl KvCheckpointStore {
+    /// Open (or create) the bucket named by `RITUAL_RUN_STATE_BUCKET`,
+    /// default `RITUAL_RUNS`
+    pub async fn connect(nats_url: &str) -> Result<Self> {
+        let bucket =
+            std::env::var("RITUAL_RUN_STATE_BUCKET").unwrap_or_else(|_| "RITUAL_RUNS".to_string());
+        let client = async_nats::connect(nats_url)
+            .await
+            .context("Failed to connect to NATS")?;
+        let js = jetstream::new(client);
+        let store = match js.get_key_value(&bucket).await {
+            Ok(store) => store,
+            Err(_) => js
+                .create_key_value(kv::Config {
+                    bucket: bucket.clone(),
+                    description: "Ritual run checkpoints".to_string(),
+                    history: 1,
+                    ..Default::default()
+                })
+                .await
+                .with_context(|| format!("creating KV bucket {bucket}"))?,
+        };
+        Ok(Self { store })
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for KvCheckpointStore {
+    async fn save(&self, checkpoint: &RunCheckpoint) -> Result<()> {
+        let key = format!("{KEY_PREFIX}{}", checkpoint.run_id);
+        self.store
+            .put(&key, serde_json::to_vec(checkpoint)?.into())
+            .await
+            .with_context(|| format!("writing checkpoint {key}"))?;
+        Ok(())
+    }
+
+    async fn load(&self, run_id: &str) -> Result<Option<RunCheckpoint>> {
+        let key = format!("{KEY_PREFIX}{run_id}");
+        let Some(bytes) = self.store.get(&key).await? else {
+            return Ok(None);
+        };
+        let checkpoint =
+            serde_json::from_slice(&bytes).with_context(|| format!("parsing checkpoint {key}"))?;
+        Ok(Some(checkpoint))
+    }
+
+    async fn in_flight(&self) -> Result<Vec<RunCheckpoint>> {
+        let mut keys = self.store.keys().await.context("listing checkpoint keys")?;
+        let mut runs = Vec::new();
+        while let Some(key) = keys.try_next().await? {
+            let Some(bytes) = self.store.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_slice::<RunCheckpoint>(&bytes) {
+                Ok(checkpoint) if !checkpoint.phase.is_terminal() => runs.push(checkpoint),
+                Ok(_) => {}
+                Err(e) => warn!(%key, error = %e, "invalid run checkpoint; skipping"),
+            }
+        }
+        Ok(runs)
+    }
+}
+
+/// Process-local snapshots; useful for tests and single-shot CLI runs
+#[derive(Default)]
+pub struct MemoryCheckpointStore {
+    runs: Mutex<HashMap<String, RunCheckpoint>>,
+}
+
+impl MemoryCheckpointStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for MemoryCheckpointStore {
+    async fn save(&self, checkpoint: &RunCheckpoint) -> Result<()> {
+        self.runs
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(checkpoint.run_id.clone(), checkpoint.clone());
+        Ok(())
+    }
+
+    async fn load(&self, run_id: &str) -> Result<Option<RunCheckpoint>> {
+        Ok(self
+            .runs
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(run_id)
+            .cloned())
+    }
+
+    async fn in_flight(&self) -> Result<Vec<RunCheckpoint>> {
+        let mut runs: Vec<RunCheckpoint> = self
+            .runs
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .values()
+            .filter(|checkpoint| !checkpoint.phase.is_terminal())
+            .cloned()
+            .collect();
+        runs.sort_by_key(|checkpoint| checkpoint.started_at);
+        Ok(runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition() -> RitualDefinition {
+        RitualDefinition::from_yaml(
+            r#"
+id: release
+version: '1.0'
+steps:
+  - { id: build, type: capsule, capsule: echo, with: { message: hi } }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn lifecycle_allows_only_forward_transitions() {
+        let mut checkpoint = RunCheckpoint::new("run-1", "default", &definition());
+
+        assert_eq!(
+            checkpoint.transition(RunPhase::Running).unwrap(),
+            RunPhase::Pending
+        );
+        checkpoint.transition(RunPhase::AwaitingApproval).unwrap();
+        assert!(checkpoint.transition(RunPhase::Completed).is_err());
+        checkpoint.transition(RunPhase::Running).unwrap();
+        checkpoint.transition(RunPhase::Completed).unwrap();
+
+        assert!(checkpoint.phase.is_terminal());
+        assert!(checkpoint.transition(RunPhase::Running).is_err());
+    }
+
+    #[test]
+    fn checkpoint_round_trips_with_kebab_case_phase() {
+        let mut checkpoint = RunCheckpoint::new("run-1", "default", &definition());
+        checkpoint.phase = RunPhase::AwaitingApproval;
+
+        let json = serde_json::to_value(&checkpoint).unwrap();
+        assert_eq!(json["phase"], "awaiting-approval");
+        assert_eq!(json["runId"], "run-1");
+
+        let back: RunCheckpoint = serde_json::from_value(json).unwrap();
+        assert_eq!(back, checkpoint);
+    }
+
+    #[tokio::test]
+    async fn memory_store_lists_only_in_flight_runs() {
+        let store = MemoryCheckpointStore::new();
+        let mut running = RunCheckpoint::new("run-1", "default", &definition());
+        running.phase = RunPhase::Running;
+        let mut done = RunCheckpoint::new("run-2", "default", &definition());
+        done.phase = RunPhase::Completed;
+        store.save(&running).await.unwrap();
+        store.save(&done).await.unwrap();
+
+        let in_flight = store.in_flight().await.unwrap();
+
+        assert_eq!(in_flight.len(), 1);
+        assert_eq!(in_flight[0].run_id, "run-1");
+        assert!(store.load("run-2").await.unwrap().is_some());
+    }
+}
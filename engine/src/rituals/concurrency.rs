@@ -0,0 +1,318 @@
+//! Run-level concurrency groups
+//!
+//! A definition may name a concurrency group; at most one run per tenant and
+//! group executes at a time:
+//!
+//! ```yaml
+//! concurrency:
+//!   group: deploy-${{ inputs.environment }}
+//!   cancelInProgress: false   # true: cancel the run holding the group, then take it
+//!   onConflict: queue         # queue (default) | reject
+//! ```
+//!
+//! `concurrency: deploy-prod` is shorthand for a queued group. The group is
+//! held through a lease in the `RITUAL_CONCURRENCY` KV bucket (override with
+//! `RITUAL_CONCURRENCY_BUCKET`), keyed `<tenant>.<group>`. The running engine
+//! renews the lease while the run is in flight and releases it when the run
+//! ends; a lease left behind by a crashed engine expires after
+//! [`LEASE_TTL`], after which a queued run may take the group. A resumed run
+//! re-acquires its own lease.
+
+use anyhow::{bail, Context, Result};
+use async_nats::jetstream::{self, kv};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long a lease stays valid without being renewed
+pub const LEASE_TTL: Duration = Duration::from_secs(30);
+
+/// The run holding a concurrency group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupLease {
+    pub tenant_id: String,
+    pub group: String,
+    pub ritual_id: String,
+    pub run_id: String,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl GroupLease {
+    pub fn new(tenant_id: &str, group: &str, ritual_id: &str, run_id: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            tenant_id: tenant_id.to_string(),
+            group: group.to_string(),
+            ritual_id: ritual_id.to_string(),
+            run_id: run_id.to_string(),
+            acquired_at: now,
+            expires_at: now + LEASE_TTL,
+        }
+    }
+
+    /// Key in the KV bucket
+    pub fn key(&self) -> String {
+        format!("{}.{}", self.tenant_id, self.group)
+    }
+
+    /// Whether `run_id` may take the group from this lease at `now`
+    pub fn available_to(&self, run_id: &str, now: DateTime<Utc>) -> bool {
+        self.run_id == run_id || self.expires_at <= now
+    }
+
+    /// This lease with a fresh expiry
+    fn renewed(&self) -> Self {
+        Self {
+            expires_at: Utc::now() + LEASE_TTL,
+            ..self.clone()
+        }
+    }
+}
+
+/// Group names become part of a KV key, so they are limited to key-safe characters
+pub fn validate_group(group: &str) -> Result<()> {
+    let valid = !group.is_empty()
+        && !group.starts_with('.')
+        && !group.ends_with('.')
+        && !group.contains("..")
+        && group
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!(
+            "concurrency group '{}' must be non-empty and use only letters, digits, '-', '_' and '.'",
+            group
+        );
+    }
+    Ok(())
+}
+
+/// Where concurrency group leases are kept
+#[async_trait]
+pub trait ConcurrencyLocks: Send + Sync {
+    /// Take the group for `lease.run_id`, or return the live lease of the run
+    /// that holds it
+    async fn try_acquire(&self, lease: &GroupLease) -> Result<Option<GroupLease>>;
+
+    /// Extend the lease; `false` if the run no longer holds the group
+    async fn renew(&self, lease: &GroupLease) -> Result<bool>;
+
+    /// Give the group up if `lease.run_id` still holds it
+    async fn release(&self, lease: &GroupLease) -> Result<()>;
+}
+
+/// Leases in a JetStream KV bucket, written with compare-and-set
+pub struct KvConcurrencyLocks {
+    store: kv::Store,
+}
+
+impl KvConcurrencyLocks {
+    /// Open (or create) the bucket named by `RITUAL_CONCURRENCY_BUCKET`,
+    /// default `RITUAL_CONCURRENCY`
+    pub async fn connect(nats_url: &str) -> Result<Self> {
+        let bucket = std::env::var("RITUAL_CONCURRENCY_BUCKET")
+            .unwrap_or_else(|_| "RITUAL_CONCURRENCY".to_string());
+        let client = async_nats::connect(nats_url)
+            .await
+            .context("Failed to connect to NATS")?;
+        let js = jetstream::new(client);
+        let store = match js.get_key_value(&bucket).await {
+            Ok(store) => store,
+            Err(_) => js
+                .create_key_value(kv::Config {
+                    bucket: bucket.clone(),
+                    description: "Ritual concurrency group leases".to_string(),
+                    history: 1,
+                    ..Default::default()
+                })
+                .await
+                .with_context(|| format!("creating KV bucket {bucket}"))?,
+        };
+        Ok(Self { store })
+    }
+
+    /// The current lease and the revision to update it at
+    async fn current(&self, key: &str) -> Result<(Option<GroupLease>, Option<u64>)> {
+        match self.store.entry(key).await? {
+            Some(entry) if matches!(entry.operation, kv::Operation::Put) => Ok((
+                serde_json::from_slice(&entry.value).ok(),
+                Some(entry.revision),
+            )),
+            Some(entry) => Ok((None, Some(entry.revision))),
+            None => Ok((None, None)),
+        }
+    }
+}
+
+#[async_trait]
+impl ConcurrencyLocks for KvConcurrencyLocks {
+    async fn try_acquire(&self, lease: &GroupLease) -> Result<Option<GroupLease>> {
+        let key = lease.key();
+        // Compare-and-set; a lost race is retried against the new revision
+        for _ in 0..3 {
+            let (current, revision) = self.current(&key).await?;
+            if let Some(current) = current {
+                if !current.available_to(&lease.run_id, Utc::now()) {
+                    return Ok(Some(current));
+                }
+            }
+            let value = serde_json::to_vec(&lease.renewed())?;
+            let written = match revision {
+                Some(revision) => self
+                    .store
+                    .update(&key, value.into(), revision)
+                    .await
+                    .is_ok(),
+                None => self.store.update(&key, value.into(), 0).await.is_ok(),
+            };
+            if written {
+                return Ok(None);
+            }
+        }
+        bail!("concurrency group {key} is contended; could not write its lease")
+    }
+
+    async fn renew(&self, lease: &GroupLease) -> Result<bool> {
+        let key = lease.key();
+        let (current, revision) = self.current(&key).await?;
+        match (current, revision) {
+            (Some(current), Some(revision)) if current.run_id == lease.run_id => {
+                let value = serde_json::to_vec(&lease.renewed())?;
+                Ok(self
+                    .store
+                    .update(&key, value.into(), revision)
+                    .await
+                    .is_ok())
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release(&self, lease: &GroupLease) -> Result<()> {
+        let key = lease.key();
+        let (current, revision) = self.current(&key).await?;
+        if let (Some(current), Some(revision)) = (current, revision) {
+            if current.run_id == lease.run_id {
+                // An expired lease frees the group without racing a new holder
+                let released = GroupLease {
+                    expires_at: Utc::now(),
+                    ..current
+                };
+                self.store
+                    .update(&key, serde_json::to_vec(&released)?.into(), revision)
+                    .await
+                    .with_context(|| format!("releasing concurrency group {key}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Process-local leases; useful for tests and sandboxed runs
+#[derive(Default)]
+pub struct MemoryConcurrencyLocks {
+    leases: Mutex<HashMap<String, GroupLease>>,
+}
+
+impl MemoryConcurrencyLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ConcurrencyLocks for MemoryConcurrencyLocks {
+    async fn try_acquire(&self, lease: &GroupLease) -> Result<Option<GroupLease>> {
+        let mut leases = self.leases.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(current) = leases.get(&lease.key()) {
+            if !current.available_to(&lease.run_id, Utc::now()) {
+                return Ok(Some(current.clone()));
+            }
+        }
+        leases.insert(lease.key(), lease.renewed());
+        Ok(None)
+    }
+
+    async fn renew(&self, lease: &GroupLease) -> Result<bool> {
+        let mut leases = self.leases.lock().unwrap_or_else(|p| p.into_inner());
+        match leases.get_mut(&lease.key()) {
+            Some(current) if current.run_id == lease.run_id => {
+                *current = lease.renewed();
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn release(&self, lease: &GroupLease) -> Result<()> {
+        let mut leases = self.leases.lock().unwrap_or_else(|p| p.into_inner());
+        if leases
+            .get(&lease.key())
+            .is_some_and(|current| current.run_id == lease.run_id)
+        {
+            leases.remove(&lease.key());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn memory_locks_admit_one_run_per_group() {
+        let locks = MemoryConcurrencyLocks::new();
+        let first = GroupLease::new("acme", "deploy-prod", "deploy", "run-1");
+        let second = GroupLease::new("acme", "deploy-prod", "deploy", "run-2");
+        let other_tenant = GroupLease::new("globex", "deploy-prod", "deploy", "run-3");
+
+        assert_eq!(locks.try_acquire(&first).await.unwrap(), None);
+        let holder = locks.try_acquire(&second).await.unwrap().unwrap();
+        assert_eq!(holder.run_id, "run-1");
+        assert_eq!(locks.try_acquire(&other_tenant).await.unwrap(), None);
+        // The holder may re-acquire, e.g. after a restart
+        assert_eq!(locks.try_acquire(&first).await.unwrap(), None);
+
+        assert!(!locks.renew(&second).await.unwrap());
+        locks.release(&second).await.unwrap();
+        assert!(locks.try_acquire(&second).await.unwrap().is_some());
+
+        locks.release(&first).await.unwrap();
+        assert_eq!(locks.try_acquire(&second).await.unwrap(), None);
+    }
+
+    #[test]
+    fn expired_lease_is_available_to_anyone() {
+        let mut lease = GroupLease::new("acme", "deploy", "deploy", "run-1");
+        let now = Utc::now();
+        assert!(lease.available_to("run-1", now));
+        assert!(!lease.available_to("run-2", now));
+        lease.expires_at = now;
+        assert!(lease.available_to("run-2", now));
+        assert_eq!(lease.key(), "acme.deploy");
+    }
+
+    #[test]
+    fn group_names_must_be_key_safe() {
+        for ok in ["deploy-prod", "deploy.eu_west", "a"] {
+            assert!(validate_group(ok).is_ok(), "{ok}");
+        }
+        for bad in [
+            "",
+            "deploy prod",
+            "deploy/prod",
+            ".x",
+            "x.",
+            "a..b",
+            "deploy*",
+        ] {
+            assert!(validate_group(bad).is_err(), "{bad}");
+        }
+    }
+}
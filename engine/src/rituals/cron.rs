@@ -0,0 +1,293 @@
+//! Five-field cron expressions for scheduled ritual triggers
+//!
+//! Supports `minute hour day-of-month month day-of-week` with `*`, lists
+//! (`1,15`), ranges (`9-17`), steps (`*/5`, `10-40/10`), month and weekday
+//! names (`jan`, `mon`), and the `@hourly`/`@daily`/`@weekly`/`@monthly`/
+//! `@yearly` shorthands. All times are UTC. As in classic cron, when both
+//! day-of-month and day-of-week are restricted a day matches if either does.
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// How far ahead `next_after` searches before giving up (e.g. `0 0 30 2 *`)
+const SEARCH_LIMIT_DAYS: i64 = 366 * 5;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            bail!(
+                "cron expression '{}' must have 5 fields (minute hour day month weekday), found {}",
+                expression,
+                fields.len()
+            );
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, &WEEKDAYS)
+            .map_err(|e| anyhow!("cron '{}' day-of-week: {}", expression, e))?;
+        // 7 is an alias for Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: parse_field(fields[0], 0, 59, &[])
+                .map_err(|e| anyhow!("cron '{}' minute: {}", expression, e))?,
+            hours: parse_field(fields[1], 0, 23, &[])
+                .map_err(|e| anyhow!("cron '{}' hour: {}", expression, e))?,
+            days_of_month: parse_field(fields[2], 1, 31, &[])
+                .map_err(|e| anyhow!("cron '{}' day-of-month: {}", expression, e))?,
+            months: parse_field(fields[3], 1, 12, &MONTHS)
+                .map_err(|e| anyhow!("cron '{}' month: {}", expression, e))?,
+            days_of_week,
+            dom_restricted: !is_wildcard(fields[2]),
+            dow_restricted: !is_wildcard(fields[4]),
+        })
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// First matching minute strictly after `after`
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(SEARCH_LIMIT_DAYS);
+        let mut t = start;
+
+        while t < limit {
+            if !has(self.months, t.month()) {
+                t = first_of_next_month(t)?;
+                continue;
+            }
+            if !self.day_matches(t) {
+                t = Utc
+                    .with_ymd_and_hms(t.year(), t.month(), t.day(), 0, 0, 0)
+                    .single()?
+                    + Duration::days(1);
+                continue;
+            }
+            if !has(self.hours, t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !has(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+                continue;
+            }
+            return Some(t);
+        }
+        None
+    }
+
+    fn day_matches(&self, t: DateTime<Utc>) -> bool {
+        let dom = has(self.days_of_month, t.day());
+        let dow = has(self.days_of_week, t.weekday().num_days_from_sunday());
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+fn has(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn is_wildcard(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+fn first_of_next_month(t: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let (year, month) = if t.month() == 12 {
+        (t.year() + 1, 1)
+    } else {
+        (t.year(), t.month() + 1)
+    };
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()
+}
+
+/// Parse one comma-separated field into a bitmask of allowed values
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| anyhow!("invalid step '{}'", step))?;
+                if step == 0 {
+                    bail!("step must be greater than 0");
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (lo, hi) = if is_wildcard(range) {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (
+                parse_value(lo, min, max, names)?,
+                parse_value(hi, min, max, names)?,
+            )
+        } else {
+            let value = parse_value(range, min, max, names)?;
+            // `5/15` means "from 5 through the end of the range every 15"
+            (value, if step > 1 { max } else { value })
+        };
+        if lo > hi {
+            bail!("range '{}' is reversed", range);
+        }
+
+        let mut v = lo;
+        while v <= hi {
+            mask |= 1 << v;
+            v += step;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(raw: &str, min: u32, max: u32, names: &[&str]) -> Result<u32> {
+    let lower = raw.to_ascii_lowercase();
+    let value = match names.iter().position(|n| *n == lower) {
+        // Month names start at 1, weekday names at 0
+        Some(index) => index as u32 + min,
+        None => raw
+            .parse()
+            .map_err(|_| anyhow!("invalid value '{}'", raw))?,
+    };
+    if value < min || value > max {
+        bail!("value {} outside {}-{}", value, min, max);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn every_five_minutes() {
+        let cron = CronSchedule::parse("*/5 * * * *").unwrap();
+        assert_eq!(
+            cron.next_after(at("2025-01-01T10:02:30Z")),
+            Some(at("2025-01-01T10:05:00Z"))
+        );
+        assert_eq!(
+            cron.next_after(at("2025-01-01T10:05:00Z")),
+            Some(at("2025-01-01T10:10:00Z"))
+        );
+    }
+
+    #[test]
+    fn weekday_business_hours_rolls_over_weekend() {
+        let cron = CronSchedule::parse("0 9-17 * * mon-fri").unwrap();
+        // Friday 2025-01-03 after 17:00 -> Monday 09:00
+        assert_eq!(
+            cron.next_after(at("2025-01-03T17:30:00Z")),
+            Some(at("2025-01-06T09:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn month_names_and_year_rollover() {
+        let cron = CronSchedule::parse("30 2 1 jan,jul *").unwrap();
+        assert_eq!(
+            cron.next_after(at("2025-07-01T02:30:00Z")),
+            Some(at("2026-01-01T02:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn day_of_month_or_day_of_week() {
+        let cron = CronSchedule::parse("0 0 15 * sun").unwrap();
+        // Wed 2025-01-01 -> Sun 2025-01-05 comes before the 15th
+        assert_eq!(
+            cron.next_after(at("2025-01-01T00:00:00Z")),
+            Some(at("2025-01-05T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn sunday_alias_and_shorthands() {
+        assert_eq!(
+            CronSchedule::parse("0 0 * * 7").unwrap().days_of_week,
+            CronSchedule::parse("0 0 * * 0").unwrap().days_of_week
+        );
+        assert_eq!(
+            CronSchedule::parse("@daily")
+                .unwrap()
+                .next_after(at("2025-01-01T12:00:00Z")),
+            Some(at("2025-01-02T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn impossible_dates_return_none() {
+        let cron = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(cron.next_after(at("2025-01-01T00:00:00Z")), None);
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        for bad in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "0 0 * foo *",
+        ] {
+            assert!(CronSchedule::parse(bad).is_err(), "{bad} should fail");
+        }
+    }
+}
@@ -0,0 +1,715 @@
+//! Declarative ritual definitions built from typed steps
+//!
+//! A definition is a list of steps, each one of: a capsule invocation, an
+//! approval gate, a timer, a condition over an earlier step's output, or a
+//! parallel block. Definitions are parsed from YAML and validated against
+//! `contracts/schemas/ritual.definition.v1.json` before any step runs.
+//!
+//! Parallel blocks run at most `maxConcurrency` branches at once and complete
+//! per `join`: `all` (default), `any`, or `{ quorum: <n> }`.
+//!
+//! Capsule and approval steps may declare a `retry` policy and an `onFailure`
+//! handler: `abort` (default) halts the run, `continue` records the error and
+//! moves on, and `{ compensate: <id> }` runs a step from the top-level
+//! `compensations` list before halting.
+//!
+//! Capsule, approval and timer steps may set `timeoutSeconds` to bound each
+//! attempt, and the definition's own `timeoutSeconds` is a deadline for the
+//! whole run.
+//!
+//! Capsule `with` arguments and approval `reason`s may reference earlier step
+//! outputs with `${{ steps.<id>.<path> }}` expressions (see
+//! [`super::expressions`]); references to unknown or later steps are rejected
+//! here, and type errors are raised before the dependent step runs.
+//!
+//! `inputs` is a JSON Schema for the values a run is started with (see
+//! [`super::inputs`]); steps read them with `${{ inputs.<name> }}`, and
+//! references to undeclared inputs are rejected here.
+//!
+//! `concurrency` names a group of runs that may not execute at the same time
+//! (see [`super::concurrency`]). The group may interpolate
+//! `${{ inputs.<name> }}`, and `cancelInProgress` cannot be combined with
+//! `onConflict: reject`.
+//!
+//! `triggers` start the ritual when a matching event arrives on a JetStream
+//! subject; `match` maps JSON Pointers into the event to required values.
+//!
+//! ```yaml
+//! id: release
+//! version: '1.0'
+//! timeoutSeconds: 7200
+//! concurrency: release-${{ inputs.environment }}
+//! inputs:
+//!   type: object
+//!   required: [environment]
+//!   properties:
+//!     environment: { type: string, enum: [staging, prod] }
+//! steps:
+//!   - id: build
+//!     type: capsule
+//!     capsule: echo
+//!     with: { message: "building" }
+//!     retry: { maxAttempts: 3, backoff: { strategy: exponential, delay: 2s } }
+//!     timeoutSeconds: 300
+//!     onFailure: { compensate: cleanup }
+//!   - id: sign-off
+//!     type: approval
+//!     gate: deploy
+//!     ttlSeconds: 3600
+//!   - id: healthy
+//!     type: condition
+//!     when: { step: build, path: /result/success, equals: true }
+//!     then:
+//!       - id: announce
+//!         type: capsule
+//!         capsule: echo
+//!         with: { message: "shipped ${{ steps.build.result.data.echoed_message }} to ${{ inputs.environment }}" }
+//! compensations:
+//!   - { id: cleanup, type: capsule, capsule: echo, with: { message: "cleaning up" } }
+//! triggers:
+//!   - id: prod-tag
+//!     subject: demon.graph.v1.*.*.*.commit
+//!     event: graph.tag.updated:v1
+//!     match: { /tag: prod }
+//! ```
+
+use anyhow::{bail, Context, Result};
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use super::expressions::{self, Root};
+use super::{concurrency, inputs};
+
+static DEFINITION_SCHEMA: OnceLock<JSONSchema> = OnceLock::new();
+
+fn schema() -> &'static JSONSchema {
+    DEFINITION_SCHEMA.get_or_init(|| {
+        let schema_str = include_str!("../../../contracts/schemas/ritual.definition.v1.json");
+        let schema_json: Value = serde_json::from_str(schema_str)
+            .expect("contracts/schemas/ritual.definition.v1.json must be valid JSON");
+        JSONSchema::compile(&schema_json).expect("ritual definition schema must compile")
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RitualDefinition {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+    /// JSON Schema for the values a run is started with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<Value>,
+    pub steps: Vec<Step>,
+    /// Steps that only run as the `compensate` target of a failed step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compensations: Vec<Step>,
+    /// Events that start a new run of this ritual
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<EventTrigger>,
+    /// Deadline for the whole run, measured from its start
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+    /// Group of runs that may not execute at the same time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<Concurrency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub id: String,
+    #[serde(flatten)]
+    pub kind: StepKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    #[serde(default, rename = "onFailure")]
+    pub on_failure: OnFailure,
+    /// Limit for each attempt of a capsule, approval or timer step
+    #[serde(
+        default,
+        rename = "timeoutSeconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StepKind {
+    /// Invoke a capsule with arguments
+    Capsule {
+        capsule: String,
+        #[serde(default, rename = "with")]
+        args: Value,
+    },
+    /// Request approval and wait for the gate to be granted or denied
+    #[serde(rename_all = "camelCase")]
+    Approval {
+        gate: String,
+        #[serde(default)]
+        reason: Option<String>,
+        #[serde(default)]
+        ttl_seconds: Option<u64>,
+    },
+    /// Pause the run for a fixed delay (humantime format, e.g. `30s`)
+    Timer { delay: String },
+    /// Run `then` when the predicate holds, otherwise `else`
+    Condition {
+        when: Predicate,
+        then: Vec<Step>,
+        #[serde(default, rename = "else")]
+        otherwise: Vec<Step>,
+    },
+    /// Run child steps concurrently and continue once `join` is satisfied
+    #[serde(rename_all = "camelCase")]
+    Parallel {
+        steps: Vec<Step>,
+        /// Branches in flight at once; defaults to the engine's limit
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_concurrency: Option<usize>,
+        #[serde(default)]
+        join: Join,
+    },
+}
+
+/// How many parallel branches must succeed before the block completes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Join {
+    /// Every branch; the first halted branch halts the block
+    #[default]
+    All,
+    /// The first branch to succeed; the rest are cancelled
+    Any,
+    /// `n` branches; the rest are cancelled once `n` have succeeded
+    Quorum(usize),
+}
+
+impl Join {
+    /// Successful branches needed out of `branches`
+    pub fn required(&self, branches: usize) -> usize {
+        match self {
+            Join::All => branches,
+            Join::Any => 1.min(branches),
+            Join::Quorum(n) => *n,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Join::All => "all",
+            Join::Any => "any",
+            Join::Quorum(_) => "quorum",
+        }
+    }
+}
+
+impl StepKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepKind::Capsule { .. } => "capsule",
+            StepKind::Approval { .. } => "approval",
+            StepKind::Timer { .. } => "timer",
+            StepKind::Condition { .. } => "condition",
+            StepKind::Parallel { .. } => "parallel",
+        }
+    }
+}
+
+/// How often a failed step is re-attempted and how long to wait in between
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one
+    pub max_attempts: u32,
+    #[serde(default)]
+    pub backoff: Backoff,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backoff {
+    #[serde(default)]
+    pub strategy: BackoffStrategy,
+    /// Wait before the first retry (humantime format)
+    #[serde(default = "default_backoff_delay")]
+    pub delay: String,
+    /// Upper bound for exponential backoff
+    #[serde(default)]
+    pub max_delay: Option<String>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            strategy: BackoffStrategy::default(),
+            delay: default_backoff_delay(),
+            max_delay: None,
+        }
+    }
+}
+
+fn default_backoff_delay() -> String {
+    "1s".to_string()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackoffStrategy {
+    #[default]
+    Fixed,
+    Exponential,
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `retry` (1-based)
+    pub fn delay_for(&self, retry: u32) -> Result<Duration> {
+        let base = parse_delay(&self.backoff.delay)?;
+        let delay = match self.backoff.strategy {
+            BackoffStrategy::Fixed => base,
+            BackoffStrategy::Exponential => {
+                base.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            }
+        };
+        match &self.backoff.max_delay {
+            Some(max) => Ok(delay.min(parse_delay(max)?)),
+            None => Ok(delay),
+        }
+    }
+}
+
+/// What happens once a step has exhausted its attempts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnFailure {
+    /// Halt the run
+    #[default]
+    Abort,
+    /// Record the error as the step output and run the next step
+    Continue,
+    /// Run the named compensation step, then halt the run
+    Compensate(String),
+}
+
+/// Test over an earlier step's output, addressed by JSON Pointer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Predicate {
+    pub step: String,
+    #[serde(default)]
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_equals: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+}
+
+impl Predicate {
+    /// Evaluate against the referenced step's output (`None` if it never ran)
+    pub fn evaluate(&self, output: Option<&Value>) -> bool {
+        let target = output.and_then(|o| o.pointer(&self.path));
+        if let Some(expected) = &self.equals {
+            return target == Some(expected);
+        }
+        if let Some(unexpected) = &self.not_equals {
+            return target != Some(unexpected);
+        }
+        target.is_some() == self.exists.unwrap_or(true)
+    }
+}
+
+/// Start the ritual when a matching event arrives on a JetStream subject
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventTrigger {
+    pub id: String,
+    /// NATS subject filter; wildcards allowed
+    pub subject: String,
+    /// Required value of the payload's `event` field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    /// JSON Pointer to required value; every entry must match
+    #[serde(default, rename = "match", skip_serializing_if = "BTreeMap::is_empty")]
+    pub filter: BTreeMap<String, Value>,
+    /// Input passed to the triggered run
+    #[serde(default)]
+    pub parameters: Value,
+}
+
+impl EventTrigger {
+    /// Whether an event payload should start a run
+    pub fn matches(&self, payload: &Value) -> bool {
+        if let Some(expected) = &self.event {
+            if payload.get("event").and_then(|v| v.as_str()) != Some(expected.as_str()) {
+                return false;
+            }
+        }
+        self.filter
+            .iter()
+            .all(|(path, expected)| payload.pointer(path) == Some(expected))
+    }
+}
+
+/// At most one run per tenant and `group` executes at a time (see
+/// [`super::concurrency`]); `concurrency: <group>` is shorthand for a queued group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", from = "ConcurrencyDoc")]
+pub struct Concurrency {
+    /// Group name; may interpolate `${{ inputs.* }}`
+    pub group: String,
+    /// Cancel the run holding the group instead of waiting for it to finish
+    pub cancel_in_progress: bool,
+    pub on_conflict: OnConflict,
+}
+
+/// What a run does when another run holds its concurrency group
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    /// Wait until the group is free
+    #[default]
+    Queue,
+    /// Halt immediately with `concurrency_rejected`
+    Reject,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ConcurrencyDoc {
+    Group(String),
+    #[serde(rename_all = "camelCase")]
+    Full {
+        group: String,
+        #[serde(default)]
+        cancel_in_progress: bool,
+        #[serde(default)]
+        on_conflict: OnConflict,
+    },
+}
+
+impl From<ConcurrencyDoc> for Concurrency {
+    fn from(doc: ConcurrencyDoc) -> Self {
+        match doc {
+            ConcurrencyDoc::Group(group) => Self {
+                group,
+                cancel_in_progress: false,
+                on_conflict: OnConflict::Queue,
+            },
+            ConcurrencyDoc::Full {
+                group,
+                cancel_in_progress,
+                on_conflict,
+            } => Self {
+                group,
+                cancel_in_progress,
+                on_conflict,
+            },
+        }
+    }
+}
+
+impl Concurrency {
+    /// The group name for a run started with `inputs`
+    pub fn resolve_group(&self, inputs: &Map<String, Value>) -> Result<String> {
+        let group = expressions::interpolate(&self.group, &|root: &Root| match root {
+            Root::Inputs => Some(Value::Object(inputs.clone())),
+            Root::Step(_) => None,
+        })
+        .context("resolving concurrency group")?;
+        concurrency::validate_group(&group)?;
+        Ok(group)
+    }
+
+    fn validate(&self, inputs: Option<&Value>) -> Result<()> {
+        if self.cancel_in_progress && self.on_conflict == OnConflict::Reject {
+            bail!("concurrency: cancelInProgress cannot be combined with onConflict: reject");
+        }
+        let template = Value::String(self.group.clone());
+        let references = expressions::references(&template).context("concurrency group")?;
+        if let Some(reference) = references.iter().find(|r| r.step().is_some()) {
+            bail!(
+                "concurrency group may only reference inputs, found '${{{{ {} }}}}'",
+                reference
+            );
+        }
+        if references.is_empty() {
+            concurrency::validate_group(&self.group)?;
+        }
+        check_input_references("concurrency group", &references, inputs)
+    }
+}
+
+impl RitualDefinition {
+    /// Whether a parsed ritual document uses the typed-step format
+    pub fn is_definition(doc: &Value) -> bool {
+        doc.get("steps").is_some() && doc.get("states").is_none()
+    }
+
+    /// Parse and validate a YAML ritual definition
+    pub fn from_yaml(text: &str) -> Result<Self> {
+        let doc: Value = serde_yaml::from_str(text).context("parsing ritual yaml")?;
+        Self::from_value(doc)
+    }
+
+    /// Validate a ritual document against the definition schema and step rules
+    pub fn from_value(doc: Value) -> Result<Self> {
+        if let Err(errors) = schema().validate(&doc) {
+            let details: Vec<String> = errors
+                .map(|e| format!("{}: {}", e.instance_path, e))
+                .collect();
+            bail!("invalid ritual definition:\n  {}", details.join("\n  "));
+        }
+        let definition: Self = serde_json::from_value(doc).context("decoding ritual definition")?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Checks the schema cannot express: unique step and trigger ids, resolvable
+    /// condition, compensation and input references, and parseable delays
+    pub fn validate(&self) -> Result<()> {
+        if let Some(schema) = &self.inputs {
+            inputs::compile(schema)?;
+        }
+        let mut seen = HashSet::new();
+        validate_steps(&self.steps, &mut seen)?;
+        let main_flow = seen.clone();
+        for step in &self.compensations {
+            if !seen.insert(step.id.clone()) {
+                bail!("duplicate step id '{}'", step.id);
+            }
+            validate_failure_handling(step)?;
+            // Compensations run after a failure, so any main-flow step may be referenced
+            validate_references(step, &main_flow)?;
+            if let OnFailure::Compensate(_) = step.on_failure {
+                bail!("compensation step '{}' cannot itself compensate", step.id);
+            }
+        }
+        for step in self.all_steps() {
+            if let OnFailure::Compensate(target) = &step.on_failure {
+                if self.compensation(target).is_none() {
+                    bail!(
+                        "step '{}' compensates with unknown step '{}'",
+                        step.id,
+                        target
+                    );
+                }
+            }
+        }
+        for step in self.all_steps().into_iter().chain(&self.compensations) {
+            let references = step_references(step)?;
+            check_input_references(
+                &format!("step '{}'", step.id),
+                &references,
+                self.inputs.as_ref(),
+            )?;
+        }
+        if let Some(concurrency) = &self.concurrency {
+            concurrency.validate(self.inputs.as_ref())?;
+        }
+        let mut trigger_ids = HashSet::new();
+        for trigger in &self.triggers {
+            if !trigger_ids.insert(trigger.id.as_str()) {
+                bail!("duplicate trigger id '{}'", trigger.id);
+            }
+            if let Some(path) = trigger.filter.keys().find(|p| !p.starts_with('/')) {
+                bail!(
+                    "trigger '{}': match key '{}' must be a JSON Pointer starting with '/'",
+                    trigger.id,
+                    path
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up a step from the `compensations` list
+    pub fn compensation(&self, id: &str) -> Option<&Step> {
+        self.compensations.iter().find(|s| s.id == id)
+    }
+
+    /// Every step in definition order, including nested ones
+    pub fn all_steps(&self) -> Vec<&Step> {
+        fn collect<'a>(steps: &'a [Step], out: &mut Vec<&'a Step>) {
+            for step in steps {
+                out.push(step);
+                match &step.kind {
+                    StepKind::Condition {
+                        then, otherwise, ..
+                    } => {
+                        collect(then, out);
+                        collect(otherwise, out);
+                    }
+                    StepKind::Parallel { steps, .. } => collect(steps, out),
+                    _ => {}
+                }
+            }
+        }
+        let mut out = Vec::new();
+        collect(&self.steps, &mut out);
+        out
+    }
+}
+
+/// Parse a timer delay such as `30s` or `1h 30m`
+pub fn parse_delay(delay: &str) -> Result<Duration> {
+    humantime::parse_duration(delay).with_context(|| format!("invalid timer delay '{}'", delay))
+}
+
+fn validate_steps(steps: &[Step], seen: &mut HashSet<String>) -> Result<()> {
+    for step in steps {
+        if !seen.insert(step.id.clone()) {
+            bail!("duplicate step id '{}'", step.id);
+        }
+        validate_failure_handling(step)?;
+        validate_references(step, seen)?;
+        match &step.kind {
+            StepKind::Timer { delay } => {
+                parse_delay(delay).with_context(|| format!("step '{}'", step.id))?;
+            }
+            StepKind::Condition {
+                when,
+                then,
+                otherwise,
+            } => {
+                // Only steps that ran before the condition can be tested
+                if !seen.contains(&when.step) || when.step == step.id {
+                    bail!(
+                        "step '{}' tests unknown or later step '{}'",
+                        step.id,
+                        when.step
+                    );
+                }
+                validate_steps(then, seen)?;
+                validate_steps(otherwise, seen)?;
+            }
+            StepKind::Parallel {
+                steps,
+                max_concurrency,
+                join,
+            } => {
+                if *max_concurrency == Some(0) {
+                    bail!("step '{}': maxConcurrency must be at least 1", step.id);
+                }
+                if let Join::Quorum(n) = join {
+                    if *n == 0 || *n > steps.len() {
+                        bail!(
+                            "step '{}': quorum {} must be between 1 and {} branches",
+                            step.id,
+                            n,
+                            steps.len()
+                        );
+                    }
+                }
+                // Branches run concurrently, so each one only sees the steps
+                // before the block and its own earlier steps
+                let before = seen.clone();
+                for branch in steps {
+                    let mut scope = before.clone();
+                    validate_steps(std::slice::from_ref(branch), &mut scope)?;
+                    for id in scope.difference(&before) {
+                        if !seen.insert(id.clone()) {
+                            bail!("duplicate step id '{}'", id);
+                        }
+                    }
+                }
+            }
+            StepKind::Capsule { .. } | StepKind::Approval { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+fn step_references(step: &Step) -> Result<Vec<expressions::Reference>> {
+    match &step.kind {
+        StepKind::Capsule { args, .. } => expressions::references(args),
+        StepKind::Approval {
+            reason: Some(reason),
+            ..
+        } => expressions::references(&Value::String(reason.clone())),
+        _ => Ok(Vec::new()),
+    }
+    .with_context(|| format!("step '{}'", step.id))
+}
+
+/// Expressions must parse and may only reference steps that have already run
+fn validate_references(step: &Step, available: &HashSet<String>) -> Result<()> {
+    for reference in step_references(step)? {
+        let Some(target) = reference.step() else {
+            continue;
+        };
+        if !available.contains(target) || target == step.id {
+            bail!(
+                "step '{}' references unknown or later step '{}' in '${{{{ {} }}}}'",
+                step.id,
+                target,
+                reference
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `${{ inputs.* }}` expressions may only read inputs the definition declares
+fn check_input_references(
+    owner: &str,
+    references: &[expressions::Reference],
+    schema: Option<&Value>,
+) -> Result<()> {
+    for reference in references {
+        if reference.step().is_some() {
+            continue;
+        }
+        let Some(schema) = schema else {
+            bail!(
+                "{} references '${{{{ {} }}}}' but the ritual declares no inputs",
+                owner,
+                reference
+            );
+        };
+        let declared = inputs::declared(schema);
+        if let Some(name) = reference.input() {
+            if !declared.contains(&name) {
+                bail!(
+                    "{} references undeclared input '{}' in '${{{{ {} }}}}'",
+                    owner,
+                    name,
+                    reference
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_failure_handling(step: &Step) -> Result<()> {
+    let leaf = matches!(
+        step.kind,
+        StepKind::Capsule { .. } | StepKind::Approval { .. }
+    );
+    if !leaf && (step.retry.is_some() || step.on_failure != OnFailure::Abort) {
+        bail!(
+            "step '{}': retry and onFailure apply to capsule and approval steps only",
+            step.id
+        );
+    }
+    if let Some(retry) = &step.retry {
+        if retry.max_attempts == 0 {
+            bail!("step '{}': retry.maxAttempts must be at least 1", step.id);
+        }
+        retry
+            .delay_for(1)
+            .with_context(|| format!("step '{}' retry backoff", step.id))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,436 @@
+//! Dead-letter queue for messages that keep failing
+//!
+//! JetStream redelivers a nacked message forever, so one poison message (a
+//! malformed event, a handler that fails the same way every time) is retried
+//! endlessly and delays everything behind it. Consumers hand failures to a
+//! [`DeadLetterQueue`] instead: the message is nacked for redelivery until it
+//! has been delivered `RITUAL_DLQ_MAX_DELIVERIES` times, then republished to
+//! the `RITUAL_DLQ` stream with its failure metadata in `Demon-Dlq-*` headers
+//! and terminated on the source consumer.
+//!
+//! Dead letters are listed with [`list`] and put back on their original
+//! subject with [`requeue`] (`demonctl runs dlq list|requeue`).
+
+use anyhow::{bail, Context, Result};
+use async_nats::jetstream::{self, consumer::DeliverPolicy, AckKind, Message};
+use async_nats::HeaderMap;
+use chrono::Utc;
+use futures_util::StreamExt;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{error, warn};
+
+pub const DLQ_STREAM: &str = "RITUAL_DLQ";
+const DLQ_SUBJECTS: &str = "demon.ritual.dlq.v1.>";
+
+/// Delay before a failed message is redelivered
+const REDELIVERY_DELAY: Duration = Duration::from_millis(500);
+
+const CONSUMER_HEADER: &str = "Demon-Dlq-Consumer";
+const REASON_HEADER: &str = "Demon-Dlq-Reason";
+const DELIVERIES_HEADER: &str = "Demon-Dlq-Deliveries";
+const FAILED_AT_HEADER: &str = "Demon-Dlq-Failed-At";
+const SOURCE_STREAM_HEADER: &str = "Demon-Dlq-Source-Stream";
+const SOURCE_SUBJECT_HEADER: &str = "Demon-Dlq-Source-Subject";
+const SOURCE_SEQUENCE_HEADER: &str = "Demon-Dlq-Source-Sequence";
+const SOURCE_MSG_ID_HEADER: &str = "Demon-Dlq-Source-Msg-Id";
+
+static DEAD_LETTERED: AtomicU64 = AtomicU64::new(0);
+
+/// Messages moved to the DLQ since start
+pub fn dead_lettered() -> u64 {
+    DEAD_LETTERED.load(Ordering::Relaxed)
+}
+
+#[derive(Clone, Debug)]
+pub struct DlqPolicy {
+    /// Deliveries after which a failing message is dead-lettered
+    pub max_deliveries: u64, // default: 5
+}
+
+impl Default for DlqPolicy {
+    fn default() -> Self {
+        Self {
+            max_deliveries: std::env::var("RITUAL_DLQ_MAX_DELIVERIES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5u64)
+                .max(1),
+        }
+    }
+}
+
+impl DlqPolicy {
+    /// Whether a message that failed on its `delivered`-th delivery is out of retries
+    pub fn exhausted(&self, delivered: u64) -> bool {
+        delivered >= self.max_deliveries
+    }
+}
+
+/// DLQ subject for messages given up on by `consumer`
+pub fn subject_for(consumer: &str) -> String {
+    format!("demon.ritual.dlq.v1.{}", consumer)
+}
+
+/// Create the `RITUAL_DLQ` stream if it does not exist yet
+pub async fn ensure_stream(js: &jetstream::Context) -> Result<jetstream::stream::Stream> {
+    js.get_or_create_stream(jetstream::stream::Config {
+        name: DLQ_STREAM.to_string(),
+        subjects: vec![DLQ_SUBJECTS.to_string()],
+        storage: jetstream::stream::StorageType::File,
+        duplicate_window: Duration::from_secs(120),
+        ..Default::default()
+    })
+    .await
+    .with_context(|| format!("Failed to create/get stream '{}'", DLQ_STREAM))
+}
+
+/// Failure handling for one durable consumer
+#[derive(Clone, Debug)]
+pub struct DeadLetterQueue {
+    js: jetstream::Context,
+    consumer: String,
+    policy: DlqPolicy,
+}
+
+impl DeadLetterQueue {
+    pub fn new(js: jetstream::Context, consumer: &str, policy: DlqPolicy) -> Self {
+        Self {
+            js,
+            consumer: consumer.to_string(),
+            policy,
+        }
+    }
+
+    /// Nack `msg` for redelivery, or dead-letter it once it is out of retries
+    pub async fn retry_or_dead_letter(&self, msg: &Message, reason: &str) {
+        let delivered = msg.info().map(|i| i.delivered.max(0) as u64).unwrap_or(0);
+        if self.policy.exhausted(delivered) {
+            self.dead_letter(msg, reason).await;
+        } else {
+            let _ = msg.ack_with(AckKind::Nak(Some(REDELIVERY_DELAY))).await;
+        }
+    }
+
+    /// Dead-letter `msg` now; for failures no redelivery can fix, such as a
+    /// payload that is not valid JSON. If the DLQ cannot be written the
+    /// message is nacked instead so it is not lost.
+    pub async fn dead_letter(&self, msg: &Message, reason: &str) {
+        let subject = msg.message.subject.to_string();
+        match self.publish(msg, reason).await {
+            Ok(()) => {
+                DEAD_LETTERED.fetch_add(1, Ordering::Relaxed);
+                warn!(consumer=%self.consumer, %subject, %reason, "dlq: message dead-lettered");
+                let _ = msg.ack_with(AckKind::Term).await;
+            }
+            Err(e) => {
+                error!(consumer=%self.consumer, %subject, error=%format!("{e:#}"), "dlq: dead-lettering failed; nack");
+                let _ = msg.ack_with(AckKind::Nak(Some(REDELIVERY_DELAY))).await;
+            }
+        }
+    }
+
+    async fn publish(&self, msg: &Message, reason: &str) -> Result<()> {
+        ensure_stream(&self.js).await?;
+        let info = msg
+            .info()
+            .map_err(|e| anyhow::anyhow!("missing delivery info: {e}"))?;
+        let source_subject = msg.message.subject.to_string();
+        let headers = dead_letter_headers(
+            msg.message.headers.as_ref(),
+            &FailureInfo {
+                consumer: &self.consumer,
+                reason,
+                deliveries: info.delivered.max(0) as u64,
+                source_stream: info.stream,
+                source_subject: &source_subject,
+                source_sequence: info.stream_sequence,
+            },
+        );
+        self.js
+            .publish_with_headers(
+                subject_for(&self.consumer),
+                headers,
+                msg.message.payload.clone(),
+            )
+            .await
+            .context("Failed to publish to DLQ")?
+            .await
+            .context("Failed to get DLQ publish acknowledgement")?;
+        Ok(())
+    }
+}
+
+/// Why and where a message failed
+pub struct FailureInfo<'a> {
+    pub consumer: &'a str,
+    pub reason: &'a str,
+    pub deliveries: u64,
+    pub source_stream: &'a str,
+    pub source_subject: &'a str,
+    pub source_sequence: u64,
+}
+
+/// Headers of the dead-lettered copy: the original headers plus the failure
+/// metadata. The original `Nats-Msg-Id` is kept as `Demon-Dlq-Source-Msg-Id`;
+/// the copy is deduplicated on its source position instead.
+pub fn dead_letter_headers(original: Option<&HeaderMap>, failure: &FailureInfo<'_>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(original) = original {
+        for (name, values) in original.iter() {
+            let name = name.to_string();
+            for value in values {
+                if name == "Nats-Msg-Id" {
+                    headers.insert(SOURCE_MSG_ID_HEADER, value.as_str());
+                } else {
+                    headers.append(name.as_str(), value.as_str());
+                }
+            }
+        }
+    }
+    let msg_id = format!("dlq:{}:{}", failure.source_stream, failure.source_sequence);
+    headers.insert("Nats-Msg-Id", msg_id.as_str());
+    headers.insert(CONSUMER_HEADER, failure.consumer);
+    headers.insert(REASON_HEADER, header_safe(failure.reason).as_str());
+    headers.insert(DELIVERIES_HEADER, failure.deliveries.to_string().as_str());
+    headers.insert(FAILED_AT_HEADER, Utc::now().to_rfc3339().as_str());
+    headers.insert(SOURCE_STREAM_HEADER, failure.source_stream);
+    headers.insert(SOURCE_SUBJECT_HEADER, failure.source_subject);
+    headers.insert(
+        SOURCE_SEQUENCE_HEADER,
+        failure.source_sequence.to_string().as_str(),
+    );
+    headers
+}
+
+/// Header values cannot span lines
+fn header_safe(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// A message in `RITUAL_DLQ`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    /// Position in `RITUAL_DLQ`; identifies the dead letter for requeue
+    pub sequence: u64,
+    pub consumer: String,
+    pub reason: String,
+    pub deliveries: u64,
+    pub failed_at: String,
+    pub source_stream: String,
+    pub source_subject: String,
+    pub source_sequence: u64,
+    /// The original payload: JSON when it parses, otherwise lossy UTF-8
+    pub payload: Value,
+    #[serde(skip)]
+    raw_payload: Vec<u8>,
+    #[serde(skip)]
+    headers: HeaderMap,
+}
+
+impl DeadLetter {
+    pub fn from_parts(sequence: u64, headers: HeaderMap, payload: &[u8]) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .map(|v| v.as_str().to_string())
+                .unwrap_or_default()
+        };
+        Self {
+            sequence,
+            consumer: header(CONSUMER_HEADER),
+            reason: header(REASON_HEADER),
+            deliveries: header(DELIVERIES_HEADER).parse().unwrap_or(0),
+            failed_at: header(FAILED_AT_HEADER),
+            source_stream: header(SOURCE_STREAM_HEADER),
+            source_subject: header(SOURCE_SUBJECT_HEADER),
+            source_sequence: header(SOURCE_SEQUENCE_HEADER).parse().unwrap_or(0),
+            payload: serde_json::from_slice(payload)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(payload).into())),
+            raw_payload: payload.to_vec(),
+            headers,
+        }
+    }
+
+    /// Headers to republish the original message with: everything except
+    /// the DLQ metadata and `Nats-Msg-Id`, which the source stream would
+    /// otherwise drop as a duplicate
+    pub fn requeue_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, values) in self.headers.iter() {
+            let name = name.to_string();
+            if name == "Nats-Msg-Id" || name.starts_with("Demon-Dlq-") {
+                continue;
+            }
+            for value in values {
+                headers.append(name.as_str(), value.as_str());
+            }
+        }
+        headers
+    }
+}
+
+/// Dead letters in `RITUAL_DLQ`, oldest first; `consumer` narrows to one consumer
+pub async fn list(js: &jetstream::Context, consumer: Option<&str>) -> Result<Vec<DeadLetter>> {
+    let filter = consumer.map(subject_for).unwrap_or_default();
+    read(js, filter, DeliverPolicy::All, usize::MAX).await
+}
+
+/// Republish the dead letter at `sequence` to its original subject and
+/// remove it from `RITUAL_DLQ`
+pub async fn requeue(js: &jetstream::Context, sequence: u64) -> Result<DeadLetter> {
+    let letter = read(
+        js,
+        String::new(),
+        DeliverPolicy::ByStartSequence {
+            start_sequence: sequence,
+        },
+        1,
+    )
+    .await?
+    .into_iter()
+    .find(|l| l.sequence == sequence)
+    .with_context(|| format!("No dead letter at sequence {} in {}", sequence, DLQ_STREAM))?;
+    if letter.source_subject.is_empty() {
+        bail!("Dead letter {} has no source subject", sequence);
+    }
+
+    js.publish_with_headers(
+        letter.source_subject.clone(),
+        letter.requeue_headers(),
+        letter.raw_payload.clone().into(),
+    )
+    .await
+    .context("Failed to republish dead letter")?
+    .await
+    .context("Failed to get republish acknowledgement")?;
+
+    let stream = ensure_stream(js).await?;
+    stream
+        .delete_message(sequence)
+        .await
+        .with_context(|| format!("Requeued dead letter {} but could not delete it", sequence))?;
+    Ok(letter)
+}
+
+async fn read(
+    js: &jetstream::Context,
+    filter_subject: String,
+    deliver_policy: DeliverPolicy,
+    max: usize,
+) -> Result<Vec<DeadLetter>> {
+    let stream = ensure_stream(js).await?;
+    let mut consumer: jetstream::consumer::PullConsumer = stream
+        .create_consumer(jetstream::consumer::pull::Config {
+            name: None,
+            filter_subject,
+            deliver_policy,
+            ack_policy: jetstream::consumer::AckPolicy::None,
+            ..Default::default()
+        })
+        .await
+        .context("Failed to create ephemeral DLQ consumer")?;
+
+    let mut letters = Vec::new();
+    'fetch: while letters.len() < max {
+        let mut batch = consumer
+            .fetch()
+            .max_messages(100)
+            .messages()
+            .await
+            .context("Failed to fetch dead letters")?;
+        let mut fetched = 0;
+        while let Some(msg) = batch.next().await {
+            let msg = msg.map_err(|e| anyhow::anyhow!("Failed to read dead letter: {e}"))?;
+            fetched += 1;
+            let sequence = msg
+                .info()
+                .map_err(|e| anyhow::anyhow!("missing delivery info: {e}"))?
+                .stream_sequence;
+            letters.push(DeadLetter::from_parts(
+                sequence,
+                msg.message.headers.clone().unwrap_or_default(),
+                &msg.message.payload,
+            ));
+            if letters.len() >= max {
+                break 'fetch;
+            }
+        }
+        if fetched == 0 {
+            break;
+        }
+    }
+
+    if let Ok(info) = consumer.info().await {
+        let _ = stream.delete_consumer(&info.name).await;
+    }
+    Ok(letters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure() -> FailureInfo<'static> {
+        FailureInfo {
+            consumer: "event-trigger-deploy-on-tag",
+            reason: "publish failed:\nno responders",
+            deliveries: 5,
+            source_stream: "GRAPH",
+            source_subject: "demon.graph.v1.acme.p.n.commit",
+            source_sequence: 42,
+        }
+    }
+
+    #[test]
+    fn policy_is_exhausted_at_max_deliveries() {
+        let policy = DlqPolicy { max_deliveries: 3 };
+        assert!(!policy.exhausted(1));
+        assert!(!policy.exhausted(2));
+        assert!(policy.exhausted(3));
+        assert!(policy.exhausted(4));
+    }
+
+    #[test]
+    fn dead_letter_round_trips_failure_metadata() {
+        let mut original = HeaderMap::new();
+        original.insert("Nats-Msg-Id", "t:p:n:tag:prod");
+        original.insert("Traceparent", "00-abc-def-01");
+
+        let headers = dead_letter_headers(Some(&original), &failure());
+        assert_eq!(headers.get("Nats-Msg-Id").unwrap().as_str(), "dlq:GRAPH:42");
+        assert_eq!(
+            headers.get(SOURCE_MSG_ID_HEADER).unwrap().as_str(),
+            "t:p:n:tag:prod"
+        );
+
+        let letter = DeadLetter::from_parts(7, headers, b"{\"a\":1}");
+        assert_eq!(letter.sequence, 7);
+        assert_eq!(letter.consumer, "event-trigger-deploy-on-tag");
+        assert_eq!(letter.reason, "publish failed: no responders");
+        assert_eq!(letter.deliveries, 5);
+        assert_eq!(letter.source_subject, "demon.graph.v1.acme.p.n.commit");
+        assert_eq!(letter.source_sequence, 42);
+        assert_eq!(letter.payload["a"], 1);
+
+        let requeue = letter.requeue_headers();
+        assert!(requeue.get("Nats-Msg-Id").is_none());
+        assert!(requeue.get(CONSUMER_HEADER).is_none());
+        assert_eq!(
+            requeue.get("Traceparent").unwrap().as_str(),
+            "00-abc-def-01"
+        );
+    }
+
+    #[test]
+    fn non_json_payload_is_kept_as_text() {
+        let letter = DeadLetter::from_parts(1, dead_letter_headers(None, &failure()), b"not json");
+        assert_eq!(letter.payload, Value::String("not json".into()));
+    }
+}
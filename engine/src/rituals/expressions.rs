@@ -0,0 +1,476 @@
+//! `${{ steps.<id>.<path> }}` and `${{ inputs.<path> }}` expressions
+//!
+//! Capsule `with` arguments and approval `reason`s may reference the output
+//! recorded for an earlier step or the run's validated inputs (see
+//! [`super::inputs`]). Paths walk the value with `.key`, `[index]` and
+//! `["quoted key"]` segments, e.g.
+//! `${{ steps.build.result.data.artifactUrl }}`,
+//! `${{ steps.scan.result.data.findings[0].id }}` or
+//! `${{ inputs.environment }}`.
+//!
+//! A string that is exactly one expression is replaced by the referenced
+//! value, keeping its JSON type. Expressions embedded in a longer string are
+//! interpolated and must resolve to a string, number or boolean. Anything
+//! else — a step with no output, a missing key, indexing into a scalar — is
+//! an error raised before the dependent step runs.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{Map, Value};
+use std::fmt;
+
+const OPEN: &str = "${{";
+const CLOSE: &str = "}}";
+
+/// One hop from a value to a child value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// The value an expression starts from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Root {
+    /// The run's inputs object
+    Inputs,
+    /// The recorded output of a step
+    Step(String),
+}
+
+impl fmt::Display for Root {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Root::Inputs => f.write_str("inputs"),
+            Root::Step(step) => write!(f, "steps.{}", step),
+        }
+    }
+}
+
+/// A parsed `steps.<id>...` or `inputs...` reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub root: Root,
+    pub path: Vec<Segment>,
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root)?;
+        for segment in &self.path {
+            match segment {
+                Segment::Key(key) if is_identifier(key) => write!(f, ".{}", key)?,
+                Segment::Key(key) => write!(f, "[{:?}]", key)?,
+                Segment::Index(i) => write!(f, "[{}]", i)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Reference {
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expr = expression.trim();
+        if let Some(rest) = expr
+            .strip_prefix("inputs")
+            .filter(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+        {
+            let path = parse_path(rest).map_err(|e| anyhow!("expression '{}': {}", expr, e))?;
+            return Ok(Self {
+                root: Root::Inputs,
+                path,
+            });
+        }
+        let rest = expr.strip_prefix("steps").ok_or_else(|| {
+            anyhow!(
+                "expression '{}' must start with 'steps.' or 'inputs.'",
+                expr
+            )
+        })?;
+        let mut segments = parse_path(rest).map_err(|e| anyhow!("expression '{}': {}", expr, e))?;
+        if segments.is_empty() {
+            bail!("expression '{}' must name a step", expr);
+        }
+        let step = match segments.remove(0) {
+            Segment::Key(step) => step,
+            Segment::Index(_) => bail!("expression '{}' must name a step", expr),
+        };
+        Ok(Self {
+            root: Root::Step(step),
+            path: segments,
+        })
+    }
+
+    /// The referenced step, for `steps.*` expressions
+    pub fn step(&self) -> Option<&str> {
+        match &self.root {
+            Root::Step(step) => Some(step),
+            Root::Inputs => None,
+        }
+    }
+
+    /// The top-level input read, for `inputs.*` expressions
+    pub fn input(&self) -> Option<&str> {
+        match (&self.root, self.path.first()) {
+            (Root::Inputs, Some(Segment::Key(key))) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Walk `output` along the path; errors name the first hop that fails
+    pub fn lookup<'a>(&self, output: &'a Value) -> Result<&'a Value> {
+        let mut current = output;
+        let mut walked = self.root.to_string();
+        for segment in &self.path {
+            current = match (segment, current) {
+                (Segment::Key(key), Value::Object(map)) => map
+                    .get(key)
+                    .ok_or_else(|| anyhow!("'{}' has no key '{}'", walked, key))?,
+                (Segment::Index(i), Value::Array(items)) => items.get(*i).ok_or_else(|| {
+                    anyhow!(
+                        "'{}' has {} items, index {} is out of range",
+                        walked,
+                        items.len(),
+                        i
+                    )
+                })?,
+                (Segment::Key(key), other) => bail!(
+                    "cannot read key '{}' of '{}': expected object, found {}",
+                    key,
+                    walked,
+                    type_name(other)
+                ),
+                (Segment::Index(i), other) => bail!(
+                    "cannot read index {} of '{}': expected array, found {}",
+                    i,
+                    walked,
+                    type_name(other)
+                ),
+            };
+            match segment {
+                Segment::Key(key) if is_identifier(key) => walked = format!("{}.{}", walked, key),
+                Segment::Key(key) => walked = format!("{}[{:?}]", walked, key),
+                Segment::Index(i) => walked = format!("{}[{}]", walked, i),
+            }
+        }
+        Ok(current)
+    }
+}
+
+/// Every reference in `value`, in document order
+pub fn references(value: &Value) -> Result<Vec<Reference>> {
+    let mut refs = Vec::new();
+    collect(value, &mut refs)?;
+    Ok(refs)
+}
+
+/// Whether `value` contains any expression
+pub fn has_expressions(value: &Value) -> bool {
+    match value {
+        Value::String(s) => s.contains(OPEN),
+        Value::Array(items) => items.iter().any(has_expressions),
+        Value::Object(map) => map.values().any(has_expressions),
+        _ => false,
+    }
+}
+
+/// Replace every expression in `value` using `output`, which returns the
+/// value a root stands for: the run's inputs, or the recorded output of a
+/// step (`None` if it has not run)
+pub fn resolve<F>(value: &Value, output: &F) -> Result<Value>
+where
+    F: Fn(&Root) -> Option<Value>,
+{
+    Ok(match value {
+        Value::String(s) => resolve_str(s, output)?,
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|v| resolve(v, output))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), resolve(v, output)?)))
+                .collect::<Result<Map<_, _>>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Resolve a string template to a plain string (e.g. an approval reason)
+pub fn interpolate<F>(template: &str, output: &F) -> Result<String>
+where
+    F: Fn(&Root) -> Option<Value>,
+{
+    let mut out = String::new();
+    for part in split(template)? {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Expr(reference) => {
+                let value = evaluate(&reference, output)?;
+                out.push_str(&scalar_text(&reference, &value)?);
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn resolve_str<F>(s: &str, output: &F) -> Result<Value>
+where
+    F: Fn(&Root) -> Option<Value>,
+{
+    if !s.contains(OPEN) {
+        return Ok(Value::String(s.to_string()));
+    }
+    let parts = split(s)?;
+    // A lone expression keeps the referenced value's type
+    if let [Part::Expr(reference)] = parts.as_slice() {
+        return evaluate(reference, output);
+    }
+    interpolate(s, output).map(Value::String)
+}
+
+fn evaluate<F>(reference: &Reference, output: &F) -> Result<Value>
+where
+    F: Fn(&Root) -> Option<Value>,
+{
+    let recorded = output(&reference.root).ok_or_else(|| match &reference.root {
+        Root::Step(step) => anyhow!(
+            "'${{{{ {} }}}}': step '{}' has no output (it has not run)",
+            reference,
+            step
+        ),
+        Root::Inputs => anyhow!("'${{{{ {} }}}}': the run has no inputs", reference),
+    })?;
+    reference
+        .lookup(&recorded)
+        .cloned()
+        .map_err(|e| anyhow!("'${{{{ {} }}}}': {}", reference, e))
+}
+
+fn scalar_text(reference: &Reference, value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        other => bail!(
+            "'${{{{ {} }}}}' is embedded in a string and must be a string, number or boolean, found {}",
+            reference,
+            type_name(other)
+        ),
+    }
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    Expr(Reference),
+}
+
+fn split(s: &str) -> Result<Vec<Part<'_>>> {
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find(OPEN) {
+        if start > 0 {
+            parts.push(Part::Text(&rest[..start]));
+        }
+        let after = &rest[start + OPEN.len()..];
+        let end = after
+            .find(CLOSE)
+            .ok_or_else(|| anyhow!("unterminated expression in '{}'", s))?;
+        parts.push(Part::Expr(Reference::parse(&after[..end])?));
+        rest = &after[end + CLOSE.len()..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    Ok(parts)
+}
+
+fn collect(value: &Value, refs: &mut Vec<Reference>) -> Result<()> {
+    match value {
+        Value::String(s) if s.contains(OPEN) => {
+            refs.extend(split(s)?.into_iter().filter_map(|part| match part {
+                Part::Expr(reference) => Some(reference),
+                Part::Text(_) => None,
+            }))
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect(item, refs)?;
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values() {
+                collect(item, refs)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn parse_path(mut rest: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let len = after
+                .find(|c: char| !is_identifier_char(c))
+                .unwrap_or(after.len());
+            if len == 0 {
+                bail!("expected a key after '.'");
+            }
+            segments.push(Segment::Key(after[..len].to_string()));
+            rest = &after[len..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| anyhow!("unclosed '['"))?;
+            let inner = after[..end].trim();
+            let quoted = inner
+                .strip_prefix('"')
+                .and_then(|s| s.strip_suffix('"'))
+                .or_else(|| inner.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')));
+            segments.push(match quoted {
+                Some(key) => Segment::Key(key.to_string()),
+                None => Segment::Index(
+                    inner
+                        .parse()
+                        .map_err(|_| anyhow!("invalid index '[{}]'", inner))?,
+                ),
+            });
+            rest = &after[end + 1..];
+        } else {
+            bail!("unexpected '{}'", rest);
+        }
+    }
+    Ok(segments)
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn is_identifier(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(is_identifier_char)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn outputs(root: &Root) -> Option<Value> {
+        match root {
+            Root::Inputs => Some(json!({ "environment": "prod", "replicas": 3 })),
+            Root::Step(id) if id == "build" => Some(json!({
+                "result": {
+                    "success": true,
+                    "data": {
+                        "artifactUrl": "oci://ghcr.io/demon/app:1.2.3",
+                        "size": 42,
+                        "layers": [{ "digest": "sha256:a" }, { "digest": "sha256:b" }],
+                        "dotted.key": "yes"
+                    }
+                }
+            })),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parses_keys_indices_and_quoted_keys() {
+        let r = Reference::parse(" steps.build.result.layers[1]['dotted.key'] ").unwrap();
+        assert_eq!(r.step(), Some("build"));
+        assert_eq!(
+            r.path,
+            vec![
+                Segment::Key("result".into()),
+                Segment::Key("layers".into()),
+                Segment::Index(1),
+                Segment::Key("dotted.key".into()),
+            ]
+        );
+        assert_eq!(
+            r.to_string(),
+            "steps.build.result.layers[1][\"dotted.key\"]"
+        );
+    }
+
+    #[test]
+    fn lone_expression_keeps_type_and_embedded_ones_interpolate() {
+        let args = json!({
+            "url": "${{ steps.build.result.data.artifactUrl }}",
+            "size": "${{ steps.build.result.data.size }}",
+            "digest": "${{steps.build.result.data.layers[0].digest}}",
+            "message": "pushed ${{ steps.build.result.data.size }} bytes (ok=${{ steps.build.result.success }})",
+            "literal": "no expressions here",
+        });
+        let resolved = resolve(&args, &outputs).unwrap();
+        assert_eq!(resolved["url"], "oci://ghcr.io/demon/app:1.2.3");
+        assert_eq!(resolved["size"], 42);
+        assert_eq!(resolved["digest"], "sha256:a");
+        assert_eq!(resolved["message"], "pushed 42 bytes (ok=true)");
+        assert_eq!(resolved["literal"], "no expressions here");
+    }
+
+    #[test]
+    fn type_errors_name_the_failing_hop() {
+        let err = |s: &str| resolve(&json!(s), &outputs).unwrap_err().to_string();
+        assert!(err("${{ steps.deploy.result }}").contains("has no output"));
+        assert!(err("${{ steps.build.result.data.missing }}")
+            .contains("'steps.build.result.data' has no key 'missing'"));
+        assert!(err("${{ steps.build.result.data.size.bytes }}")
+            .contains("expected object, found number"));
+        assert!(err("${{ steps.build.result.data.layers[5] }}").contains("out of range"));
+        assert!(err("layers: ${{ steps.build.result.data.layers }}")
+            .contains("must be a string, number or boolean, found array"));
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected() {
+        for bad in [
+            "${{ steps.build",
+            "${{ build.result }}",
+            "${{ steps }}",
+            "${{ steps.build..x }}",
+            "${{ steps.build[x] }}",
+            "${{ inputs..x }}",
+            "${{ inputsx }}",
+        ] {
+            assert!(references(&json!(bad)).is_err(), "{bad} should fail");
+        }
+        let refs = references(&json!({ "a": ["${{ steps.x.y }} and ${{ steps.z }}"] })).unwrap();
+        assert_eq!(
+            refs.iter().filter_map(Reference::step).collect::<Vec<_>>(),
+            vec!["x", "z"]
+        );
+    }
+
+    #[test]
+    fn inputs_resolve_like_step_outputs() {
+        let args = json!({
+            "environment": "${{ inputs.environment }}",
+            "replicas": "${{ inputs.replicas }}",
+            "message": "deploying to ${{ inputs.environment }} after ${{ steps.build.result.data.size }}",
+        });
+        let resolved = resolve(&args, &outputs).unwrap();
+        assert_eq!(resolved["environment"], "prod");
+        assert_eq!(resolved["replicas"], 3);
+        assert_eq!(resolved["message"], "deploying to prod after 42");
+
+        let r = Reference::parse("inputs.environment").unwrap();
+        assert_eq!((r.step(), r.input()), (None, Some("environment")));
+        assert_eq!(r.to_string(), "inputs.environment");
+        let err = resolve(&json!("${{ inputs.region }}"), &outputs).unwrap_err();
+        assert!(err.to_string().contains("'inputs' has no key 'region'"));
+    }
+}
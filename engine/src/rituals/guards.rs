@@ -2,6 +2,7 @@ use anyhow::Result;
 use serde_json::{json, Value};
 use std::sync::{Mutex, OnceLock};
 
+use wards::audit::{self, WardsDecision};
 use wards::config::{load_from_env, WardsConfig};
 use wards::policy::{quota_key, Decision as KernelDecision, PolicyKernel};
 
@@ -92,6 +93,14 @@ pub async fn check_and_emit(
         .await?
         .await?;
 
+    if !decision.allowed {
+        let record = WardsDecision::capability_quota_rejected(tenant_id, capability, &decision)
+            .for_run(ritual_id, run_id);
+        if let Err(e) = audit::record(&js, &audit::decisions_stream(), &record).await {
+            tracing::warn!(rule_id = %record.rule_id, "failed to record policy decision: {}", e);
+        }
+    }
+
     Ok(decision)
 }
 
@@ -0,0 +1,240 @@
+//! Typed run inputs for parameterized rituals
+//!
+//! A definition may declare `inputs` as a JSON Schema for an object. Values
+//! for a run come from a JSON file (`demonctl run --inputs`), `key=value`
+//! assignments (`--set`) or a caller-supplied object, and are checked against
+//! the schema before any step runs. Steps read them with
+//! `${{ inputs.<name>.<path> }}` (see [`super::expressions`]).
+//!
+//! `--set` values arrive as text: they stay strings when the property is
+//! declared as a string and are otherwise parsed as JSON, so `--set
+//! replicas=3` yields a number. Top-level property `default`s fill in values
+//! that were not supplied.
+
+use anyhow::{anyhow, bail, Context, Result};
+use jsonschema::JSONSchema;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Values supplied for one run, before they are checked against a schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunInputs {
+    values: Map<String, Value>,
+    /// Keys whose value came from a `--set` assignment and is still raw text
+    assigned: HashSet<String>,
+}
+
+impl RunInputs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inputs from a JSON object, e.g. the body of a REST trigger
+    pub fn from_value(value: Value) -> Result<Self> {
+        match value {
+            Value::Null => Ok(Self::default()),
+            Value::Object(values) => Ok(Self {
+                values,
+                assigned: HashSet::new(),
+            }),
+            _ => bail!("ritual inputs must be a JSON object"),
+        }
+    }
+
+    /// Merge the `--inputs` file (then `--set` assignments, in order) into one set of values
+    pub fn from_sources(file: Option<&Path>, sets: &[String]) -> Result<Self> {
+        let mut inputs = Self::default();
+        if let Some(path) = file {
+            inputs.merge_file(path)?;
+        }
+        for assignment in sets {
+            inputs.set(assignment)?;
+        }
+        Ok(inputs)
+    }
+
+    /// Merge a JSON object of input values
+    pub fn merge_file(&mut self, path: &Path) -> Result<()> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("read inputs: {}", path.display()))?;
+        let doc: Map<String, Value> = serde_json::from_str(&raw)
+            .with_context(|| format!("parse inputs {}: expected a JSON object", path.display()))?;
+        for (key, value) in doc {
+            self.assigned.remove(&key);
+            self.values.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// Apply a `key=value` assignment; the value is typed against the
+    /// ritual's schema when the run starts
+    pub fn set(&mut self, assignment: &str) -> Result<()> {
+        let (key, value) = assignment
+            .split_once('=')
+            .filter(|(key, _)| !key.trim().is_empty())
+            .ok_or_else(|| anyhow!("invalid --set '{}': expected key=value", assignment))?;
+        let key = key.trim().to_string();
+        self.values
+            .insert(key.clone(), Value::String(value.to_string()));
+        self.assigned.insert(key);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Type `--set` values, apply defaults and validate against `schema`,
+    /// reporting every problem at once. Without a schema no inputs are accepted.
+    pub fn resolve(&self, ritual_id: &str, schema: Option<&Value>) -> Result<Map<String, Value>> {
+        let Some(schema) = schema else {
+            if !self.is_empty() {
+                let mut keys: Vec<&str> = self.values.keys().map(String::as_str).collect();
+                keys.sort_unstable();
+                bail!(
+                    "ritual '{}' declares no inputs, but {} supplied",
+                    ritual_id,
+                    keys.join(", ")
+                );
+            }
+            return Ok(Map::new());
+        };
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let mut values = Map::new();
+        for (key, value) in &self.values {
+            let value = match value {
+                Value::String(text) if self.assigned.contains(key) => {
+                    coerce(text, properties.and_then(|p| p.get(key)))
+                }
+                other => other.clone(),
+            };
+            values.insert(key.clone(), value);
+        }
+        for (key, property) in properties.into_iter().flatten() {
+            if let Some(default) = property.get("default") {
+                values.entry(key.clone()).or_insert_with(|| default.clone());
+            }
+        }
+
+        let compiled = compile(schema)?;
+        let instance = Value::Object(values.clone());
+        if let Err(errors) = compiled.validate(&instance) {
+            let details: Vec<String> = errors
+                .map(|e| format!("{}: {}", e.instance_path, e))
+                .collect();
+            bail!(
+                "invalid inputs for ritual '{}':\n  {}",
+                ritual_id,
+                details.join("\n  ")
+            );
+        }
+        Ok(values)
+    }
+}
+
+/// Compile an `inputs` schema, which must describe an object
+pub fn compile(schema: &Value) -> Result<JSONSchema> {
+    if let Some(kind) = schema.get("type") {
+        if kind != "object" {
+            bail!("inputs schema must have type 'object', found {}", kind);
+        }
+    }
+    JSONSchema::compile(schema).map_err(|e| anyhow!("invalid inputs schema: {}", e))
+}
+
+/// Names of the top-level properties `schema` declares
+pub fn declared(schema: &Value) -> Vec<&str> {
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|props| props.keys().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Raw `--set` text typed by the property's declared `type`
+fn coerce(text: &str, property: Option<&Value>) -> Value {
+    let wants_string = match property.and_then(|p| p.get("type")) {
+        Some(Value::String(kind)) => kind == "string",
+        Some(Value::Array(kinds)) => kinds.iter().any(|k| k == "string"),
+        _ => true,
+    };
+    if wants_string {
+        return Value::String(text.to_string());
+    }
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["environment"],
+            "properties": {
+                "environment": { "type": "string", "enum": ["staging", "prod"] },
+                "replicas": { "type": "integer", "minimum": 1, "default": 2 },
+                "version": { "type": "string" },
+                "dryRun": { "type": "boolean" }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn set_values_are_typed_by_the_schema() {
+        let inputs = RunInputs::from_sources(
+            None,
+            &[
+                "environment=prod".to_string(),
+                "replicas=3".to_string(),
+                "version=1.10".to_string(),
+                "dryRun=true".to_string(),
+            ],
+        )
+        .unwrap();
+        let resolved = inputs.resolve("deploy", Some(&schema())).unwrap();
+        assert_eq!(resolved["environment"], "prod");
+        assert_eq!(resolved["replicas"], 3);
+        assert_eq!(resolved["version"], "1.10");
+        assert_eq!(resolved["dryRun"], true);
+    }
+
+    #[test]
+    fn defaults_fill_missing_values() {
+        let inputs = RunInputs::from_value(json!({ "environment": "staging" })).unwrap();
+        let resolved = inputs.resolve("deploy", Some(&schema())).unwrap();
+        assert_eq!(resolved["replicas"], 2);
+        assert!(!resolved.contains_key("version"));
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let mut inputs = RunInputs::new();
+        inputs.set("replicas=zero").unwrap();
+        inputs.set("region=eu").unwrap();
+        let err = inputs
+            .resolve("deploy", Some(&schema()))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid inputs for ritual 'deploy'"), "{err}");
+        assert!(err.contains("environment"), "{err}");
+        assert!(err.contains("/replicas"), "{err}");
+        assert!(err.contains("region"), "{err}");
+    }
+
+    #[test]
+    fn inputs_require_a_declared_schema() {
+        assert!(RunInputs::new().resolve("plain", None).unwrap().is_empty());
+        let mut inputs = RunInputs::new();
+        inputs.set("environment=prod").unwrap();
+        let err = inputs.resolve("plain", None).unwrap_err().to_string();
+        assert!(err.contains("declares no inputs"), "{err}");
+        assert!(inputs.set("novalue").is_err());
+        assert!(RunInputs::from_value(json!(["a"])).is_err());
+    }
+}
@@ -0,0 +1,1485 @@
+//! Interpreter for typed-step ritual definitions
+//!
+//! `Engine` walks a [`RitualDefinition`] step by step. Side effects (capsule
+//! calls, approval waits, timers) go through a [`StepRunner`] so the walk can
+//! be exercised without NATS or containers. A run halts early — without
+//! error — when an approval is denied or a policy/quota check rejects a
+//! capsule step; the completion envelope then carries the `reason`.
+//!
+//! A capsule or approval step fails when its side effect errors or a capsule
+//! reports `result.success == false`. Failed steps are re-attempted per their
+//! `retry` policy (emitting `step.retried:v1`), then handled per `onFailure`;
+//! compensation emits `step.compensated:v1`. Every attempt of a capsule,
+//! approval or timer step is bracketed by `step.started:v1` (with the time a
+//! parallel branch waited for a slot) and `step.completed:v1` (with its
+//! duration and outcome).
+//!
+//! Parallel blocks run up to `maxConcurrency` branches at once (default
+//! `RITUAL_PARALLEL_LIMIT`, else 8) and halt with `join_not_met` when too few
+//! branches succeed for an `any` or `quorum` join.
+//!
+//! Before a capsule or approval step runs, `${{ steps.<id>.<path> }}` and
+//! `${{ inputs.<path> }}` expressions in its arguments are resolved against
+//! the outputs recorded so far and the run's validated inputs. A reference
+//! that cannot be resolved fails the step without invoking it; `onFailure`
+//! still applies but retries do not.
+//!
+//! `timeoutSeconds` bounds each attempt of a capsule, approval or timer step;
+//! a timed-out attempt emits `step.timeout:v1` and fails like any other, so it
+//! may be retried. The definition's `timeoutSeconds` is a run deadline: every
+//! attempt gets at most the time left, `container-exec` receives that budget
+//! as its own `timeoutSeconds`, and running out halts the run with
+//! `deadline_exceeded`.
+//!
+//! While a run is in flight the runner watches for `run.cancel.requested:v1`.
+//! On cancellation no further steps are scheduled, in-flight side effects are
+//! abandoned (the runner kills running containers), `run.canceled:v1` is
+//! emitted, and the run completes with `reason: "canceled"`.
+//!
+//! A definition with a `concurrency` group first takes the group's lease (see
+//! [`super::concurrency`]). While another run holds it, the run waits, or
+//! halts with `concurrency_rejected` under `onConflict: reject`; with
+//! `cancelInProgress` the holder is sent `run.cancel.requested:v1` first. The
+//! lease is renewed while the steps run and released when the run ends.
+//!
+//! With an execution ledger configured, each capsule attempt is looked up by
+//! `runId:stepId:attempt` first; an attempt that already produced an
+//! envelope returns it without invoking the capsule (see [`super::ledger`]).
+//!
+//! With a checkpoint store configured, the run's lifecycle phase and recorded
+//! outputs are persisted as it goes (see [`super::checkpoint`]). A run
+//! resumed from its checkpoint skips every step that already has an output.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::future::{join_all, select, Either, FutureExt, LocalBoxFuture};
+use futures_util::stream::{self, StreamExt};
+use serde_json::{json, Map, Value};
+use tracing::{info, warn};
+use wards::audit::WardsDecision;
+use wards::quota::QuotaResource;
+
+use super::approvals;
+use super::checkpoint::{RunCheckpoint, RunPhase};
+use super::concurrency::{ConcurrencyLocks, GroupLease, KvConcurrencyLocks, LEASE_TTL};
+use super::definition::{
+    parse_delay, Join, OnConflict, OnFailure, RitualDefinition, Step, StepKind,
+};
+use super::expressions::{self, Root};
+use super::inputs::RunInputs;
+use super::ledger::LedgerKey;
+use super::{quota_resources, Engine};
+
+/// Identity of the step being executed
+#[derive(Debug, Clone)]
+pub struct StepContext {
+    pub tenant_id: String,
+    pub ritual_id: String,
+    pub run_id: String,
+    pub step_id: String,
+}
+
+/// Resolution of an approval gate
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalOutcome {
+    pub granted: bool,
+    pub approver: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Operator request to stop a run (`run.cancel.requested:v1`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CancelRequest {
+    pub requested_by: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// Side effects performed on behalf of ritual steps
+#[async_trait]
+pub trait StepRunner: Send + Sync {
+    /// Invoke a capsule and return its result envelope
+    async fn invoke_capsule(&self, capsule: &str, args: &Value, ctx: &StepContext)
+        -> Result<Value>;
+
+    /// Request approval for `gate` and wait until it is resolved
+    async fn await_approval(
+        &self,
+        gate: &str,
+        reason: &str,
+        ttl_seconds: Option<u64>,
+        ctx: &StepContext,
+    ) -> Result<ApprovalOutcome>;
+
+    /// Pause the run for a timer step or retry backoff
+    async fn sleep(&self, delay: Duration) {
+        tokio::time::sleep(delay).await;
+    }
+
+    /// Publish a step lifecycle event (e.g. `step.retried:v1`) for the run
+    async fn emit(&self, msg_id: &str, event: &Value, ctx: &StepContext) -> Result<()>;
+
+    /// Resolve once cancellation of the run is requested. The default never
+    /// resolves; an error means cancellation cannot be observed for this run.
+    async fn wait_for_cancel(&self, _ctx: &StepContext) -> Result<CancelRequest> {
+        std::future::pending().await
+    }
+
+    /// Stop side effects still running for a canceled run
+    async fn cancel_run(&self, _ctx: &StepContext) {}
+
+    /// Drop any per-run state once the run has finished
+    async fn release_run(&self, _ctx: &StepContext) {}
+}
+
+/// Default runner: capsules through the runtime router, approvals through JetStream
+pub struct RouterStepRunner {
+    router: runtime::link::router::Router,
+    poll_interval: Duration,
+}
+
+impl Default for RouterStepRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouterStepRunner {
+    pub fn new() -> Self {
+        let poll_ms = std::env::var("APPROVAL_POLL_INTERVAL_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(2000);
+        Self {
+            router: runtime::link::router::Router::new(),
+            poll_interval: Duration::from_millis(poll_ms),
+        }
+    }
+}
+
+#[async_trait]
+impl StepRunner for RouterStepRunner {
+    async fn invoke_capsule(
+        &self,
+        capsule: &str,
+        args: &Value,
+        ctx: &StepContext,
+    ) -> Result<Value> {
+        self.router
+            .dispatch(capsule, args, &ctx.run_id, &ctx.ritual_id)
+            .await
+    }
+
+    async fn await_approval(
+        &self,
+        gate: &str,
+        reason: &str,
+        ttl_seconds: Option<u64>,
+        ctx: &StepContext,
+    ) -> Result<ApprovalOutcome> {
+        match ttl_seconds {
+            Some(ttl) => {
+                approvals::await_gate_with_ttl(
+                    &ctx.run_id,
+                    &ctx.ritual_id,
+                    gate,
+                    "engine",
+                    reason,
+                    Some(ttl),
+                )
+                .await?
+            }
+            None => {
+                approvals::await_gate(&ctx.run_id, &ctx.ritual_id, gate, "engine", reason).await?
+            }
+        }
+
+        // Approval requests are published under the default tenant
+        let terminal = approvals::wait_for_gate_resolution(
+            "default",
+            &ctx.run_id,
+            &ctx.ritual_id,
+            gate,
+            self.poll_interval,
+        )
+        .await?;
+        let field = |name: &str| {
+            terminal
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        };
+        Ok(ApprovalOutcome {
+            granted: field("event").as_deref() != Some("approval.denied:v1"),
+            approver: field("approver"),
+            reason: field("reason").or_else(|| field("note")),
+        })
+    }
+
+    async fn emit(&self, msg_id: &str, event: &Value, ctx: &StepContext) -> Result<()> {
+        let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+        let client = async_nats::connect(&url).await?;
+        let js = async_nats::jetstream::new(client);
+        let subject = format!(
+            "demon.ritual.v1.{}.{}.{}.events",
+            ctx.tenant_id, ctx.ritual_id, ctx.run_id
+        );
+        let mut headers = async_nats::HeaderMap::new();
+        headers.insert("Nats-Msg-Id", msg_id);
+        js.publish_with_headers(subject, headers, serde_json::to_vec(event)?.into())
+            .await?
+            .await?;
+        Ok(())
+    }
+
+    async fn wait_for_cancel(&self, ctx: &StepContext) -> Result<CancelRequest> {
+        let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+        let client = async_nats::connect(&url).await?;
+        let subject = format!(
+            "demon.ritual.v1.{}.{}.{}.events",
+            ctx.tenant_id, ctx.ritual_id, ctx.run_id
+        );
+        let mut events = client.subscribe(subject).await?;
+        while let Some(msg) = events.next().await {
+            let Ok(event) = serde_json::from_slice::<Value>(&msg.payload) else {
+                continue;
+            };
+            if event.get("event").and_then(|v| v.as_str()) == Some("run.cancel.requested:v1") {
+                let field = |name: &str| event.get(name).and_then(|v| v.as_str()).map(String::from);
+                return Ok(CancelRequest {
+                    requested_by: field("requestedBy"),
+                    reason: field("reason"),
+                });
+            }
+        }
+        anyhow::bail!("cancellation subscription closed")
+    }
+
+    async fn cancel_run(&self, ctx: &StepContext) {
+        self.router.cancel_run(&ctx.run_id);
+    }
+
+    async fn release_run(&self, ctx: &StepContext) {
+        self.router.release_run(&ctx.run_id);
+    }
+}
+
+/// How often a queued run checks whether its concurrency group has freed up
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Whether a run may start walking its steps
+enum Admission {
+    /// Holding its concurrency group, if the definition declares one
+    Admitted(Option<HeldGroup>),
+    /// Halted before any step ran
+    Halted(Flow),
+}
+
+/// A concurrency group held by a run in flight
+struct HeldGroup {
+    locks: Arc<dyn ConcurrencyLocks>,
+    lease: GroupLease,
+}
+
+impl HeldGroup {
+    /// Renew the lease until dropped
+    async fn keep_alive(&self) {
+        loop {
+            tokio::time::sleep(LEASE_TTL / 3).await;
+            match self.locks.renew(&self.lease).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    warn!(run_id = %self.lease.run_id, group = %self.lease.group, "concurrency group lease lost")
+                }
+                Err(e) => {
+                    warn!(run_id = %self.lease.run_id, error = %format!("{:#}", e), "failed to renew concurrency group lease")
+                }
+            }
+        }
+    }
+
+    /// Like step events, a failed release is logged; the lease then expires
+    async fn release(self) {
+        if let Err(e) = self.locks.release(&self.lease).await {
+            warn!(run_id = %self.lease.run_id, error = %format!("{:#}", e), "failed to release concurrency group");
+        }
+    }
+}
+
+/// Whether the walk should keep going
+enum Flow {
+    Continue,
+    Halt(String),
+}
+
+/// Which limit cut an attempt short
+#[derive(Clone, Copy)]
+enum TimeoutScope {
+    Step,
+    Run,
+}
+
+impl TimeoutScope {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TimeoutScope::Step => "step",
+            TimeoutScope::Run => "run",
+        }
+    }
+}
+
+/// Why a single attempt of a step failed
+struct Failure {
+    message: String,
+    /// Capsule envelope that reported the failure, if any
+    output: Option<Value>,
+}
+
+impl From<anyhow::Error> for Failure {
+    fn from(err: anyhow::Error) -> Self {
+        Self {
+            message: format!("{:#}", err),
+            output: None,
+        }
+    }
+}
+
+/// Per-run state shared by every step, including parallel branches
+struct RunState {
+    tenant_id: String,
+    ritual_id: String,
+    run_id: String,
+    compensations: Vec<Step>,
+    /// Validated values the run was started with
+    inputs: Map<String, Value>,
+    outputs: Mutex<Map<String, Value>>,
+    timeout_seconds: Option<u64>,
+    deadline: Option<Instant>,
+    /// When each parallel branch became runnable, for `queuedMs`
+    ready: Mutex<HashMap<String, Instant>>,
+    /// Steps whose output came from a checkpoint; they are not run again
+    restored: HashSet<String>,
+    /// Approval steps currently waiting on a gate
+    awaiting_approvals: Mutex<usize>,
+    /// Persisted snapshot, when the engine has a checkpoint store
+    checkpoint: Option<tokio::sync::Mutex<RunCheckpoint>>,
+}
+
+/// Marks the run as awaiting approval until dropped
+struct AwaitingApproval<'a>(&'a RunState);
+
+impl Drop for AwaitingApproval<'_> {
+    fn drop(&mut self) {
+        let mut awaiting = self
+            .0
+            .awaiting_approvals
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        *awaiting = awaiting.saturating_sub(1);
+    }
+}
+
+impl RunState {
+    /// State for `run_id`, picking up outputs and the deadline from
+    /// `checkpoint` when resuming
+    fn new(
+        definition: &RitualDefinition,
+        run_id: String,
+        inputs: Map<String, Value>,
+        checkpoint: Option<RunCheckpoint>,
+    ) -> Self {
+        let (outputs, elapsed) = match &checkpoint {
+            Some(checkpoint) => (
+                checkpoint.outputs.clone(),
+                (chrono::Utc::now() - checkpoint.started_at)
+                    .to_std()
+                    .unwrap_or_default(),
+            ),
+            None => (Map::new(), Duration::ZERO),
+        };
+        Self {
+            tenant_id: definition
+                .tenant_id
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            ritual_id: definition.id.clone(),
+            run_id,
+            compensations: definition.compensations.clone(),
+            inputs,
+            restored: outputs.keys().cloned().collect(),
+            outputs: Mutex::new(outputs),
+            timeout_seconds: definition.timeout_seconds,
+            deadline: definition
+                .timeout_seconds
+                .map(|secs| Instant::now() + Duration::from_secs(secs).saturating_sub(elapsed)),
+            ready: Mutex::new(HashMap::new()),
+            awaiting_approvals: Mutex::new(0),
+            checkpoint: checkpoint.map(tokio::sync::Mutex::new),
+        }
+    }
+
+    fn await_approval(&self) -> AwaitingApproval<'_> {
+        *self
+            .awaiting_approvals
+            .lock()
+            .unwrap_or_else(|p| p.into_inner()) += 1;
+        AwaitingApproval(self)
+    }
+
+    /// `running` or `awaiting-approval`, depending on open approval gates
+    fn active_phase(&self) -> RunPhase {
+        let awaiting = *self
+            .awaiting_approvals
+            .lock()
+            .unwrap_or_else(|p| p.into_inner());
+        if awaiting > 0 {
+            RunPhase::AwaitingApproval
+        } else {
+            RunPhase::Running
+        }
+    }
+
+    fn context(&self, step: &Step) -> StepContext {
+        StepContext {
+            tenant_id: self.tenant_id.clone(),
+            ritual_id: self.ritual_id.clone(),
+            run_id: self.run_id.clone(),
+            step_id: step.id.clone(),
+        }
+    }
+
+    /// Context for run-level events, which belong to no particular step
+    fn run_context(&self) -> StepContext {
+        StepContext {
+            tenant_id: self.tenant_id.clone(),
+            ritual_id: self.ritual_id.clone(),
+            run_id: self.run_id.clone(),
+            step_id: String::new(),
+        }
+    }
+
+    /// Time left before the run deadline, if the definition sets one
+    fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    fn mark_ready(&self, step_id: &str) {
+        self.ready
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(step_id.to_string(), Instant::now());
+    }
+
+    /// How long `step_id` waited for a parallel slot; zero outside parallel blocks
+    fn take_queued(&self, step_id: &str) -> Duration {
+        self.ready
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(step_id)
+            .map_or(Duration::ZERO, |ready| ready.elapsed())
+    }
+
+    fn record(&self, step_id: &str, output: Value) {
+        self.outputs
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(step_id.to_string(), output);
+    }
+
+    fn output(&self, step_id: &str) -> Option<Value> {
+        self.outputs
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(step_id)
+            .cloned()
+    }
+
+    /// `step` with `${{ steps.* }}` and `${{ inputs.* }}` expressions replaced
+    /// by recorded outputs and run inputs
+    fn resolve_inputs(&self, step: &Step) -> Result<Step> {
+        let output = |root: &Root| match root {
+            Root::Inputs => Some(Value::Object(self.inputs.clone())),
+            Root::Step(id) => self.output(id),
+        };
+        let kind = match &step.kind {
+            StepKind::Capsule { capsule, args } if expressions::has_expressions(args) => {
+                StepKind::Capsule {
+                    capsule: capsule.clone(),
+                    args: expressions::resolve(args, &output)
+                        .with_context(|| format!("step '{}' input", step.id))?,
+                }
+            }
+            StepKind::Approval {
+                gate,
+                reason: Some(reason),
+                ttl_seconds,
+            } => StepKind::Approval {
+                gate: gate.clone(),
+                reason: Some(
+                    expressions::interpolate(reason, &output)
+                        .with_context(|| format!("step '{}' reason", step.id))?,
+                ),
+                ttl_seconds: *ttl_seconds,
+            },
+            other => other.clone(),
+        };
+        Ok(Step {
+            kind,
+            ..step.clone()
+        })
+    }
+}
+
+impl Engine {
+    /// Execute a typed-step ritual definition and return its completion envelope.
+    ///
+    /// `outputs.steps` maps each executed step id to its output. Steps on an
+    /// untaken condition branch are absent.
+    pub async fn run_definition_with_result(
+        &mut self,
+        definition: RitualDefinition,
+    ) -> Result<Value> {
+        self.run_definition_internal(definition, &RunInputs::default(), false)
+            .await
+    }
+
+    /// Like [`Engine::run_definition_with_result`], with values for the
+    /// definition's declared `inputs`. Invalid inputs fail before the run starts.
+    pub async fn run_definition_with_inputs(
+        &mut self,
+        definition: RitualDefinition,
+        inputs: &RunInputs,
+    ) -> Result<Value> {
+        self.run_definition_internal(definition, inputs, false)
+            .await
+    }
+
+    pub(super) async fn run_definition_internal(
+        &self,
+        definition: RitualDefinition,
+        inputs: &RunInputs,
+        emit_completion_stdout: bool,
+    ) -> Result<Value> {
+        let inputs = inputs.resolve(&definition.id, definition.inputs.as_ref())?;
+        let run_id = self.take_run_id();
+        let checkpoint = self.checkpoints.as_ref().map(|_| {
+            let tenant_id = definition.tenant_id.as_deref().unwrap_or("default");
+            let mut checkpoint = RunCheckpoint::new(&run_id, tenant_id, &definition);
+            checkpoint.inputs = inputs.clone();
+            checkpoint
+        });
+        let run = RunState::new(&definition, run_id, inputs, checkpoint);
+        self.execute_run(&definition, run, true, emit_completion_stdout)
+            .await
+    }
+
+    /// Resume every run the checkpoint store still holds as in flight, e.g.
+    /// after the engine restarted mid-run, and return their completion
+    /// envelopes. Runs that fail to resume are logged and left out.
+    pub async fn recover_runs(&self) -> Result<Vec<Value>> {
+        let Some(store) = &self.checkpoints else {
+            return Ok(Vec::new());
+        };
+        let runs = store.in_flight().await?;
+        info!(runs = runs.len(), "ritual.recovery.start");
+
+        let resumed = join_all(runs.into_iter().map(|checkpoint| async move {
+            let run_id = checkpoint.run_id.clone();
+            (run_id, self.resume_run(checkpoint).await)
+        }))
+        .await;
+
+        let mut completions = Vec::new();
+        for (run_id, result) in resumed {
+            match result {
+                Ok(evt) => completions.push(evt),
+                Err(e) => warn!(%run_id, error = %format!("{:#}", e), "ritual.recovery.failed"),
+            }
+        }
+        Ok(completions)
+    }
+
+    /// Continue a run from its last checkpoint under the same run id
+    async fn resume_run(&self, mut checkpoint: RunCheckpoint) -> Result<Value> {
+        let phase = checkpoint.phase;
+        checkpoint.recoveries += 1;
+        let definition = checkpoint.definition.clone();
+        let recoveries = checkpoint.recoveries;
+        let run = RunState::new(
+            &definition,
+            checkpoint.run_id.clone(),
+            checkpoint.inputs.clone(),
+            Some(checkpoint),
+        );
+        let mut completed: Vec<String> = run.restored.iter().cloned().collect();
+        completed.sort();
+        warn!(ritual = %run.ritual_id, run_id = %run.run_id, %phase, completed = completed.len(), "ritual.recovered");
+
+        let event = json!({
+            "event": "run.recovered:v1",
+            "ts": chrono::Utc::now().to_rfc3339(),
+            "tenantId": run.tenant_id,
+            "ritualId": run.ritual_id,
+            "runId": run.run_id,
+            "phase": phase,
+            "completedSteps": completed,
+            "recoveries": recoveries,
+        });
+        let msg_id = format!("{}:recovered:{}", run.run_id, recoveries);
+        self.emit_event(&msg_id, &event, &run.run_context()).await;
+
+        // Quota was consumed before the run left `pending`
+        let consume_quota = phase == RunPhase::Pending;
+        self.execute_run(&definition, run, consume_quota, false)
+            .await
+    }
+
+    async fn execute_run(
+        &self,
+        definition: &RitualDefinition,
+        run: RunState,
+        consume_quota: bool,
+        emit_completion_stdout: bool,
+    ) -> Result<Value> {
+        info!(ritual = %run.ritual_id, run_id = %run.run_id, steps = definition.steps.len(), "ritual.start");
+        self.persist(&run, None, None).await;
+
+        let run_ctx = run.run_context();
+        let flow = match self.acquire_group(definition, &run, &run_ctx).await {
+            Err(e) => Err(e),
+            Ok(Admission::Halted(flow)) => Ok(flow),
+            Ok(Admission::Admitted(held)) => {
+                let flow = self
+                    .run_admitted(definition, &run, &run_ctx, held.as_ref(), consume_quota)
+                    .await;
+                if let Some(held) = held {
+                    held.release().await;
+                }
+                flow
+            }
+        };
+        let (phase, reason) = match &flow {
+            Ok(Flow::Continue) => (RunPhase::Completed, None),
+            Ok(Flow::Halt(reason)) if reason == "canceled" => {
+                (RunPhase::Canceled, Some(reason.clone()))
+            }
+            Ok(Flow::Halt(reason)) => (RunPhase::Failed, Some(reason.clone())),
+            Err(e) => (RunPhase::Failed, Some(format!("{:#}", e))),
+        };
+        self.persist(&run, Some(phase), reason).await;
+        let flow = flow?;
+        self.step_runner.release_run(&run_ctx).await;
+
+        let mut outputs = run.outputs.into_inner().unwrap_or_else(|p| p.into_inner());
+        // Later steps saw the raw outputs; only the recorded completion is redacted
+        for step in definition
+            .all_steps()
+            .into_iter()
+            .chain(&definition.compensations)
+        {
+            if let (StepKind::Capsule { capsule, .. }, Some(output)) =
+                (&step.kind, outputs.get_mut(&step.id))
+            {
+                self.redactor.redact_json(Some(capsule), output);
+            }
+        }
+        let mut evt = json!({
+          "event": "ritual.completed:v1",
+          "ritualId": run.ritual_id,
+          "runId": run.run_id,
+          "tenantId": run.tenant_id,
+          "ts": chrono::Utc::now().to_rfc3339(),
+          "outputs": { "steps": outputs }
+        });
+        if !run.inputs.is_empty() {
+            evt["inputs"] = Value::Object(run.inputs);
+        }
+        if let Flow::Halt(reason) = flow {
+            evt["reason"] = json!(reason);
+        }
+        if emit_completion_stdout {
+            println!("{}", serde_json::to_string_pretty(&evt)?);
+        }
+        info!(ritual = %run.ritual_id, run_id = %run.run_id, "ritual.end");
+        Ok(evt)
+    }
+
+    /// Consume the run quota and walk the steps, renewing the run's group
+    /// lease until they finish
+    async fn run_admitted(
+        &self,
+        definition: &RitualDefinition,
+        run: &RunState,
+        run_ctx: &StepContext,
+        held: Option<&HeldGroup>,
+        consume_quota: bool,
+    ) -> Result<Flow> {
+        let quota_halt = if consume_quota {
+            self.consume_run_quota(run).await?
+        } else {
+            None
+        };
+        if let Some(reason) = quota_halt {
+            return Ok(Flow::Halt(reason));
+        }
+        self.persist(run, Some(RunPhase::Running), None).await;
+        let steps = self.run_steps(&definition.steps, run);
+        let cancel = self.step_runner.wait_for_cancel(run_ctx);
+        let walk = async {
+            match select(steps, cancel).await {
+                Either::Left((flow, _)) => flow,
+                // Dropping the steps future abandons whatever is in flight
+                Either::Right((Ok(request), _)) => Ok(self.cancel(run, run_ctx, request).await),
+                Either::Right((Err(e), steps)) => {
+                    warn!(run_id = %run.run_id, error = %e, "run cancellation unavailable");
+                    steps.await
+                }
+            }
+        };
+        match held {
+            Some(held) => match select(walk.boxed_local(), held.keep_alive().boxed_local()).await {
+                Either::Left((flow, _)) => flow,
+                Either::Right(((), _)) => unreachable!("lease renewal never finishes"),
+            },
+            None => walk.await,
+        }
+    }
+
+    /// Take the definition's concurrency group for the run. A run that finds
+    /// the group held is rejected, or waits for it (first asking the holder to
+    /// cancel with `cancelInProgress`) until the group frees up, the run is
+    /// canceled, or its deadline passes.
+    async fn acquire_group(
+        &self,
+        definition: &RitualDefinition,
+        run: &RunState,
+        ctx: &StepContext,
+    ) -> Result<Admission> {
+        let Some(concurrency) = &definition.concurrency else {
+            return Ok(Admission::Admitted(None));
+        };
+        let group = concurrency.resolve_group(&run.inputs)?;
+        let locks: Arc<dyn ConcurrencyLocks> = match &self.concurrency {
+            Some(locks) => locks.clone(),
+            None => {
+                let url = std::env::var("NATS_URL")
+                    .unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+                Arc::new(KvConcurrencyLocks::connect(&url).await?)
+            }
+        };
+        let lease = GroupLease::new(&run.tenant_id, &group, &run.ritual_id, &run.run_id);
+        let mut cancel = Some(self.step_runner.wait_for_cancel(ctx));
+        let mut superseded: Option<String> = None;
+        let mut queued = false;
+        loop {
+            let Some(holder) = locks.try_acquire(&lease).await? else {
+                info!(run_id = %run.run_id, %group, "ritual.concurrency.acquired");
+                return Ok(Admission::Admitted(Some(HeldGroup { locks, lease })));
+            };
+            if concurrency.on_conflict == OnConflict::Reject {
+                warn!(run_id = %run.run_id, %group, holder = %holder.run_id, "ritual.concurrency.rejected");
+                return Ok(Admission::Halted(Flow::Halt(
+                    "concurrency_rejected".to_string(),
+                )));
+            }
+            if concurrency.cancel_in_progress && superseded.as_ref() != Some(&holder.run_id) {
+                self.supersede(&holder, run).await;
+                superseded = Some(holder.run_id.clone());
+            }
+            if !queued {
+                info!(run_id = %run.run_id, %group, holder = %holder.run_id, "ritual.concurrency.queued");
+                queued = true;
+            }
+
+            let mut poll = QUEUE_POLL_INTERVAL;
+            if let Some(remaining) = run.remaining() {
+                if remaining.is_zero() {
+                    warn!(run_id = %run.run_id, %group, "ritual.deadline_exceeded");
+                    return Ok(Admission::Halted(Flow::Halt(
+                        "deadline_exceeded".to_string(),
+                    )));
+                }
+                poll = poll.min(remaining);
+            }
+            let requested = match cancel.as_mut() {
+                Some(watch) => match select(tokio::time::sleep(poll).boxed(), watch).await {
+                    Either::Left(_) => None,
+                    Either::Right((request, _)) => Some(request),
+                },
+                None => {
+                    tokio::time::sleep(poll).await;
+                    None
+                }
+            };
+            match requested {
+                Some(Ok(request)) => {
+                    return Ok(Admission::Halted(self.cancel(run, ctx, request).await));
+                }
+                Some(Err(e)) => {
+                    warn!(run_id = %run.run_id, error = %e, "run cancellation unavailable");
+                    cancel = None;
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Ask the run holding a group to cancel so `run` can take it over
+    async fn supersede(&self, holder: &GroupLease, run: &RunState) {
+        warn!(run_id = %run.run_id, superseded = %holder.run_id, group = %holder.group, "ritual.concurrency.superseding");
+        let event = json!({
+            "event": "run.cancel.requested:v1",
+            "ts": chrono::Utc::now().to_rfc3339(),
+            "tenantId": holder.tenant_id,
+            "ritualId": holder.ritual_id,
+            "runId": holder.run_id,
+            "requestedBy": format!("concurrency:{}", holder.group),
+            "reason": format!("superseded by run {}", run.run_id),
+        });
+        let ctx = StepContext {
+            tenant_id: holder.tenant_id.clone(),
+            ritual_id: holder.ritual_id.clone(),
+            run_id: holder.run_id.clone(),
+            step_id: String::new(),
+        };
+        let msg_id = format!("{}:cancel:superseded:{}", holder.run_id, run.run_id);
+        self.emit_event(&msg_id, &event, &ctx).await;
+    }
+
+    /// Write the run's snapshot, first moving it to `next` (or, for an active
+    /// run, to whichever of `running`/`awaiting-approval` applies) and
+    /// publishing `run.state.changed:v1`. Like step events, a failed write is
+    /// logged and never fails the run.
+    async fn persist(&self, run: &RunState, next: Option<RunPhase>, reason: Option<String>) {
+        let (Some(store), Some(checkpoint)) = (&self.checkpoints, &run.checkpoint) else {
+            return;
+        };
+        let mut checkpoint = checkpoint.lock().await;
+        let next = match next {
+            Some(next) => next,
+            None if matches!(
+                checkpoint.phase,
+                RunPhase::Running | RunPhase::AwaitingApproval
+            ) =>
+            {
+                run.active_phase()
+            }
+            None => checkpoint.phase,
+        };
+        let previous = checkpoint.phase;
+        if next != previous {
+            if let Err(e) = checkpoint.transition(next) {
+                warn!(run_id = %run.run_id, error = %e, "invalid run state transition");
+                return;
+            }
+        }
+        if reason.is_some() {
+            checkpoint.reason = reason;
+        }
+        checkpoint.outputs = run
+            .outputs
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone();
+        checkpoint.touch();
+        if let Err(e) = store.save(&checkpoint).await {
+            warn!(run_id = %run.run_id, error = %format!("{:#}", e), "failed to save run checkpoint");
+        }
+        if next == previous {
+            return;
+        }
+
+        info!(run_id = %run.run_id, from = %previous, to = %next, "run.state.changed");
+        let mut event = json!({
+            "event": "run.state.changed:v1",
+            "ts": checkpoint.updated_at.to_rfc3339(),
+            "tenantId": run.tenant_id,
+            "ritualId": run.ritual_id,
+            "runId": run.run_id,
+            "from": previous,
+            "to": next,
+            "revision": checkpoint.revision,
+        });
+        if let Some(reason) = &checkpoint.reason {
+            event["reason"] = json!(reason);
+        }
+        let msg_id = format!("{}:state:{}", run.run_id, checkpoint.revision);
+        self.emit_event(&msg_id, &event, &run.run_context()).await;
+    }
+
+    async fn cancel(&self, run: &RunState, ctx: &StepContext, request: CancelRequest) -> Flow {
+        warn!(run_id = %run.run_id, requested_by = ?request.requested_by, "ritual.canceled");
+        self.step_runner.cancel_run(ctx).await;
+        let completed: Vec<String> = run
+            .outputs
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        let event = json!({
+            "event": "run.canceled:v1",
+            "ts": chrono::Utc::now().to_rfc3339(),
+            "tenantId": run.tenant_id,
+            "ritualId": run.ritual_id,
+            "runId": run.run_id,
+            "requestedBy": request.requested_by,
+            "reason": request.reason,
+            "completedSteps": completed,
+        });
+        let msg_id = format!("{}:canceled", run.run_id);
+        self.emit_event(&msg_id, &event, ctx).await;
+        Flow::Halt("canceled".to_string())
+    }
+
+    fn run_steps<'a>(
+        &'a self,
+        steps: &'a [Step],
+        run: &'a RunState,
+    ) -> LocalBoxFuture<'a, Result<Flow>> {
+        async move {
+            for step in steps {
+                if run.remaining() == Some(Duration::ZERO) {
+                    warn!(run_id = %run.run_id, step = %step.id, "ritual.deadline_exceeded");
+                    return Ok(Flow::Halt("deadline_exceeded".to_string()));
+                }
+                if let Flow::Halt(reason) = self.run_step(step, run).await? {
+                    return Ok(Flow::Halt(reason));
+                }
+            }
+            Ok(Flow::Continue)
+        }
+        .boxed_local()
+    }
+
+    async fn run_step(&self, step: &Step, run: &RunState) -> Result<Flow> {
+        // A restored condition is walked again so its branch can resume
+        if run.restored.contains(&step.id) && !matches!(step.kind, StepKind::Condition { .. }) {
+            info!(run_id = %run.run_id, step = %step.id, "step.restored");
+            return Ok(Flow::Continue);
+        }
+        info!(run_id = %run.run_id, step = %step.id, kind = step.kind.as_str(), "step.start");
+
+        let flow = match &step.kind {
+            StepKind::Condition {
+                when,
+                then,
+                otherwise,
+            } => {
+                let matched = when.evaluate(run.output(&when.step).as_ref());
+                run.record(&step.id, json!({ "matched": matched }));
+                let branch = if matched { then } else { otherwise };
+                self.run_steps(branch, run).await
+            }
+            StepKind::Parallel {
+                steps,
+                max_concurrency,
+                join,
+            } => {
+                let limit = max_concurrency.unwrap_or(self.parallel_limit);
+                self.run_parallel(step, steps, limit, *join, run).await
+            }
+            _ => self.run_with_retry(step, run).await,
+        }?;
+        // A halted step's output is only persisted with the terminal phase, so
+        // a resumed run never skips past it
+        if matches!(flow, Flow::Continue) {
+            self.persist(run, None, None).await;
+        }
+        Ok(flow)
+    }
+
+    /// Fan out branches with at most `limit` in flight and stop as soon as
+    /// `join` is satisfied or can no longer be; unfinished branches are dropped
+    async fn run_parallel(
+        &self,
+        step: &Step,
+        branches: &[Step],
+        limit: usize,
+        join: Join,
+        run: &RunState,
+    ) -> Result<Flow> {
+        let required = join.required(branches.len());
+        for child in branches {
+            run.mark_ready(&child.id);
+        }
+        let mut pending = stream::iter(branches.iter().map(|child| {
+            self.run_steps(std::slice::from_ref(child), run)
+                .map(move |flow| (child, flow))
+        }))
+        .buffer_unordered(limit.max(1));
+
+        let mut succeeded = Vec::new();
+        let mut halted = Vec::new();
+        while let Some((child, flow)) = pending.next().await {
+            match flow? {
+                Flow::Continue => succeeded.push(child.id.clone()),
+                Flow::Halt(reason) => halted.push((child.id.clone(), reason)),
+            }
+            if succeeded.len() >= required || branches.len() - halted.len() < required {
+                break;
+            }
+        }
+        drop(pending);
+
+        let cancelled = branches.len() - succeeded.len() - halted.len();
+        info!(run_id = %run.run_id, step = %step.id, join = join.as_str(), succeeded = succeeded.len(), halted = halted.len(), cancelled, "parallel.join");
+        run.record(
+            &step.id,
+            json!({
+                "branches": branches.len(),
+                "join": join.as_str(),
+                "required": required,
+                "succeeded": succeeded,
+                "halted": halted.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+                "cancelled": cancelled,
+            }),
+        );
+
+        if succeeded.len() >= required {
+            return Ok(Flow::Continue);
+        }
+        match join {
+            // Surface the failing branch's own reason, e.g. approval_denied
+            Join::All => Ok(Flow::Halt(
+                halted
+                    .into_iter()
+                    .next()
+                    .map(|(_, reason)| reason)
+                    .unwrap_or_else(|| "join_not_met".to_string()),
+            )),
+            Join::Any | Join::Quorum(_) => Ok(Flow::Halt("join_not_met".to_string())),
+        }
+    }
+
+    /// Attempt a leaf step up to `retry.maxAttempts` times, then apply `onFailure`
+    async fn run_with_retry(&self, step: &Step, run: &RunState) -> Result<Flow> {
+        let ctx = run.context(step);
+        let step = match run.resolve_inputs(step) {
+            Ok(resolved) => resolved,
+            Err(err) => {
+                // Never attempted: retrying cannot change earlier outputs
+                warn!(run_id = %run.run_id, step = %step.id, error = %format!("{:#}", err), "step.input.invalid");
+                return self.fail_step(step, run, &ctx, err.into(), 0).await;
+            }
+        };
+        let step = &step;
+        let max_attempts = step.retry.as_ref().map_or(1, |r| r.max_attempts.max(1));
+        let mut queued = run.take_queued(&step.id);
+        let mut attempt = 1;
+        let failure = loop {
+            self.emit_step_started(step, run, &ctx, attempt, queued)
+                .await;
+            queued = Duration::ZERO;
+            let started = Instant::now();
+            let outcome = self.attempt_with_timeout(step, &ctx, run, attempt).await;
+            self.emit_step_completed(step, run, &ctx, attempt, started.elapsed(), &outcome)
+                .await;
+            let failure = match outcome {
+                Ok(flow) => return Ok(flow),
+                Err(failure) => failure,
+            };
+            let Some(retry) = step.retry.as_ref().filter(|_| attempt < max_attempts) else {
+                break failure;
+            };
+            let delay = retry.delay_for(attempt)?;
+            warn!(run_id = %run.run_id, step = %step.id, attempt, max_attempts, error = %failure.message, "step.retry");
+            let event = json!({
+                "event": "step.retried:v1",
+                "ts": chrono::Utc::now().to_rfc3339(),
+                "tenantId": run.tenant_id,
+                "ritualId": run.ritual_id,
+                "runId": run.run_id,
+                "stepId": step.id,
+                "attempt": attempt,
+                "maxAttempts": max_attempts,
+                "delayMs": delay.as_millis() as u64,
+                "error": failure.message,
+            });
+            let msg_id = format!("{}:step:{}:retried:{}", run.run_id, step.id, attempt);
+            self.emit_event(&msg_id, &event, &ctx).await;
+            self.step_runner.sleep(delay).await;
+            attempt += 1;
+        };
+
+        warn!(run_id = %run.run_id, step = %step.id, attempts = attempt, error = %failure.message, "step.failed");
+        self.fail_step(step, run, &ctx, failure, attempt).await
+    }
+
+    /// `step.started:v1`: an attempt of a leaf step is about to run
+    async fn emit_step_started(
+        &self,
+        step: &Step,
+        run: &RunState,
+        ctx: &StepContext,
+        attempt: u32,
+        queued: Duration,
+    ) {
+        let event = json!({
+            "event": "step.started:v1",
+            "ts": chrono::Utc::now().to_rfc3339(),
+            "tenantId": run.tenant_id,
+            "ritualId": run.ritual_id,
+            "runId": run.run_id,
+            "stepId": step.id,
+            "kind": step.kind.as_str(),
+            "attempt": attempt,
+            "queuedMs": queued.as_millis() as u64,
+        });
+        let msg_id = format!("{}:step:{}:started:{}", run.run_id, step.id, attempt);
+        self.emit_event(&msg_id, &event, ctx).await;
+    }
+
+    /// `step.completed:v1`: how an attempt ended and how long it took
+    async fn emit_step_completed(
+        &self,
+        step: &Step,
+        run: &RunState,
+        ctx: &StepContext,
+        attempt: u32,
+        elapsed: Duration,
+        outcome: &std::result::Result<Flow, Failure>,
+    ) {
+        let mut event = json!({
+            "event": "step.completed:v1",
+            "ts": chrono::Utc::now().to_rfc3339(),
+            "tenantId": run.tenant_id,
+            "ritualId": run.ritual_id,
+            "runId": run.run_id,
+            "stepId": step.id,
+            "kind": step.kind.as_str(),
+            "attempt": attempt,
+            "durationMs": elapsed.as_millis() as u64,
+        });
+        match outcome {
+            Ok(Flow::Continue) => event["outcome"] = json!("succeeded"),
+            Ok(Flow::Halt(reason)) => {
+                event["outcome"] = json!("halted");
+                event["reason"] = json!(reason);
+            }
+            Err(failure) => {
+                event["outcome"] = json!("failed");
+                event["error"] = json!(failure.message);
+            }
+        }
+        let msg_id = format!("{}:step:{}:completed:{}", run.run_id, step.id, attempt);
+        self.emit_event(&msg_id, &event, ctx).await;
+    }
+
+    /// Record a step that failed after `attempt` attempts and apply `onFailure`
+    async fn fail_step(
+        &self,
+        step: &Step,
+        run: &RunState,
+        ctx: &StepContext,
+        failure: Failure,
+        attempt: u32,
+    ) -> Result<Flow> {
+        run.record(
+            &step.id,
+            failure
+                .output
+                .unwrap_or_else(|| json!({ "error": failure.message, "attempts": attempt })),
+        );
+        match &step.on_failure {
+            OnFailure::Abort => Ok(Flow::Halt("step_failed".to_string())),
+            OnFailure::Continue => Ok(Flow::Continue),
+            OnFailure::Compensate(target) => {
+                let compensation = run
+                    .compensations
+                    .iter()
+                    .find(|s| &s.id == target)
+                    .with_context(|| format!("unknown compensation step '{}'", target))?;
+                let outcome = match self
+                    .run_steps(std::slice::from_ref(compensation), run)
+                    .await?
+                {
+                    Flow::Continue => "completed".to_string(),
+                    Flow::Halt(reason) => reason,
+                };
+                let event = json!({
+                    "event": "step.compensated:v1",
+                    "ts": chrono::Utc::now().to_rfc3339(),
+                    "tenantId": run.tenant_id,
+                    "ritualId": run.ritual_id,
+                    "runId": run.run_id,
+                    "stepId": step.id,
+                    "compensationStepId": compensation.id,
+                    "attempts": attempt,
+                    "error": failure.message,
+                    "outcome": outcome,
+                });
+                let msg_id = format!("{}:step:{}:compensated", run.run_id, step.id);
+                self.emit_event(&msg_id, &event, ctx).await;
+                Ok(Flow::Halt("compensated".to_string()))
+            }
+        }
+    }
+
+    /// `attempt_step` bounded by the step's `timeoutSeconds` and the run deadline
+    async fn attempt_with_timeout(
+        &self,
+        step: &Step,
+        ctx: &StepContext,
+        run: &RunState,
+        attempt: u32,
+    ) -> std::result::Result<Flow, Failure> {
+        let step_limit = step.timeout_seconds.map(Duration::from_secs);
+        let (budget, scope) = match (step_limit, run.remaining()) {
+            (None, None) => return self.attempt_step(step, ctx, run, attempt, None).await,
+            (Some(limit), Some(left)) if left < limit => (left, TimeoutScope::Run),
+            (Some(limit), _) => (limit, TimeoutScope::Step),
+            (None, Some(left)) => (left, TimeoutScope::Run),
+        };
+        if !budget.is_zero() {
+            let attempt_fut = self.attempt_step(step, ctx, run, attempt, Some(budget));
+            if let Ok(outcome) = tokio::time::timeout(budget, attempt_fut).await {
+                return outcome;
+            }
+        }
+
+        let timeout_seconds = match scope {
+            TimeoutScope::Step => step.timeout_seconds,
+            TimeoutScope::Run => run.timeout_seconds,
+        };
+        warn!(run_id = %run.run_id, step = %step.id, attempt, scope = scope.as_str(), budget_ms = budget.as_millis() as u64, "step.timeout");
+        let event = json!({
+            "event": "step.timeout:v1",
+            "ts": chrono::Utc::now().to_rfc3339(),
+            "tenantId": run.tenant_id,
+            "ritualId": run.ritual_id,
+            "runId": run.run_id,
+            "stepId": step.id,
+            "attempt": attempt,
+            "scope": scope.as_str(),
+            "timeoutSeconds": timeout_seconds,
+            "budgetMs": budget.as_millis() as u64,
+        });
+        let msg_id = format!("{}:step:{}:timeout:{}", run.run_id, step.id, attempt);
+        self.emit_event(&msg_id, &event, ctx).await;
+
+        match scope {
+            TimeoutScope::Step => Err(Failure {
+                message: format!("step '{}' timed out after {}s", step.id, budget.as_secs()),
+                output: None,
+            }),
+            // Out of run time: retries and onFailure handlers could not run either
+            TimeoutScope::Run => {
+                run.record(
+                    &step.id,
+                    json!({ "error": "run deadline exceeded", "attempts": attempt }),
+                );
+                Ok(Flow::Halt("deadline_exceeded".to_string()))
+            }
+        }
+    }
+
+    /// One attempt of a capsule, approval or timer step; `budget` is the time
+    /// the attempt has before it is abandoned
+    async fn attempt_step(
+        &self,
+        step: &Step,
+        ctx: &StepContext,
+        run: &RunState,
+        attempt: u32,
+        budget: Option<Duration>,
+    ) -> std::result::Result<Flow, Failure> {
+        match &step.kind {
+            StepKind::Capsule { capsule, args } => {
+                let key = LedgerKey::new(&run.run_id, &step.id, attempt);
+                let output = match self.recorded_envelope(&key).await {
+                    Some(output) => output,
+                    None => {
+                        let args = &with_budget(capsule, args, budget);
+                        if let Some(reason) = self.guard_capsule(capsule, args, run).await? {
+                            return Ok(Flow::Halt(reason));
+                        }
+                        let output = self
+                            .step_runner
+                            .invoke_capsule(capsule, args, ctx)
+                            .await
+                            .with_context(|| format!("step '{}' ({})", step.id, capsule))?;
+                        self.record_envelope(&key, &output).await;
+                        output
+                    }
+                };
+                if output.pointer("/result/success") == Some(&Value::Bool(false)) {
+                    let message = output
+                        .pointer("/result/error/message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("capsule reported failure")
+                        .to_string();
+                    return Err(Failure {
+                        message,
+                        output: Some(output),
+                    });
+                }
+                run.record(&step.id, output);
+            }
+            StepKind::Approval {
+                gate,
+                reason,
+                ttl_seconds,
+            } => {
+                let reason = reason
+                    .clone()
+                    .unwrap_or_else(|| format!("ritual step {}", step.id));
+                let awaiting = run.await_approval();
+                self.persist(run, None, None).await;
+                let outcome = self
+                    .step_runner
+                    .await_approval(gate, &reason, *ttl_seconds, ctx)
+                    .await
+                    .with_context(|| format!("step '{}' (approval {})", step.id, gate))?;
+                drop(awaiting);
+                run.record(
+                    &step.id,
+                    json!({
+                        "gateId": gate,
+                        "granted": outcome.granted,
+                        "approver": outcome.approver,
+                        "reason": outcome.reason,
+                    }),
+                );
+                if !outcome.granted {
+                    return Ok(Flow::Halt("approval_denied".to_string()));
+                }
+            }
+            StepKind::Timer { delay } => {
+                self.step_runner.sleep(parse_delay(delay)?).await;
+                run.record(
+                    &step.id,
+                    json!({ "delay": delay, "firedAt": chrono::Utc::now().to_rfc3339() }),
+                );
+            }
+            StepKind::Condition { .. } | StepKind::Parallel { .. } => {
+                unreachable!("composite steps are walked by run_step")
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Envelope an earlier execution of this attempt recorded in the ledger
+    async fn recorded_envelope(&self, key: &LedgerKey) -> Option<Value> {
+        let ledger = self.ledger.as_ref()?;
+        match ledger.get(key).await {
+            Ok(Some(envelope)) => {
+                info!(ledger_key = %key, "step.deduplicated");
+                Some(envelope)
+            }
+            Ok(None) => None,
+            // Unavailable ledger: executing beats stalling the run
+            Err(e) => {
+                warn!(ledger_key = %key, error = %format!("{:#}", e), "execution ledger lookup failed");
+                None
+            }
+        }
+    }
+
+    async fn record_envelope(&self, key: &LedgerKey, envelope: &Value) {
+        let Some(ledger) = &self.ledger else {
+            return;
+        };
+        if let Err(e) = ledger.record(key, envelope).await {
+            warn!(ledger_key = %key, error = %format!("{:#}", e), "failed to record step envelope");
+        }
+    }
+
+    /// Step events are informational; a publish failure never fails the run
+    async fn emit_event(&self, msg_id: &str, event: &Value, ctx: &StepContext) {
+        if let Err(e) = self.step_runner.emit(msg_id, event, ctx).await {
+            warn!(run_id = %ctx.run_id, step = %ctx.step_id, error = %e, "failed to publish step event");
+        }
+    }
+
+    /// Consume the tenant's `runs` quota once per definition run
+    async fn consume_run_quota(&self, run: &RunState) -> Result<Option<String>> {
+        let Some(quotas) = &self.tenant_quotas else {
+            return Ok(None);
+        };
+        let decision = quotas
+            .check_and_consume(&run.tenant_id, QuotaResource::Runs, 1)
+            .await?;
+        if decision.allowed {
+            return Ok(None);
+        }
+        warn!(ritual = %run.ritual_id, run_id = %run.run_id, tenant_id = %run.tenant_id, "ritual denied due to tenant quota");
+        if let Some(log) = &self.decision_log {
+            let record =
+                WardsDecision::quota_rejected(&decision).for_run(&run.ritual_id, &run.run_id);
+            log.record_best_effort(&record).await;
+        }
+        Ok(Some("quota_exceeded".to_string()))
+    }
+
+    /// Capability policy and per-tenant quotas for one capsule step
+    async fn guard_capsule(
+        &self,
+        capsule: &str,
+        args: &Value,
+        run: &RunState,
+    ) -> Result<Option<String>> {
+        if let Some(kernel) = &self.policy_kernel {
+            let decision = kernel
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .allow_and_count(&run.tenant_id, capsule);
+            if !decision.allowed {
+                warn!(run_id = %run.run_id, capability = %capsule, "step denied due to quota limits");
+                if let Some(log) = &self.decision_log {
+                    let record = WardsDecision::capability_quota_rejected(
+                        &run.tenant_id,
+                        capsule,
+                        &decision,
+                    )
+                    .for_run(&run.ritual_id, &run.run_id);
+                    log.record_best_effort(&record).await;
+                }
+                return Ok(Some("policy_denied".to_string()));
+            }
+        }
+
+        if let Some(quotas) = &self.tenant_quotas {
+            for resource in quota_resources(capsule, args)
+                .into_iter()
+                .filter(|r| *r != QuotaResource::Runs)
+            {
+                let decision = quotas
+                    .check_and_consume(&run.tenant_id, resource, 1)
+                    .await?;
+                if decision.allowed {
+                    continue;
+                }
+                warn!(run_id = %run.run_id, resource = %resource, "step denied due to tenant quota");
+                if let Some(log) = &self.decision_log {
+                    let record = WardsDecision::quota_rejected(&decision)
+                        .for_run(&run.ritual_id, &run.run_id);
+                    log.record_best_effort(&record).await;
+                }
+                return Ok(Some("quota_exceeded".to_string()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Cap `container-exec`'s own `timeoutSeconds` at the attempt's budget so the
+/// container is stopped when the engine stops waiting for it
+fn with_budget(capsule: &str, args: &Value, budget: Option<Duration>) -> Value {
+    let (Some(budget), "container-exec", Value::Object(map)) = (budget, capsule, args) else {
+        return args.clone();
+    };
+    let budget_secs = budget.as_secs_f64().ceil().max(1.0) as u64;
+    let timeout = map
+        .get("timeoutSeconds")
+        .and_then(|v| v.as_u64())
+        .map_or(budget_secs, |own| own.min(budget_secs));
+    let mut map = map.clone();
+    map.insert("timeoutSeconds".to_string(), json!(timeout));
+    Value::Object(map)
+}
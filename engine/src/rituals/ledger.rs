@@ -0,0 +1,180 @@
+//! Execution ledger for idempotent capsule steps
+//!
+//! Before a capsule step is invoked the engine looks up
+//! `runId:stepId:attempt` in the ledger. If an envelope was already recorded
+//! for that attempt — because a redelivered message or a recovered run is
+//! executing it again — the stored envelope is used and the capsule is not
+//! called. Otherwise the envelope the capsule returns is recorded before the
+//! run moves on.
+//!
+//! The KV-backed ledger lives in the `RITUAL_LEDGER` bucket (override with
+//! `RITUAL_LEDGER_BUCKET`). KV keys cannot contain `:`, so entries are stored
+//! under `runId.stepId.attempt`.
+
+use anyhow::{Context, Result};
+use async_nats::jetstream::{self, kv};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+
+/// One attempt of one step of one run
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LedgerKey {
+    pub run_id: String,
+    pub step_id: String,
+    pub attempt: u32,
+}
+
+impl LedgerKey {
+    pub fn new(run_id: &str, step_id: &str, attempt: u32) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            step_id: step_id.to_string(),
+            attempt,
+        }
+    }
+
+    /// Key in the KV bucket
+    pub fn kv_key(&self) -> String {
+        format!("{}.{}.{}", self.run_id, self.step_id, self.attempt)
+    }
+}
+
+impl fmt::Display for LedgerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.run_id, self.step_id, self.attempt)
+    }
+}
+
+/// Envelopes recorded per step attempt
+#[async_trait]
+pub trait ExecutionLedger: Send + Sync {
+    async fn get(&self, key: &LedgerKey) -> Result<Option<Value>>;
+
+    async fn record(&self, key: &LedgerKey, envelope: &Value) -> Result<()>;
+}
+
+/// Ledger in a JetStream KV bucket
+pub struct KvExecutionLedger {
+    store: kv::Store,
+}
+
+impl KvExecutionLedger {
+    /// Open (or create) the bucket named by `RITUAL_LEDGER_BUCKET`, default
+    /// `RITUAL_LEDGER`
+    pub async fn connect(nats_url: &str) -> Result<Self> {
+        let bucket =
+            std::env::var("RITUAL_LEDGER_BUCKET").unwrap_or_else(|_| "RITUAL_LEDGER".to_string());
+        let client = async_nats::connect(nats_url)
+            .await
+            .context("Failed to connect to NATS")?;
+        let js = jetstream::new(client);
+        let store = match js.get_key_value(&bucket).await {
+            Ok(store) => store,
+            Err(_) => js
+                .create_key_value(kv::Config {
+                    bucket: bucket.clone(),
+                    description: "Ritual step execution ledger".to_string(),
+                    history: 1,
+                    ..Default::default()
+                })
+                .await
+                .with_context(|| format!("creating KV bucket {bucket}"))?,
+        };
+        Ok(Self { store })
+    }
+}
+
+#[async_trait]
+impl ExecutionLedger for KvExecutionLedger {
+    async fn get(&self, key: &LedgerKey) -> Result<Option<Value>> {
+        let Some(bytes) = self.store.get(key.kv_key()).await? else {
+            return Ok(None);
+        };
+        let envelope = serde_json::from_slice(&bytes)
+            .with_context(|| format!("parsing ledger entry {}", key))?;
+        Ok(Some(envelope))
+    }
+
+    async fn record(&self, key: &LedgerKey, envelope: &Value) -> Result<()> {
+        // An update expecting revision 0 fails if another executor recorded
+        // the attempt first; the first envelope wins either way
+        if let Err(e) = self
+            .store
+            .update(key.kv_key(), serde_json::to_vec(envelope)?.into(), 0)
+            .await
+        {
+            if self.get(key).await?.is_none() {
+                return Err(anyhow::anyhow!("recording ledger entry {}: {}", key, e));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Process-local ledger; useful for tests
+#[derive(Default)]
+pub struct MemoryExecutionLedger {
+    entries: Mutex<HashMap<LedgerKey, Value>>,
+}
+
+impl MemoryExecutionLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ExecutionLedger for MemoryExecutionLedger {
+    async fn get(&self, key: &LedgerKey) -> Result<Option<Value>> {
+        Ok(self
+            .entries
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(key)
+            .cloned())
+    }
+
+    async fn record(&self, key: &LedgerKey, envelope: &Value) -> Result<()> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .entry(key.clone())
+            .or_insert_with(|| envelope.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn key_formats_for_display_and_kv() {
+        let key = LedgerKey::new("run-1", "build", 2);
+
+        assert_eq!(key.to_string(), "run-1:build:2");
+        assert_eq!(key.kv_key(), "run-1.build.2");
+    }
+
+    #[tokio::test]
+    async fn memory_ledger_keeps_first_envelope() {
+        let ledger = MemoryExecutionLedger::new();
+        let key = LedgerKey::new("run-1", "build", 1);
+
+        ledger.record(&key, &json!({ "n": 1 })).await.unwrap();
+        ledger.record(&key, &json!({ "n": 2 })).await.unwrap();
+
+        assert_eq!(ledger.get(&key).await.unwrap(), Some(json!({ "n": 1 })));
+        assert_eq!(
+            ledger
+                .get(&LedgerKey::new("run-1", "build", 2))
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
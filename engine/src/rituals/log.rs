@@ -4,6 +4,7 @@ fn default_tenant() -> String {
 }
 use async_nats::jetstream::{self, consumer::PullConsumer, stream::Stream};
 use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tracing::{debug, info};
@@ -208,6 +209,82 @@ impl EventLog {
         run_id: &str,
         tenant_id: Option<&str>,
     ) -> Result<Vec<RitualEvent>> {
+        self.read_run_as(ritual_id, run_id, tenant_id).await
+    }
+
+    /// Every event of a run as raw JSON, including events `RitualEvent` does
+    /// not model (step, approval and cancellation events)
+    pub async fn read_run_events(
+        &self,
+        ritual_id: &str,
+        run_id: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<Value>> {
+        self.read_run_as(ritual_id, run_id, tenant_id).await
+    }
+
+    /// Raw events of every run for `tenant_id`, oldest first. Runs recorded on
+    /// legacy subjects are included for the default tenant.
+    pub async fn read_tenant_events(&self, tenant_id: &str) -> Result<Vec<Value>> {
+        let filter_subject = format!("demon.ritual.v1.{}.*.*.events", tenant_id);
+        let mut events = self.read_run_internal(&filter_subject, tenant_id).await?;
+        if tenant_id == DEFAULT_TENANT {
+            // Best effort: streams created without legacy subjects reject the filter
+            if let Ok(legacy) = self
+                .read_run_internal::<Value>("demon.ritual.v1.*.*.events", tenant_id)
+                .await
+            {
+                events.extend(legacy);
+            }
+        }
+        Ok(events)
+    }
+
+    /// A run's events as they are published: everything already recorded,
+    /// then new events until the stream is dropped. `None` matches any
+    /// tenant; legacy subjects are not followed.
+    pub async fn follow_run(
+        &self,
+        tenant_id: Option<&str>,
+        run_id: &str,
+    ) -> Result<futures_util::stream::BoxStream<'static, Result<Value>>> {
+        let filter_subject = format!(
+            "demon.ritual.v1.{}.*.{}.events",
+            tenant_id.unwrap_or("*"),
+            run_id
+        );
+        let consumer: PullConsumer = self
+            .stream
+            .create_consumer(jetstream::consumer::pull::Config {
+                filter_subject,
+                deliver_policy: jetstream::consumer::DeliverPolicy::All,
+                ack_policy: jetstream::consumer::AckPolicy::None,
+                // The server drops the consumer soon after the follower goes away
+                inactive_threshold: std::time::Duration::from_secs(30),
+                ..Default::default()
+            })
+            .await
+            .context("Failed to create consumer to follow run")?;
+        let messages = consumer
+            .messages()
+            .await
+            .context("Failed to follow run events")?;
+
+        Ok(messages
+            .map(|message| {
+                let message =
+                    message.map_err(|e| anyhow::anyhow!("Failed to receive run event: {}", e))?;
+                serde_json::from_slice(&message.payload).context("Failed to parse run event")
+            })
+            .boxed())
+    }
+
+    async fn read_run_as<T: DeserializeOwned>(
+        &self,
+        ritual_id: &str,
+        run_id: &str,
+        tenant_id: Option<&str>,
+    ) -> Result<Vec<T>> {
         let tenant = tenant_id.unwrap_or(DEFAULT_TENANT);
 
         // Try new tenant-scoped subject first
@@ -223,11 +300,11 @@ impl EventLog {
         Ok(events)
     }
 
-    async fn read_run_internal(
+    async fn read_run_internal<T: DeserializeOwned>(
         &self,
         filter_subject: &str,
-        run_id: &str,
-    ) -> Result<Vec<RitualEvent>> {
+        label: &str,
+    ) -> Result<Vec<T>> {
         // Create truly ephemeral pull consumer (no name = auto-generated)
         // This allows concurrent reads and prevents consumer conflicts
         let mut consumer: PullConsumer = self
@@ -242,7 +319,7 @@ impl EventLog {
             .context("Failed to create ephemeral consumer")?;
 
         // Ensure consumer cleanup happens regardless of success or failure
-        let result = self.read_messages_with_cleanup(&mut consumer, run_id).await;
+        let result = self.read_messages_with_cleanup(&mut consumer, label).await;
 
         // Always attempt cleanup, even if reading failed
         if let Ok(info) = consumer.info().await {
@@ -253,11 +330,11 @@ impl EventLog {
         result
     }
 
-    async fn read_messages_with_cleanup(
+    async fn read_messages_with_cleanup<T: DeserializeOwned>(
         &self,
         consumer: &mut PullConsumer,
-        run_id: &str,
-    ) -> Result<Vec<RitualEvent>> {
+        label: &str,
+    ) -> Result<Vec<T>> {
         let mut events = Vec::new();
 
         // Fetch messages in batches to avoid infinite blocking
@@ -315,7 +392,7 @@ impl EventLog {
                 batch_empty = false;
                 match msg_result {
                     Ok(msg) => {
-                        let event: RitualEvent = serde_json::from_slice(&msg.message.payload)
+                        let event: T = serde_json::from_slice(&msg.message.payload)
                             .context("Failed to deserialize event")?;
                         events.push(event);
                         let _ = msg.ack().await; // Best effort ack
@@ -336,7 +413,7 @@ impl EventLog {
             }
         }
 
-        debug!("Read {} events for run {}", events.len(), run_id);
+        debug!("Read {} events for {}", events.len(), label);
 
         Ok(events)
     }
@@ -1,20 +1,42 @@
 //! Minimal ritual interpreter for Milestone 0 (single task with end=true)
 
 pub mod approvals;
+pub mod checkpoint;
+pub mod concurrency;
+pub mod cron;
+pub mod definition;
+pub mod dlq;
 pub mod escalation;
+pub mod expressions;
 pub mod guards;
+pub mod inputs;
+pub mod interpreter;
+pub mod ledger;
 pub mod log;
+pub mod replay;
 pub mod state;
 pub mod timers;
+pub mod triggers;
 pub mod worker;
 
 use anyhow::{Context, Result};
+use envelope::Redactor;
 use serde::Deserialize;
 use serde_json::{json, Value::Null as null};
+use std::sync::{Arc, Mutex};
 use tracing::{info, warn};
 use uuid::Uuid;
+use wards::audit::{DecisionLog, WardsDecision};
+use wards::quota::{QuotaResource, TenantQuotas};
 use wards::{config::load_from_env, policy::PolicyKernel};
 
+use checkpoint::CheckpointStore;
+use concurrency::ConcurrencyLocks;
+use definition::RitualDefinition;
+use inputs::RunInputs;
+use interpreter::{RouterStepRunner, StepContext, StepRunner};
+use ledger::ExecutionLedger;
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct FunctionRef {
     #[serde(rename = "refName")]
@@ -50,9 +72,27 @@ pub struct RitualSpec {
     pub states: Vec<State>,
 }
 
+/// A ritual file in either supported format
+enum LoadedRitual {
+    Definition(RitualDefinition),
+    Spec(RitualSpec),
+}
+
+/// Parallel branches in flight when neither the step nor `RITUAL_PARALLEL_LIMIT` sets one
+pub const DEFAULT_PARALLEL_LIMIT: usize = 8;
+
 pub struct Engine {
-    router: runtime::link::router::Router,
-    policy_kernel: Option<PolicyKernel>,
+    step_runner: Arc<dyn StepRunner>,
+    policy_kernel: Option<Mutex<PolicyKernel>>,
+    tenant_quotas: Option<TenantQuotas>,
+    decision_log: Option<DecisionLog>,
+    parallel_limit: usize,
+    redactor: Redactor,
+    checkpoints: Option<Arc<dyn CheckpointStore>>,
+    ledger: Option<Arc<dyn ExecutionLedger>>,
+    concurrency: Option<Arc<dyn ConcurrencyLocks>>,
+    /// Run id for the next run, taken when it starts
+    next_run_id: Mutex<Option<String>>,
 }
 
 impl Default for Engine {
@@ -70,19 +110,121 @@ impl Engine {
         {
             None
         } else {
-            Some(PolicyKernel::new(config))
+            Some(Mutex::new(PolicyKernel::new(config)))
         };
 
+        let tenant_quotas = TenantQuotas::from_env().unwrap_or_else(|e| panic!("{}", e));
+
         Self {
-            router: runtime::link::router::Router::new(),
+            step_runner: Arc::new(RouterStepRunner::new()),
             policy_kernel,
+            tenant_quotas,
+            decision_log: DecisionLog::from_env(),
+            parallel_limit: std::env::var("RITUAL_PARALLEL_LIMIT")
+                .ok()
+                .and_then(|s| s.parse::<usize>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(DEFAULT_PARALLEL_LIMIT),
+            redactor: Redactor::from_env().unwrap_or_else(|e| panic!("{}", e)),
+            checkpoints: None,
+            ledger: None,
+            concurrency: None,
+            next_run_id: Mutex::new(None),
         }
     }
 
-    /// Execute a minimal ritual: only a single `task` with `end: true` is supported.
+    /// Enforce per-tenant quotas with an explicit ledger instead of `WARDS_TENANT_QUOTAS`
+    pub fn with_tenant_quotas(mut self, quotas: TenantQuotas) -> Self {
+        self.tenant_quotas = Some(quotas);
+        self
+    }
+
+    /// Perform capsule calls, approval waits, and timers through a custom runner
+    pub fn with_step_runner(mut self, runner: Arc<dyn StepRunner>) -> Self {
+        self.step_runner = runner;
+        self
+    }
+
+    /// Cap in-flight branches for parallel steps without their own `maxConcurrency`
+    pub fn with_parallel_limit(mut self, limit: usize) -> Self {
+        self.parallel_limit = limit.max(1);
+        self
+    }
+
+    /// Record policy decisions to an explicit audit log instead of the `WARDS_AUDIT` default
+    pub fn with_decision_log(mut self, log: DecisionLog) -> Self {
+        self.decision_log = Some(log);
+        self
+    }
+
+    /// Redact completion envelopes with explicit rules instead of `ENVELOPE_REDACTION_RULES`
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Persist each definition run's state machine so it can be resumed with
+    /// [`Engine::recover_runs`] after a restart
+    pub fn with_checkpoints(mut self, store: Arc<dyn CheckpointStore>) -> Self {
+        self.checkpoints = Some(store);
+        self
+    }
+
+    /// Reuse envelopes recorded per `runId:stepId:attempt` instead of invoking
+    /// a capsule step again
+    pub fn with_execution_ledger(mut self, ledger: Arc<dyn ExecutionLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Keep concurrency group leases in an explicit store instead of the
+    /// `RITUAL_CONCURRENCY` KV bucket
+    pub fn with_concurrency_locks(mut self, locks: Arc<dyn ConcurrencyLocks>) -> Self {
+        self.concurrency = Some(locks);
+        self
+    }
+
+    /// Start the next run as `run_id` instead of a random id, so a caller can
+    /// subscribe to the run's events before it begins. Later runs get random
+    /// ids again.
+    pub fn with_run_id(self, run_id: impl Into<String>) -> Self {
+        *self.next_run_id.lock().unwrap_or_else(|p| p.into_inner()) = Some(run_id.into());
+        self
+    }
+
+    /// The id for a run that is starting
+    fn take_run_id(&self) -> String {
+        self.next_run_id
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .take()
+            .unwrap_or_else(|| Uuid::new_v4().to_string())
+    }
+
+    /// Execute a ritual file: either a typed-step definition or a legacy
+    /// single-`task` spec with `end: true`.
     pub async fn run_from_file(&mut self, path: &str) -> Result<()> {
-        let spec = Self::load_spec(path)?;
-        let _ = self.run_spec_internal(spec, true).await?;
+        self.run_from_file_with_inputs(path, &RunInputs::default())
+            .await
+    }
+
+    /// [`Engine::run_from_file`] with values for the definition's declared
+    /// `inputs`, validated before the run starts. Legacy specs take no inputs.
+    pub async fn run_from_file_with_inputs(
+        &mut self,
+        path: &str,
+        inputs: &RunInputs,
+    ) -> Result<()> {
+        let _ = match Self::load(path)? {
+            LoadedRitual::Definition(definition) => {
+                self.run_definition_internal(definition, inputs, true)
+                    .await?
+            }
+            LoadedRitual::Spec(spec) => {
+                Self::reject_spec_inputs(&spec, inputs)?;
+                self.run_spec_internal(spec, true).await?
+            }
+        };
         Ok(())
     }
 
@@ -90,8 +232,37 @@ impl Engine {
     /// This method is similar to run_from_file but returns the ritual completion event
     /// instead of printing it, allowing the caller to save it or process it further.
     pub async fn run_from_file_with_result(&mut self, path: &str) -> Result<serde_json::Value> {
-        let spec = Self::load_spec(path)?;
-        self.run_spec_internal(spec, false).await
+        self.run_from_file_with_inputs_result(path, &RunInputs::default())
+            .await
+    }
+
+    /// [`Engine::run_from_file_with_result`] with values for the definition's
+    /// declared `inputs`
+    pub async fn run_from_file_with_inputs_result(
+        &mut self,
+        path: &str,
+        inputs: &RunInputs,
+    ) -> Result<serde_json::Value> {
+        match Self::load(path)? {
+            LoadedRitual::Definition(definition) => {
+                self.run_definition_internal(definition, inputs, false)
+                    .await
+            }
+            LoadedRitual::Spec(spec) => {
+                Self::reject_spec_inputs(&spec, inputs)?;
+                self.run_spec_internal(spec, false).await
+            }
+        }
+    }
+
+    fn reject_spec_inputs(spec: &RitualSpec, inputs: &RunInputs) -> Result<()> {
+        if !inputs.is_empty() {
+            anyhow::bail!(
+                "ritual '{}' uses the legacy single-task format, which takes no inputs",
+                spec.id
+            );
+        }
+        Ok(())
     }
 
     /// Execute a ritual specification that has already been loaded from disk and return
@@ -101,11 +272,18 @@ impl Engine {
         self.run_spec_internal(spec, false).await
     }
 
-    fn load_spec(path: &str) -> Result<RitualSpec> {
+    fn load(path: &str) -> Result<LoadedRitual> {
         let text = std::fs::read_to_string(path)
             .with_context(|| format!("reading ritual spec: {path}"))?;
-        let spec = serde_yaml::from_str(&text).with_context(|| "parsing ritual yaml")?;
-        Ok(spec)
+        let doc: serde_json::Value =
+            serde_yaml::from_str(&text).with_context(|| "parsing ritual yaml")?;
+        if RitualDefinition::is_definition(&doc) {
+            let definition = RitualDefinition::from_value(doc)
+                .with_context(|| format!("validating ritual definition: {path}"))?;
+            return Ok(LoadedRitual::Definition(definition));
+        }
+        let spec = serde_json::from_value(doc).with_context(|| "parsing ritual yaml")?;
+        Ok(LoadedRitual::Spec(spec))
     }
 
     async fn run_spec_internal(
@@ -114,7 +292,7 @@ impl Engine {
         emit_completion_stdout: bool,
     ) -> Result<serde_json::Value> {
         let ritual_id = spec.id.clone();
-        let run_id = Uuid::new_v4().to_string();
+        let run_id = self.take_run_id();
         info!(ritual = %ritual_id, %run_id, "ritual.start");
 
         let state = spec
@@ -124,12 +302,19 @@ impl Engine {
             .context("Milestone 0 expects exactly one state")?;
 
         match state {
-            State::Task { action, end, .. } => {
+            State::Task {
+                name: state_name,
+                action,
+                end,
+            } => {
                 let tenant_id = "default"; // TODO: Extract from ritual spec or context
                 let capability = action.function_ref.ref_name.clone();
 
-                if let Some(ref mut kernel) = self.policy_kernel {
-                    let decision = kernel.allow_and_count(tenant_id, &capability);
+                if let Some(kernel) = &self.policy_kernel {
+                    let decision = kernel
+                        .lock()
+                        .unwrap_or_else(|p| p.into_inner())
+                        .allow_and_count(tenant_id, &capability);
 
                     let policy_event = json!({
                         "event": "policy.decision:v1",
@@ -158,6 +343,15 @@ impl Engine {
                             capability = %capability,
                             "ritual denied due to quota limits"
                         );
+                        if let Some(log) = &self.decision_log {
+                            let record = WardsDecision::capability_quota_rejected(
+                                tenant_id,
+                                &capability,
+                                &decision,
+                            )
+                            .for_run(&ritual_id, &run_id);
+                            log.record_best_effort(&record).await;
+                        }
                         let evt = json!({
                           "event": "ritual.completed:v1",
                           "ritualId": ritual_id,
@@ -184,15 +378,62 @@ impl Engine {
                     );
                 }
 
-                let out = self
-                    .router
-                    .dispatch(
+                if let Some(quotas) = &self.tenant_quotas {
+                    for resource in quota_resources(
+                        &action.function_ref.ref_name,
+                        &action.function_ref.arguments,
+                    ) {
+                        let decision = quotas.check_and_consume(tenant_id, resource, 1).await?;
+                        if decision.allowed {
+                            continue;
+                        }
+
+                        warn!(
+                            ritual = %ritual_id,
+                            %run_id,
+                            %tenant_id,
+                            resource = %resource,
+                            used = decision.used,
+                            "ritual denied due to tenant quota"
+                        );
+                        if let Some(log) = &self.decision_log {
+                            let record = WardsDecision::quota_rejected(&decision)
+                                .for_run(&ritual_id, &run_id);
+                            log.record_best_effort(&record).await;
+                        }
+                        let evt = json!({
+                          "event": "ritual.completed:v1",
+                          "ritualId": ritual_id,
+                          "runId": run_id,
+                          "ts": chrono::Utc::now().to_rfc3339(),
+                          "outputs": null,
+                          "reason": "quota_exceeded",
+                          "quota": decision
+                        });
+                        if emit_completion_stdout {
+                            println!("{}", serde_json::to_string_pretty(&evt)?);
+                        }
+                        info!(ritual = %ritual_id, %run_id, "ritual.end");
+                        return Ok(evt);
+                    }
+                }
+
+                let ctx = StepContext {
+                    tenant_id: tenant_id.to_string(),
+                    ritual_id: ritual_id.clone(),
+                    run_id: run_id.clone(),
+                    step_id: state_name.clone(),
+                };
+                let mut out = self
+                    .step_runner
+                    .invoke_capsule(
                         &action.function_ref.ref_name,
                         &action.function_ref.arguments,
-                        &run_id,
-                        &ritual_id,
+                        &ctx,
                     )
                     .await?;
+                self.redactor
+                    .redact_json(Some(&action.function_ref.ref_name), &mut out);
                 if !end {
                     warn!(
                         "Milestone 0 only supports single task with end=true; treating as terminal"
@@ -215,6 +456,16 @@ impl Engine {
     }
 }
 
+/// Tenant quota resources consumed by a single-task run, checked in order
+fn quota_resources(capsule: &str, args: &serde_json::Value) -> Vec<QuotaResource> {
+    let mut resources = vec![QuotaResource::Runs, QuotaResource::CapsuleExecutions];
+    let operation = args.get("operation").and_then(|op| op.as_str());
+    if capsule == "graph" && matches!(operation, Some("create" | "commit")) {
+        resources.push(QuotaResource::GraphCommits);
+    }
+    resources
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,4 +488,17 @@ states:
         let spec: RitualSpec = serde_yaml::from_str(y).unwrap();
         assert_eq!(spec.states.len(), 1);
     }
+
+    #[test]
+    fn graph_commits_count_against_commit_quota() {
+        let commit = json!({ "operation": "commit" });
+        let query = json!({ "operation": "get-node" });
+
+        assert!(quota_resources("graph", &commit).contains(&QuotaResource::GraphCommits));
+        assert!(!quota_resources("graph", &query).contains(&QuotaResource::GraphCommits));
+        assert_eq!(
+            quota_resources("echo", &commit),
+            vec![QuotaResource::Runs, QuotaResource::CapsuleExecutions]
+        );
+    }
 }
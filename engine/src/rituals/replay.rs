@@ -0,0 +1,417 @@
+//! Replay a recorded run from its JetStream events
+//!
+//! The run is rebuilt purely from its events: the ritual spec comes from
+//! `ritual.started:v1` (or a caller-supplied fallback), and each step's
+//! recorded output and the run's inputs from `ritual.completed:v1`. The ritual is then executed
+//! again in a sandbox that never touches NATS:
+//!
+//! - deterministic capsules (`echo`) are re-executed locally;
+//! - every other capsule returns its recorded envelope, which is checked
+//!   against `contracts/envelopes/result.json` instead;
+//! - approvals resolve to their recorded outcome and timers fire immediately.
+//!
+//! The report diffs recorded against replayed outputs per step, ignoring
+//! fields that legitimately change between executions (timestamps, metrics,
+//! diagnostics).
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use envelope::{EnvelopeValidator, Redactor};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::concurrency::MemoryConcurrencyLocks;
+use super::definition::RitualDefinition;
+use super::inputs::RunInputs;
+use super::interpreter::{ApprovalOutcome, StepContext, StepRunner};
+use super::log::EventLog;
+use super::{Engine, RitualSpec, State, DEFAULT_PARALLEL_LIMIT};
+
+/// Fields that differ between two executions of the same step
+const VOLATILE_KEYS: &[&str] = &[
+    "diagnostics",
+    "metrics",
+    "provenance",
+    "timestamp",
+    "ts",
+    "firedAt",
+];
+
+/// A run as reconstructed from its events
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRun {
+    pub tenant_id: String,
+    pub ritual_id: String,
+    pub run_id: String,
+    pub spec: Option<Value>,
+    /// Output per step id, from the completion event
+    pub outputs: Map<String, Value>,
+    /// Inputs the run was started with, from the completion event
+    pub inputs: Map<String, Value>,
+    pub completed: bool,
+    pub reason: Option<String>,
+    pub event_count: usize,
+}
+
+impl RecordedRun {
+    pub fn from_events(events: &[Value]) -> Result<Self> {
+        let Some(first) = events.first() else {
+            bail!("run has no events to replay");
+        };
+        let field =
+            |event: &Value, name: &str| event.get(name).and_then(|v| v.as_str()).map(String::from);
+        let by_name = |name: &str| {
+            events
+                .iter()
+                .find(|e| e.get("event").and_then(|v| v.as_str()) == Some(name))
+        };
+
+        let started = by_name("ritual.started:v1");
+        let identity = started.unwrap_or(first);
+        let mut run = Self {
+            tenant_id: field(identity, "tenantId").unwrap_or_else(|| "default".to_string()),
+            ritual_id: field(identity, "ritualId").context("events carry no ritualId")?,
+            run_id: field(identity, "runId").context("events carry no runId")?,
+            spec: started.and_then(|e| e.get("spec")).cloned(),
+            outputs: Map::new(),
+            inputs: Map::new(),
+            completed: false,
+            reason: None,
+            event_count: events.len(),
+        };
+
+        if let Some(completed) = by_name("ritual.completed:v1") {
+            run.completed = true;
+            run.reason = field(completed, "reason");
+            if let Some(inputs) = completed.get("inputs").and_then(|i| i.as_object()) {
+                run.inputs = inputs.clone();
+            }
+            let outputs = completed.get("outputs").cloned().unwrap_or(Value::Null);
+            match outputs.get("steps").and_then(|s| s.as_object()) {
+                Some(steps) => run.outputs = steps.clone(),
+                // Single-task specs record the task's envelope as the outputs
+                None if !outputs.is_null() => {
+                    if let Some(task) = run.spec.as_ref().and_then(legacy_task_name) {
+                        run.outputs.insert(task, outputs);
+                    }
+                }
+                None => {}
+            }
+        }
+        Ok(run)
+    }
+}
+
+fn legacy_task_name(spec: &Value) -> Option<String> {
+    spec.get("states")?
+        .as_array()?
+        .first()?
+        .get("name")?
+        .as_str()
+        .map(String::from)
+}
+
+/// Where a step's replayed output came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayMode {
+    /// Executed again in the sandbox
+    Reexecuted,
+    /// Served from the recorded output
+    Recorded,
+    /// Computed by the interpreter (timers, conditions, parallel joins)
+    Derived,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Match,
+    Mismatch,
+    /// Recorded but not reached during replay
+    Missing,
+    /// Reached during replay but never recorded
+    Extra,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepReplay {
+    pub step_id: String,
+    pub mode: ReplayMode,
+    pub status: StepStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replayed: Option<Value>,
+    /// Schema violation in the recorded envelope
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub envelope_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayReport {
+    pub tenant_id: String,
+    pub ritual_id: String,
+    pub run_id: String,
+    pub event_count: usize,
+    pub original_completed: bool,
+    pub original_reason: Option<String>,
+    pub replayed_reason: Option<String>,
+    pub steps: Vec<StepReplay>,
+    /// Every step matched, every envelope is valid and the run ended the same way
+    pub matched: bool,
+}
+
+impl ReplayReport {
+    pub fn mismatches(&self) -> impl Iterator<Item = &StepReplay> {
+        self.steps
+            .iter()
+            .filter(|s| s.status != StepStatus::Match || s.envelope_error.is_some())
+    }
+}
+
+/// Sandbox runner: deterministic capsules run locally, everything else is recorded
+struct ReplayRunner {
+    recorded: Map<String, Value>,
+    modes: Mutex<HashMap<String, ReplayMode>>,
+}
+
+impl ReplayRunner {
+    fn recorded(&self, ctx: &StepContext, mode: ReplayMode) -> Result<Value> {
+        self.modes
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .insert(ctx.step_id.clone(), mode);
+        self.recorded
+            .get(&ctx.step_id)
+            .cloned()
+            .with_context(|| format!("no recorded output for step '{}'", ctx.step_id))
+    }
+
+    fn mode(&self, step_id: &str) -> ReplayMode {
+        self.modes
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .get(step_id)
+            .copied()
+            .unwrap_or(ReplayMode::Derived)
+    }
+}
+
+#[async_trait]
+impl StepRunner for ReplayRunner {
+    async fn invoke_capsule(
+        &self,
+        capsule: &str,
+        args: &Value,
+        ctx: &StepContext,
+    ) -> Result<Value> {
+        if capsule == "echo" {
+            self.modes
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .insert(ctx.step_id.clone(), ReplayMode::Reexecuted);
+            let message = args.get("message").and_then(|v| v.as_str()).unwrap_or("");
+            return Ok(serde_json::to_value(capsules_echo::echo(
+                message.to_string(),
+            ))?);
+        }
+        self.recorded(ctx, ReplayMode::Recorded)
+    }
+
+    async fn await_approval(
+        &self,
+        _gate: &str,
+        _reason: &str,
+        _ttl_seconds: Option<u64>,
+        ctx: &StepContext,
+    ) -> Result<ApprovalOutcome> {
+        let output = self.recorded(ctx, ReplayMode::Recorded)?;
+        let field = |name: &str| output.get(name).and_then(|v| v.as_str()).map(String::from);
+        Ok(ApprovalOutcome {
+            granted: output.get("granted").and_then(|v| v.as_bool()) == Some(true),
+            approver: field("approver"),
+            reason: field("reason"),
+        })
+    }
+
+    async fn sleep(&self, _delay: Duration) {}
+
+    async fn emit(&self, _msg_id: &str, _event: &Value, _ctx: &StepContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Strip fields that change between executions
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(k, _)| !VOLATILE_KEYS.contains(&k.as_str()))
+                .map(|(k, v)| (k.clone(), normalize(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Re-execute a recorded run in the sandbox and diff the outputs.
+///
+/// `fallback_spec` is used when the events carry no `ritual.started:v1` spec.
+pub async fn replay(recorded: &RecordedRun, fallback_spec: Option<&Value>) -> Result<ReplayReport> {
+    let spec = recorded
+        .spec
+        .as_ref()
+        .or(fallback_spec)
+        .context("run events carry no ritual spec; pass the ritual file to replay against")?
+        .clone();
+
+    let runner = Arc::new(ReplayRunner {
+        recorded: recorded.outputs.clone(),
+        modes: Mutex::new(HashMap::new()),
+    });
+    let mut engine = Engine {
+        step_runner: runner.clone(),
+        policy_kernel: None,
+        tenant_quotas: None,
+        decision_log: None,
+        parallel_limit: DEFAULT_PARALLEL_LIMIT,
+        // Recordings were redacted with these rules; redact replays the same way
+        redactor: Redactor::from_env()?,
+        checkpoints: None,
+        ledger: None,
+        // The recorded run already held its group; never wait on the live one
+        concurrency: Some(Arc::new(MemoryConcurrencyLocks::new())),
+        next_run_id: Mutex::new(None),
+    };
+
+    let (completion, replayed) = if RitualDefinition::is_definition(&spec) {
+        let mut definition = RitualDefinition::from_value(spec)?;
+        definition.tenant_id = Some(recorded.tenant_id.clone());
+        let inputs = RunInputs::from_value(Value::Object(recorded.inputs.clone()))?;
+        let completion = engine
+            .run_definition_internal(definition, &inputs, false)
+            .await?;
+        let steps = completion
+            .pointer("/outputs/steps")
+            .and_then(|s| s.as_object())
+            .cloned()
+            .unwrap_or_default();
+        (completion, steps)
+    } else {
+        let spec: RitualSpec =
+            serde_json::from_value(spec).context("parsing recorded ritual spec")?;
+        let task = spec
+            .states
+            .first()
+            .map(|State::Task { name, .. }| name.clone());
+        let completion = engine.run_spec_internal(spec, false).await?;
+        let mut steps = Map::new();
+        if let (Some(task), Some(outputs)) = (task, completion.get("outputs")) {
+            if !outputs.is_null() {
+                steps.insert(task, outputs.clone());
+            }
+        }
+        (completion, steps)
+    };
+
+    let validator = EnvelopeValidator::new()?;
+    let step_ids: BTreeSet<&String> = recorded.outputs.keys().chain(replayed.keys()).collect();
+    let steps: Vec<StepReplay> = step_ids
+        .into_iter()
+        .map(|step_id| {
+            let original = recorded.outputs.get(step_id).cloned();
+            let replayed = replayed.get(step_id).cloned();
+            let status = match (&original, &replayed) {
+                (Some(a), Some(b)) if normalize(a) == normalize(b) => StepStatus::Match,
+                (Some(_), Some(_)) => StepStatus::Mismatch,
+                (Some(_), None) => StepStatus::Missing,
+                (None, _) => StepStatus::Extra,
+            };
+            let mode = runner.mode(step_id);
+            let envelope_error = match (&original, mode) {
+                (Some(envelope), ReplayMode::Recorded) if envelope.get("result").is_some() => {
+                    validator
+                        .validate_json(envelope)
+                        .err()
+                        .map(|e| e.to_string())
+                }
+                _ => None,
+            };
+            StepReplay {
+                step_id: step_id.clone(),
+                mode,
+                status,
+                original,
+                replayed,
+                envelope_error,
+            }
+        })
+        .collect();
+
+    let replayed_reason = completion
+        .get("reason")
+        .and_then(|r| r.as_str())
+        .map(String::from);
+    let mut report = ReplayReport {
+        tenant_id: recorded.tenant_id.clone(),
+        ritual_id: recorded.ritual_id.clone(),
+        run_id: recorded.run_id.clone(),
+        event_count: recorded.event_count,
+        original_completed: recorded.completed,
+        original_reason: recorded.reason.clone(),
+        replayed_reason,
+        steps,
+        matched: false,
+    };
+    report.matched = report.mismatches().next().is_none()
+        && (!recorded.completed || report.original_reason == report.replayed_reason);
+    Ok(report)
+}
+
+/// Read a run's events from JetStream and replay it
+pub async fn replay_run(
+    log: &EventLog,
+    tenant_id: Option<&str>,
+    ritual_id: &str,
+    run_id: &str,
+    fallback_spec: Option<&Value>,
+) -> Result<ReplayReport> {
+    let events = log
+        .read_run_events(ritual_id, run_id, tenant_id)
+        .await
+        .with_context(|| format!("reading events for run {run_id}"))?;
+    if events.is_empty() {
+        bail!("no events found for run {run_id} of ritual {ritual_id}");
+    }
+    let recorded = RecordedRun::from_events(&events)?;
+    replay(&recorded, fallback_spec).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn normalize_drops_volatile_fields_at_any_depth() {
+        let a = json!({"result": {"success": true, "data": {"v": 1, "timestamp": "t1"}}, "metrics": {"ms": 1}});
+        let b = json!({"result": {"success": true, "data": {"v": 1, "timestamp": "t2"}}, "metrics": {"ms": 9}});
+        assert_eq!(normalize(&a), normalize(&b));
+        assert_ne!(
+            normalize(&a),
+            normalize(&json!({"result": {"success": true, "data": {"v": 2}}}))
+        );
+    }
+
+    #[test]
+    fn recorded_run_requires_events() {
+        assert!(RecordedRun::from_events(&[]).is_err());
+    }
+}
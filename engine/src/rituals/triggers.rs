@@ -0,0 +1,141 @@
+//! `ritual.triggered:v1` events that ask a runner to start a new ritual run
+//!
+//! Triggers publish onto the new run's event subject so the run shows up in
+//! operate-ui as soon as it is requested. Publishing is idempotent through
+//! `Nats-Msg-Id`, which each trigger source derives from what fired it.
+
+use anyhow::Result;
+use async_nats::jetstream;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// What caused a ritual to be triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TriggerSource {
+    Schedule,
+    Event,
+}
+
+#[derive(Debug, Clone)]
+pub struct RitualTriggered {
+    pub tenant_id: String,
+    pub ritual_id: String,
+    pub run_id: String,
+    pub trigger_id: String,
+    pub source: TriggerSource,
+    pub scheduled_for: Option<DateTime<Utc>>,
+    pub source_subject: Option<String>,
+    pub source_msg_id: Option<String>,
+    pub trigger_event: Option<Value>,
+    pub parameters: Value,
+}
+
+impl RitualTriggered {
+    /// A scheduled firing of `trigger_id` for the slot `scheduled_for`
+    pub fn scheduled(
+        trigger_id: &str,
+        tenant_id: &str,
+        ritual_id: &str,
+        scheduled_for: DateTime<Utc>,
+        parameters: Value,
+    ) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            ritual_id: ritual_id.to_string(),
+            run_id: Uuid::new_v4().to_string(),
+            trigger_id: trigger_id.to_string(),
+            source: TriggerSource::Schedule,
+            scheduled_for: Some(scheduled_for),
+            source_subject: None,
+            source_msg_id: None,
+            trigger_event: None,
+            parameters,
+        }
+    }
+
+    /// A run started by the message `source_msg_id` arriving on `source_subject`
+    pub fn from_event(
+        trigger_id: &str,
+        tenant_id: &str,
+        ritual_id: &str,
+        source_subject: &str,
+        source_msg_id: &str,
+        payload: Value,
+        parameters: Value,
+    ) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            ritual_id: ritual_id.to_string(),
+            run_id: Uuid::new_v4().to_string(),
+            trigger_id: trigger_id.to_string(),
+            source: TriggerSource::Event,
+            scheduled_for: None,
+            source_subject: Some(source_subject.to_string()),
+            source_msg_id: Some(source_msg_id.to_string()),
+            trigger_event: Some(payload),
+            parameters,
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        format!(
+            "demon.ritual.v1.{}.{}.{}.events",
+            self.tenant_id, self.ritual_id, self.run_id
+        )
+    }
+
+    /// Idempotency key: one run per trigger and slot or source message,
+    /// however many times it is published
+    pub fn msg_id(&self) -> String {
+        if let Some(slot) = self.scheduled_for {
+            return format!("trigger:{}:{}", self.trigger_id, slot.timestamp());
+        }
+        match &self.source_msg_id {
+            Some(source) => format!("trigger:{}:{}", self.trigger_id, source),
+            None => format!("trigger:{}:{}", self.trigger_id, self.run_id),
+        }
+    }
+
+    pub fn to_event(&self) -> Value {
+        let mut event = json!({
+            "event": "ritual.triggered:v1",
+            "ts": Utc::now().to_rfc3339(),
+            "tenantId": self.tenant_id,
+            "ritualId": self.ritual_id,
+            "runId": self.run_id,
+            "triggerId": self.trigger_id,
+            "source": self.source,
+            "parameters": self.parameters,
+        });
+        if let Some(slot) = self.scheduled_for {
+            event["scheduledFor"] = json!(slot.to_rfc3339());
+        }
+        if let Some(subject) = &self.source_subject {
+            event["sourceSubject"] = json!(subject);
+        }
+        if let Some(msg_id) = &self.source_msg_id {
+            event["sourceMsgId"] = json!(msg_id);
+        }
+        if let Some(payload) = &self.trigger_event {
+            event["triggerEvent"] = payload.clone();
+        }
+        event
+    }
+}
+
+pub async fn publish(js: &jetstream::Context, triggered: &RitualTriggered) -> Result<()> {
+    let msg_id = triggered.msg_id();
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert("Nats-Msg-Id", msg_id.as_str());
+    js.publish_with_headers(
+        triggered.subject(),
+        headers,
+        serde_json::to_vec(&triggered.to_event())?.into(),
+    )
+    .await?
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,643 @@
+//! Approval notifications for approvers who don't live in Operate UI
+//!
+//! Consumes `approval.requested:v1` from the ritual stream through a durable
+//! pull consumer and posts the request, with grant and deny deep links into
+//! Operate UI, to every channel in `APPROVAL_NOTIFY_CHANNELS` that covers the
+//! tenant and gate: a generic JSON webhook or a Slack incoming webhook.
+//! Each delivery is retried with exponential backoff and its outcome recorded
+//! as `notification.sent:v1` on the run's subject. The request is acked once
+//! every outcome is recorded, so a redelivered request can notify twice.
+
+use anyhow::{Context, Result};
+use async_nats::jetstream;
+use async_nats::jetstream::{consumer::DeliverPolicy, Message};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::rituals::dlq::{DeadLetterQueue, DlqPolicy};
+
+static DELIVERED: AtomicU64 = AtomicU64::new(0);
+static FAILED: AtomicU64 = AtomicU64::new(0);
+
+/// Longest wait between delivery attempts
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    /// POST the request as JSON
+    Webhook,
+    /// POST a Slack message with grant and deny buttons
+    Slack,
+}
+
+impl ChannelKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Webhook => "webhook",
+            Self::Slack => "slack",
+        }
+    }
+}
+
+/// One entry of `APPROVAL_NOTIFY_CHANNELS`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationChannel {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: ChannelKind,
+    pub url: String,
+    /// Tenants this channel hears about; every tenant when empty
+    #[serde(default)]
+    pub tenants: Vec<String>,
+    /// Gates this channel hears about; every gate when empty
+    #[serde(default)]
+    pub gates: Vec<String>,
+    /// Extra request headers, e.g. `Authorization` for a webhook
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+impl NotificationChannel {
+    pub fn covers(&self, tenant: &str, gate_id: &str) -> bool {
+        (self.tenants.is_empty() || self.tenants.iter().any(|t| t == tenant))
+            && (self.gates.is_empty() || self.gates.iter().any(|g| g == gate_id))
+    }
+}
+
+/// Parse `APPROVAL_NOTIFY_CHANNELS`: a JSON array of channels with unique ids
+pub fn parse_channels(raw: &str) -> Result<Vec<NotificationChannel>> {
+    let channels: Vec<NotificationChannel> =
+        serde_json::from_str(raw).context("APPROVAL_NOTIFY_CHANNELS is not a channel array")?;
+    for (i, channel) in channels.iter().enumerate() {
+        anyhow::ensure!(!channel.id.trim().is_empty(), "channel {i} has no id");
+        anyhow::ensure!(
+            channel.url.starts_with("http://") || channel.url.starts_with("https://"),
+            "channel '{}' url must be http(s)",
+            channel.id
+        );
+        anyhow::ensure!(
+            !channels[..i].iter().any(|c| c.id == channel.id),
+            "duplicate channel id '{}'",
+            channel.id
+        );
+    }
+    Ok(channels)
+}
+
+#[derive(Clone, Debug)]
+pub struct NotifierConfig {
+    pub nats_url: String,
+    pub stream_name: Option<String>,
+    pub consumer_name: String,  // default: approval-notifier
+    pub subject_filter: String, // default: demon.ritual.v1.*.*.*.events
+    /// Base URL deep links point at; default: http://127.0.0.1:3000
+    pub operate_ui_url: String,
+    pub channels: Vec<NotificationChannel>,
+    pub max_attempts: u32,    // default: 5
+    pub backoff_ms: u64,      // default: 500, doubled per attempt
+    pub timeout_ms: u64,      // default: 5000, per request
+    pub batch: usize,         // default: 50
+    pub pull_timeout_ms: u64, // default: 1500
+}
+
+impl NotifierConfig {
+    /// Read the environment; fails when `APPROVAL_NOTIFY_CHANNELS` is set but invalid
+    pub fn from_env() -> Result<Self> {
+        let env_or = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(default)
+        };
+        let channels = match std::env::var("APPROVAL_NOTIFY_CHANNELS") {
+            Ok(raw) if !raw.trim().is_empty() => parse_channels(&raw)?,
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            nats_url: std::env::var("NATS_URL")
+                .unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string()),
+            stream_name: std::env::var("RITUAL_STREAM_NAME").ok(),
+            consumer_name: std::env::var("APPROVAL_NOTIFIER_CONSUMER")
+                .unwrap_or_else(|_| "approval-notifier".to_string()),
+            subject_filter: "demon.ritual.v1.*.*.*.events".to_string(),
+            operate_ui_url: std::env::var("OPERATE_UI_URL")
+                .unwrap_or_else(|_| "http://127.0.0.1:3000".to_string()),
+            channels,
+            max_attempts: env_or("APPROVAL_NOTIFY_MAX_ATTEMPTS", 5).max(1) as u32,
+            backoff_ms: env_or("APPROVAL_NOTIFY_BACKOFF_MS", 500),
+            timeout_ms: env_or("APPROVAL_NOTIFY_TIMEOUT_MS", 5000),
+            batch: env_or("APPROVAL_NOTIFY_BATCH", 50) as usize,
+            pull_timeout_ms: env_or("APPROVAL_NOTIFY_PULL_TIMEOUT_MS", 1500),
+        })
+    }
+}
+
+fn incr(a: &AtomicU64) {
+    a.fetch_add(1, Ordering::Relaxed);
+}
+
+/// (delivered, failed) notifications since start
+pub fn counters() -> (u64, u64) {
+    (
+        DELIVERED.load(Ordering::Relaxed),
+        FAILED.load(Ordering::Relaxed),
+    )
+}
+
+/// The fields of an `approval.requested:v1` a notification needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalRequest {
+    pub tenant_id: String,
+    pub ritual_id: String,
+    pub run_id: String,
+    pub gate_id: String,
+    pub requester: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl ApprovalRequest {
+    /// `None` unless `payload` is an `approval.requested:v1` with a gate;
+    /// ids missing from the payload come from the subject
+    pub fn from_event(subject: (&str, &str, &str), payload: &Value) -> Option<Self> {
+        if payload.get("event")?.as_str()? != "approval.requested:v1" {
+            return None;
+        }
+        let field = |key: &str| payload.get(key).and_then(|v| v.as_str()).map(String::from);
+        let (tenant, ritual_id, run_id) = subject;
+        Some(Self {
+            tenant_id: field("tenantId").unwrap_or_else(|| tenant.to_string()),
+            ritual_id: field("ritualId").unwrap_or_else(|| ritual_id.to_string()),
+            run_id: field("runId").unwrap_or_else(|| run_id.to_string()),
+            gate_id: field("gateId")?,
+            requester: field("requester"),
+            reason: field("reason"),
+        })
+    }
+
+    /// The run page, and the same page opened at the grant or deny action
+    pub fn links(&self, operate_ui_url: &str) -> ApprovalLinks {
+        let run = format!(
+            "{}/tenants/{}/runs/{}",
+            operate_ui_url.trim_end_matches('/'),
+            encode_component(&self.tenant_id),
+            encode_component(&self.run_id)
+        );
+        let action = |decision: &str| {
+            format!(
+                "{}?gate={}&decision={}#approval-actions",
+                run,
+                encode_component(&self.gate_id),
+                decision
+            )
+        };
+        ApprovalLinks {
+            grant: action("grant"),
+            deny: action("deny"),
+            run,
+        }
+    }
+
+    /// Subject the run's events, including `notification.sent:v1`, go to
+    fn subject(&self) -> String {
+        format!(
+            "demon.ritual.v1.{}.{}.{}.events",
+            self.tenant_id, self.ritual_id, self.run_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalLinks {
+    pub run: String,
+    pub grant: String,
+    pub deny: String,
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Request body for `channel`
+pub fn message_for(
+    channel: &NotificationChannel,
+    request: &ApprovalRequest,
+    links: &ApprovalLinks,
+) -> Value {
+    match channel.kind {
+        ChannelKind::Webhook => json!({
+            "event": "approval.requested:v1",
+            "tenantId": request.tenant_id,
+            "ritualId": request.ritual_id,
+            "runId": request.run_id,
+            "gateId": request.gate_id,
+            "requester": request.requester,
+            "reason": request.reason,
+            "links": {
+                "run": links.run,
+                "grant": links.grant,
+                "deny": links.deny,
+            },
+        }),
+        ChannelKind::Slack => {
+            let mut summary = format!(
+                "*Approval requested* for gate `{}` in ritual `{}` (run `{}`, tenant `{}`)",
+                request.gate_id, request.ritual_id, request.run_id, request.tenant_id
+            );
+            if let Some(requester) = &request.requester {
+                summary.push_str(&format!("\nRequested by {}", requester));
+            }
+            if let Some(reason) = request.reason.as_deref().filter(|r| !r.is_empty()) {
+                summary.push_str(&format!("\n>{}", reason));
+            }
+            json!({
+                "text": format!(
+                    "Approval requested for gate {} in ritual {}: {}",
+                    request.gate_id, request.ritual_id, links.run
+                ),
+                "blocks": [
+                    { "type": "section", "text": { "type": "mrkdwn", "text": summary } },
+                    { "type": "actions", "elements": [
+                        { "type": "button", "style": "primary", "url": links.grant,
+                          "text": { "type": "plain_text", "text": "Grant" } },
+                        { "type": "button", "style": "danger", "url": links.deny,
+                          "text": { "type": "plain_text", "text": "Deny" } },
+                        { "type": "button", "url": links.run,
+                          "text": { "type": "plain_text", "text": "Open run" } }
+                    ] }
+                ]
+            })
+        }
+    }
+}
+
+/// How delivery to one channel ended
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryOutcome {
+    pub attempts: u32,
+    /// Last error when every attempt failed
+    pub error: Option<String>,
+}
+
+/// Client errors other than timeouts and rate limits won't succeed on retry
+fn retryable(status: reqwest::StatusCode) -> bool {
+    !status.is_client_error()
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Delay before attempt `attempt + 1`
+fn backoff(base_ms: u64, attempt: u32) -> Duration {
+    Duration::from_millis(base_ms.saturating_mul(1 << attempt.saturating_sub(1).min(16)))
+        .min(MAX_BACKOFF)
+}
+
+async fn deliver(
+    http: &reqwest::Client,
+    cfg: &NotifierConfig,
+    channel: &NotificationChannel,
+    body: &Value,
+) -> DeliveryOutcome {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let mut request = http
+            .post(&channel.url)
+            .timeout(Duration::from_millis(cfg.timeout_ms))
+            .json(body);
+        for (name, value) in &channel.headers {
+            request = request.header(name, value);
+        }
+        let (error, retry) = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                return DeliveryOutcome {
+                    attempts: attempt,
+                    error: None,
+                }
+            }
+            Ok(response) => (
+                format!("HTTP {}", response.status()),
+                retryable(response.status()),
+            ),
+            Err(e) => (e.to_string(), true),
+        };
+        if !retry || attempt >= cfg.max_attempts {
+            return DeliveryOutcome {
+                attempts: attempt,
+                error: Some(error),
+            };
+        }
+        warn!(channel=%channel.id, attempt, error=%error, "approval_notifier: delivery failed; retrying");
+        tokio::time::sleep(backoff(cfg.backoff_ms, attempt)).await;
+    }
+}
+
+/// The `notification.sent:v1` audit event for one channel
+pub fn notification_sent_event(
+    request: &ApprovalRequest,
+    channel: &NotificationChannel,
+    outcome: &DeliveryOutcome,
+) -> Value {
+    let delivered = if outcome.error.is_none() {
+        "delivered"
+    } else {
+        "failed"
+    };
+    let mut event = json!({
+        "event": "notification.sent:v1",
+        "ts": chrono::Utc::now().to_rfc3339(),
+        "tenantId": request.tenant_id,
+        "ritualId": request.ritual_id,
+        "runId": request.run_id,
+        "gateId": request.gate_id,
+        "channelId": channel.id,
+        "channelType": channel.kind.as_str(),
+        "outcome": delivered,
+        "attempts": outcome.attempts,
+    });
+    if let Some(error) = &outcome.error {
+        event["error"] = json!(error);
+    }
+    event
+}
+
+async fn publish_outcome(
+    js: &jetstream::Context,
+    request: &ApprovalRequest,
+    event: &Value,
+    channel_id: &str,
+) -> Result<()> {
+    let mut headers = async_nats::HeaderMap::new();
+    let msg_id = format!(
+        "{}:approval:{}:notification:{}",
+        request.run_id, request.gate_id, channel_id
+    );
+    headers.insert("Nats-Msg-Id", msg_id.as_str());
+    js.publish_with_headers(
+        request.subject(),
+        headers,
+        serde_json::to_vec(event)?.into(),
+    )
+    .await?
+    .await?;
+    Ok(())
+}
+
+/// Handle a single JetStream message
+async fn handle_message(
+    js: &jetstream::Context,
+    http: &reqwest::Client,
+    dlq: &DeadLetterQueue,
+    cfg: &NotifierConfig,
+    msg: Message,
+) -> Result<()> {
+    let subject = msg.message.subject.to_string();
+    let Some((tenant, ritual_id, run_id)) = super::ttl_worker::parse_subject(&subject) else {
+        let _ = msg.ack().await;
+        return Ok(());
+    };
+    let payload: Value = match serde_json::from_slice(&msg.message.payload) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!(%subject, error=%e, "approval_notifier: invalid JSON; dead-letter");
+            dlq.dead_letter(&msg, &format!("invalid JSON: {e}")).await;
+            return Ok(());
+        }
+    };
+    let Some(request) = ApprovalRequest::from_event((&tenant, &ritual_id, &run_id), &payload)
+    else {
+        let _ = msg.ack().await;
+        return Ok(());
+    };
+
+    let channels: Vec<&NotificationChannel> = cfg
+        .channels
+        .iter()
+        .filter(|c| c.covers(&request.tenant_id, &request.gate_id))
+        .collect();
+    if channels.is_empty() {
+        let _ = msg.ack().await;
+        return Ok(());
+    }
+
+    let links = request.links(&cfg.operate_ui_url);
+    let (request_ref, links_ref) = (&request, &links);
+    let outcomes = futures_util::future::join_all(channels.iter().map(|&channel| async move {
+        let body = message_for(channel, request_ref, links_ref);
+        (channel, deliver(http, cfg, channel, &body).await)
+    }))
+    .await;
+
+    let mut unrecorded = Vec::new();
+    for (channel, outcome) in outcomes {
+        match &outcome.error {
+            None => {
+                incr(&DELIVERED);
+                info!(run_id=%request.run_id, gate_id=%request.gate_id, channel=%channel.id, attempts=outcome.attempts, "approval_notifier: delivered");
+            }
+            Some(e) => {
+                incr(&FAILED);
+                error!(run_id=%request.run_id, gate_id=%request.gate_id, channel=%channel.id, attempts=outcome.attempts, error=%e, "approval_notifier: delivery failed");
+            }
+        }
+        let event = notification_sent_event(&request, channel, &outcome);
+        if let Err(e) = publish_outcome(js, &request, &event, &channel.id).await {
+            unrecorded.push(format!("{}: {e:#}", channel.id));
+        }
+    }
+
+    if unrecorded.is_empty() {
+        let _ = msg.ack().await;
+    } else {
+        error!(run_id=%request.run_id, gate_id=%request.gate_id, "approval_notifier: failed to record outcomes; nack for redelivery");
+        dlq.retry_or_dead_letter(
+            &msg,
+            &format!(
+                "notification.sent publish failed: {}",
+                unrecorded.join("; ")
+            ),
+        )
+        .await;
+    }
+    Ok(())
+}
+
+pub async fn run_loop(cfg: NotifierConfig) -> Result<()> {
+    info!(
+        consumer=%cfg.consumer_name,
+        channels=cfg.channels.len(),
+        operate_ui_url=%cfg.operate_ui_url,
+        "approval_notifier: starting"
+    );
+    let client = async_nats::connect(&cfg.nats_url).await?;
+    let js = jetstream::new(client);
+    let stream = super::ttl_worker::resolve_stream(&js, &cfg.stream_name).await?;
+    let http = reqwest::Client::new();
+
+    let dlq = DeadLetterQueue::new(js.clone(), &cfg.consumer_name, DlqPolicy::default());
+    let consumer = stream
+        .create_consumer(jetstream::consumer::pull::Config {
+            durable_name: Some(cfg.consumer_name.clone()),
+            filter_subject: cfg.subject_filter.clone(),
+            deliver_policy: DeliverPolicy::New,
+            // Room for every retry of every channel before redelivery
+            ack_wait: Duration::from_secs(300),
+            ..Default::default()
+        })
+        .await?;
+
+    loop {
+        let mut batch = consumer
+            .batch()
+            .max_messages(cfg.batch)
+            .expires(Duration::from_millis(cfg.pull_timeout_ms))
+            .messages()
+            .await?;
+        while let Some(m) = batch.next().await {
+            match m {
+                Ok(m) => handle_message(&js, &http, &dlq, &cfg, m).await?,
+                Err(e) => warn!(error=%e, "approval_notifier: message error"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(kind: ChannelKind, tenants: &[&str], gates: &[&str]) -> NotificationChannel {
+        NotificationChannel {
+            id: "ops".to_string(),
+            kind,
+            url: "https://hooks.example.com/T000".to_string(),
+            tenants: tenants.iter().map(|t| t.to_string()).collect(),
+            gates: gates.iter().map(|g| g.to_string()).collect(),
+            headers: BTreeMap::new(),
+        }
+    }
+
+    fn request() -> ApprovalRequest {
+        ApprovalRequest::from_event(
+            ("acme", "deploy", "run 1"),
+            &json!({
+                "event": "approval.requested:v1",
+                "gateId": "prod/eu",
+                "requester": "dev@example.com",
+                "reason": "ship it"
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn requests_take_missing_ids_from_the_subject() {
+        let request = request();
+        assert_eq!(request.tenant_id, "acme");
+        assert_eq!(request.run_id, "run 1");
+        assert_eq!(request.gate_id, "prod/eu");
+
+        let other = json!({"event": "approval.granted:v1", "gateId": "prod"});
+        assert!(ApprovalRequest::from_event(("acme", "deploy", "r"), &other).is_none());
+        let no_gate = json!({"event": "approval.requested:v1"});
+        assert!(ApprovalRequest::from_event(("acme", "deploy", "r"), &no_gate).is_none());
+    }
+
+    #[test]
+    fn links_are_encoded_deep_links_into_the_run_page() {
+        let links = request().links("https://ui.example.com/");
+        assert_eq!(
+            links.run,
+            "https://ui.example.com/tenants/acme/runs/run%201"
+        );
+        assert_eq!(
+            links.grant,
+            "https://ui.example.com/tenants/acme/runs/run%201?gate=prod%2Feu&decision=grant#approval-actions"
+        );
+        assert!(links.deny.contains("decision=deny"));
+    }
+
+    #[test]
+    fn channels_filter_by_tenant_and_gate() {
+        assert!(channel(ChannelKind::Webhook, &[], &[]).covers("acme", "prod"));
+        assert!(channel(ChannelKind::Webhook, &["acme"], &["prod"]).covers("acme", "prod"));
+        assert!(!channel(ChannelKind::Webhook, &["globex"], &[]).covers("acme", "prod"));
+        assert!(!channel(ChannelKind::Webhook, &[], &["staging"]).covers("acme", "prod"));
+    }
+
+    #[test]
+    fn slack_messages_carry_grant_and_deny_buttons() {
+        let request = request();
+        let links = request.links("https://ui.example.com");
+        let message = message_for(&channel(ChannelKind::Slack, &[], &[]), &request, &links);
+        let buttons = message["blocks"][1]["elements"].as_array().unwrap();
+        assert_eq!(buttons[0]["url"], links.grant.as_str());
+        assert_eq!(buttons[1]["url"], links.deny.as_str());
+        assert!(message["blocks"][0]["text"]["text"]
+            .as_str()
+            .unwrap()
+            .contains(">ship it"));
+
+        let webhook = message_for(&channel(ChannelKind::Webhook, &[], &[]), &request, &links);
+        assert_eq!(webhook["links"]["deny"], links.deny.as_str());
+        assert_eq!(webhook["requester"], "dev@example.com");
+    }
+
+    #[test]
+    fn channel_config_is_validated() {
+        let channels = parse_channels(
+            r#"[{"id": "ops", "type": "slack", "url": "https://hooks.slack.com/services/T/B/X", "tenants": ["acme"]}]"#,
+        )
+        .unwrap();
+        assert_eq!(channels[0].kind, ChannelKind::Slack);
+        assert_eq!(channels[0].tenants, vec!["acme"]);
+
+        assert!(parse_channels(r#"[{"id": "ops", "type": "email", "url": "https://x"}]"#).is_err());
+        assert!(parse_channels(r#"[{"id": "ops", "type": "webhook", "url": "ftp://x"}]"#).is_err());
+        assert!(parse_channels(
+            r#"[{"id": "ops", "type": "webhook", "url": "https://a"}, {"id": "ops", "type": "slack", "url": "https://b"}]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn retries_back_off_and_skip_permanent_client_errors() {
+        assert_eq!(backoff(500, 1), Duration::from_millis(500));
+        assert_eq!(backoff(500, 3), Duration::from_millis(2000));
+        assert_eq!(backoff(500, 20), MAX_BACKOFF);
+        assert!(retryable(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(retryable(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(!retryable(reqwest::StatusCode::NOT_FOUND));
+    }
+
+    #[test]
+    fn failed_deliveries_are_recorded_with_their_error() {
+        let event = notification_sent_event(
+            &request(),
+            &channel(ChannelKind::Webhook, &[], &[]),
+            &DeliveryOutcome {
+                attempts: 5,
+                error: Some("HTTP 503 Service Unavailable".to_string()),
+            },
+        );
+        assert_eq!(event["event"], "notification.sent:v1");
+        assert_eq!(event["outcome"], "failed");
+        assert_eq!(event["attempts"], 5);
+        assert_eq!(event["channelType"], "webhook");
+    }
+}
@@ -0,0 +1,392 @@
+//! Event-driven ritual triggers
+//!
+//! Loads typed-step ritual definitions from `RITUAL_DEFINITIONS_DIR` and, for
+//! each declared trigger, consumes its subject through a durable pull
+//! consumer on whichever stream captures that subject. A matching message is
+//! acked only after `ritual.triggered:v1` has been published, so delivery is
+//! at-least-once; redeliveries reuse the triggering message id in
+//! `Nats-Msg-Id` and are dropped by the ritual stream's duplicate window.
+//! Messages that are not JSON objects, or whose trigger cannot be published
+//! within `RITUAL_DLQ_MAX_DELIVERIES` attempts, move to `RITUAL_DLQ`.
+//!
+//! Runs always belong to the ritual's tenant; events naming another tenant in
+//! `tenantId` are skipped. A consumer that fails is logged and restarted.
+
+use anyhow::{Context, Result};
+use async_nats::jetstream;
+use async_nats::jetstream::{consumer::DeliverPolicy, AckKind, Message};
+use futures_util::{StreamExt, TryStreamExt};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{error, info, warn};
+
+use crate::rituals::definition::{EventTrigger, RitualDefinition};
+use crate::rituals::dlq::{DeadLetterQueue, DlqPolicy};
+use crate::rituals::triggers::{self, RitualTriggered};
+
+static FIRED: AtomicU64 = AtomicU64::new(0);
+static SKIPPED: AtomicU64 = AtomicU64::new(0);
+
+/// Wait before restarting a trigger consumer that failed
+const RESTART_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Clone, Debug)]
+pub struct EventTriggerConfig {
+    pub nats_url: String,
+    pub stream_name: Option<String>,
+    pub definitions_dir: PathBuf, // default: rituals
+    pub consumer_prefix: String,  // default: event-trigger
+    pub batch: usize,             // default: 100
+    pub pull_timeout_ms: u64,     // default: 1500
+}
+
+impl Default for EventTriggerConfig {
+    fn default() -> Self {
+        Self {
+            nats_url: std::env::var("NATS_URL")
+                .unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string()),
+            stream_name: std::env::var("RITUAL_STREAM_NAME").ok(),
+            definitions_dir: std::env::var("RITUAL_DEFINITIONS_DIR")
+                .unwrap_or_else(|_| "rituals".to_string())
+                .into(),
+            consumer_prefix: std::env::var("EVENT_TRIGGER_CONSUMER_PREFIX")
+                .unwrap_or_else(|_| "event-trigger".to_string()),
+            batch: std::env::var("EVENT_TRIGGER_BATCH")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+            pull_timeout_ms: std::env::var("EVENT_TRIGGER_PULL_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1500),
+        }
+    }
+}
+
+/// A trigger together with the ritual it starts
+#[derive(Clone, Debug)]
+pub struct BoundTrigger {
+    pub ritual_id: String,
+    pub tenant_id: Option<String>,
+    pub trigger: EventTrigger,
+}
+
+impl BoundTrigger {
+    /// Unique across rituals; also the `triggerId` on emitted events
+    pub fn key(&self) -> String {
+        format!("{}.{}", self.ritual_id, self.trigger.id)
+    }
+
+    /// Tenant for the new run: always the ritual's (`default` when unset).
+    /// `None` when the event's `tenantId` names a different tenant.
+    pub fn tenant_for(&self, payload: &Value) -> Option<String> {
+        let tenant = self.tenant_id.as_deref().unwrap_or("default");
+        match payload.get("tenantId").and_then(|v| v.as_str()) {
+            Some(event_tenant) if event_tenant != tenant => None,
+            _ => Some(tenant.to_string()),
+        }
+    }
+}
+
+fn incr(a: &AtomicU64) {
+    a.fetch_add(1, Ordering::Relaxed);
+}
+
+/// (fired, skipped) since start
+pub fn counters() -> (u64, u64) {
+    (
+        FIRED.load(Ordering::Relaxed),
+        SKIPPED.load(Ordering::Relaxed),
+    )
+}
+
+/// Every trigger declared by the typed-step definitions in `dir`
+pub fn load_triggers(dir: &Path) -> Result<Vec<BoundTrigger>> {
+    let mut bound = Vec::new();
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("reading rituals from {}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("yaml" | "yml")))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let text = std::fs::read_to_string(&path)?;
+        let doc: Value = match serde_yaml::from_str(&text) {
+            Ok(doc) => doc,
+            Err(e) => {
+                warn!(path=%path.display(), error=%e, "event_triggers: unreadable ritual; skipping");
+                continue;
+            }
+        };
+        if !RitualDefinition::is_definition(&doc) {
+            continue;
+        }
+        match RitualDefinition::from_value(doc) {
+            Ok(def) => bound.extend(def.triggers.iter().map(|trigger| BoundTrigger {
+                ritual_id: def.id.clone(),
+                tenant_id: def.tenant_id.clone(),
+                trigger: trigger.clone(),
+            })),
+            Err(e) => {
+                warn!(path=%path.display(), error=%format!("{e:#}"), "event_triggers: invalid ritual; skipping")
+            }
+        }
+    }
+    Ok(bound)
+}
+
+/// Durable consumer name for a trigger (consumer names cannot contain `.`, `*` or `>`)
+pub fn consumer_name(prefix: &str, bound: &BoundTrigger) -> String {
+    let raw = format!("{}-{}-{}", prefix, bound.ritual_id, bound.trigger.id);
+    raw.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Dedupe key for a triggering message: its own `Nats-Msg-Id` when the
+/// publisher set one, otherwise its stream position
+pub fn source_msg_id(header: Option<&str>, stream: &str, sequence: u64) -> String {
+    match header {
+        Some(id) if !id.is_empty() => id.to_string(),
+        _ => format!("{stream}:{sequence}"),
+    }
+}
+
+/// Whether stream subject `pattern` captures every subject matching `filter`
+pub fn subject_covers(pattern: &str, filter: &str) -> bool {
+    let mut pattern = pattern.split('.');
+    let mut filter = filter.split('.');
+    loop {
+        match (pattern.next(), filter.next()) {
+            (Some(">"), Some(_)) => return true,
+            (Some("*"), Some(token)) if token != ">" => {}
+            (Some(a), Some(b)) if a == b => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Name of the first stream whose subjects capture `subject`
+async fn stream_for_subject(js: &jetstream::Context, subject: &str) -> Result<String> {
+    let mut streams = js.streams();
+    while let Some(info) = streams.try_next().await? {
+        if info
+            .config
+            .subjects
+            .iter()
+            .any(|pattern| subject_covers(pattern, subject))
+        {
+            return Ok(info.config.name);
+        }
+    }
+    anyhow::bail!("no stream captures subject '{subject}'")
+}
+
+/// Handle a single JetStream message for one trigger.
+async fn handle_message(
+    js: &jetstream::Context,
+    dlq: &DeadLetterQueue,
+    bound: &BoundTrigger,
+    msg: Message,
+) -> Result<()> {
+    let subject = msg.message.subject.to_string();
+    let payload: Value = match serde_json::from_slice(&msg.message.payload) {
+        Ok(v @ Value::Object(_)) => v,
+        _ => {
+            warn!(%subject, trigger=%bound.key(), "event_triggers: payload is not a JSON object; dead-letter");
+            dlq.dead_letter(&msg, "payload is not a JSON object").await;
+            return Ok(());
+        }
+    };
+    if !bound.trigger.matches(&payload) {
+        incr(&SKIPPED);
+        let _ = msg.ack().await;
+        return Ok(());
+    }
+    let Some(tenant) = bound.tenant_for(&payload) else {
+        warn!(%subject, trigger=%bound.key(), "event_triggers: event is for another tenant; skipping");
+        incr(&SKIPPED);
+        let _ = msg.ack().await;
+        return Ok(());
+    };
+
+    let header = msg
+        .message
+        .headers
+        .as_ref()
+        .and_then(|h| h.get("Nats-Msg-Id"))
+        .map(|v| v.as_str().to_string());
+    let source = match msg.info() {
+        Ok(info) => source_msg_id(header.as_deref(), info.stream, info.stream_sequence),
+        Err(e) => {
+            warn!(%subject, error=%e, "event_triggers: missing delivery info; nack");
+            let _ = msg
+                .ack_with(AckKind::Nak(Some(std::time::Duration::from_millis(500))))
+                .await;
+            return Ok(());
+        }
+    };
+
+    let triggered = RitualTriggered::from_event(
+        &bound.key(),
+        &tenant,
+        &bound.ritual_id,
+        &subject,
+        &source,
+        payload,
+        bound.trigger.parameters.clone(),
+    );
+    match triggers::publish(js, &triggered).await {
+        Ok(()) => {
+            incr(&FIRED);
+            info!(trigger=%bound.key(), %subject, source_msg_id=%source, run_id=%triggered.run_id, "event_triggers: fired");
+            let _ = msg.ack().await;
+        }
+        Err(e) => {
+            error!(trigger=%bound.key(), %subject, error=%e, "event_triggers: publish failed; nack for redelivery");
+            dlq.retry_or_dead_letter(&msg, &format!("trigger publish failed: {e:#}"))
+                .await;
+        }
+    }
+    Ok(())
+}
+
+async fn consume(
+    js: &jetstream::Context,
+    bound: &BoundTrigger,
+    cfg: &EventTriggerConfig,
+) -> Result<()> {
+    let stream_name = stream_for_subject(js, &bound.trigger.subject).await?;
+    let stream = js.get_stream(&stream_name).await?;
+    let durable_name = consumer_name(&cfg.consumer_prefix, bound);
+    let dlq = DeadLetterQueue::new(js.clone(), &durable_name, DlqPolicy::default());
+    let consumer = stream
+        .create_consumer(jetstream::consumer::pull::Config {
+            durable_name: Some(durable_name),
+            filter_subject: bound.trigger.subject.clone(),
+            deliver_policy: DeliverPolicy::New,
+            ..Default::default()
+        })
+        .await?;
+    info!(trigger=%bound.key(), subject=%bound.trigger.subject, stream=%stream_name, "event_triggers: consuming");
+
+    loop {
+        let mut batch = consumer
+            .batch()
+            .max_messages(cfg.batch)
+            .expires(std::time::Duration::from_millis(cfg.pull_timeout_ms))
+            .messages()
+            .await?;
+        while let Some(m) = batch.next().await {
+            match m {
+                Ok(m) => handle_message(js, &dlq, bound, m).await?,
+                Err(e) => warn!(error=%e, "event_triggers: message error"),
+            }
+        }
+    }
+}
+
+/// Run one trigger's consumer, restarting it whenever it fails
+async fn supervise(js: jetstream::Context, bound: BoundTrigger, cfg: EventTriggerConfig) {
+    loop {
+        if let Err(e) = consume(&js, &bound, &cfg).await {
+            error!(trigger=%bound.key(), error=%format!("{e:#}"), "event_triggers: consumer failed; restarting");
+        }
+        tokio::time::sleep(RESTART_DELAY).await;
+    }
+}
+
+pub async fn run_loop(cfg: EventTriggerConfig) -> Result<()> {
+    info!(?cfg, "event_triggers: starting");
+    let bound = load_triggers(&cfg.definitions_dir)?;
+    if bound.is_empty() {
+        warn!(dir=%cfg.definitions_dir.display(), "event_triggers: no rituals declare triggers");
+        return Ok(());
+    }
+    let client = async_nats::connect(&cfg.nats_url).await?;
+    let js = jetstream::new(client);
+    super::ttl_worker::resolve_stream(&js, &cfg.stream_name).await?;
+
+    let consumers = bound
+        .into_iter()
+        .map(|b| tokio::spawn(supervise(js.clone(), b, cfg.clone())));
+    // Consumers restart themselves, so this only returns if one panics
+    futures_util::future::try_join_all(consumers).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bound(ritual_id: &str, trigger_id: &str, tenant: Option<&str>) -> BoundTrigger {
+        BoundTrigger {
+            ritual_id: ritual_id.to_string(),
+            tenant_id: tenant.map(String::from),
+            trigger: EventTrigger {
+                id: trigger_id.to_string(),
+                subject: "demon.graph.v1.*.*.*.commit".to_string(),
+                event: None,
+                filter: Default::default(),
+                parameters: Value::Null,
+            },
+        }
+    }
+
+    #[test]
+    fn consumer_names_are_sanitized() {
+        let b = bound("deploy.prod", "on-tag", None);
+        assert_eq!(
+            consumer_name("event-trigger", &b),
+            "event-trigger-deploy_prod-on-tag"
+        );
+    }
+
+    #[test]
+    fn source_msg_id_prefers_publisher_header() {
+        assert_eq!(
+            source_msg_id(Some("t:p:n:tag:prod"), "GRAPH", 7),
+            "t:p:n:tag:prod"
+        );
+        assert_eq!(source_msg_id(None, "GRAPH", 7), "GRAPH:7");
+        assert_eq!(source_msg_id(Some(""), "GRAPH", 7), "GRAPH:7");
+    }
+
+    #[test]
+    fn tenant_is_always_the_rituals() {
+        let b = bound("deploy", "on-tag", Some("acme"));
+        assert_eq!(b.tenant_for(&json!({})).as_deref(), Some("acme"));
+        assert_eq!(
+            b.tenant_for(&json!({"tenantId": "acme"})).as_deref(),
+            Some("acme")
+        );
+        assert_eq!(b.tenant_for(&json!({"tenantId": "globex"})), None);
+
+        let unscoped = bound("deploy", "on-tag", None);
+        assert_eq!(unscoped.tenant_for(&json!({})).as_deref(), Some("default"));
+        assert_eq!(unscoped.tenant_for(&json!({"tenantId": "globex"})), None);
+    }
+
+    #[test]
+    fn stream_subjects_cover_trigger_filters() {
+        let filter = "demon.graph.v1.*.*.*.commit";
+        assert!(subject_covers("demon.graph.v1.>", filter));
+        assert!(subject_covers("demon.graph.v1.*.*.*.commit", filter));
+        assert!(subject_covers("demon.*.v1.*.*.*.*", filter));
+        assert!(!subject_covers("demon.graph.v1.acme.*.*.commit", filter));
+        assert!(!subject_covers("demon.ritual.v1.>", filter));
+        assert!(!subject_covers("demon.graph.v1.*", filter));
+        assert!(!subject_covers("demon.graph.*", "demon.graph.>"));
+    }
+}
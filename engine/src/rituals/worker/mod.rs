@@ -1 +1,4 @@
+pub mod approval_notifier;
+pub mod event_triggers;
+pub mod scheduler;
 pub mod ttl_worker;
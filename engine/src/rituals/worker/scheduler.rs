@@ -0,0 +1,385 @@
+//! Cron-style ritual triggers
+//!
+//! Trigger definitions live as JSON values in the `RITUAL_TRIGGERS` KV
+//! bucket (one key per trigger). Every engine replica may run the scheduler;
+//! a lease in the state bucket makes sure only one of them fires at a time.
+//! The state bucket also remembers the last slot checked per trigger, so a
+//! restart or failover neither skips nor repeats a firing. Slots missed while
+//! no scheduler was leading collapse into a single catch-up run.
+
+use anyhow::{Context, Result};
+use async_nats::jetstream::{self, kv};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{debug, info, warn};
+
+use crate::rituals::cron::CronSchedule;
+use crate::rituals::triggers::{self, RitualTriggered};
+
+static FIRED: AtomicU64 = AtomicU64::new(0);
+static SKIPPED: AtomicU64 = AtomicU64::new(0);
+
+const LEASE_KEY: &str = "leader";
+const STATE_PREFIX: &str = "fired.";
+
+#[derive(Clone, Debug)]
+pub struct SchedulerConfig {
+    pub nats_url: String,
+    pub stream_name: Option<String>,
+    pub triggers_bucket: String, // default: RITUAL_TRIGGERS
+    pub state_bucket: String,    // default: RITUAL_SCHEDULER
+    pub instance_id: String,     // default: random per process
+    pub lease_ttl_ms: u64,       // default: 15000
+    pub tick_ms: u64,            // default: 1000
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            nats_url: std::env::var("NATS_URL")
+                .unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string()),
+            stream_name: std::env::var("RITUAL_STREAM_NAME").ok(),
+            triggers_bucket: std::env::var("SCHEDULER_TRIGGERS_BUCKET")
+                .unwrap_or_else(|_| "RITUAL_TRIGGERS".to_string()),
+            state_bucket: std::env::var("SCHEDULER_STATE_BUCKET")
+                .unwrap_or_else(|_| "RITUAL_SCHEDULER".to_string()),
+            instance_id: std::env::var("SCHEDULER_INSTANCE_ID")
+                .unwrap_or_else(|_| uuid::Uuid::new_v4().to_string()),
+            lease_ttl_ms: std::env::var("SCHEDULER_LEASE_TTL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(15_000),
+            tick_ms: std::env::var("SCHEDULER_TICK_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1000),
+        }
+    }
+}
+
+/// A scheduled trigger as stored in the triggers bucket
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerDefinition {
+    pub id: String,
+    #[serde(default = "default_tenant")]
+    pub tenant_id: String,
+    pub ritual_id: String,
+    pub cron: String,
+    #[serde(default)]
+    pub parameters: Value,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn default_tenant() -> String {
+    "default".to_string()
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+impl TriggerDefinition {
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let def: Self = serde_json::from_slice(bytes).context("parsing trigger definition")?;
+        def.schedule()?;
+        Ok(def)
+    }
+
+    pub fn schedule(&self) -> Result<CronSchedule> {
+        CronSchedule::parse(&self.cron).with_context(|| format!("trigger '{}'", self.id))
+    }
+}
+
+/// Leadership lease held by one scheduler replica
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Lease {
+    pub holder: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Lease {
+    pub fn available_to(&self, instance_id: &str, now: DateTime<Utc>) -> bool {
+        self.holder == instance_id || self.expires_at <= now
+    }
+}
+
+/// Per-trigger progress kept in the state bucket
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FireState {
+    /// Slots up to and including this instant have been handled
+    pub checked_through: DateTime<Utc>,
+    pub last_slot: Option<DateTime<Utc>>,
+    pub last_run_id: Option<String>,
+}
+
+/// The slot to fire now, if one fell due since `checked_through`
+pub fn due_slot(
+    schedule: &CronSchedule,
+    checked_through: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    schedule
+        .next_after(checked_through)
+        .filter(|slot| *slot <= now)
+}
+
+fn incr(a: &AtomicU64) {
+    a.fetch_add(1, Ordering::Relaxed);
+}
+
+/// (fired, skipped) since start
+pub fn counters() -> (u64, u64) {
+    (
+        FIRED.load(Ordering::Relaxed),
+        SKIPPED.load(Ordering::Relaxed),
+    )
+}
+
+async fn open_bucket(
+    js: &jetstream::Context,
+    bucket: &str,
+    description: &str,
+) -> Result<kv::Store> {
+    if let Ok(store) = js.get_key_value(bucket).await {
+        return Ok(store);
+    }
+    js.create_key_value(kv::Config {
+        bucket: bucket.to_string(),
+        description: description.to_string(),
+        history: 1,
+        ..Default::default()
+    })
+    .await
+    .with_context(|| format!("creating KV bucket {bucket}"))
+}
+
+/// Take or renew the leadership lease; returns whether this replica leads.
+async fn try_lead(state: &kv::Store, cfg: &SchedulerConfig, now: DateTime<Utc>) -> Result<bool> {
+    let lease = Lease {
+        holder: cfg.instance_id.clone(),
+        expires_at: now + Duration::milliseconds(cfg.lease_ttl_ms as i64),
+    };
+    let value = serde_json::to_vec(&lease)?;
+
+    match state.entry(LEASE_KEY).await? {
+        Some(entry) if matches!(entry.operation, kv::Operation::Put) => {
+            let available = serde_json::from_slice::<Lease>(&entry.value)
+                .map(|current| current.available_to(&cfg.instance_id, now))
+                .unwrap_or(true);
+            if !available {
+                return Ok(false);
+            }
+            Ok(state
+                .update(LEASE_KEY, value.into(), entry.revision)
+                .await
+                .is_ok())
+        }
+        Some(entry) => Ok(state
+            .update(LEASE_KEY, value.into(), entry.revision)
+            .await
+            .is_ok()),
+        // Revision 0 only succeeds while the key has never been written
+        None => Ok(state.update(LEASE_KEY, value.into(), 0).await.is_ok()),
+    }
+}
+
+async fn load_triggers(triggers: &kv::Store) -> Result<Vec<TriggerDefinition>> {
+    let mut keys = triggers.keys().await.context("listing trigger keys")?;
+    let mut defs = Vec::new();
+    while let Some(key) = keys.try_next().await? {
+        let Some(bytes) = triggers.get(&key).await? else {
+            continue;
+        };
+        match TriggerDefinition::from_slice(&bytes) {
+            Ok(def) => defs.push(def),
+            Err(e) => warn!(%key, error=%format!("{e:#}"), "scheduler: invalid trigger; skipping"),
+        }
+    }
+    Ok(defs)
+}
+
+/// Fire one trigger if a slot is due and record its progress.
+async fn tick_trigger(
+    js: &jetstream::Context,
+    state: &kv::Store,
+    def: &TriggerDefinition,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let key = format!("{STATE_PREFIX}{}", def.id);
+    let entry = state.entry(&key).await?;
+    let (current, revision) = match &entry {
+        Some(e) if matches!(e.operation, kv::Operation::Put) => (
+            serde_json::from_slice::<FireState>(&e.value).ok(),
+            Some(e.revision),
+        ),
+        Some(e) => (None, Some(e.revision)),
+        None => (None, None),
+    };
+
+    let next = match current {
+        // First sighting: start counting from now instead of backfilling
+        None => FireState {
+            checked_through: now,
+            last_slot: None,
+            last_run_id: None,
+        },
+        Some(fs) if !def.enabled => {
+            incr(&SKIPPED);
+            FireState {
+                checked_through: now,
+                ..fs
+            }
+        }
+        Some(fs) => match due_slot(&def.schedule()?, fs.checked_through, now) {
+            None => return Ok(()),
+            Some(slot) => {
+                let triggered = RitualTriggered::scheduled(
+                    &def.id,
+                    &def.tenant_id,
+                    &def.ritual_id,
+                    slot,
+                    def.parameters.clone(),
+                );
+                triggers::publish(js, &triggered).await?;
+                incr(&FIRED);
+                info!(trigger=%def.id, ritual=%def.ritual_id, tenant=%def.tenant_id, run_id=%triggered.run_id, %slot, "scheduler: fired");
+                FireState {
+                    checked_through: now,
+                    last_slot: Some(slot),
+                    last_run_id: Some(triggered.run_id),
+                }
+            }
+        },
+    };
+
+    let value = serde_json::to_vec(&next)?.into();
+    let written = match revision {
+        Some(revision) => state.update(&key, value, revision).await.is_ok(),
+        None => state.update(&key, value, 0).await.is_ok(),
+    };
+    if !written {
+        // Another writer got there first; the Nats-Msg-Id dedupes any repeat publish
+        debug!(trigger=%def.id, "scheduler: state changed concurrently");
+    }
+    Ok(())
+}
+
+/// One scheduling pass; returns whether this replica held the lease.
+pub async fn tick(
+    js: &jetstream::Context,
+    triggers: &kv::Store,
+    state: &kv::Store,
+    cfg: &SchedulerConfig,
+) -> Result<bool> {
+    let now = Utc::now();
+    if !try_lead(state, cfg, now).await? {
+        return Ok(false);
+    }
+    for def in load_triggers(triggers).await? {
+        if let Err(e) = tick_trigger(js, state, &def, now).await {
+            warn!(trigger=%def.id, error=%format!("{e:#}"), "scheduler: trigger failed; retrying next tick");
+        }
+    }
+    Ok(true)
+}
+
+pub async fn run_loop(cfg: SchedulerConfig) -> Result<()> {
+    info!(?cfg, "scheduler: starting");
+    let client = async_nats::connect(&cfg.nats_url).await?;
+    let js = jetstream::new(client);
+    super::ttl_worker::resolve_stream(&js, &cfg.stream_name).await?;
+    let triggers = open_bucket(&js, &cfg.triggers_bucket, "Scheduled ritual triggers").await?;
+    let state = open_bucket(
+        &js,
+        &cfg.state_bucket,
+        "Ritual scheduler lease and progress",
+    )
+    .await?;
+
+    let mut leading = false;
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(cfg.tick_ms));
+    loop {
+        interval.tick().await;
+        match tick(&js, &triggers, &state, &cfg).await {
+            Ok(now_leading) => {
+                if now_leading != leading {
+                    info!(instance=%cfg.instance_id, leading=now_leading, "scheduler: leadership changed");
+                    leading = now_leading;
+                }
+            }
+            Err(e) => warn!(error=%format!("{e:#}"), "scheduler: tick failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn trigger_definition_defaults() {
+        let def = TriggerDefinition::from_slice(
+            br#"{"id":"nightly","ritualId":"release","cron":"0 2 * * *"}"#,
+        )
+        .unwrap();
+        assert_eq!(def.tenant_id, "default");
+        assert!(def.enabled);
+        assert_eq!(def.parameters, Value::Null);
+    }
+
+    #[test]
+    fn trigger_definition_rejects_bad_cron() {
+        let err = TriggerDefinition::from_slice(
+            br#"{"id":"nightly","ritualId":"release","cron":"0 25 * * *"}"#,
+        )
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("nightly"));
+    }
+
+    #[test]
+    fn due_slot_fires_once_per_slot() {
+        let cron = CronSchedule::parse("*/15 * * * *").unwrap();
+        let now = at("2025-01-01T10:16:00Z");
+        assert_eq!(
+            due_slot(&cron, at("2025-01-01T10:14:00Z"), now),
+            Some(at("2025-01-01T10:15:00Z"))
+        );
+        // Once checked through `now`, nothing is due until 10:30
+        assert_eq!(due_slot(&cron, now, now), None);
+    }
+
+    #[test]
+    fn due_slot_collapses_missed_slots() {
+        let cron = CronSchedule::parse("0 * * * *").unwrap();
+        let now = at("2025-01-01T15:30:00Z");
+        // Down since 10:00: the first missed slot is fired, the rest are skipped
+        assert_eq!(
+            due_slot(&cron, at("2025-01-01T10:00:00Z"), now),
+            Some(at("2025-01-01T11:00:00Z"))
+        );
+        assert_eq!(due_slot(&cron, now, now), None);
+    }
+
+    #[test]
+    fn lease_is_reclaimable_only_by_holder_or_after_expiry() {
+        let lease = Lease {
+            holder: "a".into(),
+            expires_at: at("2025-01-01T00:00:15Z"),
+        };
+        let before = at("2025-01-01T00:00:10Z");
+        assert!(lease.available_to("a", before));
+        assert!(!lease.available_to("b", before));
+        assert!(lease.available_to("b", at("2025-01-01T00:00:15Z")));
+    }
+}
@@ -1,10 +1,12 @@
 use anyhow::Result;
 use async_nats::jetstream;
-use async_nats::jetstream::{consumer::DeliverPolicy, AckKind, Message};
+use async_nats::jetstream::{consumer::DeliverPolicy, Message};
 use futures_util::StreamExt;
 use std::sync::atomic::{AtomicU64, Ordering};
 use tracing::{error, info, warn};
 
+use crate::rituals::dlq::{DeadLetterQueue, DlqPolicy};
+
 static HANDLED: AtomicU64 = AtomicU64::new(0);
 static EXPIRED: AtomicU64 = AtomicU64::new(0);
 static NOOP: AtomicU64 = AtomicU64::new(0);
@@ -62,7 +64,7 @@ pub fn reset_counters() {
 /// Supports both tenant-aware "demon.ritual.v1.<tenant>.<ritual>.<run>.events"
 /// and legacy "demon.ritual.v1.<ritual>.<run>.events" formats.
 /// Returns (tenant, ritual_id, run_id).
-fn parse_subject(subject: &str) -> Option<(String, String, String)> {
+pub(crate) fn parse_subject(subject: &str) -> Option<(String, String, String)> {
     let parts: Vec<&str> = subject.split('.').collect();
     if parts.len() >= 6 && parts[0] == "demon" && parts[1] == "ritual" && parts[2] == "v1" {
         if parts.len() == 7 {
@@ -88,7 +90,7 @@ fn parse_subject(subject: &str) -> Option<(String, String, String)> {
 }
 
 /// Handle a single JetStream message; returns true if acked.
-async fn handle_message(msg: Message) -> Result<bool> {
+async fn handle_message(dlq: &DeadLetterQueue, msg: Message) -> Result<bool> {
     let subject = msg.message.subject.clone();
     let (tenant, ritual_id, run_id_from_subject) = match parse_subject(&subject) {
         Some(x) => x,
@@ -102,8 +104,8 @@ async fn handle_message(msg: Message) -> Result<bool> {
     let v: serde_json::Value = match serde_json::from_slice(&msg.message.payload) {
         Ok(v) => v,
         Err(e) => {
-            warn!(error=%e, "ttl_worker: invalid JSON; ack and skip");
-            let _ = msg.ack().await;
+            warn!(error=%e, "ttl_worker: invalid JSON; dead-letter");
+            dlq.dead_letter(&msg, &format!("invalid JSON: {e}")).await;
             return Ok(true);
         }
     };
@@ -151,11 +153,10 @@ async fn handle_message(msg: Message) -> Result<bool> {
             }
             Err(e) => {
                 error!(error=%e, %run_id, %gate_id, %ritual_id, "ttl_worker: expiry failed; nack with backoff");
-                // Bounded small backoff then NAK with server-side redelivery delay.
+                // Bounded small backoff, then NAK with server-side redelivery delay
+                // until the message is out of retries and moves to the DLQ.
                 tokio::time::sleep(std::time::Duration::from_millis(250)).await;
-                let _ = msg
-                    .ack_with(AckKind::Nak(Some(std::time::Duration::from_millis(500))))
-                    .await;
+                dlq.retry_or_dead_letter(&msg, &format!("{e:#}")).await;
                 Ok(true)
             }
         }
@@ -188,11 +189,44 @@ async fn handle_message(msg: Message) -> Result<bool> {
             }
             Err(e) => {
                 error!(error=%e, %run_id, %gate_id, %ritual_id, level=%level, "ttl_worker: escalation expiry failed; nack with backoff");
-                // Bounded small backoff then NAK with server-side redelivery delay.
+                // Bounded small backoff, then NAK with server-side redelivery delay
+                // until the message is out of retries and moves to the DLQ.
                 tokio::time::sleep(std::time::Duration::from_millis(250)).await;
-                let _ = msg
-                    .ack_with(AckKind::Nak(Some(std::time::Duration::from_millis(500))))
-                    .await;
+                dlq.retry_or_dead_letter(&msg, &format!("{e:#}")).await;
+                Ok(true)
+            }
+        }
+    }
+    // Try to parse as escalation policy rule timer
+    else if let Some((run_id, gate_id, rule_index)) =
+        crate::rituals::approvals::parse_policy_escalation_timer_id(timer_id)
+    {
+        if run_id != run_id_from_subject {
+            warn!(%subject, run_id=%run_id, sub_run=%run_id_from_subject, rule=%rule_index, "ttl_worker: policy escalation runId mismatch; ack");
+            let _ = msg.ack().await;
+            return Ok(true);
+        }
+        incr(&HANDLED);
+        match crate::rituals::approvals::process_policy_escalation(
+            &tenant, &run_id, &ritual_id, &gate_id, rule_index,
+        )
+        .await
+        {
+            Ok(fired) => {
+                if fired {
+                    incr(&EXPIRED);
+                    info!(%run_id, %gate_id, %ritual_id, rule=%rule_index, "ttl_worker: escalation rule fired");
+                } else {
+                    incr(&NOOP);
+                    info!(%run_id, %gate_id, %ritual_id, rule=%rule_index, "ttl_worker: escalation rule noop");
+                }
+                let _ = msg.ack().await;
+                Ok(true)
+            }
+            Err(e) => {
+                error!(error=%e, %run_id, %gate_id, %ritual_id, rule=%rule_index, "ttl_worker: escalation rule failed; nack with backoff");
+                tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+                dlq.retry_or_dead_letter(&msg, &format!("{e:#}")).await;
                 Ok(true)
             }
         }
@@ -204,7 +238,7 @@ async fn handle_message(msg: Message) -> Result<bool> {
 }
 
 /// Resolve the ritual events stream (env override, then RITUAL_EVENTS, then DEMON_RITUAL_EVENTS).
-async fn resolve_stream(
+pub(crate) async fn resolve_stream(
     js: &jetstream::Context,
     override_name: &Option<String>,
 ) -> Result<jetstream::stream::Stream> {
@@ -229,6 +263,7 @@ pub async fn run_loop(cfg: TtlWorkerConfig) -> Result<()> {
     let js = jetstream::new(client);
     let stream = resolve_stream(&js, &cfg.stream_name).await?;
 
+    let dlq = DeadLetterQueue::new(js.clone(), &cfg.consumer_name, DlqPolicy::default());
     let consumer = stream
         .create_consumer(jetstream::consumer::pull::Config {
             durable_name: Some(cfg.consumer_name.clone()),
@@ -249,7 +284,7 @@ pub async fn run_loop(cfg: TtlWorkerConfig) -> Result<()> {
         while let Some(m) = batch.next().await {
             match m {
                 Ok(m) => {
-                    let _ = handle_message(m).await?;
+                    let _ = handle_message(&dlq, m).await?;
                 }
                 Err(e) => warn!(error=%e, "ttl_worker: message error"),
             }
@@ -262,6 +297,7 @@ pub async fn run_one_batch(cfg: TtlWorkerConfig) -> Result<(u64, u64, u64)> {
     let client = async_nats::connect(&cfg.nats_url).await?;
     let js = jetstream::new(client);
     let stream = resolve_stream(&js, &cfg.stream_name).await?;
+    let dlq = DeadLetterQueue::new(js.clone(), &cfg.consumer_name, DlqPolicy::default());
     let consumer = stream
         .create_consumer(jetstream::consumer::pull::Config {
             durable_name: Some(cfg.consumer_name.clone()),
@@ -279,7 +315,7 @@ pub async fn run_one_batch(cfg: TtlWorkerConfig) -> Result<(u64, u64, u64)> {
         .await?;
     while let Some(m) = batch.next().await {
         if let Ok(m) = m {
-            let _ = handle_message(m).await?;
+            let _ = handle_message(&dlq, m).await?;
         }
     }
     let (h1, e1, n1) = counters();
@@ -312,6 +348,15 @@ mod tests {
         assert_eq!(out, Some(("run-1".into(), "gate-1".into())));
     }
 
+    #[test]
+    fn parse_policy_escalation_timer_id() {
+        let tid = crate::rituals::approvals::policy_escalation_timer_id("run-1", "gate-a", 2);
+        let out = crate::rituals::approvals::parse_policy_escalation_timer_id(&tid);
+        assert_eq!(out, Some(("run-1".to_string(), "gate-a".to_string(), 2)));
+        assert!(crate::rituals::approvals::parse_approval_expiry_timer_id(&tid).is_none());
+        assert!(crate::rituals::approvals::parse_escalation_timer_id(&tid).is_none());
+    }
+
     #[test]
     fn parse_escalation_timer_id() {
         let tid = "run-1:approval:gate-1:expiry:level:2";
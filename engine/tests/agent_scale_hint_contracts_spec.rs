@@ -16,6 +16,14 @@ fn agent_scale_hint_fixtures_validate_against_schema() {
             "../contracts/schemas/events.agent.scale.hint.v1.json",
             "../contracts/fixtures/events/agent.scale.hint.steady.v1.json",
         ),
+        (
+            "../contracts/schemas/events.agent.scale.decision.v1.json",
+            "../contracts/fixtures/events/agent.scale.decision.scaled.v1.json",
+        ),
+        (
+            "../contracts/schemas/events.agent.scale.decision.v1.json",
+            "../contracts/fixtures/events/agent.scale.decision.skipped.v1.json",
+        ),
     ];
 
     for (schema_path, fixture_path) in schemas {
@@ -17,6 +17,10 @@ fn approval_fixtures_validate_against_schemas() {
             "../contracts/schemas/approval.granted.v1.json",
             "../contracts/fixtures/approvals/approval.granted.v1.json",
         ),
+        (
+            "../contracts/schemas/approval.granted.v1.json",
+            "../contracts/fixtures/approvals/approval.granted.delegated.v1.json",
+        ),
         (
             "../contracts/schemas/approval.denied.v1.json",
             "../contracts/fixtures/approvals/approval.denied.v1.json",
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use engine::rituals::checkpoint::{
+    CheckpointStore, MemoryCheckpointStore, RunCheckpoint, RunPhase,
+};
+use engine::rituals::definition::RitualDefinition;
+use engine::rituals::interpreter::{ApprovalOutcome, StepContext, StepRunner};
+use engine::rituals::ledger::{ExecutionLedger, LedgerKey, MemoryExecutionLedger};
+use engine::rituals::Engine;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+/// Counts capsule invocations per step
+#[derive(Default)]
+struct CountingRunner {
+    calls: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl StepRunner for CountingRunner {
+    async fn invoke_capsule(
+        &self,
+        _capsule: &str,
+        args: &Value,
+        ctx: &StepContext,
+    ) -> Result<Value> {
+        self.calls.lock().unwrap().push(ctx.step_id.clone());
+        Ok(json!({ "result": { "success": true, "data": args.clone() } }))
+    }
+
+    async fn await_approval(
+        &self,
+        _gate: &str,
+        _reason: &str,
+        _ttl_seconds: Option<u64>,
+        _ctx: &StepContext,
+    ) -> Result<ApprovalOutcome> {
+        Ok(ApprovalOutcome {
+            granted: true,
+            approver: None,
+            reason: None,
+        })
+    }
+
+    async fn emit(&self, _msg_id: &str, _event: &Value, _ctx: &StepContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+const DEPLOY: &str = r#"
+id: deploy
+version: '1.0'
+steps:
+  - { id: apply, type: capsule, capsule: echo, with: { message: applying } }
+  - { id: notify, type: capsule, capsule: echo, with: { message: done } }
+"#;
+
+#[tokio::test]
+async fn given_ledger_when_step_runs_then_envelope_is_recorded_per_attempt() {
+    let runner = Arc::new(CountingRunner::default());
+    let ledger = Arc::new(MemoryExecutionLedger::new());
+    let mut engine = Engine::new()
+        .with_step_runner(runner)
+        .with_execution_ledger(ledger.clone());
+
+    let evt = engine
+        .run_definition_with_result(RitualDefinition::from_yaml(DEPLOY).unwrap())
+        .await
+        .unwrap();
+
+    let run_id = evt["runId"].as_str().unwrap();
+    let recorded = ledger
+        .get(&LedgerKey::new(run_id, "apply", 1))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(recorded, evt["outputs"]["steps"]["apply"]);
+    assert!(ledger
+        .get(&LedgerKey::new(run_id, "apply", 2))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn given_step_already_executed_when_run_is_redelivered_then_capsule_is_not_invoked_again() {
+    // The engine died after `apply` ran but before its checkpoint was written
+    let definition = RitualDefinition::from_yaml(DEPLOY).unwrap();
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let mut checkpoint = RunCheckpoint::new("run-1", "default", &definition);
+    checkpoint.phase = RunPhase::Running;
+    store.save(&checkpoint).await.unwrap();
+    let ledger = Arc::new(MemoryExecutionLedger::new());
+    let stored = json!({ "result": { "success": true, "data": { "message": "applied once" } } });
+    ledger
+        .record(&LedgerKey::new("run-1", "apply", 1), &stored)
+        .await
+        .unwrap();
+
+    let runner = Arc::new(CountingRunner::default());
+    let completions = Engine::new()
+        .with_step_runner(runner.clone())
+        .with_checkpoints(store)
+        .with_execution_ledger(ledger)
+        .recover_runs()
+        .await
+        .unwrap();
+
+    assert_eq!(*runner.calls.lock().unwrap(), vec!["notify".to_string()]);
+    assert_eq!(completions[0]["outputs"]["steps"]["apply"], stored);
+}
@@ -0,0 +1,70 @@
+use engine::rituals::worker::approval_notifier::{
+    notification_sent_event, parse_channels, ApprovalRequest, DeliveryOutcome,
+};
+use jsonschema::JSONSchema;
+use std::{fs, path::Path};
+
+const SCHEMA: &str = "../contracts/schemas/events.notification.sent.v1.json";
+const FIXTURE: &str = "../contracts/fixtures/events/notification.sent.v1.json";
+
+fn schema() -> JSONSchema {
+    assert!(Path::new(SCHEMA).exists(), "missing {SCHEMA}");
+    let text = fs::read_to_string(SCHEMA).expect(SCHEMA);
+    JSONSchema::compile(&serde_json::from_str(&text).expect("parse schema"))
+        .expect("schema compiles")
+}
+
+#[test]
+fn notification_sent_fixture_validates_against_schema() {
+    assert!(Path::new(FIXTURE).exists(), "missing {FIXTURE}");
+    let schema = schema();
+    let instance: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(FIXTURE).expect(FIXTURE)).expect("parse fixture");
+
+    assert!(
+        schema.validate(&instance).is_ok(),
+        "fixture {} should validate against schema {}. Validation errors: {:?}",
+        FIXTURE,
+        SCHEMA,
+        schema.validate(&instance).unwrap_err().collect::<Vec<_>>()
+    );
+
+    let mut no_attempts = instance.clone();
+    no_attempts["attempts"] = serde_json::json!(0);
+    assert!(
+        schema.validate(&no_attempts).is_err(),
+        "a notification is only recorded after at least one attempt"
+    );
+}
+
+#[test]
+fn notifier_events_validate_against_schema() {
+    let schema = schema();
+    let channels = parse_channels(
+        r#"[{"id": "ops", "type": "webhook", "url": "https://hooks.example.com/approvals"}]"#,
+    )
+    .expect("channels parse");
+    let request = ApprovalRequest::from_event(
+        ("default", "deploy", "run-123"),
+        &serde_json::json!({"event": "approval.requested:v1", "gateId": "prod-release"}),
+    )
+    .expect("approval request");
+
+    for outcome in [
+        DeliveryOutcome {
+            attempts: 1,
+            error: None,
+        },
+        DeliveryOutcome {
+            attempts: 5,
+            error: Some("HTTP 503 Service Unavailable".to_string()),
+        },
+    ] {
+        let event = notification_sent_event(&request, &channels[0], &outcome);
+        assert!(
+            schema.validate(&event).is_ok(),
+            "{event} should validate: {:?}",
+            schema.validate(&event).unwrap_err().collect::<Vec<_>>()
+        );
+    }
+}
@@ -12,6 +12,14 @@ fn policy_fixtures_validate_against_schemas() {
             "../contracts/schemas/policy.decision.v1.json",
             "../contracts/fixtures/events/policy.decision.denied.v1.json",
         ),
+        (
+            "../contracts/schemas/wards.decision.v1.json",
+            "../contracts/fixtures/events/wards.decision.quota_rejected.v1.json",
+        ),
+        (
+            "../contracts/schemas/wards.decision.v1.json",
+            "../contracts/fixtures/events/wards.decision.approval_granted.v1.json",
+        ),
     ];
 
     for (schema_path, fixture_path) in schemas {
@@ -0,0 +1,1155 @@
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use engine::rituals::concurrency::{ConcurrencyLocks, GroupLease, MemoryConcurrencyLocks};
+use engine::rituals::definition::{OnConflict, OnFailure, RitualDefinition, StepKind};
+use engine::rituals::inputs::RunInputs;
+use engine::rituals::interpreter::{ApprovalOutcome, CancelRequest, StepContext, StepRunner};
+use engine::rituals::Engine;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Records every side effect instead of touching NATS or capsules
+#[derive(Default)]
+struct FakeRunner {
+    deny_gates: Vec<String>,
+    /// Remaining failing invocations per capsule name
+    failures: Mutex<HashMap<String, u32>>,
+    calls: Mutex<Vec<String>>,
+    events: Mutex<Vec<Value>>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    /// Request cancellation this long after the run starts
+    cancel_after: Option<Duration>,
+}
+
+impl FakeRunner {
+    fn failing(capsule: &str, times: u32) -> Self {
+        Self {
+            failures: Mutex::new(HashMap::from([(capsule.to_string(), times)])),
+            ..Default::default()
+        }
+    }
+
+    /// Emitted events other than the `step.started`/`step.completed` pair
+    /// bracketing every attempt
+    fn diagnostic_events(&self) -> Vec<Value> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| {
+                !matches!(
+                    e["event"].as_str(),
+                    Some("step.started:v1" | "step.completed:v1")
+                )
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn event_names(&self) -> Vec<String> {
+        self.diagnostic_events()
+            .iter()
+            .map(|e| e["event"].as_str().unwrap_or_default().to_string())
+            .collect()
+    }
+
+    /// `(event, stepId, attempt)` for each lifecycle event, in emission order
+    fn lifecycle(&self) -> Vec<(String, String, u64)> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e["event"].as_str(),
+                    Some("step.started:v1" | "step.completed:v1")
+                )
+            })
+            .map(|e| {
+                (
+                    e["event"].as_str().unwrap_or_default().to_string(),
+                    e["stepId"].as_str().unwrap_or_default().to_string(),
+                    e["attempt"].as_u64().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl StepRunner for FakeRunner {
+    async fn invoke_capsule(
+        &self,
+        capsule: &str,
+        args: &Value,
+        ctx: &StepContext,
+    ) -> Result<Value> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("capsule:{}:{}", ctx.step_id, capsule));
+        if capsule == "slow" {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        }
+        if capsule == "hang" {
+            std::future::pending::<()>().await;
+        }
+        if let Some(remaining) = self.failures.lock().unwrap().get_mut(capsule) {
+            if *remaining > 0 {
+                *remaining -= 1;
+                bail!("capsule {} unavailable", capsule);
+            }
+        }
+        Ok(json!({ "result": { "success": true, "data": args.clone() } }))
+    }
+
+    async fn await_approval(
+        &self,
+        gate: &str,
+        _reason: &str,
+        _ttl_seconds: Option<u64>,
+        ctx: &StepContext,
+    ) -> Result<ApprovalOutcome> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("approval:{}:{}", ctx.step_id, gate));
+        Ok(ApprovalOutcome {
+            granted: !self.deny_gates.iter().any(|g| g == gate),
+            approver: Some("ops@example.com".to_string()),
+            reason: None,
+        })
+    }
+
+    async fn sleep(&self, delay: Duration) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("sleep:{}", delay.as_secs()));
+    }
+
+    async fn emit(&self, _msg_id: &str, event: &Value, _ctx: &StepContext) -> Result<()> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+
+    async fn wait_for_cancel(&self, _ctx: &StepContext) -> Result<CancelRequest> {
+        match self.cancel_after {
+            Some(delay) => {
+                tokio::time::sleep(delay).await;
+                Ok(CancelRequest {
+                    requested_by: Some("ops@example.com".to_string()),
+                    reason: Some("runaway".to_string()),
+                })
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    async fn cancel_run(&self, ctx: &StepContext) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("cancel:{}", ctx.run_id));
+    }
+}
+
+const RELEASE: &str = r#"
+id: release
+version: '1.0'
+steps:
+  - id: build
+    type: capsule
+    capsule: echo
+    with:
+      message: building
+  - id: cool-off
+    type: timer
+    delay: 30s
+  - id: sign-off
+    type: approval
+    gate: deploy
+    ttlSeconds: 3600
+  - id: healthy
+    type: condition
+    when:
+      step: build
+      path: /result/success
+      equals: true
+    then:
+      - id: verify
+        type: parallel
+        steps:
+          - { id: smoke, type: capsule, capsule: echo, with: { message: smoke } }
+          - { id: canary, type: capsule, capsule: echo, with: { message: canary } }
+    else:
+      - { id: rollback, type: capsule, capsule: echo, with: { message: rollback } }
+"#;
+
+fn engine_with(runner: Arc<FakeRunner>) -> Engine {
+    Engine::new().with_step_runner(runner)
+}
+
+#[test]
+fn given_typed_steps_when_parsed_then_each_kind_is_modelled() {
+    let definition = RitualDefinition::from_yaml(RELEASE).unwrap();
+
+    let kinds: Vec<&str> = definition
+        .all_steps()
+        .iter()
+        .map(|s| s.kind.as_str())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "capsule",
+            "timer",
+            "approval",
+            "condition",
+            "parallel",
+            "capsule",
+            "capsule",
+            "capsule"
+        ]
+    );
+    match &definition.steps[2].kind {
+        StepKind::Approval {
+            gate, ttl_seconds, ..
+        } => {
+            assert_eq!(gate, "deploy");
+            assert_eq!(*ttl_seconds, Some(3600));
+        }
+        other => panic!("expected approval step, got {:?}", other),
+    }
+}
+
+#[test]
+fn given_unknown_step_type_when_parsed_then_schema_rejects_it() {
+    let err = RitualDefinition::from_yaml(
+        "id: r\nversion: '1'\nsteps:\n  - { id: a, type: webhook, url: http://x }\n",
+    )
+    .unwrap_err();
+
+    assert!(err.to_string().contains("invalid ritual definition"));
+}
+
+#[test]
+fn given_duplicate_ids_or_forward_references_when_parsed_then_error() {
+    let duplicate = "id: r\nversion: '1'\nsteps:\n  - { id: a, type: timer, delay: 1s }\n  - { id: a, type: timer, delay: 2s }\n";
+    assert!(RitualDefinition::from_yaml(duplicate)
+        .unwrap_err()
+        .to_string()
+        .contains("duplicate step id 'a'"));
+
+    let forward = r#"
+id: r
+version: '1'
+steps:
+  - id: check
+    type: condition
+    when: { step: later, exists: true }
+    then: [{ id: x, type: timer, delay: 1s }]
+  - { id: later, type: timer, delay: 1s }
+"#;
+    assert!(RitualDefinition::from_yaml(forward).is_err());
+
+    let bad_delay = "id: r\nversion: '1'\nsteps:\n  - { id: a, type: timer, delay: soon }\n";
+    assert!(RitualDefinition::from_yaml(bad_delay).is_err());
+}
+
+#[tokio::test]
+async fn given_release_definition_when_run_then_steps_execute_in_order_and_branch() {
+    let runner = Arc::new(FakeRunner::default());
+    let mut engine = engine_with(runner.clone());
+
+    let evt = engine
+        .run_definition_with_result(RitualDefinition::from_yaml(RELEASE).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(evt["event"], "ritual.completed:v1");
+    assert!(evt.get("reason").is_none());
+    let steps = &evt["outputs"]["steps"];
+    assert_eq!(steps["sign-off"]["granted"], true);
+    assert_eq!(steps["healthy"]["matched"], true);
+    assert!(steps.get("rollback").is_none());
+
+    let calls = runner.calls.lock().unwrap().clone();
+    assert_eq!(
+        &calls[..3],
+        &[
+            "capsule:build:echo".to_string(),
+            "sleep:30".to_string(),
+            "approval:sign-off:deploy".to_string()
+        ]
+    );
+    assert!(calls.contains(&"capsule:smoke:echo".to_string()));
+    assert!(calls.contains(&"capsule:canary:echo".to_string()));
+}
+
+#[tokio::test]
+async fn given_denied_gate_when_run_then_run_halts_before_later_steps() {
+    let runner = Arc::new(FakeRunner {
+        deny_gates: vec!["deploy".to_string()],
+        ..Default::default()
+    });
+    let mut engine = engine_with(runner.clone());
+
+    let evt = engine
+        .run_definition_with_result(RitualDefinition::from_yaml(RELEASE).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(evt["reason"], "approval_denied");
+    assert_eq!(evt["outputs"]["steps"]["sign-off"]["granted"], false);
+    assert!(evt["outputs"]["steps"].get("healthy").is_none());
+    assert!(!runner
+        .calls
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|c| c.starts_with("capsule:smoke")));
+}
+
+const FLAKY: &str = r#"
+id: flaky
+version: '1'
+steps:
+  - id: fetch
+    type: capsule
+    capsule: flaky
+    retry:
+      maxAttempts: 3
+      backoff: { strategy: exponential, delay: 2s, maxDelay: 3s }
+    onFailure: { compensate: cleanup }
+  - { id: publish, type: capsule, capsule: echo, with: { message: publish } }
+compensations:
+  - { id: cleanup, type: capsule, capsule: echo, with: { message: cleanup } }
+"#;
+
+#[test]
+fn given_failure_handlers_when_parsed_then_retry_and_compensation_are_modelled() {
+    let definition = RitualDefinition::from_yaml(FLAKY).unwrap();
+
+    let fetch = &definition.steps[0];
+    let retry = fetch.retry.as_ref().unwrap();
+    assert_eq!(retry.max_attempts, 3);
+    assert_eq!(retry.delay_for(1).unwrap(), Duration::from_secs(2));
+    assert_eq!(retry.delay_for(2).unwrap(), Duration::from_secs(3));
+    assert_eq!(
+        fetch.on_failure,
+        OnFailure::Compensate("cleanup".to_string())
+    );
+    assert_eq!(definition.steps[1].on_failure, OnFailure::Abort);
+    assert!(definition.compensation("cleanup").is_some());
+
+    let unknown = FLAKY.replace("compensate: cleanup", "compensate: missing");
+    assert!(RitualDefinition::from_yaml(&unknown)
+        .unwrap_err()
+        .to_string()
+        .contains("unknown step 'missing'"));
+}
+
+#[tokio::test]
+async fn given_flaky_capsule_when_retries_succeed_then_run_continues_and_emits_retried() {
+    let runner = Arc::new(FakeRunner::failing("flaky", 2));
+    let mut engine = engine_with(runner.clone());
+
+    let evt = engine
+        .run_definition_with_result(RitualDefinition::from_yaml(FLAKY).unwrap())
+        .await
+        .unwrap();
+
+    assert!(evt.get("reason").is_none());
+    assert_eq!(evt["outputs"]["steps"]["fetch"]["result"]["success"], true);
+    assert_eq!(
+        runner.event_names(),
+        vec!["step.retried:v1", "step.retried:v1"]
+    );
+    let events = runner.diagnostic_events();
+    assert_eq!(events[1]["attempt"], 2);
+    assert_eq!(events[1]["maxAttempts"], 3);
+    assert_eq!(events[1]["delayMs"], 3000);
+    let calls = runner.calls.lock().unwrap().clone();
+    assert!(calls.contains(&"sleep:2".to_string()));
+    assert!(calls.contains(&"capsule:publish:echo".to_string()));
+}
+
+#[tokio::test]
+async fn given_exhausted_retries_when_compensating_then_cleanup_runs_and_run_halts() {
+    let runner = Arc::new(FakeRunner::failing("flaky", 5));
+    let mut engine = engine_with(runner.clone());
+
+    let evt = engine
+        .run_definition_with_result(RitualDefinition::from_yaml(FLAKY).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(evt["reason"], "compensated");
+    let steps = &evt["outputs"]["steps"];
+    assert_eq!(steps["fetch"]["attempts"], 3);
+    assert!(steps["cleanup"].is_object());
+    assert!(steps.get("publish").is_none());
+
+    let events = runner.events.lock().unwrap().clone();
+    let compensated = events.last().unwrap();
+    assert_eq!(compensated["event"], "step.compensated:v1");
+    assert_eq!(compensated["stepId"], "fetch");
+    assert_eq!(compensated["compensationStepId"], "cleanup");
+    assert_eq!(compensated["outcome"], "completed");
+}
+
+#[tokio::test]
+async fn given_retried_step_when_run_then_each_attempt_is_bracketed_by_started_and_completed() {
+    let runner = Arc::new(FakeRunner::failing("flaky", 2));
+    let mut engine = engine_with(runner.clone());
+
+    engine
+        .run_definition_with_result(RitualDefinition::from_yaml(FLAKY).unwrap())
+        .await
+        .unwrap();
+
+    let expected: Vec<(String, String, u64)> = [
+        ("step.started:v1", "fetch", 1),
+        ("step.completed:v1", "fetch", 1),
+        ("step.started:v1", "fetch", 2),
+        ("step.completed:v1", "fetch", 2),
+        ("step.started:v1", "fetch", 3),
+        ("step.completed:v1", "fetch", 3),
+        ("step.started:v1", "publish", 1),
+        ("step.completed:v1", "publish", 1),
+    ]
+    .iter()
+    .map(|(e, s, a)| (e.to_string(), s.to_string(), *a))
+    .collect();
+    assert_eq!(runner.lifecycle(), expected);
+
+    let events = runner.events.lock().unwrap().clone();
+    let completed: Vec<&Value> = events
+        .iter()
+        .filter(|e| e["event"] == "step.completed:v1" && e["stepId"] == "fetch")
+        .collect();
+    assert_eq!(completed[0]["outcome"], "failed");
+    assert!(completed[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("unavailable"));
+    assert_eq!(completed[2]["outcome"], "succeeded");
+    assert_eq!(completed[2]["kind"], "capsule");
+    assert!(completed[2]["durationMs"].is_u64());
+    // Lifecycle events precede the retry they lead to
+    let names: Vec<&str> = events.iter().filter_map(|e| e["event"].as_str()).collect();
+    assert_eq!(
+        &names[..3],
+        ["step.started:v1", "step.completed:v1", "step.retried:v1"]
+    );
+}
+
+#[tokio::test]
+async fn given_on_failure_continue_or_abort_when_step_fails_then_run_follows_handler() {
+    let continue_def = RitualDefinition::from_yaml(
+        "id: r\nversion: '1'\nsteps:\n  - { id: a, type: capsule, capsule: flaky, onFailure: continue }\n  - { id: b, type: capsule, capsule: echo }\n",
+    )
+    .unwrap();
+    let runner = Arc::new(FakeRunner::failing("flaky", 1));
+    let evt = engine_with(runner.clone())
+        .run_definition_with_result(continue_def)
+        .await
+        .unwrap();
+    assert!(evt.get("reason").is_none());
+    assert!(evt["outputs"]["steps"]["a"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("unavailable"));
+    assert!(evt["outputs"]["steps"]["b"].is_object());
+
+    let abort_def = RitualDefinition::from_yaml(
+        "id: r\nversion: '1'\nsteps:\n  - { id: a, type: capsule, capsule: flaky }\n  - { id: b, type: capsule, capsule: echo }\n",
+    )
+    .unwrap();
+    let runner = Arc::new(FakeRunner::failing("flaky", 1));
+    let evt = engine_with(runner.clone())
+        .run_definition_with_result(abort_def)
+        .await
+        .unwrap();
+    assert_eq!(evt["reason"], "step_failed");
+    assert!(evt["outputs"]["steps"].get("b").is_none());
+    assert!(runner.event_names().is_empty());
+}
+
+fn matrix(join: &str, max_concurrency: usize, capsules: &[&str]) -> RitualDefinition {
+    let branches: Vec<String> = capsules
+        .iter()
+        .enumerate()
+        .map(|(i, c)| format!("      - {{ id: b{}, type: capsule, capsule: {} }}\n", i, c))
+        .collect();
+    RitualDefinition::from_yaml(&format!(
+        "id: matrix\nversion: '1'\nsteps:\n  - id: verify\n    type: parallel\n    maxConcurrency: {}\n    join: {}\n    steps:\n{}",
+        max_concurrency,
+        join,
+        branches.concat()
+    ))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn given_concurrency_limit_when_parallel_runs_then_in_flight_branches_are_bounded() {
+    let runner = Arc::new(FakeRunner::default());
+    let mut engine = engine_with(runner.clone());
+
+    let evt = engine
+        .run_definition_with_result(matrix("all", 2, &["slow", "slow", "slow", "slow"]))
+        .await
+        .unwrap();
+
+    assert!(evt.get("reason").is_none());
+    assert_eq!(runner.max_in_flight.load(Ordering::SeqCst), 2);
+    let verify = &evt["outputs"]["steps"]["verify"];
+    assert_eq!(verify["join"], "all");
+    assert_eq!(verify["succeeded"].as_array().unwrap().len(), 4);
+    assert_eq!(verify["cancelled"], 0);
+}
+
+#[tokio::test]
+async fn given_single_slot_when_parallel_runs_then_waiting_branch_reports_queue_time() {
+    let runner = Arc::new(FakeRunner::default());
+    let mut engine = engine_with(runner.clone());
+
+    engine
+        .run_definition_with_result(matrix("all", 1, &["slow", "slow"]))
+        .await
+        .unwrap();
+
+    let queued: HashMap<String, u64> = runner
+        .events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|e| e["event"] == "step.started:v1")
+        .map(|e| {
+            (
+                e["stepId"].as_str().unwrap().to_string(),
+                e["queuedMs"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(queued.len(), 2);
+    assert!(queued["b0"] < 10);
+    assert!(queued["b1"] >= 10, "b1 waited for b0's slot: {:?}", queued);
+}
+
+#[tokio::test]
+async fn given_any_join_when_first_branch_succeeds_then_remaining_branches_are_cancelled() {
+    let runner = Arc::new(FakeRunner::default());
+    let mut engine = engine_with(runner.clone());
+
+    let evt = engine
+        .run_definition_with_result(matrix("any", 1, &["echo", "never"]))
+        .await
+        .unwrap();
+
+    assert!(evt.get("reason").is_none());
+    assert_eq!(evt["outputs"]["steps"]["verify"]["cancelled"], 1);
+    assert!(!runner
+        .calls
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|c| c.ends_with(":never")));
+}
+
+#[tokio::test]
+async fn given_quorum_join_when_too_many_branches_fail_then_run_halts() {
+    let runner = Arc::new(FakeRunner {
+        failures: Mutex::new(HashMap::from([("flaky".to_string(), 10)])),
+        ..Default::default()
+    });
+    let mut engine = engine_with(runner.clone());
+
+    let met = engine
+        .run_definition_with_result(matrix("{ quorum: 2 }", 3, &["echo", "flaky", "echo"]))
+        .await
+        .unwrap();
+    assert!(met.get("reason").is_none());
+    assert_eq!(met["outputs"]["steps"]["verify"]["required"], 2);
+
+    let unmet = engine
+        .run_definition_with_result(matrix("{ quorum: 2 }", 3, &["flaky", "flaky", "echo"]))
+        .await
+        .unwrap();
+    assert_eq!(unmet["reason"], "join_not_met");
+
+    let all = engine
+        .run_definition_with_result(matrix("all", 3, &["echo", "flaky"]))
+        .await
+        .unwrap();
+    assert_eq!(all["reason"], "step_failed");
+}
+
+#[test]
+fn given_quorum_larger_than_branches_when_parsed_then_error() {
+    let definition = "id: r\nversion: '1'\nsteps:\n  - id: p\n    type: parallel\n    join: { quorum: 3 }\n    steps:\n      - { id: a, type: timer, delay: 1s }\n";
+    assert!(RitualDefinition::from_yaml(definition)
+        .unwrap_err()
+        .to_string()
+        .contains("quorum 3"));
+}
+
+#[tokio::test]
+async fn given_cancel_request_when_step_in_flight_then_run_stops_and_emits_canceled() {
+    let runner = Arc::new(FakeRunner {
+        cancel_after: Some(Duration::from_millis(20)),
+        ..Default::default()
+    });
+    let mut engine = engine_with(runner.clone());
+    let definition = RitualDefinition::from_yaml(
+        "id: r\nversion: '1'\nsteps:\n  - { id: a, type: capsule, capsule: echo }\n  - { id: b, type: capsule, capsule: hang }\n  - { id: c, type: capsule, capsule: echo }\n",
+    )
+    .unwrap();
+
+    let evt = engine.run_definition_with_result(definition).await.unwrap();
+
+    assert_eq!(evt["reason"], "canceled");
+    let steps = &evt["outputs"]["steps"];
+    assert!(steps["a"].is_object());
+    assert!(steps.get("b").is_none());
+    assert!(steps.get("c").is_none());
+
+    let events = runner.events.lock().unwrap().clone();
+    let canceled = events.last().unwrap();
+    assert_eq!(canceled["event"], "run.canceled:v1");
+    assert_eq!(canceled["requestedBy"], "ops@example.com");
+    assert_eq!(canceled["completedSteps"], json!(["a"]));
+    assert!(runner
+        .calls
+        .lock()
+        .unwrap()
+        .contains(&format!("cancel:{}", evt["runId"].as_str().unwrap())));
+}
+
+#[test]
+fn given_event_triggers_when_parsed_then_filters_match_only_the_declared_events() {
+    let definition = RitualDefinition::from_yaml(
+        &std::fs::read_to_string("../examples/rituals/release.yaml").unwrap(),
+    )
+    .expect("release example parses");
+    let trigger = &definition.triggers[0];
+    assert_eq!(trigger.id, "prod-tag");
+    assert_eq!(trigger.subject, "demon.graph.v1.*.*.*.commit");
+
+    let tag = |tag: &str, action: &str| json!({"event": "graph.tag.updated:v1", "tenantId": "t", "tag": tag, "action": action});
+    assert!(trigger.matches(&tag("prod", "set")));
+    assert!(!trigger.matches(&tag("staging", "set")));
+    assert!(!trigger.matches(&tag("prod", "delete")));
+    assert!(!trigger
+        .matches(&json!({"event": "graph.commit.created:v1", "tag": "prod", "action": "set"})));
+}
+
+#[test]
+fn given_invalid_triggers_when_parsed_then_error() {
+    let duplicate = "id: r\nversion: '1'\nsteps:\n  - { id: a, type: timer, delay: 1s }\ntriggers:\n  - { id: t, subject: a.b }\n  - { id: t, subject: a.c }\n";
+    assert!(RitualDefinition::from_yaml(duplicate)
+        .unwrap_err()
+        .to_string()
+        .contains("duplicate trigger id 't'"));
+
+    let bad_pointer = "id: r\nversion: '1'\nsteps:\n  - { id: a, type: timer, delay: 1s }\ntriggers:\n  - { id: t, subject: a.b, match: { tag: prod } }\n";
+    assert!(RitualDefinition::from_yaml(bad_pointer)
+        .unwrap_err()
+        .to_string()
+        .contains("JSON Pointer"));
+
+    let no_subject = "id: r\nversion: '1'\nsteps:\n  - { id: a, type: timer, delay: 1s }\ntriggers:\n  - { id: t }\n";
+    assert!(RitualDefinition::from_yaml(no_subject).is_err());
+}
+
+const PIPELINE: &str = r#"
+id: pipeline
+version: '1'
+steps:
+  - id: build
+    type: capsule
+    capsule: echo
+    with: { artifactUrl: "oci://ghcr.io/demon/app:1.2.3", layers: [{ digest: "sha256:a" }] }
+  - id: deploy
+    type: capsule
+    capsule: container-exec
+    with:
+      image: "${{ steps.build.result.data.artifactUrl }}"
+      firstLayer: "${{ steps.build.result.data.layers[0] }}"
+      note: "deploying ${{ steps.build.result.data.layers[0].digest }}"
+  - id: sign-off
+    type: approval
+    gate: prod
+    reason: "Promote ${{ steps.deploy.result.data.image }}"
+"#;
+
+#[tokio::test]
+async fn given_step_expressions_when_run_then_earlier_outputs_flow_into_later_inputs() {
+    let runner = Arc::new(FakeRunner::default());
+    let evt = engine_with(runner.clone())
+        .run_definition_with_result(RitualDefinition::from_yaml(PIPELINE).unwrap())
+        .await
+        .unwrap();
+
+    assert!(evt.get("reason").is_none(), "{evt:#}");
+    // FakeRunner echoes its arguments back as `result.data`
+    let deploy = &evt["outputs"]["steps"]["deploy"]["result"]["data"];
+    assert_eq!(deploy["image"], "oci://ghcr.io/demon/app:1.2.3");
+    assert_eq!(deploy["firstLayer"], json!({ "digest": "sha256:a" }));
+    assert_eq!(deploy["note"], "deploying sha256:a");
+}
+
+#[tokio::test]
+async fn given_expression_type_error_when_run_then_step_fails_before_it_runs() {
+    let definition = RitualDefinition::from_yaml(
+        r#"
+id: r
+version: '1'
+steps:
+  - { id: build, type: capsule, capsule: echo, with: { layers: [1, 2] } }
+  - id: deploy
+    type: capsule
+    capsule: flaky
+    retry: { maxAttempts: 3 }
+    with: { image: "image ${{ steps.build.result.data.layers }}" }
+  - { id: after, type: capsule, capsule: echo }
+"#,
+    )
+    .unwrap();
+    let runner = Arc::new(FakeRunner::default());
+    let evt = engine_with(runner.clone())
+        .run_definition_with_result(definition)
+        .await
+        .unwrap();
+
+    assert_eq!(evt["reason"], "step_failed");
+    let deploy = &evt["outputs"]["steps"]["deploy"];
+    assert_eq!(deploy["attempts"], 0);
+    assert!(deploy["error"]
+        .as_str()
+        .unwrap()
+        .contains("must be a string, number or boolean, found array"));
+    // Neither attempted nor retried
+    assert_eq!(
+        runner.calls.lock().unwrap().clone(),
+        vec!["capsule:build:echo".to_string()]
+    );
+    assert!(runner.event_names().is_empty());
+}
+
+#[test]
+fn given_expression_to_later_or_sibling_step_when_parsed_then_error() {
+    let later = r#"
+id: r
+version: '1'
+steps:
+  - { id: a, type: capsule, capsule: echo, with: { v: "${{ steps.b.result }}" } }
+  - { id: b, type: capsule, capsule: echo }
+"#;
+    assert!(RitualDefinition::from_yaml(later)
+        .unwrap_err()
+        .to_string()
+        .contains("references unknown or later step 'b'"));
+
+    let sibling = r#"
+id: r
+version: '1'
+steps:
+  - id: fan-out
+    type: parallel
+    steps:
+      - { id: a, type: capsule, capsule: echo }
+      - { id: b, type: capsule, capsule: echo, with: { v: "${{ steps.a.result }}" } }
+"#;
+    assert!(RitualDefinition::from_yaml(sibling).is_err());
+
+    let malformed = "id: r\nversion: '1'\nsteps:\n  - { id: a, type: capsule, capsule: echo, with: { v: \"${{ steps.a\" } }\n";
+    assert!(RitualDefinition::from_yaml(malformed).is_err());
+}
+
+const PARAMETERIZED: &str = r#"
+id: deploy
+version: '1'
+inputs:
+  type: object
+  required: [environment]
+  properties:
+    environment: { type: string, enum: [staging, prod] }
+    replicas: { type: integer, minimum: 1, default: 2 }
+steps:
+  - id: rollout
+    type: capsule
+    capsule: echo
+    with:
+      environment: "${{ inputs.environment }}"
+      replicas: "${{ inputs.replicas }}"
+      message: "rolling out to ${{ inputs.environment }}"
+"#;
+
+#[tokio::test]
+async fn given_declared_inputs_when_run_then_steps_read_validated_values() {
+    let mut inputs = RunInputs::new();
+    inputs.set("environment=prod").unwrap();
+    let runner = Arc::new(FakeRunner::default());
+    let evt = engine_with(runner.clone())
+        .run_definition_with_inputs(RitualDefinition::from_yaml(PARAMETERIZED).unwrap(), &inputs)
+        .await
+        .unwrap();
+
+    let rollout = &evt["outputs"]["steps"]["rollout"]["result"]["data"];
+    assert_eq!(rollout["environment"], "prod");
+    assert_eq!(rollout["replicas"], 2);
+    assert_eq!(rollout["message"], "rolling out to prod");
+    assert_eq!(
+        evt["inputs"],
+        json!({ "environment": "prod", "replicas": 2 })
+    );
+}
+
+#[tokio::test]
+async fn given_invalid_inputs_when_run_then_no_step_runs() {
+    let mut inputs = RunInputs::new();
+    inputs.set("environment=dev").unwrap();
+    let runner = Arc::new(FakeRunner::default());
+    let err = engine_with(runner.clone())
+        .run_definition_with_inputs(RitualDefinition::from_yaml(PARAMETERIZED).unwrap(), &inputs)
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("invalid inputs for ritual 'deploy'"), "{err}");
+    assert!(err.contains("/environment"), "{err}");
+    assert!(runner.calls.lock().unwrap().is_empty());
+    assert!(runner.events.lock().unwrap().is_empty());
+
+    // Required inputs must be supplied even when none are passed
+    assert!(engine_with(runner)
+        .run_definition_with_result(RitualDefinition::from_yaml(PARAMETERIZED).unwrap())
+        .await
+        .is_err());
+}
+
+#[test]
+fn given_undeclared_input_reference_when_parsed_then_error() {
+    let undeclared = PARAMETERIZED.replace("inputs.replicas", "inputs.region");
+    assert!(RitualDefinition::from_yaml(&undeclared)
+        .unwrap_err()
+        .to_string()
+        .contains("references undeclared input 'region'"));
+
+    let no_inputs = r#"
+id: r
+version: '1'
+steps:
+  - { id: a, type: capsule, capsule: echo, with: { v: "${{ inputs.environment }}" } }
+"#;
+    assert!(RitualDefinition::from_yaml(no_inputs)
+        .unwrap_err()
+        .to_string()
+        .contains("declares no inputs"));
+
+    let not_object = "id: r\nversion: '1'\ninputs: { type: string }\nsteps:\n  - { id: a, type: capsule, capsule: echo }\n";
+    assert!(RitualDefinition::from_yaml(not_object).is_err());
+}
+
+const SERIALIZED: &str = r#"
+id: deploy
+version: '1'
+inputs:
+  type: object
+  required: [environment]
+  properties:
+    environment: { type: string }
+concurrency:
+  group: deploy-${{ inputs.environment }}
+  onConflict: queue
+steps:
+  - { id: rollout, type: capsule, capsule: echo, with: { environment: "${{ inputs.environment }}" } }
+"#;
+
+fn prod() -> RunInputs {
+    let mut inputs = RunInputs::new();
+    inputs.set("environment=prod").unwrap();
+    inputs
+}
+
+/// Locks with `deploy-prod` held by another run, released after `release_after`
+async fn held_group(release_after: Option<Duration>) -> Arc<MemoryConcurrencyLocks> {
+    let locks = Arc::new(MemoryConcurrencyLocks::new());
+    let holder = GroupLease::new("default", "deploy-prod", "deploy", "run-0");
+    assert_eq!(locks.try_acquire(&holder).await.unwrap(), None);
+    if let Some(delay) = release_after {
+        let locks = locks.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            locks.release(&holder).await.unwrap();
+        });
+    }
+    locks
+}
+
+#[test]
+fn given_concurrency_group_when_parsed_then_shorthand_and_options_are_modelled() {
+    let full = RitualDefinition::from_yaml(SERIALIZED).unwrap();
+    let concurrency = full.concurrency.unwrap();
+    assert_eq!(concurrency.group, "deploy-${{ inputs.environment }}");
+    assert!(!concurrency.cancel_in_progress);
+    assert_eq!(concurrency.on_conflict, OnConflict::Queue);
+
+    let shorthand = "id: r\nversion: '1'\nconcurrency: deploy-prod\nsteps:\n  - { id: a, type: capsule, capsule: echo }\n";
+    let concurrency = RitualDefinition::from_yaml(shorthand)
+        .unwrap()
+        .concurrency
+        .unwrap();
+    assert_eq!(concurrency.group, "deploy-prod");
+    assert_eq!(concurrency.on_conflict, OnConflict::Queue);
+
+    let contradictory = SERIALIZED.replace(
+        "onConflict: queue",
+        "onConflict: reject\n  cancelInProgress: true",
+    );
+    assert!(RitualDefinition::from_yaml(&contradictory)
+        .unwrap_err()
+        .to_string()
+        .contains("cancelInProgress"));
+    let step_ref = SERIALIZED.replace("inputs.environment }}\n", "steps.rollout.result }}\n");
+    assert!(RitualDefinition::from_yaml(&step_ref)
+        .unwrap_err()
+        .to_string()
+        .contains("may only reference inputs"));
+    assert!(RitualDefinition::from_yaml(&shorthand.replace("deploy-prod", "deploy/prod")).is_err());
+}
+
+#[tokio::test]
+async fn given_held_group_when_reject_on_conflict_then_run_halts_before_any_step() {
+    let definition = SERIALIZED.replace("onConflict: queue", "onConflict: reject");
+    let runner = Arc::new(FakeRunner::default());
+    let evt = engine_with(runner.clone())
+        .with_concurrency_locks(held_group(None).await)
+        .run_definition_with_inputs(RitualDefinition::from_yaml(&definition).unwrap(), &prod())
+        .await
+        .unwrap();
+
+    assert_eq!(evt["reason"], "concurrency_rejected");
+    assert!(runner.calls.lock().unwrap().is_empty());
+
+    // Another group is unaffected
+    let mut staging = RunInputs::new();
+    staging.set("environment=staging").unwrap();
+    let evt = engine_with(runner.clone())
+        .with_concurrency_locks(held_group(None).await)
+        .run_definition_with_inputs(RitualDefinition::from_yaml(&definition).unwrap(), &staging)
+        .await
+        .unwrap();
+    assert!(evt.get("reason").is_none());
+}
+
+#[tokio::test]
+async fn given_held_group_when_queued_then_run_starts_once_released_and_frees_the_group() {
+    let locks = held_group(Some(Duration::from_millis(300))).await;
+    let runner = Arc::new(FakeRunner::default());
+    let started = std::time::Instant::now();
+    let evt = engine_with(runner.clone())
+        .with_concurrency_locks(locks.clone())
+        .run_definition_with_inputs(RitualDefinition::from_yaml(SERIALIZED).unwrap(), &prod())
+        .await
+        .unwrap();
+
+    assert!(started.elapsed() >= Duration::from_millis(300));
+    assert!(evt.get("reason").is_none());
+    assert_eq!(
+        *runner.calls.lock().unwrap(),
+        vec!["capsule:rollout:echo".to_string()]
+    );
+    let next = GroupLease::new("default", "deploy-prod", "deploy", "run-2");
+    assert_eq!(locks.try_acquire(&next).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn given_cancel_in_progress_when_group_held_then_holder_is_asked_to_cancel() {
+    let definition = SERIALIZED.replace("onConflict: queue", "cancelInProgress: true");
+    let runner = Arc::new(FakeRunner::default());
+    let evt = engine_with(runner.clone())
+        .with_concurrency_locks(held_group(Some(Duration::from_millis(100))).await)
+        .run_definition_with_inputs(RitualDefinition::from_yaml(&definition).unwrap(), &prod())
+        .await
+        .unwrap();
+
+    assert!(evt.get("reason").is_none());
+    let events = runner.events.lock().unwrap();
+    let requests: Vec<&Value> = events
+        .iter()
+        .filter(|e| e["event"] == "run.cancel.requested:v1")
+        .collect();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["runId"], "run-0");
+    assert_eq!(requests[0]["requestedBy"], "concurrency:deploy-prod");
+    assert_eq!(
+        requests[0]["reason"],
+        format!("superseded by run {}", evt["runId"].as_str().unwrap())
+    );
+}
+
+#[tokio::test]
+async fn given_step_timeout_when_attempt_hangs_then_timeout_is_emitted_and_retried() {
+    let definition = RitualDefinition::from_yaml(
+        r#"
+id: r
+version: '1'
+steps:
+  - id: stuck
+    type: capsule
+    capsule: hang
+    timeoutSeconds: 1
+    retry: { maxAttempts: 2, backoff: { delay: 1ms } }
+    onFailure: continue
+  - { id: after, type: capsule, capsule: echo }
+"#,
+    )
+    .unwrap();
+    assert_eq!(definition.steps[0].timeout_seconds, Some(1));
+
+    let runner = Arc::new(FakeRunner::default());
+    let evt = engine_with(runner.clone())
+        .run_definition_with_result(definition)
+        .await
+        .unwrap();
+
+    assert!(evt.get("reason").is_none());
+    assert!(evt["outputs"]["steps"]["stuck"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("timed out after 1s"));
+    assert!(evt["outputs"]["steps"]["after"].is_object());
+    assert_eq!(
+        runner.event_names(),
+        vec!["step.timeout:v1", "step.retried:v1", "step.timeout:v1"]
+    );
+    let timeout = runner.diagnostic_events()[0].clone();
+    assert_eq!(timeout["scope"], "step");
+    assert_eq!(timeout["timeoutSeconds"], 1);
+}
+
+#[tokio::test]
+async fn given_run_deadline_when_exceeded_then_run_halts_without_retry_or_later_steps() {
+    let definition = RitualDefinition::from_yaml(
+        r#"
+id: r
+version: '1'
+timeoutSeconds: 1
+steps:
+  - id: stuck
+    type: capsule
+    capsule: hang
+    timeoutSeconds: 60
+    retry: { maxAttempts: 3 }
+    onFailure: continue
+  - { id: after, type: capsule, capsule: echo }
+"#,
+    )
+    .unwrap();
+
+    let runner = Arc::new(FakeRunner::default());
+    let evt = engine_with(runner.clone())
+        .run_definition_with_result(definition)
+        .await
+        .unwrap();
+
+    assert_eq!(evt["reason"], "deadline_exceeded");
+    assert!(evt["outputs"]["steps"].get("after").is_none());
+    assert_eq!(runner.event_names(), vec!["step.timeout:v1"]);
+    assert_eq!(runner.diagnostic_events()[0]["scope"], "run");
+}
+
+#[test]
+fn given_timeout_on_composite_step_when_parsed_then_schema_rejects_it() {
+    let definition = r#"
+id: r
+version: '1'
+steps:
+  - id: fan-out
+    type: parallel
+    timeoutSeconds: 5
+    steps: [{ id: a, type: timer, delay: 1s }]
+"#;
+    assert!(RitualDefinition::from_yaml(definition).is_err());
+}
+
+#[tokio::test]
+async fn given_step_budget_when_invoking_container_exec_then_timeout_is_capped() {
+    let definition = RitualDefinition::from_yaml(
+        r#"
+id: r
+version: '1'
+timeoutSeconds: 3600
+steps:
+  - id: deploy
+    type: capsule
+    capsule: container-exec
+    timeoutSeconds: 30
+    with: { imageDigest: "ghcr.io/x@sha256:1", timeoutSeconds: 600 }
+  - { id: notify, type: capsule, capsule: echo, with: { message: done } }
+"#,
+    )
+    .unwrap();
+
+    let evt = engine_with(Arc::new(FakeRunner::default()))
+        .run_definition_with_result(definition)
+        .await
+        .unwrap();
+
+    // FakeRunner echoes its arguments back as `result.data`
+    let steps = &evt["outputs"]["steps"];
+    assert_eq!(steps["deploy"]["result"]["data"]["timeoutSeconds"], 30);
+    assert!(steps["notify"]["result"]["data"]
+        .get("timeoutSeconds")
+        .is_none());
+}
+
+#[tokio::test]
+async fn given_sensitive_capsule_output_when_run_completes_then_completion_is_redacted() {
+    let definition = RitualDefinition::from_yaml(
+        r#"
+id: r
+version: '1'
+steps:
+  - { id: login, type: capsule, capsule: vault, with: { user: ops, password: hunter2, region: eu } }
+  - { id: notify, type: capsule, capsule: echo, with: { region: eu } }
+"#,
+    )
+    .unwrap();
+    let rules: envelope::RedactionRules = serde_json::from_value(json!({
+        "capsules": { "vault": { "paths": ["$.result.data.region"] } }
+    }))
+    .unwrap();
+
+    let evt = engine_with(Arc::new(FakeRunner::default()))
+        .with_redactor(envelope::Redactor::new(rules).unwrap())
+        .run_definition_with_result(definition)
+        .await
+        .unwrap();
+
+    let login = &evt["outputs"]["steps"]["login"];
+    assert_eq!(login["result"]["data"]["user"], "ops");
+    assert_eq!(login["result"]["data"]["password"], envelope::REDACTED);
+    assert_eq!(login["result"]["data"]["region"], envelope::REDACTED);
+    assert_eq!(
+        login["diagnostics"][0]["source"],
+        envelope::REDACTION_SOURCE
+    );
+    assert_eq!(
+        login["diagnostics"][0]["context"]["redacted_fields"],
+        json!(2)
+    );
+    let notify = &evt["outputs"]["steps"]["notify"];
+    assert_eq!(notify["result"]["data"]["region"], "eu");
+    assert!(notify.get("diagnostics").is_none());
+}
@@ -0,0 +1,68 @@
+//! Ignored by default; requires NATS dev environment.
+use async_nats::jetstream::{self, consumer::DeliverPolicy};
+use engine::rituals::dlq::{self, DeadLetterQueue, DlqPolicy};
+use futures_util::StreamExt;
+
+#[tokio::test]
+#[ignore]
+async fn failing_message_is_dead_lettered_after_max_deliveries_then_requeued() {
+    let nats_url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".into());
+    let client = async_nats::connect(&nats_url).await.unwrap();
+    let js = jetstream::new(client);
+
+    let run = uuid::Uuid::new_v4().to_string();
+    let subject = format!("demon.ritual.v1.default.dlq-spec.{}.events", run);
+    let stream = js
+        .get_or_create_stream(jetstream::stream::Config {
+            name: "RITUAL_EVENTS".into(),
+            subjects: vec!["demon.ritual.v1.>".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    js.publish(subject.clone(), "not json".into())
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+
+    let consumer_name = format!("dlq-spec-{}", run);
+    let consumer = stream
+        .create_consumer(jetstream::consumer::pull::Config {
+            durable_name: Some(consumer_name.clone()),
+            filter_subject: subject.clone(),
+            deliver_policy: DeliverPolicy::All,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let queue = DeadLetterQueue::new(js.clone(), &consumer_name, DlqPolicy { max_deliveries: 2 });
+
+    // First delivery is retried, the second is out of retries
+    for _ in 0..2 {
+        let mut batch = consumer
+            .fetch()
+            .max_messages(1)
+            .expires(std::time::Duration::from_secs(2))
+            .messages()
+            .await
+            .unwrap();
+        let msg = batch.next().await.unwrap().unwrap();
+        queue.retry_or_dead_letter(&msg, "handler failed").await;
+    }
+
+    let letters = dlq::list(&js, Some(&consumer_name)).await.unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].reason, "handler failed");
+    assert_eq!(letters[0].deliveries, 2);
+    assert_eq!(letters[0].source_subject, subject);
+
+    let requeued = dlq::requeue(&js, letters[0].sequence).await.unwrap();
+    assert_eq!(requeued.source_subject, subject);
+    assert!(dlq::list(&js, Some(&consumer_name))
+        .await
+        .unwrap()
+        .is_empty());
+
+    let _ = stream.delete_consumer(&consumer_name).await;
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use engine::rituals::triggers::RitualTriggered;
+use jsonschema::JSONSchema;
+use serde_json::json;
+use std::fs;
+
+const SCHEMA: &str = "../contracts/schemas/events.ritual.triggered.v1.json";
+const FIXTURE: &str = "../contracts/fixtures/events/ritual.triggered.v1.json";
+
+fn schema() -> JSONSchema {
+    let text = fs::read_to_string(SCHEMA).expect(SCHEMA);
+    JSONSchema::compile(&serde_json::from_str(&text).expect("parse schema")).expect("compiles")
+}
+
+#[test]
+fn ritual_triggered_fixture_validates_against_schema() {
+    let fixture: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(FIXTURE).expect(FIXTURE)).expect("parse fixture");
+    let schema = schema();
+    assert!(
+        schema.validate(&fixture).is_ok(),
+        "fixture should validate: {:?}",
+        schema.validate(&fixture).unwrap_err().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn given_scheduled_trigger_when_built_then_event_matches_schema_and_msg_id_is_per_slot() {
+    let slot: DateTime<Utc> = "2025-01-01T02:00:00Z".parse().unwrap();
+    let a = RitualTriggered::scheduled("nightly", "acme", "release", slot, json!({"a": 1}));
+    let b = RitualTriggered::scheduled("nightly", "acme", "release", slot, json!({"a": 1}));
+
+    let event = a.to_event();
+    assert!(schema().is_valid(&event));
+    assert_eq!(event["scheduledFor"], "2025-01-01T02:00:00+00:00");
+    assert_eq!(
+        a.subject(),
+        format!("demon.ritual.v1.acme.release.{}.events", a.run_id)
+    );
+
+    // Two replicas firing the same slot publish with the same message id
+    assert_ne!(a.run_id, b.run_id);
+    assert_eq!(a.msg_id(), b.msg_id());
+    assert_eq!(a.msg_id(), format!("trigger:nightly:{}", slot.timestamp()));
+}
+
+#[test]
+fn given_redelivered_event_when_triggered_twice_then_msg_id_is_keyed_on_source_message() {
+    let payload = json!({"event": "graph.tag.updated:v1", "tenantId": "acme", "tag": "prod"});
+    let build = || {
+        RitualTriggered::from_event(
+            "release.prod-tag",
+            "acme",
+            "release",
+            "demon.graph.v1.acme.p.ns.commit",
+            "acme:p:ns:tag:prod:c1:1700000000000",
+            payload.clone(),
+            json!({"channel": "prod"}),
+        )
+    };
+    let (first, redelivered) = (build(), build());
+
+    let event = first.to_event();
+    assert!(schema().is_valid(&event));
+    assert_eq!(event["source"], "event");
+    assert_eq!(event["triggerEvent"]["tag"], "prod");
+    assert_eq!(first.msg_id(), redelivered.msg_id());
+    assert_eq!(
+        first.msg_id(),
+        "trigger:release.prod-tag:acme:p:ns:tag:prod:c1:1700000000000"
+    );
+}
@@ -0,0 +1,39 @@
+use jsonschema::JSONSchema;
+use std::{fs, path::Path};
+
+#[test]
+fn run_abandoned_fixture_validates_against_schema() {
+    let schemas = [(
+        "../contracts/schemas/events.run.abandoned.v1.json",
+        "../contracts/fixtures/events/run.abandoned.v1.json",
+    )];
+
+    for (schema_path, fixture_path) in schemas {
+        assert!(Path::new(schema_path).exists(), "missing {schema_path}");
+        assert!(Path::new(fixture_path).exists(), "missing {fixture_path}");
+
+        let schema_text = fs::read_to_string(schema_path).expect(schema_path);
+        let fixture_text = fs::read_to_string(fixture_path).expect(fixture_path);
+
+        let schema =
+            JSONSchema::compile(&serde_json::from_str(&schema_text).expect("parse schema"))
+                .expect("schema compiles");
+        let instance: serde_json::Value =
+            serde_json::from_str(&fixture_text).expect("parse fixture");
+
+        assert!(
+            schema.validate(&instance).is_ok(),
+            "fixture {} should validate against schema {}. Validation errors: {:?}",
+            fixture_path,
+            schema_path,
+            schema.validate(&instance).unwrap_err().collect::<Vec<_>>()
+        );
+
+        let mut without_reason = instance.clone();
+        without_reason["reason"] = serde_json::json!("");
+        assert!(
+            schema.validate(&without_reason).is_err(),
+            "an abandoned run must record why"
+        );
+    }
+}
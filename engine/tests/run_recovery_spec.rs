@@ -0,0 +1,191 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use engine::rituals::checkpoint::{CheckpointStore, MemoryCheckpointStore, RunPhase};
+use engine::rituals::definition::RitualDefinition;
+use engine::rituals::interpreter::{ApprovalOutcome, StepContext, StepRunner};
+use engine::rituals::Engine;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Records side effects; `hang` capsules never return while `hang` is set,
+/// standing in for an engine that dies mid-step
+#[derive(Default)]
+struct FakeRunner {
+    hang: bool,
+    deny_gates: Vec<String>,
+    calls: Mutex<Vec<String>>,
+    events: Mutex<Vec<Value>>,
+}
+
+impl FakeRunner {
+    fn events_named(&self, name: &str) -> Vec<Value> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|e| e["event"] == name)
+            .cloned()
+            .collect()
+    }
+
+    /// `to` phase of every `run.state.changed:v1`, in emission order
+    fn phases(&self) -> Vec<String> {
+        self.events_named("run.state.changed:v1")
+            .iter()
+            .map(|e| e["to"].as_str().unwrap_or_default().to_string())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl StepRunner for FakeRunner {
+    async fn invoke_capsule(
+        &self,
+        capsule: &str,
+        args: &Value,
+        ctx: &StepContext,
+    ) -> Result<Value> {
+        self.calls.lock().unwrap().push(ctx.step_id.clone());
+        if capsule == "hang" && self.hang {
+            std::future::pending::<()>().await;
+        }
+        Ok(json!({ "result": { "success": true, "data": args.clone() } }))
+    }
+
+    async fn await_approval(
+        &self,
+        gate: &str,
+        _reason: &str,
+        _ttl_seconds: Option<u64>,
+        ctx: &StepContext,
+    ) -> Result<ApprovalOutcome> {
+        self.calls.lock().unwrap().push(ctx.step_id.clone());
+        Ok(ApprovalOutcome {
+            granted: !self.deny_gates.iter().any(|g| g == gate),
+            approver: Some("ops@example.com".to_string()),
+            reason: None,
+        })
+    }
+
+    async fn emit(&self, _msg_id: &str, event: &Value, _ctx: &StepContext) -> Result<()> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
+const RELEASE: &str = r#"
+id: release
+version: '1.0'
+steps:
+  - { id: build, type: capsule, capsule: echo, with: { message: building } }
+  - { id: sign-off, type: approval, gate: deploy }
+  - { id: deploy, type: capsule, capsule: hang, with: { message: deploying } }
+  - { id: announce, type: capsule, capsule: echo, with: { message: shipped } }
+"#;
+
+fn engine_with(runner: Arc<FakeRunner>, store: Arc<MemoryCheckpointStore>) -> Engine {
+    Engine::new()
+        .with_step_runner(runner)
+        .with_checkpoints(store)
+}
+
+#[tokio::test]
+async fn given_checkpoint_store_when_run_completes_then_each_phase_is_persisted() {
+    let runner = Arc::new(FakeRunner::default());
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let mut engine = engine_with(runner.clone(), store.clone());
+
+    let evt = engine
+        .run_definition_with_result(RitualDefinition::from_yaml(RELEASE).unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        runner.phases(),
+        vec!["running", "awaiting-approval", "running", "completed"]
+    );
+    let checkpoint = store
+        .load(evt["runId"].as_str().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(checkpoint.phase, RunPhase::Completed);
+    assert_eq!(checkpoint.outputs.len(), 4);
+    assert!(store.in_flight().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn given_denied_approval_when_run_halts_then_checkpoint_is_failed_with_reason() {
+    let runner = Arc::new(FakeRunner {
+        deny_gates: vec!["deploy".to_string()],
+        ..Default::default()
+    });
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let mut engine = engine_with(runner.clone(), store.clone());
+
+    let evt = engine
+        .run_definition_with_result(RitualDefinition::from_yaml(RELEASE).unwrap())
+        .await
+        .unwrap();
+
+    let checkpoint = store
+        .load(evt["runId"].as_str().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(checkpoint.phase, RunPhase::Failed);
+    assert_eq!(checkpoint.reason.as_deref(), Some("approval_denied"));
+    let last = runner.events_named("run.state.changed:v1").pop().unwrap();
+    assert_eq!(last["from"], "awaiting-approval");
+    assert_eq!(last["reason"], "approval_denied");
+}
+
+#[tokio::test]
+async fn given_engine_died_mid_run_when_recovered_then_run_resumes_after_last_checkpoint() {
+    let store = Arc::new(MemoryCheckpointStore::new());
+    let crashed = Arc::new(FakeRunner {
+        hang: true,
+        ..Default::default()
+    });
+    let mut engine = engine_with(crashed.clone(), store.clone());
+    let run = engine.run_definition_with_result(RitualDefinition::from_yaml(RELEASE).unwrap());
+    // Dropping the run future mid-step is what a crash looks like to the store
+    assert!(tokio::time::timeout(Duration::from_millis(50), run)
+        .await
+        .is_err());
+    drop(engine);
+
+    let in_flight = store.in_flight().await.unwrap();
+    assert_eq!(in_flight.len(), 1);
+    assert_eq!(in_flight[0].phase, RunPhase::Running);
+    let run_id = in_flight[0].run_id.clone();
+
+    let restarted = Arc::new(FakeRunner::default());
+    let completions = engine_with(restarted.clone(), store.clone())
+        .recover_runs()
+        .await
+        .unwrap();
+
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0]["runId"], run_id.as_str());
+    assert!(completions[0].get("reason").is_none());
+    assert_eq!(
+        *restarted.calls.lock().unwrap(),
+        vec!["deploy".to_string(), "announce".to_string()]
+    );
+    assert_eq!(
+        completions[0]["outputs"]["steps"]["build"]["result"]["data"]["message"],
+        "building"
+    );
+
+    let recovered = restarted.events_named("run.recovered:v1");
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(recovered[0]["completedSteps"], json!(["build", "sign-off"]));
+    assert_eq!(recovered[0]["recoveries"], 1);
+
+    let checkpoint = store.load(&run_id).await.unwrap().unwrap();
+    assert_eq!(checkpoint.phase, RunPhase::Completed);
+    assert_eq!(checkpoint.recoveries, 1);
+    assert!(store.in_flight().await.unwrap().is_empty());
+}
@@ -0,0 +1,151 @@
+use engine::rituals::replay::{replay, RecordedRun, ReplayMode, StepStatus};
+use serde_json::{json, Value};
+
+fn echo(message: &str) -> Value {
+    serde_json::to_value(capsules_echo::echo(message.to_string())).unwrap()
+}
+
+fn release_spec() -> Value {
+    json!({
+        "id": "release",
+        "version": "1",
+        "steps": [
+            { "id": "build", "type": "capsule", "capsule": "echo", "with": { "message": "building" } },
+            { "id": "deploy", "type": "capsule", "capsule": "container-exec", "with": { "imageDigest": "ghcr.io/x@sha256:1" } },
+            { "id": "sign-off", "type": "approval", "gate": "prod" },
+            {
+                "id": "ok",
+                "type": "condition",
+                "when": { "step": "deploy", "path": "/result/success", "equals": true },
+                "then": [{ "id": "announce", "type": "capsule", "capsule": "echo", "with": { "message": "shipped" } }]
+            }
+        ]
+    })
+}
+
+fn recorded_steps() -> serde_json::Map<String, Value> {
+    json!({
+        "build": echo("building"),
+        "deploy": { "result": { "success": true, "data": { "exitCode": 0 } } },
+        "sign-off": { "gateId": "prod", "granted": true, "approver": "ops@example.com", "reason": null },
+        "ok": { "matched": true },
+        "announce": echo("shipped")
+    })
+    .as_object()
+    .cloned()
+    .unwrap()
+}
+
+fn events(spec: Option<Value>, steps: serde_json::Map<String, Value>) -> Vec<Value> {
+    let mut started = json!({
+        "event": "ritual.started:v1",
+        "ritualId": "release",
+        "runId": "run-1",
+        "tenantId": "acme",
+        "ts": "2025-01-01T00:00:00Z"
+    });
+    if let Some(spec) = spec {
+        started["spec"] = spec;
+    }
+    vec![
+        started,
+        json!({ "event": "step.retried:v1", "runId": "run-1", "stepId": "deploy", "attempt": 1 }),
+        json!({
+            "event": "ritual.completed:v1",
+            "ritualId": "release",
+            "runId": "run-1",
+            "tenantId": "acme",
+            "ts": "2025-01-01T00:05:00Z",
+            "outputs": { "steps": steps }
+        }),
+    ]
+}
+
+#[tokio::test]
+async fn given_recorded_run_when_replayed_then_deterministic_steps_rerun_and_all_match() {
+    let recorded =
+        RecordedRun::from_events(&events(Some(release_spec()), recorded_steps())).unwrap();
+    assert_eq!(recorded.tenant_id, "acme");
+    assert_eq!(recorded.event_count, 3);
+
+    let report = replay(&recorded, None).await.unwrap();
+
+    assert!(report.matched, "{:#?}", report);
+    let mode = |id: &str| report.steps.iter().find(|s| s.step_id == id).unwrap().mode;
+    assert_eq!(mode("build"), ReplayMode::Reexecuted);
+    assert_eq!(mode("announce"), ReplayMode::Reexecuted);
+    assert_eq!(mode("deploy"), ReplayMode::Recorded);
+    assert_eq!(mode("sign-off"), ReplayMode::Recorded);
+    assert_eq!(mode("ok"), ReplayMode::Derived);
+    assert!(report.steps.iter().all(|s| s.status == StepStatus::Match));
+}
+
+#[tokio::test]
+async fn given_divergent_output_when_replayed_then_report_flags_the_step() {
+    let mut steps = recorded_steps();
+    steps.insert("build".to_string(), echo("building something else"));
+
+    let recorded = RecordedRun::from_events(&events(Some(release_spec()), steps)).unwrap();
+    let report = replay(&recorded, None).await.unwrap();
+
+    assert!(!report.matched);
+    let diverged: Vec<&str> = report.mismatches().map(|s| s.step_id.as_str()).collect();
+    assert_eq!(diverged, vec!["build"]);
+    let build = report.steps.iter().find(|s| s.step_id == "build").unwrap();
+    assert_eq!(build.status, StepStatus::Mismatch);
+}
+
+#[tokio::test]
+async fn given_invalid_recorded_envelope_when_replayed_then_schema_error_is_reported() {
+    let mut steps = recorded_steps();
+    steps.insert(
+        "deploy".to_string(),
+        json!({ "result": { "data": { "exitCode": 0 } } }),
+    );
+
+    let recorded = RecordedRun::from_events(&events(Some(release_spec()), steps)).unwrap();
+    let report = replay(&recorded, None).await.unwrap();
+
+    let deploy = report.steps.iter().find(|s| s.step_id == "deploy").unwrap();
+    assert!(deploy.envelope_error.is_some());
+    assert!(!report.matched);
+}
+
+#[tokio::test]
+async fn given_events_without_spec_when_replayed_then_fallback_spec_is_required() {
+    let recorded = RecordedRun::from_events(&events(None, recorded_steps())).unwrap();
+
+    let err = replay(&recorded, None).await.unwrap_err();
+    assert!(err.to_string().contains("no ritual spec"));
+
+    let report = replay(&recorded, Some(&release_spec())).await.unwrap();
+    assert!(report.matched);
+}
+
+#[tokio::test]
+async fn given_single_task_run_when_replayed_then_envelope_is_compared() {
+    let spec = json!({
+        "id": "echo-ritual",
+        "version": "1.0",
+        "states": [{
+            "name": "say",
+            "type": "task",
+            "action": { "functionRef": { "refName": "echo", "arguments": { "message": "hi" } } },
+            "end": true
+        }]
+    });
+    let events = vec![
+        json!({ "event": "ritual.started:v1", "ritualId": "echo-ritual", "runId": "run-2", "ts": "t", "spec": spec }),
+        json!({ "event": "ritual.completed:v1", "ritualId": "echo-ritual", "runId": "run-2", "ts": "t", "outputs": echo("hi") }),
+    ];
+
+    let report = replay(&RecordedRun::from_events(&events).unwrap(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(report.tenant_id, "default");
+    assert_eq!(report.steps.len(), 1);
+    assert_eq!(report.steps[0].step_id, "say");
+    assert_eq!(report.steps[0].mode, ReplayMode::Reexecuted);
+    assert!(report.matched, "{:#?}", report);
+}
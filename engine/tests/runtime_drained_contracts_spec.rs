@@ -0,0 +1,29 @@
+use jsonschema::JSONSchema;
+use std::fs;
+
+#[test]
+fn runtime_drained_fixture_validates_against_schema() {
+    let schema_path = "../contracts/schemas/events.runtime.drained.v1.json";
+    let fixture_path = "../contracts/fixtures/events/runtime.drained.v1.json";
+
+    let schema_text = fs::read_to_string(schema_path).expect(schema_path);
+    let fixture_text = fs::read_to_string(fixture_path).expect(fixture_path);
+
+    let schema = JSONSchema::compile(&serde_json::from_str(&schema_text).expect("parse schema"))
+        .expect("schema compiles");
+    let mut instance: serde_json::Value =
+        serde_json::from_str(&fixture_text).expect("parse fixture");
+
+    assert!(
+        schema.validate(&instance).is_ok(),
+        "fixture {} should validate. Validation errors: {:?}",
+        fixture_path,
+        schema.validate(&instance).unwrap_err().collect::<Vec<_>>()
+    );
+
+    instance["interrupted"] = serde_json::json!("run-456");
+    assert!(
+        schema.validate(&instance).is_err(),
+        "run lists must be arrays"
+    );
+}
@@ -1,12 +1,33 @@
 use anyhow::{Context, Result};
+use runtime::app_pack::StoreFingerprint;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// UI card definitions are shared with the runtime and demonctl
+pub use runtime::app_pack::{UiCard as CardDefinition, UiCardMatch as MatchRules};
 
 /// Registry of installed App Packs
+///
+/// Clones share one current [`RegistrySnapshot`]; [`AppPackRegistry::reload`]
+/// swaps in a new snapshot when `registry.json` or a listed manifest changed,
+/// so newly installed cards show up without restarting Operate UI.
 #[derive(Debug, Clone)]
 pub struct AppPackRegistry {
+    registry_path: PathBuf,
+    current: Arc<RwLock<Arc<RegistrySnapshot>>>,
+}
+
+/// Installed packs as of one registry load
+#[derive(Debug, Default)]
+pub struct RegistrySnapshot {
+    /// Increases by one each time the registry is reloaded
+    pub version: u64,
+    fingerprint: StoreFingerprint,
     packs: HashMap<String, Vec<AppPackInfo>>,
 }
 
@@ -31,28 +52,6 @@ pub struct AppPackInfo {
     pub ui_cards: Vec<CardDefinition>,
 }
 
-/// UI card definition from App Pack manifest
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CardDefinition {
-    pub id: String,
-    pub kind: String,
-    #[serde(default)]
-    pub title: Option<String>,
-    #[serde(default)]
-    pub description: Option<String>,
-    #[serde(rename = "match")]
-    pub match_rules: MatchRules,
-    #[serde(default)]
-    pub config: Option<serde_json::Value>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MatchRules {
-    pub rituals: Vec<String>,
-    #[serde(default)]
-    pub tags: Vec<String>,
-}
-
 #[derive(Debug, Clone, Deserialize)]
 struct AppPackManifest {
     metadata: ManifestMetadata,
@@ -81,8 +80,71 @@ impl AppPackRegistry {
 
     /// Load the App Pack registry from a specific path
     pub fn load_from_path(path: &Path) -> Result<Self> {
+        let snapshot = Self::load_snapshot(path, 1)?;
+        Ok(Self {
+            registry_path: path.to_path_buf(),
+            current: Arc::new(RwLock::new(Arc::new(snapshot))),
+        })
+    }
+
+    /// The current snapshot; hold on to it to serve a request consistently
+    pub fn snapshot(&self) -> Arc<RegistrySnapshot> {
+        self.current
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Reload if the registry or a manifest changed; returns whether it did
+    ///
+    /// A registry that fails to parse (e.g. mid-write) keeps the previous
+    /// snapshot in place.
+    pub fn reload(&self) -> Result<bool> {
+        let current = self.snapshot();
+        if StoreFingerprint::of(current.fingerprint.paths()) == current.fingerprint {
+            return Ok(false);
+        }
+
+        let snapshot = Self::load_snapshot(&self.registry_path, current.version + 1)?;
+        info!(
+            version = snapshot.version,
+            packs = snapshot.packs.len(),
+            "Reloaded App Pack registry"
+        );
+        *self.current.write().unwrap_or_else(|p| p.into_inner()) = Arc::new(snapshot);
+        Ok(true)
+    }
+
+    /// Poll for registry changes every `interval`
+    pub fn spawn_watcher(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let registry = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = registry.reload() {
+                    warn!("Failed to reload App Pack registry: {:#}", e);
+                }
+            }
+        })
+    }
+
+    /// Get all card definitions from all installed App Packs
+    pub fn get_all_cards(&self) -> Vec<CardDefinition> {
+        self.snapshot().get_all_cards()
+    }
+
+    /// Get cards matching a specific ritual name
+    pub fn get_cards_for_ritual(&self, ritual_name: &str) -> Vec<CardDefinition> {
+        self.snapshot().get_cards_for_ritual(ritual_name)
+    }
+
+    fn load_snapshot(path: &Path, version: u64) -> Result<RegistrySnapshot> {
         if !path.exists() {
-            return Ok(Self {
+            return Ok(RegistrySnapshot {
+                version,
+                fingerprint: StoreFingerprint::of([path]),
                 packs: HashMap::new(),
             });
         }
@@ -94,11 +156,13 @@ impl AppPackRegistry {
             .with_context(|| format!("Failed to parse registry JSON from '{}'", path.display()))?;
 
         let mut packs = HashMap::new();
+        let mut watched = vec![path.to_path_buf()];
 
         for (name, installs) in registry.apps {
             let mut pack_infos = Vec::new();
 
             for install in installs {
+                watched.push(install.manifest_path.clone());
                 if let Ok(manifest) = Self::load_manifest(&install.manifest_path) {
                     let ui_cards = manifest.ui.map(|ui| ui.cards).unwrap_or_default();
 
@@ -115,28 +179,11 @@ impl AppPackRegistry {
             }
         }
 
-        Ok(Self { packs })
-    }
-
-    /// Get all card definitions from all installed App Packs
-    pub fn get_all_cards(&self) -> Vec<CardDefinition> {
-        let mut cards = Vec::new();
-
-        for pack_versions in self.packs.values() {
-            for pack_info in pack_versions {
-                cards.extend(pack_info.ui_cards.clone());
-            }
-        }
-
-        cards
-    }
-
-    /// Get cards matching a specific ritual name
-    pub fn get_cards_for_ritual(&self, ritual_name: &str) -> Vec<CardDefinition> {
-        self.get_all_cards()
-            .into_iter()
-            .filter(|card| card.matches_ritual(ritual_name))
-            .collect()
+        Ok(RegistrySnapshot {
+            version,
+            fingerprint: StoreFingerprint::of(watched.iter().map(PathBuf::as_path)),
+            packs,
+        })
     }
 
     /// Load an App Pack manifest from a file
@@ -157,15 +204,26 @@ impl AppPackRegistry {
     }
 }
 
-impl CardDefinition {
-    /// Check if this card matches a given ritual name
-    pub fn matches_ritual(&self, ritual_name: &str) -> bool {
-        self.match_rules.rituals.contains(&ritual_name.to_string())
+impl RegistrySnapshot {
+    /// Get all card definitions from all installed App Packs
+    pub fn get_all_cards(&self) -> Vec<CardDefinition> {
+        let mut cards = Vec::new();
+
+        for pack_versions in self.packs.values() {
+            for pack_info in pack_versions {
+                cards.extend(pack_info.ui_cards.clone());
+            }
+        }
+
+        cards
     }
 
-    /// Get the card configuration as a JSON value
-    pub fn get_config(&self) -> Option<&serde_json::Value> {
-        self.config.as_ref()
+    /// Get cards matching a specific ritual name
+    pub fn get_cards_for_ritual(&self, ritual_name: &str) -> Vec<CardDefinition> {
+        self.get_all_cards()
+            .into_iter()
+            .filter(|card| card.matches_ritual(ritual_name))
+            .collect()
     }
 }
 
@@ -242,6 +300,7 @@ ui:
                 rituals: vec!["ritual-a".to_string(), "ritual-b".to_string()],
                 tags: vec![],
             },
+            fields: None,
             config: None,
         };
 
@@ -297,4 +356,53 @@ ui:
         assert_eq!(cards_b.len(), 1);
         assert_eq!(cards_b[0].id, "card-b");
     }
+
+    #[test]
+    fn test_reload_swaps_snapshot_when_registry_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry_path = temp_dir.path().join("registry.json");
+
+        let registry = AppPackRegistry::load_from_path(&registry_path).unwrap();
+        let before = registry.snapshot();
+        assert!(!registry.reload().unwrap());
+
+        let manifest_path = temp_dir.path().join("app-pack.yaml");
+        let manifest_content = r#"
+apiVersion: demon.io/v1
+kind: AppPack
+metadata:
+  name: late-pack
+  version: 1.0.0
+ui:
+  cards:
+    - id: late-card
+      kind: result-envelope
+      match:
+        rituals: ["late-ritual"]
+"#;
+        fs::write(&manifest_path, manifest_content).unwrap();
+        let registry_content = serde_json::json!({
+            "apps": {
+                "late-pack": [{
+                    "version": "1.0.0",
+                    "manifest_path": manifest_path.to_str().unwrap()
+                }]
+            }
+        });
+        fs::write(&registry_path, registry_content.to_string()).unwrap();
+
+        assert!(registry.reload().unwrap());
+        let after = registry.snapshot();
+        assert_eq!(after.version, before.version + 1);
+        assert_eq!(registry.get_cards_for_ritual("late-ritual").len(), 1);
+
+        // Requests holding the earlier snapshot keep a consistent view
+        assert!(before.get_cards_for_ritual("late-ritual").is_empty());
+
+        // A half-written registry leaves the current snapshot in place
+        fs::write(&registry_path, "{\"apps\": {").unwrap();
+        assert!(registry.reload().is_err());
+        assert_eq!(registry.snapshot().version, after.version);
+        assert_eq!(registry.get_cards_for_ritual("late-ritual").len(), 1);
+    }
 }
@@ -1,19 +1,24 @@
-// JWT Authentication middleware for Agent Flow API
+// JWT Authentication middleware for Agent Flow API and role-based access control
 //
 // This module provides JWT token validation and scope enforcement for agent flow endpoints.
 // Tokens are expected to be issued by Auth0 or a compatible JWT issuer.
+//
+// Runs, approvals and admin endpoints are guarded by roles instead (see `AccessControl`),
+// using the same token format as the Schema Registry.
 
 use axum::{
-    extract::Request,
+    extract::{Path, Request, State},
     http::{HeaderMap, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use jwt_auth::{JwtConfig, Role};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tracing::{debug, warn};
+use std::collections::HashMap;
+use tracing::{debug, error, warn};
 
 /// JWT Claims structure
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -155,13 +160,166 @@ pub async fn require_flows_write(request: Request, next: Next) -> Response {
     }
 }
 
+/// How runs, approvals and admin endpoints are protected
+#[derive(Clone, Default)]
+pub enum AccessControl {
+    /// No checks (`OPERATE_UI_AUTH` unset or `none`)
+    #[default]
+    Disabled,
+    /// Bearer JWTs carrying `roles` and `tenants` claims (`OPERATE_UI_AUTH=jwt`)
+    Jwt(JwtConfig),
+    /// `OPERATE_UI_AUTH=jwt` without `JWT_SECRET`, an unknown mode, or a leftover
+    /// `ADMIN_TOKEN` without JWT auth; every guarded request is refused
+    Misconfigured,
+}
+
+impl AccessControl {
+    pub fn from_env() -> Self {
+        let mode = std::env::var("OPERATE_UI_AUTH");
+        // `ADMIN_TOKEN` used to guard the admin endpoints; running them open
+        // just because a deployment was not migrated would be a silent downgrade
+        let admin_token_set = std::env::var("ADMIN_TOKEN").is_ok_and(|t| !t.is_empty());
+        if admin_token_set && mode.as_deref() != Ok("jwt") {
+            error!(
+                "ADMIN_TOKEN is no longer supported; set OPERATE_UI_AUTH=jwt (or unset ADMIN_TOKEN \
+                 to run without auth). Refusing guarded requests"
+            );
+            return AccessControl::Misconfigured;
+        }
+        match mode.as_deref() {
+            Ok("jwt") => match JwtConfig::try_from_env() {
+                Some(config) => AccessControl::Jwt(config),
+                None => {
+                    error!("OPERATE_UI_AUTH=jwt requires JWT_SECRET; refusing guarded requests");
+                    AccessControl::Misconfigured
+                }
+            },
+            Ok("none") | Err(_) => AccessControl::Disabled,
+            Ok(other) => {
+                error!(
+                    "Unknown OPERATE_UI_AUTH '{}' (expected jwt or none); refusing guarded requests",
+                    other
+                );
+                AccessControl::Misconfigured
+            }
+        }
+    }
+
+    /// Verify the request's bearer token without checking any role
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Option<jwt_auth::Claims>, AuthError> {
+        let config = match self {
+            AccessControl::Disabled => return Ok(None),
+            AccessControl::Misconfigured => return Err(AuthError::ConfigurationError),
+            AccessControl::Jwt(config) => config,
+        };
+        let header = headers
+            .get("Authorization")
+            .ok_or(AuthError::MissingToken)?
+            .to_str()
+            .map_err(|_| AuthError::InvalidToken)?;
+        let token = jwt_auth::bearer_token(header).ok_or(AuthError::InvalidToken)?;
+        jwt_auth::verify(token, config)
+            .map(Some)
+            .map_err(|_| AuthError::InvalidToken)
+    }
+
+    /// Check the request's bearer token for `required` on `tenant`
+    pub fn authorize(
+        &self,
+        headers: &HeaderMap,
+        required: Role,
+        tenant: &str,
+    ) -> Result<Option<jwt_auth::Claims>, AuthError> {
+        let Some(claims) = self.authenticate(headers)? else {
+            return Ok(None);
+        };
+        if !claims.grants(required, tenant) {
+            return Err(AuthError::InsufficientRole {
+                required,
+                tenant: tenant.to_string(),
+                subject: claims.sub,
+            });
+        }
+        Ok(Some(claims))
+    }
+}
+
+async fn require_role(
+    required: Role,
+    state: crate::AppState,
+    params: Option<Path<HashMap<String, String>>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    // Routes without a tenant segment act on the default tenant
+    let tenant = params
+        .as_ref()
+        .and_then(|Path(p)| p.get("tenant"))
+        .map(String::as_str)
+        .unwrap_or("default");
+    match state
+        .access_control
+        .authorize(request.headers(), required, tenant)
+    {
+        Ok(claims) => {
+            if let Some(claims) = claims {
+                debug!(
+                    "RBAC allowed {} as {} on tenant {}",
+                    claims.sub, required, tenant
+                );
+                request.extensions_mut().insert(claims);
+            }
+            next.run(request).await
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Middleware to require the viewer role for the route's tenant
+pub async fn require_viewer(
+    State(state): State<crate::AppState>,
+    params: Option<Path<HashMap<String, String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    require_role(Role::Viewer, state, params, request, next).await
+}
+
+/// Middleware to require the operator role for the route's tenant
+pub async fn require_operator(
+    State(state): State<crate::AppState>,
+    params: Option<Path<HashMap<String, String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    require_role(Role::Operator, state, params, request, next).await
+}
+
+/// Middleware to require the admin role for the route's tenant
+pub async fn require_admin(
+    State(state): State<crate::AppState>,
+    params: Option<Path<HashMap<String, String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    require_role(Role::Admin, state, params, request, next).await
+}
+
 /// Authentication errors
 #[derive(Debug)]
 pub enum AuthError {
     MissingToken,
     InvalidToken,
     ConfigurationError,
-    InsufficientScope { required: String, provided: String },
+    InsufficientScope {
+        required: String,
+        provided: String,
+    },
+    InsufficientRole {
+        required: Role,
+        tenant: String,
+        subject: String,
+    },
 }
 
 impl IntoResponse for AuthError {
@@ -196,6 +354,21 @@ impl IntoResponse for AuthError {
                     "Token does not have required scope",
                 )
             }
+            AuthError::InsufficientRole {
+                required,
+                ref tenant,
+                ref subject,
+            } => {
+                warn!(
+                    "Insufficient role - {} needs {} on tenant {}",
+                    subject, required, tenant
+                );
+                (
+                    StatusCode::FORBIDDEN,
+                    "insufficient_role",
+                    "Token does not grant the required role for this tenant",
+                )
+            }
         };
 
         (
@@ -242,4 +415,20 @@ mod tests {
 
         assert!(!claims.has_scope("flows:read"));
     }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_leftover_admin_token_without_jwt_auth_is_misconfigured() {
+        std::env::set_var("ADMIN_TOKEN", "legacy-secret");
+        std::env::remove_var("OPERATE_UI_AUTH");
+        let unset = AccessControl::from_env();
+        std::env::set_var("OPERATE_UI_AUTH", "none");
+        let none = AccessControl::from_env();
+        std::env::remove_var("OPERATE_UI_AUTH");
+        std::env::remove_var("ADMIN_TOKEN");
+
+        assert!(matches!(unset, AccessControl::Misconfigured));
+        assert!(matches!(none, AccessControl::Misconfigured));
+        assert!(matches!(AccessControl::from_env(), AccessControl::Disabled));
+    }
 }
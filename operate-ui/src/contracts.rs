@@ -165,9 +165,7 @@ pub async fn contracts_browser_html(
         &crate::feature_flags::is_enabled("canvas-ui"),
     );
 
-    let html = state
-        .tera
-        .render("contracts_browser.html", &context)
+    let html = crate::metrics::render_template(&state.tera, "contracts_browser.html", &context)
         .map_err(|e| {
             error!("Failed to render contracts browser template: {}", e);
             crate::AppError::from(e)
@@ -0,0 +1,251 @@
+//! Approval delegations (vacation rules)
+//!
+//! Approvers on `APPROVER_ALLOWLIST` hand their gate authority to another
+//! principal for a time window, so gates keep moving while they are out.
+//! Delegations are stored by `wards::delegation` in the
+//! `APPROVAL_DELEGATIONS` KV bucket; grant and deny accept a delegate through
+//! [`approval_authority`], which also holds a delegate to their own token
+//! subject, and record the delegator as `onBehalfOf`.
+//! With token auth enabled, only the delegator can create a delegation and
+//! only the delegator or delegate can remove it.
+
+use crate::routes::{approver_allowed, require_self_approver};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{error, info, warn};
+use wards::delegation::{
+    same_principal, Delegation, DelegationError, DelegationStore, DELEGATIONS_BUCKET,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDelegationBody {
+    pub delegator: String,
+    pub delegate: String,
+    /// Defaults to now
+    #[serde(default)]
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: DateTime<Utc>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListDelegationsQuery {
+    /// Only delegations whose window covers now
+    #[serde(default)]
+    pub active: bool,
+    /// Only delegations from or to this principal
+    pub principal: Option<String>,
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+fn require_csrf_header(headers: &HeaderMap) -> Result<(), Box<Response>> {
+    if headers.get("X-Requested-With").is_none() {
+        return Err(Box::new(error_response(
+            StatusCode::BAD_REQUEST,
+            "X-Requested-With header required",
+        )));
+    }
+    Ok(())
+}
+
+/// The token subject, or `None` when auth is disabled
+fn caller(state: &AppState, headers: &HeaderMap) -> Result<Option<String>, Box<Response>> {
+    match state.access_control.authenticate(headers) {
+        Ok(claims) => Ok(claims.map(|c| c.sub)),
+        Err(e) => Err(Box::new(e.into_response())),
+    }
+}
+
+async fn store(state: &AppState) -> Result<DelegationStore, Response> {
+    let Some(client) = &state.jetstream_client else {
+        return Err(error_response(
+            StatusCode::BAD_GATEWAY,
+            "JetStream is not available",
+        ));
+    };
+    client
+        .key_value(DELEGATIONS_BUCKET, "Approval delegations")
+        .await
+        .map(DelegationStore::new)
+        .map_err(|e| {
+            error!("Failed to open delegations bucket: {}", e);
+            error_response(
+                StatusCode::BAD_GATEWAY,
+                format!("Delegations are unavailable: {}", e),
+            )
+        })
+}
+
+/// Whether the caller may decide gates as `approver`, and for whom.
+///
+/// With token auth on, `approver` must be the authenticated subject, whether
+/// they are allowlisted or a delegate. Allowlisted approvers then act for
+/// themselves (`Ok(None)`); a delegate inside an active window acts on behalf
+/// of whoever delegated to them (`Ok(Some(delegator))`); anyone else gets a
+/// 403. Delegations are only consulted when the approver is not on the
+/// allowlist, so an unreachable KV bucket never blocks direct approvals.
+pub(crate) async fn approval_authority(
+    state: &AppState,
+    claims: Option<&jwt_auth::Claims>,
+    approver: &str,
+) -> Result<Option<String>, Box<Response>> {
+    require_self_approver(claims, approver)?;
+    if approver_allowed(approver) {
+        return Ok(None);
+    }
+    let forbidden = || {
+        Box::new(error_response(
+            StatusCode::FORBIDDEN,
+            "approver not allowed",
+        ))
+    };
+    let store = store(state).await.map_err(|_| forbidden())?;
+    match store
+        .on_behalf_of(approver, Utc::now(), approver_allowed)
+        .await
+    {
+        Ok(Some(delegation)) => Ok(Some(delegation.delegator)),
+        Ok(None) => Err(forbidden()),
+        Err(e) => {
+            warn!("Failed to read delegations for {}: {}", approver, e);
+            Err(forbidden())
+        }
+    }
+}
+
+/// GET /api/delegations - every delegation, optionally only active ones or
+/// those involving one principal
+pub async fn list_delegations_api(
+    State(state): State<AppState>,
+    Query(query): Query<ListDelegationsQuery>,
+) -> Response {
+    let store = match store(&state).await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match store.list().await {
+        Ok(delegations) => {
+            let now = Utc::now();
+            let delegations: Vec<Delegation> = delegations
+                .into_iter()
+                .filter(|d| !query.active || d.is_active_at(now))
+                .filter(|d| {
+                    query.principal.as_deref().is_none_or(|p| {
+                        same_principal(&d.delegator, p) || same_principal(&d.delegate, p)
+                    })
+                })
+                .collect();
+            Json(json!({ "delegations": delegations })).into_response()
+        }
+        Err(e) => {
+            error!("Failed to list delegations: {}", e);
+            error_response(StatusCode::BAD_GATEWAY, "Failed to list delegations")
+        }
+    }
+}
+
+/// POST /api/delegations - delegate an approver's authority for a window;
+/// saving the same delegator and delegate again replaces the window
+pub async fn create_delegation_api(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CreateDelegationBody>,
+) -> Response {
+    if let Err(response) = require_csrf_header(&headers) {
+        return *response;
+    }
+    match caller(&state, &headers) {
+        Ok(Some(sub)) if !same_principal(&sub, &body.delegator) => {
+            return error_response(
+                StatusCode::FORBIDDEN,
+                "only the delegator can delegate their authority",
+            )
+        }
+        Ok(_) => {}
+        Err(response) => return *response,
+    }
+    if !approver_allowed(&body.delegator) {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "delegator is not an allowed approver",
+        );
+    }
+    let delegation = Delegation {
+        delegator: body.delegator.trim().to_string(),
+        delegate: body.delegate.trim().to_string(),
+        starts_at: body.starts_at.unwrap_or_else(Utc::now),
+        ends_at: body.ends_at,
+        reason: body
+            .reason
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty()),
+    };
+    if let Err(e) = delegation.validate() {
+        return error_response(StatusCode::BAD_REQUEST, e.to_string());
+    }
+    let store = match store(&state).await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match store.put(&delegation).await {
+        Ok(()) => {
+            info!(
+                delegator = %delegation.delegator,
+                delegate = %delegation.delegate,
+                ends_at = %delegation.ends_at,
+                "Approval authority delegated"
+            );
+            (StatusCode::CREATED, Json(delegation)).into_response()
+        }
+        Err(DelegationError::Store(e)) => {
+            error!("Failed to save delegation: {}", e);
+            error_response(StatusCode::BAD_GATEWAY, "Failed to save delegation")
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// DELETE /api/delegations/:delegator/:delegate - end a delegation early
+pub async fn delete_delegation_api(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((delegator, delegate)): Path<(String, String)>,
+) -> Response {
+    if let Err(response) = require_csrf_header(&headers) {
+        return *response;
+    }
+    match caller(&state, &headers) {
+        Ok(Some(sub)) if !same_principal(&sub, &delegator) && !same_principal(&sub, &delegate) => {
+            return error_response(
+                StatusCode::FORBIDDEN,
+                "only the delegator or delegate can remove a delegation",
+            )
+        }
+        Ok(_) => {}
+        Err(response) => return *response,
+    }
+    let store = match store(&state).await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match store.remove(&delegator, &delegate).await {
+        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(false) => error_response(StatusCode::NOT_FOUND, "delegation not found"),
+        Err(e) => {
+            error!("Failed to remove delegation: {}", e);
+            error_response(StatusCode::BAD_GATEWAY, "Failed to remove delegation")
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Wizard steps and server-side validation for the schema form renderer
+//!
+//! A schema can split its top-level properties into pages with an `x-wizard`
+//! extension:
+//!
+//! ```json
+//! "x-wizard": { "steps": [
+//!   { "id": "who", "title": "Requester", "properties": ["requester", "tenantId"] },
+//!   { "id": "why", "title": "Reason", "properties": ["reason"] }
+//! ] }
+//! ```
+//!
+//! Properties no step lists are shown on the last one; a schema without
+//! `x-wizard` is a single `form` step. Each step is validated against the full
+//! schema, keeping only the errors for fields on that step.
+
+use jsonschema::{error::ValidationErrorKind, JSONSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Step id used when the schema declares no wizard
+pub const SINGLE_STEP_ID: &str = "form";
+
+/// One page of a multi-step form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WizardStep {
+    pub id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Top-level property names rendered on this step
+    #[serde(default)]
+    pub properties: Vec<String>,
+}
+
+/// A validation failure, located by JSON pointer into the submitted data
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub path: String,
+    pub message: String,
+}
+
+/// The wizard steps for `schema`, covering every top-level property once
+pub fn wizard_steps(schema: &Value) -> Vec<WizardStep> {
+    let declared: Vec<WizardStep> = schema
+        .pointer("/x-wizard/steps")
+        .cloned()
+        .and_then(|steps| serde_json::from_value(steps).ok())
+        .unwrap_or_default();
+    let properties: Vec<String> = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|props| props.keys().cloned().collect())
+        .unwrap_or_default();
+
+    let mut steps: Vec<WizardStep> = Vec::new();
+    let mut assigned: Vec<String> = Vec::new();
+    for mut step in declared {
+        if step.id.is_empty() || steps.iter().any(|s| s.id == step.id) {
+            continue;
+        }
+        let listed = std::mem::take(&mut step.properties);
+        for property in listed {
+            if properties.contains(&property) && !assigned.contains(&property) {
+                assigned.push(property.clone());
+                step.properties.push(property);
+            }
+        }
+        steps.push(step);
+    }
+
+    let unassigned: Vec<String> = properties
+        .iter()
+        .filter(|p| !assigned.contains(p))
+        .cloned()
+        .collect();
+    match steps.last_mut() {
+        Some(last) => last.properties.extend(unassigned),
+        None => steps.push(WizardStep {
+            id: SINGLE_STEP_ID.to_string(),
+            title: schema
+                .get("title")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            description: None,
+            properties: unassigned,
+        }),
+    }
+    steps
+}
+
+/// Validate `data` against `schema`. With `step`, only errors on that step's
+/// fields are returned; errors not tied to a field (such as an unmatched
+/// root-level `oneOf`) are reported on the last step.
+pub fn validate(
+    schema: &Value,
+    data: &Value,
+    step: Option<&str>,
+) -> anyhow::Result<Vec<FieldError>> {
+    let compiled =
+        JSONSchema::compile(schema).map_err(|e| anyhow::anyhow!("Invalid schema: {}", e))?;
+    let errors: Vec<FieldError> = match compiled.validate(data) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|error| {
+                let mut path = error.instance_path.to_string();
+                if let ValidationErrorKind::Required { property } = &error.kind {
+                    if let Some(property) = property.as_str() {
+                        path = format!("{}/{}", path, escape_pointer(property));
+                    }
+                }
+                FieldError {
+                    path,
+                    message: error.to_string(),
+                }
+            })
+            .collect(),
+    };
+
+    let Some(step) = step else {
+        return Ok(errors);
+    };
+    let steps = wizard_steps(schema);
+    let Some(index) = steps.iter().position(|s| s.id == step) else {
+        anyhow::bail!("Unknown wizard step '{}'", step);
+    };
+    let is_last = index + 1 == steps.len();
+    Ok(errors
+        .into_iter()
+        .filter(|error| match top_level_property(&error.path) {
+            Some(property) => steps[index].properties.contains(&property),
+            None => is_last,
+        })
+        .collect())
+}
+
+fn top_level_property(pointer: &str) -> Option<String> {
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .next()
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn wizard_schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["name", "kind"],
+            "properties": {
+                "name": { "type": "string" },
+                "kind": { "enum": ["person", "team"] },
+                "members": { "type": "array", "items": { "type": "object",
+                    "required": ["email"],
+                    "properties": { "email": { "type": "string" } } } },
+                "notes": { "type": "string" }
+            },
+            "if": { "properties": { "kind": { "const": "team" } }, "required": ["kind"] },
+            "then": { "required": ["members"] },
+            "x-wizard": { "steps": [
+                { "id": "basics", "title": "Basics", "properties": ["name", "kind"] },
+                { "id": "team", "title": "Team", "properties": ["members", "kind"] }
+            ] }
+        })
+    }
+
+    #[test]
+    fn steps_default_to_a_single_page() {
+        let steps = wizard_steps(&json!({
+            "title": "Order",
+            "properties": { "a": {}, "b": {} }
+        }));
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].id, SINGLE_STEP_ID);
+        assert_eq!(steps[0].title.as_deref(), Some("Order"));
+        assert_eq!(steps[0].properties, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn declared_steps_assign_each_property_once() {
+        let steps = wizard_steps(&wizard_schema());
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].properties, vec!["name", "kind"]);
+        // `kind` is already on `basics`; unlisted `notes` lands on the last step
+        assert_eq!(steps[1].properties, vec!["members", "notes"]);
+    }
+
+    #[test]
+    fn step_validation_only_reports_that_steps_fields() {
+        let schema = wizard_schema();
+        let data = json!({ "kind": "team", "members": [{}] });
+
+        let basics = validate(&schema, &data, Some("basics")).unwrap();
+        assert_eq!(basics.len(), 1);
+        assert_eq!(basics[0].path, "/name");
+
+        let team = validate(&schema, &data, Some("team")).unwrap();
+        assert_eq!(team.len(), 1);
+        assert_eq!(team[0].path, "/members/0/email");
+
+        assert_eq!(validate(&schema, &data, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn conditional_requirements_are_enforced() {
+        let schema = wizard_schema();
+        let data = json!({ "name": "ops", "kind": "team" });
+        let errors = validate(&schema, &data, Some("team")).unwrap();
+        assert_eq!(errors[0].path, "/members");
+
+        let person = json!({ "name": "ada", "kind": "person" });
+        assert!(validate(&schema, &person, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn unknown_step_is_an_error() {
+        assert!(validate(&wizard_schema(), &json!({}), Some("nope")).is_err());
+    }
+}
@@ -1,16 +1,20 @@
 use anyhow::{Context, Result};
 use async_nats::jetstream::{self, consumer::DeliverPolicy};
 use chrono::{DateTime, Utc};
+use envelope::Redactor;
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
+use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
 /// JetStream client for querying ritual events
 #[derive(Clone)]
 pub struct JetStreamClient {
     jetstream: jetstream::Context,
+    /// Applied to envelopes in run events before they are shown
+    redactor: Arc<Redactor>,
 }
 
 /// Run summary information
@@ -31,6 +35,7 @@ pub enum RunStatus {
     Running,
     Completed,
     Failed,
+    Canceled,
 }
 
 impl std::fmt::Display for RunStatus {
@@ -39,10 +44,75 @@ impl std::fmt::Display for RunStatus {
             RunStatus::Running => write!(f, "Running"),
             RunStatus::Completed => write!(f, "Completed"),
             RunStatus::Failed => write!(f, "Failed"),
+            RunStatus::Canceled => write!(f, "Canceled"),
         }
     }
 }
 
+/// A change to one run in the runs list, derived from a single ritual event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunUpdate {
+    #[serde(rename = "runId")]
+    pub run_id: String,
+    #[serde(rename = "ritualId")]
+    pub ritual_id: String,
+    pub status: RunStatus,
+    /// Event type that produced this update
+    pub event: String,
+    pub ts: DateTime<Utc>,
+    /// Only set when the event is `ritual.started:v1`
+    #[serde(rename = "startTs", skip_serializing_if = "Option::is_none", default)]
+    pub start_ts: Option<DateTime<Utc>>,
+}
+
+/// Size and activity of the ritual events stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamHealth {
+    pub name: String,
+    pub messages: u64,
+    pub bytes: u64,
+    pub last_sequence: u64,
+    pub consumers: usize,
+}
+
+/// Delivery state of one consumer, as reported by JetStream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerHealth {
+    pub name: String,
+    /// Stream messages the consumer has not been delivered yet
+    pub pending: u64,
+    /// Delivered but not yet acknowledged
+    pub ack_pending: usize,
+    pub redelivered: usize,
+    /// Stream sequences between the last delivery and the end of the stream
+    pub lag: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_delivery: Option<DateTime<Utc>>,
+}
+
+/// A stream with all of its consumers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamReport {
+    pub stream: StreamHealth,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_message: Option<DateTime<Utc>>,
+    pub consumers: Vec<ConsumerHealth>,
+}
+
+/// A raw ritual event as delivered by [`JetStreamClient::follow_ritual_events`]
+#[derive(Debug, Clone)]
+pub struct RitualEventMessage {
+    pub subject: String,
+    pub payload: serde_json::Value,
+    /// Stream sequence of this message
+    pub sequence: u64,
+    /// Messages still waiting in the stream after this one
+    pub pending: u64,
+}
+
 /// Detailed run information with events
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunDetail {
@@ -108,6 +178,58 @@ pub struct ScaleHint {
     pub trace_id: Option<String>,
 }
 
+/// The hint a scale decision was made for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaleDecisionHint {
+    pub ts: String,
+    pub reason: String,
+    pub metrics: ScaleMetrics,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+}
+
+/// Action taken by the scale hint handler for a hint (`agent.scale.decision:v1`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaleDecision {
+    pub ts: DateTime<Utc>,
+    pub tenant_id: String,
+    pub recommendation: String,
+    /// scaled | clamped | skipped | forwarded | failed
+    pub outcome: String,
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_replicas: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desired_replicas: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+    pub hint: ScaleDecisionHint,
+}
+
+/// Stream holding scale decisions, from `SCALE_DECISIONS_STREAM`
+fn scale_decisions_stream() -> String {
+    env::var("SCALE_DECISIONS_STREAM").unwrap_or_else(|_| "SCALE_DECISIONS".to_string())
+}
+
+/// Subject scale decisions for `tenant` are published on
+fn scale_decision_subject(tenant: &str) -> String {
+    let tenant: String = tenant
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("demon.scale.v1.{}.decisions", tenant)
+}
+
 impl JetStreamClient {
     /// Create a new JetStream client
     pub async fn new() -> Result<Self> {
@@ -128,8 +250,12 @@ impl JetStreamClient {
         };
 
         let jetstream = jetstream::new(client);
+        let redactor = Arc::new(Redactor::from_env().context("Invalid redaction rules")?);
 
-        Ok(Self { jetstream })
+        Ok(Self {
+            jetstream,
+            redactor,
+        })
     }
 
     /// List recent runs with optional limit (legacy - uses default tenant)
@@ -179,9 +305,9 @@ impl JetStreamClient {
                                 }
                                 // Always update to the most definitive status
                                 match (existing.status, summary.status) {
-                                    (_, RunStatus::Completed) | (_, RunStatus::Failed) => {
-                                        existing.status = summary.status
-                                    }
+                                    (_, RunStatus::Completed)
+                                    | (_, RunStatus::Failed)
+                                    | (_, RunStatus::Canceled) => existing.status = summary.status,
                                     (RunStatus::Running, _) => existing.status = summary.status,
                                     _ => {} // Keep existing status
                                 }
@@ -251,7 +377,9 @@ impl JetStreamClient {
                                     }
                                     // Always update to the most definitive status
                                     match (existing.status, summary.status) {
-                                        (_, RunStatus::Completed) | (_, RunStatus::Failed) => {
+                                        (_, RunStatus::Completed)
+                                        | (_, RunStatus::Failed)
+                                        | (_, RunStatus::Canceled) => {
                                             existing.status = summary.status
                                         }
                                         (RunStatus::Running, _) => existing.status = summary.status,
@@ -775,6 +903,7 @@ impl JetStreamClient {
                 match event_type {
                     "ritual.completed:v1" => (RunStatus::Completed, false),
                     "ritual.failed:v1" => (RunStatus::Failed, false),
+                    "run.canceled:v1" | "run.abandoned:v1" => (RunStatus::Canceled, false),
                     "ritual.started:v1" => (RunStatus::Running, true),
                     _ => (RunStatus::Running, false),
                 }
@@ -803,8 +932,9 @@ impl JetStreamClient {
         &self,
         message: &async_nats::jetstream::Message,
     ) -> Result<Option<RitualEvent>> {
-        let payload: serde_json::Value = serde_json::from_slice(&message.message.payload)
+        let mut payload: serde_json::Value = serde_json::from_slice(&message.message.payload)
             .context("Failed to parse message payload as JSON")?;
+        redact_event_payload(&self.redactor, &mut payload);
 
         let stream_sequence = message.info().ok().map(|info| info.stream_sequence);
 
@@ -974,6 +1104,279 @@ impl JetStreamClient {
         })
     }
 
+    /// Resolve the ritual events stream: `RITUAL_STREAM_NAME`, then `RITUAL_EVENTS`,
+    /// then the deprecated `DEMON_RITUAL_EVENTS`
+    async fn ritual_stream(&self) -> Result<jetstream::stream::Stream> {
+        if let Ok(name) = std::env::var("RITUAL_STREAM_NAME") {
+            return self
+                .jetstream
+                .get_stream(&name)
+                .await
+                .with_context(|| format!("JetStream stream '{}' not found", name));
+        }
+        match self.jetstream.get_stream("RITUAL_EVENTS").await {
+            Ok(s) => Ok(s),
+            Err(_) => {
+                let s = self
+                    .jetstream
+                    .get_stream("DEMON_RITUAL_EVENTS")
+                    .await
+                    .with_context(|| "JetStream stream 'RITUAL_EVENTS' not found")?;
+                warn!("Using deprecated stream name 'DEMON_RITUAL_EVENTS'");
+                Ok(s)
+            }
+        }
+    }
+
+    /// Open a KV bucket, creating it on first use
+    pub async fn key_value(&self, bucket: &str, description: &str) -> Result<jetstream::kv::Store> {
+        if let Ok(store) = self.jetstream.get_key_value(bucket).await {
+            return Ok(store);
+        }
+        self.jetstream
+            .create_key_value(jetstream::kv::Config {
+                bucket: bucket.to_string(),
+                description: description.to_string(),
+                history: 1,
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("Failed to create KV bucket '{}'", bucket))
+    }
+
+    /// Delete every event of one run from the ritual stream; the default tenant
+    /// also owns the legacy 6-part subject. Returns the number of messages purged.
+    pub async fn purge_run(&self, tenant: &str, ritual_id: &str, run_id: &str) -> Result<u64> {
+        let stream = self.ritual_stream().await?;
+        let mut subjects = vec![format!(
+            "demon.ritual.v1.{}.{}.{}.events",
+            tenant, ritual_id, run_id
+        )];
+        if tenant == "default" {
+            subjects.push(format!("demon.ritual.v1.{}.{}.events", ritual_id, run_id));
+        }
+        let mut purged = 0;
+        for subject in subjects {
+            let response = stream
+                .purge()
+                .filter(subject.as_str())
+                .await
+                .with_context(|| format!("Failed to purge '{}'", subject))?;
+            purged += response.purged;
+        }
+        Ok(purged)
+    }
+
+    /// Current state of the ritual events stream
+    pub async fn ritual_stream_health(&self) -> Result<StreamHealth> {
+        let mut stream = self.ritual_stream().await?;
+        let info = stream.info().await.context("Failed to get stream info")?;
+        Ok(StreamHealth {
+            name: info.config.name.clone(),
+            messages: info.state.messages,
+            bytes: info.state.bytes,
+            last_sequence: info.state.last_sequence,
+            consumers: info.state.consumer_count,
+        })
+    }
+
+    /// Stream state and consumer delivery state of the ritual events stream
+    pub async fn ritual_stream_report(&self) -> Result<StreamReport> {
+        let stream = self.ritual_stream().await?;
+        Self::stream_report_for(stream).await
+    }
+
+    /// Stream state and consumer delivery state of the stream called `name`
+    pub async fn stream_report(&self, name: &str) -> Result<StreamReport> {
+        let stream = self
+            .jetstream
+            .get_stream(name)
+            .await
+            .with_context(|| format!("JetStream stream '{}' not found", name))?;
+        Self::stream_report_for(stream).await
+    }
+
+    async fn stream_report_for(mut stream: jetstream::stream::Stream) -> Result<StreamReport> {
+        let info = stream.info().await.context("Failed to get stream info")?;
+        let last_sequence = info.state.last_sequence;
+        let stream_health = StreamHealth {
+            name: info.config.name.clone(),
+            messages: info.state.messages,
+            bytes: info.state.bytes,
+            last_sequence,
+            consumers: info.state.consumer_count,
+        };
+        let last_message = (info.state.messages > 0)
+            .then(|| {
+                let ts = info.state.last_timestamp;
+                DateTime::from_timestamp(ts.unix_timestamp(), ts.nanosecond())
+            })
+            .flatten();
+
+        let mut consumers = Vec::new();
+        let mut infos = stream.consumers();
+        while let Some(consumer) = infos.next().await {
+            let consumer = consumer.context("Failed to list stream consumers")?;
+            consumers.push(ConsumerHealth {
+                name: consumer.name.clone(),
+                pending: consumer.num_pending,
+                ack_pending: consumer.num_ack_pending,
+                redelivered: consumer.num_redelivered,
+                lag: last_sequence.saturating_sub(consumer.delivered.stream_sequence),
+                last_delivery: consumer
+                    .delivered
+                    .last_active
+                    .and_then(|ts| DateTime::from_timestamp(ts.unix_timestamp(), ts.nanosecond())),
+            });
+        }
+        consumers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(StreamReport {
+            stream: stream_health,
+            last_message,
+            consumers,
+        })
+    }
+
+    /// Read every ritual event for all tenants from the start of the stream, then
+    /// keep following new ones; also returns how many events were stored when the
+    /// consumer was created
+    pub async fn follow_ritual_events(
+        &self,
+    ) -> Result<(
+        u64,
+        impl futures_util::Stream<Item = Result<RitualEventMessage>>,
+    )> {
+        let stream = self.ritual_stream().await?;
+        let consumer_config = jetstream::consumer::pull::Config {
+            filter_subject: "demon.ritual.v1.>".to_string(),
+            durable_name: None,
+            deliver_policy: DeliverPolicy::All,
+            ack_policy: async_nats::jetstream::consumer::AckPolicy::None,
+            inactive_threshold: std::time::Duration::from_secs(300), // 5 minute timeout
+            ..Default::default()
+        };
+        let mut consumer = stream.create_consumer(consumer_config).await?;
+        let backlog = consumer.info().await?.num_pending;
+        debug!(
+            "Created ephemeral consumer following all ritual events ({} pending)",
+            backlog
+        );
+
+        let events = async_stream::try_stream! {
+            loop {
+                let mut messages = consumer
+                    .batch()
+                    .max_messages(500)
+                    .expires(std::time::Duration::from_secs(30)) // Long poll for 30 seconds
+                    .messages()
+                    .await?;
+
+                while let Some(msg_result) = messages.next().await {
+                    match msg_result {
+                        Ok(msg) => {
+                            let (sequence, pending) = msg
+                                .info()
+                                .map(|info| (info.stream_sequence, info.pending))
+                                .unwrap_or_default();
+                            match serde_json::from_slice(&msg.message.payload) {
+                                Ok(payload) => yield RitualEventMessage {
+                                    subject: msg.subject.to_string(),
+                                    payload,
+                                    sequence,
+                                    pending,
+                                },
+                                Err(e) => debug!("Skipping non-JSON ritual event on {}: {}", msg.subject, e),
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Error receiving ritual event: {}", e);
+                        }
+                    }
+                }
+            }
+        };
+        Ok((backlog, events))
+    }
+
+    /// Tail new ritual events for a tenant as runs list updates, each with
+    /// the stream sequence of its event
+    pub async fn stream_run_updates_for_tenant(
+        &self,
+        tenant: &str,
+    ) -> Result<impl futures_util::Stream<Item = Result<(u64, RunUpdate)>>> {
+        debug!("Starting runs list update stream for tenant {}", tenant);
+
+        // The default tenant also owns runs published on legacy 6-part subjects
+        let subject_filter = if tenant == "default" {
+            "demon.ritual.v1.>".to_string()
+        } else {
+            format!("demon.ritual.v1.{}.*.*.events", tenant)
+        };
+
+        let js_client = self.clone();
+        let tenant_owned = tenant.to_string();
+
+        Ok(async_stream::try_stream! {
+            let stream = js_client.ritual_stream().await?;
+
+            // The page already rendered the current list; only tail what arrives after it
+            let consumer_config = jetstream::consumer::pull::Config {
+                filter_subject: subject_filter.clone(),
+                durable_name: None,
+                deliver_policy: DeliverPolicy::New,
+                ack_policy: async_nats::jetstream::consumer::AckPolicy::None,
+                inactive_threshold: std::time::Duration::from_secs(300), // 5 minute timeout
+                ..Default::default()
+            };
+
+            let consumer = stream.create_consumer(consumer_config).await?;
+            debug!("Created ephemeral consumer for runs list of tenant {}", tenant_owned);
+
+            loop {
+                let mut messages = consumer
+                    .batch()
+                    .max_messages(100)
+                    .expires(std::time::Duration::from_secs(30)) // Long poll for 30 seconds
+                    .messages()
+                    .await?;
+
+                while let Some(msg_result) = messages.next().await {
+                    match msg_result {
+                        Ok(msg) => {
+                            let payload: serde_json::Value = match serde_json::from_slice(&msg.message.payload) {
+                                Ok(v) => v,
+                                Err(e) => {
+                                    debug!("Skipping non-JSON ritual event on {}: {}", msg.subject, e);
+                                    continue;
+                                }
+                            };
+                            if let Some(update) = run_update_from_event(&tenant_owned, &msg.subject, &payload) {
+                                let sequence = msg.info().map(|info| info.stream_sequence).unwrap_or_default();
+                                yield (sequence, update);
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Error receiving runs list message: {}", e);
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// List wards policy decisions (`wards.decision:v1`) matching a query, newest first
+    pub async fn query_decisions(
+        &self,
+        query: &wards::audit::DecisionQuery,
+    ) -> Result<Vec<wards::audit::WardsDecision>> {
+        debug!("Querying policy decisions: {:?}", query);
+        let stream = wards::audit::decisions_stream();
+        wards::audit::query(&self.jetstream, &stream, query)
+            .await
+            .context("Failed to query policy decisions")
+    }
+
     /// Get the latest scale hint for a tenant
     pub async fn get_latest_scale_hint(&self, tenant: &str) -> Result<Option<ScaleHint>> {
         debug!("Getting latest scale hint for tenant: {}", tenant);
@@ -1046,6 +1449,94 @@ impl JetStreamClient {
         Ok(None)
     }
 
+    /// List scale decisions for a tenant, newest first
+    ///
+    /// `outcome` filters by outcome (e.g. `skipped`). A missing stream means
+    /// the scale hint handler has not recorded anything yet.
+    pub async fn query_scale_decisions(
+        &self,
+        tenant: &str,
+        outcome: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<ScaleDecision>> {
+        debug!(
+            "Querying scale decisions for tenant {} (outcome: {:?})",
+            tenant, outcome
+        );
+        let mut decisions = self
+            .fetch_scale_decisions(tenant, DeliverPolicy::All)
+            .await?;
+        decisions.retain(|d| outcome.is_none_or(|o| d.outcome == o));
+        decisions.sort_by_key(|d| std::cmp::Reverse(d.ts));
+        decisions.truncate(limit);
+        Ok(decisions)
+    }
+
+    /// Get the latest scale decision for a tenant
+    pub async fn get_latest_scale_decision(&self, tenant: &str) -> Result<Option<ScaleDecision>> {
+        Ok(self
+            .fetch_scale_decisions(tenant, DeliverPolicy::LastPerSubject)
+            .await?
+            .pop())
+    }
+
+    async fn fetch_scale_decisions(
+        &self,
+        tenant: &str,
+        deliver_policy: DeliverPolicy,
+    ) -> Result<Vec<ScaleDecision>> {
+        let stream = match self.jetstream.get_stream(scale_decisions_stream()).await {
+            Ok(s) => s,
+            Err(_) => {
+                debug!(
+                    "Scale decisions stream not found - scale hint handler may not be recording"
+                );
+                return Ok(Vec::new());
+            }
+        };
+
+        let consumer = stream
+            .create_consumer(jetstream::consumer::pull::Config {
+                filter_subject: scale_decision_subject(tenant),
+                durable_name: None,
+                deliver_policy,
+                ack_policy: async_nats::jetstream::consumer::AckPolicy::None,
+                inactive_threshold: std::time::Duration::from_secs(60),
+                ..Default::default()
+            })
+            .await
+            .context("Failed to create consumer for scale decisions")?;
+
+        let mut pending = consumer.cached_info().num_pending as usize;
+        let mut decisions = Vec::new();
+        while pending > 0 {
+            let mut messages = consumer
+                .batch()
+                .max_messages(pending.min(256))
+                .expires(std::time::Duration::from_secs(2))
+                .messages()
+                .await
+                .context("Failed to fetch scale decision messages")?;
+
+            let mut received = 0;
+            while let Some(msg_result) = messages.next().await {
+                received += 1;
+                match msg_result {
+                    Ok(msg) => match serde_json::from_slice::<ScaleDecision>(&msg.payload) {
+                        Ok(decision) => decisions.push(decision),
+                        Err(e) => debug!("Skipping malformed scale decision: {}", e),
+                    },
+                    Err(e) => warn!("Error receiving scale decision message: {}", e),
+                }
+            }
+            if received == 0 {
+                break;
+            }
+            pending = pending.saturating_sub(received);
+        }
+        Ok(decisions)
+    }
+
     /// Parse a scale hint message from JetStream
     fn parse_scale_hint_message(
         &self,
@@ -1143,6 +1634,80 @@ fn extract_ritual_id_from_subject(subject: &str) -> Option<String> {
     }
 }
 
+/// Redact the result envelopes an event carries: the step envelopes in
+/// `ritual.completed:v1` outputs and `envelope.fragment:v1` fragments.
+/// Capsule-specific rules are applied by the engine, which knows the capsule.
+pub fn redact_event_payload(redactor: &Redactor, payload: &mut serde_json::Value) {
+    if let Some(outputs) = payload.get_mut("outputs") {
+        if outputs
+            .get("steps")
+            .is_some_and(serde_json::Value::is_object)
+        {
+            if let Some(steps) = outputs.get_mut("steps").and_then(|s| s.as_object_mut()) {
+                for output in steps.values_mut() {
+                    redactor.redact_json(None, output);
+                }
+            }
+        } else if !outputs.is_null() {
+            redactor.redact_json(None, outputs);
+        }
+    }
+    if let Some(fragment) = payload.get_mut("fragment") {
+        redactor.redact_json(None, fragment);
+    }
+}
+
+/// `(tenant, ritualId, runId)` from a ritual event subject; legacy 6-part
+/// subjects belong to the default tenant
+pub fn parse_ritual_subject(subject: &str) -> Option<(&str, &str, &str)> {
+    let parts: Vec<&str> = subject.split('.').collect();
+    match parts.as_slice() {
+        ["demon", "ritual", "v1", tenant, ritual, run, "events"] => Some((tenant, ritual, run)),
+        ["demon", "ritual", "v1", ritual, run, "events"] => Some(("default", ritual, run)),
+        _ => None,
+    }
+}
+
+/// Turn a ritual event into a runs list update for `tenant`; `None` when the
+/// subject belongs to another tenant or is not a ritual event subject
+pub(crate) fn run_update_from_event(
+    tenant: &str,
+    subject: &str,
+    payload: &serde_json::Value,
+) -> Option<RunUpdate> {
+    let (subject_tenant, ritual_id, run_id) = parse_ritual_subject(subject)?;
+    if subject_tenant != tenant {
+        return None;
+    }
+
+    let event = payload
+        .get("event")
+        .and_then(|v| v.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let ts = payload
+        .get("ts")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<DateTime<Utc>>().ok())
+        .unwrap_or_else(Utc::now);
+    let status = match event.as_str() {
+        "ritual.completed:v1" => RunStatus::Completed,
+        "ritual.failed:v1" => RunStatus::Failed,
+        "run.canceled:v1" | "run.abandoned:v1" => RunStatus::Canceled,
+        _ => RunStatus::Running,
+    };
+    let start_ts = (event == "ritual.started:v1").then_some(ts);
+
+    Some(RunUpdate {
+        run_id: run_id.to_string(),
+        ritual_id: ritual_id.to_string(),
+        status,
+        event,
+        ts,
+        start_ts,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1189,4 +1754,85 @@ mod tests {
         // Both should extract the same ritual ID despite different subject formats
         assert_eq!(legacy_ritual_id, tenant_ritual_id);
     }
+
+    #[test]
+    fn run_update_from_event_maps_terminal_events_and_start_time() {
+        let started =
+            serde_json::json!({"event": "ritual.started:v1", "ts": "2025-01-01T00:00:00Z"});
+        let update = run_update_from_event(
+            "acme",
+            "demon.ritual.v1.acme.release.run-1.events",
+            &started,
+        )
+        .unwrap();
+        assert_eq!(update.run_id, "run-1");
+        assert_eq!(update.ritual_id, "release");
+        assert_eq!(update.status, RunStatus::Running);
+        assert_eq!(update.start_ts, Some(update.ts));
+
+        let failed = serde_json::json!({"event": "ritual.failed:v1", "ts": "2025-01-01T00:01:00Z"});
+        let update =
+            run_update_from_event("acme", "demon.ritual.v1.acme.release.run-1.events", &failed)
+                .unwrap();
+        assert_eq!(update.status, RunStatus::Failed);
+        assert_eq!(update.start_ts, None);
+    }
+
+    #[test]
+    fn run_update_from_event_scopes_subjects_to_the_tenant() {
+        let payload = serde_json::json!({"event": "ritual.completed:v1"});
+        assert!(run_update_from_event(
+            "acme",
+            "demon.ritual.v1.other.release.run-1.events",
+            &payload
+        )
+        .is_none());
+        // Legacy subjects only belong to the default tenant
+        assert!(
+            run_update_from_event("acme", "demon.ritual.v1.release.run-1.events", &payload)
+                .is_none()
+        );
+        let legacy =
+            run_update_from_event("default", "demon.ritual.v1.release.run-1.events", &payload)
+                .unwrap();
+        assert_eq!(legacy.status, RunStatus::Completed);
+        assert!(run_update_from_event(
+            "default",
+            "demon.ritual.v1.default.release.run-1.events",
+            &payload
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn completed_outputs_and_fragments_are_redacted() {
+        let redactor = Redactor::default();
+        let mut completed = serde_json::json!({
+            "event": "ritual.completed:v1",
+            "outputs": {"steps": {
+                "deploy": {"result": {"success": true, "data": {"token": "abc123"}}},
+                "approve": {"gateId": "prod", "granted": true}
+            }}
+        });
+        redact_event_payload(&redactor, &mut completed);
+        let deploy = &completed["outputs"]["steps"]["deploy"];
+        assert_eq!(deploy["result"]["data"]["token"], envelope::REDACTED);
+        assert_eq!(
+            deploy["diagnostics"][0]["source"],
+            envelope::REDACTION_SOURCE
+        );
+        assert_eq!(completed["outputs"]["steps"]["approve"]["granted"], true);
+
+        let mut fragment = serde_json::json!({
+            "event": "envelope.fragment:v1",
+            "fragment": {"sequence": 0, "diagnostics": [
+                {"level": "info", "message": "curl -H 'Authorization: Bearer abcdefgh12345'"}
+            ]}
+        });
+        redact_event_payload(&redactor, &mut fragment);
+        assert_eq!(
+            fragment["fragment"]["diagnostics"][0]["message"],
+            "curl -H 'Authorization: [REDACTED]'"
+        );
+    }
 }
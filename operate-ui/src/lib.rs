@@ -6,9 +6,22 @@ pub mod app_packs;
 pub mod auth;
 pub mod card_renderers;
 pub mod contracts;
+pub mod delegations;
 pub mod feature_flags;
+pub mod forms;
 pub mod jetstream;
+pub mod metrics;
+pub mod report;
 pub mod routes;
+pub mod run_admin;
+pub mod run_index;
+pub mod saved_views;
+pub mod system;
+pub mod telemetry;
+pub mod tenants;
+pub mod timeline;
+pub mod usage;
+pub mod workflow_overlay;
 
 use anyhow::Result;
 use axum::{
@@ -16,7 +29,7 @@ use axum::{
     http::StatusCode,
     middleware,
     response::{Html, IntoResponse, Response},
-    routing::{get, get_service, post},
+    routing::{delete, get, get_service, post},
     Router,
 };
 use tera::Tera;
@@ -30,10 +43,13 @@ use tracing::{error, info, warn};
 pub struct AppState {
     pub jetstream_client: Option<jetstream::JetStreamClient>,
     pub tera: Tera,
-    pub admin_token: Option<String>,
+    pub access_control: auth::AccessControl,
     pub bundle_loader: runtime::bundle::BundleLoader,
     pub app_pack_registry: Option<app_packs::AppPackRegistry>,
     pub feature_flags: std::collections::HashSet<String>,
+    pub run_index: run_index::RunIndex,
+    /// Limits and usage from `WARDS_TENANT_QUOTAS`, for the tenant admin page
+    pub tenant_quotas: Option<std::sync::Arc<wards::quota::TenantQuotas>>,
 }
 
 impl AppState {
@@ -112,7 +128,13 @@ impl AppState {
         };
         tera.register_filter("json_query", json_query);
 
-        let admin_token = std::env::var("ADMIN_TOKEN").ok();
+        let access_control = auth::AccessControl::from_env();
+
+        // Search index over runs, built from and kept current by the ritual events stream
+        let run_index = run_index::RunIndex::default();
+        if let Some(client) = &jetstream_client {
+            run_index.spawn_follower(client.clone());
+        }
 
         // Initialize bundle loader
         let bundle_loader = runtime::bundle::BundleLoader::new(None);
@@ -121,6 +143,12 @@ impl AppState {
         let app_pack_registry = match app_packs::AppPackRegistry::load() {
             Ok(registry) => {
                 info!("Successfully loaded App Pack registry");
+                let reload_secs = std::env::var("DEMON_APP_PACK_RELOAD_SECS")
+                    .ok()
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .unwrap_or(2)
+                    .max(1);
+                registry.spawn_watcher(std::time::Duration::from_secs(reload_secs));
                 Some(registry)
             }
             Err(e) => {
@@ -132,13 +160,24 @@ impl AppState {
         // Initialize feature flags from environment variables
         let feature_flags = feature_flags::init_feature_flags();
 
+        // Tenant quotas are read-only here; the engine and registry enforce them
+        let tenant_quotas = match wards::quota::TenantQuotas::from_env() {
+            Ok(quotas) => quotas.map(std::sync::Arc::new),
+            Err(e) => {
+                warn!("Failed to load tenant quotas: {}", e);
+                None
+            }
+        };
+
         Self {
             jetstream_client,
             tera,
-            admin_token,
+            access_control,
             bundle_loader,
             app_pack_registry,
             feature_flags,
+            run_index,
+            tenant_quotas,
         }
     }
 
@@ -337,6 +376,7 @@ async fn handle_static_file_error() -> impl IntoResponse {
 pub fn create_app(state: AppState) -> Router {
     let mut app = Router::new()
         .route("/health", get(health))
+        .route("/metrics", get(metrics::metrics_handler))
         // Contract validation endpoints
         .route(
             "/api/contracts/validate/envelope",
@@ -350,7 +390,16 @@ pub fn create_app(state: AppState) -> Router {
         .route(
             "/api/contracts/status",
             get(contracts::bundle_status_endpoint),
-        );
+        )
+        // Filters by the caller's token itself; see tenants::list_tenants_api
+        .route("/api/tenants", get(tenants::list_tenants_api))
+        // Saved views check ownership and tenant access in the handlers
+        .route(
+            "/api/views",
+            get(saved_views::list_views_api).post(saved_views::create_view_api),
+        )
+        .route("/api/views/:id", delete(saved_views::delete_view_api))
+        .route("/views/:id", get(saved_views::open_view));
 
     // Agent Flow API (feature-flagged, JWT-protected)
     // Routes only registered when agent-flows feature flag is enabled
@@ -373,33 +422,9 @@ pub fn create_app(state: AppState) -> Router {
             );
     }
 
-    app
-        // Contracts Browser (feature-flagged)
-        .route("/ui/contracts", get(contracts::contracts_browser_html))
-        .route(
-            "/api/contracts/registry/list",
-            get(contracts::list_contracts_api),
-        )
-        .route(
-            "/api/contracts/registry/:name/:version",
-            get(contracts::get_contract_detail_api),
-        )
-        // Graph viewer
-        .route("/graph", get(routes::graph_viewer_html))
-        // Canvas DAG viewer (feature-flagged)
-        .route("/canvas", get(routes::canvas_viewer_html))
-        // App Pack cards viewer
-        .route("/app-pack-cards", get(routes::app_pack_cards_html))
-        .route("/api/app-pack-cards", get(routes::app_pack_cards_api))
-        // Schema form renderer
-        .route("/ui/form", get(routes::schema_form_html))
-        .route("/api/schema/metadata", get(routes::schema_metadata_api))
-        .route("/api/form/submit", post(routes::submit_form_api))
-        // Workflow viewer
-        .route("/ui/workflow", get(routes::workflow_viewer_html))
-        .route("/api/workflows", get(routes::list_workflows_api))
-        .route("/api/workflow/metadata", get(routes::workflow_metadata_api))
-        .route("/api/workflow/state", get(routes::workflow_state_api))
+    // Runs, approvals and admin endpoints require a role for the route's tenant
+    // when OPERATE_UI_AUTH=jwt; see auth::AccessControl
+    let viewer_routes = Router::new()
         // Legacy routes (redirect to default tenant)
         .route("/runs", get(routes::list_runs_html))
         .route("/runs/:run_id", get(routes::get_run_html))
@@ -410,16 +435,35 @@ pub fn create_app(state: AppState) -> Router {
             get(routes::get_run_html_tenant),
         )
         .route("/api/runs", get(routes::list_runs_api))
+        .route("/api/runs/stream", get(routes::stream_runs_sse))
         .route("/api/runs/:run_id", get(routes::get_run_api))
         .route(
             "/api/runs/:run_id/events/stream",
             get(routes::stream_run_events_sse),
         )
+        .route("/api/runs/:run_id/report", get(report::get_run_report_api))
+        .route(
+            "/api/runs/:run_id/overlay",
+            get(workflow_overlay::get_run_overlay_api),
+        )
+        .route(
+            "/api/runs/:run_id/overlay/stream",
+            get(workflow_overlay::stream_run_overlay_sse),
+        )
+        .route(
+            "/api/runs/:run_id/steps/:step_id/envelope",
+            get(workflow_overlay::get_step_envelope_api),
+        )
         // Tenant-aware routes
+        .route("/api/tenants/:tenant", get(tenants::get_tenant_api))
         .route(
             "/api/tenants/:tenant/runs",
             get(routes::list_runs_api_tenant),
         )
+        .route(
+            "/api/tenants/:tenant/runs/stream",
+            get(routes::stream_runs_sse_tenant),
+        )
         .route(
             "/api/tenants/:tenant/runs/:run_id",
             get(routes::get_run_api_tenant),
@@ -429,9 +473,47 @@ pub fn create_app(state: AppState) -> Router {
             get(routes::stream_run_events_sse_tenant),
         )
         .route(
-            "/admin/templates/report",
-            get(routes::admin_templates_report),
+            "/api/tenants/:tenant/runs/:run_id/report",
+            get(report::get_run_report_api_tenant),
+        )
+        .route(
+            "/api/tenants/:tenant/runs/:run_id/overlay",
+            get(workflow_overlay::get_run_overlay_api_tenant),
+        )
+        .route(
+            "/api/tenants/:tenant/runs/:run_id/overlay/stream",
+            get(workflow_overlay::stream_run_overlay_sse_tenant),
+        )
+        .route(
+            "/api/tenants/:tenant/runs/:run_id/steps/:step_id/envelope",
+            get(workflow_overlay::get_step_envelope_api_tenant),
+        )
+        // Resource usage rollup for chargeback exports
+        .route(
+            "/api/tenants/:tenant/usage",
+            get(usage::get_tenant_usage_api),
         )
+        // Scale hint handler decisions (agent.scale.decision:v1)
+        .route(
+            "/api/tenants/:tenant/scale/decisions",
+            get(routes::list_scale_decisions_api_tenant),
+        )
+        // Policy audit trail (wards.decision:v1)
+        .route(
+            "/api/tenants/:tenant/decisions",
+            get(routes::list_decisions_api_tenant),
+        )
+        .route(
+            "/api/runs/:run_id/decisions",
+            get(routes::list_run_decisions_api),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_viewer,
+        ));
+
+    let operator_routes = Router::new()
+        .route("/api/runs/:run_id/cancel", post(routes::cancel_run_api))
         // Approvals endpoints (publish Granted/Denied)
         .route(
             "/api/approvals/:run_id/:gate_id/grant",
@@ -449,10 +531,92 @@ pub fn create_app(state: AppState) -> Router {
             "/api/tenants/:tenant/approvals/:run_id/:gate_id/deny",
             post(routes::deny_approval_api_tenant),
         )
+        // Approval delegations (vacation rules)
+        .route(
+            "/api/delegations",
+            get(delegations::list_delegations_api).post(delegations::create_delegation_api),
+        )
+        .route(
+            "/api/delegations/:delegator/:delegate",
+            delete(delegations::delete_delegation_api),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_operator,
+        ));
+
+    let admin_routes = Router::new()
+        .route(
+            "/admin/templates/report",
+            get(routes::admin_templates_report),
+        )
+        .route("/admin/tenants", get(tenants::tenants_admin_html))
+        // JetStream stream and consumer health
+        .route("/system", get(system::system_html))
+        .route("/api/system/streams", get(system::system_streams_api))
         .route(
             "/api/tenants/:tenant/approvals/:run_id/:gate_id/override",
             post(routes::override_approval_api_tenant),
         )
+        // Stuck-run actions (abandon, re-emit gate decision, purge previews)
+        .route(
+            "/api/tenants/:tenant/runs/:run_id/abandon",
+            post(run_admin::abandon_run_api),
+        )
+        .route(
+            "/api/tenants/:tenant/runs/:run_id/gates/:gate_id/reemit",
+            post(run_admin::reemit_gate_api),
+        )
+        .route(
+            "/api/tenants/:tenant/preview-runs/purge",
+            post(run_admin::purge_preview_runs_api),
+        )
+        .route(
+            "/api/tenants/:tenant/admin/actions",
+            get(run_admin::list_admin_actions_api),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_admin,
+        ));
+
+    app
+        // Contracts Browser (feature-flagged)
+        .route("/ui/contracts", get(contracts::contracts_browser_html))
+        .route(
+            "/api/contracts/registry/list",
+            get(contracts::list_contracts_api),
+        )
+        .route(
+            "/api/contracts/registry/:name/:version",
+            get(contracts::get_contract_detail_api),
+        )
+        // Graph viewer
+        .route("/graph", get(routes::graph_viewer_html))
+        .route("/api/graph/tags", get(routes::graph_api_proxy))
+        .route("/api/graph/commits", get(routes::graph_api_proxy))
+        .route(
+            "/api/graph/commits/:commit_id",
+            get(routes::graph_api_proxy),
+        )
+        .route("/api/graph/snapshot", get(routes::graph_api_proxy))
+        // Canvas DAG viewer (feature-flagged)
+        .route("/canvas", get(routes::canvas_viewer_html))
+        // App Pack cards viewer
+        .route("/app-pack-cards", get(routes::app_pack_cards_html))
+        .route("/api/app-pack-cards", get(routes::app_pack_cards_api))
+        // Schema form renderer
+        .route("/ui/form", get(routes::schema_form_html))
+        .route("/api/schema/metadata", get(routes::schema_metadata_api))
+        .route("/api/form/submit", post(routes::submit_form_api))
+        // Workflow viewer
+        .route("/ui/workflow", get(routes::workflow_viewer_html))
+        .route("/api/workflows", get(routes::list_workflows_api))
+        .route("/api/workflow/metadata", get(routes::workflow_metadata_api))
+        .route("/api/workflow/state", get(routes::workflow_state_api))
+        .merge(viewer_routes)
+        .merge(operator_routes)
+        .merge(admin_routes)
         .route(
             "/static/*path",
             get_service(
@@ -460,6 +624,7 @@ pub fn create_app(state: AppState) -> Router {
             ),
         )
         .fallback(not_found)
+        .layer(middleware::from_fn(metrics::track_http))
         .layer(middleware::from_fn(
             api_version::version_negotiation_middleware,
         ))
@@ -0,0 +1,203 @@
+//! Prometheus metrics for Operate UI
+//!
+//! Everything is registered in one process-wide registry and served in the
+//! text exposition format from `GET /metrics`:
+//!
+//! - `operate_ui_http_request_duration_seconds{method,route,status}`
+//! - `operate_ui_sse_connections{stream}` (`run_events` or `runs`)
+//! - `operate_ui_template_render_errors_total{template}`
+//! - `operate_ui_jetstream_consumer_pending{consumer}`
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tracing::error;
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+pub struct Metrics {
+    registry: Registry,
+    pub http_request_duration: HistogramVec,
+    pub sse_connections: IntGaugeVec,
+    pub template_render_errors: IntCounterVec,
+    pub jetstream_consumer_pending: IntGaugeVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("operate_ui".to_string()), None)
+            .expect("valid metrics prefix");
+
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by method, matched route and status",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("valid histogram");
+        let sse_connections = IntGaugeVec::new(
+            Opts::new("sse_connections", "Open server-sent event connections"),
+            &["stream"],
+        )
+        .expect("valid gauge");
+        let template_render_errors = IntCounterVec::new(
+            Opts::new(
+                "template_render_errors_total",
+                "Tera template renders that failed",
+            ),
+            &["template"],
+        )
+        .expect("valid counter");
+        let jetstream_consumer_pending = IntGaugeVec::new(
+            Opts::new(
+                "jetstream_consumer_pending",
+                "Messages a JetStream consumer has not yet delivered",
+            ),
+            &["consumer"],
+        )
+        .expect("valid gauge");
+
+        for collector in [
+            Box::new(http_request_duration.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(sse_connections.clone()),
+            Box::new(template_render_errors.clone()),
+            Box::new(jetstream_consumer_pending.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            http_request_duration,
+            sse_connections,
+            template_render_errors,
+            jetstream_consumer_pending,
+        }
+    }
+
+    /// Current values in the Prometheus text format
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            error!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// The process-wide metrics, registered on first use
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Middleware recording request latency; the route label is the matched path
+/// pattern (e.g. `/api/runs/:run_id`) so run IDs do not create new series
+pub async fn track_http(req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    metrics()
+        .http_request_duration
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .observe(start.elapsed().as_secs_f64());
+    response
+}
+
+/// `GET /metrics`
+pub async fn metrics_handler() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics().encode(),
+    )
+}
+
+/// Render `template`, counting failures
+pub fn render_template(
+    tera: &tera::Tera,
+    template: &str,
+    context: &tera::Context,
+) -> tera::Result<String> {
+    tera.render(template, context).inspect_err(|_| {
+        metrics()
+            .template_render_errors
+            .with_label_values(&[template])
+            .inc();
+    })
+}
+
+/// Record how far a JetStream consumer is behind its stream
+pub fn record_consumer_pending(consumer: &str, pending: u64) {
+    metrics()
+        .jetstream_consumer_pending
+        .with_label_values(&[consumer])
+        .set(pending as i64);
+}
+
+/// Counts an open SSE connection until dropped
+pub struct SseConnection {
+    stream: &'static str,
+}
+
+impl SseConnection {
+    pub fn open(stream: &'static str) -> Self {
+        metrics().sse_connections.with_label_values(&[stream]).inc();
+        Self { stream }
+    }
+}
+
+impl Drop for SseConnection {
+    fn drop(&mut self) {
+        metrics()
+            .sse_connections
+            .with_label_values(&[self.stream])
+            .dec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sse_connection_gauge_follows_guard_lifetime() {
+        let gauge = metrics().sse_connections.with_label_values(&["unit_test"]);
+        let guard = SseConnection::open("unit_test");
+        assert_eq!(gauge.get(), 1);
+        drop(guard);
+        assert_eq!(gauge.get(), 0);
+    }
+
+    #[test]
+    fn failed_render_is_counted_by_template() {
+        let tera = tera::Tera::default();
+        let counter = metrics()
+            .template_render_errors
+            .with_label_values(&["missing.html"]);
+        let before = counter.get();
+
+        assert!(render_template(&tera, "missing.html", &tera::Context::new()).is_err());
+        assert_eq!(counter.get(), before + 1);
+        assert!(metrics()
+            .encode()
+            .contains("operate_ui_template_render_errors_total{template=\"missing.html\"}"));
+    }
+}
@@ -0,0 +1,526 @@
+//! Downloadable run reports for audits and postmortems
+//!
+//! `GET /api/runs/:run_id/report?format=json|html` assembles everything the
+//! event store knows about a run into one self-contained file: the raw
+//! events, each step's result envelope, per-gate approval records,
+//! diagnostics (envelope diagnostics plus retries, timeouts and
+//! compensations), an index of artifacts the steps reported, and the step
+//! timeline. The HTML variant inlines its styles so it renders offline.
+
+use crate::jetstream::{RitualEvent, RunDetail};
+use crate::timeline::RunTimeline;
+use crate::workflow_overlay::step_envelopes;
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use tracing::{error, info};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportQuery {
+    /// `json` (default) or `html`
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn parse(format: Option<&str>) -> Option<Self> {
+        match format.map(str::trim).filter(|f| !f.is_empty()) {
+            None => Some(Self::Json),
+            Some(f) if f.eq_ignore_ascii_case("json") => Some(Self::Json),
+            Some(f) if f.eq_ignore_ascii_case("html") => Some(Self::Html),
+            Some(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunReport {
+    pub generated_at: DateTime<Utc>,
+    pub tenant: String,
+    pub run_id: String,
+    pub ritual_id: String,
+    pub status: String,
+    pub started_at: Option<DateTime<Utc>>,
+    /// Time of the terminal event; absent while the run is in flight
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Completion reason, e.g. `approval_denied` or `canceled`
+    pub reason: Option<String>,
+    /// Result envelope per step id, from `ritual.completed:v1`
+    pub envelopes: BTreeMap<String, Value>,
+    pub approvals: Vec<ApprovalRecord>,
+    pub diagnostics: Vec<DiagnosticEntry>,
+    pub artifacts: Vec<ArtifactEntry>,
+    pub timeline: Option<RunTimeline>,
+    pub events: Vec<RitualEvent>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRecord {
+    pub gate_id: String,
+    /// `pending`, `granted` or `denied`
+    pub status: String,
+    pub requester: Option<String>,
+    pub requested_at: Option<DateTime<Utc>>,
+    pub approver: Option<String>,
+    /// Delegator when the approver decided as their delegate
+    pub on_behalf_of: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticEntry {
+    pub ts: Option<DateTime<Utc>>,
+    pub step_id: Option<String>,
+    /// Envelope level (`debug` … `fatal`), or `warning`/`error` for engine events
+    pub level: String,
+    pub message: String,
+    /// `envelope` or the engine event name, e.g. `step.retried:v1`
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactEntry {
+    pub step_id: String,
+    pub path: String,
+    /// The step's own description of the artifact, when it gave more than a path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl RunReport {
+    pub fn from_run(tenant: &str, run: RunDetail) -> Self {
+        let status = run.status();
+        let envelopes = step_envelopes(&run.events);
+        let ended_at = match status {
+            crate::jetstream::RunStatus::Running => None,
+            _ => run.events.last().map(|e| e.ts),
+        };
+
+        Self {
+            generated_at: Utc::now(),
+            tenant: tenant.to_string(),
+            run_id: run.run_id.clone(),
+            ritual_id: run.ritual_id.clone(),
+            status: status.to_string(),
+            started_at: run.events.first().map(|e| e.ts),
+            ended_at,
+            reason: run
+                .events
+                .last()
+                .and_then(|e| str_field(e, "reason"))
+                .map(str::to_string),
+            approvals: approvals(&run.events),
+            diagnostics: diagnostics(&run.events, &envelopes),
+            artifacts: artifacts(&envelopes),
+            timeline: RunTimeline::from_events(&run.events),
+            envelopes,
+            events: run.events,
+        }
+    }
+
+    fn filename(&self, format: ReportFormat) -> String {
+        let ext = match format {
+            ReportFormat::Json => "json",
+            ReportFormat::Html => "html",
+        };
+        let safe: String = self
+            .run_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("run-{}-report.{}", safe, ext)
+    }
+}
+
+/// One record per gate, in the order gates were first requested
+fn approvals(events: &[RitualEvent]) -> Vec<ApprovalRecord> {
+    let mut records: Vec<ApprovalRecord> = Vec::new();
+    for event in events.iter().filter(|e| e.event.starts_with("approval.")) {
+        let Some(gate_id) = str_field(event, "gateId") else {
+            continue;
+        };
+        let index = match records.iter().position(|r| r.gate_id == gate_id) {
+            Some(i) => i,
+            None => {
+                records.push(ApprovalRecord {
+                    gate_id: gate_id.to_string(),
+                    status: "pending".to_string(),
+                    requester: None,
+                    requested_at: None,
+                    approver: None,
+                    on_behalf_of: None,
+                    decided_at: None,
+                    reason: None,
+                    note: None,
+                });
+                records.len() - 1
+            }
+        };
+        let record = &mut records[index];
+        let field = |key: &str| str_field(event, key).map(str::to_string);
+        match event.event.as_str() {
+            "approval.requested:v1" => {
+                record.requester = field("requester");
+                record.requested_at = Some(event.ts);
+                record.reason = field("reason");
+            }
+            "approval.granted:v1" | "approval.denied:v1" if record.decided_at.is_none() => {
+                record.status = if event.event == "approval.granted:v1" {
+                    "granted"
+                } else {
+                    "denied"
+                }
+                .to_string();
+                record.approver = field("approver");
+                record.on_behalf_of = field("onBehalfOf");
+                record.decided_at = Some(event.ts);
+                record.note = field("note");
+                if let Some(reason) = field("reason") {
+                    record.reason = Some(reason);
+                }
+            }
+            _ => {}
+        }
+    }
+    records
+}
+
+fn diagnostics(
+    events: &[RitualEvent],
+    envelopes: &BTreeMap<String, Value>,
+) -> Vec<DiagnosticEntry> {
+    let mut entries: Vec<DiagnosticEntry> = events
+        .iter()
+        .filter_map(|e| {
+            let (level, message) = match e.event.as_str() {
+                "step.retried:v1" => (
+                    "warning",
+                    format!(
+                        "attempt {} of {} failed: {}",
+                        e.extra.get("attempt").unwrap_or(&Value::Null),
+                        e.extra.get("maxAttempts").unwrap_or(&Value::Null),
+                        str_field(e, "error").unwrap_or_default()
+                    ),
+                ),
+                "step.timeout:v1" => (
+                    "warning",
+                    format!(
+                        "attempt {} exceeded its {} time budget",
+                        e.extra.get("attempt").unwrap_or(&Value::Null),
+                        str_field(e, "scope").unwrap_or("step")
+                    ),
+                ),
+                "step.compensated:v1" => (
+                    "error",
+                    format!(
+                        "compensated by '{}': {}",
+                        str_field(e, "compensationStepId").unwrap_or_default(),
+                        str_field(e, "error").unwrap_or_default()
+                    ),
+                ),
+                "step.completed:v1" if str_field(e, "outcome") == Some("failed") => (
+                    "error",
+                    str_field(e, "error").unwrap_or("step failed").to_string(),
+                ),
+                _ => return None,
+            };
+            Some(DiagnosticEntry {
+                ts: Some(e.ts),
+                step_id: str_field(e, "stepId").map(str::to_string),
+                level: level.to_string(),
+                message,
+                source: e.event.clone(),
+            })
+        })
+        .collect();
+
+    for (step_id, envelope) in envelopes {
+        let Some(list) = envelope.get("diagnostics").and_then(|d| d.as_array()) else {
+            continue;
+        };
+        entries.extend(list.iter().map(|d| {
+            DiagnosticEntry {
+                ts: d
+                    .get("timestamp")
+                    .and_then(|t| serde_json::from_value(t.clone()).ok()),
+                step_id: Some(step_id.clone()),
+                level: d
+                    .get("level")
+                    .and_then(|l| l.as_str())
+                    .unwrap_or("info")
+                    .to_string(),
+                message: d
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                source: "envelope".to_string(),
+            }
+        }));
+    }
+    entries
+}
+
+/// Artifacts a step listed under `result.data.artifacts`, either as paths or
+/// as objects with a `path` (or `uri`)
+fn artifacts(envelopes: &BTreeMap<String, Value>) -> Vec<ArtifactEntry> {
+    let mut entries = Vec::new();
+    for (step_id, envelope) in envelopes {
+        let Some(list) = envelope
+            .pointer("/result/data/artifacts")
+            .and_then(|a| a.as_array())
+        else {
+            continue;
+        };
+        for artifact in list {
+            let (path, details) = match artifact {
+                Value::String(path) => (path.clone(), None),
+                Value::Object(obj) => match obj.get("path").or_else(|| obj.get("uri")) {
+                    Some(Value::String(path)) => (path.clone(), Some(artifact.clone())),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            entries.push(ArtifactEntry {
+                step_id: step_id.clone(),
+                path,
+                details,
+            });
+        }
+    }
+    entries
+}
+
+fn str_field<'a>(event: &'a RitualEvent, key: &str) -> Option<&'a str> {
+    event.extra.get(key).and_then(|v| v.as_str())
+}
+
+/// Render the standalone HTML report
+pub fn render_html(tera: &tera::Tera, report: &RunReport) -> tera::Result<String> {
+    let mut context = tera::Context::new();
+    context.insert("report", report);
+    crate::metrics::render_template(tera, "run_report.html", &context)
+}
+
+/// GET /api/runs/:run_id/report
+pub async fn get_run_report_api(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+    Query(query): Query<ReportQuery>,
+) -> Response {
+    run_report(state, "default".to_string(), run_id, query).await
+}
+
+/// GET /api/tenants/:tenant/runs/:run_id/report
+pub async fn get_run_report_api_tenant(
+    State(state): State<AppState>,
+    Path((tenant, run_id)): Path<(String, String)>,
+    Query(query): Query<ReportQuery>,
+) -> Response {
+    run_report(state, tenant, run_id, query).await
+}
+
+async fn run_report(
+    state: AppState,
+    tenant: String,
+    run_id: String,
+    query: ReportQuery,
+) -> Response {
+    let Some(format) = ReportFormat::parse(query.format.as_deref()) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "format must be 'json' or 'html'",
+                "format": query.format,
+            })),
+        )
+            .into_response();
+    };
+
+    let Some(client) = &state.jetstream_client else {
+        return (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({ "error": "JetStream is not available" })),
+        )
+            .into_response();
+    };
+    let run = match client.get_run_detail_for_tenant(&tenant, &run_id).await {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "Run not found", "runId": run_id })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("Failed to retrieve run {} for report: {}", run_id, e);
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({ "error": format!("Failed to retrieve run detail: {}", e) })),
+            )
+                .into_response();
+        }
+    };
+
+    let report = RunReport::from_run(&tenant, run);
+    info!(
+        "Generated {:?} report for run {} ({} events)",
+        format,
+        run_id,
+        report.events.len()
+    );
+    let disposition = format!("attachment; filename=\"{}\"", report.filename(format));
+    match format {
+        ReportFormat::Json => {
+            ([(header::CONTENT_DISPOSITION, disposition)], Json(report)).into_response()
+        }
+        ReportFormat::Html => match render_html(&state.tera, &report) {
+            Ok(html) => (
+                [
+                    (header::CONTENT_TYPE, "text/html; charset=utf-8".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                html,
+            )
+                .into_response(),
+            Err(e) => {
+                error!("Failed to render run report: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "error": format!("Failed to render report: {}", e) })),
+                )
+                    .into_response()
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(events: Value) -> RunDetail {
+        RunDetail {
+            run_id: "run-1".to_string(),
+            ritual_id: "release".to_string(),
+            events: serde_json::from_value(events).unwrap(),
+        }
+    }
+
+    #[test]
+    fn report_collects_envelopes_approvals_diagnostics_and_artifacts() {
+        let report = RunReport::from_run(
+            "acme",
+            run(json!([
+                { "ts": "2025-01-01T00:00:00Z", "event": "ritual.started:v1" },
+                { "ts": "2025-01-01T00:00:01Z", "event": "step.retried:v1", "stepId": "build", "attempt": 1, "maxAttempts": 2, "error": "exit 1" },
+                { "ts": "2025-01-01T00:00:02Z", "event": "approval.requested:v1", "gateId": "prod", "requester": "dev@example.com", "reason": "ship it" },
+                { "ts": "2025-01-01T00:00:05Z", "event": "approval.granted:v1", "gateId": "prod", "approver": "ops@example.com", "note": "ok" },
+                { "ts": "2025-01-01T00:00:09Z", "event": "ritual.completed:v1", "outputs": { "steps": {
+                    "build": {
+                        "result": { "success": true, "data": { "artifacts": [
+                            "/workspace/.artifacts/app.tar",
+                            { "path": "/workspace/.artifacts/sbom.json", "kind": "sbom" },
+                            42
+                        ] } },
+                        "diagnostics": [{ "level": "warning", "message": "cache miss" }]
+                    }
+                } } }
+            ])),
+        );
+
+        assert_eq!(report.tenant, "acme");
+        assert_eq!(report.status, "Completed");
+        assert_eq!(
+            report.ended_at.unwrap().to_rfc3339(),
+            "2025-01-01T00:00:09+00:00"
+        );
+        assert!(report.envelopes.contains_key("build"));
+
+        let gate = &report.approvals[0];
+        assert_eq!(
+            (gate.gate_id.as_str(), gate.status.as_str()),
+            ("prod", "granted")
+        );
+        assert_eq!(gate.approver.as_deref(), Some("ops@example.com"));
+        assert_eq!(gate.reason.as_deref(), Some("ship it"));
+
+        let sources: Vec<&str> = report
+            .diagnostics
+            .iter()
+            .map(|d| d.source.as_str())
+            .collect();
+        assert_eq!(sources, vec!["step.retried:v1", "envelope"]);
+        assert_eq!(report.diagnostics[1].message, "cache miss");
+
+        let paths: Vec<&str> = report.artifacts.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/workspace/.artifacts/app.tar",
+                "/workspace/.artifacts/sbom.json"
+            ]
+        );
+        assert_eq!(
+            report.artifacts[1].details.as_ref().unwrap()["kind"],
+            "sbom"
+        );
+    }
+
+    #[test]
+    fn running_runs_have_no_end_and_pending_gates_stay_pending() {
+        let report = RunReport::from_run(
+            "default",
+            run(json!([
+                { "ts": "2025-01-01T00:00:00Z", "event": "ritual.started:v1" },
+                { "ts": "2025-01-01T00:00:02Z", "event": "approval.requested:v1", "gateId": "prod" }
+            ])),
+        );
+        assert_eq!(report.status, "Running");
+        assert!(report.ended_at.is_none());
+        assert_eq!(report.approvals[0].status, "pending");
+        assert!(report.envelopes.is_empty());
+    }
+
+    #[test]
+    fn format_defaults_to_json_and_rejects_unknown_values() {
+        assert_eq!(ReportFormat::parse(None), Some(ReportFormat::Json));
+        assert_eq!(ReportFormat::parse(Some("HTML")), Some(ReportFormat::Html));
+        assert_eq!(ReportFormat::parse(Some("pdf")), None);
+    }
+
+    #[test]
+    fn filename_is_safe_for_content_disposition() {
+        let mut report = RunReport::from_run("default", run(json!([])));
+        report.run_id = "run\"/../x".to_string();
+        assert_eq!(
+            report.filename(ReportFormat::Html),
+            "run-run_____x-report.html"
+        );
+    }
+}
@@ -1,18 +1,19 @@
 use crate::jetstream::{RunDetail, RunSummary};
-use crate::{AppError, AppState};
+use crate::{delegations, AppError, AppState};
 
 use axum::http::HeaderMap;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
     response::{Html, IntoResponse, Response},
-    Json,
+    Extension, Json,
 };
 use futures_util::StreamExt as _;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio_stream::wrappers::IntervalStream;
 use tracing::{debug, error, info, warn};
+use wards::delegation::same_principal;
 
 // Graph viewer types
 #[derive(Deserialize, Debug, Clone)]
@@ -59,7 +60,18 @@ pub struct ListRunsQuery {
     pub ritual_filter: Option<String>,
     #[serde(rename = "runId")]
     pub run_id_filter: Option<String>,
-    pub status: Option<String>, // Running | Completed | Failed
+    pub status: Option<String>, // Running | Completed | Failed | Canceled
+    pub since: Option<String>,  // RFC 3339, YYYY-MM-DDTHH:MM or YYYY-MM-DD (UTC)
+    pub until: Option<String>,
+    pub gate: Option<String>, // pending | granted | denied | expired | overridden
+    #[serde(rename = "q")]
+    pub text: Option<String>,
+    /// `nextCursor` of the previous page
+    pub cursor: Option<String>,
+    /// Only runs changed after this stream sequence; the `Last-Event-ID`
+    /// header takes precedence
+    #[serde(rename = "lastEventId")]
+    pub last_event_id: Option<String>,
 }
 
 fn parse_status_filter(s: &str) -> Option<crate::jetstream::RunStatus> {
@@ -67,10 +79,248 @@ fn parse_status_filter(s: &str) -> Option<crate::jetstream::RunStatus> {
         "running" => Some(crate::jetstream::RunStatus::Running),
         "completed" => Some(crate::jetstream::RunStatus::Completed),
         "failed" => Some(crate::jetstream::RunStatus::Failed),
+        "canceled" => Some(crate::jetstream::RunStatus::Canceled),
         _ => None,
     }
 }
 
+/// Validate list runs query parameters into a search for `tenant`
+fn run_search_from_query(
+    tenant: &str,
+    query: &ListRunsQuery,
+) -> Result<crate::run_index::RunSearch, String> {
+    fn non_empty(value: &Option<String>) -> Option<&str> {
+        value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+    }
+    fn time_bound(
+        name: &str,
+        value: &Option<String>,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, String> {
+        non_empty(value)
+            .map(|s| {
+                crate::run_index::parse_time_bound(s).ok_or_else(|| {
+                    format!(
+                        "invalid '{}': expected an RFC 3339 time, YYYY-MM-DDTHH:MM or YYYY-MM-DD",
+                        name
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    let limit = query.limit.unwrap_or(50);
+    if limit == 0 || limit > 1000 {
+        return Err("invalid 'limit': must be 1..=1000".to_string());
+    }
+    let status = non_empty(&query.status)
+        .map(|s| {
+            parse_status_filter(s).ok_or_else(|| {
+                "invalid 'status': expected one of Running, Completed, Failed, Canceled".to_string()
+            })
+        })
+        .transpose()?;
+    let gate = non_empty(&query.gate)
+        .map(str::parse::<crate::run_index::GateState>)
+        .transpose()?;
+    let after = non_empty(&query.cursor)
+        .map(str::parse::<crate::run_index::RunCursor>)
+        .transpose()?;
+    let updated_after = non_empty(&query.last_event_id)
+        .map(|s| {
+            s.parse::<u64>().map_err(|_| {
+                "invalid 'lastEventId': expected the id of a runs stream event".to_string()
+            })
+        })
+        .transpose()?;
+
+    Ok(crate::run_index::RunSearch {
+        tenant: tenant.to_string(),
+        ritual: non_empty(&query.ritual_filter).map(str::to_string),
+        run_id: non_empty(&query.run_id_filter).map(str::to_string),
+        status,
+        since: time_bound("since", &query.since)?,
+        until: time_bound("until", &query.until)?,
+        gate,
+        text: non_empty(&query.text).map(str::to_string),
+        after,
+        updated_after,
+        limit,
+    })
+}
+
+/// Answer a run search from the run index, or by scanning JetStream while the
+/// index is still being built
+async fn search_runs(
+    state: &AppState,
+    search: &crate::run_index::RunSearch,
+) -> Result<Vec<RunSummary>, (StatusCode, String)> {
+    search_run_page(state, search)
+        .await
+        .map(|(page, _)| page.runs)
+}
+
+/// Like [`search_runs`]; the flag is false when the page came from a scan and
+/// carries no cursor or stream sequence
+async fn search_run_page(
+    state: &AppState,
+    search: &crate::run_index::RunSearch,
+) -> Result<(crate::run_index::RunPage, bool), (StatusCode, String)> {
+    if state.run_index.is_ready() {
+        return Ok((state.run_index.search_page(search), true));
+    }
+    let Some(client) = &state.jetstream_client else {
+        return Err((
+            StatusCode::BAD_GATEWAY,
+            "JetStream is not available".to_string(),
+        ));
+    };
+    if search.needs_index() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Run index is still loading; 'gate', 'q', 'cursor' and 'lastEventId' are not available yet"
+                .to_string(),
+        ));
+    }
+    let mut runs = client
+        .list_runs_for_tenant(&search.tenant, Some(search.limit))
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("Failed to retrieve runs: {}", e),
+            )
+        })?;
+    runs.retain(|run| search.matches_summary(run));
+    Ok((
+        crate::run_index::RunPage {
+            runs,
+            ..Default::default()
+        },
+        false,
+    ))
+}
+
+/// JSON list runs response: one page of runs with paging and caching headers
+///
+/// Pages served from the run index get a weak `ETag` derived from the latest
+/// stream sequence, so a poll with a matching `If-None-Match` is answered with
+/// `304 Not Modified` and no body. `X-Next-Cursor` is the `cursor` of the next
+/// page and `X-Last-Event-ID` the sequence to pass as `Last-Event-ID` to fetch
+/// only the runs that change from now on. The tenant endpoint also returns
+/// both in the body; `/api/runs` keeps its bare array.
+async fn list_runs_json(
+    state: &AppState,
+    tenant: &str,
+    mut query: ListRunsQuery,
+    headers: &HeaderMap,
+    bare_array: bool,
+) -> Response {
+    use axum::http::{header, HeaderValue};
+
+    if let Some(id) = headers.get("last-event-id").and_then(|v| v.to_str().ok()) {
+        query.last_event_id = Some(id.to_string());
+    }
+    let search = match run_search_from_query(tenant, &query) {
+        Ok(search) => search,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e })),
+            )
+                .into_response()
+        }
+    };
+
+    let (page, indexed) = match search_run_page(state, &search).await {
+        Ok(result) => result,
+        Err((status, e)) => {
+            error!("Failed to retrieve runs: {}", e);
+            return (status, Json(serde_json::json!({ "error": e }))).into_response();
+        }
+    };
+    info!(
+        "Successfully retrieved {} runs for tenant {} API",
+        page.runs.len(),
+        tenant
+    );
+
+    let etag = indexed.then(|| runs_etag(tenant, &query, &page));
+    let cache_control = HeaderValue::from_static(if indexed {
+        "private, no-cache"
+    } else {
+        "no-store"
+    });
+    if let Some(etag) = &etag {
+        if if_none_match(headers, etag) {
+            let mut response = StatusCode::NOT_MODIFIED.into_response();
+            let response_headers = response.headers_mut();
+            response_headers.insert(header::CACHE_CONTROL, cache_control);
+            if let Ok(value) = HeaderValue::from_str(etag) {
+                response_headers.insert(header::ETAG, value);
+            }
+            return response;
+        }
+    }
+
+    let next_cursor = page.next_cursor.as_ref().map(ToString::to_string);
+    let last_event_id = indexed.then(|| page.last_sequence.to_string());
+    let mut response = if bare_array {
+        Json(&page.runs).into_response()
+    } else {
+        Json(serde_json::json!({
+            "runs": page.runs,
+            "nextCursor": next_cursor,
+            "lastEventId": last_event_id,
+        }))
+        .into_response()
+    };
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CACHE_CONTROL, cache_control);
+    for (name, value) in [
+        (header::ETAG, etag),
+        (
+            header::HeaderName::from_static("x-next-cursor"),
+            next_cursor,
+        ),
+        (
+            header::HeaderName::from_static("x-last-event-id"),
+            last_event_id,
+        ),
+    ] {
+        if let Some(value) = value.and_then(|v| HeaderValue::from_str(&v).ok()) {
+            response_headers.insert(name, value);
+        }
+    }
+    response
+}
+
+/// Weak validator for a page: the index state plus the request that selected it
+fn runs_etag(tenant: &str, query: &ListRunsQuery, page: &crate::run_index::RunPage) -> String {
+    use sha2::{Digest, Sha256};
+
+    let request = Sha256::digest(format!("{}\n{:?}", tenant, query));
+    format!(
+        "W/\"{}-{}-{}\"",
+        page.last_sequence,
+        page.generation,
+        request[..8]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+/// Whether `If-None-Match` lists `etag` (weak comparison) or `*`
+fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    let strip = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    headers
+        .get_all(axum::http::header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|tag| tag.trim() == "*" || strip(tag) == strip(etag))
+}
+
 /// List runs - HTML response
 #[axum::debug_handler]
 pub async fn list_runs_html(
@@ -90,23 +340,9 @@ pub async fn list_runs_html_tenant(
 ) -> Html<String> {
     debug!("Handling HTML list runs for tenant {}: {:?}", tenant, query);
 
-    let (runs, error) = match &state.jetstream_client {
-        Some(client) => match client.list_runs_for_tenant(&tenant, query.limit).await {
-            Ok(mut runs) => {
-                // Apply in-memory filters (fast, bounded by limit)
-                if let Some(ref r) = query.ritual_filter {
-                    let needle = r.to_ascii_lowercase();
-                    runs.retain(|x| x.ritual_id.to_ascii_lowercase().contains(&needle));
-                }
-                if let Some(ref r) = query.run_id_filter {
-                    let needle = r.to_ascii_lowercase();
-                    runs.retain(|x| x.run_id.to_ascii_lowercase().contains(&needle));
-                }
-                if let Some(ref s) = query.status {
-                    if let Some(want) = parse_status_filter(s) {
-                        runs.retain(|x| x.status == want);
-                    }
-                }
+    let (runs, error) = match run_search_from_query(&tenant, &query) {
+        Ok(search) => match search_runs(&state, &search).await {
+            Ok(runs) => {
                 info!(
                     "Successfully retrieved {} runs for tenant {} HTML",
                     runs.len(),
@@ -114,12 +350,12 @@ pub async fn list_runs_html_tenant(
                 );
                 (runs, None)
             }
-            Err(e) => {
+            Err((_, e)) => {
                 error!("Failed to retrieve runs for tenant {}: {}", tenant, e);
-                (vec![], Some(format!("Failed to retrieve runs: {}", e)))
+                (vec![], Some(e))
             }
         },
-        None => (vec![], Some("JetStream is not available".to_string())),
+        Err(e) => (vec![], Some(e)),
     };
 
     let mut context = tera::Context::new();
@@ -132,6 +368,11 @@ pub async fn list_runs_html_tenant(
     context.insert("ritual_filter", &query.ritual_filter);
     context.insert("run_id_filter", &query.run_id_filter);
     context.insert("status_filter", &query.status);
+    context.insert("since_filter", &query.since);
+    context.insert("until_filter", &query.until);
+    context.insert("gate_filter", &query.gate);
+    context.insert("text_filter", &query.text);
+    context.insert("run_index_ready", &state.run_index.is_ready());
 
     context.insert(
         "contracts_browser_enabled",
@@ -141,9 +382,7 @@ pub async fn list_runs_html_tenant(
         "canvas_enabled",
         &crate::feature_flags::is_enabled("canvas-ui"),
     );
-    let html = state
-        .tera
-        .render("runs_list.html", &context)
+    let html = crate::metrics::render_template(&state.tera, "runs_list.html", &context)
         .map_err(|e| {
             error!("Template rendering failed: {}", e);
             AppError::from(e as tera::Error)
@@ -167,75 +406,11 @@ pub async fn list_runs_html_tenant(
 #[axum::debug_handler]
 pub async fn list_runs_api(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<ListRunsQuery>,
 ) -> Response {
     debug!("Handling JSON API list runs: {:?}", query);
-
-    // Validate inputs
-    if let Some(limit) = query.limit {
-        if limit == 0 || limit > 1000 {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({
-                    "error": "invalid 'limit': must be 1..=1000"
-                })),
-            )
-                .into_response();
-        }
-    }
-    if let Some(ref s) = query.status {
-        if parse_status_filter(s).is_none() {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({
-                    "error": "invalid 'status': expected one of Running, Completed, Failed"
-                })),
-            )
-                .into_response();
-        }
-    }
-
-    match &state.jetstream_client {
-        Some(client) => match client.list_runs(query.limit).await {
-            Ok(mut runs) => {
-                if let Some(ref r) = query.ritual_filter {
-                    let needle = r.to_ascii_lowercase();
-                    runs.retain(|x| x.ritual_id.to_ascii_lowercase().contains(&needle));
-                }
-                if let Some(ref r) = query.run_id_filter {
-                    let needle = r.to_ascii_lowercase();
-                    runs.retain(|x| x.run_id.to_ascii_lowercase().contains(&needle));
-                }
-                if let Some(ref s) = query.status {
-                    if let Some(want) = parse_status_filter(s) {
-                        runs.retain(|x| x.status == want);
-                    }
-                }
-                info!("Successfully retrieved {} runs for API", runs.len());
-                Json(runs).into_response()
-            }
-            Err(e) => {
-                error!("Failed to retrieve runs: {}", e);
-                (
-                    StatusCode::BAD_GATEWAY,
-                    Json(serde_json::json!({
-                        "error": format!("Failed to retrieve runs: {}", e)
-                    })),
-                )
-                    .into_response()
-            }
-        },
-        None => {
-            error!("JetStream client not available");
-            (
-                StatusCode::BAD_GATEWAY,
-                Json(serde_json::json!({
-                    "error": "JetStream is not available"
-                })),
-            )
-                .into_response()
-        }
-    }
+    list_runs_json(&state, "default", query, &headers, true).await
 }
 
 /// Get run detail - HTML response
@@ -301,6 +476,7 @@ pub async fn get_run_html_tenant(
             .map(|e| match e.event.as_str() {
                 "ritual.completed:v1" => ("Completed", "status-completed"),
                 "ritual.failed:v1" => ("Failed", "status-failed"),
+                "run.canceled:v1" | "run.abandoned:v1" => ("Canceled", "status-canceled"),
                 _ => ("Running", "status-running"),
             })
             .unwrap_or(("Running", "status-running"));
@@ -312,6 +488,17 @@ pub async fn get_run_html_tenant(
             context.insert("approvals", &summary);
         }
 
+        // Step durations, queue and approval waits for the Gantt view
+        if let Some(timeline) = crate::timeline::RunTimeline::from_events(&rd.events) {
+            context.insert("timeline", &timeline);
+        }
+
+        // CPU, memory and container totals from the steps' envelopes
+        if !rd.events.is_empty() {
+            let usage = crate::usage::RunUsage::from_events(&rd.events);
+            context.insert("usage", &usage);
+        }
+
         // Scale hint metrics for this tenant
         // Always insert scale_hint into context (as null if unavailable) to prevent Tera render errors
         if let Some(client) = &state.jetstream_client {
@@ -350,6 +537,22 @@ pub async fn get_run_html_tenant(
             context.insert("scale_hint", &serde_json::Value::Null);
         }
 
+        // What the scale hint handler last did for this tenant
+        let scale_decision = match &state.jetstream_client {
+            Some(client) => client
+                .get_latest_scale_decision(&tenant)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!(
+                        "Failed to fetch scale decision for tenant {}: {}",
+                        tenant, e
+                    );
+                    None
+                }),
+            None => None,
+        };
+        context.insert("scale_decision", &scale_decision);
+
         // Render App Pack cards for this ritual
         if let Some(registry) = &state.app_pack_registry {
             let matching_cards = registry.get_cards_for_ritual(&rd.ritual_id);
@@ -378,9 +581,7 @@ pub async fn get_run_html_tenant(
         "canvas_enabled",
         &crate::feature_flags::is_enabled("canvas-ui"),
     );
-    let html = state
-        .tera
-        .render("run_detail.html", &context)
+    let html = crate::metrics::render_template(&state.tera, "run_detail.html", &context)
         .map_err(|e| {
             error!("Template rendering failed: {}", e);
             AppError::from(e as tera::Error)
@@ -474,6 +675,7 @@ pub async fn stream_run_events_sse(
 
     let run_id_owned = run_id.clone();
     let body_stream = async_stream::stream! {
+        let _connection = crate::metrics::SseConnection::open("run_events");
         if let Some(js_client) = jetstream_client {
             // Stream with real events from JetStream
             match js_client.stream_run_events(&run_id_owned).await {
@@ -598,20 +800,12 @@ pub struct TemplateReport {
     pub templates: Vec<String>,
     pub has_filter_tojson: bool,
     pub template_ready: bool,
+    /// Operate UI build version, compared by `bootstrap --diff`
+    pub version: String,
 }
 
-/// Admin: templates/report (JSON)
-pub async fn admin_templates_report(State(state): State<AppState>, headers: HeaderMap) -> Response {
-    if let Some(expected) = &state.admin_token {
-        let got = headers
-            .get("X-Admin-Token")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-        if got != expected {
-            return (StatusCode::UNAUTHORIZED, "missing or invalid admin token").into_response();
-        }
-    }
-
+/// Admin: templates/report (JSON); guarded by the admin role
+pub async fn admin_templates_report(State(state): State<AppState>) -> Response {
     let templates = state
         .tera
         .get_template_names()
@@ -621,6 +815,7 @@ pub async fn admin_templates_report(State(state): State<AppState>, headers: Head
         templates,
         has_filter_tojson: true,
         template_ready: true,
+        version: env!("CARGO_PKG_VERSION").to_string(),
     };
     Json(body).into_response()
 }
@@ -636,6 +831,7 @@ impl RunSummary {
             crate::jetstream::RunStatus::Running => "status-running",
             crate::jetstream::RunStatus::Completed => "status-completed",
             crate::jetstream::RunStatus::Failed => "status-failed",
+            crate::jetstream::RunStatus::Canceled => "status-canceled",
         }
     }
 }
@@ -655,6 +851,7 @@ impl RunDetail {
             match last_event.event.as_str() {
                 "ritual.completed:v1" => crate::jetstream::RunStatus::Completed,
                 "ritual.failed:v1" => crate::jetstream::RunStatus::Failed,
+                "run.canceled:v1" | "run.abandoned:v1" => crate::jetstream::RunStatus::Canceled,
                 _ => crate::jetstream::RunStatus::Running,
             }
         } else {
@@ -671,6 +868,7 @@ impl RunDetail {
             crate::jetstream::RunStatus::Running => "status-running",
             crate::jetstream::RunStatus::Completed => "status-completed",
             crate::jetstream::RunStatus::Failed => "status-failed",
+            crate::jetstream::RunStatus::Canceled => "status-canceled",
         }
     }
 }
@@ -691,6 +889,8 @@ impl crate::jetstream::RitualEvent {
             "ritual.failed:v1" => "Ritual Failed".to_string(),
             "ritual.transitioned:v1" => "State Transition".to_string(),
             "timer.scheduled:v1" => "Timer Scheduled".to_string(),
+            "step.started:v1" => "Step Started".to_string(),
+            "step.completed:v1" => "Step Completed".to_string(),
             _ => self.event.clone(),
         }
     }
@@ -710,6 +910,9 @@ struct ApprovalsSummary {
     gate_id: String,
     requester: Option<String>,
     approver: Option<String>,
+    /// Delegator when the approver decided as their delegate
+    #[serde(rename = "onBehalfOf")]
+    on_behalf_of: Option<String>,
     reason: Option<String>,
     note: Option<String>,
     // Escalation information
@@ -758,6 +961,11 @@ impl ApprovalsSummary {
 
         // Extract escalation information from all events for this gate
         let escalation_info = Self::extract_escalation_info(events, &gate_id);
+        let on_behalf_of = evt
+            .extra
+            .get("onBehalfOf")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
 
         match evt.event.as_str() {
             "approval.granted:v1" => Some(Self {
@@ -770,6 +978,7 @@ impl ApprovalsSummary {
                     .get("approver")
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string()),
+                on_behalf_of,
                 reason: None,
                 note: evt
                     .extra
@@ -799,6 +1008,7 @@ impl ApprovalsSummary {
                         .get("approver")
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string()),
+                    on_behalf_of,
                     reason,
                     note: None,
                     escalation_info,
@@ -830,6 +1040,7 @@ impl ApprovalsSummary {
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string()),
                     approver: None,
+                    on_behalf_of: None,
                     reason: evt
                         .extra
                         .get("reason")
@@ -849,6 +1060,7 @@ impl ApprovalsSummary {
                     .get("approver")
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string()),
+                on_behalf_of,
                 reason: None,
                 note: evt
                     .extra
@@ -933,6 +1145,141 @@ impl ApprovalsSummary {
     }
 }
 
+// ---- Run cancellation endpoint ----
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelBody {
+    requested_by: String,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// Request cancellation of an in-flight run by publishing `run.cancel.requested:v1`.
+/// The engine running the ritual stops scheduling steps and emits `run.canceled:v1`.
+#[axum::debug_handler]
+pub async fn cancel_run_api(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<CancelBody>,
+) -> Response {
+    // CSRF protection: require X-Requested-With header for API calls
+    if headers.get("X-Requested-With").is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "X-Requested-With header required"
+            })),
+        )
+            .into_response();
+    }
+    // Same operator allowlist as approvals
+    if !approver_allowed(&body.requested_by) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({ "error": "requester not allowed" })),
+        )
+            .into_response();
+    }
+
+    let Some(js) = &state.jetstream_client else {
+        return (
+            StatusCode::BAD_GATEWAY,
+            Json(serde_json::json!({ "error": "JetStream unavailable" })),
+        )
+            .into_response();
+    };
+    let run = match js.get_run_detail(&run_id).await {
+        Ok(Some(run)) => run,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "run not found" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("get_run_detail failed: {}", e);
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({ "error": "JetStream error" })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(last) = run.events.iter().rev().find(|e| {
+        matches!(
+            e.event.as_str(),
+            "ritual.completed:v1" | "ritual.failed:v1" | "run.canceled:v1" | "run.abandoned:v1"
+        )
+    }) {
+        return (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "run already finished",
+                "event": last.event,
+            })),
+        )
+            .into_response();
+    }
+    if run
+        .events
+        .iter()
+        .any(|e| e.event == "run.cancel.requested:v1")
+    {
+        return (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "noop",
+                "reason": "cancellation already requested"
+            })),
+        )
+            .into_response();
+    }
+
+    let tenant = run
+        .events
+        .first()
+        .and_then(|e| e.extra.get("tenantId"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("default")
+        .to_string();
+    let payload = serde_json::json!({
+        "event": "run.cancel.requested:v1",
+        "ts": chrono::Utc::now().to_rfc3339(),
+        "tenantId": tenant,
+        "ritualId": run.ritual_id,
+        "runId": run_id,
+        "requestedBy": body.requested_by,
+        "reason": body.reason,
+    });
+    let msg_id = format!("{}:cancel:requested", run_id);
+    match publish_run_event(
+        &tenant,
+        &run.ritual_id,
+        &run_id,
+        payload.clone(),
+        msg_id,
+        None,
+    )
+    .await
+    {
+        Ok(_) => {
+            info!(run_id = %run_id, requested_by = %body.requested_by, "run cancellation requested");
+            (StatusCode::ACCEPTED, Json(payload)).into_response()
+        }
+        Err(e) => {
+            error!("failed to publish: {}", e);
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({"error": format!("publish failed: {}", e)})),
+            )
+                .into_response()
+        }
+    }
+}
+
 // ---- Approvals grant/deny endpoints ----
 #[derive(Debug, Deserialize)]
 pub struct ApproveBody {
@@ -955,12 +1302,12 @@ pub struct OverrideBody {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum PublishOutcome {
+pub(crate) enum PublishOutcome {
     Published,
     Conflict,
 }
 
-async fn publish_approval_event(
+pub(crate) async fn publish_run_event(
     tenant: &str,
     ritual_id: &str,
     run_id: &str,
@@ -1014,7 +1361,15 @@ async fn publish_approval_event(
         .await?
         .await
     {
-        Ok(_) => Ok(PublishOutcome::Published),
+        Ok(_) => {
+            if let Some(decision) = wards::audit::WardsDecision::from_approval_event(&payload) {
+                let stream = wards::audit::decisions_stream();
+                if let Err(e) = wards::audit::record(&js, &stream, &decision).await {
+                    warn!(rule_id = %decision.rule_id, "failed to record policy decision: {}", e);
+                }
+            }
+            Ok(PublishOutcome::Published)
+        }
         Err(err)
             if err.kind()
                 == async_nats::jetstream::context::PublishErrorKind::WrongLastSequence =>
@@ -1025,7 +1380,7 @@ async fn publish_approval_event(
     }
 }
 
-fn approver_allowed(email: &str) -> bool {
+pub(crate) fn approver_allowed(email: &str) -> bool {
     let allowlist = std::env::var("APPROVER_ALLOWLIST").unwrap_or_default();
     if allowlist.is_empty() {
         return false;
@@ -1036,11 +1391,34 @@ fn approver_allowed(email: &str) -> bool {
         .any(|allowed| !allowed.is_empty() && allowed.eq_ignore_ascii_case(email))
 }
 
+/// With token auth on, approvers can only decide gates as themselves
+pub(crate) fn require_self_approver(
+    claims: Option<&jwt_auth::Claims>,
+    approver: &str,
+) -> Result<(), Box<Response>> {
+    match claims {
+        Some(claims) if !same_principal(&claims.sub, approver) => {
+            warn!("{} tried to decide an approval as {}", claims.sub, approver);
+            Err(Box::new(
+                (
+                    StatusCode::FORBIDDEN,
+                    Json(serde_json::json!({
+                        "error": "approver must match the authenticated subject"
+                    })),
+                )
+                    .into_response(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
 #[axum::debug_handler]
 pub async fn grant_approval_api(
     State(state): State<AppState>,
     Path((run_id, gate_id)): Path<(String, String)>,
     headers: HeaderMap,
+    claims: Option<Extension<jwt_auth::Claims>>,
     Json(body): Json<ApproveBody>,
 ) -> Response {
     // CSRF protection: require X-Requested-With header for API calls
@@ -1053,13 +1431,11 @@ pub async fn grant_approval_api(
         )
             .into_response();
     }
-    if !approver_allowed(&body.approver) {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({ "error": "approver not allowed" })),
-        )
-            .into_response();
-    }
+    let on_behalf_of =
+        match delegations::approval_authority(&state, claims.as_deref(), &body.approver).await {
+            Ok(on_behalf_of) => on_behalf_of,
+            Err(response) => return *response,
+        };
 
     // Ensure stream exists before attempting to read (if explicit name set)
     if let Ok(name) = std::env::var("RITUAL_STREAM_NAME") {
@@ -1161,7 +1537,7 @@ pub async fn grant_approval_api(
     };
 
     let now = chrono::Utc::now().to_rfc3339();
-    let payload = serde_json::json!({
+    let mut payload = serde_json::json!({
         "event": "approval.granted:v1",
         "ts": now,
         "tenantId": tenant,
@@ -1171,12 +1547,15 @@ pub async fn grant_approval_api(
         "approver": body.approver,
         "note": body.note,
     });
+    if let Some(delegator) = &on_behalf_of {
+        payload["onBehalfOf"] = serde_json::Value::String(delegator.clone());
+    }
     let msg_id = format!(
         "{}:approval:{}:granted",
         payload["runId"].as_str().unwrap(),
         payload["gateId"].as_str().unwrap()
     );
-    match publish_approval_event(
+    match publish_run_event(
         &tenant,
         payload["ritualId"].as_str().unwrap(),
         payload["runId"].as_str().unwrap(),
@@ -1243,6 +1622,7 @@ pub async fn deny_approval_api(
     State(state): State<AppState>,
     Path((run_id, gate_id)): Path<(String, String)>,
     headers: HeaderMap,
+    claims: Option<Extension<jwt_auth::Claims>>,
     Json(body): Json<DenyBody>,
 ) -> Response {
     // CSRF protection: require X-Requested-With header for API calls
@@ -1255,13 +1635,11 @@ pub async fn deny_approval_api(
         )
             .into_response();
     }
-    if !approver_allowed(&body.approver) {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({ "error": "approver not allowed" })),
-        )
-            .into_response();
-    }
+    let on_behalf_of =
+        match delegations::approval_authority(&state, claims.as_deref(), &body.approver).await {
+            Ok(on_behalf_of) => on_behalf_of,
+            Err(response) => return *response,
+        };
 
     // Ensure stream exists before attempting to read
     if let Some(_jsctx) = &state.jetstream_client {
@@ -1363,7 +1741,7 @@ pub async fn deny_approval_api(
     };
 
     let now = chrono::Utc::now().to_rfc3339();
-    let payload = serde_json::json!({
+    let mut payload = serde_json::json!({
         "event": "approval.denied:v1",
         "ts": now,
         "tenantId": tenant,
@@ -1373,12 +1751,15 @@ pub async fn deny_approval_api(
         "approver": body.approver,
         "reason": body.reason,
     });
+    if let Some(delegator) = &on_behalf_of {
+        payload["onBehalfOf"] = serde_json::Value::String(delegator.clone());
+    }
     let msg_id = format!(
         "{}:approval:{}:denied",
         payload["runId"].as_str().unwrap(),
         payload["gateId"].as_str().unwrap()
     );
-    match publish_approval_event(
+    match publish_run_event(
         &tenant,
         payload["ritualId"].as_str().unwrap(),
         payload["runId"].as_str().unwrap(),
@@ -1519,79 +1900,11 @@ mod tests {
 pub async fn list_runs_api_tenant(
     State(state): State<AppState>,
     Path(tenant): Path<String>,
+    headers: HeaderMap,
     Query(query): Query<ListRunsQuery>,
 ) -> Response {
     debug!("Handling tenant {} JSON API list runs: {:?}", tenant, query);
-
-    // Validate inputs
-    if let Some(limit) = query.limit {
-        if limit == 0 || limit > 1000 {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({
-                    "error": "invalid 'limit': must be 1..=1000"
-                })),
-            )
-                .into_response();
-        }
-    }
-    if let Some(ref s) = query.status {
-        if parse_status_filter(s).is_none() {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({
-                    "error": "invalid 'status': expected one of Running, Completed, Failed"
-                })),
-            )
-                .into_response();
-        }
-    }
-
-    match &state.jetstream_client {
-        Some(client) => match client.list_runs_for_tenant(&tenant, query.limit).await {
-            Ok(mut runs) => {
-                if let Some(ref r) = query.ritual_filter {
-                    let needle = r.to_ascii_lowercase();
-                    runs.retain(|x| x.ritual_id.to_ascii_lowercase().contains(&needle));
-                }
-                if let Some(ref r) = query.run_id_filter {
-                    let needle = r.to_ascii_lowercase();
-                    runs.retain(|x| x.run_id.to_ascii_lowercase().contains(&needle));
-                }
-                if let Some(ref s) = query.status {
-                    if let Some(want) = parse_status_filter(s) {
-                        runs.retain(|x| x.status == want);
-                    }
-                }
-                info!(
-                    "Successfully retrieved {} runs for tenant {} API",
-                    runs.len(),
-                    tenant
-                );
-                Json(serde_json::json!({ "runs": runs })).into_response()
-            }
-            Err(e) => {
-                error!("Failed to retrieve runs for tenant {}: {}", tenant, e);
-                (
-                    StatusCode::BAD_GATEWAY,
-                    Json(serde_json::json!({
-                        "error": format!("Failed to retrieve runs: {}", e)
-                    })),
-                )
-                    .into_response()
-            }
-        },
-        None => {
-            error!("JetStream client not available");
-            (
-                StatusCode::BAD_GATEWAY,
-                Json(serde_json::json!({
-                    "error": "JetStream is not available"
-                })),
-            )
-                .into_response()
-        }
-    }
+    list_runs_json(&state, &tenant, query, &headers, false).await
 }
 
 /// Get run detail for a specific tenant - JSON API response
@@ -1678,6 +1991,7 @@ pub async fn stream_run_events_sse_tenant(
     let tenant_owned = tenant.clone();
     let run_id_owned = run_id.clone();
     let body_stream = async_stream::stream! {
+        let _connection = crate::metrics::SseConnection::open("run_events");
         if let Some(js_client) = jetstream_client {
             // Stream with real events from JetStream
             match js_client.stream_run_events_for_tenant(&tenant_owned, &run_id_owned).await {
@@ -1772,7 +2086,139 @@ pub async fn stream_run_events_sse_tenant(
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(hb_secs));
             interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
             loop {
-                interval.tick().await;
+                interval.tick().await;
+                yield Ok(
+                    axum::response::sse::Event::default()
+                        .event("heartbeat")
+                        .comment("keep-alive")
+                );
+            }
+        }
+    };
+
+    axum::response::Sse::new(body_stream).into_response()
+}
+
+/// Stream runs list updates - SSE response
+#[axum::debug_handler]
+pub async fn stream_runs_sse(State(state): State<AppState>) -> Response {
+    stream_runs_sse_tenant(State(state), Path("default".to_string())).await
+}
+
+/// Stream runs list updates for a specific tenant - SSE response
+///
+/// Each `run` event carries a [`crate::jetstream::RunUpdate`] for one run that
+/// received a new ritual event after the stream was opened. Its `id` is the
+/// event's stream sequence, which `GET /api/runs` accepts as `Last-Event-ID`.
+#[axum::debug_handler]
+pub async fn stream_runs_sse_tenant(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+) -> Response {
+    let hb_secs: u64 = std::env::var("SSE_HEARTBEAT_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(15);
+
+    let jetstream_client = state.jetstream_client.clone();
+    let tenant_owned = tenant.clone();
+    let body_stream = async_stream::stream! {
+        let _connection = crate::metrics::SseConnection::open("runs");
+        let mut heartbeat_interval = tokio::time::interval(tokio::time::Duration::from_secs(hb_secs));
+        heartbeat_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        let update_stream = match jetstream_client {
+            Some(js_client) => match js_client.stream_run_updates_for_tenant(&tenant_owned).await {
+                Ok(update_stream) => Some(update_stream),
+                Err(e) => {
+                    error!("Failed to create runs stream for tenant {}: {}", tenant_owned, e);
+                    let error_payload = serde_json::json!({
+                        "type": "error",
+                        "message": format!("Failed to create runs stream: {}", e)
+                    });
+                    yield Ok::<_, std::convert::Infallible>(
+                        axum::response::sse::Event::default()
+                            .event("error")
+                            .json_data(error_payload)
+                            .expect("Valid JSON")
+                    );
+                    None
+                }
+            },
+            None => {
+                let warning_payload = serde_json::json!({
+                    "type": "warning",
+                    "message": "JetStream unavailable; operating in degraded mode"
+                });
+                yield Ok(
+                    axum::response::sse::Event::default()
+                        .event("warning")
+                        .json_data(warning_payload)
+                        .expect("Valid JSON")
+                );
+                None
+            }
+        };
+
+        if let Some(update_stream) = update_stream {
+            let init_payload = serde_json::json!({
+                "type": "init",
+                "message": "Connected to runs stream"
+            });
+            yield Ok(
+                axum::response::sse::Event::default()
+                    .event("init")
+                    .json_data(init_payload)
+                    .expect("Valid JSON")
+            );
+
+            futures_util::pin_mut!(update_stream);
+            loop {
+                tokio::select! {
+                    update_result = update_stream.next() => {
+                        match update_result {
+                            Some(Ok((sequence, update))) => {
+                                yield Ok(
+                                    axum::response::sse::Event::default()
+                                        .event("run")
+                                        .id(sequence.to_string())
+                                        .json_data(update)
+                                        .expect("Valid JSON")
+                                );
+                            }
+                            Some(Err(e)) => {
+                                error!("Runs stream error for tenant {}: {}", tenant_owned, e);
+                                let error_payload = serde_json::json!({
+                                    "type": "error",
+                                    "message": format!("Stream error: {}", e)
+                                });
+                                yield Ok(
+                                    axum::response::sse::Event::default()
+                                        .event("error")
+                                        .json_data(error_payload)
+                                        .expect("Valid JSON")
+                                );
+                                break;
+                            }
+                            None => {
+                                debug!("Runs stream ended for tenant {}", tenant_owned);
+                                break;
+                            }
+                        }
+                    }
+                    _ = heartbeat_interval.tick() => {
+                        yield Ok(
+                            axum::response::sse::Event::default()
+                                .event("heartbeat")
+                                .comment("keep-alive")
+                        );
+                    }
+                }
+            }
+        } else {
+            // Keep the connection open so the browser does not reconnect in a tight loop
+            loop {
+                heartbeat_interval.tick().await;
                 yield Ok(
                     axum::response::sse::Event::default()
                         .event("heartbeat")
@@ -1791,6 +2237,7 @@ pub async fn grant_approval_api_tenant(
     State(state): State<AppState>,
     Path((tenant, run_id, gate_id)): Path<(String, String, String)>,
     headers: HeaderMap,
+    claims: Option<Extension<jwt_auth::Claims>>,
     Json(body): Json<ApproveBody>,
 ) -> Response {
     debug!(
@@ -1808,13 +2255,11 @@ pub async fn grant_approval_api_tenant(
         )
             .into_response();
     }
-    if !approver_allowed(&body.approver) {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({ "error": "approver not allowed" })),
-        )
-            .into_response();
-    }
+    let on_behalf_of =
+        match delegations::approval_authority(&state, claims.as_deref(), &body.approver).await {
+            Ok(on_behalf_of) => on_behalf_of,
+            Err(response) => return *response,
+        };
 
     // Ensure stream exists before attempting to read (if explicit name set)
     if let Ok(name) = std::env::var("RITUAL_STREAM_NAME") {
@@ -1907,7 +2352,7 @@ pub async fn grant_approval_api_tenant(
     };
 
     let now = chrono::Utc::now().to_rfc3339();
-    let payload = serde_json::json!({
+    let mut payload = serde_json::json!({
         "event": "approval.granted:v1",
         "ts": now,
         "tenantId": tenant,
@@ -1917,12 +2362,15 @@ pub async fn grant_approval_api_tenant(
         "approver": body.approver,
         "note": body.note,
     });
+    if let Some(delegator) = &on_behalf_of {
+        payload["onBehalfOf"] = serde_json::Value::String(delegator.clone());
+    }
     let msg_id = format!(
         "{}:approval:{}:granted",
         payload["runId"].as_str().unwrap(),
         payload["gateId"].as_str().unwrap()
     );
-    match publish_approval_event(
+    match publish_run_event(
         &tenant,
         payload["ritualId"].as_str().unwrap(),
         payload["runId"].as_str().unwrap(),
@@ -1996,6 +2444,7 @@ pub async fn deny_approval_api_tenant(
     State(state): State<AppState>,
     Path((tenant, run_id, gate_id)): Path<(String, String, String)>,
     headers: HeaderMap,
+    claims: Option<Extension<jwt_auth::Claims>>,
     Json(body): Json<DenyBody>,
 ) -> Response {
     debug!(
@@ -2013,13 +2462,11 @@ pub async fn deny_approval_api_tenant(
         )
             .into_response();
     }
-    if !approver_allowed(&body.approver) {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(serde_json::json!({ "error": "approver not allowed" })),
-        )
-            .into_response();
-    }
+    let on_behalf_of =
+        match delegations::approval_authority(&state, claims.as_deref(), &body.approver).await {
+            Ok(on_behalf_of) => on_behalf_of,
+            Err(response) => return *response,
+        };
 
     // Ensure stream exists before attempting to read
     if let Some(_jsctx) = &state.jetstream_client {
@@ -2115,7 +2562,7 @@ pub async fn deny_approval_api_tenant(
     };
 
     let now = chrono::Utc::now().to_rfc3339();
-    let payload = serde_json::json!({
+    let mut payload = serde_json::json!({
         "event": "approval.denied:v1",
         "ts": now,
         "tenantId": tenant,
@@ -2125,12 +2572,15 @@ pub async fn deny_approval_api_tenant(
         "approver": body.approver,
         "reason": body.reason,
     });
+    if let Some(delegator) = &on_behalf_of {
+        payload["onBehalfOf"] = serde_json::Value::String(delegator.clone());
+    }
     let msg_id = format!(
         "{}:approval:{}:denied",
         payload["runId"].as_str().unwrap(),
         payload["gateId"].as_str().unwrap()
     );
-    match publish_approval_event(
+    match publish_run_event(
         &tenant,
         payload["ritualId"].as_str().unwrap(),
         payload["runId"].as_str().unwrap(),
@@ -2204,6 +2654,7 @@ pub async fn override_approval_api_tenant(
     State(state): State<AppState>,
     Path((tenant, run_id, gate_id)): Path<(String, String, String)>,
     headers: HeaderMap,
+    claims: Option<Extension<jwt_auth::Claims>>,
     Json(body): Json<OverrideBody>,
 ) -> Response {
     debug!(
@@ -2221,6 +2672,9 @@ pub async fn override_approval_api_tenant(
         )
             .into_response();
     }
+    if let Err(response) = require_self_approver(claims.as_deref(), &body.approver) {
+        return *response;
+    }
 
     // Check if approver is allowed (for emergency override, we might have stricter requirements)
     if !approver_allowed(&body.approver) {
@@ -2356,7 +2810,7 @@ pub async fn override_approval_api_tenant(
         payload["gateId"].as_str().unwrap()
     );
 
-    match publish_approval_event(
+    match publish_run_event(
         &tenant,
         payload["ritualId"].as_str().unwrap(),
         payload["runId"].as_str().unwrap(),
@@ -2421,6 +2875,181 @@ pub async fn override_approval_api_tenant(
     }
 }
 
+// ---- Scale Decision Routes ----
+
+const SCALE_OUTCOMES: [&str; 5] = ["scaled", "clamped", "skipped", "forwarded", "failed"];
+
+// Query parameters for the scale decisions API
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScaleDecisionsQuery {
+    pub outcome: Option<String>, // scaled | clamped | skipped | forwarded | failed
+    pub limit: Option<usize>,
+}
+
+/// List scale hint handler decisions for a tenant, newest first - JSON API response
+#[axum::debug_handler]
+pub async fn list_scale_decisions_api_tenant(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    Query(query): Query<ScaleDecisionsQuery>,
+) -> Response {
+    debug!("Handling scale decisions query for {}: {:?}", tenant, query);
+
+    let limit = query.limit.unwrap_or(50);
+    if limit == 0 || limit > 1000 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "invalid 'limit': must be 1..=1000"
+            })),
+        )
+            .into_response();
+    }
+    if let Some(outcome) = query.outcome.as_deref() {
+        if !SCALE_OUTCOMES.contains(&outcome) {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": "invalid 'outcome': expected one of scaled, clamped, skipped, forwarded, failed"
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    match &state.jetstream_client {
+        Some(client) => match client
+            .query_scale_decisions(&tenant, query.outcome.as_deref(), limit)
+            .await
+        {
+            Ok(decisions) => Json(decisions).into_response(),
+            Err(e) => {
+                error!("Failed to query scale decisions: {}", e);
+                (
+                    StatusCode::BAD_GATEWAY,
+                    Json(serde_json::json!({
+                        "error": format!("Failed to query scale decisions: {}", e)
+                    })),
+                )
+                    .into_response()
+            }
+        },
+        None => {
+            error!("JetStream client not available");
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({
+                    "error": "JetStream is not available"
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+// ---- Policy Audit Routes ----
+
+// Query parameters for the policy decisions API
+#[derive(Deserialize, Debug, Clone)]
+pub struct DecisionsQuery {
+    #[serde(rename = "runId")]
+    pub run_id: Option<String>,
+    pub kind: Option<String>, // approval-granted | approval-denied | quota-rejected | override-used
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<usize>,
+}
+
+/// List wards policy decisions for a tenant - JSON API response
+#[axum::debug_handler]
+pub async fn list_decisions_api_tenant(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    Query(query): Query<DecisionsQuery>,
+) -> Response {
+    list_decisions(&state, Some(tenant), query).await
+}
+
+/// List wards policy decisions recorded for a run across tenants - JSON API response
+#[axum::debug_handler]
+pub async fn list_run_decisions_api(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+    Query(mut query): Query<DecisionsQuery>,
+) -> Response {
+    query.run_id = Some(run_id);
+    list_decisions(&state, None, query).await
+}
+
+async fn list_decisions(
+    state: &AppState,
+    tenant: Option<String>,
+    query: DecisionsQuery,
+) -> Response {
+    debug!(
+        "Handling policy decisions query for {:?}: {:?}",
+        tenant, query
+    );
+
+    if let Some(limit) = query.limit {
+        if limit == 0 || limit > wards::audit::MAX_QUERY_LIMIT {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": "invalid 'limit': must be 1..=1000"
+                })),
+            )
+                .into_response();
+        }
+    }
+    let kind = match query.kind.as_deref().map(str::parse) {
+        None => None,
+        Some(Ok(kind)) => Some(kind),
+        Some(Err(_)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": "invalid 'kind': expected one of approval-granted, approval-denied, quota-rejected, override-used"
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let decision_query = wards::audit::DecisionQuery {
+        tenant_id: tenant,
+        run_id: query.run_id,
+        kind,
+        since: query.since,
+        limit: query.limit,
+    };
+
+    match &state.jetstream_client {
+        Some(client) => match client.query_decisions(&decision_query).await {
+            Ok(decisions) => Json(decisions).into_response(),
+            Err(e) => {
+                error!("Failed to query policy decisions: {}", e);
+                (
+                    StatusCode::BAD_GATEWAY,
+                    Json(serde_json::json!({
+                        "error": format!("Failed to query policy decisions: {}", e)
+                    })),
+                )
+                    .into_response()
+            }
+        },
+        None => {
+            error!("JetStream client not available");
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({
+                    "error": "JetStream is not available"
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
 // ---- Graph Viewer Routes ----
 
 /// Graph viewer - HTML page
@@ -2443,7 +3072,6 @@ pub async fn graph_viewer_html(
     context.insert("project_id", &project_id);
     context.insert("namespace", &namespace);
     context.insert("graph_id", &graph_id);
-    context.insert("runtime_api_url", &get_runtime_api_url());
 
     // If run_id is provided, fetch run details and render cards
     if let Some(ref run_id) = query.run_id {
@@ -2490,9 +3118,7 @@ pub async fn graph_viewer_html(
         "canvas_enabled",
         &crate::feature_flags::is_enabled("canvas-ui"),
     );
-    let html = state
-        .tera
-        .render("graph_viewer.html", &context)
+    let html = crate::metrics::render_template(&state.tera, "graph_viewer.html", &context)
         .unwrap_or_else(|e| {
             error!("Template rendering failed: {}", e);
             format!(
@@ -2508,6 +3134,64 @@ fn get_runtime_api_url() -> String {
     std::env::var("RUNTIME_API_URL").unwrap_or_else(|_| "http://localhost:8080".to_string())
 }
 
+/// Graph API responses larger than this are refused rather than relayed
+const GRAPH_PROXY_MAX_BYTES: usize = 10_000_000;
+
+/// Graph data API - relays read-only `/api/graph/*` requests to the runtime
+/// (`RUNTIME_API_URL`) so the graph viewer fetches from its own origin
+///
+/// Serves `/api/graph/tags`, `/api/graph/commits`, `/api/graph/commits/:commit_id`
+/// and `/api/graph/snapshot`; query parameters are passed through unchanged.
+pub async fn graph_api_proxy(uri: axum::http::Uri) -> Response {
+    let path_and_query = uri
+        .path_and_query()
+        .map(|p| p.as_str())
+        .unwrap_or_else(|| uri.path());
+    let target = format!(
+        "{}{}",
+        get_runtime_api_url().trim_end_matches('/'),
+        path_and_query
+    );
+    debug!("Proxying graph API request to {}", target);
+
+    match fetch_graph_api(&target).await {
+        Ok((status, body)) => (
+            status,
+            [(axum::http::header::CONTENT_TYPE, "application/json")],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Graph API request to runtime failed: {}", e);
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(serde_json::json!({
+                    "error": format!("Runtime graph API unavailable: {}", e),
+                    "code": "RUNTIME_UNAVAILABLE"
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn fetch_graph_api(url: &str) -> anyhow::Result<(StatusCode, axum::body::Bytes)> {
+    // Materializing a large graph can take a while
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?;
+
+    let response = client.get(url).send().await?;
+    let status = StatusCode::from_u16(response.status().as_u16())?;
+
+    let bytes = response.bytes().await?;
+    if bytes.len() > GRAPH_PROXY_MAX_BYTES {
+        anyhow::bail!("Graph response too large (>10MB)");
+    }
+
+    Ok((status, bytes))
+}
+
 // ---- Canvas DAG Viewer Routes ----
 
 /// Canvas DAG viewer - interactive ritual DAG visualization with telemetry overlays
@@ -2533,9 +3217,7 @@ pub async fn canvas_viewer_html(State(state): State<AppState>) -> Result<Html<St
     );
     context.insert("canvas_enabled", &true);
 
-    let html = state
-        .tera
-        .render("canvas_viewer.html", &context)
+    let html = crate::metrics::render_template(&state.tera, "canvas_viewer.html", &context)
         .map_err(|e| {
             error!("Canvas template rendering failed: {}", e);
             AppError {
@@ -2563,6 +3245,26 @@ pub struct SchemaMetadata {
     #[serde(rename = "schemaId")]
     pub schema_id: String,
     pub source: String,
+    /// Wizard pages, from the schema's `x-wizard` extension or a single page
+    pub steps: Vec<crate::forms::WizardStep>,
+}
+
+/// Form data posted by the renderer
+#[derive(Deserialize, Debug)]
+pub struct FormSubmission {
+    #[serde(rename = "schemaId", default)]
+    pub schema_id: Option<String>,
+    /// Where to load the schema from for validation; without either, the
+    /// data is accepted unvalidated
+    #[serde(rename = "schemaName", default)]
+    pub schema_name: Option<String>,
+    #[serde(rename = "schemaUrl", default)]
+    pub schema_url: Option<String>,
+    #[serde(default)]
+    pub data: serde_json::Value,
+    /// Validate only this wizard step's fields
+    #[serde(default)]
+    pub step: Option<String>,
 }
 
 /// Schema form renderer - HTML page
@@ -2586,9 +3288,7 @@ pub async fn schema_form_html(
         "canvas_enabled",
         &crate::feature_flags::is_enabled("canvas-ui"),
     );
-    let html = state
-        .tera
-        .render("form_renderer.html", &context)
+    let html = crate::metrics::render_template(&state.tera, "form_renderer.html", &context)
         .unwrap_or_else(|e| {
             error!("Template rendering failed: {}", e);
             format!(
@@ -2608,19 +3308,38 @@ pub async fn schema_metadata_api(
 ) -> Response {
     debug!("Handling schema metadata API: {:?}", query);
 
+    let (schema, schema_id, source) = match resolve_schema(query).await {
+        Ok(resolved) => resolved,
+        Err(response) => return response,
+    };
+    let steps = crate::forms::wizard_steps(&schema);
+
+    Json(SchemaMetadata {
+        schema,
+        schema_id,
+        source,
+        steps,
+    })
+    .into_response()
+}
+
+/// Load the schema a form query points at, or the error response to return
+async fn resolve_schema(
+    query: SchemaFormQuery,
+) -> Result<(serde_json::Value, String, String), Response> {
     // Determine schema source and fetch
     let (schema, schema_id, source) = if let Some(name) = query.schema_name {
         // Load from local contracts/schemas
         match load_local_schema(&name) {
             Ok((schema, id)) => (schema, id, "local".to_string()),
             Err(e) => {
-                return (
+                return Err((
                     StatusCode::NOT_FOUND,
                     Json(serde_json::json!({
                         "error": format!("Failed to load local schema: {}", e)
                     })),
                 )
-                    .into_response()
+                    .into_response())
             }
         }
     } else if let Some(url) = query.schema_url {
@@ -2628,45 +3347,44 @@ pub async fn schema_metadata_api(
         match fetch_remote_schema(&url).await {
             Ok(schema) => (schema, url.clone(), format!("remote:{}", url)),
             Err(e) => {
-                return (
+                return Err((
                     StatusCode::BAD_GATEWAY,
                     Json(serde_json::json!({
                         "error": format!("Failed to fetch remote schema: {}", e)
                     })),
                 )
-                    .into_response()
+                    .into_response())
             }
         }
     } else {
-        return (
+        return Err((
             StatusCode::BAD_REQUEST,
             Json(serde_json::json!({
                 "error": "Either schemaUrl or schemaName must be provided"
             })),
         )
-            .into_response();
+            .into_response());
     };
 
     // Validate it's a valid JSON Schema
     if !schema.is_object() {
-        return (
+        return Err((
             StatusCode::BAD_REQUEST,
             Json(serde_json::json!({
                 "error": "Invalid schema: must be a JSON object"
             })),
         )
-            .into_response();
+            .into_response());
     }
 
-    Json(SchemaMetadata {
-        schema,
-        schema_id,
-        source,
-    })
-    .into_response()
+    Ok((schema, schema_id, source))
 }
 
 /// Submit form data - JSON API endpoint
+///
+/// When the submission names its schema, the data is validated against it:
+/// `422` lists the failing fields. With `step`, only that wizard step's
+/// fields are checked and a valid step answers `valid` with the next step.
 #[axum::debug_handler]
 pub async fn submit_form_api(
     State(_state): State<AppState>,
@@ -2674,12 +3392,93 @@ pub async fn submit_form_api(
 ) -> Response {
     debug!("Handling form submission: {:?}", payload);
 
-    // Echo back the form data for now (can be integrated with workflow later)
-    Json(serde_json::json!({
-        "status": "received",
-        "data": payload
-    }))
-    .into_response()
+    let submission: FormSubmission = match serde_json::from_value(payload.clone()) {
+        Ok(submission) => submission,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("Invalid form submission: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    };
+    if submission.schema_name.is_none() && submission.schema_url.is_none() {
+        // Echo back the form data for now (can be integrated with workflow later)
+        return Json(serde_json::json!({
+            "status": "received",
+            "data": payload
+        }))
+        .into_response();
+    }
+
+    let query = SchemaFormQuery {
+        schema_url: submission.schema_url.clone(),
+        schema_name: submission.schema_name.clone(),
+    };
+    let (schema, schema_id, _) = match resolve_schema(query).await {
+        Ok(resolved) => resolved,
+        Err(response) => return response,
+    };
+    if submission
+        .schema_id
+        .as_deref()
+        .is_some_and(|id| id != schema_id)
+    {
+        warn!(
+            "Form submitted for schema {:?} but validated against {}",
+            submission.schema_id, schema_id
+        );
+    }
+
+    let step = submission.step.as_deref();
+    let errors = match crate::forms::validate(&schema, &submission.data, step) {
+        Ok(errors) => errors,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    };
+    if !errors.is_empty() {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(serde_json::json!({
+                "status": "invalid",
+                "schemaId": schema_id,
+                "step": step,
+                "errors": errors
+            })),
+        )
+            .into_response();
+    }
+
+    match step {
+        Some(step) => {
+            let steps = crate::forms::wizard_steps(&schema);
+            let next_step = steps
+                .iter()
+                .position(|s| s.id == step)
+                .and_then(|index| steps.get(index + 1))
+                .map(|s| s.id.clone());
+            Json(serde_json::json!({
+                "status": "valid",
+                "schemaId": schema_id,
+                "step": step,
+                "nextStep": next_step
+            }))
+            .into_response()
+        }
+        None => Json(serde_json::json!({
+            "status": "received",
+            "schemaId": schema_id,
+            "data": submission.data
+        }))
+        .into_response(),
+    }
 }
 
 fn load_local_schema(name: &str) -> anyhow::Result<(serde_json::Value, String)> {
@@ -2743,6 +3542,10 @@ pub struct WorkflowViewerQuery {
     pub workflow_url: Option<String>,
     #[serde(rename = "workflowPath")]
     pub workflow_path: Option<String>,
+    /// Run whose live step states are overlaid on the graph
+    #[serde(rename = "runId")]
+    pub run_id: Option<String>,
+    pub tenant: Option<String>,
 }
 
 #[derive(Serialize, Debug)]
@@ -2765,6 +3568,8 @@ pub async fn workflow_viewer_html(
     context.insert("current_page", &"workflow");
     context.insert("workflow_url", &query.workflow_url);
     context.insert("workflow_path", &query.workflow_path);
+    context.insert("run_id", &query.run_id);
+    context.insert("tenant", &query.tenant);
     context.insert("runtime_api_url", &get_runtime_api_url());
 
     context.insert(
@@ -2775,9 +3580,7 @@ pub async fn workflow_viewer_html(
         "canvas_enabled",
         &crate::feature_flags::is_enabled("canvas-ui"),
     );
-    let html = state
-        .tera
-        .render("workflow_viewer.html", &context)
+    let html = crate::metrics::render_template(&state.tera, "workflow_viewer.html", &context)
         .unwrap_or_else(|e| {
             error!("Template rendering failed: {}", e);
             format!(
@@ -3135,9 +3938,7 @@ pub async fn app_pack_cards_html(
         "canvas_enabled",
         &crate::feature_flags::is_enabled("canvas-ui"),
     );
-    let html = state
-        .tera
-        .render("app_pack_cards.html", &context)
+    let html = crate::metrics::render_template(&state.tera, "app_pack_cards.html", &context)
         .map_err(|e| {
             error!("Template rendering failed: {}", e);
             AppError::from(e as tera::Error)
@@ -3164,13 +3965,15 @@ pub async fn app_pack_cards_api(
         query.ritual
     );
 
-    let cards = match &state.app_pack_registry {
+    let (cards, registry_version) = match &state.app_pack_registry {
         Some(registry) => {
-            if let Some(ref ritual) = query.ritual {
-                registry.get_cards_for_ritual(ritual)
+            let snapshot = registry.snapshot();
+            let cards = if let Some(ref ritual) = query.ritual {
+                snapshot.get_cards_for_ritual(ritual)
             } else {
-                registry.get_all_cards()
-            }
+                snapshot.get_all_cards()
+            };
+            (cards, snapshot.version)
         }
         None => {
             return (
@@ -3183,5 +3986,9 @@ pub async fn app_pack_cards_api(
         }
     };
 
-    (StatusCode::OK, Json(serde_json::json!({ "cards": cards }))).into_response()
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "cards": cards, "registryVersion": registry_version })),
+    )
+        .into_response()
 }
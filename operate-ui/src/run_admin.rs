@@ -0,0 +1,573 @@
+//! Admin actions for runs the engine cannot finish on its own
+//!
+//! - **Abandon** publishes `run.abandoned:v1` for a run that will never reach a
+//!   terminal event (its engine is gone, its ritual was deleted), so it stops
+//!   showing as running.
+//! - **Re-emit gate** publishes a gate's latest decision again under a fresh
+//!   message id, for runs whose engine missed the original grant or denial.
+//! - **Purge preview runs** deletes a tenant's seeded demo runs, recognised by
+//!   run-id prefix (`OPERATE_UI_PREVIEW_RUN_PREFIXES`, comma separated,
+//!   default `bootstrap-run-`), from the ritual stream and the run index.
+//!
+//! Every action needs a reason and a requester on `APPROVER_ALLOWLIST`, and is
+//! recorded in the `OPERATE_UI_ADMIN_ACTIONS` KV bucket.
+
+use crate::jetstream::{RitualEvent, RunDetail};
+use crate::routes::{approver_allowed, publish_run_event, PublishOutcome};
+use crate::AppState;
+use async_nats::jetstream::kv;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{error, info, warn};
+
+const BUCKET: &str = "OPERATE_UI_ADMIN_ACTIONS";
+const MAX_PURGE_RUNS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AdminActionKind {
+    AbandonRun,
+    ReemitGate,
+    PurgePreviewRuns,
+}
+
+/// One recorded admin action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminAction {
+    pub id: String,
+    pub tenant: String,
+    pub action: AdminActionKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gate_id: Option<String>,
+    pub requested_by: String,
+    pub reason: String,
+    pub ts: DateTime<Utc>,
+    pub details: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminActionBody {
+    pub requested_by: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgePreviewBody {
+    pub requested_by: String,
+    pub reason: String,
+    /// List the runs that would be purged without deleting anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Run-id prefixes that mark seeded preview runs
+pub fn preview_run_prefixes() -> Vec<String> {
+    std::env::var("OPERATE_UI_PREVIEW_RUN_PREFIXES")
+        .unwrap_or_else(|_| "bootstrap-run-".to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn is_preview_run(run_id: &str, prefixes: &[String]) -> bool {
+    prefixes.iter().any(|p| run_id.starts_with(p.as_str()))
+}
+
+/// The event that finished the run, if any
+pub fn terminal_event(run: &RunDetail) -> Option<&RitualEvent> {
+    run.events.iter().rev().find(|e| {
+        matches!(
+            e.event.as_str(),
+            "ritual.completed:v1" | "ritual.failed:v1" | "run.canceled:v1" | "run.abandoned:v1"
+        )
+    })
+}
+
+/// The latest grant, denial or override of `gate_id`
+pub fn gate_decision<'a>(run: &'a RunDetail, gate_id: &str) -> Option<&'a RitualEvent> {
+    run.events.iter().rev().find(|e| {
+        matches!(
+            e.event.as_str(),
+            "approval.granted:v1" | "approval.denied:v1" | "approval.override:v1"
+        ) && e.extra.get("gateId").and_then(|v| v.as_str()) == Some(gate_id)
+    })
+}
+
+/// The payload a recorded event was published with
+pub fn original_payload(event: &RitualEvent) -> serde_json::Value {
+    let mut payload = serde_json::to_value(event).unwrap_or_else(|_| json!({}));
+    if let Some(map) = payload.as_object_mut() {
+        map.remove("stream_sequence");
+    }
+    payload
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+/// CSRF header, a reason and an allowlisted requester
+fn check_request(
+    headers: &HeaderMap,
+    requested_by: &str,
+    reason: &str,
+) -> Result<(), Box<Response>> {
+    if headers.get("X-Requested-With").is_none() {
+        return Err(Box::new(error_response(
+            StatusCode::BAD_REQUEST,
+            "X-Requested-With header required",
+        )));
+    }
+    if reason.trim().is_empty() {
+        return Err(Box::new(error_response(
+            StatusCode::BAD_REQUEST,
+            "reason is required",
+        )));
+    }
+    if !approver_allowed(requested_by) {
+        return Err(Box::new(error_response(
+            StatusCode::FORBIDDEN,
+            "requester not allowed",
+        )));
+    }
+    Ok(())
+}
+
+async fn load_run(state: &AppState, tenant: &str, run_id: &str) -> Result<RunDetail, Response> {
+    let Some(client) = &state.jetstream_client else {
+        return Err(error_response(
+            StatusCode::BAD_GATEWAY,
+            "JetStream is not available",
+        ));
+    };
+    match client.get_run_detail_for_tenant(tenant, run_id).await {
+        Ok(Some(run)) => Ok(run),
+        Ok(None) => Err(error_response(StatusCode::NOT_FOUND, "run not found")),
+        Err(e) => {
+            error!("get_run_detail_for_tenant failed: {}", e);
+            Err(error_response(StatusCode::BAD_GATEWAY, "JetStream error"))
+        }
+    }
+}
+
+async fn store(state: &AppState) -> anyhow::Result<kv::Store> {
+    let client = state
+        .jetstream_client
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("JetStream is not available"))?;
+    client
+        .key_value(BUCKET, "Operate UI admin actions on runs")
+        .await
+}
+
+/// Record an action that has already happened; a failure is logged and
+/// reported in the response rather than undoing the action
+async fn record(state: &AppState, action: &AdminAction) -> Option<String> {
+    let result = async {
+        let store = store(state).await?;
+        let key = format!("{}.{}", action.tenant, action.id);
+        store
+            .put(key, serde_json::to_vec(action)?.into())
+            .await
+            .map_err(anyhow::Error::from)
+    }
+    .await;
+    match result {
+        Ok(_) => None,
+        Err(e) => {
+            error!(action_id = %action.id, "failed to record admin action: {}", e);
+            Some(e.to_string())
+        }
+    }
+}
+
+fn new_action(
+    tenant: &str,
+    action: AdminActionKind,
+    run_id: Option<&str>,
+    gate_id: Option<&str>,
+    requested_by: &str,
+    reason: &str,
+    details: serde_json::Value,
+) -> AdminAction {
+    AdminAction {
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        tenant: tenant.to_string(),
+        action,
+        run_id: run_id.map(str::to_string),
+        gate_id: gate_id.map(str::to_string),
+        requested_by: requested_by.to_string(),
+        reason: reason.trim().to_string(),
+        ts: Utc::now(),
+        details,
+    }
+}
+
+async fn finish(state: &AppState, status: StatusCode, action: AdminAction) -> Response {
+    let record_error = record(state, &action).await;
+    (
+        status,
+        Json(json!({
+            "action": action,
+            "recorded": record_error.is_none(),
+            "recordError": record_error,
+        })),
+    )
+        .into_response()
+}
+
+/// POST /api/tenants/:tenant/runs/:run_id/abandon
+pub async fn abandon_run_api(
+    State(state): State<AppState>,
+    Path((tenant, run_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<AdminActionBody>,
+) -> Response {
+    if let Err(response) = check_request(&headers, &body.requested_by, &body.reason) {
+        return *response;
+    }
+    let run = match load_run(&state, &tenant, &run_id).await {
+        Ok(run) => run,
+        Err(response) => return response,
+    };
+    if let Some(last) = terminal_event(&run) {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "run already finished", "event": last.event })),
+        )
+            .into_response();
+    }
+
+    let last_event = run.events.last().map(|e| e.event.clone());
+    let payload = json!({
+        "event": "run.abandoned:v1",
+        "ts": Utc::now().to_rfc3339(),
+        "tenantId": tenant,
+        "ritualId": run.ritual_id,
+        "runId": run_id,
+        "abandonedBy": body.requested_by,
+        "reason": body.reason.trim(),
+        "lastEvent": last_event,
+    });
+    let msg_id = format!("{}:abandoned", run_id);
+    if let Err(e) = publish_run_event(
+        &tenant,
+        &run.ritual_id,
+        &run_id,
+        payload.clone(),
+        msg_id,
+        None,
+    )
+    .await
+    {
+        error!("failed to publish run.abandoned: {}", e);
+        return error_response(StatusCode::BAD_GATEWAY, format!("publish failed: {}", e));
+    }
+    info!(tenant = %tenant, run_id = %run_id, requested_by = %body.requested_by, "run abandoned");
+
+    let action = new_action(
+        &tenant,
+        AdminActionKind::AbandonRun,
+        Some(&run_id),
+        None,
+        &body.requested_by,
+        &body.reason,
+        json!({ "event": payload }),
+    );
+    finish(&state, StatusCode::ACCEPTED, action).await
+}
+
+/// POST /api/tenants/:tenant/runs/:run_id/gates/:gate_id/reemit
+pub async fn reemit_gate_api(
+    State(state): State<AppState>,
+    Path((tenant, run_id, gate_id)): Path<(String, String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<AdminActionBody>,
+) -> Response {
+    if let Err(response) = check_request(&headers, &body.requested_by, &body.reason) {
+        return *response;
+    }
+    let run = match load_run(&state, &tenant, &run_id).await {
+        Ok(run) => run,
+        Err(response) => return response,
+    };
+    if let Some(last) = terminal_event(&run) {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({ "error": "run already finished", "event": last.event })),
+        )
+            .into_response();
+    }
+    let Some(decision) = gate_decision(&run, &gate_id) else {
+        let requested = run.events.iter().any(|e| {
+            e.event == "approval.requested:v1"
+                && e.extra.get("gateId").and_then(|v| v.as_str()) == Some(gate_id.as_str())
+        });
+        return if requested {
+            error_response(
+                StatusCode::CONFLICT,
+                "gate has no decision to re-emit; grant, deny or override it instead",
+            )
+        } else {
+            error_response(StatusCode::NOT_FOUND, "gate not found")
+        };
+    };
+
+    let payload = original_payload(decision);
+    let msg_id = format!(
+        "{}:approval:{}:reemit:{}",
+        run_id,
+        gate_id,
+        uuid::Uuid::new_v4().simple()
+    );
+    match publish_run_event(
+        &tenant,
+        &run.ritual_id,
+        &run_id,
+        payload.clone(),
+        msg_id,
+        None,
+    )
+    .await
+    {
+        Ok(PublishOutcome::Published) => {}
+        Ok(PublishOutcome::Conflict) => {
+            return error_response(StatusCode::CONFLICT, "run changed; reload and retry")
+        }
+        Err(e) => {
+            error!("failed to re-emit gate decision: {}", e);
+            return error_response(StatusCode::BAD_GATEWAY, format!("publish failed: {}", e));
+        }
+    }
+    info!(tenant = %tenant, run_id = %run_id, gate_id = %gate_id, event = %decision.event, "gate decision re-emitted");
+
+    let action = new_action(
+        &tenant,
+        AdminActionKind::ReemitGate,
+        Some(&run_id),
+        Some(&gate_id),
+        &body.requested_by,
+        &body.reason,
+        json!({ "event": payload }),
+    );
+    finish(&state, StatusCode::ACCEPTED, action).await
+}
+
+/// POST /api/tenants/:tenant/preview-runs/purge
+pub async fn purge_preview_runs_api(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<PurgePreviewBody>,
+) -> Response {
+    if let Err(response) = check_request(&headers, &body.requested_by, &body.reason) {
+        return *response;
+    }
+    let Some(client) = &state.jetstream_client else {
+        return error_response(StatusCode::BAD_GATEWAY, "JetStream is not available");
+    };
+    let prefixes = preview_run_prefixes();
+    if prefixes.is_empty() {
+        return error_response(
+            StatusCode::CONFLICT,
+            "OPERATE_UI_PREVIEW_RUN_PREFIXES is empty; no runs count as previews",
+        );
+    }
+    let runs = match client
+        .list_runs_for_tenant(&tenant, Some(MAX_PURGE_RUNS))
+        .await
+    {
+        Ok(runs) => runs,
+        Err(e) => {
+            error!("list_runs_for_tenant failed: {}", e);
+            return error_response(StatusCode::BAD_GATEWAY, "JetStream error");
+        }
+    };
+    let previews: Vec<_> = runs
+        .into_iter()
+        .filter(|run| is_preview_run(&run.run_id, &prefixes))
+        .collect();
+
+    if body.dry_run {
+        return Json(json!({
+            "dryRun": true,
+            "prefixes": prefixes,
+            "runs": previews,
+        }))
+        .into_response();
+    }
+
+    let mut purged_runs = Vec::new();
+    let mut messages = 0;
+    for run in &previews {
+        match client.purge_run(&tenant, &run.ritual_id, &run.run_id).await {
+            Ok(count) => {
+                messages += count;
+                state.run_index.remove(&tenant, &run.run_id);
+                purged_runs.push(run.run_id.clone());
+            }
+            Err(e) => {
+                warn!(tenant = %tenant, run_id = %run.run_id, "failed to purge preview run: {}", e);
+            }
+        }
+    }
+    info!(tenant = %tenant, runs = purged_runs.len(), messages, requested_by = %body.requested_by, "preview runs purged");
+
+    let failed = previews.len() - purged_runs.len();
+    let action = new_action(
+        &tenant,
+        AdminActionKind::PurgePreviewRuns,
+        None,
+        None,
+        &body.requested_by,
+        &body.reason,
+        json!({
+            "prefixes": prefixes,
+            "runs": purged_runs,
+            "messages": messages,
+            "failed": failed,
+        }),
+    );
+    let status = if failed == 0 {
+        StatusCode::OK
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+    finish(&state, status, action).await
+}
+
+/// GET /api/tenants/:tenant/admin/actions - recorded actions, newest first
+pub async fn list_admin_actions_api(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+) -> Response {
+    let result = async {
+        let store = store(&state).await?;
+        let mut keys = store.keys().await?;
+        let prefix = format!("{}.", tenant);
+        let mut actions = Vec::new();
+        while let Some(key) = keys.try_next().await? {
+            if !key.starts_with(&prefix) {
+                continue;
+            }
+            if let Some(bytes) = store.get(&key).await? {
+                match serde_json::from_slice::<AdminAction>(&bytes) {
+                    Ok(action) => actions.push(action),
+                    Err(e) => warn!("Ignoring unreadable admin action {}: {}", key, e),
+                }
+            }
+        }
+        anyhow::Ok(actions)
+    }
+    .await;
+    match result {
+        Ok(mut actions) => {
+            actions.sort_by_key(|a| std::cmp::Reverse(a.ts));
+            Json(actions).into_response()
+        }
+        Err(e) => {
+            error!("Failed to list admin actions: {}", e);
+            error_response(StatusCode::BAD_GATEWAY, "Failed to list admin actions")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn event(name: &str, ts: &str, extra: serde_json::Value) -> RitualEvent {
+        RitualEvent {
+            ts: ts.parse().unwrap(),
+            event: name.to_string(),
+            state_from: None,
+            state_to: None,
+            stream_sequence: Some(7),
+            extra: serde_json::from_value::<HashMap<_, _>>(extra).unwrap(),
+        }
+    }
+
+    fn run(events: Vec<RitualEvent>) -> RunDetail {
+        RunDetail {
+            run_id: "run-1".to_string(),
+            ritual_id: "release".to_string(),
+            events,
+        }
+    }
+
+    #[test]
+    fn preview_runs_match_by_prefix_only() {
+        let prefixes = vec!["bootstrap-run-".to_string(), "demo-".to_string()];
+        assert!(is_preview_run("bootstrap-run-b", &prefixes));
+        assert!(is_preview_run("demo-42", &prefixes));
+        assert!(!is_preview_run("prod-bootstrap-run-b", &prefixes));
+        assert!(!is_preview_run("run-1", &[]));
+    }
+
+    #[test]
+    fn abandoned_and_canceled_runs_are_terminal() {
+        let started = event("ritual.started:v1", "2025-01-01T00:00:00Z", json!({}));
+        assert!(terminal_event(&run(vec![started.clone()])).is_none());
+
+        let abandoned = event(
+            "run.abandoned:v1",
+            "2025-01-01T01:00:00Z",
+            json!({"reason": "engine lost"}),
+        );
+        let detail = run(vec![started, abandoned]);
+        assert_eq!(
+            terminal_event(&detail).map(|e| e.event.as_str()),
+            Some("run.abandoned:v1")
+        );
+    }
+
+    #[test]
+    fn gate_decision_is_the_latest_for_that_gate_without_stream_metadata() {
+        let detail = run(vec![
+            event(
+                "approval.requested:v1",
+                "2025-01-01T00:00:00Z",
+                json!({"gateId": "deploy", "runId": "run-1"}),
+            ),
+            event(
+                "approval.denied:v1",
+                "2025-01-01T00:01:00Z",
+                json!({"gateId": "deploy", "runId": "run-1", "approver": "a@example.com"}),
+            ),
+            event(
+                "approval.override:v1",
+                "2025-01-01T00:02:00Z",
+                json!({"gateId": "deploy", "runId": "run-1", "approver": "b@example.com"}),
+            ),
+            event(
+                "approval.granted:v1",
+                "2025-01-01T00:03:00Z",
+                json!({"gateId": "smoke", "runId": "run-1"}),
+            ),
+        ]);
+
+        let decision = gate_decision(&detail, "deploy").unwrap();
+        assert_eq!(decision.event, "approval.override:v1");
+        assert!(gate_decision(&detail, "canary").is_none());
+
+        let payload = original_payload(decision);
+        assert_eq!(payload["event"], "approval.override:v1");
+        assert_eq!(payload["approver"], "b@example.com");
+        assert!(payload.get("stream_sequence").is_none());
+    }
+}
@@ -0,0 +1,771 @@
+//! In-memory search index over ritual runs
+//!
+//! The index is built by reading the ritual events stream from the start and is
+//! then kept current by following new events, so `/api/runs` can filter on
+//! gate state and free text without scanning JetStream per request. Until the
+//! backlog has been read, [`RunIndex::is_ready`] is false and callers fall back
+//! to the scan-based listing.
+//!
+//! The number of indexed runs is capped by `RUN_INDEX_MAX_RUNS` (default
+//! 100000); the runs with the oldest activity are evicted first.
+//!
+//! Each run remembers the stream sequence of its latest event, so a client
+//! can page through results with a [`RunCursor`] and fetch only the runs that
+//! changed after the `id` of the last `run` SSE event it saw.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::jetstream::{
+    parse_ritual_subject, run_update_from_event, JetStreamClient, RunStatus, RunSummary,
+};
+
+const DEFAULT_MAX_RUNS: usize = 100_000;
+
+/// Payload text kept per run for free-text matching
+const MAX_TEXT_BYTES: usize = 32 * 1024;
+
+/// Latest state of an approval gate within a run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GateState {
+    Pending,
+    Granted,
+    Denied,
+    Expired,
+    Overridden,
+}
+
+impl std::str::FromStr for GateState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pending" => Ok(GateState::Pending),
+            "granted" => Ok(GateState::Granted),
+            "denied" => Ok(GateState::Denied),
+            "expired" => Ok(GateState::Expired),
+            "overridden" | "override" => Ok(GateState::Overridden),
+            _ => Err(format!(
+                "invalid 'gate': expected one of pending, granted, denied, expired, overridden; got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IndexedRun {
+    run_id: String,
+    ritual_id: String,
+    status: RunStatus,
+    start_ts: Option<DateTime<Utc>>,
+    first_ts: DateTime<Utc>,
+    last_ts: DateTime<Utc>,
+    gates: BTreeMap<String, GateState>,
+    /// Lowercased string values from every event payload
+    text: String,
+    /// Stream sequence of the latest event applied to this run
+    last_sequence: u64,
+}
+
+impl IndexedRun {
+    fn started_at(&self) -> DateTime<Utc> {
+        self.start_ts.unwrap_or(self.first_ts)
+    }
+
+    /// Position in search results: newest start first, then by run id
+    fn cursor(&self) -> RunCursor {
+        RunCursor {
+            start_micros: self.started_at().timestamp_micros(),
+            run_id: self.run_id.clone(),
+        }
+    }
+
+    fn summary(&self) -> RunSummary {
+        RunSummary {
+            run_id: self.run_id.clone(),
+            ritual_id: self.ritual_id.clone(),
+            start_ts: self.started_at(),
+            status: self.status,
+        }
+    }
+}
+
+/// Filters for [`RunIndex::search`]; every set field must match
+#[derive(Debug, Clone, Default)]
+pub struct RunSearch {
+    pub tenant: String,
+    /// Case-insensitive substring of the ritual id
+    pub ritual: Option<String>,
+    /// Case-insensitive substring of the run id
+    pub run_id: Option<String>,
+    pub status: Option<RunStatus>,
+    /// Runs started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Runs started before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Runs with at least one gate in this state
+    pub gate: Option<GateState>,
+    /// Whitespace-separated terms that must all appear in the run's event payloads
+    pub text: Option<String>,
+    /// Continue after the last run of a previous page
+    pub after: Option<RunCursor>,
+    /// Only runs with an event after this stream sequence (`Last-Event-ID`)
+    pub updated_after: Option<u64>,
+    pub limit: usize,
+}
+
+impl RunSearch {
+    /// Whether the search uses filters only the index can answer
+    pub fn needs_index(&self) -> bool {
+        self.gate.is_some()
+            || self.text.is_some()
+            || self.after.is_some()
+            || self.updated_after.is_some()
+    }
+
+    /// Apply the filters that a [`RunSummary`] carries
+    pub fn matches_summary(&self, run: &RunSummary) -> bool {
+        if let Some(ref r) = self.ritual {
+            if !run
+                .ritual_id
+                .to_ascii_lowercase()
+                .contains(&r.to_ascii_lowercase())
+            {
+                return false;
+            }
+        }
+        if let Some(ref r) = self.run_id {
+            if !run
+                .run_id
+                .to_ascii_lowercase()
+                .contains(&r.to_ascii_lowercase())
+            {
+                return false;
+            }
+        }
+        if self.status.is_some_and(|s| s != run.status) {
+            return false;
+        }
+        if self.since.is_some_and(|since| run.start_ts < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| run.start_ts >= until) {
+            return false;
+        }
+        true
+    }
+
+    fn matches(&self, run: &IndexedRun) -> bool {
+        if !self.matches_summary(&run.summary()) {
+            return false;
+        }
+        if let Some(gate) = self.gate {
+            if !run.gates.values().any(|g| *g == gate) {
+                return false;
+            }
+        }
+        if let Some(ref text) = self.text {
+            let text = text.to_lowercase();
+            if !text.split_whitespace().all(|term| run.text.contains(term)) {
+                return false;
+            }
+        }
+        if self
+            .updated_after
+            .is_some_and(|sequence| run.last_sequence <= sequence)
+        {
+            return false;
+        }
+        if self
+            .after
+            .as_ref()
+            .is_some_and(|after| run.cursor() <= *after)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Opaque position in search results, handed out as `nextCursor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunCursor {
+    start_micros: i64,
+    run_id: String,
+}
+
+impl Ord for RunCursor {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .start_micros
+            .cmp(&self.start_micros)
+            .then_with(|| self.run_id.cmp(&other.run_id))
+    }
+}
+
+impl PartialOrd for RunCursor {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for RunCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.start_micros, self.run_id)
+    }
+}
+
+impl std::str::FromStr for RunCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once('.')
+            .and_then(|(micros, run_id)| {
+                Some(RunCursor {
+                    start_micros: micros.parse().ok()?,
+                    run_id: run_id.to_string(),
+                })
+            })
+            .filter(|cursor| !cursor.run_id.is_empty())
+            .ok_or_else(|| {
+                "invalid 'cursor': pass the nextCursor of a previous response".to_string()
+            })
+    }
+}
+
+/// One page of [`RunIndex::search_page`]
+#[derive(Debug, Clone, Default)]
+pub struct RunPage {
+    pub runs: Vec<RunSummary>,
+    /// Where the next page starts; `None` on the last page
+    pub next_cursor: Option<RunCursor>,
+    /// Stream sequence of the newest event in the index
+    pub last_sequence: u64,
+    /// Changes whenever runs leave the index other than by eviction, so
+    /// together with `last_sequence` it identifies the index contents
+    pub generation: u64,
+}
+
+/// How many indexed runs a tenant has, by status
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantRunStats {
+    pub tenant: String,
+    pub total: usize,
+    pub running: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub canceled: usize,
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    runs: HashMap<(String, String), IndexedRun>,
+    ready: bool,
+    max_runs: usize,
+    last_sequence: u64,
+    generation: u64,
+}
+
+/// Shared handle to the run index; clones see the same data
+#[derive(Debug, Clone)]
+pub struct RunIndex {
+    inner: Arc<RwLock<Inner>>,
+}
+
+impl Default for RunIndex {
+    fn default() -> Self {
+        let max_runs = std::env::var("RUN_INDEX_MAX_RUNS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_MAX_RUNS);
+        Self::with_capacity(max_runs)
+    }
+}
+
+impl RunIndex {
+    pub fn with_capacity(max_runs: usize) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Inner {
+                runs: HashMap::new(),
+                ready: false,
+                max_runs,
+                last_sequence: 0,
+                generation: 0,
+            })),
+        }
+    }
+
+    /// True once the stream backlog has been read
+    pub fn is_ready(&self) -> bool {
+        self.inner.read().map(|i| i.ready).unwrap_or(false)
+    }
+
+    pub fn mark_ready(&self) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.ready = true;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.read().map(|i| i.runs.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop everything and mark the index as not ready, e.g. before re-reading
+    /// the stream from the start
+    pub fn reset(&self) {
+        if let Ok(mut inner) = self.inner.write() {
+            inner.runs.clear();
+            inner.ready = false;
+            inner.last_sequence = 0;
+            inner.generation += 1;
+        }
+    }
+
+    /// Forget one run, e.g. after its events were purged from the stream
+    pub fn remove(&self, tenant: &str, run_id: &str) -> bool {
+        self.inner
+            .write()
+            .map(|mut inner| {
+                let removed = inner
+                    .runs
+                    .remove(&(tenant.to_string(), run_id.to_string()))
+                    .is_some();
+                if removed {
+                    inner.generation += 1;
+                }
+                removed
+            })
+            .unwrap_or(false)
+    }
+
+    /// Stream sequence of the newest event applied
+    pub fn last_sequence(&self) -> u64 {
+        self.inner.read().map(|i| i.last_sequence).unwrap_or(0)
+    }
+
+    /// Fold one ritual event into the index as the next stream sequence
+    pub fn apply(&self, subject: &str, payload: &serde_json::Value) {
+        self.apply_at(self.last_sequence() + 1, subject, payload);
+    }
+
+    /// Fold the ritual event at stream `sequence` into the index; events on
+    /// other subjects are ignored
+    pub fn apply_at(&self, sequence: u64, subject: &str, payload: &serde_json::Value) {
+        let Some((tenant, _, _)) = parse_ritual_subject(subject) else {
+            return;
+        };
+        let Some(update) = run_update_from_event(tenant, subject, payload) else {
+            return;
+        };
+        let Ok(mut inner) = self.inner.write() else {
+            return;
+        };
+        inner.last_sequence = inner.last_sequence.max(sequence);
+
+        let key = (tenant.to_string(), update.run_id.clone());
+        let run = inner.runs.entry(key).or_insert_with(|| IndexedRun {
+            run_id: update.run_id.clone(),
+            ritual_id: update.ritual_id.clone(),
+            status: RunStatus::Running,
+            start_ts: None,
+            first_ts: update.ts,
+            last_ts: update.ts,
+            gates: BTreeMap::new(),
+            text: format!("{}\n{}\n", update.run_id, update.ritual_id).to_lowercase(),
+            last_sequence: sequence,
+        });
+        run.last_sequence = run.last_sequence.max(sequence);
+
+        // A finished run stays finished even if late events arrive
+        if run.status == RunStatus::Running || update.status != RunStatus::Running {
+            run.status = update.status;
+        }
+        if update.start_ts.is_some() {
+            run.start_ts = update.start_ts;
+        }
+        run.first_ts = run.first_ts.min(update.ts);
+        run.last_ts = run.last_ts.max(update.ts);
+
+        if let Some(gate_id) = payload.get("gateId").and_then(|v| v.as_str()) {
+            let state = match update.event.as_str() {
+                "approval.requested:v1" => Some(GateState::Pending),
+                "approval.granted:v1" => Some(GateState::Granted),
+                "approval.denied:v1"
+                    if payload.get("reason").and_then(|v| v.as_str()) == Some("expired") =>
+                {
+                    Some(GateState::Expired)
+                }
+                "approval.denied:v1" => Some(GateState::Denied),
+                "approval.override:v1" => Some(GateState::Overridden),
+                _ => None,
+            };
+            if let Some(state) = state {
+                run.gates.insert(gate_id.to_string(), state);
+            }
+        }
+
+        append_text(&mut run.text, payload);
+
+        let max_runs = inner.max_runs;
+        if inner.runs.len() > max_runs + max_runs / 10 {
+            evict_oldest(&mut inner.runs, max_runs);
+        }
+    }
+
+    /// Runs matching `search`, newest start first, at most `search.limit`
+    pub fn search(&self, search: &RunSearch) -> Vec<RunSummary> {
+        self.search_page(search).runs
+    }
+
+    /// Like [`RunIndex::search`], with the cursor of the next page
+    pub fn search_page(&self, search: &RunSearch) -> RunPage {
+        let Ok(inner) = self.inner.read() else {
+            return RunPage::default();
+        };
+        let mut matched: Vec<(RunCursor, &IndexedRun)> = inner
+            .runs
+            .iter()
+            .filter(|((tenant, _), run)| *tenant == search.tenant && search.matches(run))
+            .map(|(_, run)| (run.cursor(), run))
+            .collect();
+        matched.sort_by(|a, b| a.0.cmp(&b.0));
+        let next_cursor = (search.limit > 0 && matched.len() > search.limit)
+            .then(|| matched[search.limit - 1].0.clone());
+        RunPage {
+            runs: matched
+                .into_iter()
+                .take(search.limit)
+                .map(|(_, run)| run.summary())
+                .collect(),
+            next_cursor,
+            last_sequence: inner.last_sequence,
+            generation: inner.generation,
+        }
+    }
+
+    /// Run counts per tenant, ordered by tenant name
+    pub fn tenant_stats(&self) -> Vec<TenantRunStats> {
+        let Ok(inner) = self.inner.read() else {
+            return Vec::new();
+        };
+        let mut stats: BTreeMap<&str, TenantRunStats> = BTreeMap::new();
+        for ((tenant, _), run) in &inner.runs {
+            let entry = stats
+                .entry(tenant.as_str())
+                .or_insert_with(|| TenantRunStats {
+                    tenant: tenant.clone(),
+                    ..Default::default()
+                });
+            entry.total += 1;
+            match run.status {
+                RunStatus::Running => entry.running += 1,
+                RunStatus::Completed => entry.completed += 1,
+                RunStatus::Failed => entry.failed += 1,
+                RunStatus::Canceled => entry.canceled += 1,
+            }
+            entry.last_activity = entry.last_activity.max(Some(run.last_ts));
+        }
+        stats.into_values().collect()
+    }
+
+    /// Build the index from the ritual events stream and keep it current; the
+    /// stream is re-read from the start after any error
+    pub fn spawn_follower(&self, client: JetStreamClient) -> tokio::task::JoinHandle<()> {
+        let index = self.clone();
+        tokio::spawn(async move {
+            loop {
+                index.reset();
+                match client.follow_ritual_events().await {
+                    Ok((backlog, events)) => {
+                        info!("Building run index from {} ritual events", backlog);
+                        crate::metrics::record_consumer_pending("run_index", backlog);
+                        if backlog == 0 {
+                            index.mark_ready();
+                        }
+                        futures_util::pin_mut!(events);
+                        while let Some(event) = events.next().await {
+                            match event {
+                                Ok(event) => {
+                                    index.apply_at(event.sequence, &event.subject, &event.payload);
+                                    crate::metrics::record_consumer_pending(
+                                        "run_index",
+                                        event.pending,
+                                    );
+                                    if event.pending == 0 && !index.is_ready() {
+                                        info!("Run index ready with {} runs", index.len());
+                                        index.mark_ready();
+                                    }
+                                }
+                                Err(e) => {
+                                    warn!("Run index stopped following ritual events: {}", e);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => warn!("Run index could not follow ritual events: {}", e),
+                }
+                debug!("Restarting run index follower");
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            }
+        })
+    }
+}
+
+fn append_text(text: &mut String, value: &serde_json::Value) {
+    if text.len() >= MAX_TEXT_BYTES {
+        return;
+    }
+    match value {
+        serde_json::Value::String(s) => {
+            text.push_str(&s.to_lowercase());
+            text.push('\n');
+        }
+        serde_json::Value::Number(n) => {
+            text.push_str(&n.to_string());
+            text.push('\n');
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|v| append_text(text, v)),
+        serde_json::Value::Object(obj) => obj.values().for_each(|v| append_text(text, v)),
+        _ => {}
+    }
+}
+
+fn evict_oldest(runs: &mut HashMap<(String, String), IndexedRun>, keep: usize) {
+    let mut by_activity: Vec<((String, String), DateTime<Utc>)> = runs
+        .iter()
+        .map(|(key, run)| (key.clone(), run.last_ts))
+        .collect();
+    by_activity.sort_by_key(|(_, ts)| *ts);
+    let excess = by_activity.len().saturating_sub(keep);
+    for (key, _) in by_activity.into_iter().take(excess) {
+        runs.remove(&key);
+    }
+}
+
+/// Parse a `since`/`until` bound: RFC 3339, or a `datetime-local` value
+/// (`2025-01-07T09:30`) or date (`2025-01-07`) taken as UTC
+pub fn parse_time_bound(s: &str) -> Option<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(ts) = NaiveDateTime::parse_from_str(s, format) {
+            return Some(ts.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|ts| ts.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SUBJECT: &str = "demon.ritual.v1.acme.release.run-1.events";
+
+    fn search(tenant: &str) -> RunSearch {
+        RunSearch {
+            tenant: tenant.to_string(),
+            limit: 50,
+            ..Default::default()
+        }
+    }
+
+    fn seeded() -> RunIndex {
+        let index = RunIndex::with_capacity(100);
+        index.apply(
+            SUBJECT,
+            &json!({"event": "ritual.started:v1", "ts": "2025-01-07T09:00:00Z"}),
+        );
+        index.apply(
+            SUBJECT,
+            &json!({"event": "approval.requested:v1", "ts": "2025-01-07T09:01:00Z", "gateId": "deploy"}),
+        );
+        index.apply(
+            SUBJECT,
+            &json!({"event": "ritual.failed:v1", "ts": "2025-01-07T09:02:00Z", "error": "Image pull BackOff for api-server"}),
+        );
+        index.apply(
+            "demon.ritual.v1.acme.nightly.run-2.events",
+            &json!({"event": "ritual.started:v1", "ts": "2025-01-08T00:00:00Z"}),
+        );
+        index.apply(
+            "demon.ritual.v1.other.release.run-3.events",
+            &json!({"event": "ritual.started:v1", "ts": "2025-01-07T09:00:00Z"}),
+        );
+        index
+    }
+
+    #[test]
+    fn search_filters_by_status_time_range_and_tenant() {
+        let index = seeded();
+        let mut failed = search("acme");
+        failed.status = Some(RunStatus::Failed);
+        failed.since = parse_time_bound("2025-01-07");
+        failed.until = parse_time_bound("2025-01-08");
+        let runs = index.search(&failed);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].run_id, "run-1");
+        assert_eq!(
+            runs[0].start_ts,
+            parse_time_bound("2025-01-07T09:00").unwrap()
+        );
+
+        let all = index.search(&search("acme"));
+        assert_eq!(
+            all.iter().map(|r| r.run_id.as_str()).collect::<Vec<_>>(),
+            vec!["run-2", "run-1"]
+        );
+    }
+
+    #[test]
+    fn search_matches_gate_state_and_every_text_term() {
+        let index = seeded();
+        let mut pending = search("acme");
+        pending.gate = Some(GateState::Pending);
+        assert_eq!(index.search(&pending).len(), 1);
+
+        index.apply(
+            SUBJECT,
+            &json!({"event": "approval.denied:v1", "ts": "2025-01-07T09:03:00Z", "gateId": "deploy", "reason": "expired"}),
+        );
+        assert!(index.search(&pending).is_empty());
+        pending.gate = Some(GateState::Expired);
+        assert_eq!(index.search(&pending).len(), 1);
+
+        let mut text = search("acme");
+        text.text = Some("backoff API-server".to_string());
+        assert_eq!(index.search(&text).len(), 1);
+        text.text = Some("backoff database".to_string());
+        assert!(index.search(&text).is_empty());
+    }
+
+    #[test]
+    fn late_running_events_do_not_reopen_finished_runs() {
+        let index = seeded();
+        index.apply(
+            SUBJECT,
+            &json!({"event": "approval.escalated:v1", "ts": "2025-01-07T09:05:00Z", "gateId": "deploy"}),
+        );
+        let mut failed = search("acme");
+        failed.status = Some(RunStatus::Failed);
+        assert_eq!(index.search(&failed).len(), 1);
+    }
+
+    #[test]
+    fn eviction_keeps_the_most_recently_active_runs() {
+        let index = RunIndex::with_capacity(10);
+        for i in 0..12 {
+            index.apply(
+                &format!("demon.ritual.v1.acme.release.run-{}.events", i),
+                &json!({"event": "ritual.started:v1", "ts": format!("2025-01-07T09:{:02}:00Z", i)}),
+            );
+        }
+        assert_eq!(index.len(), 10);
+        let runs = index.search(&search("acme"));
+        assert!(runs
+            .iter()
+            .all(|r| r.run_id != "run-0" && r.run_id != "run-1"));
+    }
+
+    #[test]
+    fn tenant_stats_count_runs_by_status() {
+        let stats = seeded().tenant_stats();
+        assert_eq!(
+            stats.iter().map(|s| s.tenant.as_str()).collect::<Vec<_>>(),
+            vec!["acme", "other"]
+        );
+        assert_eq!(
+            (stats[0].total, stats[0].running, stats[0].failed),
+            (2, 1, 1)
+        );
+        assert_eq!(stats[0].last_activity, parse_time_bound("2025-01-08T00:00"));
+    }
+
+    #[test]
+    fn removed_runs_leave_the_index_and_other_tenants_alone() {
+        let index = seeded();
+        assert!(index.remove("acme", "run-1"));
+        assert!(!index.remove("acme", "run-1"));
+        assert_eq!(index.search(&search("acme")).len(), 1);
+        assert_eq!(index.search(&search("other")).len(), 1);
+    }
+
+    #[test]
+    fn pages_follow_the_cursor_and_last_event_id_skips_unchanged_runs() {
+        let index = RunIndex::with_capacity(100);
+        for (sequence, run) in [(10, "run-a"), (11, "run-b"), (12, "run-c")] {
+            index.apply_at(
+                sequence,
+                &format!("demon.ritual.v1.acme.release.{}.events", run),
+                &json!({"event": "ritual.started:v1", "ts": "2025-01-07T09:00:00Z"}),
+            );
+        }
+        let mut page = search("acme");
+        page.limit = 2;
+        let first = index.search_page(&page);
+        assert_eq!(first.last_sequence, 12);
+        assert_eq!(
+            first
+                .runs
+                .iter()
+                .map(|r| r.run_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["run-a", "run-b"]
+        );
+        let cursor = first.next_cursor.unwrap().to_string();
+        page.after = Some(cursor.parse().unwrap());
+        let second = index.search_page(&page);
+        assert_eq!(second.runs[0].run_id, "run-c");
+        assert!(second.next_cursor.is_none());
+        assert!("garbage".parse::<RunCursor>().is_err());
+
+        index.apply_at(
+            13,
+            "demon.ritual.v1.acme.release.run-a.events",
+            &json!({"event": "ritual.completed:v1", "ts": "2025-01-07T09:05:00Z"}),
+        );
+        let mut changed = search("acme");
+        changed.updated_after = Some(12);
+        let runs = index.search(&changed);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].status, RunStatus::Completed);
+
+        let generation = index.search_page(&changed).generation;
+        index.remove("acme", "run-b");
+        assert_ne!(index.search_page(&changed).generation, generation);
+    }
+
+    #[test]
+    fn parse_time_bound_accepts_rfc3339_local_and_dates() {
+        assert_eq!(
+            parse_time_bound("2025-01-07T09:30:00+01:00"),
+            parse_time_bound("2025-01-07T08:30")
+        );
+        assert!(parse_time_bound("2025-01-07").is_some());
+        assert!(parse_time_bound("last tuesday").is_none());
+    }
+
+    #[test]
+    fn gate_state_parses_case_insensitively() {
+        assert_eq!("Granted".parse::<GateState>(), Ok(GateState::Granted));
+        assert!("maybe".parse::<GateState>().is_err());
+    }
+}
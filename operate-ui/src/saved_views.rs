@@ -0,0 +1,372 @@
+//! Saved run-list views and their share links
+//!
+//! A view is a name plus the run-list filters (the same query parameters
+//! `/runs` accepts) for one tenant. Views live in the `OPERATE_UI_VIEWS` KV
+//! bucket keyed by id so that `/views/:id` resolves a pasted link for anyone
+//! who can read the tenant's runs; listing and deleting are limited to the
+//! view's owner (the token subject, or `anonymous` when auth is disabled).
+//! Without JetStream the runs page falls back to keeping views in local storage.
+
+use crate::AppState;
+use async_nats::jetstream::kv;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Redirect, Response},
+};
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use tracing::{error, warn};
+
+const BUCKET: &str = "OPERATE_UI_VIEWS";
+const MAX_NAME_LEN: usize = 100;
+const ANONYMOUS: &str = "anonymous";
+
+/// Query parameters a view may carry, in the order they appear in links
+pub const FILTER_KEYS: [&str; 8] = [
+    "ritual", "runId", "status", "since", "until", "gate", "q", "limit",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedView {
+    pub id: String,
+    pub name: String,
+    pub tenant: String,
+    pub owner: String,
+    pub filters: BTreeMap<String, String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SavedView {
+    /// The run list this view reproduces, e.g. `/tenants/acme/runs?status=Failed`
+    pub fn runs_url(&self) -> String {
+        let path = if self.tenant == "default" {
+            "/runs".to_string()
+        } else {
+            format!("/tenants/{}/runs", urlencoding::encode(&self.tenant))
+        };
+        let query: Vec<String> = FILTER_KEYS
+            .iter()
+            .filter_map(|key| {
+                self.filters
+                    .get(*key)
+                    .map(|value| format!("{}={}", key, urlencoding::encode(value)))
+            })
+            .collect();
+        if query.is_empty() {
+            path
+        } else {
+            format!("{}?{}", path, query.join("&"))
+        }
+    }
+
+    /// The share link for this view
+    pub fn share_url(&self) -> String {
+        format!("/views/{}", self.id)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateViewBody {
+    pub name: String,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub filters: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListViewsQuery {
+    pub tenant: Option<String>,
+}
+
+/// Check a create request and build the view it describes; empty filter
+/// values are dropped so links stay short
+pub fn new_view(body: CreateViewBody, owner: &str) -> Result<SavedView, String> {
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("name must be 1 to {} characters", MAX_NAME_LEN));
+    }
+    let tenant = body.tenant.unwrap_or_else(|| "default".to_string());
+    if tenant.is_empty()
+        || !tenant
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("invalid tenant '{}'", tenant));
+    }
+    let mut filters = BTreeMap::new();
+    for (key, value) in body.filters {
+        if !FILTER_KEYS.contains(&key.as_str()) {
+            return Err(format!("unknown filter '{}'", key));
+        }
+        let value = value.trim();
+        if !value.is_empty() {
+            filters.insert(key, value.to_string());
+        }
+    }
+    Ok(SavedView {
+        id: uuid::Uuid::new_v4().simple().to_string(),
+        name: name.to_string(),
+        tenant,
+        owner: owner.to_string(),
+        filters,
+        created_at: Utc::now(),
+    })
+}
+
+fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+fn require_csrf_header(headers: &HeaderMap) -> Result<(), Box<Response>> {
+    if headers.get("X-Requested-With").is_none() {
+        return Err(Box::new(error_response(
+            StatusCode::BAD_REQUEST,
+            "X-Requested-With header required",
+        )));
+    }
+    Ok(())
+}
+
+/// The caller's identity for ownership checks
+fn owner(state: &AppState, headers: &HeaderMap) -> Result<String, Box<Response>> {
+    match state.access_control.authenticate(headers) {
+        Ok(Some(claims)) => Ok(claims.sub),
+        Ok(None) => Ok(ANONYMOUS.to_string()),
+        Err(e) => Err(Box::new(e.into_response())),
+    }
+}
+
+async fn store(state: &AppState) -> Result<kv::Store, Response> {
+    let Some(client) = &state.jetstream_client else {
+        return Err(error_response(
+            StatusCode::BAD_GATEWAY,
+            "JetStream is not available",
+        ));
+    };
+    client
+        .key_value(BUCKET, "Operate UI saved run-list views")
+        .await
+        .map_err(|e| {
+            error!("Failed to open saved views bucket: {}", e);
+            error_response(
+                StatusCode::BAD_GATEWAY,
+                format!("Saved views are unavailable: {}", e),
+            )
+        })
+}
+
+async fn load(store: &kv::Store, id: &str) -> Result<Option<SavedView>, Response> {
+    let bytes = store.get(id).await.map_err(|e| {
+        error!("Failed to read saved view {}: {}", id, e);
+        error_response(StatusCode::BAD_GATEWAY, "Failed to read saved view")
+    })?;
+    Ok(
+        bytes.and_then(|bytes| match serde_json::from_slice(&bytes) {
+            Ok(view) => Some(view),
+            Err(e) => {
+                warn!("Ignoring unreadable saved view {}: {}", id, e);
+                None
+            }
+        }),
+    )
+}
+
+async fn list(store: &kv::Store) -> anyhow::Result<Vec<SavedView>> {
+    let mut keys = store.keys().await?;
+    let mut views = Vec::new();
+    while let Some(key) = keys.try_next().await? {
+        if let Some(bytes) = store.get(&key).await? {
+            match serde_json::from_slice::<SavedView>(&bytes) {
+                Ok(view) => views.push(view),
+                Err(e) => warn!("Ignoring unreadable saved view {}: {}", key, e),
+            }
+        }
+    }
+    Ok(views)
+}
+
+/// GET /api/views - the caller's views, optionally for one tenant
+pub async fn list_views_api(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListViewsQuery>,
+) -> Response {
+    let owner = match owner(&state, &headers) {
+        Ok(owner) => owner,
+        Err(response) => return *response,
+    };
+    let store = match store(&state).await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match list(&store).await {
+        Ok(views) => {
+            let mut views: Vec<SavedView> = views
+                .into_iter()
+                .filter(|v| v.owner == owner)
+                .filter(|v| query.tenant.as_ref().is_none_or(|t| &v.tenant == t))
+                .collect();
+            views.sort_by(|a, b| a.name.cmp(&b.name));
+            Json(views).into_response()
+        }
+        Err(e) => {
+            error!("Failed to list saved views: {}", e);
+            error_response(StatusCode::BAD_GATEWAY, "Failed to list saved views")
+        }
+    }
+}
+
+/// POST /api/views - save a view; the caller needs viewer access to its tenant
+pub async fn create_view_api(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CreateViewBody>,
+) -> Response {
+    if let Err(response) = require_csrf_header(&headers) {
+        return *response;
+    }
+    let owner = match owner(&state, &headers) {
+        Ok(owner) => owner,
+        Err(response) => return *response,
+    };
+    let view = match new_view(body, &owner) {
+        Ok(view) => view,
+        Err(message) => return error_response(StatusCode::BAD_REQUEST, message),
+    };
+    if let Err(e) = state
+        .access_control
+        .authorize(&headers, jwt_auth::Role::Viewer, &view.tenant)
+    {
+        return e.into_response();
+    }
+    let store = match store(&state).await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    let bytes = match serde_json::to_vec(&view) {
+        Ok(bytes) => bytes,
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    if let Err(e) = store.put(&view.id, bytes.into()).await {
+        error!("Failed to save view {}: {}", view.id, e);
+        return error_response(StatusCode::BAD_GATEWAY, "Failed to save view");
+    }
+    (
+        StatusCode::CREATED,
+        Json(json!({
+            "view": view,
+            "shareUrl": view.share_url(),
+            "runsUrl": view.runs_url(),
+        })),
+    )
+        .into_response()
+}
+
+/// DELETE /api/views/:id - only the owner may delete a view
+pub async fn delete_view_api(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(response) = require_csrf_header(&headers) {
+        return *response;
+    }
+    let owner = match owner(&state, &headers) {
+        Ok(owner) => owner,
+        Err(response) => return *response,
+    };
+    let store = match store(&state).await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match load(&store, &id).await {
+        Ok(Some(view)) if view.owner == owner => match store.delete(&id).await {
+            Ok(()) => StatusCode::NO_CONTENT.into_response(),
+            Err(e) => {
+                error!("Failed to delete view {}: {}", id, e);
+                error_response(StatusCode::BAD_GATEWAY, "Failed to delete view")
+            }
+        },
+        Ok(Some(_)) => error_response(StatusCode::FORBIDDEN, "Only the owner can delete a view"),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Saved view not found"),
+        Err(response) => response,
+    }
+}
+
+/// GET /views/:id - share link; redirects to the filtered run list, which
+/// applies its own tenant check
+pub async fn open_view(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    if let Err(e) = state.access_control.authenticate(&headers) {
+        return e.into_response();
+    }
+    let store = match store(&state).await {
+        Ok(store) => store,
+        Err(response) => return response,
+    };
+    match load(&store, &id).await {
+        Ok(Some(view)) => Redirect::to(&view.runs_url()).into_response(),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "Saved view not found"),
+        Err(response) => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(tenant: Option<&str>, filters: &[(&str, &str)]) -> CreateViewBody {
+        CreateViewBody {
+            name: " Failed prod deploys ".to_string(),
+            tenant: tenant.map(str::to_string),
+            filters: filters
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn views_reproduce_the_run_list_url() {
+        let view = new_view(
+            body(
+                Some("acme"),
+                &[("status", "Failed"), ("q", "disk full"), ("ritual", "")],
+            ),
+            "ops@example.com",
+        )
+        .unwrap();
+        assert_eq!(view.name, "Failed prod deploys");
+        assert!(!view.filters.contains_key("ritual"));
+        assert_eq!(
+            view.runs_url(),
+            "/tenants/acme/runs?status=Failed&q=disk%20full"
+        );
+        assert_eq!(view.share_url(), format!("/views/{}", view.id));
+
+        let default = new_view(body(None, &[]), ANONYMOUS).unwrap();
+        assert_eq!(default.runs_url(), "/runs");
+    }
+
+    #[test]
+    fn unknown_filters_and_bad_tenants_are_rejected() {
+        assert!(new_view(body(None, &[("sort", "asc")]), ANONYMOUS)
+            .unwrap_err()
+            .contains("unknown filter 'sort'"));
+        assert!(new_view(body(Some("../admin"), &[]), ANONYMOUS)
+            .unwrap_err()
+            .contains("invalid tenant"));
+        let mut unnamed = body(None, &[]);
+        unnamed.name = "  ".to_string();
+        assert!(new_view(unnamed, ANONYMOUS).is_err());
+    }
+}
@@ -0,0 +1,369 @@
+//! JetStream stream and consumer health for the `/system` page
+//!
+//! Reports every consumer on the ritual events stream and on the streams named
+//! by `OPERATE_UI_SYSTEM_STREAMS` (comma separated, default `GRAPH_COMMITS`),
+//! so operators can tell whether the UI is behind without the `nats` CLI.
+//!
+//! Each consumer is graded against thresholds read from the environment:
+//!
+//! | Variable | Default | Meaning |
+//! |----------|---------|---------|
+//! | `OPERATE_UI_PENDING_WARN` | `1000` | undelivered messages before a warning |
+//! | `OPERATE_UI_PENDING_CRITICAL` | `10000` | undelivered messages before critical |
+//! | `OPERATE_UI_REDELIVERED_CRITICAL` | `100` | redelivered messages before critical |
+//! | `OPERATE_UI_STALL_SECONDS` | `300` | seconds without a delivery, while messages are pending, before critical |
+
+use crate::jetstream::{ConsumerHealth, StreamHealth, StreamReport};
+use crate::AppState;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::error;
+
+/// Tile colour: green, amber or red
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Warning,
+    Critical,
+}
+
+/// Limits that turn a consumer tile amber or red
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthThresholds {
+    pub pending_warning: u64,
+    pub pending_critical: u64,
+    pub redelivered_critical: usize,
+    pub stall_seconds: i64,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            pending_warning: 1_000,
+            pending_critical: 10_000,
+            redelivered_critical: 100,
+            stall_seconds: 300,
+        }
+    }
+}
+
+impl HealthThresholds {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        }
+        let defaults = Self::default();
+        Self {
+            pending_warning: var("OPERATE_UI_PENDING_WARN", defaults.pending_warning),
+            pending_critical: var("OPERATE_UI_PENDING_CRITICAL", defaults.pending_critical),
+            redelivered_critical: var(
+                "OPERATE_UI_REDELIVERED_CRITICAL",
+                defaults.redelivered_critical,
+            ),
+            stall_seconds: var("OPERATE_UI_STALL_SECONDS", defaults.stall_seconds),
+        }
+    }
+
+    /// Grade one consumer, with the reasons it is not `Ok`
+    pub fn assess(
+        &self,
+        consumer: &ConsumerHealth,
+        now: DateTime<Utc>,
+    ) -> (HealthStatus, Vec<String>) {
+        let mut status = HealthStatus::Ok;
+        let mut reasons = Vec::new();
+        let mut flag = |level: HealthStatus, reason: String| {
+            status = status.max(level);
+            reasons.push(reason);
+        };
+
+        if consumer.pending >= self.pending_critical {
+            flag(
+                HealthStatus::Critical,
+                format!("{} messages pending", consumer.pending),
+            );
+        } else if consumer.pending >= self.pending_warning {
+            flag(
+                HealthStatus::Warning,
+                format!("{} messages pending", consumer.pending),
+            );
+        }
+        if consumer.redelivered >= self.redelivered_critical {
+            flag(
+                HealthStatus::Critical,
+                format!("{} messages redelivered", consumer.redelivered),
+            );
+        }
+        if consumer.pending > 0 {
+            let idle = consumer
+                .last_delivery
+                .map(|ts| (now - ts).num_seconds())
+                .unwrap_or(i64::MAX);
+            if idle >= self.stall_seconds {
+                flag(
+                    HealthStatus::Critical,
+                    match consumer.last_delivery {
+                        Some(_) => format!("no delivery for {}s with messages pending", idle),
+                        None => "nothing delivered yet with messages pending".to_string(),
+                    },
+                );
+            }
+        }
+
+        (status, reasons)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerStatus {
+    #[serde(flatten)]
+    pub consumer: ConsumerHealth,
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamStatus {
+    pub name: String,
+    /// Worst status of any consumer; critical when the stream could not be read
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<StreamHealth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_message: Option<DateTime<Utc>>,
+    pub consumers: Vec<ConsumerStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemStreams {
+    pub status: HealthStatus,
+    pub streams: Vec<StreamStatus>,
+    pub thresholds: HealthThresholds,
+    pub checked_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Streams other than the ritual events stream to report on
+fn extra_stream_names() -> Vec<String> {
+    std::env::var("OPERATE_UI_SYSTEM_STREAMS")
+        .unwrap_or_else(|_| "GRAPH_COMMITS".to_string())
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Grade a stream report (or the error reading it)
+pub fn stream_status(
+    name: &str,
+    report: anyhow::Result<StreamReport>,
+    thresholds: &HealthThresholds,
+    now: DateTime<Utc>,
+) -> StreamStatus {
+    match report {
+        Ok(report) => {
+            let consumers: Vec<ConsumerStatus> = report
+                .consumers
+                .into_iter()
+                .map(|consumer| {
+                    let (status, reasons) = thresholds.assess(&consumer, now);
+                    ConsumerStatus {
+                        consumer,
+                        status,
+                        reasons,
+                    }
+                })
+                .collect();
+            StreamStatus {
+                name: report.stream.name.clone(),
+                status: consumers
+                    .iter()
+                    .map(|c| c.status)
+                    .max()
+                    .unwrap_or(HealthStatus::Ok),
+                stream: Some(report.stream),
+                last_message: report.last_message,
+                consumers,
+                error: None,
+            }
+        }
+        Err(e) => {
+            error!("Failed to read stream health for {}: {}", name, e);
+            StreamStatus {
+                name: name.to_string(),
+                status: HealthStatus::Critical,
+                stream: None,
+                last_message: None,
+                consumers: Vec::new(),
+                error: Some(e.to_string()),
+            }
+        }
+    }
+}
+
+/// Health of the ritual events stream and the configured extra streams
+pub async fn system_streams(state: &AppState) -> SystemStreams {
+    let thresholds = HealthThresholds::from_env();
+    let now = Utc::now();
+    let Some(client) = &state.jetstream_client else {
+        return SystemStreams {
+            status: HealthStatus::Critical,
+            streams: Vec::new(),
+            thresholds,
+            checked_at: now,
+            error: Some("JetStream is not available".to_string()),
+        };
+    };
+
+    let mut streams = vec![stream_status(
+        "RITUAL_EVENTS",
+        client.ritual_stream_report().await,
+        &thresholds,
+        now,
+    )];
+    for name in extra_stream_names() {
+        let report = client.stream_report(&name).await;
+        streams.push(stream_status(&name, report, &thresholds, now));
+    }
+
+    SystemStreams {
+        status: streams
+            .iter()
+            .map(|s| s.status)
+            .max()
+            .unwrap_or(HealthStatus::Ok),
+        streams,
+        thresholds,
+        checked_at: now,
+        error: None,
+    }
+}
+
+/// GET /api/system/streams
+pub async fn system_streams_api(State(state): State<AppState>) -> Json<SystemStreams> {
+    Json(system_streams(&state).await)
+}
+
+/// GET /system
+pub async fn system_html(State(state): State<AppState>) -> Response {
+    let system = system_streams(&state).await;
+    let mut context = tera::Context::new();
+    context.insert("system", &system);
+    context.insert("current_page", &"system");
+    context.insert(
+        "contracts_browser_enabled",
+        &crate::feature_flags::is_enabled("contracts-browser"),
+    );
+    context.insert(
+        "canvas_enabled",
+        &crate::feature_flags::is_enabled("canvas-ui"),
+    );
+    match crate::metrics::render_template(&state.tera, "system.html", &context) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => {
+            error!("Template rendering failed: {}", e);
+            crate::AppError::from(e).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn consumer(pending: u64, redelivered: usize, idle: Option<i64>) -> ConsumerHealth {
+        ConsumerHealth {
+            name: "operate-ui".to_string(),
+            pending,
+            ack_pending: 0,
+            redelivered,
+            lag: pending,
+            last_delivery: idle.map(|secs| Utc::now() - Duration::seconds(secs)),
+        }
+    }
+
+    #[test]
+    fn caught_up_consumer_is_ok_even_when_idle() {
+        let (status, reasons) =
+            HealthThresholds::default().assess(&consumer(0, 0, Some(86_400)), Utc::now());
+
+        assert_eq!(status, HealthStatus::Ok);
+        assert!(reasons.is_empty());
+    }
+
+    #[test]
+    fn pending_and_redelivery_thresholds_escalate() {
+        let thresholds = HealthThresholds::default();
+        let now = Utc::now();
+
+        assert_eq!(
+            thresholds.assess(&consumer(1_500, 0, Some(1)), now).0,
+            HealthStatus::Warning
+        );
+        assert_eq!(
+            thresholds.assess(&consumer(20_000, 0, Some(1)), now).0,
+            HealthStatus::Critical
+        );
+        let (status, reasons) = thresholds.assess(&consumer(10, 250, Some(1)), now);
+        assert_eq!(status, HealthStatus::Critical);
+        assert_eq!(reasons, vec!["250 messages redelivered".to_string()]);
+    }
+
+    #[test]
+    fn stalled_consumer_with_pending_messages_is_critical() {
+        let (status, reasons) =
+            HealthThresholds::default().assess(&consumer(5, 0, Some(600)), Utc::now());
+
+        assert_eq!(status, HealthStatus::Critical);
+        assert!(reasons[0].starts_with("no delivery for 600s"));
+    }
+
+    #[test]
+    fn stream_takes_worst_consumer_status_and_errors_are_critical() {
+        let thresholds = HealthThresholds::default();
+        let report = StreamReport {
+            stream: StreamHealth {
+                name: "RITUAL_EVENTS".to_string(),
+                messages: 2_000,
+                bytes: 1,
+                last_sequence: 2_000,
+                consumers: 2,
+            },
+            last_message: None,
+            consumers: vec![consumer(0, 0, Some(1)), consumer(1_500, 0, Some(1))],
+        };
+
+        let status = stream_status("RITUAL_EVENTS", Ok(report), &thresholds, Utc::now());
+        assert_eq!(status.status, HealthStatus::Warning);
+        assert_eq!(status.consumers[0].status, HealthStatus::Ok);
+
+        let missing = stream_status(
+            "GRAPH_COMMITS",
+            Err(anyhow::anyhow!(
+                "JetStream stream 'GRAPH_COMMITS' not found"
+            )),
+            &thresholds,
+            Utc::now(),
+        );
+        assert_eq!(missing.status, HealthStatus::Critical);
+        assert!(missing.error.unwrap().contains("not found"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Tenant overview for the navbar switcher and the tenant admin page
+//!
+//! Tenants are discovered from the run index (anyone with indexed runs), the
+//! per-tenant overrides in `WARDS_TENANT_QUOTAS`, the caller's token, and
+//! `default`. Callers with a JWT only see the tenants their token covers.
+
+use crate::jetstream::StreamHealth;
+use crate::run_index::TenantRunStats;
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{Html, IntoResponse, Json, Response},
+    Extension,
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use tracing::{error, warn};
+use wards::quota::QuotaResource;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantOverview {
+    pub tenant: String,
+    pub runs: TenantRunStats,
+    pub quotas: Vec<QuotaUsage>,
+}
+
+/// A configured limit and how much of the current window is used
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaUsage {
+    pub resource: QuotaResource,
+    pub limit: u32,
+    pub window_seconds: u64,
+    /// `None` when the quota store could not be read
+    pub used: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantList {
+    pub tenants: Vec<TenantOverview>,
+    /// False while the run index is still reading the stream; run counts may be low
+    pub index_ready: bool,
+    pub stream: Option<StreamHealth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_error: Option<String>,
+}
+
+/// Every known tenant name, sorted, with `default` always present
+fn tenant_names(
+    state: &AppState,
+    stats: &[TenantRunStats],
+    claims: Option<&jwt_auth::Claims>,
+) -> BTreeSet<String> {
+    let mut names: BTreeSet<String> = stats.iter().map(|s| s.tenant.clone()).collect();
+    if let Some(quotas) = &state.tenant_quotas {
+        names.extend(quotas.config().tenants.keys().cloned());
+    }
+    if let Some(claims) = claims {
+        names.extend(claims.tenants.iter().filter(|t| *t != "*").cloned());
+    }
+    names.insert("default".to_string());
+    names
+}
+
+async fn quota_usage(state: &AppState, tenant: &str) -> Vec<QuotaUsage> {
+    let Some(quotas) = &state.tenant_quotas else {
+        return Vec::new();
+    };
+    let mut usage = Vec::new();
+    for resource in QuotaResource::ALL {
+        let Some(limit) = quotas.config().limit_for(tenant, resource) else {
+            continue;
+        };
+        let (used, error) = match quotas.usage(tenant, resource).await {
+            Ok(used) => (Some(used), None),
+            Err(e) => {
+                warn!(
+                    "Failed to read {} quota usage for {}: {}",
+                    resource, tenant, e
+                );
+                (None, Some(e.to_string()))
+            }
+        };
+        usage.push(QuotaUsage {
+            resource,
+            limit: limit.limit,
+            window_seconds: limit.window_seconds,
+            used,
+            error,
+        });
+    }
+    usage
+}
+
+async fn overview(state: &AppState, tenant: &str, stats: &[TenantRunStats]) -> TenantOverview {
+    TenantOverview {
+        tenant: tenant.to_string(),
+        runs: stats
+            .iter()
+            .find(|s| s.tenant == tenant)
+            .cloned()
+            .unwrap_or_else(|| TenantRunStats {
+                tenant: tenant.to_string(),
+                ..Default::default()
+            }),
+        quotas: quota_usage(state, tenant).await,
+    }
+}
+
+/// All tenants visible to the caller, with stream health
+pub async fn tenant_list(state: &AppState, claims: Option<&jwt_auth::Claims>) -> TenantList {
+    let stats = state.run_index.tenant_stats();
+    let mut tenants = Vec::new();
+    for name in tenant_names(state, &stats, claims) {
+        if claims.is_some_and(|c| !c.has_tenant(&name)) {
+            continue;
+        }
+        tenants.push(overview(state, &name, &stats).await);
+    }
+
+    let (stream, stream_error) = match &state.jetstream_client {
+        Some(client) => match client.ritual_stream_health().await {
+            Ok(health) => (Some(health), None),
+            Err(e) => {
+                error!("Failed to read ritual stream health: {}", e);
+                (None, Some(e.to_string()))
+            }
+        },
+        None => (None, Some("JetStream is not available".to_string())),
+    };
+
+    TenantList {
+        tenants,
+        index_ready: state.run_index.is_ready(),
+        stream,
+        stream_error,
+    }
+}
+
+/// GET /api/tenants
+///
+/// Needs a valid token but no particular role: the list is what the switcher
+/// offers, so it is filtered to the token's tenants instead of refused
+pub async fn list_tenants_api(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    match state.access_control.authenticate(&headers) {
+        Ok(claims) => Json(tenant_list(&state, claims.as_ref()).await).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// GET /api/tenants/:tenant
+pub async fn get_tenant_api(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+) -> Json<TenantOverview> {
+    let stats = state.run_index.tenant_stats();
+    Json(overview(&state, &tenant, &stats).await)
+}
+
+/// GET /admin/tenants
+pub async fn tenants_admin_html(
+    State(state): State<AppState>,
+    claims: Option<Extension<jwt_auth::Claims>>,
+) -> Response {
+    let list = tenant_list(&state, claims.as_deref()).await;
+    let mut context = tera::Context::new();
+    context.insert("list", &list);
+    context.insert("current_page", &"tenants");
+    context.insert(
+        "contracts_browser_enabled",
+        &crate::feature_flags::is_enabled("contracts-browser"),
+    );
+    context.insert(
+        "canvas_enabled",
+        &crate::feature_flags::is_enabled("canvas-ui"),
+    );
+    match crate::metrics::render_template(&state.tera, "tenants_admin.html", &context) {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => {
+            error!("Template rendering failed: {}", e);
+            crate::AppError::from(e).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wards::quota::{TenantQuotaConfig, TenantQuotas};
+
+    fn state() -> AppState {
+        let config: TenantQuotaConfig = serde_json::from_value(serde_json::json!({
+            "default": { "runs": { "limit": 100, "windowSeconds": 3600 } },
+            "tenants": { "globex": { "graph-commits": { "limit": 5, "windowSeconds": 60 } } }
+        }))
+        .unwrap();
+        let run_index = crate::run_index::RunIndex::with_capacity(10);
+        run_index.apply(
+            "demon.ritual.v1.acme.release.run-1.events",
+            &serde_json::json!({"event": "ritual.started:v1", "ts": "2025-01-07T09:00:00Z"}),
+        );
+        AppState {
+            jetstream_client: None,
+            tera: tera::Tera::default(),
+            access_control: Default::default(),
+            bundle_loader: runtime::bundle::BundleLoader::new(None),
+            app_pack_registry: None,
+            feature_flags: Default::default(),
+            run_index,
+            tenant_quotas: Some(std::sync::Arc::new(TenantQuotas::in_memory(config))),
+        }
+    }
+
+    #[tokio::test]
+    async fn tenants_come_from_runs_quota_overrides_and_default() {
+        let state = state();
+        state
+            .tenant_quotas
+            .as_ref()
+            .unwrap()
+            .check_and_consume("acme", QuotaResource::Runs, 3)
+            .await
+            .unwrap();
+
+        let list = tenant_list(&state, None).await;
+        let names: Vec<&str> = list.tenants.iter().map(|t| t.tenant.as_str()).collect();
+        assert_eq!(names, vec!["acme", "default", "globex"]);
+
+        let acme = &list.tenants[0];
+        assert_eq!(acme.runs.running, 1);
+        assert_eq!(acme.quotas.len(), 1);
+        assert_eq!(acme.quotas[0].used, Some(3));
+        assert_eq!(acme.quotas[0].limit, 100);
+
+        let globex = &list.tenants[2];
+        assert_eq!(globex.quotas.len(), 2);
+        assert_eq!(
+            list.stream_error.as_deref(),
+            Some("JetStream is not available")
+        );
+    }
+
+    #[tokio::test]
+    async fn callers_only_see_tenants_their_token_covers() {
+        let claims = jwt_auth::Claims {
+            tenants: vec!["globex".to_string()],
+            ..Default::default()
+        };
+        let list = tenant_list(&state(), Some(&claims)).await;
+        let names: Vec<&str> = list.tenants.iter().map(|t| t.tenant.as_str()).collect();
+        assert_eq!(names, vec!["globex"]);
+    }
+}
@@ -0,0 +1,348 @@
+//! Gantt-style step timeline for the run detail page
+//!
+//! Bars come from the `step.started:v1` / `step.completed:v1` pair the engine
+//! emits around every attempt of a capsule, approval or timer step. Each step
+//! row shows the time it waited for a parallel slot (`queuedMs`), one segment
+//! per attempt, and for approval steps how long the gate stayed open between
+//! `approval.requested:v1` and its grant or denial. Positions are percentages
+//! of the run's span so the template can lay bars out with plain CSS.
+
+use crate::jetstream::RitualEvent;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Steps shorter than this are never flagged as slow
+const SLOW_FLOOR_MS: u64 = 1_000;
+/// A step is slow when it takes this many times the run's median step
+const SLOW_FACTOR: u64 = 2;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunTimeline {
+    pub steps: Vec<StepBar>,
+    pub span_ms: u64,
+    /// Durations at or above this are highlighted
+    pub slow_threshold_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepBar {
+    pub step_id: String,
+    pub kind: String,
+    /// `succeeded`, `failed`, `halted` or `running`
+    pub outcome: String,
+    pub queued_ms: u64,
+    /// From the first attempt's start to the last attempt's end
+    pub duration_ms: u64,
+    pub approval_wait_ms: Option<u64>,
+    pub retries: u32,
+    pub slow: bool,
+    pub queue_offset_pct: f64,
+    pub queue_width_pct: f64,
+    pub attempts: Vec<AttemptBar>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptBar {
+    pub attempt: u64,
+    pub outcome: String,
+    pub duration_ms: u64,
+    pub offset_pct: f64,
+    pub width_pct: f64,
+}
+
+struct Attempt {
+    attempt: u64,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    outcome: String,
+}
+
+struct Step {
+    step_id: String,
+    kind: String,
+    queued_ms: u64,
+    attempts: Vec<Attempt>,
+}
+
+impl RunTimeline {
+    /// The timeline for a run, or `None` when no step lifecycle events were
+    /// recorded (e.g. runs from engines that predate them)
+    pub fn from_events(events: &[RitualEvent]) -> Option<Self> {
+        let origin = events.iter().map(|e| e.ts).min()?;
+        let end = events.iter().map(|e| e.ts).max()?;
+
+        let mut steps: Vec<Step> = Vec::new();
+        for event in events {
+            let Some(step_id) = str_field(event, "stepId") else {
+                continue;
+            };
+            let attempt = event
+                .extra
+                .get("attempt")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(1);
+            match event.event.as_str() {
+                "step.started:v1" => {
+                    let step = match steps.iter().position(|s| s.step_id == step_id) {
+                        Some(i) => &mut steps[i],
+                        None => {
+                            steps.push(Step {
+                                step_id: step_id.to_string(),
+                                kind: str_field(event, "kind").unwrap_or("capsule").to_string(),
+                                queued_ms: event
+                                    .extra
+                                    .get("queuedMs")
+                                    .and_then(|v| v.as_u64())
+                                    .unwrap_or(0),
+                                attempts: Vec::new(),
+                            });
+                            steps.last_mut().expect("just pushed")
+                        }
+                    };
+                    step.attempts.push(Attempt {
+                        attempt,
+                        start: event.ts,
+                        end: None,
+                        outcome: "running".to_string(),
+                    });
+                }
+                "step.completed:v1" => {
+                    let open = steps
+                        .iter_mut()
+                        .find(|s| s.step_id == step_id)
+                        .and_then(|s| {
+                            s.attempts
+                                .iter_mut()
+                                .find(|a| a.attempt == attempt && a.end.is_none())
+                        });
+                    if let Some(open) = open {
+                        open.end = Some(event.ts);
+                        open.outcome = str_field(event, "outcome")
+                            .unwrap_or("succeeded")
+                            .to_string();
+                    }
+                }
+                _ => {}
+            }
+        }
+        if steps.is_empty() {
+            return None;
+        }
+
+        let span_ms = millis(origin, end).max(1);
+        let pct = |ms: u64| ((ms as f64 / span_ms as f64) * 10_000.0).round() / 100.0;
+        let approval_waits = approval_waits(events, end);
+
+        let mut bars: Vec<StepBar> = steps
+            .into_iter()
+            .map(|step| {
+                let first = step
+                    .attempts
+                    .first()
+                    .expect("started steps have an attempt");
+                let last = step.attempts.last().expect("started steps have an attempt");
+                let step_end = last.end.unwrap_or(end);
+                let queue_start =
+                    first.start - chrono::Duration::milliseconds(step.queued_ms as i64);
+                let approval_wait_ms = (step.kind == "approval")
+                    .then(|| {
+                        approval_waits
+                            .iter()
+                            .find(|(requested, _)| {
+                                *requested >= first.start && *requested <= step_end
+                            })
+                            .map(|(_, wait)| *wait)
+                    })
+                    .flatten();
+                StepBar {
+                    kind: step.kind.clone(),
+                    outcome: last.outcome.clone(),
+                    queued_ms: step.queued_ms,
+                    duration_ms: millis(first.start, step_end),
+                    approval_wait_ms,
+                    retries: step.attempts.len().saturating_sub(1) as u32,
+                    slow: false,
+                    queue_offset_pct: pct(millis(origin, queue_start)),
+                    queue_width_pct: pct(step.queued_ms),
+                    attempts: step
+                        .attempts
+                        .iter()
+                        .map(|a| {
+                            let duration_ms = millis(a.start, a.end.unwrap_or(end));
+                            AttemptBar {
+                                attempt: a.attempt,
+                                outcome: a.outcome.clone(),
+                                duration_ms,
+                                offset_pct: pct(millis(origin, a.start)),
+                                width_pct: pct(duration_ms),
+                            }
+                        })
+                        .collect(),
+                    step_id: step.step_id,
+                }
+            })
+            .collect();
+
+        let slow_threshold_ms = slow_threshold(&bars);
+        for bar in &mut bars {
+            bar.slow = bar.duration_ms >= slow_threshold_ms;
+        }
+
+        Some(Self {
+            steps: bars,
+            span_ms,
+            slow_threshold_ms,
+        })
+    }
+}
+
+/// Twice the median step duration, but never below [`SLOW_FLOOR_MS`];
+/// `u64::MAX` (nothing is slow) for runs with a single step
+fn slow_threshold(bars: &[StepBar]) -> u64 {
+    if bars.len() < 2 {
+        return u64::MAX;
+    }
+    let mut durations: Vec<u64> = bars.iter().map(|b| b.duration_ms).collect();
+    durations.sort_unstable();
+    let median = durations[durations.len() / 2];
+    (median * SLOW_FACTOR).max(SLOW_FLOOR_MS)
+}
+
+/// `(requested at, wait in ms)` per approval gate; gates still open wait until `end`
+fn approval_waits(events: &[RitualEvent], end: DateTime<Utc>) -> Vec<(DateTime<Utc>, u64)> {
+    events
+        .iter()
+        .filter(|e| e.event == "approval.requested:v1")
+        .map(|requested| {
+            let gate = str_field(requested, "gateId");
+            let resolved = events
+                .iter()
+                .filter(|e| e.event == "approval.granted:v1" || e.event == "approval.denied:v1")
+                .find(|e| e.ts >= requested.ts && str_field(e, "gateId") == gate)
+                .map_or(end, |e| e.ts);
+            (requested.ts, millis(requested.ts, resolved))
+        })
+        .collect()
+}
+
+fn str_field<'a>(event: &'a RitualEvent, key: &str) -> Option<&'a str> {
+    event.extra.get(key).and_then(|v| v.as_str())
+}
+
+fn millis(from: DateTime<Utc>, to: DateTime<Utc>) -> u64 {
+    (to - from).num_milliseconds().max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(ts: &str, body: serde_json::Value) -> RitualEvent {
+        let mut body = body;
+        body["ts"] = json!(ts);
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn started(ts: &str, step: &str, kind: &str, attempt: u64, queued_ms: u64) -> RitualEvent {
+        event(
+            ts,
+            json!({ "event": "step.started:v1", "stepId": step, "kind": kind, "attempt": attempt, "queuedMs": queued_ms }),
+        )
+    }
+
+    fn completed(ts: &str, step: &str, attempt: u64, outcome: &str) -> RitualEvent {
+        event(
+            ts,
+            json!({ "event": "step.completed:v1", "stepId": step, "attempt": attempt, "outcome": outcome }),
+        )
+    }
+
+    #[test]
+    fn runs_without_lifecycle_events_have_no_timeline() {
+        let events = vec![event(
+            "2025-01-01T00:00:00Z",
+            json!({ "event": "ritual.started:v1" }),
+        )];
+        assert!(RunTimeline::from_events(&events).is_none());
+    }
+
+    #[test]
+    fn attempts_queue_time_and_approval_wait_are_laid_out_over_the_run() {
+        let events = vec![
+            event(
+                "2025-01-01T00:00:00Z",
+                json!({ "event": "ritual.started:v1" }),
+            ),
+            started("2025-01-01T00:00:00Z", "build", "capsule", 1, 0),
+            completed("2025-01-01T00:00:01Z", "build", 1, "failed"),
+            started("2025-01-01T00:00:02Z", "build", "capsule", 2, 0),
+            completed("2025-01-01T00:00:03Z", "build", 2, "succeeded"),
+            started("2025-01-01T00:00:04Z", "gate", "approval", 1, 1000),
+            event(
+                "2025-01-01T00:00:04Z",
+                json!({ "event": "approval.requested:v1", "gateId": "prod" }),
+            ),
+            event(
+                "2025-01-01T00:00:09Z",
+                json!({ "event": "approval.granted:v1", "gateId": "prod" }),
+            ),
+            completed("2025-01-01T00:00:09Z", "gate", 1, "succeeded"),
+            started("2025-01-01T00:00:09Z", "deploy", "capsule", 1, 0),
+            event(
+                "2025-01-01T00:00:10Z",
+                json!({ "event": "ritual.completed:v1" }),
+            ),
+        ];
+
+        let timeline = RunTimeline::from_events(&events).unwrap();
+        assert_eq!(timeline.span_ms, 10_000);
+        let [build, gate, deploy] = &timeline.steps[..] else {
+            panic!("expected three steps: {:?}", timeline.steps);
+        };
+
+        assert_eq!(build.retries, 1);
+        assert_eq!(build.duration_ms, 3_000);
+        assert_eq!(build.attempts[0].outcome, "failed");
+        assert_eq!(build.attempts[1].offset_pct, 20.0);
+        assert_eq!(build.attempts[1].width_pct, 10.0);
+
+        assert_eq!(gate.queued_ms, 1_000);
+        assert_eq!(gate.queue_offset_pct, 30.0);
+        assert_eq!(gate.queue_width_pct, 10.0);
+        assert_eq!(gate.approval_wait_ms, Some(5_000));
+        assert_eq!(build.approval_wait_ms, None);
+
+        // Still running: the bar extends to the latest event
+        assert_eq!(deploy.outcome, "running");
+        assert_eq!(deploy.duration_ms, 1_000);
+
+        // Median is 3s, so only steps of 6s or more are slow
+        assert_eq!(timeline.slow_threshold_ms, 6_000);
+        assert!(!build.slow && !gate.slow && !deploy.slow);
+    }
+
+    #[test]
+    fn steps_far_above_the_median_are_flagged_slow() {
+        let events = vec![
+            started("2025-01-01T00:00:00Z", "a", "capsule", 1, 0),
+            completed("2025-01-01T00:00:02Z", "a", 1, "succeeded"),
+            started("2025-01-01T00:00:02Z", "b", "capsule", 1, 0),
+            completed("2025-01-01T00:00:04Z", "b", 1, "succeeded"),
+            started("2025-01-01T00:00:04Z", "c", "timer", 1, 0),
+            completed("2025-01-01T00:00:30Z", "c", 1, "succeeded"),
+        ];
+
+        let timeline = RunTimeline::from_events(&events).unwrap();
+        let slow: Vec<&str> = timeline
+            .steps
+            .iter()
+            .filter(|s| s.slow)
+            .map(|s| s.step_id.as_str())
+            .collect();
+        assert_eq!(slow, vec!["c"]);
+    }
+}
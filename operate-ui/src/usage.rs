@@ -0,0 +1,400 @@
+//! Per-run resource usage and tenant chargeback rollups
+//!
+//! Capsules report what they consumed under `metrics.resources` of their
+//! result envelope: `memory_bytes` for peak memory, and either `cpu_seconds`
+//! or a `cpu_percent` average that is scaled by `metrics.duration.total_ms`.
+//! The run detail page sums those across the steps of a run, next to the
+//! number of steps container-exec ran and the run's wall-clock time.
+//! `GET /api/tenants/:tenant/usage?format=json|csv` rolls the same summary up
+//! over a tenant's recent runs for chargeback exports.
+//!
+//! Envelopes come from `ritual.completed:v1`, so runs that failed or are
+//! still in flight only contribute their wall-clock time.
+
+use crate::jetstream::{RitualEvent, RunDetail};
+use crate::workflow_overlay::step_envelopes;
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{error, info, warn};
+
+/// Runs rolled up when the request gives no `limit`
+const DEFAULT_ROLLUP_RUNS: usize = 200;
+/// `provenance.source.system` of envelopes produced by container-exec
+const CONTAINER_SYSTEM: &str = "container-exec";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunUsage {
+    /// Sum over the steps that reported CPU usage
+    pub cpu_seconds: Option<f64>,
+    /// Largest `memory_bytes` any step reported
+    pub peak_memory_bytes: Option<i64>,
+    /// Steps whose envelope container-exec produced
+    pub container_count: u64,
+    /// From the run's first event to its last
+    pub wall_clock_ms: u64,
+    /// Steps with any resource metrics, out of `steps`
+    pub steps_reporting: u64,
+    pub steps: u64,
+}
+
+impl RunUsage {
+    pub fn from_events(events: &[RitualEvent]) -> Self {
+        let wall_clock_ms = match (
+            events.iter().map(|e| e.ts).min(),
+            events.iter().map(|e| e.ts).max(),
+        ) {
+            (Some(start), Some(end)) => (end - start).num_milliseconds().max(0) as u64,
+            _ => 0,
+        };
+        let mut usage = Self {
+            wall_clock_ms,
+            ..Self::default()
+        };
+
+        for envelope in step_envelopes(events).values() {
+            usage.steps += 1;
+            if envelope
+                .pointer("/provenance/source/system")
+                .and_then(Value::as_str)
+                == Some(CONTAINER_SYSTEM)
+            {
+                usage.container_count += 1;
+            }
+            let Some(metrics) = envelope.get("metrics") else {
+                continue;
+            };
+            let cpu_seconds = step_cpu_seconds(metrics);
+            let memory_bytes = metrics
+                .pointer("/resources/memory_bytes")
+                .and_then(Value::as_i64);
+            if cpu_seconds.is_none() && memory_bytes.is_none() {
+                continue;
+            }
+            usage.steps_reporting += 1;
+            if let Some(seconds) = cpu_seconds {
+                *usage.cpu_seconds.get_or_insert(0.0) += seconds;
+            }
+            if let Some(bytes) = memory_bytes {
+                usage.peak_memory_bytes = usage.peak_memory_bytes.max(Some(bytes));
+            }
+        }
+        usage
+    }
+
+    /// Fold another run's usage into a rollup total
+    fn add(&mut self, other: &Self) {
+        if let Some(seconds) = other.cpu_seconds {
+            *self.cpu_seconds.get_or_insert(0.0) += seconds;
+        }
+        self.peak_memory_bytes = self.peak_memory_bytes.max(other.peak_memory_bytes);
+        self.container_count += other.container_count;
+        self.wall_clock_ms += other.wall_clock_ms;
+        self.steps_reporting += other.steps_reporting;
+        self.steps += other.steps;
+    }
+}
+
+/// CPU-seconds a step reported directly, or derived from its average
+/// `cpu_percent` over `duration.total_ms`
+fn step_cpu_seconds(metrics: &Value) -> Option<f64> {
+    let resources = metrics.get("resources")?;
+    if let Some(seconds) = resources.get("cpu_seconds").and_then(Value::as_f64) {
+        return Some(seconds);
+    }
+    let percent = resources.get("cpu_percent").and_then(Value::as_f64)?;
+    let total_ms = metrics
+        .pointer("/duration/total_ms")
+        .and_then(Value::as_f64)?;
+    Some(percent / 100.0 * total_ms / 1000.0)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunUsageRow {
+    pub run_id: String,
+    pub ritual_id: String,
+    pub status: String,
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub usage: RunUsage,
+}
+
+impl RunUsageRow {
+    pub fn from_run(run: &RunDetail) -> Self {
+        Self {
+            run_id: run.run_id.clone(),
+            ritual_id: run.ritual_id.clone(),
+            status: run.status().to_string(),
+            started_at: run.events.iter().map(|e| e.ts).min(),
+            usage: RunUsage::from_events(&run.events),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantUsage {
+    pub tenant: String,
+    pub generated_at: DateTime<Utc>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub totals: RunUsage,
+    pub runs: Vec<RunUsageRow>,
+}
+
+impl TenantUsage {
+    pub fn from_rows(
+        tenant: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        runs: Vec<RunUsageRow>,
+    ) -> Self {
+        let mut totals = RunUsage::default();
+        for row in &runs {
+            totals.add(&row.usage);
+        }
+        Self {
+            tenant: tenant.to_string(),
+            generated_at: Utc::now(),
+            since,
+            until,
+            totals,
+            runs,
+        }
+    }
+
+    /// One line per run, for spreadsheets and billing imports
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "run_id,ritual_id,status,started_at,wall_clock_ms,cpu_seconds,peak_memory_bytes,container_count,steps_reporting,steps\n",
+        );
+        for row in &self.runs {
+            let fields = [
+                csv_field(&row.run_id),
+                csv_field(&row.ritual_id),
+                csv_field(&row.status),
+                row.started_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                row.usage.wall_clock_ms.to_string(),
+                row.usage
+                    .cpu_seconds
+                    .map(|s| format!("{:.3}", s))
+                    .unwrap_or_default(),
+                row.usage
+                    .peak_memory_bytes
+                    .map(|b| b.to_string())
+                    .unwrap_or_default(),
+                row.usage.container_count.to_string(),
+                row.usage.steps_reporting.to_string(),
+                row.usage.steps.to_string(),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UsageQuery {
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+    /// Only runs that started at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only runs that started before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Most recent runs to roll up (default 200, at most 1000)
+    pub limit: Option<usize>,
+}
+
+/// GET /api/tenants/:tenant/usage - resource usage of the tenant's recent runs
+pub async fn get_tenant_usage_api(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    Query(query): Query<UsageQuery>,
+) -> Response {
+    let csv = match query.format.as_deref().map(str::trim) {
+        None | Some("") => false,
+        Some(f) if f.eq_ignore_ascii_case("json") => false,
+        Some(f) if f.eq_ignore_ascii_case("csv") => true,
+        Some(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "format must be 'json' or 'csv'",
+                    "format": query.format,
+                })),
+            )
+                .into_response()
+        }
+    };
+
+    let Some(client) = &state.jetstream_client else {
+        return (
+            StatusCode::BAD_GATEWAY,
+            Json(json!({ "error": "JetStream is not available" })),
+        )
+            .into_response();
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_ROLLUP_RUNS);
+    let summaries = match client.list_runs_for_tenant(&tenant, Some(limit)).await {
+        Ok(summaries) => summaries,
+        Err(e) => {
+            error!("Failed to list runs of tenant {} for usage: {}", tenant, e);
+            return (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({ "error": format!("Failed to list runs: {}", e) })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut rows = Vec::new();
+    for summary in summaries.iter().filter(|s| {
+        query.since.is_none_or(|since| s.start_ts >= since)
+            && query.until.is_none_or(|until| s.start_ts < until)
+    }) {
+        match client
+            .get_run_detail_for_tenant(&tenant, &summary.run_id)
+            .await
+        {
+            Ok(Some(run)) => rows.push(RunUsageRow::from_run(&run)),
+            Ok(None) => {}
+            Err(e) => warn!(
+                "Skipping run {} in usage rollup for tenant {}: {}",
+                summary.run_id, tenant, e
+            ),
+        }
+    }
+
+    let usage = TenantUsage::from_rows(&tenant, query.since, query.until, rows);
+    info!(
+        "Rolled up usage of {} runs for tenant {}",
+        usage.runs.len(),
+        tenant
+    );
+    if !csv {
+        return Json(usage).into_response();
+    }
+    let disposition = format!(
+        "attachment; filename=\"usage-{}.csv\"",
+        tenant.replace(
+            |c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_',
+            "_"
+        )
+    );
+    (
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        usage.to_csv(),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(value: Value) -> Vec<RitualEvent> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn usage_sums_cpu_and_takes_peak_memory_across_steps() {
+        let usage = RunUsage::from_events(&events(json!([
+            { "ts": "2025-01-01T00:00:00Z", "event": "ritual.started:v1" },
+            { "ts": "2025-01-01T00:01:30Z", "event": "ritual.completed:v1", "outputs": { "steps": {
+                "build": {
+                    "result": { "success": true },
+                    "metrics": { "resources": { "cpu_seconds": 12.5, "memory_bytes": 268435456 } },
+                    "provenance": { "source": { "system": "container-exec" } }
+                },
+                "test": {
+                    "result": { "success": true },
+                    "metrics": {
+                        "duration": { "total_ms": 4000.0 },
+                        "resources": { "cpu_percent": 50.0, "memory_bytes": 536870912 }
+                    },
+                    "provenance": { "source": { "system": "container-exec" } }
+                },
+                "notify": { "result": { "success": true } }
+            } } }
+        ])));
+
+        assert_eq!(usage.cpu_seconds, Some(14.5));
+        assert_eq!(usage.peak_memory_bytes, Some(536870912));
+        assert_eq!(usage.container_count, 2);
+        assert_eq!(usage.wall_clock_ms, 90_000);
+        assert_eq!((usage.steps_reporting, usage.steps), (2, 3));
+    }
+
+    #[test]
+    fn runs_without_metrics_only_report_wall_clock() {
+        let usage = RunUsage::from_events(&events(json!([
+            { "ts": "2025-01-01T00:00:00Z", "event": "ritual.started:v1" },
+            { "ts": "2025-01-01T00:00:02Z", "event": "step.started:v1", "stepId": "build" }
+        ])));
+        assert_eq!(
+            usage,
+            RunUsage {
+                wall_clock_ms: 2_000,
+                ..RunUsage::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rollup_totals_runs_and_escapes_csv() {
+        let row = |run_id: &str, cpu: Option<f64>, memory: Option<i64>| RunUsageRow {
+            run_id: run_id.to_string(),
+            ritual_id: "release".to_string(),
+            status: "Completed".to_string(),
+            started_at: None,
+            usage: RunUsage {
+                cpu_seconds: cpu,
+                peak_memory_bytes: memory,
+                container_count: 1,
+                wall_clock_ms: 1_000,
+                steps_reporting: 1,
+                steps: 1,
+            },
+        };
+        let usage = TenantUsage::from_rows(
+            "acme",
+            None,
+            None,
+            vec![
+                row("run-1", Some(1.5), Some(100)),
+                row("run,2", None, Some(300)),
+            ],
+        );
+
+        assert_eq!(usage.totals.cpu_seconds, Some(1.5));
+        assert_eq!(usage.totals.peak_memory_bytes, Some(300));
+        assert_eq!(usage.totals.container_count, 2);
+        assert_eq!(usage.totals.wall_clock_ms, 2_000);
+
+        let csv = usage.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "run-1,release,Completed,,1000,1.500,100,1,1,1");
+        assert_eq!(lines[2], "\"run,2\",release,Completed,,1000,,300,1,1,1");
+    }
+}
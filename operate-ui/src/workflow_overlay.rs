@@ -0,0 +1,572 @@
+//! Live run state for the workflow viewer
+//!
+//! The viewer draws a ritual's steps from its definition; this module supplies
+//! where a run of it currently is. Each step the engine has touched gets a
+//! state from its `step.started:v1` / `step.completed:v1` events — `running`,
+//! `completed` or `failed` — and an approval step becomes `waiting-approval`
+//! between the `approval.requested:v1` for its gate and the decision. Steps the
+//! run has not reached are absent and drawn as pending. Steps with a result
+//! envelope in `ritual.completed:v1` link to it so a node can be clicked
+//! through.
+//!
+//! `GET /api/runs/:run_id/overlay` returns the current overlay and
+//! `/api/runs/:run_id/overlay/stream` re-sends it as an `overlay` SSE event
+//! whenever the run appends events; both have `/api/tenants/:tenant/...`
+//! variants.
+
+use crate::jetstream::{RitualEvent, RunDetail};
+use crate::AppState;
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use futures_util::{FutureExt as _, StreamExt as _};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio_stream::wrappers::IntervalStream;
+use tracing::{error, warn};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunOverlay {
+    pub run_id: String,
+    pub ritual_id: String,
+    /// Run status as shown on the run detail page, e.g. `Running`
+    pub status: String,
+    /// Steps in the order the run first reached them
+    pub steps: Vec<StepOverlay>,
+    /// Time of the latest event folded into the overlay
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepOverlay {
+    pub step_id: String,
+    pub kind: String,
+    pub state: StepState,
+    pub attempt: u64,
+    pub updated_at: DateTime<Utc>,
+    /// Gate the step is (or was) waiting on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gate_id: Option<String>,
+    /// Failure message, or the halt reason such as `approval_denied`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The step's result envelope, once the run has recorded it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub envelope_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StepState {
+    Running,
+    Completed,
+    Failed,
+    WaitingApproval,
+}
+
+impl RunOverlay {
+    /// The overlay for `run`; envelope links are relative to `run_url`, the
+    /// run's API path such as `/api/runs/<run_id>`
+    pub fn from_run(run: &RunDetail, run_url: &str) -> Self {
+        let mut steps = step_states(&run.events);
+        let envelopes = step_envelopes(&run.events);
+        for step_id in envelopes.keys() {
+            if !steps.iter().any(|s| s.step_id == *step_id) {
+                // Engines that predate step lifecycle events only report outputs
+                steps.push(StepOverlay {
+                    step_id: step_id.clone(),
+                    kind: "capsule".to_string(),
+                    state: StepState::Completed,
+                    attempt: 1,
+                    updated_at: run.events.last().map_or_else(Utc::now, |e| e.ts),
+                    gate_id: None,
+                    error: None,
+                    envelope_url: None,
+                });
+            }
+        }
+        for step in &mut steps {
+            if envelopes.contains_key(&step.step_id) {
+                step.envelope_url = Some(format!(
+                    "{}/steps/{}/envelope",
+                    run_url,
+                    urlencoding::encode(&step.step_id)
+                ));
+            }
+        }
+
+        Self {
+            run_id: run.run_id.clone(),
+            ritual_id: run.ritual_id.clone(),
+            status: run.status().to_string(),
+            steps,
+            updated_at: run.events.iter().map(|e| e.ts).max(),
+        }
+    }
+}
+
+fn step_states(events: &[RitualEvent]) -> Vec<StepOverlay> {
+    let mut steps: Vec<StepOverlay> = Vec::new();
+    for event in events {
+        match event.event.as_str() {
+            "step.started:v1" => {
+                let Some(step_id) = str_field(event, "stepId") else {
+                    continue;
+                };
+                let attempt = attempt(event);
+                let kind = str_field(event, "kind").unwrap_or("capsule").to_string();
+                match steps.iter_mut().find(|s| s.step_id == step_id) {
+                    Some(step) => {
+                        step.state = StepState::Running;
+                        step.attempt = attempt;
+                        step.updated_at = event.ts;
+                        step.error = None;
+                    }
+                    None => steps.push(StepOverlay {
+                        step_id: step_id.to_string(),
+                        kind,
+                        state: StepState::Running,
+                        attempt,
+                        updated_at: event.ts,
+                        gate_id: None,
+                        error: None,
+                        envelope_url: None,
+                    }),
+                }
+            }
+            "step.completed:v1" => {
+                let Some(step) = str_field(event, "stepId")
+                    .and_then(|id| steps.iter_mut().find(|s| s.step_id == id))
+                else {
+                    continue;
+                };
+                step.attempt = attempt(event);
+                step.updated_at = event.ts;
+                match str_field(event, "outcome").unwrap_or("succeeded") {
+                    "succeeded" => step.state = StepState::Completed,
+                    _ => {
+                        step.state = StepState::Failed;
+                        step.error = str_field(event, "error")
+                            .or_else(|| str_field(event, "reason"))
+                            .map(str::to_string);
+                    }
+                }
+            }
+            "approval.requested:v1" => {
+                // The gate belongs to the approval step that is running now
+                let gate = str_field(event, "gateId").map(str::to_string);
+                if let Some(step) = steps
+                    .iter_mut()
+                    .rev()
+                    .find(|s| s.kind == "approval" && s.state == StepState::Running)
+                {
+                    step.state = StepState::WaitingApproval;
+                    step.gate_id = gate;
+                    step.updated_at = event.ts;
+                }
+            }
+            "approval.granted:v1" | "approval.denied:v1" | "approval.override:v1" => {
+                let gate = str_field(event, "gateId");
+                if let Some(step) = steps
+                    .iter_mut()
+                    .find(|s| s.state == StepState::WaitingApproval && s.gate_id.as_deref() == gate)
+                {
+                    // Decided; the step completes with its own event
+                    step.state = StepState::Running;
+                    step.updated_at = event.ts;
+                }
+            }
+            _ => {}
+        }
+    }
+    steps
+}
+
+/// Result envelope per step id, from the run's `ritual.completed:v1`
+pub fn step_envelopes(events: &[RitualEvent]) -> BTreeMap<String, Value> {
+    events
+        .iter()
+        .rev()
+        .find(|e| e.event == "ritual.completed:v1")
+        .and_then(|e| e.extra.get("outputs"))
+        .and_then(|o| o.get("steps"))
+        .and_then(|s| s.as_object())
+        .map(|steps| {
+            steps
+                .iter()
+                .map(|(id, v)| (id.clone(), v.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn attempt(event: &RitualEvent) -> u64 {
+    event
+        .extra
+        .get("attempt")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1)
+}
+
+fn str_field<'a>(event: &'a RitualEvent, key: &str) -> Option<&'a str> {
+    event.extra.get(key).and_then(|v| v.as_str())
+}
+
+fn run_url(tenant: &str, run_id: &str) -> String {
+    let run_id = urlencoding::encode(run_id);
+    if tenant == "default" {
+        format!("/api/runs/{}", run_id)
+    } else {
+        format!(
+            "/api/tenants/{}/runs/{}",
+            urlencoding::encode(tenant),
+            run_id
+        )
+    }
+}
+
+/// GET /api/runs/:run_id/overlay
+pub async fn get_run_overlay_api(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+) -> Response {
+    run_overlay(state, "default".to_string(), run_id).await
+}
+
+/// GET /api/tenants/:tenant/runs/:run_id/overlay
+pub async fn get_run_overlay_api_tenant(
+    State(state): State<AppState>,
+    Path((tenant, run_id)): Path<(String, String)>,
+) -> Response {
+    run_overlay(state, tenant, run_id).await
+}
+
+async fn run_overlay(state: AppState, tenant: String, run_id: String) -> Response {
+    match load_run(&state, &tenant, &run_id).await {
+        Ok(run) => Json(RunOverlay::from_run(&run, &run_url(&tenant, &run_id))).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// GET /api/runs/:run_id/steps/:step_id/envelope
+pub async fn get_step_envelope_api(
+    State(state): State<AppState>,
+    Path((run_id, step_id)): Path<(String, String)>,
+) -> Response {
+    step_envelope(state, "default".to_string(), run_id, step_id).await
+}
+
+/// GET /api/tenants/:tenant/runs/:run_id/steps/:step_id/envelope
+pub async fn get_step_envelope_api_tenant(
+    State(state): State<AppState>,
+    Path((tenant, run_id, step_id)): Path<(String, String, String)>,
+) -> Response {
+    step_envelope(state, tenant, run_id, step_id).await
+}
+
+async fn step_envelope(
+    state: AppState,
+    tenant: String,
+    run_id: String,
+    step_id: String,
+) -> Response {
+    let run = match load_run(&state, &tenant, &run_id).await {
+        Ok(run) => run,
+        Err(response) => return response,
+    };
+    match step_envelopes(&run.events).remove(&step_id) {
+        Some(envelope) => Json(envelope).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "error": "No result envelope recorded for step",
+                "runId": run_id,
+                "stepId": step_id,
+            })),
+        )
+            .into_response(),
+    }
+}
+
+async fn load_run(state: &AppState, tenant: &str, run_id: &str) -> Result<RunDetail, Response> {
+    let Some(client) = &state.jetstream_client else {
+        return Err((
+            StatusCode::BAD_GATEWAY,
+            Json(json!({ "error": "JetStream is not available" })),
+        )
+            .into_response());
+    };
+    match client.get_run_detail_for_tenant(tenant, run_id).await {
+        Ok(Some(run)) => Ok(run),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Run not found", "runId": run_id })),
+        )
+            .into_response()),
+        Err(e) => {
+            error!("Failed to retrieve run {} for overlay: {}", run_id, e);
+            Err((
+                StatusCode::BAD_GATEWAY,
+                Json(json!({ "error": format!("Failed to retrieve run detail: {}", e) })),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// GET /api/runs/:run_id/overlay/stream
+pub async fn stream_run_overlay_sse(
+    State(state): State<AppState>,
+    Path(run_id): Path<String>,
+) -> Response {
+    overlay_stream(state, "default".to_string(), run_id)
+}
+
+/// GET /api/tenants/:tenant/runs/:run_id/overlay/stream
+pub async fn stream_run_overlay_sse_tenant(
+    State(state): State<AppState>,
+    Path((tenant, run_id)): Path<(String, String)>,
+) -> Response {
+    overlay_stream(state, tenant, run_id)
+}
+
+fn overlay_stream(state: AppState, tenant: String, run_id: String) -> Response {
+    let hb_secs: u64 = std::env::var("SSE_HEARTBEAT_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(15);
+
+    let body_stream = async_stream::stream! {
+        let _connection = crate::metrics::SseConnection::open("workflow_overlay");
+        let interval = tokio::time::interval(Duration::from_secs(hb_secs.max(1)));
+        let mut heartbeat_stream = IntervalStream::new(interval);
+        let mut seq = 0u64;
+
+        let events = match &state.jetstream_client {
+            Some(client) => client.stream_run_events_for_tenant(&tenant, &run_id).await,
+            None => Err(anyhow::anyhow!("JetStream unavailable")),
+        };
+        match events {
+            Ok(event_stream) => {
+                let mut event_stream = Box::pin(event_stream.fuse());
+                let mut run = RunDetail {
+                    run_id: run_id.clone(),
+                    ritual_id: String::new(),
+                    events: Vec::new(),
+                };
+                let url = run_url(&tenant, &run_id);
+                loop {
+                    tokio::select! {
+                        Some(event_result) = event_stream.next() => {
+                            let mut batch = vec![event_result];
+                            // Fold everything already buffered (e.g. the initial
+                            // snapshot) into one overlay instead of one per event
+                            while let Some(Some(next)) = event_stream.next().now_or_never() {
+                                batch.push(next);
+                            }
+                            for result in batch {
+                                match result {
+                                    Ok(event) => run.events.push(event),
+                                    Err(e) => warn!("Error streaming overlay event: {}", e),
+                                }
+                            }
+                            if run.ritual_id.is_empty() {
+                                if let Some(ritual_id) = run
+                                    .events
+                                    .iter()
+                                    .find_map(|e| e.extra.get("ritualId").and_then(|v| v.as_str()))
+                                {
+                                    run.ritual_id = ritual_id.to_string();
+                                }
+                            }
+                            let overlay = RunOverlay::from_run(&run, &url);
+                            if let Ok(payload) = serde_json::to_string(&overlay) {
+                                yield Ok::<_, std::io::Error>(
+                                    format!("event: overlay\ndata: {}\n\n", payload)
+                                );
+                            }
+                        }
+                        Some(_) = heartbeat_stream.next() => {
+                            let payload = json!({ "type": "heartbeat", "runId": &run_id, "seq": seq });
+                            seq += 1;
+                            yield Ok(format!("event: heartbeat\ndata: {}\n\n", payload));
+                        }
+                        else => break,
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Failed to start overlay stream for run {}: {}", run_id, e);
+                let payload = json!({
+                    "type": "warning",
+                    "runId": &run_id,
+                    "message": "Run events unavailable, streaming heartbeats only"
+                });
+                yield Ok::<_, std::io::Error>(format!("event: warning\ndata: {}\n\n", payload));
+                while heartbeat_stream.next().await.is_some() {
+                    let payload = json!({ "type": "heartbeat", "runId": &run_id, "seq": seq });
+                    seq += 1;
+                    yield Ok(format!("event: heartbeat\ndata: {}\n\n", payload));
+                }
+            }
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/event-stream"),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    headers.insert(header::CONNECTION, HeaderValue::from_static("keep-alive"));
+    (headers, axum::body::Body::from_stream(body_stream)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(ts: &str, body: Value) -> RitualEvent {
+        let mut body = body;
+        body["ts"] = json!(ts);
+        serde_json::from_value(body).unwrap()
+    }
+
+    fn run(events: Vec<RitualEvent>) -> RunDetail {
+        RunDetail {
+            run_id: "run-1".to_string(),
+            ritual_id: "release-ritual".to_string(),
+            events,
+        }
+    }
+
+    fn state_of(overlay: &RunOverlay, step_id: &str) -> Option<StepState> {
+        overlay
+            .steps
+            .iter()
+            .find(|s| s.step_id == step_id)
+            .map(|s| s.state)
+    }
+
+    #[test]
+    fn steps_follow_their_lifecycle_events() {
+        let overlay = RunOverlay::from_run(
+            &run(vec![
+                event(
+                    "2025-01-01T00:00:00Z",
+                    json!({ "event": "ritual.started:v1" }),
+                ),
+                event(
+                    "2025-01-01T00:00:01Z",
+                    json!({ "event": "step.started:v1", "stepId": "build", "kind": "capsule", "attempt": 1 }),
+                ),
+                event(
+                    "2025-01-01T00:00:02Z",
+                    json!({ "event": "step.completed:v1", "stepId": "build", "attempt": 1, "outcome": "failed", "error": "boom" }),
+                ),
+                event(
+                    "2025-01-01T00:00:03Z",
+                    json!({ "event": "step.started:v1", "stepId": "build", "kind": "capsule", "attempt": 2 }),
+                ),
+                event(
+                    "2025-01-01T00:00:04Z",
+                    json!({ "event": "step.started:v1", "stepId": "smoke", "kind": "capsule", "attempt": 1 }),
+                ),
+                event(
+                    "2025-01-01T00:00:05Z",
+                    json!({ "event": "step.completed:v1", "stepId": "smoke", "attempt": 1, "outcome": "failed", "error": "smoke failed" }),
+                ),
+            ]),
+            "/api/runs/run-1",
+        );
+
+        assert_eq!(overlay.status, "Running");
+        assert_eq!(state_of(&overlay, "build"), Some(StepState::Running));
+        assert_eq!(overlay.steps[0].attempt, 2);
+        assert_eq!(overlay.steps[0].error, None);
+        assert_eq!(state_of(&overlay, "smoke"), Some(StepState::Failed));
+        assert_eq!(overlay.steps[1].error.as_deref(), Some("smoke failed"));
+        assert_eq!(state_of(&overlay, "canary"), None);
+    }
+
+    #[test]
+    fn approval_steps_wait_on_their_gate_until_decided() {
+        let mut events = vec![
+            event(
+                "2025-01-01T00:00:00Z",
+                json!({ "event": "step.started:v1", "stepId": "sign-off", "kind": "approval" }),
+            ),
+            event(
+                "2025-01-01T00:00:01Z",
+                json!({ "event": "approval.requested:v1", "gateId": "deploy" }),
+            ),
+        ];
+        let waiting = RunOverlay::from_run(&run(events.clone()), "/api/runs/run-1");
+        assert_eq!(
+            state_of(&waiting, "sign-off"),
+            Some(StepState::WaitingApproval)
+        );
+        assert_eq!(waiting.steps[0].gate_id.as_deref(), Some("deploy"));
+
+        events.push(event(
+            "2025-01-01T00:00:02Z",
+            json!({ "event": "approval.denied:v1", "gateId": "deploy" }),
+        ));
+        let decided = RunOverlay::from_run(&run(events.clone()), "/api/runs/run-1");
+        assert_eq!(state_of(&decided, "sign-off"), Some(StepState::Running));
+
+        events.push(event(
+            "2025-01-01T00:00:02Z",
+            json!({ "event": "step.completed:v1", "stepId": "sign-off", "outcome": "halted", "reason": "approval_denied" }),
+        ));
+        let halted = RunOverlay::from_run(&run(events), "/api/runs/run-1");
+        assert_eq!(state_of(&halted, "sign-off"), Some(StepState::Failed));
+        assert_eq!(halted.steps[0].error.as_deref(), Some("approval_denied"));
+    }
+
+    #[test]
+    fn recorded_envelopes_are_linked() {
+        let overlay = RunOverlay::from_run(
+            &run(vec![
+                event(
+                    "2025-01-01T00:00:00Z",
+                    json!({ "event": "step.started:v1", "stepId": "build", "kind": "capsule" }),
+                ),
+                event(
+                    "2025-01-01T00:00:01Z",
+                    json!({ "event": "step.completed:v1", "stepId": "build", "outcome": "succeeded" }),
+                ),
+                event(
+                    "2025-01-01T00:00:02Z",
+                    json!({ "event": "ritual.completed:v1", "outputs": { "steps": {
+                        "build": { "result": { "success": true } },
+                        "legacy step": { "result": { "success": true } }
+                    } } }),
+                ),
+            ]),
+            &run_url("acme", "run-1"),
+        );
+
+        assert_eq!(overlay.status, "Completed");
+        assert_eq!(
+            overlay.steps[0].envelope_url.as_deref(),
+            Some("/api/tenants/acme/runs/run-1/steps/build/envelope")
+        );
+        // Steps without lifecycle events are still shown from their outputs
+        assert_eq!(
+            state_of(&overlay, "legacy step"),
+            Some(StepState::Completed)
+        );
+        assert_eq!(
+            overlay.steps[1].envelope_url.as_deref(),
+            Some("/api/tenants/acme/runs/run-1/steps/legacy%20step/envelope")
+        );
+    }
+}
@@ -1,22 +1,41 @@
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use operate_ui::auth::AccessControl;
 use tower::util::ServiceExt; // for oneshot
 
-#[tokio::test]
-async fn admin_probe_open_when_no_token() {
-    std::env::remove_var("ADMIN_TOKEN");
-    // Create AppState directly to ensure clean environment
-    let jetstream_client = None; // No JetStream for this test
-    let tera = tera::Tera::new("nonexistent/*").unwrap(); // Empty Tera
-    let state = operate_ui::AppState {
-        jetstream_client,
-        tera,
-        admin_token: None, // Explicitly no admin token
+fn state(access_control: AccessControl) -> operate_ui::AppState {
+    operate_ui::AppState {
+        jetstream_client: None,                          // No JetStream for this test
+        tera: tera::Tera::new("nonexistent/*").unwrap(), // Empty Tera
+        access_control,
         bundle_loader: runtime::bundle::BundleLoader::new(None),
         app_pack_registry: None,
         feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    }
+}
+
+fn token(roles: &[&str]) -> String {
+    let claims = jwt_auth::Claims {
+        sub: "ops@example.com".to_string(),
+        exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+        roles: roles.iter().map(|r| r.to_string()).collect(),
+        tenants: vec!["*".to_string()],
+        ..Default::default()
     };
-    let app = operate_ui::create_app(state);
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(b"secret"),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn admin_probe_open_when_auth_disabled() {
+    let app = operate_ui::create_app(state(AccessControl::Disabled));
     let response = app
         .oneshot(
             Request::builder()
@@ -30,41 +49,25 @@ async fn admin_probe_open_when_no_token() {
 }
 
 #[tokio::test]
-async fn admin_probe_requires_token_when_set() {
-    // Create AppState with explicit admin token
-    let jetstream_client = None; // No JetStream for this test
-    let tera = tera::Tera::new("nonexistent/*").unwrap(); // Empty Tera
-    let state = operate_ui::AppState {
-        jetstream_client,
-        tera,
-        admin_token: Some("secret".to_string()), // Explicitly set admin token
-        bundle_loader: runtime::bundle::BundleLoader::new(None),
-        app_pack_registry: None,
-        feature_flags: std::collections::HashSet::new(),
-    };
-    let app = operate_ui::create_app(state);
-    // missing token -> 401
-    let resp = app
-        .clone()
-        .oneshot(
-            Request::builder()
-                .uri("/admin/templates/report")
-                .body(Body::empty())
-                .unwrap(),
-        )
-        .await
-        .unwrap();
-    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
-    // correct token -> 200
-    let resp_ok = app
-        .oneshot(
-            Request::builder()
-                .uri("/admin/templates/report")
-                .header("X-Admin-Token", "secret")
-                .body(Body::empty())
-                .unwrap(),
-        )
-        .await
-        .unwrap();
-    assert_eq!(resp_ok.status(), StatusCode::OK);
+async fn admin_probe_requires_admin_role_when_jwt_auth_enabled() {
+    let config = jwt_auth::JwtConfig::new("secret".to_string(), jsonwebtoken::Algorithm::HS256);
+    let app = operate_ui::create_app(state(AccessControl::Jwt(config)));
+
+    // missing token -> 401, operator -> 403, admin -> 200
+    for (bearer, expected) in [
+        (None, StatusCode::UNAUTHORIZED),
+        (Some(token(&["operator"])), StatusCode::FORBIDDEN),
+        (Some(token(&["admin"])), StatusCode::OK),
+    ] {
+        let mut request = Request::builder().uri("/admin/templates/report");
+        if let Some(bearer) = bearer {
+            request = request.header("Authorization", format!("Bearer {}", bearer));
+        }
+        let resp = app
+            .clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), expected);
+    }
 }
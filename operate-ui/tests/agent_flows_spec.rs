@@ -44,10 +44,12 @@ fn create_test_app_with_flags(flags: Vec<&str>) -> axum::Router {
     let state = AppState {
         jetstream_client: None,
         tera,
-        admin_token: None,
+        access_control: Default::default(),
         bundle_loader: runtime::bundle::BundleLoader::new(None),
         app_pack_registry: None,
         feature_flags,
+        run_index: Default::default(),
+        tenant_quotas: None,
     };
 
     operate_ui::create_app(state)
@@ -150,3 +150,73 @@ async fn grant_then_grant_is_noop_and_deny_conflicts() -> Result<()> {
     assert_eq!(terminals, 1);
     Ok(())
 }
+
+#[tokio::test]
+#[ignore]
+async fn delegate_grants_on_behalf_of_approver_within_window() -> Result<()> {
+    std::env::set_var("APPROVER_ALLOWLIST", "ops@example.com");
+    std::env::set_var("RITUAL_STREAM_NAME", "RITUAL_EVENTS");
+    ensure_stream().await?;
+    let port = start_ui().await?;
+    let base = format!("http://127.0.0.1:{}", port);
+
+    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".into());
+    let client = async_nats::connect(url).await?;
+    let js = jetstream::new(client);
+
+    let ritual = "echo-ritual";
+    let run = format!("rr-delegation-{}", uuid::Uuid::new_v4());
+    let delegate = format!("oncall-{}@example.com", uuid::Uuid::new_v4().simple());
+    publish_requested(&js, ritual, &run, "gate-1").await?;
+    publish_requested(&js, ritual, &run, "gate-2").await?;
+
+    let http = reqwest::Client::new();
+    let grant = |gate: &'static str| {
+        http.post(format!("{}/api/approvals/{}/{}/grant", base, run, gate))
+            .header("X-Requested-With", "XMLHttpRequest")
+            .json(&serde_json::json!({ "approver": delegate }))
+            .send()
+    };
+
+    // Without a delegation the delegate is not an approver
+    assert_eq!(grant("gate-1").await?.status(), StatusCode::FORBIDDEN);
+
+    let created = http
+        .post(format!("{}/api/delegations", base))
+        .header("X-Requested-With", "XMLHttpRequest")
+        .json(&serde_json::json!({
+            "delegator": "ops@example.com",
+            "delegate": delegate,
+            "endsAt": (Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+            "reason": "vacation"
+        }))
+        .send()
+        .await?;
+    assert_eq!(created.status(), StatusCode::CREATED);
+
+    let granted = grant("gate-1").await?;
+    assert_eq!(granted.status(), StatusCode::OK);
+    let body: serde_json::Value = granted.json().await?;
+    assert_eq!(body["approver"], delegate.as_str());
+    assert_eq!(body["onBehalfOf"], "ops@example.com");
+
+    // Ending the delegation early revokes the delegate's authority
+    let removed = http
+        .delete(format!(
+            "{}/api/delegations/{}/{}",
+            base, "ops@example.com", delegate
+        ))
+        .header("X-Requested-With", "XMLHttpRequest")
+        .send()
+        .await?;
+    assert_eq!(removed.status(), StatusCode::NO_CONTENT);
+    assert_eq!(grant("gate-2").await?.status(), StatusCode::FORBIDDEN);
+
+    let events = fetch_events_for_run(&js, ritual, &run).await?;
+    let granted = events
+        .iter()
+        .find(|e| e.get("event").and_then(|v| v.as_str()) == Some("approval.granted:v1"))
+        .expect("granted event");
+    assert_eq!(granted["onBehalfOf"], "ops@example.com");
+    Ok(())
+}
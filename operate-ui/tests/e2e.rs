@@ -61,10 +61,12 @@ fn create_test_app() -> axum::Router {
     let state = AppState {
         jetstream_client: None, // Simulate JetStream unavailable for testing
         tera,
-        admin_token: None,
+        access_control: Default::default(),
         bundle_loader: runtime::bundle::BundleLoader::new(None),
         app_pack_registry: None,
         feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
     };
 
     operate_ui::create_app(state)
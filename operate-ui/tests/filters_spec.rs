@@ -8,10 +8,12 @@ async fn list_runs_api_rejects_invalid_status() {
     let state = operate_ui::AppState {
         jetstream_client: None,
         tera: tera::Tera::new("nonexistent/*").unwrap(),
-        admin_token: None,
+        access_control: Default::default(),
         bundle_loader: runtime::bundle::BundleLoader::new(None),
         app_pack_registry: None,
         feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
     };
     let app = operate_ui::create_app(state);
     let resp = app
@@ -31,10 +33,12 @@ async fn list_runs_api_rejects_invalid_limit() {
     let state = operate_ui::AppState {
         jetstream_client: None,
         tera: tera::Tera::new("nonexistent/*").unwrap(),
-        admin_token: None,
+        access_control: Default::default(),
         bundle_loader: runtime::bundle::BundleLoader::new(None),
         app_pack_registry: None,
         feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
     };
     let app = operate_ui::create_app(state);
     for bad in [0usize, 1001usize] {
@@ -51,3 +55,212 @@ async fn list_runs_api_rejects_invalid_limit() {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 }
+
+#[tokio::test]
+async fn list_decisions_api_rejects_unknown_kind() {
+    let state = operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: Default::default(),
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    };
+    let app = operate_ui::create_app(state);
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/tenants/default/decisions?kind=bogus")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn list_scale_decisions_api_rejects_invalid_filters() {
+    for query in ["outcome=exploded", "limit=0", "limit=5000"] {
+        let app = operate_ui::create_app(state_with_index(Default::default()));
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/tenants/default/scale/decisions?{}", query))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "query {}", query);
+    }
+}
+
+fn state_with_index(run_index: operate_ui::run_index::RunIndex) -> operate_ui::AppState {
+    operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: Default::default(),
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index,
+        tenant_quotas: None,
+    }
+}
+
+#[tokio::test]
+async fn list_runs_api_rejects_invalid_search_facets() {
+    let app = operate_ui::create_app(state_with_index(Default::default()));
+    for query in [
+        "gate=maybe",
+        "since=last-tuesday",
+        "until=2025-13-40",
+        "cursor=nonsense",
+        "lastEventId=latest",
+    ] {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/runs?{}", query))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", query);
+    }
+}
+
+#[tokio::test]
+async fn list_runs_api_searches_the_run_index_by_gate_time_and_text() {
+    let run_index = operate_ui::run_index::RunIndex::with_capacity(100);
+    let events = [
+        (
+            "demon.ritual.v1.acme.release.run-tue.events",
+            serde_json::json!({"event": "ritual.started:v1", "ts": "2025-01-07T09:00:00Z"}),
+        ),
+        (
+            "demon.ritual.v1.acme.release.run-tue.events",
+            serde_json::json!({"event": "approval.granted:v1", "ts": "2025-01-07T09:05:00Z", "gateId": "deploy", "approver": "ops@example.com"}),
+        ),
+        (
+            "demon.ritual.v1.acme.release.run-tue.events",
+            serde_json::json!({"event": "ritual.failed:v1", "ts": "2025-01-07T09:10:00Z", "error": "quota exceeded"}),
+        ),
+        (
+            "demon.ritual.v1.acme.release.run-wed.events",
+            serde_json::json!({"event": "ritual.failed:v1", "ts": "2025-01-08T09:10:00Z", "error": "timeout"}),
+        ),
+    ];
+    for (subject, payload) in &events {
+        run_index.apply(subject, payload);
+    }
+    run_index.mark_ready();
+    let app = operate_ui::create_app(state_with_index(run_index));
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/tenants/acme/runs?status=failed&since=2025-01-07&until=2025-01-08&gate=granted&q=quota")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let runs = json["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["runId"], "run-tue");
+    assert_eq!(runs[0]["status"], "Failed");
+}
+
+#[tokio::test]
+async fn list_runs_api_pages_with_cursors_and_answers_unchanged_polls_with_304() {
+    let run_index = operate_ui::run_index::RunIndex::with_capacity(100);
+    for run in ["run-1", "run-2", "run-3"] {
+        run_index.apply(
+            &format!("demon.ritual.v1.acme.release.{}.events", run),
+            &serde_json::json!({"event": "ritual.started:v1", "ts": "2025-01-07T09:00:00Z"}),
+        );
+    }
+    run_index.mark_ready();
+    let app = operate_ui::create_app(state_with_index(run_index.clone()));
+    let get = |uri: &str, if_none_match: Option<&str>| {
+        let mut request = Request::builder().uri(uri);
+        if let Some(etag) = if_none_match {
+            request = request.header("If-None-Match", etag);
+        }
+        app.clone().oneshot(request.body(Body::empty()).unwrap())
+    };
+
+    let resp = get("/api/tenants/acme/runs?limit=2", None).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["cache-control"], "private, no-cache");
+    let etag = resp.headers()["etag"].to_str().unwrap().to_string();
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["runs"].as_array().unwrap().len(), 2);
+    assert_eq!(json["lastEventId"], "3");
+    let cursor = json["nextCursor"].as_str().unwrap();
+
+    let resp = get(
+        &format!(
+            "/api/tenants/acme/runs?limit=2&cursor={}",
+            urlencoding::encode(cursor)
+        ),
+        None,
+    )
+    .await
+    .unwrap();
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["runs"][0]["runId"], "run-3");
+    assert!(json["nextCursor"].is_null());
+
+    let resp = get("/api/tenants/acme/runs?limit=2", Some(&etag))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+
+    // A new event changes the validator, and Last-Event-ID returns only what changed
+    run_index.apply(
+        "demon.ritual.v1.acme.release.run-2.events",
+        &serde_json::json!({"event": "ritual.completed:v1", "ts": "2025-01-07T09:05:00Z"}),
+    );
+    let resp = get("/api/tenants/acme/runs?limit=2", Some(&etag))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/tenants/acme/runs")
+                .header("Last-Event-ID", "3")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["x-last-event-id"], "4");
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let runs = json["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["runId"], "run-2");
+}
@@ -158,3 +158,114 @@ async fn test_schema_path_traversal_prevention() {
     // Should fail to find the file (404) not expose system files
     assert_eq!(response.status_code(), 404);
 }
+
+#[tokio::test]
+async fn test_schema_metadata_includes_wizard_steps() {
+    let state = AppState::new().await;
+    let app = create_app(state);
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .get("/api/schema/metadata")
+        .add_query_param("schemaName", "approval.requested.v1")
+        .await;
+
+    assert_eq!(response.status_code(), 200);
+    let body: serde_json::Value = response.json();
+    let steps = body["steps"].as_array().expect("steps array");
+    // No `x-wizard` extension: one page holding every property
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0]["id"], "form");
+    assert_eq!(steps[0]["properties"].as_array().unwrap().len(), 9);
+}
+
+#[tokio::test]
+async fn test_form_submit_validates_against_named_schema() {
+    let state = AppState::new().await;
+    let app = create_app(state);
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/api/form/submit")
+        .json(&json!({
+            "schemaName": "approval.requested.v1",
+            "data": { "event": "approval.requested:v1", "requester": 42 }
+        }))
+        .await;
+
+    assert_eq!(response.status_code(), 422);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["status"], "invalid");
+    let paths: Vec<&str> = body["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|e| e["path"].as_str())
+        .collect();
+    assert!(paths.contains(&"/requester"), "{paths:?}");
+    assert!(paths.contains(&"/reason"), "{paths:?}");
+}
+
+#[tokio::test]
+async fn test_form_submit_accepts_valid_step_and_full_form() {
+    let state = AppState::new().await;
+    let app = create_app(state);
+    let server = TestServer::new(app).unwrap();
+    let data = json!({
+        "event": "approval.requested:v1",
+        "ts": "2025-01-01T00:00:00Z",
+        "tenantId": "default",
+        "runId": "run-1",
+        "ritualId": "ritual-1",
+        "gateId": "gate-1",
+        "requester": "ops@example.com",
+        "reason": "deploy"
+    });
+
+    let step = server
+        .post("/api/form/submit")
+        .json(&json!({ "schemaName": "approval.requested.v1", "step": "form", "data": data }))
+        .await;
+    assert_eq!(step.status_code(), 200);
+    let body: serde_json::Value = step.json();
+    assert_eq!(body["status"], "valid");
+    assert!(body["nextStep"].is_null());
+
+    let full = server
+        .post("/api/form/submit")
+        .json(&json!({ "schemaName": "approval.requested.v1", "data": data }))
+        .await;
+    assert_eq!(full.status_code(), 200);
+    let body: serde_json::Value = full.json();
+    assert_eq!(body["status"], "received");
+    assert_eq!(body["data"]["reason"], "deploy");
+}
+
+#[tokio::test]
+async fn test_form_submit_rejects_unknown_step() {
+    let state = AppState::new().await;
+    let app = create_app(state);
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .post("/api/form/submit")
+        .json(&json!({ "schemaName": "approval.requested.v1", "step": "missing", "data": {} }))
+        .await;
+
+    assert_eq!(response.status_code(), 400);
+}
+
+#[tokio::test]
+async fn test_form_renderer_includes_wizard_and_conditional_controls() {
+    let state = AppState::new().await;
+    let app = create_app(state);
+    let server = TestServer::new(app).unwrap();
+
+    let html = server.get("/ui/form").await.text();
+
+    assert!(html.contains("id=\"wizardSteps\""));
+    assert!(html.contains("id=\"nextBtn\""));
+    assert!(html.contains("createBranchChooser"));
+    assert!(html.contains("updateConditionalSections"));
+    assert!(html.contains("renderArrayRow"));
+}
@@ -57,3 +57,69 @@ async fn test_graph_viewer_with_query_params() {
     assert!(body_str.contains("test-ns"));
     assert!(body_str.contains("test-graph"));
 }
+
+#[tokio::test]
+async fn test_graph_viewer_page_has_canvas_and_timeline() {
+    let state = AppState::new().await;
+    let app = create_app(state);
+
+    let request = Request::builder()
+        .uri("/graph")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body_str = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(body_str.contains("id=\"graphCanvas\""));
+    assert!(body_str.contains("id=\"timelineSlider\""));
+    assert!(body_str.contains("id=\"nodePanel\""));
+    // Data comes from the Operate UI's own /api/graph endpoints
+    assert!(body_str.contains("const GRAPH_API = \"/api/graph\""));
+}
+
+#[tokio::test]
+async fn test_graph_api_relays_to_runtime() {
+    // Stand-in runtime that echoes the path and query it was called with
+    let runtime = axum::Router::new().route(
+        "/api/graph/*rest",
+        axum::routing::get(|uri: axum::http::Uri| async move {
+            axum::Json(serde_json::json!({ "seen": uri.to_string() }))
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move { axum::serve(listener, runtime).await });
+    std::env::set_var("RUNTIME_API_URL", format!("http://{}", addr));
+
+    let app = create_app(AppState::new().await);
+    let request = Request::builder()
+        .uri("/api/graph/snapshot?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1&commitId=abc")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        json["seen"],
+        "/api/graph/snapshot?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1&commitId=abc"
+    );
+
+    // Runtime gone: the relay answers 502 instead of hanging or panicking
+    server.abort();
+    let _ = server.await;
+    let request = Request::builder()
+        .uri("/api/graph/tags?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+    std::env::remove_var("RUNTIME_API_URL");
+}
@@ -0,0 +1,58 @@
+//! `/metrics` exposes request latency by matched route and template render
+//! failures in the Prometheus text format.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use tower::util::ServiceExt; // for oneshot
+
+fn app() -> axum::Router {
+    operate_ui::create_app(operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: Default::default(),
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    })
+}
+
+async fn get(uri: &str) -> (StatusCode, String) {
+    let response = app()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn given_requests_when_scraping_metrics_then_latency_is_labelled_by_route_pattern() {
+    get("/api/tenants/acme/runs/run-123").await;
+
+    let (status, body) = get("/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("operate_ui_http_request_duration_seconds_bucket"));
+    assert!(body.contains(r#"route="/api/tenants/:tenant/runs/:run_id""#));
+    assert!(!body.contains("run-123"));
+}
+
+#[tokio::test]
+async fn given_missing_template_when_rendering_runs_page_then_render_error_is_counted() {
+    get("/runs").await;
+
+    let (_, body) = get("/metrics").await;
+    assert!(body.contains(r#"operate_ui_template_render_errors_total{template="runs_list.html"}"#));
+}
+
+#[tokio::test]
+async fn given_unknown_path_when_scraping_metrics_then_route_is_unmatched() {
+    get("/definitely/not/a/route").await;
+
+    let (_, body) = get("/metrics").await;
+    assert!(body.contains(r#"route="unmatched""#));
+}
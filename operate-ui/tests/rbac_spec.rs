@@ -0,0 +1,346 @@
+//! Role checks on runs, approvals and override endpoints with OPERATE_UI_AUTH=jwt.
+//! Requests that pass the role check reach the handler, which then rejects them
+//! for reasons unrelated to auth (no JetStream, missing CSRF header).
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use operate_ui::auth::AccessControl;
+use tower::util::ServiceExt; // for oneshot
+
+const SECRET: &[u8] = b"rbac-test-secret";
+
+fn app() -> axum::Router {
+    let config = jwt_auth::JwtConfig::new(
+        String::from_utf8(SECRET.to_vec()).unwrap(),
+        jsonwebtoken::Algorithm::HS256,
+    );
+    operate_ui::create_app(operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: AccessControl::Jwt(config),
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    })
+}
+
+fn token(role: &str, tenant: &str) -> String {
+    let claims = jwt_auth::Claims {
+        sub: format!("{}@example.com", role),
+        exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+        roles: vec![role.to_string()],
+        tenants: vec![tenant.to_string()],
+        ..Default::default()
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(SECRET),
+    )
+    .unwrap()
+}
+
+async fn status(method: &str, uri: &str, bearer: Option<String>) -> StatusCode {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+    if let Some(bearer) = bearer {
+        request = request.header("Authorization", format!("Bearer {}", bearer));
+    }
+    let body = if method == "POST" {
+        Body::from(r#"{"approver":"ops@example.com","reason":"r","note":"n"}"#)
+    } else {
+        Body::empty()
+    };
+    app()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn given_viewer_token_when_reading_runs_then_only_own_tenant_is_visible() {
+    let uri = "/api/tenants/acme/runs";
+    assert_eq!(status("GET", uri, None).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        status("GET", uri, Some("not-a-jwt".to_string())).await,
+        StatusCode::UNAUTHORIZED
+    );
+    // Passed RBAC; no JetStream behind it
+    assert_eq!(
+        status("GET", uri, Some(token("viewer", "acme"))).await,
+        StatusCode::BAD_GATEWAY
+    );
+    assert_eq!(
+        status("GET", uri, Some(token("viewer", "globex"))).await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn given_viewer_token_when_granting_approval_then_forbidden_but_operator_passes() {
+    let uri = "/api/tenants/acme/approvals/run-1/deploy/grant";
+    assert_eq!(
+        status("POST", uri, Some(token("viewer", "acme"))).await,
+        StatusCode::FORBIDDEN
+    );
+    // Passed RBAC; the handler then wants X-Requested-With
+    assert_eq!(
+        status("POST", uri, Some(token("operator", "acme"))).await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        status("POST", uri, Some(token("operator", "globex"))).await,
+        StatusCode::FORBIDDEN
+    );
+    // Legacy routes act on the default tenant
+    assert_eq!(
+        status(
+            "POST",
+            "/api/approvals/run-1/deploy/deny",
+            Some(token("operator", "default"))
+        )
+        .await,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn given_operator_token_when_overriding_approval_then_forbidden_but_admin_passes() {
+    let uri = "/api/tenants/acme/approvals/run-1/deploy/override";
+    assert_eq!(
+        status("POST", uri, Some(token("operator", "acme"))).await,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        status("POST", uri, Some(token("admin", "*"))).await,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn given_operator_token_when_approving_as_someone_else_then_forbidden() {
+    let decide = |uri: &'static str, approver: &'static str| async move {
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("Content-Type", "application/json")
+                    .header("X-Requested-With", "XMLHttpRequest")
+                    .header("Authorization", format!("Bearer {}", token("admin", "*")))
+                    .body(Body::from(format!(
+                        r#"{{"approver":"{}","reason":"r","note":"n"}}"#,
+                        approver
+                    )))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        (
+            status,
+            body["error"].as_str().unwrap_or_default().to_string(),
+        )
+    };
+
+    for uri in [
+        "/api/approvals/run-1/deploy/grant",
+        "/api/approvals/run-1/deploy/deny",
+        "/api/tenants/acme/approvals/run-1/deploy/grant",
+        "/api/tenants/acme/approvals/run-1/deploy/deny",
+        "/api/tenants/acme/approvals/run-1/deploy/override",
+    ] {
+        let (status, error) = decide(uri, "ops@example.com").await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+        assert_eq!(error, "approver must match the authenticated subject");
+
+        // Naming a delegate is held to the same check before any delegation
+        // is looked up
+        let (status, error) = decide(uri, "delegate@example.com").await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+        assert_eq!(error, "approver must match the authenticated subject");
+
+        // Acting as themselves gets past the identity check
+        let (_, error) = decide(uri, "ADMIN@example.com").await;
+        assert_ne!(error, "approver must match the authenticated subject");
+    }
+}
+
+#[tokio::test]
+async fn given_tenant_token_when_listing_tenants_then_only_own_tenants_are_returned() {
+    assert_eq!(
+        status("GET", "/api/tenants", None).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let response = app()
+        .oneshot(
+            Request::builder()
+                .uri("/api/tenants")
+                .header(
+                    "Authorization",
+                    format!("Bearer {}", token("viewer", "acme")),
+                )
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let list: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let names: Vec<&str> = list["tenants"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["tenant"].as_str().unwrap())
+        .collect();
+    // acme has no runs yet but the token names it; default is not covered
+    assert_eq!(names, vec!["acme"]);
+
+    assert_eq!(
+        status("GET", "/api/tenants/acme", Some(token("viewer", "acme"))).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        status("GET", "/admin/tenants", Some(token("operator", "*"))).await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn given_admin_token_when_reading_system_streams_then_reports_jetstream_unavailable() {
+    assert_eq!(
+        status("GET", "/api/system/streams", Some(token("operator", "*"))).await,
+        StatusCode::FORBIDDEN
+    );
+
+    let response = app()
+        .oneshot(
+            Request::builder()
+                .uri("/api/system/streams")
+                .header("Authorization", format!("Bearer {}", token("admin", "*")))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let system: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(system["status"], "critical");
+    assert_eq!(system["error"], "JetStream is not available");
+    assert_eq!(system["thresholds"]["pendingWarning"], 1000);
+}
+
+#[tokio::test]
+async fn given_operator_token_when_using_run_admin_actions_then_forbidden_but_admin_needs_a_reason()
+{
+    let uris = [
+        "/api/tenants/acme/runs/run-1/abandon",
+        "/api/tenants/acme/runs/run-1/gates/deploy/reemit",
+        "/api/tenants/acme/preview-runs/purge",
+    ];
+    for uri in uris {
+        assert_eq!(
+            status("POST", uri, Some(token("operator", "acme"))).await,
+            StatusCode::FORBIDDEN,
+            "{}",
+            uri
+        );
+
+        let response = app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(uri)
+                    .header("Content-Type", "application/json")
+                    .header("X-Requested-With", "XMLHttpRequest")
+                    .header(
+                        "Authorization",
+                        format!("Bearer {}", token("admin", "acme")),
+                    )
+                    .body(Body::from(
+                        r#"{"requestedBy":"admin@example.com","reason":" "}"#,
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"], "reason is required");
+    }
+}
+
+#[tokio::test]
+async fn given_tenant_token_when_saving_view_for_other_tenant_then_forbidden() {
+    let save = |tenant: &'static str, bearer: String| async move {
+        app()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/api/views")
+                    .header("Content-Type", "application/json")
+                    .header("X-Requested-With", "XMLHttpRequest")
+                    .header("Authorization", format!("Bearer {}", bearer))
+                    .body(Body::from(
+                        serde_json::json!({ "name": "Failed", "tenant": tenant }).to_string(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    };
+    assert_eq!(
+        save("globex", token("viewer", "acme")).await,
+        StatusCode::FORBIDDEN
+    );
+    // Passed the tenant check; no JetStream to store it in
+    assert_eq!(
+        save("acme", token("viewer", "acme")).await,
+        StatusCode::BAD_GATEWAY
+    );
+}
+
+#[tokio::test]
+async fn given_misconfigured_auth_when_calling_guarded_endpoint_then_server_error() {
+    let app = operate_ui::create_app(operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: AccessControl::Misconfigured,
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    });
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/runs")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -0,0 +1,66 @@
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serial_test::serial;
+use tower::util::ServiceExt; // for oneshot
+
+fn app_without_jetstream() -> axum::Router {
+    let state = operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: Default::default(),
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    };
+    operate_ui::create_app(state)
+}
+
+fn cancel_request(requested_by: &str, csrf: bool) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri("/api/runs/run-123/cancel")
+        .header("content-type", "application/json");
+    if csrf {
+        builder = builder.header("X-Requested-With", "XMLHttpRequest");
+    }
+    builder
+        .body(Body::from(
+            serde_json::json!({ "requestedBy": requested_by, "reason": "runaway" }).to_string(),
+        ))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn cancel_run_api_requires_csrf_header() {
+    let resp = app_without_jetstream()
+        .oneshot(cancel_request("ops@example.com", false))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial]
+async fn cancel_run_api_rejects_requester_outside_allowlist() {
+    std::env::set_var("APPROVER_ALLOWLIST", "ops@example.com");
+    let resp = app_without_jetstream()
+        .oneshot(cancel_request("intruder@example.com", true))
+        .await
+        .unwrap();
+    std::env::remove_var("APPROVER_ALLOWLIST");
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+#[serial]
+async fn cancel_run_api_without_jetstream_is_bad_gateway() {
+    std::env::set_var("APPROVER_ALLOWLIST", "ops@example.com");
+    let resp = app_without_jetstream()
+        .oneshot(cancel_request("ops@example.com", true))
+        .await
+        .unwrap();
+    std::env::remove_var("APPROVER_ALLOWLIST");
+    assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+}
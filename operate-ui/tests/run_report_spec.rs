@@ -0,0 +1,89 @@
+//! `GET /api/runs/:run_id/report` bundles a run into a downloadable JSON or
+//! HTML file.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use operate_ui::report::{render_html, RunReport};
+use tower::util::ServiceExt; // for oneshot
+
+fn app() -> axum::Router {
+    operate_ui::create_app(operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: Default::default(),
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    })
+}
+
+async fn get(uri: &str) -> (StatusCode, serde_json::Value) {
+    let response = app()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn given_unknown_format_when_requesting_report_then_bad_request() {
+    let (status, body) = get("/api/runs/run-1/report?format=pdf").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("'json' or 'html'"));
+}
+
+#[tokio::test]
+async fn given_no_jetstream_when_requesting_report_then_bad_gateway() {
+    let (status, body) = get("/api/tenants/acme/runs/run-1/report?format=html").await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["error"], "JetStream is not available");
+}
+
+#[test]
+fn given_completed_run_when_rendering_html_report_then_every_section_is_included() {
+    let pattern = format!("{}/templates/**/*.html", env!("CARGO_MANIFEST_DIR"));
+    let mut tera = tera::Tera::new(&pattern).expect("templates should compile");
+    tera.register_filter(
+        "json",
+        |value: &tera::Value,
+         _: &std::collections::HashMap<String, tera::Value>|
+         -> tera::Result<tera::Value> {
+            Ok(tera::Value::String(
+                serde_json::to_string_pretty(value).unwrap(),
+            ))
+        },
+    );
+
+    let run: operate_ui::jetstream::RunDetail = serde_json::from_value(serde_json::json!({
+        "runId": "run-7",
+        "ritualId": "release",
+        "events": [
+            { "ts": "2025-01-01T00:00:00Z", "event": "step.started:v1", "stepId": "build", "kind": "capsule", "attempt": 1, "queuedMs": 0 },
+            { "ts": "2025-01-01T00:00:01Z", "event": "approval.requested:v1", "gateId": "prod", "requester": "dev@example.com" },
+            { "ts": "2025-01-01T00:00:02Z", "event": "approval.denied:v1", "gateId": "prod", "approver": "ops@example.com", "reason": "freeze" },
+            { "ts": "2025-01-01T00:00:03Z", "event": "step.completed:v1", "stepId": "build", "attempt": 1, "outcome": "succeeded", "durationMs": 3000 },
+            { "ts": "2025-01-01T00:00:04Z", "event": "ritual.completed:v1", "reason": "approval_denied", "outputs": { "steps": {
+                "build": {
+                    "result": { "success": true, "data": { "artifacts": ["/workspace/.artifacts/app.tar"] } },
+                    "diagnostics": [{ "level": "warning", "message": "cache miss" }]
+                }
+            } } }
+        ]
+    }))
+    .unwrap();
+
+    let html = render_html(&tera, &RunReport::from_run("acme", run)).expect("report renders");
+    assert!(html.contains("<code>run-7</code>"));
+    assert!(html.contains("Completed (approval_denied)"));
+    assert!(html.contains(r#"<td class="status-denied">denied</td>"#));
+    assert!(html.contains("cache miss"));
+    // Tera escapes `/` in autoescaped output
+    assert!(html.contains("&#x2F;workspace&#x2F;.artifacts&#x2F;app.tar"));
+    assert!(html.contains("approval.requested:v1"));
+}
@@ -0,0 +1,70 @@
+//! `/api/views` checks requests before touching storage; without JetStream
+//! valid requests surface as 502 so the runs page falls back to local storage.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use tower::util::ServiceExt; // for oneshot
+
+fn app() -> axum::Router {
+    operate_ui::create_app(operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: Default::default(),
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    })
+}
+
+async fn send(
+    method: &str,
+    uri: &str,
+    body: Option<serde_json::Value>,
+    csrf: bool,
+) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+    if csrf {
+        request = request.header("X-Requested-With", "XMLHttpRequest");
+    }
+    let body = body.map_or_else(Body::empty, |b| Body::from(b.to_string()));
+    let response = app().oneshot(request.body(body).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn given_missing_csrf_header_when_saving_view_then_bad_request() {
+    let view = serde_json::json!({ "name": "Failed", "filters": { "status": "Failed" } });
+    let (status, body) = send("POST", "/api/views", Some(view), false).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "X-Requested-With header required");
+}
+
+#[tokio::test]
+async fn given_unknown_filter_when_saving_view_then_bad_request() {
+    let view = serde_json::json!({ "name": "Sorted", "filters": { "sort": "asc" } });
+    let (status, body) = send("POST", "/api/views", Some(view), true).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "unknown filter 'sort'");
+}
+
+#[tokio::test]
+async fn given_no_jetstream_when_using_views_then_bad_gateway() {
+    let view = serde_json::json!({ "name": "Failed", "tenant": "acme", "filters": { "status": "Failed" } });
+    let (status, body) = send("POST", "/api/views", Some(view), true).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["error"], "JetStream is not available");
+
+    let (status, _) = send("GET", "/api/views?tenant=acme", None, false).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    let (status, _) = send("GET", "/views/0123abcd", None, false).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+}
@@ -125,3 +125,70 @@ async fn sse_stream_handles_invalid_run_id() {
         );
     }
 }
+
+#[tokio::test]
+async fn runs_stream_is_served_as_sse_not_as_a_run_lookup() {
+    std::env::set_var("SSE_HEARTBEAT_SECONDS", "1");
+    let state = AppState::new().await;
+    let app = create_app(state);
+
+    for uri in ["/api/runs/stream", "/api/tenants/acme/runs/stream"] {
+        let resp = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .method("GET")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status(), 200, "{uri}");
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "text/event-stream",
+            "{uri}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn runs_stream_emits_init_or_degraded_warning() {
+    std::env::set_var("SSE_HEARTBEAT_SECONDS", "1");
+    let state = AppState::new().await;
+    let jetstream_available = state.jetstream_client.is_some();
+    let app = create_app(state);
+
+    let resp = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/tenants/acme/runs/stream")
+                .method("GET")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = resp.into_body();
+    let first = tokio::time::timeout(Duration::from_secs(3), async {
+        let chunk = body.into_data_stream().next().await.unwrap().unwrap();
+        String::from_utf8_lossy(&chunk).to_string()
+    })
+    .await
+    .expect("runs stream should emit an event right away");
+
+    if jetstream_available {
+        assert!(
+            first.contains("event: init") || first.contains("event: error"),
+            "unexpected first event: {first}"
+        );
+    } else {
+        assert!(
+            first.contains("event: warning"),
+            "unexpected first event: {first}"
+        );
+    }
+}
@@ -73,3 +73,235 @@ async fn templates_compile_and_render_minimal() {
         .expect("run_detail.html should render with approvals");
     assert!(html3.contains("Denied — expired"));
 }
+
+#[tokio::test]
+async fn runs_list_wires_live_updates_for_the_tenant() {
+    let pattern = format!("{}/templates/**/*.html", env!("CARGO_MANIFEST_DIR"));
+    let tera = tera::Tera::new(&pattern).expect("templates should compile");
+
+    let mut ctx = tera::Context::new();
+    ctx.insert(
+        "runs",
+        &vec![serde_json::json!({
+            "runId": "run-1",
+            "ritualId": "release",
+            "startTs": "2025-01-01T00:00:00Z",
+            "status": "Running"
+        })],
+    );
+    ctx.insert("error", &Option::<String>::None);
+    ctx.insert("jetstream_available", &true);
+    ctx.insert("current_page", &"runs");
+    ctx.insert("tenant", &"acme");
+
+    let html = tera
+        .render("runs_list.html", &ctx)
+        .expect("runs_list.html should render");
+    assert!(html.contains(r#"<tr data-run-id="run-1">"#));
+    assert!(html.contains(r#"const tenant = "acme";"#));
+    assert!(html.contains("new EventSource(streamUrl)"));
+}
+
+#[tokio::test]
+async fn run_detail_renders_step_timeline_with_slow_steps_highlighted() {
+    let pattern = format!("{}/templates/**/*.html", env!("CARGO_MANIFEST_DIR"));
+    let mut tera = tera::Tera::new(&pattern).expect("templates should compile");
+    tera.register_filter(
+        "json",
+        |value: &tera::Value,
+         _: &std::collections::HashMap<String, tera::Value>|
+         -> tera::Result<tera::Value> { Ok(tera::Value::String(value.to_string())) },
+    );
+
+    let events: Vec<operate_ui::jetstream::RitualEvent> = serde_json::from_value(serde_json::json!([
+        { "ts": "2025-01-01T00:00:00Z", "event": "step.started:v1", "stepId": "fetch", "kind": "capsule", "attempt": 1, "queuedMs": 0 },
+        { "ts": "2025-01-01T00:00:01Z", "event": "step.completed:v1", "stepId": "fetch", "attempt": 1, "outcome": "failed", "durationMs": 1000 },
+        { "ts": "2025-01-01T00:00:02Z", "event": "step.started:v1", "stepId": "fetch", "kind": "capsule", "attempt": 2, "queuedMs": 0 },
+        { "ts": "2025-01-01T00:00:03Z", "event": "step.completed:v1", "stepId": "fetch", "attempt": 2, "outcome": "succeeded", "durationMs": 1000 },
+        { "ts": "2025-01-01T00:00:03Z", "event": "step.started:v1", "stepId": "lint", "kind": "capsule", "attempt": 1, "queuedMs": 0 },
+        { "ts": "2025-01-01T00:00:04Z", "event": "step.completed:v1", "stepId": "lint", "attempt": 1, "outcome": "succeeded", "durationMs": 1000 },
+        { "ts": "2025-01-01T00:00:04Z", "event": "step.started:v1", "stepId": "build", "kind": "capsule", "attempt": 1, "queuedMs": 0 },
+        { "ts": "2025-01-01T00:00:40Z", "event": "step.completed:v1", "stepId": "build", "attempt": 1, "outcome": "succeeded", "durationMs": 36000 }
+    ]))
+    .unwrap();
+    let timeline = operate_ui::timeline::RunTimeline::from_events(&events).unwrap();
+
+    let mut ctx = tera::Context::new();
+    ctx.insert(
+        "run",
+        &serde_json::json!({ "runId": "run-t", "ritualId": "release", "events": events }),
+    );
+    ctx.insert("jetstream_available", &true);
+    ctx.insert("run_id", &"run-t");
+    ctx.insert("current_page", &"runs");
+    ctx.insert("tenant", &"default");
+    ctx.insert("run_status", &"Completed");
+    ctx.insert("run_status_class", &"status-completed");
+    ctx.insert("timeline", &timeline);
+
+    let html = tera
+        .render("run_detail.html", &ctx)
+        .expect("run_detail.html should render with a timeline");
+    assert!(html.contains("Step Timeline"));
+    assert!(html.contains(r#"<div class="gantt-row gantt-slow" data-step-id="build">"#));
+    assert!(html.contains(r#"<div class="gantt-row" data-step-id="fetch">"#));
+    assert!(html.contains("1 retry"));
+    assert!(html.contains("Attempt 1: failed in 1000 ms"));
+}
+
+#[tokio::test]
+async fn run_detail_renders_resource_usage_card() {
+    let pattern = format!("{}/templates/**/*.html", env!("CARGO_MANIFEST_DIR"));
+    let mut tera = tera::Tera::new(&pattern).expect("templates should compile");
+    tera.register_filter(
+        "json",
+        |value: &tera::Value,
+         _: &std::collections::HashMap<String, tera::Value>|
+         -> tera::Result<tera::Value> { Ok(tera::Value::String(value.to_string())) },
+    );
+
+    let events: Vec<operate_ui::jetstream::RitualEvent> =
+        serde_json::from_value(serde_json::json!([
+            { "ts": "2025-01-01T00:00:00Z", "event": "ritual.started:v1" },
+            { "ts": "2025-01-01T00:00:30Z", "event": "ritual.completed:v1", "outputs": { "steps": {
+                "build": {
+                    "result": { "success": true },
+                    "metrics": { "resources": { "cpu_seconds": 12.25, "memory_bytes": 1048576 } },
+                    "provenance": { "source": { "system": "container-exec" } }
+                },
+                "notify": { "result": { "success": true } }
+            } } }
+        ]))
+        .unwrap();
+    let usage = operate_ui::usage::RunUsage::from_events(&events);
+
+    let mut ctx = tera::Context::new();
+    ctx.insert(
+        "run",
+        &serde_json::json!({ "runId": "run-u", "ritualId": "release", "events": events }),
+    );
+    ctx.insert("jetstream_available", &true);
+    ctx.insert("run_id", &"run-u");
+    ctx.insert("current_page", &"runs");
+    ctx.insert("tenant", &"default");
+    ctx.insert("run_status", &"Completed");
+    ctx.insert("run_status_class", &"status-completed");
+    ctx.insert("usage", &usage);
+
+    let html = tera
+        .render("run_detail.html", &ctx)
+        .expect("run_detail.html should render with resource usage");
+    assert!(html.contains(r#"id="run-usage""#));
+    assert!(html.contains("12.3 s"));
+    assert!(html.contains(" MB</div>"));
+    assert!(html.contains("30000 ms"));
+    assert!(html.contains("1 of 2 steps reported resource metrics"));
+}
+
+#[tokio::test]
+async fn pages_ship_theme_toggle_and_labelled_approval_controls() {
+    let pattern = format!("{}/templates/**/*.html", env!("CARGO_MANIFEST_DIR"));
+    let mut tera = tera::Tera::new(&pattern).expect("templates should compile");
+    tera.register_filter(
+        "json",
+        |value: &tera::Value,
+         _: &std::collections::HashMap<String, tera::Value>|
+         -> tera::Result<tera::Value> { Ok(tera::Value::String(value.to_string())) },
+    );
+
+    let mut ctx = tera::Context::new();
+    ctx.insert(
+        "run",
+        &serde_json::json!({ "runId": "run-a", "ritualId": "release", "events": [] }),
+    );
+    ctx.insert("jetstream_available", &true);
+    ctx.insert("run_id", &"run-a");
+    ctx.insert("current_page", &"runs");
+    ctx.insert("tenant", &"default");
+    ctx.insert("run_status", &"Running");
+    ctx.insert("run_status_class", &"status-running");
+    ctx.insert(
+        "approvals",
+        &serde_json::json!({ "status": "Pending", "statusClass": "status-running", "gateId": "prod" }),
+    );
+
+    let html = tera
+        .render("run_detail.html", &ctx)
+        .expect("run_detail.html should render with a pending approval");
+    assert!(html.contains("@media (prefers-color-scheme: dark)"));
+    assert!(html.contains(r#"id="theme-toggle""#));
+    assert!(html.contains(r#"href="/runs" id="nav-runs" class="active" aria-current="page""#));
+    assert!(html.contains(r#"aria-label="Run status: Running""#));
+    assert!(html.contains(r#"aria-label="Approval status: Pending""#));
+    assert!(html.contains(r#"role="toolbar" aria-label="Approval decision""#));
+    assert!(html.contains(r#"id="deny-approval-btn" class="btn btn-danger" tabindex="-1""#));
+}
+
+#[tokio::test]
+async fn run_detail_renders_last_scale_decision() {
+    let pattern = format!("{}/templates/**/*.html", env!("CARGO_MANIFEST_DIR"));
+    let mut tera = tera::Tera::new(&pattern).expect("templates should compile");
+    tera.register_filter(
+        "json",
+        |value: &tera::Value,
+         _: &std::collections::HashMap<String, tera::Value>|
+         -> tera::Result<tera::Value> { Ok(tera::Value::String(value.to_string())) },
+    );
+
+    let mut ctx = tera::Context::new();
+    ctx.insert(
+        "run",
+        &serde_json::json!({ "runId": "run-s", "ritualId": "release", "events": [] }),
+    );
+    ctx.insert("jetstream_available", &true);
+    ctx.insert("run_id", &"run-s");
+    ctx.insert("current_page", &"runs");
+    ctx.insert("tenant", &"default");
+    ctx.insert("run_status", &"Running");
+    ctx.insert("run_status_class", &"status-running");
+    ctx.insert(
+        "scale_hint",
+        &serde_json::json!({
+            "ts": "2025-01-01T00:00:00Z",
+            "recommendation": "scale_up",
+            "reason": "Queue lag above threshold",
+            "metrics": {
+                "queueLag": 900,
+                "formattedP95Latency": "120.0 ms",
+                "formattedErrorRate": "0.50%",
+                "totalErrors": 1,
+                "totalProcessed": 200
+            }
+        }),
+    );
+    ctx.insert("scale_decision", &serde_json::Value::Null);
+
+    let html = tera
+        .render("run_detail.html", &ctx)
+        .expect("run_detail.html should render without a scale decision");
+    assert!(html.contains("Scale Feedback Metrics"));
+    assert!(!html.contains(r#"id="scale-decision""#));
+
+    ctx.insert(
+        "scale_decision",
+        &serde_json::json!({
+            "ts": "2025-01-01T00:00:01Z",
+            "tenantId": "default",
+            "recommendation": "scale_up",
+            "outcome": "clamped",
+            "reason": "ScaleUp to 12 clamped to 10 (bounds 1..=10)",
+            "currentReplicas": 9,
+            "desiredReplicas": 10,
+            "target": "demon/demon-engine",
+            "dryRun": true
+        }),
+    );
+    let html = tera
+        .render("run_detail.html", &ctx)
+        .expect("run_detail.html should render with a scale decision");
+    assert!(html.contains(r#"id="scale-decision""#));
+    assert!(html.contains("scale-outcome-clamped"));
+    assert!(html.contains("replicas 9 → 10"));
+    assert!(html.contains("<code>demon&#x2F;demon-engine</code>"));
+    assert!(html.contains("(dry run)"));
+}
@@ -7,6 +7,7 @@ fn test_run_status_display() {
     assert_eq!(RunStatus::Running.to_string(), "Running");
     assert_eq!(RunStatus::Completed.to_string(), "Completed");
     assert_eq!(RunStatus::Failed.to_string(), "Failed");
+    assert_eq!(RunStatus::Canceled.to_string(), "Canceled");
 }
 
 #[test]
@@ -0,0 +1,45 @@
+//! `GET /api/tenants/:tenant/usage` rolls run resource usage up for
+//! chargeback exports.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use tower::util::ServiceExt; // for oneshot
+
+fn app() -> axum::Router {
+    operate_ui::create_app(operate_ui::AppState {
+        jetstream_client: None,
+        tera: tera::Tera::new("nonexistent/*").unwrap(),
+        access_control: Default::default(),
+        bundle_loader: runtime::bundle::BundleLoader::new(None),
+        app_pack_registry: None,
+        feature_flags: std::collections::HashSet::new(),
+        run_index: Default::default(),
+        tenant_quotas: None,
+    })
+}
+
+async fn get(uri: &str) -> (StatusCode, serde_json::Value) {
+    let response = app()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn given_unknown_format_when_requesting_usage_then_bad_request() {
+    let (status, body) = get("/api/tenants/acme/usage?format=xlsx").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("'json' or 'csv'"));
+}
+
+#[tokio::test]
+async fn given_no_jetstream_when_requesting_usage_then_bad_gateway() {
+    let (status, body) = get("/api/tenants/acme/usage?format=csv&since=2025-01-01T00:00:00Z").await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["error"], "JetStream is not available");
+}
@@ -168,3 +168,45 @@ async fn test_workflow_viewer_with_browse_button() {
     assert!(html.contains("workflowListView"));
     assert!(html.contains("workflowSearchInput"));
 }
+
+#[tokio::test]
+async fn test_workflow_viewer_prefills_run_overlay() {
+    let state = AppState::new().await;
+    let app = create_app(state);
+    let server = TestServer::new(app).unwrap();
+
+    let response = server
+        .get("/ui/workflow")
+        .add_query_param("workflowPath", "release.yaml")
+        .add_query_param("runId", "run-42")
+        .add_query_param("tenant", "acme")
+        .await;
+
+    assert_eq!(response.status_code(), 200);
+    let html = response.text();
+    assert!(html.contains(r#"id="overlayRunId""#));
+    assert!(html.contains(r#"value="run-42""#));
+    assert!(html.contains(r#"value="acme""#));
+    assert!(html.contains("/overlay/stream"));
+    assert!(html.contains("envelopeCard"));
+}
+
+#[tokio::test]
+async fn test_run_overlay_api_reports_missing_run() {
+    let state = AppState::new().await;
+    let app = create_app(state);
+    let server = TestServer::new(app).unwrap();
+
+    let overlay = server.get("/api/runs/no-such-run/overlay").await;
+    let envelope = server
+        .get("/api/tenants/acme/runs/no-such-run/steps/build/envelope")
+        .await;
+
+    // 404 with a JetStream connection, 502 without one
+    for response in [overlay, envelope] {
+        let status = response.status_code();
+        assert!(status == 404 || status == 502, "unexpected status {status}");
+        let body: serde_json::Value = response.json();
+        assert!(body.get("error").is_some());
+    }
+}
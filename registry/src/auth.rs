@@ -7,55 +7,9 @@ use axum::{
     middleware::Next,
     response::{IntoResponse, Response},
 };
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
-use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
-/// JWT Claims structure
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Claims {
-    pub sub: String,         // Subject (user identifier)
-    pub exp: usize,          // Expiration time (Unix timestamp)
-    pub iat: Option<usize>,  // Issued at (Unix timestamp)
-    pub scopes: Vec<String>, // Permission scopes
-}
-
-/// JWT configuration loaded from environment
-#[derive(Clone)]
-pub struct JwtConfig {
-    pub secret: String,
-    pub algorithm: Algorithm,
-}
-
-impl JwtConfig {
-    /// Load JWT configuration from environment variables
-    ///
-    /// # Panics
-    ///
-    /// Panics if `JWT_SECRET` environment variable is not set, as this is required
-    /// for production security.
-    pub fn from_env() -> Self {
-        let secret = std::env::var("JWT_SECRET")
-            .expect("JWT_SECRET environment variable must be set. Set it to a secure random string (minimum 32 characters recommended).");
-
-        let algorithm = std::env::var("JWT_ALGORITHM")
-            .ok()
-            .and_then(|a| match a.as_str() {
-                "HS256" => Some(Algorithm::HS256),
-                "HS384" => Some(Algorithm::HS384),
-                "HS512" => Some(Algorithm::HS512),
-                _ => None,
-            })
-            .unwrap_or(Algorithm::HS256);
-
-        Self { secret, algorithm }
-    }
-
-    /// Create from explicit secret (for testing)
-    pub fn new(secret: String, algorithm: Algorithm) -> Self {
-        Self { secret, algorithm }
-    }
-}
+pub use jwt_auth::{Claims, JwtConfig};
 
 /// Extension to attach validated claims to the request
 #[derive(Clone, Debug)]
@@ -110,15 +64,7 @@ pub async fn jwt_middleware(
 
 /// Verify JWT token and extract claims
 fn verify_jwt(token: &str, config: &JwtConfig) -> Result<Claims, String> {
-    let mut validation = Validation::new(config.algorithm);
-    validation.validate_exp = true;
-
-    let decoding_key = DecodingKey::from_secret(config.secret.as_bytes());
-
-    let token_data = decode::<Claims>(token, &decoding_key, &validation)
-        .map_err(|e| format!("Token decode error: {}", e))?;
-
-    Ok(token_data.claims)
+    jwt_auth::verify(token, config)
 }
 
 /// Create a 401 Unauthorized response
@@ -128,7 +74,7 @@ fn unauthorized_response(message: String) -> Response {
 
 /// Check if claims contain a specific scope
 pub fn has_scope(claims: &Claims, required_scope: &str) -> bool {
-    claims.scopes.iter().any(|s| s == required_scope)
+    claims.has_scope(required_scope)
 }
 
 /// Extract claims from request extensions
@@ -142,7 +88,7 @@ pub fn extract_claims(request: &Request<Body>) -> Option<Claims> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use jsonwebtoken::{encode, EncodingKey, Header};
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 
     fn create_test_token(scopes: Vec<String>, secret: &str) -> String {
         let claims = Claims {
@@ -150,6 +96,7 @@ mod tests {
             exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
             iat: Some(chrono::Utc::now().timestamp() as usize),
             scopes,
+            ..Default::default()
         };
 
         let header = Header::new(Algorithm::HS256);
@@ -193,6 +140,7 @@ mod tests {
             exp: 9999999999,
             iat: None,
             scopes: vec!["contracts:write".to_string(), "contracts:read".to_string()],
+            ..Default::default()
         };
 
         assert!(has_scope(&claims, "contracts:write"));
@@ -210,6 +158,7 @@ mod tests {
             exp: (chrono::Utc::now() - chrono::Duration::hours(1)).timestamp() as usize,
             iat: Some(chrono::Utc::now().timestamp() as usize),
             scopes: vec!["contracts:read".to_string()],
+            ..Default::default()
         };
 
         let header = Header::new(Algorithm::HS256);
@@ -226,14 +175,4 @@ mod tests {
         let err_msg = result.unwrap_err().to_lowercase();
         assert!(err_msg.contains("exp") || err_msg.contains("token"));
     }
-
-    #[test]
-    #[should_panic(expected = "JWT_SECRET environment variable must be set")]
-    fn given_missing_jwt_secret_when_from_env_called_then_panics() {
-        // Ensure JWT_SECRET is not set for this test
-        std::env::remove_var("JWT_SECRET");
-
-        // This should panic with a clear error message
-        let _config = JwtConfig::from_env();
-    }
 }
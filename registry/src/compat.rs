@@ -0,0 +1,160 @@
+//! Per-contract compatibility policies
+//!
+//! Each contract declares how a new version must relate to the versions
+//! published before it, in the style of Confluent Schema Registry:
+//!
+//! - `backward`: the new schema accepts everything the prior ones did, so
+//!   consumers can upgrade first
+//! - `forward`: the prior schemas accept everything the new one does, so
+//!   producers can upgrade first
+//! - `full`: both
+//! - `none`: no check
+//!
+//! Checks run the contract linter's breaking-change detection in the
+//! direction the policy requires.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompatibilityMode {
+    Backward,
+    Forward,
+    Full,
+    #[default]
+    None,
+}
+
+impl CompatibilityMode {
+    /// Policy used when a contract declares none, from
+    /// `REGISTRY_DEFAULT_COMPATIBILITY` (default `none`)
+    pub fn default_from_env() -> Result<Self> {
+        match std::env::var("REGISTRY_DEFAULT_COMPATIBILITY") {
+            Ok(value) => value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("REGISTRY_DEFAULT_COMPATIBILITY: {}", e)),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Reasons `proposed` violates this policy against `prior`; empty when
+    /// compatible
+    pub fn check(self, prior: &Value, proposed: &Value) -> Result<Vec<String>> {
+        let mut violations = Vec::new();
+        if matches!(self, Self::Backward | Self::Full) {
+            violations.extend(breaking_changes(prior, proposed)?);
+        }
+        if matches!(self, Self::Forward | Self::Full) {
+            // Data written with the new schema must still validate against the old one
+            violations.extend(
+                breaking_changes(proposed, prior)?
+                    .into_iter()
+                    .map(|change| format!("forward: {}", change)),
+            );
+        }
+        Ok(violations)
+    }
+}
+
+fn breaking_changes(from: &Value, to: &Value) -> Result<Vec<String>> {
+    Ok(contract_linter::lint_schema_change(from, to, None, None)?.breaking_changes)
+}
+
+impl FromStr for CompatibilityMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "backward" => Ok(Self::Backward),
+            "forward" => Ok(Self::Forward),
+            "full" => Ok(Self::Full),
+            "none" => Ok(Self::None),
+            other => Err(format!(
+                "unknown compatibility mode '{}' (expected backward, forward, full or none)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CompatibilityMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Backward => "backward",
+            Self::Forward => "forward",
+            Self::Full => "full",
+            Self::None => "none",
+        };
+        f.write_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn v1() -> Value {
+        json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "note": {"type": "string"}}
+        })
+    }
+
+    #[test]
+    fn test_backward_rejects_removed_field_but_forward_allows_it() {
+        let v2 = json!({"type": "object", "properties": {"id": {"type": "string"}}});
+
+        assert_eq!(
+            CompatibilityMode::Backward.check(&v1(), &v2).unwrap().len(),
+            1
+        );
+        assert!(CompatibilityMode::Forward
+            .check(&v1(), &v2)
+            .unwrap()
+            .is_empty());
+        assert!(CompatibilityMode::None
+            .check(&v1(), &v2)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_forward_rejects_added_field_and_full_checks_both_ways() {
+        let v2 = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "note": {"type": "string"},
+                "tag": {"type": "string"}
+            }
+        });
+
+        assert!(CompatibilityMode::Backward
+            .check(&v1(), &v2)
+            .unwrap()
+            .is_empty());
+        let forward = CompatibilityMode::Forward.check(&v1(), &v2).unwrap();
+        assert_eq!(forward.len(), 1);
+        assert!(forward[0].starts_with("forward: Removed"));
+        assert_eq!(CompatibilityMode::Full.check(&v1(), &v2).unwrap(), forward);
+    }
+
+    #[test]
+    fn test_parse_and_serialize_modes() {
+        assert_eq!(
+            "FULL".parse::<CompatibilityMode>().unwrap(),
+            CompatibilityMode::Full
+        );
+        assert!("transitive".parse::<CompatibilityMode>().is_err());
+        assert_eq!(
+            serde_json::to_value(CompatibilityMode::Backward).unwrap(),
+            json!("backward")
+        );
+        assert_eq!(CompatibilityMode::default(), CompatibilityMode::None);
+    }
+}
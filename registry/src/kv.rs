@@ -1,12 +1,23 @@
 //! JetStream KV client for contract metadata storage
 //!
 //! Provides CRUD operations for contract schema bundles stored in JetStream KV.
-//! Key layout: contracts.meta.<name>.<version>
+//! Schema bodies are content-addressed, so a schema published under several
+//! names or versions is stored once.
+//!
+//! Key layout:
+//! - `meta.<name>.<version>`: platform bundle metadata with `schemaDigest`, no body
+//! - `tenants.<tenant>.meta.<name>.<version>`: the same, in a tenant's namespace
+//! - `schema.<sha256>`: JSON schema body, shared by every namespace
 
+use crate::compat::CompatibilityMode;
 use anyhow::{Context, Result};
-use async_nats::jetstream::{self, kv::Store};
+use async_nats::jetstream::{
+    self,
+    kv::{Operation, Store, UpdateErrorKind},
+};
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::{debug, info, warn};
 
 /// Contract metadata stored in KV
@@ -17,6 +28,11 @@ pub struct ContractMetadata {
     pub description: Option<String>,
     #[serde(rename = "createdAt")]
     pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<CompatibilityMode>,
+    /// Namespace the entry was listed from; not stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
 }
 
 /// Full contract bundle including schemas
@@ -35,12 +51,75 @@ pub struct ContractBundle {
     pub descriptor_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub digest: Option<String>,
+    /// SHA-256 of `jsonSchema`, for clients to verify the body they received
+    #[serde(
+        rename = "schemaDigest",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub schema_digest: Option<String>,
+    /// Policy new versions of this contract are checked against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<CompatibilityMode>,
+}
+
+/// Hex SHA-256 of a schema body, the key its content is stored under
+pub fn schema_digest(json_schema: &str) -> String {
+    hex::encode(Sha256::digest(json_schema.as_bytes()))
+}
+
+/// Namespace shared by all tenants; it keeps the pre-tenancy key layout
+pub const PLATFORM_NAMESPACE: &str = "platform";
+
+/// Attempts at a compare-and-set create before giving up under contention
+const MAX_CREATE_ATTEMPTS: usize = 3;
+
+/// Check that `tenant` can be used as a namespace: 1-63 lowercase letters,
+/// digits, `-` or `_`, so it stays a single KV key token
+pub fn validate_tenant(tenant: &str) -> Result<()> {
+    let valid = !tenant.is_empty()
+        && tenant.len() <= 63
+        && tenant
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        anyhow::bail!(
+            "Invalid tenant '{}': use 1-63 lowercase letters, digits, '-' or '_'",
+            tenant
+        );
+    }
+    Ok(())
+}
+
+fn meta_prefix(namespace: &str) -> String {
+    if namespace == PLATFORM_NAMESPACE {
+        "meta.".to_string()
+    } else {
+        format!("tenants.{}.meta.", namespace)
+    }
 }
 
-/// JetStream KV client for contract storage
+fn meta_key(namespace: &str, name: &str, version: &str) -> String {
+    format!("{}{}.{}", meta_prefix(namespace), name, version)
+}
+
+/// Whether `key` holds bundle metadata in any namespace
+fn is_meta_key(key: &str) -> bool {
+    key.starts_with("meta.") || key.starts_with("tenants.")
+}
+
+fn schema_key(digest: &str) -> String {
+    format!("schema.{}", digest)
+}
+
+/// JetStream KV client for contract storage, scoped to one namespace
+///
+/// [`KvClient::new`] starts in the platform namespace; use
+/// [`KvClient::for_tenant`] to reach a tenant's contracts.
 #[derive(Clone)]
 pub struct KvClient {
     kv_store: Store,
+    namespace: String,
 }
 
 impl KvClient {
@@ -92,25 +171,46 @@ impl KvClient {
             }
         };
 
-        Ok(Self { kv_store })
+        Ok(Self {
+            kv_store,
+            namespace: PLATFORM_NAMESPACE.to_string(),
+        })
+    }
+
+    /// A client for `tenant`'s namespace on the same bucket. `platform`
+    /// selects the shared namespace.
+    pub fn for_tenant(&self, tenant: &str) -> Result<Self> {
+        validate_tenant(tenant)?;
+        Ok(Self {
+            kv_store: self.kv_store.clone(),
+            namespace: tenant.to_string(),
+        })
+    }
+
+    /// The namespace this client reads and writes
+    pub fn namespace(&self) -> &str {
+        &self.namespace
     }
 
-    /// List all contract metadata entries
+    /// List all contract metadata entries in this namespace
     pub async fn list_contracts(&self) -> Result<Vec<ContractMetadata>> {
-        debug!("Listing all contracts from KV");
+        debug!("Listing contracts in namespace {} from KV", self.namespace);
 
         let mut contracts = Vec::new();
+        let prefix = meta_prefix(&self.namespace);
 
-        // Get all keys with "meta." prefix
         let mut keys = self.kv_store.keys().await?.boxed();
 
         while let Some(key_result) = keys.next().await {
             match key_result {
                 Ok(key) => {
-                    if key.starts_with("meta.") {
+                    if key.starts_with(&prefix) {
                         if let Ok(Some(bytes)) = self.kv_store.get(&key).await {
                             match serde_json::from_slice::<ContractMetadata>(&bytes) {
-                                Ok(metadata) => contracts.push(metadata),
+                                Ok(mut metadata) => {
+                                    metadata.namespace = Some(self.namespace.clone());
+                                    contracts.push(metadata);
+                                }
                                 Err(e) => {
                                     warn!("Failed to parse metadata for key {}: {}", key, e);
                                 }
@@ -128,33 +228,76 @@ impl KvClient {
         Ok(contracts)
     }
 
-    /// Get a specific contract bundle by name and version
+    /// Get a specific contract bundle by name and version, with its schema
+    /// body loaded and checked against `schemaDigest`
     pub async fn get_contract(&self, name: &str, version: &str) -> Result<Option<ContractBundle>> {
-        let key = format!("meta.{}.{}", name, version);
+        let key = meta_key(&self.namespace, name, version);
         debug!("Fetching contract from KV: {}", key);
 
-        match self.kv_store.get(&key).await? {
-            Some(bytes) => {
-                let bundle = serde_json::from_slice::<ContractBundle>(&bytes)
-                    .with_context(|| format!("Failed to parse contract bundle for {}", key))?;
-                info!("Retrieved contract: {} v{}", name, version);
-                Ok(Some(bundle))
+        let Some(bytes) = self.kv_store.get(&key).await? else {
+            debug!("Contract not found: {} v{}", name, version);
+            return Ok(None);
+        };
+        let mut bundle = serde_json::from_slice::<ContractBundle>(&bytes)
+            .with_context(|| format!("Failed to parse contract bundle for {}", key))?;
+
+        match (&bundle.json_schema, &bundle.schema_digest) {
+            (None, Some(digest)) => {
+                let body = self
+                    .get_schema(digest)
+                    .await?
+                    .with_context(|| format!("Schema {} for {} is missing", digest, key))?;
+                bundle.json_schema = Some(body);
+            }
+            // Written before content addressing; the body is inline
+            (Some(body), None) => bundle.schema_digest = Some(schema_digest(body)),
+            _ => {}
+        }
+
+        info!("Retrieved contract: {} v{}", name, version);
+        Ok(Some(bundle))
+    }
+
+    /// Every stored version of the contract `name`, with schema bodies loaded
+    pub async fn list_versions(&self, name: &str) -> Result<Vec<ContractBundle>> {
+        let mut versions = Vec::new();
+        for metadata in self.list_contracts().await? {
+            if metadata.name != name {
+                continue;
             }
-            None => {
-                debug!("Contract not found: {} v{}", name, version);
-                Ok(None)
+            if let Some(bundle) = self.get_contract(name, &metadata.version).await? {
+                versions.push(bundle);
             }
         }
+        Ok(versions)
     }
 
-    /// Store a contract bundle in KV
+    /// Get a schema body by digest, verifying it still hashes to that digest
+    pub async fn get_schema(&self, digest: &str) -> Result<Option<String>> {
+        let key = schema_key(digest);
+        let Some(bytes) = self.kv_store.get(&key).await? else {
+            return Ok(None);
+        };
+        let body = String::from_utf8(bytes.to_vec())
+            .with_context(|| format!("Schema body at {} is not UTF-8", key))?;
+        let actual = schema_digest(&body);
+        if actual != digest {
+            anyhow::bail!(
+                "Schema body at {} does not match its digest (got {})",
+                key,
+                actual
+            );
+        }
+        Ok(Some(body))
+    }
+
+    /// Store a contract bundle in KV. The schema body is written under its
+    /// digest unless an identical body is already stored.
     pub async fn put_contract(&self, bundle: &ContractBundle) -> Result<()> {
-        let key = format!("meta.{}.{}", bundle.name, bundle.version);
+        let key = meta_key(&self.namespace, &bundle.name, &bundle.version);
         debug!("Storing contract in KV: {}", key);
 
-        let value = serde_json::to_vec(bundle)
-            .with_context(|| format!("Failed to serialize contract bundle for {}", key))?;
-
+        let value = self.store_schema_body(&key, bundle).await?;
         self.kv_store
             .put(&key, value.into())
             .await
@@ -164,19 +307,124 @@ impl KvClient {
         Ok(())
     }
 
-    /// Delete a contract from KV
+    /// Like [`KvClient::put_contract`], but never overwrites: returns `false`
+    /// when the version already exists, including one stored concurrently by
+    /// another publisher
+    pub async fn create_contract(&self, bundle: &ContractBundle) -> Result<bool> {
+        let key = meta_key(&self.namespace, &bundle.name, &bundle.version);
+        debug!("Creating contract in KV: {}", key);
+
+        let value = self.store_schema_body(&key, bundle).await?;
+        // Compare-and-set; a lost race is retried against the new revision
+        for _ in 0..MAX_CREATE_ATTEMPTS {
+            // A deleted (or rolled-back) version leaves a tombstone, which
+            // is written over at its revision like a missing key at 0
+            let revision = match self.kv_store.entry(&key).await? {
+                Some(entry) if matches!(entry.operation, Operation::Put) => return Ok(false),
+                Some(entry) => entry.revision,
+                None => 0,
+            };
+            match self
+                .kv_store
+                .update(&key, value.clone().into(), revision)
+                .await
+            {
+                Ok(_) => {
+                    info!("Created contract: {} v{}", bundle.name, bundle.version);
+                    return Ok(true);
+                }
+                // A wrong-last-sequence rejection surfaces as `Other`
+                Err(e) if e.kind() == UpdateErrorKind::Other => {
+                    debug!("{} changed while creating it, retrying", key);
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to create contract in KV: {}", key))
+                }
+            }
+        }
+        anyhow::bail!(
+            "Failed to create contract in KV: {} is still contended after {} attempts",
+            key,
+            MAX_CREATE_ATTEMPTS
+        )
+    }
+
+    /// Store the bundle's schema body by digest and return the metadata entry
+    /// to write under `key`
+    async fn store_schema_body(&self, key: &str, bundle: &ContractBundle) -> Result<Vec<u8>> {
+        let mut entry = bundle.clone();
+        if let Some(body) = entry.json_schema.take() {
+            let digest = schema_digest(&body);
+            let body_key = schema_key(&digest);
+            if self.kv_store.get(&body_key).await?.is_some() {
+                debug!("Schema {} already stored; reusing it for {}", digest, key);
+            } else {
+                self.kv_store
+                    .put(&body_key, body.into_bytes().into())
+                    .await
+                    .with_context(|| format!("Failed to store schema in KV: {}", body_key))?;
+            }
+            entry.schema_digest = Some(digest);
+        }
+
+        serde_json::to_vec(&entry)
+            .with_context(|| format!("Failed to serialize contract bundle for {}", key))
+    }
+
+    /// Delete a contract from KV, and its schema body once no other contract
+    /// references it
     pub async fn delete_contract(&self, name: &str, version: &str) -> Result<()> {
-        let key = format!("meta.{}.{}", name, version);
+        let key = meta_key(&self.namespace, name, version);
         debug!("Deleting contract from KV: {}", key);
 
+        let digest = match self.kv_store.get(&key).await? {
+            Some(bytes) => serde_json::from_slice::<ContractBundle>(&bytes)
+                .ok()
+                .and_then(|bundle| bundle.schema_digest),
+            None => None,
+        };
+
         self.kv_store
             .delete(&key)
             .await
             .with_context(|| format!("Failed to delete contract from KV: {}", key))?;
 
+        if let Some(digest) = digest {
+            if !self.schema_referenced(&digest).await? {
+                let body_key = schema_key(&digest);
+                self.kv_store
+                    .delete(&body_key)
+                    .await
+                    .with_context(|| format!("Failed to delete schema from KV: {}", body_key))?;
+                debug!("Deleted unreferenced schema {}", digest);
+            }
+        }
+
         info!("Deleted contract: {} v{}", name, version);
         Ok(())
     }
+
+    /// Whether any stored contract, in any namespace, still points at `digest`
+    async fn schema_referenced(&self, digest: &str) -> Result<bool> {
+        let mut keys = self.kv_store.keys().await?.boxed();
+        while let Some(key) = keys.next().await {
+            let key = key?;
+            if !is_meta_key(&key) {
+                continue;
+            }
+            if let Some(bytes) = self.kv_store.get(&key).await? {
+                let references = serde_json::from_slice::<ContractBundle>(&bytes)
+                    .ok()
+                    .and_then(|bundle| bundle.schema_digest)
+                    .is_some_and(|d| d == digest);
+                if references {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
 }
 
 #[cfg(test)]
@@ -190,9 +438,12 @@ mod tests {
             version: "1.0.0".to_string(),
             description: Some("Test contract".to_string()),
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            compatibility: Some(CompatibilityMode::Backward),
+            namespace: None,
         };
 
         let json = serde_json::to_string(&metadata).unwrap();
+        assert!(json.contains(r#""compatibility":"backward""#));
         assert!(json.contains("test-contract"));
         assert!(json.contains("createdAt"));
 
@@ -212,6 +463,8 @@ mod tests {
             wit_path: Some("/path/to/schema.wit".to_string()),
             descriptor_path: Some("/path/to/descriptor.json".to_string()),
             digest: Some("abc123".to_string()),
+            schema_digest: None,
+            compatibility: None,
         };
 
         let json = serde_json::to_string(&bundle).unwrap();
@@ -223,5 +476,53 @@ mod tests {
         let deserialized: ContractBundle = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.name, "test-contract");
         assert_eq!(deserialized.digest, Some("abc123".to_string()));
+        assert!(!json.contains("schemaDigest"));
+    }
+
+    #[test]
+    fn test_schema_digest_is_hex_sha256_of_body() {
+        assert_eq!(
+            schema_digest("{}"),
+            "44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+        assert_eq!(
+            schema_key(&schema_digest("{}")),
+            "schema.44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a"
+        );
+        assert_eq!(
+            meta_key(PLATFORM_NAMESPACE, "ritual.started", "1.0.0"),
+            "meta.ritual.started.1.0.0"
+        );
+    }
+
+    #[test]
+    fn test_tenant_keys_are_prefixed_and_disjoint_from_platform() {
+        let key = meta_key("acme", "ritual.started", "1.0.0");
+        assert_eq!(key, "tenants.acme.meta.ritual.started.1.0.0");
+        assert!(!key.starts_with(&meta_prefix(PLATFORM_NAMESPACE)));
+        assert!(!key.starts_with(&meta_prefix("acme-eu")));
+        assert!(is_meta_key(&key));
+        assert!(!is_meta_key(&schema_key("abc")));
+    }
+
+    #[test]
+    fn test_validate_tenant() {
+        for tenant in ["acme", "acme-eu", "team_7", PLATFORM_NAMESPACE] {
+            assert!(validate_tenant(tenant).is_ok(), "{tenant}");
+        }
+        for tenant in ["", "Acme", "acme.eu", "acme/eu", "*", &"a".repeat(64)] {
+            assert!(validate_tenant(tenant).is_err(), "{tenant}");
+        }
+    }
+
+    #[test]
+    fn test_legacy_bundle_without_schema_digest_deserializes() {
+        let json = r#"{"name":"a","version":"1.0.0","description":null,"createdAt":"t",
+            "jsonSchema":"{}","witPath":null,"descriptorPath":null}"#;
+
+        let bundle: ContractBundle = serde_json::from_str(json).unwrap();
+
+        assert_eq!(bundle.schema_digest, None);
+        assert_eq!(bundle.json_schema.as_deref(), Some("{}"));
     }
 }
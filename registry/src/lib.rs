@@ -3,17 +3,20 @@
 //! Provides REST API and NATS JetStream KV integration for contract schema management.
 
 pub mod auth;
+pub mod compat;
 pub mod kv;
 pub mod routes;
+pub mod schema_rules;
 
 use anyhow::Result;
 use axum::{
     http::StatusCode,
     middleware,
     response::{IntoResponse, Response},
-    routing::get,
+    routing::{get, post},
     Router,
 };
+use std::sync::Arc;
 use tower_http::trace::TraceLayer;
 use tracing::info;
 
@@ -22,6 +25,12 @@ use tracing::info;
 pub struct AppState {
     pub kv_client: kv::KvClient,
     pub jwt_config: auth::JwtConfig,
+    /// Per-tenant publish quotas; `None` when `WARDS_TENANT_QUOTAS` sets no limits
+    pub quotas: Option<Arc<wards::quota::TenantQuotas>>,
+    /// Audit trail for quota rejections; `None` when `WARDS_AUDIT` is off
+    pub audit: Option<Arc<wards::audit::DecisionLog>>,
+    /// Policy for contracts that declare none (`REGISTRY_DEFAULT_COMPATIBILITY`)
+    pub default_compatibility: compat::CompatibilityMode,
 }
 
 impl AppState {
@@ -33,10 +42,16 @@ impl AppState {
     pub async fn new() -> Result<Self> {
         let kv_client = kv::KvClient::new().await?;
         let jwt_config = auth::JwtConfig::from_env();
+        let quotas = wards::quota::TenantQuotas::from_env()?.map(Arc::new);
+        let audit = wards::audit::DecisionLog::from_env().map(Arc::new);
+        let default_compatibility = compat::CompatibilityMode::default_from_env()?;
         info!("Successfully initialized Schema Registry application state");
         Ok(Self {
             kv_client,
             jwt_config,
+            quotas,
+            audit,
+            default_compatibility,
         })
     }
 }
@@ -86,10 +101,26 @@ pub fn create_app(state: AppState) -> Router {
             "/registry/contracts",
             get(routes::list_contracts).post(routes::publish_contract),
         )
+        .route(
+            "/registry/contracts/bulk",
+            post(routes::publish_contracts_bulk),
+        )
         .route(
             "/registry/contracts/:name/:version",
             get(routes::get_contract),
         )
+        .route(
+            "/registry/tenants/:tenant/contracts",
+            get(routes::list_tenant_contracts).post(routes::publish_tenant_contract),
+        )
+        .route(
+            "/registry/tenants/:tenant/contracts/bulk",
+            post(routes::publish_tenant_contracts_bulk),
+        )
+        .route(
+            "/registry/tenants/:tenant/contracts/:name/:version",
+            get(routes::get_tenant_contract),
+        )
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth::jwt_middleware,
@@ -1,36 +1,73 @@
 //! HTTP route handlers for the Schema Registry API
 
-use crate::{auth, kv::ContractBundle, AppError, AppResult, AppState};
+use crate::{
+    auth,
+    compat::CompatibilityMode,
+    kv::{self, ContractBundle, KvClient, PLATFORM_NAMESPACE},
+    schema_rules, AppError, AppResult, AppState,
+};
 use axum::{
     body::Body,
     extract::{Path, Request, State},
     http::StatusCode,
     response::Json,
+    Extension,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, error, info, warn};
+use wards::audit::WardsDecision;
+use wards::quota::QuotaResource;
 
-/// GET /registry/contracts - List all contracts
+/// GET /registry/contracts - List all contracts in the platform namespace
 ///
 /// Returns a JSON array of contract metadata entries
 pub async fn list_contracts(State(state): State<AppState>) -> AppResult<Json<Value>> {
     debug!("Handling GET /registry/contracts");
 
-    match state.kv_client.list_contracts().await {
-        Ok(contracts) => {
-            info!("Successfully listed {} contracts", contracts.len());
-            Ok(Json(json!({ "contracts": contracts })))
-        }
-        Err(e) => {
-            error!("Failed to list contracts: {}", e);
-            Err(AppError {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                message: format!("Failed to list contracts: {}", e),
-            })
-        }
+    let contracts = list_namespace(&state.kv_client).await?;
+    info!("Successfully listed {} contracts", contracts.len());
+    Ok(Json(json!({ "contracts": contracts })))
+}
+
+/// GET /registry/tenants/:tenant/contracts - List a tenant's contracts
+///
+/// Requires the tenant in the token's `tenants` claim. The platform
+/// namespace's contracts are listed after the tenant's own.
+pub async fn list_tenant_contracts(
+    State(state): State<AppState>,
+    Extension(auth::AuthClaims(claims)): Extension<auth::AuthClaims>,
+    Path(tenant): Path<String>,
+) -> AppResult<Json<Value>> {
+    debug!("Handling GET /registry/tenants/{}/contracts", tenant);
+
+    let namespace = tenant_namespace(&state, &claims, &tenant)?;
+    let mut contracts = list_namespace(&namespace).await?;
+    if namespace.namespace() != PLATFORM_NAMESPACE {
+        contracts.extend(list_namespace(&state.kv_client).await?);
     }
+    info!(
+        "Successfully listed {} contracts for tenant {}",
+        contracts.len(),
+        tenant
+    );
+    Ok(Json(json!({ "contracts": contracts })))
+}
+
+async fn list_namespace(namespace: &KvClient) -> AppResult<Vec<kv::ContractMetadata>> {
+    namespace.list_contracts().await.map_err(|e| {
+        error!(
+            "Failed to list contracts in namespace {}: {}",
+            namespace.namespace(),
+            e
+        );
+        AppError {
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            message: format!("Failed to list contracts: {}", e),
+        }
+    })
 }
 
 /// GET /registry/contracts/:name/:version - Get specific contract bundle
@@ -42,31 +79,95 @@ pub async fn get_contract(
 ) -> AppResult<Json<Value>> {
     debug!("Handling GET /registry/contracts/{}/{}", name, version);
 
-    match state.kv_client.get_contract(&name, &version).await {
-        Ok(Some(bundle)) => {
-            info!("Successfully retrieved contract: {} v{}", name, version);
-            Ok(Json(serde_json::to_value(bundle).map_err(|e| {
-                AppError {
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                    message: format!("Failed to serialize contract bundle: {}", e),
-                }
-            })?))
-        }
-        Ok(None) => {
-            debug!("Contract not found: {} v{}", name, version);
-            Err(AppError {
-                status_code: StatusCode::NOT_FOUND,
-                message: format!("Contract not found: {} v{}", name, version),
-            })
-        }
-        Err(e) => {
-            error!("Failed to get contract {} v{}: {}", name, version, e);
-            Err(AppError {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                message: format!("Failed to get contract: {}", e),
-            })
+    let bundle = find_contract(&state.kv_client, &name, &version)
+        .await?
+        .ok_or_else(|| not_found(&name, &version))?;
+    info!("Successfully retrieved contract: {} v{}", name, version);
+    Ok(Json(bundle_json(bundle)?))
+}
+
+/// GET /registry/tenants/:tenant/contracts/:name/:version - Get a contract
+/// bundle visible to a tenant
+///
+/// Looks in the tenant's namespace, then the platform namespace. The response
+/// carries the `namespace` the bundle was found in.
+pub async fn get_tenant_contract(
+    State(state): State<AppState>,
+    Extension(auth::AuthClaims(claims)): Extension<auth::AuthClaims>,
+    Path((tenant, name, version)): Path<(String, String, String)>,
+) -> AppResult<Json<Value>> {
+    debug!(
+        "Handling GET /registry/tenants/{}/contracts/{}/{}",
+        tenant, name, version
+    );
+
+    let namespace = tenant_namespace(&state, &claims, &tenant)?;
+    let mut found = find_contract(&namespace, &name, &version)
+        .await?
+        .map(|bundle| (bundle, namespace.namespace()));
+    if found.is_none() && namespace.namespace() != PLATFORM_NAMESPACE {
+        found = find_contract(&state.kv_client, &name, &version)
+            .await?
+            .map(|bundle| (bundle, PLATFORM_NAMESPACE));
+    }
+    let (bundle, found_in) = found.ok_or_else(|| not_found(&name, &version))?;
+
+    info!(
+        "Successfully retrieved contract for tenant {}: {} v{} (namespace {})",
+        tenant, name, version, found_in
+    );
+    let mut body = bundle_json(bundle)?;
+    body["namespace"] = json!(found_in);
+    Ok(Json(body))
+}
+
+async fn find_contract(
+    namespace: &KvClient,
+    name: &str,
+    version: &str,
+) -> AppResult<Option<ContractBundle>> {
+    namespace.get_contract(name, version).await.map_err(|e| {
+        error!("Failed to get contract {} v{}: {}", name, version, e);
+        AppError {
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            message: format!("Failed to get contract: {}", e),
         }
+    })
+}
+
+fn not_found(name: &str, version: &str) -> AppError {
+    debug!("Contract not found: {} v{}", name, version);
+    AppError {
+        status_code: StatusCode::NOT_FOUND,
+        message: format!("Contract not found: {} v{}", name, version),
+    }
+}
+
+fn bundle_json(bundle: ContractBundle) -> AppResult<Value> {
+    serde_json::to_value(bundle).map_err(|e| AppError {
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        message: format!("Failed to serialize contract bundle: {}", e),
+    })
+}
+
+/// Resolve `tenant` to its namespace. Tokens must name the tenant, or `*`,
+/// in their `tenants` claim; `platform` is open to every token.
+fn tenant_namespace(state: &AppState, claims: &auth::Claims, tenant: &str) -> AppResult<KvClient> {
+    let namespace = state.kv_client.for_tenant(tenant).map_err(|e| AppError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: e.to_string(),
+    })?;
+    if tenant != PLATFORM_NAMESPACE && !claims.has_tenant(tenant) {
+        warn!("User {} is not a member of tenant {}", claims.sub, tenant);
+        return Err(AppError {
+            status_code: StatusCode::FORBIDDEN,
+            message: format!(
+                "Insufficient permissions: token not valid for tenant '{}'",
+                tenant
+            ),
+        });
     }
+    Ok(namespace)
 }
 
 /// Request body for publishing a contract
@@ -81,23 +182,524 @@ pub struct PublishContractRequest {
     pub wit_path: Option<String>,
     #[serde(rename = "descriptorPath")]
     pub descriptor_path: Option<String>,
+    /// Policy for this and later versions; defaults to the latest prior
+    /// version's policy, then the registry default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<CompatibilityMode>,
 }
 
 /// POST /registry/contracts - Publish a new contract bundle
 ///
 /// Requires JWT with `contracts:write` scope.
-/// Computes SHA-256 digest and stores bundle in KV.
+/// Rejects schemas that fail the meta-schema or house rules (422, with every
+/// finding) and schemas that break the contract's compatibility policy against
+/// earlier versions (409), then computes SHA-256 digest and stores bundle in KV.
 pub async fn publish_contract(
     State(state): State<AppState>,
     request: Request<Body>,
 ) -> AppResult<(StatusCode, Json<Value>)> {
-    // Extract and validate JWT claims
-    let claims = auth::extract_claims(&request).ok_or_else(|| AppError {
+    publish(&state, None, request).await
+}
+
+/// POST /registry/tenants/:tenant/contracts - Publish into a tenant's namespace
+///
+/// As `POST /registry/contracts`, but the token must also be valid for the
+/// tenant. Versions and compatibility are checked within the namespace only,
+/// and the tenant's publish quota is charged.
+pub async fn publish_tenant_contract(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    request: Request<Body>,
+) -> AppResult<(StatusCode, Json<Value>)> {
+    publish(&state, Some(&tenant), request).await
+}
+
+async fn publish(
+    state: &AppState,
+    tenant: Option<&str>,
+    request: Request<Body>,
+) -> AppResult<(StatusCode, Json<Value>)> {
+    let claims = require_write_scope(&request)?;
+    let contracts = match tenant {
+        Some(tenant) => tenant_namespace(state, &claims, tenant)?,
+        None => state.kv_client.clone(),
+    };
+
+    // Enforce the publisher's quota before reading the body
+    charge_publishes(state, tenant, &claims, 1).await?;
+
+    let body_bytes = read_body(request).await?;
+    let payload: PublishContractRequest =
+        serde_json::from_slice(&body_bytes).map_err(|e| AppError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: format!("Invalid JSON payload: {}", e),
+        })?;
+
+    debug!(
+        "Handling contract publish for {} v{} in namespace {}",
+        payload.name,
+        payload.version,
+        contracts.namespace()
+    );
+
+    // Check for duplicate version
+    if let Ok(Some(_)) = contracts
+        .get_contract(&payload.name, &payload.version)
+        .await
+    {
+        warn!(
+            "Duplicate contract version: {} v{}",
+            payload.name, payload.version
+        );
+        return Err(AppError {
+            status_code: StatusCode::CONFLICT,
+            message: format!(
+                "Contract {} version {} already exists",
+                payload.name, payload.version
+            ),
+        });
+    }
+
+    if let Some(raw) = &payload.json_schema {
+        let findings = schema_rules::check(raw, &payload.version);
+        if !findings.is_empty() {
+            warn!(
+                "Rejected {} v{}: schema has {} finding(s)",
+                payload.name,
+                payload.version,
+                findings.len()
+            );
+            return Ok((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({
+                    "status": "invalid",
+                    "name": payload.name,
+                    "version": payload.version,
+                    "error": format!(
+                        "Contract {} v{} has an invalid JSON schema",
+                        payload.name, payload.version
+                    ),
+                    "findings": findings
+                })),
+            ));
+        }
+    }
+
+    let compatibility =
+        check_compatibility(&contracts, state.default_compatibility, &payload).await?;
+    let bundle = new_bundle(&payload, compatibility)?;
+
+    // Store in KV
+    contracts.put_contract(&bundle).await.map_err(|e| {
+        error!("Failed to store contract: {}", e);
+        AppError {
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            message: format!("Failed to store contract: {}", e),
+        }
+    })?;
+
+    info!(
+        "Successfully published contract: {} v{} in namespace {} (digest: {})",
+        bundle.name,
+        bundle.version,
+        contracts.namespace(),
+        bundle.digest.as_deref().unwrap_or_default()
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "created",
+            "namespace": contracts.namespace(),
+            "name": bundle.name,
+            "version": bundle.version,
+            "digest": bundle.digest,
+            "schemaDigest": bundle.schema_digest,
+            "compatibility": compatibility,
+            "createdAt": bundle.created_at
+        })),
+    ))
+}
+
+/// Most contracts a single bulk publish may carry
+pub const MAX_BULK_CONTRACTS: usize = 100;
+
+/// Request body for a bulk publish: an array of contracts, or an object with
+/// a `contracts` array
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum BulkPublishRequest {
+    Contracts(Vec<PublishContractRequest>),
+    Wrapped {
+        contracts: Vec<PublishContractRequest>,
+    },
+}
+
+impl BulkPublishRequest {
+    pub fn into_contracts(self) -> Vec<PublishContractRequest> {
+        match self {
+            Self::Contracts(contracts) | Self::Wrapped { contracts } => contracts,
+        }
+    }
+}
+
+/// What happened to one contract of a bulk publish
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BulkItemStatus {
+    /// Passed every check; not stored because the batch was not applied
+    Valid,
+    /// The schema fails the schema rules or is not valid JSON
+    Invalid,
+    /// The version exists, appears twice in the batch, or breaks the
+    /// compatibility mode
+    Conflict,
+    Created,
+    /// Stored, then removed again because a later contract could not be
+    RolledBack,
+    /// Could not be stored, or could not be removed during a rollback
+    Failed,
+}
+
+/// Per-contract entry of a bulk publish report, in request order
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkItemResult {
+    pub index: usize,
+    pub name: String,
+    pub version: String,
+    pub status: BulkItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub findings: Vec<schema_rules::Finding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_digest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<CompatibilityMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+impl BulkItemResult {
+    fn new(index: usize, payload: &PublishContractRequest, status: BulkItemStatus) -> Self {
+        Self {
+            index,
+            name: payload.name.clone(),
+            version: payload.version.clone(),
+            status,
+            error: None,
+            findings: Vec::new(),
+            digest: None,
+            schema_digest: None,
+            compatibility: None,
+            created_at: None,
+        }
+    }
+
+    fn rejected(
+        index: usize,
+        payload: &PublishContractRequest,
+        status: BulkItemStatus,
+        error: impl Into<String>,
+    ) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..Self::new(index, payload, status)
+        }
+    }
+
+    fn accepted(index: usize, payload: &PublishContractRequest, bundle: &ContractBundle) -> Self {
+        Self {
+            digest: bundle.digest.clone(),
+            schema_digest: bundle.schema_digest.clone(),
+            compatibility: bundle.compatibility,
+            created_at: Some(bundle.created_at.clone()),
+            ..Self::new(index, payload, BulkItemStatus::Valid)
+        }
+    }
+}
+
+/// POST /registry/contracts/bulk - Publish several contract bundles at once
+///
+/// Requires JWT with `contracts:write` scope. Every contract is checked as by
+/// `POST /registry/contracts`, and against earlier versions in the same batch.
+/// Only when all pass are they stored, in request order; if one cannot be
+/// stored, those already stored are removed again. The response reports each
+/// contract: 201 when all were created, 422 when any failed a check, 409 when
+/// a version was published concurrently, 500 when storage failed.
+pub async fn publish_contracts_bulk(
+    State(state): State<AppState>,
+    request: Request<Body>,
+) -> AppResult<(StatusCode, Json<Value>)> {
+    publish_bulk(&state, None, request).await
+}
+
+/// POST /registry/tenants/:tenant/contracts/bulk - Bulk publish into a
+/// tenant's namespace
+pub async fn publish_tenant_contracts_bulk(
+    State(state): State<AppState>,
+    Path(tenant): Path<String>,
+    request: Request<Body>,
+) -> AppResult<(StatusCode, Json<Value>)> {
+    publish_bulk(&state, Some(&tenant), request).await
+}
+
+async fn publish_bulk(
+    state: &AppState,
+    tenant: Option<&str>,
+    request: Request<Body>,
+) -> AppResult<(StatusCode, Json<Value>)> {
+    let claims = require_write_scope(&request)?;
+    let contracts = match tenant {
+        Some(tenant) => tenant_namespace(state, &claims, tenant)?,
+        None => state.kv_client.clone(),
+    };
+
+    let body_bytes = read_body(request).await?;
+    let payloads = serde_json::from_slice::<BulkPublishRequest>(&body_bytes)
+        .map_err(|e| AppError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: format!(
+                "Invalid JSON payload: expected an array of contracts or {{\"contracts\": [...]}}: {}",
+                e
+            ),
+        })?
+        .into_contracts();
+    if payloads.is_empty() || payloads.len() > MAX_BULK_CONTRACTS {
+        return Err(AppError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: format!(
+                "A bulk publish takes 1 to {} contracts, got {}",
+                MAX_BULK_CONTRACTS,
+                payloads.len()
+            ),
+        });
+    }
+
+    debug!(
+        "Handling bulk publish of {} contracts in namespace {}",
+        payloads.len(),
+        contracts.namespace()
+    );
+
+    let (mut results, bundles) =
+        validate_bulk(&contracts, state.default_compatibility, &payloads).await?;
+    let rejected = results
+        .iter()
+        .filter(|r| r.status != BulkItemStatus::Valid)
+        .count();
+    if rejected > 0 {
+        warn!(
+            "Rejected bulk publish in namespace {}: {} of {} contracts failed checks",
+            contracts.namespace(),
+            rejected,
+            results.len()
+        );
+        return Ok(bulk_report(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "rejected",
+            &contracts,
+            format!(
+                "{} of {} contracts failed checks; nothing was published",
+                rejected,
+                results.len()
+            ),
+            results,
+        ));
+    }
+
+    // Charged only for a batch that passed its checks, and refunded if it is
+    // rolled back, so a batch that publishes nothing costs nothing
+    let charged = bundles.len() as u32;
+    charge_publishes(state, tenant, &claims, charged).await?;
+
+    for index in 0..bundles.len() {
+        let failure = match contracts.create_contract(&bundles[index]).await {
+            Ok(true) => {
+                results[index].status = BulkItemStatus::Created;
+                continue;
+            }
+            Ok(false) => (
+                StatusCode::CONFLICT,
+                BulkItemStatus::Conflict,
+                "version was published by another request".to_string(),
+            ),
+            Err(e) => {
+                error!("Failed to store contract during bulk publish: {:#}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    BulkItemStatus::Failed,
+                    format!("Failed to store contract: {:#}", e),
+                )
+            }
+        };
+        let (status_code, status, message) = failure;
+        results[index].status = status;
+        results[index].error = Some(message);
+        rollback_bulk(&contracts, &bundles[..index], &mut results).await;
+        refund_publishes(state, tenant, &claims, charged).await;
+        return Ok(bulk_report(
+            status_code,
+            "rolled-back",
+            &contracts,
+            format!(
+                "Contract {} v{} could not be stored; the batch was rolled back",
+                bundles[index].name, bundles[index].version
+            ),
+            results,
+        ));
+    }
+
+    info!(
+        "Successfully published {} contracts in namespace {}",
+        bundles.len(),
+        contracts.namespace()
+    );
+    Ok(bulk_report(
+        StatusCode::CREATED,
+        "created",
+        &contracts,
+        format!("Published {} contracts", bundles.len()),
+        results,
+    ))
+}
+
+/// Check every contract of a batch, returning a result per contract and the
+/// bundles to store for those that passed
+async fn validate_bulk(
+    contracts: &KvClient,
+    default_compatibility: CompatibilityMode,
+    payloads: &[PublishContractRequest],
+) -> AppResult<(Vec<BulkItemResult>, Vec<ContractBundle>)> {
+    // Stored versions per contract name, plus the batch's accepted ones, so
+    // later versions in the batch are checked against earlier ones
+    let mut versions: HashMap<String, Vec<ContractBundle>> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut results = Vec::with_capacity(payloads.len());
+    let mut bundles = Vec::new();
+    use BulkItemStatus::{Conflict, Invalid};
+
+    for (index, payload) in payloads.iter().enumerate() {
+        if !seen.insert((payload.name.clone(), payload.version.clone())) {
+            results.push(BulkItemResult::rejected(
+                index,
+                payload,
+                Conflict,
+                "version appears more than once in this request",
+            ));
+            continue;
+        }
+        let prior = match versions.get(&payload.name) {
+            Some(prior) => prior.clone(),
+            None => {
+                let prior = load_versions(contracts, &payload.name).await?;
+                versions.insert(payload.name.clone(), prior.clone());
+                prior
+            }
+        };
+        if prior.iter().any(|bundle| bundle.version == payload.version) {
+            results.push(BulkItemResult::rejected(
+                index,
+                payload,
+                Conflict,
+                format!("version {} already exists", payload.version),
+            ));
+            continue;
+        }
+
+        if let Some(raw) = &payload.json_schema {
+            let findings = schema_rules::check(raw, &payload.version);
+            if !findings.is_empty() {
+                let mut result =
+                    BulkItemResult::rejected(index, payload, Invalid, "invalid JSON schema");
+                result.findings = findings;
+                results.push(result);
+                continue;
+            }
+        }
+
+        match check_compatibility_with(prior, default_compatibility, payload) {
+            Ok(mode) => {
+                let bundle = new_bundle(payload, mode)?;
+                results.push(BulkItemResult::accepted(index, payload, &bundle));
+                versions
+                    .entry(payload.name.clone())
+                    .or_default()
+                    .push(bundle.clone());
+                bundles.push(bundle);
+            }
+            Err(e) if e.status_code == StatusCode::CONFLICT => {
+                results.push(BulkItemResult::rejected(
+                    index, payload, Conflict, e.message,
+                ));
+            }
+            Err(e) if e.status_code == StatusCode::BAD_REQUEST => {
+                results.push(BulkItemResult::rejected(index, payload, Invalid, e.message));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok((results, bundles))
+}
+
+/// Remove the bundles a failed bulk publish already stored, newest first
+async fn rollback_bulk(
+    contracts: &KvClient,
+    stored: &[ContractBundle],
+    results: &mut [BulkItemResult],
+) {
+    for (index, bundle) in stored.iter().enumerate().rev() {
+        match contracts
+            .delete_contract(&bundle.name, &bundle.version)
+            .await
+        {
+            Ok(()) => results[index].status = BulkItemStatus::RolledBack,
+            Err(e) => {
+                error!(
+                    "Failed to roll back {} v{} after a failed bulk publish: {:#}",
+                    bundle.name, bundle.version, e
+                );
+                results[index].status = BulkItemStatus::Failed;
+                results[index].error = Some(format!("stored, but rollback failed: {:#}", e));
+            }
+        }
+    }
+}
+
+fn bulk_report(
+    status_code: StatusCode,
+    status: &str,
+    contracts: &KvClient,
+    message: String,
+    results: Vec<BulkItemResult>,
+) -> (StatusCode, Json<Value>) {
+    let key = if status_code.is_success() {
+        "message"
+    } else {
+        "error"
+    };
+    (
+        status_code,
+        Json(json!({
+            "status": status,
+            "namespace": contracts.namespace(),
+            key: message,
+            "results": results
+        })),
+    )
+}
+
+/// Claims of a token allowed to publish (`contracts:write`)
+fn require_write_scope(request: &Request<Body>) -> AppResult<auth::Claims> {
+    let claims = auth::extract_claims(request).ok_or_else(|| AppError {
         status_code: StatusCode::UNAUTHORIZED,
         message: "Missing authentication claims".to_string(),
     })?;
 
-    // Verify contracts:write scope
     if !auth::has_scope(&claims, "contracts:write") {
         warn!("User {} lacks contracts:write scope", claims.sub);
         return Err(AppError {
@@ -105,11 +707,82 @@ pub async fn publish_contract(
             message: "Insufficient permissions: contracts:write scope required".to_string(),
         });
     }
+    Ok(claims)
+}
+
+/// Tenant whose publish quota is charged; outside a tenant namespace the JWT
+/// subject is the tenant
+fn quota_tenant<'a>(tenant: Option<&'a str>, claims: &'a auth::Claims) -> &'a str {
+    tenant
+        .filter(|tenant| *tenant != PLATFORM_NAMESPACE)
+        .unwrap_or(&claims.sub)
+}
 
-    // Extract request body with size limit to prevent DoS
+/// Charge `count` publishes to the publisher's quota
+async fn charge_publishes(
+    state: &AppState,
+    tenant: Option<&str>,
+    claims: &auth::Claims,
+    count: u32,
+) -> AppResult<()> {
+    let quota_tenant = quota_tenant(tenant, claims);
+    let Some(quotas) = &state.quotas else {
+        return Ok(());
+    };
+    let decision = quotas
+        .check_and_consume(quota_tenant, QuotaResource::ContractPublishes, count)
+        .await
+        .map_err(|e| AppError {
+            status_code: StatusCode::SERVICE_UNAVAILABLE,
+            message: format!("Quota check failed: {}", e),
+        })?;
+    if decision.allowed {
+        return Ok(());
+    }
+    warn!(
+        "Tenant {} exceeded contract publish quota ({} used)",
+        quota_tenant, decision.used
+    );
+    if let Some(audit) = &state.audit {
+        let record = WardsDecision::quota_rejected(&decision).with_actor(&claims.sub);
+        audit.record_best_effort(&record).await;
+    }
+    Err(AppError {
+        status_code: StatusCode::TOO_MANY_REQUESTS,
+        message: format!(
+            "Contract publish quota exceeded; retry in {} seconds",
+            decision.reset_after_seconds.unwrap_or_default()
+        ),
+    })
+}
+
+/// Return `count` publishes charged for work that was rolled back
+async fn refund_publishes(
+    state: &AppState,
+    tenant: Option<&str>,
+    claims: &auth::Claims,
+    count: u32,
+) {
+    let Some(quotas) = &state.quotas else {
+        return;
+    };
+    let quota_tenant = quota_tenant(tenant, claims);
+    if let Err(e) = quotas
+        .release(quota_tenant, QuotaResource::ContractPublishes, count)
+        .await
+    {
+        warn!(
+            "Failed to refund {} contract publishes to tenant {}: {}",
+            count, quota_tenant, e
+        );
+    }
+}
+
+/// Read a publish body with size limit to prevent DoS
+async fn read_body(request: Request<Body>) -> AppResult<axum::body::Bytes> {
     // 10 MB limit is reasonable for contract bundles (schemas + metadata)
     const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
-    let body_bytes = axum::body::to_bytes(request.into_body(), MAX_BODY_SIZE)
+    axum::body::to_bytes(request.into_body(), MAX_BODY_SIZE)
         .await
         .map_err(|e| {
             let err_msg = e.to_string();
@@ -127,40 +800,15 @@ pub async fn publish_contract(
                     message: format!("Failed to read request body: {}", e),
                 }
             }
-        })?;
-
-    let payload: PublishContractRequest =
-        serde_json::from_slice(&body_bytes).map_err(|e| AppError {
-            status_code: StatusCode::BAD_REQUEST,
-            message: format!("Invalid JSON payload: {}", e),
-        })?;
-
-    debug!(
-        "Handling POST /registry/contracts for {} v{}",
-        payload.name, payload.version
-    );
-
-    // Check for duplicate version
-    if let Ok(Some(_)) = state
-        .kv_client
-        .get_contract(&payload.name, &payload.version)
-        .await
-    {
-        warn!(
-            "Duplicate contract version: {} v{}",
-            payload.name, payload.version
-        );
-        return Err(AppError {
-            status_code: StatusCode::CONFLICT,
-            message: format!(
-                "Contract {} version {} already exists",
-                payload.name, payload.version
-            ),
-        });
-    }
+        })
+}
 
-    // Compute SHA-256 digest of the bundle content
-    let bundle_json = serde_json::to_vec(&payload).map_err(|e| AppError {
+/// The bundle to store for `payload`, with its SHA-256 digest and timestamp
+fn new_bundle(
+    payload: &PublishContractRequest,
+    compatibility: CompatibilityMode,
+) -> AppResult<ContractBundle> {
+    let bundle_json = serde_json::to_vec(payload).map_err(|e| AppError {
         status_code: StatusCode::INTERNAL_SERVER_ERROR,
         message: format!("Failed to serialize bundle: {}", e),
     })?;
@@ -174,43 +822,110 @@ pub async fn publish_contract(
         payload.name, payload.version, digest
     );
 
-    // Create bundle with timestamp and digest
-    let now = chrono::Utc::now().to_rfc3339();
-    let bundle = ContractBundle {
+    Ok(ContractBundle {
         name: payload.name.clone(),
         version: payload.version.clone(),
         description: payload.description.clone(),
-        created_at: now.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
         json_schema: payload.json_schema.clone(),
         wit_path: payload.wit_path.clone(),
         descriptor_path: payload.descriptor_path.clone(),
-        digest: Some(digest.clone()),
-    };
+        digest: Some(digest),
+        schema_digest: payload.json_schema.as_deref().map(kv::schema_digest),
+        compatibility: Some(compatibility),
+    })
+}
 
-    // Store in KV
-    state.kv_client.put_contract(&bundle).await.map_err(|e| {
-        error!("Failed to store contract: {}", e);
+/// Resolve the policy for `payload` and check its schema against every
+/// earlier version of the contract in the same namespace
+async fn check_compatibility(
+    contracts: &KvClient,
+    default_compatibility: CompatibilityMode,
+    payload: &PublishContractRequest,
+) -> AppResult<CompatibilityMode> {
+    let prior = load_versions(contracts, &payload.name).await?;
+    check_compatibility_with(prior, default_compatibility, payload)
+}
+
+async fn load_versions(contracts: &KvClient, name: &str) -> AppResult<Vec<ContractBundle>> {
+    contracts.list_versions(name).await.map_err(|e| {
+        error!("Failed to load prior versions of {}: {}", name, e);
         AppError {
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            message: format!("Failed to store contract: {}", e),
+            message: format!("Failed to load prior versions: {}", e),
         }
+    })
+}
+
+/// [`check_compatibility`] against an explicit set of stored versions
+fn check_compatibility_with(
+    mut prior: Vec<ContractBundle>,
+    default_compatibility: CompatibilityMode,
+    payload: &PublishContractRequest,
+) -> AppResult<CompatibilityMode> {
+    // Only versions that precede the new one constrain it; without semver every
+    // existing version does
+    let new_version = semver::Version::parse(&payload.version).ok();
+    let parsed = |bundle: &ContractBundle| semver::Version::parse(&bundle.version).ok();
+    if let Some(new_version) = &new_version {
+        prior.retain(|bundle| !matches!(parsed(bundle), Some(v) if v >= *new_version));
+    }
+    prior.sort_by(|a, b| match (parsed(a), parsed(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.created_at.cmp(&b.created_at),
+    });
+
+    let mode = payload
+        .compatibility
+        .or_else(|| prior.iter().rev().find_map(|bundle| bundle.compatibility))
+        .unwrap_or(default_compatibility);
+
+    let Some(proposed) = &payload.json_schema else {
+        return Ok(mode);
+    };
+    if mode == CompatibilityMode::None {
+        return Ok(mode);
+    }
+    let proposed: Value = serde_json::from_str(proposed).map_err(|e| AppError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: format!("jsonSchema is not valid JSON: {}", e),
     })?;
 
-    info!(
-        "Successfully published contract: {} v{} (digest: {})",
-        payload.name, payload.version, digest
-    );
+    for bundle in &prior {
+        let Some(schema) = &bundle.json_schema else {
+            continue;
+        };
+        let Ok(schema) = serde_json::from_str::<Value>(schema) else {
+            warn!(
+                "Skipping compatibility check against unparseable {} v{}",
+                bundle.name, bundle.version
+            );
+            continue;
+        };
+        let violations = mode.check(&schema, &proposed).map_err(|e| AppError {
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            message: format!("Compatibility check failed: {}", e),
+        })?;
+        if !violations.is_empty() {
+            warn!(
+                "Rejected {} v{}: not {} compatible with v{}",
+                payload.name, payload.version, mode, bundle.version
+            );
+            return Err(AppError {
+                status_code: StatusCode::CONFLICT,
+                message: format!(
+                    "Contract {} v{} is not {} compatible with v{}: {}",
+                    payload.name,
+                    payload.version,
+                    mode,
+                    bundle.version,
+                    violations.join("; ")
+                ),
+            });
+        }
+    }
 
-    Ok((
-        StatusCode::CREATED,
-        Json(json!({
-            "status": "created",
-            "name": payload.name,
-            "version": payload.version,
-            "digest": digest,
-            "createdAt": now
-        })),
-    ))
+    Ok(mode)
 }
 
 #[cfg(test)]
@@ -0,0 +1,230 @@
+//! Checks every published JSON schema must pass
+//!
+//! A schema must be valid against the meta-schema of the draft it declares in
+//! `$schema` (draft-07 when it declares none) and follow the house rules:
+//!
+//! - `id`, `title`, `description`: present and non-empty
+//! - `additional-properties`: the root does not set `additionalProperties: true`
+//! - `version`: a `.v<N>` segment in the `$id` file name, and a `:v<N>` suffix
+//!   on the `event` property's `const`, match the published major version
+//!
+//! A publish with any finding is rejected with `422 Unprocessable Entity`
+//! listing every finding, so a malformed schema never reaches consumers.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Drafts a schema may declare in `$schema`
+const DRAFTS: &[&str] = &[
+    "http://json-schema.org/draft-04/schema",
+    "http://json-schema.org/draft-06/schema",
+    "http://json-schema.org/draft-07/schema",
+    "https://json-schema.org/draft/2019-09/schema",
+    "https://json-schema.org/draft/2020-12/schema",
+];
+
+/// One problem with a published schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// Rule that failed, e.g. `meta-schema` or `title`
+    pub rule: &'static str,
+    /// JSON pointer into the schema; empty for the root
+    pub path: String,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(rule: &'static str, path: &str, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Every finding for `raw`, the `jsonSchema` of a publish of `version`
+pub fn check(raw: &str, version: &str) -> Vec<Finding> {
+    let schema: Value = match serde_json::from_str(raw) {
+        Ok(schema) => schema,
+        Err(e) => {
+            return vec![Finding::new(
+                "json",
+                "",
+                format!("jsonSchema is not valid JSON: {}", e),
+            )]
+        }
+    };
+    let mut findings = meta_schema(&schema);
+    findings.extend(house_rules(&schema, version));
+    findings
+}
+
+fn meta_schema(schema: &Value) -> Vec<Finding> {
+    if let Some(declared) = schema.get("$schema") {
+        let known = declared
+            .as_str()
+            .is_some_and(|url| DRAFTS.contains(&url.trim_end_matches('#')));
+        if !known {
+            return vec![Finding::new(
+                "meta-schema",
+                "/$schema",
+                format!(
+                    "unsupported $schema {}; declare one of {}",
+                    declared,
+                    DRAFTS.join(", ")
+                ),
+            )];
+        }
+    }
+    match jsonschema::JSONSchema::compile(schema) {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![Finding::new(
+            "meta-schema",
+            &e.instance_path.to_string(),
+            e.to_string(),
+        )],
+    }
+}
+
+fn house_rules(schema: &Value, version: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (rule, keyword) in [
+        ("id", "$id"),
+        ("title", "title"),
+        ("description", "description"),
+    ] {
+        let present = schema
+            .get(keyword)
+            .and_then(Value::as_str)
+            .is_some_and(|value| !value.trim().is_empty());
+        if !present {
+            findings.push(Finding::new(
+                rule,
+                "",
+                format!("the root must have a non-empty {}", keyword),
+            ));
+        }
+    }
+
+    if schema.get("additionalProperties") == Some(&Value::Bool(true)) {
+        findings.push(Finding::new(
+            "additional-properties",
+            "/additionalProperties",
+            "the root must not allow arbitrary additional properties",
+        ));
+    }
+
+    if let Ok(published) = semver::Version::parse(version) {
+        let declared = [
+            (
+                "/$id",
+                schema
+                    .get("$id")
+                    .and_then(Value::as_str)
+                    .and_then(id_version),
+            ),
+            (
+                "/properties/event/const",
+                schema
+                    .pointer("/properties/event/const")
+                    .and_then(Value::as_str)
+                    .and_then(event_version),
+            ),
+        ];
+        for (path, major) in declared {
+            if let Some(major) = major.filter(|major| *major != published.major) {
+                findings.push(Finding::new(
+                    "version",
+                    path,
+                    format!(
+                        "declares v{} but is published as version {}",
+                        major, version
+                    ),
+                ));
+            }
+        }
+    }
+    findings
+}
+
+/// `https://demon.meta/contracts/approval.denied.v1.json` → 1
+fn id_version(id: &str) -> Option<u64> {
+    let file = id.trim_end_matches('#').rsplit('/').next()?;
+    file.split('.')
+        .rev()
+        .find_map(|segment| segment.strip_prefix('v')?.parse().ok())
+}
+
+/// `approval.denied:v1` → 1
+fn event_version(event: &str) -> Option<u64> {
+    event.rsplit_once(":v")?.1.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://demon.meta/contracts/approval.denied.v1.json",
+            "title": "ApprovalDeniedV1",
+            "description": "An approval was denied",
+            "type": "object",
+            "properties": {"event": {"const": "approval.denied:v1"}},
+            "additionalProperties": false
+        })
+    }
+
+    fn rules(schema: &Value, version: &str) -> Vec<&'static str> {
+        check(&schema.to_string(), version)
+            .into_iter()
+            .map(|finding| finding.rule)
+            .collect()
+    }
+
+    #[test]
+    fn conforming_schema_has_no_findings() {
+        assert!(check(&schema().to_string(), "1.2.0").is_empty());
+        // Versions that are not semver skip the version rule
+        assert!(check(&schema().to_string(), "latest").is_empty());
+    }
+
+    #[test]
+    fn every_house_rule_is_reported() {
+        let findings = check(
+            r#"{"type": "object", "additionalProperties": true}"#,
+            "1.0.0",
+        );
+        let rules: Vec<_> = findings.iter().map(|f| f.rule).collect();
+        assert_eq!(
+            rules,
+            ["id", "title", "description", "additional-properties"]
+        );
+        assert_eq!(findings[3].path, "/additionalProperties");
+    }
+
+    #[test]
+    fn declared_versions_must_match_the_published_major() {
+        let findings = check(&schema().to_string(), "2.0.0");
+        let paths: Vec<_> = findings.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["/$id", "/properties/event/const"]);
+        assert!(findings[0].message.contains("declares v1"));
+    }
+
+    #[test]
+    fn meta_schema_violations_and_unknown_drafts_are_rejected() {
+        let mut invalid = schema();
+        invalid["properties"]["count"] = json!({"type": "integr"});
+        assert_eq!(rules(&invalid, "1.0.0"), ["meta-schema"]);
+
+        let mut unknown = schema();
+        unknown["$schema"] = json!("https://example.com/my-draft");
+        assert_eq!(rules(&unknown, "1.0.0"), ["meta-schema"]);
+
+        assert_eq!(rules(&json!("not a schema"), "1.0.0")[0], "meta-schema");
+        assert_eq!(check("{", "1.0.0")[0].rule, "json");
+    }
+}
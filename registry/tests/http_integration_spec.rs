@@ -50,6 +50,8 @@ async fn given_stored_contract_when_http_get_requested_then_returns_bundle() ->
         wit_path: Some("/test.wit".to_string()),
         descriptor_path: Some("/test.json".to_string()),
         digest: Some("abc123".to_string()),
+        schema_digest: None,
+        compatibility: None,
     };
 
     // Store contract via KV client directly
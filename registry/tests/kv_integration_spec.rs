@@ -57,6 +57,8 @@ async fn given_contract_when_stored_and_retrieved_then_data_matches() -> Result<
         wit_path: Some("/contracts/test.wit".to_string()),
         descriptor_path: Some("/contracts/test.json".to_string()),
         digest: Some("abc123".to_string()),
+        schema_digest: None,
+        compatibility: None,
     };
 
     // Act - Store the contract
@@ -98,6 +100,8 @@ async fn given_stored_contract_when_listed_then_appears_in_results() -> Result<(
         wit_path: None,
         descriptor_path: None,
         digest: Some("def456".to_string()),
+        schema_digest: None,
+        compatibility: None,
     };
 
     // Act
@@ -133,3 +137,95 @@ async fn given_nonexistent_contract_when_retrieved_then_returns_none() -> Result
 
     Ok(())
 }
+
+#[tokio::test]
+#[ignore] // Requires NATS server running
+async fn given_identical_schemas_under_two_names_when_stored_then_body_is_shared() -> Result<()> {
+    // Arrange
+    let (client, _) = new_isolated_client().await?;
+    let schema = r#"{"type": "object", "properties": {"id": {"type": "string"}}}"#;
+    let bundle = |name: &str| ContractBundle {
+        name: name.to_string(),
+        version: "1.0.0".to_string(),
+        description: None,
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+        json_schema: Some(schema.to_string()),
+        wit_path: None,
+        descriptor_path: None,
+        digest: None,
+        schema_digest: None,
+        compatibility: None,
+    };
+    let digest = demon_registry::kv::schema_digest(schema);
+
+    // Act
+    client.put_contract(&bundle("first")).await?;
+    client.put_contract(&bundle("second")).await?;
+
+    // Assert - both resolve to the same stored body and report its digest
+    for name in ["first", "second"] {
+        let retrieved = client
+            .get_contract(name, "1.0.0")
+            .await?
+            .expect("Contract should exist");
+        assert_eq!(retrieved.json_schema.as_deref(), Some(schema));
+        assert_eq!(retrieved.schema_digest.as_deref(), Some(digest.as_str()));
+    }
+
+    // The body outlives one reference and goes with the last
+    client.delete_contract("first", "1.0.0").await?;
+    assert_eq!(client.get_schema(&digest).await?.as_deref(), Some(schema));
+    client.delete_contract("second", "1.0.0").await?;
+    assert!(client.get_schema(&digest).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS server running
+async fn given_rolled_back_batch_when_republished_then_versions_are_created() -> Result<()> {
+    // Arrange
+    let (client, _) = new_isolated_client().await?;
+    let test_name = format!("test-contract-{}", uuid::Uuid::new_v4());
+    let batch: Vec<ContractBundle> = ["1.0.0", "1.1.0"]
+        .into_iter()
+        .map(|version| ContractBundle {
+            name: test_name.clone(),
+            version: version.to_string(),
+            description: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            json_schema: Some(r#"{"type": "object"}"#.to_string()),
+            wit_path: None,
+            descriptor_path: None,
+            digest: None,
+            schema_digest: None,
+            compatibility: None,
+        })
+        .collect();
+
+    // Act - publish the batch, then roll it back as a failed bulk publish does
+    for bundle in &batch {
+        assert!(client.create_contract(bundle).await?);
+    }
+    for bundle in batch.iter().rev() {
+        client
+            .delete_contract(&bundle.name, &bundle.version)
+            .await?;
+    }
+
+    // Assert - the deleted versions can be published again, but only once
+    for bundle in &batch {
+        assert!(
+            client.create_contract(bundle).await?,
+            "{} should be creatable after rollback",
+            bundle.version
+        );
+        assert!(!client.create_contract(bundle).await?);
+        assert!(client
+            .get_contract(&bundle.name, &bundle.version)
+            .await?
+            .is_some());
+    }
+
+    Ok(())
+}
@@ -17,6 +17,7 @@ fn create_test_token(scopes: Vec<String>, secret: &str) -> String {
         exp: (Utc::now() + Duration::hours(1)).timestamp() as usize,
         iat: Some(Utc::now().timestamp() as usize),
         scopes,
+        ..Default::default()
     };
 
     let header = Header::new(Algorithm::HS256);
@@ -28,6 +29,19 @@ fn create_test_token(scopes: Vec<String>, secret: &str) -> String {
     .unwrap()
 }
 
+/// A schema that passes the registry's meta-schema and house rules
+fn conforming_schema(properties: serde_json::Value) -> String {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": "https://demon.meta/contracts/test-contract.json",
+        "title": "TestContract",
+        "description": "Contract published by the registry tests",
+        "type": "object",
+        "properties": properties
+    })
+    .to_string()
+}
+
 #[tokio::test]
 #[ignore] // Requires NATS JetStream
 async fn test_publish_contract_success() {
@@ -47,7 +61,7 @@ async fn test_publish_contract_success() {
         "name": "test-contract",
         "version": "1.0.0",
         "description": "Test contract",
-        "jsonSchema": r#"{"type": "object"}"#
+        "jsonSchema": conforming_schema(json!({}))
     });
 
     // Make POST request
@@ -226,3 +240,225 @@ async fn test_publish_contract_payload_too_large() {
 
     assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
 }
+
+#[tokio::test]
+#[ignore] // Requires NATS JetStream
+async fn test_publish_contract_backward_incompatible_version_rejected() {
+    std::env::set_var("JWT_SECRET", "test-secret");
+    std::env::set_var("NATS_URL", "nats://127.0.0.1:4222");
+
+    let state = AppState::new().await.expect("Failed to create app state");
+    let token = create_test_token(vec!["contracts:write".to_string()], "test-secret");
+    let name = format!("compat-test-{}", Utc::now().timestamp_nanos_opt().unwrap());
+
+    let publish = |payload: serde_json::Value| {
+        let app = create_app(state.clone());
+        let request = Request::builder()
+            .method("POST")
+            .uri("/registry/contracts")
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+            .unwrap();
+        app.oneshot(request)
+    };
+
+    // v1 opts the contract into backward compatibility
+    let response = publish(json!({
+        "name": name,
+        "version": "1.0.0",
+        "compatibility": "backward",
+        "jsonSchema": conforming_schema(json!({"id": {"type": "string"}}))
+    }))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    // v2 inherits the policy and drops a property
+    let response = publish(json!({
+        "name": name,
+        "version": "2.0.0",
+        "jsonSchema": conforming_schema(json!({}))
+    }))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let message = String::from_utf8_lossy(&body_bytes);
+    assert!(message.contains("not backward compatible with v1.0.0"));
+
+    // Adding an optional property is backward compatible
+    let response = publish(json!({
+        "name": name,
+        "version": "1.1.0",
+        "jsonSchema": conforming_schema(json!({"id": {"type": "string"}, "note": {"type": "string"}}))
+    }))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS JetStream
+async fn test_publish_contract_invalid_schema_rejected_with_findings() {
+    std::env::set_var("JWT_SECRET", "test-secret");
+    std::env::set_var("NATS_URL", "nats://127.0.0.1:4222");
+
+    let state = AppState::new().await.expect("Failed to create app state");
+    let app = create_app(state.clone());
+    let token = create_test_token(vec!["contracts:write".to_string()], "test-secret");
+    let name = format!("invalid-test-{}", Utc::now().timestamp_nanos_opt().unwrap());
+
+    let payload = json!({
+        "name": name,
+        "version": "2.0.0",
+        "jsonSchema": json!({
+            "$id": "https://demon.meta/contracts/order.created.v1.json",
+            "type": "object",
+            "properties": {"count": {"type": "integr"}},
+            "additionalProperties": true
+        })
+        .to_string()
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/registry/contracts")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Content-Type", "application/json")
+        .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body["status"], "invalid");
+    let rules: Vec<&str> = body["findings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|finding| finding["rule"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        rules,
+        [
+            "meta-schema",
+            "title",
+            "description",
+            "additional-properties",
+            "version"
+        ]
+    );
+
+    // Nothing was stored
+    assert!(state
+        .kv_client
+        .get_contract(&name, "2.0.0")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS JetStream
+async fn test_bulk_publish_is_all_or_nothing() {
+    std::env::set_var("JWT_SECRET", "test-secret");
+    std::env::set_var("NATS_URL", "nats://127.0.0.1:4222");
+
+    let state = AppState::new().await.expect("Failed to create app state");
+    let token = create_test_token(vec!["contracts:write".to_string()], "test-secret");
+    let name = format!("bulk-test-{}", Utc::now().timestamp_nanos_opt().unwrap());
+
+    let publish = |payload: serde_json::Value| {
+        let app = create_app(state.clone());
+        let request = Request::builder()
+            .method("POST")
+            .uri("/registry/contracts/bulk")
+            .header("Authorization", format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_vec(&payload).unwrap()))
+            .unwrap();
+        app.oneshot(request)
+    };
+    let read_json = |response: axum::response::Response| async move {
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body_bytes).unwrap()
+    };
+
+    // v2 drops a property v1 in the same batch had, so the batch is rejected
+    let response = publish(json!([
+        {
+            "name": name,
+            "version": "1.0.0",
+            "compatibility": "backward",
+            "jsonSchema": conforming_schema(json!({"id": {"type": "string"}}))
+        },
+        {
+            "name": name,
+            "version": "2.0.0",
+            "jsonSchema": conforming_schema(json!({}))
+        }
+    ]))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = read_json(response).await;
+    assert_eq!(body["status"], "rejected");
+    assert_eq!(body["results"][0]["status"], "valid");
+    assert_eq!(body["results"][1]["status"], "conflict");
+    assert!(state
+        .kv_client
+        .get_contract(&name, "1.0.0")
+        .await
+        .unwrap()
+        .is_none());
+
+    // A compatible batch is stored in full
+    let response = publish(json!({
+        "contracts": [
+            {
+                "name": name,
+                "version": "1.0.0",
+                "compatibility": "backward",
+                "jsonSchema": conforming_schema(json!({"id": {"type": "string"}}))
+            },
+            {
+                "name": name,
+                "version": "1.1.0",
+                "jsonSchema": conforming_schema(json!({"id": {"type": "string"}, "note": {"type": "string"}}))
+            }
+        ]
+    }))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = read_json(response).await;
+    assert_eq!(body["results"][1]["status"], "created");
+    assert_eq!(body["results"][1]["compatibility"], "backward");
+    for version in ["1.0.0", "1.1.0"] {
+        assert!(state
+            .kv_client
+            .get_contract(&name, version)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    // Republishing an existing version is a conflict
+    let response = publish(json!([{
+        "name": name,
+        "version": "1.1.0",
+        "jsonSchema": conforming_schema(json!({"id": {"type": "string"}}))
+    }]))
+    .await
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = read_json(response).await;
+    assert_eq!(body["results"][0]["status"], "conflict");
+}
@@ -0,0 +1,271 @@
+//! Integration tests for per-tenant contract namespaces
+//!
+//! Requires NATS server running with JetStream enabled.
+
+use anyhow::Result;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use chrono::{Duration, Utc};
+use demon_registry::{
+    auth::Claims,
+    create_app,
+    kv::{ContractBundle, KvClient},
+    AppState,
+};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::{json, Value};
+use tower::util::ServiceExt; // for `oneshot`
+
+const SECRET: &str = "test-secret-for-integration-tests";
+
+#[tokio::test]
+#[ignore] // Requires NATS server running
+async fn given_tenant_contract_when_other_tenant_reads_then_forbidden() -> Result<()> {
+    // Arrange
+    let state = new_state().await?;
+    put(&state.kv_client.for_tenant("acme")?, "acme.order", "1.0.0").await?;
+    let app = create_app(state);
+
+    // Act
+    let response = app
+        .oneshot(get(
+            "/registry/tenants/acme/contracts/acme.order/1.0.0",
+            &token(&["globex"]),
+        ))
+        .await?;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS server running
+async fn given_tenant_and_platform_contracts_when_listed_then_only_visible_ones_returned(
+) -> Result<()> {
+    // Arrange
+    let state = new_state().await?;
+    put(&state.kv_client, "ritual.started", "1.0.0").await?;
+    put(&state.kv_client.for_tenant("acme")?, "acme.order", "1.0.0").await?;
+    put(
+        &state.kv_client.for_tenant("globex")?,
+        "globex.invoice",
+        "1.0.0",
+    )
+    .await?;
+    let app = create_app(state);
+
+    // Act
+    let response = app
+        .clone()
+        .oneshot(get("/registry/tenants/acme/contracts", &token(&["acme"])))
+        .await?;
+    let platform = app
+        .oneshot(get("/registry/contracts", &token(&["acme"])))
+        .await?;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let names = contract_names(response.into_body()).await?;
+    assert_eq!(
+        names,
+        vec![
+            ("acme.order".to_string(), "acme".to_string()),
+            ("ritual.started".to_string(), "platform".to_string()),
+        ]
+    );
+    assert_eq!(
+        contract_names(platform.into_body()).await?,
+        vec![("ritual.started".to_string(), "platform".to_string())]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS server running
+async fn given_platform_contract_when_tenant_gets_it_then_falls_back_to_platform() -> Result<()> {
+    // Arrange
+    let state = new_state().await?;
+    put(&state.kv_client, "ritual.started", "1.0.0").await?;
+    let app = create_app(state);
+
+    // Act
+    let response = app
+        .oneshot(get(
+            "/registry/tenants/acme/contracts/ritual.started/1.0.0",
+            &token(&["acme"]),
+        ))
+        .await?;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response.into_body()).await?;
+    assert_eq!(body["namespace"], "platform");
+    assert_eq!(body["name"], "ritual.started");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS server running
+async fn given_tenant_token_when_publishing_then_stored_in_tenant_namespace_only() -> Result<()> {
+    // Arrange
+    let state = new_state().await?;
+    let app = create_app(state.clone());
+    let payload = json!({
+        "name": "acme.order",
+        "version": "1.0.0",
+        "jsonSchema": json!({
+            "$id": "https://acme.example/contracts/acme.order.v1.json",
+            "title": "AcmeOrderV1",
+            "description": "An order placed with Acme",
+            "type": "object"
+        })
+        .to_string()
+    });
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/registry/tenants/acme/contracts")
+                .header("Authorization", format!("Bearer {}", token(&["acme"])))
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&payload)?))
+                .unwrap(),
+        )
+        .await?;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(json_body(response.into_body()).await?["namespace"], "acme");
+    let acme = state.kv_client.for_tenant("acme")?;
+    assert!(acme.get_contract("acme.order", "1.0.0").await?.is_some());
+    assert!(state
+        .kv_client
+        .get_contract("acme.order", "1.0.0")
+        .await?
+        .is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS server running
+async fn given_wildcard_tenant_claim_when_publishing_to_any_tenant_then_allowed() -> Result<()> {
+    // Arrange
+    let state = new_state().await?;
+    let app = create_app(state);
+    let payload = json!({ "name": "globex.invoice", "version": "1.0.0" });
+
+    // Act
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/registry/tenants/globex/contracts")
+                .header("Authorization", format!("Bearer {}", token(&["*"])))
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&payload)?))
+                .unwrap(),
+        )
+        .await?;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore] // Requires NATS server running
+async fn given_invalid_tenant_name_when_requested_then_bad_request() -> Result<()> {
+    // Arrange
+    let state = new_state().await?;
+    let app = create_app(state);
+
+    // Act
+    let response = app
+        .oneshot(get("/registry/tenants/Acme.EU/contracts", &token(&["*"])))
+        .await?;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+async fn new_state() -> Result<AppState> {
+    let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+    std::env::set_var("NATS_URL", url);
+    let bucket = format!("contracts_test_{}", uuid::Uuid::new_v4());
+    std::env::set_var("REGISTRY_KV_BUCKET", bucket);
+    std::env::set_var("JWT_SECRET", SECRET);
+    AppState::new().await
+}
+
+async fn put(client: &KvClient, name: &str, version: &str) -> Result<()> {
+    client
+        .put_contract(&ContractBundle {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            json_schema: Some(r#"{"type": "object"}"#.to_string()),
+            wit_path: None,
+            descriptor_path: None,
+            digest: None,
+            schema_digest: None,
+            compatibility: None,
+        })
+        .await
+}
+
+fn token(tenants: &[&str]) -> String {
+    let claims = Claims {
+        sub: "test-user".to_string(),
+        exp: (Utc::now() + Duration::hours(1)).timestamp() as usize,
+        iat: Some(Utc::now().timestamp() as usize),
+        scopes: vec!["contracts:read".to_string(), "contracts:write".to_string()],
+        tenants: tenants.iter().map(|t| t.to_string()).collect(),
+        ..Default::default()
+    };
+    encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+fn get(uri: &str, token: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn json_body(body: Body) -> Result<Value> {
+    let bytes = axum::body::to_bytes(body, usize::MAX).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+async fn contract_names(body: Body) -> Result<Vec<(String, String)>> {
+    let body = json_body(body).await?;
+    Ok(body["contracts"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .map(|c| {
+            (
+                c["name"].as_str().unwrap_or_default().to_string(),
+                c["namespace"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,359 @@
+//! App Pack manifest model shared by the runtime, demonctl and Operate UI
+//!
+//! Manifests declare `apiVersion: demon.io/v1` or `demon.io/v2` and are
+//! validated against the matching schema under `contracts/schemas/`. v2 adds
+//! per-capsule resource limits, required secrets and network policy,
+//! renderer `config` on UI cards, `requires.contracts` /
+//! `requires.capabilities` dependencies checked before a pack is activated,
+//! and an `inputs` schema on rituals for their invocation parameters.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{anyhow, bail, Result};
+use jsonschema::JSONSchema;
+use once_cell::sync::Lazy;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const API_VERSION_V1: &str = "demon.io/v1";
+pub const API_VERSION_V2: &str = "demon.io/v2";
+
+static SCHEMA_V1: Lazy<JSONSchema> = Lazy::new(|| {
+    compile(include_str!(
+        "../../contracts/schemas/app-pack.v1.schema.json"
+    ))
+});
+
+static SCHEMA_V2: Lazy<JSONSchema> = Lazy::new(|| {
+    compile(include_str!(
+        "../../contracts/schemas/app-pack.v2.schema.json"
+    ))
+});
+
+fn compile(schema: &str) -> JSONSchema {
+    let schema: Value = serde_json::from_str(schema).expect("App Pack schema must be valid JSON");
+    JSONSchema::compile(&schema).expect("App Pack schema must compile")
+}
+
+/// Manifest schema a document declares through `apiVersion`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestVersion {
+    V1,
+    V2,
+}
+
+impl ManifestVersion {
+    pub fn from_api_version(api_version: &str) -> Option<Self> {
+        match api_version {
+            API_VERSION_V1 => Some(Self::V1),
+            API_VERSION_V2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    pub fn api_version(self) -> &'static str {
+        match self {
+            Self::V1 => API_VERSION_V1,
+            Self::V2 => API_VERSION_V2,
+        }
+    }
+
+    fn schema(self) -> &'static JSONSchema {
+        match self {
+            Self::V1 => &SCHEMA_V1,
+            Self::V2 => &SCHEMA_V2,
+        }
+    }
+}
+
+/// A schema violation and the JSON pointer of the offending value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON pointer into the manifest, e.g. `/capsules/0/resources/cpus`
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+/// Check a manifest against the schema its `apiVersion` selects
+pub fn schema_violations(manifest: &Value) -> Result<(ManifestVersion, Vec<SchemaViolation>)> {
+    let api_version = manifest
+        .get("apiVersion")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("App Pack manifest is missing apiVersion"))?;
+    let version = ManifestVersion::from_api_version(api_version).ok_or_else(|| {
+        anyhow!(
+            "Unsupported apiVersion '{}' (expected {} or {})",
+            api_version,
+            API_VERSION_V1,
+            API_VERSION_V2
+        )
+    })?;
+
+    let violations = match version.schema().validate(manifest) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|e| SchemaViolation {
+                path: e.instance_path.to_string(),
+                message: e.to_string(),
+            })
+            .collect(),
+    };
+    Ok((version, violations))
+}
+
+/// Validate a manifest, reporting every violation with its path
+pub fn validate_manifest(manifest: &Value) -> Result<ManifestVersion> {
+    let (version, violations) = schema_violations(manifest)?;
+    if !violations.is_empty() {
+        let mut lines = vec![format!(
+            "App Pack schema validation failed ({}):",
+            version.api_version()
+        )];
+        lines.extend(violations.iter().map(|v| format!("  - {}", v)));
+        bail!(lines.join("\n"));
+    }
+    Ok(version)
+}
+
+/// Resource limits for a capsule container (v2)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapsuleResources {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    /// Memory limit such as `256m`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pids_limit: Option<u32>,
+    /// `"all"`, a GPU count, or a list of GPU ids
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpus: Option<capsules_container_exec::GpuRequest>,
+    /// Host device mappings such as `/dev/fuse` or `/dev/dri/card0:/dev/dri/card0:rw`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<String>,
+    /// Writable `/workspace/.scratch` volume discarded after each run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scratch: Option<capsules_container_exec::ScratchVolume>,
+}
+
+/// A secret a capsule requires, exposed as an environment variable (v2)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapsuleSecret {
+    pub env: String,
+    /// `secret://scope/key` reference
+    pub secret: String,
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Network access for a capsule container (v2)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkPolicy {
+    /// No network at all
+    #[default]
+    None,
+    /// Outbound access through the default bridge network
+    Egress,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapsuleNetwork {
+    pub policy: NetworkPolicy,
+}
+
+/// Dependencies declared under `requires` (v2)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackRequirements {
+    #[serde(default)]
+    pub contracts: Vec<ContractRequirement>,
+    /// Runtime capabilities such as `container-exec` or `wasm`
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+impl PackRequirements {
+    /// Read `requires` from a manifest; absent sections mean no requirements
+    pub fn from_manifest(manifest: &Value) -> Result<Self> {
+        match manifest.get("requires") {
+            Some(requires) => serde_json::from_value(requires.clone())
+                .map_err(|err| anyhow!("invalid requires section: {}", err)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Required capabilities this runtime build does not provide
+    pub fn missing_capabilities(&self) -> Vec<&str> {
+        let provided = platform_capabilities();
+        self.capabilities
+            .iter()
+            .map(String::as_str)
+            .filter(|capability| !provided.iter().any(|p| p == capability))
+            .collect()
+    }
+
+    /// Fail with one line per capability this runtime build lacks
+    pub fn ensure_capabilities(&self) -> Result<()> {
+        let missing = self.missing_capabilities();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let mut lines = vec![format!(
+            "App Pack requires capabilities this runtime does not provide (available: {}):",
+            platform_capabilities().join(", ")
+        )];
+        lines.extend(missing.iter().map(|capability| match *capability {
+            "wasm" => "  - wasm: rebuild the runtime with `--features wasm`".to_string(),
+            other => format!("  - {}: not supported by this Demon release", other),
+        }));
+        bail!(lines.join("\n"))
+    }
+}
+
+/// A contract the pack needs in the Schema Registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractRequirement {
+    pub name: String,
+    /// Semver range such as `>=1.2.0 <2.0.0`
+    pub version: String,
+}
+
+impl ContractRequirement {
+    pub fn version_req(&self) -> Result<VersionReq> {
+        parse_version_range(&self.version)
+    }
+
+    /// Whether `version` is a semver version inside the required range
+    pub fn accepts(&self, version: &str) -> bool {
+        match (self.version_req(), Version::parse(version)) {
+            (Ok(req), Ok(version)) => req.matches(&version),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for ContractRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+/// Capabilities provided by this runtime build
+pub fn platform_capabilities() -> Vec<&'static str> {
+    let mut capabilities = vec!["in-process", "container-exec"];
+    if cfg!(feature = "wasm") {
+        capabilities.push("wasm");
+    }
+    capabilities
+}
+
+/// Parse a manifest semver range. Comparators may be separated by spaces
+/// (`>=1.2 <2.0.0`, `>= 1.2`) as well as by commas.
+pub fn parse_version_range(range: &str) -> Result<VersionReq> {
+    let mut comparators: Vec<String> = Vec::new();
+    let mut pending_op = String::new();
+    for token in range.split(|c: char| c == ',' || c.is_whitespace()) {
+        if token.is_empty() {
+            continue;
+        }
+        if token.chars().all(|c| "<>=~^".contains(c)) {
+            pending_op.push_str(token);
+        } else {
+            comparators.push(format!("{}{}", std::mem::take(&mut pending_op), token));
+        }
+    }
+    if comparators.is_empty() || !pending_op.is_empty() {
+        bail!("invalid version range '{}'", range);
+    }
+    VersionReq::parse(&comparators.join(", "))
+        .map_err(|err| anyhow!("invalid version range '{}': {}", range, err))
+}
+
+/// Operate UI card declared under `ui.cards`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiCard {
+    pub id: String,
+    pub kind: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "match")]
+    pub match_rules: UiCardMatch,
+    #[serde(default)]
+    pub fields: Option<UiCardFields>,
+    /// Renderer settings; declared in v2 manifests
+    #[serde(default)]
+    pub config: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiCardMatch {
+    pub rituals: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiCardFields {
+    #[serde(default)]
+    pub show: Vec<String>,
+    #[serde(default)]
+    pub map: BTreeMap<String, String>,
+}
+
+impl UiCard {
+    /// Check if this card matches a given ritual name
+    pub fn matches_ritual(&self, ritual_name: &str) -> bool {
+        self.match_rules.rituals.iter().any(|r| r == ritual_name)
+    }
+
+    /// Get the card configuration as a JSON value
+    pub fn get_config(&self) -> Option<&Value> {
+        self.config.as_ref()
+    }
+}
+
+/// Modification time and size of each file an installed-pack view was built
+/// from. Installs and uninstalls rewrite `registry.json`, so a changed
+/// fingerprint means the view is stale.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreFingerprint(Vec<(PathBuf, Option<(SystemTime, u64)>)>);
+
+impl StoreFingerprint {
+    pub fn of<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Self {
+        Self(
+            paths
+                .into_iter()
+                .map(|path| {
+                    let stamp = std::fs::metadata(path)
+                        .ok()
+                        .and_then(|meta| Some((meta.modified().ok()?, meta.len())));
+                    (path.to_path_buf(), stamp)
+                })
+                .collect(),
+        )
+    }
+
+    /// Files covered by the fingerprint
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.0.iter().map(|(path, _)| path.as_path())
+    }
+}
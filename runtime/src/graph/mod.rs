@@ -2,4 +2,7 @@
 
 pub mod query;
 
-pub use query::{get_commit_by_id, get_tag, graph_subject, list_commits, list_tags, CommitEvent};
+pub use query::{
+    get_commit_by_id, get_snapshot, get_tag, graph_subject, list_commits, list_tags, CommitEvent,
+    GraphSnapshot,
+};
@@ -4,7 +4,8 @@
 
 use anyhow::{Context, Result};
 use async_nats::jetstream;
-use capsules_graph::{CommitResult, GraphScope, TaggedCommit};
+use capsules_graph::storage::GraphStore;
+use capsules_graph::{CommitResult, EdgeSnapshot, GraphScope, NodeSnapshot, TaggedCommit};
 use chrono::Utc;
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
@@ -23,6 +24,10 @@ pub struct CommitEvent {
     pub parent_commit_id: Option<String>,
     pub ts: String,
     pub mutations: Vec<serde_json::Value>,
+    /// Set on snapshot commits written by retention, whose mutations hold the
+    /// whole graph instead of a change
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub snapshot: bool,
 }
 
 impl CommitEvent {
@@ -41,6 +46,33 @@ impl CommitEvent {
     }
 }
 
+/// Nodes and edges of a graph as of a commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphSnapshot {
+    pub commit_id: String,
+    pub nodes: Vec<NodeSnapshot>,
+    pub edges: Vec<EdgeSnapshot>,
+    pub commits_replayed: usize,
+}
+
+impl GraphSnapshot {
+    /// Snapshot of a materialized graph, with nodes and edges sorted by ID
+    pub fn from_store(commit_id: &str, store: GraphStore) -> Self {
+        let mut nodes: Vec<NodeSnapshot> = store.nodes.into_values().collect();
+        nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        let mut edges: Vec<EdgeSnapshot> = store.edges.into_values().collect();
+        edges.sort_by(|a, b| a.edge_id.cmp(&b.edge_id));
+
+        Self {
+            commit_id: commit_id.to_string(),
+            nodes,
+            edges,
+            commits_replayed: store.commit_count,
+        }
+    }
+}
+
 /// Get NATS URL from environment
 fn nats_url() -> String {
     std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string())
@@ -220,6 +252,18 @@ pub async fn list_tags(scope: &GraphScope) -> Result<Vec<TaggedCommit>> {
         .context("Failed to list tags from KV")
 }
 
+/// Materialize every node and edge of a graph as of `commit_id`
+///
+/// Returns `None` when the commit is not in the scope's history.
+pub async fn get_snapshot(scope: &GraphScope, commit_id: &str) -> Result<Option<GraphSnapshot>> {
+    match capsules_graph::storage::materialize_graph_at_commit(scope, commit_id).await {
+        Ok(store) => Ok(Some(GraphSnapshot::from_store(commit_id, store))),
+        // materialize_graph_at_commit replays the whole history before giving up on the target
+        Err(e) if e.to_string().ends_with("not found in stream") => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,4 +293,36 @@ mod tests {
         let subject = graph_subject(&scope, Some("abc123"));
         assert_eq!(subject, "demon.graph.v1.tenant-1.proj-1.ns-1.commit:abc123");
     }
+
+    #[test]
+    fn snapshot_sorts_nodes_and_edges_by_id() {
+        let node = |id: &str| NodeSnapshot {
+            node_id: id.to_string(),
+            labels: vec![],
+            properties: vec![],
+        };
+        let mut store = GraphStore::new();
+        store.nodes.insert("b".to_string(), node("b"));
+        store.nodes.insert("a".to_string(), node("a"));
+        store.edges.insert(
+            "a-b".to_string(),
+            EdgeSnapshot {
+                edge_id: "a-b".to_string(),
+                from_node: "a".to_string(),
+                to_node: "b".to_string(),
+                label: Some("links".to_string()),
+                properties: vec![],
+            },
+        );
+        store.commit_count = 2;
+
+        let snapshot = GraphSnapshot::from_store("abc123", store);
+        let json = serde_json::to_value(&snapshot).unwrap();
+
+        assert_eq!(json["commitId"], "abc123");
+        assert_eq!(json["nodes"][0]["nodeId"], "a");
+        assert_eq!(json["nodes"][1]["nodeId"], "b");
+        assert_eq!(json["edges"][0]["fromNode"], "a");
+        assert_eq!(json["commitsReplayed"], 2);
+    }
 }
@@ -1,3 +1,4 @@
+pub mod app_pack;
 pub mod audit;
 pub mod bundle;
 pub mod contracts;
@@ -0,0 +1,167 @@
+//! Capsule invocation routing
+//!
+//! Every capsule type is executed by a [`CapsuleBackend`]: built-in capsules
+//! (`echo`, `graph`) run in-process, App Pack capsules run through
+//! container-exec, and further types (e.g. WASM) plug in by registering a
+//! backend with the [`CapsuleRouter`]. Backends return a uniform
+//! `ResultEnvelope<Value>`, so callers never need to know how a capsule ran.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use envelope::ResultEnvelope;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tracing::Instrument;
+
+use crate::telemetry::trace_context::{self, TraceContext};
+
+/// How a capsule is executed, as declared by an App Pack capsule's `type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CapsuleType {
+    /// Built-in capsules linked into the runtime
+    InProcess,
+    /// OCI images run by the container-exec capsule
+    ContainerExec,
+    /// WebAssembly components run by wasmtime (runtime feature `wasm`)
+    Wasm,
+}
+
+impl CapsuleType {
+    /// The capsule type behind a functionRef, for refs the runtime knows
+    pub fn for_ref(ref_name: &str) -> Option<Self> {
+        match ref_name {
+            "echo" | "graph" => Some(Self::InProcess),
+            "container-exec" => Some(Self::ContainerExec),
+            "wasm" => Some(Self::Wasm),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InProcess => "in-process",
+            Self::ContainerExec => "container-exec",
+            Self::Wasm => "wasm",
+        }
+    }
+}
+
+impl fmt::Display for CapsuleType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single capsule call
+#[derive(Debug, Clone)]
+pub struct CapsuleInvocation {
+    /// functionRef for built-in capsules (e.g. `echo`), otherwise the capsule ref
+    pub capsule: String,
+    pub args: Value,
+    pub run_id: String,
+    pub ritual_id: String,
+    /// Trace context of the caller, e.g. the ritual step; a new trace is
+    /// started when unset
+    pub trace: Option<TraceContext>,
+}
+
+impl CapsuleInvocation {
+    pub fn new(
+        capsule: impl Into<String>,
+        args: Value,
+        run_id: impl Into<String>,
+        ritual_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            capsule: capsule.into(),
+            args,
+            run_id: run_id.into(),
+            ritual_id: ritual_id.into(),
+            trace: None,
+        }
+    }
+
+    pub fn with_trace(mut self, trace: TraceContext) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+}
+
+/// Executes capsules of one type
+#[async_trait]
+pub trait CapsuleBackend: Send + Sync {
+    /// The capsule type this backend executes
+    fn capsule_type(&self) -> CapsuleType;
+
+    /// Run a capsule. An `Err` means it could not be run at all (bad
+    /// arguments, invalid configuration); capsule failures are error envelopes.
+    async fn invoke(&self, invocation: &CapsuleInvocation) -> Result<ResultEnvelope<Value>>;
+}
+
+/// Dispatches invocations to the backend registered for their capsule type
+#[derive(Clone, Default)]
+pub struct CapsuleRouter {
+    backends: HashMap<CapsuleType, Arc<dyn CapsuleBackend>>,
+}
+
+impl CapsuleRouter {
+    /// A router without backends
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `backend` for its capsule type, replacing any existing one
+    pub fn register(&mut self, backend: Arc<dyn CapsuleBackend>) {
+        self.backends.insert(backend.capsule_type(), backend);
+    }
+
+    pub fn with_backend(mut self, backend: Arc<dyn CapsuleBackend>) -> Self {
+        self.register(backend);
+        self
+    }
+
+    pub fn backend(&self, capsule_type: CapsuleType) -> Option<&Arc<dyn CapsuleBackend>> {
+        self.backends.get(&capsule_type)
+    }
+
+    /// Run `invocation` on the backend for `capsule_type` inside a
+    /// `capsule.invoke` span. The backend sees the span's trace context, which
+    /// is also recorded in the envelope provenance.
+    pub async fn invoke(
+        &self,
+        capsule_type: CapsuleType,
+        invocation: &CapsuleInvocation,
+    ) -> Result<ResultEnvelope<Value>> {
+        let backend = self.backend(capsule_type).ok_or_else(|| {
+            anyhow!(
+                "no backend registered for {} capsules (capsule '{}')",
+                capsule_type,
+                invocation.capsule
+            )
+        })?;
+
+        let span = tracing::info_span!(
+            "capsule.invoke",
+            capsule = %invocation.capsule,
+            capsule_type = %capsule_type,
+            run = %invocation.run_id,
+            trace_id = tracing::field::Empty,
+            span_id = tracing::field::Empty,
+        );
+        let trace = trace_context::enter(&span, invocation.trace.as_ref());
+        let invocation = invocation.clone().with_trace(trace.clone());
+
+        let mut envelope = backend.invoke(&invocation).instrument(span).await?;
+        trace_context::stamp_provenance(&mut envelope, &trace);
+        Ok(envelope)
+    }
+}
+
+/// Erase a capsule's typed envelope into the uniform JSON form
+pub fn json_envelope<T: Serialize>(envelope: ResultEnvelope<T>) -> Result<ResultEnvelope<Value>> {
+    Ok(serde_json::from_value(serde_json::to_value(envelope)?)?)
+}
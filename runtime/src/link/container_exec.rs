@@ -0,0 +1,180 @@
+//! Container-exec capsule backend
+
+use super::capsule::{CapsuleBackend, CapsuleInvocation, CapsuleType};
+use crate::app_pack::{CapsuleNetwork, CapsuleResources, CapsuleSecret, NetworkPolicy};
+use crate::telemetry::trace_context::TRACEPARENT_ENV;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use config_loader::{EnvFileSecretProvider, SecretProviderFactory};
+use envelope::ResultEnvelope;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::task;
+
+/// Runs App Pack capsules as containers through `capsules_container_exec`
+#[derive(Default)]
+pub struct ContainerExecBackend {
+    /// Cancellation flags for container-exec calls, keyed by run id
+    cancel_tokens: Mutex<HashMap<String, capsules_container_exec::CancelToken>>,
+}
+
+impl ContainerExecBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel calls for `run_id`: in-flight containers are killed and later
+    /// calls for the run fail immediately.
+    pub fn cancel_run(&self, run_id: &str) {
+        self.cancel_token(run_id).cancel();
+    }
+
+    /// Forget cancellation state for a run that has finished
+    pub fn release_run(&self, run_id: &str) {
+        self.cancel_tokens
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .remove(run_id);
+    }
+
+    fn cancel_token(&self, run_id: &str) -> capsules_container_exec::CancelToken {
+        self.cancel_tokens
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .entry(run_id.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+#[async_trait]
+impl CapsuleBackend for ContainerExecBackend {
+    fn capsule_type(&self) -> CapsuleType {
+        CapsuleType::ContainerExec
+    }
+
+    async fn invoke(&self, invocation: &CapsuleInvocation) -> Result<ResultEnvelope<Value>> {
+        let mut request: ContainerExecRequest = serde_json::from_value(invocation.args.clone())
+            .context("Failed to parse container-exec request")?;
+        if !request.secrets.is_empty() {
+            resolve_secrets(&mut request)?;
+        }
+        if let Some(trace) = &invocation.trace {
+            request
+                .env
+                .insert(TRACEPARENT_ENV.to_string(), trace.traceparent());
+        }
+
+        let config: capsules_container_exec::ContainerExecConfig = request.into();
+        let cancel = self.cancel_token(&invocation.run_id);
+
+        task::spawn_blocking(move || capsules_container_exec::execute_with_cancel(&config, &cancel))
+            .await
+            .context("container-exec task join error")
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContainerExecRequest {
+    #[serde(rename = "imageDigest")]
+    image_digest: String,
+    command: Vec<String>,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default, rename = "workingDir")]
+    working_dir: Option<String>,
+    outputs: ContainerExecOutputs,
+    #[serde(default, rename = "capsuleName")]
+    capsule_name: Option<String>,
+    #[serde(default, rename = "workspaceDir")]
+    workspace_dir: Option<String>,
+    #[serde(default, rename = "artifactsDir")]
+    artifacts_dir: Option<String>,
+    #[serde(default, rename = "timeoutSeconds")]
+    timeout_seconds: Option<u64>,
+    #[serde(default)]
+    resources: Option<CapsuleResources>,
+    #[serde(default)]
+    secrets: Vec<CapsuleSecret>,
+    #[serde(default)]
+    network: Option<CapsuleNetwork>,
+}
+
+/// Inject the capsule's declared secrets into its environment
+fn resolve_secrets(request: &mut ContainerExecRequest) -> Result<()> {
+    let provider = SecretProviderFactory::create().unwrap_or_else(|e| {
+        tracing::warn!(
+            "Failed to create secret provider from factory: {}. Falling back to EnvFileSecretProvider",
+            e
+        );
+        Box::new(EnvFileSecretProvider::new())
+    });
+
+    for declared in &request.secrets {
+        let (scope, key) = declared
+            .secret
+            .strip_prefix("secret://")
+            .and_then(|rest| rest.split_once('/'))
+            .ok_or_else(|| anyhow!("Invalid secret reference '{}'", declared.secret))?;
+        match provider.resolve(scope, key) {
+            Ok(value) => {
+                request.env.insert(declared.env.clone(), value);
+            }
+            Err(err) if declared.optional => {
+                tracing::debug!(env = %declared.env, error = %err, "optional capsule secret not set");
+            }
+            Err(err) => {
+                return Err(anyhow!(
+                    "Capsule secret {} for env {} is not available: {}",
+                    declared.secret,
+                    declared.env,
+                    err
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ContainerExecOutputs {
+    #[serde(rename = "envelopePath")]
+    envelope_path: String,
+    #[serde(default)]
+    transport: capsules_container_exec::ResultTransport,
+}
+
+impl From<ContainerExecRequest> for capsules_container_exec::ContainerExecConfig {
+    fn from(request: ContainerExecRequest) -> Self {
+        let resources = request.resources.unwrap_or_default();
+        Self {
+            image_digest: request.image_digest,
+            command: request.command,
+            env: request.env,
+            working_dir: request.working_dir,
+            envelope_path: request.outputs.envelope_path,
+            result_transport: request.outputs.transport,
+            timeout_seconds: request.timeout_seconds,
+            capsule_name: request.capsule_name,
+            app_pack_dir: request.workspace_dir.map(PathBuf::from),
+            artifacts_dir: request.artifacts_dir.map(PathBuf::from),
+            network: match request.network.map(|n| n.policy) {
+                Some(NetworkPolicy::Egress) => capsules_container_exec::NetworkMode::Egress,
+                _ => capsules_container_exec::NetworkMode::None,
+            },
+            gpus: resources.gpus,
+            devices: resources.devices,
+            scratch: resources.scratch,
+            resources: capsules_container_exec::ResourceLimits {
+                cpus: resources.cpus,
+                memory: resources.memory,
+                pids_limit: resources.pids_limit,
+            },
+        }
+    }
+}
@@ -1 +1,6 @@
+pub mod capsule;
+pub mod container_exec;
+pub mod in_process;
 pub mod router;
+#[cfg(feature = "wasm")]
+pub mod wasm;
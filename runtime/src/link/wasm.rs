@@ -0,0 +1,378 @@
+//! WASM component capsule backend
+//!
+//! Runs capsules built as WebAssembly components implementing the
+//! `capsule-component` world from `contracts/wit/demon-capsule.wit`. Each call
+//! gets a fresh store with a fuel budget, an epoch deadline (wall-clock
+//! timeout) and a memory limit; running out of any of them ends the call with
+//! an error envelope instead of failing the ritual runner. Components import
+//! nothing, so they have no filesystem, network or clock access.
+
+use super::capsule::{CapsuleBackend, CapsuleInvocation, CapsuleType};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use envelope::{
+    Diagnostic, DurationMetrics, ErrorCategory, ErrorInfo, Metrics, ResultEnvelope,
+    ResultEnvelopeBuilder,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::task;
+use wasmtime::component::{Component, Linker};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+wasmtime::component::bindgen!({
+    path: "../contracts/wit/demon-capsule.wit",
+    world: "capsule-component",
+});
+
+use exports::demon::capsule::capsule::Invocation;
+
+/// How often the epoch ticker advances; timeouts are rounded up to it
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Resource limits for a single component call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WasmLimits {
+    /// Fuel budget; roughly one unit per executed instruction
+    pub fuel: u64,
+    /// Maximum size of each linear memory, in bytes
+    pub memory_bytes: usize,
+    pub timeout: Duration,
+}
+
+impl WasmLimits {
+    /// Defaults overridden by `DEMON_WASM_FUEL`, `DEMON_WASM_MEMORY_LIMIT_BYTES`
+    /// and `DEMON_WASM_TIMEOUT_SECONDS`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            fuel: env_or("DEMON_WASM_FUEL", defaults.fuel),
+            memory_bytes: env_or("DEMON_WASM_MEMORY_LIMIT_BYTES", defaults.memory_bytes),
+            timeout: Duration::from_secs(env_or(
+                "DEMON_WASM_TIMEOUT_SECONDS",
+                defaults.timeout.as_secs(),
+            )),
+        }
+    }
+
+    /// Limits for a call requesting `fuel`, `memory_bytes` and `timeout`.
+    /// Requests can only tighten the backend's limits, never raise them.
+    pub fn restrict(
+        &self,
+        fuel: Option<u64>,
+        memory_bytes: Option<usize>,
+        timeout: Option<Duration>,
+    ) -> Self {
+        Self {
+            fuel: fuel.map_or(self.fuel, |f| f.min(self.fuel)),
+            memory_bytes: memory_bytes.map_or(self.memory_bytes, |m| m.min(self.memory_bytes)),
+            timeout: timeout.map_or(self.timeout, |t| t.min(self.timeout)),
+        }
+    }
+
+    fn epoch_deadline(&self) -> u64 {
+        let ticks = self.timeout.as_millis().div_ceil(EPOCH_TICK.as_millis());
+        u64::try_from(ticks).unwrap_or(u64::MAX).max(1)
+    }
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 10_000_000_000,
+            memory_bytes: 64 * 1024 * 1024,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Runs WebAssembly component capsules with wasmtime
+pub struct WasmBackend {
+    engine: Engine,
+    limits: WasmLimits,
+    /// Compiled components, keyed by path and invalidated when the file changes
+    components: Mutex<HashMap<PathBuf, (Option<SystemTime>, Component)>>,
+    /// Stops advancing the epoch when the backend is dropped
+    _ticker: EpochTicker,
+}
+
+impl WasmBackend {
+    pub fn new(limits: WasmLimits) -> Result<Self> {
+        let mut config = Config::new();
+        config
+            .wasm_component_model(true)
+            .consume_fuel(true)
+            .epoch_interruption(true);
+        let engine = Engine::new(&config).context("Failed to create wasmtime engine")?;
+        let ticker = EpochTicker::start(engine.clone());
+
+        Ok(Self {
+            engine,
+            limits,
+            components: Mutex::new(HashMap::new()),
+            _ticker: ticker,
+        })
+    }
+
+    pub fn limits(&self) -> &WasmLimits {
+        &self.limits
+    }
+
+    fn component(&self, path: &Path) -> Result<Component> {
+        let modified = std::fs::metadata(path)
+            .with_context(|| format!("WASM component not found: {}", path.display()))?
+            .modified()
+            .ok();
+
+        let mut components = self.components.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some((cached_at, component)) = components.get(path) {
+            if modified.is_some() && *cached_at == modified {
+                return Ok(component.clone());
+            }
+        }
+
+        let component = Component::from_file(&self.engine, path)
+            .with_context(|| format!("Failed to load WASM component {}", path.display()))?;
+        components.insert(path.to_path_buf(), (modified, component.clone()));
+        Ok(component)
+    }
+}
+
+#[async_trait]
+impl CapsuleBackend for WasmBackend {
+    fn capsule_type(&self) -> CapsuleType {
+        CapsuleType::Wasm
+    }
+
+    async fn invoke(&self, invocation: &CapsuleInvocation) -> Result<ResultEnvelope<Value>> {
+        let request: WasmRequest = serde_json::from_value(invocation.args.clone())
+            .context("Failed to parse wasm request")?;
+        let limits = self.limits.restrict(
+            request.fuel,
+            request.memory_limit_bytes,
+            request.timeout_seconds.map(Duration::from_secs),
+        );
+        let component = self.component(Path::new(&request.component))?;
+
+        let engine = self.engine.clone();
+        let capsule = request
+            .capsule_name
+            .unwrap_or_else(|| invocation.capsule.clone());
+        let call = Invocation {
+            capsule: capsule.clone(),
+            run_id: invocation.run_id.clone(),
+            ritual_id: invocation.ritual_id.clone(),
+            args: serde_json::to_string(&request.input)?,
+        };
+
+        let outcome =
+            task::spawn_blocking(move || run_component(&engine, &component, &call, limits))
+                .await
+                .context("wasm task join error")??;
+
+        outcome.into_envelope(&capsule, &request.component, limits)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WasmRequest {
+    /// Path to the component (`.wasm`) file
+    component: String,
+    /// Capsule input, passed to the component as JSON
+    #[serde(default)]
+    input: Value,
+    #[serde(default)]
+    capsule_name: Option<String>,
+    #[serde(default)]
+    fuel: Option<u64>,
+    #[serde(default)]
+    memory_limit_bytes: Option<usize>,
+    #[serde(default)]
+    timeout_seconds: Option<u64>,
+}
+
+/// Per-call store state
+struct CapsuleState {
+    limits: StoreLimits,
+}
+
+/// What a component call produced
+struct CallOutcome {
+    result: std::result::Result<String, CallFailure>,
+    fuel_consumed: u64,
+    elapsed: Duration,
+}
+
+enum CallFailure {
+    /// The component returned `err`
+    Capsule(String),
+    OutOfFuel,
+    Timeout,
+    Trap(String),
+}
+
+fn run_component(
+    engine: &Engine,
+    component: &Component,
+    invocation: &Invocation,
+    limits: WasmLimits,
+) -> Result<CallOutcome> {
+    let started = Instant::now();
+    let mut store = Store::new(
+        engine,
+        CapsuleState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(limits.memory_bytes)
+                .trap_on_grow_failure(true)
+                .build(),
+        },
+    );
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(limits.fuel)?;
+    store.set_epoch_deadline(limits.epoch_deadline());
+
+    let linker = Linker::<CapsuleState>::new(engine);
+    let result =
+        CapsuleComponent::instantiate(&mut store, component, &linker).and_then(|bindings| {
+            bindings
+                .demon_capsule_capsule()
+                .call_run(&mut store, invocation)
+        });
+
+    let fuel_consumed = limits.fuel.saturating_sub(store.get_fuel().unwrap_or(0));
+    let result = match result {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(message)) => Err(CallFailure::Capsule(message)),
+        Err(error) => Err(match error.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => CallFailure::OutOfFuel,
+            Some(Trap::Interrupt) => CallFailure::Timeout,
+            _ => CallFailure::Trap(format!("{error:#}")),
+        }),
+    };
+
+    Ok(CallOutcome {
+        result,
+        fuel_consumed,
+        elapsed: started.elapsed(),
+    })
+}
+
+impl CallOutcome {
+    fn into_envelope(
+        self,
+        capsule: &str,
+        component: &str,
+        limits: WasmLimits,
+    ) -> Result<ResultEnvelope<Value>> {
+        let builder = match self.result {
+            Ok(output) => match serde_json::from_str::<Value>(&output) {
+                Ok(data) => ResultEnvelope::builder().success(data),
+                Err(error) => failure(
+                    ErrorInfo::new(format!("component returned invalid JSON: {error}"))
+                        .with_code("WASM_OUTPUT_INVALID")
+                        .with_category(ErrorCategory::User),
+                ),
+            },
+            Err(CallFailure::Capsule(message)) => failure(
+                ErrorInfo::new(message)
+                    .with_code("WASM_CAPSULE_ERROR")
+                    .with_category(ErrorCategory::User),
+            ),
+            Err(CallFailure::OutOfFuel) => failure(
+                ErrorInfo::new(format!("component ran out of fuel ({} units)", limits.fuel))
+                    .with_code("WASM_OUT_OF_FUEL")
+                    .with_category(ErrorCategory::Timeout),
+            ),
+            Err(CallFailure::Timeout) => failure(
+                ErrorInfo::new(format!(
+                    "component timed out after {}ms",
+                    limits.timeout.as_millis()
+                ))
+                .with_code("WASM_TIMEOUT")
+                .with_category(ErrorCategory::Timeout),
+            ),
+            Err(CallFailure::Trap(message)) => failure(
+                ErrorInfo::new(format!("component trapped: {message}"))
+                    .with_code("WASM_TRAP")
+                    .with_category(ErrorCategory::Infrastructure),
+            ),
+        };
+
+        let counters = HashMap::from([(
+            "fuelConsumed".to_string(),
+            i64::try_from(self.fuel_consumed).unwrap_or(i64::MAX),
+        )]);
+
+        Ok(builder
+            .metrics(Metrics {
+                duration: Some(DurationMetrics {
+                    total_ms: Some(self.elapsed.as_secs_f64() * 1000.0),
+                    phases: HashMap::new(),
+                }),
+                resources: None,
+                counters,
+                custom: None,
+            })
+            .add_diagnostic(
+                Diagnostic::debug(format!("component {component}"))
+                    .with_source("wasm")
+                    .with_context(serde_json::json!({
+                        "component": component,
+                        "fuel": limits.fuel,
+                        "memoryLimitBytes": limits.memory_bytes,
+                        "timeoutMs": limits.timeout.as_millis() as u64,
+                    })),
+            )
+            .with_source_info("wasm", Some(env!("CARGO_PKG_VERSION")), Some(capsule))
+            .build()?)
+    }
+}
+
+fn failure(error: ErrorInfo) -> ResultEnvelopeBuilder<Value> {
+    let message = error.message.clone();
+    ResultEnvelope::builder()
+        .error_info(error)
+        .add_diagnostic(Diagnostic::error(message).with_source("wasm"))
+}
+
+/// Advances the engine epoch every [`EPOCH_TICK`] until dropped, so calls
+/// past their epoch deadline are interrupted
+struct EpochTicker {
+    stop: Arc<AtomicBool>,
+}
+
+impl EpochTicker {
+    fn start(engine: Engine) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        std::thread::Builder::new()
+            .name("wasm-epoch".to_string())
+            .spawn(move || {
+                while !flag.load(Ordering::Relaxed) {
+                    std::thread::sleep(EPOCH_TICK);
+                    engine.increment_epoch();
+                }
+            })
+            .expect("failed to spawn wasm epoch thread");
+        Self { stop }
+    }
+}
+
+impl Drop for EpochTicker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -1,14 +1,11 @@
 use anyhow::Result;
 use std::env;
 use tracing::info;
-use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
+    // Initialize tracing; the guard flushes exported spans on exit
+    let _telemetry = runtime::telemetry::otel::init_tracing();
 
     let port = env::var("PORT")
         .unwrap_or_else(|_| "8080".to_string())
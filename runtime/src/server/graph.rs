@@ -1,8 +1,10 @@
 //! Graph REST API endpoints
 //!
-//! Provides read-only REST endpoints for querying graph commits and tags.
+//! Provides read-only REST endpoints for querying graph commits and tags,
+//! plus proxies for the graph capsule's node, neighbor and path queries and a
+//! snapshot of the whole materialized graph at a commit.
 
-use crate::graph::query::{get_commit_by_id, get_tag, list_commits, list_tags};
+use crate::graph::query::{get_commit_by_id, get_snapshot, get_tag, list_commits, list_tags};
 use axum::{
     body::{Body, Bytes},
     extract::{Path, Query},
@@ -55,6 +57,61 @@ pub struct TagQuery {
     pub graph_id: String,
 }
 
+/// Query parameters for node and neighbor lookups
+#[derive(Debug, Deserialize)]
+pub struct NodeQuery {
+    #[serde(rename = "tenantId")]
+    pub tenant_id: String,
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub namespace: String,
+    #[serde(rename = "graphId")]
+    pub graph_id: String,
+    #[serde(rename = "commitId")]
+    pub commit_id: String,
+    pub depth: Option<u32>,
+    pub limit: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+/// Query parameters for whole-graph snapshots
+#[derive(Debug, Deserialize)]
+pub struct SnapshotQuery {
+    #[serde(rename = "tenantId")]
+    pub tenant_id: String,
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub namespace: String,
+    #[serde(rename = "graphId")]
+    pub graph_id: String,
+    #[serde(rename = "commitId")]
+    pub commit_id: String,
+}
+
+/// Query parameters for path existence checks
+#[derive(Debug, Deserialize)]
+pub struct PathQuery {
+    #[serde(rename = "tenantId")]
+    pub tenant_id: String,
+    #[serde(rename = "projectId")]
+    pub project_id: String,
+    pub namespace: String,
+    #[serde(rename = "graphId")]
+    pub graph_id: String,
+    #[serde(rename = "commitId")]
+    pub commit_id: String,
+    pub from: String,
+    pub to: String,
+    #[serde(rename = "maxDepth")]
+    pub max_depth: Option<u32>,
+}
+
+/// Default traversal depth for neighbor queries
+const DEFAULT_NEIGHBOR_DEPTH: u32 = 1;
+
+/// Default traversal depth for path existence queries
+const DEFAULT_PATH_MAX_DEPTH: u32 = 5;
+
 /// Error response format
 #[derive(Serialize)]
 struct ErrorResponse {
@@ -70,6 +127,20 @@ pub fn routes() -> Router {
         .route("/commits/stream", get(stream_commits_sse))
         .route("/tags/:tag", get(get_tag_handler))
         .route("/tags", get(list_tags_handler))
+        .route("/nodes/:nodeId", get(get_node_handler))
+        .route("/nodes/:nodeId/neighbors", get(neighbors_handler))
+        .route("/path", get(path_exists_handler))
+        .route("/snapshot", get(snapshot_handler))
+}
+
+/// Return a capsule envelope, mapping failed envelopes to 500
+fn envelope_response<T: Serialize>(envelope: envelope::ResultEnvelope<T>) -> Response {
+    let status = if envelope.result.is_success() {
+        StatusCode::OK
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    (status, Json(envelope)).into_response()
 }
 
 /// GET /api/graph/commits/:commitId
@@ -288,6 +359,147 @@ async fn list_tags_handler(Query(query): Query<TagQuery>) -> Response {
     }
 }
 
+/// GET /api/graph/nodes/:nodeId
+///
+/// Retrieve a node snapshot at a commit. Returns the graph capsule envelope;
+/// `result.data` is `null` when the node does not exist at that commit.
+///
+/// Query params:
+/// - tenantId, projectId, namespace, graphId (required)
+/// - commitId (required)
+///
+/// Example: GET /api/graph/nodes/node-1?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1&commitId=abc123
+async fn get_node_handler(Path(node_id): Path<String>, Query(query): Query<NodeQuery>) -> Response {
+    debug!("GET /api/graph/nodes/{} with query {:?}", node_id, query);
+
+    let scope = GraphScope {
+        tenant_id: query.tenant_id,
+        project_id: query.project_id,
+        namespace: query.namespace,
+        graph_id: query.graph_id,
+    };
+
+    envelope_response(capsules_graph::get_node(scope, query.commit_id, node_id).await)
+}
+
+/// GET /api/graph/nodes/:nodeId/neighbors
+///
+/// List nodes reachable from a node within `depth` hops at a commit.
+///
+/// Query params:
+/// - tenantId, projectId, namespace, graphId (required)
+/// - commitId (required)
+/// - depth (optional, default: 1)
+/// - limit (optional, default and maximum: 1000)
+/// - cursor (optional, `nextCursor` of the previous page)
+///
+/// Example: GET /api/graph/nodes/node-1/neighbors?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1&commitId=abc123&depth=2&limit=100
+async fn neighbors_handler(
+    Path(node_id): Path<String>,
+    Query(query): Query<NodeQuery>,
+) -> Response {
+    debug!(
+        "GET /api/graph/nodes/{}/neighbors with query {:?}",
+        node_id, query
+    );
+
+    let depth = query.depth.unwrap_or(DEFAULT_NEIGHBOR_DEPTH);
+    let scope = GraphScope {
+        tenant_id: query.tenant_id,
+        project_id: query.project_id,
+        namespace: query.namespace,
+        graph_id: query.graph_id,
+    };
+
+    let page = capsules_graph::PageRequest {
+        limit: query.limit,
+        cursor: query.cursor,
+    };
+
+    envelope_response(capsules_graph::neighbors(scope, query.commit_id, node_id, depth, page).await)
+}
+
+/// GET /api/graph/path
+///
+/// Check whether two nodes are connected within `maxDepth` hops at a commit.
+///
+/// Query params:
+/// - tenantId, projectId, namespace, graphId (required)
+/// - commitId, from, to (required)
+/// - maxDepth (optional, default: 5)
+///
+/// Example: GET /api/graph/path?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1&commitId=abc123&from=a&to=b
+async fn path_exists_handler(Query(query): Query<PathQuery>) -> Response {
+    debug!("GET /api/graph/path with query {:?}", query);
+
+    let max_depth = query.max_depth.unwrap_or(DEFAULT_PATH_MAX_DEPTH);
+    let scope = GraphScope {
+        tenant_id: query.tenant_id,
+        project_id: query.project_id,
+        namespace: query.namespace,
+        graph_id: query.graph_id,
+    };
+
+    envelope_response(
+        capsules_graph::path_exists(scope, query.commit_id, query.from, query.to, max_depth).await,
+    )
+}
+
+/// GET /api/graph/snapshot
+///
+/// Materialize every node and edge of the graph as of a commit.
+///
+/// Query params:
+/// - tenantId, projectId, namespace, graphId (required)
+/// - commitId (required)
+///
+/// Returns: { "commitId": "abc123", "nodes": [...], "edges": [...], "commitsReplayed": 3 }
+///
+/// Example: GET /api/graph/snapshot?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1&commitId=abc123
+async fn snapshot_handler(Query(query): Query<SnapshotQuery>) -> Response {
+    debug!("GET /api/graph/snapshot with query {:?}", query);
+
+    let scope = GraphScope {
+        tenant_id: query.tenant_id,
+        project_id: query.project_id,
+        namespace: query.namespace,
+        graph_id: query.graph_id,
+    };
+
+    match get_snapshot(&scope, &query.commit_id).await {
+        Ok(Some(snapshot)) => {
+            let mut headers = HeaderMap::new();
+            if let Ok(etag) = format!("\"snapshot:{}\"", snapshot.commit_id).parse() {
+                headers.insert(header::ETAG, etag);
+            }
+
+            (StatusCode::OK, headers, Json(snapshot)).into_response()
+        }
+        Ok(None) => {
+            error!("Commit not found for snapshot: {}", query.commit_id);
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Commit '{}' not found", query.commit_id),
+                    code: "COMMIT_NOT_FOUND".to_string(),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("Failed to materialize graph at {}: {}", query.commit_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to materialize graph: {}", e),
+                    code: "INTERNAL_ERROR".to_string(),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
 /// GET /api/graph/commits/stream
 ///
 /// Server-Sent Events endpoint for streaming graph commit updates.
@@ -2,12 +2,16 @@
 
 pub mod graph;
 pub mod rituals;
+pub mod shutdown;
 
+use axum::http::StatusCode;
 use axum::{routing::get, Extension, Router};
+use config_loader::{ConfigSource, RemoteConfigSource};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Create the REST API application router
 pub fn create_app() -> anyhow::Result<Router> {
@@ -33,19 +37,71 @@ async fn health_check() -> &'static str {
     "OK"
 }
 
-/// Readiness check handler
-async fn readiness_check() -> &'static str {
-    "OK"
+/// Readiness check handler; not ready once shutdown has begun
+async fn readiness_check(
+    Extension(service): Extension<Arc<rituals::RitualService>>,
+) -> (StatusCode, &'static str) {
+    if service.is_accepting() {
+        (StatusCode::OK, "OK")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "Draining")
+    }
 }
 
-/// Start the REST API server
+/// Start the REST API server. Runs left `Pending` by a previous shutdown are
+/// resumed; on SIGTERM the server drains in-flight runs before exiting.
 pub async fn serve(addr: SocketAddr) -> anyhow::Result<()> {
-    let app = create_app()?;
+    if ConfigSource::from_env() == ConfigSource::Kv {
+        start_remote_config().await;
+    }
+
+    let service = Arc::new(rituals::RitualService::new()?);
+    match service.resume_pending().await {
+        Ok(0) => {}
+        Ok(resumed) => info!(resumed, "Resumed pending ritual runs"),
+        Err(err) => warn!(error = %err, "Failed to resume pending ritual runs"),
+    }
+
+    let app = create_app_with_service(service.clone());
+    let coordinator =
+        shutdown::ShutdownCoordinator::new(service, shutdown::ShutdownConfig::from_env());
 
     info!("Starting REST API server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown::wait_for_signal().await;
+            coordinator.shutdown().await;
+        })
+        .await?;
 
     Ok(())
 }
+
+/// With `CONFIG_SOURCE=kv`, mirror capsule config from the KV bucket into the
+/// local cache before serving, then keep following the bucket. Failures are
+/// logged and leave the last cached config in place.
+async fn start_remote_config() {
+    let source = match RemoteConfigSource::from_env().await {
+        Ok(source) => source,
+        Err(err) => {
+            warn!(error = %err, "Config KV unavailable; using cached config");
+            return;
+        }
+    };
+    if let Err(err) = source.sync().await {
+        warn!(error = %err, "Failed to synchronize config from KV; using cached config");
+    }
+    tokio::spawn(async move {
+        loop {
+            if let Err(err) = source.watch().await {
+                warn!(error = %err, "Config KV watch failed");
+            }
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            if let Err(err) = source.sync().await {
+                warn!(error = %err, "Failed to resynchronize config from KV");
+            }
+        }
+    });
+}
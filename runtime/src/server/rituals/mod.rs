@@ -1,30 +1,35 @@
 mod models;
+mod queue;
 mod registry;
 mod runner;
 mod service;
 mod store;
 
 pub use models::*;
-pub use registry::AppPackRegistry;
+pub use queue::{parse_weights, FairQueue, FairQueueConfig};
+pub use registry::{AppPackRegistry, RegistrySnapshot, ResolvedInvocation};
 pub use runner::{EngineRitualRunner, ExecutionPlan, RitualRunner};
 pub use service::RitualService;
 pub use store::RunStore;
 
 use axum::extract::{Path, Query};
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Extension, Json, Router};
 use serde::Deserialize;
 use serde_json::json;
 use std::time::Duration;
-use tracing::{info, warn};
+use tracing::{field::Empty, info, info_span, warn, Instrument};
 
 use std::sync::Arc;
 
+use crate::telemetry::trace_context::{self, TraceContext, TRACEPARENT_HEADER};
+
 /// Build the ritual API router.
 pub fn routes() -> Router {
     Router::new()
+        .route("/queue", get(get_queue_status))
         .route(
             "/:ritual/runs",
             post(schedule_ritual_run).get(list_ritual_runs),
@@ -55,6 +60,7 @@ struct RunLookupQuery {
 async fn schedule_ritual_run(
     Extension(service): Extension<Arc<RitualService>>,
     Path(ritual): Path<String>,
+    headers: HeaderMap,
     Json(request): Json<RitualInvocationRequest>,
 ) -> Response {
     if request.app.trim().is_empty() {
@@ -65,7 +71,25 @@ async fn schedule_ritual_run(
             .into_response();
     }
 
-    match service.schedule_run(&ritual, request).await {
+    // Continue the caller's trace (W3C `traceparent`) through the run
+    let parent = headers
+        .get(TRACEPARENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(TraceContext::parse);
+    let span = info_span!(
+        "ritual.schedule",
+        %ritual,
+        app = %request.app,
+        trace_id = Empty,
+        span_id = Empty,
+    );
+    let trace = trace_context::enter(&span, parent.as_ref());
+
+    match service
+        .schedule_traced_run(&ritual, request, Some(trace))
+        .instrument(span)
+        .await
+    {
         Ok((_record, response)) => (StatusCode::ACCEPTED, Json(response)).into_response(),
         Err(err) => {
             let message = err.to_string();
@@ -192,8 +216,14 @@ async fn get_ritual_run_envelope(
     }
 }
 
+async fn get_queue_status(Extension(service): Extension<Arc<RitualService>>) -> Response {
+    (StatusCode::OK, Json(service.queue_status())).into_response()
+}
+
 fn classify_error(message: &str) -> StatusCode {
-    if message.contains("not installed")
+    if message.contains(service::SHUTTING_DOWN) {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else if message.contains("not installed")
         || message.contains("not defined")
         || message.contains("not found")
     {
@@ -273,6 +303,15 @@ async fn stream_run_sse(
                         }
                         break;
                     }
+                    _ if service.is_drained() => {
+                        let ev = serde_json::json!({
+                            "type": "warning",
+                            "message": "Runtime shut down before the run finished; it will be resumed",
+                            "runId": run,
+                        });
+                        yield Ok::<_, std::convert::Infallible>(axum::response::sse::Event::default().json_data(ev).unwrap());
+                        break;
+                    }
                     _ => {}
                 }
             } else {
@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct RitualInvocationRequest {
     pub app: String,
+    /// Tenant whose run queue the run waits in; defaults to the app
+    #[serde(default)]
+    pub tenant: Option<String>,
     #[serde(default)]
     pub version: Option<String>,
     #[serde(default)]
@@ -26,6 +29,9 @@ pub enum RunStatus {
 pub struct RunRecord {
     pub run_id: String,
     pub app: String,
+    /// Tenant whose run queue the run is scheduled through
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
     pub ritual: String,
     pub version: String,
     pub status: RunStatus,
@@ -156,6 +162,25 @@ pub struct RunEnvelopeResponse {
     pub envelope: serde_json::Value,
 }
 
+/// Run queue occupancy across tenants
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueStatus {
+    pub max_concurrent: usize,
+    pub running: usize,
+    pub queued: usize,
+    pub tenants: Vec<TenantQueueStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TenantQueueStatus {
+    pub tenant: String,
+    pub weight: u32,
+    pub queued: usize,
+    pub running: usize,
+}
+
 mod serde_rfc3339 {
     use chrono::{DateTime, Utc};
     use serde::{self, Deserialize, Deserializer, Serializer};
@@ -0,0 +1,189 @@
+//! Fair scheduling of ritual runs across tenants
+//!
+//! Runs wait in one FIFO queue per tenant and are started, up to a global
+//! concurrency limit, by weighted round robin over the tenants with queued
+//! work: each turn a tenant may start as many runs as its weight before the
+//! next tenant gets a turn. A burst from one tenant therefore only lengthens
+//! that tenant's queue; other tenants keep getting their share of slots.
+
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{anyhow, Result};
+use metrics::gauge;
+use tracing::warn;
+
+/// Concurrency limit and tenant weights for the run queue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FairQueueConfig {
+    /// Runs executing at once across all tenants
+    pub max_concurrent: usize,
+    /// Runs a tenant may start per turn; unlisted tenants use `default_weight`
+    pub tenant_weights: HashMap<String, u32>,
+    pub default_weight: u32,
+}
+
+impl Default for FairQueueConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 16,
+            tenant_weights: HashMap::new(),
+            default_weight: 1,
+        }
+    }
+}
+
+impl FairQueueConfig {
+    /// Load from `DEMON_RUNTIME_MAX_CONCURRENT_RUNS` and
+    /// `DEMON_RUNTIME_TENANT_WEIGHTS` (e.g. `acme=3,globex=1`)
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Some(max) = std::env::var("DEMON_RUNTIME_MAX_CONCURRENT_RUNS")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+        {
+            config.max_concurrent = max.max(1);
+        }
+        if let Ok(raw) = std::env::var("DEMON_RUNTIME_TENANT_WEIGHTS") {
+            match parse_weights(&raw) {
+                Ok(weights) => config.tenant_weights = weights,
+                Err(err) => warn!(error = %err, "ignoring DEMON_RUNTIME_TENANT_WEIGHTS"),
+            }
+        }
+        config
+    }
+
+    pub fn weight(&self, tenant: &str) -> u32 {
+        self.tenant_weights
+            .get(tenant)
+            .copied()
+            .unwrap_or(self.default_weight)
+            .max(1)
+    }
+}
+
+/// Parse `tenant=weight` pairs separated by commas
+pub fn parse_weights(raw: &str) -> Result<HashMap<String, u32>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (tenant, weight) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("tenant weight '{pair}' must be tenant=weight"))?;
+            let weight: u32 = weight
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("tenant weight '{pair}' must be a positive integer"))?;
+            if weight == 0 {
+                return Err(anyhow!("tenant weight '{pair}' must be a positive integer"));
+            }
+            Ok((tenant.trim().to_string(), weight))
+        })
+        .collect()
+}
+
+/// Per-tenant FIFO queues drained by weighted round robin
+#[derive(Debug)]
+pub struct FairQueue<T> {
+    config: FairQueueConfig,
+    queues: HashMap<String, VecDeque<T>>,
+    /// Tenants with queued items, in turn order; the front tenant has the turn
+    turns: VecDeque<String>,
+    /// Items the front tenant may still take this turn
+    credit: u32,
+}
+
+impl<T> FairQueue<T> {
+    pub fn new(config: FairQueueConfig) -> Self {
+        Self {
+            config,
+            queues: HashMap::new(),
+            turns: VecDeque::new(),
+            credit: 0,
+        }
+    }
+
+    pub fn config(&self) -> &FairQueueConfig {
+        &self.config
+    }
+
+    /// Queue `item` behind the tenant's earlier items
+    pub fn push(&mut self, tenant: &str, item: T) {
+        let queue = self.queues.entry(tenant.to_string()).or_default();
+        if queue.is_empty() {
+            self.turns.push_back(tenant.to_string());
+        }
+        queue.push_back(item);
+        record_depth(tenant, queue.len());
+    }
+
+    /// Take the next item, along with its tenant
+    pub fn pop(&mut self) -> Option<(String, T)> {
+        let tenant = self.turns.front()?.clone();
+        if self.credit == 0 {
+            self.credit = self.config.weight(&tenant);
+        }
+
+        let queue = self.queues.get_mut(&tenant)?;
+        let item = queue.pop_front()?;
+        let remaining = queue.len();
+        record_depth(&tenant, remaining);
+        self.credit -= 1;
+
+        if remaining == 0 {
+            self.queues.remove(&tenant);
+            self.turns.pop_front();
+            self.credit = 0;
+        } else if self.credit == 0 {
+            self.turns.rotate_left(1);
+        }
+        Some((tenant, item))
+    }
+
+    /// Remove and return the first queued item matching `predicate`
+    pub fn remove_where(&mut self, mut predicate: impl FnMut(&T) -> bool) -> Option<T> {
+        let (tenant, index) = self.queues.iter().find_map(|(tenant, queue)| {
+            queue
+                .iter()
+                .position(&mut predicate)
+                .map(|index| (tenant.clone(), index))
+        })?;
+
+        let queue = self.queues.get_mut(&tenant)?;
+        let item = queue.remove(index)?;
+        record_depth(&tenant, queue.len());
+        if queue.is_empty() {
+            self.queues.remove(&tenant);
+            if self.turns.front() == Some(&tenant) {
+                self.credit = 0;
+            }
+            self.turns.retain(|t| t != &tenant);
+        }
+        Some(item)
+    }
+
+    /// Queued items for `tenant`
+    pub fn depth(&self, tenant: &str) -> usize {
+        self.queues.get(tenant).map_or(0, VecDeque::len)
+    }
+
+    /// Queued items per tenant with queued work
+    pub fn depths(&self) -> HashMap<String, usize> {
+        self.queues
+            .iter()
+            .map(|(tenant, queue)| (tenant.clone(), queue.len()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queues.values().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+}
+
+fn record_depth(tenant: &str, depth: usize) {
+    gauge!("demon_runtime_queue_depth", depth as f64, "tenant" => tenant.to_string());
+}
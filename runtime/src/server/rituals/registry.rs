@@ -1,28 +1,51 @@
 use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
 use anyhow::{anyhow, bail, Context, Result};
 use semver::Version;
 use serde::Deserialize;
+use tracing::{info, warn};
 
 use super::models::RitualInvocationRequest;
+use crate::app_pack::{self, CapsuleNetwork, CapsuleResources, CapsuleSecret, StoreFingerprint};
+use crate::link::capsule::CapsuleType;
 
+/// Installed App Packs, read from `registry.json` and the manifests it lists
+///
+/// Lookups go through an immutable [`RegistrySnapshot`]. The snapshot is
+/// rebuilt when `registry.json` or one of its manifests changes, so packs
+/// installed, upgraded or removed by `demonctl app` take effect without a
+/// restart, while a run keeps the manifest it was planned from.
 #[derive(Clone)]
 pub struct AppPackRegistry {
     #[allow(dead_code)]
     root: PathBuf,
     registry_path: PathBuf,
+    current: Arc<RwLock<Option<Arc<RegistrySnapshot>>>>,
+}
+
+/// A consistent view of the installed App Packs
+#[derive(Debug)]
+pub struct RegistrySnapshot {
+    /// Increases by one each time the registry is reloaded
+    pub version: u64,
+    fingerprint: StoreFingerprint,
+    apps: BTreeMap<String, Vec<SnapshotInstall>>,
+}
+
+#[derive(Debug)]
+struct SnapshotInstall {
+    version: String,
+    manifest_path: PathBuf,
+    /// The parsed manifest, or why it could not be loaded
+    manifest: std::result::Result<Arc<AppPackManifest>, String>,
 }
 
 impl AppPackRegistry {
     pub fn new() -> Result<Self> {
-        let root = resolve_store_root()?;
-        let registry_path = root.join("registry.json");
-        Ok(Self {
-            root,
-            registry_path,
-        })
+        Ok(Self::with_root(resolve_store_root()?))
     }
 
     pub fn with_root(root: PathBuf) -> Self {
@@ -30,6 +53,58 @@ impl AppPackRegistry {
         Self {
             root,
             registry_path,
+            current: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// The current snapshot, reloaded first if the installed packs changed
+    pub fn snapshot(&self) -> Result<Arc<RegistrySnapshot>> {
+        let current = self
+            .current
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone();
+        if let Some(snapshot) = &current {
+            if StoreFingerprint::of(snapshot.fingerprint.paths()) == snapshot.fingerprint {
+                return Ok(snapshot.clone());
+            }
+        }
+
+        let mut slot = self.current.write().unwrap_or_else(|p| p.into_inner());
+        // Another caller may have reloaded while we waited for the lock
+        if let (Some(latest), Some(seen)) = (slot.as_ref(), current.as_ref()) {
+            if latest.version != seen.version {
+                return Ok(latest.clone());
+            }
+        }
+
+        let version = slot.as_ref().map_or(1, |s| s.version + 1);
+        match self.load_snapshot(version) {
+            Ok(snapshot) => {
+                let snapshot = Arc::new(snapshot);
+                info!(
+                    version = snapshot.version,
+                    apps = snapshot.apps.len(),
+                    "loaded App Pack registry"
+                );
+                *slot = Some(snapshot.clone());
+                Ok(snapshot)
+            }
+            // A half-written registry.json should not take packs offline
+            Err(err) => match slot.as_ref() {
+                Some(previous) if self.registry_path.exists() => {
+                    warn!(
+                        error = %format!("{err:#}"),
+                        version = previous.version,
+                        "failed to reload App Pack registry; keeping previous snapshot"
+                    );
+                    Ok(previous.clone())
+                }
+                _ => {
+                    *slot = None;
+                    Err(err)
+                }
+            },
         }
     }
 
@@ -38,8 +113,8 @@ impl AppPackRegistry {
         ritual_name: &str,
         request: &RitualInvocationRequest,
     ) -> Result<ResolvedInvocation> {
-        let registry = self.load_registry()?;
-        let installs = registry
+        let snapshot = self.snapshot()?;
+        let installs = snapshot
             .apps
             .get(&request.app)
             .ok_or_else(|| anyhow!("App Pack '{}' is not installed", request.app))?;
@@ -55,7 +130,7 @@ impl AppPackRegistry {
             })?
         };
 
-        let manifest = load_manifest(&install.manifest_path)?;
+        let manifest = install.manifest.clone().map_err(|err| anyhow!(err))?;
         let ritual = manifest
             .rituals
             .iter()
@@ -73,9 +148,10 @@ impl AppPackRegistry {
         ensure_single_step(&ritual)?;
 
         Ok(ResolvedInvocation {
-            manifest,
+            manifest: (*manifest).clone(),
             ritual,
             manifest_path: install.manifest_path.clone(),
+            registry_version: snapshot.version,
         })
     }
 }
@@ -85,6 +161,8 @@ pub struct ResolvedInvocation {
     pub manifest: AppPackManifest,
     pub ritual: RitualEntry,
     pub manifest_path: PathBuf,
+    /// Registry snapshot the invocation was resolved against
+    pub registry_version: u64,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -104,27 +182,48 @@ pub struct ManifestMetadata {
 #[derive(Debug, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum CapsuleEntry {
-    #[serde(rename_all = "camelCase")]
-    ContainerExec {
-        name: String,
-        #[serde(rename = "imageDigest")]
-        image_digest: String,
-        command: Vec<String>,
-        #[serde(default)]
-        env: BTreeMap<String, String>,
-        #[serde(default, rename = "workingDir")]
-        working_dir: Option<String>,
-        outputs: CapsuleOutputs,
-    },
+    ContainerExec(Box<ContainerExecCapsule>),
     #[serde(other)]
     Unsupported,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerExecCapsule {
+    pub name: String,
+    #[serde(rename = "imageDigest")]
+    pub image_digest: String,
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default, rename = "workingDir")]
+    pub working_dir: Option<String>,
+    pub outputs: CapsuleOutputs,
+    #[serde(default)]
+    pub resources: Option<CapsuleResources>,
+    #[serde(default)]
+    pub secrets: Vec<CapsuleSecret>,
+    #[serde(default)]
+    pub network: Option<CapsuleNetwork>,
+}
+
+impl CapsuleEntry {
+    /// The backend this capsule runs on, or `None` for unsupported types
+    pub fn capsule_type(&self) -> Option<CapsuleType> {
+        match self {
+            Self::ContainerExec(_) => Some(CapsuleType::ContainerExec),
+            Self::Unsupported => None,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CapsuleOutputs {
     #[serde(rename = "envelopePath")]
     pub envelope_path: String,
+    #[serde(default)]
+    pub transport: capsules_container_exec::ResultTransport,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -132,6 +231,9 @@ pub struct RitualEntry {
     pub name: String,
     #[serde(default)]
     pub steps: Vec<RitualStep>,
+    /// JSON Schema for invocation parameters (App Pack v2)
+    #[serde(default)]
+    pub inputs: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -157,7 +259,15 @@ fn resolve_store_root() -> Result<PathBuf> {
 fn load_manifest(path: &Path) -> Result<AppPackManifest> {
     let raw = fs::read_to_string(path)
         .with_context(|| format!("reading manifest at {}", path.display()))?;
-    let manifest = serde_yaml::from_str(&raw).with_context(|| "parsing App Pack manifest")?;
+    let document: serde_json::Value =
+        serde_yaml::from_str(&raw).with_context(|| "parsing App Pack manifest")?;
+    app_pack::validate_manifest(&document)
+        .with_context(|| format!("invalid App Pack manifest at {}", path.display()))?;
+    // A pack needing e.g. wasm on a build without it stays inactive
+    app_pack::PackRequirements::from_manifest(&document)
+        .and_then(|requirements| requirements.ensure_capabilities())
+        .with_context(|| format!("App Pack at {} cannot be activated", path.display()))?;
+    let manifest = serde_json::from_value(document).with_context(|| "parsing App Pack manifest")?;
     Ok(manifest)
 }
 
@@ -198,10 +308,41 @@ impl AppPackRegistry {
         let registry = serde_json::from_str(&raw).with_context(|| "parsing registry JSON")?;
         Ok(registry)
     }
+
+    fn load_snapshot(&self, version: u64) -> Result<RegistrySnapshot> {
+        let registry = self.load_registry()?;
+        let mut paths = vec![self.registry_path.clone()];
+        let apps = registry
+            .apps
+            .into_iter()
+            .map(|(name, installs)| {
+                let installs = installs
+                    .into_iter()
+                    .map(|install| {
+                        paths.push(install.manifest_path.clone());
+                        SnapshotInstall {
+                            manifest: load_manifest(&install.manifest_path)
+                                .map(Arc::new)
+                                .map_err(|err| format!("{err:#}")),
+                            version: install.version,
+                            manifest_path: install.manifest_path,
+                        }
+                    })
+                    .collect();
+                (name, installs)
+            })
+            .collect();
+
+        Ok(RegistrySnapshot {
+            version,
+            fingerprint: StoreFingerprint::of(paths.iter().map(PathBuf::as_path)),
+            apps,
+        })
+    }
 }
 
-fn select_latest(installs: &[RegistryInstall]) -> Option<&RegistryInstall> {
-    let mut best: Option<(&RegistryInstall, Version)> = None;
+fn select_latest(installs: &[SnapshotInstall]) -> Option<&SnapshotInstall> {
+    let mut best: Option<(&SnapshotInstall, Version)> = None;
     for install in installs {
         if let Ok(ver) = Version::parse(&install.version) {
             match best {
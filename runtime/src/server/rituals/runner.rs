@@ -1,33 +1,77 @@
 use async_trait::async_trait;
+use tracing::{field::Empty, info_span, Instrument};
+
+use crate::link::capsule::{CapsuleInvocation, CapsuleType};
+use crate::link::router::Router;
+use crate::telemetry::trace_context::{self, TraceContext};
 
 #[derive(Debug, Clone)]
 pub struct ExecutionPlan {
     pub run_id: String,
     pub ritual_id: String,
     pub capsule_ref: String,
+    /// Backend the capsule runs on, from the App Pack manifest's capsule type
+    pub capsule_type: CapsuleType,
     pub arguments: serde_json::Value,
+    /// Trace context of the request that scheduled the run, if any
+    pub trace: Option<TraceContext>,
 }
 
 #[async_trait]
 pub trait RitualRunner: Send + Sync {
     async fn run(&self, plan: ExecutionPlan) -> anyhow::Result<serde_json::Value>;
+
+    /// Stop work started for `run_id` that outlives the `run` future, such as
+    /// capsule containers. Called when a run is canceled or interrupted.
+    async fn cancel(&self, _run_id: &str) {}
 }
 
-#[derive(Debug, Default)]
-pub struct EngineRitualRunner;
+#[derive(Default)]
+pub struct EngineRitualRunner {
+    router: Router,
+}
+
+impl EngineRitualRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
 #[async_trait]
 impl RitualRunner for EngineRitualRunner {
     async fn run(&self, plan: ExecutionPlan) -> anyhow::Result<serde_json::Value> {
-        let router = crate::link::router::Router::new();
-        let outputs = router
-            .dispatch(
-                &plan.capsule_ref,
-                &plan.arguments,
-                &plan.run_id,
-                &plan.ritual_id,
-            )
-            .await?;
+        let run_span = info_span!(
+            "ritual.run",
+            ritual = %plan.ritual_id,
+            run = %plan.run_id,
+            trace_id = Empty,
+            span_id = Empty,
+        );
+        let run_trace = trace_context::enter(&run_span, plan.trace.as_ref());
+        let step_span = info_span!(
+            parent: &run_span,
+            "ritual.step",
+            step = %plan.capsule_ref,
+            trace_id = Empty,
+            span_id = Empty,
+        );
+        let step_trace = trace_context::enter(&step_span, Some(&run_trace));
+
+        let invocation = CapsuleInvocation::new(
+            plan.capsule_ref.clone(),
+            plan.arguments.clone(),
+            plan.run_id.clone(),
+            plan.ritual_id.clone(),
+        )
+        .with_trace(step_trace);
+        let outputs = self
+            .router
+            .invoke_capsule(plan.capsule_type, &invocation)
+            .instrument(step_span)
+            .instrument(run_span)
+            .await;
+        self.router.release_run(&plan.run_id);
+        let outputs = outputs?;
 
         Ok(serde_json::json!({
             "event": "ritual.completed:v1",
@@ -37,4 +81,8 @@ impl RitualRunner for EngineRitualRunner {
             "outputs": outputs
         }))
     }
+
+    async fn cancel(&self, run_id: &str) {
+        self.router.cancel_run(run_id);
+    }
 }
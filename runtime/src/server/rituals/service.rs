@@ -1,34 +1,97 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
-use futures_util::future::{AbortHandle, Abortable};
+use futures_util::future::{AbortHandle, AbortRegistration, Abortable};
+use metrics::{gauge, histogram};
 use serde_json::Value as JsonValue;
-use tokio::sync::Mutex;
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use super::models::{
-    RitualInvocationRequest, RunCreatedResponse, RunDetail, RunLinks, RunListResponse, RunRecord,
-    RunStatus,
+    QueueStatus, RitualInvocationRequest, RunCreatedResponse, RunDetail, RunLinks, RunListResponse,
+    RunRecord, RunStatus, TenantQueueStatus,
+};
+use super::queue::{FairQueue, FairQueueConfig};
+use super::registry::{
+    AppPackRegistry, CapsuleEntry, ContainerExecCapsule, ResolvedInvocation, RitualEntry,
 };
-use super::registry::{AppPackRegistry, CapsuleEntry, ResolvedInvocation};
 use super::runner::{EngineRitualRunner, ExecutionPlan, RitualRunner};
 use super::store::RunStore;
+use crate::telemetry::TraceContext;
+
+/// Error returned for runs submitted after shutdown has begun
+pub(super) const SHUTTING_DOWN: &str = "runtime is shutting down; not accepting new runs";
+
+/// How often [`RitualService::wait_idle`] checks for in-flight runs
+const IDLE_POLL: Duration = Duration::from_millis(50);
 
 #[derive(Clone)]
 pub struct RitualService {
     registry: AppPackRegistry,
     store: RunStore,
     runner: Arc<dyn RitualRunner>,
-    tasks: Arc<Mutex<HashMap<String, AbortHandle>>>,
+    scheduler: Arc<Mutex<Scheduler>>,
+}
+
+/// Runs waiting for a slot and runs currently executing
+struct Scheduler {
+    queue: FairQueue<QueuedRun>,
+    running: HashMap<String, RunningRun>,
+    phase: Phase,
+}
+
+/// Where the service is in its shutdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Accepting,
+    /// New runs are refused; in-flight runs may finish
+    Draining,
+    /// Shutdown finished; unfinished runs were handed back as `Pending`
+    Drained,
+}
+
+struct QueuedRun {
+    plan: ExecutionPlan,
+    app: String,
+    queued_at: Instant,
+}
+
+struct RunningRun {
+    tenant: String,
+    abort: AbortHandle,
+    /// Stopped by shutdown rather than canceled; the run goes back to `Pending`
+    interrupted: bool,
+}
+
+impl Scheduler {
+    fn new(config: FairQueueConfig) -> Self {
+        Self {
+            queue: FairQueue::new(config),
+            running: HashMap::new(),
+            phase: Phase::Accepting,
+        }
+    }
+
+    fn running_for(&self, tenant: &str) -> usize {
+        self.running.values().filter(|r| r.tenant == tenant).count()
+    }
+
+    fn record_running(&self, tenant: &str) {
+        gauge!(
+            "demon_runtime_runs_running",
+            self.running_for(tenant) as f64,
+            "tenant" => tenant.to_string()
+        );
+    }
 }
 
 impl RitualService {
     pub fn new() -> Result<Self> {
-        Self::with_runner(Arc::new(EngineRitualRunner))
+        Self::with_runner(Arc::new(EngineRitualRunner::new()))
     }
 
     pub fn with_runner(runner: Arc<dyn RitualRunner>) -> Result<Self> {
@@ -39,7 +102,7 @@ impl RitualService {
             registry,
             store,
             runner,
-            tasks: Arc::new(Mutex::new(HashMap::new())),
+            scheduler: Arc::new(Mutex::new(Scheduler::new(FairQueueConfig::from_env()))),
         })
     }
 
@@ -52,7 +115,15 @@ impl RitualService {
             registry,
             store,
             runner,
-            tasks: Arc::new(Mutex::new(HashMap::new())),
+            scheduler: Arc::new(Mutex::new(Scheduler::new(FairQueueConfig::from_env()))),
+        }
+    }
+
+    /// Replace the run queue's concurrency limit and tenant weights
+    pub fn with_queue_config(self, config: FairQueueConfig) -> Self {
+        Self {
+            scheduler: Arc::new(Mutex::new(Scheduler::new(config))),
+            ..self
         }
     }
 
@@ -61,6 +132,21 @@ impl RitualService {
         ritual_name: &str,
         request: RitualInvocationRequest,
     ) -> Result<(RunRecord, RunCreatedResponse)> {
+        self.schedule_traced_run(ritual_name, request, None).await
+    }
+
+    /// Like [`RitualService::schedule_run`], continuing the caller's trace
+    /// in the run's spans and capsule calls
+    pub async fn schedule_traced_run(
+        &self,
+        ritual_name: &str,
+        request: RitualInvocationRequest,
+        trace: Option<TraceContext>,
+    ) -> Result<(RunRecord, RunCreatedResponse)> {
+        if !self.is_accepting() {
+            return Err(anyhow!(SHUTTING_DOWN));
+        }
+
         let resolved = self
             .registry
             .resolve_invocation(ritual_name, &request)
@@ -73,15 +159,22 @@ impl RitualService {
 
         let now = Utc::now();
         let run_id = Uuid::new_v4().to_string();
+        let tenant = request
+            .tenant
+            .clone()
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| request.app.clone());
 
-        let plan = build_execution_plan(&resolved, &request.parameters, &run_id)?;
+        let mut plan = build_execution_plan(&resolved, &request.parameters, &run_id)?;
+        plan.trace = trace;
 
         let record = RunRecord {
             run_id: run_id.clone(),
             app: request.app.clone(),
+            tenant: Some(tenant.clone()),
             ritual: ritual_name.to_string(),
             version: version.clone(),
-            status: RunStatus::Running,
+            status: RunStatus::Pending,
             created_at: now,
             updated_at: now,
             completed_at: None,
@@ -95,11 +188,21 @@ impl RitualService {
             .await
             .context("persisting run metadata")?;
 
-        self.spawn_execution(plan, record.app.clone()).await?;
+        info!(
+            run = %run_id,
+            app = %record.app,
+            registry_version = resolved.registry_version,
+            "scheduled ritual run"
+        );
+        let started = self.enqueue(plan, record.app.clone(), &tenant);
 
         let response = RunCreatedResponse {
             run_id: run_id.clone(),
-            status: RunStatus::Running,
+            status: if started {
+                RunStatus::Running
+            } else {
+                RunStatus::Pending
+            },
             created_at: now.to_rfc3339(),
             links: RunLinks {
                 run: format!(
@@ -168,23 +271,243 @@ impl RitualService {
         Ok(None)
     }
 
-    async fn spawn_execution(&self, plan: ExecutionPlan, app: String) -> Result<()> {
+    /// Current run queue occupancy per tenant
+    pub fn queue_status(&self) -> QueueStatus {
+        let scheduler = self.lock_scheduler();
+        let config = scheduler.queue.config();
+
+        let depths = scheduler.queue.depths();
+        let mut tenants: BTreeMap<&str, TenantQueueStatus> = BTreeMap::new();
+        let busy = depths
+            .keys()
+            .map(String::as_str)
+            .chain(scheduler.running.values().map(|run| run.tenant.as_str()));
+        for tenant in busy {
+            tenants.entry(tenant).or_insert_with(|| TenantQueueStatus {
+                tenant: tenant.to_string(),
+                weight: config.weight(tenant),
+                queued: scheduler.queue.depth(tenant),
+                running: scheduler.running_for(tenant),
+            });
+        }
+
+        QueueStatus {
+            max_concurrent: config.max_concurrent,
+            running: scheduler.running.len(),
+            queued: scheduler.queue.len(),
+            tenants: tenants.into_values().collect(),
+        }
+    }
+
+    /// Whether new runs are accepted; false once shutdown has begun
+    pub fn is_accepting(&self) -> bool {
+        self.lock_scheduler().phase == Phase::Accepting
+    }
+
+    /// Whether shutdown has finished
+    pub fn is_drained(&self) -> bool {
+        self.lock_scheduler().phase == Phase::Drained
+    }
+
+    /// Ids of runs currently executing
+    pub fn in_flight(&self) -> Vec<String> {
+        self.lock_scheduler().running.keys().cloned().collect()
+    }
+
+    /// Stop accepting and starting runs. Queued runs are taken off the queue
+    /// but stay `Pending` in the run store for [`Self::resume_pending`];
+    /// returns their ids.
+    pub fn begin_shutdown(&self) -> Vec<String> {
+        let mut scheduler = self.lock_scheduler();
+        if scheduler.phase == Phase::Accepting {
+            scheduler.phase = Phase::Draining;
+        }
+        std::iter::from_fn(|| scheduler.queue.pop())
+            .map(|(_, queued)| queued.plan.run_id)
+            .collect()
+    }
+
+    /// Wait up to `timeout` for in-flight runs to finish; returns whether
+    /// none are left
+    pub async fn wait_idle(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if self.lock_scheduler().running.is_empty() {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(IDLE_POLL).await;
+        }
+    }
+
+    /// Stop the runs still executing and hand them back as `Pending`, so a
+    /// runtime started later runs them again; returns their ids
+    pub async fn interrupt_running(&self) -> Vec<String> {
+        let interrupted: Vec<String> = {
+            let mut scheduler = self.lock_scheduler();
+            scheduler
+                .running
+                .iter_mut()
+                .map(|(run_id, run)| {
+                    run.interrupted = true;
+                    run.abort.abort();
+                    run_id.clone()
+                })
+                .collect()
+        };
+        for run_id in &interrupted {
+            self.runner.cancel(run_id).await;
+        }
+        interrupted
+    }
+
+    /// Mark shutdown as finished
+    pub fn finish_shutdown(&self) {
+        self.lock_scheduler().phase = Phase::Drained;
+    }
+
+    /// Queue the runs left `Pending` by an earlier shutdown; returns how
+    /// many were queued. Runs that can no longer be planned are failed.
+    pub async fn resume_pending(&self) -> Result<usize> {
+        let mut resumed = 0;
+        for record in self.store.list_by_status(RunStatus::Pending).await {
+            let tenant = record.tenant.clone().unwrap_or_else(|| record.app.clone());
+            let request = RitualInvocationRequest {
+                app: record.app.clone(),
+                tenant: Some(tenant.clone()),
+                version: Some(record.version.clone()),
+                parameters: record.parameters.clone(),
+            };
+            let plan = self
+                .registry
+                .resolve_invocation(&record.ritual, &request)
+                .and_then(|resolved| {
+                    build_execution_plan(&resolved, &record.parameters, &record.run_id)
+                });
+            match plan {
+                Ok(plan) => {
+                    info!(run = %record.run_id, %tenant, "resuming pending run");
+                    self.enqueue(plan, record.app, &tenant);
+                    resumed += 1;
+                }
+                Err(err) => {
+                    warn!(run = %record.run_id, error = %err, "cannot resume pending run");
+                    self.store
+                        .mark_failed(&record.run_id, format!("cannot resume run: {err}"))
+                        .await?;
+                }
+            }
+        }
+        Ok(resumed)
+    }
+
+    fn lock_scheduler(&self) -> std::sync::MutexGuard<'_, Scheduler> {
+        self.scheduler.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Queue a run behind the tenant's earlier runs; returns whether it
+    /// started right away
+    fn enqueue(&self, plan: ExecutionPlan, app: String, tenant: &str) -> bool {
+        let run_id = plan.run_id.clone();
+        self.lock_scheduler().queue.push(
+            tenant,
+            QueuedRun {
+                plan,
+                app,
+                queued_at: Instant::now(),
+            },
+        );
+        self.dispatch().contains(&run_id)
+    }
+
+    /// Start queued runs while slots are free; returns the started run ids
+    fn dispatch(&self) -> Vec<String> {
+        let mut to_start = Vec::new();
+        {
+            let mut scheduler = self.lock_scheduler();
+            while scheduler.phase == Phase::Accepting
+                && scheduler.running.len() < scheduler.queue.config().max_concurrent
+            {
+                let Some((tenant, queued)) = scheduler.queue.pop() else {
+                    break;
+                };
+                let (abort, registration) = AbortHandle::new_pair();
+                scheduler.running.insert(
+                    queued.plan.run_id.clone(),
+                    RunningRun {
+                        tenant: tenant.clone(),
+                        abort,
+                        interrupted: false,
+                    },
+                );
+                scheduler.record_running(&tenant);
+                to_start.push((tenant, queued, registration));
+            }
+        }
+
+        to_start
+            .into_iter()
+            .map(|(tenant, queued, registration)| {
+                let run_id = queued.plan.run_id.clone();
+                self.spawn_execution(tenant, queued, registration);
+                run_id
+            })
+            .collect()
+    }
+
+    fn was_interrupted(&self, run_id: &str) -> bool {
+        self.lock_scheduler()
+            .running
+            .get(run_id)
+            .is_some_and(|run| run.interrupted)
+    }
+
+    /// Free the slot held by `run_id` and start the next queued run
+    fn finish(&self, run_id: &str) {
+        {
+            let mut scheduler = self.lock_scheduler();
+            if let Some(run) = scheduler.running.remove(run_id) {
+                scheduler.record_running(&run.tenant);
+            }
+        }
+        self.dispatch();
+    }
+
+    fn spawn_execution(&self, tenant: String, queued: QueuedRun, registration: AbortRegistration) {
+        let service = self.clone();
         let store = self.store.clone();
         let runner = Arc::clone(&self.runner);
+        let QueuedRun {
+            plan,
+            app,
+            queued_at,
+        } = queued;
         let run_id = plan.run_id.clone();
         let ritual_id = plan.ritual_id.clone();
 
-        let (abort_handle, abort_reg) = AbortHandle::new_pair();
-        {
-            let mut map = self.tasks.lock().await;
-            map.insert(run_id.clone(), abort_handle);
-        }
+        histogram!(
+            "demon_runtime_queue_wait_ms",
+            queued_at.elapsed().as_millis() as f64,
+            "tenant" => tenant.clone()
+        );
 
-        let tasks_map = Arc::clone(&self.tasks);
         tokio::spawn(async move {
-            info!(run = %run_id, ritual = %ritual_id, "starting ritual execution task");
-            let fut = runner.run(plan);
-            match Abortable::new(fut, abort_reg).await {
+            info!(run = %run_id, ritual = %ritual_id, %tenant, "starting ritual execution task");
+            let fut = async {
+                store
+                    .update(&run_id, |record| {
+                        if record.status == RunStatus::Pending {
+                            record.status = RunStatus::Running;
+                            record.updated_at = Utc::now();
+                            record.error = None;
+                        }
+                    })
+                    .await?;
+                runner.run(plan).await
+            };
+            match Abortable::new(fut, registration).await {
                 Ok(Ok(envelope_json)) => {
                     let now = Utc::now();
                     if let Err(err) = store
@@ -215,24 +538,16 @@ impl RitualService {
                         error!(run = %run_id, %app, error = %err, "failed to persist failure metadata");
                     }
                 }
+                Err(_aborted) if service.was_interrupted(&run_id) => {
+                    warn!(run = %run_id, %app, "ritual execution interrupted by shutdown");
+                    mark_interrupted(&store, &run_id).await;
+                }
                 Err(_aborted) => {
-                    let now = Utc::now();
-                    let _ = store
-                        .update(&run_id, |record| {
-                            record.status = RunStatus::Canceled;
-                            record.updated_at = now;
-                            record.completed_at = Some(now);
-                            record.error = Some("Canceled by user".to_string());
-                        })
-                        .await;
+                    mark_canceled(&store, &run_id).await;
                 }
             }
-            // Remove handle after completion
-            let mut map = tasks_map.lock().await;
-            map.remove(&run_id);
+            service.finish(&run_id);
         });
-
-        Ok(())
     }
 
     pub async fn cancel_run(&self, app: &str, ritual: &str, run_id: &str) -> Result<bool> {
@@ -245,19 +560,27 @@ impl RitualService {
             return Ok(false);
         }
 
-        let handle = { self.tasks.lock().await.remove(run_id) };
-        if let Some(h) = handle {
-            h.abort();
-            let now = Utc::now();
-            self.store
-                .update(run_id, |record| {
-                    record.status = RunStatus::Canceled;
-                    record.updated_at = now;
-                    record.completed_at = Some(now);
-                    record.error = Some("Canceled by user".to_string());
-                })
-                .await
-                .ok();
+        let queued = self
+            .lock_scheduler()
+            .queue
+            .remove_where(|queued| queued.plan.run_id == run_id);
+        if queued.is_some() {
+            mark_canceled(&self.store, run_id).await;
+            return Ok(true);
+        }
+
+        let handle = {
+            let mut scheduler = self.lock_scheduler();
+            let run = scheduler.running.remove(run_id);
+            if let Some(run) = &run {
+                scheduler.record_running(&run.tenant);
+            }
+            run
+        };
+        if let Some(run) = handle {
+            run.abort.abort();
+            self.runner.cancel(run_id).await;
+            mark_canceled(&self.store, run_id).await;
             Ok(true)
         } else {
             // Nothing to abort — return false if not running
@@ -266,6 +589,31 @@ impl RitualService {
     }
 }
 
+/// Put a run stopped by shutdown back to `Pending`
+async fn mark_interrupted(store: &RunStore, run_id: &str) {
+    store
+        .update(run_id, |record| {
+            record.status = RunStatus::Pending;
+            record.updated_at = Utc::now();
+            record.error = Some("Interrupted by runtime shutdown; will be resumed".to_string());
+        })
+        .await
+        .ok();
+}
+
+async fn mark_canceled(store: &RunStore, run_id: &str) {
+    let now = Utc::now();
+    store
+        .update(run_id, |record| {
+            record.status = RunStatus::Canceled;
+            record.updated_at = now;
+            record.completed_at = Some(now);
+            record.error = Some("Canceled by user".to_string());
+        })
+        .await
+        .ok();
+}
+
 fn build_execution_plan(
     resolved: &ResolvedInvocation,
     parameters: &JsonValue,
@@ -281,7 +629,7 @@ fn build_execution_plan(
         .capsules
         .iter()
         .find(|entry| match entry {
-            CapsuleEntry::ContainerExec { name, .. } => name == &step.capsule,
+            CapsuleEntry::ContainerExec(exec) => exec.name == step.capsule,
             CapsuleEntry::Unsupported => false,
         })
         .ok_or_else(|| {
@@ -298,21 +646,36 @@ fn build_execution_plan(
     if !parameters.is_null() && !parameters.is_object() {
         return Err(anyhow!("Invocation parameters must be a JSON object"));
     }
+    let parameters = &validate_parameters(&resolved.ritual, parameters)?;
+
+    let capsule_type = capsule.capsule_type().ok_or_else(|| {
+        anyhow!(
+            "capsule '{}' uses an unsupported type for HTTP invocation",
+            step.capsule
+        )
+    })?;
 
     let (ref_name, args) = match capsule {
-        CapsuleEntry::ContainerExec {
-            name,
-            image_digest,
-            command,
-            env,
-            working_dir,
-            outputs,
-        } => {
+        CapsuleEntry::ContainerExec(exec) => {
+            let ContainerExecCapsule {
+                name,
+                image_digest,
+                command,
+                env,
+                working_dir,
+                outputs,
+                resources,
+                secrets,
+                network,
+            } = exec.as_ref();
             let mut base = serde_json::json!({
                 "imageDigest": image_digest,
                 "command": command,
                 "env": env,
-                "outputs": { "envelopePath": outputs.envelope_path },
+                "outputs": {
+                    "envelopePath": outputs.envelope_path,
+                    "transport": outputs.transport,
+                },
             });
 
             if let Some(dir) = working_dir {
@@ -324,8 +687,22 @@ fn build_execution_plan(
             merge_json(&mut base, &step.with)?;
             merge_json(&mut base, parameters)?;
 
+            // Sandbox declarations come from the manifest only; invocation
+            // parameters cannot widen them
             if let Some(obj) = base.as_object_mut() {
                 obj.insert("capsuleName".into(), JsonValue::String(name.clone()));
+                obj.remove("resources");
+                obj.remove("secrets");
+                obj.remove("network");
+                if let Some(resources) = resources {
+                    obj.insert("resources".into(), serde_json::to_value(resources)?);
+                }
+                if !secrets.is_empty() {
+                    obj.insert("secrets".into(), serde_json::to_value(secrets)?);
+                }
+                if let Some(network) = network {
+                    obj.insert("network".into(), serde_json::to_value(network)?);
+                }
             }
 
             ("container-exec".to_string(), base)
@@ -345,10 +722,48 @@ fn build_execution_plan(
             resolved.manifest.metadata.name, resolved.ritual.name
         ),
         capsule_ref: ref_name,
+        capsule_type,
         arguments: args,
+        trace: None,
     })
 }
 
+/// Check parameters against the ritual's `inputs` schema, filling in
+/// top-level property defaults, so invalid invocations are rejected before a
+/// run is scheduled
+fn validate_parameters(ritual: &RitualEntry, parameters: &JsonValue) -> Result<JsonValue> {
+    let Some(schema) = &ritual.inputs else {
+        return Ok(parameters.clone());
+    };
+    let compiled = jsonschema::JSONSchema::compile(schema).map_err(|e| {
+        anyhow!(
+            "ritual '{}' has an invalid inputs schema: {}",
+            ritual.name,
+            e
+        )
+    })?;
+    let mut values = parameters.as_object().cloned().unwrap_or_default();
+    if let Some(properties) = schema.get("properties").and_then(JsonValue::as_object) {
+        for (key, property) in properties {
+            if let Some(default) = property.get("default") {
+                values.entry(key.clone()).or_insert_with(|| default.clone());
+            }
+        }
+    }
+    let values = JsonValue::Object(values);
+    if let Err(errors) = compiled.validate(&values) {
+        let details: Vec<String> = errors
+            .map(|e| format!("{}: {}", e.instance_path, e))
+            .collect();
+        return Err(anyhow!(
+            "Invocation parameters must match the inputs of ritual '{}': {}",
+            ritual.name,
+            details.join("; ")
+        ));
+    }
+    Ok(values)
+}
+
 fn merge_json(target: &mut JsonValue, other: &JsonValue) -> Result<()> {
     if other.is_null() {
         return Ok(());
@@ -84,6 +84,25 @@ impl RunStore {
         runs
     }
 
+    /// Runs with `status`, oldest first
+    pub async fn list_by_status(&self, status: RunStatus) -> Vec<RunRecord> {
+        let guard = self.state.read().await;
+        let mut runs: Vec<RunRecord> = guard
+            .runs
+            .values()
+            .filter(|record| record.status == status)
+            .cloned()
+            .collect();
+
+        runs.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.run_id.cmp(&b.run_id))
+        });
+
+        runs
+    }
+
     pub async fn mark_failed(&self, run_id: &str, message: String) -> Result<()> {
         self.update(run_id, |record| {
             record.status = RunStatus::Failed;
@@ -0,0 +1,232 @@
+//! Graceful shutdown and draining of in-flight runs
+//!
+//! On SIGTERM (or Ctrl-C) the [`ShutdownCoordinator`] drains the runtime
+//! before the HTTP server stops:
+//!
+//! 1. new runs are refused with `503` and `/ready` fails, so traffic moves to
+//!    other replicas; queued runs that never started stay `Pending`;
+//! 2. in-flight runs get up to the drain timeout to finish;
+//! 3. runs still executing are interrupted: their capsule containers are
+//!    stopped and the runs go back to `Pending`;
+//! 4. idle containers in the container-exec warm pool are removed;
+//! 5. `runtime.drained:v1` is published.
+//!
+//! The next runtime to start resumes every `Pending` run
+//! ([`RitualService::resume_pending`]), so a rolling deployment reruns
+//! interrupted work instead of leaving it stuck as `Running`.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use async_nats::jetstream;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::rituals::RitualService;
+
+/// How long interrupted runs get to record that they go back to `Pending`
+const INTERRUPT_GRACE: Duration = Duration::from_secs(5);
+
+/// How long publishing `runtime.drained:v1` may take
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct ShutdownConfig {
+    /// How long in-flight runs may take to finish before they are interrupted
+    pub drain_timeout: Duration,
+    /// Identifies this runtime replica in `runtime.drained:v1`
+    pub instance_id: String,
+    /// NATS server to publish `runtime.drained:v1` to; not published when unset
+    pub nats_url: Option<String>,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_timeout: Duration::from_secs(25),
+            instance_id: "runtime".to_string(),
+            nats_url: None,
+        }
+    }
+}
+
+impl ShutdownConfig {
+    /// Load from `DEMON_RUNTIME_DRAIN_TIMEOUT_SECS`, `DEMON_RUNTIME_INSTANCE_ID`
+    /// (falling back to `HOSTNAME`) and `NATS_URL`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            drain_timeout: std::env::var("DEMON_RUNTIME_DRAIN_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.drain_timeout),
+            instance_id: std::env::var("DEMON_RUNTIME_INSTANCE_ID")
+                .or_else(|_| std::env::var("HOSTNAME"))
+                .unwrap_or(defaults.instance_id),
+            nats_url: std::env::var("NATS_URL").ok(),
+        }
+    }
+}
+
+/// `runtime.drained:v1`: what happened to the runs of a replica that shut down
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeDrained {
+    pub event: String,
+    pub ts: String,
+    pub instance_id: String,
+    pub drain_timeout_ms: u64,
+    pub duration_ms: u64,
+    /// In-flight runs that finished (or were canceled) during the drain
+    pub finished: Vec<String>,
+    /// Runs stopped at the drain timeout and handed back as `Pending`
+    pub interrupted: Vec<String>,
+    /// Queued runs that never started and stay `Pending`
+    pub requeued: Vec<String>,
+}
+
+/// Drains a [`RitualService`] when the runtime is asked to stop
+pub struct ShutdownCoordinator {
+    service: Arc<RitualService>,
+    config: ShutdownConfig,
+}
+
+impl ShutdownCoordinator {
+    pub fn new(service: Arc<RitualService>, config: ShutdownConfig) -> Self {
+        Self { service, config }
+    }
+
+    /// Drain the service and publish `runtime.drained:v1`
+    pub async fn shutdown(&self) -> RuntimeDrained {
+        let drained = self.drain().await;
+        match self.publish(&drained).await {
+            Ok(Some(subject)) => info!(%subject, "published runtime.drained:v1"),
+            Ok(None) => info!("NATS_URL not set; runtime.drained:v1 not published"),
+            Err(err) => warn!(error = %format!("{err:#}"), "failed to publish runtime.drained:v1"),
+        }
+        drained
+    }
+
+    /// Stop accepting runs and wait for in-flight ones, interrupting those
+    /// still running at the drain timeout
+    pub async fn drain(&self) -> RuntimeDrained {
+        let started = Instant::now();
+        let in_flight = self.service.in_flight();
+        let requeued = self.service.begin_shutdown();
+        info!(
+            in_flight = in_flight.len(),
+            requeued = requeued.len(),
+            timeout_secs = self.config.drain_timeout.as_secs(),
+            "draining runtime"
+        );
+
+        let mut interrupted = Vec::new();
+        if !self.service.wait_idle(self.config.drain_timeout).await {
+            interrupted = self.service.interrupt_running().await;
+            warn!(
+                runs = ?interrupted,
+                "drain timeout reached; interrupted runs will be resumed"
+            );
+            if !self.service.wait_idle(INTERRUPT_GRACE).await {
+                warn!("interrupted runs did not stop in time");
+            }
+        }
+        self.service.finish_shutdown();
+        if let Err(err) =
+            tokio::task::spawn_blocking(capsules_container_exec::drain_warm_pool).await
+        {
+            warn!(error = %err, "failed to drain the warm container pool");
+        }
+
+        let finished = in_flight
+            .into_iter()
+            .filter(|run_id| !interrupted.contains(run_id))
+            .collect();
+
+        RuntimeDrained {
+            event: "runtime.drained:v1".to_string(),
+            ts: Utc::now().to_rfc3339(),
+            instance_id: self.config.instance_id.clone(),
+            drain_timeout_ms: self.config.drain_timeout.as_millis() as u64,
+            duration_ms: started.elapsed().as_millis() as u64,
+            finished,
+            interrupted,
+            requeued,
+        }
+    }
+
+    async fn publish(&self, drained: &RuntimeDrained) -> Result<Option<String>> {
+        let Some(nats_url) = &self.config.nats_url else {
+            return Ok(None);
+        };
+
+        // Subject pattern: demon.runtime.v1.<instance>.drained
+        let subject = format!(
+            "demon.runtime.v1.{}.drained",
+            subject_token(&drained.instance_id)
+        );
+        let payload = serde_json::to_vec(drained).context("Failed to serialize drained event")?;
+
+        tokio::time::timeout(PUBLISH_TIMEOUT, async {
+            let client = async_nats::connect(nats_url)
+                .await
+                .context("Failed to connect to NATS")?;
+            jetstream::new(client)
+                .publish(subject.clone(), payload.into())
+                .await
+                .context("Failed to publish drained event to JetStream")?
+                .await
+                .context("Failed to get publish acknowledgement")?;
+            Ok::<_, anyhow::Error>(())
+        })
+        .await
+        .context("Timed out publishing drained event")??;
+
+        Ok(Some(subject))
+    }
+}
+
+/// Subject tokens cannot contain `.`, `*`, `>` or whitespace
+fn subject_token(raw: &str) -> String {
+    raw.chars()
+        .map(|c| match c {
+            '.' | '*' | '>' => '_',
+            c if c.is_whitespace() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Resolve once SIGTERM or Ctrl-C is received
+pub async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            warn!(error = %err, "failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(err) => {
+                warn!(error = %err, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("received Ctrl-C"),
+        _ = terminate => info!("received SIGTERM"),
+    }
+}
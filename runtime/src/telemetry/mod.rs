@@ -1,8 +1,11 @@
-//! Telemetry module for runtime metrics and scale hints
+//! Telemetry module for runtime metrics, scale hints and tracing
 
+pub mod otel;
 pub mod scale_hint;
+pub mod trace_context;
 
 pub use scale_hint::{
     HysteresisState, PressureState, Recommendation, RuntimeMetrics, ScaleHintConfig,
     ScaleHintEmitter,
 };
+pub use trace_context::TraceContext;
@@ -0,0 +1,132 @@
+//! Tracing setup for the runtime binary
+//!
+//! Logs always go to stdout. With the `otlp` feature, spans are also exported
+//! over OTLP/gRPC when `OTEL_EXPORTER_OTLP_ENDPOINT` is set; the service name
+//! comes from `OTEL_SERVICE_NAME` (default `demon-runtime`).
+
+use tracing::{info, warn};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+const DEFAULT_SERVICE_NAME: &str = "demon-runtime";
+
+/// Flushes exported spans when dropped; keep it alive for the life of the server
+pub struct TelemetryGuard {
+    otlp: bool,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if self.otlp {
+            #[cfg(feature = "otlp")]
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// The OTLP endpoint from `OTEL_EXPORTER_OTLP_ENDPOINT`, if set and non-empty
+pub fn otlp_endpoint() -> Option<String> {
+    std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Service name reported with exported spans
+pub fn service_name() -> String {
+    std::env::var("OTEL_SERVICE_NAME")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string())
+}
+
+/// Install the global subscriber
+pub fn init_tracing() -> TelemetryGuard {
+    let endpoint = otlp_endpoint();
+
+    #[cfg(feature = "otlp")]
+    let (layer, error) = otlp::layer(endpoint.as_deref());
+    #[cfg(not(feature = "otlp"))]
+    let (layer, error) = (
+        None::<tracing_subscriber::layer::Identity>,
+        endpoint
+            .as_ref()
+            .map(|_| "runtime was built without the 'otlp' feature".to_string()),
+    );
+
+    let otlp = layer.is_some();
+    tracing_subscriber::registry()
+        .with(EnvFilter::from_default_env())
+        .with(tracing_subscriber::fmt::layer())
+        .with(layer)
+        .init();
+
+    match (endpoint, error) {
+        (Some(endpoint), None) => info!(
+            "Exporting traces to {} as service '{}'",
+            endpoint,
+            service_name()
+        ),
+        (Some(endpoint), Some(e)) => warn!("Not exporting traces to {}: {}", endpoint, e),
+        _ => {}
+    }
+    TelemetryGuard { otlp }
+}
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{trace, Resource};
+    use tracing_opentelemetry::OpenTelemetryLayer;
+    use tracing_subscriber::registry::LookupSpan;
+
+    /// The export layer for `endpoint`, or why it could not be built
+    pub fn layer<S>(
+        endpoint: Option<&str>,
+    ) -> (Option<OpenTelemetryLayer<S, trace::Tracer>>, Option<String>)
+    where
+        S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+    {
+        match endpoint.map(tracer) {
+            Some(Ok(tracer)) => (
+                Some(tracing_opentelemetry::layer().with_tracer(tracer)),
+                None,
+            ),
+            Some(Err(e)) => (None, Some(e.to_string())),
+            None => (None, None),
+        }
+    }
+
+    fn tracer(endpoint: &str) -> Result<trace::Tracer, opentelemetry::trace::TraceError> {
+        opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(
+                opentelemetry_otlp::new_exporter()
+                    .tonic()
+                    .with_endpoint(endpoint),
+            )
+            .with_trace_config(
+                trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                    "service.name",
+                    super::service_name(),
+                )])),
+            )
+            .install_batch(opentelemetry_sdk::runtime::Tokio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn service_name_defaults_to_demon_runtime() {
+        std::env::remove_var("OTEL_SERVICE_NAME");
+        assert_eq!(service_name(), "demon-runtime");
+        std::env::set_var("OTEL_SERVICE_NAME", "runtime-staging");
+        assert_eq!(service_name(), "runtime-staging");
+        std::env::remove_var("OTEL_SERVICE_NAME");
+    }
+}
@@ -0,0 +1,202 @@
+//! W3C trace context carried from ingress through capsule execution
+//!
+//! Ritual runs, steps and capsule calls are `tracing` spans. With the `otlp`
+//! feature and an active exporter, their OpenTelemetry ids are used; otherwise
+//! ids are generated here, so `TRACEPARENT` and envelope provenance still join
+//! the caller's trace.
+
+use envelope::{Provenance, ResultEnvelope};
+use uuid::Uuid;
+
+/// HTTP header carrying the caller's trace context
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+/// Environment variable passing the trace context into capsule containers
+pub const TRACEPARENT_ENV: &str = "TRACEPARENT";
+
+/// One span's position in a distributed trace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext {
+    /// 32 lowercase hex characters
+    pub trace_id: String,
+    /// 16 lowercase hex characters
+    pub span_id: String,
+    pub sampled: bool,
+}
+
+impl TraceContext {
+    /// A span starting a new trace
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: Uuid::new_v4().simple().to_string(),
+            span_id: new_span_id(),
+            sampled: true,
+        }
+    }
+
+    /// A new span in the same trace
+    pub fn child(&self) -> Self {
+        Self {
+            span_id: new_span_id(),
+            ..self.clone()
+        }
+    }
+
+    /// Parse a version 00 `traceparent` value; invalid or all-zero ids are rejected
+    pub fn parse(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let (version, trace_id, span_id, flags) =
+            (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        if version != "00" || parts.next().is_some() {
+            return None;
+        }
+        let is_id = |value: &str, len: usize| {
+            value.len() == len
+                && value
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+                && value.chars().any(|c| c != '0')
+        };
+        if !is_id(trace_id, 32) || !is_id(span_id, 16) || flags.len() != 2 {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            span_id: span_id.to_string(),
+            sampled: flags & 0x01 == 0x01,
+        })
+    }
+
+    /// The `traceparent` value for this span
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            self.trace_id,
+            self.span_id,
+            u8::from(self.sampled)
+        )
+    }
+}
+
+fn new_span_id() -> String {
+    Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+/// Attach `span` to `parent`, or start a new trace without one, and return the
+/// span's context. The ids are recorded in the span's `trace_id`/`span_id`
+/// fields when it declares them.
+pub fn enter(span: &tracing::Span, parent: Option<&TraceContext>) -> TraceContext {
+    let context = otel_context(span, parent).unwrap_or_else(|| match parent {
+        Some(parent) => parent.child(),
+        None => TraceContext::new_root(),
+    });
+    span.record("trace_id", context.trace_id.as_str());
+    span.record("span_id", context.span_id.as_str());
+    context
+}
+
+#[cfg(feature = "otlp")]
+fn otel_context(span: &tracing::Span, parent: Option<&TraceContext>) -> Option<TraceContext> {
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    if let Some(parent) = parent {
+        if let (Ok(trace_id), Ok(span_id)) = (
+            TraceId::from_hex(&parent.trace_id),
+            SpanId::from_hex(&parent.span_id),
+        ) {
+            let flags = if parent.sampled {
+                TraceFlags::SAMPLED
+            } else {
+                TraceFlags::default()
+            };
+            let remote = SpanContext::new(trace_id, span_id, flags, true, TraceState::default());
+            span.set_parent(opentelemetry::Context::new().with_remote_span_context(remote));
+        }
+    }
+
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    span_context.is_valid().then(|| TraceContext {
+        trace_id: span_context.trace_id().to_string(),
+        span_id: span_context.span_id().to_string(),
+        sampled: span_context.is_sampled(),
+    })
+}
+
+#[cfg(not(feature = "otlp"))]
+fn otel_context(_span: &tracing::Span, _parent: Option<&TraceContext>) -> Option<TraceContext> {
+    None
+}
+
+/// Record `context` in the envelope's provenance unless the capsule already
+/// set a trace id or signed the envelope
+pub fn stamp_provenance<T>(envelope: &mut ResultEnvelope<T>, context: &TraceContext) {
+    let provenance = envelope.provenance.get_or_insert_with(|| Provenance {
+        source: None,
+        timestamp: None,
+        trace_id: None,
+        span_id: None,
+        parent_span_id: None,
+        chain: vec![],
+        signature: None,
+    });
+    if provenance.trace_id.is_some() || provenance.signature.is_some() {
+        return;
+    }
+    provenance.trace_id = Some(context.trace_id.clone());
+    provenance.span_id = Some(context.span_id.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn traceparent_round_trips() {
+        let context = TraceContext::parse(SAMPLE).unwrap();
+        assert_eq!(context.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.span_id, "00f067aa0ba902b7");
+        assert!(context.sampled);
+        assert_eq!(context.traceparent(), SAMPLE);
+    }
+
+    #[test]
+    fn malformed_traceparent_is_rejected() {
+        for value in [
+            "",
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert_eq!(TraceContext::parse(value), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn child_and_root_contexts_have_valid_ids() {
+        let root = TraceContext::new_root();
+        assert_eq!(TraceContext::parse(&root.traceparent()), Some(root.clone()));
+
+        let child = root.child();
+        assert_eq!(child.trace_id, root.trace_id);
+        assert_ne!(child.span_id, root.span_id);
+        assert_eq!(child.span_id.len(), 16);
+    }
+
+    #[test]
+    fn enter_without_exporter_continues_parent_trace() {
+        let parent = TraceContext::parse(SAMPLE).unwrap();
+        let span = tracing::info_span!("test");
+        let context = enter(&span, Some(&parent));
+        assert_eq!(context.trace_id, parent.trace_id);
+        assert_ne!(context.span_id, parent.span_id);
+    }
+}
@@ -0,0 +1,235 @@
+use capsules_container_exec::{GpuRequest, ScratchBacking};
+use runtime::app_pack::{
+    parse_version_range, platform_capabilities, schema_violations, validate_manifest,
+    CapsuleNetwork, CapsuleResources, ManifestVersion, NetworkPolicy, PackRequirements, UiCard,
+};
+use serde_json::{json, Value};
+
+fn manifest(api_version: &str, capsule_extras: Value) -> Value {
+    let mut capsule = json!({
+        "type": "container-exec",
+        "name": "scan",
+        "imageDigest": format!("ghcr.io/example/scan@sha256:{}", "a".repeat(64)),
+        "command": ["/bin/scan"],
+        "outputs": { "envelopePath": "/workspace/.artifacts/result.json" }
+    });
+    if let (Some(capsule), Some(extras)) = (capsule.as_object_mut(), capsule_extras.as_object()) {
+        capsule.extend(extras.clone());
+    }
+
+    json!({
+        "apiVersion": api_version,
+        "kind": "AppPack",
+        "metadata": { "name": "scanner", "version": "1.0.0" },
+        "contracts": [
+            { "id": "scanner/result", "version": "1.0.0", "path": "contracts/result.json" }
+        ],
+        "capsules": [capsule],
+        "rituals": [{ "name": "scan", "steps": [{ "capsule": "scan" }] }]
+    })
+}
+
+#[test]
+fn given_v2_manifest_with_sandbox_declarations_when_validated_then_accepted() {
+    let document = manifest(
+        "demon.io/v2",
+        json!({
+            "resources": { "cpus": 0.5, "memory": "256m", "pidsLimit": 64 },
+            "secrets": [
+                { "env": "REGISTRY_TOKEN", "secret": "secret://scanner/registry_token" },
+                { "env": "PROXY_URL", "secret": "secret://scanner/proxy", "optional": true }
+            ],
+            "network": { "policy": "egress" }
+        }),
+    );
+
+    assert_eq!(validate_manifest(&document).unwrap(), ManifestVersion::V2);
+
+    let capsule = &document["capsules"][0];
+    let resources: CapsuleResources = serde_json::from_value(capsule["resources"].clone()).unwrap();
+    assert_eq!(resources.memory.as_deref(), Some("256m"));
+    assert_eq!(resources.pids_limit, Some(64));
+    let network: CapsuleNetwork = serde_json::from_value(capsule["network"].clone()).unwrap();
+    assert_eq!(network.policy, NetworkPolicy::Egress);
+}
+
+#[test]
+fn given_v2_manifest_with_device_passthrough_when_validated_then_accepted() {
+    let document = manifest(
+        "demon.io/v2",
+        json!({
+            "resources": { "gpus": "all", "devices": ["/dev/fuse", "/dev/dri/card0:/dev/dri/card0:rw"] }
+        }),
+    );
+    assert_eq!(validate_manifest(&document).unwrap(), ManifestVersion::V2);
+
+    let resources: CapsuleResources =
+        serde_json::from_value(document["capsules"][0]["resources"].clone()).unwrap();
+    assert_eq!(resources.gpus, Some(GpuRequest::All));
+    assert_eq!(resources.devices.len(), 2);
+
+    for gpus in [json!(2), json!(["0", "GPU-5f1c"])] {
+        let document = manifest("demon.io/v2", json!({ "resources": { "gpus": gpus } }));
+        assert_eq!(validate_manifest(&document).unwrap(), ManifestVersion::V2);
+    }
+
+    for resources in [
+        json!({ "gpus": 0 }),
+        json!({ "gpus": "some" }),
+        json!({ "devices": ["/tmp/fake"] }),
+    ] {
+        let document = manifest("demon.io/v2", json!({ "resources": resources }));
+        assert!(validate_manifest(&document).is_err(), "{resources}");
+    }
+}
+
+#[test]
+fn given_v2_manifest_with_scratch_volume_when_validated_then_accepted() {
+    let document = manifest(
+        "demon.io/v2",
+        json!({ "resources": { "scratch": { "size": "2g", "backing": "host" } } }),
+    );
+    assert_eq!(validate_manifest(&document).unwrap(), ManifestVersion::V2);
+
+    let resources: CapsuleResources =
+        serde_json::from_value(document["capsules"][0]["resources"].clone()).unwrap();
+    let scratch = resources.scratch.unwrap();
+    assert_eq!(scratch.backing, ScratchBacking::Host);
+    assert_eq!(scratch.size_bytes(), Some(2 << 30));
+
+    for scratch in [
+        json!({ "size": "0" }),
+        json!({ "size": "lots" }),
+        json!({ "size": "1g", "backing": "nfs" }),
+        json!({ "backing": "tmpfs" }),
+    ] {
+        let document = manifest(
+            "demon.io/v2",
+            json!({ "resources": { "scratch": scratch } }),
+        );
+        assert!(validate_manifest(&document).is_err(), "{scratch}");
+    }
+}
+
+#[test]
+fn given_v1_manifest_when_validated_then_still_accepted() {
+    let document = manifest("demon.io/v1", json!({}));
+    assert_eq!(validate_manifest(&document).unwrap(), ManifestVersion::V1);
+}
+
+#[test]
+fn given_v1_manifest_with_v2_fields_when_validated_then_rejected() {
+    let document = manifest("demon.io/v1", json!({ "resources": { "cpus": 1 } }));
+    let err = validate_manifest(&document).unwrap_err().to_string();
+    assert!(err.contains("demon.io/v1"), "{err}");
+    assert!(err.contains("/capsules/0"), "{err}");
+}
+
+#[test]
+fn given_invalid_sandbox_declarations_when_validated_then_violations_point_at_fields() {
+    let document = manifest(
+        "demon.io/v2",
+        json!({
+            "resources": { "cpus": 0, "memory": "lots" },
+            "secrets": [{ "env": "TOKEN", "secret": "vault:token" }],
+            "network": { "policy": "host" }
+        }),
+    );
+
+    let (version, violations) = schema_violations(&document).unwrap();
+    assert_eq!(version, ManifestVersion::V2);
+    let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+    for expected in [
+        "/capsules/0/resources/cpus",
+        "/capsules/0/resources/memory",
+        "/capsules/0/secrets/0/secret",
+        "/capsules/0/network/policy",
+    ] {
+        assert!(paths.contains(&expected), "missing {expected} in {paths:?}");
+    }
+
+    let err = validate_manifest(&document).unwrap_err().to_string();
+    assert!(err.contains("/capsules/0/resources/cpus"), "{err}");
+}
+
+#[test]
+fn given_unknown_api_version_when_validated_then_rejected() {
+    let document = manifest("demon.io/v3", json!({}));
+    let err = validate_manifest(&document).unwrap_err().to_string();
+    assert!(
+        err.contains("Unsupported apiVersion 'demon.io/v3'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn given_v2_ui_card_with_config_when_parsed_then_config_is_kept() {
+    let mut document = manifest("demon.io/v2", json!({}));
+    document["ui"] = json!({
+        "cards": [{
+            "id": "scan-summary",
+            "kind": "fields-table",
+            "match": { "rituals": ["scan"] },
+            "config": { "fields": [{ "label": "Findings", "path": "result.count" }] }
+        }]
+    });
+
+    validate_manifest(&document).unwrap();
+    let card: UiCard = serde_json::from_value(document["ui"]["cards"][0].clone()).unwrap();
+    assert!(card.matches_ritual("scan"));
+    assert!(card.get_config().unwrap()["fields"].is_array());
+}
+
+#[test]
+fn given_v2_requires_section_when_validated_then_requirements_are_parsed() {
+    let mut document = manifest("demon.io/v2", json!({}));
+    document["requires"] = json!({
+        "contracts": [{ "name": "result-envelope", "version": ">= 1.2 <2.0.0" }],
+        "capabilities": ["container-exec"]
+    });
+
+    validate_manifest(&document).unwrap();
+    let requirements = PackRequirements::from_manifest(&document).unwrap();
+    let contract = &requirements.contracts[0];
+    assert!(contract.accepts("1.2.0"));
+    assert!(contract.accepts("1.9.3"));
+    assert!(!contract.accepts("1.1.0"));
+    assert!(!contract.accepts("2.0.0"));
+    assert!(requirements.ensure_capabilities().is_ok());
+}
+
+#[test]
+fn given_unknown_capability_when_validated_then_rejected() {
+    let mut document = manifest("demon.io/v2", json!({}));
+    document["requires"] = json!({ "capabilities": ["gpu"] });
+
+    let (_, violations) = schema_violations(&document).unwrap();
+    assert!(
+        violations
+            .iter()
+            .any(|v| v.path == "/requires/capabilities/0"),
+        "{violations:?}"
+    );
+}
+
+#[test]
+fn given_wasm_requirement_when_checked_then_matches_runtime_features() {
+    let requirements = PackRequirements {
+        capabilities: vec!["wasm".into()],
+        ..Default::default()
+    };
+
+    if platform_capabilities().contains(&"wasm") {
+        assert!(requirements.missing_capabilities().is_empty());
+    } else {
+        let err = requirements.ensure_capabilities().unwrap_err().to_string();
+        assert!(err.contains("--features wasm"), "{err}");
+    }
+}
+
+#[test]
+fn given_malformed_version_range_when_parsed_then_error_names_range() {
+    let err = parse_version_range(">=").unwrap_err().to_string();
+    assert!(err.contains("'>='"), "{err}");
+    assert!(parse_version_range(">=1.0.0, <2").is_ok());
+}
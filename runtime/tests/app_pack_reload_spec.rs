@@ -0,0 +1,78 @@
+use std::path::Path;
+
+use chrono::Utc;
+use runtime::server::rituals::{AppPackRegistry, RitualInvocationRequest};
+use serde_json::json;
+
+fn hoss_manifest(version: &str) -> String {
+    let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    std::fs::read_to_string(workspace_root.join("examples/app-packs/hoss/app-pack.yaml"))
+        .unwrap()
+        .replace("version: 0.1.0", &format!("version: {version}"))
+}
+
+/// Install `versions` of the hoss pack the way `demonctl app install` does
+fn install(app_root: &Path, versions: &[&str]) {
+    let installs: Vec<_> = versions
+        .iter()
+        .map(|version| {
+            let pack_dir = app_root.join("packs/hoss").join(version);
+            std::fs::create_dir_all(&pack_dir).unwrap();
+            let manifest_path = pack_dir.join("app-pack.yaml");
+            std::fs::write(&manifest_path, hoss_manifest(version)).unwrap();
+            json!({
+                "version": version,
+                "manifest_path": manifest_path,
+                "installed_at": Utc::now().to_rfc3339(),
+                "source": "tests",
+            })
+        })
+        .collect();
+    std::fs::write(
+        app_root.join("registry.json"),
+        serde_json::to_string_pretty(&json!({ "apps": { "hoss": installs } })).unwrap(),
+    )
+    .unwrap();
+}
+
+fn request() -> RitualInvocationRequest {
+    RitualInvocationRequest {
+        app: "hoss".to_string(),
+        tenant: None,
+        version: None,
+        parameters: json!({}),
+    }
+}
+
+#[test]
+fn given_pack_installed_after_startup_when_resolving_then_it_is_picked_up() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let registry = AppPackRegistry::with_root(tempdir.path().to_path_buf());
+
+    let err = registry.resolve_invocation("noop", &request()).unwrap_err();
+    assert!(err.to_string().contains("registry not found"), "{err}");
+
+    install(tempdir.path(), &["0.1.0"]);
+    let resolved = registry.resolve_invocation("noop", &request()).unwrap();
+    assert_eq!(resolved.manifest.metadata.version, "0.1.0");
+    assert_eq!(resolved.registry_version, 1);
+}
+
+#[test]
+fn given_pack_upgraded_when_resolving_then_new_snapshot_serves_latest_version() {
+    let tempdir = tempfile::tempdir().unwrap();
+    install(tempdir.path(), &["0.1.0"]);
+    let registry = AppPackRegistry::with_root(tempdir.path().to_path_buf());
+
+    let before = registry.snapshot().unwrap();
+    assert_eq!(
+        registry.snapshot().unwrap().version,
+        before.version,
+        "unchanged registry must not reload"
+    );
+
+    install(tempdir.path(), &["0.1.0", "0.2.0"]);
+    let resolved = registry.resolve_invocation("noop", &request()).unwrap();
+    assert_eq!(resolved.manifest.metadata.version, "0.2.0");
+    assert_eq!(resolved.registry_version, before.version + 1);
+}
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use envelope::ResultEnvelope;
+use runtime::link::capsule::{CapsuleBackend, CapsuleInvocation, CapsuleRouter, CapsuleType};
+use runtime::link::router::Router;
+use runtime::telemetry::TraceContext;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Backend answering every invocation with its capsule name and arguments
+struct RecordingBackend(CapsuleType);
+
+#[async_trait]
+impl CapsuleBackend for RecordingBackend {
+    fn capsule_type(&self) -> CapsuleType {
+        self.0
+    }
+
+    async fn invoke(&self, invocation: &CapsuleInvocation) -> Result<ResultEnvelope<Value>> {
+        Ok(ResultEnvelope::builder()
+            .success(json!({
+                "capsule": invocation.capsule,
+                "args": invocation.args,
+                "runId": invocation.run_id,
+                "traceparent": invocation.trace.as_ref().map(|t| t.traceparent()),
+            }))
+            .build()?)
+    }
+}
+
+#[test]
+fn given_builtin_function_refs_when_resolved_then_map_to_capsule_types() {
+    assert_eq!(CapsuleType::for_ref("echo"), Some(CapsuleType::InProcess));
+    assert_eq!(CapsuleType::for_ref("graph"), Some(CapsuleType::InProcess));
+    assert_eq!(
+        CapsuleType::for_ref("container-exec"),
+        Some(CapsuleType::ContainerExec)
+    );
+    assert_eq!(CapsuleType::for_ref("wasm"), Some(CapsuleType::Wasm));
+    assert_eq!(CapsuleType::for_ref("unknown"), None);
+
+    let parsed: CapsuleType = serde_json::from_value(json!("container-exec")).unwrap();
+    assert_eq!(parsed, CapsuleType::ContainerExec);
+}
+
+#[tokio::test]
+async fn given_registered_backend_when_dispatch_then_backend_returns_envelope() {
+    let router = Router::new().with_backend(Arc::new(RecordingBackend(CapsuleType::Wasm)));
+
+    let response = router
+        .dispatch("wasm", &json!({"input": 1}), "run-1", "ritual-1")
+        .await
+        .unwrap();
+
+    assert_eq!(response["result"]["success"], true);
+    assert_eq!(response["result"]["data"]["capsule"], "wasm");
+    assert_eq!(response["result"]["data"]["args"]["input"], 1);
+    assert_eq!(response["result"]["data"]["runId"], "run-1");
+}
+
+#[tokio::test]
+async fn given_backend_override_when_invoke_then_replaces_builtin_backend() {
+    let router = Router::new().with_backend(Arc::new(RecordingBackend(CapsuleType::ContainerExec)));
+
+    let response = router
+        .invoke(
+            CapsuleType::ContainerExec,
+            "build-capsule",
+            &json!({}),
+            "run-2",
+            "ritual-2",
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response["result"]["data"]["capsule"], "build-capsule");
+}
+
+#[tokio::test]
+async fn given_no_backend_for_type_when_invoke_then_error_names_type() {
+    let router = CapsuleRouter::new();
+    let invocation = CapsuleInvocation::new("module", json!({}), "run-3", "ritual-3");
+
+    let error = router
+        .invoke(CapsuleType::Wasm, &invocation)
+        .await
+        .unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains("no backend registered for wasm capsules"));
+}
+
+#[tokio::test]
+async fn given_invocation_with_trace_when_invoke_then_backend_and_provenance_join_trace() {
+    let router = CapsuleRouter::new().with_backend(Arc::new(RecordingBackend(CapsuleType::Wasm)));
+    let parent =
+        TraceContext::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").unwrap();
+    let invocation =
+        CapsuleInvocation::new("module", json!({}), "run-4", "ritual-4").with_trace(parent.clone());
+
+    let envelope = router.invoke(CapsuleType::Wasm, &invocation).await.unwrap();
+
+    let json = serde_json::to_value(&envelope).unwrap();
+    let seen =
+        TraceContext::parse(json["result"]["data"]["traceparent"].as_str().unwrap()).unwrap();
+    assert_eq!(seen.trace_id, parent.trace_id);
+    assert_ne!(seen.span_id, parent.span_id);
+
+    let provenance = envelope.provenance.expect("provenance stamped");
+    assert_eq!(
+        provenance.trace_id.as_deref(),
+        Some(parent.trace_id.as_str())
+    );
+    assert_eq!(provenance.span_id.as_deref(), Some(seen.span_id.as_str()));
+}
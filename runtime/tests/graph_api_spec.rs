@@ -281,6 +281,133 @@ async fn given_commits_exist_when_list_commits_then_returns_array() -> Result<()
     Ok(())
 }
 
+#[tokio::test]
+#[serial]
+#[ignore] // Requires NATS; run via CI with --ignored
+async fn given_graph_with_edge_when_querying_nodes_then_returns_capsule_envelopes() -> Result<()> {
+    // Arrange
+    let scope = GraphScope {
+        tenant_id: format!("tenant-query-{}", uuid::Uuid::new_v4()),
+        project_id: "proj-1".to_string(),
+        namespace: "ns-1".to_string(),
+        graph_id: "graph-1".to_string(),
+    };
+
+    let mutations = vec![
+        capsules_graph::Mutation::AddNode {
+            node_id: "a".to_string(),
+            labels: vec![],
+            properties: vec![],
+        },
+        capsules_graph::Mutation::AddNode {
+            node_id: "b".to_string(),
+            labels: vec![],
+            properties: vec![],
+        },
+        capsules_graph::Mutation::AddEdge {
+            edge_id: "a-b".to_string(),
+            from: "a".to_string(),
+            to: "b".to_string(),
+            label: None,
+            properties: vec![],
+        },
+    ];
+    let commit_id = match capsules_graph::create(scope.clone(), mutations)
+        .await
+        .result
+    {
+        OperationResult::Success { data, .. } => data.commit_id,
+        _ => panic!("Expected success result"),
+    };
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Act
+    let server = start_test_server().await?;
+    let client = reqwest::Client::new();
+    let query = format!(
+        "tenantId={}&projectId={}&namespace={}&graphId={}&commitId={}",
+        scope.tenant_id, scope.project_id, scope.namespace, scope.graph_id, commit_id
+    );
+    let base_url = format!("http://{}/api/graph", server.addr());
+
+    let node: serde_json::Value = client
+        .get(format!("{}/nodes/a?{}", base_url, query))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let neighbors: serde_json::Value = client
+        .get(format!("{}/nodes/a/neighbors?{}&depth=1", base_url, query))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let path: serde_json::Value = client
+        .get(format!("{}/path?{}&from=a&to=b", base_url, query))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let snapshot: serde_json::Value = client
+        .get(format!("{}/snapshot?{}", base_url, query))
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    // Assert
+    assert_eq!(node["result"]["data"]["nodeId"], "a");
+    assert_eq!(neighbors["result"]["data"]["nodes"][0]["nodeId"], "b");
+    assert_eq!(neighbors["result"]["data"]["total"], 1);
+    assert_eq!(path["result"]["data"], true);
+    assert_eq!(snapshot["nodes"].as_array().map(Vec::len), Some(2));
+    assert_eq!(snapshot["edges"][0]["edgeId"], "a-b");
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn given_node_query_without_commit_when_requested_then_returns_400() -> Result<()> {
+    // Act
+    let server = start_test_server().await?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!(
+            "http://{}/api/graph/nodes/a?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1",
+            server.addr()
+        ))
+        .send()
+        .await?;
+
+    // Assert
+    assert_eq!(response.status(), 400);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn given_snapshot_query_without_commit_when_requested_then_returns_400() -> Result<()> {
+    // Act
+    let server = start_test_server().await?;
+
+    let response = reqwest::Client::new()
+        .get(format!(
+            "http://{}/api/graph/snapshot?tenantId=t1&projectId=p1&namespace=ns1&graphId=g1",
+            server.addr()
+        ))
+        .send()
+        .await?;
+
+    // Assert
+    assert_eq!(response.status(), 400);
+
+    Ok(())
+}
+
 #[tokio::test]
 #[serial]
 async fn given_health_endpoint_when_requested_then_returns_ok() -> Result<()> {
@@ -105,6 +105,50 @@ async fn ritual_http_api_executes_and_persists_runs() {
     assert!(envelope_json["envelope"].is_object());
 }
 
+#[tokio::test]
+async fn ritual_http_api_continues_caller_trace_into_run() {
+    let (app, _tempdir) = setup_test_app().await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/rituals/noop/runs")
+                .header("content-type", "application/json")
+                .header(
+                    "traceparent",
+                    "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                )
+                .body(Body::from(json!({"app": "hoss"}).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let created: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let run_id = created["runId"].as_str().unwrap();
+
+    tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+
+    let detail = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v1/rituals/noop/runs/{}?app=hoss", run_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = to_bytes(detail.into_body(), usize::MAX).await.unwrap();
+    let detail: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let trace = &detail["resultEnvelope"]["trace"];
+    assert_eq!(trace["traceId"], "4bf92f3577b34da6a3ce929d0e0e4736");
+    // The run is parented by the runtime's own ingress span, not the caller's
+    assert_ne!(trace["spanId"], "00f067aa0ba902b7");
+}
+
 #[tokio::test]
 async fn ritual_http_api_validates_required_app_parameter() {
     let (app, _tempdir) = setup_test_app().await;
@@ -127,6 +171,51 @@ async fn ritual_http_api_validates_required_app_parameter() {
     assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
 }
 
+#[tokio::test]
+async fn ritual_http_api_validates_parameters_against_ritual_inputs() {
+    let (app, _tempdir) = setup_test_app_with(|manifest| {
+        manifest.replace("apiVersion: demon.io/v1", "apiVersion: demon.io/v2")
+            + "    inputs:\n"
+            + "      type: object\n"
+            + "      required: [environment]\n"
+            + "      properties:\n"
+            + "        environment: { type: string, enum: [staging, prod] }\n"
+    })
+    .await;
+    let invoke = |parameters: serde_json::Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/v1/rituals/noop/runs")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({ "app": "hoss", "parameters": parameters }).to_string(),
+            ))
+            .unwrap()
+    };
+
+    let rejected = app
+        .clone()
+        .oneshot(invoke(json!({ "environment": "dev" })))
+        .await
+        .unwrap();
+    assert_eq!(rejected.status(), StatusCode::BAD_REQUEST);
+    let bytes = to_bytes(rejected.into_body(), usize::MAX).await.unwrap();
+    let error: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .contains("must match the inputs of ritual 'noop'"));
+
+    let missing = app.clone().oneshot(invoke(json!({}))).await.unwrap();
+    assert_eq!(missing.status(), StatusCode::BAD_REQUEST);
+
+    let accepted = app
+        .oneshot(invoke(json!({ "environment": "prod" })))
+        .await
+        .unwrap();
+    assert!(accepted.status().is_success());
+}
+
 #[tokio::test]
 async fn ritual_http_api_returns_error_for_unknown_app() {
     let (app, _tempdir) = setup_test_app().await;
@@ -348,6 +437,10 @@ async fn ritual_http_api_rejects_invalid_status_filter() {
 }
 
 async fn setup_test_app() -> (axum::Router, TempDir) {
+    setup_test_app_with(|manifest| manifest).await
+}
+
+async fn setup_test_app_with(customize: impl FnOnce(String) -> String) -> (axum::Router, TempDir) {
     let tempdir = tempfile::tempdir().unwrap();
     let app_root = tempdir.path().join("app-packs");
     std::fs::create_dir_all(app_root.clone()).unwrap();
@@ -365,7 +458,7 @@ async fn setup_test_app() -> (axum::Router, TempDir) {
         std::fs::read_to_string(workspace_root.join("examples/app-packs/hoss/app-pack.yaml"))
             .unwrap();
     let manifest_path = packs_dir.join("app-pack.yaml");
-    std::fs::write(&manifest_path, manifest_src).unwrap();
+    std::fs::write(&manifest_path, customize(manifest_src)).unwrap();
 
     let registry = json!({
         "apps": {
@@ -404,7 +497,8 @@ impl RitualRunner for StubRunner {
             "ritualId": plan.ritual_id,
             "runId": plan.run_id,
             "ts": Utc::now().to_rfc3339(),
-            "outputs": {"result": "ok"}
+            "outputs": {"result": "ok"},
+            "trace": plan.trace.map(|t| json!({"traceId": t.trace_id, "spanId": t.span_id}))
         }))
     }
 }
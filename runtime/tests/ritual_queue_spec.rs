@@ -0,0 +1,243 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use chrono::Utc;
+use runtime::server::create_app_with_service;
+use runtime::server::rituals::{
+    parse_weights, AppPackRegistry, ExecutionPlan, FairQueue, FairQueueConfig, RitualRunner,
+    RitualService, RunStore,
+};
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::sync::Semaphore;
+use tower::ServiceExt;
+
+fn drain(queue: &mut FairQueue<&'static str>) -> Vec<&'static str> {
+    std::iter::from_fn(|| queue.pop().map(|(_, item)| item)).collect()
+}
+
+#[test]
+fn given_burst_from_one_tenant_when_drained_then_other_tenant_is_not_starved() {
+    let mut queue = FairQueue::new(FairQueueConfig::default());
+    for item in ["noisy-1", "noisy-2", "noisy-3", "noisy-4"] {
+        queue.push("noisy", item);
+    }
+    queue.push("quiet", "quiet-1");
+
+    assert_eq!(queue.depth("noisy"), 4);
+    assert_eq!(
+        drain(&mut queue),
+        vec!["noisy-1", "quiet-1", "noisy-2", "noisy-3", "noisy-4"]
+    );
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn given_tenant_weights_when_drained_then_turns_follow_weights() {
+    let config = FairQueueConfig {
+        tenant_weights: parse_weights("gold=2").unwrap(),
+        ..FairQueueConfig::default()
+    };
+    let mut queue = FairQueue::new(config);
+    for item in ["gold-1", "gold-2", "gold-3", "gold-4"] {
+        queue.push("gold", item);
+    }
+    for item in ["basic-1", "basic-2"] {
+        queue.push("basic", item);
+    }
+
+    assert_eq!(
+        drain(&mut queue),
+        vec!["gold-1", "gold-2", "basic-1", "gold-3", "gold-4", "basic-2"]
+    );
+}
+
+#[test]
+fn given_queued_item_when_removed_then_tenant_leaves_rotation() {
+    let mut queue = FairQueue::new(FairQueueConfig::default());
+    queue.push("a", "a-1");
+    queue.push("b", "b-1");
+
+    assert_eq!(queue.remove_where(|item| *item == "a-1"), Some("a-1"));
+    assert_eq!(queue.depths().get("a"), None);
+    assert_eq!(drain(&mut queue), vec!["b-1"]);
+}
+
+#[test]
+fn given_malformed_weights_when_parsed_then_rejected() {
+    assert!(parse_weights("gold").is_err());
+    assert!(parse_weights("gold=0").is_err());
+    assert!(parse_weights("gold=x").is_err());
+    assert_eq!(parse_weights(" gold=3, ,basic=1 ").unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn given_single_slot_when_tenants_queue_runs_then_runs_start_in_fair_order() {
+    let runner = Arc::new(GatedRunner::new());
+    let (app, service, _tmp) = setup_test_app(runner.clone()).await;
+
+    for (tenant, tag) in [
+        ("noisy", "noisy-1"),
+        ("noisy", "noisy-2"),
+        ("noisy", "noisy-3"),
+        ("quiet", "quiet-1"),
+    ] {
+        let status = schedule(&app, tenant, tag).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+    }
+    runner.wait_for_started(1).await;
+
+    let queue = service.queue_status();
+    assert_eq!(queue.running, 1);
+    assert_eq!(queue.queued, 3);
+    let noisy = queue.tenants.iter().find(|t| t.tenant == "noisy").unwrap();
+    assert_eq!((noisy.queued, noisy.running), (2, 1));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/rituals/queue")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value =
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["maxConcurrent"], 1);
+    assert_eq!(body["queued"], 3);
+
+    for started in 2..=4 {
+        runner.gate.add_permits(1);
+        runner.wait_for_started(started).await;
+    }
+    runner.gate.add_permits(1);
+
+    assert_eq!(
+        runner.started(),
+        vec!["noisy-1", "noisy-2", "quiet-1", "noisy-3"]
+    );
+}
+
+async fn schedule(app: &axum::Router, tenant: &str, tag: &str) -> StatusCode {
+    let payload = json!({
+        "app": "hoss",
+        "tenant": tenant,
+        "parameters": {"tag": tag}
+    });
+    app.clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/rituals/noop/runs")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+        .status()
+}
+
+async fn setup_test_app(runner: Arc<GatedRunner>) -> (axum::Router, Arc<RitualService>, TempDir) {
+    let tempdir = tempfile::tempdir().unwrap();
+    let app_root = tempdir.path().join("app-packs");
+    let packs_dir = app_root.join("packs").join("hoss").join("0.1.0");
+    std::fs::create_dir_all(&packs_dir).unwrap();
+
+    let workspace_root = std::env::var("CARGO_MANIFEST_DIR")
+        .map(std::path::PathBuf::from)
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    let manifest_src =
+        std::fs::read_to_string(workspace_root.join("examples/app-packs/hoss/app-pack.yaml"))
+            .unwrap();
+    let manifest_path = packs_dir.join("app-pack.yaml");
+    std::fs::write(&manifest_path, manifest_src).unwrap();
+
+    let registry = json!({
+        "apps": {
+            "hoss": [{
+                "version": "0.1.0",
+                "manifest_path": manifest_path,
+                "installed_at": Utc::now().to_rfc3339(),
+                "source": "tests",
+                "schema_range": ">=1.0.0 <2.0.0"
+            }]
+        }
+    });
+    std::fs::write(
+        app_root.join("registry.json"),
+        serde_json::to_string_pretty(&registry).unwrap(),
+    )
+    .unwrap();
+
+    let run_store = RunStore::open(tempdir.path().join("runtime").join("runs.json")).unwrap();
+    let registry = AppPackRegistry::with_root(app_root);
+    let service = Arc::new(
+        RitualService::with_dependencies(registry, run_store, runner).with_queue_config(
+            FairQueueConfig {
+                max_concurrent: 1,
+                ..FairQueueConfig::default()
+            },
+        ),
+    );
+    let router = create_app_with_service(service.clone());
+
+    (router, service, tempdir)
+}
+
+/// Records the order runs start in and holds each run until a permit is added
+struct GatedRunner {
+    started: Mutex<Vec<String>>,
+    gate: Semaphore,
+}
+
+impl GatedRunner {
+    fn new() -> Self {
+        Self {
+            started: Mutex::new(Vec::new()),
+            gate: Semaphore::new(0),
+        }
+    }
+
+    fn started(&self) -> Vec<String> {
+        self.started.lock().unwrap().clone()
+    }
+
+    async fn wait_for_started(&self, count: usize) {
+        for _ in 0..200 {
+            if self.started().len() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("expected {count} started runs, got {:?}", self.started());
+    }
+}
+
+#[async_trait]
+impl RitualRunner for GatedRunner {
+    async fn run(&self, plan: ExecutionPlan) -> anyhow::Result<serde_json::Value> {
+        let tag = plan.arguments["tag"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        self.started.lock().unwrap().push(tag);
+        self.gate.acquire().await?.forget();
+        Ok(json!({
+            "event": "ritual.completed:v1",
+            "ritualId": plan.ritual_id,
+            "runId": plan.run_id,
+            "ts": Utc::now().to_rfc3339(),
+            "outputs": {"result": "ok"}
+        }))
+    }
+}
@@ -0,0 +1,219 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use chrono::Utc;
+use runtime::server::create_app_with_service;
+use runtime::server::rituals::{
+    AppPackRegistry, ExecutionPlan, FairQueueConfig, RitualRunner, RitualService, RunStatus,
+    RunStore,
+};
+use runtime::server::shutdown::{ShutdownConfig, ShutdownCoordinator};
+use serde_json::json;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn given_in_flight_run_when_drained_then_it_finishes_and_queued_run_stays_pending() {
+    let tmp = install_app_pack();
+    let runner = Arc::new(SleepyRunner::new(Duration::from_millis(100)));
+    let service = Arc::new(service_for(&tmp, runner.clone()));
+    let app = create_app_with_service(service.clone());
+
+    let first = schedule(&app).await;
+    let second = schedule(&app).await;
+    runner.wait_for_started(1).await;
+
+    let drained = coordinator(service.clone(), Duration::from_secs(5))
+        .drain()
+        .await;
+
+    assert_eq!(drained.event, "runtime.drained:v1");
+    assert_eq!(drained.finished, vec![first.clone()]);
+    assert!(drained.interrupted.is_empty());
+    assert_eq!(drained.requeued, vec![second.clone()]);
+    assert_eq!(status_of(&service, &first).await, RunStatus::Completed);
+    assert_eq!(status_of(&service, &second).await, RunStatus::Pending);
+    assert!(service.is_drained());
+
+    let response = app
+        .clone()
+        .oneshot(Request::get("/ready").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let response = post_run(&app).await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn given_run_past_drain_timeout_when_drained_then_interrupted_and_resumed_on_restart() {
+    let tmp = install_app_pack();
+    let stuck = Arc::new(SleepyRunner::new(Duration::from_secs(60)));
+    let service = Arc::new(service_for(&tmp, stuck.clone()));
+    let app = create_app_with_service(service.clone());
+
+    let run_id = schedule(&app).await;
+    stuck.wait_for_started(1).await;
+
+    let drained = coordinator(service.clone(), Duration::from_millis(100))
+        .drain()
+        .await;
+
+    assert_eq!(drained.interrupted, vec![run_id.clone()]);
+    assert!(drained.finished.is_empty());
+    assert_eq!(stuck.canceled(), vec![run_id.clone()]);
+    assert_eq!(status_of(&service, &run_id).await, RunStatus::Pending);
+
+    // A new runtime on the same run store picks the run up again
+    let fast = Arc::new(SleepyRunner::new(Duration::ZERO));
+    let restarted = service_for(&tmp, fast.clone());
+    assert_eq!(restarted.resume_pending().await.unwrap(), 1);
+    fast.wait_for_started(1).await;
+    assert!(restarted.wait_idle(Duration::from_secs(5)).await);
+    assert_eq!(status_of(&restarted, &run_id).await, RunStatus::Completed);
+}
+
+fn coordinator(service: Arc<RitualService>, drain_timeout: Duration) -> ShutdownCoordinator {
+    ShutdownCoordinator::new(
+        service,
+        ShutdownConfig {
+            drain_timeout,
+            ..ShutdownConfig::default()
+        },
+    )
+}
+
+async fn status_of(service: &RitualService, run_id: &str) -> RunStatus {
+    service
+        .get_run("hoss", "noop", run_id)
+        .await
+        .unwrap()
+        .expect("run exists")
+        .status
+}
+
+async fn post_run(app: &axum::Router) -> axum::response::Response {
+    app.clone()
+        .oneshot(
+            Request::post("/api/v1/rituals/noop/runs")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"app": "hoss"}).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+async fn schedule(app: &axum::Router) -> String {
+    let response = post_run(app).await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let body: serde_json::Value = serde_json::from_slice(
+        &axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    body["runId"].as_str().unwrap().to_string()
+}
+
+fn service_for(tmp: &TempDir, runner: Arc<SleepyRunner>) -> RitualService {
+    let run_store = RunStore::open(tmp.path().join("runtime").join("runs.json")).unwrap();
+    let registry = AppPackRegistry::with_root(tmp.path().join("app-packs"));
+    RitualService::with_dependencies(registry, run_store, runner).with_queue_config(
+        FairQueueConfig {
+            max_concurrent: 1,
+            ..FairQueueConfig::default()
+        },
+    )
+}
+
+fn install_app_pack() -> TempDir {
+    let tempdir = tempfile::tempdir().unwrap();
+    let app_root = tempdir.path().join("app-packs");
+    let packs_dir = app_root.join("packs").join("hoss").join("0.1.0");
+    std::fs::create_dir_all(&packs_dir).unwrap();
+
+    let workspace_root: PathBuf = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf();
+    let manifest_src =
+        std::fs::read_to_string(workspace_root.join("examples/app-packs/hoss/app-pack.yaml"))
+            .unwrap();
+    let manifest_path = packs_dir.join("app-pack.yaml");
+    std::fs::write(&manifest_path, manifest_src).unwrap();
+
+    let registry = json!({
+        "apps": {
+            "hoss": [{
+                "version": "0.1.0",
+                "manifest_path": manifest_path,
+                "installed_at": Utc::now().to_rfc3339(),
+                "source": "tests",
+                "schema_range": ">=1.0.0 <2.0.0"
+            }]
+        }
+    });
+    std::fs::write(
+        app_root.join("registry.json"),
+        serde_json::to_string_pretty(&registry).unwrap(),
+    )
+    .unwrap();
+
+    tempdir
+}
+
+/// Takes `duration` per run and records the runs it was asked to cancel
+struct SleepyRunner {
+    duration: Duration,
+    started: Mutex<Vec<String>>,
+    canceled: Mutex<Vec<String>>,
+}
+
+impl SleepyRunner {
+    fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            started: Mutex::new(Vec::new()),
+            canceled: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn canceled(&self) -> Vec<String> {
+        self.canceled.lock().unwrap().clone()
+    }
+
+    async fn wait_for_started(&self, count: usize) {
+        for _ in 0..200 {
+            if self.started.lock().unwrap().len() >= count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("expected {count} started runs");
+    }
+}
+
+#[async_trait]
+impl RitualRunner for SleepyRunner {
+    async fn run(&self, plan: ExecutionPlan) -> anyhow::Result<serde_json::Value> {
+        self.started.lock().unwrap().push(plan.run_id.clone());
+        tokio::time::sleep(self.duration).await;
+        Ok(json!({
+            "event": "ritual.completed:v1",
+            "ritualId": plan.ritual_id,
+            "runId": plan.run_id,
+            "ts": Utc::now().to_rfc3339(),
+            "outputs": {"result": "ok"}
+        }))
+    }
+
+    async fn cancel(&self, run_id: &str) {
+        self.canceled.lock().unwrap().push(run_id.to_string());
+    }
+}
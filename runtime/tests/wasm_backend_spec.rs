@@ -0,0 +1,58 @@
+#![cfg(feature = "wasm")]
+
+use runtime::link::capsule::{CapsuleBackend, CapsuleInvocation, CapsuleType};
+use runtime::link::router::Router;
+use runtime::link::wasm::{WasmBackend, WasmLimits};
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn given_request_limits_when_restricted_then_only_tighten_backend_limits() {
+    let limits = WasmLimits {
+        fuel: 1_000,
+        memory_bytes: 4096,
+        timeout: Duration::from_secs(10),
+    };
+
+    let tighter = limits.restrict(Some(10), Some(1024), Some(Duration::from_secs(1)));
+    assert_eq!(tighter.fuel, 10);
+    assert_eq!(tighter.memory_bytes, 1024);
+    assert_eq!(tighter.timeout, Duration::from_secs(1));
+
+    let looser = limits.restrict(
+        Some(1_000_000),
+        Some(1 << 30),
+        Some(Duration::from_secs(60)),
+    );
+    assert_eq!(looser, limits);
+    assert_eq!(limits.restrict(None, None, None), limits);
+}
+
+#[tokio::test]
+async fn given_missing_component_when_invoke_then_error_names_path() {
+    let backend = WasmBackend::new(WasmLimits::default()).unwrap();
+    assert_eq!(backend.capsule_type(), CapsuleType::Wasm);
+
+    let invocation = CapsuleInvocation::new(
+        "wasm",
+        json!({"component": "/nonexistent/capsule.wasm", "input": {}}),
+        "run-1",
+        "ritual-1",
+    );
+    let error = backend.invoke(&invocation).await.unwrap_err();
+
+    assert!(error.to_string().contains("/nonexistent/capsule.wasm"));
+}
+
+#[tokio::test]
+async fn given_wasm_feature_when_dispatch_without_component_then_request_is_rejected() {
+    let router = Router::new();
+    assert!(router.capsules().backend(CapsuleType::Wasm).is_some());
+
+    let error = router
+        .dispatch("wasm", &json!({"input": {}}), "run-2", "ritual-2")
+        .await
+        .unwrap_err();
+
+    assert!(error.to_string().contains("Failed to parse wasm request"));
+}
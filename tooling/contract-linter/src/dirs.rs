@@ -0,0 +1,278 @@
+//! Directory mode: lint every schema in two contract trees
+//!
+//! Files are paired by their path relative to the schema directory, so
+//! `contracts/schemas/bootstrapper/k8s-config.json` in the old tree is compared
+//! with the same path in the new one. Each side's version is extracted from
+//! its own file (see [`extract_version`]), and `$ref`s are resolved against
+//! the other schemas in the same tree.
+
+use crate::{lint_resolved_schemas, resolve_refs, LintReport};
+use anyhow::{Context, Result};
+use semver::Version;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Findings for one schema present in both trees
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileLintReport {
+    /// Path relative to the schema directory, `/`-separated
+    pub path: String,
+    #[serde(flatten)]
+    pub report: LintReport,
+}
+
+/// Aggregated result of [`lint_contract_dirs`]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirLintReport {
+    /// Number of schemas present in both trees
+    pub compared: usize,
+    /// Compared schemas with at least one finding
+    pub files: Vec<FileLintReport>,
+    /// Schemas only in the new tree
+    pub added: Vec<String>,
+    /// Schemas only in the old tree; removing a contract is breaking
+    pub removed: Vec<String>,
+}
+
+impl DirLintReport {
+    pub fn is_ok(&self) -> bool {
+        self.removed.is_empty() && self.files.iter().all(|file| file.report.is_ok())
+    }
+
+    pub fn has_breaking_changes(&self) -> bool {
+        !self.removed.is_empty()
+            || self
+                .files
+                .iter()
+                .any(|file| file.report.has_breaking_changes())
+    }
+}
+
+/// Compare every `*.json` schema under `old_dir` with its counterpart under
+/// `new_dir`, e.g. the `contracts/schemas` directories of two checkouts
+pub fn lint_contract_dirs(old_dir: &Path, new_dir: &Path) -> Result<DirLintReport> {
+    let old_files = read_schemas(old_dir)?;
+    let new_files = read_schemas(new_dir)?;
+    let mut report = DirLintReport::default();
+
+    for (name, new_schema) in &new_files {
+        let Some(old_schema) = old_files.get(name) else {
+            report.added.push(name.clone());
+            continue;
+        };
+        let file_report = lint_resolved_schemas(
+            &resolve_refs(old_schema, name, &old_files),
+            &resolve_refs(new_schema, name, &new_files),
+            extract_version(name, old_schema).as_deref(),
+            extract_version(name, new_schema).as_deref(),
+        )?;
+
+        report.compared += 1;
+        if !file_report.findings.is_empty() {
+            report.files.push(FileLintReport {
+                path: name.clone(),
+                report: file_report,
+            });
+        }
+    }
+
+    report.removed = old_files
+        .keys()
+        .filter(|name| !new_files.contains_key(*name))
+        .cloned()
+        .collect();
+
+    Ok(report)
+}
+
+/// Version of a schema file: a top-level semver `version` string if present,
+/// otherwise the `.vN.` segment of the file name as `N.0.0`
+pub fn extract_version(file_name: &str, schema: &Value) -> Option<String> {
+    if let Some(version) = schema.get("version").and_then(|v| v.as_str()) {
+        if Version::parse(version).is_ok() {
+            return Some(version.to_string());
+        }
+    }
+
+    let base_name = file_name.rsplit('/').next().unwrap_or(file_name);
+    base_name
+        .split('.')
+        .filter_map(|segment| segment.strip_prefix('v'))
+        .find(|major| !major.is_empty() && major.chars().all(|c| c.is_ascii_digit()))
+        .map(|major| format!("{}.0.0", major))
+}
+
+/// Map of `/`-separated relative path to parsed schema for every `*.json`
+/// under `dir`
+fn read_schemas(dir: &Path) -> Result<BTreeMap<String, Value>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current)
+            .with_context(|| format!("Failed to read directory {}", current.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+                let relative = path
+                    .strip_prefix(dir)
+                    .expect("walked path is under dir")
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.insert(relative, read_schema(&path)?);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn read_schema(path: &Path) -> Result<Value> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read schema: {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse schema JSON: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, schema: Value) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, serde_json::to_string_pretty(&schema).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_extract_version_prefers_top_level_version() {
+        let schema = json!({"version": "1.4.0"});
+        assert_eq!(
+            extract_version("app-pack.v2.schema.json", &schema).as_deref(),
+            Some("1.4.0")
+        );
+        assert_eq!(
+            extract_version("events.step.started.v1.json", &json!({})).as_deref(),
+            Some("1.0.0")
+        );
+        assert_eq!(
+            extract_version(
+                "bootstrapper/k8s-config.json",
+                &json!({"version": "latest"})
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lint_contract_dirs_pairs_files_by_relative_path() {
+        let old = TempDir::new().unwrap();
+        let new = TempDir::new().unwrap();
+        let status_v1 = json!({
+            "type": "object",
+            "properties": {"status": {"type": "string", "enum": ["open", "closed"]}}
+        });
+
+        write(old.path(), "events.status.v1.json", status_v1.clone());
+        write(
+            new.path(),
+            "events.status.v1.json",
+            json!({
+                "type": "object",
+                "properties": {"status": {"type": "string", "enum": ["open"]}}
+            }),
+        );
+        write(
+            old.path(),
+            "nested/unchanged.v1.json",
+            json!({"type": "string"}),
+        );
+        write(
+            new.path(),
+            "nested/unchanged.v1.json",
+            json!({"type": "string"}),
+        );
+        write(old.path(), "legacy.v1.json", json!({"type": "string"}));
+        write(new.path(), "events.status.v2.json", status_v1);
+
+        let report = lint_contract_dirs(old.path(), new.path()).unwrap();
+
+        assert_eq!(report.compared, 2);
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].path, "events.status.v1.json");
+        assert_eq!(
+            report.files[0].report.current_version.as_deref(),
+            Some("1.0.0")
+        );
+        assert!(!report.files[0].report.is_ok());
+        assert_eq!(report.added, vec!["events.status.v2.json"]);
+        assert_eq!(report.removed, vec!["legacy.v1.json"]);
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn test_lint_contract_dirs_accepts_bumped_top_level_version() {
+        let old = TempDir::new().unwrap();
+        let new = TempDir::new().unwrap();
+        write(
+            old.path(),
+            "config.json",
+            json!({"version": "1.0.0", "type": "object", "properties": {"a": {"type": "string"}}}),
+        );
+        write(
+            new.path(),
+            "config.json",
+            json!({"version": "2.0.0", "type": "object", "properties": {}}),
+        );
+
+        let report = lint_contract_dirs(old.path(), new.path()).unwrap();
+
+        assert!(report.has_breaking_changes());
+        assert!(report.is_ok());
+    }
+
+    #[test]
+    fn test_lint_contract_dirs_follows_refs_across_files() {
+        let old = TempDir::new().unwrap();
+        let new = TempDir::new().unwrap();
+        let event = json!({
+            "type": "object",
+            "properties": {"ts": {"$ref": "common/ts.v1.json#/$defs/Ts"}}
+        });
+        write(old.path(), "events.step.v1.json", event.clone());
+        write(new.path(), "events.step.v1.json", event);
+        write(
+            old.path(),
+            "common/ts.v1.json",
+            json!({"$defs": {"Ts": {"type": "string"}}}),
+        );
+        write(
+            new.path(),
+            "common/ts.v1.json",
+            json!({"$defs": {"Ts": {"type": "string", "format": "date-time"}}}),
+        );
+
+        let report = lint_contract_dirs(old.path(), new.path()).unwrap();
+
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].path, "events.step.v1.json");
+        assert_eq!(report.files[0].report.findings[0].rule, "format-added");
+        assert_eq!(
+            report.files[0].report.findings[0]
+                .proposed_pointer
+                .as_deref(),
+            Some("#/properties/ts/format")
+        );
+    }
+}
@@ -1,14 +1,30 @@
 //! Contract schema linter for detecting breaking changes
 //!
 //! Compares two versions of a JSON Schema contract and detects breaking changes
-//! such as removed fields, type changes, or constraint tightening.
+//! such as removed fields, type changes, or constraint tightening. Each change
+//! names the schema location it was found at as a JSON pointer fragment
+//! (e.g. `#/properties/status/enum`).
+//!
+//! [`lint_schema_change_detailed`] also reports risky and informational
+//! findings as a serializable [`LintReport`] for CI annotations.
+//! [`lint_contract_dirs`] lints whole contract trees.
+//!
+//! `$ref`s are inlined before comparing (see [`resolve_refs`]), so pointers
+//! locate findings in the resolved schema.
 
 use anyhow::{Context, Result};
 use semver::Version;
+use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use thiserror::Error;
 
+mod dirs;
+mod refs;
+
+pub use dirs::{extract_version, lint_contract_dirs, DirLintReport, FileLintReport};
+pub use refs::resolve_refs;
+
 #[derive(Debug, Error)]
 pub enum LintError {
     #[error("Breaking change detected: {0}")]
@@ -21,6 +37,90 @@ pub enum LintError {
     InvalidVersion(String),
 }
 
+/// How much a finding matters to existing producers and consumers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Data valid under the current schema may be rejected; needs a version bump
+    Breaking,
+    /// Still valid data, but consumers may mishandle it (e.g. a new enum value)
+    Risky,
+    /// Additions and relaxed constraints
+    Informational,
+}
+
+/// A single difference between the current and proposed schema
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    /// Stable rule id, e.g. `enum-value-removed`
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Location in the current schema, if it exists there
+    pub current_pointer: Option<String>,
+    /// Location in the proposed schema, if it exists there
+    pub proposed_pointer: Option<String>,
+}
+
+impl Finding {
+    fn new(
+        rule: &'static str,
+        severity: Severity,
+        message: String,
+        current_pointer: Option<String>,
+        proposed_pointer: Option<String>,
+    ) -> Self {
+        Self {
+            rule,
+            severity,
+            message,
+            current_pointer,
+            proposed_pointer,
+        }
+    }
+
+    /// A breaking finding located at the same pointer in both schemas
+    fn breaking(rule: &'static str, message: String, pointer: String) -> Self {
+        Self::new(
+            rule,
+            Severity::Breaking,
+            message,
+            Some(pointer.clone()),
+            Some(pointer),
+        )
+    }
+}
+
+/// Machine-readable result of linting a contract schema change
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub findings: Vec<Finding>,
+    pub version_check_passed: bool,
+    pub current_version: Option<String>,
+    pub proposed_version: Option<String>,
+}
+
+impl LintReport {
+    pub fn is_ok(&self) -> bool {
+        !self.has_breaking_changes() || self.version_check_passed
+    }
+
+    pub fn has_breaking_changes(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == Severity::Breaking)
+    }
+
+    /// Findings of the given severity, in detection order
+    pub fn findings_with(&self, severity: Severity) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(move |finding| finding.severity == severity)
+    }
+}
+
 /// Result of linting a contract schema change
 #[derive(Debug, Clone)]
 pub struct LintResult {
@@ -40,6 +140,20 @@ impl LintResult {
     }
 }
 
+impl From<LintReport> for LintResult {
+    fn from(report: LintReport) -> Self {
+        Self {
+            breaking_changes: report
+                .findings_with(Severity::Breaking)
+                .map(|finding| finding.message.clone())
+                .collect(),
+            version_check_passed: report.version_check_passed,
+            current_version: report.current_version,
+            proposed_version: report.proposed_version,
+        }
+    }
+}
+
 /// Compare two JSON Schema objects and detect breaking changes
 pub fn lint_schema_change(
     current_schema: &Value,
@@ -47,18 +161,51 @@ pub fn lint_schema_change(
     current_version: Option<&str>,
     proposed_version: Option<&str>,
 ) -> Result<LintResult> {
-    let mut breaking_changes = Vec::new();
-
-    // Detect breaking changes in schema structure
-    detect_breaking_changes(
+    lint_schema_change_detailed(
         current_schema,
         proposed_schema,
-        "root",
-        &mut breaking_changes,
-    );
+        current_version,
+        proposed_version,
+    )
+    .map(LintResult::from)
+}
+
+/// Compare two JSON Schema objects and report every finding with its
+/// severity, rule id and location in both schemas. Only document-local
+/// `$ref`s are resolved; [`lint_contract_dirs`] also follows refs across files.
+pub fn lint_schema_change_detailed(
+    current_schema: &Value,
+    proposed_schema: &Value,
+    current_version: Option<&str>,
+    proposed_version: Option<&str>,
+) -> Result<LintReport> {
+    let no_documents = BTreeMap::new();
+    lint_resolved_schemas(
+        &resolve_refs(current_schema, "", &no_documents),
+        &resolve_refs(proposed_schema, "", &no_documents),
+        current_version,
+        proposed_version,
+    )
+}
+
+/// Lint two schemas whose `$ref`s have already been resolved
+pub(crate) fn lint_resolved_schemas(
+    current_schema: &Value,
+    proposed_schema: &Value,
+    current_version: Option<&str>,
+    proposed_version: Option<&str>,
+) -> Result<LintReport> {
+    let mut findings = Vec::new();
+
+    // Detect changes in schema structure
+    detect_breaking_changes(current_schema, proposed_schema, "#", &mut findings);
+
+    let has_breaking = findings
+        .iter()
+        .any(|finding| finding.severity == Severity::Breaking);
 
     // Check if version bump is appropriate for breaking changes
-    let version_check_passed = if !breaking_changes.is_empty() {
+    let version_check_passed = if has_breaking {
         match (current_version, proposed_version) {
             (Some(curr), Some(prop)) => validate_version_bump(curr, prop)?,
             _ => false, // No version info, can't validate
@@ -67,29 +214,46 @@ pub fn lint_schema_change(
         true // No breaking changes, version bump is optional
     };
 
-    Ok(LintResult {
-        breaking_changes,
+    Ok(LintReport {
+        findings,
         version_check_passed,
         current_version: current_version.map(String::from),
         proposed_version: proposed_version.map(String::from),
     })
 }
 
-/// Detect breaking changes between two schema values
+/// Detect changes between two schema values
 fn detect_breaking_changes(
     current: &Value,
     proposed: &Value,
     path: &str,
-    changes: &mut Vec<String>,
+    changes: &mut Vec<Finding>,
 ) {
     match (current, proposed) {
         (Value::Object(curr_obj), Value::Object(prop_obj)) => {
             // Check for removed properties
             let current_props = get_properties(curr_obj);
             let proposed_props = get_properties(prop_obj);
+            let props_path = pointer(path, "properties");
 
             for key in current_props.difference(&proposed_props) {
-                changes.push(format!("Removed required property '{}' at {}", key, path));
+                changes.push(Finding::new(
+                    "property-removed",
+                    Severity::Breaking,
+                    format!("Removed required property '{}' at {}", key, path),
+                    Some(pointer(&props_path, key)),
+                    None,
+                ));
+            }
+
+            for key in proposed_props.difference(&current_props) {
+                changes.push(Finding::new(
+                    "property-added",
+                    Severity::Informational,
+                    format!("Added property '{}' at {}", key, path),
+                    None,
+                    Some(pointer(&props_path, key)),
+                ));
             }
 
             // Check for type changes in common properties
@@ -104,7 +268,7 @@ fn detect_breaking_changes(
                     .and_then(|p| p.get(key.as_str()));
 
                 if let (Some(curr_val), Some(prop_val)) = (curr_val, prop_val) {
-                    let new_path = format!("{}.{}", path, key);
+                    let new_path = pointer(&props_path, key);
                     detect_breaking_changes(curr_val, prop_val, &new_path, changes);
                 }
             }
@@ -113,9 +277,13 @@ fn detect_breaking_changes(
             if let (Some(curr_type), Some(prop_type)) = (curr_obj.get("type"), prop_obj.get("type"))
             {
                 if curr_type != prop_type {
-                    changes.push(format!(
-                        "Type changed from {:?} to {:?} at {}",
-                        curr_type, prop_type, path
+                    changes.push(Finding::breaking(
+                        "type-changed",
+                        format!(
+                            "Type changed from {:?} to {:?} at {}",
+                            curr_type, prop_type, path
+                        ),
+                        pointer(path, "type"),
                     ));
                 }
 
@@ -127,34 +295,60 @@ fn detect_breaking_changes(
                         detect_breaking_changes(
                             curr_items,
                             prop_items,
-                            &format!("{}[items]", path),
+                            &pointer(path, "items"),
                             changes,
                         );
                     }
                 }
             }
 
+            // Refs left after resolution are recursive or external; a different
+            // target may mean a different shape
+            if let (Some(curr_ref), Some(prop_ref)) = (curr_obj.get("$ref"), prop_obj.get("$ref")) {
+                if curr_ref != prop_ref {
+                    changes.push(Finding::new(
+                        "ref-changed",
+                        Severity::Risky,
+                        format!(
+                            "Changed unresolved $ref from {} to {} at {}",
+                            curr_ref, prop_ref, path
+                        ),
+                        Some(pointer(path, "$ref")),
+                        Some(pointer(path, "$ref")),
+                    ));
+                }
+            }
+
             // Check for stricter constraints
             check_constraint_changes(curr_obj, prop_obj, path, changes);
         }
         (Value::Array(curr_arr), Value::Array(prop_arr)) => {
             // For arrays, check if items schema changed
             if let (Some(curr_items), Some(prop_items)) = (curr_arr.first(), prop_arr.first()) {
-                detect_breaking_changes(curr_items, prop_items, &format!("{}[]", path), changes);
+                detect_breaking_changes(curr_items, prop_items, &pointer(path, "0"), changes);
             }
         }
         _ => {
             // Primitive type mismatch
             if current != proposed && !is_compatible_change(current, proposed) {
-                changes.push(format!(
-                    "Incompatible value change at {}: {:?} -> {:?}",
-                    path, current, proposed
+                changes.push(Finding::breaking(
+                    "value-changed",
+                    format!(
+                        "Incompatible value change at {}: {:?} -> {:?}",
+                        path, current, proposed
+                    ),
+                    path.to_string(),
                 ));
             }
         }
     }
 }
 
+/// Append a reference token to a JSON pointer, escaping `~` and `/` per RFC 6901
+fn pointer(path: &str, token: &str) -> String {
+    format!("{}/{}", path, token.replace('~', "~0").replace('/', "~1"))
+}
+
 /// Extract property names from a JSON Schema object
 fn get_properties(obj: &serde_json::Map<String, Value>) -> HashSet<String> {
     obj.get("properties")
@@ -168,7 +362,7 @@ fn check_constraint_changes(
     current: &serde_json::Map<String, Value>,
     proposed: &serde_json::Map<String, Value>,
     path: &str,
-    changes: &mut Vec<String>,
+    changes: &mut Vec<Finding>,
 ) {
     // Check minLength increase
     if let (Some(curr_min), Some(prop_min)) = (
@@ -176,10 +370,16 @@ fn check_constraint_changes(
         proposed.get("minLength").and_then(|v| v.as_u64()),
     ) {
         if prop_min > curr_min {
-            changes.push(format!(
-                "Increased minLength from {} to {} at {}",
-                curr_min, prop_min, path
+            changes.push(Finding::breaking(
+                "min-length-increased",
+                format!(
+                    "Increased minLength from {} to {} at {}",
+                    curr_min, prop_min, path
+                ),
+                pointer(path, "minLength"),
             ));
+        } else if prop_min < curr_min {
+            changes.push(relaxed("minLength", path));
         }
     }
 
@@ -189,28 +389,309 @@ fn check_constraint_changes(
         proposed.get("maxLength").and_then(|v| v.as_u64()),
     ) {
         if prop_max < curr_max {
-            changes.push(format!(
-                "Decreased maxLength from {} to {} at {}",
-                curr_max, prop_max, path
+            changes.push(Finding::breaking(
+                "max-length-decreased",
+                format!(
+                    "Decreased maxLength from {} to {} at {}",
+                    curr_max, prop_max, path
+                ),
+                pointer(path, "maxLength"),
             ));
+        } else if prop_max > curr_max {
+            changes.push(relaxed("maxLength", path));
+        }
+    }
+    for keyword in ["minLength", "maxLength"] {
+        if current.contains_key(keyword) && !proposed.contains_key(keyword) {
+            changes.push(relaxed(keyword, path));
         }
     }
 
-    // Check required fields added
-    if let (Some(curr_req), Some(prop_req)) = (
-        current.get("required").and_then(|v| v.as_array()),
-        proposed.get("required").and_then(|v| v.as_array()),
-    ) {
+    // Check required fields added or dropped
+    let curr_req = current.get("required").and_then(|v| v.as_array());
+    let prop_req = proposed.get("required").and_then(|v| v.as_array());
+    if let (Some(curr_req), Some(prop_req)) = (curr_req, prop_req) {
         let curr_set: HashSet<_> = curr_req.iter().collect();
-        let prop_set: HashSet<_> = prop_req.iter().collect();
 
-        for new_req in prop_set.difference(&curr_set) {
-            changes.push(format!(
-                "New required field added: {:?} at {}",
-                new_req, path
+        for (index, new_req) in prop_req.iter().enumerate() {
+            if !curr_set.contains(new_req) {
+                changes.push(Finding::new(
+                    "required-added",
+                    Severity::Breaking,
+                    format!("New required field added: {:?} at {}", new_req, path),
+                    Some(pointer(path, "required")),
+                    Some(pointer(&pointer(path, "required"), &index.to_string())),
+                ));
+            }
+        }
+    }
+    // Producers may now omit a field that consumers still read
+    let proposed_props = get_properties(proposed);
+    for (index, old_req) in curr_req.into_iter().flatten().enumerate() {
+        let still_required = prop_req.is_some_and(|req| req.contains(old_req));
+        let still_defined = old_req
+            .as_str()
+            .is_some_and(|name| proposed_props.contains(name));
+        if !still_required && still_defined {
+            changes.push(Finding::new(
+                "required-removed",
+                Severity::Risky,
+                format!("Field no longer required: {} at {}", old_req, path),
+                Some(pointer(&pointer(path, "required"), &index.to_string())),
+                prop_req.map(|_| pointer(path, "required")),
             ));
         }
     }
+
+    if let (Some(curr_default), Some(prop_default)) =
+        (current.get("default"), proposed.get("default"))
+    {
+        if curr_default != prop_default {
+            changes.push(Finding::new(
+                "default-changed",
+                Severity::Risky,
+                format!(
+                    "Changed default from {} to {} at {}",
+                    curr_default,
+                    prop_default,
+                    pointer(path, "default")
+                ),
+                Some(pointer(path, "default")),
+                Some(pointer(path, "default")),
+            ));
+        }
+    }
+
+    check_enum_changes(current, proposed, path, changes);
+
+    // A new or different pattern may reject strings the old one accepted
+    check_keyword_change(current, proposed, "pattern", path, changes);
+    // Same for format, which validators may enforce
+    check_keyword_change(current, proposed, "format", path, changes);
+
+    for keyword in ["minimum", "exclusiveMinimum"] {
+        check_bound_change(
+            current,
+            proposed,
+            keyword,
+            |curr, prop| prop > curr,
+            path,
+            changes,
+        );
+    }
+    for keyword in ["maximum", "exclusiveMaximum"] {
+        check_bound_change(
+            current,
+            proposed,
+            keyword,
+            |curr, prop| prop < curr,
+            path,
+            changes,
+        );
+    }
+    // Every multiple of the old step must still be a multiple of the new one
+    check_bound_change(
+        current,
+        proposed,
+        "multipleOf",
+        |curr, prop| {
+            let ratio = curr / prop;
+            (ratio - ratio.round()).abs() > 1e-9
+        },
+        path,
+        changes,
+    );
+
+    check_additional_properties_change(current, proposed, path, changes);
+}
+
+/// Check for enum values that are no longer accepted, or newly accepted
+fn check_enum_changes(
+    current: &serde_json::Map<String, Value>,
+    proposed: &serde_json::Map<String, Value>,
+    path: &str,
+    changes: &mut Vec<Finding>,
+) {
+    let enum_path = pointer(path, "enum");
+    match (
+        current.get("enum").and_then(|v| v.as_array()),
+        proposed.get("enum").and_then(|v| v.as_array()),
+    ) {
+        (Some(curr_enum), Some(prop_enum)) => {
+            for (index, value) in curr_enum.iter().enumerate() {
+                if !prop_enum.contains(value) {
+                    changes.push(Finding::new(
+                        "enum-value-removed",
+                        Severity::Breaking,
+                        format!("Removed enum value {} at {}", value, enum_path),
+                        Some(pointer(&enum_path, &index.to_string())),
+                        Some(enum_path.clone()),
+                    ));
+                }
+            }
+            // Consumers that match exhaustively will not know the new value
+            for (index, value) in prop_enum.iter().enumerate() {
+                if !curr_enum.contains(value) {
+                    changes.push(Finding::new(
+                        "enum-value-added",
+                        Severity::Risky,
+                        format!("Added enum value {} at {}", value, enum_path),
+                        Some(enum_path.clone()),
+                        Some(pointer(&enum_path, &index.to_string())),
+                    ));
+                }
+            }
+        }
+        (None, Some(prop_enum)) => changes.push(Finding::new(
+            "enum-added",
+            Severity::Breaking,
+            format!(
+                "Added enum restriction {} at {}",
+                Value::Array(prop_enum.clone()),
+                enum_path
+            ),
+            None,
+            Some(enum_path),
+        )),
+        (Some(_), None) => changes.push(relaxed("enum", path)),
+        (None, None) => {}
+    }
+}
+
+/// Check a keyword where any new or different value may reject existing data
+fn check_keyword_change(
+    current: &serde_json::Map<String, Value>,
+    proposed: &serde_json::Map<String, Value>,
+    keyword: &'static str,
+    path: &str,
+    changes: &mut Vec<Finding>,
+) {
+    let keyword_path = pointer(path, keyword);
+    match (current.get(keyword), proposed.get(keyword)) {
+        (None, Some(prop_val)) => changes.push(Finding::new(
+            if keyword == "pattern" {
+                "pattern-added"
+            } else {
+                "format-added"
+            },
+            Severity::Breaking,
+            format!("Added {} {} at {}", keyword, prop_val, keyword_path),
+            None,
+            Some(keyword_path),
+        )),
+        (Some(curr_val), Some(prop_val)) if curr_val != prop_val => {
+            changes.push(Finding::breaking(
+                if keyword == "pattern" {
+                    "pattern-changed"
+                } else {
+                    "format-changed"
+                },
+                format!(
+                    "Changed {} from {} to {} at {}",
+                    keyword, curr_val, prop_val, keyword_path
+                ),
+                keyword_path,
+            ))
+        }
+        (Some(_), None) => changes.push(relaxed(keyword, path)),
+        _ => {}
+    }
+}
+
+/// Check a numeric keyword for tightening; adding the keyword always tightens
+fn check_bound_change(
+    current: &serde_json::Map<String, Value>,
+    proposed: &serde_json::Map<String, Value>,
+    keyword: &str,
+    tightened: impl Fn(f64, f64) -> bool,
+    path: &str,
+    changes: &mut Vec<Finding>,
+) {
+    let curr_val = current.get(keyword).filter(|v| v.is_number());
+    let prop_val = proposed.get(keyword).filter(|v| v.is_number());
+    match (curr_val, prop_val) {
+        (None, Some(prop_val)) => changes.push(Finding::new(
+            "bound-added",
+            Severity::Breaking,
+            format!(
+                "Added {} {} at {}",
+                keyword,
+                prop_val,
+                pointer(path, keyword)
+            ),
+            None,
+            Some(pointer(path, keyword)),
+        )),
+        (Some(curr_val), Some(prop_val)) if curr_val != prop_val => {
+            if let (Some(curr), Some(prop)) = (curr_val.as_f64(), prop_val.as_f64()) {
+                if tightened(curr, prop) {
+                    changes.push(Finding::breaking(
+                        "bound-tightened",
+                        format!(
+                            "Tightened {} from {} to {} at {}",
+                            keyword,
+                            curr_val,
+                            prop_val,
+                            pointer(path, keyword)
+                        ),
+                        pointer(path, keyword),
+                    ));
+                } else {
+                    changes.push(relaxed(keyword, path));
+                }
+            }
+        }
+        (Some(_), None) => changes.push(relaxed(keyword, path)),
+        _ => {}
+    }
+}
+
+/// Check for additionalProperties that accept less than before
+fn check_additional_properties_change(
+    current: &serde_json::Map<String, Value>,
+    proposed: &serde_json::Map<String, Value>,
+    path: &str,
+    changes: &mut Vec<Finding>,
+) {
+    let additional_path = pointer(path, "additionalProperties");
+    // Absent is equivalent to `true`
+    let allow_all = Value::Bool(true);
+    let curr = current.get("additionalProperties").unwrap_or(&allow_all);
+    let prop = proposed.get("additionalProperties").unwrap_or(&allow_all);
+
+    match (curr, prop) {
+        _ if curr == prop => {}
+        (Value::Bool(false), _) | (_, Value::Bool(true)) => {
+            changes.push(relaxed("additionalProperties", path))
+        }
+        (Value::Object(_), Value::Object(_)) => {
+            detect_breaking_changes(curr, prop, &additional_path, changes)
+        }
+        _ => changes.push(Finding::new(
+            "additional-properties-tightened",
+            Severity::Breaking,
+            format!(
+                "Tightened additionalProperties from {} to {} at {}",
+                curr, prop, additional_path
+            ),
+            current
+                .get("additionalProperties")
+                .map(|_| additional_path.clone()),
+            Some(additional_path),
+        )),
+    }
+}
+
+/// An informational finding for a constraint that now accepts more
+fn relaxed(keyword: &str, path: &str) -> Finding {
+    let keyword_path = pointer(path, keyword);
+    Finding::new(
+        "constraint-relaxed",
+        Severity::Informational,
+        format!("Relaxed {} at {}", keyword, keyword_path),
+        Some(keyword_path),
+        None,
+    )
 }
 
 /// Check if a value change is compatible (non-breaking)
@@ -355,7 +836,7 @@ mod tests {
         assert!(result.has_breaking_changes());
         assert_eq!(result.breaking_changes.len(), 1);
         assert!(result.breaking_changes[0].contains("Type changed"));
-        assert!(result.breaking_changes[0].contains("root[items]"));
+        assert!(result.breaking_changes[0].contains("#/items"));
         assert!(result.version_check_passed); // Major bump is valid
     }
 
@@ -390,4 +871,346 @@ mod tests {
         assert!(result.breaking_changes[0].contains("name"));
         assert!(result.version_check_passed); // Major bump is valid
     }
+
+    #[test]
+    fn test_removed_enum_value_reports_pointer() {
+        let current = json!({
+            "type": "object",
+            "properties": {"status": {"type": "string", "enum": ["open", "closed", "draft"]}}
+        });
+        let proposed = json!({
+            "type": "object",
+            "properties": {"status": {"type": "string", "enum": ["open", "closed", "archived"]}}
+        });
+
+        let result = lint_schema_change(&current, &proposed, Some("1.0.0"), Some("1.1.0")).unwrap();
+
+        assert_eq!(
+            result.breaking_changes,
+            vec!["Removed enum value \"draft\" at #/properties/status/enum"]
+        );
+        assert!(!result.version_check_passed);
+    }
+
+    #[test]
+    fn test_added_enum_restriction() {
+        let current = json!({"type": "string"});
+        let proposed = json!({"type": "string", "enum": ["a"]});
+
+        let result = lint_schema_change(&current, &proposed, None, None).unwrap();
+
+        assert_eq!(
+            result.breaking_changes,
+            vec!["Added enum restriction [\"a\"] at #/enum"]
+        );
+    }
+
+    #[test]
+    fn test_pattern_added_or_changed() {
+        let current = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "slug": {"type": "string", "pattern": "^[a-z]+$"}
+            }
+        });
+        let proposed = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string", "pattern": "^[0-9]+$"},
+                "slug": {"type": "string", "pattern": "^[a-z-]+$"}
+            }
+        });
+
+        let mut changes = lint_schema_change(&current, &proposed, None, None)
+            .unwrap()
+            .breaking_changes;
+        changes.sort();
+
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].starts_with("Added pattern"));
+        assert!(changes[0].ends_with("at #/properties/id/pattern"));
+        assert!(changes[1].starts_with("Changed pattern"));
+        assert!(changes[1].ends_with("at #/properties/slug/pattern"));
+    }
+
+    #[test]
+    fn test_removed_pattern_is_compatible() {
+        let current = json!({"type": "string", "pattern": "^[a-z]+$", "format": "email"});
+        let proposed = json!({"type": "string"});
+
+        let result = lint_schema_change(&current, &proposed, None, None).unwrap();
+
+        assert!(result.breaking_changes.is_empty());
+    }
+
+    #[test]
+    fn test_numeric_bounds_tightened() {
+        let current = json!({
+            "type": "object",
+            "properties": {
+                "age": {"type": "integer", "minimum": 0, "maximum": 150},
+                "score": {"type": "number", "multipleOf": 0.5}
+            }
+        });
+        let proposed = json!({
+            "type": "object",
+            "properties": {
+                "age": {"type": "integer", "minimum": 18, "maximum": 120, "exclusiveMaximum": 200},
+                "score": {"type": "number", "multipleOf": 0.25}
+            }
+        });
+
+        let mut changes = lint_schema_change(&current, &proposed, None, None)
+            .unwrap()
+            .breaking_changes;
+        changes.sort();
+
+        assert_eq!(
+            changes,
+            vec![
+                "Added exclusiveMaximum 200 at #/properties/age/exclusiveMaximum",
+                "Tightened maximum from 150 to 120 at #/properties/age/maximum",
+                "Tightened minimum from 0 to 18 at #/properties/age/minimum",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiple_of_changed_to_non_divisor() {
+        let current = json!({"type": "integer", "multipleOf": 2});
+        let proposed = json!({"type": "integer", "multipleOf": 3});
+
+        let result = lint_schema_change(&current, &proposed, None, None).unwrap();
+
+        assert_eq!(
+            result.breaking_changes,
+            vec!["Tightened multipleOf from 2 to 3 at #/multipleOf"]
+        );
+    }
+
+    #[test]
+    fn test_loosened_bounds_are_compatible() {
+        let current = json!({"type": "integer", "minimum": 10, "maximum": 20, "multipleOf": 4});
+        let proposed = json!({"type": "integer", "minimum": 5, "multipleOf": 2});
+
+        let result = lint_schema_change(&current, &proposed, None, None).unwrap();
+
+        assert!(result.breaking_changes.is_empty());
+    }
+
+    #[test]
+    fn test_additional_properties_tightened() {
+        let current = json!({
+            "type": "object",
+            "properties": {
+                "labels": {"type": "object", "additionalProperties": {"type": "string"}},
+                "meta": {"type": "object"}
+            }
+        });
+        let proposed = json!({
+            "type": "object",
+            "additionalProperties": false,
+            "properties": {
+                "labels": {"type": "object", "additionalProperties": {"type": "integer"}},
+                "meta": {"type": "object", "additionalProperties": {"type": "string"}}
+            }
+        });
+
+        let mut changes = lint_schema_change(&current, &proposed, None, None)
+            .unwrap()
+            .breaking_changes;
+        changes.sort();
+
+        assert_eq!(changes.len(), 3);
+        assert!(changes[0].starts_with("Tightened additionalProperties from true to false"));
+        assert!(changes[0].ends_with("at #/additionalProperties"));
+        assert!(changes[1].ends_with("at #/properties/meta/additionalProperties"));
+        assert!(changes[2].starts_with("Type changed"));
+        assert!(changes[2].ends_with("at #/properties/labels/additionalProperties"));
+    }
+
+    #[test]
+    fn test_format_changed() {
+        let current = json!({"type": "object", "properties": {"contact": {"type": "string", "format": "email"}}});
+        let proposed = json!({"type": "object", "properties": {"contact": {"type": "string", "format": "uri"}}});
+
+        let result = lint_schema_change(&current, &proposed, None, None).unwrap();
+
+        assert_eq!(
+            result.breaking_changes,
+            vec!["Changed format from \"email\" to \"uri\" at #/properties/contact/format"]
+        );
+    }
+
+    #[test]
+    fn test_pointer_escapes_reference_tokens() {
+        assert_eq!(pointer("#/properties", "a/b~c"), "#/properties/a~1b~0c");
+    }
+
+    #[test]
+    fn test_detailed_report_severities_and_pointers() {
+        let current = json!({
+            "type": "object",
+            "required": ["status", "note"],
+            "properties": {
+                "status": {"type": "string", "enum": ["open", "closed"], "default": "open"},
+                "note": {"type": "string", "maxLength": 10}
+            }
+        });
+        let proposed = json!({
+            "type": "object",
+            "required": ["status"],
+            "properties": {
+                "status": {"type": "string", "enum": ["open", "paused"], "default": "paused"},
+                "note": {"type": "string"},
+                "tags": {"type": "array"}
+            }
+        });
+
+        let report =
+            lint_schema_change_detailed(&current, &proposed, Some("1.0.0"), Some("2.0.0")).unwrap();
+        let find = |rule: &str| {
+            report
+                .findings
+                .iter()
+                .find(|finding| finding.rule == rule)
+                .unwrap_or_else(|| panic!("missing {}", rule))
+        };
+
+        let removed = find("enum-value-removed");
+        assert_eq!(removed.severity, Severity::Breaking);
+        assert_eq!(
+            removed.current_pointer.as_deref(),
+            Some("#/properties/status/enum/1")
+        );
+        assert_eq!(
+            removed.proposed_pointer.as_deref(),
+            Some("#/properties/status/enum")
+        );
+
+        let added = find("enum-value-added");
+        assert_eq!(added.severity, Severity::Risky);
+        assert_eq!(
+            added.proposed_pointer.as_deref(),
+            Some("#/properties/status/enum/1")
+        );
+
+        assert_eq!(find("default-changed").severity, Severity::Risky);
+        assert_eq!(find("required-removed").severity, Severity::Risky);
+        assert_eq!(
+            find("required-removed").current_pointer.as_deref(),
+            Some("#/required/1")
+        );
+
+        let tags = find("property-added");
+        assert_eq!(tags.severity, Severity::Informational);
+        assert_eq!(tags.current_pointer, None);
+        assert_eq!(tags.proposed_pointer.as_deref(), Some("#/properties/tags"));
+
+        let relaxed = find("constraint-relaxed");
+        assert_eq!(
+            relaxed.current_pointer.as_deref(),
+            Some("#/properties/note/maxLength")
+        );
+        assert_eq!(relaxed.proposed_pointer, None);
+
+        assert_eq!(report.findings_with(Severity::Breaking).count(), 1);
+        assert!(report.version_check_passed);
+        assert!(report.is_ok());
+    }
+
+    #[test]
+    fn test_risky_findings_do_not_require_version_bump() {
+        let current = json!({"type": "string", "enum": ["a"]});
+        let proposed = json!({"type": "string", "enum": ["a", "b"]});
+
+        let report =
+            lint_schema_change_detailed(&current, &proposed, Some("1.0.0"), Some("1.0.1")).unwrap();
+
+        assert!(!report.has_breaking_changes());
+        assert!(report.version_check_passed);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].severity, Severity::Risky);
+    }
+
+    #[test]
+    fn test_report_serializes_to_json() {
+        let current = json!({"type": "object", "properties": {"age": {"type": "integer"}}});
+        let proposed = json!({"type": "object", "properties": {}});
+
+        let report =
+            lint_schema_change_detailed(&current, &proposed, Some("1.0.0"), Some("1.1.0")).unwrap();
+        let value = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "findings": [{
+                    "rule": "property-removed",
+                    "severity": "breaking",
+                    "message": "Removed required property 'age' at #",
+                    "currentPointer": "#/properties/age",
+                    "proposedPointer": null
+                }],
+                "versionCheckPassed": false,
+                "currentVersion": "1.0.0",
+                "proposedVersion": "1.1.0"
+            })
+        );
+    }
+
+    #[test]
+    fn test_moving_property_into_defs_is_not_a_change() {
+        let current = json!({
+            "type": "object",
+            "properties": {"status": {"type": "string", "enum": ["open", "closed"]}}
+        });
+        let proposed = json!({
+            "type": "object",
+            "properties": {"status": {"$ref": "#/$defs/Status"}},
+            "$defs": {"Status": {"type": "string", "enum": ["open", "closed"]}}
+        });
+
+        let report = lint_schema_change_detailed(&current, &proposed, None, None).unwrap();
+
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_change_behind_ref_is_detected() {
+        let current = json!({
+            "type": "object",
+            "properties": {"status": {"$ref": "#/$defs/Status"}},
+            "$defs": {"Status": {"type": "string", "enum": ["open", "closed"]}}
+        });
+        let proposed = json!({
+            "type": "object",
+            "properties": {"status": {"$ref": "#/$defs/Status"}},
+            "$defs": {"Status": {"type": "string", "enum": ["open"]}}
+        });
+
+        let result = lint_schema_change(&current, &proposed, Some("1.0.0"), Some("1.1.0")).unwrap();
+
+        assert_eq!(
+            result.breaking_changes,
+            vec!["Removed enum value \"closed\" at #/properties/status/enum"]
+        );
+    }
+
+    #[test]
+    fn test_changed_unresolved_ref_is_risky() {
+        let current = json!({"properties": {"ts": {"$ref": "common.v1.json#/$defs/Ts"}}});
+        let proposed = json!({"properties": {"ts": {"$ref": "common.v2.json#/$defs/Ts"}}});
+
+        let report = lint_schema_change_detailed(&current, &proposed, None, None).unwrap();
+
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].rule, "ref-changed");
+        assert_eq!(report.findings[0].severity, Severity::Risky);
+        assert_eq!(
+            report.findings[0].current_pointer.as_deref(),
+            Some("#/properties/ts/$ref")
+        );
+    }
 }
@@ -4,8 +4,8 @@
 //! semantic versioning compliance.
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use contract_linter::lint_schema_change;
+use clap::{Parser, Subcommand, ValueEnum};
+use contract_linter::{lint_schema_change_detailed, LintReport, Severity};
 use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
@@ -42,9 +42,19 @@ enum Commands {
         /// Fail with non-zero exit code if breaking changes detected
         #[arg(long, default_value_t = true)]
         strict: bool,
+
+        /// Output format; `json` prints the full report with every finding
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
     },
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+}
+
 fn main() {
     if let Err(e) = run() {
         eprintln!("Error: {:#}", e);
@@ -62,6 +72,7 @@ fn run() -> Result<()> {
             current_version,
             proposed_version,
             strict,
+            format,
         } => {
             let result = compare_schemas(
                 &current,
@@ -70,6 +81,11 @@ fn run() -> Result<()> {
                 proposed_version.as_deref(),
             )?;
 
+            if format == ReportFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&result)?);
+                process::exit(if strict && !result.is_ok() { 1 } else { 0 });
+            }
+
             // Print results
             println!("Contract Schema Linter Results");
             println!("===============================");
@@ -83,15 +99,17 @@ fn run() -> Result<()> {
             }
             println!();
 
-            if result.breaking_changes.is_empty() {
+            print_other_findings(&result);
+
+            if !result.has_breaking_changes() {
                 println!("✓ No breaking changes detected");
                 println!();
                 process::exit(0);
             } else {
                 println!("⚠ Breaking changes detected:");
                 println!();
-                for (i, change) in result.breaking_changes.iter().enumerate() {
-                    println!("  {}. {}", i + 1, change);
+                for (i, finding) in result.findings_with(Severity::Breaking).enumerate() {
+                    println!("  {}. {}", i + 1, finding.message);
                 }
                 println!();
 
@@ -122,12 +140,30 @@ fn run() -> Result<()> {
     }
 }
 
+/// Print risky and informational findings ahead of the breaking-change verdict
+fn print_other_findings(result: &LintReport) {
+    for (severity, heading) in [
+        (Severity::Risky, "Risky changes:"),
+        (Severity::Informational, "Informational:"),
+    ] {
+        let findings: Vec<_> = result.findings_with(severity).collect();
+        if findings.is_empty() {
+            continue;
+        }
+        println!("{}", heading);
+        for finding in findings {
+            println!("  - [{}] {}", finding.rule, finding.message);
+        }
+        println!();
+    }
+}
+
 fn compare_schemas(
     current_path: &PathBuf,
     proposed_path: &PathBuf,
     current_version: Option<&str>,
     proposed_version: Option<&str>,
-) -> Result<contract_linter::LintResult> {
+) -> Result<LintReport> {
     // Read schema files
     let current_contents = fs::read_to_string(current_path)
         .with_context(|| format!("Failed to read current schema: {:?}", current_path))?;
@@ -143,7 +179,7 @@ fn compare_schemas(
         .with_context(|| format!("Failed to parse proposed schema JSON: {:?}", proposed_path))?;
 
     // Run linter
-    lint_schema_change(
+    lint_schema_change_detailed(
         &current_schema,
         &proposed_schema,
         current_version,
@@ -176,7 +212,7 @@ mod tests {
         .unwrap();
 
         assert!(result.is_ok());
-        assert!(result.breaking_changes.is_empty());
+        assert!(result.findings.is_empty());
     }
 
     #[test]
@@ -0,0 +1,211 @@
+//! `$ref` resolution ahead of comparison
+//!
+//! Schemas are compared with every resolvable `$ref` inlined, so moving a
+//! property behind `$defs` indirection neither hides nor fakes a change.
+//! Supported references:
+//!
+//! - local JSON pointers (`#/$defs/Status`)
+//! - paths relative to the referencing file (`common.v1.json#/$defs/Ts`)
+//! - absolute URIs matching another document's `$id`
+//!
+//! Recursive references are expanded once; the inner `$ref` is left in place.
+//! Anything that does not resolve is also left as is.
+
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Inline `$ref`s in `schema`, which lives at `path` (relative to the contract
+/// directory) alongside `documents`, keyed the same way
+pub fn resolve_refs(schema: &Value, path: &str, documents: &BTreeMap<String, Value>) -> Value {
+    let resolver = Resolver { documents };
+    resolver.resolve(schema, path, schema, &mut Vec::new())
+}
+
+struct Resolver<'a> {
+    documents: &'a BTreeMap<String, Value>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(
+        &self,
+        value: &Value,
+        doc_path: &str,
+        doc: &'a Value,
+        stack: &mut Vec<String>,
+    ) -> Value {
+        match value {
+            Value::Object(map) => {
+                if let Some(resolved) = self.resolve_ref(map, doc_path, doc, stack) {
+                    return resolved;
+                }
+                Value::Object(
+                    map.iter()
+                        .map(|(key, child)| {
+                            (key.clone(), self.resolve(child, doc_path, doc, stack))
+                        })
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.resolve(item, doc_path, doc, stack))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    /// The `$ref` target with sibling keywords applied on top, or `None` to
+    /// keep the object as written
+    fn resolve_ref(
+        &self,
+        map: &Map<String, Value>,
+        doc_path: &str,
+        doc: &'a Value,
+        stack: &mut Vec<String>,
+    ) -> Option<Value> {
+        let reference = map.get("$ref")?.as_str()?;
+        let (target_path, target_doc, fragment) = self.locate(reference, doc_path, doc)?;
+        let target = if fragment.is_empty() {
+            target_doc
+        } else {
+            target_doc.pointer(fragment)?
+        };
+
+        let key = format!("{}#{}", target_path, fragment);
+        if stack.contains(&key) {
+            return None;
+        }
+        stack.push(key);
+        let resolved = self.resolve(target, &target_path, target_doc, stack);
+        stack.pop();
+
+        match resolved {
+            Value::Object(mut resolved_map) => {
+                for (name, sibling) in map.iter().filter(|(name, _)| *name != "$ref") {
+                    resolved_map.insert(name.clone(), self.resolve(sibling, doc_path, doc, stack));
+                }
+                Some(Value::Object(resolved_map))
+            }
+            // Boolean schemas have nowhere to put siblings
+            other if map.len() == 1 => Some(other),
+            _ => None,
+        }
+    }
+
+    /// Document path, document and JSON pointer fragment `reference` points at
+    fn locate<'r>(
+        &self,
+        reference: &'r str,
+        doc_path: &str,
+        doc: &'a Value,
+    ) -> Option<(String, &'a Value, &'r str)> {
+        let (location, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        if !fragment.is_empty() && !fragment.starts_with('/') {
+            // Named anchors are not supported
+            return None;
+        }
+        if location.is_empty() {
+            return Some((doc_path.to_string(), doc, fragment));
+        }
+
+        if location.contains("://") {
+            let (path, target) = self.documents.iter().find(|(_, candidate)| {
+                candidate.get("$id").and_then(|id| id.as_str()) == Some(location)
+            })?;
+            return Some((path.clone(), target, fragment));
+        }
+
+        let path = join_relative(doc_path, location)?;
+        let target = self.documents.get(&path)?;
+        Some((path, target, fragment))
+    }
+}
+
+/// Resolve `relative` against the directory of `base`, both `/`-separated
+fn join_relative(base: &str, relative: &str) -> Option<String> {
+    let mut parts: Vec<&str> = base.split('/').collect();
+    parts.pop();
+    for segment in relative.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            other => parts.push(other),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolves_local_defs_with_siblings() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"status": {"$ref": "#/$defs/Status", "description": "Current state"}},
+            "$defs": {"Status": {"type": "string", "enum": ["open", "closed"]}}
+        });
+
+        let resolved = resolve_refs(&schema, "a.v1.json", &BTreeMap::new());
+
+        assert_eq!(
+            resolved["properties"]["status"],
+            json!({"type": "string", "enum": ["open", "closed"], "description": "Current state"})
+        );
+    }
+
+    #[test]
+    fn test_resolves_relative_and_id_refs_across_documents() {
+        let mut documents = BTreeMap::new();
+        documents.insert(
+            "common/ts.v1.json".to_string(),
+            json!({
+                "$id": "https://demon.meta/contracts/common/ts.v1.json",
+                "$defs": {"Ts": {"type": "string", "format": "date-time"}}
+            }),
+        );
+        let schema = json!({
+            "properties": {
+                "ts": {"$ref": "../common/ts.v1.json#/$defs/Ts"},
+                "at": {"$ref": "https://demon.meta/contracts/common/ts.v1.json#/$defs/Ts"}
+            }
+        });
+
+        let resolved = resolve_refs(&schema, "events/step.v1.json", &documents);
+
+        let expected = json!({"type": "string", "format": "date-time"});
+        assert_eq!(resolved["properties"]["ts"], expected);
+        assert_eq!(resolved["properties"]["at"], expected);
+    }
+
+    #[test]
+    fn test_recursive_ref_is_expanded_once() {
+        let schema = json!({
+            "$defs": {"Node": {"type": "object", "properties": {"child": {"$ref": "#/$defs/Node"}}}},
+            "$ref": "#/$defs/Node"
+        });
+
+        let resolved = resolve_refs(&schema, "tree.json", &BTreeMap::new());
+
+        assert_eq!(resolved["type"], "object");
+        assert_eq!(
+            resolved["properties"]["child"],
+            json!({"$ref": "#/$defs/Node"})
+        );
+    }
+
+    #[test]
+    fn test_unresolvable_ref_is_left_in_place() {
+        let schema = json!({"properties": {"a": {"$ref": "missing.json#/$defs/A"}}});
+
+        let resolved = resolve_refs(&schema, "a.json", &BTreeMap::new());
+
+        assert_eq!(resolved, schema);
+    }
+}
@@ -1,3 +1,5 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Resolution state for a single approval gate
@@ -8,37 +10,233 @@ pub enum GateState {
     Denied,
 }
 
+/// What happens when an escalation rule fires for a still-pending gate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum EscalationAction {
+    /// Notify additional approver groups; the gate stays pending
+    Notify { groups: Vec<String> },
+    /// Resolve the gate as denied
+    AutoDeny,
+    /// Resolve the gate as granted
+    AutoGrant,
+}
+
+impl EscalationAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EscalationAction::Notify { .. } => "notify",
+            EscalationAction::AutoDeny => "auto-deny",
+            EscalationAction::AutoGrant => "auto-grant",
+        }
+    }
+
+    /// Terminal actions resolve the gate and end the escalation sequence
+    pub fn resolution(&self) -> Option<GateState> {
+        match self {
+            EscalationAction::Notify { .. } => None,
+            EscalationAction::AutoDeny => Some(GateState::Denied),
+            EscalationAction::AutoGrant => Some(GateState::Granted),
+        }
+    }
+}
+
+/// Escalation step that fires once a gate has been pending for `afterSeconds`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscalationRule {
+    pub after_seconds: u64,
+    #[serde(flatten)]
+    pub action: EscalationAction,
+}
+
+/// Ordered escalation rules for a gate, e.g. notify leads after 4h, auto-deny after 24h
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscalationPolicy {
+    pub rules: Vec<EscalationRule>,
+}
+
+impl EscalationPolicy {
+    /// Rules must be strictly increasing in time, and only the last may resolve the gate
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rules.is_empty() {
+            return Err("escalation policy must have at least one rule".to_string());
+        }
+        for (idx, rule) in self.rules.iter().enumerate() {
+            if idx > 0 && rule.after_seconds <= self.rules[idx - 1].after_seconds {
+                return Err(format!(
+                    "rule #{} afterSeconds must be greater than the previous rule",
+                    idx + 1
+                ));
+            }
+            if rule.action.resolution().is_some() && idx + 1 != self.rules.len() {
+                return Err(format!(
+                    "rule #{} ({}) resolves the gate and must be the last rule",
+                    idx + 1,
+                    rule.action.as_str()
+                ));
+            }
+            if let EscalationAction::Notify { groups } = &rule.action {
+                if groups.is_empty() {
+                    return Err(format!("rule #{} must notify at least one group", idx + 1));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// When rule `index` fires for a gate requested at `requested_at`
+    pub fn fires_at(&self, index: usize, requested_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.rules
+            .get(index)
+            .map(|rule| requested_at + Duration::seconds(rule.after_seconds as i64))
+    }
+
+    /// Escalation record for rule `index`, or None if it is not yet due
+    pub fn escalation(
+        &self,
+        run_id: &str,
+        gate_id: &str,
+        index: usize,
+        requested_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<ApprovalEscalation> {
+        let rule = self.rules.get(index)?;
+        let fires_at = self.fires_at(index, requested_at)?;
+        if now < fires_at {
+            return None;
+        }
+        Some(ApprovalEscalation {
+            run_id: run_id.to_string(),
+            gate_id: gate_id.to_string(),
+            rule_index: index,
+            total_rules: self.rules.len(),
+            rule: rule.clone(),
+            fired_at: now,
+            next_at: if rule.action.resolution().is_some() {
+                None
+            } else {
+                self.fires_at(index + 1, requested_at)
+            },
+        })
+    }
+}
+
+/// A fired escalation rule, convertible to an `approval.escalated:v1` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalEscalation {
+    pub run_id: String,
+    pub gate_id: String,
+    /// 0-based index of the rule that fired
+    pub rule_index: usize,
+    pub total_rules: usize,
+    pub rule: EscalationRule,
+    pub fired_at: DateTime<Utc>,
+    /// When the next rule fires, if any
+    pub next_at: Option<DateTime<Utc>>,
+}
+
+impl ApprovalEscalation {
+    /// `approval.escalated:v1` payload; the initial request is level 1 and each
+    /// fired rule moves the gate up one level.
+    pub fn event_payload(&self, tenant_id: &str, ritual_id: &str) -> serde_json::Value {
+        let mut payload = serde_json::json!({
+            "event": "approval.escalated:v1",
+            "ts": self.fired_at.to_rfc3339(),
+            "tenantId": tenant_id,
+            "runId": self.run_id,
+            "ritualId": ritual_id,
+            "gateId": self.gate_id,
+            "fromLevel": self.rule_index + 1,
+            "toLevel": self.rule_index + 2,
+            "reason": "timeout",
+            "action": self.rule.action.as_str(),
+            "escalationState": {
+                "currentLevel": self.rule_index + 2,
+                "totalLevels": self.total_rules + 1,
+                "levelStartedAt": self.fired_at.to_rfc3339(),
+                "nextEscalationAt": self.next_at.map(|t| t.to_rfc3339()),
+                "emergencyOverride": false
+            }
+        });
+        if let EscalationAction::Notify { groups } = &self.rule.action {
+            payload["notify"] = serde_json::json!(groups);
+        }
+        payload
+    }
+}
+
 /// In-memory approvals store keyed by (runId, gateId)
 #[derive(Default)]
 pub struct Approvals {
     states: HashMap<(String, String), GateState>,
+    requested_at: HashMap<(String, String), DateTime<Utc>>,
+    escalations_fired: HashMap<(String, String), usize>,
 }
 
 impl Approvals {
     pub fn new() -> Self {
-        Self {
-            states: HashMap::new(),
-        }
+        Self::default()
     }
 
     /// Request an approval; returns true only on the first request per (runId, gateId)
-    pub fn request(
+    pub fn request(&mut self, run_id: &str, gate_id: &str, requester: &str, reason: &str) -> bool {
+        self.request_at(run_id, gate_id, requester, reason, Utc::now())
+    }
+
+    /// Same as `request`, recording an explicit request time for escalation
+    pub fn request_at(
         &mut self,
         run_id: &str,
         gate_id: &str,
         _requester: &str,
         _reason: &str,
+        at: DateTime<Utc>,
     ) -> bool {
         let key = (run_id.to_string(), gate_id.to_string());
         match self.states.get(&key) {
             None => {
-                self.states.insert(key, GateState::Requested);
+                self.states.insert(key.clone(), GateState::Requested);
+                self.requested_at.insert(key, at);
                 true
             }
             Some(_) => false, // duplicate requests are no-ops
         }
     }
 
+    /// Fire every escalation rule that has come due for a pending gate
+    ///
+    /// Each rule fires at most once. A terminal rule (auto-deny/auto-grant)
+    /// resolves the gate, so later grants or denials become no-ops.
+    pub fn escalate_due(
+        &mut self,
+        run_id: &str,
+        gate_id: &str,
+        policy: &EscalationPolicy,
+        now: DateTime<Utc>,
+    ) -> Vec<ApprovalEscalation> {
+        let key = (run_id.to_string(), gate_id.to_string());
+        let Some(requested_at) = self.requested_at.get(&key).copied() else {
+            return Vec::new();
+        };
+
+        let mut fired = Vec::new();
+        let mut next = self.escalations_fired.get(&key).copied().unwrap_or(0);
+        while self.states.get(&key) == Some(&GateState::Requested) {
+            let Some(escalation) = policy.escalation(run_id, gate_id, next, requested_at, now)
+            else {
+                break;
+            };
+            if let Some(resolution) = escalation.rule.action.resolution() {
+                self.states.insert(key.clone(), resolution);
+            }
+            fired.push(escalation);
+            next += 1;
+        }
+        self.escalations_fired.insert(key, next);
+        fired
+    }
+
     /// Grant an approval; first-writer-wins. Returns true only if transitioning from Requested.
     pub fn grant(
         &mut self,
@@ -0,0 +1,536 @@
+//! Policy audit trail
+//!
+//! Every policy decision that blocks or unblocks work — approval grants and
+//! denials, quota rejections, emergency overrides — is recorded as a
+//! `wards.decision:v1` event carrying the id of the rule that fired. Events are
+//! published to `demon.wards.v1.<tenant>.decisions` in the `WARDS_DECISIONS`
+//! stream and can be listed per tenant or run with [`query`] for post-incident
+//! review.
+//!
+//! Recording is best-effort at call sites: a failure to write the audit trail
+//! is logged but never changes the decision itself.
+
+use std::sync::Mutex;
+
+use async_nats::jetstream::{self, consumer::DeliverPolicy};
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tokio::sync::OnceCell;
+
+use crate::policy::Decision as KernelDecision;
+use crate::quota::QuotaDecision;
+
+/// Event name carried by every audit record
+pub const DECISION_EVENT: &str = "wards.decision:v1";
+
+/// Default stream holding audit records
+pub const DEFAULT_DECISIONS_STREAM: &str = "WARDS_DECISIONS";
+
+/// Upper bound on records returned by a single query
+pub const MAX_QUERY_LIMIT: usize = 1000;
+
+const FETCH_BATCH: usize = 256;
+
+/// What kind of policy decision was taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DecisionKind {
+    ApprovalGranted,
+    ApprovalDenied,
+    QuotaRejected,
+    OverrideUsed,
+}
+
+impl DecisionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecisionKind::ApprovalGranted => "approval-granted",
+            DecisionKind::ApprovalDenied => "approval-denied",
+            DecisionKind::QuotaRejected => "quota-rejected",
+            DecisionKind::OverrideUsed => "override-used",
+        }
+    }
+}
+
+impl std::str::FromStr for DecisionKind {
+    type Err = AuditError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            DecisionKind::ApprovalGranted,
+            DecisionKind::ApprovalDenied,
+            DecisionKind::QuotaRejected,
+            DecisionKind::OverrideUsed,
+        ]
+        .into_iter()
+        .find(|k| k.as_str() == s)
+        .ok_or_else(|| AuditError::UnknownKind(s.to_string()))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("unknown decision kind: {0}")]
+    UnknownKind(String),
+
+    #[error("audit store unavailable: {0}")]
+    Store(String),
+}
+
+/// A single `wards.decision:v1` audit record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WardsDecision {
+    pub event: String,
+    pub ts: DateTime<Utc>,
+    pub tenant_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ritual_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    pub kind: DecisionKind,
+    /// Id of the policy rule that fired, e.g. `approval:deploy` or `quota:runs`
+    pub rule_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub details: Value,
+}
+
+impl WardsDecision {
+    pub fn new(
+        tenant_id: impl Into<String>,
+        kind: DecisionKind,
+        rule_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            event: DECISION_EVENT.to_string(),
+            ts: Utc::now(),
+            tenant_id: tenant_id.into(),
+            ritual_id: None,
+            run_id: None,
+            kind,
+            rule_id: rule_id.into(),
+            actor: None,
+            reason: None,
+            details: Value::Null,
+        }
+    }
+
+    pub fn for_run(mut self, ritual_id: impl Into<String>, run_id: impl Into<String>) -> Self {
+        self.ritual_id = Some(ritual_id.into());
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    /// Replace the rule id, e.g. to name the escalation rule behind an approval event
+    pub fn with_rule(mut self, rule_id: impl Into<String>) -> Self {
+        self.rule_id = rule_id.into();
+        self
+    }
+
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+
+    pub fn at(mut self, ts: DateTime<Utc>) -> Self {
+        self.ts = ts;
+        self
+    }
+
+    /// Audit record for an `approval.granted|denied|override:v1` event
+    ///
+    /// The rule id is `approval:<gateId>` (`approval:<gateId>:override` for
+    /// emergency overrides). Returns `None` for any other event.
+    pub fn from_approval_event(event: &Value) -> Option<Self> {
+        let str_field = |name: &str| event.get(name).and_then(|v| v.as_str());
+
+        let gate_id = str_field("gateId")?;
+        let (kind, rule_id) = match str_field("event")? {
+            "approval.granted:v1" => (DecisionKind::ApprovalGranted, format!("approval:{gate_id}")),
+            "approval.denied:v1" => (DecisionKind::ApprovalDenied, format!("approval:{gate_id}")),
+            "approval.override:v1" => (
+                DecisionKind::OverrideUsed,
+                format!("approval:{gate_id}:override"),
+            ),
+            _ => return None,
+        };
+
+        let mut decision = Self::new(str_field("tenantId").unwrap_or("default"), kind, rule_id)
+            .with_details(serde_json::json!({ "gateId": gate_id }));
+        if let (Some(ritual_id), Some(run_id)) = (str_field("ritualId"), str_field("runId")) {
+            decision = decision.for_run(ritual_id, run_id);
+        }
+        if let Some(approver) = str_field("approver") {
+            decision = decision.with_actor(approver);
+        }
+        if let Some(reason) = str_field("reason").or_else(|| str_field("note")) {
+            decision = decision.with_reason(reason);
+        }
+        if let Some(ts) = str_field("ts").and_then(|ts| ts.parse::<DateTime<Utc>>().ok()) {
+            decision = decision.at(ts);
+        }
+        Some(decision)
+    }
+
+    /// Audit record for a denied per-tenant quota check (rule `quota:<resource>`)
+    pub fn quota_rejected(decision: &QuotaDecision) -> Self {
+        let mut record = Self::new(
+            decision.tenant.clone(),
+            DecisionKind::QuotaRejected,
+            format!("quota:{}", decision.resource),
+        )
+        .with_details(serde_json::to_value(decision).unwrap_or(Value::Null));
+        if let Some(reason) = decision.deny_reason() {
+            record = record.with_reason(reason);
+        }
+        record
+    }
+
+    /// Audit record for a denied capability quota check (rule `quota:<capability>`)
+    pub fn capability_quota_rejected(
+        tenant_id: &str,
+        capability: &str,
+        decision: &KernelDecision,
+    ) -> Self {
+        Self::new(
+            tenant_id,
+            DecisionKind::QuotaRejected,
+            format!("quota:{capability}"),
+        )
+        .with_reason(
+            decision
+                .deny_reason
+                .clone()
+                .unwrap_or_else(|| "limit_exceeded".to_string()),
+        )
+        .with_details(serde_json::json!({
+            "capability": capability,
+            "limit": decision.limit,
+            "windowSeconds": decision.window_seconds,
+            "remaining": decision.remaining,
+        }))
+    }
+
+    fn msg_id(&self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            self.run_id.as_deref().unwrap_or(&self.tenant_id),
+            self.rule_id,
+            self.kind.as_str(),
+            self.ts.timestamp_nanos_opt().unwrap_or(0)
+        )
+    }
+}
+
+/// Filters for listing audit records; every set field must match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecisionQuery {
+    pub tenant_id: Option<String>,
+    pub run_id: Option<String>,
+    pub kind: Option<DecisionKind>,
+    pub since: Option<DateTime<Utc>>,
+    /// Maximum records returned, newest first (capped at [`MAX_QUERY_LIMIT`])
+    pub limit: Option<usize>,
+}
+
+impl DecisionQuery {
+    pub fn for_tenant(tenant_id: impl Into<String>) -> Self {
+        Self {
+            tenant_id: Some(tenant_id.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn for_run(run_id: impl Into<String>) -> Self {
+        Self {
+            run_id: Some(run_id.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn matches(&self, decision: &WardsDecision) -> bool {
+        self.tenant_id
+            .as_deref()
+            .is_none_or(|t| decision.tenant_id == t)
+            && self
+                .run_id
+                .as_deref()
+                .is_none_or(|r| decision.run_id.as_deref() == Some(r))
+            && self.kind.is_none_or(|k| decision.kind == k)
+            && self.since.is_none_or(|since| decision.ts >= since)
+    }
+
+    fn effective_limit(&self) -> usize {
+        self.limit.unwrap_or(100).clamp(1, MAX_QUERY_LIMIT)
+    }
+}
+
+/// Apply a query to already-loaded records: filter, newest first, limit
+pub fn filter_decisions(
+    decisions: impl IntoIterator<Item = WardsDecision>,
+    query: &DecisionQuery,
+) -> Vec<WardsDecision> {
+    let mut matched: Vec<WardsDecision> =
+        decisions.into_iter().filter(|d| query.matches(d)).collect();
+    matched.sort_by_key(|d| std::cmp::Reverse(d.ts));
+    matched.truncate(query.effective_limit());
+    matched
+}
+
+/// Subject an audit record for `tenant_id` is published on
+pub fn decision_subject(tenant_id: &str) -> String {
+    let tenant: String = tenant_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("demon.wards.v1.{}.decisions", tenant)
+}
+
+/// Stream name from `WARDS_DECISIONS_STREAM`, defaulting to `WARDS_DECISIONS`
+pub fn decisions_stream() -> String {
+    std::env::var("WARDS_DECISIONS_STREAM").unwrap_or_else(|_| DEFAULT_DECISIONS_STREAM.to_string())
+}
+
+/// Publish an audit record to `stream`, creating the stream if needed
+pub async fn record(
+    js: &jetstream::Context,
+    stream: &str,
+    decision: &WardsDecision,
+) -> Result<(), AuditError> {
+    ensure_stream(js, stream).await?;
+
+    let payload = serde_json::to_vec(decision).map_err(|e| AuditError::Store(e.to_string()))?;
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert("Nats-Msg-Id", decision.msg_id().as_str());
+    js.publish_with_headers(
+        decision_subject(&decision.tenant_id),
+        headers,
+        payload.into(),
+    )
+    .await
+    .map_err(|e| AuditError::Store(e.to_string()))?
+    .await
+    .map_err(|e| AuditError::Store(e.to_string()))?;
+    Ok(())
+}
+
+/// List audit records in `stream` matching `query`, newest first
+///
+/// Reads the tenant's subject when `tenant_id` is set, otherwise every
+/// tenant. A missing stream means nothing has been recorded yet.
+pub async fn query(
+    js: &jetstream::Context,
+    stream: &str,
+    query: &DecisionQuery,
+) -> Result<Vec<WardsDecision>, AuditError> {
+    let stream = match js.get_stream(stream).await {
+        Ok(stream) => stream,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let filter_subject = query
+        .tenant_id
+        .as_deref()
+        .map(decision_subject)
+        .unwrap_or_else(|| "demon.wards.v1.*.decisions".to_string());
+    let consumer = stream
+        .create_consumer(jetstream::consumer::pull::Config {
+            filter_subject,
+            durable_name: None,
+            deliver_policy: DeliverPolicy::All,
+            ack_policy: jetstream::consumer::AckPolicy::None,
+            inactive_threshold: std::time::Duration::from_secs(60),
+            ..Default::default()
+        })
+        .await
+        .map_err(|e| AuditError::Store(e.to_string()))?;
+
+    let mut pending = consumer.cached_info().num_pending as usize;
+    let mut decisions = Vec::new();
+    while pending > 0 {
+        let mut messages = consumer
+            .batch()
+            .max_messages(pending.min(FETCH_BATCH))
+            .expires(std::time::Duration::from_secs(2))
+            .messages()
+            .await
+            .map_err(|e| AuditError::Store(e.to_string()))?;
+
+        let mut received = 0;
+        while let Some(message) = messages.next().await {
+            let message = message.map_err(|e| AuditError::Store(e.to_string()))?;
+            received += 1;
+            match serde_json::from_slice::<WardsDecision>(&message.payload) {
+                Ok(decision) if query.matches(&decision) => decisions.push(decision),
+                Ok(_) => {}
+                Err(e) => tracing::debug!("skipping malformed audit record: {}", e),
+            }
+        }
+        if received == 0 {
+            break;
+        }
+        pending = pending.saturating_sub(received);
+    }
+
+    Ok(filter_decisions(decisions, query))
+}
+
+async fn ensure_stream(js: &jetstream::Context, stream: &str) -> Result<(), AuditError> {
+    if js.get_stream(stream).await.is_ok() {
+        return Ok(());
+    }
+    js.get_or_create_stream(jetstream::stream::Config {
+        name: stream.to_string(),
+        subjects: vec!["demon.wards.v1.*.decisions".to_string()],
+        ..Default::default()
+    })
+    .await
+    .map_err(|e| AuditError::Store(e.to_string()))?;
+    Ok(())
+}
+
+enum Sink {
+    /// Records in a JetStream stream, connected on first use
+    JetStream {
+        nats_url: String,
+        stream: String,
+        js: OnceCell<jetstream::Context>,
+    },
+    /// Process-local records for tests and single-node development
+    Memory(Mutex<Vec<WardsDecision>>),
+}
+
+/// Handle for recording and querying the audit trail
+pub struct DecisionLog {
+    sink: Sink,
+}
+
+impl DecisionLog {
+    /// Audit log on `NATS_URL`, stream `WARDS_DECISIONS_STREAM`
+    ///
+    /// Returns `None` when `WARDS_AUDIT` is `0`/`false`/`off`.
+    pub fn from_env() -> Option<Self> {
+        let disabled = std::env::var("WARDS_AUDIT")
+            .map(|v| matches!(v.to_ascii_lowercase().as_str(), "0" | "false" | "off"))
+            .unwrap_or(false);
+        if disabled {
+            return None;
+        }
+        let nats_url =
+            std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+        Some(Self::with_jetstream(nats_url, decisions_stream()))
+    }
+
+    pub fn with_jetstream(nats_url: impl Into<String>, stream: impl Into<String>) -> Self {
+        Self {
+            sink: Sink::JetStream {
+                nats_url: nats_url.into(),
+                stream: stream.into(),
+                js: OnceCell::new(),
+            },
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            sink: Sink::Memory(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub async fn record(&self, decision: &WardsDecision) -> Result<(), AuditError> {
+        match &self.sink {
+            Sink::Memory(records) => {
+                records
+                    .lock()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .push(decision.clone());
+                Ok(())
+            }
+            Sink::JetStream { stream, .. } => record(self.context().await?, stream, decision).await,
+        }
+    }
+
+    /// Record a decision, logging instead of failing when the store is unavailable
+    pub async fn record_best_effort(&self, decision: &WardsDecision) {
+        if let Err(e) = self.record(decision).await {
+            tracing::warn!(
+                rule_id = %decision.rule_id,
+                kind = decision.kind.as_str(),
+                "failed to record policy decision: {}",
+                e
+            );
+        }
+    }
+
+    pub async fn query(&self, q: &DecisionQuery) -> Result<Vec<WardsDecision>, AuditError> {
+        match &self.sink {
+            Sink::Memory(records) => Ok(filter_decisions(
+                records.lock().unwrap_or_else(|p| p.into_inner()).clone(),
+                q,
+            )),
+            Sink::JetStream { stream, .. } => query(self.context().await?, stream, q).await,
+        }
+    }
+
+    async fn context(&self) -> Result<&jetstream::Context, AuditError> {
+        let Sink::JetStream { nats_url, js, .. } = &self.sink else {
+            return Err(AuditError::Store("not a JetStream-backed log".to_string()));
+        };
+        js.get_or_try_init(|| async {
+            let client = async_nats::connect(nats_url.as_str())
+                .await
+                .map_err(|e| AuditError::Store(e.to_string()))?;
+            Ok(jetstream::new(client))
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subjects_sanitize_tenant() {
+        assert_eq!(
+            decision_subject("acme.eu"),
+            "demon.wards.v1.acme_eu.decisions"
+        );
+    }
+
+    #[test]
+    fn kinds_round_trip_through_strings() {
+        for kind in [
+            DecisionKind::ApprovalGranted,
+            DecisionKind::ApprovalDenied,
+            DecisionKind::QuotaRejected,
+            DecisionKind::OverrideUsed,
+        ] {
+            assert_eq!(kind.as_str().parse::<DecisionKind>().unwrap(), kind);
+        }
+        assert!("bogus".parse::<DecisionKind>().is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Approval delegation (vacation rules)
+//!
+//! An approver who will be out hands their gate authority to another
+//! principal for a time window. While the window is open the delegate may
+//! grant or deny gates the delegator could, and the decision records both
+//! names ("approved by X on behalf of Y"). Authority is not transitive: a
+//! delegate cannot pass on authority they only hold through a delegation.
+//!
+//! Delegations live in the `APPROVAL_DELEGATIONS` KV bucket, one key per
+//! delegator and delegate pair, so writing the same pair again replaces the
+//! window.
+
+use async_nats::jetstream::kv;
+use chrono::{DateTime, Utc};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+/// KV bucket holding delegations
+pub const DELEGATIONS_BUCKET: &str = "APPROVAL_DELEGATIONS";
+
+/// Longest window a single delegation may cover
+pub const MAX_DELEGATION_DAYS: i64 = 90;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delegation {
+    /// Approver whose authority is delegated
+    pub delegator: String,
+    /// Principal acting for the delegator
+    pub delegate: String,
+    pub starts_at: DateTime<Utc>,
+    /// Exclusive end of the window
+    pub ends_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DelegationError {
+    #[error("{0} must not be empty")]
+    MissingPrincipal(&'static str),
+    #[error("an approver cannot delegate to themselves")]
+    SelfDelegation,
+    #[error("endsAt must be after startsAt")]
+    EmptyWindow,
+    #[error("a delegation may not span more than {} days", MAX_DELEGATION_DAYS)]
+    WindowTooLong,
+    #[error("delegation store error: {0}")]
+    Store(String),
+}
+
+impl Delegation {
+    /// Check the principals and the window
+    pub fn validate(&self) -> Result<(), DelegationError> {
+        if self.delegator.trim().is_empty() {
+            return Err(DelegationError::MissingPrincipal("delegator"));
+        }
+        if self.delegate.trim().is_empty() {
+            return Err(DelegationError::MissingPrincipal("delegate"));
+        }
+        if same_principal(&self.delegator, &self.delegate) {
+            return Err(DelegationError::SelfDelegation);
+        }
+        if self.ends_at <= self.starts_at {
+            return Err(DelegationError::EmptyWindow);
+        }
+        if self.ends_at - self.starts_at > chrono::Duration::days(MAX_DELEGATION_DAYS) {
+            return Err(DelegationError::WindowTooLong);
+        }
+        Ok(())
+    }
+
+    /// Whether the window covers `now`
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    /// KV key for this delegation
+    pub fn key(&self) -> String {
+        delegation_key(&self.delegator, &self.delegate)
+    }
+}
+
+/// Principals compare case-insensitively, like the approver allowlist
+pub fn same_principal(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// KV key for a delegator and delegate pair. KV keys only allow
+/// `[A-Za-z0-9_-]` between dots, so other bytes (`@`, `.`, ...) are written
+/// as `=XX`.
+pub fn delegation_key(delegator: &str, delegate: &str) -> String {
+    format!(
+        "{}.{}",
+        encode_principal(delegator),
+        encode_principal(delegate)
+    )
+}
+
+fn encode_principal(principal: &str) -> String {
+    let mut out = String::new();
+    for byte in principal.trim().to_ascii_lowercase().bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-' {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("={:02X}", byte));
+        }
+    }
+    out
+}
+
+/// The active delegation that lets `approver` act at `now`, if any.
+///
+/// Only delegations whose delegator passes `delegator_allowed` count, so a
+/// delegation from someone who has since lost approval rights lapses with
+/// them. When several apply, the one ending soonest is used.
+pub fn on_behalf_of<'a>(
+    delegations: impl IntoIterator<Item = &'a Delegation>,
+    approver: &str,
+    now: DateTime<Utc>,
+    delegator_allowed: impl Fn(&str) -> bool,
+) -> Option<&'a Delegation> {
+    delegations
+        .into_iter()
+        .filter(|d| same_principal(&d.delegate, approver) && d.is_active_at(now))
+        .filter(|d| !same_principal(&d.delegator, approver) && delegator_allowed(&d.delegator))
+        .min_by(|a, b| {
+            a.ends_at
+                .cmp(&b.ends_at)
+                .then_with(|| a.delegator.cmp(&b.delegator))
+        })
+}
+
+/// Delegations in a JetStream KV bucket
+pub struct DelegationStore {
+    store: kv::Store,
+}
+
+impl DelegationStore {
+    /// Wrap an opened `APPROVAL_DELEGATIONS` bucket
+    pub fn new(store: kv::Store) -> Self {
+        Self { store }
+    }
+
+    /// Validate and save `delegation`, replacing any window for the same pair
+    pub async fn put(&self, delegation: &Delegation) -> Result<(), DelegationError> {
+        delegation.validate()?;
+        let bytes =
+            serde_json::to_vec(delegation).map_err(|e| DelegationError::Store(e.to_string()))?;
+        self.store
+            .put(delegation.key(), bytes.into())
+            .await
+            .map_err(|e| DelegationError::Store(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Remove the delegation for a pair; returns whether one existed
+    pub async fn remove(&self, delegator: &str, delegate: &str) -> Result<bool, DelegationError> {
+        let key = delegation_key(delegator, delegate);
+        let existing = self
+            .store
+            .get(&key)
+            .await
+            .map_err(|e| DelegationError::Store(e.to_string()))?;
+        if existing.is_none() {
+            return Ok(false);
+        }
+        self.store
+            .delete(&key)
+            .await
+            .map_err(|e| DelegationError::Store(e.to_string()))?;
+        Ok(true)
+    }
+
+    /// Every stored delegation, including expired and future ones, ordered
+    /// by delegator then start
+    pub async fn list(&self) -> Result<Vec<Delegation>, DelegationError> {
+        let mut keys = self
+            .store
+            .keys()
+            .await
+            .map_err(|e| DelegationError::Store(e.to_string()))?;
+        let mut delegations = Vec::new();
+        while let Some(key) = keys
+            .try_next()
+            .await
+            .map_err(|e| DelegationError::Store(e.to_string()))?
+        {
+            let Some(bytes) = self
+                .store
+                .get(&key)
+                .await
+                .map_err(|e| DelegationError::Store(e.to_string()))?
+            else {
+                continue;
+            };
+            match serde_json::from_slice::<Delegation>(&bytes) {
+                Ok(delegation) => delegations.push(delegation),
+                Err(e) => warn!("Ignoring unreadable delegation {}: {}", key, e),
+            }
+        }
+        delegations.sort_by(|a, b| {
+            a.delegator
+                .cmp(&b.delegator)
+                .then_with(|| a.starts_at.cmp(&b.starts_at))
+        });
+        Ok(delegations)
+    }
+
+    /// Like [`on_behalf_of`], reading the delegations from the bucket
+    pub async fn on_behalf_of(
+        &self,
+        approver: &str,
+        now: DateTime<Utc>,
+        delegator_allowed: impl Fn(&str) -> bool,
+    ) -> Result<Option<Delegation>, DelegationError> {
+        let delegations = self.list().await?;
+        Ok(on_behalf_of(&delegations, approver, now, delegator_allowed).cloned())
+    }
+}
@@ -1,8 +1,11 @@
 //! Policy/tenancy engine (stub for Milestone 0)
 
 pub mod approvals;
+pub mod audit;
 pub mod config;
+pub mod delegation;
 pub mod policy;
+pub mod quota;
 pub mod schedule;
 
 #[derive(Default)]
@@ -0,0 +1,551 @@
+//! Per-tenant quotas backed by JetStream KV
+//!
+//! Counters are kept per `(tenant, resource, window)` in a KV bucket so every
+//! engine and registry replica shares the same view. Windows are fixed and
+//! aligned to the Unix epoch: a `windowSeconds: 60` quota resets on the minute.
+//! Increments use optimistic concurrency (KV revisions) so concurrent
+//! consumers never overshoot a limit.
+//!
+//! Limits are read from `WARDS_TENANT_QUOTAS`:
+//!
+//! ```json
+//! {
+//!   "default": { "runs": { "limit": 100, "windowSeconds": 3600 } },
+//!   "tenants": { "tenant-a": { "graph-commits": { "limit": 10, "windowSeconds": 60 } } }
+//! }
+//! ```
+//!
+//! Resources without a configured limit are unlimited.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_nats::jetstream::{self, kv};
+use thiserror::Error;
+use tokio::sync::OnceCell;
+
+use crate::config::QuotaCfg;
+
+/// Default KV bucket holding quota counters
+pub const DEFAULT_QUOTA_BUCKET: &str = "wards_quotas";
+
+/// Attempts at a compare-and-set increment before giving up under contention
+const MAX_CAS_ATTEMPTS: usize = 16;
+
+/// Kinds of work a tenant can be rate-limited on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuotaResource {
+    /// Ritual runs accepted by the engine
+    Runs,
+    /// Capsule invocations dispatched by the engine
+    CapsuleExecutions,
+    /// Graph commits (including graph creation)
+    GraphCommits,
+    /// Contract bundles published to the registry
+    ContractPublishes,
+}
+
+impl QuotaResource {
+    pub const ALL: [QuotaResource; 4] = [
+        QuotaResource::Runs,
+        QuotaResource::CapsuleExecutions,
+        QuotaResource::GraphCommits,
+        QuotaResource::ContractPublishes,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaResource::Runs => "runs",
+            QuotaResource::CapsuleExecutions => "capsule-executions",
+            QuotaResource::GraphCommits => "graph-commits",
+            QuotaResource::ContractPublishes => "contract-publishes",
+        }
+    }
+}
+
+impl fmt::Display for QuotaResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for QuotaResource {
+    type Err = QuotaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        QuotaResource::ALL
+            .into_iter()
+            .find(|r| r.as_str() == s)
+            .ok_or_else(|| QuotaError::UnknownResource(s.to_string()))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum QuotaError {
+    #[error("unknown quota resource: {0}")]
+    UnknownResource(String),
+
+    #[error("invalid WARDS_TENANT_QUOTAS: {0}")]
+    InvalidConfig(String),
+
+    #[error("quota store unavailable: {0}")]
+    Store(String),
+
+    #[error("quota counter {key} is still contended after {attempts} attempts")]
+    Contended { key: String, attempts: usize },
+}
+
+/// Configured limits: defaults for every tenant plus per-tenant overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TenantQuotaConfig {
+    #[serde(default)]
+    pub default: HashMap<QuotaResource, QuotaCfg>,
+    #[serde(default)]
+    pub tenants: HashMap<String, HashMap<QuotaResource, QuotaCfg>>,
+}
+
+impl TenantQuotaConfig {
+    /// Parse limits from `WARDS_TENANT_QUOTAS`; unset or blank means no limits
+    pub fn from_env() -> Result<Self, QuotaError> {
+        match std::env::var("WARDS_TENANT_QUOTAS") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                serde_json::from_str(&raw).map_err(|e| QuotaError::InvalidConfig(e.to_string()))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default.is_empty() && self.tenants.values().all(HashMap::is_empty)
+    }
+
+    /// Limit for a tenant's resource: tenant override first, then the default
+    pub fn limit_for(&self, tenant: &str, resource: QuotaResource) -> Option<&QuotaCfg> {
+        self.tenants
+            .get(tenant)
+            .and_then(|limits| limits.get(&resource))
+            .or_else(|| self.default.get(&resource))
+    }
+
+    /// Longest configured window, used to expire stale counters from the bucket
+    pub fn longest_window_seconds(&self) -> Option<u64> {
+        self.default
+            .values()
+            .chain(self.tenants.values().flat_map(HashMap::values))
+            .map(|q| q.window_seconds)
+            .max()
+    }
+}
+
+/// Outcome of a `check_and_consume` call
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaDecision {
+    pub tenant: String,
+    pub resource: QuotaResource,
+    pub allowed: bool,
+    pub requested: u32,
+    /// Units consumed in the current window (after this request if allowed)
+    pub used: u64,
+    /// `None` when the resource has no configured limit
+    pub limit: Option<u32>,
+    pub remaining: Option<u64>,
+    pub window_seconds: Option<u64>,
+    /// Seconds until the current window resets
+    pub reset_after_seconds: Option<u64>,
+}
+
+impl QuotaDecision {
+    fn unlimited(tenant: &str, resource: QuotaResource, requested: u32) -> Self {
+        Self {
+            tenant: tenant.to_string(),
+            resource,
+            allowed: true,
+            requested,
+            used: 0,
+            limit: None,
+            remaining: None,
+            window_seconds: None,
+            reset_after_seconds: None,
+        }
+    }
+
+    /// Stable deny reason for policy events
+    pub fn deny_reason(&self) -> Option<String> {
+        (!self.allowed).then(|| format!("quota_exceeded:{}", self.resource))
+    }
+}
+
+/// Start of the fixed window containing `now_secs`
+pub fn window_start(now_secs: u64, window_seconds: u64) -> u64 {
+    if window_seconds == 0 {
+        return now_secs;
+    }
+    now_secs - now_secs % window_seconds
+}
+
+/// KV key for a counter; characters KV keys do not allow are replaced with `_`
+pub fn counter_key(tenant: &str, resource: QuotaResource, window_start: u64) -> String {
+    let tenant: String = tenant
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}.{}.{}", tenant, resource, window_start)
+}
+
+/// Decide whether `requested` more units fit under `quota` given `current` usage
+pub fn evaluate(
+    tenant: &str,
+    resource: QuotaResource,
+    quota: &QuotaCfg,
+    current: u64,
+    requested: u32,
+    now_secs: u64,
+) -> QuotaDecision {
+    let limit = u64::from(quota.limit);
+    let wanted = current.saturating_add(u64::from(requested));
+    let allowed = wanted <= limit;
+    let used = if allowed { wanted } else { current };
+    let reset_after = (window_start(now_secs, quota.window_seconds) + quota.window_seconds)
+        .saturating_sub(now_secs);
+
+    QuotaDecision {
+        tenant: tenant.to_string(),
+        resource,
+        allowed,
+        requested,
+        used,
+        limit: Some(quota.limit),
+        remaining: Some(limit.saturating_sub(used)),
+        window_seconds: Some(quota.window_seconds),
+        reset_after_seconds: Some(reset_after),
+    }
+}
+
+enum Ledger {
+    /// Shared counters in a JetStream KV bucket, connected on first use
+    Kv {
+        nats_url: String,
+        bucket: String,
+        store: Box<OnceCell<kv::Store>>,
+    },
+    /// Process-local counters for tests and single-node development
+    Memory(Mutex<HashMap<String, u64>>),
+}
+
+/// Per-tenant quota enforcement shared by the engine and registry
+pub struct TenantQuotas {
+    config: TenantQuotaConfig,
+    ledger: Ledger,
+}
+
+impl TenantQuotas {
+    /// Quotas backed by the KV bucket `WARDS_QUOTA_BUCKET` on `NATS_URL`
+    ///
+    /// Returns `Ok(None)` when no limits are configured so callers can skip
+    /// quota checks entirely.
+    pub fn from_env() -> Result<Option<Self>, QuotaError> {
+        let config = TenantQuotaConfig::from_env()?;
+        if config.is_empty() {
+            return Ok(None);
+        }
+        let nats_url =
+            std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+        let bucket = std::env::var("WARDS_QUOTA_BUCKET")
+            .unwrap_or_else(|_| DEFAULT_QUOTA_BUCKET.to_string());
+        Ok(Some(Self::with_kv(config, nats_url, bucket)))
+    }
+
+    pub fn with_kv(
+        config: TenantQuotaConfig,
+        nats_url: impl Into<String>,
+        bucket: impl Into<String>,
+    ) -> Self {
+        Self {
+            config,
+            ledger: Ledger::Kv {
+                nats_url: nats_url.into(),
+                bucket: bucket.into(),
+                store: Box::default(),
+            },
+        }
+    }
+
+    pub fn in_memory(config: TenantQuotaConfig) -> Self {
+        Self {
+            config,
+            ledger: Ledger::Memory(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> &TenantQuotaConfig {
+        &self.config
+    }
+
+    /// Consume `n` units of `resource` for `tenant` if the limit allows it
+    ///
+    /// A denied request consumes nothing. Resources without a configured
+    /// limit are always allowed and not counted.
+    pub async fn check_and_consume(
+        &self,
+        tenant: &str,
+        resource: QuotaResource,
+        n: u32,
+    ) -> Result<QuotaDecision, QuotaError> {
+        let Some(quota) = self.config.limit_for(tenant, resource) else {
+            return Ok(QuotaDecision::unlimited(tenant, resource, n));
+        };
+        let now = now_secs();
+        let key = counter_key(tenant, resource, window_start(now, quota.window_seconds));
+
+        match &self.ledger {
+            Ledger::Memory(counters) => {
+                let mut counters = counters.lock().unwrap_or_else(|p| p.into_inner());
+                let current = counters.get(&key).copied().unwrap_or(0);
+                let decision = evaluate(tenant, resource, quota, current, n, now);
+                if decision.allowed {
+                    counters.insert(key, decision.used);
+                }
+                Ok(decision)
+            }
+            Ledger::Kv { .. } => {
+                let store = self.store().await?;
+                for _ in 0..MAX_CAS_ATTEMPTS {
+                    let entry = store
+                        .entry(&key)
+                        .await
+                        .map_err(|e| QuotaError::Store(e.to_string()))?;
+                    let (current, revision) = match &entry {
+                        Some(entry) if matches!(entry.operation, kv::Operation::Put) => {
+                            (parse_count(&entry.value), Some(entry.revision))
+                        }
+                        Some(entry) => (0, Some(entry.revision)),
+                        None => (0, None),
+                    };
+
+                    let decision = evaluate(tenant, resource, quota, current, n, now);
+                    if !decision.allowed {
+                        return Ok(decision);
+                    }
+
+                    let value = decision.used.to_string().into_bytes().into();
+                    // Revision 0 only succeeds while the key has never been written
+                    let written = store
+                        .update(&key, value, revision.unwrap_or(0))
+                        .await
+                        .is_ok();
+                    if written {
+                        return Ok(decision);
+                    }
+                    tracing::debug!(%key, "quota counter changed concurrently, retrying");
+                }
+                Err(QuotaError::Contended {
+                    key,
+                    attempts: MAX_CAS_ATTEMPTS,
+                })
+            }
+        }
+    }
+
+    /// Give back `n` units of `resource` consumed by `tenant` in the current
+    /// window, for work that was charged but then undone
+    ///
+    /// Usage never drops below zero. Units charged in an earlier window are
+    /// not returned.
+    pub async fn release(
+        &self,
+        tenant: &str,
+        resource: QuotaResource,
+        n: u32,
+    ) -> Result<(), QuotaError> {
+        let Some(quota) = self.config.limit_for(tenant, resource) else {
+            return Ok(());
+        };
+        let key = counter_key(
+            tenant,
+            resource,
+            window_start(now_secs(), quota.window_seconds),
+        );
+
+        match &self.ledger {
+            Ledger::Memory(counters) => {
+                let mut counters = counters.lock().unwrap_or_else(|p| p.into_inner());
+                if let Some(current) = counters.get_mut(&key) {
+                    *current = current.saturating_sub(u64::from(n));
+                }
+                Ok(())
+            }
+            Ledger::Kv { .. } => {
+                let store = self.store().await?;
+                for _ in 0..MAX_CAS_ATTEMPTS {
+                    let entry = store
+                        .entry(&key)
+                        .await
+                        .map_err(|e| QuotaError::Store(e.to_string()))?;
+                    let Some(entry) = entry.filter(|e| matches!(e.operation, kv::Operation::Put))
+                    else {
+                        return Ok(());
+                    };
+
+                    let remaining = parse_count(&entry.value).saturating_sub(u64::from(n));
+                    let value = remaining.to_string().into_bytes().into();
+                    if store.update(&key, value, entry.revision).await.is_ok() {
+                        return Ok(());
+                    }
+                    tracing::debug!(%key, "quota counter changed concurrently, retrying");
+                }
+                Err(QuotaError::Contended {
+                    key,
+                    attempts: MAX_CAS_ATTEMPTS,
+                })
+            }
+        }
+    }
+
+    /// Units of `resource` consumed by `tenant` in the current window
+    pub async fn usage(&self, tenant: &str, resource: QuotaResource) -> Result<u64, QuotaError> {
+        let Some(quota) = self.config.limit_for(tenant, resource) else {
+            return Ok(0);
+        };
+        let key = counter_key(
+            tenant,
+            resource,
+            window_start(now_secs(), quota.window_seconds),
+        );
+
+        match &self.ledger {
+            Ledger::Memory(counters) => Ok(counters
+                .lock()
+                .unwrap_or_else(|p| p.into_inner())
+                .get(&key)
+                .copied()
+                .unwrap_or(0)),
+            Ledger::Kv { .. } => {
+                let value = self
+                    .store()
+                    .await?
+                    .get(&key)
+                    .await
+                    .map_err(|e| QuotaError::Store(e.to_string()))?;
+                Ok(value.map(|v| parse_count(&v)).unwrap_or(0))
+            }
+        }
+    }
+
+    async fn store(&self) -> Result<&kv::Store, QuotaError> {
+        let Ledger::Kv {
+            nats_url,
+            bucket,
+            store,
+        } = &self.ledger
+        else {
+            return Err(QuotaError::Store("not a KV-backed ledger".to_string()));
+        };
+
+        store
+            .get_or_try_init(|| async {
+                let client = async_nats::connect(nats_url.as_str())
+                    .await
+                    .map_err(|e| QuotaError::Store(e.to_string()))?;
+                let js = jetstream::new(client);
+                if let Ok(existing) = js.get_key_value(bucket.as_str()).await {
+                    return Ok(existing);
+                }
+                // Old windows are never read again; let JetStream drop them
+                let max_age = self
+                    .config
+                    .longest_window_seconds()
+                    .map(|secs| std::time::Duration::from_secs(secs.saturating_mul(2)))
+                    .unwrap_or_default();
+                js.create_key_value(kv::Config {
+                    bucket: bucket.clone(),
+                    description: "Per-tenant quota counters".to_string(),
+                    history: 1,
+                    max_age,
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| QuotaError::Store(e.to_string()))
+            })
+            .await
+    }
+}
+
+fn parse_count(value: &[u8]) -> u64 {
+    std::str::from_utf8(value)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quota(limit: u32, window_seconds: u64) -> QuotaCfg {
+        QuotaCfg {
+            limit,
+            window_seconds,
+        }
+    }
+
+    #[test]
+    fn windows_align_to_epoch() {
+        assert_eq!(window_start(125, 60), 120);
+        assert_eq!(window_start(120, 60), 120);
+        assert_eq!(window_start(125, 0), 125);
+    }
+
+    #[test]
+    fn counter_keys_sanitize_tenant() {
+        assert_eq!(
+            counter_key("acme corp/eu", QuotaResource::Runs, 60),
+            "acme_corp_eu.runs.60"
+        );
+    }
+
+    #[test]
+    fn evaluate_denies_without_consuming() {
+        let q = quota(5, 60);
+
+        let allowed = evaluate("t", QuotaResource::Runs, &q, 3, 2, 130);
+        assert!(allowed.allowed);
+        assert_eq!(allowed.used, 5);
+        assert_eq!(allowed.remaining, Some(0));
+        assert_eq!(allowed.reset_after_seconds, Some(50));
+
+        let denied = evaluate("t", QuotaResource::Runs, &q, 3, 3, 130);
+        assert!(!denied.allowed);
+        assert_eq!(denied.used, 3);
+        assert_eq!(denied.remaining, Some(2));
+        assert_eq!(denied.deny_reason().as_deref(), Some("quota_exceeded:runs"));
+    }
+
+    #[test]
+    fn resources_round_trip_through_strings() {
+        for resource in QuotaResource::ALL {
+            assert_eq!(
+                resource.as_str().parse::<QuotaResource>().unwrap(),
+                resource
+            );
+        }
+        assert!("bogus".parse::<QuotaResource>().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::approvals::EscalationPolicy;
 use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
 use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
@@ -157,6 +158,72 @@ pub fn load_schedules_from_env() -> ScheduleConfig {
     config
 }
 
+/// Approval escalation policies keyed by gate id
+#[derive(Debug, Clone, Default)]
+pub struct EscalationSchedule {
+    pub tenant_policies: HashMap<String, HashMap<String, EscalationPolicy>>, // tenant -> gate -> policy
+    pub global_policies: HashMap<String, EscalationPolicy>,                  // gate -> policy
+}
+
+impl EscalationSchedule {
+    /// Policy for a gate; tenant-specific policies replace global ones
+    pub fn policy_for(&self, tenant: &str, gate_id: &str) -> Option<&EscalationPolicy> {
+        self.tenant_policies
+            .get(tenant)
+            .and_then(|gates| gates.get(gate_id))
+            .or_else(|| self.global_policies.get(gate_id))
+    }
+}
+
+/// Load approval escalation policies from `WARDS_APPROVAL_ESCALATIONS`
+///
+/// Uses the same `global`/tenant layout as `WARDS_SCHEDULES`; invalid
+/// policies are skipped with a warning.
+pub fn load_escalations_from_env() -> EscalationSchedule {
+    let mut schedule = EscalationSchedule::default();
+
+    let Ok(raw) = std::env::var("WARDS_APPROVAL_ESCALATIONS") else {
+        return schedule;
+    };
+    if raw.trim().is_empty() {
+        return schedule;
+    }
+
+    let parsed =
+        match serde_json::from_str::<HashMap<String, HashMap<String, EscalationPolicy>>>(&raw) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                tracing::warn!("Ignoring malformed WARDS_APPROVAL_ESCALATIONS: {}", e);
+                return schedule;
+            }
+        };
+
+    for (tenant_or_global, gates) in parsed {
+        let gates: HashMap<String, EscalationPolicy> = gates
+            .into_iter()
+            .filter(|(gate_id, policy)| match policy.validate() {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!(
+                        "Ignoring escalation policy for {}/{}: {}",
+                        tenant_or_global,
+                        gate_id,
+                        e
+                    );
+                    false
+                }
+            })
+            .collect();
+        if tenant_or_global == "global" {
+            schedule.global_policies = gates;
+        } else {
+            schedule.tenant_policies.insert(tenant_or_global, gates);
+        }
+    }
+
+    schedule
+}
+
 impl ScheduleConfig {
     /// Get applicable schedule rules for a tenant and capability
     /// Returns rules in precedence order: tenant-specific -> global
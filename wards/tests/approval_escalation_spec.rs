@@ -0,0 +1,158 @@
+use chrono::{Duration, TimeZone, Utc};
+use serial_test::serial;
+use wards::approvals::{Approvals, EscalationAction, EscalationPolicy, EscalationRule, GateState};
+
+fn policy(terminal: EscalationAction) -> EscalationPolicy {
+    EscalationPolicy {
+        rules: vec![
+            EscalationRule {
+                after_seconds: 4 * 3600,
+                action: EscalationAction::Notify {
+                    groups: vec!["release-managers".to_string()],
+                },
+            },
+            EscalationRule {
+                after_seconds: 24 * 3600,
+                action: terminal,
+            },
+        ],
+    }
+}
+
+#[test]
+fn given_pending_gate_when_notify_rule_due_then_escalates_without_resolving() {
+    let requested = Utc.with_ymd_and_hms(2025, 1, 6, 9, 0, 0).unwrap();
+    let policy = policy(EscalationAction::AutoDeny);
+    let mut approvals = Approvals::new();
+    approvals.request_at("run-1", "deploy", "ci", "release", requested);
+
+    assert!(approvals
+        .escalate_due("run-1", "deploy", &policy, requested + Duration::hours(3))
+        .is_empty());
+
+    let fired = approvals.escalate_due("run-1", "deploy", &policy, requested + Duration::hours(5));
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].rule_index, 0);
+    assert_eq!(fired[0].next_at, Some(requested + Duration::hours(24)));
+    assert_eq!(
+        approvals.state("run-1", "deploy"),
+        Some(GateState::Requested)
+    );
+
+    // Already-fired rules do not fire again
+    assert!(approvals
+        .escalate_due("run-1", "deploy", &policy, requested + Duration::hours(6))
+        .is_empty());
+}
+
+#[test]
+fn given_auto_grant_policy_when_final_rule_due_then_gate_is_granted() {
+    let requested = Utc.with_ymd_and_hms(2025, 1, 6, 9, 0, 0).unwrap();
+    let policy = policy(EscalationAction::AutoGrant);
+    let mut approvals = Approvals::new();
+    approvals.request_at("run-2", "deploy", "ci", "release", requested);
+
+    // Both rules come due at once: notify first, then the terminal action
+    let fired = approvals.escalate_due("run-2", "deploy", &policy, requested + Duration::days(2));
+
+    assert_eq!(fired.len(), 2);
+    assert_eq!(approvals.state("run-2", "deploy"), Some(GateState::Granted));
+    assert!(!approvals.deny("run-2", "deploy", "approver", "too late"));
+}
+
+#[test]
+fn given_resolved_gate_when_rules_due_then_nothing_fires() {
+    let requested = Utc.with_ymd_and_hms(2025, 1, 6, 9, 0, 0).unwrap();
+    let policy = policy(EscalationAction::AutoDeny);
+    let mut approvals = Approvals::new();
+    approvals.request_at("run-3", "deploy", "ci", "release", requested);
+    approvals.grant("run-3", "deploy", "approver", None);
+
+    assert!(approvals
+        .escalate_due("run-3", "deploy", &policy, requested + Duration::days(2))
+        .is_empty());
+}
+
+#[test]
+fn given_escalation_when_rendered_then_event_carries_action_and_groups() {
+    let requested = Utc.with_ymd_and_hms(2025, 1, 6, 9, 0, 0).unwrap();
+    let policy = policy(EscalationAction::AutoDeny);
+
+    let escalation = policy
+        .escalation(
+            "run-4",
+            "deploy",
+            0,
+            requested,
+            requested + Duration::hours(4),
+        )
+        .unwrap();
+    let event = escalation.event_payload("tenant-a", "release-ritual");
+
+    assert_eq!(event["event"], "approval.escalated:v1");
+    assert_eq!(event["fromLevel"], 1);
+    assert_eq!(event["toLevel"], 2);
+    assert_eq!(event["action"], "notify");
+    assert_eq!(event["notify"][0], "release-managers");
+    assert_eq!(event["escalationState"]["totalLevels"], 3);
+}
+
+#[test]
+fn given_terminal_rule_before_last_when_validate_then_error() {
+    let invalid = EscalationPolicy {
+        rules: vec![
+            EscalationRule {
+                after_seconds: 60,
+                action: EscalationAction::AutoDeny,
+            },
+            EscalationRule {
+                after_seconds: 120,
+                action: EscalationAction::Notify {
+                    groups: vec!["ops".to_string()],
+                },
+            },
+        ],
+    };
+
+    assert!(invalid.validate().is_err());
+    assert!(policy(EscalationAction::AutoGrant).validate().is_ok());
+}
+
+#[test]
+fn given_json_policy_when_parsed_then_actions_are_tagged() {
+    let policy: EscalationPolicy = serde_json::from_str(
+        r#"{"rules":[{"afterSeconds":14400,"action":"notify","groups":["leads"]},{"afterSeconds":86400,"action":"auto-deny"}]}"#,
+    )
+    .unwrap();
+
+    assert_eq!(policy.rules[1].action, EscalationAction::AutoDeny);
+    assert!(policy.validate().is_ok());
+}
+
+#[test]
+#[serial]
+fn given_escalations_env_when_loaded_then_tenant_policy_overrides_global_and_invalid_is_skipped() {
+    std::env::set_var(
+        "WARDS_APPROVAL_ESCALATIONS",
+        r#"{
+            "global": {"deploy": {"rules": [{"afterSeconds": 3600, "action": "auto-deny"}]}},
+            "tenant-a": {
+                "deploy": {"rules": [{"afterSeconds": 60, "action": "notify", "groups": ["oncall"]}]},
+                "broken": {"rules": [{"afterSeconds": 60, "action": "notify", "groups": []}]}
+            }
+        }"#,
+    );
+
+    let schedule = wards::schedule::load_escalations_from_env();
+    std::env::remove_var("WARDS_APPROVAL_ESCALATIONS");
+
+    assert_eq!(
+        schedule.policy_for("tenant-a", "deploy").unwrap().rules[0].after_seconds,
+        60
+    );
+    assert_eq!(
+        schedule.policy_for("tenant-b", "deploy").unwrap().rules[0].action,
+        EscalationAction::AutoDeny
+    );
+    assert!(schedule.policy_for("tenant-a", "broken").is_none());
+}
@@ -0,0 +1,115 @@
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::json;
+use wards::audit::{DecisionKind, DecisionLog, DecisionQuery, WardsDecision};
+use wards::config::QuotaCfg;
+use wards::quota::{evaluate, QuotaResource};
+
+#[test]
+fn given_approval_events_when_converted_then_kind_rule_and_actor_are_recorded() {
+    let granted = WardsDecision::from_approval_event(&json!({
+        "event": "approval.granted:v1",
+        "ts": "2025-01-06T11:02:45Z",
+        "tenantId": "tenant-a",
+        "runId": "run-1",
+        "ritualId": "release",
+        "gateId": "deploy",
+        "approver": "ops@example.com",
+        "note": "change window open"
+    }))
+    .unwrap();
+
+    assert_eq!(granted.kind, DecisionKind::ApprovalGranted);
+    assert_eq!(granted.rule_id, "approval:deploy");
+    assert_eq!(granted.actor.as_deref(), Some("ops@example.com"));
+    assert_eq!(granted.reason.as_deref(), Some("change window open"));
+    assert_eq!(granted.run_id.as_deref(), Some("run-1"));
+    assert_eq!(
+        granted.ts,
+        Utc.with_ymd_and_hms(2025, 1, 6, 11, 2, 45).unwrap()
+    );
+
+    let overridden = WardsDecision::from_approval_event(&json!({
+        "event": "approval.override:v1",
+        "tenantId": "tenant-a",
+        "runId": "run-1",
+        "ritualId": "release",
+        "gateId": "deploy",
+        "approver": "cto@example.com"
+    }))
+    .unwrap();
+    assert_eq!(overridden.kind, DecisionKind::OverrideUsed);
+    assert_eq!(overridden.rule_id, "approval:deploy:override");
+
+    assert!(WardsDecision::from_approval_event(&json!({
+        "event": "approval.requested:v1",
+        "gateId": "deploy"
+    }))
+    .is_none());
+}
+
+#[test]
+fn given_denied_quota_when_recorded_then_rule_names_the_resource() {
+    let quota = QuotaCfg {
+        limit: 1,
+        window_seconds: 60,
+    };
+    let denied = evaluate("tenant-a", QuotaResource::GraphCommits, &quota, 1, 1, 120);
+
+    let record = WardsDecision::quota_rejected(&denied).for_run("graph-sync", "run-2");
+    let value = serde_json::to_value(&record).unwrap();
+
+    assert_eq!(value["event"], "wards.decision:v1");
+    assert_eq!(value["kind"], "quota-rejected");
+    assert_eq!(value["ruleId"], "quota:graph-commits");
+    assert_eq!(value["reason"], "quota_exceeded:graph-commits");
+    assert_eq!(value["details"]["limit"], 1);
+    assert!(value.get("actor").is_none());
+}
+
+#[tokio::test]
+async fn given_recorded_decisions_when_queried_then_filters_apply_newest_first() {
+    let log = DecisionLog::in_memory();
+    let base = Utc.with_ymd_and_hms(2025, 1, 6, 9, 0, 0).unwrap();
+
+    for (i, (tenant, run, kind)) in [
+        ("tenant-a", "run-1", DecisionKind::ApprovalGranted),
+        ("tenant-a", "run-2", DecisionKind::QuotaRejected),
+        ("tenant-a", "run-1", DecisionKind::ApprovalDenied),
+        ("tenant-b", "run-3", DecisionKind::QuotaRejected),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let record = WardsDecision::new(tenant, kind, "rule")
+            .for_run("ritual", run)
+            .at(base + Duration::minutes(i as i64));
+        log.record(&record).await.unwrap();
+    }
+
+    let run_one = log.query(&DecisionQuery::for_run("run-1")).await.unwrap();
+    assert_eq!(
+        run_one.iter().map(|d| d.kind).collect::<Vec<_>>(),
+        vec![DecisionKind::ApprovalDenied, DecisionKind::ApprovalGranted]
+    );
+
+    let rejections = log
+        .query(&DecisionQuery {
+            kind: Some(DecisionKind::QuotaRejected),
+            ..DecisionQuery::for_tenant("tenant-a")
+        })
+        .await
+        .unwrap();
+    assert_eq!(rejections.len(), 1);
+    assert_eq!(rejections[0].run_id.as_deref(), Some("run-2"));
+
+    let recent = log
+        .query(&DecisionQuery {
+            since: Some(base + Duration::minutes(2)),
+            limit: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].tenant_id, "tenant-b");
+}
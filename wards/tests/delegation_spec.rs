@@ -0,0 +1,97 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use wards::delegation::{delegation_key, on_behalf_of, Delegation, DelegationError};
+
+fn at(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 7, 1, hour, 0, 0).unwrap()
+}
+
+fn delegation(delegator: &str, delegate: &str, from: u32, to: u32) -> Delegation {
+    Delegation {
+        delegator: delegator.to_string(),
+        delegate: delegate.to_string(),
+        starts_at: at(from),
+        ends_at: at(to),
+        reason: None,
+    }
+}
+
+fn allowlist(email: &str) -> bool {
+    ["lead@example.com", "cto@example.com"]
+        .iter()
+        .any(|a| a.eq_ignore_ascii_case(email))
+}
+
+#[test]
+fn delegate_acts_only_inside_the_window() {
+    let delegations = vec![delegation("lead@example.com", "dev@example.com", 9, 17)];
+
+    for (hour, expected) in [(8, false), (9, true), (16, true), (17, false)] {
+        let found = on_behalf_of(&delegations, "dev@example.com", at(hour), allowlist);
+        assert_eq!(found.is_some(), expected, "hour {}", hour);
+    }
+    let found = on_behalf_of(&delegations, "DEV@example.com", at(12), allowlist).unwrap();
+    assert_eq!(found.delegator, "lead@example.com");
+}
+
+#[test]
+fn delegation_lapses_when_delegator_is_not_an_approver() {
+    let delegations = vec![delegation("former@example.com", "dev@example.com", 9, 17)];
+    assert!(on_behalf_of(&delegations, "dev@example.com", at(12), allowlist).is_none());
+    assert!(on_behalf_of(&delegations, "other@example.com", at(12), allowlist).is_none());
+}
+
+#[test]
+fn authority_is_not_transitive() {
+    // dev only holds lead's authority through a delegation, so dev's own
+    // delegation to intern grants nothing
+    let delegations = vec![
+        delegation("lead@example.com", "dev@example.com", 9, 17),
+        delegation("dev@example.com", "intern@example.com", 9, 17),
+    ];
+    assert!(on_behalf_of(&delegations, "intern@example.com", at(12), allowlist).is_none());
+}
+
+#[test]
+fn soonest_ending_delegation_wins() {
+    let delegations = vec![
+        delegation("lead@example.com", "dev@example.com", 9, 17),
+        delegation("cto@example.com", "dev@example.com", 8, 12),
+    ];
+    let found = on_behalf_of(&delegations, "dev@example.com", at(10), allowlist).unwrap();
+    assert_eq!(found.delegator, "cto@example.com");
+}
+
+#[test]
+fn validate_rejects_bad_delegations() {
+    assert_eq!(
+        delegation("lead@example.com", "Lead@Example.com", 9, 17).validate(),
+        Err(DelegationError::SelfDelegation)
+    );
+    assert_eq!(
+        delegation("lead@example.com", "dev@example.com", 17, 9).validate(),
+        Err(DelegationError::EmptyWindow)
+    );
+    assert_eq!(
+        delegation(" ", "dev@example.com", 9, 17).validate(),
+        Err(DelegationError::MissingPrincipal("delegator"))
+    );
+    let mut long = delegation("lead@example.com", "dev@example.com", 9, 17);
+    long.ends_at = long.starts_at + Duration::days(91);
+    assert_eq!(long.validate(), Err(DelegationError::WindowTooLong));
+    assert!(delegation("lead@example.com", "dev@example.com", 9, 17)
+        .validate()
+        .is_ok());
+}
+
+#[test]
+fn keys_are_kv_safe_and_case_insensitive() {
+    let key = delegation_key("Lead@Example.com", "dev.ops@example.com");
+    assert_eq!(key, "lead=40example=2Ecom.dev=2Eops=40example=2Ecom");
+    assert_eq!(
+        key,
+        delegation_key("lead@example.com", "DEV.OPS@example.com")
+    );
+    assert!(key.split('.').all(|token| token
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-=".contains(c))));
+}
@@ -0,0 +1,145 @@
+use serial_test::serial;
+use std::collections::HashMap;
+use wards::config::QuotaCfg;
+use wards::quota::{QuotaResource, TenantQuotaConfig, TenantQuotas};
+
+fn config() -> TenantQuotaConfig {
+    TenantQuotaConfig {
+        default: HashMap::from([(
+            QuotaResource::Runs,
+            QuotaCfg {
+                limit: 3,
+                window_seconds: 3600,
+            },
+        )]),
+        tenants: HashMap::from([(
+            "noisy".to_string(),
+            HashMap::from([(
+                QuotaResource::Runs,
+                QuotaCfg {
+                    limit: 1,
+                    window_seconds: 3600,
+                },
+            )]),
+        )]),
+    }
+}
+
+#[tokio::test]
+async fn given_tenant_override_when_limit_reached_then_other_tenants_are_unaffected() {
+    let quotas = TenantQuotas::in_memory(config());
+
+    assert!(
+        quotas
+            .check_and_consume("noisy", QuotaResource::Runs, 1)
+            .await
+            .unwrap()
+            .allowed
+    );
+    let denied = quotas
+        .check_and_consume("noisy", QuotaResource::Runs, 1)
+        .await
+        .unwrap();
+    assert!(!denied.allowed);
+    assert_eq!(denied.limit, Some(1));
+
+    // Quiet tenant falls back to the default limit of 3
+    for _ in 0..3 {
+        assert!(
+            quotas
+                .check_and_consume("quiet", QuotaResource::Runs, 1)
+                .await
+                .unwrap()
+                .allowed
+        );
+    }
+    assert_eq!(quotas.usage("quiet", QuotaResource::Runs).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn given_batch_larger_than_remaining_when_consume_then_nothing_is_consumed() {
+    let quotas = TenantQuotas::in_memory(config());
+
+    quotas
+        .check_and_consume("quiet", QuotaResource::Runs, 2)
+        .await
+        .unwrap();
+    let denied = quotas
+        .check_and_consume("quiet", QuotaResource::Runs, 2)
+        .await
+        .unwrap();
+
+    assert!(!denied.allowed);
+    assert_eq!(denied.remaining, Some(1));
+    assert_eq!(quotas.usage("quiet", QuotaResource::Runs).await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn given_released_units_when_consume_then_they_are_available_again() {
+    let quotas = TenantQuotas::in_memory(config());
+
+    quotas
+        .check_and_consume("quiet", QuotaResource::Runs, 3)
+        .await
+        .unwrap();
+    quotas
+        .release("quiet", QuotaResource::Runs, 2)
+        .await
+        .unwrap();
+    assert_eq!(quotas.usage("quiet", QuotaResource::Runs).await.unwrap(), 1);
+
+    // Releasing more than was used stops at zero
+    quotas
+        .release("quiet", QuotaResource::Runs, 5)
+        .await
+        .unwrap();
+    assert_eq!(quotas.usage("quiet", QuotaResource::Runs).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn given_unconfigured_resource_when_consume_then_always_allowed() {
+    let quotas = TenantQuotas::in_memory(config());
+
+    let decision = quotas
+        .check_and_consume("quiet", QuotaResource::GraphCommits, 1000)
+        .await
+        .unwrap();
+
+    assert!(decision.allowed);
+    assert_eq!(decision.limit, None);
+}
+
+#[test]
+#[serial]
+fn given_tenant_quotas_env_when_loaded_then_resources_parse_from_kebab_case() {
+    std::env::set_var(
+        "WARDS_TENANT_QUOTAS",
+        r#"{"default":{"capsule-executions":{"limit":10,"windowSeconds":60}},"tenants":{"a":{"graph-commits":{"limit":2,"windowSeconds":30}}}}"#,
+    );
+
+    let cfg = TenantQuotaConfig::from_env().unwrap();
+    std::env::remove_var("WARDS_TENANT_QUOTAS");
+
+    assert_eq!(
+        cfg.limit_for("a", QuotaResource::GraphCommits)
+            .map(|q| q.limit),
+        Some(2)
+    );
+    assert_eq!(
+        cfg.limit_for("b", QuotaResource::CapsuleExecutions)
+            .map(|q| q.limit),
+        Some(10)
+    );
+    assert_eq!(cfg.longest_window_seconds(), Some(60));
+}
+
+#[test]
+#[serial]
+fn given_malformed_tenant_quotas_env_when_loaded_then_error() {
+    std::env::set_var("WARDS_TENANT_QUOTAS", r#"{"default":{"bogus":{}}}"#);
+
+    let result = TenantQuotaConfig::from_env();
+    std::env::remove_var("WARDS_TENANT_QUOTAS");
+
+    assert!(result.is_err());
+}